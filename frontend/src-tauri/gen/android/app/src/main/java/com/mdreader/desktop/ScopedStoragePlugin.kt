@@ -0,0 +1,260 @@
+package com.mdreader.desktop
+
+import android.app.Activity
+import android.content.Intent
+import android.net.Uri
+import android.provider.DocumentsContract
+import android.provider.DocumentsContract.Document
+import androidx.activity.result.ActivityResult
+import app.tauri.annotation.ActivityCallback
+import app.tauri.annotation.Command
+import app.tauri.annotation.InvokeArg
+import app.tauri.annotation.TauriPlugin
+import app.tauri.plugin.Invoke
+import app.tauri.plugin.JSArray
+import app.tauri.plugin.JSObject
+import app.tauri.plugin.Plugin
+import org.json.JSONObject
+
+// Storage Access Framework backend of `storage.rs` (SafStorage).
+//
+// Every command gets the granted tree URI and a `/`-separated path of
+// display names below it, which is resolved by walking the tree.
+
+@InvokeArg
+class DocumentArgs {
+    lateinit var tree: String
+    var path: String = ""
+}
+
+@InvokeArg
+class WriteArgs {
+    lateinit var tree: String
+    var path: String = ""
+    var contents: String = ""
+    var createNew: Boolean = false
+}
+
+@InvokeArg
+class RemoveArgs {
+    lateinit var tree: String
+    var path: String = ""
+    var recursive: Boolean = false
+}
+
+@InvokeArg
+class MoveArgs {
+    lateinit var tree: String
+    var from: String = ""
+    var to: String = ""
+}
+
+@TauriPlugin
+class ScopedStoragePlugin(private val activity: Activity) : Plugin(activity) {
+    private val resolver get() = activity.contentResolver
+
+    private val grantFlags =
+        Intent.FLAG_GRANT_READ_URI_PERMISSION or Intent.FLAG_GRANT_WRITE_URI_PERMISSION
+
+    @Command
+    fun pickTree(invoke: Invoke) {
+        val intent = Intent(Intent.ACTION_OPEN_DOCUMENT_TREE)
+            .addFlags(grantFlags or Intent.FLAG_GRANT_PERSISTABLE_URI_PERMISSION)
+        startActivityForResult(invoke, intent, "pickTreeResult")
+    }
+
+    @ActivityCallback
+    fun pickTreeResult(invoke: Invoke, result: ActivityResult) {
+        val uri = result.data?.data
+        if (result.resultCode != Activity.RESULT_OK || uri == null) {
+            invoke.resolve(JSObject().put("uri", JSONObject.NULL))
+            return
+        }
+        // Keep access across restarts
+        resolver.takePersistableUriPermission(uri, grantFlags)
+        invoke.resolve(JSObject().put("uri", uri.toString()))
+    }
+
+    @Command
+    fun list(invoke: Invoke) = respond(invoke) {
+        val args = invoke.parseArgs(DocumentArgs::class.java)
+        val tree = grantedTree(args.tree)
+        val dir = find(tree, args.path) ?: throw StorageException("Folder not found: ${args.path}")
+        val entries = JSArray()
+        children(tree, dir).forEach { (name, uri) ->
+            entry(uri, childPath(args.tree, args.path, name))?.let { entries.put(it) }
+        }
+        JSObject().put("entries", entries)
+    }
+
+    @Command
+    fun stat(invoke: Invoke) = respond(invoke) {
+        val args = invoke.parseArgs(DocumentArgs::class.java)
+        val tree = grantedTree(args.tree)
+        val uri = find(tree, args.path)
+        JSObject().put("entry", uri?.let { entry(it, join(args.tree, args.path)) } ?: JSONObject.NULL)
+    }
+
+    @Command
+    fun read(invoke: Invoke) = respond(invoke) {
+        val args = invoke.parseArgs(DocumentArgs::class.java)
+        val uri = find(grantedTree(args.tree), args.path) ?: throw StorageException("File not found: ${args.path}")
+        val contents = resolver.openInputStream(uri)?.use { it.readBytes().toString(Charsets.UTF_8) }
+            ?: throw StorageException("Cannot open ${args.path}")
+        JSObject().put("contents", contents)
+    }
+
+    @Command
+    fun write(invoke: Invoke) = respond(invoke) {
+        val args = invoke.parseArgs(WriteArgs::class.java)
+        val tree = grantedTree(args.tree)
+        val existing = find(tree, args.path)
+        if (existing != null && args.createNew) {
+            return@respond JSObject().put("created", false)
+        }
+        val uri = existing ?: create(tree, args.path, "text/markdown")
+        // "wt" truncates what was there before
+        resolver.openOutputStream(uri, "wt")?.use { it.write(args.contents.toByteArray(Charsets.UTF_8)) }
+            ?: throw StorageException("Cannot open ${args.path}")
+        JSObject().put("created", existing == null)
+    }
+
+    @Command
+    fun remove(invoke: Invoke) = respond(invoke) {
+        val args = invoke.parseArgs(RemoveArgs::class.java)
+        val tree = grantedTree(args.tree)
+        val uri = find(tree, args.path) ?: throw StorageException("Not found: ${args.path}")
+        if (!args.recursive && isDirectory(uri) && children(tree, uri).isNotEmpty()) {
+            throw StorageException("Directory not empty: ${args.path}")
+        }
+        // Deleting a folder document deletes its contents too
+        DocumentsContract.deleteDocument(resolver, uri)
+        JSObject()
+    }
+
+    @Command
+    fun rename(invoke: Invoke) = respond(invoke) {
+        val args = invoke.parseArgs(MoveArgs::class.java)
+        val tree = grantedTree(args.tree)
+        val source = find(tree, args.from) ?: throw StorageException("Not found: ${args.from}")
+        if (find(tree, args.to) != null) throw StorageException("Already exists: ${args.to}")
+
+        var moved = source
+        val (fromParent, _) = splitParent(args.from)
+        val (toParent, toName) = splitParent(args.to)
+        if (fromParent != toParent) {
+            val oldParent = find(tree, fromParent) ?: throw StorageException("Not found: $fromParent")
+            val newParent = mkdirs(tree, toParent)
+            moved = DocumentsContract.moveDocument(resolver, source, oldParent, newParent)
+                ?: throw StorageException("Cannot move ${args.from}")
+        }
+        if (displayName(moved) != toName) {
+            DocumentsContract.renameDocument(resolver, moved, toName)
+                ?: throw StorageException("Cannot rename ${args.from}")
+        }
+        JSObject()
+    }
+
+    @Command
+    fun copy(invoke: Invoke) = respond(invoke) {
+        val args = invoke.parseArgs(MoveArgs::class.java)
+        val tree = grantedTree(args.tree)
+        val source = find(tree, args.from) ?: throw StorageException("Not found: ${args.from}")
+        val target = find(tree, args.to) ?: create(tree, args.to, mimeType(source))
+        resolver.openInputStream(source)?.use { input ->
+            resolver.openOutputStream(target, "wt")?.use { input.copyTo(it) }
+        } ?: throw StorageException("Cannot copy ${args.from}")
+        JSObject()
+    }
+
+    private class StorageException(message: String) : Exception(message)
+
+    // Resolves or rejects the invoke with the result of `body`
+    private fun respond(invoke: Invoke, body: () -> JSObject) {
+        try {
+            invoke.resolve(body())
+        } catch (e: Exception) {
+            invoke.reject(e.message ?: e.toString())
+        }
+    }
+
+    // The tree, if its grant is still held (users can revoke it in the settings)
+    private fun grantedTree(tree: String): Uri {
+        val uri = Uri.parse(tree)
+        val granted = resolver.persistedUriPermissions.any { it.uri == uri && it.isWritePermission }
+        if (!granted) {
+            throw StorageException("Permission denied: access to $tree was revoked, pick the folder again")
+        }
+        return uri
+    }
+
+    private fun names(path: String) = path.split('/').filter { it.isNotEmpty() }
+
+    private fun join(tree: String, path: String) = if (path.isEmpty()) tree else "$tree/$path"
+
+    private fun childPath(tree: String, dir: String, name: String) = join(tree, if (dir.isEmpty()) name else "$dir/$name")
+
+    private fun splitParent(path: String): Pair<String, String> {
+        val names = names(path)
+        return Pair(names.dropLast(1).joinToString("/"), names.lastOrNull() ?: "")
+    }
+
+    private fun root(tree: Uri): Uri =
+        DocumentsContract.buildDocumentUriUsingTree(tree, DocumentsContract.getTreeDocumentId(tree))
+
+    // Display names and URIs of the documents in a folder
+    private fun children(tree: Uri, dir: Uri): List<Pair<String, Uri>> {
+        val childrenUri = DocumentsContract.buildChildDocumentsUriUsingTree(tree, DocumentsContract.getDocumentId(dir))
+        val columns = arrayOf(Document.COLUMN_DOCUMENT_ID, Document.COLUMN_DISPLAY_NAME)
+        val found = mutableListOf<Pair<String, Uri>>()
+        resolver.query(childrenUri, columns, null, null, null)?.use { cursor ->
+            while (cursor.moveToNext()) {
+                found.add(Pair(cursor.getString(1), DocumentsContract.buildDocumentUriUsingTree(tree, cursor.getString(0))))
+            }
+        }
+        return found
+    }
+
+    private fun find(tree: Uri, path: String): Uri? =
+        names(path).fold(root(tree) as Uri?) { dir, name ->
+            dir?.let { parent -> children(tree, parent).firstOrNull { it.first == name }?.second }
+        }
+
+    private fun mkdirs(tree: Uri, path: String): Uri =
+        names(path).fold(root(tree)) { dir, name ->
+            children(tree, dir).firstOrNull { it.first == name }?.second
+                ?: DocumentsContract.createDocument(resolver, dir, Document.MIME_TYPE_DIR, name)
+                ?: throw StorageException("Cannot create folder $name")
+        }
+
+    private fun create(tree: Uri, path: String, mime: String): Uri {
+        val (parent, name) = splitParent(path)
+        return DocumentsContract.createDocument(resolver, mkdirs(tree, parent), mime, name)
+            ?: throw StorageException("Cannot create $path")
+    }
+
+    private fun query(uri: Uri, column: String): String? =
+        resolver.query(uri, arrayOf(column), null, null, null)?.use { if (it.moveToFirst()) it.getString(0) else null }
+
+    private fun displayName(uri: Uri) = query(uri, Document.COLUMN_DISPLAY_NAME)
+
+    private fun mimeType(uri: Uri) = query(uri, Document.COLUMN_MIME_TYPE) ?: "application/octet-stream"
+
+    private fun isDirectory(uri: Uri) = mimeType(uri) == Document.MIME_TYPE_DIR
+
+    // A StorageEntry (see storage.rs)
+    private fun entry(uri: Uri, path: String): JSObject? {
+        val columns = arrayOf(
+            Document.COLUMN_DISPLAY_NAME, Document.COLUMN_MIME_TYPE, Document.COLUMN_SIZE, Document.COLUMN_LAST_MODIFIED
+        )
+        return resolver.query(uri, columns, null, null, null)?.use { cursor ->
+            if (!cursor.moveToFirst()) return@use null
+            JSObject()
+                .put("name", cursor.getString(0))
+                .put("path", path)
+                .put("isDirectory", cursor.getString(1) == Document.MIME_TYPE_DIR)
+                .put("size", if (cursor.isNull(2)) 0 else cursor.getLong(2))
+                .put("modifiedMs", if (cursor.isNull(3)) 0 else cursor.getLong(3))
+        }
+    }
+}
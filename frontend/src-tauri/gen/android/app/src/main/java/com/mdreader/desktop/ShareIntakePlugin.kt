@@ -0,0 +1,105 @@
+package com.mdreader.desktop
+
+import android.app.Activity
+import android.content.Intent
+import android.net.Uri
+import android.os.Build
+import android.provider.OpenableColumns
+import android.util.Patterns
+import android.webkit.WebView
+import app.tauri.annotation.Command
+import app.tauri.annotation.TauriPlugin
+import app.tauri.plugin.Invoke
+import app.tauri.plugin.JSArray
+import app.tauri.plugin.JSObject
+import app.tauri.plugin.Plugin
+import java.io.File
+import java.util.UUID
+
+// Share sheet backend of `share.rs` (NativeShares).
+//
+// ACTION_SEND / ACTION_SEND_MULTIPLE intents delivered to MainActivity are
+// turned into SharedItems and queued until Rust takes them. Images are
+// copied into the cache, since the granted URI dies with the intent.
+// MainActivity needs SEND and SEND_MULTIPLE intent filters for text/plain
+// and image/* in AndroidManifest.xml.
+
+@TauriPlugin
+class ShareIntakePlugin(private val activity: Activity) : Plugin(activity) {
+    private val pending = mutableListOf<JSObject>()
+
+    override fun load(webView: WebView) {
+        super.load(webView)
+        // The share that launched the app
+        receive(activity.intent)
+    }
+
+    override fun onNewIntent(intent: Intent) {
+        receive(intent)
+    }
+
+    @Command
+    fun takeShares(invoke: Invoke) {
+        val items = JSArray()
+        synchronized(pending) {
+            pending.forEach { items.put(it) }
+            pending.clear()
+        }
+        invoke.resolve(JSObject().put("items", items))
+    }
+
+    private fun receive(intent: Intent?) {
+        if (intent == null || (intent.action != Intent.ACTION_SEND && intent.action != Intent.ACTION_SEND_MULTIPLE)) {
+            return
+        }
+        val items = mutableListOf<JSObject>()
+        if (intent.type?.startsWith("image/") == true) {
+            streams(intent).forEach { uri -> copyImage(uri)?.let { items.add(it) } }
+        } else {
+            intent.getStringExtra(Intent.EXTRA_TEXT)?.let { text ->
+                items.add(textItem(text.trim(), intent.getStringExtra(Intent.EXTRA_SUBJECT)))
+            }
+        }
+        synchronized(pending) { pending.addAll(items) }
+        // Handled; a configuration change must not add it again
+        intent.action = null
+    }
+
+    private fun textItem(text: String, subject: String?): JSObject =
+        if (Patterns.WEB_URL.matcher(text).matches()) {
+            JSObject().put("kind", "url").put("url", text).put("title", subject)
+        } else {
+            JSObject().put("kind", "text").put("text", text).put("subject", subject)
+        }
+
+    @Suppress("DEPRECATION")
+    private fun streams(intent: Intent): List<Uri> =
+        if (intent.action == Intent.ACTION_SEND_MULTIPLE) {
+            if (Build.VERSION.SDK_INT >= 33) {
+                intent.getParcelableArrayListExtra(Intent.EXTRA_STREAM, Uri::class.java)
+            } else {
+                intent.getParcelableArrayListExtra(Intent.EXTRA_STREAM)
+            } ?: emptyList()
+        } else {
+            listOfNotNull(
+                if (Build.VERSION.SDK_INT >= 33) {
+                    intent.getParcelableExtra(Intent.EXTRA_STREAM, Uri::class.java)
+                } else {
+                    intent.getParcelableExtra(Intent.EXTRA_STREAM)
+                }
+            )
+        }
+
+    // Copies a shared image into the cache; Rust removes the copy once stored
+    private fun copyImage(uri: Uri): JSObject? {
+        val name = activity.contentResolver.query(uri, arrayOf(OpenableColumns.DISPLAY_NAME), null, null, null)
+            ?.use { if (it.moveToFirst()) it.getString(0) else null }
+        val dir = File(activity.cacheDir, "shared").apply { mkdirs() }
+        val target = File(dir, UUID.randomUUID().toString())
+        val copied = activity.contentResolver.openInputStream(uri)?.use { input ->
+            target.outputStream().use { input.copyTo(it) }
+        }
+        if (copied == null) return null
+        return JSObject().put("kind", "image").put("path", target.absolutePath).put("name", name)
+    }
+}
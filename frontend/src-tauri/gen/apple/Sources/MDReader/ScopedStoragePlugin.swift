@@ -0,0 +1,229 @@
+import Foundation
+import SwiftRs
+import Tauri
+import UIKit
+import UniformTypeIdentifiers
+import WebKit
+
+// iOS backend of `storage.rs` (BookmarkStorage).
+//
+// Folders picked in the Files app are returned with a security-scoped
+// bookmark, which Rust persists and hands back at launch. File access goes
+// through NSFileCoordinator so iCloud Drive never syncs a file halfway
+// through a write, and evicted files are downloaded before they are read.
+
+class PathArgs: Decodable {
+  let path: String
+}
+
+class WriteArgs: Decodable {
+  let path: String
+  let contents: String
+  let createNew: Bool
+}
+
+class RemoveArgs: Decodable {
+  let path: String
+  let recursive: Bool
+}
+
+class MoveArgs: Decodable {
+  let from: String
+  let to: String
+}
+
+class BookmarkArgs: Decodable {
+  let bookmark: String
+}
+
+struct Picked: Encodable {
+  let path: String?
+  let bookmark: String?
+}
+
+struct ResolvedBookmark: Encodable {
+  let path: String
+  let bookmark: String
+}
+
+struct Contents: Encodable {
+  let contents: String
+}
+
+struct Created: Encodable {
+  let created: Bool
+}
+
+struct Done: Encodable {}
+
+enum StorageError: LocalizedError {
+  case message(String)
+
+  var errorDescription: String? {
+    switch self {
+    case .message(let message): return message
+    }
+  }
+}
+
+class ScopedStoragePlugin: Plugin, UIDocumentPickerDelegate {
+  private var pendingPick: Invoke?
+
+  @objc public func pickFolder(_ invoke: Invoke) {
+    DispatchQueue.main.async {
+      let picker = UIDocumentPickerViewController(forOpeningContentTypes: [.folder])
+      picker.delegate = self
+      self.pendingPick = invoke
+      self.manager.viewController?.present(picker, animated: true)
+    }
+  }
+
+  func documentPicker(_ controller: UIDocumentPickerViewController, didPickDocumentsAt urls: [URL]) {
+    guard let invoke = pendingPick else { return }
+    pendingPick = nil
+    guard let url = urls.first, url.startAccessingSecurityScopedResource() else {
+      invoke.resolve(Picked(path: nil, bookmark: nil))
+      return
+    }
+    do {
+      invoke.resolve(Picked(path: url.path, bookmark: try bookmark(of: url)))
+    } catch {
+      invoke.reject("Failed to bookmark folder: \(error.localizedDescription)")
+    }
+  }
+
+  func documentPickerWasCancelled(_ controller: UIDocumentPickerViewController) {
+    pendingPick?.resolve(Picked(path: nil, bookmark: nil))
+    pendingPick = nil
+  }
+
+  @objc public func resolveBookmark(_ invoke: Invoke) throws {
+    let args = try invoke.parseArgs(BookmarkArgs.self)
+    guard let data = Data(base64Encoded: args.bookmark) else {
+      throw StorageError.message("Invalid bookmark")
+    }
+    var stale = false
+    let url = try URL(resolvingBookmarkData: data, bookmarkDataIsStale: &stale)
+    guard url.startAccessingSecurityScopedResource() else {
+      throw StorageError.message("Permission denied: access to \(url.path) was revoked, pick the folder again")
+    }
+    // A stale bookmark still resolves (the folder moved); replace it
+    invoke.resolve(ResolvedBookmark(path: url.path, bookmark: stale ? try bookmark(of: url) : args.bookmark))
+  }
+
+  @objc public func read(_ invoke: Invoke) throws {
+    let args = try invoke.parseArgs(PathArgs.self)
+    let url = URL(fileURLWithPath: args.path)
+    var contents = ""
+    try coordinate { coordinator, error in
+      coordinator.coordinate(readingItemAt: url, options: [], error: error) { url in
+        contents = (try? String(contentsOf: url, encoding: .utf8)) ?? ""
+      }
+    }
+    invoke.resolve(Contents(contents: contents))
+  }
+
+  @objc public func write(_ invoke: Invoke) throws {
+    let args = try invoke.parseArgs(WriteArgs.self)
+    let url = URL(fileURLWithPath: args.path)
+    let exists = FileManager.default.fileExists(atPath: url.path)
+    if exists && args.createNew {
+      invoke.resolve(Created(created: false))
+      return
+    }
+    var failure: Error?
+    try coordinate { coordinator, error in
+      coordinator.coordinate(writingItemAt: url, options: .forReplacing, error: error) { url in
+        do {
+          try args.contents.write(to: url, atomically: true, encoding: .utf8)
+        } catch {
+          failure = error
+        }
+      }
+    }
+    if let failure = failure { throw failure }
+    invoke.resolve(Created(created: !exists))
+  }
+
+  @objc public func remove(_ invoke: Invoke) throws {
+    let args = try invoke.parseArgs(RemoveArgs.self)
+    let url = URL(fileURLWithPath: args.path)
+    var isDirectory: ObjCBool = false
+    if !args.recursive, FileManager.default.fileExists(atPath: url.path, isDirectory: &isDirectory), isDirectory.boolValue,
+      !(try FileManager.default.contentsOfDirectory(atPath: url.path)).isEmpty
+    {
+      throw StorageError.message("Directory not empty: \(args.path)")
+    }
+    var failure: Error?
+    try coordinate { coordinator, error in
+      coordinator.coordinate(writingItemAt: url, options: .forDeleting, error: error) { url in
+        do {
+          try FileManager.default.removeItem(at: url)
+        } catch {
+          failure = error
+        }
+      }
+    }
+    if let failure = failure { throw failure }
+    invoke.resolve(Done())
+  }
+
+  @objc public func move(_ invoke: Invoke) throws {
+    let args = try invoke.parseArgs(MoveArgs.self)
+    let from = URL(fileURLWithPath: args.from)
+    let to = URL(fileURLWithPath: args.to)
+    var failure: Error?
+    try coordinate { coordinator, error in
+      coordinator.coordinate(
+        writingItemAt: from, options: .forMoving, writingItemAt: to, options: .forReplacing, error: error
+      ) { from, to in
+        do {
+          coordinator.item(at: from, willMoveTo: to)
+          try FileManager.default.moveItem(at: from, to: to)
+          coordinator.item(at: from, didMoveTo: to)
+        } catch {
+          failure = error
+        }
+      }
+    }
+    if let failure = failure { throw failure }
+    invoke.resolve(Done())
+  }
+
+  @objc public func copy(_ invoke: Invoke) throws {
+    let args = try invoke.parseArgs(MoveArgs.self)
+    let from = URL(fileURLWithPath: args.from)
+    let to = URL(fileURLWithPath: args.to)
+    var failure: Error?
+    try coordinate { coordinator, error in
+      coordinator.coordinate(
+        readingItemAt: from, options: [], writingItemAt: to, options: .forReplacing, error: error
+      ) { from, to in
+        do {
+          try FileManager.default.copyItem(at: from, to: to)
+        } catch {
+          failure = error
+        }
+      }
+    }
+    if let failure = failure { throw failure }
+    invoke.resolve(Done())
+  }
+
+  private func bookmark(of url: URL) throws -> String {
+    try url.bookmarkData(options: .minimalBookmark, includingResourceValuesForKeys: nil, relativeTo: nil)
+      .base64EncodedString()
+  }
+
+  // Runs a coordinated access and throws the coordination error, if any
+  private func coordinate(_ body: (NSFileCoordinator, NSErrorPointer) -> Void) throws {
+    var error: NSError?
+    body(NSFileCoordinator(filePresenter: nil), &error)
+    if let error = error { throw error }
+  }
+}
+
+@_cdecl("init_plugin_scoped_storage")
+func initPlugin() -> Plugin {
+  return ScopedStoragePlugin()
+}
@@ -0,0 +1,56 @@
+import Foundation
+import SwiftRs
+import Tauri
+import UIKit
+import WebKit
+
+// Share sheet backend of `share.rs` (NativeShares).
+//
+// The share extension cannot reach the app directly: it writes each shared
+// item to `pending.json` in the app group container (images next to it)
+// and opens the app. `takeShares` hands the queue to Rust and empties it.
+
+let appGroup = "group.com.mdreader.desktop"
+
+struct PendingShare: Codable {
+  let kind: String
+  var text: String?
+  var subject: String?
+  var url: String?
+  var title: String?
+  var path: String?
+  var name: String?
+}
+
+struct PendingShares: Encodable {
+  let items: [PendingShare]
+}
+
+class ShareIntakePlugin: Plugin {
+  @objc public func takeShares(_ invoke: Invoke) throws {
+    guard
+      let inbox = FileManager.default.containerURL(forSecurityApplicationGroupIdentifier: appGroup)?
+        .appendingPathComponent("Shared")
+    else {
+      invoke.resolve(PendingShares(items: []))
+      return
+    }
+    let queue = inbox.appendingPathComponent("pending.json")
+    var items: [PendingShare] = []
+    // The extension may be writing the queue right now
+    var error: NSError?
+    NSFileCoordinator(filePresenter: nil).coordinate(writingItemAt: queue, options: .forDeleting, error: &error) { url in
+      if let data = try? Data(contentsOf: url) {
+        items = (try? JSONDecoder().decode([PendingShare].self, from: data)) ?? []
+        try? FileManager.default.removeItem(at: url)
+      }
+    }
+    if let error = error { throw error }
+    invoke.resolve(PendingShares(items: items))
+  }
+}
+
+@_cdecl("init_plugin_share_intake")
+func initSharePlugin() -> Plugin {
+  return ShareIntakePlugin()
+}
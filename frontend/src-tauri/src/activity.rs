@@ -0,0 +1,354 @@
+//! Workspace Activity Feed
+//!
+//! Records what changed in a workspace: notes created, modified or deleted
+//! (seen by the file watcher), saved from the editor, and imports. The feed
+//! is kept per workspace in `.mdreader/activity.json`, oldest entry first and
+//! capped at `MAX_ENTRIES`:
+//!
+//! ```json
+//! { "entries": [ { "at": 1700000000, "kind": "saved", "path": "plan.md" } ] }
+//! ```
+//!
+//! Git commits are not copied into the store; when the workspace is a git
+//! repository they are read from `git log` and merged into the feed.
+//!
+//! The same change is often seen twice (a save is followed by a watcher
+//! event for the written file, an import by one event per imported file), so
+//! an event for a path that already has an entry within
+//! `ACTIVITY_COALESCE_SECS` only refreshes that entry's time.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::sync::{Arc, Mutex};
+use serde::{Deserialize, Serialize};
+use crate::notes::relative_path;
+use crate::workspace_settings::SETTINGS_DIR;
+
+/// File name of the store inside the settings directory
+pub const ACTIVITY_FILE: &str = "activity.json";
+
+/// Number of entries kept per workspace
+pub const MAX_ENTRIES: usize = 2000;
+
+/// Events for the same path closer together than this are merged
+pub const ACTIVITY_COALESCE_SECS: i64 = 60;
+
+/// What happened
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ActivityKind {
+    Created,
+    Modified,
+    Deleted,
+    Saved,
+    Imported,
+    Commit,
+}
+
+impl ActivityKind {
+    /// Changes reported by the file watcher
+    fn is_watcher_event(self) -> bool {
+        matches!(self, Self::Created | Self::Modified | Self::Deleted)
+    }
+}
+
+/// One entry of the feed
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ActivityEntry {
+    /// Unix timestamp (seconds)
+    pub at: i64,
+    pub kind: ActivityKind,
+    /// Path relative to the workspace root (none for commits)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub path: Option<String>,
+    /// Import source, or commit hash, author and subject
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub detail: Option<String>,
+}
+
+/// Activity of one workspace
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ActivityStore {
+    /// Oldest first
+    #[serde(default)]
+    pub entries: Vec<ActivityEntry>,
+}
+
+impl ActivityStore {
+    /// Loads the store of a workspace (empty if missing or unreadable)
+    pub fn load(workspace_root: &Path) -> Self {
+        let path = store_path(workspace_root);
+        match fs::read_to_string(&path) {
+            Ok(json) => serde_json::from_str(&json).unwrap_or_else(|e| {
+                log::warn!("⚠️ Ignoring unreadable activity store {:?}: {}", path, e);
+                Self::default()
+            }),
+            Err(_) => Self::default(),
+        }
+    }
+
+    /// Writes the store to the workspace settings directory
+    pub fn save(&self, workspace_root: &Path) -> Result<(), String> {
+        let path = store_path(workspace_root);
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)
+                .map_err(|e| format!("Failed to create settings directory: {}", e))?;
+        }
+        let json = serde_json::to_string_pretty(self)
+            .map_err(|e| format!("Failed to serialize activity store: {}", e))?;
+        fs::write(&path, json)
+            .map_err(|e| format!("Failed to write activity store: {}", e))
+    }
+
+    /// Records an entry; returns false if it was merged into a recent one
+    ///
+    /// A watcher event merges into a recent entry for the same path (or an
+    /// import of a folder containing it); a save also merges into a recent
+    /// watcher event, other entries only into an entry of the same kind.
+    pub fn record(&mut self, entry: ActivityEntry) -> bool {
+        let recent = self.entries
+            .iter_mut()
+            .rev()
+            .take_while(|e| entry.at - e.at < ACTIVITY_COALESCE_SECS)
+            .find(|e| {
+                let covers = match (&e.path, &entry.path) {
+                    (Some(existing), Some(path)) => {
+                        existing == path
+                            || (e.kind == ActivityKind::Imported && path.starts_with(&format!("{}/", existing)))
+                    }
+                    _ => false,
+                };
+                covers
+                    && (entry.kind.is_watcher_event()
+                        || e.kind == entry.kind
+                        || (entry.kind == ActivityKind::Saved && e.kind.is_watcher_event()))
+            });
+
+        if let Some(recent) = recent {
+            // An editor save explains the watcher event it causes
+            if entry.kind == ActivityKind::Saved && recent.kind.is_watcher_event() {
+                recent.kind = ActivityKind::Saved;
+            }
+            recent.at = recent.at.max(entry.at);
+            return false;
+        }
+
+        self.entries.push(entry);
+        if self.entries.len() > MAX_ENTRIES {
+            let excess = self.entries.len() - MAX_ENTRIES;
+            self.entries.drain(..excess);
+        }
+        true
+    }
+
+    /// Entries at or after `since`, newest first
+    pub fn since(&self, since: i64, limit: usize) -> Vec<ActivityEntry> {
+        self.entries
+            .iter()
+            .rev()
+            .filter(|e| e.at >= since)
+            .take(limit)
+            .cloned()
+            .collect()
+    }
+}
+
+fn store_path(workspace_root: &Path) -> PathBuf {
+    workspace_root.join(SETTINGS_DIR).join(ACTIVITY_FILE)
+}
+
+/// Reads commits at or after `since` from the workspace's git repository
+///
+/// Returns nothing if the workspace is not a repository or git is missing.
+pub fn git_commits(workspace_root: &Path, since: i64, limit: usize) -> Vec<ActivityEntry> {
+    if !workspace_root.join(".git").exists() {
+        return Vec::new();
+    }
+
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(workspace_root)
+        .args(["log", "--no-color", "--pretty=format:%ct%x1f%h%x1f%an%x1f%s"])
+        .arg(format!("--since=@{}", since))
+        .arg(format!("--max-count={}", limit))
+        .output();
+
+    match output {
+        Ok(output) if output.status.success() => parse_git_log(&String::from_utf8_lossy(&output.stdout)),
+        Ok(output) => {
+            log::warn!("⚠️ git log failed: {}", String::from_utf8_lossy(&output.stderr).trim());
+            Vec::new()
+        }
+        Err(e) => {
+            log::warn!("⚠️ Could not run git: {}", e);
+            Vec::new()
+        }
+    }
+}
+
+/// Checks if the `git` executable can be run
+pub fn git_available() -> bool {
+    Command::new("git")
+        .arg("--version")
+        .output()
+        .is_ok_and(|output| output.status.success())
+}
+
+/// Parses `git log` lines of `%ct`, `%h`, `%an` and `%s` separated by 0x1f
+fn parse_git_log(output: &str) -> Vec<ActivityEntry> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let mut fields = line.splitn(4, '\u{1f}');
+            let at = fields.next()?.trim().parse().ok()?;
+            let (hash, author, subject) = (fields.next()?, fields.next()?, fields.next().unwrap_or(""));
+            Some(ActivityEntry {
+                at,
+                kind: ActivityKind::Commit,
+                path: None,
+                detail: Some(format!("{} {} ({})", hash, subject, author)),
+            })
+        })
+        .collect()
+}
+
+/// In-memory feed of the current workspace, shared by all commands and
+/// watchers (internally synchronized)
+///
+/// The store is loaded on first use and reloaded when the workspace changes.
+/// Failures are logged, never returned: recording must not break saving,
+/// importing or watching.
+#[derive(Clone, Default)]
+pub struct ActivityTracker {
+    current: Arc<Mutex<Option<(PathBuf, ActivityStore)>>>,
+}
+
+impl ActivityTracker {
+    /// Creates a tracker with no workspace loaded
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Records a change of a workspace file or folder
+    pub fn record(&self, workspace: &str, path: &Path, kind: ActivityKind, detail: Option<String>) {
+        let root = canonical_root(workspace);
+        if !path.starts_with(&root) {
+            return;
+        }
+        let entry = ActivityEntry {
+            at: chrono::Utc::now().timestamp(),
+            kind,
+            path: Some(relative_path(&root, path)),
+            detail,
+        };
+
+        let Ok(mut current) = self.current.lock() else {
+            log::warn!("⚠️ Activity store lock poisoned");
+            return;
+        };
+        let store = Self::store_for(&mut current, &root);
+        if store.record(entry) {
+            if let Err(e) = store.save(&root) {
+                log::warn!("⚠️ Activity not saved: {}", e);
+            }
+        }
+    }
+
+    /// Entries at or after `since`, git commits included, newest first
+    pub fn feed(&self, workspace: &str, since: i64, limit: usize) -> Result<Vec<ActivityEntry>, String> {
+        let root = canonical_root(workspace);
+        let mut entries = {
+            let mut current = self.current
+                .lock()
+                .map_err(|e| format!("Failed to lock activity store: {}", e))?;
+            Self::store_for(&mut current, &root).since(since, limit)
+        };
+
+        entries.extend(git_commits(&root, since, limit));
+        entries.sort_by_key(|e| std::cmp::Reverse(e.at));
+        entries.truncate(limit);
+        Ok(entries)
+    }
+
+    /// Entries recorded in the workspace's store (no git commits), oldest first
+    pub fn entries(&self, workspace: &str) -> Result<Vec<ActivityEntry>, String> {
+        let root = canonical_root(workspace);
+        let mut current = self.current
+            .lock()
+            .map_err(|e| format!("Failed to lock activity store: {}", e))?;
+        Ok(Self::store_for(&mut current, &root).entries.clone())
+    }
+
+    fn store_for<'a>(current: &'a mut Option<(PathBuf, ActivityStore)>, root: &Path) -> &'a mut ActivityStore {
+        if current.as_ref().map_or(true, |(loaded, _)| loaded != root) {
+            *current = Some((root.to_path_buf(), ActivityStore::load(root)));
+        }
+        &mut current.as_mut().unwrap().1
+    }
+}
+
+/// Validated paths are canonical, so keys are computed against the canonical root
+fn canonical_root(workspace: &str) -> PathBuf {
+    Path::new(workspace)
+        .canonicalize()
+        .unwrap_or_else(|_| PathBuf::from(workspace))
+}
+
+// ============================================================================
+// TESTS
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const NOW: i64 = 1_700_000_000;
+
+    fn entry(at: i64, kind: ActivityKind, path: &str) -> ActivityEntry {
+        ActivityEntry { at, kind, path: Some(path.to_string()), detail: None }
+    }
+
+    #[test]
+    fn test_duplicate_events_are_coalesced() {
+        let mut store = ActivityStore::default();
+
+        // A watcher event, then the editor save that caused it
+        assert!(store.record(entry(NOW, ActivityKind::Modified, "plan.md")));
+        assert!(!store.record(entry(NOW + 1, ActivityKind::Saved, "plan.md")));
+        // Files created by an import
+        assert!(store.record(entry(NOW + 2, ActivityKind::Imported, "Archive")));
+        assert!(!store.record(entry(NOW + 3, ActivityKind::Created, "Archive/a.md")));
+        // Much later, a new entry
+        assert!(store.record(entry(NOW + ACTIVITY_COALESCE_SECS + 5, ActivityKind::Saved, "plan.md")));
+
+        let kinds: Vec<ActivityKind> = store.entries.iter().map(|e| e.kind).collect();
+        assert_eq!(kinds, vec![ActivityKind::Saved, ActivityKind::Imported, ActivityKind::Saved]);
+        assert_eq!(store.entries[0].at, NOW + 1);
+    }
+
+    #[test]
+    fn test_since_is_newest_first_and_capped() {
+        let mut store = ActivityStore::default();
+        for i in 0..(MAX_ENTRIES as i64 + 10) {
+            store.record(entry(NOW + i * 100, ActivityKind::Saved, "a.md"));
+        }
+        assert_eq!(store.entries.len(), MAX_ENTRIES);
+
+        let recent = store.since(NOW + (MAX_ENTRIES as i64 + 7) * 100, 10);
+        let times: Vec<i64> = recent.iter().map(|e| e.at).collect();
+        assert_eq!(times, vec![NOW + (MAX_ENTRIES as i64 + 9) * 100, NOW + (MAX_ENTRIES as i64 + 8) * 100, NOW + (MAX_ENTRIES as i64 + 7) * 100]);
+        assert_eq!(store.since(0, 2).len(), 2);
+    }
+
+    #[test]
+    fn test_parse_git_log() {
+        let commits = parse_git_log("1700000000\u{1f}abc1234\u{1f}Ada\u{1f}Add plan\nnot a commit\n");
+        assert_eq!(commits, vec![ActivityEntry {
+            at: 1_700_000_000,
+            kind: ActivityKind::Commit,
+            path: None,
+            detail: Some("abc1234 Add plan (Ada)".to_string()),
+        }]);
+    }
+}
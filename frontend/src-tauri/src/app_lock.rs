@@ -0,0 +1,258 @@
+//! App Lock
+//!
+//! When enabled, the app starts locked and every command except those in
+//! `UNLOCKED_COMMANDS` is rejected by the invoke handler with an
+//! `App locked:` error until the user passes the platform's owner check
+//! (see `crate::biometric`): Touch ID/Face ID or the passcode on Apple
+//! devices, Windows Hello, or the fingerprint/screen lock on Android. The
+//! backend never sees a password; a successful check only flips the lock.
+//!
+//! On mobile the app locks again when it comes back after more than
+//! `relock_after_secs` in the background (see `commands::lifecycle`); on
+//! desktop it stays unlocked until `lock_app` is called or it restarts.
+//!
+//! The setting is a user one (not a workspace one), stored in
+//! `<config>/app-lock.json`. A setting that exists but cannot be read or
+//! parsed fails closed: the app starts locked, and only the owner check
+//! unlocks it or resets the setting (`set_app_lock` rewrites the file).
+
+use std::fs;
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use serde::{Deserialize, Serialize};
+
+/// File name of the setting inside the config directory
+pub const LOCK_FILE: &str = "app-lock.json";
+
+/// Background time after which the app locks again, unless set
+pub const DEFAULT_RELOCK_AFTER_SECS: u64 = 60;
+
+/// Commands the locked app still answers
+pub const UNLOCKED_COMMANDS: &[&str] = &[
+    "get_app_lock_status",
+    "unlock_app",
+    "lock_app",
+    "get_backend_capabilities",
+    "suspend_background_tasks",
+    "resume_background_tasks",
+];
+
+/// Whether the app lock is on, and when it relocks
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AppLockSettings {
+    #[serde(default)]
+    pub enabled: bool,
+    /// Background time (mobile) after which the app locks again; 0 locks it
+    /// every time it is sent to the background
+    #[serde(default = "default_relock_after_secs")]
+    pub relock_after_secs: u64,
+}
+
+fn default_relock_after_secs() -> u64 {
+    DEFAULT_RELOCK_AFTER_SECS
+}
+
+impl Default for AppLockSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            relock_after_secs: DEFAULT_RELOCK_AFTER_SECS,
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+struct LockState {
+    settings: AppLockSettings,
+    locked: bool,
+    backgrounded_at: Option<Instant>,
+}
+
+/// The app lock (internally synchronized)
+#[derive(Clone, Default)]
+pub struct AppLock {
+    state: Arc<Mutex<LockState>>,
+}
+
+impl AppLock {
+    /// Creates a disabled lock
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Loads the setting from `config_dir`; an enabled lock starts locked
+    ///
+    /// A missing file leaves the lock disabled. A file that cannot be read
+    /// or parsed engages the lock (see `engage`) and returns the error.
+    pub fn load(&self, config_dir: &Path) -> Result<(), String> {
+        let settings = match fs::read_to_string(config_dir.join(LOCK_FILE)) {
+            Ok(json) => serde_json::from_str::<AppLockSettings>(&json)
+                .map_err(|e| format!("Failed to parse app lock settings: {}", e)),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(()),
+            Err(e) => Err(format!("Failed to read app lock settings: {}", e)),
+        };
+        let settings = settings.inspect_err(|_| self.engage())?;
+
+        let mut state = self.lock_state()?;
+        state.settings = settings;
+        state.locked = settings.enabled;
+        Ok(())
+    }
+
+    /// Enables and engages the lock when its setting is unknown (e.g. it
+    /// failed to load), so the app fails closed until the owner is verified
+    pub fn engage(&self) {
+        if let Ok(mut state) = self.state.lock() {
+            state.settings.enabled = true;
+            state.locked = true;
+        }
+    }
+
+    /// Saves the setting to `config_dir`
+    pub fn save(&self, config_dir: &Path) -> Result<(), String> {
+        let json = serde_json::to_string_pretty(&self.settings()?)
+            .map_err(|e| format!("Failed to serialize app lock settings: {}", e))?;
+        fs::write(config_dir.join(LOCK_FILE), json)
+            .map_err(|e| format!("Failed to save app lock settings: {}", e))
+    }
+
+    /// Gets the current setting
+    pub fn settings(&self) -> Result<AppLockSettings, String> {
+        Ok(self.lock_state()?.settings)
+    }
+
+    /// Replaces the setting; the app stays unlocked (the caller just
+    /// verified the user)
+    pub fn set_settings(&self, settings: AppLockSettings) -> Result<(), String> {
+        let mut state = self.lock_state()?;
+        state.settings = settings;
+        state.locked = false;
+        Ok(())
+    }
+
+    /// Checks if the app is locked
+    pub fn is_locked(&self) -> bool {
+        self.state.lock().map(|s| s.locked).unwrap_or(true)
+    }
+
+    /// Unlocks the app after a successful owner check
+    pub fn unlock(&self) -> Result<(), String> {
+        self.lock_state()?.locked = false;
+        Ok(())
+    }
+
+    /// Locks the app; returns false if the lock is disabled
+    pub fn lock(&self) -> Result<bool, String> {
+        let mut state = self.lock_state()?;
+        state.locked = state.settings.enabled;
+        Ok(state.locked)
+    }
+
+    /// Notes that the app went to the background
+    pub fn background(&self, now: Instant) {
+        if let Ok(mut state) = self.state.lock() {
+            state.backgrounded_at.get_or_insert(now);
+        }
+    }
+
+    /// Notes that the app is back, locking it if it was away too long
+    ///
+    /// Returns whether the app is locked.
+    pub fn foreground(&self, now: Instant) -> bool {
+        let Ok(mut state) = self.state.lock() else {
+            return true;
+        };
+        if let Some(since) = state.backgrounded_at.take() {
+            let away = now.saturating_duration_since(since);
+            if state.settings.enabled && away >= Duration::from_secs(state.settings.relock_after_secs) {
+                state.locked = true;
+            }
+        }
+        state.locked
+    }
+
+    /// Rejects a command while the app is locked (see `UNLOCKED_COMMANDS`)
+    pub fn check(&self, command: &str) -> Result<(), String> {
+        if self.is_locked() && !UNLOCKED_COMMANDS.contains(&command) {
+            return Err(format!("App locked: unlock the app to use {}", command));
+        }
+        Ok(())
+    }
+
+    fn lock_state(&self) -> Result<std::sync::MutexGuard<'_, LockState>, String> {
+        self.state
+            .lock()
+            .map_err(|e| format!("Failed to lock app lock state: {}", e))
+    }
+}
+
+// ============================================================================
+// TESTS
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_enabled_lock_starts_locked() {
+        let dir = TempDir::new().unwrap();
+        let lock = AppLock::new();
+        lock.load(dir.path()).unwrap();
+        assert!(!lock.is_locked());
+        assert!(lock.check("load_document_from_file").is_ok());
+
+        lock.set_settings(AppLockSettings { enabled: true, relock_after_secs: 30 }).unwrap();
+        lock.save(dir.path()).unwrap();
+
+        let restarted = AppLock::new();
+        restarted.load(dir.path()).unwrap();
+        assert!(restarted.is_locked());
+        let error = restarted.check("load_document_from_file").unwrap_err();
+        assert!(error.starts_with("App locked:"));
+        assert!(restarted.check("unlock_app").is_ok());
+
+        restarted.unlock().unwrap();
+        assert!(restarted.check("load_document_from_file").is_ok());
+    }
+
+    #[test]
+    fn test_unreadable_setting_starts_locked() {
+        let dir = TempDir::new().unwrap();
+        fs::write(dir.path().join(LOCK_FILE), "{ not json").unwrap();
+
+        let lock = AppLock::new();
+        assert!(lock.load(dir.path()).unwrap_err().starts_with("Failed to parse"));
+        assert!(lock.is_locked());
+        assert!(lock.check("load_document_from_file").is_err());
+        assert!(lock.settings().unwrap().enabled);
+
+        // Resetting the setting (after the owner check) unlocks it
+        lock.set_settings(AppLockSettings::default()).unwrap();
+        lock.save(dir.path()).unwrap();
+        let restarted = AppLock::new();
+        restarted.load(dir.path()).unwrap();
+        assert!(!restarted.is_locked());
+    }
+
+    #[test]
+    fn test_relocks_after_time_in_background() {
+        let lock = AppLock::new();
+        lock.set_settings(AppLockSettings { enabled: true, relock_after_secs: 30 }).unwrap();
+        let start = Instant::now();
+
+        lock.background(start);
+        assert!(!lock.foreground(start + Duration::from_secs(10)));
+
+        lock.background(start);
+        assert!(lock.foreground(start + Duration::from_secs(31)));
+
+        // A disabled lock never locks
+        let disabled = AppLock::new();
+        disabled.background(start);
+        assert!(!disabled.foreground(start + Duration::from_secs(3600)));
+        assert!(!disabled.lock().unwrap());
+    }
+}
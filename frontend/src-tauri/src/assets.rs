@@ -0,0 +1,387 @@
+//! Asset Bundling for Exports
+//!
+//! A note exported on its own loses its images: their links are relative to
+//! the note's place in the workspace. `bundle_assets` resolves the image
+//! links of a note and either copies the images into a folder next to the
+//! exported file (`<name>_assets/`) or embeds them as `data:` URIs, then
+//! rewrites the links to match.
+//!
+//! Recognized links:
+//!
+//! - `![alt](images/photo.png)` - relative to the note
+//! - `![alt](/assets/photo.png)` - relative to the workspace root
+//! - `![[photo.png]]` - by path, or by file name anywhere in the workspace
+//!
+//! Remote (`https://…`) and `data:` images are left alone, as are images
+//! inside code. Only files inside the workspace are ever bundled.
+
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use serde::{Deserialize, Serialize};
+use crate::links::percent_decode;
+use crate::notes::relative_path;
+use crate::utils::{base64_encode, walk_metadata};
+use crate::workspace_settings::WorkspaceSettings;
+
+/// Image formats that are bundled
+pub const ASSET_EXTENSIONS: &[&str] = &["png", "jpg", "jpeg", "gif", "webp", "bmp", "svg", "avif"];
+
+/// How referenced images travel with an exported note
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AssetMode {
+    /// Copy images into `<name>_assets/` next to the export
+    Copy,
+    /// Inline images as base64 `data:` URIs
+    Embed,
+}
+
+/// Outcome of bundling the assets of a note
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct AssetReport {
+    /// Bundled images (paths relative to the workspace root)
+    pub bundled: Vec<String>,
+    /// Image links that could not be resolved, as written
+    pub missing: Vec<String>,
+    /// Folder the images were copied to (copy mode only)
+    pub assets_dir: Option<String>,
+}
+
+/// An image reference found in a note
+#[derive(Debug, Clone, PartialEq)]
+pub struct ImageLink {
+    pub alt: String,
+    /// Link target as written (percent-decoded)
+    pub target: String,
+    /// True for `![[...]]` embeds
+    pub wiki: bool,
+}
+
+/// Bundles the images referenced by `content` for an export to `dest`
+///
+/// # Arguments
+/// * `content` - Note content (possibly already transformed for export)
+/// * `note_path` - The note inside the workspace, for relative links
+/// * `workspace_root` - Canonical workspace root
+/// * `mode` - Copy next to `dest` or embed
+/// * `dest` - Path of the exported file
+pub fn bundle_assets(
+    content: &str,
+    note_path: &Path,
+    workspace_root: &Path,
+    mode: AssetMode,
+    dest: &Path,
+) -> Result<(String, AssetReport), String> {
+    let note_dir = note_path.parent().unwrap_or(workspace_root);
+    let dir_name = format!(
+        "{}_assets",
+        dest.file_stem().map(|s| s.to_string_lossy().to_string()).unwrap_or_default()
+    );
+    let assets_dir = dest.parent().unwrap_or(Path::new(".")).join(&dir_name);
+
+    let mut report = AssetReport::default();
+    let mut names: HashMap<PathBuf, String> = HashMap::new();
+    let mut error = None;
+
+    let rewritten = rewrite_image_links(content, &mut |link| {
+        if error.is_some() {
+            return None;
+        }
+        let Some(source) = resolve_asset(link, note_dir, workspace_root) else {
+            if !is_remote(&link.target) && !report.missing.contains(&link.target) {
+                report.missing.push(link.target.clone());
+            }
+            return None;
+        };
+
+        let relative = relative_path(workspace_root, &source);
+        if !report.bundled.contains(&relative) {
+            report.bundled.push(relative);
+        }
+
+        let url = match mode {
+            AssetMode::Embed => match fs::read(&source) {
+                Ok(bytes) => format!("data:{};base64,{}", mime_type(&source), base64_encode(&bytes)),
+                Err(e) => {
+                    error = Some(format!("Failed to read asset {:?}: {}", source, e));
+                    return None;
+                }
+            },
+            AssetMode::Copy => {
+                let name = match names.get(&source) {
+                    Some(name) => name.clone(),
+                    None => {
+                        let name = unique_name(&source, &names);
+                        if let Err(e) = fs::create_dir_all(&assets_dir)
+                            .and_then(|_| fs::copy(&source, assets_dir.join(&name)))
+                        {
+                            error = Some(format!("Failed to copy asset {:?}: {}", source, e));
+                            return None;
+                        }
+                        names.insert(source.clone(), name.clone());
+                        name
+                    }
+                };
+                link_target(&format!("{}/{}", dir_name, name))
+            }
+        };
+
+        Some(format!("![{}]({})", link.alt, url))
+    });
+
+    if let Some(e) = error {
+        return Err(e);
+    }
+    if mode == AssetMode::Copy && !names.is_empty() {
+        report.assets_dir = Some(assets_dir.to_string_lossy().to_string());
+    }
+    Ok((rewritten, report))
+}
+
+/// Calls `replace` for every image link outside code; `Some` replaces the link
+pub fn rewrite_image_links(content: &str, replace: &mut dyn FnMut(&ImageLink) -> Option<String>) -> String {
+    let mut out = Vec::new();
+    let mut in_fence = false;
+
+    for line in content.split('\n') {
+        let trimmed = line.trim_start();
+        if trimmed.starts_with("```") || trimmed.starts_with("~~~") {
+            in_fence = !in_fence;
+        }
+        if in_fence || !line.contains("![") {
+            out.push(line.to_string());
+            continue;
+        }
+        out.push(rewrite_line(line, replace));
+    }
+
+    out.join("\n")
+}
+
+fn rewrite_line(line: &str, replace: &mut dyn FnMut(&ImageLink) -> Option<String>) -> String {
+    let mut out = String::new();
+    let mut rest = line;
+
+    while let Some(start) = rest.find("![") {
+        out.push_str(&rest[..start]);
+        let candidate = &rest[start..];
+        let in_code = out.matches('`').count() % 2 == 1;
+
+        match parse_image_link(candidate) {
+            Some((link, len)) if !in_code => {
+                match replace(&link) {
+                    Some(replacement) => out.push_str(&replacement),
+                    None => out.push_str(&candidate[..len]),
+                }
+                rest = &candidate[len..];
+            }
+            _ => {
+                out.push_str("![");
+                rest = &candidate[2..];
+            }
+        }
+    }
+
+    out.push_str(rest);
+    out
+}
+
+/// Parses an image link at the start of `text`; returns it with its byte length
+fn parse_image_link(text: &str) -> Option<(ImageLink, usize)> {
+    if let Some(inner) = text.strip_prefix("![[") {
+        let end = inner.find("]]")?;
+        let mut parts = inner[..end].splitn(2, '|');
+        let target = parts.next()?.trim().to_string();
+        // `|300` or `|300x200` is a size, anything else an alias
+        let alt = parts
+            .next()
+            .map(str::trim)
+            .filter(|a| !a.chars().all(|c| c.is_ascii_digit() || c == 'x'))
+            .unwrap_or("")
+            .to_string();
+        return has_asset_extension(&target).then(|| (ImageLink { alt, target, wiki: true }, 3 + end + 2));
+    }
+
+    let inner = text.strip_prefix("![")?;
+    let alt_end = inner.find(']')?;
+    let after_alt = inner[alt_end + 1..].strip_prefix('(')?;
+    let close = after_alt.find(')')?;
+    let raw = after_alt[..close].trim();
+
+    let target = match raw.strip_prefix('<') {
+        Some(angled) => &angled[..angled.find('>')?],
+        None => raw.split_whitespace().next().unwrap_or(""),
+    };
+    if target.is_empty() {
+        return None;
+    }
+
+    let link = ImageLink {
+        alt: inner[..alt_end].to_string(),
+        target: percent_decode(target),
+        wiki: false,
+    };
+    Some((link, 2 + alt_end + 2 + close + 1))
+}
+
+/// Finds the file an image link points to, inside the workspace only
+pub fn resolve_asset(link: &ImageLink, note_dir: &Path, workspace_root: &Path) -> Option<PathBuf> {
+    if is_remote(&link.target) || !has_asset_extension(&link.target) {
+        return None;
+    }
+
+    let mut candidates = Vec::new();
+    match link.target.strip_prefix('/') {
+        Some(in_vault) => candidates.push(workspace_root.join(in_vault)),
+        None => {
+            candidates.push(note_dir.join(&link.target));
+            if link.wiki {
+                candidates.push(workspace_root.join(&link.target));
+            }
+        }
+    }
+
+    let found = candidates.into_iter().find(|c| c.is_file()).or_else(|| {
+        // Wiki embeds may name a file anywhere in the workspace
+        let name = Path::new(&link.target).file_name()?;
+        link.wiki.then(|| find_by_name(workspace_root, name)).flatten()
+    })?;
+
+    let canonical = found.canonicalize().ok()?;
+    canonical.starts_with(workspace_root).then_some(canonical)
+}
+
+/// MIME type of an image file, by extension
+pub fn mime_type(path: &Path) -> &'static str {
+    let extension = path.extension().map(|e| e.to_string_lossy().to_lowercase()).unwrap_or_default();
+    match extension.as_str() {
+        "png" => "image/png",
+        "jpg" | "jpeg" => "image/jpeg",
+        "gif" => "image/gif",
+        "webp" => "image/webp",
+        "bmp" => "image/bmp",
+        "svg" => "image/svg+xml",
+        "avif" => "image/avif",
+        _ => "application/octet-stream",
+    }
+}
+
+fn is_remote(target: &str) -> bool {
+    target.contains("://") || target.starts_with("data:") || target.starts_with("mailto:")
+}
+
+fn has_asset_extension(target: &str) -> bool {
+    let name = target.rsplit('/').next().unwrap_or(target);
+    name.rsplit_once('.')
+        .is_some_and(|(_, ext)| ASSET_EXTENSIONS.contains(&ext.to_lowercase().as_str()))
+}
+
+/// Breadth-first search for a file name below `root` (the canonical
+/// workspace root), skipping hidden entries and following symbolic links
+/// according to the workspace symlink policy
+fn find_by_name(root: &Path, name: &std::ffi::OsStr) -> Option<PathBuf> {
+    let policy = WorkspaceSettings::load_symlink_policy(root);
+    let mut visited = HashSet::from([root.to_path_buf()]);
+    let mut queue = std::collections::VecDeque::from([root.to_path_buf()]);
+    while let Some(dir) = queue.pop_front() {
+        let Ok(read) = fs::read_dir(&dir) else { continue };
+        let mut entries: Vec<PathBuf> = read
+            .filter_map(|e| e.ok().map(|e| e.path()))
+            .filter(|p| !p.file_name().is_some_and(|n| n.to_string_lossy().starts_with('.')))
+            .collect();
+        entries.sort();
+        for entry in entries {
+            let Some(metadata) = walk_metadata(&entry, root, policy, &mut visited) else {
+                continue;
+            };
+            if metadata.is_dir() {
+                queue.push_back(entry);
+            } else if entry.file_name() == Some(name) {
+                return Some(entry);
+            }
+        }
+    }
+    None
+}
+
+/// File name for a copied asset that does not clash with another source
+fn unique_name(source: &Path, taken: &HashMap<PathBuf, String>) -> String {
+    let file_name = source.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+    let (stem, ext) = file_name.rsplit_once('.').unwrap_or((&file_name, ""));
+
+    let mut name = file_name.clone();
+    let mut counter = 2;
+    while taken.values().any(|n| n.eq_ignore_ascii_case(&name)) {
+        name = format!("{}-{}.{}", stem, counter, ext);
+        counter += 1;
+    }
+    name
+}
+
+/// Writes a link target, in angle brackets when it would break the link
+fn link_target(target: &str) -> String {
+    if target.contains([' ', '(', ')']) {
+        format!("<{}>", target)
+    } else {
+        target.to_string()
+    }
+}
+
+// ============================================================================
+// TESTS
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn setup() -> (TempDir, PathBuf) {
+        let dir = TempDir::new().unwrap();
+        let root = dir.path().canonicalize().unwrap();
+        fs::create_dir_all(root.join("Notes/images")).unwrap();
+        fs::create_dir_all(root.join("assets")).unwrap();
+        fs::create_dir_all(root.join("other")).unwrap();
+        fs::write(root.join("Notes/images/photo one.png"), b"png-1").unwrap();
+        fs::write(root.join("assets/logo.svg"), b"<svg/>").unwrap();
+        fs::write(root.join("other/photo one.png"), b"png-2").unwrap();
+        (dir, root)
+    }
+
+    #[test]
+    fn test_copy_mode_copies_and_rewrites() {
+        let (_dir, root) = setup();
+        let export = TempDir::new().unwrap();
+        let dest = export.path().join("Trip.md");
+        let note = root.join("Notes/trip.md");
+
+        let content = "![Photo](images/photo%20one.png)\n![[logo.svg|120]]\n![](</other/photo one.png>)\n![gone](missing.png)\n`![x](images/photo%20one.png)`\n![web](https://x.io/a.png)";
+        let (out, report) = bundle_assets(content, &note, &root, AssetMode::Copy, &dest).unwrap();
+
+        assert_eq!(
+            out,
+            "![Photo](<Trip_assets/photo one.png>)\n![](Trip_assets/logo.svg)\n![](<Trip_assets/photo one-2.png>)\n![gone](missing.png)\n`![x](images/photo%20one.png)`\n![web](https://x.io/a.png)"
+        );
+        assert_eq!(report.bundled, vec!["Notes/images/photo one.png", "assets/logo.svg", "other/photo one.png"]);
+        assert_eq!(report.missing, vec!["missing.png"]);
+        assert_eq!(fs::read(export.path().join("Trip_assets/photo one-2.png")).unwrap(), b"png-2");
+    }
+
+    #[test]
+    fn test_embed_mode_inlines_data_uris() {
+        let (_dir, root) = setup();
+        let dest = root.join("out.md");
+
+        let (out, report) = bundle_assets("![[logo.svg]]", &root.join("Notes/a.md"), &root, AssetMode::Embed, &dest).unwrap();
+        assert_eq!(out, "![](data:image/svg+xml;base64,PHN2Zy8+)");
+        assert_eq!(report.assets_dir, None);
+    }
+
+    #[test]
+    fn test_links_outside_workspace_are_not_bundled() {
+        let (_dir, root) = setup();
+        let link = ImageLink { alt: String::new(), target: "../../../etc/img.png".to_string(), wiki: false };
+        assert_eq!(resolve_asset(&link, &root.join("Notes"), &root), None);
+    }
+}
@@ -0,0 +1,264 @@
+//! Audio Recording
+//!
+//! Voice memos recorded from the default input device with `cpal`:
+//!
+//! - Only one recording runs at a time. The `cpal` stream is not `Send`, so it
+//!   lives on a dedicated thread that collects samples until it is told to
+//!   stop.
+//! - Samples are kept as 16-bit PCM in the device's sample rate and channel
+//!   count and saved as a WAV file.
+
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::{Arc, Mutex};
+use std::thread::{self, JoinHandle};
+use chrono::{DateTime, Local};
+use cpal::traits::{DeviceTrait, HostTrait, StreamTrait};
+
+/// Captured audio, interleaved 16-bit PCM
+#[derive(Debug, Clone, PartialEq)]
+pub struct Recording {
+    pub samples: Vec<i16>,
+    pub sample_rate: u32,
+    pub channels: u16,
+    pub started_at: DateTime<Local>,
+}
+
+impl Recording {
+    /// Length of the recording in seconds
+    pub fn duration_secs(&self) -> f64 {
+        let frames = self.samples.len() / self.channels.max(1) as usize;
+        frames as f64 / self.sample_rate.max(1) as f64
+    }
+
+    /// File name for the recording, e.g. `recording-20240517-093000.wav`
+    pub fn file_name(&self) -> String {
+        format!("recording-{}.wav", self.started_at.format("%Y%m%d-%H%M%S"))
+    }
+
+    /// Encodes the recording as a PCM WAV file
+    pub fn to_wav(&self) -> Vec<u8> {
+        const BITS_PER_SAMPLE: u16 = 16;
+        let block_align = self.channels * BITS_PER_SAMPLE / 8;
+        let byte_rate = self.sample_rate * block_align as u32;
+        let data_len = (self.samples.len() * 2) as u32;
+
+        let mut wav = Vec::with_capacity(44 + data_len as usize);
+        wav.extend_from_slice(b"RIFF");
+        wav.extend_from_slice(&(36 + data_len).to_le_bytes());
+        wav.extend_from_slice(b"WAVE");
+        wav.extend_from_slice(b"fmt ");
+        wav.extend_from_slice(&16u32.to_le_bytes());
+        wav.extend_from_slice(&1u16.to_le_bytes()); // PCM
+        wav.extend_from_slice(&self.channels.to_le_bytes());
+        wav.extend_from_slice(&self.sample_rate.to_le_bytes());
+        wav.extend_from_slice(&byte_rate.to_le_bytes());
+        wav.extend_from_slice(&block_align.to_le_bytes());
+        wav.extend_from_slice(&BITS_PER_SAMPLE.to_le_bytes());
+        wav.extend_from_slice(b"data");
+        wav.extend_from_slice(&data_len.to_le_bytes());
+        for sample in &self.samples {
+            wav.extend_from_slice(&sample.to_le_bytes());
+        }
+        wav
+    }
+}
+
+/// A recording in progress
+struct ActiveRecording {
+    stop: Sender<()>,
+    worker: JoinHandle<Result<Recording, String>>,
+}
+
+/// The single audio recorder of the application (internally synchronized)
+#[derive(Clone, Default)]
+pub struct AudioRecorder {
+    active: Arc<Mutex<Option<ActiveRecording>>>,
+}
+
+impl AudioRecorder {
+    /// Creates an idle recorder
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Starts recording from the default input device
+    ///
+    /// Fails if a recording is already running or the device cannot be opened.
+    pub fn start(&self) -> Result<(), String> {
+        let mut active = self.lock()?;
+        if active.is_some() {
+            return Err("A recording is already running".to_string());
+        }
+
+        let (stop, stop_signal) = mpsc::channel();
+        let (ready, started) = mpsc::channel();
+        let worker = thread::spawn(move || record(stop_signal, ready));
+
+        match started.recv() {
+            Ok(Ok(())) => {
+                *active = Some(ActiveRecording { stop, worker });
+                log::info!("🎙️ Started audio recording");
+                Ok(())
+            }
+            Ok(Err(e)) => Err(e),
+            Err(_) => Err("Audio recording thread exited unexpectedly".to_string()),
+        }
+    }
+
+    /// Stops the running recording and returns what was captured
+    pub fn stop(&self) -> Result<Recording, String> {
+        let recording = self
+            .lock()?
+            .take()
+            .ok_or("No recording is running")?;
+
+        // The worker also stops if the sender is gone, so a failed send is harmless
+        let _ = recording.stop.send(());
+        let recording = recording
+            .worker
+            .join()
+            .map_err(|_| "Audio recording thread panicked".to_string())??;
+
+        log::info!("🎙️ Stopped audio recording ({:.1}s)", recording.duration_secs());
+        Ok(recording)
+    }
+
+    fn lock(&self) -> Result<std::sync::MutexGuard<'_, Option<ActiveRecording>>, String> {
+        self.active
+            .lock()
+            .map_err(|e| format!("Failed to lock audio recorder: {}", e))
+    }
+}
+
+/// Checks if there is an input device to record from
+pub fn input_available() -> bool {
+    cpal::default_host().default_input_device().is_some()
+}
+
+/// Records on the current thread until `stop_signal` fires
+///
+/// The outcome of opening the device is reported through `ready` before
+/// any samples are collected.
+fn record(stop_signal: Receiver<()>, ready: Sender<Result<(), String>>) -> Result<Recording, String> {
+    let samples = Arc::new(Mutex::new(Vec::new()));
+    let opened = open_stream(samples.clone());
+    let (stream, sample_rate, channels) = match opened {
+        Ok(opened) => {
+            let _ = ready.send(Ok(()));
+            opened
+        }
+        Err(e) => {
+            let _ = ready.send(Err(e.clone()));
+            return Err(e);
+        }
+    };
+    let started_at = Local::now();
+
+    // Returns on stop or when the recorder is dropped
+    let _ = stop_signal.recv();
+    drop(stream);
+
+    let samples = std::mem::take(
+        &mut *samples
+            .lock()
+            .map_err(|e| format!("Failed to lock audio buffer: {}", e))?,
+    );
+    Ok(Recording { samples, sample_rate, channels, started_at })
+}
+
+/// Opens and starts an input stream that appends to `samples`
+fn open_stream(samples: Arc<Mutex<Vec<i16>>>) -> Result<(cpal::Stream, u32, u16), String> {
+    let device = cpal::default_host()
+        .default_input_device()
+        .ok_or("No audio input device found")?;
+    let supported = device
+        .default_input_config()
+        .map_err(|e| format!("Failed to read input device config: {}", e))?;
+    let sample_format = supported.sample_format();
+    let config: cpal::StreamConfig = supported.into();
+
+    let on_error = |e| log::error!("❌ Audio input error: {}", e);
+    let stream = match sample_format {
+        cpal::SampleFormat::F32 => device.build_input_stream(
+            &config,
+            move |data: &[f32], _: &cpal::InputCallbackInfo| {
+                push_samples(&samples, data.iter().map(|&s| (s.clamp(-1.0, 1.0) * i16::MAX as f32) as i16))
+            },
+            on_error,
+            None,
+        ),
+        cpal::SampleFormat::I16 => device.build_input_stream(
+            &config,
+            move |data: &[i16], _: &cpal::InputCallbackInfo| push_samples(&samples, data.iter().copied()),
+            on_error,
+            None,
+        ),
+        cpal::SampleFormat::U16 => device.build_input_stream(
+            &config,
+            move |data: &[u16], _: &cpal::InputCallbackInfo| {
+                push_samples(&samples, data.iter().map(|&s| (s as i32 - 32768) as i16))
+            },
+            on_error,
+            None,
+        ),
+        other => return Err(format!("Unsupported audio sample format: {:?}", other)),
+    }
+    .map_err(|e| format!("Failed to open audio input: {}", e))?;
+
+    stream
+        .play()
+        .map_err(|e| format!("Failed to start audio input: {}", e))?;
+    Ok((stream, config.sample_rate.0, config.channels))
+}
+
+fn push_samples(buffer: &Mutex<Vec<i16>>, data: impl Iterator<Item = i16>) {
+    if let Ok(mut buffer) = buffer.lock() {
+        buffer.extend(data);
+    }
+}
+
+// ============================================================================
+// TESTS
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn recording(samples: Vec<i16>) -> Recording {
+        Recording {
+            samples,
+            sample_rate: 8000,
+            channels: 2,
+            started_at: Local.with_ymd_and_hms(2024, 5, 17, 9, 30, 0).unwrap(),
+        }
+    }
+
+    #[test]
+    fn test_wav_header_and_data() {
+        let wav = recording(vec![1, -1, 256, 0]).to_wav();
+
+        assert_eq!(wav.len(), 44 + 8);
+        assert_eq!(&wav[0..4], b"RIFF");
+        assert_eq!(&wav[8..16], b"WAVEfmt ");
+        assert_eq!(u16::from_le_bytes([wav[22], wav[23]]), 2);
+        assert_eq!(u32::from_le_bytes([wav[24], wav[25], wav[26], wav[27]]), 8000);
+        assert_eq!(u32::from_le_bytes([wav[28], wav[29], wav[30], wav[31]]), 32000);
+        assert_eq!(&wav[36..40], b"data");
+        assert_eq!(&wav[44..], &[1, 0, 255, 255, 0, 1, 0, 0]);
+    }
+
+    #[test]
+    fn test_recording_name_and_duration() {
+        let recording = recording(vec![0; 16000]);
+        assert_eq!(recording.file_name(), "recording-20240517-093000.wav");
+        assert_eq!(recording.duration_secs(), 1.0);
+    }
+
+    #[test]
+    fn test_stop_without_recording_fails() {
+        let recorder = AudioRecorder::new();
+        assert!(recorder.stop().is_err());
+    }
+}
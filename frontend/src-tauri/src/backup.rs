@@ -0,0 +1,592 @@
+//! Deduplicating Backups
+//!
+//! A content-addressed backup store. Files are cut into content-defined
+//! chunks (boundaries come from a rolling gear hash, so an edit only changes
+//! the chunks around it), every chunk is named by its BLAKE3 hash and stored
+//! once, compressed with zstd. A backup is a manifest listing each file with
+//! its chunk hashes, so repeated backups of a large vault only add the
+//! chunks that changed.
+//!
+//! ## Layout
+//! ```text
+//! <store>/
+//! ├── chunks/ab/ab12…    zstd-compressed chunk, named by the hash of its content
+//! └── backups/<id>.json  manifest of one backup
+//! ```
+//!
+//! The manifest is written after all of its chunks, so an interrupted backup
+//! only leaves unreferenced chunks behind; `prune` removes those, and the
+//! chunks no backup needs anymore after `delete`.
+
+use std::collections::{BTreeSet, HashSet};
+use std::fs;
+use std::io::Read;
+use std::path::{Component, Path, PathBuf};
+use serde::{Deserialize, Serialize};
+use crate::mindmap_meta::MINDMAP_DIR;
+use crate::utils::walk_metadata;
+use crate::workspace_settings::{WorkspaceSettings, SETTINGS_DIR};
+
+/// Smallest chunk (except the last one of a file)
+const MIN_CHUNK: usize = 16 * 1024;
+
+/// Largest chunk
+const MAX_CHUNK: usize = 256 * 1024;
+
+/// Cut where these hash bits are zero: 16 bits, about 64 KiB past `MIN_CHUNK`
+const BOUNDARY_MASK: u64 = 0xFFFF << 48;
+
+/// zstd compression level of chunks
+const COMPRESSION_LEVEL: i32 = 3;
+
+/// Random values per byte for the gear hash (splitmix64 sequence)
+const GEAR: [u64; 256] = gear_table();
+
+const fn gear_table() -> [u64; 256] {
+    let mut table = [0u64; 256];
+    let mut state: u64 = 0;
+    let mut i = 0;
+    while i < 256 {
+        state = state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        table[i] = z ^ (z >> 31);
+        i += 1;
+    }
+    table
+}
+
+/// Manifest of one backup
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BackupManifest {
+    pub id: String,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+    /// Folder that was backed up
+    pub source: String,
+    pub files: Vec<BackupFile>,
+}
+
+/// A file of a backup
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BackupFile {
+    /// Path relative to the backed-up folder, `/`-separated
+    pub path: String,
+    pub size: u64,
+    /// Hashes of the file's chunks, in order
+    pub chunks: Vec<String>,
+}
+
+/// Summary of a backup for listings
+#[derive(Debug, Clone, Serialize)]
+pub struct BackupInfo {
+    pub id: String,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+    pub source: String,
+    pub files: usize,
+    /// Total size of the backed-up files in bytes
+    pub size: u64,
+}
+
+/// Result of creating a backup
+#[derive(Debug, Clone, Serialize)]
+pub struct BackupSummary {
+    pub id: String,
+    pub files: usize,
+    /// Total size of the backed-up files in bytes
+    pub size: u64,
+    /// Chunks the backup references
+    pub chunks: usize,
+    /// Chunks that were not in the store yet
+    pub new_chunks: usize,
+    /// Compressed bytes added to the store
+    pub stored_bytes: u64,
+}
+
+/// Result of checking a backup's integrity
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct VerifyReport {
+    pub id: String,
+    /// Distinct chunks checked
+    pub chunks: usize,
+    /// Chunks missing from the store
+    pub missing: Vec<String>,
+    /// Chunks that cannot be decompressed or do not match their hash
+    pub corrupt: Vec<String>,
+    /// Files that cannot be restored completely
+    pub damaged_files: Vec<String>,
+    pub ok: bool,
+}
+
+/// Result of removing unreferenced chunks
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct PruneReport {
+    pub chunks_removed: usize,
+    pub bytes_freed: u64,
+}
+
+/// Default store location (in the user's data directory)
+pub fn default_dir() -> Result<PathBuf, String> {
+    Ok(dirs::data_dir()
+        .ok_or("Failed to get data directory")?
+        .join("mdreader")
+        .join("backups"))
+}
+
+/// A backup store in a directory
+pub struct BackupStore {
+    root: PathBuf,
+}
+
+impl BackupStore {
+    /// Opens the store in `root` (created on the first backup)
+    pub fn open(root: impl Into<PathBuf>) -> Self {
+        Self { root: root.into() }
+    }
+
+    /// Backs up a folder
+    ///
+    /// Hidden files are skipped, except the settings and mindmap folders.
+    /// `on_file` is called with `(done, total, path)` before each file;
+    /// returning `false` cancels the backup, leaving no manifest.
+    pub fn create(
+        &self,
+        source: &Path,
+        on_file: &mut dyn FnMut(usize, usize, &str) -> bool,
+    ) -> Result<BackupSummary, String> {
+        let paths = collect_files(source)?;
+        let created_at = chrono::Utc::now();
+        let id = format!(
+            "{}-{}",
+            created_at.format("%Y%m%dT%H%M%S%3fZ"),
+            &uuid::Uuid::new_v4().simple().to_string()[..8]
+        );
+
+        let mut files = Vec::with_capacity(paths.len());
+        let mut referenced = HashSet::new();
+        let (mut new_chunks, mut stored_bytes) = (0, 0);
+        for (done, (path, relative)) in paths.iter().enumerate() {
+            if !on_file(done, paths.len(), relative) {
+                return Err("Backup cancelled".to_string());
+            }
+            let mut chunks = Vec::new();
+            let size = chunk_file(path, &mut |data| {
+                let (hash, stored) = self.put_chunk(data)?;
+                if let Some(bytes) = stored {
+                    new_chunks += 1;
+                    stored_bytes += bytes;
+                }
+                referenced.insert(hash.clone());
+                chunks.push(hash);
+                Ok(())
+            })?;
+            files.push(BackupFile { path: relative.clone(), size, chunks });
+        }
+
+        let manifest = BackupManifest {
+            id: id.clone(),
+            created_at,
+            source: source.to_string_lossy().to_string(),
+            files,
+        };
+        let json = serde_json::to_string_pretty(&manifest)
+            .map_err(|e| format!("Failed to serialize backup manifest: {}", e))?;
+        write_atomic(&self.manifest_path(&id)?, json.as_bytes())?;
+
+        Ok(BackupSummary {
+            id,
+            files: manifest.files.len(),
+            size: manifest.files.iter().map(|f| f.size).sum(),
+            chunks: referenced.len(),
+            new_chunks,
+            stored_bytes,
+        })
+    }
+
+    /// Lists the backups in the store, newest first
+    pub fn list(&self) -> Result<Vec<BackupInfo>, String> {
+        let mut backups: Vec<BackupInfo> = self
+            .manifests()?
+            .into_iter()
+            .map(|m| BackupInfo {
+                size: m.files.iter().map(|f| f.size).sum(),
+                files: m.files.len(),
+                id: m.id,
+                created_at: m.created_at,
+                source: m.source,
+            })
+            .collect();
+        backups.sort_by_key(|b| std::cmp::Reverse(b.created_at));
+        Ok(backups)
+    }
+
+    /// Reads the manifest of a backup
+    pub fn manifest(&self, id: &str) -> Result<BackupManifest, String> {
+        let json = fs::read_to_string(self.manifest_path(id)?)
+            .map_err(|e| format!("Failed to read backup {}: {}", id, e))?;
+        serde_json::from_str(&json).map_err(|e| format!("Failed to parse backup manifest: {}", e))
+    }
+
+    /// Checks that every chunk of a backup is present and intact
+    pub fn verify(&self, id: &str) -> Result<VerifyReport, String> {
+        let manifest = self.manifest(id)?;
+        let hashes: BTreeSet<&String> = manifest.files.iter().flat_map(|f| &f.chunks).collect();
+
+        let mut report = VerifyReport { id: id.to_string(), chunks: hashes.len(), ..Default::default() };
+        for hash in hashes {
+            if !self.chunk_path(hash).is_file() {
+                report.missing.push(hash.clone());
+            } else if self.read_chunk(hash).is_err() {
+                report.corrupt.push(hash.clone());
+            }
+        }
+        report.damaged_files = manifest
+            .files
+            .iter()
+            .filter(|f| f.chunks.iter().any(|h| report.missing.contains(h) || report.corrupt.contains(h)))
+            .map(|f| f.path.clone())
+            .collect();
+        report.ok = report.missing.is_empty() && report.corrupt.is_empty();
+        Ok(report)
+    }
+
+    /// Restores a backup into `dest`, which must not exist yet
+    ///
+    /// # Returns
+    /// * `Ok(usize)` - Number of files restored
+    pub fn restore(&self, id: &str, dest: &Path) -> Result<usize, String> {
+        if dest.exists() {
+            return Err(format!("Restore destination already exists: {}", dest.display()));
+        }
+        let manifest = self.manifest(id)?;
+        for file in &manifest.files {
+            let relative = Path::new(&file.path);
+            if !relative.components().all(|c| matches!(c, Component::Normal(_))) {
+                return Err(format!("Invalid path in backup: {}", file.path));
+            }
+            let mut content = Vec::with_capacity(file.size as usize);
+            for hash in &file.chunks {
+                content.extend(self.read_chunk(hash)?);
+            }
+            let target = dest.join(relative);
+            if let Some(parent) = target.parent() {
+                fs::create_dir_all(parent)
+                    .map_err(|e| format!("Failed to create directory: {}", e))?;
+            }
+            fs::write(&target, content)
+                .map_err(|e| format!("Failed to restore {}: {}", file.path, e))?;
+        }
+        Ok(manifest.files.len())
+    }
+
+    /// Deletes a backup's manifest; its chunks stay until `prune`
+    pub fn delete(&self, id: &str) -> Result<(), String> {
+        fs::remove_file(self.manifest_path(id)?)
+            .map_err(|e| format!("Failed to delete backup {}: {}", id, e))
+    }
+
+    /// Removes the chunks no backup references (and leftovers of
+    /// interrupted writes)
+    pub fn prune(&self) -> Result<PruneReport, String> {
+        let referenced: HashSet<String> = self
+            .manifests()?
+            .into_iter()
+            .flat_map(|m| m.files.into_iter().flat_map(|f| f.chunks))
+            .collect();
+
+        let mut report = PruneReport::default();
+        for dir in read_dir_sorted(&self.root.join("chunks"))? {
+            for chunk in read_dir_sorted(&dir)? {
+                let name = chunk.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+                if referenced.contains(&name) {
+                    continue;
+                }
+                let size = fs::metadata(&chunk).map(|m| m.len()).unwrap_or(0);
+                fs::remove_file(&chunk)
+                    .map_err(|e| format!("Failed to remove chunk: {}", e))?;
+                report.chunks_removed += 1;
+                report.bytes_freed += size;
+            }
+            // Only succeeds once the folder is empty
+            let _ = fs::remove_dir(&dir);
+        }
+        Ok(report)
+    }
+
+    // Helper: Stores a chunk unless it is already stored; returns its hash
+    // and the compressed size if it was new
+    fn put_chunk(&self, data: &[u8]) -> Result<(String, Option<u64>), String> {
+        let hash = blake3::hash(data).to_hex().to_string();
+        let path = self.chunk_path(&hash);
+        if path.is_file() {
+            return Ok((hash, None));
+        }
+        let compressed = zstd::encode_all(data, COMPRESSION_LEVEL)
+            .map_err(|e| format!("Failed to compress chunk: {}", e))?;
+        write_atomic(&path, &compressed)?;
+        Ok((hash, Some(compressed.len() as u64)))
+    }
+
+    // Helper: Reads a chunk, checking it against its hash
+    fn read_chunk(&self, hash: &str) -> Result<Vec<u8>, String> {
+        let compressed = fs::read(self.chunk_path(hash))
+            .map_err(|e| format!("Failed to read chunk {}: {}", hash, e))?;
+        let data = zstd::decode_all(compressed.as_slice())
+            .map_err(|e| format!("Failed to decompress chunk {}: {}", hash, e))?;
+        if blake3::hash(&data).to_hex().as_str() != hash {
+            return Err(format!("Chunk {} does not match its hash", hash));
+        }
+        Ok(data)
+    }
+
+    fn chunk_path(&self, hash: &str) -> PathBuf {
+        self.root.join("chunks").join(hash.get(..2).unwrap_or("00")).join(hash)
+    }
+
+    fn manifest_path(&self, id: &str) -> Result<PathBuf, String> {
+        if id.is_empty() || !id.chars().all(|c| c.is_ascii_alphanumeric() || c == '-') {
+            return Err(format!("Invalid backup id: {}", id));
+        }
+        Ok(self.root.join("backups").join(format!("{}.json", id)))
+    }
+
+    fn manifests(&self) -> Result<Vec<BackupManifest>, String> {
+        let mut manifests = Vec::new();
+        for path in read_dir_sorted(&self.root.join("backups"))? {
+            if path.extension().is_some_and(|e| e == "json") {
+                let json = fs::read_to_string(&path)
+                    .map_err(|e| format!("Failed to read backup manifest: {}", e))?;
+                manifests.push(
+                    serde_json::from_str(&json)
+                        .map_err(|e| format!("Failed to parse backup manifest {}: {}", path.display(), e))?,
+                );
+            }
+        }
+        Ok(manifests)
+    }
+}
+
+/// Cuts a file into content-defined chunks, passing each to `on_chunk`
+///
+/// # Returns
+/// * `Ok(u64)` - Size of the file
+fn chunk_file(path: &Path, on_chunk: &mut dyn FnMut(&[u8]) -> Result<(), String>) -> Result<u64, String> {
+    let mut file = fs::File::open(path)
+        .map_err(|e| format!("Failed to read file: {}", e))?;
+    let mut buffer = Vec::with_capacity(MAX_CHUNK);
+    let mut size = 0;
+    let mut eof = false;
+    loop {
+        while !eof && buffer.len() < MAX_CHUNK {
+            let wanted = (MAX_CHUNK - buffer.len()) as u64;
+            let read = (&mut file)
+                .take(wanted)
+                .read_to_end(&mut buffer)
+                .map_err(|e| format!("Failed to read file: {}", e))?;
+            eof = read == 0;
+        }
+        if buffer.is_empty() {
+            return Ok(size);
+        }
+        let cut = cut_point(&buffer);
+        on_chunk(&buffer[..cut])?;
+        size += cut as u64;
+        buffer.drain(..cut);
+    }
+}
+
+/// Length of the first chunk of `data`
+fn cut_point(data: &[u8]) -> usize {
+    if data.len() <= MIN_CHUNK {
+        return data.len();
+    }
+    let limit = data.len().min(MAX_CHUNK);
+    let mut hash: u64 = 0;
+    for (i, &byte) in data.iter().enumerate().take(limit).skip(MIN_CHUNK) {
+        hash = (hash << 1).wrapping_add(GEAR[byte as usize]);
+        if hash & BOUNDARY_MASK == 0 {
+            return i + 1;
+        }
+    }
+    limit
+}
+
+/// Files below `source` (a canonical workspace root) to back up, with
+/// their relative paths, sorted
+///
+/// Symbolic links are followed according to the workspace symlink policy.
+fn collect_files(source: &Path) -> Result<Vec<(PathBuf, String)>, String> {
+    let policy = WorkspaceSettings::load_symlink_policy(source);
+    let mut visited = HashSet::from([source.to_path_buf()]);
+    let mut files = Vec::new();
+    let mut stack = vec![source.to_path_buf()];
+    while let Some(dir) = stack.pop() {
+        for path in read_dir_sorted(&dir)? {
+            let name = path.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+            if name.starts_with('.') && name != SETTINGS_DIR && name != MINDMAP_DIR {
+                continue;
+            }
+            let Some(metadata) = walk_metadata(&path, source, policy, &mut visited) else {
+                continue;
+            };
+            if metadata.is_dir() {
+                stack.push(path);
+            } else if metadata.is_file() {
+                let relative = path
+                    .strip_prefix(source)
+                    .map_err(|e| format!("Failed to build backup path: {}", e))?
+                    .components()
+                    .map(|c| c.as_os_str().to_string_lossy().to_string())
+                    .collect::<Vec<_>>()
+                    .join("/");
+                files.push((path, relative));
+            }
+        }
+    }
+    files.sort_by(|a, b| a.1.cmp(&b.1));
+    Ok(files)
+}
+
+/// Entries of a directory, sorted (none if it does not exist)
+fn read_dir_sorted(dir: &Path) -> Result<Vec<PathBuf>, String> {
+    if !dir.is_dir() {
+        return Ok(Vec::new());
+    }
+    let mut entries: Vec<PathBuf> = fs::read_dir(dir)
+        .map_err(|e| format!("Failed to read directory: {}", e))?
+        .filter_map(|e| e.ok().map(|e| e.path()))
+        .collect();
+    entries.sort();
+    Ok(entries)
+}
+
+/// Writes through a temporary file, so readers never see partial content
+fn write_atomic(path: &Path, content: &[u8]) -> Result<(), String> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)
+            .map_err(|e| format!("Failed to create directory: {}", e))?;
+    }
+    let temp = path.with_extension("tmp");
+    fs::write(&temp, content)
+        .map_err(|e| format!("Failed to write {}: {}", temp.display(), e))?;
+    fs::rename(&temp, path)
+        .map_err(|e| format!("Failed to write {}: {}", path.display(), e))
+}
+
+// ============================================================================
+// TESTS
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Deterministic pseudo-random bytes
+    fn noise(len: usize, seed: u64) -> Vec<u8> {
+        let mut state = seed;
+        (0..len)
+            .map(|_| {
+                state = state.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+                (state >> 56) as u8
+            })
+            .collect()
+    }
+
+    fn chunks_of(data: &[u8]) -> Vec<Vec<u8>> {
+        let mut chunks = Vec::new();
+        let mut rest = data;
+        while !rest.is_empty() {
+            let cut = cut_point(rest);
+            chunks.push(rest[..cut].to_vec());
+            rest = &rest[cut..];
+        }
+        chunks
+    }
+
+    #[test]
+    fn test_chunk_boundaries_follow_content() {
+        let data = noise(2 * 1024 * 1024, 7);
+        let chunks = chunks_of(&data);
+        assert!(chunks.len() > 4);
+        assert!(chunks.iter().all(|c| c.len() <= MAX_CHUNK));
+        assert!(chunks[..chunks.len() - 1].iter().all(|c| c.len() >= MIN_CHUNK));
+
+        // An insertion near the start only changes the chunks around it
+        let mut edited = data[..1000].to_vec();
+        edited.extend_from_slice(b"inserted text");
+        edited.extend_from_slice(&data[1000..]);
+        let edited_chunks = chunks_of(&edited);
+        let shared = edited_chunks.iter().filter(|c| chunks.contains(c)).count();
+        assert!(shared >= chunks.len() - 2);
+    }
+
+    #[test]
+    fn test_backups_are_deduplicated() {
+        let vault = tempfile::TempDir::new().unwrap();
+        let store_dir = tempfile::TempDir::new().unwrap();
+        fs::create_dir_all(vault.path().join("notes")).unwrap();
+        fs::create_dir_all(vault.path().join(".git")).unwrap();
+        fs::write(vault.path().join("notes/big.bin"), noise(1024 * 1024, 1)).unwrap();
+        fs::write(vault.path().join("notes/plan.md"), "# Plan\n").unwrap();
+        fs::write(vault.path().join("empty.md"), "").unwrap();
+        fs::write(vault.path().join(".git/HEAD"), "ref").unwrap();
+
+        let store = BackupStore::open(store_dir.path());
+        let first = store.create(vault.path(), &mut |_, _, _| true).unwrap();
+        assert_eq!((first.files, first.new_chunks), (3, first.chunks));
+
+        fs::write(vault.path().join("notes/plan.md"), "# Plan\n\nShip it\n").unwrap();
+        let second = store.create(vault.path(), &mut |_, _, _| true).unwrap();
+        assert_eq!(second.new_chunks, 1);
+        assert!(second.stored_bytes < 100);
+
+        let list = store.list().unwrap();
+        assert_eq!(list.len(), 2);
+        assert_eq!(list[0].id, second.id);
+
+        let manifest = store.manifest(&second.id).unwrap();
+        let paths: Vec<&str> = manifest.files.iter().map(|f| f.path.as_str()).collect();
+        assert_eq!(paths, vec!["empty.md", "notes/big.bin", "notes/plan.md"]);
+
+        let restored = tempfile::TempDir::new().unwrap();
+        let dest = restored.path().join("vault");
+        assert_eq!(store.restore(&first.id, &dest).unwrap(), 3);
+        assert_eq!(fs::read_to_string(dest.join("notes/plan.md")).unwrap(), "# Plan\n");
+        assert_eq!(fs::read(dest.join("notes/big.bin")).unwrap(), noise(1024 * 1024, 1));
+        assert!(store.restore(&first.id, &dest).is_err());
+
+        // Deleting the first backup frees only the chunk it alone used
+        store.delete(&first.id).unwrap();
+        assert_eq!(store.prune().unwrap().chunks_removed, 1);
+        assert_eq!(store.prune().unwrap().chunks_removed, 0);
+        assert!(store.verify(&second.id).unwrap().ok);
+
+        let cancelled = store.create(vault.path(), &mut |done, _, _| done < 1);
+        assert_eq!(cancelled.unwrap_err(), "Backup cancelled");
+        assert_eq!(store.list().unwrap().len(), 1);
+        assert!(store.manifest("../secrets").is_err());
+    }
+
+    #[test]
+    fn test_verify_reports_damage() {
+        let vault = tempfile::TempDir::new().unwrap();
+        let store_dir = tempfile::TempDir::new().unwrap();
+        fs::write(vault.path().join("a.md"), "alpha").unwrap();
+        fs::write(vault.path().join("b.md"), "beta").unwrap();
+
+        let store = BackupStore::open(store_dir.path());
+        let backup = store.create(vault.path(), &mut |_, _, _| true).unwrap();
+        let manifest = store.manifest(&backup.id).unwrap();
+        let (a, b) = (&manifest.files[0].chunks[0], &manifest.files[1].chunks[0]);
+
+        fs::remove_file(store.chunk_path(a)).unwrap();
+        fs::write(store.chunk_path(b), zstd::encode_all(&b"tampered"[..], COMPRESSION_LEVEL).unwrap()).unwrap();
+
+        let report = store.verify(&backup.id).unwrap();
+        assert!(!report.ok);
+        assert_eq!(report.chunks, 2);
+        assert_eq!(report.missing, vec![a.clone()]);
+        assert_eq!(report.corrupt, vec![b.clone()]);
+        assert_eq!(report.damaged_files, vec!["a.md", "b.md"]);
+    }
+}
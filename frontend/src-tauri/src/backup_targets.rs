@@ -0,0 +1,345 @@
+//! Backup Targets
+//!
+//! Where and how often a workspace is backed up (see `crate::backup`).
+//! Targets are part of the workspace settings; the outcome of their runs is
+//! kept in `.mdreader/backup_status.json`, so overdue backups are noticed
+//! even while a drive is unplugged.
+//!
+//! An external volume target points at the mount point of a drive
+//! (`/Volumes/Backup`, `/media/me/Backup`, `E:\`). When the target is saved
+//! while the drive is mounted, the drive is claimed: a `.mdreader-volume`
+//! marker with a random id is written to its root, and another drive
+//! mounted at the same place is not mistaken for it. The volume watcher
+//! (see `commands::backup`) polls for the drive and runs a pending backup as
+//! soon as it shows up.
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use crate::backup;
+use crate::workspace_settings::SETTINGS_DIR;
+
+/// File name of the run status inside the settings directory
+pub const STATUS_FILE: &str = "backup_status.json";
+
+/// Marker file identifying a claimed drive
+pub const VOLUME_MARKER: &str = ".mdreader-volume";
+
+/// Folder of the store on a drive, unless configured otherwise
+pub const DEFAULT_STORE_FOLDER: &str = "MDReader Backups";
+
+/// Minutes before a failed backup is retried
+const RETRY_AFTER_MINUTES: i64 = 30;
+
+/// Where a target keeps its backup store
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum TargetLocation {
+    /// The store in the user's data directory
+    Local,
+    /// A store on a removable drive
+    ExternalVolume {
+        /// Absolute mount point of the drive
+        mount_path: String,
+        /// Folder of the store, relative to the mount point
+        #[serde(default = "default_store_folder")]
+        folder: String,
+        /// Id in the drive's marker file, once the drive is claimed
+        #[serde(default)]
+        volume_id: Option<String>,
+    },
+}
+
+/// A configured backup target
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BackupTarget {
+    /// Unique name of the target
+    pub name: String,
+    #[serde(flatten)]
+    pub location: TargetLocation,
+    /// A backup is pending once the last success is this old
+    #[serde(default = "default_interval")]
+    pub interval_hours: u32,
+    /// Notify when no backup succeeded for this many days (0 = never)
+    #[serde(default = "default_warn_after")]
+    pub warn_after_days: u32,
+}
+
+fn default_store_folder() -> String {
+    DEFAULT_STORE_FOLDER.to_string()
+}
+
+fn default_interval() -> u32 {
+    24
+}
+
+fn default_warn_after() -> u32 {
+    7
+}
+
+impl BackupTarget {
+    /// Checks the target's configuration
+    pub fn validate(&self) -> Result<(), String> {
+        if self.name.trim().is_empty() {
+            return Err("Backup target name must not be empty".to_string());
+        }
+        if let TargetLocation::ExternalVolume { mount_path, folder, .. } = &self.location {
+            if !Path::new(mount_path).is_absolute() {
+                return Err(format!("Mount path must be an absolute path: {}", mount_path));
+            }
+            let relative = Path::new(folder);
+            if folder.is_empty() || !relative.components().all(|c| matches!(c, Component::Normal(_))) {
+                return Err(format!("Invalid backup folder on the volume: {}", folder));
+            }
+        }
+        Ok(())
+    }
+
+    /// Directory of the target's backup store
+    pub fn store_dir(&self) -> Result<PathBuf, String> {
+        match &self.location {
+            TargetLocation::Local => backup::default_dir(),
+            TargetLocation::ExternalVolume { mount_path, folder, .. } => Ok(Path::new(mount_path).join(folder)),
+        }
+    }
+
+    /// Checks if the store can be reached (the claimed drive is mounted)
+    pub fn is_available(&self) -> bool {
+        match &self.location {
+            TargetLocation::Local => true,
+            TargetLocation::ExternalVolume { mount_path, volume_id, .. } => {
+                let mount = Path::new(mount_path);
+                mount.is_dir() && volume_id.as_ref().map_or(true, |id| read_marker(mount).as_ref() == Some(id))
+            }
+        }
+    }
+
+    /// Claims the drive of an external volume target if it is mounted and
+    /// not claimed yet; returns whether the target changed
+    pub fn claim_volume(&mut self) -> Result<bool, String> {
+        let TargetLocation::ExternalVolume { mount_path, volume_id, .. } = &mut self.location else {
+            return Ok(false);
+        };
+        let mount = Path::new(mount_path.as_str());
+        if volume_id.is_some() || !mount.is_dir() {
+            return Ok(false);
+        }
+        let id = match read_marker(mount) {
+            Some(id) => id,
+            None => {
+                let id = uuid::Uuid::new_v4().to_string();
+                fs::write(mount.join(VOLUME_MARKER), &id)
+                    .map_err(|e| format!("Failed to mark backup volume: {}", e))?;
+                id
+            }
+        };
+        *volume_id = Some(id);
+        Ok(true)
+    }
+
+    /// Checks if a backup should run
+    ///
+    /// It is pending once the last success is `interval_hours` old; after a
+    /// failure it is retried at most every `RETRY_AFTER_MINUTES`.
+    pub fn is_due(&self, status: &TargetStatus, now: DateTime<Utc>) -> bool {
+        let retrying = status.last_error.is_some()
+            && status.last_attempt.is_some_and(|t| now - t < Duration::minutes(RETRY_AFTER_MINUTES));
+        let stale = status
+            .last_success
+            .map_or(true, |t| now - t >= Duration::hours(i64::from(self.interval_hours)));
+        stale && !retrying
+    }
+
+    /// Days without a successful backup, if that is `warn_after_days` or more
+    pub fn overdue_days(&self, status: &TargetStatus, now: DateTime<Utc>) -> Option<i64> {
+        if self.warn_after_days == 0 {
+            return None;
+        }
+        let since = status.last_success.or(status.tracked_since)?;
+        let days = (now - since).num_days();
+        (days >= i64::from(self.warn_after_days)).then_some(days)
+    }
+}
+
+// Helper: Id in a drive's marker file
+fn read_marker(mount: &Path) -> Option<String> {
+    let id = fs::read_to_string(mount.join(VOLUME_MARKER)).ok()?;
+    Some(id.trim().to_string()).filter(|id| !id.is_empty())
+}
+
+/// Outcome of a target's backups
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct TargetStatus {
+    #[serde(default)]
+    pub last_success: Option<DateTime<Utc>>,
+    /// Id of the last successful backup
+    #[serde(default)]
+    pub last_backup_id: Option<String>,
+    #[serde(default)]
+    pub last_attempt: Option<DateTime<Utc>>,
+    /// Error of the last attempt, if it failed
+    #[serde(default)]
+    pub last_error: Option<String>,
+    /// When the target was first seen (overdue checks before any success)
+    #[serde(default)]
+    pub tracked_since: Option<DateTime<Utc>>,
+}
+
+/// Status of every target of a workspace, keyed by target name
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct BackupStatuses {
+    #[serde(default)]
+    pub targets: BTreeMap<String, TargetStatus>,
+}
+
+impl BackupStatuses {
+    /// Loads the status of a workspace's targets (empty if none is saved)
+    pub fn load(workspace_root: &Path) -> Result<Self, String> {
+        let path = status_path(workspace_root);
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let json = fs::read_to_string(&path)
+            .map_err(|e| format!("Failed to read backup status: {}", e))?;
+        serde_json::from_str(&json)
+            .map_err(|e| format!("Failed to parse backup status: {}", e))
+    }
+
+    /// Saves the status, creating `.mdreader/` if needed
+    pub fn save(&self, workspace_root: &Path) -> Result<(), String> {
+        let dir = workspace_root.join(SETTINGS_DIR);
+        fs::create_dir_all(&dir)
+            .map_err(|e| format!("Failed to create settings directory: {}", e))?;
+        let json = serde_json::to_string_pretty(self)
+            .map_err(|e| format!("Failed to serialize backup status: {}", e))?;
+        fs::write(dir.join(STATUS_FILE), json)
+            .map_err(|e| format!("Failed to save backup status: {}", e))
+    }
+
+    /// Status of a target (default for unknown ones)
+    pub fn get(&self, name: &str) -> TargetStatus {
+        self.targets.get(name).cloned().unwrap_or_default()
+    }
+
+    /// Starts tracking new targets and forgets removed ones; returns
+    /// whether anything changed
+    pub fn sync_targets(&mut self, targets: &[BackupTarget], now: DateTime<Utc>) -> bool {
+        let before = self.targets.len();
+        self.targets.retain(|name, _| targets.iter().any(|t| &t.name == name));
+        let mut changed = self.targets.len() != before;
+        for target in targets {
+            if !self.targets.contains_key(&target.name) {
+                let status = TargetStatus { tracked_since: Some(now), ..Default::default() };
+                self.targets.insert(target.name.clone(), status);
+                changed = true;
+            }
+        }
+        changed
+    }
+
+    /// Records a successful backup
+    pub fn record_success(&mut self, name: &str, backup_id: &str, now: DateTime<Utc>) {
+        let status = self.targets.entry(name.to_string()).or_default();
+        status.last_success = Some(now);
+        status.last_backup_id = Some(backup_id.to_string());
+        status.last_attempt = Some(now);
+        status.last_error = None;
+    }
+
+    /// Records a failed backup
+    pub fn record_failure(&mut self, name: &str, error: &str, now: DateTime<Utc>) {
+        let status = self.targets.entry(name.to_string()).or_default();
+        status.last_attempt = Some(now);
+        status.last_error = Some(error.to_string());
+    }
+}
+
+fn status_path(workspace_root: &Path) -> PathBuf {
+    workspace_root.join(SETTINGS_DIR).join(STATUS_FILE)
+}
+
+// ============================================================================
+// TESTS
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn volume(mount: &Path) -> BackupTarget {
+        serde_json::from_value(serde_json::json!({
+            "name": "Drive",
+            "type": "external_volume",
+            "mount_path": mount.to_string_lossy(),
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_volume_is_claimed_and_detected() {
+        let drive = TempDir::new().unwrap();
+        let mut target = volume(drive.path());
+        assert_eq!(target.interval_hours, 24);
+        assert_eq!(target.store_dir().unwrap(), drive.path().join(DEFAULT_STORE_FOLDER));
+        assert!(target.validate().is_ok());
+        assert!(target.is_available());
+
+        assert!(target.claim_volume().unwrap());
+        assert!(!target.claim_volume().unwrap());
+        assert!(target.is_available());
+
+        // Another drive at the same mount point
+        fs::write(drive.path().join(VOLUME_MARKER), "other").unwrap();
+        assert!(!target.is_available());
+
+        let unplugged = volume(&drive.path().join("missing"));
+        assert!(!unplugged.is_available());
+
+        let mut invalid = volume(drive.path());
+        invalid.location = TargetLocation::ExternalVolume {
+            mount_path: drive.path().to_string_lossy().to_string(),
+            folder: "../elsewhere".to_string(),
+            volume_id: None,
+        };
+        assert!(invalid.validate().is_err());
+    }
+
+    #[test]
+    fn test_schedule_and_overdue() {
+        let target = volume(Path::new("/Volumes/Drive"));
+        let start = Utc::now();
+        let mut statuses = BackupStatuses::default();
+        assert!(statuses.sync_targets(std::slice::from_ref(&target), start));
+        assert!(!statuses.sync_targets(std::slice::from_ref(&target), start));
+
+        let status = statuses.get("Drive");
+        assert!(target.is_due(&status, start));
+        assert_eq!(target.overdue_days(&status, start + Duration::days(3)), None);
+        assert_eq!(target.overdue_days(&status, start + Duration::days(8)), Some(8));
+
+        statuses.record_failure("Drive", "disk full", start);
+        assert!(!target.is_due(&statuses.get("Drive"), start + Duration::minutes(10)));
+        assert!(target.is_due(&statuses.get("Drive"), start + Duration::minutes(40)));
+
+        statuses.record_success("Drive", "20260101T000000000Z-abcd1234", start);
+        let status = statuses.get("Drive");
+        assert!(!target.is_due(&status, start + Duration::hours(23)));
+        assert!(target.is_due(&status, start + Duration::hours(24)));
+        assert_eq!(status.last_error, None);
+
+        assert!(statuses.sync_targets(&[], start));
+        assert!(statuses.targets.is_empty());
+    }
+
+    #[test]
+    fn test_status_roundtrip() {
+        let workspace = TempDir::new().unwrap();
+        let mut statuses = BackupStatuses::default();
+        statuses.record_success("Drive", "b1", Utc::now());
+        statuses.save(workspace.path()).unwrap();
+        assert_eq!(BackupStatuses::load(workspace.path()).unwrap(), statuses);
+    }
+}
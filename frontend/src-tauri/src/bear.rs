@@ -0,0 +1,477 @@
+//! Bear and Apple Notes Import
+//!
+//! ## Bear
+//! Bear exports notes as TextBundles: a `Note.textbundle/` folder holding
+//! `text.md` (or `text.markdown` / `text.txt`), an optional `info.json` and
+//! an `assets/` folder. A backup (`.bear2bk`) or a `.textpack` is a ZIP of
+//! such bundles. Each bundle becomes `<Title>.md`; its assets are copied to
+//! `attachments/<Title>/` and links to them are rewritten.
+//!
+//! ## Apple Notes
+//! On macOS, notes are read through the Notes scripting interface
+//! (`osascript`, JavaScript for Automation) and their HTML bodies converted
+//! with `convert::html_to_markdown`. Each Notes folder becomes a workspace
+//! folder. Attachments other than inline images are not exposed by the
+//! scripting interface and are not imported.
+
+use std::fs;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use crate::frontmatter;
+use crate::utils::sanitize_filename;
+
+/// Text file names inside a TextBundle, in order of preference
+const BUNDLE_TEXT_FILES: &[&str] = &["text.md", "text.markdown", "text.txt"];
+
+/// Result of a Bear or Apple Notes import
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct NotesImportReport {
+    pub dest_path: String,
+    /// Notes written, relative to the destination
+    pub notes: Vec<String>,
+    pub attachments: usize,
+    /// Bundles or notes that could not be converted, with the reason
+    pub skipped: Vec<String>,
+}
+
+// ============================================================================
+// BEAR / TEXTBUNDLE
+// ============================================================================
+
+/// Imports a Bear backup, `.textpack`, folder of bundles or single `.textbundle`
+///
+/// With `group_by_tag`, each note is placed in a folder named after its
+/// first tag (`#work/projects` → `work/projects/`).
+pub fn import_bear(source: &Path, dest: &Path, group_by_tag: bool) -> Result<NotesImportReport, String> {
+    let mut report = NotesImportReport {
+        dest_path: dest.to_string_lossy().to_string(),
+        ..Default::default()
+    };
+
+    // Archives are unpacked to a temporary folder first
+    let staging = if source.is_file() {
+        Some(extract_archive(source)?)
+    } else {
+        None
+    };
+    let root = staging.as_deref().unwrap_or(source);
+
+    let result = (|| {
+        let bundles = find_bundles(root)?;
+        if bundles.is_empty() {
+            return Err(format!("No TextBundles found in {}", source.display()));
+        }
+
+        fs::create_dir_all(dest).map_err(|e| format!("Failed to create directory: {}", e))?;
+        for bundle in bundles {
+            if let Err(e) = import_bundle(&bundle, dest, group_by_tag, &mut report) {
+                log::warn!("⚠️ Skipping bundle {}: {}", bundle.display(), e);
+                report.skipped.push(format!("{}: {}", bundle_name(&bundle), e));
+            }
+        }
+        Ok(())
+    })();
+
+    if let Some(staging) = staging {
+        if let Err(e) = fs::remove_dir_all(&staging) {
+            log::warn!("⚠️ Failed to remove staging folder {}: {}", staging.display(), e);
+        }
+    }
+
+    result.map(|_| report)
+}
+
+/// Unpacks a ZIP archive to a fresh temporary folder
+fn extract_archive(archive: &Path) -> Result<PathBuf, String> {
+    let file = fs::File::open(archive)
+        .map_err(|e| format!("Failed to open archive: {}", e))?;
+    let mut zip = zip::ZipArchive::new(file)
+        .map_err(|e| format!("Failed to read archive: {}", e))?;
+
+    let staging = std::env::temp_dir().join(format!(
+        "mdreader-bear-{}-{}",
+        std::process::id(),
+        chrono::Utc::now().timestamp_millis()
+    ));
+    fs::create_dir_all(&staging).map_err(|e| format!("Failed to create staging folder: {}", e))?;
+
+    for i in 0..zip.len() {
+        let mut entry = zip.by_index(i)
+            .map_err(|e| format!("Failed to read archive entry: {}", e))?;
+
+        // enclosed_name rejects absolute paths and `..` (zip slip)
+        let Some(relative) = entry.enclosed_name() else {
+            log::warn!("⚠️ Skipping unsafe archive entry: {}", entry.name());
+            continue;
+        };
+        let target = staging.join(relative);
+
+        if entry.is_dir() {
+            fs::create_dir_all(&target).map_err(|e| format!("Failed to extract archive: {}", e))?;
+            continue;
+        }
+        if let Some(parent) = target.parent() {
+            fs::create_dir_all(parent).map_err(|e| format!("Failed to extract archive: {}", e))?;
+        }
+
+        let mut bytes = Vec::new();
+        entry.read_to_end(&mut bytes)
+            .map_err(|e| format!("Failed to extract archive: {}", e))?;
+        fs::write(&target, bytes).map_err(|e| format!("Failed to extract archive: {}", e))?;
+    }
+
+    Ok(staging)
+}
+
+/// Finds `.textbundle` folders (the root itself may be one)
+fn find_bundles(root: &Path) -> Result<Vec<PathBuf>, String> {
+    if is_bundle(root) {
+        return Ok(vec![root.to_path_buf()]);
+    }
+
+    let mut bundles = Vec::new();
+    let mut stack = vec![root.to_path_buf()];
+    while let Some(dir) = stack.pop() {
+        let entries = fs::read_dir(&dir).map_err(|e| format!("Failed to read directory: {}", e))?;
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if !path.is_dir() {
+                continue;
+            }
+            if is_bundle(&path) {
+                bundles.push(path);
+            } else {
+                stack.push(path);
+            }
+        }
+    }
+
+    bundles.sort();
+    Ok(bundles)
+}
+
+fn is_bundle(path: &Path) -> bool {
+    let has_text = BUNDLE_TEXT_FILES.iter().any(|name| path.join(name).is_file());
+    path.extension().is_some_and(|e| e.eq_ignore_ascii_case("textbundle"))
+        || (has_text && path.join("info.json").is_file())
+}
+
+fn bundle_name(bundle: &Path) -> String {
+    bundle.file_stem().map(|s| s.to_string_lossy().to_string()).unwrap_or_default()
+}
+
+fn import_bundle(
+    bundle: &Path,
+    dest: &Path,
+    group_by_tag: bool,
+    report: &mut NotesImportReport,
+) -> Result<(), String> {
+    let text_path = BUNDLE_TEXT_FILES
+        .iter()
+        .map(|name| bundle.join(name))
+        .find(|p| p.is_file())
+        .ok_or("No text file in bundle")?;
+    let text = fs::read_to_string(&text_path).map_err(|e| format!("Failed to read note: {}", e))?;
+
+    let info: Value = fs::read_to_string(bundle.join("info.json"))
+        .ok()
+        .and_then(|json| serde_json::from_str(&json).ok())
+        .unwrap_or(Value::Null);
+
+    let body = convert_bear_tags(&text);
+    let title = body
+        .lines()
+        .find(|l| !l.trim().is_empty())
+        .map(|l| l.trim_start_matches('#').trim().to_string())
+        .filter(|t| !t.is_empty())
+        .unwrap_or_else(|| bundle_name(bundle));
+    let tags = crate::notes::inline_tags(&body);
+
+    let folder = match (group_by_tag, tags.first()) {
+        (true, Some(tag)) => tag.split('/').map(sanitize_filename).collect::<Vec<_>>().join("/"),
+        _ => String::new(),
+    };
+    let note_dir = if folder.is_empty() { dest.to_path_buf() } else { dest.join(&folder) };
+    fs::create_dir_all(&note_dir).map_err(|e| format!("Failed to create directory: {}", e))?;
+
+    let stem = unique_stem(&note_dir, &sanitize_filename(&title));
+    let note_path = note_dir.join(format!("{}.md", stem));
+
+    // Assets → attachments/<stem>/, relative to the note
+    let mut body = body;
+    let assets = bundle.join("assets");
+    if assets.is_dir() {
+        let attachments = note_dir.join("attachments").join(&stem);
+        fs::create_dir_all(&attachments).map_err(|e| format!("Failed to create attachments folder: {}", e))?;
+        for entry in fs::read_dir(&assets).map_err(|e| format!("Failed to read assets: {}", e))?.flatten() {
+            if entry.path().is_file() {
+                fs::copy(entry.path(), attachments.join(entry.file_name()))
+                    .map_err(|e| format!("Failed to copy attachment: {}", e))?;
+                report.attachments += 1;
+            }
+        }
+        let link_prefix = format!("attachments/{}/", stem.replace(' ', "%20"));
+        body = body.replace("](assets/", &format!("]({}", link_prefix));
+    }
+
+    let mut fields = Map::new();
+    if !tags.is_empty() {
+        fields.insert("tags".to_string(), Value::from(tags));
+    }
+    let bear_info = info.get("net.shinyfrog.bear").unwrap_or(&info);
+    for (source_key, field) in [("creationDate", "created"), ("modificationDate", "modified")] {
+        if let Some(date) = bear_info.get(source_key).and_then(Value::as_str) {
+            fields.insert(field.to_string(), Value::from(date));
+        }
+    }
+
+    fs::write(&note_path, format!("{}{}", frontmatter::render(&fields), body))
+        .map_err(|e| format!("Failed to write note: {}", e))?;
+
+    let relative = if folder.is_empty() { format!("{}.md", stem) } else { format!("{}/{}.md", folder, stem) };
+    report.notes.push(relative);
+    Ok(())
+}
+
+/// Bear multi-word tags `#my tag#` → `#my-tag`
+fn convert_bear_tags(text: &str) -> String {
+    let mut out = Vec::new();
+    let mut in_fence = false;
+
+    for line in text.lines() {
+        if line.trim_start().starts_with("```") {
+            in_fence = !in_fence;
+        }
+        if in_fence || !line.contains('#') {
+            out.push(line.to_string());
+            continue;
+        }
+
+        let mut converted = String::new();
+        let mut rest = line;
+        while let Some(start) = rest.find('#') {
+            let preceded = start == 0 || rest[..start].ends_with(char::is_whitespace);
+            let after = &rest[start + 1..];
+            let closing = after.find('#').filter(|&end| {
+                let inner = &after[..end];
+                preceded
+                    && inner.contains(' ')
+                    && !inner.starts_with(' ')
+                    && !inner.ends_with(' ')
+                    && inner.chars().all(|c| c.is_alphanumeric() || matches!(c, ' ' | '-' | '_' | '/'))
+            });
+
+            converted.push_str(&rest[..start]);
+            match closing {
+                Some(end) => {
+                    converted.push('#');
+                    converted.push_str(&after[..end].replace(' ', "-"));
+                    rest = &after[end + 1..];
+                }
+                None => {
+                    converted.push('#');
+                    rest = after;
+                }
+            }
+        }
+        converted.push_str(rest);
+        out.push(converted);
+    }
+
+    let mut result = out.join("\n");
+    if text.ends_with('\n') {
+        result.push('\n');
+    }
+    result
+}
+
+/// Picks a file stem that does not collide with an existing note
+pub(crate) fn unique_stem(dir: &Path, stem: &str) -> String {
+    let stem = if stem.is_empty() { "Untitled" } else { stem };
+    if !dir.join(format!("{}.md", stem)).exists() {
+        return stem.to_string();
+    }
+    (2..)
+        .map(|n| format!("{} {}", stem, n))
+        .find(|candidate| !dir.join(format!("{}.md", candidate)).exists())
+        .unwrap_or_else(|| stem.to_string())
+}
+
+// ============================================================================
+// APPLE NOTES
+// ============================================================================
+
+/// A note as returned by the Notes scripting bridge
+#[derive(Debug, Clone, Deserialize)]
+#[cfg_attr(not(target_os = "macos"), allow(dead_code))]
+struct AppleNote {
+    folder: String,
+    name: String,
+    body: String,
+    created: Option<String>,
+    modified: Option<String>,
+}
+
+/// JXA script listing every note with its folder and HTML body
+#[cfg(target_os = "macos")]
+const APPLE_NOTES_SCRIPT: &str = r#"
+var notes = [];
+Application('Notes').folders().forEach(function (folder) {
+  folder.notes().forEach(function (note) {
+    notes.push({
+      folder: folder.name(),
+      name: note.name(),
+      body: note.body(),
+      created: note.creationDate().toISOString(),
+      modified: note.modificationDate().toISOString()
+    });
+  });
+});
+JSON.stringify(notes);
+"#;
+
+/// Exports all Apple Notes into `dest`, one folder per Notes folder
+///
+/// macOS asks the user once to allow MDReader to control Notes.
+#[cfg(target_os = "macos")]
+pub fn import_apple_notes(dest: &Path) -> Result<NotesImportReport, String> {
+    let output = std::process::Command::new("osascript")
+        .args(["-l", "JavaScript", "-e", APPLE_NOTES_SCRIPT])
+        .output()
+        .map_err(|e| format!("Failed to run osascript: {}", e))?;
+
+    if !output.status.success() {
+        return Err(format!(
+            "Apple Notes export failed: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+
+    let notes: Vec<AppleNote> = serde_json::from_slice(&output.stdout)
+        .map_err(|e| format!("Failed to parse Apple Notes export: {}", e))?;
+    write_apple_notes(&notes, dest)
+}
+
+/// Apple Notes can only be reached through macOS scripting
+#[cfg(not(target_os = "macos"))]
+pub fn import_apple_notes(_dest: &Path) -> Result<NotesImportReport, String> {
+    Err("Apple Notes import is only available on macOS".to_string())
+}
+
+#[cfg_attr(not(target_os = "macos"), allow(dead_code))]
+fn write_apple_notes(notes: &[AppleNote], dest: &Path) -> Result<NotesImportReport, String> {
+    let mut report = NotesImportReport {
+        dest_path: dest.to_string_lossy().to_string(),
+        ..Default::default()
+    };
+
+    for note in notes {
+        let folder = sanitize_filename(&note.folder);
+        let dir = dest.join(&folder);
+        fs::create_dir_all(&dir).map_err(|e| format!("Failed to create directory: {}", e))?;
+
+        let stem = unique_stem(&dir, &sanitize_filename(&note.name));
+        let mut fields = Map::new();
+        if let Some(created) = &note.created {
+            fields.insert("created".to_string(), Value::from(created.as_str()));
+        }
+        if let Some(modified) = &note.modified {
+            fields.insert("modified".to_string(), Value::from(modified.as_str()));
+        }
+
+        let body = crate::convert::html_to_markdown(&note.body);
+        match fs::write(dir.join(format!("{}.md", stem)), format!("{}{}", frontmatter::render(&fields), body)) {
+            Ok(()) => report.notes.push(format!("{}/{}.md", folder, stem)),
+            Err(e) => report.skipped.push(format!("{}: {}", note.name, e)),
+        }
+    }
+
+    Ok(report)
+}
+
+// ============================================================================
+// TESTS
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn create_bundle(root: &Path, name: &str, text: &str, asset: Option<&str>) {
+        let bundle = root.join(format!("{}.textbundle", name));
+        fs::create_dir_all(&bundle).unwrap();
+        fs::write(bundle.join("text.md"), text).unwrap();
+        fs::write(
+            bundle.join("info.json"),
+            r#"{"net.shinyfrog.bear": {"creationDate": "2024-01-02T10:00:00Z"}}"#,
+        ).unwrap();
+        if let Some(asset) = asset {
+            fs::create_dir_all(bundle.join("assets")).unwrap();
+            fs::write(bundle.join("assets").join(asset), b"img").unwrap();
+        }
+    }
+
+    #[test]
+    fn test_import_bear_bundles() {
+        let source = TempDir::new().unwrap();
+        let dest = TempDir::new().unwrap();
+        create_bundle(source.path(), "a", "# Trip plan\n#travel/2024 #road trip#\n![map](assets/map.png)\n", Some("map.png"));
+        create_bundle(source.path(), "b", "# Trip plan\nSecond note with the same title\n", None);
+
+        let report = import_bear(source.path(), dest.path(), false).unwrap();
+        assert_eq!(report.notes, vec!["Trip plan.md", "Trip plan 2.md"]);
+        assert_eq!(report.attachments, 1);
+
+        let note = fs::read_to_string(dest.path().join("Trip plan.md")).unwrap();
+        let (fields, body) = frontmatter::split(&note);
+        let fields = fields.unwrap();
+        assert_eq!(fields["tags"], serde_json::json!(["travel/2024", "road-trip"]));
+        assert_eq!(fields["created"], serde_json::json!("2024-01-02T10:00:00Z"));
+        assert!(body.contains("![map](attachments/Trip%20plan/map.png)"));
+        assert!(dest.path().join("attachments/Trip plan/map.png").exists());
+    }
+
+    #[test]
+    fn test_import_bear_group_by_tag() {
+        let source = TempDir::new().unwrap();
+        let dest = TempDir::new().unwrap();
+        create_bundle(source.path(), "a", "# Standup\n#work/meetings\n", None);
+
+        let report = import_bear(source.path(), dest.path(), true).unwrap();
+        assert_eq!(report.notes, vec!["work/meetings/Standup.md"]);
+    }
+
+    #[test]
+    fn test_import_bear_without_bundles() {
+        let source = TempDir::new().unwrap();
+        let dest = TempDir::new().unwrap();
+        assert!(import_bear(source.path(), dest.path(), false).is_err());
+    }
+
+    #[test]
+    fn test_convert_bear_tags() {
+        assert_eq!(convert_bear_tags("#multi word tag# and #simple\n"), "#multi-word-tag and #simple\n");
+        assert_eq!(convert_bear_tags("# Heading # not a tag"), "# Heading # not a tag");
+    }
+
+    #[test]
+    fn test_write_apple_notes() {
+        let dest = TempDir::new().unwrap();
+        let notes = vec![AppleNote {
+            folder: "Recipes".to_string(),
+            name: "Soup".to_string(),
+            body: "<div><h1>Soup</h1></div><div>Add <b>salt</b></div>".to_string(),
+            created: Some("2024-03-01T08:00:00.000Z".to_string()),
+            modified: None,
+        }];
+
+        let report = write_apple_notes(&notes, dest.path()).unwrap();
+        assert_eq!(report.notes, vec!["Recipes/Soup.md"]);
+
+        let content = fs::read_to_string(dest.path().join("Recipes/Soup.md")).unwrap();
+        assert!(content.ends_with("# Soup\n\nAdd **salt**\n"));
+    }
+}
@@ -0,0 +1,162 @@
+//! Device Owner Verification
+//!
+//! Asks the operating system to verify the device owner, with biometrics
+//! where available and the device passcode/PIN as fallback:
+//!
+//! | Platform | API |
+//! |----------|-----|
+//! | macOS | LocalAuthentication (Touch ID, or the login password) |
+//! | Windows | Windows Hello (`UserConsentVerifier`: face, fingerprint or PIN) |
+//! | Android, iOS | `tauri-plugin-biometric` (BiometricPrompt / Face ID, Touch ID, passcode) |
+//!
+//! Linux has no such API, so verification is reported as unavailable. The
+//! prompt is shown and answered by the system; the backend only learns
+//! whether it succeeded. Calls block until the user answers, so commands
+//! run them on the blocking pool.
+
+use serde::Serialize;
+use tauri::{AppHandle, Runtime};
+
+/// Whether the owner can be verified, and how
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct OwnerCheck {
+    pub available: bool,
+    /// `local_authentication`, `windows_hello`, `biometric_prompt` or `none`
+    pub method: &'static str,
+    /// Why it is unavailable (no enrolled biometrics and no passcode, ...)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+}
+
+impl OwnerCheck {
+    fn unavailable(method: &'static str, reason: impl Into<String>) -> Self {
+        Self { available: false, method, reason: Some(reason.into()) }
+    }
+}
+
+/// Checks if the owner can be verified on this device
+pub fn availability<R: Runtime>(app: &AppHandle<R>) -> OwnerCheck {
+    platform::availability(app)
+}
+
+/// Shows the system prompt with `reason` and waits for the answer
+///
+/// # Returns
+/// * `Ok(true)` - If the owner was verified
+/// * `Ok(false)` - If the user cancelled or failed the check
+/// * `Err(String)` - If verification is not available
+pub fn verify<R: Runtime>(app: &AppHandle<R>, reason: &str) -> Result<bool, String> {
+    let check = availability(app);
+    if !check.available {
+        return Err(format!(
+            "Owner verification unavailable: {}",
+            check.reason.unwrap_or_else(|| "not supported on this platform".to_string())
+        ));
+    }
+    platform::verify(app, reason)
+}
+
+#[cfg(target_os = "macos")]
+mod platform {
+    use super::*;
+    use block2::RcBlock;
+    use objc2::runtime::Bool;
+    use objc2_foundation::{NSError, NSString};
+    use objc2_local_authentication::{LAContext, LAPolicy};
+
+    // Biometrics with the login password as fallback
+    const POLICY: LAPolicy = LAPolicy::DeviceOwnerAuthentication;
+
+    pub fn availability<R: Runtime>(_app: &AppHandle<R>) -> OwnerCheck {
+        let context = unsafe { LAContext::new() };
+        match unsafe { context.canEvaluatePolicy_error(POLICY) } {
+            Ok(()) => OwnerCheck { available: true, method: "local_authentication", reason: None },
+            Err(e) => OwnerCheck::unavailable("local_authentication", e.localizedDescription().to_string()),
+        }
+    }
+
+    pub fn verify<R: Runtime>(_app: &AppHandle<R>, reason: &str) -> Result<bool, String> {
+        let (tx, rx) = std::sync::mpsc::channel();
+        let reply = RcBlock::new(move |success: Bool, _error: *mut NSError| {
+            let _ = tx.send(success.as_bool());
+        });
+        // The context must outlive the prompt
+        let context = unsafe { LAContext::new() };
+        unsafe { context.evaluatePolicy_localizedReason_reply(POLICY, &NSString::from_str(reason), &reply) };
+        rx.recv().map_err(|e| format!("Failed to verify owner: {}", e))
+    }
+}
+
+#[cfg(windows)]
+mod platform {
+    use super::*;
+    use windows::core::HSTRING;
+    use windows::Security::Credentials::UI::{
+        UserConsentVerificationResult, UserConsentVerifier, UserConsentVerifierAvailability,
+    };
+
+    pub fn availability<R: Runtime>(_app: &AppHandle<R>) -> OwnerCheck {
+        match UserConsentVerifier::CheckAvailabilityAsync().and_then(|op| op.get()) {
+            Ok(UserConsentVerifierAvailability::Available) => {
+                OwnerCheck { available: true, method: "windows_hello", reason: None }
+            }
+            Ok(UserConsentVerifierAvailability::NotConfiguredForUser) => {
+                OwnerCheck::unavailable("windows_hello", "Windows Hello is not set up for this user")
+            }
+            Ok(UserConsentVerifierAvailability::DisabledByPolicy) => {
+                OwnerCheck::unavailable("windows_hello", "Windows Hello is disabled by policy")
+            }
+            Ok(_) => OwnerCheck::unavailable("windows_hello", "No Windows Hello device found"),
+            Err(e) => OwnerCheck::unavailable("windows_hello", e.message().to_string()),
+        }
+    }
+
+    pub fn verify<R: Runtime>(_app: &AppHandle<R>, reason: &str) -> Result<bool, String> {
+        let result = UserConsentVerifier::RequestVerificationAsync(&HSTRING::from(reason))
+            .and_then(|op| op.get())
+            .map_err(|e| format!("Failed to verify owner: {}", e))?;
+        Ok(result == UserConsentVerificationResult::Verified)
+    }
+}
+
+#[cfg(mobile)]
+mod platform {
+    use super::*;
+    use tauri_plugin_biometric::{AuthOptions, BiometricExt};
+
+    pub fn availability<R: Runtime>(app: &AppHandle<R>) -> OwnerCheck {
+        match app.biometric().status() {
+            Ok(status) if status.is_available => {
+                OwnerCheck { available: true, method: "biometric_prompt", reason: None }
+            }
+            Ok(status) => OwnerCheck::unavailable(
+                "biometric_prompt",
+                status.error.unwrap_or_else(|| "No biometrics or passcode set up".to_string()),
+            ),
+            Err(e) => OwnerCheck::unavailable("biometric_prompt", e.to_string()),
+        }
+    }
+
+    pub fn verify<R: Runtime>(app: &AppHandle<R>, reason: &str) -> Result<bool, String> {
+        let options = AuthOptions {
+            // Fall back to the passcode/screen lock
+            allow_device_credential: true,
+            ..Default::default()
+        };
+        // Cancelling or failing the prompt is reported as an error
+        Ok(app.biometric().authenticate(reason.to_string(), options).is_ok())
+    }
+}
+
+#[cfg(not(any(target_os = "macos", windows, mobile)))]
+mod platform {
+    use super::*;
+
+    pub fn availability<R: Runtime>(_app: &AppHandle<R>) -> OwnerCheck {
+        OwnerCheck::unavailable("none", "Owner verification is not supported on this platform")
+    }
+
+    pub fn verify<R: Runtime>(_app: &AppHandle<R>, _reason: &str) -> Result<bool, String> {
+        Err("Owner verification is not supported on this platform".to_string())
+    }
+}
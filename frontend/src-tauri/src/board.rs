@@ -0,0 +1,105 @@
+//! Note Status Board
+//!
+//! Groups notes by their frontmatter `status` field into board columns, one
+//! per configured status value (see `WorkspaceSettings::status_values`), in
+//! the configured order. Notes with a status that is not configured get a
+//! column of their own after the configured ones, so nothing disappears
+//! from the board when the values change. Notes without a status are left
+//! out.
+
+use std::collections::BTreeMap;
+use serde::Serialize;
+use crate::metadata_cache::MetadataCache;
+
+/// A note on the board
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct BoardNote {
+    pub path: String,
+    pub title: String,
+    /// Modification time (ms since the epoch)
+    pub modified_ms: i64,
+}
+
+/// Notes with one status, most recently modified first
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct StatusColumn {
+    pub status: String,
+    /// False for a status found in notes but not configured
+    pub configured: bool,
+    pub notes: Vec<BoardNote>,
+}
+
+/// Builds the board columns from the metadata cache
+///
+/// Statuses are matched case-insensitively; a column is named like the
+/// configured value.
+pub fn build_board(cache: &MetadataCache, statuses: &[String]) -> Vec<StatusColumn> {
+    let mut columns: Vec<StatusColumn> = statuses
+        .iter()
+        .map(|status| StatusColumn { status: status.clone(), configured: true, notes: Vec::new() })
+        .collect();
+    let mut unknown: BTreeMap<String, StatusColumn> = BTreeMap::new();
+
+    for (path, note) in &cache.notes {
+        let Some(status) = &note.status else {
+            continue;
+        };
+        let entry = BoardNote { path: path.clone(), title: note.title.clone(), modified_ms: note.modified_ms };
+        match columns.iter_mut().find(|c| c.status.eq_ignore_ascii_case(status)) {
+            Some(column) => column.notes.push(entry),
+            None => unknown
+                .entry(status.to_lowercase())
+                .or_insert_with(|| StatusColumn { status: status.clone(), configured: false, notes: Vec::new() })
+                .notes
+                .push(entry),
+        }
+    }
+
+    columns.extend(unknown.into_values());
+    for column in &mut columns {
+        column.notes.sort_by(|a, b| b.modified_ms.cmp(&a.modified_ms).then_with(|| a.path.cmp(&b.path)));
+    }
+    columns
+}
+
+/// Finds the configured value matching `status` (case-insensitively)
+pub fn configured_status<'a>(statuses: &'a [String], status: &str) -> Option<&'a String> {
+    statuses.iter().find(|s| s.eq_ignore_ascii_case(status.trim()))
+}
+
+// ============================================================================
+// TESTS
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::metadata_cache::CachedNote;
+
+    #[test]
+    fn test_columns_follow_configured_order() {
+        let mut cache = MetadataCache::default();
+        let note = |status: Option<&str>, modified_ms| CachedNote {
+            status: status.map(String::from),
+            modified_ms,
+            ..CachedNote::default()
+        };
+        cache.notes.insert("a.md".into(), note(Some("done"), 1));
+        cache.notes.insert("b.md".into(), note(Some("Draft"), 2));
+        cache.notes.insert("c.md".into(), note(Some("draft"), 3));
+        cache.notes.insert("d.md".into(), note(Some("blocked"), 4));
+        cache.notes.insert("e.md".into(), note(None, 5));
+
+        let statuses: Vec<String> = ["draft", "in-progress", "done"].iter().map(|s| s.to_string()).collect();
+        let board = build_board(&cache, &statuses);
+
+        let names: Vec<(&str, bool)> = board.iter().map(|c| (c.status.as_str(), c.configured)).collect();
+        assert_eq!(names, vec![("draft", true), ("in-progress", true), ("done", true), ("blocked", false)]);
+        let drafts: Vec<&str> = board[0].notes.iter().map(|n| n.path.as_str()).collect();
+        assert_eq!(drafts, vec!["c.md", "b.md"]);
+        assert_eq!(board.iter().map(|c| c.notes.len()).sum::<usize>(), 4);
+
+        assert_eq!(configured_status(&statuses, " Done "), Some(&statuses[2]));
+        assert_eq!(configured_status(&statuses, "blocked"), None);
+    }
+}
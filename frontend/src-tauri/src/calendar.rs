@@ -0,0 +1,412 @@
+//! Calendar Events
+//!
+//! Dated events written in notes, so notes double as a lightweight planner
+//! that any calendar app can subscribe to:
+//!
+//! ```text
+//! ---
+//! event:
+//!   start: 2024-06-01 14:00
+//!   end: 15:30
+//!   title: Design review
+//!   location: Room 4
+//! ---
+//!
+//! Follow-up @event(2024-06-08 10:00-10:30, Check-in with Ann)
+//! ```
+//!
+//! `event:` may also be a single date or a list of dates, titled after the
+//! note. Annotations take a date, an optional time or time range and an
+//! optional title; annotations in code are ignored. Times are local
+//! ("floating" in iCalendar terms). Events without a time last all day;
+//! timed events without an end last an hour.
+//!
+//! Events are extracted when notes are indexed (see `crate::metadata_cache`)
+//! and exported as iCalendar (RFC 5545).
+
+use chrono::{DateTime, Duration, NaiveDate, NaiveDateTime, NaiveTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use crate::frontmatter;
+use crate::metadata_cache::MetadataCache;
+use crate::timeline::TimelineRange;
+
+/// Days listed by `upcoming` when the range has no end
+pub const UPCOMING_DAYS: i64 = 30;
+
+/// Inline event annotation
+const ANNOTATION: &str = "@event(";
+
+/// Keys of an `event:` block
+const EVENT_KEYS: &[&str] = &["start", "date", "end", "title", "location"];
+
+/// Product identifier of exported calendars
+const PRODID: &str = "-//MDReader//Notes Calendar//EN";
+
+/// Longest line of an iCalendar file, in bytes (longer lines are folded)
+const ICS_LINE_BYTES: usize = 75;
+
+/// An event written in a note
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct NoteEvent {
+    /// Title (`None`: the note's title)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    pub start: NaiveDateTime,
+    /// End (exclusive; midnight after the last day of all-day events)
+    pub end: NaiveDateTime,
+    pub all_day: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub location: Option<String>,
+    /// 1-based line of an annotation (0: frontmatter)
+    #[serde(default)]
+    pub line: usize,
+}
+
+/// An event with the note it comes from
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct CalendarEvent {
+    /// Note path relative to the workspace root
+    pub path: String,
+    pub note_title: String,
+    pub title: String,
+    pub start: NaiveDateTime,
+    pub end: NaiveDateTime,
+    pub all_day: bool,
+    pub location: Option<String>,
+    pub line: usize,
+}
+
+impl CalendarEvent {
+    fn new(path: &str, note_title: &str, event: &NoteEvent) -> Self {
+        Self {
+            path: path.to_string(),
+            note_title: note_title.to_string(),
+            title: event.title.clone().unwrap_or_else(|| note_title.to_string()),
+            start: event.start,
+            end: event.end,
+            all_day: event.all_day,
+            location: event.location.clone(),
+            line: event.line,
+        }
+    }
+
+    /// Identifier that stays the same across exports, so calendar apps
+    /// update the event instead of adding it again
+    fn uid(&self) -> String {
+        let key = format!("{}\n{}\n{}", self.path, self.start, self.title);
+        format!("{}@mdreader", &blake3::hash(key.as_bytes()).to_hex()[..24])
+    }
+}
+
+/// Extracts the events of a note: the frontmatter `event` (or `events`)
+/// field, then `@event(...)` annotations in the body
+pub fn note_events(content: &str) -> Vec<NoteEvent> {
+    let (fields, body) = frontmatter::split(content);
+    let mut events = match fields.as_ref().and_then(|f| f.get("event").or_else(|| f.get("events"))) {
+        Some(Value::String(text)) => frontmatter_event(text).into_iter().collect(),
+        Some(Value::Array(items)) => items.iter().filter_map(Value::as_str).filter_map(frontmatter_event).collect(),
+        _ => Vec::new(),
+    };
+    let first_line = content[..content.len() - body.len()].lines().count() + 1;
+    events.extend(annotation_events(body, first_line));
+    events
+}
+
+/// Events of the cached notes overlapping `from..=to` (either open),
+/// by start time
+pub fn collect(cache: &MetadataCache, from: Option<NaiveDate>, to: Option<NaiveDate>) -> Vec<CalendarEvent> {
+    let mut events: Vec<CalendarEvent> = cache
+        .notes
+        .iter()
+        .flat_map(|(path, note)| note.events.iter().map(|event| CalendarEvent::new(path, &note.title, event)))
+        .filter(|event| {
+            !(from.is_some_and(|from| event.end <= from.and_time(NaiveTime::MIN))
+                || to.is_some_and(|to| event.start.date() > to))
+        })
+        .collect();
+    events.sort_by(|a, b| {
+        a.start.cmp(&b.start).then_with(|| a.path.cmp(&b.path)).then_with(|| a.line.cmp(&b.line))
+    });
+    events
+}
+
+/// Events from `range.from` (default `today`) to `range.to` (default
+/// `UPCOMING_DAYS` later), including ones still running
+pub fn upcoming(cache: &MetadataCache, range: &TimelineRange, today: NaiveDate) -> Result<Vec<CalendarEvent>, String> {
+    let (from, to) = range.bounds()?;
+    let from = from.unwrap_or(today);
+    let to = to.unwrap_or(from + Duration::days(UPCOMING_DAYS));
+    Ok(collect(cache, Some(from), Some(to)))
+}
+
+/// Writes events as an iCalendar file
+///
+/// `stamp` is the time of the export (`DTSTAMP`).
+pub fn to_ics(events: &[CalendarEvent], stamp: DateTime<Utc>) -> String {
+    let stamp = stamp.format("%Y%m%dT%H%M%SZ").to_string();
+    let mut lines = vec![
+        "BEGIN:VCALENDAR".to_string(),
+        "VERSION:2.0".to_string(),
+        format!("PRODID:{}", PRODID),
+        "CALSCALE:GREGORIAN".to_string(),
+    ];
+    for event in events {
+        lines.push("BEGIN:VEVENT".to_string());
+        lines.push(format!("UID:{}", event.uid()));
+        lines.push(format!("DTSTAMP:{}", stamp));
+        if event.all_day {
+            lines.push(format!("DTSTART;VALUE=DATE:{}", event.start.format("%Y%m%d")));
+            lines.push(format!("DTEND;VALUE=DATE:{}", event.end.format("%Y%m%d")));
+        } else {
+            lines.push(format!("DTSTART:{}", event.start.format("%Y%m%dT%H%M%S")));
+            lines.push(format!("DTEND:{}", event.end.format("%Y%m%dT%H%M%S")));
+        }
+        lines.push(format!("SUMMARY:{}", ics_escape(&event.title)));
+        if let Some(location) = &event.location {
+            lines.push(format!("LOCATION:{}", ics_escape(location)));
+        }
+        lines.push(format!("DESCRIPTION:{}", ics_escape(&format!("{} ({})", event.note_title, event.path))));
+        lines.push("END:VEVENT".to_string());
+    }
+    lines.push("END:VCALENDAR".to_string());
+
+    let mut ics = String::new();
+    for line in lines {
+        ics.push_str(&fold(&line));
+        ics.push_str("\r\n");
+    }
+    ics
+}
+
+// Helper: Parses an `event:` entry: a date and time, or `key: value` lines
+fn frontmatter_event(text: &str) -> Option<NoteEvent> {
+    let entries: Vec<(String, &str)> = text
+        .lines()
+        .filter_map(|line| {
+            let (key, value) = line.split_once(':')?;
+            let key = key.trim().to_lowercase();
+            EVENT_KEYS.contains(&key.as_str()).then(|| (key, unquote(value.trim())))
+        })
+        .collect();
+    if entries.is_empty() {
+        return when_event(text, None);
+    }
+
+    let get = |key: &str| entries.iter().find(|(k, _)| k == key).map(|(_, value)| *value);
+    let mut event = when_event(get("start").or_else(|| get("date"))?, get("title"))?;
+    if let Some(end) = get("end").and_then(|end| parse_end(end, event.start)) {
+        if end > event.start {
+            event.end = end;
+        }
+    }
+    event.location = get("location").filter(|l| !l.is_empty()).map(String::from);
+    Some(event)
+}
+
+// Helper: Finds `@event(when, title)` annotations outside code
+fn annotation_events(body: &str, first_line: usize) -> Vec<NoteEvent> {
+    let mut events = Vec::new();
+    let mut in_code = false;
+    for (i, line) in body.lines().enumerate() {
+        let trimmed = line.trim_start();
+        if trimmed.starts_with("```") || trimmed.starts_with("~~~") {
+            in_code = !in_code;
+            continue;
+        }
+        if in_code {
+            continue;
+        }
+
+        let mut search = 0;
+        while let Some(found) = line[search..].find(ANNOTATION) {
+            let at = search + found;
+            let args_start = at + ANNOTATION.len();
+            let Some(len) = line[args_start..].find(')') else {
+                break;
+            };
+            search = args_start + len + 1;
+            // An odd number of backticks before it: inside inline code
+            if line[..at].matches('`').count() % 2 == 1 {
+                continue;
+            }
+            let args = &line[args_start..args_start + len];
+            let (when, title) = match args.split_once(',') {
+                Some((when, title)) => (when, Some(title)),
+                None => (args, None),
+            };
+            if let Some(mut event) = when_event(when, title) {
+                event.line = first_line + i;
+                events.push(event);
+            }
+        }
+    }
+    events
+}
+
+// Helper: An event at `when`, with an optional title
+fn when_event(when: &str, title: Option<&str>) -> Option<NoteEvent> {
+    let (start, end, all_day) = parse_when(when)?;
+    let title = title.map(|t| unquote(t.trim())).filter(|t| !t.is_empty()).map(String::from);
+    Some(NoteEvent { title, start, end, all_day, location: None, line: 0 })
+}
+
+// Helper: Parses `YYYY-MM-DD`, optionally followed by `HH:MM` or a range
+// `HH:MM-HH:MM`; returns start, end and whether the event lasts all day
+fn parse_when(text: &str) -> Option<(NaiveDateTime, NaiveDateTime, bool)> {
+    let text = text.trim();
+    let date = NaiveDate::parse_from_str(text.get(..10)?, "%Y-%m-%d").ok()?;
+    let rest = text[10..].trim_start_matches(|c: char| c == 'T' || c.is_whitespace());
+    if rest.is_empty() {
+        let start = date.and_time(NaiveTime::MIN);
+        return Some((start, start + Duration::days(1), true));
+    }
+
+    let (from, to) = match rest.split_once(['-', '\u{2013}']) {
+        Some((from, to)) => (from.trim(), Some(to.trim())),
+        None => (rest, None),
+    };
+    let start = date.and_time(parse_time(from)?);
+    let end = match to.and_then(parse_time).map(|time| date.and_time(time)) {
+        Some(end) if end > start => end,
+        _ => start + Duration::hours(1),
+    };
+    Some((start, end, false))
+}
+
+// Helper: Parses the `end` of an `event:` block: a date (inclusive), a date
+// and time, or a time on the start's day
+fn parse_end(text: &str, start: NaiveDateTime) -> Option<NaiveDateTime> {
+    match parse_when(text) {
+        Some((_, end, true)) => Some(end),
+        Some((end, _, false)) => Some(end),
+        None => parse_time(text.trim()).map(|time| start.date().and_time(time)),
+    }
+}
+
+fn parse_time(text: &str) -> Option<NaiveTime> {
+    NaiveTime::parse_from_str(text, "%H:%M")
+        .or_else(|_| NaiveTime::parse_from_str(text, "%H:%M:%S"))
+        .ok()
+}
+
+// Helper: Removes matching quotes around a value
+fn unquote(text: &str) -> &str {
+    for quote in ['"', '\''] {
+        if let Some(inner) = text.strip_prefix(quote).and_then(|t| t.strip_suffix(quote)) {
+            return inner;
+        }
+    }
+    text
+}
+
+// Helper: Escapes iCalendar text
+fn ics_escape(text: &str) -> String {
+    text.replace('\\', "\\\\")
+        .replace(';', "\\;")
+        .replace(',', "\\,")
+        .replace('\n', "\\n")
+}
+
+// Helper: Folds a content line to lines of at most `ICS_LINE_BYTES` bytes,
+// without splitting characters
+fn fold(line: &str) -> String {
+    let mut folded = String::with_capacity(line.len());
+    let mut len = 0;
+    for c in line.chars() {
+        if len + c.len_utf8() > ICS_LINE_BYTES {
+            folded.push_str("\r\n ");
+            len = 1;
+        }
+        folded.push(c);
+        len += c.len_utf8();
+    }
+    folded
+}
+
+// ============================================================================
+// TESTS
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::metadata_cache::CachedNote;
+
+    fn at(text: &str) -> NaiveDateTime {
+        NaiveDateTime::parse_from_str(text, "%Y-%m-%d %H:%M").unwrap()
+    }
+
+    #[test]
+    fn test_note_events() {
+        let content = "---\nevent:\n  start: 2024-06-01 14:00\n  end: 15:30\n  title: \"Design review\"\n  location: Room 4\n---\n\
+                       # Review\n\nFollow-up @event(2024-06-08 10:00-10:30, Check-in with Ann) and @event(2024-06-10)\n\
+                       `@event(2024-06-11)`\n```\n@event(2024-06-12)\n```\n@event(someday, Later)\n";
+        let events = note_events(content);
+        assert_eq!(events.len(), 3);
+
+        assert_eq!(events[0].title.as_deref(), Some("Design review"));
+        assert_eq!((events[0].start, events[0].end), (at("2024-06-01 14:00"), at("2024-06-01 15:30")));
+        assert_eq!(events[0].location.as_deref(), Some("Room 4"));
+        assert_eq!(events[0].line, 0);
+
+        assert_eq!(events[1].title.as_deref(), Some("Check-in with Ann"));
+        assert_eq!(events[1].end, at("2024-06-08 10:30"));
+        assert_eq!(events[1].line, 10);
+        assert!(events[2].all_day);
+        assert_eq!(events[2].title, None);
+        assert_eq!(events[2].end, at("2024-06-11 00:00"));
+
+        let listed = note_events("---\nevents:\n  - 2024-07-01 09:00\n  - 2024-07-02\n---\n");
+        assert_eq!(listed.len(), 2);
+        assert_eq!(listed[0].end, at("2024-07-01 10:00"));
+    }
+
+    #[test]
+    fn test_upcoming_events() {
+        let mut cache = MetadataCache::default();
+        let note = |title: &str, content: &str| CachedNote {
+            title: title.to_string(),
+            events: note_events(content),
+            ..CachedNote::default()
+        };
+        cache.notes.insert("a.md".into(), note("Sprint", "@event(2024-05-30) @event(2024-06-02 09:00, Standup)"));
+        cache.notes.insert("b.md".into(), note("Trip", "---\nevent:\n  date: 2024-05-28\n  end: 2024-06-01\n---\n"));
+        cache.notes.insert("c.md".into(), note("Later", "@event(2024-08-01)"));
+
+        let today = NaiveDate::from_ymd_opt(2024, 6, 1).unwrap();
+        let events = upcoming(&cache, &TimelineRange::default(), today).unwrap();
+        let titles: Vec<&str> = events.iter().map(|e| e.title.as_str()).collect();
+        // The trip still runs on June 1st; the sprint event ended
+        assert_eq!(titles, ["Trip", "Standup"]);
+
+        let range = TimelineRange { from: Some("2024-05-01".into()), to: Some("2024-06-30".into()) };
+        assert_eq!(upcoming(&cache, &range, today).unwrap().len(), 3);
+    }
+
+    #[test]
+    fn test_ics_export() {
+        let event = CalendarEvent {
+            path: "meetings/review.md".to_string(),
+            note_title: "Review".to_string(),
+            title: "Design review; round 2, final".to_string(),
+            start: at("2024-06-01 14:00"),
+            end: at("2024-06-01 15:30"),
+            all_day: false,
+            location: Some("Room 4".to_string()),
+            line: 0,
+        };
+        let all_day = CalendarEvent { all_day: true, start: at("2024-06-03 00:00"), end: at("2024-06-04 00:00"), title: "x".repeat(100), ..event.clone() };
+        let stamp = DateTime::<Utc>::from_timestamp(1_717_200_000, 0).unwrap();
+        let ics = to_ics(&[event.clone(), all_day], stamp);
+
+        assert!(ics.starts_with("BEGIN:VCALENDAR\r\nVERSION:2.0\r\n"));
+        assert!(ics.ends_with("END:VCALENDAR\r\n"));
+        assert!(ics.contains("DTSTART:20240601T140000\r\nDTEND:20240601T153000\r\n"));
+        assert!(ics.contains("SUMMARY:Design review\\; round 2\\, final\r\n"));
+        assert!(ics.contains("DTSTART;VALUE=DATE:20240603\r\nDTEND;VALUE=DATE:20240604\r\n"));
+        assert!(ics.contains("DTSTAMP:20240601T000000Z"));
+        assert!(ics.split("\r\n").all(|line| line.len() <= ICS_LINE_BYTES));
+        assert_eq!(ics.matches(&format!("UID:{}", event.uid())).count(), 1);
+    }
+}
@@ -0,0 +1,131 @@
+//! Backend Capabilities
+//!
+//! Describes what this build of the backend can do on the current machine,
+//! so the frontend can hide UI for features that are not compiled in or not
+//! available (a missing PDF renderer, no microphone, no OCR language data,
+//! Apple Notes outside macOS).
+//!
+//! Compiled-in features are constant; the others are probed when the
+//! capabilities are detected, which can take a moment (libraries are
+//! loaded, `git` is run), so the frontend should ask once per session.
+
+use serde::Serialize;
+use crate::{activity, audio, ocr, pdf};
+
+/// Features and whether they can be used
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Features {
+    /// Full-text search index
+    pub search: bool,
+    /// Git history in the activity feed (`git` on the PATH)
+    pub git: bool,
+    /// AI assistance
+    pub ai: bool,
+    /// Sync with a remote
+    pub sync: bool,
+    /// PDF page previews (PDFium library found)
+    pub pdf_preview: bool,
+    /// Text recognition in images (Tesseract with English data)
+    pub ocr: bool,
+    /// Voice memos (an input device is present)
+    pub audio_recording: bool,
+    /// Offline transcription (the model is downloaded on first use)
+    pub transcription: bool,
+    /// Apple Notes import (macOS only)
+    pub apple_notes: bool,
+}
+
+/// Operating system the backend runs on
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Platform {
+    /// `macos`, `windows`, `linux`, ...
+    pub os: String,
+    /// `x86_64`, `aarch64`, ...
+    pub arch: String,
+    /// `unix` or `windows`
+    pub family: String,
+}
+
+/// Result of `get_backend_capabilities`
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct BackendCapabilities {
+    /// Backend version (crate version)
+    pub version: String,
+    pub platform: Platform,
+    pub features: Features,
+    /// Formats the import commands accept
+    pub import_formats: Vec<String>,
+    /// Formats the export commands produce
+    pub export_formats: Vec<String>,
+}
+
+impl BackendCapabilities {
+    /// Probes the runtime features of the current machine
+    pub fn detect() -> Self {
+        Self::with_features(Features {
+            search: true,
+            git: activity::git_available(),
+            ai: false,
+            sync: false,
+            pdf_preview: pdf::renderer_available(),
+            ocr: ocr::available(ocr::DEFAULT_LANG),
+            audio_recording: audio::input_available(),
+            transcription: true,
+            apple_notes: cfg!(target_os = "macos"),
+        })
+    }
+
+    /// Capabilities with the given features and this build's constants
+    pub fn with_features(features: Features) -> Self {
+        let mut import_formats = vec![
+            "markdown", "folder", "csv", "tsv", "org", "asciidoc", "logseq", "bear", "textbundle", "vcard",
+        ];
+        if features.apple_notes {
+            import_formats.push("apple_notes");
+        }
+
+        Self {
+            version: env!("CARGO_PKG_VERSION").to_string(),
+            platform: Platform {
+                os: std::env::consts::OS.to_string(),
+                arch: std::env::consts::ARCH.to_string(),
+                family: std::env::consts::FAMILY.to_string(),
+            },
+            features,
+            import_formats: import_formats.into_iter().map(String::from).collect(),
+            export_formats: ["markdown", "zip", "csv", "json", "ics"].into_iter().map(String::from).collect(),
+        }
+    }
+}
+
+// ============================================================================
+// TESTS
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_apple_notes_format_follows_feature() {
+        let features = |apple_notes| Features {
+            search: false,
+            git: false,
+            ai: false,
+            sync: false,
+            pdf_preview: false,
+            ocr: false,
+            audio_recording: false,
+            transcription: true,
+            apple_notes,
+        };
+
+        let mac = BackendCapabilities::with_features(features(true));
+        let other = BackendCapabilities::with_features(features(false));
+
+        assert!(mac.import_formats.contains(&"apple_notes".to_string()));
+        assert!(!other.import_formats.contains(&"apple_notes".to_string()));
+        assert_eq!(other.version, env!("CARGO_PKG_VERSION"));
+        assert_eq!(other.platform.os, std::env::consts::OS);
+    }
+}
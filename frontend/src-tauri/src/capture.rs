@@ -0,0 +1,189 @@
+//! Quick Capture
+//!
+//! Helpers for adding short entries to a note without rewriting it from the
+//! frontend: quick-capture, web clipper and reminder features append or
+//! prepend a line, optionally as a timestamped bullet, optionally inside a
+//! heading's section. Missing notes can be created from a template (filled
+//! in by `crate::template`).
+
+use chrono::NaiveDateTime;
+use serde::{Deserialize, Serialize};
+use crate::frontmatter;
+use crate::outline;
+
+/// Timestamp format used when the caller does not specify one
+pub const DEFAULT_TIMESTAMP_FORMAT: &str = "%Y-%m-%d %H:%M";
+
+/// How a captured entry is formatted and where it goes
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct CaptureOptions {
+    /// Prefix the entry with the current time
+    #[serde(default)]
+    pub timestamp: bool,
+    /// chrono format string for the timestamp (default `%Y-%m-%d %H:%M`)
+    #[serde(default)]
+    pub timestamp_format: Option<String>,
+    /// Turn the entry into a list item (`- `)
+    #[serde(default)]
+    pub bullet: bool,
+    /// Add the entry inside this heading's section (created if missing)
+    #[serde(default)]
+    pub heading: Option<String>,
+    /// Workspace-relative template used when the note does not exist yet
+    #[serde(default)]
+    pub template: Option<String>,
+}
+
+/// Where an entry is inserted
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CapturePosition {
+    /// End of the note (or section)
+    Append,
+    /// Start of the note after the frontmatter (or start of the section)
+    Prepend,
+}
+
+/// Formats captured text according to the options
+pub fn format_entry(text: &str, options: &CaptureOptions, now: NaiveDateTime) -> String {
+    let text = text.trim_matches('\n');
+    let mut lines = text.lines();
+    let mut first = lines.next().unwrap_or("").to_string();
+
+    if options.timestamp {
+        let format = options.timestamp_format.as_deref().unwrap_or(DEFAULT_TIMESTAMP_FORMAT);
+        first = format!("{} {}", now.format(format), first);
+    }
+
+    if !options.bullet {
+        return std::iter::once(first).chain(lines.map(String::from)).collect::<Vec<_>>().join("\n");
+    }
+
+    // Continuation lines are indented so they stay part of the list item
+    let mut entry = format!("- {}", first);
+    for line in lines {
+        entry.push_str("\n  ");
+        entry.push_str(line);
+    }
+    entry
+}
+
+/// Inserts a formatted entry into note content
+pub fn insert_entry(
+    content: &str,
+    entry: &str,
+    heading: Option<&str>,
+    position: CapturePosition,
+) -> Result<String, String> {
+    let Some(heading) = heading else {
+        return Ok(match position {
+            CapturePosition::Append => append_block(content, entry),
+            CapturePosition::Prepend => prepend_block(content, entry),
+        });
+    };
+
+    match outline::read_section(content, heading) {
+        Ok(body) => {
+            let body = match position {
+                CapturePosition::Append => join_blocks(&body, entry),
+                CapturePosition::Prepend => join_blocks(entry, &body),
+            };
+            outline::write_section(content, heading, &body)
+        }
+        Err(_) => {
+            let heading_line = if heading.trim_start().starts_with('#') {
+                heading.trim().to_string()
+            } else {
+                format!("## {}", heading.trim())
+            };
+            Ok(append_block(content, &format!("{}\n{}", heading_line, entry)))
+        }
+    }
+}
+
+fn append_block(content: &str, block: &str) -> String {
+    if content.trim().is_empty() {
+        return format!("{}\n", block);
+    }
+    format!("{}\n", join_blocks(content.trim_end(), block))
+}
+
+fn prepend_block(content: &str, block: &str) -> String {
+    let (_, body) = frontmatter::split(content);
+    let header = &content[..content.len() - body.len()];
+    let body = body.trim_start_matches('\n');
+
+    if body.trim().is_empty() {
+        return format!("{}{}\n", header, block);
+    }
+    format!("{}{}\n", header, join_blocks(block, body.trim_end()))
+}
+
+/// Joins two blocks; consecutive list items stay in one list
+fn join_blocks(first: &str, second: &str) -> String {
+    let first = first.trim_end_matches('\n');
+    let second = second.trim_start_matches('\n');
+    if first.is_empty() || second.is_empty() {
+        return format!("{}{}", first, second);
+    }
+
+    let last_line = first.lines().last().unwrap_or("");
+    let first_line = second.lines().next().unwrap_or("");
+    let separator = if is_list_item(last_line) && is_list_item(first_line) { "\n" } else { "\n\n" };
+    format!("{}{}{}", first, separator, second)
+}
+
+fn is_list_item(line: &str) -> bool {
+    let trimmed = line.trim_start();
+    trimmed.starts_with("- ") || trimmed.starts_with("* ") || trimmed.starts_with("+ ")
+}
+
+// ============================================================================
+// TESTS
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn now() -> NaiveDateTime {
+        chrono::NaiveDate::from_ymd_opt(2024, 5, 17).unwrap().and_hms_opt(9, 30, 0).unwrap()
+    }
+
+    #[test]
+    fn test_format_entry() {
+        let options = CaptureOptions { timestamp: true, bullet: true, ..Default::default() };
+        assert_eq!(format_entry("Call Ana\nabout lease\n", &options, now()), "- 2024-05-17 09:30 Call Ana\n  about lease");
+
+        let options = CaptureOptions { timestamp: true, timestamp_format: Some("%H:%M".to_string()), ..Default::default() };
+        assert_eq!(format_entry("idea", &options, now()), "09:30 idea");
+    }
+
+    #[test]
+    fn test_append_and_prepend() {
+        let note = "---\ntags: [inbox]\n---\n# Inbox\n- first\n";
+
+        assert_eq!(
+            insert_entry(note, "- second", None, CapturePosition::Append).unwrap(),
+            "---\ntags: [inbox]\n---\n# Inbox\n- first\n- second\n"
+        );
+        assert_eq!(
+            insert_entry(note, "Top", None, CapturePosition::Prepend).unwrap(),
+            "---\ntags: [inbox]\n---\nTop\n\n# Inbox\n- first\n"
+        );
+        assert_eq!(insert_entry("", "x", None, CapturePosition::Append).unwrap(), "x\n");
+    }
+
+    #[test]
+    fn test_insert_into_section() {
+        let note = "# Day\n## Meeting Notes\n- standup\n\n## Todo\n- a\n";
+
+        assert_eq!(
+            insert_entry(note, "- retro", Some("Meeting Notes"), CapturePosition::Append).unwrap(),
+            "# Day\n## Meeting Notes\n- standup\n- retro\n\n## Todo\n- a\n"
+        );
+        assert_eq!(
+            insert_entry(note, "- call", Some("Calls"), CapturePosition::Append).unwrap(),
+            "# Day\n## Meeting Notes\n- standup\n\n## Todo\n- a\n\n## Calls\n- call\n"
+        );
+    }
+}
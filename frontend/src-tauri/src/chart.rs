@@ -0,0 +1,369 @@
+//! Table Charts
+//!
+//! Draws the pipe tables of a note as bar, line or pie charts in SVG, so
+//! previews and static exports (HTML, print, mail) can show charts without
+//! a JavaScript charting library. Like `crate::text_pdf`, the SVG is
+//! written directly; the text is left to the viewer's fonts.
+//!
+//! The first column labels the rows; every other column whose cells are
+//! numbers is a series (`1,200`, `$5`, `12.5%` and `**3**` count as
+//! numbers; empty cells as gaps). Bar charts group the series per row,
+//! line charts draw one line per series, pie charts use the first series.
+
+use std::fmt::Write;
+use serde::{Deserialize, Serialize};
+use crate::render::escape_html;
+use crate::tables::{markdown_tables, parse_number, MarkdownTable};
+
+/// Size of a chart, in pixels
+const WIDTH: f64 = 640.0;
+const HEIGHT: f64 = 400.0;
+
+/// Space around the plot area for axis labels and the legend
+const MARGIN_LEFT: f64 = 56.0;
+const MARGIN_RIGHT: f64 = 16.0;
+const MARGIN_TOP: f64 = 40.0;
+const MARGIN_BOTTOM: f64 = 48.0;
+
+/// Gridlines aimed for on the value axis
+const TICKS: f64 = 5.0;
+
+/// Series colors, repeated when a table has more series
+const PALETTE: &[&str] = &["#0969da", "#1a7f37", "#bf8700", "#cf222e", "#8250df", "#1b7c83", "#bc4c00", "#6e7781"];
+
+const FONT: &str = "-apple-system, BlinkMacSystemFont, 'Segoe UI', sans-serif";
+
+/// Chart to draw
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ChartType {
+    Bar,
+    Line,
+    Pie,
+}
+
+/// A named column of values; `None` for empty or non-numeric cells
+struct Series {
+    name: String,
+    values: Vec<Option<f64>>,
+}
+
+/// Draws table `table_index` (0-based) of a note as an SVG chart
+///
+/// # Returns
+/// * `Err(String)` - If the note has no such table, or no numeric column
+pub fn render_table_chart(content: &str, table_index: usize, chart_type: ChartType) -> Result<String, String> {
+    let tables = markdown_tables(content);
+    let table = tables
+        .get(table_index)
+        .ok_or_else(|| format!("Table {} not found ({} tables in the note)", table_index + 1, tables.len()))?;
+    chart_svg(table, chart_type)
+}
+
+/// Draws a table as an SVG chart
+pub fn chart_svg(table: &MarkdownTable, chart_type: ChartType) -> Result<String, String> {
+    let labels: Vec<String> = table.rows.iter().map(|row| plain(row.first().map_or("", String::as_str))).collect();
+    let series: Vec<Series> = (1..table.header.len())
+        .map(|column| Series {
+            name: plain(&table.header[column]),
+            values: table.rows.iter().map(|row| row.get(column).and_then(|cell| parse_number(cell))).collect(),
+        })
+        .filter(|series| series.values.iter().any(Option::is_some))
+        .collect();
+    if labels.is_empty() || series.is_empty() {
+        return Err("The table has no rows with numbers to chart".to_string());
+    }
+    let title = plain(table.header.first().map_or("", String::as_str));
+
+    let mut svg = format!(
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{w}\" height=\"{h}\" viewBox=\"0 0 {w} {h}\" \
+         font-family=\"{font}\" font-size=\"12\" role=\"img\">\n<title>{title}</title>\n\
+         <rect width=\"{w}\" height=\"{h}\" fill=\"#ffffff\"/>\n",
+        w = WIDTH,
+        h = HEIGHT,
+        font = escape_html(FONT),
+        title = escape_html(&title)
+    );
+    match chart_type {
+        ChartType::Bar | ChartType::Line => axes_chart(&mut svg, &labels, &series, chart_type),
+        ChartType::Pie => pie_chart(&mut svg, &labels, &series[0])?,
+    }
+    svg.push_str("</svg>\n");
+    Ok(svg)
+}
+
+// Helper: Bar or line chart with a value axis
+fn axes_chart(svg: &mut String, labels: &[String], series: &[Series], chart_type: ChartType) {
+    let values = || series.iter().flat_map(|s| s.values.iter().flatten().copied());
+    let (low, high, step) = value_axis(values().fold(0.0, f64::min), values().fold(0.0, f64::max));
+
+    let plot_w = WIDTH - MARGIN_LEFT - MARGIN_RIGHT;
+    let plot_h = HEIGHT - MARGIN_TOP - MARGIN_BOTTOM;
+    let y = |value: f64| MARGIN_TOP + plot_h * (high - value) / (high - low);
+    let slot = plot_w / labels.len() as f64;
+    let x = |index: usize| MARGIN_LEFT + slot * (index as f64 + 0.5);
+
+    // Gridlines and value labels
+    let mut tick = low;
+    while tick <= high + step / 2.0 {
+        let _ = writeln!(
+            svg,
+            "<line x1=\"{:.1}\" y1=\"{y:.1}\" x2=\"{:.1}\" y2=\"{y:.1}\" stroke=\"{}\"/>\
+             <text x=\"{:.1}\" y=\"{:.1}\" text-anchor=\"end\" fill=\"#59636e\">{}</text>",
+            MARGIN_LEFT,
+            WIDTH - MARGIN_RIGHT,
+            if tick == 0.0 { "#8c959f" } else { "#d1d9e0" },
+            MARGIN_LEFT - 6.0,
+            y(tick) + 4.0,
+            format_value(tick),
+            y = y(tick)
+        );
+        tick += step;
+    }
+
+    // Row labels, shortened to their slot
+    let max_chars = ((slot / 7.0) as usize).max(3);
+    for (i, label) in labels.iter().enumerate() {
+        let _ = writeln!(
+            svg,
+            "<text x=\"{:.1}\" y=\"{:.1}\" text-anchor=\"middle\" fill=\"#1f2328\">{}</text>",
+            x(i),
+            HEIGHT - MARGIN_BOTTOM + 18.0,
+            escape_html(&shorten(label, max_chars))
+        );
+    }
+
+    match chart_type {
+        ChartType::Bar => {
+            let bar = slot * 0.8 / series.len() as f64;
+            for (k, s) in series.iter().enumerate() {
+                for (i, value) in s.values.iter().enumerate() {
+                    let Some(value) = value else { continue };
+                    let left = x(i) - slot * 0.4 + bar * k as f64;
+                    let (top, bottom) = (y(value.max(0.0)), y(value.min(0.0)));
+                    let _ = writeln!(
+                        svg,
+                        "<rect x=\"{:.1}\" y=\"{:.1}\" width=\"{:.1}\" height=\"{:.1}\" fill=\"{}\"><title>{}: {}</title></rect>",
+                        left,
+                        top,
+                        (bar - 1.0).max(1.0),
+                        bottom - top,
+                        color(k),
+                        escape_html(&labels[i]),
+                        format_value(*value)
+                    );
+                }
+            }
+        }
+        ChartType::Line | ChartType::Pie => {
+            for (k, s) in series.iter().enumerate() {
+                // Gaps split the line
+                let mut path = String::new();
+                let mut pen_down = false;
+                for (i, value) in s.values.iter().enumerate() {
+                    match value {
+                        Some(value) => {
+                            let _ = write!(path, "{}{:.1} {:.1} ", if pen_down { "L" } else { "M" }, x(i), y(*value));
+                            pen_down = true;
+                        }
+                        None => pen_down = false,
+                    }
+                }
+                let _ = writeln!(
+                    svg,
+                    "<path d=\"{}\" fill=\"none\" stroke=\"{}\" stroke-width=\"2\"/>",
+                    path.trim_end(),
+                    color(k)
+                );
+                for (i, value) in s.values.iter().enumerate() {
+                    let Some(value) = value else { continue };
+                    let _ = writeln!(
+                        svg,
+                        "<circle cx=\"{:.1}\" cy=\"{:.1}\" r=\"3\" fill=\"{}\"><title>{}: {}</title></circle>",
+                        x(i),
+                        y(*value),
+                        color(k),
+                        escape_html(&labels[i]),
+                        format_value(*value)
+                    );
+                }
+            }
+        }
+    }
+
+    let names: Vec<&str> = series.iter().map(|s| s.name.as_str()).collect();
+    legend_row(svg, &names);
+}
+
+// Helper: Pie chart of the positive values of a series, legend on the right
+fn pie_chart(svg: &mut String, labels: &[String], series: &Series) -> Result<(), String> {
+    let slices: Vec<(usize, f64)> = series
+        .values
+        .iter()
+        .enumerate()
+        .filter_map(|(i, value)| value.filter(|v| *v > 0.0).map(|v| (i, v)))
+        .collect();
+    let total: f64 = slices.iter().map(|(_, v)| v).sum();
+    if total <= 0.0 {
+        return Err("A pie chart needs positive values".to_string());
+    }
+
+    let radius = (HEIGHT - 2.0 * MARGIN_TOP) / 2.0;
+    let (cx, cy) = (MARGIN_LEFT + radius, HEIGHT / 2.0);
+    let mut angle = -std::f64::consts::FRAC_PI_2;
+    for (k, (i, value)) in slices.iter().enumerate() {
+        let share = value / total;
+        let tooltip = format!("{}: {} ({:.1}%)", labels[*i], format_value(*value), share * 100.0);
+        if slices.len() == 1 {
+            let _ = writeln!(
+                svg,
+                "<circle cx=\"{:.1}\" cy=\"{:.1}\" r=\"{:.1}\" fill=\"{}\"><title>{}</title></circle>",
+                cx,
+                cy,
+                radius,
+                color(k),
+                escape_html(&tooltip)
+            );
+            break;
+        }
+        let end = angle + share * std::f64::consts::TAU;
+        let _ = writeln!(
+            svg,
+            "<path d=\"M{:.1} {:.1} L{:.1} {:.1} A{r:.1} {r:.1} 0 {} 1 {:.1} {:.1} Z\" fill=\"{}\" stroke=\"#ffffff\"><title>{}</title></path>",
+            cx,
+            cy,
+            cx + radius * angle.cos(),
+            cy + radius * angle.sin(),
+            if share > 0.5 { 1 } else { 0 },
+            cx + radius * end.cos(),
+            cy + radius * end.sin(),
+            color(k),
+            escape_html(&tooltip),
+            r = radius
+        );
+        angle = end;
+    }
+
+    let left = cx + radius + 32.0;
+    let top = cy - slices.len() as f64 * 10.0;
+    for (k, (i, value)) in slices.iter().enumerate() {
+        let row = top + k as f64 * 20.0;
+        let _ = writeln!(
+            svg,
+            "<rect x=\"{:.1}\" y=\"{:.1}\" width=\"12\" height=\"12\" fill=\"{}\"/>\
+             <text x=\"{:.1}\" y=\"{:.1}\" fill=\"#1f2328\">{} ({:.1}%)</text>",
+            left,
+            row,
+            color(k),
+            left + 18.0,
+            row + 10.0,
+            escape_html(&shorten(&labels[*i], 32)),
+            value / total * 100.0
+        );
+    }
+    Ok(())
+}
+
+// Helper: Series names above the plot area
+fn legend_row(svg: &mut String, names: &[&str]) {
+    let mut left = MARGIN_LEFT;
+    for (k, name) in names.iter().enumerate() {
+        let name = shorten(name, 24);
+        let _ = writeln!(
+            svg,
+            "<rect x=\"{:.1}\" y=\"14\" width=\"12\" height=\"12\" fill=\"{}\"/>\
+             <text x=\"{:.1}\" y=\"24\" fill=\"#1f2328\">{}</text>",
+            left,
+            color(k),
+            left + 18.0,
+            escape_html(&name)
+        );
+        left += 18.0 + name.chars().count() as f64 * 7.0 + 16.0;
+    }
+}
+
+/// Bounds and gridline step of a value axis covering `min..max` (and 0)
+fn value_axis(min: f64, max: f64) -> (f64, f64, f64) {
+    let range = if max > min { max - min } else { TICKS };
+    let raw = range / TICKS;
+    let magnitude = 10f64.powf(raw.log10().floor());
+    let step = [1.0, 2.0, 5.0, 10.0]
+        .iter()
+        .map(|f| f * magnitude)
+        .find(|step| *step >= raw)
+        .unwrap_or(10.0 * magnitude);
+    let low = (min / step).floor() * step;
+    let high = ((max / step).ceil() * step).max(low + step);
+    (low, high, step)
+}
+
+fn format_value(value: f64) -> String {
+    if (value - value.round()).abs() < 1e-9 {
+        format!("{:.0}", value)
+    } else {
+        let text = format!("{:.2}", value);
+        text.trim_end_matches('0').trim_end_matches('.').to_string()
+    }
+}
+
+fn color(series: usize) -> &'static str {
+    PALETTE[series % PALETTE.len()]
+}
+
+// Helper: A cell's text without emphasis and code markers
+fn plain(cell: &str) -> String {
+    cell.replace(['*', '`'], "").replace("~~", "").trim().to_string()
+}
+
+fn shorten(text: &str, max_chars: usize) -> String {
+    if text.chars().count() <= max_chars {
+        return text.to_string();
+    }
+    let mut short: String = text.chars().take(max_chars.saturating_sub(1)).collect();
+    short.push('…');
+    short
+}
+
+// ============================================================================
+// TESTS
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const NOTE: &str = "---\ntitle: Sales\n---\n# Sales\n\n```\n| not | a table |\n|---|---|\n```\n\n\
+                        | Quarter | Revenue | Notes | Costs |\n|---|--:|---|--:|\n\
+                        | Q1 | $1,200 | slow | 800 |\n| **Q2** | 1500 | | 12.5% |\n| Q3 | | n/a | -300 |\n";
+
+    #[test]
+    fn test_value_axis() {
+        assert_eq!(value_axis(-300.0, 1500.0), (-500.0, 1500.0, 500.0));
+        assert_eq!(value_axis(0.0, 0.0), (0.0, 1.0, 1.0));
+        assert_eq!(format_value(1200.0), "1200");
+        assert_eq!(format_value(0.25), "0.25");
+    }
+
+    #[test]
+    fn test_render_table_chart() {
+        let bar = render_table_chart(NOTE, 0, ChartType::Bar).unwrap();
+        assert!(bar.starts_with("<svg xmlns=\"http://www.w3.org/2000/svg\""));
+        assert!(bar.contains("<title>Q1: 1200</title>"));
+        // The text column is not a series; the empty cell is a gap
+        assert!(!bar.contains(">Notes<"));
+        assert_eq!(bar.matches("<rect x=").count(), 5 + 2);
+        assert!(bar.contains("<title>Q3: -300</title>"));
+
+        let line = render_table_chart(NOTE, 0, ChartType::Line).unwrap();
+        assert_eq!(line.matches("<path d=").count(), 2);
+        assert_eq!(line.matches("<circle").count(), 5);
+
+        let pie = render_table_chart(NOTE, 0, ChartType::Pie).unwrap();
+        assert_eq!(pie.matches("<path d=").count(), 2);
+        assert!(pie.contains("Q2 (55.6%)"));
+
+        assert!(render_table_chart(NOTE, 1, ChartType::Bar).unwrap_err().contains("not found"));
+        let text_only = "| A | B |\n|---|---|\n| x | y |\n";
+        assert!(render_table_chart(text_only, 0, ChartType::Pie).is_err());
+    }
+}
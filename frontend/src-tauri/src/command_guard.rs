@@ -0,0 +1,160 @@
+//! Command Rate Limiting and Reentrancy Guards
+//!
+//! A frontend bug (an effect re-running on every render, a retry loop) can
+//! invoke the same command thousands of times. Two guards keep that from
+//! exhausting the backend:
+//!
+//! - Rate limits: the invoke handler checks every call against `LIMITS`
+//!   before dispatching it. Calls over the limit are rejected with a
+//!   `Rate limited:` error and the offending command is logged once per
+//!   window. Commands without a limit are never throttled.
+//! - In-flight guards: a command can mark a key (e.g. the directory being
+//!   watched) as in progress, so a second call for the same key while the
+//!   first is still running becomes a no-op instead of racing it.
+
+use std::collections::{HashMap, HashSet};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// Maximum number of calls of a command within a window
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RateLimit {
+    pub max_calls: u32,
+    pub per: Duration,
+}
+
+impl RateLimit {
+    const fn per_second(max_calls: u32) -> Self {
+        Self { max_calls, per: Duration::from_secs(1) }
+    }
+}
+
+/// Commands that are throttled, with their limits
+///
+/// Limits are well above what a working frontend needs; they only stop loops.
+pub const LIMITS: &[(&str, RateLimit)] = &[
+    ("list_workspace_contents", RateLimit::per_second(30)),
+    ("list_directory_page", RateLimit::per_second(60)),
+    ("list_workspace_files", RateLimit::per_second(60)),
+    ("watch_directory", RateLimit::per_second(10)),
+    ("stop_watching", RateLimit::per_second(10)),
+    ("get_graph_metrics", RateLimit::per_second(5)),
+    ("get_backend_capabilities", RateLimit::per_second(5)),
+];
+
+/// Calls of one command in the current window
+struct CallWindow {
+    started: Instant,
+    calls: u32,
+    rejected: u32,
+}
+
+/// Rate limits and in-flight keys of all commands (internally synchronized)
+#[derive(Clone, Default)]
+pub struct CommandGuard {
+    windows: Arc<Mutex<HashMap<&'static str, CallWindow>>>,
+    in_flight: Arc<Mutex<HashSet<String>>>,
+}
+
+/// Marks a key as in progress until dropped
+pub struct InFlight {
+    key: String,
+    in_flight: Arc<Mutex<HashSet<String>>>,
+}
+
+impl Drop for InFlight {
+    fn drop(&mut self) {
+        if let Ok(mut in_flight) = self.in_flight.lock() {
+            in_flight.remove(&self.key);
+        }
+    }
+}
+
+impl CommandGuard {
+    /// Creates a guard with no calls recorded
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Counts a call of `command`, failing if it is over its limit
+    pub fn check(&self, command: &str, now: Instant) -> Result<(), String> {
+        let Some((name, limit)) = LIMITS.iter().find(|(name, _)| *name == command) else {
+            return Ok(());
+        };
+        let mut windows = self.windows
+            .lock()
+            .map_err(|e| format!("Failed to lock rate limits: {}", e))?;
+
+        let window = windows.entry(name).or_insert(CallWindow { started: now, calls: 0, rejected: 0 });
+        if now.duration_since(window.started) >= limit.per {
+            if window.rejected > 0 {
+                log::warn!("🚦 {} was rate limited: {} calls rejected", name, window.rejected);
+            }
+            *window = CallWindow { started: now, calls: 0, rejected: 0 };
+        }
+
+        if window.calls >= limit.max_calls {
+            if window.rejected == 0 {
+                log::warn!("🚦 Rate limiting {}: more than {} calls per {:?}", name, limit.max_calls, limit.per);
+            }
+            window.rejected += 1;
+            return Err(format!("Rate limited: {} called too often", name));
+        }
+
+        window.calls += 1;
+        Ok(())
+    }
+
+    /// Marks `key` of `command` as in progress
+    ///
+    /// Returns `None` if another call holds the same key; the caller should
+    /// then return without doing anything.
+    pub fn enter(&self, command: &str, key: &str) -> Option<InFlight> {
+        let key = format!("{}:{}", command, key);
+        let mut in_flight = self.in_flight.lock().ok()?;
+        if !in_flight.insert(key.clone()) {
+            log::warn!("🔁 Ignoring reentrant call {}", key);
+            return None;
+        }
+        Some(InFlight { key, in_flight: self.in_flight.clone() })
+    }
+}
+
+// ============================================================================
+// TESTS
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_calls_over_limit_are_rejected_until_window_ends() {
+        let guard = CommandGuard::new();
+        let start = Instant::now();
+
+        for _ in 0..60 {
+            assert!(guard.check("list_workspace_files", start).is_ok());
+        }
+        let rejected = guard.check("list_workspace_files", start + Duration::from_millis(500));
+        assert!(rejected.unwrap_err().starts_with("Rate limited:"));
+
+        assert!(guard.check("list_workspace_files", start + Duration::from_secs(1)).is_ok());
+        // Commands without a limit are never throttled
+        for _ in 0..1000 {
+            assert!(guard.check("queue_save", start).is_ok());
+        }
+    }
+
+    #[test]
+    fn test_in_flight_key_blocks_until_dropped() {
+        let guard = CommandGuard::new();
+
+        let first = guard.enter("watch_directory", "/notes").unwrap();
+        assert!(guard.enter("watch_directory", "/notes").is_none());
+        assert!(guard.enter("watch_directory", "/other").is_some());
+
+        drop(first);
+        assert!(guard.enter("watch_directory", "/notes").is_some());
+    }
+}
@@ -0,0 +1,109 @@
+//! App Lock Commands
+//!
+//! This module provides the Tauri commands for the app lock (see
+//! `crate::app_lock`): its status, turning it on or off, and unlocking the
+//! app by verifying the device owner (see `crate::biometric`).
+
+use serde::Serialize;
+use tauri::{command, AppHandle, State};
+use crate::app_lock::{AppLockSettings, DEFAULT_RELOCK_AFTER_SECS};
+use crate::biometric::{self, OwnerCheck};
+use crate::commands::workspace::get_config_dir;
+use crate::state::AppState;
+
+/// Result of `get_app_lock_status`
+#[derive(Debug, Clone, Serialize)]
+pub struct AppLockStatus {
+    pub enabled: bool,
+    pub locked: bool,
+    pub relock_after_secs: u64,
+    /// Whether (and how) the owner can be verified on this device
+    pub owner_check: OwnerCheck,
+}
+
+/// Gets the app lock setting, whether the app is locked, and whether the
+/// device can verify its owner.
+#[command]
+pub async fn get_app_lock_status(
+    app_handle: AppHandle,
+    state: State<'_, AppState>,
+) -> Result<AppLockStatus, String> {
+    let settings = state.app_lock.settings()?;
+    Ok(AppLockStatus {
+        enabled: settings.enabled,
+        locked: state.app_lock.is_locked(),
+        relock_after_secs: settings.relock_after_secs,
+        owner_check: biometric::availability(&app_handle),
+    })
+}
+
+/// Turns the app lock on or off and saves the setting.
+///
+/// Either change requires verifying the owner first, so a lock cannot be
+/// enabled on a device that cannot unlock it, nor disabled by someone else.
+/// This is also the only way to reset a setting that failed to load (which
+/// starts the app locked).
+///
+/// # Returns
+/// * `Ok(false)` - If the owner check was cancelled or failed (nothing changes)
+/// * `Err(String)` - If the device cannot verify its owner
+#[command]
+pub async fn set_app_lock(
+    app_handle: AppHandle,
+    state: State<'_, AppState>,
+    enabled: bool,
+    relock_after_secs: Option<u64>,
+) -> Result<bool, String> {
+    let reason = if enabled { "Turn on the MDReader app lock" } else { "Turn off the MDReader app lock" };
+    if !verify_owner(&app_handle, reason).await? {
+        return Ok(false);
+    }
+
+    state.app_lock.set_settings(AppLockSettings {
+        enabled,
+        relock_after_secs: relock_after_secs.unwrap_or(DEFAULT_RELOCK_AFTER_SECS),
+    })?;
+    state.app_lock.save(&get_config_dir()?)?;
+    log::info!("🔐 App lock {}", if enabled { "enabled" } else { "disabled" });
+    Ok(true)
+}
+
+/// Unlocks the app after the system's owner check (biometrics or passcode).
+///
+/// # Returns
+/// * `Ok(true)` - If the app is unlocked (or was not locked)
+/// * `Ok(false)` - If the check was cancelled or failed
+#[command]
+pub async fn unlock_app(app_handle: AppHandle, state: State<'_, AppState>) -> Result<bool, String> {
+    if !state.app_lock.is_locked() {
+        return Ok(true);
+    }
+    if !verify_owner(&app_handle, "Unlock MDReader").await? {
+        log::warn!("🔐 App unlock failed or cancelled");
+        return Ok(false);
+    }
+    state.app_lock.unlock()?;
+    log::info!("🔓 App unlocked");
+    Ok(true)
+}
+
+/// Locks the app right away.
+///
+/// # Returns
+/// * `Ok(false)` - If the app lock is not enabled
+#[command]
+pub async fn lock_app(state: State<'_, AppState>) -> Result<bool, String> {
+    let locked = state.app_lock.lock()?;
+    if locked {
+        log::info!("🔒 App locked");
+    }
+    Ok(locked)
+}
+
+// Helper: Runs the blocking system prompt off the async runtime
+async fn verify_owner(app_handle: &AppHandle, reason: &'static str) -> Result<bool, String> {
+    let app_handle = app_handle.clone();
+    tauri::async_runtime::spawn_blocking(move || biometric::verify(&app_handle, reason))
+        .await
+        .map_err(|e| format!("Failed to verify owner: {}", e))?
+}
@@ -0,0 +1,158 @@
+//! Audio Commands
+//!
+//! This module provides the Tauri commands behind voice memos: record from
+//! the default microphone, save the recording next to a note's other
+//! attachments and link it from the note (see `crate::audio`), and transcribe
+//! recordings offline (see `crate::transcribe`).
+//!
+//! ## Security
+//! The target note and audio files are validated against the configured
+//! workspace, and both the note and the attachment folder must be writable.
+//! The speech model is only ever written to the config directory.
+
+use std::fs;
+use std::path::Path;
+use std::time::{Duration, Instant};
+use tauri::{command, AppHandle, State};
+use crate::capture::{self, CapturePosition};
+use crate::commands::file_operations::{available_path, SavedAttachment};
+use crate::commands::workspace::get_config_dir;
+use crate::jobs::emit_progress;
+use crate::safe_write;
+use crate::state::AppState;
+use crate::transcribe::{self, Transcript};
+use crate::utils::validate_file_path;
+use crate::workspace_settings::{ensure_writable, WorkspaceSettings};
+use crate::write_locks::DEFAULT_LOCK_WAIT_MS;
+
+/// Starts recording a voice memo from the default input device.
+///
+/// Only one recording can run at a time.
+#[command]
+pub async fn start_audio_recording(state: State<'_, AppState>) -> Result<(), String> {
+    state.recorder.start()
+}
+
+/// Stops the running voice memo, saves it as WAV and links it from a note.
+///
+/// The recording goes to the note's attachment folder and the link follows
+/// the workspace attachment settings; it is appended to the end of the note.
+/// If the note is missing or protected, the recording keeps running so the
+/// call can be retried with another note.
+///
+/// Security: Validates dest_note is a markdown file within the workspace.
+#[command]
+pub async fn stop_audio_recording(
+    state: State<'_, AppState>,
+    dest_note: String,
+) -> Result<SavedAttachment, String> {
+    let workspace = state.get_workspace_path()?;
+
+    let validated_note = validate_file_path(&dest_note, &workspace, &["md"])
+        .map_err(|e| format!("Security error: {}", e))?;
+    if !validated_note.is_file() {
+        return Err(format!("Note not found: {}", dest_note));
+    }
+
+    let root = Path::new(&workspace)
+        .canonicalize()
+        .map_err(|e| format!("Failed to resolve workspace: {}", e))?;
+    let attachments = WorkspaceSettings::load(&root)?.attachments;
+    attachments.validate()?;
+
+    let dir = attachments.attachment_dir(&root, &validated_note);
+    ensure_writable(&workspace, &validated_note)?;
+    ensure_writable(&workspace, &dir)?;
+
+    let recording = state.recorder.stop()?;
+
+    fs::create_dir_all(&dir)
+        .map_err(|e| format!("Failed to create attachment folder: {}", e))?;
+    let target = available_path(&dir, &recording.file_name());
+    fs::write(&target, recording.to_wav())
+        .map_err(|e| format!("Failed to save recording: {}", e))?;
+
+    let markdown = attachments.link(&root, &validated_note, &target);
+
+    // Pending debounced content must land first, or it would drop the link
+    state.save_queue.flush_path_async(&validated_note).await?;
+
+    let _guard = state.write_locks
+        .acquire_async(&validated_note, Duration::from_millis(DEFAULT_LOCK_WAIT_MS)).await?;
+    let content = fs::read_to_string(&validated_note)
+        .map_err(|e| format!("Failed to read file: {}", e))?;
+    let updated = capture::insert_entry(&content, &markdown, None, CapturePosition::Append)?;
+    safe_write::write_file(&validated_note, updated)?;
+
+    log::info!("🎙️ Saved voice memo {:?} into {:?}", target, validated_note);
+    Ok(SavedAttachment {
+        path: target.to_string_lossy().to_string(),
+        markdown,
+    })
+}
+
+/// Minimum time between model download progress events
+const PROGRESS_INTERVAL_MS: u128 = 100;
+
+/// Transcribes a WAV audio attachment offline.
+///
+/// The speech model is downloaded to the config directory on first use.
+/// Progress is reported as `job-progress` events of kind `transcribe_audio`:
+/// downloaded bytes while fetching the model, then percent transcribed. The
+/// job can be cancelled while the model downloads.
+///
+/// # Arguments
+/// * `path` - WAV file within the workspace
+/// * `language` - ISO 639-1 code such as `en` (default: detect)
+///
+/// Security: Validates path is a WAV file within the workspace.
+#[command]
+pub async fn transcribe_audio(
+    app_handle: AppHandle,
+    state: State<'_, AppState>,
+    path: String,
+    language: Option<String>,
+) -> Result<Transcript, String> {
+    let workspace = state.get_workspace_path()?;
+
+    let validated_path = validate_file_path(&path, &workspace, &["wav"])
+        .map_err(|e| format!("Security error: {}", e))?;
+    let language = language
+        .map(|l| l.trim().to_lowercase())
+        .filter(|l| !l.is_empty())
+        .unwrap_or_else(|| "auto".to_string());
+
+    let job = state.jobs.start("transcribe_audio")?;
+    let (task_app, task_job, audio_path) = (app_handle.clone(), job.clone(), validated_path.clone());
+    let result = tauri::async_runtime::spawn_blocking(move || {
+        let mut last_emit: Option<Instant> = None;
+        let model = transcribe::ensure_model(
+            &get_config_dir()?.join("whisper"),
+            &mut |downloaded, total| {
+                if last_emit.map_or(true, |t| t.elapsed().as_millis() >= PROGRESS_INTERVAL_MS) {
+                    emit_progress(&task_app, &task_job.progress(downloaded, total, Some("Downloading speech model".to_string())));
+                    last_emit = Some(Instant::now());
+                }
+            },
+            &|| task_job.is_cancelled(),
+        )?;
+
+        let bytes = fs::read(&audio_path)
+            .map_err(|e| format!("Failed to read audio: {}", e))?;
+        let samples = transcribe::wav_to_whisper_input(&bytes)?;
+
+        let current = audio_path.file_name().map(|n| n.to_string_lossy().to_string());
+        transcribe::transcribe(&model, &samples, &language, move |percent| {
+            emit_progress(&task_app, &task_job.progress(percent.max(0) as u64, Some(100), current.clone()));
+        })
+    })
+    .await
+    .map_err(|e| format!("Failed to transcribe audio: {}", e));
+
+    state.jobs.finish(&job.id)?;
+    emit_progress(&app_handle, &job.progress(100, Some(100), None).finished());
+    let transcript = result??;
+
+    log::info!("📝 Transcribed {:?} ({} segments)", validated_path, transcript.segments.len());
+    Ok(transcript)
+}
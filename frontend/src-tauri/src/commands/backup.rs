@@ -0,0 +1,397 @@
+//! Backup Commands
+//!
+//! This module provides the Tauri commands of the deduplicating backup
+//! store (see `crate::backup`) and the volume watcher that runs scheduled
+//! backups (see `crate::backup_targets`). Commands work on the local store
+//! in the user's data directory unless a configured `target` is named.
+//! Stores are shared by all workspaces; listings only show the backups of
+//! the current one.
+//!
+//! ## Security
+//! Only the configured workspace is backed up, and restores go into a new
+//! folder inside it. Backup ids are checked before they become file names.
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use chrono::Utc;
+use serde::Serialize;
+use tauri::{command, AppHandle, Manager, State};
+use crate::activity::ActivityKind;
+use crate::backup::{self, BackupInfo, BackupStore, BackupSummary, PruneReport, VerifyReport};
+use crate::backup_targets::{BackupStatuses, BackupTarget, TargetStatus};
+use crate::command_guard::InFlight;
+use crate::events::{self, BackupState, BackupStatusEvent};
+use crate::jobs::emit_progress;
+use crate::state::AppState;
+use crate::utils::validate_directory_path;
+use crate::workspace_settings::{ensure_tree_writable, WorkspaceSettings};
+
+/// Minimum time between two progress events of a backup
+const PROGRESS_INTERVAL_MS: u128 = 100;
+
+/// Seconds between two checks of the volume watcher
+const VOLUME_POLL_SECS: u64 = 30;
+
+/// A backup target with its availability and run status
+#[derive(Debug, Clone, Serialize)]
+pub struct TargetState {
+    pub target: BackupTarget,
+    /// Whether the store can be reached (the drive is mounted)
+    pub available: bool,
+    pub status: TargetStatus,
+    /// Whether a scheduled backup is pending
+    pub due: bool,
+    /// Days without a successful backup, once over the warning threshold
+    pub overdue_days: Option<i64>,
+}
+
+// Helper: A configured target whose store can be reached
+fn find_target(workspace: &str, name: &str) -> Result<BackupTarget, String> {
+    let settings = WorkspaceSettings::load(Path::new(workspace))?;
+    let target = settings
+        .backup_targets
+        .into_iter()
+        .find(|t| t.name == name)
+        .ok_or_else(|| format!("Unknown backup target: {}", name))?;
+    if !target.is_available() {
+        return Err(format!("Backup volume is not mounted: {}", name));
+    }
+    Ok(target)
+}
+
+// Helper: Store directory of a target (the local store without one)
+fn store_dir(workspace: &str, target: Option<&str>) -> Result<PathBuf, String> {
+    match target {
+        Some(name) => find_target(workspace, name)?.store_dir(),
+        None => backup::default_dir(),
+    }
+}
+
+// Helper: Opens a backup store, failing while another call changes it
+fn open_store(state: &AppState, dir: PathBuf) -> Result<(BackupStore, InFlight), String> {
+    let in_flight = state
+        .command_guard
+        .enter("backup_store", &dir.to_string_lossy())
+        .ok_or("Another backup operation is still running")?;
+    Ok((BackupStore::open(dir), in_flight))
+}
+
+// Helper: Canonical workspace root as recorded in backup manifests
+fn workspace_source(workspace: &str) -> Result<PathBuf, String> {
+    Path::new(workspace)
+        .canonicalize()
+        .map_err(|e| format!("Failed to resolve workspace: {}", e))
+}
+
+// Helper: Backs up the workspace as a job reporting `job-progress`
+fn run_backup(
+    app_handle: &AppHandle,
+    state: &AppState,
+    workspace: &str,
+    store: &BackupStore,
+) -> Result<BackupSummary, String> {
+    let source = workspace_source(workspace)?;
+    // Nothing is left pending when called from `create_backup`
+    state.save_queue.flush_all()?;
+
+    let job = state.jobs.start("backup")?;
+    let mut last_emit: Option<std::time::Instant> = None;
+    let result = store.create(&source, &mut |done, total, path| {
+        if last_emit.map_or(true, |t| t.elapsed().as_millis() >= PROGRESS_INTERVAL_MS) {
+            emit_progress(app_handle, &job.progress(done as u64, Some(total as u64), Some(path.to_string())));
+            last_emit = Some(std::time::Instant::now());
+        }
+        job.checkpoint()
+    });
+    let files = result.as_ref().map_or(0, |s| s.files as u64);
+    emit_progress(app_handle, &job.progress(files, Some(files), None).finished());
+    state.jobs.finish(&job.id)?;
+    let summary = result?;
+
+    log::info!(
+        "🗄️ Backup {}: {} files, {} of {} chunks new ({} bytes stored)",
+        summary.id,
+        summary.files,
+        summary.new_chunks,
+        summary.chunks,
+        summary.stored_bytes
+    );
+    Ok(summary)
+}
+
+// Helper: Backs up to a target, recording the outcome in its status and
+// announcing it with `backup-status` events
+fn backup_to_target(
+    app_handle: &AppHandle,
+    state: &AppState,
+    workspace: &str,
+    target: &BackupTarget,
+) -> Result<BackupSummary, String> {
+    let (store, _in_flight) = open_store(state, target.store_dir()?)?;
+    let status_event = |kind: BackupState| BackupStatusEvent {
+        target: target.name.clone(),
+        state: kind,
+        backup_id: None,
+        message: None,
+        last_success: None,
+        days_without_success: None,
+    };
+    events::emit(app_handle, &status_event(BackupState::Running));
+
+    let result = run_backup(app_handle, state, workspace, &store);
+
+    let root = Path::new(workspace);
+    let mut statuses = BackupStatuses::load(root)?;
+    let now = Utc::now();
+    match &result {
+        Ok(summary) => {
+            statuses.record_success(&target.name, &summary.id, now);
+            events::emit(app_handle, &BackupStatusEvent {
+                backup_id: Some(summary.id.clone()),
+                last_success: Some(now.to_rfc3339()),
+                ..status_event(BackupState::Succeeded)
+            });
+        }
+        Err(e) => {
+            log::warn!("⚠️ Backup to {} failed: {}", target.name, e);
+            statuses.record_failure(&target.name, e, now);
+            events::emit(app_handle, &BackupStatusEvent {
+                message: Some(e.clone()),
+                last_success: statuses.get(&target.name).last_success.map(|t| t.to_rfc3339()),
+                ..status_event(BackupState::Failed)
+            });
+        }
+    }
+    statuses.save(root)?;
+    result
+}
+
+/// Backs up the workspace into the deduplicating store.
+///
+/// Only chunks that are not in the store yet are written, so repeated
+/// backups of a large vault are cheap. Pending debounced saves are flushed
+/// first. The backup runs as a job: `job-progress` events count files, and
+/// `cancel_job` stops it without recording a backup.
+///
+/// With `target`, the backup goes to that configured target (which must be
+/// mounted) and counts as its scheduled backup.
+///
+/// # Returns
+/// * `Ok(BackupSummary)` - Id, size and how much was newly stored
+#[command]
+pub async fn create_backup(
+    app_handle: AppHandle,
+    state: State<'_, AppState>,
+    target: Option<String>,
+) -> Result<BackupSummary, String> {
+    let workspace = state.get_workspace_path()?;
+    state.save_queue.flush_all_async().await?;
+
+    match target {
+        Some(name) => backup_to_target(&app_handle, &state, &workspace, &find_target(&workspace, &name)?),
+        None => {
+            let (store, _in_flight) = open_store(&state, backup::default_dir()?)?;
+            run_backup(&app_handle, &state, &workspace, &store)
+        }
+    }
+}
+
+/// Lists the backups of the current workspace, newest first.
+#[command]
+pub async fn list_backups(
+    state: State<'_, AppState>,
+    target: Option<String>,
+) -> Result<Vec<BackupInfo>, String> {
+    let workspace = state.get_workspace_path()?;
+    let source = workspace_source(&workspace)?.to_string_lossy().to_string();
+
+    let store = BackupStore::open(store_dir(&workspace, target.as_deref())?);
+    Ok(store.list()?.into_iter().filter(|b| b.source == source).collect())
+}
+
+/// Checks that every chunk of a backup is present and matches its hash.
+///
+/// # Returns
+/// * `Ok(VerifyReport)` - Missing and corrupt chunks and the files they damage
+#[command]
+pub async fn verify_backup(
+    state: State<'_, AppState>,
+    id: String,
+    target: Option<String>,
+) -> Result<VerifyReport, String> {
+    let workspace = state.get_workspace_path()?;
+    let store = BackupStore::open(store_dir(&workspace, target.as_deref())?);
+    let report = store.verify(&id)?;
+
+    if report.ok {
+        log::info!("✅ Backup {} verified ({} chunks)", id, report.chunks);
+    } else {
+        log::warn!(
+            "⚠️ Backup {} is damaged: {} missing, {} corrupt chunks",
+            id,
+            report.missing.len(),
+            report.corrupt.len()
+        );
+    }
+    Ok(report)
+}
+
+/// Restores a backup into a new folder of the workspace.
+///
+/// Security: Validates dest_folder is within the workspace and does not exist.
+///
+/// # Returns
+/// * `Ok(usize)` - Number of files restored
+#[command]
+pub async fn restore_backup(
+    state: State<'_, AppState>,
+    id: String,
+    dest_folder: String,
+    target: Option<String>,
+) -> Result<usize, String> {
+    let workspace = state.get_workspace_path()?;
+
+    let validated_dest = validate_directory_path(&dest_folder, &workspace, false)
+        .map_err(|e| format!("Security error: {}", e))?;
+    ensure_tree_writable(&workspace, &validated_dest)?;
+
+    let store = BackupStore::open(store_dir(&workspace, target.as_deref())?);
+    let count = store.restore(&id, &validated_dest)?;
+    state.activity.record(&workspace, &validated_dest, ActivityKind::Imported, Some(format!("backup {}", id)));
+
+    log::info!("♻️ Restored backup {} to {} ({} files)", id, validated_dest.display(), count);
+    Ok(count)
+}
+
+/// Deletes a backup and the chunks no other backup uses.
+#[command]
+pub async fn delete_backup(
+    state: State<'_, AppState>,
+    id: String,
+    target: Option<String>,
+) -> Result<PruneReport, String> {
+    let workspace = state.get_workspace_path()?;
+    let (store, _in_flight) = open_store(&state, store_dir(&workspace, target.as_deref())?)?;
+    store.delete(&id)?;
+    let report = store.prune()?;
+
+    log::info!("🗑️ Deleted backup {} ({} bytes freed)", id, report.bytes_freed);
+    Ok(report)
+}
+
+/// Removes chunks no backup references, e.g. after an interrupted backup.
+#[command]
+pub async fn prune_backups(
+    state: State<'_, AppState>,
+    target: Option<String>,
+) -> Result<PruneReport, String> {
+    let workspace = state.get_workspace_path()?;
+    let (store, _in_flight) = open_store(&state, store_dir(&workspace, target.as_deref())?)?;
+    let report = store.prune()?;
+
+    log::info!("🧹 Pruned {} backup chunks ({} bytes freed)", report.chunks_removed, report.bytes_freed);
+    Ok(report)
+}
+
+/// Lists the workspace's backup targets with their availability and status.
+#[command]
+pub async fn get_backup_status(state: State<'_, AppState>) -> Result<Vec<TargetState>, String> {
+    let workspace = state.get_workspace_path()?;
+    let root = Path::new(&workspace);
+    let settings = WorkspaceSettings::load(root)?;
+    let statuses = BackupStatuses::load(root)?;
+    let now = Utc::now();
+
+    Ok(settings
+        .backup_targets
+        .into_iter()
+        .map(|target| {
+            let status = statuses.get(&target.name);
+            TargetState {
+                available: target.is_available(),
+                due: target.is_due(&status, now),
+                overdue_days: target.overdue_days(&status, now),
+                status,
+                target,
+            }
+        })
+        .collect())
+}
+
+// ============================================================================
+// VOLUME WATCHER
+// ============================================================================
+
+/// Starts the volume watcher.
+///
+/// Every `VOLUME_POLL_SECS` it runs the pending backups of the current
+/// workspace's reachable targets, so an external drive is backed up as soon
+/// as it is mounted, and emits a `backup-status` event of state `overdue`
+/// (at most once a day per target) while a target has gone without a
+/// successful backup for its `warn_after_days`.
+pub fn spawn_volume_watcher(app_handle: AppHandle) {
+    std::thread::spawn(move || {
+        let mut warned = HashMap::new();
+        loop {
+            std::thread::sleep(Duration::from_secs(VOLUME_POLL_SECS));
+            let state = app_handle.state::<AppState>();
+            if state.lifecycle.is_suspended() {
+                continue;
+            }
+            let Ok(workspace) = state.get_workspace_path() else {
+                continue;
+            };
+            if let Err(e) = check_targets(&app_handle, &state, &workspace, &mut warned) {
+                log::warn!("⚠️ Backup check failed: {}", e);
+            }
+        }
+    });
+}
+
+// Helper: One pass of the volume watcher
+fn check_targets(
+    app_handle: &AppHandle,
+    state: &AppState,
+    workspace: &str,
+    warned: &mut HashMap<String, chrono::NaiveDate>,
+) -> Result<(), String> {
+    let root = Path::new(workspace);
+    let settings = WorkspaceSettings::load(root)?;
+    let mut statuses = BackupStatuses::load(root)?;
+    if statuses.sync_targets(&settings.backup_targets, Utc::now()) {
+        statuses.save(root)?;
+    }
+
+    for target in &settings.backup_targets {
+        if target.is_available() && target.is_due(&statuses.get(&target.name), Utc::now()) {
+            log::info!("💽 Backup target {} is reachable, running pending backup", target.name);
+            // Failures are recorded in the status and retried later
+            let _ = backup_to_target(app_handle, state, workspace, target);
+            statuses = BackupStatuses::load(root)?;
+        }
+
+        let now = Utc::now();
+        let status = statuses.get(&target.name);
+        let key = format!("{}:{}", workspace, target.name);
+        match target.overdue_days(&status, now) {
+            Some(days) if warned.get(&key) != Some(&now.date_naive()) => {
+                log::warn!("⏰ No backup to {} for {} days", target.name, days);
+                events::emit(app_handle, &BackupStatusEvent {
+                    target: target.name.clone(),
+                    state: BackupState::Overdue,
+                    backup_id: None,
+                    message: status.last_error.clone(),
+                    last_success: status.last_success.map(|t| t.to_rfc3339()),
+                    days_without_success: Some(days),
+                });
+                warned.insert(key, now.date_naive());
+            }
+            Some(_) => {}
+            None => {
+                warned.remove(&key);
+            }
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,204 @@
+//! Diagram Commands
+//! 
+//! This module provides Tauri commands that turn the structure of a note
+//! into diagram source (see `crate::mermaid`), list the diagram blocks of
+//! a note and cache their rendered SVG (see `crate::diagrams`), and draw
+//! the tables of a note as charts (see `crate::chart`).
+//! 
+//! ## Security
+//! Note paths are validated against the configured workspace. Cache keys
+//! must be blake3 hashes, so they cannot name files outside the cache.
+//! PlantUML is only run or contacted as set up on this machine.
+
+use tauri::{command, State};
+use std::fs;
+use std::path::Path;
+use crate::chart::{self, ChartType};
+use crate::commands::workspace::get_config_dir;
+use crate::diagrams::{self, DiagramBlock, DiagramLanguage, PlantUmlConfig};
+use crate::mermaid::{self, MermaidKind};
+use crate::state::AppState;
+use crate::utils::validate_file_path;
+
+/// Builds Mermaid source from a note.
+/// 
+/// * `flowchart` - from nested lists
+/// * `gantt` - from tasks with dates
+/// * `sequence` - from `A -> B: message` bullets
+/// 
+/// Security: Validates file_path is a markdown file within the workspace.
+#[command]
+pub async fn generate_mermaid(
+    state: State<'_, AppState>,
+    file_path: String,
+    kind: MermaidKind,
+) -> Result<String, String> {
+    let workspace = state.get_workspace_path()?;
+    
+    let validated_path = validate_file_path(&file_path, &workspace, &["md"])
+        .map_err(|e| format!("Security error: {}", e))?;
+    
+    let content = fs::read_to_string(&validated_path)
+        .map_err(|e| format!("Failed to read file: {}", e))?;
+    
+    let source = mermaid::generate_mermaid(&content, &validated_path, kind)?;
+    
+    log::info!("📊 Generated {:?} diagram from: {:?}", kind, validated_path);
+    Ok(source)
+}
+
+/// Lists the diagram blocks (` ```mermaid `, ` ```plantuml `) of a note.
+/// 
+/// Each block has an id derived from its content, which stays the same
+/// while the block is unchanged, its cache key and whether a rendered SVG
+/// is cached.
+/// 
+/// Security: Validates file_path is a markdown file within the workspace.
+#[command]
+pub async fn extract_diagrams(
+    state: State<'_, AppState>,
+    file_path: String,
+) -> Result<Vec<DiagramBlock>, String> {
+    let workspace = state.get_workspace_path()?;
+    
+    let validated_path = validate_file_path(&file_path, &workspace, &["md"])
+        .map_err(|e| format!("Security error: {}", e))?;
+    
+    let content = fs::read_to_string(&validated_path)
+        .map_err(|e| format!("Failed to read file: {}", e))?;
+    
+    let root = Path::new(&workspace);
+    let mut blocks = diagrams::extract_diagrams(&content);
+    for block in &mut blocks {
+        block.cached = diagrams::cached_svg(root, &block.hash).is_some();
+    }
+    Ok(blocks)
+}
+
+/// Returns the cached SVG of a diagram, if it was rendered before.
+/// 
+/// * `hash` - Cache key of the block (`data-diagram` in rendered HTML)
+#[command]
+pub async fn get_cached_diagram(
+    state: State<'_, AppState>,
+    hash: String,
+) -> Result<Option<String>, String> {
+    let workspace = state.get_workspace_path()?;
+    Ok(diagrams::cached_svg(Path::new(&workspace), &hash))
+}
+
+/// Caches the SVG the preview rendered for a diagram.
+/// 
+/// Mermaid diagrams are drawn by the preview's Mermaid runtime; storing
+/// the result lets exports and later previews skip rendering them again.
+/// 
+/// Security: `hash` must be a blake3 hash; `svg` must be an SVG of at most
+/// `diagrams::MAX_SVG_BYTES`. Rendered HTML embeds it as an image only, so
+/// scripts in it never run (see `diagrams::inline_cached`).
+#[command]
+pub async fn cache_diagram(
+    state: State<'_, AppState>,
+    hash: String,
+    svg: String,
+) -> Result<(), String> {
+    let workspace = state.get_workspace_path()?;
+    diagrams::store_svg(Path::new(&workspace), &hash, &svg)
+}
+
+/// Renders a diagram block of a note to SVG.
+/// 
+/// A cached rendering is returned as is. PlantUML blocks are otherwise
+/// rendered as set up with `set_plantuml_config` and cached; Mermaid blocks
+/// are rendered by the preview (see `cache_diagram`).
+/// 
+/// * `id` - Block id from `extract_diagrams`
+/// 
+/// Security: Validates file_path is a markdown file within the workspace.
+#[command]
+pub async fn render_diagram(
+    state: State<'_, AppState>,
+    file_path: String,
+    id: String,
+) -> Result<String, String> {
+    let workspace = state.get_workspace_path()?;
+    
+    let validated_path = validate_file_path(&file_path, &workspace, &["md"])
+        .map_err(|e| format!("Security error: {}", e))?;
+    
+    let content = fs::read_to_string(&validated_path)
+        .map_err(|e| format!("Failed to read file: {}", e))?;
+    
+    let block = diagrams::extract_diagrams(&content)
+        .into_iter()
+        .find(|block| block.id == id)
+        .ok_or_else(|| format!("Diagram not found: {}", id))?;
+    
+    let root = Path::new(&workspace);
+    if let Some(svg) = diagrams::cached_svg(root, &block.hash) {
+        return Ok(svg);
+    }
+    if block.language == DiagramLanguage::Mermaid {
+        return Err("Mermaid diagrams are rendered by the preview".to_string());
+    }
+    
+    let svg = PlantUmlConfig::load(&get_config_dir()?)?.render(&block.source)?;
+    diagrams::store_svg(root, &block.hash, &svg)?;
+    
+    log::info!("📊 Rendered {} in {:?}", block.id, validated_path);
+    Ok(svg)
+}
+
+/// Returns how PlantUML diagrams are rendered on this machine.
+#[command]
+pub async fn get_plantuml_config() -> Result<PlantUmlConfig, String> {
+    PlantUmlConfig::load(&get_config_dir()?)
+}
+
+/// Sets how PlantUML diagrams are rendered on this machine: a PlantUML
+/// server, or a local `plantuml.jar` (with the Java runtime to run it).
+/// 
+/// # Returns
+/// * `Err(String)` - If the server is not an http(s) URL or the jar is missing
+#[command]
+pub async fn set_plantuml_config(config: PlantUmlConfig) -> Result<PlantUmlConfig, String> {
+    let blank = |value: Option<String>| value.filter(|v| !v.trim().is_empty());
+    let config = PlantUmlConfig {
+        server: blank(config.server),
+        jar: blank(config.jar),
+        java: blank(config.java),
+    };
+    config.validate()?;
+    config.save(&get_config_dir()?)?;
+    log::info!("📊 PlantUML set to {}", config.jar.as_deref().or(config.server.as_deref()).unwrap_or("off"));
+    Ok(config)
+}
+
+/// Draws a table of a note as a bar, line or pie chart, as SVG that
+/// previews and exports can embed without a charting library.
+/// 
+/// * `table_index` - Which table of the note (0-based, code blocks skipped)
+/// * `chart_type` - `bar`, `line` or `pie`
+/// 
+/// The first column labels the rows; the numeric columns are the series.
+/// 
+/// Security: Validates file_path is a markdown file within the workspace.
+#[command]
+pub async fn render_table_chart(
+    state: State<'_, AppState>,
+    file_path: String,
+    table_index: usize,
+    chart_type: ChartType,
+) -> Result<String, String> {
+    let workspace = state.get_workspace_path()?;
+    
+    let validated_path = validate_file_path(&file_path, &workspace, &["md"])
+        .map_err(|e| format!("Security error: {}", e))?;
+    
+    let content = fs::read_to_string(&validated_path)
+        .map_err(|e| format!("Failed to read file: {}", e))?;
+    
+    let svg = chart::render_table_chart(&content, table_index, chart_type)?;
+    
+    log::info!("📊 Charted table {} of {:?} as {:?}", table_index + 1, validated_path, chart_type);
+    Ok(svg)
+}
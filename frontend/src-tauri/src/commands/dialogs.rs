@@ -0,0 +1,247 @@
+//! Native Dialog Commands
+//!
+//! This module provides Tauri commands that open the native open/save/folder
+//! dialogs for import and export flows, so the frontend never has to build
+//! filesystem paths itself: it picks a path here and passes it on to the
+//! import or export command.
+//!
+//! All pickers return nothing (an empty list or `None`) when the dialog is
+//! cancelled, and fail with `Dialog timed out` if it is left open for
+//! `DIALOG_TIMEOUT_SECS`. Dialogs are awaited off the async runtime (see
+//! `crate::dialogs`); `cancel_dialog` stops waiting for the open one.
+//!
+//! ## Security
+//! - Save destinations are granted to one export and export folders to one
+//!   `set_export_folders` (see `crate::export_policy`); both are refused
+//!   inside system directories
+//! - Import destinations must be inside the configured workspace
+
+use std::path::Path;
+use std::time::Duration;
+use serde::Deserialize;
+use rfd::AsyncFileDialog;
+use tauri::{command, State};
+use crate::dialogs::DIALOG_TIMEOUT_SECS;
+use crate::state::AppState;
+use crate::utils::{sanitize_filename, validate_directory_path};
+
+/// File type filter of a dialog
+#[derive(Debug, Clone, Deserialize)]
+pub struct DialogFilter {
+    /// Label shown in the dialog, e.g. `Markdown`
+    pub name: String,
+    /// Extensions without the dot, e.g. `["md"]`
+    pub extensions: Vec<String>,
+}
+
+/// What a picked folder is for
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FolderPurpose {
+    /// Folder, Logseq graph or backup to import (anywhere)
+    ImportSource,
+    /// Workspace folder to import into (inside the workspace)
+    ImportDestination,
+    /// Folder exports may write to (see `set_export_folders`)
+    ExportFolder,
+}
+
+impl FolderPurpose {
+    fn title(self) -> &'static str {
+        match self {
+            FolderPurpose::ImportSource => "Select Folder to Import",
+            FolderPurpose::ImportDestination => "Import Into",
+            FolderPurpose::ExportFolder => "Select Export Folder",
+        }
+    }
+}
+
+/// Opens the native save dialog to pick an export destination.
+///
+/// The picked path may be used by one export command; later exports to
+/// the same path need a new pick unless it lies in the workspace or an
+/// export folder.
+///
+/// Security: System directories are refused even when picked.
+///
+/// # Returns
+/// * `Ok(Some(path))` - Destination to pass to the export command
+/// * `Ok(None)` - The dialog was cancelled
+#[command]
+pub async fn choose_export_destination(
+    state: State<'_, AppState>,
+    default_name: String,
+    filters: Option<Vec<DialogFilter>>,
+) -> Result<Option<String>, String> {
+    let mut dialog = AsyncFileDialog::new()
+        .set_title("Export To")
+        .set_file_name(sanitize_filename(&default_name));
+    for filter in filters.unwrap_or_default() {
+        dialog = dialog.add_filter(filter.name, &filter.extensions);
+    }
+
+    let picked = show(&state, async move {
+        dialog.save_file().await.map(|file| file.path().to_path_buf())
+    }).await?;
+    grant_picked(&state, picked.as_deref())
+}
+
+/// Opens the native save dialog for a file of one type.
+///
+/// `extension` (without the dot) is added to the picked name if the user
+/// left it out. Like `choose_export_destination`, the path is granted to
+/// one export.
+#[command]
+pub async fn pick_save_path(
+    state: State<'_, AppState>,
+    default_name: String,
+    extension: String,
+) -> Result<Option<String>, String> {
+    let extension = extension.trim_start_matches('.').to_string();
+    let default_name = with_extension(&sanitize_filename(&default_name), &extension);
+
+    let dialog = AsyncFileDialog::new()
+        .set_title("Save As")
+        .set_file_name(default_name)
+        .add_filter(extension.to_uppercase(), std::slice::from_ref(&extension));
+
+    let picked = show(&state, async move {
+        dialog.save_file().await.map(|file| file.path().to_path_buf())
+    })
+    .await?
+    .map(|path| {
+        let name = path.file_name().unwrap_or_default().to_string_lossy().to_string();
+        path.with_file_name(with_extension(&name, &extension))
+    });
+    grant_picked(&state, picked.as_deref())
+}
+
+/// Opens the native open dialog to pick files to import.
+///
+/// Returns the picked paths, or an empty list if the dialog was cancelled.
+#[command]
+pub async fn pick_files_to_import(
+    state: State<'_, AppState>,
+    filters: Option<Vec<DialogFilter>>,
+) -> Result<Vec<String>, String> {
+    let mut dialog = AsyncFileDialog::new().set_title("Select Files to Import");
+    for filter in filters.unwrap_or_default() {
+        dialog = dialog.add_filter(filter.name, &filter.extensions);
+    }
+
+    let picked = show(&state, async move {
+        let files = dialog.pick_files().await?;
+        Some(files.iter().map(|file| file.path().to_path_buf()).collect::<Vec<_>>())
+    }).await?;
+    let files: Vec<String> = picked
+        .unwrap_or_default()
+        .into_iter()
+        .map(|path| path.to_string_lossy().to_string())
+        .collect();
+
+    log::info!("📥 {} files picked for import", files.len());
+    Ok(files)
+}
+
+/// Opens the native folder dialog for `purpose`.
+///
+/// Security:
+/// - `import_destination` must be inside the configured workspace
+/// - `export_folder` must not be a system directory; the folder may then
+///   be added once with `set_export_folders`
+#[command]
+pub async fn pick_folder(
+    state: State<'_, AppState>,
+    purpose: FolderPurpose,
+) -> Result<Option<String>, String> {
+    let mut dialog = AsyncFileDialog::new()
+        .set_title(purpose.title())
+        .set_can_create_directories(purpose != FolderPurpose::ImportSource);
+    let workspace = state.get_workspace_path().ok();
+    if let (FolderPurpose::ImportDestination, Some(workspace)) = (purpose, &workspace) {
+        dialog = dialog.set_directory(workspace);
+    }
+
+    let picked = show(&state, async move {
+        dialog.pick_folder().await.map(|folder| folder.path().to_path_buf())
+    }).await?;
+    let Some(folder) = picked else {
+        return Ok(None);
+    };
+
+    let folder = match purpose {
+        FolderPurpose::ImportSource => folder,
+        FolderPurpose::ImportDestination => {
+            let workspace = workspace.ok_or("No workspace configured")?;
+            validate_directory_path(&folder.to_string_lossy(), &workspace, true)
+                .map_err(|e| format!("Security error: {}", e))?
+        }
+        FolderPurpose::ExportFolder => state.export_grants.grant_folder(&folder)?,
+    };
+
+    log::info!("📂 Folder picked ({:?}): {}", purpose, folder.display());
+    Ok(Some(folder.to_string_lossy().to_string()))
+}
+
+/// Stops waiting for the open dialog, which then resolves as cancelled.
+///
+/// # Returns
+/// * `Ok(true)` - A dialog was open
+/// * `Ok(false)` - No dialog was open
+#[command]
+pub async fn cancel_dialog(state: State<'_, AppState>) -> Result<bool, String> {
+    let cancelled = state.dialogs.cancel();
+    if cancelled {
+        log::info!("🚫 Dialog cancelled");
+    }
+    Ok(cancelled)
+}
+
+/// Shows a dialog through the runner with the default timeout
+pub(crate) async fn show<T, F>(state: &AppState, dialog: F) -> Result<Option<T>, String>
+where
+    T: Send + 'static,
+    F: std::future::Future<Output = Option<T>> + Send + 'static,
+{
+    state.dialogs.show(dialog, Duration::from_secs(DIALOG_TIMEOUT_SECS)).await
+}
+
+// Helper: Grants a picked save destination to one export
+fn grant_picked(state: &AppState, picked: Option<&Path>) -> Result<Option<String>, String> {
+    let Some(picked) = picked else {
+        return Ok(None);
+    };
+
+    let granted = state.export_grants.grant(picked)?;
+    log::info!("📤 Export destination chosen: {}", granted.display());
+    Ok(Some(granted.to_string_lossy().to_string()))
+}
+
+// Helper: Appends `.extension` unless the name already ends with it
+fn with_extension(name: &str, extension: &str) -> String {
+    let has_extension = Path::new(name)
+        .extension()
+        .is_some_and(|e| e.to_string_lossy().eq_ignore_ascii_case(extension));
+    if has_extension || extension.is_empty() {
+        name.to_string()
+    } else {
+        format!("{}.{}", name, extension)
+    }
+}
+
+// ============================================================================
+// TESTS
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_with_extension_appends_missing_extension() {
+        assert_eq!(with_extension("notes", "zip"), "notes.zip");
+        assert_eq!(with_extension("notes.ZIP", "zip"), "notes.ZIP");
+        assert_eq!(with_extension("report.v2", "csv"), "report.v2.csv");
+        assert_eq!(with_extension("notes", ""), "notes");
+    }
+}
@@ -0,0 +1,121 @@
+//! External Editor Commands
+//!
+//! This module opens notes in the editor chosen on this machine (see
+//! `crate::external_editor`) and watches them while they are edited there:
+//! every save of the external editor is reported with `open-file-changed`,
+//! like any other external change of an open document (see
+//! `watch_file`), so the app can reload it.
+
+use std::path::Path;
+use std::process::{Command, Stdio};
+use tauri::{command, AppHandle, State};
+use crate::commands::file_watcher::watch_open_file;
+use crate::commands::workspace::get_config_dir;
+use crate::external_editor::{self, split_command, EditorConfig, EditorLaunch};
+use crate::state::AppState;
+use crate::utils::validate_file_path;
+
+/// External editors can only be started on desktop
+const EXTERNAL_EDITOR_SUPPORTED: bool = cfg!(desktop);
+
+/// Returns the external editor chosen on this machine.
+#[command]
+pub async fn get_external_editor() -> Result<EditorConfig, String> {
+    EditorConfig::load(&get_config_dir()?)
+}
+
+/// Chooses the external editor on this machine.
+///
+/// `editor` is a command line such as `code --wait`; `None` uses the
+/// system's default application.
+///
+/// # Returns
+/// * `Err(String)` - If the command line is empty or badly quoted
+#[command]
+pub async fn set_external_editor(editor: Option<String>) -> Result<EditorConfig, String> {
+    let editor = editor.filter(|e| !e.trim().is_empty());
+    if let Some(editor) = &editor {
+        split_command(editor)?;
+    }
+    let config = EditorConfig { editor };
+    config.save(&get_config_dir()?)?;
+    log::info!("✏️ External editor set to {}", config.editor.as_deref().unwrap_or("the system default"));
+    Ok(config)
+}
+
+/// Opens a note in an external editor and watches it for the editor's saves.
+///
+/// `editor` overrides the editor chosen with `set_external_editor` for this
+/// call. The note stays watched until `unwatch_file`; if it was already
+/// watched, its baseline is reset to the content on disk, so the frontend
+/// should save pending edits first.
+///
+/// Security: Validates path is a markdown file within the workspace.
+///
+/// # Events
+/// Emits `open-file-changed` whenever the external editor saves the note
+#[command]
+pub async fn open_in_external_editor(
+    app_handle: AppHandle,
+    state: State<'_, AppState>,
+    path: String,
+    editor: Option<String>,
+) -> Result<EditorLaunch, String> {
+    if !EXTERNAL_EDITOR_SUPPORTED {
+        return Err("External editors are not supported on this platform".to_string());
+    }
+    let workspace = state.get_workspace_path()?;
+    let validated_path = validate_file_path(&path, &workspace, &["md"])
+        .map_err(|e| format!("Security error: {}", e))?;
+    if !validated_path.is_file() {
+        return Err(format!("File does not exist: {}", path));
+    }
+
+    let editor = match editor {
+        Some(editor) => Some(editor),
+        None => EditorConfig::load(&get_config_dir()?)?.editor,
+    };
+    let launch = external_editor::launch(editor.as_deref(), &validated_path)?;
+
+    // Watch before starting the editor, so a quick save is not missed
+    let was_watched = watch_open_file(&app_handle, &state, &validated_path)?;
+    if let Err(e) = start(&launch, &validated_path) {
+        if !was_watched {
+            let _ = state.open_files.unwatch(&validated_path);
+        }
+        return Err(e);
+    }
+
+    log::info!("✏️ Opened {:?} in {} (watching for saves)", validated_path, launch.program);
+    Ok(launch)
+}
+
+// Helper: Starts the editor detached from the app's stdio and reaps it
+fn start(launch: &EditorLaunch, file: &Path) -> Result<(), String> {
+    let mut command = Command::new(&launch.program);
+    command.args(&launch.args);
+    if let Some(dir) = file.parent() {
+        command.current_dir(dir);
+    }
+    if cfg!(windows) && launch.terminal {
+        // CREATE_NEW_CONSOLE: the terminal editor runs in a console of its own
+        #[cfg(windows)]
+        {
+            use std::os::windows::process::CommandExt;
+            command.creation_flags(0x0000_0010);
+        }
+    } else {
+        command.stdin(Stdio::null()).stdout(Stdio::null()).stderr(Stdio::null());
+    }
+
+    let mut child = command
+        .spawn()
+        .map_err(|e| format!("Failed to start {}: {}", launch.program, e))?;
+    let program = launch.program.clone();
+    std::thread::spawn(move || match child.wait() {
+        Ok(status) if !status.success() => log::warn!("⚠️ External editor {} exited with {}", program, status),
+        Ok(_) => {}
+        Err(e) => log::warn!("⚠️ Failed to wait for external editor {}: {}", program, e),
+    });
+    Ok(())
+}
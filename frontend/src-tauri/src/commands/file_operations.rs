@@ -6,18 +6,59 @@
 //! ## Security
 //! All file operations validate paths against the workspace root using
 //! `validate_path_within_workspace` before performing any file system operations.
-
-use tauri::{command, State};
+//! 
+//! ## Scoped Storage
+//! On Android, a workspace can be a SAF document tree (a `content://` URI).
+//! Listing, loading, saving, creating, deleting, renaming, moving, copying
+//! and existence checks then go through the scoped backend (see
+//! `crate::storage`) with the same arguments as on desktop.
+
+use tauri::{command, AppHandle, Manager, State};
+use rfd::{AsyncMessageDialog, MessageButtons, MessageDialogResult, MessageLevel};
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
 use serde::{Deserialize, Serialize};
+use crate::capture::{self, CaptureOptions, CapturePosition};
+use crate::diff::{self, Diff, DiffMode, TextDiff, DEFAULT_CONTEXT};
+use crate::events::{self, SaveFailedEvent};
+use crate::commands::dialogs::show;
+use crate::commands::hooks::{run_before_save, spawn_hooks};
+use crate::commands::offline::journal_save;
+use crate::commands::templates::render_template_file;
+use crate::activity::{ActivityEntry, ActivityKind};
+use crate::file_access::{self, AccessDiagnosis};
+use crate::favorites::Favorite;
+use crate::frecency::{FrequentFile, VisitKind};
+use crate::git::{self, GitFileStatus};
+use crate::headings::{self, HeadingReport, LineRange};
+use crate::hooks::HookEvent;
+use crate::ignore::IgnoreRules;
+use crate::lists::{self, ListReport};
+use crate::mindmap_meta;
+use crate::offline_journal;
+use crate::outline;
+use crate::references::{self, FootnoteReport, LinkNormalizeReport, LinkNotation};
+use crate::safe_write;
+use crate::save_queue::{Prepare, DEFAULT_DEBOUNCE_MS, MAX_DEBOUNCE_MS};
 use crate::state::AppState;
+use crate::storage::{is_document_uri, Storage, StorageEntry};
+use crate::sync_conflicts::{self, ConflictResolution, SyncConflict};
+use crate::text_stats::{self, TextAnalysis};
+use crate::workspace_settings::{ensure_tree_writable, ensure_writable};
+use crate::write_locks::DEFAULT_LOCK_WAIT_MS;
 use crate::utils::{
+    ValidationResult,
     validate_path_within_workspace,
     validate_file_path,
     validate_directory_path,
     sanitize_filename,
+    is_symlink_visible,
 };
+use crate::file_meta::{iso_8601, modified_iso, FilePermissions, FileTimes};
+use crate::notes::{collect_markdown_files, read_preview, relative_path, NotePreview};
+use crate::workspace_settings::WorkspaceSettings;
 
 /// Metadata about a file or directory
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -25,14 +66,31 @@ pub struct FileMetadata {
     pub name: String,
     pub path: String,
     pub size: u64,
+    /// Last modification time (RFC 3339, UTC)
     pub modified: String,
     pub is_directory: bool,
+    /// True if the entry is a symbolic link (size/type describe the target)
+    #[serde(default)]
+    pub is_symlink: bool,
+    #[serde(flatten)]
+    pub times: FileTimes,
+    #[serde(default)]
+    pub permissions: Option<FilePermissions>,
+    /// Title, excerpt, tags and task counts (notes only, when requested)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub preview: Option<NotePreview>,
+    /// Git state of the file, or the most important one inside a folder
+    /// (when requested and the workspace is in a git repository)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub git: Option<GitFileStatus>,
 }
 
 /// Workspace configuration stored in user's config directory
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct WorkspaceConfig {
     pub workspace_path: String,
+    /// Legacy flat list; ranking now comes from `get_frequent_files`
+    #[serde(default)]
     pub recent_files: Vec<String>,
     pub last_opened: Option<String>,
 }
@@ -47,13 +105,22 @@ pub struct WorkspaceConfig {
 /// - User explicitly chooses the folder via native dialog
 /// - The selected path becomes the new workspace root
 /// - AppState is updated with the new workspace path
+/// 
+/// The dialog is awaited off the async runtime and gives up after
+/// `DIALOG_TIMEOUT_SECS` (see `crate::dialogs`).
 #[command]
 pub async fn select_workspace_folder(state: State<'_, AppState>) -> Result<String, String> {
-    use rfd::FileDialog;
+    use rfd::AsyncFileDialog;
     
-    let folder = FileDialog::new()
-        .set_title("Select Workspace Folder")
-        .pick_folder();
+    // Mobile has no folder dialog; the system picker grants access to a folder
+    let folder = if cfg!(any(target_os = "android", target_os = "ios")) {
+        state.storage.pick_scoped_root()?.map(PathBuf::from)
+    } else {
+        let dialog = AsyncFileDialog::new().set_title("Select Workspace Folder");
+        crate::commands::dialogs::show(&state, async move {
+            dialog.pick_folder().await.map(|folder| folder.path().to_path_buf())
+        }).await?
+    };
     
     match folder {
         Some(path) => {
@@ -69,17 +136,49 @@ pub async fn select_workspace_folder(state: State<'_, AppState>) -> Result<Strin
     }
 }
 
+/// Preview of a listed note, if requested (unreadable notes get none)
+pub(crate) fn load_preview(path: &Path, metadata: &fs::Metadata, include_preview: bool) -> Option<NotePreview> {
+    if !include_preview || metadata.is_dir() {
+        return None;
+    }
+    read_preview(path)
+        .map_err(|e| log::warn!("⚠️ No preview for {:?}: {}", path, e))
+        .ok()
+}
+
+/// Git status of the workspace's repository, if requested
+pub(crate) fn git_status(workspace_root: &Path, include_git: Option<bool>) -> Option<git::RepoStatus> {
+    if !include_git.unwrap_or(false) {
+        return None;
+    }
+    git::repo_status(workspace_root)
+}
+
 /// Lists files and directories in the workspace.
 /// 
+/// Entries matched by `.mdreaderignore` (and `.gitignore`, unless the
+/// workspace turns that off) are left out.
+/// 
+/// With `include_preview`, each note also carries its title, a short text
+/// excerpt, tags and task counts, read from the head of the file. With
+/// `include_git`, entries carry their git status when the workspace is in a
+/// git repository (see `get_git_summary` for the branch).
+/// 
 /// Security: Validates that workspace_path matches the configured workspace.
 #[command]
 pub async fn list_workspace_files(
     state: State<'_, AppState>,
     workspace_path: String,
+    include_preview: Option<bool>,
+    include_git: Option<bool>,
 ) -> Result<Vec<FileMetadata>, String> {
     // Validate workspace path matches configured workspace
     let configured_workspace = state.get_workspace_path()?;
     
+    if is_document_uri(&configured_workspace) {
+        return list_scoped_files(&state, &workspace_path, &configured_workspace);
+    }
+    
     // Canonicalize both paths for comparison
     let requested_canonical = PathBuf::from(&workspace_path)
         .canonicalize()
@@ -101,15 +200,31 @@ pub async fn list_workspace_files(
     }
     
     let mut files = Vec::new();
+    let symlink_policy = WorkspaceSettings::load_symlink_policy(&configured_canonical);
+    let repo = git_status(&configured_canonical, include_git);
+    let mut ignore = IgnoreRules::load(&configured_canonical);
     
     let entries = fs::read_dir(&path)
         .map_err(|e| format!("Failed to read directory: {}", e))?;
     
     for entry in entries {
         let entry = entry.map_err(|e| format!("Failed to read entry: {}", e))?;
-        let metadata = entry.metadata()
+        let is_symlink = entry.file_type().map(|t| t.is_symlink()).unwrap_or(false);
+        
+        // Hide links the workspace policy does not allow (or broken links)
+        if is_symlink && !is_symlink_visible(&entry.path(), &configured_canonical, symlink_policy) {
+            continue;
+        }
+        
+        // Follows links so that linked folders are listed as folders
+        let metadata = fs::metadata(entry.path())
             .map_err(|e| format!("Failed to read metadata: {}", e))?;
         
+        // Hide what .gitignore / .mdreaderignore exclude
+        if ignore.is_ignored(&requested_canonical.join(entry.file_name()), metadata.is_dir()) {
+            continue;
+        }
+        
         let file_name = entry.file_name().to_string_lossy().to_string();
         
         // Skip hidden files and only show .md files and directories
@@ -118,8 +233,13 @@ pub async fn list_workspace_files(
                 name: file_name,
                 path: entry.path().to_string_lossy().to_string(),
                 size: metadata.len(),
-                modified: format!("{:?}", metadata.modified().unwrap_or(std::time::SystemTime::UNIX_EPOCH)),
+                modified: modified_iso(&metadata),
                 is_directory: metadata.is_dir(),
+                is_symlink,
+                times: FileTimes::from_metadata(&metadata),
+                permissions: Some(FilePermissions::from_metadata(&metadata)),
+                preview: load_preview(&entry.path(), &metadata, include_preview.unwrap_or(false)),
+                git: repo.as_ref().and_then(|r| r.status_of(&requested_canonical.join(entry.file_name()))),
             });
         }
     }
@@ -136,6 +256,64 @@ pub async fn list_workspace_files(
     Ok(files)
 }
 
+// Helper: Lists a folder of a scoped workspace like `list_workspace_files`
+// (without symlinks, ignore files, previews or git status)
+fn list_scoped_files(state: &AppState, folder: &str, workspace: &str) -> Result<Vec<FileMetadata>, String> {
+    let storage = state.storage.for_workspace(workspace)?;
+    let folder = storage.resolve(folder, workspace)
+        .map_err(|e| format!("Security error: {}", e))?;
+    
+    let mut files: Vec<FileMetadata> = storage.list(&folder)
+        .map_err(|e| format!("Failed to read directory: {}", e))?
+        .into_iter()
+        .filter(|entry| !entry.name.starts_with('.') && (entry.name.ends_with(".md") || entry.is_directory))
+        .map(scoped_metadata)
+        .collect();
+    
+    files.sort_by(|a, b| {
+        b.is_directory.cmp(&a.is_directory).then_with(|| a.name.to_lowercase().cmp(&b.name.to_lowercase()))
+    });
+    Ok(files)
+}
+
+// Helper: Listing entry of a scoped file
+fn scoped_metadata(entry: StorageEntry) -> FileMetadata {
+    let modified = std::time::UNIX_EPOCH + Duration::from_millis(entry.modified_ms.max(0) as u64);
+    FileMetadata {
+        name: entry.name,
+        path: entry.path,
+        size: entry.size,
+        modified: iso_8601(modified),
+        is_directory: entry.is_directory,
+        is_symlink: false,
+        times: FileTimes { modified_ms: Some(entry.modified_ms), ..FileTimes::default() },
+        permissions: None,
+        preview: None,
+        git: None,
+    }
+}
+
+// Helper: Whether saves must be journaled because the workspace folder
+// cannot be reached (scoped workspaces are never journaled)
+fn is_offline(workspace: &str) -> bool {
+    !is_document_uri(workspace) && !offline_journal::is_reachable(Path::new(workspace))
+}
+
+// Helper: Validates a path with `validate`, or with the scoped backend if
+// the workspace is a document tree
+fn resolve_path(
+    state: &AppState,
+    path: &str,
+    workspace: &str,
+    validate: impl FnOnce(&str, &str) -> ValidationResult<PathBuf>,
+) -> Result<PathBuf, String> {
+    if is_document_uri(workspace) {
+        state.storage.for_workspace(workspace)?.resolve(path, workspace)
+    } else {
+        validate(path, workspace).map_err(|e| e.to_string())
+    }
+}
+
 // ============================================================================
 // FILE OPERATIONS (All require workspace path validation)
 // ============================================================================
@@ -143,8 +321,19 @@ pub async fn list_workspace_files(
 /// Saves content to a file within the workspace.
 /// 
 /// Security: Validates file_path is within the configured workspace.
+/// 
+/// Returns a `Busy: ... retry after Nms` error if another write to the same
+/// file is still in progress, and a `Sync tool interfered: ...` error if a
+/// cloud sync client keeps the file locked (see `crate::safe_write`). If the
+/// workspace folder cannot be reached (ejected drive, offline share), the
+/// save is journaled and replayed once it is back (see
+/// `crate::offline_journal`). The workspace's `before_save` hooks run
+/// first and its `after_save` hooks after (see `crate::hooks`), and its
+/// lists are fixed if `lists.on_save` is set; content changed by a hook or
+/// list fix comes back to the editor as `open-file-changed`.
 #[command]
 pub async fn save_document_to_file(
+    app_handle: AppHandle,
     state: State<'_, AppState>,
     file_path: String,
     content: String,
@@ -158,35 +347,200 @@ pub async fn save_document_to_file(
         format!("{}.md", file_path)
     };
     
+    // An unreachable workspace journals the save for replay
+    if is_offline(&workspace) {
+        let offline_path = offline_journal::offline_note_path(&path, &workspace)
+            .map_err(|e| format!("Security error: {}", e))?;
+        let baseline = state.open_files.baseline(&offline_path);
+        return journal_save(&app_handle, &state, &workspace, &offline_path, &content, baseline);
+    }
+    
     // Validate path is within workspace
-    let validated_path = validate_file_path(&path, &workspace, &["md"])
+    let validated_path = resolve_path(&state, &path, &workspace, |p, w| validate_file_path(p, w, &["md"]))
         .map_err(|e| format!("Security error: {}", e))?;
     
-    // Write the file
-    fs::write(&validated_path, content)
-        .map_err(|e| format!("Failed to save file: {}", e))?;
+    // Refuse writes into read-only or archived folders
+    ensure_writable(&workspace, &validated_path)?;
+    
+    // Formatters and other before_save hooks may change the content, and
+    // lists are tidied if the workspace asks for it
+    let submitted = content.clone();
+    let content = {
+        let prepare = before_save(&app_handle, &workspace);
+        let path = validated_path.clone();
+        tauri::async_runtime::spawn_blocking(move || prepare(&path, content))
+            .await
+            .map_err(|e| format!("Failed to run hooks: {}", e))??
+    };
+    
+    // Serialize with other writers of the same file
+    let _guard = state.write_locks
+        .acquire_async(&validated_path, Duration::from_millis(DEFAULT_LOCK_WAIT_MS)).await?;
+    
+    // A direct save supersedes any debounced content still waiting
+    state.save_queue.cancel(&validated_path)?;
+    
+    // Write the file (not an external change for its watcher, unless a hook
+    // changed the content); if the workspace went away meanwhile, journal
+    // it instead
+    let baseline = state.open_files.baseline(&validated_path);
+    state.open_files.expect(&validated_path, &submitted);
+    if let Err(e) = state.storage.for_workspace(&workspace)?.write(&validated_path, &content) {
+        if !is_offline(&workspace) {
+            return Err(e);
+        }
+        return journal_save(&app_handle, &state, &workspace, &validated_path, &content, baseline);
+    }
+    state.frecency.record(&workspace, &validated_path, VisitKind::Save);
+    state.activity.record(&workspace, &validated_path, ActivityKind::Saved, None);
+    spawn_hooks(&app_handle, &workspace, HookEvent::AfterSave, Some(validated_path.clone()), None);
     
     log::info!("💾 Saved document: {:?}", validated_path);
     Ok(())
 }
 
+/// Queues a debounced save for a file within the workspace.
+/// 
+/// Rapid calls for the same file are coalesced: only the latest content is
+/// written, once `debounce_ms` passes without a new call (capped so that
+/// continuous typing still flushes every few seconds).
+/// 
+/// Security: Validates file_path is within the configured workspace.
+/// 
+/// Saves to an unreachable workspace are journaled instead, as with
+/// `save_document_to_file`. The `before_save` hooks and list fixes of
+/// `save_document_to_file` run when the queued content is written; if a
+/// hook refuses it, the save is dropped and `save-failed` is emitted to the
+/// windows showing the note.
+/// 
+/// # Returns
+/// * `Ok(true)` - If the content replaced an already pending save
+/// * `Ok(false)` - If a new pending save was started (or the save was journaled)
+#[command]
+pub async fn queue_save(
+    app_handle: AppHandle,
+    state: State<'_, AppState>,
+    file_path: String,
+    content: String,
+    debounce_ms: Option<u64>,
+) -> Result<bool, String> {
+    let workspace = state.get_workspace_path()?;
+    
+    // Ensure the file has .md extension
+    let path = if file_path.ends_with(".md") {
+        file_path.clone()
+    } else {
+        format!("{}.md", file_path)
+    };
+    
+    // An unreachable workspace journals the save for replay
+    if is_offline(&workspace) {
+        let offline_path = offline_journal::offline_note_path(&path, &workspace)
+            .map_err(|e| format!("Security error: {}", e))?;
+        let baseline = state.open_files.baseline(&offline_path);
+        journal_save(&app_handle, &state, &workspace, &offline_path, &content, baseline)?;
+        return Ok(false);
+    }
+    
+    // Validate path is within workspace
+    let validated_path = resolve_path(&state, &path, &workspace, |p, w| validate_file_path(p, w, &["md"]))
+        .map_err(|e| format!("Security error: {}", e))?;
+    
+    // Refuse writes into read-only or archived folders
+    ensure_writable(&workspace, &validated_path)?;
+    
+    // Scoped storage has no debounced writer; save right away
+    if is_document_uri(&workspace) {
+        state.storage.for_workspace(&workspace)?.write(&validated_path, &content)?;
+        state.frecency.record(&workspace, &validated_path, VisitKind::Save);
+        return Ok(false);
+    }
+    
+    let debounce = Duration::from_millis(
+        debounce_ms.unwrap_or(DEFAULT_DEBOUNCE_MS).min(MAX_DEBOUNCE_MS)
+    );
+    
+    state.frecency.record(&workspace, &validated_path, VisitKind::Save);
+    state.activity.record(&workspace, &validated_path, ActivityKind::Saved, None);
+    state.open_files.expect(&validated_path, &content);
+    let prepare = before_queued_save(&app_handle, &state, &workspace);
+    state.save_queue.queue_with(validated_path, content, debounce, Some(prepare))
+}
+
+// Helper: The pre-save pipeline of the editor's saves: `before_save` hooks,
+// then list fixes if `lists.on_save` is set (blocking, runs hooks)
+fn before_save(app_handle: &AppHandle, workspace: &str) -> Prepare {
+    let (app_handle, workspace) = (app_handle.clone(), workspace.to_string());
+    Arc::new(move |path: &Path, content: String| {
+        let content = run_before_save(&app_handle, &workspace, path, content)?;
+        let list_format = WorkspaceSettings::load(Path::new(&workspace))?.lists;
+        Ok(if list_format.on_save {
+            lists::fix_lists(&content, list_format.indent).0
+        } else {
+            content
+        })
+    })
+}
+
+// Helper: `before_save` for queued saves, reporting a refused save to the
+// windows showing the note (the save queue drops it)
+fn before_queued_save(app_handle: &AppHandle, state: &AppState, workspace: &str) -> Prepare {
+    let prepare = before_save(app_handle, workspace);
+    let (app_handle, windows) = (app_handle.clone(), state.windows.clone());
+    Arc::new(move |path: &Path, content: String| {
+        prepare(path, content).inspect_err(|error| {
+            let event = SaveFailedEvent {
+                path: path.to_string_lossy().to_string(),
+                error: error.clone(),
+                timestamp: chrono::Utc::now().to_rfc3339(),
+            };
+            for label in windows.event_targets(path) {
+                events::emit_to(&app_handle, &label, &event);
+            }
+        })
+    })
+}
+
+/// Immediately writes all pending debounced saves.
+/// 
+/// The frontend should call this before closing a document or the app.
+/// 
+/// # Returns
+/// * `Ok(usize)` - Number of files written
+#[command]
+pub async fn flush_pending_saves(state: State<'_, AppState>) -> Result<usize, String> {
+    let count = state.save_queue.flush_all_async().await?;
+    
+    if count > 0 {
+        log::info!("💾 Flushed {} pending saves", count);
+    }
+    Ok(count)
+}
+
 /// Loads content from a file within the workspace.
 /// 
 /// Security: Validates file_path is within the configured workspace.
 #[command]
 pub async fn load_document_from_file(
+    app_handle: AppHandle,
     state: State<'_, AppState>,
     file_path: String,
 ) -> Result<String, String> {
     let workspace = state.get_workspace_path()?;
     
     // Validate path is within workspace
-    let validated_path = validate_path_within_workspace(&file_path, &workspace)
+    let validated_path = resolve_path(&state, &file_path, &workspace, validate_path_within_workspace)
         .map_err(|e| format!("Security error: {}", e))?;
     
+    // Make sure a pending debounced save is on disk before reading
+    state.save_queue.flush_path_async(&validated_path).await?;
+    
     // Read the file
-    let content = fs::read_to_string(&validated_path)
+    let content = state.storage.for_workspace(&workspace)?
+        .read_to_string(&validated_path)
         .map_err(|e| format!("Failed to read file: {}", e))?;
+    state.frecency.record(&workspace, &validated_path, VisitKind::Open);
+    spawn_hooks(&app_handle, &workspace, HookEvent::AfterOpen, Some(validated_path.clone()), None);
     
     log::info!("📄 Loaded document: {:?}", validated_path);
     Ok(content)
@@ -194,18 +548,22 @@ pub async fn load_document_from_file(
 
 /// Creates a new markdown file within the workspace.
 /// 
-/// Security: Validates the target path is within the configured workspace.
+/// With `template`, the file starts as that template note rendered with the
+/// file name as `{{title}}` (see `crate::template`).
+/// 
+/// Security: Validates the target path (and template) is within the configured workspace.
 /// Uses exclusive file creation to prevent TOCTOU race conditions.
 #[command]
 pub async fn create_new_file(
     state: State<'_, AppState>,
     workspace_path: String,
     file_name: String,
+    template: Option<String>,
 ) -> Result<String, String> {
     let workspace = state.get_workspace_path()?;
     
     // Validate workspace_path matches configured workspace
-    let validated_workspace = validate_directory_path(&workspace_path, &workspace, true)
+    let validated_workspace = resolve_path(&state, &workspace_path, &workspace, |p, w| validate_directory_path(p, w, true))
         .map_err(|e| format!("Security error: {}", e))?;
     
     // Sanitize the filename to prevent path traversal via filename
@@ -217,31 +575,21 @@ pub async fn create_new_file(
     };
     
     let file_path = validated_workspace.join(&file_name_with_ext);
+    ensure_writable(&workspace, &file_path)?;
     
-    // Use OpenOptions for atomic file creation (prevents TOCTOU)
-    use std::io::Write;
-    use std::fs::OpenOptions;
-    
-    let initial_content = format!(
-        "# {}\n\nStart writing...",
-        file_name_with_ext.replace(".md", "")
-    );
-    
-    // create_new() fails if file already exists - atomic operation
-    let mut file = OpenOptions::new()
-        .write(true)
-        .create_new(true)  // Fails if exists - prevents TOCTOU
-        .open(&file_path)
-        .map_err(|e| {
-            if e.kind() == std::io::ErrorKind::AlreadyExists {
-                "File already exists".to_string()
-            } else {
-                format!("Failed to create file: {}", e)
-            }
-        })?;
+    let title = file_name_with_ext.replace(".md", "");
+    let initial_content = match &template {
+        Some(template) => render_template_file(&workspace, template, &title)?,
+        None => format!("# {}\n\nStart writing...", title),
+    };
     
-    file.write_all(initial_content.as_bytes())
-        .map_err(|e| format!("Failed to write file content: {}", e))?;
+    // Fails if the file already exists - atomic, prevents TOCTOU
+    let created = state.storage.for_workspace(&workspace)?
+        .create_new(&file_path, &initial_content)
+        .map_err(|e| format!("Failed to create file: {}", e))?;
+    if !created {
+        return Err("File already exists".to_string());
+    }
     
     log::info!("✨ Created new file: {:?}", file_path);
     Ok(file_path.to_string_lossy().to_string())
@@ -258,22 +606,178 @@ pub async fn delete_file(
     let workspace = state.get_workspace_path()?;
     
     // Validate path is within workspace
-    let validated_path = validate_path_within_workspace(&file_path, &workspace)
+    let validated_path = resolve_path(&state, &file_path, &workspace, validate_path_within_workspace)
         .map_err(|e| format!("Security error: {}", e))?;
     
-    // Only allow deleting markdown files
-    if let Some(ext) = validated_path.extension() {
-        if ext != "md" {
-            return Err("Can only delete markdown (.md) files".to_string());
+    ensure_writable(&workspace, &validated_path)?;
+    ensure_deletable(&validated_path)?;
+    
+    // Nothing queued for this file may be written back afterwards
+    let _guard = state.write_locks
+        .acquire_async(&validated_path, Duration::from_millis(DEFAULT_LOCK_WAIT_MS)).await?;
+    state.save_queue.cancel(&validated_path)?;
+    
+    state.storage.for_workspace(&workspace)?
+        .remove(&validated_path, false)
+        .map_err(|e| format!("Failed to delete file: {}", e))?;
+    mindmap_meta::follow_delete(&workspace, &validated_path);
+    state.frecency.follow_delete(&workspace, &validated_path);
+    state.favorites.follow_delete(&workspace, &validated_path);
+    
+    log::info!("🗑️ Deleted file: {:?}", validated_path);
+    Ok(())
+}
+
+// Helper: Only markdown files may be deleted
+fn ensure_deletable(path: &Path) -> Result<(), String> {
+    match path.extension() {
+        Some(ext) if ext == "md" => Ok(()),
+        Some(_) => Err("Can only delete markdown (.md) files".to_string()),
+        None => Err("File has no extension - cannot delete".to_string()),
+    }
+}
+
+/// Maximum number of overwrite passes accepted by `secure_delete_file`
+const MAX_SECURE_DELETE_PASSES: u32 = 7;
+
+/// Overwrites a file's contents before deleting it.
+/// 
+/// Each pass overwrites the full file length (zeros, ones, then pseudo-random
+/// data, repeating) and syncs to disk. The file is then truncated, renamed to
+/// a meaningless name and unlinked so the original name does not linger in
+/// the directory entry either.
+/// 
+/// Limitations (best-effort only):
+/// - SSDs with wear levelling may keep old blocks that are never overwritten
+/// - Copy-on-write and journaling filesystems (APFS, Btrfs, ZFS) write new
+///   blocks instead of replacing old ones
+/// - Snapshots, backups, sync tools and the OS trash may hold other copies
+/// 
+/// For strong guarantees, use full-disk encryption.
+/// 
+/// Not available for scoped storage, which cannot overwrite a file in place.
+/// 
+/// Security: Validates file_path is a markdown file within the configured
+/// workspace and not in a read-only or archived folder.
+/// 
+/// # Arguments
+/// * `file_path` - File to destroy
+/// * `passes` - Number of overwrite passes (1 to 7)
+#[command]
+pub async fn secure_delete_file(
+    state: State<'_, AppState>,
+    file_path: String,
+    passes: u32,
+) -> Result<(), String> {
+    let workspace = state.get_workspace_path()?;
+    
+    // Validate path is within workspace
+    let validated_path = resolve_path(&state, &file_path, &workspace, validate_path_within_workspace)
+        .map_err(|e| format!("Security error: {}", e))?;
+    
+    ensure_writable(&workspace, &validated_path)?;
+    ensure_deletable(&validated_path)?;
+    
+    if is_document_uri(&workspace) {
+        return Err("Secure delete is not available for this storage".to_string());
+    }
+    
+    let metadata = fs::symlink_metadata(&validated_path)
+        .map_err(|e| format!("Failed to read metadata: {}", e))?;
+    
+    if !metadata.is_file() {
+        return Err("Can only securely delete regular files".to_string());
+    }
+    
+    if passes == 0 || passes > MAX_SECURE_DELETE_PASSES {
+        return Err(format!("Passes must be between 1 and {}", MAX_SECURE_DELETE_PASSES));
+    }
+    
+    // Nothing queued for this file may be written back afterwards
+    let _guard = state.write_locks
+        .acquire_async(&validated_path, Duration::from_millis(DEFAULT_LOCK_WAIT_MS)).await?;
+    state.save_queue.cancel(&validated_path)?;
+    
+    shred_file(&validated_path, passes, state.storage.for_workspace(&workspace)?.as_ref())?;
+    mindmap_meta::follow_delete(&workspace, &validated_path);
+    state.frecency.follow_delete(&workspace, &validated_path);
+    state.favorites.follow_delete(&workspace, &validated_path);
+    
+    log::info!("🔥 Securely deleted file ({} passes): {:?}", passes, validated_path);
+    Ok(())
+}
+
+/// Overwrites, truncates, renames and removes a file; `storage` removes it
+fn shred_file(path: &PathBuf, passes: u32, storage: &dyn Storage) -> Result<(), String> {
+    use std::io::{Seek, SeekFrom, Write};
+    use std::fs::OpenOptions;
+    
+    const CHUNK_SIZE: usize = 64 * 1024;
+    
+    let len = fs::metadata(path)
+        .map_err(|e| format!("Failed to read metadata: {}", e))?
+        .len();
+    
+    let mut file = OpenOptions::new()
+        .write(true)
+        .open(path)
+        .map_err(|e| format!("Failed to open file for overwrite: {}", e))?;
+    
+    // xorshift seed; only needs to be unpredictable enough to not be a pattern
+    let mut seed = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_nanos() as u64)
+        .unwrap_or(0x9E37_79B9_7F4A_7C15)
+        | 1;
+    
+    let mut buffer = vec![0u8; CHUNK_SIZE];
+    
+    for pass in 0..passes {
+        file.seek(SeekFrom::Start(0))
+            .map_err(|e| format!("Failed to seek file: {}", e))?;
+        
+        let mut remaining = len;
+        while remaining > 0 {
+            let chunk = remaining.min(CHUNK_SIZE as u64) as usize;
+            
+            match pass % 3 {
+                0 => buffer[..chunk].fill(0x00),
+                1 => buffer[..chunk].fill(0xFF),
+                _ => {
+                    for byte in buffer[..chunk].iter_mut() {
+                        seed ^= seed << 13;
+                        seed ^= seed >> 7;
+                        seed ^= seed << 17;
+                        *byte = seed as u8;
+                    }
+                }
+            }
+            
+            file.write_all(&buffer[..chunk])
+                .map_err(|e| format!("Failed to overwrite file: {}", e))?;
+            remaining -= chunk as u64;
         }
-    } else {
-        return Err("File has no extension - cannot delete".to_string());
+        
+        file.sync_all()
+            .map_err(|e| format!("Failed to sync file: {}", e))?;
     }
     
-    fs::remove_file(&validated_path)
+    file.set_len(0)
+        .map_err(|e| format!("Failed to truncate file: {}", e))?;
+    file.sync_all()
+        .map_err(|e| format!("Failed to sync file: {}", e))?;
+    drop(file);
+    
+    // Rename before unlinking so the original file name is not left behind
+    let scrubbed = path.with_file_name(format!(".deleted-{:016x}", seed));
+    let final_path = match fs::rename(path, &scrubbed) {
+        Ok(()) => scrubbed,
+        Err(_) => path.clone(),
+    };
+    
+    storage.remove(&final_path, false)
         .map_err(|e| format!("Failed to delete file: {}", e))?;
     
-    log::info!("🗑️ Deleted file: {:?}", validated_path);
     Ok(())
 }
 
@@ -293,17 +797,30 @@ pub async fn rename_file(
     let workspace = state.get_workspace_path()?;
     
     // Validate both paths
-    let validated_old = validate_path_within_workspace(&old_path, &workspace)
+    let validated_old = resolve_path(&state, &old_path, &workspace, validate_path_within_workspace)
         .map_err(|e| format!("Security error (source): {}", e))?;
-    let validated_new = validate_path_within_workspace(&new_path, &workspace)
+    let validated_new = resolve_path(&state, &new_path, &workspace, validate_path_within_workspace)
         .map_err(|e| format!("Security error (destination): {}", e))?;
     
-    if !validated_old.exists() {
+    let storage = state.storage.for_workspace(&workspace)?;
+    if storage.stat(&validated_old)?.is_none() {
         return Err(format!("File does not exist: {}", old_path));
     }
     
-    fs::rename(&validated_old, &validated_new)
+    ensure_writable(&workspace, &validated_old)?;
+    ensure_writable(&workspace, &validated_new)?;
+    
+    // Pending edits move with the file; nothing may recreate the old name
+    let guard = state.write_locks
+        .acquire_async(&validated_old, Duration::from_millis(DEFAULT_LOCK_WAIT_MS)).await?;
+    let (_, _guard) = state.save_queue.flush_locked_async(&validated_old, guard).await?;
+    
+    storage.rename(&validated_old, &validated_new)
         .map_err(|e| format!("Failed to rename file: {}", e))?;
+    mindmap_meta::follow_move(&workspace, &validated_old, &validated_new);
+    state.frecency.follow_move(&workspace, &validated_old, &validated_new);
+    state.favorites.follow_move(&workspace, &validated_old, &validated_new);
+    state.windows.follow_move(&validated_old, &validated_new);
     
     log::info!("✅ Renamed: {:?} → {:?}", validated_old, validated_new);
     Ok(())
@@ -321,13 +838,24 @@ pub async fn rename_directory(
     let workspace = state.get_workspace_path()?;
     
     // Validate both paths
-    let validated_old = validate_directory_path(&old_path, &workspace, true)
+    let validated_old = resolve_path(&state, &old_path, &workspace, |p, w| validate_directory_path(p, w, true))
         .map_err(|e| format!("Security error (source): {}", e))?;
-    let validated_new = validate_path_within_workspace(&new_path, &workspace)
+    let validated_new = resolve_path(&state, &new_path, &workspace, validate_path_within_workspace)
         .map_err(|e| format!("Security error (destination): {}", e))?;
     
-    fs::rename(&validated_old, &validated_new)
+    ensure_tree_writable(&workspace, &validated_old)?;
+    ensure_writable(&workspace, &validated_new)?;
+    
+    // Pending edits of notes inside move with the folder
+    state.save_queue.flush_under_async(&validated_old).await?;
+    
+    state.storage.for_workspace(&workspace)?
+        .rename(&validated_old, &validated_new)
         .map_err(|e| format!("Failed to rename directory: {}", e))?;
+    mindmap_meta::follow_move(&workspace, &validated_old, &validated_new);
+    state.frecency.follow_move(&workspace, &validated_old, &validated_new);
+    state.favorites.follow_move(&workspace, &validated_old, &validated_new);
+    state.windows.follow_move(&validated_old, &validated_new);
     
     log::info!("✅ Renamed directory: {:?} → {:?}", validated_old, validated_new);
     Ok(())
@@ -346,27 +874,35 @@ pub async fn delete_directory(
     let workspace = state.get_workspace_path()?;
     
     // Validate path is within workspace
-    let validated_path = validate_directory_path(&path, &workspace, true)
+    let validated_path = resolve_path(&state, &path, &workspace, |p, w| validate_directory_path(p, w, true))
         .map_err(|e| format!("Security error: {}", e))?;
     
     // Prevent deleting the workspace root itself
-    let workspace_canonical = PathBuf::from(&workspace)
-        .canonicalize()
+    let workspace_canonical = resolve_path(&state, &workspace, &workspace, validate_path_within_workspace)
         .map_err(|_| "Invalid workspace")?;
     
     if validated_path == workspace_canonical {
         return Err("Cannot delete the workspace root directory".to_string());
     }
     
+    ensure_tree_writable(&workspace, &validated_path)?;
+    
+    // Nothing queued for notes inside may recreate the folder afterwards
+    state.save_queue.cancel_under(&validated_path)?;
+    
+    let storage = state.storage.for_workspace(&workspace)?;
     if recursive {
-        fs::remove_dir_all(&validated_path)
+        storage.remove(&validated_path, true)
             .map_err(|e| format!("Failed to delete directory recursively: {}", e))?;
         log::info!("🗑️ Deleted directory (recursive): {:?}", validated_path);
     } else {
-        fs::remove_dir(&validated_path)
+        storage.remove(&validated_path, false)
             .map_err(|e| format!("Failed to delete directory: {}", e))?;
         log::info!("🗑️ Deleted directory: {:?}", validated_path);
     }
+    mindmap_meta::follow_delete(&workspace, &validated_path);
+    state.frecency.follow_delete(&workspace, &validated_path);
+    state.favorites.follow_delete(&workspace, &validated_path);
     
     Ok(())
 }
@@ -383,16 +919,19 @@ pub async fn copy_file(
     let workspace = state.get_workspace_path()?;
     
     // Validate both paths
-    let validated_source = validate_path_within_workspace(&source_path, &workspace)
+    let validated_source = resolve_path(&state, &source_path, &workspace, validate_path_within_workspace)
         .map_err(|e| format!("Security error (source): {}", e))?;
-    let validated_dest = validate_path_within_workspace(&dest_path, &workspace)
+    let validated_dest = resolve_path(&state, &dest_path, &workspace, validate_path_within_workspace)
         .map_err(|e| format!("Security error (destination): {}", e))?;
     
-    if !validated_source.exists() {
+    let storage = state.storage.for_workspace(&workspace)?;
+    if storage.stat(&validated_source)?.is_none() {
         return Err(format!("Source file does not exist: {}", source_path));
     }
     
-    fs::copy(&validated_source, &validated_dest)
+    ensure_writable(&workspace, &validated_dest)?;
+    
+    storage.copy(&validated_source, &validated_dest)
         .map_err(|e| format!("Failed to copy file: {}", e))?;
     
     log::info!("📋 Copied: {:?} → {:?}", validated_source, validated_dest);
@@ -411,17 +950,30 @@ pub async fn move_file(
     let workspace = state.get_workspace_path()?;
     
     // Validate both paths
-    let validated_source = validate_path_within_workspace(&source_path, &workspace)
+    let validated_source = resolve_path(&state, &source_path, &workspace, validate_path_within_workspace)
         .map_err(|e| format!("Security error (source): {}", e))?;
-    let validated_dest = validate_path_within_workspace(&dest_path, &workspace)
+    let validated_dest = resolve_path(&state, &dest_path, &workspace, validate_path_within_workspace)
         .map_err(|e| format!("Security error (destination): {}", e))?;
     
-    if !validated_source.exists() {
+    let storage = state.storage.for_workspace(&workspace)?;
+    if storage.stat(&validated_source)?.is_none() {
         return Err(format!("Source file does not exist: {}", source_path));
     }
     
-    fs::rename(&validated_source, &validated_dest)
+    ensure_writable(&workspace, &validated_source)?;
+    ensure_writable(&workspace, &validated_dest)?;
+    
+    // Pending edits move with the file; nothing may recreate the old name
+    let guard = state.write_locks
+        .acquire_async(&validated_source, Duration::from_millis(DEFAULT_LOCK_WAIT_MS)).await?;
+    let (_, _guard) = state.save_queue.flush_locked_async(&validated_source, guard).await?;
+    
+    storage.rename(&validated_source, &validated_dest)
         .map_err(|e| format!("Failed to move file: {}", e))?;
+    mindmap_meta::follow_move(&workspace, &validated_source, &validated_dest);
+    state.frecency.follow_move(&workspace, &validated_source, &validated_dest);
+    state.favorites.follow_move(&workspace, &validated_source, &validated_dest);
+    state.windows.follow_move(&validated_source, &validated_dest);
     
     log::info!("📦 Moved: {:?} → {:?}", validated_source, validated_dest);
     Ok(())
@@ -438,8 +990,8 @@ pub async fn file_exists(
     let workspace = state.get_workspace_path()?;
     
     // Validate path is within workspace
-    match validate_path_within_workspace(&path, &workspace) {
-        Ok(validated_path) => Ok(validated_path.exists()),
+    match resolve_path(&state, &path, &workspace, validate_path_within_workspace) {
+        Ok(validated_path) => Ok(state.storage.for_workspace(&workspace)?.stat(&validated_path)?.is_some()),
         Err(_) => {
             // Path is outside workspace - return false, don't expose error details
             log::warn!("⚠️ file_exists called with path outside workspace: {}", path);
@@ -448,6 +1000,683 @@ pub async fn file_exists(
     }
 }
 
+/// Compares two notes line by line.
+/// 
+/// Used for conflict resolution, version history and comparing two notes
+/// side by side. `mode` defaults to a unified diff; in side-by-side mode,
+/// `word_level` (default true) marks the changed words of changed lines.
+/// Pending debounced saves are flushed first, so unsaved edits are compared.
+/// 
+/// Security: Validates both paths are markdown files within the workspace.
+/// 
+/// # Returns
+/// * `Unified` - `--- a/<path_a>` / `+++ b/<path_b>` diff text with counts
+/// * `SideBySide` - Rows of paired lines with counts
+#[command]
+pub async fn diff_files(
+    state: State<'_, AppState>,
+    path_a: String,
+    path_b: String,
+    mode: Option<DiffMode>,
+    word_level: Option<bool>,
+) -> Result<Diff, String> {
+    let workspace = state.get_workspace_path()?;
+    let root = Path::new(&workspace)
+        .canonicalize()
+        .map_err(|e| format!("Failed to resolve workspace: {}", e))?;
+    
+    let mut sides = Vec::with_capacity(2);
+    for path in [&path_a, &path_b] {
+        let validated_path = validate_file_path(path, &workspace, &["md"])
+            .map_err(|e| format!("Security error: {}", e))?;
+        state.save_queue.flush_path_async(&validated_path).await?;
+        let content = fs::read_to_string(&validated_path)
+            .map_err(|e| format!("Failed to read file: {}", e))?;
+        sides.push((relative_path(&root, &validated_path), content));
+    }
+    let (old, new) = (&sides[0], &sides[1]);
+    
+    let result = match mode.unwrap_or_default() {
+        DiffMode::Unified => Diff::Unified(diff::unified_diff(&old.1, &new.1, (&old.0, &new.0), DEFAULT_CONTEXT)),
+        DiffMode::SideBySide => {
+            let (rows, added, removed) = diff::side_by_side(&old.1, &new.1, word_level.unwrap_or(true));
+            Diff::SideBySide { rows, added, removed }
+        }
+    };
+    
+    log::info!("🔀 Compared {} with {}", old.0, new.0);
+    Ok(result)
+}
+
+// ============================================================================
+// PERMISSION RECOVERY (After a `Permission denied` save error)
+// ============================================================================
+
+/// Explains why a file cannot be written.
+/// 
+/// Called after a save fails with `Permission denied: ...`: reports whether
+/// the file or its folder is read-only, its permissions and owner, and on
+/// Windows the processes holding it open.
+/// 
+/// Security: Validates path is within the configured workspace.
+#[command]
+pub async fn diagnose_file_access(
+    state: State<'_, AppState>,
+    path: String,
+) -> Result<AccessDiagnosis, String> {
+    let workspace = state.get_workspace_path()?;
+    
+    let validated_path = validate_path_within_workspace(&path, &workspace)
+        .map_err(|e| format!("Security error: {}", e))?;
+    
+    let diagnosis = file_access::diagnose(&validated_path);
+    log::info!(
+        "🔐 Access of {:?}: read-only {}, folder read-only {}, {} lock holders",
+        validated_path,
+        diagnosis.read_only,
+        diagnosis.folder_read_only,
+        diagnosis.lock_holders.len()
+    );
+    Ok(diagnosis)
+}
+
+/// Makes a read-only file writable, after the user confirms in a native
+/// dialog.
+/// 
+/// Clears the Windows read-only attribute or adds the Unix owner write bit
+/// (`chmod u+w`). The confirmation is asked here rather than in the
+/// frontend, so the attribute is never cleared without the user's consent.
+/// 
+/// Security: Validates path is a file within the configured workspace and
+/// not in a read-only or archived folder of the workspace.
+/// 
+/// # Returns
+/// * `Ok(true)` - If the file is writable now
+/// * `Ok(false)` - If the user declined (the file stays read-only)
+#[command]
+pub async fn clear_readonly_attribute(
+    state: State<'_, AppState>,
+    path: String,
+) -> Result<bool, String> {
+    let workspace = state.get_workspace_path()?;
+    
+    let validated_path = validate_path_within_workspace(&path, &workspace)
+        .map_err(|e| format!("Security error: {}", e))?;
+    ensure_writable(&workspace, &validated_path)?;
+    if !file_access::is_read_only(&validated_path) {
+        return Ok(true);
+    }
+    
+    let name = validated_path
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let dialog = AsyncMessageDialog::new()
+        .set_level(MessageLevel::Warning)
+        .set_title("Make File Writable?")
+        .set_description(format!(
+            "\"{}\" is read-only, so changes cannot be saved. Remove the read-only attribute?",
+            name
+        ))
+        .set_buttons(MessageButtons::YesNo);
+    let confirmed = show(&state, async move {
+        Some(dialog.show().await == MessageDialogResult::Yes)
+    }).await?;
+    if confirmed != Some(true) {
+        log::info!("🔐 Kept {:?} read-only", validated_path);
+        return Ok(false);
+    }
+    
+    file_access::clear_readonly(&validated_path)?;
+    log::info!("🔓 Cleared read-only attribute of {:?}", validated_path);
+    Ok(true)
+}
+
+// ============================================================================
+// SYNC CONFLICTS (Conflict copies made by Dropbox, Syncthing, ...)
+// ============================================================================
+
+/// Lists the sync-tool conflict copies in the workspace.
+/// 
+/// Each copy is paired with the note it duplicates (see
+/// `crate::sync_conflicts` for the recognized names); compare the two with
+/// `diff_files` and settle them with `resolve_sync_conflict`.
+/// 
+/// Security: Only scans the configured workspace.
+#[command]
+pub async fn find_sync_conflicts(
+    state: State<'_, AppState>,
+) -> Result<Vec<SyncConflict>, String> {
+    let workspace = state.get_workspace_path()?;
+    let root = Path::new(&workspace);
+    
+    let files = collect_markdown_files(root, root)?;
+    let conflicts = sync_conflicts::find_conflicts(&files);
+    
+    log::info!("🔀 Found {} sync conflict copies", conflicts.len());
+    Ok(conflicts)
+}
+
+/// Result of resolving a sync conflict
+#[derive(Debug, Clone, Serialize)]
+pub struct ResolvedConflict {
+    /// The original note
+    pub path: String,
+    /// Change made to the original (empty when it was kept)
+    pub diff: TextDiff,
+    /// The conflict copy, now in the system trash
+    pub trashed: String,
+}
+
+/// Resolves a sync conflict and moves the conflict copy to the system trash.
+/// 
+/// The original keeps its content, takes the copy's, gets the copy's
+/// sections merged in, or gets content merged by hand in the frontend. If
+/// the original no longer exists it is recreated.
+/// 
+/// Security: Validates the copy is a markdown file within the workspace;
+/// the copy and the original must be writable.
+/// 
+/// # Arguments
+/// * `path` - The conflict copy
+/// * `resolution` - `keep_original`, `keep_copy`, `merge` or `replace`
+#[command]
+pub async fn resolve_sync_conflict(
+    state: State<'_, AppState>,
+    path: String,
+    resolution: ConflictResolution,
+) -> Result<ResolvedConflict, String> {
+    let workspace = state.get_workspace_path()?;
+    let root = Path::new(&workspace)
+        .canonicalize()
+        .map_err(|e| format!("Failed to resolve workspace: {}", e))?;
+    
+    let validated_copy = validate_file_path(&path, &workspace, &["md"])
+        .map_err(|e| format!("Security error: {}", e))?;
+    if !validated_copy.is_file() {
+        return Err(format!("File not found: {}", path));
+    }
+    let original = sync_conflicts::original_path(&validated_copy)
+        .ok_or_else(|| format!("Not a sync conflict copy: {}", path))?;
+    let validated_original = validate_file_path(&original.to_string_lossy(), &workspace, &["md"])
+        .map_err(|e| format!("Security error: {}", e))?;
+    ensure_writable(&workspace, &validated_original)?;
+    ensure_writable(&workspace, &validated_copy)?;
+    
+    state.save_queue.flush_path_async(&validated_original).await?;
+    state.save_queue.flush_path_async(&validated_copy).await?;
+    
+    let diff = {
+        let _guard = state.write_locks
+            .acquire_async(&validated_original, Duration::from_millis(DEFAULT_LOCK_WAIT_MS)).await?;
+        let exists = validated_original.is_file();
+        let current = if exists {
+            fs::read_to_string(&validated_original)
+                .map_err(|e| format!("Failed to read file: {}", e))?
+        } else {
+            String::new()
+        };
+        let copy = fs::read_to_string(&validated_copy)
+            .map_err(|e| format!("Failed to read file: {}", e))?;
+        
+        let content = sync_conflicts::resolved_content(&current, &copy, &resolution);
+        if !exists || content != current {
+            safe_write::write_file(&validated_original, &content)?;
+        }
+        let label = relative_path(&root, &validated_original);
+        diff::unified_diff(&current, &content, (&label, &label), DEFAULT_CONTEXT)
+    };
+    
+    {
+        let _guard = state.write_locks
+            .acquire_async(&validated_copy, Duration::from_millis(DEFAULT_LOCK_WAIT_MS)).await?;
+        state.save_queue.cancel(&validated_copy)?;
+        trash::delete(&validated_copy)
+            .map_err(|e| format!("Failed to move file to trash: {}", e))?;
+    }
+    state.outlines.close(&validated_copy)?;
+    mindmap_meta::follow_delete(&workspace, &validated_copy);
+    state.frecency.follow_delete(&workspace, &validated_copy);
+    state.favorites.follow_delete(&workspace, &validated_copy);
+    
+    log::info!(
+        "🔀 Resolved sync conflict {:?} into {:?} (+{} -{})",
+        validated_copy,
+        validated_original,
+        diff.added,
+        diff.removed
+    );
+    Ok(ResolvedConflict {
+        path: validated_original.to_string_lossy().to_string(),
+        diff,
+        trashed: validated_copy.to_string_lossy().to_string(),
+    })
+}
+
+// ============================================================================
+// SECTION OPERATIONS (Heading-anchored partial read/write)
+// ============================================================================
+
+/// Reads the block below a heading of a note.
+/// 
+/// The block runs to the next heading of the same or higher level, so
+/// subsections are included. `heading` matches titles case-insensitively;
+/// a `#` prefix (`"## Meeting Notes"`) also pins the level.
+/// 
+/// Security: Validates file_path is a markdown file within the workspace.
+#[command]
+pub async fn read_section(
+    state: State<'_, AppState>,
+    file_path: String,
+    heading: String,
+) -> Result<String, String> {
+    let workspace = state.get_workspace_path()?;
+    
+    let validated_path = validate_file_path(&file_path, &workspace, &["md"])
+        .map_err(|e| format!("Security error: {}", e))?;
+    
+    // Make sure a pending debounced save is on disk before reading
+    state.save_queue.flush_path_async(&validated_path).await?;
+    
+    let content = fs::read_to_string(&validated_path)
+        .map_err(|e| format!("Failed to read file: {}", e))?;
+    
+    outline::read_section(&content, &heading)
+}
+
+/// Replaces the block below a heading of a note, leaving the rest of the
+/// file untouched.
+/// 
+/// Security: Validates file_path is a markdown file within the workspace.
+/// 
+/// Returns a `Busy: ... retry after Nms` error if another write to the same
+/// file is still in progress.
+#[command]
+pub async fn write_section(
+    state: State<'_, AppState>,
+    file_path: String,
+    heading: String,
+    content: String,
+) -> Result<(), String> {
+    let workspace = state.get_workspace_path()?;
+    
+    let validated_path = validate_file_path(&file_path, &workspace, &["md"])
+        .map_err(|e| format!("Security error: {}", e))?;
+    
+    // Refuse writes into read-only or archived folders
+    ensure_writable(&workspace, &validated_path)?;
+    
+    // Pending debounced content must land first, or it would overwrite this edit
+    state.save_queue.flush_path_async(&validated_path).await?;
+    
+    let _guard = state.write_locks
+        .acquire_async(&validated_path, Duration::from_millis(DEFAULT_LOCK_WAIT_MS)).await?;
+    
+    let original = fs::read_to_string(&validated_path)
+        .map_err(|e| format!("Failed to read file: {}", e))?;
+    let updated = outline::write_section(&original, &heading, &content)?;
+    
+    safe_write::write_file(&validated_path, updated)?;
+    
+    log::info!("💾 Updated section {:?} of: {:?}", heading, validated_path);
+    Ok(())
+}
+
+// ============================================================================
+// REFERENCES (Reference-style links and footnotes)
+// ============================================================================
+
+/// Converts the links of a note to inline (`[text](url)`) or reference
+/// style (`[text][1]` with definitions at the end of the note).
+/// 
+/// Links to the same target share one definition; definitions that are
+/// inlined are removed. See `crate::references`.
+/// 
+/// Security: Validates file_path is a markdown file within the workspace.
+#[command]
+pub async fn normalize_links(
+    state: State<'_, AppState>,
+    file_path: String,
+    style: LinkNotation,
+) -> Result<LinkNormalizeReport, String> {
+    rewrite_note(&state, &file_path, |content| references::normalize_links(content, style)).await
+}
+
+/// Renumbers the footnotes of a note in order of first reference, moves
+/// their definitions to the end and removes unreferenced ones.
+/// 
+/// Security: Validates file_path is a markdown file within the workspace.
+#[command]
+pub async fn tidy_footnotes(
+    state: State<'_, AppState>,
+    file_path: String,
+) -> Result<FootnoteReport, String> {
+    rewrite_note(&state, &file_path, references::tidy_footnotes).await
+}
+
+// ============================================================================
+// HEADINGS (Level shifts)
+// ============================================================================
+
+/// Moves the headings of a note `delta` levels deeper (or up, if negative),
+/// clamped to `#` and `######`; with `range`, only the headings on those
+/// lines. A `<!-- toc -->` table of contents is rebuilt to match.
+/// 
+/// Security: Validates file_path is a markdown file within the workspace.
+/// 
+/// # Arguments
+/// * `range` - Lines (0-based, inclusive), e.g. a section from the outline
+#[command]
+pub async fn shift_headings(
+    state: State<'_, AppState>,
+    file_path: String,
+    delta: i32,
+    range: Option<LineRange>,
+) -> Result<HeadingReport, String> {
+    rewrite_note(&state, &file_path, |content| match headings::shift_headings(content, delta, range) {
+        Ok((updated, report)) => (updated, Ok(report)),
+        Err(e) => (content.to_string(), Err(e)),
+    }).await?
+}
+
+/// Keeps the first `#` heading of a note as its title and moves every
+/// heading after it one level down, if the note has several `#` headings.
+/// 
+/// Security: Validates file_path is a markdown file within the workspace.
+#[command]
+pub async fn enforce_single_h1(
+    state: State<'_, AppState>,
+    file_path: String,
+) -> Result<HeadingReport, String> {
+    rewrite_note(&state, &file_path, headings::enforce_single_h1).await
+}
+
+// ============================================================================
+// LISTS (Renumbering and indentation)
+// ============================================================================
+
+/// Result of `fix_list_numbering`
+#[derive(Debug, Clone, Serialize)]
+pub struct FixedLists {
+    /// The content with its lists fixed
+    pub content: String,
+    #[serde(flatten)]
+    pub report: ListReport,
+}
+
+/// Renumbers the ordered lists of a note, indents nested items `indent`
+/// spaces per level and repairs broken nesting (see `crate::lists`).
+/// 
+/// With `content` (the editor's text), the fixed content is only returned;
+/// otherwise the note at file_path is fixed and saved if it changed.
+/// 
+/// Security: Validates file_path is a markdown file within the workspace.
+/// 
+/// # Arguments
+/// * `indent` - Spaces per level; the workspace's `lists.indent` if not set
+#[command]
+pub async fn fix_list_numbering(
+    state: State<'_, AppState>,
+    file_path: Option<String>,
+    content: Option<String>,
+    indent: Option<usize>,
+) -> Result<FixedLists, String> {
+    let workspace = state.get_workspace_path()?;
+    let indent = match indent {
+        Some(indent) => indent,
+        None => WorkspaceSettings::load(Path::new(&workspace))?.lists.indent,
+    };
+    
+    let fix = |content: &str| {
+        let (content, report) = lists::fix_lists(content, indent);
+        (content.clone(), FixedLists { content, report })
+    };
+    match (content, file_path) {
+        (Some(content), _) => Ok(fix(&content).1),
+        (None, Some(file_path)) => rewrite_note(&state, &file_path, fix).await,
+        (None, None) => Err("Either file_path or content is required".to_string()),
+    }
+}
+
+// Helper: Rewrites a note with `rewrite`, saving only if it changed
+async fn rewrite_note<T>(
+    state: &AppState,
+    file_path: &str,
+    rewrite: impl FnOnce(&str) -> (String, T),
+) -> Result<T, String> {
+    let workspace = state.get_workspace_path()?;
+    
+    let validated_path = validate_file_path(file_path, &workspace, &["md"])
+        .map_err(|e| format!("Security error: {}", e))?;
+    ensure_writable(&workspace, &validated_path)?;
+    
+    // Pending debounced content must land first, or it would overwrite this edit
+    state.save_queue.flush_path_async(&validated_path).await?;
+    
+    let _guard = state.write_locks
+        .acquire_async(&validated_path, Duration::from_millis(DEFAULT_LOCK_WAIT_MS)).await?;
+    
+    let original = fs::read_to_string(&validated_path)
+        .map_err(|e| format!("Failed to read file: {}", e))?;
+    let (updated, report) = rewrite(&original);
+    if updated != original {
+        safe_write::write_file(&validated_path, updated)?;
+        log::info!("🔖 Rewrote: {:?}", validated_path);
+    }
+    Ok(report)
+}
+
+// ============================================================================
+// TEXT STATISTICS (Selections)
+// ============================================================================
+
+/// Counts words, characters, sentences and paragraphs of a piece of
+/// markdown (e.g. the editor's selection), with its readability and its
+/// reading and speaking time. Nothing is read from disk.
+#[command]
+pub async fn analyze_text(content: String) -> Result<TextAnalysis, String> {
+    Ok(text_stats::analyze_text(&content))
+}
+
+// ============================================================================
+// CAPTURE (Append/prepend entries)
+// ============================================================================
+
+/// Appends text to a note (or to one of its sections).
+/// 
+/// See `CaptureOptions` for timestamps, bullets, target heading and the
+/// template used to create a missing note.
+/// 
+/// Security: Validates file_path and template are markdown files within the workspace.
+#[command]
+pub async fn append_to_note(
+    app_handle: AppHandle,
+    file_path: String,
+    text: String,
+    options: Option<CaptureOptions>,
+) -> Result<(), String> {
+    capture_blocking(app_handle, file_path, text, options, CapturePosition::Append).await
+}
+
+/// Prepends text to a note, below its frontmatter (or to the start of a section).
+/// 
+/// Security: Validates file_path and template are markdown files within the workspace.
+#[command]
+pub async fn prepend_to_note(
+    app_handle: AppHandle,
+    file_path: String,
+    text: String,
+    options: Option<CaptureOptions>,
+) -> Result<(), String> {
+    capture_blocking(app_handle, file_path, text, options, CapturePosition::Prepend).await
+}
+
+// Helper: Runs `capture_entry` on the blocking pool, where it may wait for
+// a busy note
+async fn capture_blocking(
+    app_handle: AppHandle,
+    file_path: String,
+    text: String,
+    options: Option<CaptureOptions>,
+    position: CapturePosition,
+) -> Result<(), String> {
+    tauri::async_runtime::spawn_blocking(move || {
+        let state = app_handle.state::<AppState>();
+        capture_entry(&state, &file_path, &text, options.unwrap_or_default(), position).map(|_| ())
+    })
+    .await
+    .map_err(|e| format!("Failed to capture entry: {}", e))?
+}
+
+// Helper: Insert a captured entry, creating the note from a template if missing;
+// returns the note's validated path
+pub(crate) fn capture_entry(
+    state: &AppState,
+    file_path: &str,
+    text: &str,
+    options: CaptureOptions,
+    position: CapturePosition,
+) -> Result<PathBuf, String> {
+    let workspace = state.get_workspace_path()?;
+    
+    // Ensure the file has .md extension
+    let path = if file_path.ends_with(".md") {
+        file_path.to_string()
+    } else {
+        format!("{}.md", file_path)
+    };
+    
+    let validated_path = validate_file_path(&path, &workspace, &["md"])
+        .map_err(|e| format!("Security error: {}", e))?;
+    
+    // Refuse writes into read-only or archived folders
+    ensure_writable(&workspace, &validated_path)?;
+    
+    // Pending debounced content must land first, or it would overwrite this entry
+    state.save_queue.flush_path(&validated_path)?;
+    
+    let _guard = state.write_locks
+        .try_acquire(&validated_path, Duration::from_millis(DEFAULT_LOCK_WAIT_MS))?;
+    
+    let now = chrono::Local::now().naive_local();
+    let original = if validated_path.exists() {
+        fs::read_to_string(&validated_path)
+            .map_err(|e| format!("Failed to read file: {}", e))?
+    } else {
+        match &options.template {
+            Some(template) => {
+                let title = validated_path.file_stem()
+                    .map(|s| s.to_string_lossy().to_string())
+                    .unwrap_or_default();
+                render_template_file(&workspace, template, &title)?
+            }
+            None => String::new(),
+        }
+    };
+    
+    let entry = capture::format_entry(text, &options, now);
+    let updated = capture::insert_entry(&original, &entry, options.heading.as_deref(), position)?;
+    
+    if let Some(parent) = validated_path.parent() {
+        fs::create_dir_all(parent)
+            .map_err(|e| format!("Failed to create directory: {}", e))?;
+    }
+    safe_write::write_file(&validated_path, updated)?;
+    
+    log::info!("📝 Captured entry into: {:?}", validated_path);
+    Ok(validated_path)
+}
+
+// ============================================================================
+// ATTACHMENTS (Pasted and dropped files)
+// ============================================================================
+
+/// An attachment written to the workspace
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SavedAttachment {
+    /// Absolute path of the stored file
+    pub path: String,
+    /// Markdown to insert into the note
+    pub markdown: String,
+}
+
+/// Stores a pasted or dropped file as an attachment of a note.
+/// 
+/// The folder and the returned link follow the workspace attachment
+/// settings (see `set_attachment_settings`). An existing file with the same
+/// name is never overwritten; the new file gets a `-2`, `-3`, ... suffix.
+/// 
+/// Security: Validates note_path is a markdown file within the workspace;
+/// the attachment folder must be writable.
+#[command]
+pub async fn save_attachment(
+    app_handle: AppHandle,
+    note_path: String,
+    file_name: String,
+    data: Vec<u8>,
+) -> Result<SavedAttachment, String> {
+    // A busy target is waited for on the blocking pool
+    tauri::async_runtime::spawn_blocking(move || {
+        let state = app_handle.state::<AppState>();
+        store_attachment(&state, &note_path, &file_name, &data)
+    })
+    .await
+    .map_err(|e| format!("Failed to save attachment: {}", e))?
+}
+
+// Helper: Writes an attachment next to a note under a free name
+pub(crate) fn store_attachment(
+    state: &AppState,
+    note_path: &str,
+    file_name: &str,
+    data: &[u8],
+) -> Result<SavedAttachment, String> {
+    let workspace = state.get_workspace_path()?;
+    
+    let validated_note = validate_file_path(note_path, &workspace, &["md"])
+        .map_err(|e| format!("Security error: {}", e))?;
+    
+    let root = Path::new(&workspace)
+        .canonicalize()
+        .map_err(|e| format!("Failed to resolve workspace: {}", e))?;
+    let attachments = WorkspaceSettings::load(&root)?.attachments;
+    attachments.validate()?;
+    
+    let dir = attachments.attachment_dir(&root, &validated_note);
+    ensure_writable(&workspace, &dir)?;
+    fs::create_dir_all(&dir)
+        .map_err(|e| format!("Failed to create attachment folder: {}", e))?;
+    
+    let target = available_path(&dir, &sanitize_filename(file_name));
+    let _guard = state.write_locks
+        .try_acquire(&target, Duration::from_millis(DEFAULT_LOCK_WAIT_MS))?;
+    fs::write(&target, data)
+        .map_err(|e| format!("Failed to save attachment: {}", e))?;
+    
+    log::info!("📎 Saved attachment: {:?} ({} bytes)", target, data.len());
+    Ok(SavedAttachment {
+        markdown: attachments.link(&root, &validated_note, &target),
+        path: target.to_string_lossy().to_string(),
+    })
+}
+
+/// First `name`, `name-2`, `name-3`, ... that does not exist in `dir`
+pub(crate) fn available_path(dir: &Path, name: &str) -> PathBuf {
+    let candidate = dir.join(name);
+    if !candidate.exists() {
+        return candidate;
+    }
+    
+    let (stem, extension) = match name.rsplit_once('.') {
+        Some((stem, ext)) if !stem.is_empty() => (stem, format!(".{}", ext)),
+        _ => (name, String::new()),
+    };
+    (2..)
+        .map(|n| dir.join(format!("{}-{}{}", stem, n, extension)))
+        .find(|p| !p.exists())
+        .expect("unbounded counter always finds a free name")
+}
+
 // ============================================================================
 // WORKSPACE CONFIGURATION
 // ============================================================================
@@ -501,6 +1730,85 @@ pub async fn load_workspace_config() -> Result<Option<WorkspaceConfig>, String>
     Ok(Some(config))
 }
 
+/// Lists the files the user works on most, ranked by frecency
+/// (visit count weighted by how recent the opens and saves were).
+/// 
+/// Replaces the flat `recent_files` list of the workspace config for
+/// quick-open and the home screen.
+/// 
+/// # Arguments
+/// * `limit` - Maximum number of files (default 20)
+#[command]
+pub async fn get_frequent_files(
+    state: State<'_, AppState>,
+    limit: Option<usize>,
+) -> Result<Vec<FrequentFile>, String> {
+    let workspace = state.get_workspace_path()?;
+    
+    state.frecency.top(&workspace, limit.unwrap_or(20))
+}
+
+/// Stars a note or pins a folder, or removes it from the favorites if it
+/// already is one.
+/// 
+/// Favorites are stored per workspace in `.mdreader/favorites.json` and
+/// follow renames, moves and deletes made in the app.
+/// 
+/// Security: Validates path is a markdown file or a folder within the workspace.
+/// 
+/// # Returns
+/// * `Ok(bool)` - Whether the path is a favorite now
+#[command]
+pub async fn toggle_favorite(
+    state: State<'_, AppState>,
+    path: String,
+) -> Result<bool, String> {
+    let workspace = state.get_workspace_path()?;
+    
+    let validated_path = if Path::new(&path).is_dir() {
+        validate_directory_path(&path, &workspace, true)
+    } else {
+        validate_file_path(&path, &workspace, &["md"])
+    }
+    .map_err(|e| format!("Security error: {}", e))?;
+    if !validated_path.exists() {
+        return Err(format!("Path does not exist: {}", path));
+    }
+    
+    let favorite = state.favorites.toggle(&workspace, &validated_path)?;
+    log::info!("⭐ {} favorite: {:?}", if favorite { "Added" } else { "Removed" }, validated_path);
+    Ok(favorite)
+}
+
+/// Lists the starred notes and pinned folders of the workspace, in the
+/// order they were added. Favorites missing on disk are left out.
+#[command]
+pub async fn list_favorites(state: State<'_, AppState>) -> Result<Vec<Favorite>, String> {
+    let workspace = state.get_workspace_path()?;
+    
+    state.favorites.list(&workspace)
+}
+
+/// Lists recent activity in the workspace, newest first.
+/// 
+/// Covers notes created, modified or deleted on disk (while a directory is
+/// watched), saves from the editor, imports and, if the workspace is a git
+/// repository, commits.
+/// 
+/// # Arguments
+/// * `since` - Unix timestamp (seconds) of the oldest entry (default: all)
+/// * `limit` - Maximum number of entries (default 100)
+#[command]
+pub async fn get_activity(
+    state: State<'_, AppState>,
+    since: Option<i64>,
+    limit: Option<usize>,
+) -> Result<Vec<ActivityEntry>, String> {
+    let workspace = state.get_workspace_path()?;
+    
+    state.activity.feed(&workspace, since.unwrap_or(0), limit.unwrap_or(100))
+}
+
 // ============================================================================
 // TESTS
 // ============================================================================
@@ -511,6 +1819,7 @@ mod tests {
     use tempfile::TempDir;
     use std::fs::File;
     use crate::state::AppState;
+    use crate::storage::LocalStorage;
 
     // ========================================================================
     // TEST HELPERS
@@ -553,6 +1862,11 @@ mod tests {
             size: 100,
             modified: "2024-01-01".to_string(),
             is_directory: false,
+            is_symlink: false,
+            times: FileTimes::default(),
+            permissions: None,
+            preview: None,
+            git: None,
         };
         
         let json = serde_json::to_string(&metadata).expect("Failed to serialize");
@@ -679,6 +1993,11 @@ mod tests {
                     size: metadata.len(),
                     modified: "test".to_string(),
                     is_directory: metadata.is_dir(),
+                    is_symlink: false,
+                    times: FileTimes::default(),
+                    permissions: None,
+                    preview: None,
+                    git: None,
                 });
             }
         }
@@ -1262,4 +2581,56 @@ mod tests {
         );
         assert!(valid_result.is_ok(), "Regular file should be accessible");
     }
+
+    // ========================================================================
+    // SECURE DELETE TESTS
+    // ========================================================================
+
+    #[test]
+    fn test_shred_file_removes_file() {
+        let workspace = setup_test_workspace();
+        let secret = workspace.path().join("secret.md");
+        fs::write(&secret, "top secret content".repeat(1000)).expect("Should create file");
+        
+        shred_file(&secret, 3, &LocalStorage).expect("Shred should succeed");
+        
+        assert!(!secret.exists(), "File should be gone after shredding");
+        let leftovers = fs::read_dir(workspace.path())
+            .unwrap()
+            .filter_map(|e| e.ok())
+            .filter(|e| e.file_name().to_string_lossy().starts_with(".deleted-"))
+            .count();
+        assert_eq!(leftovers, 0, "No renamed leftovers should remain");
+    }
+
+    #[test]
+    fn test_shred_empty_file() {
+        let workspace = setup_test_workspace();
+        let empty = workspace.path().join("empty.md");
+        fs::write(&empty, "").expect("Should create file");
+        
+        shred_file(&empty, 1, &LocalStorage).expect("Shredding an empty file should succeed");
+        assert!(!empty.exists());
+    }
+
+    #[test]
+    fn test_only_markdown_files_are_deletable() {
+        assert!(ensure_deletable(Path::new("notes/plan.md")).is_ok());
+        assert!(ensure_deletable(Path::new("notes/config.json")).is_err());
+        assert!(ensure_deletable(Path::new("notes/Makefile")).is_err());
+    }
+
+    #[test]
+    fn test_available_path_never_overwrites() {
+        let workspace = setup_test_workspace();
+        let dir = workspace.path();
+        
+        assert_eq!(available_path(dir, "shot.png"), dir.join("shot.png"));
+        fs::write(dir.join("shot.png"), b"1").unwrap();
+        fs::write(dir.join("shot-2.png"), b"2").unwrap();
+        assert_eq!(available_path(dir, "shot.png"), dir.join("shot-3.png"));
+        
+        fs::write(dir.join("README"), b"").unwrap();
+        assert_eq!(available_path(dir, "README"), dir.join("README-2"));
+    }
 }
@@ -11,30 +11,40 @@
 //! - Proper cleanup when stopping watchers
 //! - No memory leaks from `std::mem::forget`
 //! - Graceful shutdown on app close
+//! 
+//! Open documents can instead be watched one file at a time
+//! (`watch_file`, see `crate::open_file`), which reports external changes
+//! as diffs.
 
-use tauri::{command, AppHandle, Emitter, State};
+use tauri::{command, AppHandle, State};
 use notify::{Config, Watcher, RecursiveMode, Result as NotifyResult, Event, EventKind, RecommendedWatcher};
+use std::path::Path;
 use std::sync::mpsc::channel;
 use serde::{Deserialize, Serialize};
+use crate::activity::ActivityKind;
+use crate::events::{self, FileChangeEvent, FileChangeKind, OpenFileChangedEvent};
+use crate::file_meta::{modified_iso, FilePermissions, FileTimes};
+use crate::ignore::IgnoreRules;
 use crate::state::AppState;
-use crate::utils::validate_directory_path;
-
-/// Event emitted when a file changes
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct FileChangeEvent {
-    pub path: String,
-    pub event_type: String,
-    pub timestamp: String,
-}
+use crate::utils::{validate_directory_path, validate_file_path};
+use crate::workspace_settings::{SETTINGS_DIR, SETTINGS_FILE};
 
 /// Metadata about a file
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct FileMetadata {
     pub path: String,
     pub size: u64,
+    /// Last modification time (RFC 3339, UTC)
     pub modified: String,
     pub is_file: bool,
     pub is_dir: bool,
+    /// True if `path` itself is a symbolic link
+    #[serde(default)]
+    pub is_symlink: bool,
+    #[serde(flatten)]
+    pub times: FileTimes,
+    #[serde(default)]
+    pub permissions: Option<FilePermissions>,
 }
 
 /// Information about an active watcher
@@ -56,7 +66,8 @@ pub struct WatcherInfo {
 /// * `directory_path` - Directory to watch (must be within workspace)
 /// 
 /// # Events
-/// Emits `file-changed` events to the frontend with `FileChangeEvent` payload
+/// Emits `file-changed` events (see `crate::events`) to the main
+/// window and, for a file open in a document window, to that window
 /// 
 /// # Returns
 /// * `Ok(())` - Watcher started successfully
@@ -73,18 +84,48 @@ pub async fn watch_directory(
     let validated_path = validate_directory_path(&directory_path, &workspace, true)
         .map_err(|e| format!("Security error: {}", e))?;
     
+    // A concurrent call for the same directory would race this one
+    let Some(_in_flight) = state.command_guard.enter("watch_directory", &directory_path) else {
+        return Ok(());
+    };
+    
     // Check if already watching this directory
     if state.has_watcher(&directory_path) {
         log::info!("👀 Already watching directory: {}", directory_path);
         return Ok(());
     }
     
+    start_directory_watcher(&app_handle, &state, directory_path, &validated_path, workspace)
+}
+
+// Helper: How a watcher event is reported, if at all (access and other
+// events are not)
+fn change_kind(kind: &EventKind) -> Option<(FileChangeKind, ActivityKind)> {
+    match kind {
+        EventKind::Create(_) => Some((FileChangeKind::Created, ActivityKind::Created)),
+        EventKind::Modify(_) => Some((FileChangeKind::Modified, ActivityKind::Modified)),
+        EventKind::Remove(_) => Some((FileChangeKind::Deleted, ActivityKind::Deleted)),
+        _ => None,
+    }
+}
+
+// Helper: Creates, registers and starts the watcher of a validated directory
+pub(crate) fn start_directory_watcher(
+    app_handle: &AppHandle,
+    state: &AppState,
+    directory_path: String,
+    validated_path: &Path,
+    workspace: String,
+) -> Result<(), String> {
     // Create a channel to receive the events
     let (tx, rx) = channel::<Event>();
     
     // Clone values for the closure
     let dir_path_clone = directory_path.clone();
     let app_handle_clone = app_handle.clone();
+    let windows = state.windows.clone();
+    let activity = state.activity.clone();
+    let mut ignore = IgnoreRules::load(Path::new(&workspace));
     
     // Create watcher with custom config
     let config = Config::default()
@@ -111,7 +152,7 @@ pub async fn watch_directory(
             .map_err(|e| format!("Failed to lock watchers: {}", e))?;
         
         if let Some(entry) = watchers.get_mut(&directory_path) {
-            entry.watcher.watch(&validated_path, RecursiveMode::Recursive)
+            entry.watcher.watch(validated_path, RecursiveMode::Recursive)
                 .map_err(|e| {
                     // Clean up on failure
                     drop(watchers);
@@ -126,38 +167,36 @@ pub async fn watch_directory(
     // Spawn a task to handle events
     std::thread::spawn(move || {
         for event in rx {
-            // Filter for relevant events
-            match event.kind {
-                EventKind::Create(_) | EventKind::Modify(_) | EventKind::Remove(_) => {
-                    // Get the first path (usually there's only one)
-                    if let Some(path) = event.paths.first() {
-                        let path_str = path.to_string_lossy().to_string();
-                        
-                        // Only notify for .md files
-                        if path_str.ends_with(".md") {
-                            let event_type = match event.kind {
-                                EventKind::Create(_) => "created",
-                                EventKind::Modify(_) => "modified",
-                                EventKind::Remove(_) => "deleted",
-                                _ => "unknown",
-                            };
-                            
-                            let change_event = FileChangeEvent {
-                                path: path_str.clone(),
-                                event_type: event_type.to_string(),
-                                timestamp: chrono::Utc::now().to_rfc3339(),
-                            };
-                            
-                            log::info!("📝 File change detected: {} - {}", event_type, path_str);
-                            
-                            // Emit event to frontend
-                            if let Err(e) = app_handle_clone.emit("file-changed", &change_event) {
-                                log::error!("Failed to emit file-changed event: {}", e);
-                            }
-                        }
+            // Only creations, changes and removals are reported
+            let Some((event_type, kind)) = change_kind(&event.kind) else {
+                continue;
+            };
+            // Get the first path (usually there's only one)
+            if let Some(path) = event.paths.first() {
+                let path_str = path.to_string_lossy().to_string();
+                
+                // Edited ignore files (or the gitignore toggle) change what is ignored
+                if IgnoreRules::is_ignore_file(path) || path.ends_with(Path::new(SETTINGS_DIR).join(SETTINGS_FILE)) {
+                    ignore = IgnoreRules::load(Path::new(&workspace));
+                }
+                
+                // Only notify for .md files that are not ignored
+                if path_str.ends_with(".md") && !ignore.is_ignored(path, false) {
+                    activity.record(&workspace, path, kind, None);
+                    
+                    let change_event = FileChangeEvent {
+                        path: path_str.clone(),
+                        event_type,
+                        timestamp: chrono::Utc::now().to_rfc3339(),
+                    };
+                    
+                    log::info!("📝 File change detected: {:?} - {}", event_type, path_str);
+                    
+                    // Emit event to the main window and the window owning the file
+                    for label in windows.event_targets(path) {
+                        events::emit_to(&app_handle_clone, &label, &change_event);
                     }
                 }
-                _ => {}
             }
         }
         
@@ -195,6 +234,84 @@ pub async fn stop_watching(
     Ok(removed)
 }
 
+/// Start watching a single open document for external changes.
+/// 
+/// Cheaper than `watch_directory` for the file being edited. Changes made
+/// outside the editor are reported with the new content and a unified diff
+/// against the content the editor last saw or saved, so it can offer to
+/// reload, keep its version or merge. Saves through `save_document_to_file`
+/// and `queue_save` are not reported. Watching a file again resets its
+/// baseline to the content on disk.
+/// 
+/// Security: Validates file_path is a markdown file within the workspace.
+/// 
+/// # Events
+/// Emits `open-file-changed` events (see `crate::events`) to the main
+/// window and, for a file open in a document window, to that window
+#[command]
+pub async fn watch_file(
+    app_handle: AppHandle,
+    state: State<'_, AppState>,
+    file_path: String,
+) -> Result<(), String> {
+    let workspace = state.get_workspace_path()?;
+    
+    let validated_path = validate_file_path(&file_path, &workspace, &["md"])
+        .map_err(|e| format!("Security error: {}", e))?;
+    
+    watch_open_file(&app_handle, &state, &validated_path)?;
+    log::info!("👀 Started watching file: {:?}", validated_path);
+    Ok(())
+}
+
+/// Watches a validated file, reporting external changes with
+/// `open-file-changed`
+/// 
+/// # Returns
+/// * `Ok(true)` - If the file was already watched (its baseline is reset)
+pub(crate) fn watch_open_file(
+    app_handle: &AppHandle,
+    state: &AppState,
+    validated_path: &Path,
+) -> Result<bool, String> {
+    let app_handle = app_handle.clone();
+    let windows = state.windows.clone();
+    state.open_files.watch(validated_path, move |change| {
+        let event = OpenFileChangedEvent {
+            path: change.path.to_string_lossy().to_string(),
+            content: change.content,
+            diff: change.diff,
+            timestamp: chrono::Utc::now().to_rfc3339(),
+        };
+        
+        log::info!("📝 External change to open file: {} (+{} -{})", event.path, event.diff.added, event.diff.removed);
+        for label in windows.event_targets(&change.path) {
+            events::emit_to(&app_handle, &label, &event);
+        }
+    })
+}
+
+/// Stop watching a single open document.
+/// 
+/// # Returns
+/// * `Ok(bool)` - True if the file was being watched
+#[command]
+pub async fn unwatch_file(
+    state: State<'_, AppState>,
+    file_path: String,
+) -> Result<bool, String> {
+    let workspace = state.get_workspace_path()?;
+    
+    let validated_path = validate_file_path(&file_path, &workspace, &["md"])
+        .map_err(|e| format!("Security error: {}", e))?;
+    
+    let removed = state.open_files.unwatch(&validated_path)?;
+    if removed {
+        log::info!("🛑 Stopped watching file: {:?}", validated_path);
+    }
+    Ok(removed)
+}
+
 /// Get file metadata (last modified time, size, etc.)
 /// 
 /// Security: Validates that file_path is within the configured workspace.
@@ -216,16 +333,20 @@ pub async fn get_file_metadata(
     let metadata = std::fs::metadata(&validated_path)
         .map_err(|e| format!("Failed to read metadata: {}", e))?;
     
-    let modified = metadata.modified()
-        .map(|t| format!("{:?}", t))
-        .unwrap_or_else(|_| "Unknown".to_string());
+    // The validated path is canonical, so check the path as requested
+    let is_symlink = std::fs::symlink_metadata(&file_path)
+        .map(|m| m.file_type().is_symlink())
+        .unwrap_or(false);
     
     Ok(FileMetadata {
         path: file_path,
         size: metadata.len(),
-        modified,
+        modified: modified_iso(&metadata),
         is_file: metadata.is_file(),
         is_dir: metadata.is_dir(),
+        is_symlink,
+        times: FileTimes::from_metadata(&metadata),
+        permissions: Some(FilePermissions::from_metadata(&metadata)),
     })
 }
 
@@ -253,14 +374,14 @@ pub async fn list_active_watchers(
     Ok(watchers)
 }
 
-/// Stop all active watchers.
+/// Stop all active watchers, including those of open documents.
 /// 
 /// This is called during app shutdown or workspace change.
 #[command]
 pub async fn stop_all_watchers(
     state: State<'_, AppState>,
 ) -> Result<usize, String> {
-    let count = state.clear_all_watchers()?;
+    let count = state.clear_all_watchers()? + state.open_files.clear()?;
     log::info!("🧹 Stopped {} watchers", count);
     Ok(count)
 }
@@ -277,7 +398,7 @@ mod tests {
     fn test_file_change_event_serialization() {
         let event = FileChangeEvent {
             path: "/test/file.md".to_string(),
-            event_type: "modified".to_string(),
+            event_type: FileChangeKind::Modified,
             timestamp: "2024-01-01T00:00:00Z".to_string(),
         };
         
@@ -290,14 +411,29 @@ mod tests {
         assert_eq!(parsed.event_type, event.event_type);
     }
 
+    #[test]
+    fn test_change_kind_reports_only_changes() {
+        use notify::event::{AccessKind, CreateKind, ModifyKind, RemoveKind};
+        
+        assert_eq!(change_kind(&EventKind::Create(CreateKind::File)).map(|k| k.0), Some(FileChangeKind::Created));
+        assert_eq!(change_kind(&EventKind::Modify(ModifyKind::Any)).map(|k| k.0), Some(FileChangeKind::Modified));
+        assert_eq!(change_kind(&EventKind::Remove(RemoveKind::File)).map(|k| k.0), Some(FileChangeKind::Deleted));
+        assert!(change_kind(&EventKind::Access(AccessKind::Any)).is_none());
+        assert!(change_kind(&EventKind::Any).is_none());
+        assert!(change_kind(&EventKind::Other).is_none());
+    }
+
     #[test]
     fn test_file_metadata_serialization() {
         let metadata = FileMetadata {
             path: "/test/file.md".to_string(),
             size: 1024,
-            modified: "SystemTime { ... }".to_string(),
+            modified: "2024-05-17T09:30:00.000Z".to_string(),
             is_file: true,
             is_dir: false,
+            is_symlink: false,
+            times: FileTimes { modified_ms: Some(1_715_938_200_000), ..Default::default() },
+            permissions: None,
         };
         
         let json = serde_json::to_string(&metadata).expect("Failed to serialize");
@@ -308,6 +444,7 @@ mod tests {
         assert_eq!(parsed.size, 1024);
         assert!(parsed.is_file);
         assert!(!parsed.is_dir);
+        assert_eq!(parsed.times.modified_ms, Some(1_715_938_200_000));
     }
 
     #[test]
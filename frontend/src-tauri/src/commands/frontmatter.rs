@@ -0,0 +1,316 @@
+//! Frontmatter Commands
+//!
+//! This module provides Tauri commands that update the frontmatter fields of
+//! a note without touching its body, and the note status workflow built on
+//! them: the frontmatter `status` field takes one of the workspace's status
+//! values (`draft`, `in-progress`, `done` unless configured otherwise) and
+//! `list_notes_by_status` groups the vault into board columns. Notes can
+//! also be checked against the workspace's frontmatter schema, get tag
+//! suggestions from their content, and keep answering to old names through
+//! `aliases`.
+//!
+//! ## Security
+//! Note paths are validated against the configured workspace; updates
+//! require the note to be writable.
+
+use std::collections::BTreeSet;
+use std::fs;
+use std::path::Path;
+use std::time::Duration;
+use serde_json::{Map, Value};
+use tauri::{command, State};
+use crate::activity::ActivityKind;
+use crate::board::{build_board, configured_status, StatusColumn};
+use crate::frontmatter;
+use crate::metadata_schema::{validate_fields, SchemaViolation};
+use crate::notes::relative_path;
+use crate::safe_write;
+use crate::state::AppState;
+use crate::tag_suggest::{self, Corpus, TagSuggestion, DEFAULT_SUGGESTION_LIMIT};
+use crate::utils::validate_file_path;
+use crate::workspace_settings::{ensure_writable, WorkspaceSettings};
+use crate::write_locks::DEFAULT_LOCK_WAIT_MS;
+
+/// Sets and removes frontmatter fields of a note.
+///
+/// Fields in `set` are added or replaced, fields in `remove` are dropped;
+/// the body is kept as is. A note left without fields loses its
+/// frontmatter block.
+///
+/// Security: Validates file_path is a markdown file within the workspace.
+///
+/// # Returns
+/// * `Ok(Map)` - The note's fields after the update
+#[command]
+pub async fn update_frontmatter(
+    state: State<'_, AppState>,
+    file_path: String,
+    set: Map<String, Value>,
+    remove: Option<Vec<String>>,
+) -> Result<Map<String, Value>, String> {
+    update_fields(&state, &file_path, |fields| {
+        for key in remove.unwrap_or_default() {
+            fields.remove(&key);
+        }
+        for (key, value) in set {
+            fields.insert(key, value);
+        }
+        Ok(())
+    }).await
+}
+
+/// Sets (or with `None`, clears) the status of a note.
+///
+/// The status must be one of the workspace's status values (matched
+/// case-insensitively and stored as configured).
+///
+/// Security: Validates file_path is a markdown file within the workspace.
+#[command]
+pub async fn set_note_status(
+    state: State<'_, AppState>,
+    file_path: String,
+    status: Option<String>,
+) -> Result<(), String> {
+    let workspace = state.get_workspace_path()?;
+    let statuses = WorkspaceSettings::load(Path::new(&workspace))?.status_values();
+
+    let status = match status.as_deref().map(str::trim).filter(|s| !s.is_empty()) {
+        Some(status) => Some(
+            configured_status(&statuses, status)
+                .cloned()
+                .ok_or_else(|| format!("Unknown status: {} (expected one of {})", status, statuses.join(", ")))?,
+        ),
+        None => None,
+    };
+
+    update_fields(&state, &file_path, |fields| {
+        match &status {
+            Some(status) => fields.insert("status".to_string(), Value::String(status.clone())),
+            None => fields.remove("status"),
+        };
+        Ok(())
+    }).await?;
+
+    log::info!("🏷️ Status of {} set to {:?}", file_path, status);
+    Ok(())
+}
+
+/// Groups the workspace's notes by status for a board view.
+///
+/// Returns one column per status value in the configured order, followed
+/// by columns for statuses found in notes but not configured. Notes without
+/// a status are not listed. Computed from the metadata cache.
+#[command]
+pub async fn list_notes_by_status(state: State<'_, AppState>) -> Result<Vec<StatusColumn>, String> {
+    let workspace = state.get_workspace_path()?;
+    let statuses = WorkspaceSettings::load(Path::new(&workspace))?.status_values();
+
+    state.metadata.with_fresh(&workspace, |cache| build_board(cache, &statuses))
+}
+
+/// Checks the frontmatter of a note against the workspace's schema.
+///
+/// Security: Validates file_path is a markdown file within the workspace.
+///
+/// # Returns
+/// * `Ok(Vec<SchemaViolation>)` - Empty when the note matches the schema
+#[command]
+pub async fn validate_note_metadata(
+    state: State<'_, AppState>,
+    file_path: String,
+) -> Result<Vec<SchemaViolation>, String> {
+    let workspace = state.get_workspace_path()?;
+    let schema = WorkspaceSettings::load(Path::new(&workspace))?.metadata_schema;
+
+    let validated_path = validate_file_path(&file_path, &workspace, &["md"])
+        .map_err(|e| format!("Security error: {}", e))?;
+    state.save_queue.flush_path_async(&validated_path).await?;
+
+    let content = fs::read_to_string(&validated_path)
+        .map_err(|e| format!("Failed to read file: {}", e))?;
+    let fields = frontmatter::split(&content).0.unwrap_or_default();
+    let root = Path::new(&workspace).canonicalize()
+        .map_err(|e| format!("Failed to resolve workspace: {}", e))?;
+
+    Ok(validate_fields(&schema, &relative_path(&root, &validated_path), &fields))
+}
+
+/// Checks every note of the workspace against the workspace's schema.
+///
+/// Computed from the metadata cache.
+///
+/// # Returns
+/// * `Ok(Vec<SchemaViolation>)` - Violations sorted by note path
+#[command]
+pub async fn find_schema_violations(state: State<'_, AppState>) -> Result<Vec<SchemaViolation>, String> {
+    let workspace = state.get_workspace_path()?;
+    let schema = WorkspaceSettings::load(Path::new(&workspace))?.metadata_schema;
+    if schema.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let violations = state.metadata.with_fresh(&workspace, |cache| {
+        cache.notes
+            .iter()
+            .flat_map(|(path, note)| validate_fields(&schema, path, &note.fields))
+            .collect::<Vec<_>>()
+    })?;
+
+    log::info!("📐 Schema check: {} violations", violations.len());
+    Ok(violations)
+}
+
+/// Proposes tags for a note from its content.
+///
+/// Words of the note are ranked by TF-IDF against the workspace's notes;
+/// tags the workspace already uses are preferred and tags the note already
+/// has are left out. Accepted suggestions can be written with `add_note_tags`.
+///
+/// Security: Validates file_path is a markdown file within the workspace.
+///
+/// # Returns
+/// * `Ok(Vec<TagSuggestion>)` - Up to `limit` (default 10) candidates, best first
+#[command]
+pub async fn suggest_tags(
+    state: State<'_, AppState>,
+    file_path: String,
+    limit: Option<usize>,
+) -> Result<Vec<TagSuggestion>, String> {
+    let workspace = state.get_workspace_path()?;
+
+    let validated_path = validate_file_path(&file_path, &workspace, &["md"])
+        .map_err(|e| format!("Security error: {}", e))?;
+    state.save_queue.flush_path_async(&validated_path).await?;
+    let content = fs::read_to_string(&validated_path)
+        .map_err(|e| format!("Failed to read file: {}", e))?;
+    let (fields, body) = frontmatter::split(&content);
+    let current_tags = frontmatter::tags(&fields.unwrap_or_default());
+
+    let (notes, vault_tags) = state.metadata.with_fresh(&workspace, |cache| {
+        let notes: Vec<String> = cache.notes.keys().cloned().collect();
+        let tags: BTreeSet<String> = cache.notes.values().flat_map(|note| note.tags.iter().cloned()).collect();
+        (notes, tags)
+    })?;
+
+    let root = Path::new(&workspace);
+    let mut corpus = Corpus::default();
+    for note in &notes {
+        if let Ok(content) = fs::read_to_string(root.join(note)) {
+            corpus.add(frontmatter::split(&content).1);
+        }
+    }
+
+    Ok(tag_suggest::suggest_tags(
+        body,
+        &corpus,
+        &vault_tags,
+        &current_tags,
+        limit.unwrap_or(DEFAULT_SUGGESTION_LIMIT),
+    ))
+}
+
+/// Adds tags to the frontmatter `tags` list of a note.
+///
+/// Tags the note already has (compared case-insensitively) are skipped; a
+/// `tag` field or comma-separated `tags` string is rewritten as a list.
+///
+/// Security: Validates file_path is a markdown file within the workspace.
+///
+/// # Returns
+/// * `Ok(Vec<String>)` - The note's tags after the update
+#[command]
+pub async fn add_note_tags(
+    state: State<'_, AppState>,
+    file_path: String,
+    tags: Vec<String>,
+) -> Result<Vec<String>, String> {
+    let fields = update_fields(&state, &file_path, |fields| {
+        let mut current = frontmatter::tags(fields);
+        for tag in &tags {
+            let tag = tag.trim().trim_start_matches('#');
+            if tag.is_empty() || tag.contains(char::is_whitespace) || tag.contains(',') {
+                return Err(format!("Invalid tag: {:?}", tag));
+            }
+            if !current.iter().any(|t| t.eq_ignore_ascii_case(tag)) {
+                current.push(tag.to_string());
+            }
+        }
+        fields.remove("tag");
+        fields.insert("tags".to_string(), Value::Array(current.into_iter().map(Value::String).collect()));
+        Ok(())
+    }).await?;
+
+    Ok(frontmatter::tags(&fields))
+}
+
+/// Makes old wiki links to a renamed or merged note reach its new location.
+///
+/// Adds the old path (workspace-relative, without `.md`) to the `aliases`
+/// of the note at new_path instead of rewriting every link: `[[Old name]]`
+/// and `[[folder/Old name]]` then resolve to the new note. Relative
+/// markdown links are not covered.
+///
+/// Security: Validates both paths are markdown files within the workspace;
+/// the note at new_path must be writable.
+///
+/// # Returns
+/// * `Ok(Vec<String>)` - The new note's aliases after the update
+#[command]
+pub async fn create_alias_redirect(
+    state: State<'_, AppState>,
+    old_path: String,
+    new_path: String,
+) -> Result<Vec<String>, String> {
+    let workspace = state.get_workspace_path()?;
+
+    let validated_old = validate_file_path(&old_path, &workspace, &["md"])
+        .map_err(|e| format!("Security error: {}", e))?;
+    if validated_old.exists() {
+        return Err(format!("Note still exists: {}", old_path));
+    }
+    let root = Path::new(&workspace).canonicalize()
+        .map_err(|e| format!("Failed to resolve workspace: {}", e))?;
+    let old_key = relative_path(&root, &validated_old);
+    let alias = old_key.strip_suffix(".md").unwrap_or(&old_key).to_string();
+
+    let fields = update_fields(&state, &new_path, |fields| {
+        let mut aliases = frontmatter::aliases(fields);
+        if !aliases.iter().any(|a| a.eq_ignore_ascii_case(&alias)) {
+            aliases.push(alias.clone());
+        }
+        fields.remove("alias");
+        fields.insert("aliases".to_string(), Value::Array(aliases.into_iter().map(Value::String).collect()));
+        Ok(())
+    }).await?;
+
+    log::info!("↪️ {} now redirects to {}", old_key, new_path);
+    Ok(frontmatter::aliases(&fields))
+}
+
+// Helper: Rewrites the frontmatter of a note with `update` applied
+async fn update_fields(
+    state: &AppState,
+    file_path: &str,
+    update: impl FnOnce(&mut Map<String, Value>) -> Result<(), String>,
+) -> Result<Map<String, Value>, String> {
+    let workspace = state.get_workspace_path()?;
+
+    let validated_path = validate_file_path(file_path, &workspace, &["md"])
+        .map_err(|e| format!("Security error: {}", e))?;
+    ensure_writable(&workspace, &validated_path)?;
+
+    // Pending debounced content must land first, or it would be overwritten
+    state.save_queue.flush_path_async(&validated_path).await?;
+    let _guard = state.write_locks
+        .acquire_async(&validated_path, Duration::from_millis(DEFAULT_LOCK_WAIT_MS)).await?;
+
+    let content = fs::read_to_string(&validated_path)
+        .map_err(|e| format!("Failed to read file: {}", e))?;
+    let mut fields = frontmatter::split(&content).0.unwrap_or_default();
+    update(&mut fields)?;
+
+    safe_write::write_file(&validated_path, frontmatter::replace_fields(&content, &fields))?;
+    state.activity.record(&workspace, &validated_path, ActivityKind::Saved, None);
+
+    Ok(fields)
+}
@@ -0,0 +1,317 @@
+//! Link Graph Commands
+//! 
+//! This module provides Tauri commands over the workspace link graph
+//! (see `crate::links`), related notes, unlinked mentions, people pages
+//! and their `@mentions` (see `crate::people`), and the link redirection
+//! used when notes are split or merged.
+//! 
+//! ## Security
+//! Note paths are validated against the configured workspace. Redirected
+//! links are only written to notes outside protected folders.
+
+use tauri::{command, State};
+use std::fs;
+use std::path::Path;
+use std::time::Duration;
+use serde::Serialize;
+use crate::activity::ActivityKind;
+use crate::frontmatter;
+use crate::links::{redirect_links, wiki_target, GraphMetrics, NoteLookup};
+use crate::mentions::{self, find_mentions, Mention, TextRange};
+use crate::metadata_cache::MetadataCache;
+use crate::notes::{collect_markdown_files, relative_path};
+use crate::people::{self, Person, PersonMention};
+use crate::related::{co_edit_counts, related_notes, RelatedNote, RelatedSignals, DEFAULT_RELATED_LIMIT};
+use crate::safe_write;
+use crate::state::AppState;
+use crate::tag_suggest::{cosine_similarity, Corpus};
+use crate::utils::validate_file_path;
+use crate::workspace_settings::ensure_writable;
+use crate::write_locks::DEFAULT_LOCK_WAIT_MS;
+
+/// Notes whose links were redirected to another note
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct LinkUpdateReport {
+    /// Rewritten notes (paths relative to the workspace)
+    pub updated: Vec<String>,
+    /// Number of links changed
+    pub link_count: usize,
+    /// Notes with matching links that are protected and were left unchanged
+    pub skipped: Vec<String>,
+}
+
+/// Lists the notes linking to a note (paths relative to the workspace).
+/// 
+/// Security: Validates file_path is a markdown file within the workspace.
+#[command]
+pub async fn get_backlinks(
+    state: State<'_, AppState>,
+    file_path: String,
+) -> Result<Vec<String>, String> {
+    let workspace = state.get_workspace_path()?;
+    
+    let validated_path = validate_file_path(&file_path, &workspace, &["md"])
+        .map_err(|e| format!("Security error: {}", e))?;
+    
+    let root = Path::new(&workspace)
+        .canonicalize()
+        .map_err(|e| format!("Failed to resolve workspace: {}", e))?;
+    let index = state.metadata.with_fresh(&workspace, |cache| cache.link_index())?;
+    
+    Ok(index.backlinks(&relative_path(&root, &validated_path)))
+}
+
+/// Lists the person pages of the workspace (notes with `type: person`),
+/// with how many notes mention each.
+#[command]
+pub async fn list_people(
+    state: State<'_, AppState>,
+) -> Result<Vec<Person>, String> {
+    let workspace = state.get_workspace_path()?;
+    
+    state.metadata.with_fresh(&workspace, people::list_people)
+}
+
+/// Lists the notes mentioning a person page with `@handle`, most recently
+/// modified first.
+/// 
+/// Security: Validates file_path is a markdown file within the workspace.
+#[command]
+pub async fn get_person_mentions(
+    state: State<'_, AppState>,
+    file_path: String,
+) -> Result<Vec<PersonMention>, String> {
+    let workspace = state.get_workspace_path()?;
+    
+    let validated_path = validate_file_path(&file_path, &workspace, &["md"])
+        .map_err(|e| format!("Security error: {}", e))?;
+    
+    let root = Path::new(&workspace)
+        .canonicalize()
+        .map_err(|e| format!("Failed to resolve workspace: {}", e))?;
+    let path = relative_path(&root, &validated_path);
+    
+    state.metadata.with_fresh(&workspace, |cache| people::mentions_of(cache, &path))
+}
+
+/// Computes degree, cluster and centrality for every note of the workspace.
+/// 
+/// The graph view uses these to size and color nodes; `hubs` lists the
+/// most central notes.
+#[command]
+pub async fn get_graph_metrics(
+    state: State<'_, AppState>,
+) -> Result<GraphMetrics, String> {
+    let workspace = state.get_workspace_path()?;
+    
+    let index = state.metadata.with_fresh(&workspace, |cache| cache.link_index())?;
+    let metrics = GraphMetrics::compute(&index);
+    
+    log::info!(
+        "🕸️ Graph metrics: {} notes, {} links, {} clusters",
+        metrics.notes.len(),
+        metrics.edge_count,
+        metrics.cluster_count
+    );
+    Ok(metrics)
+}
+
+/// Ranks the notes related to a note for the related-notes panel.
+/// 
+/// Combines shared tags, the link neighborhood, text similarity and notes
+/// edited in the same sessions (see `crate::related`); every result lists
+/// the reasons it was picked.
+/// 
+/// Security: Validates file_path is a markdown file within the workspace.
+/// 
+/// # Returns
+/// * `Ok(Vec<RelatedNote>)` - Up to `limit` (default 10) notes, most related first
+#[command]
+pub async fn get_related_notes(
+    state: State<'_, AppState>,
+    file_path: String,
+    limit: Option<usize>,
+) -> Result<Vec<RelatedNote>, String> {
+    let workspace = state.get_workspace_path()?;
+    
+    let validated_path = validate_file_path(&file_path, &workspace, &["md"])
+        .map_err(|e| format!("Security error: {}", e))?;
+    let root = Path::new(&workspace)
+        .canonicalize()
+        .map_err(|e| format!("Failed to resolve workspace: {}", e))?;
+    let target = relative_path(&root, &validated_path);
+    state.save_queue.flush_path_async(&validated_path).await?;
+    
+    // Text similarity needs every body; read them outside the cache lock
+    let notes = state.metadata.with_fresh(&workspace, |cache| cache.notes.keys().cloned().collect::<Vec<_>>())?;
+    let mut corpus = Corpus::default();
+    let mut bodies = Vec::with_capacity(notes.len());
+    for note in notes {
+        if let Ok(content) = fs::read_to_string(root.join(&note)) {
+            let body = frontmatter::split(&content).1.to_string();
+            corpus.add(&body);
+            bodies.push((note, body));
+        }
+    }
+    let mine = bodies
+        .iter()
+        .find(|(note, _)| *note == target)
+        .map(|(_, body)| corpus.vector(body))
+        .unwrap_or_default();
+    let similarity = bodies
+        .iter()
+        .filter(|(note, _)| *note != target)
+        .map(|(note, body)| (note.clone(), cosine_similarity(&mine, &corpus.vector(body))))
+        .collect();
+    
+    let signals = RelatedSignals {
+        similarity,
+        co_edits: co_edit_counts(&state.activity.entries(&workspace)?, &target),
+    };
+    state.metadata.with_fresh(&workspace, |cache| {
+        related_notes(cache, &target, &signals, limit.unwrap_or(DEFAULT_RELATED_LIMIT))
+    })
+}
+
+/// Finds plain-text mentions of a note in other notes that are not links.
+/// 
+/// Looks for the note's title, file name and aliases as whole words,
+/// ignoring case, outside code and existing links.
+/// 
+/// Security: Validates file_path is a markdown file within the workspace.
+/// 
+/// # Returns
+/// * `Ok(Vec<Mention>)` - Mentions with note, line, byte range and context
+#[command]
+pub async fn find_unlinked_mentions(
+    state: State<'_, AppState>,
+    file_path: String,
+) -> Result<Vec<Mention>, String> {
+    let workspace = state.get_workspace_path()?;
+    
+    let validated_path = validate_file_path(&file_path, &workspace, &["md"])
+        .map_err(|e| format!("Security error: {}", e))?;
+    let root = Path::new(&workspace)
+        .canonicalize()
+        .map_err(|e| format!("Failed to resolve workspace: {}", e))?;
+    let target = relative_path(&root, &validated_path);
+    
+    let (names, notes) = state.metadata.with_fresh(&workspace, |cache| {
+        (note_names(cache, &target), cache.notes.keys().cloned().collect::<Vec<_>>())
+    })?;
+    
+    let mut mentions = Vec::new();
+    for note in notes.iter().filter(|note| **note != target) {
+        if let Ok(content) = fs::read_to_string(root.join(note)) {
+            mentions.extend(find_mentions(note, &content, &names));
+        }
+    }
+    
+    log::info!("🔎 {} unlinked mentions of {}", mentions.len(), target);
+    Ok(mentions)
+}
+
+/// Turns one unlinked mention (as found by `find_unlinked_mentions`) into
+/// a wiki link to target_path.
+/// 
+/// Fails if the text at `range` is no longer a name of the target note.
+/// 
+/// Security: Validates both paths are markdown files within the workspace;
+/// file_path must be writable.
+#[command]
+pub async fn link_mention(
+    state: State<'_, AppState>,
+    file_path: String,
+    range: TextRange,
+    target_path: String,
+) -> Result<(), String> {
+    let workspace = state.get_workspace_path()?;
+    
+    let validated_path = validate_file_path(&file_path, &workspace, &["md"])
+        .map_err(|e| format!("Security error: {}", e))?;
+    let validated_target = validate_file_path(&target_path, &workspace, &["md"])
+        .map_err(|e| format!("Security error: {}", e))?;
+    ensure_writable(&workspace, &validated_path)?;
+    
+    let root = Path::new(&workspace)
+        .canonicalize()
+        .map_err(|e| format!("Failed to resolve workspace: {}", e))?;
+    let from = relative_path(&root, &validated_path);
+    let target = relative_path(&root, &validated_target);
+    let (names, wiki) = state.metadata.with_fresh(&workspace, |cache| {
+        (note_names(cache, &target), wiki_target(&cache.lookup(), &from, &target))
+    })?;
+    
+    // Pending debounced content must land first, or the range would be stale
+    state.save_queue.flush_path_async(&validated_path).await?;
+    let _guard = state.write_locks
+        .acquire_async(&validated_path, Duration::from_millis(DEFAULT_LOCK_WAIT_MS)).await?;
+    let content = fs::read_to_string(&validated_path)
+        .map_err(|e| format!("Failed to read file: {}", e))?;
+    let updated = mentions::link_mention(&content, range, &names, &wiki)?;
+    safe_write::write_file(&validated_path, updated)?;
+    state.activity.record(&workspace, &validated_path, ActivityKind::Saved, None);
+    
+    log::info!("🔗 Linked mention of {} in {}", target, from);
+    Ok(())
+}
+
+// Helper: Names a note can be mentioned by: title, file name and aliases
+fn note_names(cache: &MetadataCache, path: &str) -> Vec<String> {
+    let mut names = Vec::new();
+    if let Some(note) = cache.notes.get(path) {
+        names.push(note.title.clone());
+        names.extend(frontmatter::aliases(&note.fields));
+    }
+    let file_name = path.rsplit('/').next().unwrap_or(path);
+    names.push(file_name.strip_suffix(".md").unwrap_or(file_name).to_string());
+    names
+}
+
+// Helper: Redirects links to note `old` in every note of the workspace to `new`
+//
+// `old` and `new` are workspace-relative; `anchors` is passed to
+// `links::redirect_links`. Callers must not hold a write lock on any note.
+pub(crate) async fn redirect_workspace_links(
+    state: &AppState,
+    workspace: &str,
+    old: &str,
+    new: &str,
+    anchors: &(dyn Fn(Option<&str>) -> Option<Option<String>> + Sync),
+) -> Result<LinkUpdateReport, String> {
+    let root = Path::new(workspace)
+        .canonicalize()
+        .map_err(|e| format!("Failed to resolve workspace: {}", e))?;
+    let notes = collect_markdown_files(&root, &root)?;
+    let lookup = NoteLookup::new(notes.iter().map(|p| relative_path(&root, p)).collect());
+    let mut report = LinkUpdateReport::default();
+
+    for (note, relative) in notes.iter().zip(&lookup.paths) {
+        let Ok(content) = fs::read_to_string(note) else {
+            continue;
+        };
+        if redirect_links(&content, relative, &lookup, old, new, anchors).1 == 0 {
+            continue;
+        }
+        if ensure_writable(workspace, note).is_err() {
+            report.skipped.push(relative.clone());
+            continue;
+        }
+
+        // Re-read under the lock, after pending debounced content has landed
+        state.save_queue.flush_path_async(note).await?;
+        let _guard = state.write_locks
+            .acquire_async(note, Duration::from_millis(DEFAULT_LOCK_WAIT_MS)).await?;
+        let content = fs::read_to_string(note)
+            .map_err(|e| format!("Failed to read file: {}", e))?;
+        let (updated, count) = redirect_links(&content, relative, &lookup, old, new, anchors);
+        if count > 0 {
+            safe_write::write_file(note, updated)?;
+            report.updated.push(relative.clone());
+            report.link_count += count;
+        }
+    }
+
+    log::info!("🔗 Redirected {} links from {} to {} in {} notes", report.link_count, old, new, report.updated.len());
+    Ok(report)
+}
@@ -0,0 +1,316 @@
+//! Workspace Hook Commands
+//!
+//! This module configures and approves the workspace hooks (see
+//! `crate::hooks`) and runs them for the file and export commands:
+//! `before_save` hooks inline through `run_before_save`, the others in the
+//! background through `spawn_hooks`. Every run is reported with a
+//! `hook-finished` event carrying its output.
+//!
+//! Hooks that are not approved on this machine never run; the frontend
+//! shows them with `get_workspace_hooks` and approves them with
+//! `approve_workspace_hooks`. Approval always takes a native confirmation
+//! dialog listing the commands, so a compromised webview cannot approve
+//! hooks on its own.
+
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+use rfd::{AsyncMessageDialog, MessageButtons, MessageDialogResult, MessageLevel};
+use serde::Serialize;
+use tauri::{command, AppHandle, State};
+use crate::commands::dialogs::show;
+use crate::commands::workspace::get_config_dir;
+use crate::events::{self, HookFinishedEvent, SettingsChangedEvent, SettingsSection};
+use crate::hooks::{self, fingerprint, Hook, HookContext, HookEvent, HookRun, HookTrust};
+use crate::notes::relative_path;
+use crate::state::AppState;
+use crate::utils::validate_path_within_workspace;
+use crate::workspace_settings::WorkspaceSettings;
+
+/// Hooks can only run where the app may start processes
+const HOOKS_SUPPORTED: bool = cfg!(desktop);
+
+/// Result of the hook commands
+#[derive(Debug, Clone, Serialize)]
+pub struct HooksStatus {
+    pub hooks: Vec<Hook>,
+    /// Whether these exact hooks are approved on this machine
+    pub trusted: bool,
+    /// Fingerprint to pass to `approve_workspace_hooks`
+    pub fingerprint: String,
+    /// False on platforms that cannot run hooks (mobile)
+    pub supported: bool,
+}
+
+/// Gets the hooks of the current workspace and whether they are approved.
+#[command]
+pub async fn get_workspace_hooks(state: State<'_, AppState>) -> Result<HooksStatus, String> {
+    let workspace = state.get_workspace_path()?;
+    let hooks = WorkspaceSettings::load(Path::new(&workspace))?.hooks;
+    let trust = HookTrust::load(&get_config_dir()?)?;
+    Ok(status(&trust, &workspace, hooks))
+}
+
+/// Replaces the hooks of the current workspace.
+///
+/// The hooks are saved unapproved; they are approved on this machine only
+/// if the user confirms the native dialog listing them.
+#[command]
+pub async fn set_workspace_hooks(
+    app_handle: AppHandle,
+    state: State<'_, AppState>,
+    hooks: Vec<Hook>,
+) -> Result<HooksStatus, String> {
+    let workspace = state.get_workspace_path()?;
+    let root = Path::new(&workspace);
+
+    let mut names = HashSet::new();
+    for hook in &hooks {
+        hook.validate()?;
+        if !names.insert(hook.name.as_str()) {
+            return Err(format!("Duplicate hook: {}", hook.name));
+        }
+    }
+
+    let mut settings = WorkspaceSettings::load(root)?;
+    settings.hooks = hooks;
+    settings.save(root)?;
+
+    let config_dir = get_config_dir()?;
+    let mut trust = HookTrust::load(&config_dir)?;
+    if !trust.is_trusted(&workspace, &settings.hooks) && confirm_approval(&state, &settings.hooks).await? {
+        trust.approve(&workspace, &settings.hooks);
+        trust.save(&config_dir)?;
+    }
+
+    events::emit(&app_handle, &SettingsChangedEvent {
+        section: SettingsSection::Hooks,
+        workspace: workspace.clone(),
+    });
+    log::info!("🪝 {} hooks configured", settings.hooks.len());
+    Ok(status(&trust, &workspace, settings.hooks))
+}
+
+/// Approves the hooks of the current workspace on this machine.
+///
+/// `fingerprint` must be the one returned with the hooks the user was
+/// shown, so hooks changed in the meantime are not approved unseen. The
+/// user then confirms the approval in a native dialog.
+///
+/// # Returns
+/// * `Ok(false)` - If the hooks changed since they were shown, or the user
+///   declined
+#[command]
+pub async fn approve_workspace_hooks(
+    state: State<'_, AppState>,
+    fingerprint: String,
+) -> Result<bool, String> {
+    let workspace = state.get_workspace_path()?;
+    let hooks = WorkspaceSettings::load(Path::new(&workspace))?.hooks;
+    if hooks::fingerprint(&hooks) != fingerprint || !confirm_approval(&state, &hooks).await? {
+        return Ok(false);
+    }
+
+    let config_dir = get_config_dir()?;
+    let mut trust = HookTrust::load(&config_dir)?;
+    trust.approve(&workspace, &hooks);
+    trust.save(&config_dir)?;
+    log::info!("🪝 Approved {} hooks of {}", hooks.len(), workspace);
+    Ok(true)
+}
+
+/// Runs one hook now, to try it out.
+///
+/// A `before_save` hook gets the note's content on stdin, but its output
+/// is only returned, never saved.
+///
+/// Security: Validates file_path is within the configured workspace.
+#[command]
+pub async fn run_workspace_hook(
+    app_handle: AppHandle,
+    state: State<'_, AppState>,
+    name: String,
+    file_path: Option<String>,
+) -> Result<HookRun, String> {
+    let workspace = state.get_workspace_path()?;
+    let file = file_path
+        .map(|path| validate_path_within_workspace(&path, &workspace).map_err(|e| format!("Security error: {}", e)))
+        .transpose()?;
+
+    let hook = trusted_hooks(&workspace)?
+        .into_iter()
+        .find(|hook| hook.name == name)
+        .ok_or_else(|| format!("No approved hook named {}", name))?;
+    let content = match (&file, hook.event) {
+        (Some(file), HookEvent::BeforeSave) => Some(
+            fs::read_to_string(file).map_err(|e| format!("Failed to read file: {}", e))?,
+        ),
+        _ => None,
+    };
+
+    tauri::async_runtime::spawn_blocking(move || {
+        run_hook(&app_handle, &workspace, &hook, file.as_deref(), None, content.as_deref())
+    })
+    .await
+    .map_err(|e| format!("Failed to run hook: {}", e))?
+}
+
+/// Runs the `before_save` hooks of a note and returns the content to save
+///
+/// Hooks run in order; each `replace_content` hook gets the previous one's
+/// output. A failing hook leaves the content as it was, or cancels the save
+/// with a `Hook failed:` error if it has `abort_on_failure`.
+pub(crate) fn run_before_save(
+    app_handle: &AppHandle,
+    workspace: &str,
+    path: &Path,
+    content: String,
+) -> Result<String, String> {
+    let mut content = content;
+    for hook in applicable(workspace, HookEvent::BeforeSave, Some(path)) {
+        let run = run_hook(app_handle, workspace, &hook, Some(path), None, Some(&content));
+        match run {
+            Ok(run) if run.success => {
+                if hook.replace_content && !run.truncated {
+                    content = run.stdout;
+                }
+            }
+            Ok(run) if hook.abort_on_failure => {
+                let reason = if run.timed_out {
+                    format!("timed out after {}s", hook.timeout_secs)
+                } else {
+                    run.stderr.lines().next().unwrap_or("exited with an error").to_string()
+                };
+                return Err(format!("Hook failed: {}: {} (the note was not saved)", hook.name, reason));
+            }
+            Err(e) if hook.abort_on_failure => return Err(format!("Hook failed: {}", e)),
+            _ => {}
+        }
+    }
+    Ok(content)
+}
+
+/// Runs the hooks of an event in the background
+pub(crate) fn spawn_hooks(
+    app_handle: &AppHandle,
+    workspace: &str,
+    event: HookEvent,
+    file: Option<PathBuf>,
+    export: Option<PathBuf>,
+) {
+    let hooks = applicable(workspace, event, file.as_deref());
+    if hooks.is_empty() {
+        return;
+    }
+    let app_handle = app_handle.clone();
+    let workspace = workspace.to_string();
+    std::thread::spawn(move || {
+        for hook in &hooks {
+            // Failures are reported by `run_hook`
+            let _ = run_hook(&app_handle, &workspace, hook, file.as_deref(), export.as_deref(), None);
+        }
+    });
+}
+
+// Helper: Asks the user in a native dialog whether to approve hooks
+// (no hooks need no approval)
+async fn confirm_approval(state: &AppState, hooks: &[Hook]) -> Result<bool, String> {
+    if hooks.is_empty() {
+        return Ok(true);
+    }
+    if !HOOKS_SUPPORTED {
+        return Ok(false);
+    }
+
+    let commands: Vec<String> = hooks
+        .iter()
+        .map(|hook| format!("• {}: {} {}", hook.name, hook.command, hook.args.join(" ")).trim_end().to_string())
+        .collect();
+    let dialog = AsyncMessageDialog::new()
+        .set_level(MessageLevel::Warning)
+        .set_title("Allow Workspace Hooks?")
+        .set_description(format!(
+            "This workspace runs these commands on your computer:\n\n{}\n\nOnly allow them if you trust the workspace.",
+            commands.join("\n")
+        ))
+        .set_buttons(MessageButtons::YesNo);
+    let confirmed = show(state, async move {
+        Some(dialog.show().await == MessageDialogResult::Yes)
+    }).await?;
+    if confirmed != Some(true) {
+        log::info!("🪝 {} hooks left unapproved", hooks.len());
+    }
+    Ok(confirmed == Some(true))
+}
+
+// Helper: Approved hooks of an event that apply to `file`
+fn applicable(workspace: &str, event: HookEvent, file: Option<&Path>) -> Vec<Hook> {
+    if !HOOKS_SUPPORTED {
+        return Vec::new();
+    }
+    let root = Path::new(workspace);
+    let root = root.canonicalize().unwrap_or_else(|_| root.to_path_buf());
+    let relative = file.map(|file| relative_path(&root, file));
+    match trusted_hooks(workspace) {
+        Ok(hooks) => hooks
+            .into_iter()
+            .filter(|hook| hook.event == event && hook.applies_to(relative.as_deref()))
+            .collect(),
+        Err(e) => {
+            log::warn!("⚠️ Hooks skipped: {}", e);
+            Vec::new()
+        }
+    }
+}
+
+// Helper: The workspace's hooks, if they are approved on this machine
+fn trusted_hooks(workspace: &str) -> Result<Vec<Hook>, String> {
+    if !HOOKS_SUPPORTED {
+        return Err("Hooks are not supported on this platform".to_string());
+    }
+    let hooks = WorkspaceSettings::load(Path::new(workspace))?.hooks;
+    if hooks.is_empty() {
+        return Ok(hooks);
+    }
+    if !HookTrust::load(&get_config_dir()?)?.is_trusted(workspace, &hooks) {
+        return Err(format!("{} hooks of {} are not approved on this machine", hooks.len(), workspace));
+    }
+    Ok(hooks)
+}
+
+// Helper: Runs a hook and reports it with `hook-finished`
+fn run_hook(
+    app_handle: &AppHandle,
+    workspace: &str,
+    hook: &Hook,
+    file: Option<&Path>,
+    export: Option<&Path>,
+    stdin: Option<&str>,
+) -> Result<HookRun, String> {
+    let context = HookContext { event: hook.event, workspace: Path::new(workspace), file, export };
+    let run = hooks::run(hook, &context, stdin).map_err(|e| {
+        log::warn!("⚠️ {}", e);
+        e
+    })?;
+    if run.success {
+        log::info!("🪝 Hook {} finished in {}ms", run.name, run.duration_ms);
+    } else {
+        log::warn!("⚠️ Hook {} failed (exit {:?}, timed out: {})", run.name, run.exit_code, run.timed_out);
+    }
+    events::emit(app_handle, &HookFinishedEvent {
+        workspace: workspace.to_string(),
+        file: file.map(|f| f.to_string_lossy().to_string()),
+        run: run.clone(),
+    });
+    Ok(run)
+}
+
+// Helper: Builds the status returned by the commands
+fn status(trust: &HookTrust, workspace: &str, hooks: Vec<Hook>) -> HooksStatus {
+    HooksStatus {
+        trusted: trust.is_trusted(workspace, &hooks),
+        fingerprint: fingerprint(&hooks),
+        supported: HOOKS_SUPPORTED,
+        hooks,
+    }
+}
@@ -5,13 +5,47 @@
 //! 
 //! ## Security
 //! - Import: Source can be anywhere (user selects via dialog), destination must be in workspace
-//! - Export: Source must be in workspace, destination can be anywhere (user selects via dialog)
+//! - Export: Source must be in workspace, destination must be picked in the save
+//!   dialog or lie in the workspace or an export folder, never in a system directory
+//!   (see `crate::export_policy`)
 
-use tauri::{command, State};
+use tauri::webview::PageLoadEvent;
+use tauri::{command, AppHandle, State, WebviewUrl, WebviewWindowBuilder};
+use std::collections::{HashMap, HashSet};
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
+use serde::{Deserialize, Serialize};
+use crate::activity::ActivityKind;
+use crate::assets::{bundle_assets, AssetMode, AssetReport};
+use crate::bear::{import_bear, NotesImportReport};
+use crate::calendar;
+use crate::commands::hooks::spawn_hooks;
+use crate::commands::workspace::get_config_dir;
+use crate::convert::{asciidoc_to_markdown, org_to_markdown};
+use crate::csv_table::{self, csv_to_markdown, CsvTableOptions};
+use crate::diagrams;
+use crate::email::{self, EmailDraft, EmailFormat};
+use crate::export_policy::{resolve_destination, ExportFolders};
+use crate::flavor::{self, FlavorConversion, MarkdownFlavor};
+use crate::frontmatter;
+use crate::hooks::HookEvent;
+use crate::jobs::{emit_progress, JobHandle, JobProgress};
+use crate::logseq::{import_graph, LogseqImportReport};
+use crate::mindmap_meta::MINDMAP_DIR;
+use crate::notes::{relative_path, DatasetFormat, NoteQuery};
+use crate::paste::{self, CleanedPaste, PasteOptions};
+use crate::print::{self, PrintOptions};
+use crate::render::{html_document, render_markdown, RenderOptions};
+use crate::rtf::html_to_rtf;
+use crate::safe_write;
 use crate::state::AppState;
-use crate::utils::{validate_directory_path, sanitize_filename};
+use crate::text_pdf::markdown_to_pdf;
+use crate::transclusion::{self, DEFAULT_DEPTH};
+use crate::utils::{validate_directory_path, validate_file_path, sanitize_filename, matches_glob, walk_metadata};
+use crate::workspace_settings::{ensure_tree_writable, ensure_writable, SymlinkPolicy, WorkspaceSettings, SETTINGS_DIR};
+use crate::write_locks::DEFAULT_LOCK_WAIT_MS;
 
 // ========================================
 // IMPORT OPERATIONS
@@ -60,161 +94,1555 @@ pub async fn import_markdown_file(
     };
     
     let dest_path = validated_dest.join(&final_name);
+    ensure_writable(&workspace, &dest_path)?;
     
     // Copy file
     fs::copy(&source, &dest_path)
         .map_err(|e| format!("Failed to import file: {}", e))?;
     
+    state.activity.record(&workspace, &dest_path, ActivityKind::Imported, Some(source_path.clone()));
     log::info!("📥 Imported: {} → {}", source_path, dest_path.display());
     Ok(dest_path.to_string_lossy().to_string())
 }
 
+/// Import a CSV/TSV file as a markdown table.
+///
+/// The delimiter and quote character are detected unless set in `options`.
+/// If `dest_note` already exists the table is appended to it, otherwise the
+/// note is created. With `options.max_columns`, wide tables are split into
+/// several tables that each repeat the first column.
+///
+/// Security:
+/// - Source can be anywhere (user selected via dialog)
+/// - Destination must be a `.md` file within the configured workspace
+#[command]
+pub async fn import_csv_as_table(
+    state: State<'_, AppState>,
+    source_path: String,
+    dest_note: String,
+    options: Option<CsvTableOptions>,
+) -> Result<String, String> {
+    let workspace = state.get_workspace_path()?;
+
+    let validated_dest = validate_file_path(&dest_note, &workspace, &["md"])
+        .map_err(|e| format!("Security error: {}", e))?;
+    ensure_writable(&workspace, &validated_dest)?;
+
+    let source = PathBuf::from(&source_path);
+    if !source.is_file() {
+        return Err(format!("Source is not a file: {}", source_path));
+    }
+
+    let csv = fs::read_to_string(&source)
+        .map_err(|e| format!("Failed to read CSV: {}", e))?;
+    let table = csv_to_markdown(&csv, &options.unwrap_or_default())?;
+
+    append_to_note(&validated_dest, &table)?;
+
+    state.activity.record(&workspace, &validated_dest, ActivityKind::Imported, Some(source_path.clone()));
+    log::info!("📊 Imported table: {} → {}", source_path, validated_dest.display());
+    Ok(validated_dest.to_string_lossy().to_string())
+}
+
+/// Sets one cell of a CSV file shown in notes as a `csvtable` block.
+///
+/// The file keeps its delimiter and line endings; only the changed row is
+/// re-quoted. A row one past the end appends a row.
+///
+/// Security: Validates file_path is a CSV/TSV file within the workspace
+/// and writable.
+///
+/// # Arguments
+/// * `row` - Row of the file, 0 being the header (blank lines not counted)
+/// * `col` - Column, 0-based
+#[command]
+pub async fn update_csv_cell(
+    state: State<'_, AppState>,
+    file_path: String,
+    row: usize,
+    col: usize,
+    value: String,
+) -> Result<(), String> {
+    let workspace = state.get_workspace_path()?;
+
+    let validated_path = validate_file_path(&file_path, &workspace, &["csv", "tsv"])
+        .map_err(|e| format!("Security error: {}", e))?;
+    ensure_writable(&workspace, &validated_path)?;
+
+    let _guard = state.write_locks
+        .acquire_async(&validated_path, Duration::from_millis(DEFAULT_LOCK_WAIT_MS)).await?;
+    let csv = fs::read_to_string(&validated_path)
+        .map_err(|e| format!("Failed to read CSV: {}", e))?;
+    let updated = csv_table::update_csv_cell(&csv, row, col, &value)?;
+    safe_write::write_file(&validated_path, updated)?;
+    state.activity.record(&workspace, &validated_path, ActivityKind::Saved, None);
+
+    log::info!("📊 Updated cell {}:{} of {:?}", row, col, validated_path);
+    Ok(())
+}
+
+/// Import an Emacs org-mode file as a markdown note.
+/// 
+/// Headings, lists, source/example blocks, quotes, tables, links and
+/// emphasis are converted; `#+TITLE:` and friends become frontmatter.
+/// 
+/// Security: 
+/// - Source can be anywhere (user selected via dialog)
+/// - Destination must be within the configured workspace
+#[command]
+pub async fn import_org(
+    state: State<'_, AppState>,
+    source_path: String,
+    dest_folder: String,
+) -> Result<String, String> {
+    import_converted(&state, &source_path, &dest_folder, org_to_markdown)
+}
+
+/// Import an AsciiDoc file as a markdown note.
+/// 
+/// Sections, lists, listing blocks, quotes, admonitions, tables, links,
+/// xrefs and images are converted; header attributes become frontmatter.
+/// 
+/// Security: 
+/// - Source can be anywhere (user selected via dialog)
+/// - Destination must be within the configured workspace
+#[command]
+pub async fn import_asciidoc(
+    state: State<'_, AppState>,
+    source_path: String,
+    dest_folder: String,
+) -> Result<String, String> {
+    import_converted(&state, &source_path, &dest_folder, asciidoc_to_markdown)
+}
+
+/// Import a Logseq graph folder into the workspace.
+/// 
+/// Pages go to `<dest>/Pages` (namespaces become subfolders), journals to
+/// `<dest>/Journals` and assets to `<dest>/assets`. Page properties become
+/// frontmatter, `[[Page]]` and `((block))` references are turned into
+/// relative links; block references that cannot be resolved are listed in
+/// the report.
+/// 
+/// Security: 
+/// - Source can be anywhere (user selected via dialog)
+/// - Destination must be within the configured workspace
+#[command]
+pub async fn import_logseq_graph(
+    state: State<'_, AppState>,
+    source_path: String,
+    dest_folder: String,
+) -> Result<LogseqImportReport, String> {
+    let workspace = state.get_workspace_path()?;
+
+    let validated_dest = validate_directory_path(&dest_folder, &workspace, true)
+        .map_err(|e| format!("Security error: {}", e))?;
+
+    let source = PathBuf::from(&source_path);
+    if !source.is_dir() {
+        return Err(format!("Source is not a directory: {}", source_path));
+    }
+
+    let graph_name = source.file_name()
+        .ok_or("Failed to get folder name")?
+        .to_string_lossy()
+        .to_string();
+    let dest_path = validated_dest.join(sanitize_filename(&graph_name));
+    ensure_tree_writable(&workspace, &dest_path)?;
+
+    if dest_path.exists() {
+        return Err(format!("Folder already exists: {}", dest_path.display()));
+    }
+
+    let report = import_graph(&source, &dest_path)?;
+    state.activity.record(&workspace, &dest_path, ActivityKind::Imported, Some(source_path.clone()));
+
+    log::info!(
+        "📥 Imported Logseq graph: {} pages, {} journals, {} unresolved block refs → {}",
+        report.pages, report.journals, report.unresolved_block_refs.len(), dest_path.display()
+    );
+    Ok(report)
+}
+
+/// Import a Bear backup (`.bear2bk`), `.textpack` or TextBundle folder.
+/// 
+/// Every note becomes a markdown file in `dest_folder` (or in a folder named
+/// after its first tag when `group_by_tag` is set); bundle assets are copied
+/// to `attachments/<note>/` next to it.
+/// 
+/// Security: 
+/// - Source can be anywhere (user selected via dialog)
+/// - Destination must be within the configured workspace
+#[command]
+pub async fn import_bear_backup(
+    state: State<'_, AppState>,
+    source_path: String,
+    dest_folder: String,
+    group_by_tag: Option<bool>,
+) -> Result<NotesImportReport, String> {
+    let workspace = state.get_workspace_path()?;
+
+    let validated_dest = validate_directory_path(&dest_folder, &workspace, true)
+        .map_err(|e| format!("Security error: {}", e))?;
+    ensure_tree_writable(&workspace, &validated_dest)?;
+
+    let source = PathBuf::from(&source_path);
+    if !source.exists() {
+        return Err(format!("Source does not exist: {}", source_path));
+    }
+
+    let report = import_bear(&source, &validated_dest, group_by_tag.unwrap_or(false))?;
+
+    state.activity.record(&workspace, &validated_dest, ActivityKind::Imported, Some(source_path.clone()));
+    log::info!("📥 Imported {} Bear notes → {}", report.notes.len(), validated_dest.display());
+    Ok(report)
+}
+
+/// Import contacts from a vCard file (`.vcf`) or a folder of them.
+/// 
+/// Each contact becomes a person page with its details as frontmatter
+/// (see `crate::vcard`), so notes can mention it with `@handle`.
+/// 
+/// Security:
+/// - Source can be anywhere (user selected via dialog)
+/// - Destination must be within the configured workspace
+#[command]
+pub async fn import_vcards(
+    state: State<'_, AppState>,
+    path: String,
+    dest_folder: String,
+) -> Result<NotesImportReport, String> {
+    let workspace = state.get_workspace_path()?;
+
+    let validated_dest = validate_directory_path(&dest_folder, &workspace, true)
+        .map_err(|e| format!("Security error: {}", e))?;
+    ensure_tree_writable(&workspace, &validated_dest)?;
+
+    let source = PathBuf::from(&path);
+    if !source.exists() {
+        return Err(format!("Source does not exist: {}", path));
+    }
+
+    let report = crate::vcard::import_vcards(&source, &validated_dest)?;
+
+    state.activity.record(&workspace, &validated_dest, ActivityKind::Imported, Some(path.clone()));
+    log::info!("📥 Imported {} contacts → {}", report.notes.len(), validated_dest.display());
+    Ok(report)
+}
+
+/// Import all Apple Notes (macOS only).
+/// 
+/// Notes are read through the Notes scripting bridge; each Notes folder
+/// becomes a folder inside `dest_folder`. macOS asks for permission to
+/// control Notes the first time.
+/// 
+/// Security: Destination must be within the configured workspace
+#[command]
+pub async fn import_apple_notes(
+    state: State<'_, AppState>,
+    dest_folder: String,
+) -> Result<NotesImportReport, String> {
+    let workspace = state.get_workspace_path()?;
+
+    let validated_dest = validate_directory_path(&dest_folder, &workspace, true)
+        .map_err(|e| format!("Security error: {}", e))?;
+    ensure_tree_writable(&workspace, &validated_dest)?;
+
+    let report = crate::bear::import_apple_notes(&validated_dest)?;
+
+    state.activity.record(&workspace, &validated_dest, ActivityKind::Imported, Some("Apple Notes".to_string()));
+    log::info!("📥 Imported {} Apple Notes → {}", report.notes.len(), validated_dest.display());
+    Ok(report)
+}
+
+/// Reads `source_path`, converts it and writes `<stem>.md` into `dest_folder`
+fn import_converted(
+    state: &AppState,
+    source_path: &str,
+    dest_folder: &str,
+    convert: fn(&str) -> String,
+) -> Result<String, String> {
+    let workspace = state.get_workspace_path()?;
+
+    let validated_dest = validate_directory_path(dest_folder, &workspace, true)
+        .map_err(|e| format!("Security error: {}", e))?;
+
+    let source = PathBuf::from(source_path);
+    if !source.is_file() {
+        return Err(format!("Source is not a file: {}", source_path));
+    }
+
+    let stem = source.file_stem()
+        .ok_or("Failed to get file name")?
+        .to_string_lossy()
+        .to_string();
+    let dest_path = validated_dest.join(format!("{}.md", sanitize_filename(&stem)));
+    ensure_writable(&workspace, &dest_path)?;
+
+    if dest_path.exists() {
+        return Err(format!("File already exists: {}", dest_path.display()));
+    }
+
+    let content = fs::read_to_string(&source)
+        .map_err(|e| format!("Failed to read file: {}", e))?;
+
+    fs::write(&dest_path, convert(&content))
+        .map_err(|e| format!("Failed to import file: {}", e))?;
+
+    state.activity.record(&workspace, &dest_path, ActivityKind::Imported, Some(source_path.to_string()));
+    log::info!("📥 Converted: {} → {}", source_path, dest_path.display());
+    Ok(dest_path.to_string_lossy().to_string())
+}
+
+/// Appends a block to a note, separated by a blank line, creating the note if needed
+fn append_to_note(note: &Path, block: &str) -> Result<(), String> {
+    let content = match fs::read_to_string(note) {
+        Ok(existing) if !existing.trim().is_empty() => {
+            format!("{}\n\n{}\n", existing.trim_end(), block)
+        }
+        Ok(_) => format!("{}\n", block),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => format!("{}\n", block),
+        Err(e) => return Err(format!("Failed to read note: {}", e)),
+    };
+
+    if let Some(parent) = note.parent() {
+        fs::create_dir_all(parent)
+            .map_err(|e| format!("Failed to create directory: {}", e))?;
+    }
+
+    fs::write(note, content)
+        .map_err(|e| format!("Failed to write note: {}", e))
+}
+
+/// Files and folders that are never imported
+const DEFAULT_IGNORE_PATTERNS: &[&str] = &[".git", ".DS_Store", "Thumbs.db", "node_modules"];
+
+/// Minimum time between two progress events of the same import
+const PROGRESS_INTERVAL_MS: u128 = 100;
+
+/// Options controlling a folder import
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ImportOptions {
+    /// Glob patterns to skip (see `utils::matches_glob`), in addition to the defaults
+    #[serde(default)]
+    pub ignore_patterns: Vec<String>,
+    /// Extensions to import (without dot, case-insensitive); empty means all
+    #[serde(default)]
+    pub extensions: Vec<String>,
+    /// Files larger than this many bytes are skipped
+    #[serde(default)]
+    pub max_file_size: Option<u64>,
+    /// What to do when a changed file already exists at the destination
+    #[serde(default)]
+    pub on_conflict: ConflictStrategy,
+}
+
+/// How an incoming file is handled when a different file already exists at its path
+/// 
+/// Identical files (same content hash) are always skipped.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ConflictStrategy {
+    /// Import next to the existing file as `name-conflict.ext`
+    #[default]
+    Suffix,
+    /// Append the incoming text to the existing note (text files only, others fall back to `Suffix`)
+    Merge,
+    /// Replace the existing file
+    Overwrite,
+}
+
+/// How a conflicting file was resolved
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ImportConflict {
+    pub source: String,
+    /// The file that already existed
+    pub existing: String,
+    /// Where the incoming content ended up
+    pub written_to: String,
+    pub resolution: ConflictStrategy,
+}
+
+/// Why a file was not imported
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum SkipReason {
+    /// Extension not in the whitelist
+    ExtensionNotAllowed,
+    /// File exceeds `max_file_size`
+    TooLarge { size: u64 },
+    /// Identical content already exists in the destination
+    Duplicate { existing: String },
+    /// Reading or copying failed
+    Failed { error: String },
+}
+
+/// A file that was not imported
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SkippedFile {
+    pub path: String,
+    pub reason: SkipReason,
+}
+
+/// Result of a folder import
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ImportReport {
+    pub job_id: String,
+    pub dest_path: String,
+    /// Files written into the workspace
+    pub imported: Vec<String>,
+    /// Files left out by the filters, duplicates, or failed copies
+    pub skipped: Vec<SkippedFile>,
+    /// Changed files that collided with an existing file
+    pub conflicts: Vec<ImportConflict>,
+    /// Number of entries matching an ignore pattern
+    pub ignored_count: usize,
+    pub bytes_copied: u64,
+    /// True if the import stopped early because the job was cancelled
+    pub cancelled: bool,
+}
+
 /// Import a folder into the workspace.
 /// 
-/// Security: 
-/// - Source can be anywhere (user selected via dialog)
-/// - Destination must be within the configured workspace
+/// Re-importing into a destination that already has some of the files is
+/// safe: identical files are skipped, and changed files are handled
+/// according to `options.on_conflict`. The returned report lists each.
+/// 
+/// The import runs as a job: `job-progress` events are emitted while files
+/// are copied (`processed`/`total` count files) and it can be stopped with
+/// `cancel_job`, in which case the files copied so far are kept and the
+/// report is flagged as cancelled.
+/// 
+/// Security: 
+/// - Source can be anywhere (user selected via dialog)
+/// - Destination must be within the configured workspace
+#[command]
+pub async fn import_folder(
+    app_handle: AppHandle,
+    state: State<'_, AppState>,
+    source_path: String,
+    dest_folder: String,
+    options: Option<ImportOptions>,
+) -> Result<ImportReport, String> {
+    let workspace = state.get_workspace_path()?;
+    
+    // Validate destination is within workspace
+    let validated_dest = validate_directory_path(&dest_folder, &workspace, true)
+        .map_err(|e| format!("Security error: {}", e))?;
+    
+    let source = PathBuf::from(&source_path);
+    
+    if !source.exists() {
+        return Err(format!("Source folder does not exist: {}", source_path));
+    }
+    
+    if !source.is_dir() {
+        return Err(format!("Source is not a directory: {}", source_path));
+    }
+    
+    // Get and sanitize folder name
+    let folder_name = source.file_name()
+        .ok_or("Failed to get folder name")?
+        .to_string_lossy()
+        .to_string();
+    
+    let sanitized_name = sanitize_filename(&folder_name);
+    let dest_path = validated_dest.join(&sanitized_name);
+    ensure_tree_writable(&workspace, &dest_path)?;
+    
+    let job = state.jobs.start("import_folder")?;
+    let options = options.unwrap_or_default();
+    
+    let (tree_source, tree_dest, tree_job) = (source.clone(), dest_path.clone(), job.clone());
+    let result = tauri::async_runtime::spawn_blocking(move || {
+        let mut last_emit: Option<std::time::Instant> = None;
+        import_tree(&tree_source, &tree_dest, &options, &tree_job, &mut |progress| {
+            let due = last_emit.map_or(true, |t| t.elapsed().as_millis() >= PROGRESS_INTERVAL_MS);
+            if due || progress.done {
+                emit_progress(&app_handle, progress);
+                last_emit = Some(std::time::Instant::now());
+            }
+        })
+    })
+    .await
+    .map_err(|e| format!("Failed to import folder: {}", e));
+    
+    state.jobs.finish(&job.id)?;
+    let report = result??;
+    state.activity.record(&workspace, &dest_path, ActivityKind::Imported, Some(source_path.clone()));
+    
+    log::info!(
+        "📥 Imported folder: {} → {} ({} files, {} skipped, {} conflicts{})",
+        source_path,
+        dest_path.display(),
+        report.imported.len(),
+        report.skipped.len(),
+        report.conflicts.len(),
+        if report.cancelled { ", cancelled" } else { "" }
+    );
+    Ok(report)
+}
+
+/// A file selected for import by the scan pass
+struct ImportCandidate {
+    source: PathBuf,
+    relative: String,
+    size: u64,
+}
+
+// Helper: Scan, filter and copy a folder tree, reporting progress per file
+fn import_tree(
+    source: &Path,
+    dest: &Path,
+    options: &ImportOptions,
+    job: &JobHandle,
+    on_progress: &mut dyn FnMut(&JobProgress),
+) -> Result<ImportReport, String> {
+    let mut report = ImportReport {
+        job_id: job.id.clone(),
+        dest_path: dest.to_string_lossy().to_string(),
+        ..Default::default()
+    };
+    
+    // Pass 1: collect candidates so progress has a known total
+    let mut candidates = Vec::new();
+    let mut visited = HashSet::new();
+    scan_import_candidates(source, source, options, &mut visited, &mut candidates, &mut report)?;
+    
+    let total = candidates.len() as u64;
+    
+    // Index what is already there before anything is copied, so a re-import
+    // skips files that were migrated earlier (even if they were moved)
+    let mut existing = DedupeIndex::build(dest);
+    
+    fs::create_dir_all(dest)
+        .map_err(|e| format!("Failed to create destination directory: {}", e))?;
+    
+    // Pass 2: copy, checking for cancellation between files
+    let mut processed = 0;
+    for candidate in &candidates {
+        if !job.checkpoint() {
+            report.cancelled = true;
+            break;
+        }
+        
+        on_progress(&job.progress(processed, Some(total), Some(candidate.relative.clone())));
+        processed += 1;
+        
+        if let Err(e) = import_candidate(candidate, dest, options, &mut existing, &mut report) {
+            report.skipped.push(SkippedFile {
+                path: candidate.source.to_string_lossy().to_string(),
+                reason: SkipReason::Failed { error: e.to_string() },
+            });
+        }
+    }
+    
+    on_progress(&job.progress(processed, Some(total), None).finished());
+    
+    Ok(report)
+}
+
+// Helper: Import one file, skipping duplicates and resolving conflicts
+fn import_candidate(
+    candidate: &ImportCandidate,
+    dest: &Path,
+    options: &ImportOptions,
+    existing: &mut DedupeIndex,
+    report: &mut ImportReport,
+) -> std::io::Result<()> {
+    let source_hash = content_hash(&candidate.source)?;
+    
+    if let Some(duplicate) = existing.find(candidate.size, source_hash) {
+        report.skipped.push(SkippedFile {
+            path: candidate.source.to_string_lossy().to_string(),
+            reason: SkipReason::Duplicate { existing: duplicate.to_string_lossy().to_string() },
+        });
+        return Ok(());
+    }
+    
+    let target = dest.join(&candidate.relative);
+    if let Some(parent) = target.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    
+    if !target.exists() {
+        report.bytes_copied += fs::copy(&candidate.source, &target)?;
+        report.imported.push(target.to_string_lossy().to_string());
+        return Ok(());
+    }
+    
+    // A different file already lives at this path
+    let strategy = match options.on_conflict {
+        ConflictStrategy::Merge if !is_text_file(&target) => ConflictStrategy::Suffix,
+        other => other,
+    };
+    
+    let written_to = match strategy {
+        ConflictStrategy::Suffix => {
+            let conflict_path = conflict_path(&target);
+            report.bytes_copied += fs::copy(&candidate.source, &conflict_path)?;
+            conflict_path
+        }
+        ConflictStrategy::Merge => {
+            let incoming = fs::read_to_string(&candidate.source)?;
+            let mut merged = fs::read_to_string(&target)?;
+            if !merged.ends_with('\n') {
+                merged.push('\n');
+            }
+            merged.push_str(&format!(
+                "\n<!-- Merged from {} on {} -->\n\n{}",
+                candidate.source.display(),
+                chrono::Local::now().format("%Y-%m-%d %H:%M"),
+                incoming
+            ));
+            fs::write(&target, merged)?;
+            report.bytes_copied += incoming.len() as u64;
+            target.clone()
+        }
+        ConflictStrategy::Overwrite => {
+            report.bytes_copied += fs::copy(&candidate.source, &target)?;
+            target.clone()
+        }
+    };
+    
+    report.imported.push(written_to.to_string_lossy().to_string());
+    report.conflicts.push(ImportConflict {
+        source: candidate.source.to_string_lossy().to_string(),
+        existing: target.to_string_lossy().to_string(),
+        written_to: written_to.to_string_lossy().to_string(),
+        resolution: strategy,
+    });
+    Ok(())
+}
+
+/// Content hashes of the files already present in an import destination
+/// 
+/// Files are grouped by size first and only hashed when an incoming file
+/// has a matching size, so large destinations stay cheap to index.
+struct DedupeIndex {
+    by_size: HashMap<u64, Vec<PathBuf>>,
+    hashes: HashMap<PathBuf, u64>,
+}
+
+impl DedupeIndex {
+    /// Indexes the files below `dest`, following only symbolic links that
+    /// stay inside it
+    fn build(dest: &Path) -> Self {
+        let mut index = DedupeIndex { by_size: HashMap::new(), hashes: HashMap::new() };
+        let Ok(dest_canonical) = dest.canonicalize() else {
+            return index;
+        };
+        let mut visited = HashSet::from([dest_canonical.clone()]);
+        let mut stack = vec![dest.to_path_buf()];
+        
+        while let Some(dir) = stack.pop() {
+            let Ok(entries) = fs::read_dir(&dir) else { continue };
+            for entry in entries.filter_map(|e| e.ok()) {
+                let path = entry.path();
+                match walk_metadata(&path, &dest_canonical, SymlinkPolicy::AllowWithinWorkspace, &mut visited) {
+                    Some(m) if m.is_dir() => stack.push(path),
+                    Some(m) => index.by_size.entry(m.len()).or_default().push(path),
+                    None => {}
+                }
+            }
+        }
+        
+        index
+    }
+    
+    /// Finds an existing file with the given size and content hash
+    fn find(&mut self, size: u64, hash: u64) -> Option<PathBuf> {
+        let paths = self.by_size.get(&size)?;
+        
+        for path in paths {
+            let existing_hash = match self.hashes.get(path) {
+                Some(h) => *h,
+                None => {
+                    let Ok(h) = content_hash(path) else { continue };
+                    self.hashes.insert(path.clone(), h);
+                    h
+                }
+            };
+            if existing_hash == hash {
+                return Some(path.clone());
+            }
+        }
+        
+        None
+    }
+}
+
+// Helper: Hash a file's content (streamed, so large files are not loaded at once)
+fn content_hash(path: &Path) -> std::io::Result<u64> {
+    use std::hash::Hasher;
+    use std::io::Read;
+    
+    let mut file = fs::File::open(path)?;
+    let mut hasher = std::collections::hash_map::DefaultHasher::new();
+    let mut buffer = [0u8; 64 * 1024];
+    
+    loop {
+        let read = file.read(&mut buffer)?;
+        if read == 0 {
+            break;
+        }
+        hasher.write(&buffer[..read]);
+    }
+    
+    Ok(hasher.finish())
+}
+
+// Helper: Find a free `name-conflict.ext` (or `name-conflict-2.ext`, ...) next to a file
+fn conflict_path(target: &Path) -> PathBuf {
+    let stem = target.file_stem().map(|s| s.to_string_lossy().to_string()).unwrap_or_default();
+    let ext = target.extension().map(|e| format!(".{}", e.to_string_lossy())).unwrap_or_default();
+    
+    let mut n = 1;
+    loop {
+        let name = if n == 1 {
+            format!("{}-conflict{}", stem, ext)
+        } else {
+            format!("{}-conflict-{}{}", stem, n, ext)
+        };
+        let candidate = target.with_file_name(name);
+        if !candidate.exists() {
+            return candidate;
+        }
+        n += 1;
+    }
+}
+
+// Helper: Whether a file can be merged as text
+fn is_text_file(path: &Path) -> bool {
+    matches!(
+        path.extension().map(|e| e.to_string_lossy().to_lowercase()).as_deref(),
+        Some("md" | "markdown" | "txt")
+    )
+}
+
+// Helper: Walk the source tree applying ignore patterns, extension and size filters
+//
+// Symbolic links are followed only while they stay inside the source tree,
+// and each directory is scanned once (`visited`), so link cycles end the walk.
+fn scan_import_candidates(
+    root: &Path,
+    dir: &Path,
+    options: &ImportOptions,
+    visited: &mut HashSet<PathBuf>,
+    candidates: &mut Vec<ImportCandidate>,
+    report: &mut ImportReport,
+) -> Result<(), String> {
+    let root_canonical = root.canonicalize()
+        .map_err(|e| format!("Failed to resolve source directory: {}", e))?;
+    visited.insert(root_canonical.clone());
+    
+    let mut entries: Vec<_> = fs::read_dir(dir)
+        .map_err(|e| format!("Failed to read source directory: {}", e))?
+        .filter_map(|e| e.ok())
+        .collect();
+    entries.sort_by_key(|e| e.file_name());
+    
+    for entry in entries {
+        let path = entry.path();
+        let relative = path.strip_prefix(root)
+            .unwrap_or(&path)
+            .components()
+            .map(|c| c.as_os_str().to_string_lossy().to_string())
+            .collect::<Vec<_>>()
+            .join("/");
+        
+        let ignored = DEFAULT_IGNORE_PATTERNS.iter().any(|p| matches_glob(p, &relative))
+            || options.ignore_patterns.iter().any(|p| matches_glob(p, &relative));
+        if ignored {
+            report.ignored_count += 1;
+            continue;
+        }
+        
+        let Some(metadata) = walk_metadata(&path, &root_canonical, SymlinkPolicy::AllowWithinWorkspace, visited) else {
+            continue;
+        };
+        if metadata.is_dir() {
+            scan_import_candidates(root, &path, options, visited, candidates, report)?;
+            continue;
+        }
+        
+        if !options.extensions.is_empty() {
+            let ext = path.extension()
+                .map(|e| e.to_string_lossy().to_lowercase())
+                .unwrap_or_default();
+            let allowed = options.extensions.iter()
+                .any(|allowed| allowed.trim_start_matches('.').to_lowercase() == ext);
+            if !allowed {
+                report.skipped.push(SkippedFile {
+                    path: path.to_string_lossy().to_string(),
+                    reason: SkipReason::ExtensionNotAllowed,
+                });
+                continue;
+            }
+        }
+        
+        let size = metadata.len();
+        if options.max_file_size.is_some_and(|max| size > max) {
+            report.skipped.push(SkippedFile {
+                path: path.to_string_lossy().to_string(),
+                reason: SkipReason::TooLarge { size },
+            });
+            continue;
+        }
+        
+        candidates.push(ImportCandidate { source: path, relative, size });
+    }
+    
+    Ok(())
+}
+
+// Helper: Copy directory recursively (test fixture; `import_tree` is used for imports)
+#[cfg(test)]
+fn copy_dir_recursive(source: &PathBuf, dest: &PathBuf) -> Result<(), String> {
+    fs::create_dir_all(dest)
+        .map_err(|e| format!("Failed to create destination directory: {}", e))?;
+    
+    let entries = fs::read_dir(source)
+        .map_err(|e| format!("Failed to read source directory: {}", e))?;
+    
+    for entry in entries {
+        let entry = entry.map_err(|e| format!("Failed to read entry: {}", e))?;
+        let source_path = entry.path();
+        let file_name = entry.file_name();
+        let dest_path = dest.join(&file_name);
+        
+        if source_path.is_dir() {
+            copy_dir_recursive(&source_path, &dest_path)?;
+        } else {
+            fs::copy(&source_path, &dest_path)
+                .map_err(|e| format!("Failed to copy file: {}", e))?;
+        }
+    }
+    
+    Ok(())
+}
+
+// Helper: List all files in directory recursively (test fixture)
+#[cfg(test)]
+fn list_files_recursive(dir: &PathBuf) -> Result<Vec<String>, String> {
+    let mut files = Vec::new();
+    
+    let entries = fs::read_dir(dir)
+        .map_err(|e| format!("Failed to read directory: {}", e))?;
+    
+    for entry in entries {
+        let entry = entry.map_err(|e| format!("Failed to read entry: {}", e))?;
+        let path = entry.path();
+        
+        if path.is_dir() {
+            let sub_files = list_files_recursive(&path)?;
+            files.extend(sub_files);
+        } else {
+            files.push(path.to_string_lossy().to_string());
+        }
+    }
+    
+    Ok(files)
+}
+
+// ========================================
+// EXPORT OPERATIONS
+// ========================================
+
+// Helper: Checks an export destination against the export policy
+//
+// A destination inside the workspace must also be writable, so exports
+// never land in a read-only or archived folder.
+pub(crate) fn authorize_export(state: &AppState, workspace: &str, dest_path: &str) -> Result<PathBuf, String> {
+    let mut allowed = vec![PathBuf::from(workspace)];
+    allowed.extend(ExportFolders::load(&get_config_dir()?)?.get(workspace).iter().map(PathBuf::from));
+    
+    let dest = state.export_grants.authorize(Path::new(dest_path), &allowed)?;
+    ensure_writable(workspace, &dest)?;
+    Ok(dest)
+}
+
+/// Export the workspace (or a folder inside it) to a ZIP file.
+/// 
+/// When `password` is given, every entry is encrypted with AES-256
+/// (WinZip AE-2 format, readable by 7-Zip, WinZip and most modern tools).
+/// The passphrase is never logged.
+/// 
+/// Hidden files are skipped, except the `.mdreader/` settings folder so
+/// that folder flags survive a restore.
+/// 
+/// Security:
+/// - Source must be within the configured workspace
+/// - Destination must pass the export policy (see `crate::export_policy`)
+/// 
+/// # Returns
+/// * `Ok(usize)` - Number of files written to the archive
+#[command]
+pub async fn export_workspace_to_zip(
+    app_handle: AppHandle,
+    state: State<'_, AppState>,
+    workspace_path: String,
+    dest_path: String,
+    password: Option<String>,
+) -> Result<usize, String> {
+    let workspace = state.get_workspace_path()?;
+    
+    // Validate source is within workspace
+    let validated_source = validate_directory_path(&workspace_path, &workspace, true)
+        .map_err(|e| format!("Security error: {}", e))?;
+    
+    // Never write the archive into the folder being archived
+    let dest = resolve_destination(Path::new(&dest_path))?;
+    if dest.starts_with(&validated_source) {
+        return Err("Destination must be outside the exported folder".to_string());
+    }
+    let dest = authorize_export(&state, &workspace, &dest_path)?;
+    
+    let count = write_zip_archive(Path::new(&workspace), &validated_source, &dest, password.as_deref())?;
+    
+    log::info!(
+        "📦 Exported {} files to ZIP{}: {}",
+        count,
+        if password.is_some() { " (encrypted)" } else { "" },
+        dest_path
+    );
+    spawn_hooks(&app_handle, &workspace, HookEvent::AfterExport, None, Some(dest));
+    Ok(count)
+}
+
+// Helper: Write a directory tree of the workspace into a ZIP archive
+//
+// Symbolic links are followed according to the workspace symlink policy,
+// and each directory is archived once, so link cycles end the walk.
+fn write_zip_archive(workspace: &Path, source: &Path, dest: &Path, password: Option<&str>) -> Result<usize, String> {
+    use zip::write::SimpleFileOptions;
+    use zip::{AesMode, CompressionMethod, ZipWriter};
+    
+    if password.is_some_and(|p| p.is_empty()) {
+        return Err("Passphrase must not be empty".to_string());
+    }
+    
+    let file = fs::File::create(dest)
+        .map_err(|e| format!("Failed to create ZIP file: {}", e))?;
+    let mut zip = ZipWriter::new(file);
+    
+    let mut options = SimpleFileOptions::default()
+        .compression_method(CompressionMethod::Deflated)
+        .unix_permissions(0o644);
+    if let Some(password) = password {
+        options = options.with_aes_encryption(AesMode::Aes256, password);
+    }
+    
+    let policy = WorkspaceSettings::load_symlink_policy(workspace);
+    let workspace_canonical = workspace.canonicalize()
+        .map_err(|e| format!("Failed to resolve workspace: {}", e))?;
+    let mut visited = HashSet::from([source.canonicalize()
+        .map_err(|e| format!("Failed to resolve folder: {}", e))?]);
+    
+    let mut count = 0;
+    let mut stack = vec![source.to_path_buf()];
+    
+    while let Some(dir) = stack.pop() {
+        let mut entries: Vec<_> = fs::read_dir(&dir)
+            .map_err(|e| format!("Failed to read directory: {}", e))?
+            .filter_map(|e| e.ok())
+            .collect();
+        entries.sort_by_key(|e| e.file_name());
+        
+        for entry in entries {
+            let path = entry.path();
+            let name = entry.file_name().to_string_lossy().to_string();
+            
+            if name.starts_with('.') && name != SETTINGS_DIR && name != MINDMAP_DIR {
+                continue;
+            }
+            
+            let relative = path.strip_prefix(source)
+                .map_err(|e| format!("Failed to build archive path: {}", e))?
+                .components()
+                .map(|c| c.as_os_str().to_string_lossy().to_string())
+                .collect::<Vec<_>>()
+                .join("/");
+            
+            let Some(metadata) = walk_metadata(&path, &workspace_canonical, policy, &mut visited) else {
+                continue;
+            };
+            if metadata.is_dir() {
+                zip.add_directory(format!("{}/", relative), options)
+                    .map_err(|e| format!("Failed to add directory to ZIP: {}", e))?;
+                stack.push(path);
+            } else {
+                zip.start_file(relative, options)
+                    .map_err(|e| format!("Failed to add file to ZIP: {}", e))?;
+                let mut source_file = fs::File::open(&path)
+                    .map_err(|e| format!("Failed to read file: {}", e))?;
+                std::io::copy(&mut source_file, &mut zip)
+                    .map_err(|e| format!("Failed to write file to ZIP: {}", e))?;
+                count += 1;
+            }
+        }
+    }
+    
+    zip.finish()
+        .map_err(|e| format!("Failed to finish ZIP file: {}", e))?;
+    
+    Ok(count)
+}
+
+/// Export a document from the workspace.
+/// 
+/// Markdown documents have their `![[embeds]]` flattened into the exported
+/// copy unless `flatten_embeds` is false. With `include_assets`, images the
+/// document references are copied to `<name>_assets/` next to the export
+/// (`"copy"`) or inlined as `data:` URIs (`"embed"`), and the links are
+/// rewritten to match.
+/// 
+/// Security:
+/// - Source must be within the configured workspace
+/// - Destination must pass the export policy (see `crate::export_policy`)
+/// - Only assets inside the workspace are bundled
+/// 
+/// # Returns
+/// * `Ok(AssetReport)` - Bundled and unresolved images (empty without `include_assets`)
+#[command]
+pub async fn export_document(
+    app_handle: AppHandle,
+    state: State<'_, AppState>,
+    document_path: String,
+    dest_path: String,
+    flatten_embeds: Option<bool>,
+    include_assets: Option<AssetMode>,
+) -> Result<AssetReport, String> {
+    let workspace = state.get_workspace_path()?;
+    
+    // Validate source is within workspace
+    let validated_source = crate::utils::validate_path_within_workspace(&document_path, &workspace)
+        .map_err(|e| format!("Security error: {}", e))?;
+    
+    if !validated_source.exists() {
+        return Err(format!("Document does not exist: {}", document_path));
+    }
+    
+    let dest = authorize_export(&state, &workspace, &dest_path)?;
+    
+    let is_markdown = validated_source.extension().is_some_and(|e| e.eq_ignore_ascii_case("md"));
+    let flatten = is_markdown && flatten_embeds.unwrap_or(true);
+    let mut report = AssetReport::default();
+    
+    if flatten || (is_markdown && include_assets.is_some()) {
+        let root = Path::new(&workspace)
+            .canonicalize()
+            .map_err(|e| format!("Failed to resolve workspace: {}", e))?;
+        let mut content = if flatten {
+            let content = transclusion::resolve_transclusions(&root, &validated_source, DEFAULT_DEPTH)?;
+            let read = |relative: &str| fs::read_to_string(root.join(relative)).ok();
+            csv_table::expand_csv_blocks(&content, &relative_path(&root, &validated_source), &read)
+        } else {
+            fs::read_to_string(&validated_source)
+                .map_err(|e| format!("Failed to read document: {}", e))?
+        };
+        if let Some(mode) = include_assets {
+            (content, report) = bundle_assets(&content, &validated_source, &root, mode, &dest)?;
+        }
+        fs::write(&dest, content)
+            .map_err(|e| format!("Failed to export document: {}", e))?;
+    } else {
+        fs::copy(&validated_source, &dest)
+            .map_err(|e| format!("Failed to export document: {}", e))?;
+    }
+    
+    if !report.missing.is_empty() {
+        log::warn!("⚠️ Export left {} unresolved image links", report.missing.len());
+    }
+    log::info!("📤 Exported: {} → {} ({} assets)", document_path, dest_path, report.bundled.len());
+    spawn_hooks(&app_handle, &workspace, HookEvent::AfterExport, Some(validated_source), Some(dest));
+    Ok(report)
+}
+
+/// Converts a note to another markdown flavor for use in other tools.
+/// 
+/// Task lists, tables, footnotes, `==highlights==` and callouts are
+/// rewritten into what `to` understands (see `crate::flavor`); `from` is
+/// the flavor the note is written in (`gfm` for notes of the editor). The
+/// note itself is left unchanged.
+/// 
+/// Security: Validates path is a markdown file within the workspace.
+/// 
+/// # Returns
+/// * `Ok(FlavorConversion)` - The converted markdown and what was rewritten
+#[command]
+pub async fn convert_flavor(
+    state: State<'_, AppState>,
+    path: String,
+    from: MarkdownFlavor,
+    to: MarkdownFlavor,
+) -> Result<FlavorConversion, String> {
+    let workspace = state.get_workspace_path()?;
+    
+    let validated_path = validate_file_path(&path, &workspace, &["md"])
+        .map_err(|e| format!("Security error: {}", e))?;
+    
+    // Make sure a pending debounced save is on disk before reading
+    state.save_queue.flush_path_async(&validated_path).await?;
+    
+    let content = fs::read_to_string(&validated_path)
+        .map_err(|e| format!("Failed to read document: {}", e))?;
+    let conversion = flavor::convert_flavor(&content, from, to);
+    
+    log::info!("🔀 Converted {:?} from {:?} to {:?}", validated_path, from, to);
+    Ok(conversion)
+}
+
+/// Inlines the images a note references as `data:` URIs.
+/// 
+/// Used by the HTML and PDF exports, which render in the webview and would
+/// otherwise lose relative images. Remote and unresolvable images are left
+/// as written.
+/// 
+/// Security: Validates file_path is a markdown file within the workspace;
+/// only images inside the workspace are read.
+#[command]
+pub async fn embed_document_assets(
+    state: State<'_, AppState>,
+    file_path: String,
+    content: Option<String>,
+) -> Result<String, String> {
+    let workspace = state.get_workspace_path()?;
+    
+    let validated_path = validate_file_path(&file_path, &workspace, &["md"])
+        .map_err(|e| format!("Security error: {}", e))?;
+    
+    let root = Path::new(&workspace)
+        .canonicalize()
+        .map_err(|e| format!("Failed to resolve workspace: {}", e))?;
+    
+    // The frontend may pass the rendered-for-export text instead of the file
+    let content = match content {
+        Some(content) => content,
+        None => fs::read_to_string(&validated_path)
+            .map_err(|e| format!("Failed to read document: {}", e))?,
+    };
+    
+    let (embedded, _) = bundle_assets(&content, &validated_path, &root, AssetMode::Embed, &validated_path)?;
+    Ok(embedded)
+}
+
+/// Expands the `![[embeds]]` of a note for preview rendering.
+/// 
+/// Embeds are resolved recursively up to `depth` levels (default 5,
+/// at most 10); cyclic, missing and too-deep embeds stay as written.
+/// 
+/// Security: Validates file_path is a markdown file within the workspace.
+#[command]
+pub async fn resolve_transclusions(
+    state: State<'_, AppState>,
+    file_path: String,
+    depth: Option<usize>,
+) -> Result<String, String> {
+    let workspace = state.get_workspace_path()?;
+    
+    let validated_path = validate_file_path(&file_path, &workspace, &["md"])
+        .map_err(|e| format!("Security error: {}", e))?;
+    
+    let root = Path::new(&workspace)
+        .canonicalize()
+        .map_err(|e| format!("Failed to resolve workspace: {}", e))?;
+    
+    transclusion::resolve_transclusions(&root, &validated_path, depth.unwrap_or(DEFAULT_DEPTH))
+}
+
+/// Renders a note to the HTML the editor preview shows.
+/// 
+/// Preview windows, share links, print and HTML exports all render
+/// through this command: embeds are expanded, wiki links resolved, code
+/// highlighted and mermaid blocks marked for the diagram runtime (see
+/// `crate::render`); diagrams rendered before come from the cache (see
+/// `crate::diagrams`). The frontend may pass unsaved `content` instead of
+/// the file.
+/// 
+/// Security: Validates file_path is a markdown file within the workspace;
+/// only embeds and images inside the workspace are read.
+/// 
+/// # Returns
+/// * `Ok(String)` - An HTML fragment, or a full document with `standalone`
+#[command]
+pub async fn render_note_html(
+    state: State<'_, AppState>,
+    file_path: String,
+    options: Option<RenderOptions>,
+    content: Option<String>,
+) -> Result<String, String> {
+    let workspace = state.get_workspace_path()?;
+    let options = options.unwrap_or_default();
+    
+    let validated_path = validate_file_path(&file_path, &workspace, &["md"])
+        .map_err(|e| format!("Security error: {}", e))?;
+    
+    let (title, html) = render_note(&state, &workspace, &validated_path, &options, content)?;
+    if options.standalone {
+        Ok(html_document(&title, &html))
+    } else {
+        Ok(html)
+    }
+}
+
+/// Copies a note, or a selection of it, to the clipboard as HTML.
+/// 
+/// The markdown is rendered like `render_note_html` (images embedded as
+/// `data:` URIs) so pasting into mail or document editors keeps the
+/// formatting; the markdown itself goes along as plain text for apps that
+/// only take text.
+/// 
+/// Security: Validates file_path is a markdown file within the workspace;
+/// only embeds and images inside the workspace are read.
+/// 
+/// # Arguments
+/// * `file_path` - The note, also used to resolve links of a selection
+/// * `selection` - Markdown selected in the editor, instead of the whole note
+/// 
+/// # Returns
+/// * `Ok(String)` - The HTML placed on the clipboard
+#[command]
+pub async fn copy_as_html(
+    state: State<'_, AppState>,
+    file_path: String,
+    selection: Option<String>,
+) -> Result<String, String> {
+    let (markdown, html) = render_for_clipboard(&state, &file_path, selection)?;
+    write_clipboard(ClipboardFormat::Html, &html, markdown)?;
+    log::info!("📋 Copied {} as HTML ({} bytes)", file_path, html.len());
+    Ok(html)
+}
+
+/// Copies a note, or a selection of it, to the clipboard as rich text (RTF).
+/// 
+/// For apps that take rich text but not HTML from the clipboard. Rendered
+/// like `copy_as_html`, then converted (see `crate::rtf`); images are left
+/// out. The markdown goes along as plain text.
+/// 
+/// Security: Validates file_path is a markdown file within the workspace.
+/// 
+/// # Returns
+/// * `Ok(String)` - The RTF placed on the clipboard
 #[command]
-pub async fn import_folder(
+pub async fn copy_as_rtf(
     state: State<'_, AppState>,
-    source_path: String,
-    dest_folder: String,
-) -> Result<Vec<String>, String> {
+    file_path: String,
+    selection: Option<String>,
+) -> Result<String, String> {
+    let (markdown, html) = render_for_clipboard(&state, &file_path, selection)?;
+    let rtf = html_to_rtf(&html);
+    write_clipboard(ClipboardFormat::Rtf, &rtf, markdown)?;
+    log::info!("📋 Copied {} as RTF ({} bytes)", file_path, rtf.len());
+    Ok(rtf)
+}
+
+/// Cleans up clipboard content for the editor's paste handler: HTML is
+/// converted to markdown, tracking parameters are removed from URLs, curly
+/// quotes are straightened and links to files in the workspace are made
+/// relative (see `crate::paste`).
+/// 
+/// Security: Validates file_path is a markdown file within the workspace.
+/// 
+/// # Arguments
+/// * `html_or_text` - The clipboard's HTML, or its text if it has none
+/// * `file_path` - Note pasted into; file links become vault-absolute without it
+#[command]
+pub async fn clean_pasted_content(
+    state: State<'_, AppState>,
+    html_or_text: String,
+    options: Option<PasteOptions>,
+    file_path: Option<String>,
+) -> Result<CleanedPaste, String> {
     let workspace = state.get_workspace_path()?;
+    let note = match file_path {
+        Some(file_path) => {
+            let validated_path = validate_file_path(&file_path, &workspace, &["md"])
+                .map_err(|e| format!("Security error: {}", e))?;
+            Some(relative_path(Path::new(&workspace), &validated_path))
+        }
+        None => None,
+    };
     
-    // Validate destination is within workspace
-    let validated_dest = validate_directory_path(&dest_folder, &workspace, true)
+    let options = options.unwrap_or_default();
+    Ok(paste::clean_pasted_content(&html_or_text, &options, Path::new(&workspace), note.as_deref()))
+}
+
+/// Rich format placed on the clipboard next to the plain markdown
+enum ClipboardFormat {
+    Html,
+    Rtf,
+}
+
+// Helper: Renders a note or selection for the clipboard; returns the
+// markdown and the HTML
+fn render_for_clipboard(
+    state: &AppState,
+    file_path: &str,
+    selection: Option<String>,
+) -> Result<(String, String), String> {
+    let workspace = state.get_workspace_path()?;
+    let validated_path = validate_file_path(file_path, &workspace, &["md"])
         .map_err(|e| format!("Security error: {}", e))?;
+    let markdown = match selection {
+        Some(selection) => selection,
+        None => fs::read_to_string(&validated_path)
+            .map_err(|e| format!("Failed to read document: {}", e))?,
+    };
     
-    let source = PathBuf::from(&source_path);
-    
-    if !source.exists() {
-        return Err(format!("Source folder does not exist: {}", source_path));
-    }
+    // Pasted HTML has no stylesheet for code tokens or a base path for
+    // images, and rich text no place for SVG diagrams
+    let options = RenderOptions {
+        highlight_code: false,
+        embed_images: true,
+        cached_diagrams: false,
+        ..RenderOptions::default()
+    };
+    let (_, html) = render_note(state, &workspace, &validated_path, &options, Some(markdown.clone()))?;
+    Ok((markdown, html))
+}
+
+// Helper: Places rich content and its plain text on the OS clipboard
+#[cfg(desktop)]
+fn write_clipboard(format: ClipboardFormat, rich: &str, plain: String) -> Result<(), String> {
+    use clipboard_rs::{Clipboard, ClipboardContent, ClipboardContext};
     
-    if !source.is_dir() {
-        return Err(format!("Source is not a directory: {}", source_path));
+    let rich = match format {
+        ClipboardFormat::Html => ClipboardContent::Html(rich.to_string()),
+        ClipboardFormat::Rtf => ClipboardContent::Rtf(rich.to_string()),
+    };
+    ClipboardContext::new()
+        .and_then(|clipboard| clipboard.set(vec![rich, ClipboardContent::Text(plain)]))
+        .map_err(|e| format!("Failed to write clipboard: {}", e))
+}
+
+#[cfg(not(desktop))]
+fn write_clipboard(_format: ClipboardFormat, _rich: &str, _plain: String) -> Result<(), String> {
+    Err("Rich text copy is not supported on this platform".to_string())
+}
+
+/// Mail clients can only be reached on desktop
+const EMAIL_SUPPORTED: bool = cfg!(desktop);
+
+/// Opens the default mail client with a draft of a note.
+///
+/// The note's title is the subject. With `plain` the markdown (without
+/// frontmatter) is the message text; `html` attaches the note rendered
+/// like `render_note_html` (images embedded), `pdf` a text-only PDF of it
+/// (see `crate::text_pdf`). Recipients are left to the user; see
+/// `crate::email` for how each platform's mail client is reached.
+///
+/// Security: Validates path is a markdown file within the workspace; only
+/// embeds and images inside the workspace are read.
+///
+/// # Returns
+/// * `Ok(EmailDraft)` - Subject and attachment of the draft
+#[command]
+pub async fn share_via_email(
+    state: State<'_, AppState>,
+    path: String,
+    format: EmailFormat,
+) -> Result<EmailDraft, String> {
+    if !EMAIL_SUPPORTED {
+        return Err("Sharing via email is not supported on this platform".to_string());
     }
+    let workspace = state.get_workspace_path()?;
+    let validated_path = validate_file_path(&path, &workspace, &["md"])
+        .map_err(|e| format!("Security error: {}", e))?;
+    let content = fs::read_to_string(&validated_path)
+        .map_err(|e| format!("Failed to read document: {}", e))?;
+
+    // The mail has no stylesheet for code tokens or a base path for images
+    let options = RenderOptions { highlight_code: false, embed_images: true, ..RenderOptions::default() };
+    let (title, html) = render_note(&state, &workspace, &validated_path, &options, Some(content.clone()))?;
+    let body = frontmatter::split(&content).1.trim();
+    let draft = match format {
+        EmailFormat::Plain => EmailDraft { subject: title, body: body.to_string(), attachment: None },
+        EmailFormat::Html => {
+            let attachment = email::write_attachment(&title, "html", html_document(&title, &html).as_bytes())?;
+            EmailDraft { subject: title, body: String::new(), attachment: Some(attachment) }
+        }
+        EmailFormat::Pdf => {
+            let attachment = email::write_attachment(&title, "pdf", &markdown_to_pdf(&title, body))?;
+            EmailDraft { subject: title, body: String::new(), attachment: Some(attachment) }
+        }
+    };
+
+    let draft = tauri::async_runtime::spawn_blocking(move || email::compose(&draft).map(|_| draft))
+        .await
+        .map_err(|e| format!("Failed to open mail client: {}", e))??;
+    log::info!("✉️ Shared {} via email ({:?})", path, format);
+    Ok(draft)
+}
+
+/// Prints a note through the OS print dialog.
+/// 
+/// The note is rendered like `render_note_html` (images embedded) with
+/// print CSS: paper size and margins, page breaks before `# headings`,
+/// and the title and page numbers in the page margins (see
+/// `crate::print`). The document opens in a print window whose print
+/// dialog is shown once it has loaded.
+/// 
+/// Security: Validates file_path is a markdown file within the workspace;
+/// only embeds and images inside the workspace are read.
+/// 
+/// # Returns
+/// * `Ok(String)` - Label of the print window
+#[command]
+pub async fn print_document(
+    app_handle: AppHandle,
+    state: State<'_, AppState>,
+    file_path: String,
+    options: Option<PrintOptions>,
+) -> Result<String, String> {
+    let workspace = state.get_workspace_path()?;
+    let mut options = options.unwrap_or_default();
+    // The print window has no base path for relative images
+    options.render.embed_images = true;
     
-    // Get and sanitize folder name
-    let folder_name = source.file_name()
-        .ok_or("Failed to get folder name")?
-        .to_string_lossy()
-        .to_string();
-    
-    let sanitized_name = sanitize_filename(&folder_name);
-    let dest_path = validated_dest.join(&sanitized_name);
+    let validated_path = validate_file_path(&file_path, &workspace, &["md"])
+        .map_err(|e| format!("Security error: {}", e))?;
     
-    // Copy directory recursively
-    copy_dir_recursive(&source, &dest_path)?;
+    let (title, html) = render_note(&state, &workspace, &validated_path, &options.render, None)?;
+    let document = print::print_document(&title, &html, &options);
+    // A JSON string is a valid JavaScript string literal
+    let script = format!(
+        "document.open();document.write({});document.close();",
+        serde_json::to_string(&document).map_err(|e| format!("Failed to prepare document: {}", e))?
+    );
     
-    log::info!("📥 Imported folder: {} → {}", source_path, dest_path.display());
+    let printed = AtomicBool::new(false);
+    let label = format!("{}{}", print::PRINT_WINDOW_PREFIX, uuid::Uuid::new_v4());
+    let blank = "about:blank".parse().map_err(|e| format!("Invalid print URL: {}", e))?;
+    WebviewWindowBuilder::new(&app_handle, label.clone(), WebviewUrl::External(blank))
+        .title(format!("Print: {}", title))
+        .inner_size(800.0, 900.0)
+        .on_page_load(move |window, payload| {
+            // Writing the document must not print it a second time
+            if payload.event() != PageLoadEvent::Finished || printed.swap(true, Ordering::SeqCst) {
+                return;
+            }
+            if let Err(e) = window.eval(&script).and_then(|_| window.print()) {
+                log::error!("❌ Failed to print: {}", e);
+            }
+        })
+        .build()
+        .map_err(|e| format!("Failed to open print window: {}", e))?;
     
-    // Return list of imported files
-    let imported_files = list_files_recursive(&dest_path)?;
-    Ok(imported_files)
+    log::info!("🖨️ Printing {} in {}", file_path, label);
+    Ok(label)
 }
 
-// Helper: Copy directory recursively
-fn copy_dir_recursive(source: &PathBuf, dest: &PathBuf) -> Result<(), String> {
-    fs::create_dir_all(dest)
-        .map_err(|e| format!("Failed to create destination directory: {}", e))?;
-    
-    let entries = fs::read_dir(source)
-        .map_err(|e| format!("Failed to read source directory: {}", e))?;
+// Helper: Renders a workspace note through the shared HTML pipeline
+//
+// Returns the note title and the HTML fragment. `content` replaces the
+// file's content (unsaved editor text).
+fn render_note(
+    state: &AppState,
+    workspace: &str,
+    validated_path: &Path,
+    options: &RenderOptions,
+    content: Option<String>,
+) -> Result<(String, String), String> {
+    let root = Path::new(workspace)
+        .canonicalize()
+        .map_err(|e| format!("Failed to resolve workspace: {}", e))?;
+    let from = relative_path(&root, validated_path);
+    let (lookup, title) = state.metadata.with_fresh(workspace, |cache| {
+        (cache.lookup(), cache.notes.get(&from).map(|note| note.title.clone()))
+    })?;
     
-    for entry in entries {
-        let entry = entry.map_err(|e| format!("Failed to read entry: {}", e))?;
-        let source_path = entry.path();
-        let file_name = entry.file_name();
-        let dest_path = dest.join(&file_name);
-        
-        if source_path.is_dir() {
-            copy_dir_recursive(&source_path, &dest_path)?;
-        } else {
-            fs::copy(&source_path, &dest_path)
-                .map_err(|e| format!("Failed to copy file: {}", e))?;
-        }
+    let content = match content {
+        Some(content) => content,
+        None => fs::read_to_string(validated_path)
+            .map_err(|e| format!("Failed to read document: {}", e))?,
+    };
+    let read = |relative: &str| fs::read_to_string(root.join(relative)).ok();
+    let depth = options.embed_depth.min(transclusion::MAX_DEPTH);
+    let mut content = transclusion::expand(&content, &from, depth, &lookup, &read);
+    content = csv_table::expand_csv_blocks(&content, &from, &read);
+    if options.embed_images {
+        content = bundle_assets(&content, validated_path, &root, AssetMode::Embed, validated_path)?.0;
     }
     
-    Ok(())
+    let title = title.unwrap_or_else(|| {
+        let name = from.rsplit('/').next().unwrap_or(&from);
+        name.strip_suffix(".md").unwrap_or(name).to_string()
+    });
+    let html = render_markdown(&content, &from, &lookup, options.highlight_code);
+    if options.cached_diagrams {
+        return Ok((title, diagrams::inline_cached(&html, &root)));
+    }
+    Ok((title, html))
 }
 
-// Helper: List all files in directory recursively
-fn list_files_recursive(dir: &PathBuf) -> Result<Vec<String>, String> {
-    let mut files = Vec::new();
-    
-    let entries = fs::read_dir(dir)
-        .map_err(|e| format!("Failed to read directory: {}", e))?;
-    
-    for entry in entries {
-        let entry = entry.map_err(|e| format!("Failed to read entry: {}", e))?;
-        let path = entry.path();
-        
-        if path.is_dir() {
-            let sub_files = list_files_recursive(&path)?;
-            files.extend(sub_files);
-        } else {
-            files.push(path.to_string_lossy().to_string());
-        }
+/// Export a dataset of notes as CSV or JSON.
+/// 
+/// Runs `query` over the workspace (folder, tags and frontmatter field
+/// filters) and writes one row per matching note with the selected
+/// frontmatter fields and, optionally, text statistics.
+/// 
+/// Security:
+/// - Notes are read from the configured workspace only
+/// - Destination must pass the export policy (see `crate::export_policy`)
+/// 
+/// # Returns
+/// * `Ok(usize)` - Number of notes exported
+#[command]
+pub async fn export_query_results(
+    app_handle: AppHandle,
+    state: State<'_, AppState>,
+    query: NoteQuery,
+    format: DatasetFormat,
+    dest_path: String,
+) -> Result<usize, String> {
+    let workspace = state.get_workspace_path()?;
+    let root = Path::new(&workspace);
+
+    if let Some(folder) = &query.folder {
+        validate_directory_path(&root.join(folder).to_string_lossy(), &workspace, true)
+            .map_err(|e| format!("Security error: {}", e))?;
     }
-    
-    Ok(files)
-}
 
-// ========================================
-// EXPORT OPERATIONS
-// ========================================
+    let dest = authorize_export(&state, &workspace, &dest_path)?;
 
-/// Export entire workspace to a ZIP file.
-/// 
-/// TODO: Implement with zip crate
-#[allow(dead_code)]
-async fn export_workspace_to_zip(
-    _state: State<'_, AppState>,
-    _workspace_path: String,
-    _dest_path: String,
-) -> Result<(), String> {
-    // TODO: Implement ZIP export
-    // Requires adding zip crate to Cargo.toml
-    Err("ZIP export not yet implemented".to_string())
+    let notes = query.run(root)?;
+    let output = query.render(&notes, format)?;
+
+    fs::write(&dest, output)
+        .map_err(|e| format!("Failed to export query results: {}", e))?;
+
+    log::info!("📤 Exported {} notes as {:?} → {}", notes.len(), format, dest_path);
+    spawn_hooks(&app_handle, &workspace, HookEvent::AfterExport, None, Some(dest));
+    Ok(notes.len())
 }
 
-/// Export a document from the workspace.
+/// Export the events written in notes as an iCalendar (`.ics`) file.
 /// 
-/// Security:
-/// - Source must be within the configured workspace
-/// - Destination can be anywhere (user selects via dialog)
+/// All events of the workspace are written (see `crate::calendar`), so
+/// any calendar app can import or subscribe to the notes' planner; each
+/// event keeps its identifier across exports, so re-importing updates it.
+/// 
+/// Security: Destination must pass the export policy (see
+/// `crate::export_policy`).
+/// 
+/// # Returns
+/// * `Ok(usize)` - Number of events exported
 #[command]
-pub async fn export_document(
+pub async fn export_calendar(
+    app_handle: AppHandle,
     state: State<'_, AppState>,
-    document_path: String,
     dest_path: String,
-) -> Result<(), String> {
+) -> Result<usize, String> {
     let workspace = state.get_workspace_path()?;
-    
-    // Validate source is within workspace
-    let validated_source = crate::utils::validate_path_within_workspace(&document_path, &workspace)
-        .map_err(|e| format!("Security error: {}", e))?;
-    
-    if !validated_source.exists() {
-        return Err(format!("Document does not exist: {}", document_path));
-    }
-    
-    let dest = PathBuf::from(&dest_path);
-    
-    // Ensure destination directory exists
-    if let Some(parent) = dest.parent() {
-        if !parent.exists() {
-            return Err(format!("Destination directory does not exist: {:?}", parent));
-        }
-    }
-    
-    fs::copy(&validated_source, &dest)
-        .map_err(|e| format!("Failed to export document: {}", e))?;
-    
-    log::info!("📤 Exported: {} → {}", document_path, dest_path);
-    Ok(())
+    let dest = authorize_export(&state, &workspace, &dest_path)?;
+
+    let events = state.metadata.with_fresh(&workspace, |cache| calendar::collect(cache, None, None))?;
+    fs::write(&dest, calendar::to_ics(&events, chrono::Utc::now()))
+        .map_err(|e| format!("Failed to export calendar: {}", e))?;
+
+    log::info!("📅 Exported {} events → {}", events.len(), dest_path);
+    spawn_hooks(&app_handle, &workspace, HookEvent::AfterExport, None, Some(dest));
+    Ok(events.len())
 }
 
 // ============================================================================
@@ -652,4 +2080,267 @@ mod tests {
         assert!(files.iter().any(|f| f.contains("doc2.txt")));  // Also included
         assert!(files.iter().any(|f| f.contains("doc3.md")));
     }
+
+    // ========================================================================
+    // ZIP EXPORT TESTS
+    // ========================================================================
+
+    #[test]
+    fn test_write_zip_archive_counts_files() {
+        let source = create_source_with_files();
+        let dest_dir = TempDir::new().unwrap();
+        let dest = dest_dir.path().join("backup.zip");
+        
+        let expected = list_files_recursive(&source.path().to_path_buf()).unwrap().len();
+        let count = write_zip_archive(source.path(), source.path(), &dest, None).expect("Export should succeed");
+        
+        assert_eq!(count, expected, "Every file should be archived");
+        assert!(dest.exists(), "ZIP file should be created");
+    }
+
+    #[test]
+    fn test_write_zip_archive_with_password() {
+        let source = create_source_with_files();
+        let dest_dir = TempDir::new().unwrap();
+        let dest = dest_dir.path().join("backup.zip");
+        
+        let count = write_zip_archive(source.path(), source.path(), &dest, Some("correct horse")).expect("Encrypted export should succeed");
+        assert!(count > 0);
+        assert!(dest.exists());
+    }
+
+    #[test]
+    fn test_write_zip_archive_rejects_empty_password() {
+        let source = create_source_with_files();
+        let dest_dir = TempDir::new().unwrap();
+        let dest = dest_dir.path().join("backup.zip");
+        
+        let result = write_zip_archive(source.path(), source.path(), &dest, Some(""));
+        assert!(result.is_err(), "Empty passphrase should be rejected");
+        assert!(!dest.exists(), "No archive should be written");
+    }
+
+    #[test]
+    fn test_write_zip_archive_skips_hidden_files() {
+        let source = TempDir::new().unwrap();
+        fs::write(source.path().join("visible.md"), "# Visible").unwrap();
+        fs::write(source.path().join(".DS_Store"), "junk").unwrap();
+        fs::create_dir_all(source.path().join(SETTINGS_DIR)).unwrap();
+        fs::write(source.path().join(SETTINGS_DIR).join("settings.json"), "{}").unwrap();
+        
+        let dest_dir = TempDir::new().unwrap();
+        let count = write_zip_archive(source.path(), source.path(), &dest_dir.path().join("b.zip"), None).unwrap();
+        
+        assert_eq!(count, 2, "Visible file and workspace settings should be archived");
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn test_write_zip_archive_applies_symlink_policy() {
+        let workspace = create_source_with_files();
+        let outside = TempDir::new().unwrap();
+        fs::write(outside.path().join("secret.md"), "# Secret").unwrap();
+        std::os::unix::fs::symlink(outside.path(), workspace.path().join("external")).unwrap();
+        std::os::unix::fs::symlink(workspace.path(), workspace.path().join("subfolder").join("loop")).unwrap();
+        
+        let dest_dir = TempDir::new().unwrap();
+        let count = write_zip_archive(workspace.path(), workspace.path(), &dest_dir.path().join("b.zip"), None)
+            .expect("A link cycle must not stop the export");
+        
+        assert_eq!(count, 3, "Only the workspace's own files should be archived");
+    }
+
+    // ========================================================================
+    // STREAMED IMPORT TESTS
+    // ========================================================================
+
+    fn run_import(source: &Path, dest: &Path, options: &ImportOptions) -> (ImportReport, Vec<JobProgress>) {
+        let state = AppState::new();
+        let job = state.jobs.start("import_folder").unwrap();
+        let mut events = Vec::new();
+        let report = import_tree(source, dest, options, &job, &mut |p| events.push(p.clone()))
+            .expect("Import should succeed");
+        (report, events)
+    }
+
+    #[test]
+    fn test_import_tree_reports_progress() {
+        let source = create_source_with_files();
+        let dest_dir = TempDir::new().unwrap();
+        let dest = dest_dir.path().join("imported");
+        
+        let (report, events) = run_import(source.path(), &dest, &ImportOptions::default());
+        
+        assert_eq!(report.imported.len(), 3);
+        assert!(dest.join("subfolder").join("nested.md").exists(), "Nested files should be copied");
+        assert_eq!(events.len(), 4, "One event per file plus the final event");
+        assert_eq!(events[0].total, Some(3));
+        assert!(events.last().unwrap().done);
+    }
+
+    #[test]
+    fn test_import_tree_filters() {
+        let source = create_source_with_files();
+        fs::write(source.path().join("image.png"), vec![0u8; 10]).unwrap();
+        fs::write(source.path().join("big.md"), "x".repeat(2048)).unwrap();
+        fs::create_dir_all(source.path().join("node_modules")).unwrap();
+        fs::write(source.path().join("node_modules").join("pkg.md"), "# pkg").unwrap();
+        fs::write(source.path().join("draft.tmp"), "tmp").unwrap();
+        
+        let options = ImportOptions {
+            ignore_patterns: vec!["*.tmp".to_string()],
+            extensions: vec!["md".to_string()],
+            max_file_size: Some(1024),
+            ..Default::default()
+        };
+        let dest_dir = TempDir::new().unwrap();
+        let (report, _) = run_import(source.path(), &dest_dir.path().join("imported"), &options);
+        
+        assert_eq!(report.imported.len(), 3, "Only the small markdown files should be imported");
+        assert_eq!(report.ignored_count, 2, "node_modules and *.tmp should be ignored");
+        assert!(report.skipped.iter().any(|s| s.reason == SkipReason::ExtensionNotAllowed && s.path.ends_with("image.png")));
+        assert!(report.skipped.iter().any(|s| matches!(s.reason, SkipReason::TooLarge { size: 2048 })));
+    }
+
+    #[test]
+    fn test_import_tree_cancelled() {
+        let source = create_source_with_files();
+        let dest_dir = TempDir::new().unwrap();
+        let state = AppState::new();
+        let job = state.jobs.start("import_folder").unwrap();
+        
+        // Cancel before any file is copied
+        state.jobs.cancel(&job.id).unwrap();
+        let report = import_tree(source.path(), &dest_dir.path().join("imported"), &ImportOptions::default(), &job, &mut |_| {})
+            .unwrap();
+        
+        assert!(report.cancelled, "Report should be flagged as cancelled");
+        assert!(report.imported.is_empty());
+    }
+
+    // ========================================================================
+    // IMPORT DEDUPLICATION TESTS
+    // ========================================================================
+
+    #[test]
+    fn test_reimport_skips_identical_files() {
+        let source = create_source_with_files();
+        let dest_dir = TempDir::new().unwrap();
+        let dest = dest_dir.path().join("imported");
+        
+        run_import(source.path(), &dest, &ImportOptions::default());
+        let (report, _) = run_import(source.path(), &dest, &ImportOptions::default());
+        
+        assert!(report.imported.is_empty(), "Nothing new should be imported");
+        let duplicates = report.skipped.iter()
+            .filter(|s| matches!(s.reason, SkipReason::Duplicate { .. }))
+            .count();
+        assert_eq!(duplicates, 3);
+    }
+
+    #[test]
+    fn test_reimport_detects_moved_duplicates() {
+        let source = create_source_with_files();
+        let dest_dir = TempDir::new().unwrap();
+        let dest = dest_dir.path().join("imported");
+        fs::create_dir_all(dest.join("elsewhere")).unwrap();
+        fs::write(dest.join("elsewhere").join("renamed.md"), "# Readme\n\nContent here").unwrap();
+        
+        let (report, _) = run_import(source.path(), &dest, &ImportOptions::default());
+        
+        assert!(!dest.join("readme.md").exists(), "Content already present elsewhere should not be re-imported");
+        assert_eq!(report.imported.len(), 2);
+    }
+
+    #[test]
+    fn test_changed_file_gets_conflict_suffix() {
+        let source = create_source_with_files();
+        let dest_dir = TempDir::new().unwrap();
+        let dest = dest_dir.path().join("imported");
+        fs::create_dir_all(&dest).unwrap();
+        fs::write(dest.join("notes.md"), "# Notes\n\nEdited locally").unwrap();
+        
+        let (report, _) = run_import(source.path(), &dest, &ImportOptions::default());
+        
+        assert_eq!(report.conflicts.len(), 1);
+        assert_eq!(fs::read_to_string(dest.join("notes.md")).unwrap(), "# Notes\n\nEdited locally", "Existing note must be untouched");
+        assert!(dest.join("notes-conflict.md").exists(), "Incoming version should be saved with a suffix");
+    }
+
+    #[test]
+    fn test_changed_file_merge_strategy() {
+        let source = create_source_with_files();
+        let dest_dir = TempDir::new().unwrap();
+        let dest = dest_dir.path().join("imported");
+        fs::create_dir_all(&dest).unwrap();
+        fs::write(dest.join("notes.md"), "# Notes\n\nEdited locally").unwrap();
+        
+        let options = ImportOptions { on_conflict: ConflictStrategy::Merge, ..Default::default() };
+        let (report, _) = run_import(source.path(), &dest, &options);
+        
+        let merged = fs::read_to_string(dest.join("notes.md")).unwrap();
+        assert!(merged.starts_with("# Notes\n\nEdited locally"));
+        assert!(merged.contains("- Item 1"), "Incoming content should be appended");
+        assert_eq!(report.conflicts[0].resolution, ConflictStrategy::Merge);
+    }
+
+    #[test]
+    fn test_conflict_path_numbering() {
+        let dir = TempDir::new().unwrap();
+        let target = dir.path().join("a.md");
+        
+        assert_eq!(conflict_path(&target), dir.path().join("a-conflict.md"));
+        fs::write(dir.path().join("a-conflict.md"), "").unwrap();
+        assert_eq!(conflict_path(&target), dir.path().join("a-conflict-2.md"));
+    }
+
+    #[test]
+    fn test_csv_table_appended_to_existing_note() {
+        let dir = TempDir::new().unwrap();
+        let note = dir.path().join("data.md");
+        fs::write(&note, "# Data
+
+").unwrap();
+
+        let table = csv_to_markdown("a\tb\n1\t2", &CsvTableOptions::default()).unwrap();
+        append_to_note(&note, &table).unwrap();
+
+        let content = fs::read_to_string(&note).unwrap();
+        assert!(content.starts_with("# Data\n\n|   a |   b |"));
+        assert!(content.ends_with("|   1 |   2 |\n"));
+    }
+
+    #[test]
+    fn test_read_only_folder_refuses_exports_and_new_folders() {
+        use crate::workspace_settings::{FolderFlags, WorkspaceSettings};
+
+        let (workspace, state) = setup_workspace_with_state();
+        let workspace_path = state.get_workspace_path().unwrap();
+        fs::create_dir_all(workspace.path().join("Reference")).unwrap();
+        let mut settings = WorkspaceSettings::load(workspace.path()).unwrap();
+        settings.set_folder_flags("Reference".to_string(), FolderFlags { read_only: true, archived: false });
+        settings.save(workspace.path()).unwrap();
+
+        // export_document, export_query_results, export_calendar and
+        // export_with_plugin all check their destination here
+        let inside = workspace.path().join("Reference").join("export.md");
+        let result = authorize_export(&state, &workspace_path, &inside.to_string_lossy());
+        assert!(result.unwrap_err().starts_with("Read-only folder"));
+        let beside = workspace.path().join("export.md");
+        assert!(authorize_export(&state, &workspace_path, &beside.to_string_lossy()).is_ok());
+
+        // Same checks as create_directory
+        let folder = workspace.path().join("Reference").join("New");
+        let validated = validate_directory_path(&folder.to_string_lossy(), &workspace_path, false).unwrap();
+        assert!(ensure_writable(&workspace_path, &validated).is_err());
+    }
+
+    #[test]
+    fn test_csv_table_creates_missing_note() {
+        let dir = TempDir::new().unwrap();
+        let note = dir.path().join("tables").join("new.md");
+
+        append_to_note(&note, "| a   |\n| --- |").unwrap();
+        assert_eq!(fs::read_to_string(&note).unwrap(), "| a   |\n| --- |\n");
+    }
 }
@@ -0,0 +1,53 @@
+//! Job Commands
+//! 
+//! This module provides Tauri commands for inspecting and cancelling
+//! long-running jobs (see `crate::jobs`). Progress is reported through
+//! `job-progress` events emitted by the jobs themselves.
+
+use tauri::{command, State};
+use crate::jobs::JobInfo;
+use crate::state::AppState;
+
+/// Requests cancellation of a running job.
+/// 
+/// Cancellation is cooperative: the job stops at its next checkpoint and
+/// returns a partial result flagged as cancelled.
+/// 
+/// # Returns
+/// * `Ok(true)` - If the job was found and flagged
+/// * `Ok(false)` - If no such job is running (already finished)
+#[command]
+pub async fn cancel_job(
+    state: State<'_, AppState>,
+    job_id: String,
+) -> Result<bool, String> {
+    state.jobs.cancel(&job_id)
+}
+
+/// Lists all running jobs.
+#[command]
+pub async fn list_jobs(state: State<'_, AppState>) -> Result<Vec<JobInfo>, String> {
+    state.jobs.list()
+}
+
+// ============================================================================
+// TESTS
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use crate::state::AppState;
+
+    #[test]
+    fn test_state_job_registry() {
+        let state = AppState::new();
+        let job = state.jobs.start("import_folder").unwrap();
+        
+        assert_eq!(state.jobs.list().unwrap().len(), 1);
+        assert!(state.jobs.cancel(&job.id).unwrap());
+        assert!(job.is_cancelled());
+        
+        state.jobs.finish(&job.id).unwrap();
+        assert!(state.jobs.list().unwrap().is_empty());
+    }
+}
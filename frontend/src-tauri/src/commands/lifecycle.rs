@@ -0,0 +1,205 @@
+//! App Lifecycle Commands
+//!
+//! This module provides the Tauri commands the mobile frontend calls when
+//! the app goes to the background and comes back (see `crate::lifecycle`).
+//!
+//! ## Suspend
+//! Pending saves are written, directory and open file watchers are
+//! dropped, the app lock starts counting the time in the background, and running jobs (re-indexing, imports, backups) park at their
+//! next checkpoint. Indexes are saved by the jobs as they go, so the app
+//! can be killed while suspended without losing more than the step in
+//! progress.
+//!
+//! ## Resume
+//! Parked jobs go on (the app locks again first if it was away longer
+//! than the app lock allows), the watchers are recreated, open files changed
+//! meanwhile are reported as `open-file-changed`, and the indexes catch up
+//! with changes made while the app was in the background, after saves
+//! journaled while the workspace was unreachable are replayed (see
+//! `commands::offline`). Items shared into the app meanwhile are added to
+//! the inbox note (see `commands::share`).
+
+use std::path::Path;
+use std::time::Instant;
+use serde::Serialize;
+use tauri::{command, AppHandle, Manager, State};
+use crate::commands::file_watcher::start_directory_watcher;
+use crate::commands::offline::replay;
+use crate::commands::share::import_shares;
+use crate::commands::workspace::catch_up_indexes;
+use crate::state::AppState;
+use crate::utils::validate_directory_path;
+
+/// Result of `suspend_background_tasks`
+#[derive(Debug, Clone, Serialize)]
+pub struct SuspendReport {
+    /// False if the app was already suspended
+    pub suspended: bool,
+    pub flushed_saves: usize,
+    pub dropped_watchers: usize,
+    /// Jobs that will park at their next checkpoint
+    pub parked_jobs: usize,
+}
+
+/// Result of `resume_background_tasks`
+#[derive(Debug, Clone, Serialize)]
+pub struct ResumeReport {
+    /// False if the app was not suspended
+    pub resumed: bool,
+    pub suspended_secs: i64,
+    pub restored_watchers: usize,
+    /// Jobs that go on from where they parked
+    pub resumed_jobs: usize,
+    /// True if the app lock engaged while in the background
+    pub locked: bool,
+}
+
+/// Pauses background work when the app goes to the background.
+///
+/// Called by the mobile frontend when the app is hidden; calling it again
+/// while suspended does nothing.
+#[command]
+pub async fn suspend_background_tasks(state: State<'_, AppState>) -> Result<SuspendReport, String> {
+    suspend(&state).await
+}
+
+/// Restarts background work when the app comes back to the foreground.
+///
+/// Watchers that can no longer be created (their folder is gone) are
+/// skipped with a warning.
+#[command]
+pub async fn resume_background_tasks(
+    app_handle: AppHandle,
+    state: State<'_, AppState>,
+) -> Result<ResumeReport, String> {
+    resume(&app_handle, &state)
+}
+
+// Helper: Flushes saves, drops watchers and parks jobs
+async fn suspend(state: &AppState) -> Result<SuspendReport, String> {
+    let watched = state.get_watched_directories()?;
+    if !state.lifecycle.suspend(watched)? {
+        return Ok(SuspendReport {
+            suspended: false,
+            flushed_saves: 0,
+            dropped_watchers: 0,
+            parked_jobs: 0,
+        });
+    }
+
+    state.app_lock.background(Instant::now());
+    let flushed_saves = state.save_queue.flush_all_async().await?;
+    let dropped_watchers = state.clear_all_watchers()? + state.open_files.suspend()?;
+    let parked_jobs = state.jobs.job_count();
+
+    log::info!(
+        "🌙 Suspended: {} saves flushed, {} watchers dropped, {} jobs parked",
+        flushed_saves,
+        dropped_watchers,
+        parked_jobs
+    );
+    Ok(SuspendReport {
+        suspended: true,
+        flushed_saves,
+        dropped_watchers,
+        parked_jobs,
+    })
+}
+
+// Helper: Wakes jobs, recreates watchers and catches the indexes up
+fn resume(app_handle: &AppHandle, state: &AppState) -> Result<ResumeReport, String> {
+    let Some(resumed) = state.lifecycle.resume()? else {
+        return Ok(ResumeReport {
+            resumed: false,
+            suspended_secs: 0,
+            restored_watchers: 0,
+            resumed_jobs: 0,
+            locked: state.app_lock.is_locked(),
+        });
+    };
+    let locked = state.app_lock.foreground(Instant::now());
+    let resumed_jobs = state.jobs.job_count();
+
+    let mut restored_watchers = 0;
+    if let Ok(workspace) = state.get_workspace_path() {
+        for directory in resumed.watched_directories {
+            let restored = validate_directory_path(&directory, &workspace, true)
+                .map_err(|e| format!("Security error: {}", e))
+                .and_then(|path| {
+                    start_directory_watcher(app_handle, state, directory.clone(), &path, workspace.clone())
+                });
+            match restored {
+                Ok(()) => restored_watchers += 1,
+                Err(e) => log::warn!("⚠️ Not watching {} again: {}", directory, e),
+            }
+        }
+
+        // Notes may have been changed by sync clients in the meantime
+        let app_handle = app_handle.clone();
+        std::thread::spawn(move || {
+            let state = app_handle.state::<AppState>();
+            if Path::new(&workspace).is_dir() {
+                if let Err(e) = replay(&app_handle, &state, &workspace) {
+                    log::warn!("⚠️ Offline saves not replayed: {}", e);
+                }
+                catch_up_indexes(&app_handle, &state, &workspace);
+            }
+        });
+    }
+    restored_watchers += state.open_files.resume()?;
+
+    // The share sheet brings the app to the foreground
+    if let Err(e) = import_shares(app_handle, state) {
+        log::warn!("⚠️ Shared items not imported: {}", e);
+    }
+
+    log::info!(
+        "☀️ Resumed after {}s: {} watchers restored, {} jobs resumed",
+        resumed.suspended_secs,
+        restored_watchers,
+        resumed_jobs
+    );
+    Ok(ResumeReport {
+        resumed: true,
+        suspended_secs: resumed.suspended_secs,
+        restored_watchers,
+        resumed_jobs,
+        locked,
+    })
+}
+
+// ============================================================================
+// TESTS
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::mpsc::channel;
+
+    #[test]
+    fn test_suspend_flushes_and_parks() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("draft.md");
+        let state = AppState::new();
+        state.save_queue
+            .queue(path.clone(), "draft".to_string(), std::time::Duration::from_secs(60))
+            .unwrap();
+        let job = state.jobs.start("reindex").unwrap();
+
+        let report = tauri::async_runtime::block_on(suspend(&state)).unwrap();
+        assert!(report.suspended);
+        assert_eq!(report.flushed_saves, 1);
+        assert_eq!(report.parked_jobs, 1);
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "draft");
+        assert!(!tauri::async_runtime::block_on(suspend(&state)).unwrap().suspended);
+
+        // The job parks until the app is resumed
+        let (tx, rx) = channel();
+        let worker = std::thread::spawn(move || tx.send(job.checkpoint()).unwrap());
+        assert!(rx.recv_timeout(std::time::Duration::from_millis(100)).is_err());
+        state.lifecycle.resume().unwrap();
+        assert!(rx.recv().unwrap());
+        worker.join().unwrap();
+    }
+}
@@ -0,0 +1,294 @@
+//! External Link Check Commands
+//!
+//! This module provides the Tauri command that checks the external links of
+//! notes (see `crate::link_check`) and the background checker that runs
+//! the check on the schedule of the workspace settings. Both report with a
+//! `link-check-finished` event; runs show up as `link_check` jobs. Linked
+//! pages can also be archived as local snapshots (see `crate::web_snapshot`),
+//! and their titles looked up for pasted links (see `crate::link_preview`).
+//!
+//! ## Security
+//! Only notes within the configured workspace are read. Checks rewrite
+//! nothing; snapshots are written to the note's attachment folder and the
+//! note, both of which must be writable. Requests go to the URLs written in
+//! the notes or passed in.
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use chrono::Utc;
+use tauri::{command, AppHandle, Manager, State};
+use crate::commands::file_operations::{available_path, SavedAttachment};
+use crate::events::{self, LinkCheckFinishedEvent};
+use crate::frontmatter;
+use crate::jobs::emit_progress;
+use crate::link_check::{check_links, external_links, url_at, HttpProbe, LinkCache, LinkCheckOptions, LinkCheckReport, LinkOccurrence};
+use crate::link_preview::{self, LinkPreview};
+use crate::notes::{collect_markdown_files, relative_path};
+use crate::safe_write;
+use crate::state::AppState;
+use crate::utils::{validate_directory_path, validate_file_path};
+use crate::web_snapshot::{self, ARCHIVED_LABEL, SNAPSHOT_FOLDER};
+use crate::workspace_settings::{ensure_writable, WorkspaceSettings};
+use crate::write_locks::DEFAULT_LOCK_WAIT_MS;
+
+/// Minimum time between two progress events of a check
+const PROGRESS_INTERVAL_MS: u128 = 100;
+
+/// Seconds between two checks of the schedule
+const SCHEDULE_POLL_SECS: u64 = 600;
+
+/// Timeout of a snapshot download
+const SNAPSHOT_TIMEOUT_SECS: u64 = 30;
+
+/// Timeout of a link title lookup; the user is waiting on a paste
+const LINK_TITLE_TIMEOUT_SECS: u64 = 5;
+
+/// Checks the external links of the workspace, a folder or a note.
+///
+/// * `scope` - note or folder to check (default: the whole workspace)
+/// * `options` - concurrency, per-host interval, timeout and cache age
+///   (default: the workspace's scheduled check options)
+///
+/// Returns the dead links and the redirected ones with the final redirect
+/// target as suggested replacement. Recently checked URLs are answered from
+/// the cache. The check runs as a cancellable `link_check` job.
+///
+/// Security: Validates scope is a markdown file or folder within the workspace.
+#[command]
+pub async fn check_external_links(
+    app_handle: AppHandle,
+    state: State<'_, AppState>,
+    scope: Option<String>,
+    options: Option<LinkCheckOptions>,
+) -> Result<LinkCheckReport, String> {
+    let workspace = state.get_workspace_path()?;
+
+    let scope = match &scope {
+        Some(scope) if Path::new(scope).is_file() => Some(validate_file_path(scope, &workspace, &["md"])),
+        Some(scope) => Some(validate_directory_path(scope, &workspace, true)),
+        None => None,
+    }
+    .transpose()
+    .map_err(|e| format!("Security error: {}", e))?;
+
+    let options = match options {
+        Some(options) => options,
+        None => WorkspaceSettings::load(Path::new(&workspace))?.link_check.options,
+    };
+    let (task_app, task_workspace) = (app_handle.clone(), workspace.clone());
+    let report = tauri::async_runtime::spawn_blocking(move || {
+        let state = task_app.state::<AppState>();
+        run_link_check(&task_app, &state, &task_workspace, scope.as_deref(), &options)
+    })
+    .await
+    .map_err(|e| format!("Failed to check links: {}", e))??;
+
+    events::emit(&app_handle, &LinkCheckFinishedEvent {
+        workspace: workspace.clone(),
+        scheduled: false,
+        report: report.clone(),
+    });
+    Ok(report)
+}
+
+/// Saves a readable copy of a linked page and links it from a note.
+///
+/// The snapshot is a standalone HTML file in the `web` folder of the note's
+/// attachment folder (`assets/web/` by default). An "archived copy" link
+/// following the workspace attachment settings is added after the first
+/// line linking `url`, or appended with the URL if the note does not link it.
+///
+/// * `url` - `http(s)` page to archive
+/// * `note_path` - Note that links the page
+///
+/// Security: Validates note_path is a markdown file within the workspace;
+/// the note and the attachment folder must be writable.
+#[command]
+pub async fn snapshot_link(
+    state: State<'_, AppState>,
+    url: String,
+    note_path: String,
+) -> Result<SavedAttachment, String> {
+    let workspace = state.get_workspace_path()?;
+
+    let validated_note = validate_file_path(&note_path, &workspace, &["md"])
+        .map_err(|e| format!("Security error: {}", e))?;
+    if !validated_note.is_file() {
+        return Err(format!("Note not found: {}", note_path));
+    }
+    let url = url.trim();
+    if url_at(url) != Some(url) {
+        return Err(format!("Not a web link: {}", url));
+    }
+
+    let root = Path::new(&workspace)
+        .canonicalize()
+        .map_err(|e| format!("Failed to resolve workspace: {}", e))?;
+    let attachments = WorkspaceSettings::load(&root)?.attachments;
+    attachments.validate()?;
+
+    let dir = attachments.attachment_dir(&root, &validated_note).join(SNAPSHOT_FOLDER);
+    ensure_writable(&workspace, &validated_note)?;
+    ensure_writable(&workspace, &dir)?;
+
+    let page_url = url.to_string();
+    let target = tauri::async_runtime::spawn_blocking(move || save_snapshot(&page_url, &dir))
+        .await
+        .map_err(|e| format!("Failed to archive page: {}", e))??;
+
+    let link = attachments.text_link(&root, &validated_note, &target, ARCHIVED_LABEL);
+
+    // Pending debounced content must land first, or it would drop the link
+    state.save_queue.flush_path_async(&validated_note).await?;
+
+    let _guard = state.write_locks
+        .acquire_async(&validated_note, Duration::from_millis(DEFAULT_LOCK_WAIT_MS)).await?;
+    let content = fs::read_to_string(&validated_note)
+        .map_err(|e| format!("Failed to read file: {}", e))?;
+    let updated = web_snapshot::add_archived_link(&content, url, &link)?;
+    safe_write::write_file(&validated_note, updated)?;
+
+    log::info!("🗄️ Archived {} to {:?}", url, target);
+    Ok(SavedAttachment {
+        path: target.to_string_lossy().to_string(),
+        markdown: link,
+    })
+}
+
+/// Fetches the title and description of a web page, so a pasted URL can
+/// be inserted as `[Title](url)` (see `crate::link_preview`).
+///
+/// Only the page's `<head>` is read, within `LINK_TITLE_TIMEOUT_SECS`.
+///
+/// Security: Requests to loopback, private and other non-public addresses
+/// are refused, also after redirects.
+#[command]
+pub async fn fetch_link_title(url: String) -> Result<LinkPreview, String> {
+    tauri::async_runtime::spawn_blocking(move || {
+        link_preview::fetch_link_title(&url, Duration::from_secs(LINK_TITLE_TIMEOUT_SECS))
+    })
+    .await
+    .map_err(|e| format!("Failed to fetch link title: {}", e))?
+}
+
+/// Starts the scheduled link checker.
+///
+/// Every `SCHEDULE_POLL_SECS`, while the app is not suspended, it checks
+/// the whole workspace if its `link_check` schedule is due.
+pub fn spawn_link_checker(app_handle: AppHandle) {
+    std::thread::spawn(move || loop {
+        std::thread::sleep(Duration::from_secs(SCHEDULE_POLL_SECS));
+        let state = app_handle.state::<AppState>();
+        if state.lifecycle.is_suspended() {
+            continue;
+        }
+        let Ok(workspace) = state.get_workspace_path() else {
+            continue;
+        };
+        if let Err(e) = run_scheduled(&app_handle, &state, &workspace) {
+            log::warn!("⚠️ Scheduled link check failed: {}", e);
+        }
+    });
+}
+
+// Helper: One pass of the scheduled checker
+fn run_scheduled(app_handle: &AppHandle, state: &AppState, workspace: &str) -> Result<(), String> {
+    let root = Path::new(workspace);
+    let schedule = WorkspaceSettings::load(root)?.link_check;
+    if !schedule.is_due(LinkCache::load(root)?.last_run, Utc::now()) {
+        return Ok(());
+    }
+
+    log::info!("🔗 Scheduled link check of {}", workspace);
+    let report = run_link_check(app_handle, state, workspace, None, &schedule.options)?;
+    events::emit(app_handle, &LinkCheckFinishedEvent {
+        workspace: workspace.to_string(),
+        scheduled: true,
+        report,
+    });
+    Ok(())
+}
+
+// Helper: Downloads `url` and saves its readable copy in `dir`, returning
+// the snapshot's path
+fn save_snapshot(url: &str, dir: &Path) -> Result<PathBuf, String> {
+    let page = web_snapshot::fetch_page(url, Duration::from_secs(SNAPSHOT_TIMEOUT_SECS))?;
+    let (title, markdown) = web_snapshot::readable_page(&page.html, &page.url);
+    let today = Utc::now().date_naive();
+
+    fs::create_dir_all(dir)
+        .map_err(|e| format!("Failed to create attachment folder: {}", e))?;
+    let target = available_path(dir, &web_snapshot::snapshot_name(&page.url, &title, today));
+    fs::write(&target, web_snapshot::snapshot_document(&title, &page.url, today, &markdown))
+        .map_err(|e| format!("Failed to save snapshot: {}", e))?;
+    Ok(target)
+}
+
+// Helper: Checks the links of the notes in `scope` (a note or folder; the
+// whole workspace if `None`) as a job, updating the cache
+fn run_link_check(
+    app_handle: &AppHandle,
+    state: &AppState,
+    workspace: &str,
+    scope: Option<&Path>,
+    options: &LinkCheckOptions,
+) -> Result<LinkCheckReport, String> {
+    let root = Path::new(workspace);
+    let _in_flight = state
+        .command_guard
+        .enter("link_check", workspace)
+        .ok_or("A link check is already running")?;
+
+    let canonical_root = root
+        .canonicalize()
+        .map_err(|e| format!("Failed to resolve workspace: {}", e))?;
+    let files = match scope {
+        Some(note) if note.is_file() => vec![note.to_path_buf()],
+        Some(dir) => collect_markdown_files(&canonical_root, dir)?,
+        None => collect_markdown_files(&canonical_root, &canonical_root)?,
+    };
+
+    let mut links: BTreeMap<String, Vec<LinkOccurrence>> = BTreeMap::new();
+    for file in files {
+        let Ok(content) = fs::read_to_string(&file) else {
+            continue;
+        };
+        let path = relative_path(&canonical_root, &file);
+        let (_, body) = frontmatter::split(&content);
+        let first_line = content[..content.len() - body.len()].lines().count() + 1;
+        for (url, line) in external_links(body, first_line) {
+            links.entry(url).or_default().push(LinkOccurrence { path: path.clone(), line });
+        }
+    }
+
+    let mut cache = LinkCache::load(root)?;
+    let probe = HttpProbe::new(Duration::from_secs(options.timeout_secs.max(1)));
+    let job = state.jobs.start("link_check")?;
+    let mut last_emit: Option<std::time::Instant> = None;
+    let report = check_links(&links, &mut cache, options, &probe, &mut |done, total, url| {
+        if last_emit.map_or(true, |t| t.elapsed().as_millis() >= PROGRESS_INTERVAL_MS) {
+            emit_progress(app_handle, &job.progress(done as u64, Some(total as u64), Some(url.to_string())));
+            last_emit = Some(std::time::Instant::now());
+        }
+        job.checkpoint()
+    });
+    emit_progress(app_handle, &job.progress(report.checked as u64, Some(report.checked as u64), None).finished());
+    state.jobs.finish(&job.id)?;
+
+    // Only complete runs over the whole workspace count for the schedule
+    if scope.is_none() && !report.cancelled {
+        cache.last_run = Some(Utc::now());
+    }
+    cache.save(root, Utc::now())?;
+
+    log::info!(
+        "🔗 Checked {} links ({} requested): {} broken, {} redirected",
+        report.urls,
+        report.checked,
+        report.broken.len(),
+        report.redirected.len()
+    );
+    Ok(report)
+}
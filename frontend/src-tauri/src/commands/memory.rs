@@ -0,0 +1,60 @@
+//! Memory Budget Commands
+//!
+//! This module provides the Tauri commands for inspecting the memory held by
+//! the backend's caches and changing their budget (see
+//! `crate::memory_budget`), and the trimmer that keeps the caches within it.
+
+use std::time::Duration;
+use tauri::{command, AppHandle, Manager, State};
+use crate::commands::workspace::get_config_dir;
+use crate::memory_budget::MemoryUsage;
+use crate::state::AppState;
+
+/// Seconds between two checks of the memory budget
+const TRIM_POLL_SECS: u64 = 30;
+
+/// Returns the estimated memory held by each cache and the budget.
+#[command]
+pub async fn get_memory_usage(state: State<'_, AppState>) -> Result<MemoryUsage, String> {
+    state.memory_usage()
+}
+
+/// Sets the memory budget of the caches and saves it for later sessions.
+///
+/// Caches over the new budget are trimmed right away, least recently used
+/// entries first.
+///
+/// # Returns
+/// * `Ok(MemoryUsage)` - Usage after trimming
+/// * `Err(String)` - If `limit_mb` is outside 16-8192 MB
+#[command]
+pub async fn set_memory_budget(
+    state: State<'_, AppState>,
+    limit_mb: usize,
+) -> Result<MemoryUsage, String> {
+    state.memory.set_limit_mb(limit_mb)?;
+    state.memory.save(&get_config_dir()?)?;
+    log::info!("🧠 Memory budget set to {} MB", limit_mb);
+
+    state.enforce_memory_budget()?;
+    state.memory_usage()
+}
+
+/// Starts the cache trimmer.
+///
+/// Applies the saved budget, then every `TRIM_POLL_SECS` evicts the least
+/// recently used cache entries while the caches are over it.
+pub fn spawn_memory_trimmer(app_handle: AppHandle) {
+    std::thread::spawn(move || {
+        let state = app_handle.state::<AppState>();
+        if let Err(e) = get_config_dir().and_then(|dir| state.memory.load(&dir)) {
+            log::warn!("⚠️ Memory budget not loaded: {}", e);
+        }
+        loop {
+            std::thread::sleep(Duration::from_secs(TRIM_POLL_SECS));
+            if let Err(e) = state.enforce_memory_budget() {
+                log::warn!("⚠️ Memory budget check failed: {}", e);
+            }
+        }
+    });
+}
@@ -0,0 +1,394 @@
+//! Mindmap Metadata Commands
+//! 
+//! This module provides Tauri commands for reading and writing the
+//! mindmap sidecar of a note (see `crate::mindmap_meta`), and for keeping
+//! the outline of an open note in sync with editor changes (see
+//! `crate::outline`). Subtrees can be moved into their own note, and two
+//! notes can be merged into one (see `crate::merge`).
+//! 
+//! ## Security
+//! Note paths are validated against the configured workspace.
+
+use tauri::{command, State};
+use std::fs;
+use std::path::Path;
+use std::time::Duration;
+use serde::Serialize;
+use crate::commands::graph::{redirect_workspace_links, LinkUpdateReport};
+use crate::links::{heading_anchor, rebase_links, wiki_target, NoteLookup};
+use crate::merge::{self, MergeStrategy};
+use crate::mindmap_meta::{self, MindmapMeta};
+use crate::notes::{collect_markdown_files, relative_path};
+use crate::outline::{self, NodeLocation, OutlineDocument, OutlineNode, OutlineUpdate, TextRange};
+use crate::safe_write;
+use crate::state::AppState;
+use crate::utils::validate_file_path;
+use crate::workspace_settings::ensure_writable;
+use crate::write_locks::DEFAULT_LOCK_WAIT_MS;
+
+/// Loads the mindmap metadata of a note (empty if none was saved).
+/// 
+/// Security: Validates file_path is a markdown file within the workspace.
+#[command]
+pub async fn get_mindmap_meta(
+    state: State<'_, AppState>,
+    file_path: String,
+) -> Result<MindmapMeta, String> {
+    let workspace = state.get_workspace_path()?;
+    
+    let validated_path = validate_file_path(&file_path, &workspace, &["md"])
+        .map_err(|e| format!("Security error: {}", e))?;
+    
+    mindmap_meta::load(Path::new(&workspace), &validated_path)
+}
+
+/// Replaces the mindmap metadata of a note.
+/// 
+/// Saving empty metadata removes the sidecar file.
+/// 
+/// Security: Validates file_path is a markdown file within the workspace.
+#[command]
+pub async fn set_mindmap_meta(
+    state: State<'_, AppState>,
+    file_path: String,
+    meta: MindmapMeta,
+) -> Result<(), String> {
+    let workspace = state.get_workspace_path()?;
+    
+    let validated_path = validate_file_path(&file_path, &workspace, &["md"])
+        .map_err(|e| format!("Security error: {}", e))?;
+    
+    if !validated_path.exists() {
+        return Err(format!("File does not exist: {}", file_path));
+    }
+    
+    ensure_writable(&workspace, &validated_path)?;
+    
+    mindmap_meta::save(Path::new(&workspace), &validated_path, &meta)?;
+    
+    log::info!("🧭 Saved mindmap metadata: {:?} ({} nodes)", validated_path, meta.nodes.len());
+    Ok(())
+}
+
+// ============================================================================
+// Live Outline
+// ============================================================================
+
+/// Starts tracking the outline of a note and returns the full outline.
+/// 
+/// `content` is the editor buffer; when omitted the file is read from disk.
+/// Re-opening a tracked note replaces its state.
+/// 
+/// Security: Validates file_path is a markdown file within the workspace.
+#[command]
+pub async fn open_outline(
+    state: State<'_, AppState>,
+    file_path: String,
+    content: Option<String>,
+) -> Result<OutlineNode, String> {
+    let workspace = state.get_workspace_path()?;
+    
+    let validated_path = validate_file_path(&file_path, &workspace, &["md"])
+        .map_err(|e| format!("Security error: {}", e))?;
+    
+    let content = match content {
+        Some(content) => content,
+        None => fs::read_to_string(&validated_path)
+            .map_err(|e| format!("Failed to read file: {}", e))?,
+    };
+    
+    state.outlines.open(validated_path, &content)
+}
+
+/// Applies an editor change to a tracked note.
+/// 
+/// Returns only the part of the outline the change can affect.
+/// 
+/// Security: Validates file_path is a markdown file within the workspace.
+#[command]
+pub async fn apply_outline_edit(
+    state: State<'_, AppState>,
+    file_path: String,
+    range: TextRange,
+    text: String,
+) -> Result<OutlineUpdate, String> {
+    let workspace = state.get_workspace_path()?;
+    
+    let validated_path = validate_file_path(&file_path, &workspace, &["md"])
+        .map_err(|e| format!("Security error: {}", e))?;
+    
+    state.outlines.apply_edit(&validated_path, range, &text)
+}
+
+/// Finds the current lines of a heading or list item.
+/// 
+/// `node_id` is a node's `stable_id` (or, for headings, its `id`). Tracked
+/// notes are searched as they are in the editor; other notes are read from
+/// disk. Lets a mindmap click scroll the editor to the node after edits.
+/// 
+/// Security: Validates file_path is a markdown file within the workspace.
+#[command]
+pub async fn locate_node(
+    state: State<'_, AppState>,
+    file_path: String,
+    node_id: String,
+) -> Result<NodeLocation, String> {
+    let workspace = state.get_workspace_path()?;
+    
+    let validated_path = validate_file_path(&file_path, &workspace, &["md"])
+        .map_err(|e| format!("Security error: {}", e))?;
+    
+    let location = if state.outlines.is_open(&validated_path) {
+        state.outlines.locate(&validated_path, &node_id)?
+    } else {
+        let content = fs::read_to_string(&validated_path)
+            .map_err(|e| format!("Failed to read file: {}", e))?;
+        OutlineDocument::new(&content).locate(&node_id)
+    };
+    
+    location.ok_or_else(|| format!("Node not found: {}", node_id))
+}
+
+/// Stops tracking the outline of a note.
+/// 
+/// Returns true if the note was tracked.
+/// 
+/// Security: Validates file_path is a markdown file within the workspace.
+#[command]
+pub async fn close_outline(
+    state: State<'_, AppState>,
+    file_path: String,
+) -> Result<bool, String> {
+    let workspace = state.get_workspace_path()?;
+    
+    let validated_path = validate_file_path(&file_path, &workspace, &["md"])
+        .map_err(|e| format!("Security error: {}", e))?;
+    
+    state.outlines.close(&validated_path)
+}
+
+// ============================================================================
+// Subtree Extraction
+// ============================================================================
+
+/// Result of moving a subtree into its own note
+#[derive(Debug, Clone, Serialize)]
+pub struct ExtractedNote {
+    /// Absolute path of the new note
+    pub path: String,
+    /// Link that replaced the subtree in the source note
+    pub link: String,
+    /// Notes whose links to the moved headings now point at the new note
+    pub links: LinkUpdateReport,
+}
+
+/// Moves a heading and everything below it into a new note.
+/// 
+/// The subtree is replaced by a wiki link to the new note, or an embed
+/// (`![[...]]`) with `transclude`. Headings of the new note are raised so
+/// the moved heading is `#`, relative links inside it are rebased to the
+/// new folder, and links elsewhere to the moved headings (`[[source#Heading]]`)
+/// are redirected to the new note.
+/// 
+/// Security: Validates both paths are markdown files within the workspace;
+/// both must be writable and `dest_path` must not exist.
+#[command]
+pub async fn extract_subtree_to_note(
+    state: State<'_, AppState>,
+    file_path: String,
+    node_id: String,
+    dest_path: String,
+    transclude: Option<bool>,
+) -> Result<ExtractedNote, String> {
+    let workspace = state.get_workspace_path()?;
+    
+    let validated_path = validate_file_path(&file_path, &workspace, &["md"])
+        .map_err(|e| format!("Security error: {}", e))?;
+    let dest_path = if dest_path.ends_with(".md") {
+        dest_path
+    } else {
+        format!("{}.md", dest_path)
+    };
+    let validated_dest = validate_file_path(&dest_path, &workspace, &["md"])
+        .map_err(|e| format!("Security error: {}", e))?;
+    if validated_dest.exists() {
+        return Err(format!("File already exists: {}", dest_path));
+    }
+    ensure_writable(&workspace, &validated_path)?;
+    ensure_writable(&workspace, &validated_dest)?;
+    
+    let root = Path::new(&workspace)
+        .canonicalize()
+        .map_err(|e| format!("Failed to resolve workspace: {}", e))?;
+    let source_key = relative_path(&root, &validated_path);
+    let dest_key = relative_path(&root, &validated_dest);
+    
+    let mut paths: Vec<String> = collect_markdown_files(&root, &root)?
+        .iter()
+        .map(|p| relative_path(&root, p))
+        .collect();
+    paths.push(dest_key.clone());
+    let lookup = NoteLookup::new(paths);
+    let link = format!(
+        "{}[[{}]]",
+        if transclude.unwrap_or(false) { "!" } else { "" },
+        wiki_target(&lookup, &source_key, &dest_key)
+    );
+    
+    // Pending debounced content must land first, or it would restore the subtree
+    state.save_queue.flush_path_async(&validated_path).await?;
+    
+    let headings = {
+        let _guard = state.write_locks
+            .acquire_async(&validated_path, Duration::from_millis(DEFAULT_LOCK_WAIT_MS)).await?;
+        let content = fs::read_to_string(&validated_path)
+            .map_err(|e| format!("Failed to read file: {}", e))?;
+        let cut = outline::cut_section(&content, &node_id, &link)?;
+        
+        if let Some(parent) = validated_dest.parent() {
+            fs::create_dir_all(parent)
+                .map_err(|e| format!("Failed to create directory: {}", e))?;
+        }
+        fs::write(&validated_dest, rebase_links(&cut.markdown, &source_key, &dest_key))
+            .map_err(|e| format!("Failed to create file: {}", e))?;
+        safe_write::write_file(&validated_path, &cut.remaining)?;
+        cut.headings
+    };
+    
+    // The moved heading itself becomes the new note; its subheadings keep their anchors
+    let matches = |anchor: &str, heading: &str| {
+        anchor.eq_ignore_ascii_case(heading) || anchor.to_lowercase() == heading_anchor(heading)
+    };
+    let links = redirect_workspace_links(&state, &workspace, &source_key, &dest_key, &|anchor| {
+        let anchor = anchor?;
+        if matches(anchor, &headings[0]) {
+            Some(None)
+        } else if headings[1..].iter().any(|h| matches(anchor, h)) {
+            Some(Some(anchor.to_string()))
+        } else {
+            None
+        }
+    }).await?;
+    
+    log::info!("✂️ Extracted '{}' from {:?} into {:?}", headings[0], validated_path, validated_dest);
+    Ok(ExtractedNote {
+        path: validated_dest.to_string_lossy().to_string(),
+        link,
+        links,
+    })
+}
+
+// ============================================================================
+// Note Merging
+// ============================================================================
+
+/// Result of merging one note into another
+#[derive(Debug, Clone, Serialize)]
+pub struct MergedNote {
+    /// Absolute path of the merged (primary) note
+    pub path: String,
+    pub added_sections: usize,
+    pub merged_sections: usize,
+    pub duplicate_sections: usize,
+    /// Notes whose links to the secondary note now point at the primary
+    pub links: LinkUpdateReport,
+}
+
+/// Merges the secondary note into the primary note and moves the secondary
+/// to the system trash.
+/// 
+/// Sections are appended or interleaved by heading (see `crate::merge`) and
+/// sections the primary already has are skipped. Relative links in the
+/// secondary's content are rebased to the primary's folder, and links
+/// elsewhere to the secondary (including `#heading` links) are redirected to
+/// the primary.
+/// 
+/// Security: Validates both paths are markdown files within the workspace;
+/// both must be writable.
+/// 
+/// # Arguments
+/// * `primary` - Note that receives the content
+/// * `secondary` - Note that is merged and trashed
+/// * `strategy` - `append` (default) or `interleave`
+#[command]
+pub async fn merge_notes(
+    state: State<'_, AppState>,
+    primary: String,
+    secondary: String,
+    strategy: Option<MergeStrategy>,
+) -> Result<MergedNote, String> {
+    let workspace = state.get_workspace_path()?;
+    
+    let validated_primary = validate_file_path(&primary, &workspace, &["md"])
+        .map_err(|e| format!("Security error: {}", e))?;
+    let validated_secondary = validate_file_path(&secondary, &workspace, &["md"])
+        .map_err(|e| format!("Security error: {}", e))?;
+    for (path, name) in [(&validated_primary, &primary), (&validated_secondary, &secondary)] {
+        if !path.is_file() {
+            return Err(format!("Note not found: {}", name));
+        }
+    }
+    if validated_primary == validated_secondary {
+        return Err("Cannot merge a note into itself".to_string());
+    }
+    ensure_writable(&workspace, &validated_primary)?;
+    ensure_writable(&workspace, &validated_secondary)?;
+    
+    let root = Path::new(&workspace)
+        .canonicalize()
+        .map_err(|e| format!("Failed to resolve workspace: {}", e))?;
+    let primary_key = relative_path(&root, &validated_primary);
+    let secondary_key = relative_path(&root, &validated_secondary);
+    
+    // Pending debounced content of both notes must land before reading them
+    state.save_queue.flush_path_async(&validated_primary).await?;
+    state.save_queue.flush_path_async(&validated_secondary).await?;
+    
+    let outcome = {
+        let _guard = state.write_locks
+            .acquire_async(&validated_primary, Duration::from_millis(DEFAULT_LOCK_WAIT_MS)).await?;
+        let content = fs::read_to_string(&validated_primary)
+            .map_err(|e| format!("Failed to read file: {}", e))?;
+        let other = fs::read_to_string(&validated_secondary)
+            .map_err(|e| format!("Failed to read file: {}", e))?;
+        let other = rebase_links(&other, &secondary_key, &primary_key);
+        
+        let outcome = merge::merge(&content, &other, strategy.unwrap_or_default());
+        safe_write::write_file(&validated_primary, &outcome.content)?;
+        outcome
+    };
+    
+    // Links are resolved against the secondary, so redirect before trashing it
+    let links = redirect_workspace_links(&state, &workspace, &secondary_key, &primary_key, &|anchor| {
+        Some(anchor.map(str::to_string))
+    }).await?;
+    
+    {
+        // Nothing queued for the secondary may recreate it afterwards
+        let _guard = state.write_locks
+            .acquire_async(&validated_secondary, Duration::from_millis(DEFAULT_LOCK_WAIT_MS)).await?;
+        state.save_queue.cancel(&validated_secondary)?;
+        trash::delete(&validated_secondary)
+            .map_err(|e| format!("Failed to move file to trash: {}", e))?;
+    }
+    state.outlines.close(&validated_secondary)?;
+    mindmap_meta::follow_delete(&workspace, &validated_secondary);
+    state.frecency.follow_delete(&workspace, &validated_secondary);
+    state.favorites.follow_delete(&workspace, &validated_secondary);
+    
+    log::info!(
+        "🧩 Merged {:?} into {:?} ({} added, {} merged, {} duplicates)",
+        validated_secondary,
+        validated_primary,
+        outcome.added_sections,
+        outcome.merged_sections,
+        outcome.duplicate_sections
+    );
+    Ok(MergedNote {
+        path: validated_primary.to_string_lossy().to_string(),
+        added_sections: outcome.added_sections,
+        merged_sections: outcome.merged_sections,
+        duplicate_sections: outcome.duplicate_sections,
+        links,
+    })
+}
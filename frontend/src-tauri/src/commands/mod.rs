@@ -5,3 +5,29 @@ pub mod file_operations;
 pub mod workspace;
 pub mod import_export;
 pub mod file_watcher;
+pub mod external_editor;
+pub mod opener;
+pub mod settings;
+pub mod jobs;
+pub mod lifecycle;
+pub mod offline;
+pub mod hooks;
+pub mod plugins;
+pub mod memory;
+pub mod app_lock;
+pub mod mindmap;
+pub mod diagrams;
+pub mod graph;
+pub mod session;
+pub mod share;
+pub mod windows;
+pub mod thumbnails;
+pub mod pdf;
+pub mod audio;
+pub mod ocr;
+pub mod dialogs;
+pub mod frontmatter;
+pub mod templates;
+pub mod backup;
+pub mod link_check;
+pub mod tables;
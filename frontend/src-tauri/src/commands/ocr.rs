@@ -0,0 +1,113 @@
+//! OCR Commands
+//!
+//! This module provides the Tauri command that makes text in images
+//! searchable (see `crate::ocr`).
+//!
+//! ## Security
+//! Image and note paths are validated against the configured workspace;
+//! inserting into a note requires it to be writable.
+
+use std::fs;
+use std::path::Path;
+use std::time::Duration;
+use serde::Serialize;
+use tauri::{command, State};
+use crate::file_meta::{epoch_ms, iso_8601};
+use crate::notes::relative_path;
+use crate::ocr::{self, OcrEntry, OcrStore, DEFAULT_LANG, OCR_EXTENSIONS};
+use crate::state::AppState;
+use crate::utils::validate_file_path;
+use crate::workspace_settings::ensure_writable;
+use crate::write_locks::DEFAULT_LOCK_WAIT_MS;
+
+/// Text recognized in an image
+#[derive(Debug, Clone, Serialize)]
+pub struct OcrResult {
+    pub text: String,
+    pub lang: String,
+    /// True if the stored text was reused instead of recognizing again
+    pub cached: bool,
+}
+
+/// Recognizes the text in an image and stores it with the image.
+///
+/// Text recognized earlier from the same image version and language is
+/// reused. With `note_path`, the text is also placed in that note as a
+/// collapsed block under the image link.
+///
+/// # Arguments
+/// * `path` - Image within the workspace
+/// * `lang` - Tesseract language(s), e.g. `eng` or `eng+deu` (default `eng`)
+/// * `note_path` - Note to insert the text into (optional)
+///
+/// Security: Validates path is an image and note_path a markdown file
+/// within the workspace.
+#[command]
+pub async fn ocr_image(
+    state: State<'_, AppState>,
+    path: String,
+    lang: Option<String>,
+    note_path: Option<String>,
+) -> Result<OcrResult, String> {
+    let workspace = state.get_workspace_path()?;
+
+    let validated_path = validate_file_path(&path, &workspace, OCR_EXTENSIONS)
+        .map_err(|e| format!("Security error: {}", e))?;
+    let validated_note = note_path
+        .map(|note| {
+            validate_file_path(&note, &workspace, &["md"])
+                .map_err(|e| format!("Security error: {}", e))
+        })
+        .transpose()?;
+    if let Some(note) = &validated_note {
+        ensure_writable(&workspace, note)?;
+    }
+
+    let lang = lang
+        .map(|l| l.trim().to_string())
+        .filter(|l| !l.is_empty())
+        .unwrap_or_else(|| DEFAULT_LANG.to_string());
+    let root = Path::new(&workspace)
+        .canonicalize()
+        .map_err(|e| format!("Failed to resolve workspace: {}", e))?;
+    let key = relative_path(&root, &validated_path);
+    let modified_ms = fs::metadata(&validated_path)
+        .and_then(|m| m.modified())
+        .ok()
+        .map(epoch_ms);
+
+    let mut store = OcrStore::load(&root);
+    let (text, cached) = match store.fresh(&key, modified_ms, &lang) {
+        Some(entry) => (entry.text.clone(), true),
+        None => {
+            let text = ocr::recognize(&validated_path, &lang)?;
+            store.assets.insert(key.clone(), OcrEntry {
+                text: text.clone(),
+                lang: lang.clone(),
+                modified_ms,
+                recognized_at: iso_8601(std::time::SystemTime::now()),
+            });
+            store.save(&root)?;
+            log::info!("🔍 Recognized {} characters in {}", text.chars().count(), key);
+            (text, false)
+        }
+    };
+
+    if let Some(note) = validated_note.filter(|_| !text.is_empty()) {
+        // Pending debounced content must land first, or it would drop the block
+        state.save_queue.flush_path_async(&note).await?;
+
+        let _guard = state.write_locks
+            .acquire_async(&note, Duration::from_millis(DEFAULT_LOCK_WAIT_MS)).await?;
+        let content = fs::read_to_string(&note)
+            .map_err(|e| format!("Failed to read file: {}", e))?;
+        let image_name = validated_path
+            .file_name()
+            .map(|n| n.to_string_lossy().to_string())
+            .unwrap_or_default();
+        fs::write(&note, ocr::insert_text_block(&content, &image_name, &text))
+            .map_err(|e| format!("Failed to save file: {}", e))?;
+    }
+
+    Ok(OcrResult { text, lang, cached })
+}
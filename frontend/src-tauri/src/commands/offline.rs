@@ -0,0 +1,229 @@
+//! Offline Journal Commands
+//!
+//! This module journals saves made while the workspace folder is
+//! unreachable (see `crate::offline_journal`) and replays them once it is
+//! back: from the journal watcher every `JOURNAL_POLL_SECS`, on resume, or
+//! when the frontend calls `replay_offline_journal`. The `offline-journal`
+//! event tells the UI when saves are journaled and how a replay went.
+
+use std::fs;
+use std::path::Path;
+use std::time::Duration;
+use chrono::Utc;
+use serde::Serialize;
+use tauri::{command, AppHandle, Manager, State};
+use crate::events::{self, OfflineJournalEvent, OfflineJournalState};
+use crate::offline_journal::{self, conflict_copy_path, content_hash, replay_action, JournalEntry, ReplayAction};
+use crate::safe_write;
+use crate::state::AppState;
+use crate::utils::validate_file_path;
+use crate::write_locks::DEFAULT_LOCK_WAIT_MS;
+
+/// Interval between checks for a returned workspace
+const JOURNAL_POLL_SECS: u64 = 5;
+
+/// A journaled save
+#[derive(Debug, Clone, Serialize)]
+pub struct PendingWrite {
+    pub path: String,
+    /// First save while offline (RFC 3339, UTC)
+    pub queued_at: String,
+    /// Latest save while offline (RFC 3339, UTC)
+    pub updated_at: String,
+    pub bytes: usize,
+}
+
+/// Result of `get_offline_journal`
+#[derive(Debug, Clone, Serialize)]
+pub struct OfflineJournalStatus {
+    /// Whether the workspace folder can be reached
+    pub reachable: bool,
+    pub pending: Vec<PendingWrite>,
+}
+
+/// Result of a replay
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ReplayReport {
+    /// Notes overwritten with their journaled content
+    pub written: Vec<String>,
+    /// Notes that already had their journaled content
+    pub already_applied: usize,
+    /// Conflict copies written for notes changed elsewhere meanwhile
+    pub conflicts: Vec<String>,
+    /// Saves that could not be replayed, with the reason (kept in the journal)
+    pub failed: Vec<String>,
+    /// Saves still journaled
+    pub remaining: usize,
+}
+
+/// Lists the saves of the current workspace waiting for it to come back.
+#[command]
+pub async fn get_offline_journal(state: State<'_, AppState>) -> Result<OfflineJournalStatus, String> {
+    let workspace = state.get_workspace_path()?;
+    Ok(OfflineJournalStatus {
+        reachable: offline_journal::is_reachable(Path::new(&workspace)),
+        pending: state
+            .offline_journal
+            .pending(&workspace)
+            .into_iter()
+            .map(|entry| PendingWrite {
+                path: entry.path,
+                queued_at: entry.queued_at.to_rfc3339(),
+                updated_at: entry.updated_at.to_rfc3339(),
+                bytes: entry.content.len(),
+            })
+            .collect(),
+    })
+}
+
+/// Replays the journaled saves of the current workspace now.
+///
+/// # Returns
+/// * `Err(String)` - If the workspace folder is still unreachable
+#[command]
+pub async fn replay_offline_journal(
+    app_handle: AppHandle,
+    state: State<'_, AppState>,
+) -> Result<ReplayReport, String> {
+    let workspace = state.get_workspace_path()?;
+    if !offline_journal::is_reachable(Path::new(&workspace)) {
+        return Err(format!("Workspace unavailable: {} cannot be reached", workspace));
+    }
+    
+    // Busy notes are waited for on the blocking pool
+    tauri::async_runtime::spawn_blocking(move || {
+        let state = app_handle.state::<AppState>();
+        replay(&app_handle, &state, &workspace)
+    })
+    .await
+    .map_err(|e| format!("Failed to replay offline saves: {}", e))?
+}
+
+/// Journals a save to an unreachable workspace and tells the UI
+///
+/// `path` must have passed `offline_journal::offline_note_path`, and
+/// `baseline` is the note as the editor last knew it (see
+/// `OpenFileWatchers::baseline`), which replay expects to find on disk.
+pub(crate) fn journal_save(
+    app_handle: &AppHandle,
+    state: &AppState,
+    workspace: &str,
+    path: &Path,
+    content: &str,
+    baseline: Option<String>,
+) -> Result<(), String> {
+    let base_hash = baseline.map(|baseline| content_hash(&baseline));
+    state.offline_journal.record(workspace, path, content, base_hash, Utc::now())?;
+    state.open_files.expect(path, content);
+
+    let pending = state.offline_journal.pending(workspace).len();
+    log::warn!("📴 Workspace unreachable, journaled save of {:?} ({} pending)", path, pending);
+    events::emit(app_handle, &OfflineJournalEvent {
+        workspace: workspace.to_string(),
+        state: OfflineJournalState::Queued,
+        pending,
+        written: Vec::new(),
+        conflicts: Vec::new(),
+    });
+    Ok(())
+}
+
+/// Replays the journaled saves of `workspace`, which must be reachable
+pub(crate) fn replay(app_handle: &AppHandle, state: &AppState, workspace: &str) -> Result<ReplayReport, String> {
+    let mut report = ReplayReport::default();
+    let entries = state.offline_journal.pending(workspace);
+    if entries.is_empty() {
+        return Ok(report);
+    }
+
+    for entry in &entries {
+        if let Err(e) = replay_entry(state, workspace, entry, &mut report) {
+            log::warn!("⚠️ Failed to replay save of {}: {}", entry.path, e);
+            report.failed.push(format!("{}: {}", entry.path, e));
+        }
+    }
+    report.remaining = state.offline_journal.pending(workspace).len();
+
+    log::info!(
+        "🔁 Replayed offline saves: {} written, {} conflicts, {} failed",
+        report.written.len(),
+        report.conflicts.len(),
+        report.failed.len()
+    );
+    events::emit(app_handle, &OfflineJournalEvent {
+        workspace: workspace.to_string(),
+        state: OfflineJournalState::Replayed,
+        pending: report.remaining,
+        written: report.written.clone(),
+        conflicts: report.conflicts.clone(),
+    });
+    Ok(report)
+}
+
+// Helper: Writes one entry to its note, or to a conflict copy next to it
+fn replay_entry(
+    state: &AppState,
+    workspace: &str,
+    entry: &JournalEntry,
+    report: &mut ReplayReport,
+) -> Result<(), String> {
+    let validated_path = validate_file_path(&entry.path, workspace, &["md"])
+        .map_err(|e| format!("Security error: {}", e))?;
+    let _guard = state.write_locks
+        .try_acquire(&validated_path, Duration::from_millis(DEFAULT_LOCK_WAIT_MS))?;
+
+    let current = fs::read_to_string(&validated_path).ok();
+    let applied = match replay_action(entry, current.as_deref()) {
+        ReplayAction::Write => {
+            state.open_files.expect(&validated_path, &entry.content);
+            safe_write::write_file(&validated_path, &entry.content)?;
+            report.written.push(entry.path.clone());
+            true
+        }
+        ReplayAction::AlreadyApplied => {
+            report.already_applied += 1;
+            true
+        }
+        ReplayAction::Conflict => {
+            let copy = conflict_copy_path(&validated_path, Utc::now());
+            safe_write::write_file(&copy, &entry.content)?;
+            log::warn!("⚠️ {:?} changed while offline, saved the offline edit as {:?}", validated_path, copy);
+            report.conflicts.push(copy.to_string_lossy().to_string());
+            false
+        }
+    };
+
+    // Saved again meanwhile: newer content written on top of this one is
+    // not a conflict
+    if !state.offline_journal.complete(entry)? && applied {
+        state.offline_journal.rebase(workspace, &entry.path, content_hash(&entry.content))?;
+    }
+    Ok(())
+}
+
+/// Starts the journal watcher.
+///
+/// Every `JOURNAL_POLL_SECS`, while saves of the current workspace are
+/// journaled and the app is not suspended, it checks if the workspace is
+/// reachable again and replays them.
+pub fn spawn_journal_watcher(app_handle: AppHandle) {
+    std::thread::spawn(move || loop {
+        std::thread::sleep(Duration::from_secs(JOURNAL_POLL_SECS));
+        let state = app_handle.state::<AppState>();
+        if state.offline_journal.is_empty() || state.lifecycle.is_suspended() {
+            continue;
+        }
+        let Ok(workspace) = state.get_workspace_path() else {
+            continue;
+        };
+        if state.offline_journal.pending(&workspace).is_empty()
+            || !offline_journal::is_reachable(Path::new(&workspace))
+        {
+            continue;
+        }
+        log::info!("🔌 Workspace {} is reachable again, replaying offline saves", workspace);
+        if let Err(e) = replay(&app_handle, &state, &workspace) {
+            log::warn!("⚠️ Offline replay failed: {}", e);
+        }
+    });
+}
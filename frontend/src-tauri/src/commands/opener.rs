@@ -0,0 +1,67 @@
+//! Opener Commands
+//!
+//! This module reveals workspace files in the system file manager (Finder,
+//! Explorer, ...) and opens them with their default application, through
+//! the opener plugin's platform APIs. The frontend gets no shell or opener
+//! permissions for this: paths are validated against the workspace here,
+//! and anything the system would run instead of open is refused.
+
+use std::path::PathBuf;
+use tauri::{command, State};
+use crate::state::AppState;
+use crate::utils::{is_executable, validate_path_within_workspace};
+
+/// The opener APIs only exist on desktop
+const OPENER_SUPPORTED: bool = cfg!(desktop);
+
+/// Shows a file or folder of the workspace in the system file manager.
+///
+/// The containing folder is opened with the item selected.
+///
+/// Security: Validates path is within the configured workspace.
+#[command]
+pub async fn reveal_in_file_manager(state: State<'_, AppState>, path: String) -> Result<(), String> {
+    let validated_path = validate_existing(&state, &path)?;
+
+    #[cfg(desktop)]
+    tauri_plugin_opener::reveal_item_in_dir(&validated_path)
+        .map_err(|e| format!("Failed to reveal {}: {}", path, e))?;
+
+    log::info!("📂 Revealed in file manager: {:?}", validated_path);
+    Ok(())
+}
+
+/// Opens a file or folder of the workspace with its default application.
+///
+/// Security:
+/// - Validates path is within the configured workspace
+/// - Refuses programs and scripts (see `utils::is_executable`), which the
+///   system would run rather than open
+#[command]
+pub async fn open_with_default_app(state: State<'_, AppState>, path: String) -> Result<(), String> {
+    let validated_path = validate_existing(&state, &path)?;
+    if is_executable(&validated_path) {
+        return Err(format!("Security error: {} is a program and is not opened", path));
+    }
+
+    #[cfg(desktop)]
+    tauri_plugin_opener::open_path(&validated_path, None::<&str>)
+        .map_err(|e| format!("Failed to open {}: {}", path, e))?;
+
+    log::info!("📂 Opened with default app: {:?}", validated_path);
+    Ok(())
+}
+
+// Helper: Validates a path within the workspace that exists
+fn validate_existing(state: &AppState, path: &str) -> Result<PathBuf, String> {
+    if !OPENER_SUPPORTED {
+        return Err("Opening files outside the app is not supported on this platform".to_string());
+    }
+    let workspace = state.get_workspace_path()?;
+    let validated_path = validate_path_within_workspace(path, &workspace)
+        .map_err(|e| format!("Security error: {}", e))?;
+    if !validated_path.exists() {
+        return Err(format!("Path does not exist: {}", path));
+    }
+    Ok(validated_path)
+}
@@ -0,0 +1,60 @@
+//! PDF Commands
+//!
+//! This module provides the Tauri commands behind PDF attachment search and
+//! preview cards (see `crate::pdf`).
+//!
+//! ## Security
+//! Source paths are validated against the configured workspace; extracted
+//! text and rendered pages are only ever written to the cache directory.
+
+use tauri::{command, State};
+use crate::pdf::{self, PdfText, PDF_EXTENSIONS};
+use crate::state::AppState;
+use crate::thumbnails::{self, Thumbnail, DEFAULT_SIZE};
+use crate::utils::validate_file_path;
+
+/// Extracts the text of a PDF for indexing and preview cards.
+///
+/// Results are cached per file version, so repeated calls are cheap.
+///
+/// Security: Validates file_path is a PDF within the workspace.
+#[command]
+pub async fn extract_pdf_text(
+    state: State<'_, AppState>,
+    file_path: String,
+) -> Result<PdfText, String> {
+    let workspace = state.get_workspace_path()?;
+
+    let validated_path = validate_file_path(&file_path, &workspace, PDF_EXTENSIONS)
+        .map_err(|e| format!("Security error: {}", e))?;
+
+    pdf::extract_text(&validated_path, &pdf::text_cache_dir()?)
+}
+
+/// Renders a page of a PDF as a PNG thumbnail.
+///
+/// `page` is 1-based (default 1). `size` and `inline` behave as for
+/// `get_thumbnail`.
+///
+/// Security: Validates file_path is a PDF within the workspace.
+#[command]
+pub async fn get_pdf_page_thumbnail(
+    state: State<'_, AppState>,
+    file_path: String,
+    page: Option<u16>,
+    size: Option<u32>,
+    inline: Option<bool>,
+) -> Result<Thumbnail, String> {
+    let workspace = state.get_workspace_path()?;
+
+    let validated_path = validate_file_path(&file_path, &workspace, PDF_EXTENSIONS)
+        .map_err(|e| format!("Security error: {}", e))?;
+
+    pdf::page_thumbnail(
+        &validated_path,
+        page.unwrap_or(1),
+        size.unwrap_or(DEFAULT_SIZE),
+        &thumbnails::cache_dir()?,
+        inline.unwrap_or(true),
+    )
+}
@@ -0,0 +1,312 @@
+//! Workspace Plugin Commands
+//!
+//! This module lists and approves the plugins of the current workspace (see
+//! `crate::plugins`) and runs their commands, transforms and exporters in
+//! the plugin runtime (see `crate::plugin_runtime`), off the async runtime.
+//!
+//! Plugins that are not approved on this machine never run; the frontend
+//! shows them with `list_plugins` and approves them with `approve_plugin`.
+//! Approval always takes a native confirmation dialog naming the plugin and
+//! its capabilities, so a compromised webview cannot approve plugins on its
+//! own.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use rfd::{AsyncMessageDialog, MessageButtons, MessageDialogResult, MessageLevel};
+use serde::Serialize;
+use tauri::{command, AppHandle, State};
+use crate::commands::dialogs::show;
+use crate::commands::hooks::spawn_hooks;
+use crate::commands::import_export::authorize_export;
+use crate::commands::workspace::get_config_dir;
+use crate::hooks::HookEvent;
+use crate::notes::relative_path;
+use crate::plugin_runtime::{PluginHost, PluginRequest, PluginRun};
+use crate::plugins::{discover, Capability, CommandKind, Plugin, PluginError, PluginManifest, PluginTrust};
+use crate::state::AppState;
+use crate::utils::validate_file_path;
+
+/// Plugins can only run where the runtime may compile code (not on mobile)
+const PLUGINS_SUPPORTED: bool = cfg!(desktop);
+
+/// A plugin of the workspace
+#[derive(Debug, Clone, Serialize)]
+pub struct PluginInfo {
+    #[serde(flatten)]
+    pub manifest: PluginManifest,
+    /// Whether this exact plugin is approved on this machine
+    pub trusted: bool,
+    /// Fingerprint to pass to `approve_plugin`
+    pub fingerprint: String,
+}
+
+/// Result of `list_plugins`
+#[derive(Debug, Clone, Serialize)]
+pub struct PluginsStatus {
+    pub plugins: Vec<PluginInfo>,
+    /// Plugin folders that could not be loaded
+    pub errors: Vec<PluginError>,
+    /// False on platforms that cannot run plugins (mobile)
+    pub supported: bool,
+}
+
+/// Result of a plugin command or transform
+#[derive(Debug, Clone, Serialize)]
+pub struct PluginOutput {
+    pub output: String,
+    #[serde(flatten)]
+    pub run: PluginRun,
+}
+
+/// Lists the plugins of the current workspace and whether they are approved.
+#[command]
+pub async fn list_plugins(state: State<'_, AppState>) -> Result<PluginsStatus, String> {
+    let workspace = state.get_workspace_path()?;
+    let (plugins, errors) = discover(Path::new(&workspace));
+    let trust = PluginTrust::load(&get_config_dir()?)?;
+
+    // Modules replaced or removed since they were compiled are not needed
+    let hashes: Vec<String> = plugins.iter().map(|p| p.module_hash.clone()).collect();
+    state.plugins.retain(&hashes);
+
+    for error in &errors {
+        log::warn!("⚠️ Plugin {} skipped: {}", error.folder, error.error);
+    }
+    Ok(PluginsStatus {
+        plugins: plugins
+            .into_iter()
+            .map(|plugin| PluginInfo {
+                trusted: trust.is_trusted(&workspace, &plugin),
+                fingerprint: plugin.fingerprint,
+                manifest: plugin.manifest,
+            })
+            .collect(),
+        errors,
+        supported: PLUGINS_SUPPORTED,
+    })
+}
+
+/// Approves a plugin of the current workspace on this machine.
+///
+/// `fingerprint` must be the one returned with the plugin the user was
+/// shown, so a plugin changed in the meantime is not approved unseen. The
+/// user then confirms the plugin and its capabilities in a native dialog.
+///
+/// # Returns
+/// * `Ok(false)` - If the plugin changed since it was shown, or the user declined
+#[command]
+pub async fn approve_plugin(
+    state: State<'_, AppState>,
+    id: String,
+    fingerprint: String,
+) -> Result<bool, String> {
+    let workspace = state.get_workspace_path()?;
+    let plugin = find_plugin(&workspace, &id)?;
+    if plugin.fingerprint != fingerprint || !confirm_approval(&state, &plugin).await? {
+        return Ok(false);
+    }
+
+    let config_dir = get_config_dir()?;
+    let mut trust = PluginTrust::load(&config_dir)?;
+    trust.approve(&workspace, &plugin);
+    trust.save(&config_dir)?;
+    log::info!("🧩 Approved plugin {} ({:?}) of {}", id, plugin.manifest.capabilities, workspace);
+    Ok(true)
+}
+
+/// Withdraws the approval of a plugin of the current workspace.
+///
+/// # Returns
+/// * `Ok(false)` - If the plugin was not approved
+#[command]
+pub async fn revoke_plugin(state: State<'_, AppState>, id: String) -> Result<bool, String> {
+    let workspace = state.get_workspace_path()?;
+    let config_dir = get_config_dir()?;
+    let mut trust = PluginTrust::load(&config_dir)?;
+    let revoked = trust.revoke(&workspace, &id);
+    if revoked {
+        trust.save(&config_dir)?;
+        log::info!("🧩 Revoked plugin {} of {}", id, workspace);
+    }
+    Ok(revoked)
+}
+
+/// Runs a `command` of a plugin with input from the frontend.
+///
+/// `file_path` is the note the command runs on, if any; the plugin can ask
+/// for it but still needs `read_notes` to read it.
+///
+/// Security: Validates file_path is a markdown file within the workspace.
+#[command]
+pub async fn run_plugin_command(
+    state: State<'_, AppState>,
+    plugin: String,
+    command: String,
+    input: Option<String>,
+    file_path: Option<String>,
+) -> Result<PluginOutput, String> {
+    let workspace = state.get_workspace_path()?;
+    let plugin = trusted_plugin(&workspace, &plugin, &command, CommandKind::Command)?;
+    let note = file_path
+        .map(|path| note_request(&workspace, &path).map(|(_, relative)| relative))
+        .transpose()?;
+
+    let request = PluginRequest {
+        workspace,
+        command,
+        input: input.unwrap_or_default().into_bytes(),
+        note,
+    };
+    let run = run_blocking(state.plugins.clone(), plugin, request).await?;
+    output(run)
+}
+
+/// Runs a `transform` of a plugin on a note.
+///
+/// The transformed content is returned for the editor to apply; it is
+/// never saved here.
+///
+/// Security: Validates file_path is a markdown file within the workspace.
+#[command]
+pub async fn transform_note_with_plugin(
+    state: State<'_, AppState>,
+    plugin: String,
+    command: String,
+    file_path: String,
+) -> Result<PluginOutput, String> {
+    let workspace = state.get_workspace_path()?;
+    let plugin = trusted_plugin(&workspace, &plugin, &command, CommandKind::Transform)?;
+    let (validated_path, relative) = note_request(&workspace, &file_path)?;
+    let content = fs::read(&validated_path).map_err(|e| format!("Failed to read file: {}", e))?;
+
+    let request = PluginRequest { workspace, command, input: content, note: Some(relative) };
+    let run = run_blocking(state.plugins.clone(), plugin, request).await?;
+    output(run)
+}
+
+/// Exports a note with an `exporter` of a plugin.
+///
+/// Security:
+/// - Source must be a markdown file within the configured workspace
+/// - Destination must pass the export policy (see `crate::export_policy`)
+#[command]
+pub async fn export_with_plugin(
+    app_handle: AppHandle,
+    state: State<'_, AppState>,
+    plugin: String,
+    command: String,
+    document_path: String,
+    dest_path: String,
+) -> Result<PluginRun, String> {
+    let workspace = state.get_workspace_path()?;
+    let plugin = trusted_plugin(&workspace, &plugin, &command, CommandKind::Exporter)?;
+    let (validated_source, relative) = note_request(&workspace, &document_path)?;
+    let dest = authorize_export(&state, &workspace, &dest_path)?;
+    let content = fs::read(&validated_source).map_err(|e| format!("Failed to read document: {}", e))?;
+
+    let request = PluginRequest { workspace: workspace.clone(), command, input: content, note: Some(relative) };
+    let mut run = run_blocking(state.plugins.clone(), plugin, request).await?;
+    fs::write(&dest, &run.output).map_err(|e| format!("Failed to export document: {}", e))?;
+    run.output.clear();
+
+    log::info!("📤 Exported with plugin: {} → {}", document_path, dest_path);
+    spawn_hooks(&app_handle, &workspace, HookEvent::AfterExport, Some(validated_source), Some(dest));
+    Ok(run)
+}
+
+// Helper: Asks the user in a native dialog whether to approve a plugin
+async fn confirm_approval(state: &AppState, plugin: &Plugin) -> Result<bool, String> {
+    if !PLUGINS_SUPPORTED {
+        return Ok(false);
+    }
+
+    let manifest = &plugin.manifest;
+    let capabilities: Vec<&str> = manifest
+        .capabilities
+        .iter()
+        .map(|capability| match capability {
+            Capability::ReadNotes => "• Read and list your notes",
+            Capability::WriteNotes => "• Create and overwrite your notes",
+        })
+        .collect();
+    let dialog = AsyncMessageDialog::new()
+        .set_level(MessageLevel::Warning)
+        .set_title("Allow Workspace Plugin?")
+        .set_description(format!(
+            "This workspace runs the plugin \"{}\" ({}) on your computer. It may:\n\n{}\n\nOnly allow it if you trust the workspace.",
+            manifest.name,
+            manifest.id,
+            if capabilities.is_empty() { "• Nothing beyond its own input".to_string() } else { capabilities.join("\n") }
+        ))
+        .set_buttons(MessageButtons::YesNo);
+    let confirmed = show(state, async move {
+        Some(dialog.show().await == MessageDialogResult::Yes)
+    }).await?;
+    if confirmed != Some(true) {
+        log::info!("🧩 Plugin {} left unapproved", manifest.id);
+    }
+    Ok(confirmed == Some(true))
+}
+
+// Helper: Finds a plugin of the workspace by id
+fn find_plugin(workspace: &str, id: &str) -> Result<Plugin, String> {
+    discover(Path::new(workspace))
+        .0
+        .into_iter()
+        .find(|plugin| plugin.manifest.id == id)
+        .ok_or_else(|| format!("No plugin named {}", id))
+}
+
+// Helper: An approved plugin with a command of the expected kind
+fn trusted_plugin(workspace: &str, id: &str, command: &str, kind: CommandKind) -> Result<Plugin, String> {
+    if !PLUGINS_SUPPORTED {
+        return Err("Plugins are not supported on this platform".to_string());
+    }
+    let plugin = find_plugin(workspace, id)?;
+    if !PluginTrust::load(&get_config_dir()?)?.is_trusted(workspace, &plugin) {
+        return Err(format!("Plugin {} is not approved on this machine", id));
+    }
+    match plugin.manifest.command(command) {
+        Some(found) if found.kind == kind => Ok(plugin),
+        Some(found) => Err(format!("{} of plugin {} is a {:?}, not a {:?}", command, id, found.kind, kind)),
+        None => Err(format!("Plugin {} has no command {}", id, command)),
+    }
+}
+
+// Helper: Validates a note path and makes it workspace-relative for the plugin
+fn note_request(workspace: &str, path: &str) -> Result<(PathBuf, String), String> {
+    let validated = validate_file_path(path, workspace, &["md"])
+        .map_err(|e| format!("Security error: {}", e))?;
+    let root = Path::new(workspace)
+        .canonicalize()
+        .map_err(|e| format!("Failed to resolve workspace: {}", e))?;
+    let relative = relative_path(&root, &validated);
+    Ok((validated, relative))
+}
+
+// Helper: Runs a plugin on the blocking pool and logs the outcome
+async fn run_blocking(host: PluginHost, plugin: Plugin, request: PluginRequest) -> Result<PluginRun, String> {
+    let id = plugin.manifest.id.clone();
+    let command = request.command.clone();
+    let result = tauri::async_runtime::spawn_blocking(move || host.run(&plugin, &request))
+        .await
+        .map_err(|e| format!("Failed to run plugin: {}", e))?;
+    match &result {
+        Ok(run) => log::info!(
+            "🧩 Plugin {} ran {} in {}ms ({} notes written)",
+            id,
+            command,
+            run.duration_ms,
+            run.written.len()
+        ),
+        Err(e) => log::warn!("⚠️ {}", e),
+    }
+    result
+}
+
+// Helper: Text output of a command or transform
+fn output(mut run: PluginRun) -> Result<PluginOutput, String> {
+    let output = String::from_utf8(std::mem::take(&mut run.output))
+        .map_err(|_| "Plugin returned output that is not UTF-8".to_string())?;
+    Ok(PluginOutput { output, run })
+}
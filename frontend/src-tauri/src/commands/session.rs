@@ -0,0 +1,69 @@
+//! Session Commands
+//!
+//! This module provides Tauri commands for saving and restoring the editor
+//! session (open tabs, cursors, active tab, panel layout) of the configured
+//! workspace, stored in `<workspace>/.mdreader/session.json`.
+//!
+//! ## Security
+//! Tab paths are validated against the workspace before being stored;
+//! tabs outside the workspace are dropped.
+
+use tauri::{command, State};
+use std::path::Path;
+use crate::session::Session;
+use crate::state::AppState;
+use crate::utils::validate_path_within_workspace;
+use crate::workspace_settings::relative_key;
+
+/// Saves the session of the configured workspace.
+///
+/// Tab paths are absolute and stored relative to the workspace root.
+/// Tabs that cannot be validated (unsaved or outside the workspace) are
+/// skipped rather than failing the save.
+#[command]
+pub async fn save_session(
+    state: State<'_, AppState>,
+    session: Session,
+) -> Result<(), String> {
+    let workspace = state.get_workspace_path()?;
+    let root = Path::new(&workspace);
+
+    let to_relative = |path: &str| {
+        validate_path_within_workspace(path, &workspace)
+            .ok()
+            .and_then(|validated| relative_key(root, &validated))
+            .filter(|key| !key.is_empty())
+    };
+
+    let mut stored = Session {
+        tabs: Vec::with_capacity(session.tabs.len()),
+        active_tab: session.active_tab.as_deref().and_then(to_relative),
+        layout: session.layout,
+        saved_at: Some(chrono::Utc::now()),
+    };
+    for mut tab in session.tabs {
+        match to_relative(&tab.path) {
+            Some(key) => {
+                tab.path = key;
+                stored.tabs.push(tab);
+            }
+            None => log::warn!("⚠️ Session tab skipped: {}", tab.path),
+        }
+    }
+
+    stored.save(root)?;
+    log::info!("💾 Session saved: {} tabs", stored.tabs.len());
+    Ok(())
+}
+
+/// Loads the session of the configured workspace.
+///
+/// Returns an empty session when none was saved. Tabs whose files were
+/// deleted since are dropped.
+#[command]
+pub async fn load_session(
+    state: State<'_, AppState>,
+) -> Result<Session, String> {
+    let workspace = state.get_workspace_path()?;
+    Session::load(Path::new(&workspace))
+}
@@ -0,0 +1,459 @@
+//! Workspace Settings Commands
+//!
+//! This module provides Tauri commands for reading and updating the
+//! per-workspace settings stored in `<workspace>/.mdreader/settings.json`.
+//! Every change is announced with a `settings-changed` event.
+//!
+//! ## Security
+//! Folder paths are validated against the configured workspace before
+//! being stored.
+
+use tauri::{command, AppHandle, State};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use crate::backup_targets::{BackupStatuses, BackupTarget, TargetLocation};
+use crate::events::{self, SettingsChangedEvent, SettingsSection};
+use crate::commands::workspace::get_config_dir;
+use crate::export_policy::{is_system_directory, ExportFolders};
+use crate::link_check::{LinkCheckSchedule, MAX_CONCURRENCY};
+use crate::lists::{ListFormat, MAX_INDENT};
+use crate::metadata_schema::{validate_schema, FieldSchema};
+use crate::state::AppState;
+use crate::template::{is_variable_name, BUILTIN_PLACEHOLDERS};
+use crate::utils::validate_directory_path;
+use crate::workspace_settings::{relative_key, AttachmentSettings, FolderFlags, SymlinkPolicy, WorkspaceSettings};
+
+/// Loads the settings of the configured workspace.
+#[command]
+pub async fn get_workspace_settings(
+    state: State<'_, AppState>,
+) -> Result<WorkspaceSettings, String> {
+    let workspace = state.get_workspace_path()?;
+    WorkspaceSettings::load(Path::new(&workspace))
+}
+
+/// Marks a folder as read-only and/or archived (or clears the flags).
+///
+/// While a flag is set, every mutating command refuses to touch files inside
+/// the folder with a `Read-only folder:` or `Archived folder:` error.
+///
+/// Security: Validates folder_path is an existing directory within the workspace.
+#[command]
+pub async fn set_folder_flags(
+    app_handle: AppHandle,
+    state: State<'_, AppState>,
+    folder_path: String,
+    read_only: bool,
+    archived: bool,
+) -> Result<WorkspaceSettings, String> {
+    let workspace = state.get_workspace_path()?;
+
+    let validated_folder = validate_directory_path(&folder_path, &workspace, true)
+        .map_err(|e| format!("Security error: {}", e))?;
+
+    let root = Path::new(&workspace);
+    let key = relative_key(root, &validated_folder)
+        .ok_or("Folder is outside the workspace")?;
+
+    if key.is_empty() {
+        return Err("Cannot protect the workspace root".to_string());
+    }
+
+    let mut settings = WorkspaceSettings::load(root)?;
+    settings.set_folder_flags(key.clone(), FolderFlags { read_only, archived });
+    settings.save(root)?;
+    announce(&app_handle, &workspace, SettingsSection::FolderFlags);
+
+    log::info!("🔒 Folder flags for '{}': read_only={}, archived={}", key, read_only, archived);
+    Ok(settings)
+}
+
+/// Sets how symbolic links inside the workspace are treated.
+///
+/// * `follow` - links are followed wherever they point
+/// * `deny` - any path through a link is refused and links are hidden
+/// * `allow_within_workspace` - links are followed only if they stay inside (default)
+#[command]
+pub async fn set_symlink_policy(
+    app_handle: AppHandle,
+    state: State<'_, AppState>,
+    policy: SymlinkPolicy,
+) -> Result<WorkspaceSettings, String> {
+    let workspace = state.get_workspace_path()?;
+    let root = Path::new(&workspace);
+
+    let mut settings = WorkspaceSettings::load(root)?;
+    settings.symlink_policy = policy;
+    settings.save(root)?;
+    announce(&app_handle, &workspace, SettingsSection::SymlinkPolicy);
+
+    log::info!("🔗 Symlink policy set to {:?}", policy);
+    Ok(settings)
+}
+
+/// Sets whether `.gitignore` files hide files from listings, indexing and
+/// the file watcher.
+///
+/// On by default, so generated files in a vault repository stay out of the
+/// notes; `.mdreaderignore` files apply either way.
+#[command]
+pub async fn set_respect_gitignore(
+    app_handle: AppHandle,
+    state: State<'_, AppState>,
+    enabled: bool,
+) -> Result<WorkspaceSettings, String> {
+    let workspace = state.get_workspace_path()?;
+    let root = Path::new(&workspace);
+
+    let mut settings = WorkspaceSettings::load(root)?;
+    settings.respect_gitignore = enabled;
+    settings.save(root)?;
+    announce(&app_handle, &workspace, SettingsSection::Gitignore);
+
+    log::info!("🙈 Respect .gitignore: {}", enabled);
+    Ok(settings)
+}
+
+/// Sets where pasted and dropped attachments go and how they are linked.
+///
+/// * `location` - `assets_folder` (default), `per_note` or `global`
+/// * `global_folder` - workspace-relative folder for `global` (default `attachments`)
+/// * `link_style` - `relative` (default), `vault_absolute` or `wiki_embed`
+#[command]
+pub async fn set_attachment_settings(
+    app_handle: AppHandle,
+    state: State<'_, AppState>,
+    attachments: AttachmentSettings,
+) -> Result<WorkspaceSettings, String> {
+    let workspace = state.get_workspace_path()?;
+    let root = Path::new(&workspace);
+
+    attachments.validate()?;
+
+    let mut settings = WorkspaceSettings::load(root)?;
+    settings.attachments = attachments;
+    settings.save(root)?;
+    announce(&app_handle, &workspace, SettingsSection::Attachments);
+
+    log::info!(
+        "📎 Attachments: {:?} with {:?} links",
+        settings.attachments.location,
+        settings.attachments.link_style
+    );
+    Ok(settings)
+}
+
+/// Gets the folders outside the workspace that exports of the current
+/// workspace may write to on this machine.
+#[command]
+pub async fn get_export_folders(state: State<'_, AppState>) -> Result<Vec<String>, String> {
+    let workspace = state.get_workspace_path()?;
+    Ok(ExportFolders::load(&get_config_dir()?)?.get(&workspace).to_vec())
+}
+
+/// Sets the folders outside the workspace that exports may write to
+/// without picking the destination in the save dialog.
+///
+/// The folders are kept on this machine (in the config directory), not in
+/// the synced workspace settings.
+///
+/// Security: Every folder must be an existing absolute directory outside
+/// the system directories, and folders not allowed yet must have been
+/// picked with `pick_folder` (`export_folder`); folders are stored
+/// canonicalized.
+#[command]
+pub async fn set_export_folders(
+    app_handle: AppHandle,
+    state: State<'_, AppState>,
+    folders: Vec<String>,
+) -> Result<Vec<String>, String> {
+    let workspace = state.get_workspace_path()?;
+    let config_dir = get_config_dir()?;
+    let mut store = ExportFolders::load(&config_dir)?;
+
+    let mut export_folders = Vec::new();
+    for folder in &folders {
+        let path = PathBuf::from(folder);
+        if !path.is_absolute() {
+            return Err(format!("Export folder must be an absolute path: {}", folder));
+        }
+        let canonical = path
+            .canonicalize()
+            .map_err(|e| format!("Export folder does not exist: {} ({})", folder, e))?;
+        if !canonical.is_dir() {
+            return Err(format!("Export folder is not a directory: {}", folder));
+        }
+        if is_system_directory(&canonical) {
+            return Err(format!("Cannot export into a system directory: {}", folder));
+        }
+        let canonical_str = canonical.to_string_lossy().to_string();
+        let allowed = store.get(&workspace).contains(&canonical_str);
+        if !allowed && !state.export_grants.take_folder(&canonical)? {
+            return Err(format!("Export folder must be chosen in the folder dialog: {}", folder));
+        }
+        if !export_folders.contains(&canonical_str) {
+            export_folders.push(canonical_str);
+        }
+    }
+
+    store.set(&workspace, export_folders);
+    store.save(&config_dir)?;
+    announce(&app_handle, &workspace, SettingsSection::ExportFolders);
+
+    let export_folders = store.get(&workspace).to_vec();
+    log::info!("📤 {} export folders allowed", export_folders.len());
+    Ok(export_folders)
+}
+
+/// Sets the status values of the note status workflow, in board order.
+///
+/// An empty list restores the defaults (`draft`, `in-progress`, `done`).
+/// Notes keep their status when it is removed from the list; the board
+/// then shows it as an unconfigured column.
+#[command]
+pub async fn set_status_values(
+    app_handle: AppHandle,
+    state: State<'_, AppState>,
+    statuses: Vec<String>,
+) -> Result<WorkspaceSettings, String> {
+    let workspace = state.get_workspace_path()?;
+    let root = Path::new(&workspace);
+
+    let mut values: Vec<String> = Vec::new();
+    for status in &statuses {
+        let status = status.trim();
+        if status.is_empty() || status.contains(['\n', ':', '#']) {
+            return Err(format!("Invalid status value: {:?}", status));
+        }
+        if values.iter().any(|v| v.eq_ignore_ascii_case(status)) {
+            return Err(format!("Duplicate status value: {}", status));
+        }
+        values.push(status.to_string());
+    }
+
+    let mut settings = WorkspaceSettings::load(root)?;
+    settings.statuses = values;
+    settings.save(root)?;
+    announce(&app_handle, &workspace, SettingsSection::Statuses);
+
+    log::info!("🏷️ Status values: {}", settings.status_values().join(", "));
+    Ok(settings)
+}
+
+/// Replaces the workspace's template variables.
+///
+/// Each variable is available to every template as `{{name}}`. Names may
+/// contain letters, digits, `_` and `-`; the built-in placeholders (`date`,
+/// `time`, `title`, `uuid`, `clipboard`) cannot be redefined.
+#[command]
+pub async fn set_template_variables(
+    app_handle: AppHandle,
+    state: State<'_, AppState>,
+    variables: BTreeMap<String, String>,
+) -> Result<WorkspaceSettings, String> {
+    let workspace = state.get_workspace_path()?;
+    let root = Path::new(&workspace);
+
+    for name in variables.keys() {
+        if !is_variable_name(name) {
+            return Err(format!("Invalid template variable name: {:?}", name));
+        }
+        if BUILTIN_PLACEHOLDERS.contains(&name.as_str()) {
+            return Err(format!("Template variable shadows a built-in placeholder: {}", name));
+        }
+    }
+
+    let mut settings = WorkspaceSettings::load(root)?;
+    settings.template_variables = variables;
+    settings.save(root)?;
+    announce(&app_handle, &workspace, SettingsSection::TemplateVariables);
+
+    log::info!("🧩 {} template variables set", settings.template_variables.len());
+    Ok(settings)
+}
+
+/// Replaces the workspace's frontmatter schema.
+///
+/// Each field has a `name`, a `type` (`text`, `number`, `boolean`, `date`
+/// or `list`), and optionally `required`, `allowed` values and the
+/// `folders` it applies to. An empty list removes the schema.
+#[command]
+pub async fn set_metadata_schema(
+    app_handle: AppHandle,
+    state: State<'_, AppState>,
+    schema: Vec<FieldSchema>,
+) -> Result<WorkspaceSettings, String> {
+    let workspace = state.get_workspace_path()?;
+    let root = Path::new(&workspace);
+
+    let schema: Vec<FieldSchema> = schema
+        .into_iter()
+        .map(|field| FieldSchema { name: field.name.trim().to_string(), ..field })
+        .collect();
+    validate_schema(&schema)?;
+
+    let mut settings = WorkspaceSettings::load(root)?;
+    settings.metadata_schema = schema;
+    settings.save(root)?;
+    announce(&app_handle, &workspace, SettingsSection::MetadataSchema);
+
+    log::info!("📐 Metadata schema set: {} fields", settings.metadata_schema.len());
+    Ok(settings)
+}
+
+/// Replaces the workspace's backup targets.
+///
+/// Targets are either the local store or an `external_volume` with the
+/// drive's `mount_path`. Drives that are mounted right now are claimed with
+/// a marker file, so the volume watcher recognizes them later. Names must
+/// be unique; removed targets lose their run status.
+///
+/// Security: Volume stores must be absolute and outside the workspace.
+#[command]
+pub async fn set_backup_targets(
+    app_handle: AppHandle,
+    state: State<'_, AppState>,
+    targets: Vec<BackupTarget>,
+) -> Result<WorkspaceSettings, String> {
+    let workspace = state.get_workspace_path()?;
+    let root = Path::new(&workspace);
+    let canonical_root = root
+        .canonicalize()
+        .map_err(|e| format!("Failed to resolve workspace: {}", e))?;
+
+    let mut backup_targets: Vec<BackupTarget> = Vec::new();
+    for mut target in targets {
+        target.name = target.name.trim().to_string();
+        target.validate()?;
+        if backup_targets.iter().any(|t| t.name == target.name) {
+            return Err(format!("Duplicate backup target: {}", target.name));
+        }
+        if let TargetLocation::ExternalVolume { mount_path, .. } = &target.location {
+            let mount = Path::new(mount_path);
+            if mount.starts_with(root) || mount.starts_with(&canonical_root) {
+                return Err(format!("Backup volume must be outside the workspace: {}", mount_path));
+            }
+        }
+        target.claim_volume()?;
+        backup_targets.push(target);
+    }
+
+    let mut settings = WorkspaceSettings::load(root)?;
+    settings.backup_targets = backup_targets;
+    settings.save(root)?;
+
+    let mut statuses = BackupStatuses::load(root)?;
+    if statuses.sync_targets(&settings.backup_targets, chrono::Utc::now()) {
+        statuses.save(root)?;
+    }
+    announce(&app_handle, &workspace, SettingsSection::BackupTargets);
+
+    log::info!("🗄️ {} backup targets configured", settings.backup_targets.len());
+    Ok(settings)
+}
+
+/// Sets how often the workspace's external links are checked in the
+/// background, and how.
+///
+/// * `interval_hours` - hours between checks of the whole workspace (0 = off)
+/// * `options` - concurrency, per-host interval, timeout and cache age
+///   (see `crate::link_check`)
+#[command]
+pub async fn set_link_check_schedule(
+    app_handle: AppHandle,
+    state: State<'_, AppState>,
+    schedule: LinkCheckSchedule,
+) -> Result<WorkspaceSettings, String> {
+    let workspace = state.get_workspace_path()?;
+    let root = Path::new(&workspace);
+
+    if !(1..=MAX_CONCURRENCY).contains(&schedule.options.concurrency) {
+        return Err(format!("Concurrency must be between 1 and {}", MAX_CONCURRENCY));
+    }
+    if schedule.options.timeout_secs == 0 {
+        return Err("Timeout must be at least one second".to_string());
+    }
+
+    let mut settings = WorkspaceSettings::load(root)?;
+    settings.link_check = schedule;
+    settings.save(root)?;
+    announce(&app_handle, &workspace, SettingsSection::LinkCheck);
+
+    log::info!("🔗 Link check schedule: every {} hours (0 = off)", settings.link_check.interval_hours);
+    Ok(settings)
+}
+
+/// Sets how lists are tidied: spaces per nesting level, and whether the
+/// lists of a note are fixed on every explicit save (see `crate::lists`).
+#[command]
+pub async fn set_list_format(
+    app_handle: AppHandle,
+    state: State<'_, AppState>,
+    format: ListFormat,
+) -> Result<WorkspaceSettings, String> {
+    let workspace = state.get_workspace_path()?;
+    let root = Path::new(&workspace);
+
+    if !(1..=MAX_INDENT).contains(&format.indent) {
+        return Err(format!("List indent must be between 1 and {} spaces", MAX_INDENT));
+    }
+
+    let mut settings = WorkspaceSettings::load(root)?;
+    settings.lists = format;
+    settings.save(root)?;
+    announce(&app_handle, &workspace, SettingsSection::Lists);
+
+    log::info!("📝 List format: {} spaces, on save: {}", settings.lists.indent, settings.lists.on_save);
+    Ok(settings)
+}
+
+// Helper: Emits `settings-changed` for a saved section
+fn announce(app_handle: &AppHandle, workspace: &str, section: SettingsSection) {
+    events::emit(app_handle, &SettingsChangedEvent {
+        section,
+        workspace: workspace.to_string(),
+    });
+}
+
+// ============================================================================
+// TESTS
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+    use crate::workspace_settings::ensure_writable;
+
+    /// Creates a workspace with AppState configured for integration tests
+    fn setup_workspace_with_state() -> (TempDir, AppState) {
+        let workspace = TempDir::new().expect("Failed to create workspace");
+        let state = AppState::new();
+        state.set_workspace_path(workspace.path().to_string_lossy().to_string())
+            .expect("Failed to set workspace");
+        (workspace, state)
+    }
+
+    #[test]
+    fn test_flagged_folder_blocks_writes() {
+        let (workspace, state) = setup_workspace_with_state();
+        let configured = state.get_workspace_path().unwrap();
+        fs::create_dir_all(workspace.path().join("Reference")).unwrap();
+
+        // Simulate set_folder_flags logic
+        let folder = workspace.path().join("Reference").to_string_lossy().to_string();
+        let validated = validate_directory_path(&folder, &configured, true).unwrap();
+        let key = relative_key(Path::new(&configured), &validated).unwrap();
+        assert_eq!(key, "Reference");
+
+        let mut settings = WorkspaceSettings::load(Path::new(&configured)).unwrap();
+        settings.set_folder_flags(key, FolderFlags { read_only: true, archived: false });
+        settings.save(Path::new(&configured)).unwrap();
+
+        // Mutating commands consult the flag
+        let target = validated.join("paper.md");
+        let result = ensure_writable(&configured, &target);
+        assert!(result.is_err(), "Writes inside a read-only folder must be refused");
+    }
+}
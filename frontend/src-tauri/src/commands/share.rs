@@ -0,0 +1,122 @@
+//! Share Intake Commands
+//!
+//! This module routes items shared into the app from the mobile share sheet
+//! (see `crate::share`) through the capture pipeline: each one is appended
+//! to the workspace's inbox note, shared images are stored as its
+//! attachments, and `shared-into-note` tells the UI which note changed.
+
+use std::fs;
+use std::path::Path;
+use serde::Serialize;
+use tauri::{command, AppHandle, Manager, State};
+use crate::capture::CapturePosition;
+use crate::commands::file_operations::{capture_entry, store_attachment};
+use crate::events::{self, SharedIntoNoteEvent};
+use crate::share::{self, SharedItem, INBOX_NOTE};
+use crate::state::AppState;
+
+/// Result of routing shared items
+#[derive(Debug, Clone, Serialize)]
+pub struct ShareIntake {
+    /// Note the items were added to
+    pub note: String,
+    pub added: usize,
+    /// Items that could not be added, with the reason
+    pub failed: Vec<String>,
+}
+
+/// Adds the items shared since the last call to the inbox note.
+///
+/// Called by the frontend once a workspace is open; pending shares are
+/// also imported whenever the app resumes. Items stay queued on the
+/// native side while no workspace is open.
+///
+/// # Returns
+/// * `Ok(None)` - If nothing was shared
+/// * `Ok(Some(ShareIntake))` - Where the items went
+#[command]
+pub async fn import_pending_shares(app_handle: AppHandle) -> Result<Option<ShareIntake>, String> {
+    // Busy notes are waited for on the blocking pool
+    tauri::async_runtime::spawn_blocking(move || {
+        let state = app_handle.state::<AppState>();
+        import_shares(&app_handle, &state)
+    })
+    .await
+    .map_err(|e| format!("Failed to import shares: {}", e))?
+}
+
+/// Adds items shared through the frontend (e.g. a share target of the web
+/// view) to the inbox note, the same way as native shares.
+#[command]
+pub async fn receive_shared_items(
+    app_handle: AppHandle,
+    state: State<'_, AppState>,
+    items: Vec<SharedItem>,
+) -> Result<ShareIntake, String> {
+    route_items(&app_handle, &state, items)
+}
+
+/// Pulls pending native shares and routes them (no-op without a workspace)
+pub(crate) fn import_shares(app_handle: &AppHandle, state: &AppState) -> Result<Option<ShareIntake>, String> {
+    if !state.has_workspace() {
+        return Ok(None);
+    }
+    let items = state.shares.take()?;
+    if items.is_empty() {
+        return Ok(None);
+    }
+    route_items(app_handle, state, items).map(Some)
+}
+
+// Helper: Appends each item to the inbox note, storing images first
+fn route_items(app_handle: &AppHandle, state: &AppState, items: Vec<SharedItem>) -> Result<ShareIntake, String> {
+    let workspace = state.get_workspace_path()?;
+    let inbox = Path::new(&workspace).join(INBOX_NOTE).to_string_lossy().to_string();
+
+    let mut note = None;
+    let mut attachments = Vec::new();
+    let mut failed = Vec::new();
+    let mut added = 0;
+    for item in &items {
+        let result = (|| {
+            let link = match (item, item.image_name()) {
+                (SharedItem::Image { path, .. }, Some(name)) => {
+                    let data = fs::read(path)
+                        .map_err(|e| format!("Failed to read shared image: {}", e))?;
+                    let saved = store_attachment(state, &inbox, &name, &data)?;
+                    // The native copy is only a hand-over
+                    fs::remove_file(path).ok();
+                    attachments.push(saved.path);
+                    Some(saved.markdown)
+                }
+                _ => None,
+            };
+            let Some(text) = item.entry_text(link.as_deref()) else {
+                return Err("Nothing to add".to_string());
+            };
+            capture_entry(state, &inbox, &text, share::capture_options(), CapturePosition::Append)
+        })();
+        match result {
+            Ok(path) => {
+                note = Some(path.to_string_lossy().to_string());
+                added += 1;
+            }
+            Err(e) => {
+                log::warn!("⚠️ Shared item not added: {}", e);
+                failed.push(e);
+            }
+        }
+    }
+
+    let note = note.unwrap_or(inbox);
+    if added > 0 {
+        events::emit(app_handle, &SharedIntoNoteEvent {
+            workspace,
+            path: note.clone(),
+            added,
+            attachments,
+        });
+    }
+    log::info!("📥 Added {} shared items to {}", added, note);
+    Ok(ShareIntake { note, added, failed })
+}
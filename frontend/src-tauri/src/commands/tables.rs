@@ -0,0 +1,56 @@
+//! Table Commands
+//!
+//! This module provides Tauri commands that edit the pipe tables of a note
+//! as rows and columns (see `crate::tables`), so the table editor sends
+//! structured operations instead of rewriting markdown text.
+//!
+//! ## Security
+//! Note paths are validated against the configured workspace; edits
+//! require the note to be writable.
+
+use std::fs;
+use std::time::Duration;
+use tauri::{command, State};
+use crate::activity::ActivityKind;
+use crate::safe_write;
+use crate::state::AppState;
+use crate::tables::{self, TableOp};
+use crate::utils::validate_file_path;
+use crate::workspace_settings::ensure_writable;
+use crate::write_locks::DEFAULT_LOCK_WAIT_MS;
+
+/// Edits table `table_index` (0-based, tables in code blocks skipped) of
+/// a note: add or remove a row or column, sort by a column, or realign.
+///
+/// The table is written back with its columns lined up.
+///
+/// Security: Validates file_path is a markdown file within the workspace.
+///
+/// # Returns
+/// * `Ok(String)` - The note's content after the edit
+#[command]
+pub async fn table_ops(
+    state: State<'_, AppState>,
+    file_path: String,
+    table_index: usize,
+    op: TableOp,
+) -> Result<String, String> {
+    let workspace = state.get_workspace_path()?;
+
+    let validated_path = validate_file_path(&file_path, &workspace, &["md"])
+        .map_err(|e| format!("Security error: {}", e))?;
+    ensure_writable(&workspace, &validated_path)?;
+
+    // Pending debounced content must land first, or the edit would drop it
+    state.save_queue.flush_path_async(&validated_path).await?;
+    let _guard = state.write_locks
+        .acquire_async(&validated_path, Duration::from_millis(DEFAULT_LOCK_WAIT_MS)).await?;
+    let content = fs::read_to_string(&validated_path)
+        .map_err(|e| format!("Failed to read file: {}", e))?;
+    let updated = tables::table_ops(&content, table_index, &op)?;
+    safe_write::write_file(&validated_path, &updated)?;
+    state.activity.record(&workspace, &validated_path, ActivityKind::Saved, None);
+
+    log::info!("📋 Table {} of {:?}: {:?}", table_index + 1, validated_path, op);
+    Ok(updated)
+}
@@ -0,0 +1,122 @@
+//! Template Commands
+//!
+//! This module provides Tauri commands that fill in text with the shared
+//! template engine (see `crate::template`), for features that build text in
+//! the frontend such as AI prompts, and the workspace's snippet library
+//! (see `crate::snippets`).
+//!
+//! ## Security
+//! Template files are validated against the configured workspace.
+
+use std::fs;
+use std::path::Path;
+use tauri::{command, State};
+use crate::snippets::{Snippet, SnippetInput, SnippetLibrary};
+use crate::state::AppState;
+use crate::template::{self, TemplateContext, TemplateInput};
+use crate::utils::validate_file_path;
+use crate::workspace_settings::WorkspaceSettings;
+
+/// Fills the placeholders of `text`.
+///
+/// `context` supplies the title, the clipboard text and extra variables;
+/// the workspace's template variables are available too.
+///
+/// # Returns
+/// * `Ok(String)` - The rendered text
+#[command]
+pub async fn render_template(
+    state: State<'_, AppState>,
+    text: String,
+    context: Option<TemplateInput>,
+) -> Result<String, String> {
+    let workspace = state.get_workspace_path()?;
+    let context = workspace_context(&workspace, context.unwrap_or_default())?;
+    Ok(template::render_template(&text, &context))
+}
+
+/// Lists the workspace's snippets, sorted by name.
+#[command]
+pub async fn list_snippets(state: State<'_, AppState>) -> Result<Vec<Snippet>, String> {
+    let workspace = state.get_workspace_path()?;
+    Ok(SnippetLibrary::load(Path::new(&workspace))?.list())
+}
+
+/// Creates a snippet, or updates the one with `snippet.id`.
+///
+/// # Returns
+/// * `Ok(Snippet)` - The stored snippet, with its id
+#[command]
+pub async fn save_snippet(
+    state: State<'_, AppState>,
+    snippet: SnippetInput,
+) -> Result<Snippet, String> {
+    let workspace = state.get_workspace_path()?;
+    let root = Path::new(&workspace);
+
+    let mut library = SnippetLibrary::load(root)?;
+    let snippet = library.upsert(snippet, chrono::Utc::now().timestamp_millis())?;
+    library.save(root)?;
+
+    log::info!("🧩 Saved snippet '{}' ({})", snippet.name, snippet.id);
+    Ok(snippet)
+}
+
+/// Deletes a snippet.
+#[command]
+pub async fn delete_snippet(state: State<'_, AppState>, id: String) -> Result<(), String> {
+    let workspace = state.get_workspace_path()?;
+    let root = Path::new(&workspace);
+
+    let mut library = SnippetLibrary::load(root)?;
+    let snippet = library.remove(&id)?;
+    library.save(root)?;
+
+    log::info!("🗑️ Deleted snippet '{}' ({})", snippet.name, snippet.id);
+    Ok(())
+}
+
+/// Expands a snippet for insertion into the editor.
+///
+/// `context` supplies the title, the clipboard text and the snippet's
+/// variables (e.g. `{"variables": {"topic": "Roadmap"}}`); the workspace's
+/// template variables are available too.
+///
+/// # Returns
+/// * `Ok(String)` - The snippet body with its placeholders filled in
+#[command]
+pub async fn expand_snippet(
+    state: State<'_, AppState>,
+    id: String,
+    context: Option<TemplateInput>,
+) -> Result<String, String> {
+    let workspace = state.get_workspace_path()?;
+    let library = SnippetLibrary::load(Path::new(&workspace))?;
+    let snippet = library.get(&id)?;
+
+    let context = workspace_context(&workspace, context.unwrap_or_default())?;
+    Ok(template::render_template(&snippet.body, &context))
+}
+
+/// Builds a template context for the workspace at the current local time
+pub(crate) fn workspace_context(workspace: &str, input: TemplateInput) -> Result<TemplateContext, String> {
+    let settings = WorkspaceSettings::load(Path::new(workspace))?;
+    Ok(TemplateContext::from_input(
+        chrono::Local::now().naive_local(),
+        input,
+        &settings.template_variables,
+    ))
+}
+
+/// Reads a template note of the workspace and renders it for a note titled `title`
+///
+/// Security: Validates template_path is a markdown file within the workspace.
+pub(crate) fn render_template_file(workspace: &str, template_path: &str, title: &str) -> Result<String, String> {
+    let validated_path = validate_file_path(template_path, workspace, &["md"])
+        .map_err(|e| format!("Security error: {}", e))?;
+    let text = fs::read_to_string(&validated_path)
+        .map_err(|e| format!("Failed to read template: {}", e))?;
+
+    let input = TemplateInput { title: Some(title.to_string()), ..TemplateInput::default() };
+    Ok(template::render_template(&text, &workspace_context(workspace, input)?))
+}
@@ -0,0 +1,40 @@
+//! Thumbnail Commands
+//!
+//! This module provides the Tauri command behind gallery and card previews
+//! (see `crate::thumbnails`).
+//!
+//! ## Security
+//! Source paths are validated against the configured workspace; thumbnails
+//! are only ever written to the cache directory.
+
+use tauri::{command, State};
+use crate::state::AppState;
+use crate::thumbnails::{self, Thumbnail, DEFAULT_SIZE, THUMBNAIL_EXTENSIONS};
+use crate::utils::validate_file_path;
+
+/// Returns a thumbnail of an image or note, generating and caching it if needed.
+///
+/// `size` is the maximum edge length in pixels (default 256, clamped to
+/// 16-1024). Notes get an SVG mindmap of their headings. With `inline`
+/// (default true) the thumbnail is also returned as a `data:` URI.
+///
+/// Security: Validates file_path is an image or markdown file within the workspace.
+#[command]
+pub async fn get_thumbnail(
+    state: State<'_, AppState>,
+    file_path: String,
+    size: Option<u32>,
+    inline: Option<bool>,
+) -> Result<Thumbnail, String> {
+    let workspace = state.get_workspace_path()?;
+
+    let validated_path = validate_file_path(&file_path, &workspace, THUMBNAIL_EXTENSIONS)
+        .map_err(|e| format!("Security error: {}", e))?;
+
+    thumbnails::thumbnail(
+        &validated_path,
+        size.unwrap_or(DEFAULT_SIZE),
+        &thumbnails::cache_dir()?,
+        inline.unwrap_or(true),
+    )
+}
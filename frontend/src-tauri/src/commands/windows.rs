@@ -0,0 +1,107 @@
+//! Document Window Commands
+//!
+//! This module provides Tauri commands for opening notes in their own
+//! native window (see `crate::windows`).
+//!
+//! ## Security
+//! Note paths are validated against the configured workspace before a
+//! window is created for them.
+
+use tauri::{command, AppHandle, Manager, State, WebviewUrl, WebviewWindowBuilder};
+use crate::state::AppState;
+use crate::utils::validate_file_path;
+use crate::windows::{document_url, DocumentWindow};
+
+/// Opens a note in a new native window and returns the window label.
+///
+/// If the note already has a window, that window is focused instead, so a
+/// file is never edited in two document windows at once. The new window
+/// loads the `workspace` route with `file` and `window` query parameters.
+///
+/// Security: Validates file_path is a markdown file within the workspace.
+#[command]
+pub async fn open_document_window(
+    app_handle: AppHandle,
+    state: State<'_, AppState>,
+    file_path: String,
+) -> Result<String, String> {
+    let workspace = state.get_workspace_path()?;
+
+    let validated_path = validate_file_path(&file_path, &workspace, &["md"])
+        .map_err(|e| format!("Security error: {}", e))?;
+
+    if !validated_path.is_file() {
+        return Err(format!("File does not exist: {}", file_path));
+    }
+
+    if let Some(label) = state.windows.owner_of(&validated_path) {
+        match app_handle.get_webview_window(&label) {
+            Some(window) => {
+                window.set_focus()
+                    .map_err(|e| format!("Failed to focus window: {}", e))?;
+                log::info!("🪟 Focused existing window {} for {:?}", label, validated_path);
+                return Ok(label);
+            }
+            // The window is gone without a close event; drop the stale entry
+            None => {
+                state.windows.remove(&label);
+            }
+        }
+    }
+
+    // Register first so a concurrent open of the same file is refused
+    let label = state.windows.next_label();
+    state.windows.register(&label, &validated_path)?;
+
+    let title = validated_path
+        .file_stem()
+        .map(|s| s.to_string_lossy().to_string())
+        .unwrap_or_else(|| "Document".to_string());
+    let url = WebviewUrl::App(document_url(&validated_path, &label).into());
+
+    let built = WebviewWindowBuilder::new(&app_handle, label.clone(), url)
+        .title(title)
+        .inner_size(900.0, 700.0)
+        .min_inner_size(400.0, 300.0)
+        .resizable(true)
+        .build();
+
+    if let Err(e) = built {
+        state.windows.remove(&label);
+        return Err(format!("Failed to open window: {}", e));
+    }
+
+    log::info!("🪟 Opened window {} for {:?}", label, validated_path);
+    Ok(label)
+}
+
+/// Lists the open document windows.
+#[command]
+pub async fn list_document_windows(
+    state: State<'_, AppState>,
+) -> Result<Vec<DocumentWindow>, String> {
+    state.windows.list()
+}
+
+/// Releases the file of a closing document window.
+///
+/// Pending debounced saves of the file are written out (on the async
+/// runtime, so the close handler never waits for hooks) and its live
+/// outline is dropped. Called from the window close handler.
+pub fn release_document_window(state: &AppState, label: &str) {
+    let Some(window) = state.windows.remove(label) else {
+        return;
+    };
+
+    let (save_queue, path) = (state.save_queue.clone(), window.path.clone());
+    tauri::async_runtime::spawn(async move {
+        if let Err(e) = save_queue.flush_path_async(&path).await {
+            log::error!("❌ Failed to flush pending save of {:?}: {}", path, e);
+        }
+    });
+    if let Err(e) = state.outlines.close(&window.path) {
+        log::warn!("⚠️ Failed to close outline of {:?}: {}", window.path, e);
+    }
+
+    log::info!("🪟 Closed window {} for {:?}", label, window.path);
+}
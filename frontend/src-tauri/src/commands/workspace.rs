@@ -1,11 +1,36 @@
-use tauri::command;
+use tauri::{command, AppHandle, Manager, State};
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
 use serde::{Deserialize, Serialize};
+use crate::calendar::{self, CalendarEvent};
+use crate::capabilities::BackendCapabilities;
+use crate::events::{self, MetadataReindexedEvent};
+use crate::file_meta::{epoch_ms, modified_iso, FilePermissions, FileTimes};
+use crate::frontmatter;
+use crate::git::{self, GitSummary};
+use crate::ignore::IgnoreRules;
+use crate::jobs::emit_progress;
+use crate::metrics::{metric_series, MetricSeries};
+use crate::notes::relative_path;
+use crate::overview::WorkspaceOverview;
+use crate::paging::{self, PageKey};
+use crate::search_index::{self, SearchHit, DEFAULT_SEARCH_LIMIT};
+use crate::state::AppState;
+use crate::timeline::{build_timeline, Granularity, TimelineBucket, TimelineRange};
+use crate::utils::{is_symlink_visible, validate_file_path, validate_new_directory_path};
+use crate::vault_report::render_report;
+use crate::workspace_settings::{ensure_writable, WorkspaceSettings};
+use crate::write_locks::DEFAULT_LOCK_WAIT_MS;
+
+/// Scratchpad file name inside the app config directory
+const SCRATCHPAD_FILE: &str = "scratchpad.md";
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct WorkspaceConfig {
     pub workspace_path: String,
+    /// Legacy flat list; ranking now comes from `get_frequent_files`
+    #[serde(default)]
     pub recent_files: Vec<String>,
     pub last_opened: Option<String>,
     pub created_at: String,
@@ -22,8 +47,8 @@ fn get_default_workspace_path() -> Result<PathBuf, String> {
     Ok(home_dir.join("MDReader"))
 }
 
-/// Get the config file path (stored in app data directory)
-fn get_config_path() -> Result<PathBuf, String> {
+/// Get the app config directory, creating it if needed
+pub(crate) fn get_config_dir() -> Result<PathBuf, String> {
     // Get app data directory based on OS
     // Mac: ~/Library/Application Support/com.mdreader.app/
     // Windows: C:\Users\{user}\AppData\Roaming\com.mdreader.app\
@@ -36,15 +61,41 @@ fn get_config_path() -> Result<PathBuf, String> {
     fs::create_dir_all(&config_dir)
         .map_err(|e| format!("Failed to create config directory: {}", e))?;
     
-    Ok(config_dir.join("workspace-config.json"))
+    Ok(config_dir)
+}
+
+/// Get the config file path (stored in app data directory)
+fn get_config_path() -> Result<PathBuf, String> {
+    Ok(get_config_dir()?.join("workspace-config.json"))
+}
+
+/// Get the scratchpad file path (stored in app data directory)
+fn get_scratchpad_path() -> Result<PathBuf, String> {
+    Ok(get_config_dir()?.join(SCRATCHPAD_FILE))
+}
+
+/// Writes through a temporary file so a crash never leaves a truncated scratchpad
+fn write_scratchpad(path: &Path, content: &str) -> Result<(), String> {
+    let temp_path = path.with_extension("md.tmp");
+    fs::write(&temp_path, content)
+        .map_err(|e| format!("Failed to save scratchpad: {}", e))?;
+    fs::rename(&temp_path, path)
+        .map_err(|e| format!("Failed to save scratchpad: {}", e))
 }
 
 /// Create a directory (and parent directories if needed)
+/// 
+/// Security: Validates the nearest existing parent is within the workspace
+/// and the path is not in a read-only or archived folder.
 #[command]
-pub async fn create_directory(path: String) -> Result<(), String> {
-    let path_buf = PathBuf::from(&path);
+pub async fn create_directory(state: State<'_, AppState>, path: String) -> Result<(), String> {
+    let workspace = state.get_workspace_path()?;
+    
+    let validated_path = validate_new_directory_path(&path, &workspace)
+        .map_err(|e| format!("Security error: {}", e))?;
+    ensure_writable(&workspace, &validated_path)?;
     
-    fs::create_dir_all(&path_buf)
+    fs::create_dir_all(&validated_path)
         .map_err(|e| format!("Failed to create directory '{}': {}", path, e))?;
     
     println!("✅ Created directory: {}", path);
@@ -180,22 +231,54 @@ Happy writing! ✍️
 }
 
 /// List all markdown files and folders in a directory
+/// 
+/// If a workspace is configured, its symlink policy decides which links
+/// are listed and its ignore files which entries are left out. With `include_preview` and `include_git`, entries carry their
+/// preview and git status as in `list_workspace_files`.
 #[command]
-pub async fn list_workspace_contents(directory_path: String) -> Result<Vec<super::file_operations::FileMetadata>, String> {
+pub async fn list_workspace_contents(
+    state: State<'_, AppState>,
+    directory_path: String,
+    include_preview: Option<bool>,
+    include_git: Option<bool>,
+) -> Result<Vec<super::file_operations::FileMetadata>, String> {
     let path = PathBuf::from(&directory_path);
     
     if !path.exists() {
         return Err(format!("Directory does not exist: {}", directory_path));
     }
     
+    let workspace_root = state.get_workspace_path()
+        .ok()
+        .and_then(|w| PathBuf::from(w).canonicalize().ok());
+    
     let mut contents = Vec::new();
+    let repo = super::file_operations::git_status(&path, include_git);
+    let canonical_dir = path.canonicalize().unwrap_or_else(|_| path.clone());
+    let mut ignore = workspace_root.as_deref().map(IgnoreRules::load);
     
     let entries = fs::read_dir(&path)
         .map_err(|e| format!("Failed to read directory: {}", e))?;
     
     for entry in entries {
         let entry = entry.map_err(|e| format!("Failed to read entry: {}", e))?;
-        let metadata = entry.metadata()
+        let is_symlink = entry.file_type().map(|t| t.is_symlink()).unwrap_or(false);
+        
+        if is_symlink {
+            let visible = match &workspace_root {
+                Some(root) => {
+                    let policy = WorkspaceSettings::load_symlink_policy(root);
+                    is_symlink_visible(&entry.path(), root, policy)
+                }
+                // No workspace yet: only skip broken links
+                None => entry.path().exists(),
+            };
+            if !visible {
+                continue;
+            }
+        }
+        
+        let metadata = fs::metadata(entry.path())
             .map_err(|e| format!("Failed to read metadata: {}", e))?;
         
         let file_name = entry.file_name().to_string_lossy().to_string();
@@ -205,18 +288,26 @@ pub async fn list_workspace_contents(directory_path: String) -> Result<Vec<super
             continue;
         }
         
+        // Hide what .gitignore / .mdreaderignore exclude
+        if let Some(ignore) = ignore.as_mut() {
+            if ignore.is_ignored(&canonical_dir.join(entry.file_name()), metadata.is_dir()) {
+                continue;
+            }
+        }
+        
         // Include directories and .md files
         if metadata.is_dir() || file_name.ends_with(".md") {
-            let modified = metadata.modified()
-                .map(|t| format!("{:?}", t))
-                .unwrap_or_else(|_| "Unknown".to_string());
-            
             contents.push(super::file_operations::FileMetadata {
                 name: file_name,
                 path: entry.path().to_string_lossy().to_string(),
                 size: metadata.len(),
-                modified,
+                modified: modified_iso(&metadata),
                 is_directory: metadata.is_dir(),
+                is_symlink,
+                times: FileTimes::from_metadata(&metadata),
+                permissions: Some(FilePermissions::from_metadata(&metadata)),
+                preview: super::file_operations::load_preview(&entry.path(), &metadata, include_preview.unwrap_or(false)),
+                git: repo.as_ref().and_then(|r| r.status_of(&canonical_dir.join(entry.file_name()))),
             });
         }
     }
@@ -233,6 +324,116 @@ pub async fn list_workspace_contents(directory_path: String) -> Result<Vec<super
     Ok(contents)
 }
 
+/// One page of `list_directory_page`
+#[derive(Debug, Clone, Serialize)]
+pub struct DirectoryPage {
+    pub entries: Vec<super::file_operations::FileMetadata>,
+    /// Cursor of the next page; `None` on the last page
+    pub next_cursor: Option<String>,
+    /// Number of listed entries in the whole directory
+    pub total: usize,
+}
+
+/// List one page of a directory, for folders too large to list at once
+///
+/// Lists the same entries in the same order as `list_workspace_contents`,
+/// but only `page_size` of them (default 200, at most 1000) starting after
+/// `cursor`. Pass `None` for the first page and the returned `next_cursor`
+/// for the following ones. Each page streams the directory once and keeps
+/// only the page in memory (see `crate::paging`), and only its entries are
+/// read in full, so browsing a folder with tens of thousands of notes on a
+/// phone never builds the whole listing.
+#[command]
+pub async fn list_directory_page(
+    state: State<'_, AppState>,
+    path: String,
+    cursor: Option<String>,
+    page_size: Option<usize>,
+    include_preview: Option<bool>,
+    include_git: Option<bool>,
+) -> Result<DirectoryPage, String> {
+    let directory = PathBuf::from(&path);
+    if !directory.is_dir() {
+        return Err(format!("Directory does not exist: {}", path));
+    }
+    let after = cursor.as_deref().map(PageKey::from_cursor).transpose()?;
+    let size = paging::page_size(page_size);
+    let workspace_root = state.get_workspace_path()
+        .ok()
+        .and_then(|w| PathBuf::from(w).canonicalize().ok());
+
+    tauri::async_runtime::spawn_blocking(move || {
+        let canonical_dir = directory.canonicalize().unwrap_or_else(|_| directory.clone());
+        let mut ignore = workspace_root.as_deref().map(IgnoreRules::load);
+        let policy = workspace_root.as_deref().map(WorkspaceSettings::load_symlink_policy);
+
+        let entries = fs::read_dir(&directory)
+            .map_err(|e| format!("Failed to read directory: {}", e))?;
+        let keys = entries.filter_map(Result::ok).filter_map(|entry| {
+            let name = entry.file_name().to_string_lossy().to_string();
+            if name.starts_with('.') {
+                return None;
+            }
+            let file_type = entry.file_type().ok()?;
+            let is_directory = if file_type.is_symlink() {
+                let visible = match (&workspace_root, policy) {
+                    (Some(root), Some(policy)) => is_symlink_visible(&entry.path(), root, policy),
+                    // No workspace yet: only skip broken links
+                    _ => entry.path().exists(),
+                };
+                if !visible {
+                    return None;
+                }
+                entry.path().is_dir()
+            } else {
+                file_type.is_dir()
+            };
+            if !is_directory && !name.ends_with(".md") {
+                return None;
+            }
+            if let Some(ignore) = ignore.as_mut() {
+                if ignore.is_ignored(&canonical_dir.join(&name), is_directory) {
+                    return None;
+                }
+            }
+            Some(PageKey { is_directory, name })
+        });
+        let page = paging::select_page(keys, after.as_ref(), size);
+
+        let repo = super::file_operations::git_status(&directory, include_git);
+        let next_cursor = page.has_more
+            .then(|| page.keys.last().map(PageKey::to_cursor))
+            .flatten();
+        let entries = page.keys
+            .into_iter()
+            .filter_map(|key| {
+                let entry_path = directory.join(&key.name);
+                // Skip entries removed since the directory was read
+                let metadata = fs::metadata(&entry_path).ok()?;
+                let is_symlink = fs::symlink_metadata(&entry_path)
+                    .map(|m| m.file_type().is_symlink())
+                    .unwrap_or(false);
+                Some(super::file_operations::FileMetadata {
+                    path: entry_path.to_string_lossy().to_string(),
+                    size: metadata.len(),
+                    modified: modified_iso(&metadata),
+                    is_directory: key.is_directory,
+                    is_symlink,
+                    times: FileTimes::from_metadata(&metadata),
+                    permissions: Some(FilePermissions::from_metadata(&metadata)),
+                    preview: super::file_operations::load_preview(&entry_path, &metadata, include_preview.unwrap_or(false)),
+                    git: repo.as_ref().and_then(|r| r.status_of(&canonical_dir.join(&key.name))),
+                    name: key.name,
+                })
+            })
+            .collect();
+
+        Ok(DirectoryPage { entries, next_cursor, total: page.total })
+    })
+    .await
+    .map_err(|e| format!("Failed to list directory: {}", e))?
+}
+
 /// Check if a directory exists and is accessible
 #[command]
 pub async fn verify_workspace_path(path: String) -> Result<bool, String> {
@@ -249,6 +450,366 @@ pub async fn verify_workspace_path(path: String) -> Result<bool, String> {
     }
 }
 
+/// Load the scratchpad (empty if it was never written)
+/// 
+/// The scratchpad lives in the app config directory, not in the workspace,
+/// so quick capture and focus mode work before a workspace is configured.
+#[command]
+pub async fn get_scratchpad() -> Result<String, String> {
+    let path = get_scratchpad_path()?;
+    
+    if !path.exists() {
+        return Ok(String::new());
+    }
+    
+    fs::read_to_string(&path)
+        .map_err(|e| format!("Failed to read scratchpad: {}", e))
+}
+
+/// Replace the scratchpad content
+/// 
+/// Writes from several windows (quick capture, main editor) are serialized
+/// through the per-file write locks.
+#[command]
+pub async fn set_scratchpad(
+    state: State<'_, AppState>,
+    content: String,
+) -> Result<(), String> {
+    let path = get_scratchpad_path()?;
+    
+    let _guard = state.write_locks
+        .acquire_async(&path, Duration::from_millis(DEFAULT_LOCK_WAIT_MS)).await?;
+    write_scratchpad(&path, &content)?;
+    
+    println!("📝 Scratchpad saved ({} bytes)", content.len());
+    Ok(())
+}
+
+/// Reports the backend version, platform, available features and supported
+/// import/export formats.
+/// 
+/// Optional features are probed on the current machine (PDF renderer, OCR
+/// language data, audio input, `git`), so the frontend should call this once
+/// at startup and hide UI for whatever is unavailable.
+#[command]
+pub async fn get_backend_capabilities() -> Result<BackendCapabilities, String> {
+    let capabilities = BackendCapabilities::detect();
+    log::info!("🧰 Backend {} on {}: {:?}", capabilities.version, capabilities.platform.os, capabilities.features);
+    Ok(capabilities)
+}
+
+/// Summarizes the workspace for the dashboard view.
+/// 
+/// Returns note and word totals, notes created/modified per week (last
+/// `OVERVIEW_WEEKS` weeks), the largest and most-linked notes, tag counts
+/// and asset storage usage. Computed from the metadata cache, which only
+/// re-reads notes that changed since the last call.
+#[command]
+pub async fn get_workspace_overview(state: State<'_, AppState>) -> Result<WorkspaceOverview, String> {
+    let workspace = state.get_workspace_path()?;
+    let now_ms = epoch_ms(std::time::SystemTime::now());
+    
+    let overview = state.metadata.with_fresh(&workspace, |cache| WorkspaceOverview::from_cache(cache, now_ms))?;
+    log::info!("📊 Workspace overview: {} notes, {} words", overview.total_notes, overview.total_words);
+    Ok(overview)
+}
+
+/// Seconds between two checks for a newly opened workspace
+const INTEGRITY_POLL_SECS: u64 = 2;
+
+/// Minimum time between two progress events of a re-index
+const REINDEX_PROGRESS_INTERVAL_MS: u128 = 100;
+
+/// Warms up the indexes of each workspace once when it is opened (at launch
+/// or after switching).
+/// 
+/// The metadata cache is checked against a sample of files and re-indexed
+/// if it drifted from the disk; a vault edited while the app was closed
+/// would otherwise be re-read on the first view that needs the cache, and a
+/// note changed without a new modification time would stay stale. The
+/// re-index runs as a `reindex` job reporting `job-progress`, followed by
+/// `metadata-reindexed`.
+/// 
+/// The persisted search index is then loaded, so searches are answered at
+/// once, and reconciled with the disk as a `search_index` job.
+pub fn spawn_index_warm_start(app_handle: AppHandle) {
+    std::thread::spawn(move || {
+        let mut checked: Option<String> = None;
+        loop {
+            std::thread::sleep(Duration::from_secs(INTEGRITY_POLL_SECS));
+            let state = app_handle.state::<AppState>();
+            if state.lifecycle.is_suspended() {
+                continue;
+            }
+            let Ok(workspace) = state.get_workspace_path() else {
+                continue;
+            };
+            if checked.as_deref() == Some(workspace.as_str()) {
+                continue;
+            }
+            checked = Some(workspace.clone());
+            catch_up_indexes(&app_handle, &state, &workspace);
+        }
+    });
+}
+
+/// Brings the metadata cache and search index of a workspace up to date
+/// with changes made behind the app's back (while closed or suspended)
+pub(crate) fn catch_up_indexes(app_handle: &AppHandle, state: &AppState, workspace: &str) {
+    if let Err(e) = check_metadata_cache(app_handle, state, workspace) {
+        log::warn!("⚠️ Metadata cache check failed: {}", e);
+    }
+    if let Err(e) = warm_start_search(app_handle, state, workspace) {
+        log::warn!("⚠️ Search index update failed: {}", e);
+    }
+}
+
+// Helper: Samples the cache and re-indexes it as a job if it drifted
+fn check_metadata_cache(app_handle: &AppHandle, state: &AppState, workspace: &str) -> Result<(), String> {
+    let seed = epoch_ms(std::time::SystemTime::now()).unsigned_abs() as usize;
+    let report = state.metadata.check_sample(workspace, seed)?;
+    if !report.has_drift() {
+        log::info!("🩺 Metadata cache matches the disk ({} files sampled)", report.sampled);
+        return Ok(());
+    }
+    let drifted = report.stale.len() + report.content_changed.len() + report.missing.len();
+    log::info!(
+        "🩺 Metadata cache drifted ({} of {} sampled files: {} stale, {} changed content, {} missing), re-indexing",
+        drifted,
+        report.sampled,
+        report.stale.len(),
+        report.content_changed.len(),
+        report.missing.len()
+    );
+    
+    // Only hash every note if modification times proved unreliable
+    let verify_content = !report.content_changed.is_empty();
+    let job = state.jobs.start("reindex")?;
+    let mut last_emit: Option<std::time::Instant> = None;
+    let mut total = 0;
+    let result = state.metadata.reindex(workspace, verify_content, &mut |done, files, path| {
+        total = files;
+        if last_emit.map_or(true, |t| t.elapsed().as_millis() >= REINDEX_PROGRESS_INTERVAL_MS) {
+            emit_progress(app_handle, &job.progress(done as u64, Some(files as u64), Some(path.to_string())));
+            last_emit = Some(std::time::Instant::now());
+        }
+        job.checkpoint()
+    });
+    emit_progress(app_handle, &job.progress(total as u64, Some(total as u64), None).finished());
+    state.jobs.finish(&job.id)?;
+    let changed = result?;
+    
+    events::emit(app_handle, &MetadataReindexedEvent {
+        workspace: workspace.to_string(),
+        sampled: report.sampled,
+        drifted,
+        changed,
+    });
+    log::info!("🗂️ Re-indexed metadata cache: {} files changed", changed);
+    Ok(())
+}
+
+// Helper: Loads the persisted search index and reconciles it as a job
+fn warm_start_search(app_handle: &AppHandle, state: &AppState, workspace: &str) -> Result<(), String> {
+    let loaded = state.search.load(workspace)?;
+    log::info!("🔎 Search index loaded: {} notes", loaded);
+    
+    let job = state.jobs.start("search_index")?;
+    let mut last_emit: Option<std::time::Instant> = None;
+    let mut total = 0;
+    let result = state.search.reconcile(workspace, &mut |done, files, path| {
+        total = files;
+        if last_emit.map_or(true, |t| t.elapsed().as_millis() >= REINDEX_PROGRESS_INTERVAL_MS) {
+            emit_progress(app_handle, &job.progress(done as u64, Some(files as u64), Some(path.to_string())));
+            last_emit = Some(std::time::Instant::now());
+        }
+        job.checkpoint()
+    });
+    emit_progress(app_handle, &job.progress(total as u64, Some(total as u64), None).finished());
+    state.jobs.finish(&job.id)?;
+    result.map(|_| ())
+}
+
+/// Result of `search_notes`
+#[derive(Debug, Clone, Serialize)]
+pub struct SearchResults {
+    pub hits: Vec<SearchHit>,
+    /// True while the index is still being reconciled with the disk (after
+    /// startup), so recent changes may be missing
+    pub stale: bool,
+}
+
+/// Searches the full text of the workspace's notes.
+/// 
+/// Every word of `query` must match, the last one as a prefix; results are
+/// ranked by relevance (BM25, titles weighing more) with a snippet of the
+/// first matching line. Answered from the persisted index, which only
+/// re-reads notes that changed since the last search.
+/// 
+/// # Returns
+/// * `Ok(SearchResults)` - Up to `limit` (default 50) hits, best first
+#[command]
+pub async fn search_notes(
+    state: State<'_, AppState>,
+    query: String,
+    limit: Option<usize>,
+) -> Result<SearchResults, String> {
+    let workspace = state.get_workspace_path()?;
+    let root = Path::new(&workspace);
+    
+    // While the startup reconcile runs, answer from the loaded index
+    let stale = state.search.reconcile(&workspace, &mut |_, _, _| true)?.is_none();
+    let mut hits = state.search.search(&workspace, &query, limit.unwrap_or(DEFAULT_SEARCH_LIMIT))?;
+    for hit in &mut hits {
+        hit.snippet = fs::read_to_string(root.join(&hit.path))
+            .ok()
+            .and_then(|content| search_index::snippet(&content, &query));
+    }
+    
+    log::info!("🔎 Search {:?}: {} hits{}", query, hits.len(), if stale { " (index updating)" } else { "" });
+    Ok(SearchResults { hits, stale })
+}
+
+/// Summarizes the git state of the workspace for the status bar.
+/// 
+/// Returns the branch, commits ahead of and behind the upstream, and counts
+/// of changed, untracked and conflicted files, or `None` when the workspace
+/// is not in a git repository (or git is not installed). File states are
+/// listed with `include_git` in `list_workspace_files`.
+#[command]
+pub async fn get_git_summary(state: State<'_, AppState>) -> Result<Option<GitSummary>, String> {
+    let workspace = state.get_workspace_path()?;
+    Ok(git::repo_status(Path::new(&workspace)).map(|status| status.summary))
+}
+
+/// Buckets notes by creation and modification date for timeline views.
+/// 
+/// * `granularity` - `day` (default), `week`, `month` or `year`
+/// * `range` - optional `from`/`to` dates (`YYYY-MM-DD`, inclusive)
+/// 
+/// Creation dates come from the frontmatter `created` field when present,
+/// else from the filesystem. Only buckets with notes are returned, oldest
+/// first.
+#[command]
+pub async fn get_notes_timeline(
+    state: State<'_, AppState>,
+    granularity: Option<Granularity>,
+    range: Option<TimelineRange>,
+) -> Result<Vec<TimelineBucket>, String> {
+    let workspace = state.get_workspace_path()?;
+    let granularity = granularity.unwrap_or_default();
+    let range = range.unwrap_or_default();
+    
+    state.metadata.with_fresh(&workspace, |cache| build_timeline(cache, granularity, &range))?
+}
+
+/// Lists the dated events written in notes, for planner views.
+/// 
+/// * `range` - optional `from`/`to` dates (`YYYY-MM-DD`, inclusive); `from`
+///   defaults to today, `to` to 30 days after `from`
+/// 
+/// Events come from frontmatter `event:` fields and `@event(...)`
+/// annotations (see `crate::calendar`). Events still running on `from` are
+/// included; the list is ordered by start time.
+#[command]
+pub async fn get_upcoming_events(
+    state: State<'_, AppState>,
+    range: Option<TimelineRange>,
+) -> Result<Vec<CalendarEvent>, String> {
+    let workspace = state.get_workspace_path()?;
+    let range = range.unwrap_or_default();
+    let today = chrono::Local::now().date_naive();
+    
+    state.metadata.with_fresh(&workspace, |cache| calendar::upcoming(cache, &range, today))?
+}
+
+/// Returns the tracked values of a metric over time, for charts.
+/// 
+/// * `name` - metric name (case-insensitive), as in `- track: weight 82.5`
+///   or the frontmatter `metrics:` field (see `crate::metrics`)
+/// * `range` - optional `from`/`to` dates (`YYYY-MM-DD`, inclusive)
+/// 
+/// Samples are dated by their note (daily note file name, `date` field or
+/// creation date); the series is ordered oldest first.
+#[command]
+pub async fn get_metric_series(
+    state: State<'_, AppState>,
+    name: String,
+    range: Option<TimelineRange>,
+) -> Result<MetricSeries, String> {
+    let workspace = state.get_workspace_path()?;
+    let range = range.unwrap_or_default();
+    
+    state.metadata.with_fresh(&workspace, |cache| metric_series(cache, &name, &range))?
+}
+
+/// A note as listed by quick-open
+#[derive(Debug, Clone, Serialize)]
+pub struct QuickOpenEntry {
+    /// Path relative to the workspace root
+    pub path: String,
+    pub title: String,
+    /// Frontmatter aliases, matched like the title
+    pub aliases: Vec<String>,
+}
+
+/// Lists every note with its title and aliases for quick-open matching.
+/// 
+/// Computed from the metadata cache.
+#[command]
+pub async fn get_quick_open_index(state: State<'_, AppState>) -> Result<Vec<QuickOpenEntry>, String> {
+    let workspace = state.get_workspace_path()?;
+    
+    state.metadata.with_fresh(&workspace, |cache| {
+        cache.notes
+            .iter()
+            .map(|(path, note)| QuickOpenEntry {
+                path: path.clone(),
+                title: note.title.clone(),
+                aliases: frontmatter::aliases(&note.fields),
+            })
+            .collect()
+    })
+}
+
+/// Writes a vault health report (statistics, broken links, orphan notes,
+/// largest and recently modified notes) as a markdown note.
+/// 
+/// An existing report at `dest_path` is replaced, so regenerating it and
+/// committing the result tracks the vault's health over time.
+/// 
+/// Security: Validates dest_path is a markdown file within the workspace
+/// and not in a read-only or archived folder.
+/// 
+/// # Returns
+/// * `Ok(String)` - Path of the written report
+#[command]
+pub async fn generate_vault_report(
+    state: State<'_, AppState>,
+    dest_path: String,
+) -> Result<String, String> {
+    let workspace = state.get_workspace_path()?;
+    
+    let validated_path = validate_file_path(&dest_path, &workspace, &["md"])
+        .map_err(|e| format!("Security error: {}", e))?;
+    ensure_writable(&workspace, &validated_path)?;
+    
+    let root = Path::new(&workspace)
+        .canonicalize()
+        .map_err(|e| format!("Failed to resolve workspace: {}", e))?;
+    let report_key = relative_path(&root, &validated_path);
+    let now_ms = epoch_ms(std::time::SystemTime::now());
+    let report = state.metadata.with_fresh(&workspace, |cache| render_report(cache, &report_key, now_ms))?;
+    
+    let _guard = state.write_locks
+        .acquire_async(&validated_path, Duration::from_millis(DEFAULT_LOCK_WAIT_MS)).await?;
+    state.save_queue.cancel(&validated_path)?;
+    fs::write(&validated_path, report)
+        .map_err(|e| format!("Failed to write vault report: {}", e))?;
+    
+    log::info!("🩺 Vault report written: {:?}", validated_path);
+    Ok(validated_path.to_string_lossy().to_string())
+}
 
 // ============================================================================
 // TESTS
@@ -354,6 +915,18 @@ mod tests {
         assert!(path.to_string_lossy().contains("workspace-config.json"));
     }
 
+    #[test]
+    fn test_write_scratchpad_replaces_content() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join(SCRATCHPAD_FILE);
+        
+        write_scratchpad(&path, "first").unwrap();
+        write_scratchpad(&path, "second\n- idea").unwrap();
+        
+        assert_eq!(fs::read_to_string(&path).unwrap(), "second\n- idea");
+        assert!(!dir.path().join("scratchpad.md.tmp").exists());
+    }
+
     // ========================================================================
     // CREATE DIRECTORY TESTS (NEW)
     // ========================================================================
@@ -543,6 +1116,11 @@ This is your **local workspace**. All your documents are stored as `.md` files o
                     size: metadata.len(),
                     modified: "test".to_string(),
                     is_directory: metadata.is_dir(),
+                    is_symlink: false,
+                    times: FileTimes::default(),
+                    permissions: None,
+                    preview: None,
+                    git: None,
                 });
             }
         }
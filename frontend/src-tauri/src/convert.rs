@@ -0,0 +1,1217 @@
+//! Markup Converters for Importing Other Note Formats
+//!
+//! Line-based converters that turn documents from other tools into
+//! markdown. They cover the constructs that matter for migrating a note
+//! archive — headings, lists, code blocks, quotes, tables, links and inline
+//! emphasis — and pass anything else through as plain text.
+//!
+//! - `org_to_markdown` - Emacs org-mode (`.org`)
+//! - `asciidoc_to_markdown` - AsciiDoc (`.adoc`, `.asciidoc`)
+//! - `html_to_markdown` - HTML note bodies (Apple Notes, web clips)
+//!
+//! Document metadata (`#+TITLE:`, `:author:` ...) becomes frontmatter.
+
+use serde_json::{Map, Value};
+use crate::frontmatter;
+use crate::math::TEX_ENCODING;
+use crate::render::default_callout_title;
+
+// ============================================================================
+// ORG-MODE
+// ============================================================================
+
+/// Converts an org-mode document to markdown
+pub fn org_to_markdown(input: &str) -> String {
+    let mut meta = Map::new();
+    let mut out: Vec<String> = Vec::new();
+    let mut in_src = false;
+    let mut in_quote = false;
+    let mut in_drawer = false;
+
+    for line in input.lines() {
+        let trimmed = line.trim();
+        let upper = trimmed.to_uppercase();
+
+        if in_src {
+            if upper.starts_with("#+END_SRC") || upper.starts_with("#+END_EXAMPLE") {
+                out.push("```".to_string());
+                in_src = false;
+            } else {
+                out.push(line.to_string());
+            }
+            continue;
+        }
+
+        if in_drawer {
+            if upper == ":END:" {
+                in_drawer = false;
+            }
+            continue;
+        }
+
+        if upper.starts_with("#+BEGIN_SRC") {
+            let lang = trimmed.split_whitespace().nth(1).unwrap_or("");
+            out.push(format!("```{}", lang));
+            in_src = true;
+            continue;
+        }
+        if upper.starts_with("#+BEGIN_EXAMPLE") {
+            out.push("```".to_string());
+            in_src = true;
+            continue;
+        }
+        if upper.starts_with("#+BEGIN_QUOTE") {
+            in_quote = true;
+            continue;
+        }
+        if upper.starts_with("#+END_QUOTE") {
+            in_quote = false;
+            continue;
+        }
+
+        if let Some(rest) = trimmed.strip_prefix("#+") {
+            if let Some((key, value)) = rest.split_once(':') {
+                org_keyword(&mut meta, key, value.trim());
+            }
+            continue;
+        }
+
+        // Property drawers, logbooks, ...
+        if trimmed.starts_with(':') && trimmed.ends_with(':') && trimmed.len() > 2 && !trimmed.contains(' ') {
+            in_drawer = true;
+            continue;
+        }
+
+        if trimmed == "#" || trimmed.starts_with("# ") {
+            out.push(format!("<!-- {} -->", trimmed.trim_start_matches('#').trim()));
+            continue;
+        }
+
+        let converted = if let Some(heading) = org_heading(line) {
+            heading
+        } else if trimmed.starts_with('|') {
+            org_table_row(trimmed)
+        } else {
+            org_list_item(line).unwrap_or_else(|| org_inline(line))
+        };
+
+        if in_quote {
+            out.push(format!("> {}", converted).trim_end().to_string());
+        } else {
+            out.push(converted);
+        }
+    }
+
+    with_frontmatter(&meta, &out)
+}
+
+fn org_keyword(meta: &mut Map<String, Value>, key: &str, value: &str) {
+    let key = key.to_lowercase();
+    match key.as_str() {
+        "title" | "author" | "date" | "subtitle" | "description" | "email" | "language" => {
+            meta.insert(key, Value::from(value));
+        }
+        "filetags" | "tags" => {
+            let tags: Vec<Value> = value
+                .split(|c: char| c == ':' || c.is_whitespace())
+                .filter(|t| !t.is_empty())
+                .map(Value::from)
+                .collect();
+            meta.insert("tags".to_string(), Value::Array(tags));
+        }
+        _ => {}
+    }
+}
+
+/// `** TODO Heading :tag1:tag2:` → `## TODO Heading #tag1 #tag2`
+fn org_heading(line: &str) -> Option<String> {
+    let level = line.chars().take_while(|&c| c == '*').count();
+    if level == 0 || !line[level..].starts_with(' ') {
+        return None;
+    }
+
+    let mut text = line[level..].trim().to_string();
+    let mut tags = Vec::new();
+    if let Some(start) = text.rfind(" :") {
+        let candidate = &text[start + 1..];
+        if candidate.len() > 2 && candidate.ends_with(':') && !candidate.contains(' ') {
+            tags = candidate
+                .split(':')
+                .filter(|t| !t.is_empty())
+                .map(|t| format!("#{}", t))
+                .collect();
+            text.truncate(start);
+        }
+    }
+
+    let mut heading = format!("{} {}", "#".repeat(level.min(6)), org_inline(text.trim()));
+    if !tags.is_empty() {
+        heading.push(' ');
+        heading.push_str(&tags.join(" "));
+    }
+    Some(heading)
+}
+
+fn org_list_item(line: &str) -> Option<String> {
+    let indent = line.len() - line.trim_start().len();
+    let rest = line.trim_start();
+
+    if let Some(item) = rest.strip_prefix("- ").or_else(|| rest.strip_prefix("+ ")) {
+        return Some(format!("{}- {}", &line[..indent], org_inline(item)));
+    }
+
+    let digits = rest.chars().take_while(|c| c.is_ascii_digit()).count();
+    if digits > 0 {
+        let after = &rest[digits..];
+        if let Some(item) = after.strip_prefix(". ").or_else(|| after.strip_prefix(") ")) {
+            return Some(format!("{}{}. {}", &line[..indent], &rest[..digits], org_inline(item)));
+        }
+    }
+
+    None
+}
+
+/// Org separator rows use `+` between columns
+fn org_table_row(line: &str) -> String {
+    if line.starts_with("|-") {
+        line.replace('+', "|")
+    } else {
+        org_inline(line)
+    }
+}
+
+/// Converts org links and emphasis within a line
+fn org_inline(text: &str) -> String {
+    let rules = [
+        EmphasisRule { marker: '*', open: "**", close: "**", verbatim: false },
+        EmphasisRule { marker: '/', open: "*", close: "*", verbatim: false },
+        EmphasisRule { marker: '+', open: "~~", close: "~~", verbatim: false },
+        EmphasisRule { marker: '=', open: "`", close: "`", verbatim: true },
+        EmphasisRule { marker: '~', open: "`", close: "`", verbatim: true },
+    ];
+
+    let mut out = String::new();
+    let mut rest = text;
+
+    while let Some(start) = rest.find("[[") {
+        out.push_str(&convert_emphasis(&rest[..start], &rules));
+        let after = &rest[start + 2..];
+        match after.find("]]") {
+            Some(end) => {
+                out.push_str(&org_link(&after[..end]));
+                rest = &after[end + 2..];
+            }
+            None => {
+                out.push_str(&rest[start..]);
+                rest = "";
+            }
+        }
+    }
+    out.push_str(&convert_emphasis(rest, &rules));
+    out
+}
+
+/// `[[target][desc]]` / `[[target]]` → markdown or wiki link
+fn org_link(inner: &str) -> String {
+    let (target, desc) = match inner.split_once("][") {
+        Some((target, desc)) => (target, Some(desc)),
+        None => (inner, None),
+    };
+
+    if let Some(file) = target.strip_prefix("file:") {
+        let file = file.split("::").next().unwrap_or(file);
+        let md = replace_extension(file, &["org"]);
+        let label = desc.map(str::to_string).unwrap_or_else(|| file_stem(file));
+        if is_image(file) {
+            return format!("![{}]({})", label, file);
+        }
+        return format!("[{}]({})", label, md);
+    }
+
+    if target.contains("://") || target.starts_with("mailto:") {
+        return match desc {
+            Some(desc) => format!("[{}]({})", desc, target),
+            None if is_image(target) => format!("![]({})", target),
+            None => format!("<{}>", target),
+        };
+    }
+
+    // Internal links to headings (`*Heading`) or custom ids (`#id`)
+    let anchor = target.trim_start_matches(['*', '#']);
+    match desc {
+        Some(desc) => format!("[{}](#{})", desc, slugify(anchor)),
+        None => format!("[{}](#{})", anchor, slugify(anchor)),
+    }
+}
+
+// ============================================================================
+// ASCIIDOC
+// ============================================================================
+
+/// Converts an AsciiDoc document to markdown
+pub fn asciidoc_to_markdown(input: &str) -> String {
+    let mut meta = Map::new();
+    let mut out: Vec<String> = Vec::new();
+    let mut pending_lang: Option<String> = None;
+    let mut delimiter: Option<&str> = None;
+    let mut in_quote = false;
+    let mut in_comment = false;
+    let mut in_header = true;
+
+    for line in input.lines() {
+        let trimmed = line.trim_end();
+
+        if let Some(fence) = delimiter {
+            if trimmed == fence {
+                out.push("```".to_string());
+                delimiter = None;
+            } else {
+                out.push(line.to_string());
+            }
+            continue;
+        }
+
+        if trimmed == "////" {
+            in_comment = !in_comment;
+            continue;
+        }
+        if in_comment || trimmed.starts_with("//") {
+            continue;
+        }
+
+        // Document header: title followed by attribute lines
+        if in_header {
+            if let Some(title) = trimmed.strip_prefix("= ") {
+                meta.insert("title".to_string(), Value::from(title.trim()));
+                continue;
+            }
+            if let Some(rest) = trimmed.strip_prefix(':') {
+                if let Some((key, value)) = rest.split_once(':') {
+                    adoc_attribute(&mut meta, key, value.trim());
+                    continue;
+                }
+            }
+            if trimmed.is_empty() && out.is_empty() {
+                continue;
+            }
+            in_header = false;
+        }
+
+        if trimmed.starts_with("[source") || trimmed.starts_with("[,") {
+            let lang = trimmed
+                .trim_matches(['[', ']'])
+                .split(',')
+                .nth(1)
+                .unwrap_or("")
+                .trim()
+                .to_string();
+            pending_lang = Some(lang);
+            continue;
+        }
+
+        if trimmed == "----" || trimmed == "...." || trimmed == "```" {
+            let lang = pending_lang.take().unwrap_or_default();
+            out.push(format!("```{}", lang));
+            delimiter = Some(if trimmed == "----" { "----" } else if trimmed == "...." { "...." } else { "```" });
+            continue;
+        }
+        pending_lang = None;
+
+        if trimmed == "____" {
+            in_quote = !in_quote;
+            continue;
+        }
+
+        // Block attributes like [quote], [NOTE], [[anchor]]
+        if trimmed.starts_with('[') && trimmed.ends_with(']') && !trimmed.contains(' ') {
+            if let Some(id) = trimmed.strip_prefix("[[").and_then(|t| t.strip_suffix("]]")) {
+                out.push(format!("<a id=\"{}\"></a>", id));
+            }
+            continue;
+        }
+
+        let converted = if let Some(heading) = adoc_heading(trimmed) {
+            heading
+        } else if let Some(title) = trimmed.strip_prefix('.').filter(|t| t.starts_with(|c: char| c.is_alphanumeric())) {
+            format!("**{}**", adoc_inline(title))
+        } else if let Some(admonition) = adoc_admonition(trimmed) {
+            admonition
+        } else if let Some(image) = trimmed.strip_prefix("image::") {
+            adoc_macro_link(image, true).unwrap_or_else(|| trimmed.to_string())
+        } else if trimmed.starts_with("|===") {
+            continue;
+        } else if trimmed.starts_with('|') {
+            adoc_table_row(trimmed, &mut out)
+        } else {
+            adoc_list_item(trimmed).unwrap_or_else(|| adoc_inline(trimmed))
+        };
+
+        if in_quote {
+            out.push(format!("> {}", converted).trim_end().to_string());
+        } else {
+            out.push(converted);
+        }
+    }
+
+    with_frontmatter(&meta, &out)
+}
+
+fn adoc_attribute(meta: &mut Map<String, Value>, key: &str, value: &str) {
+    let key = key.trim().to_lowercase();
+    match key.as_str() {
+        "author" | "email" | "revdate" | "description" | "revnumber" => {
+            let key = if key == "revdate" { "date".to_string() } else { key };
+            meta.insert(key, Value::from(value));
+        }
+        "keywords" | "tags" => {
+            let tags: Vec<Value> = value
+                .split(',')
+                .map(str::trim)
+                .filter(|t| !t.is_empty())
+                .map(Value::from)
+                .collect();
+            meta.insert("tags".to_string(), Value::Array(tags));
+        }
+        _ => {}
+    }
+}
+
+/// `== Section` → `## Section` (`=` is the document title)
+fn adoc_heading(line: &str) -> Option<String> {
+    let level = line.chars().take_while(|&c| c == '=').count();
+    if level == 0 || !line[level..].starts_with(' ') {
+        return None;
+    }
+    Some(format!("{} {}", "#".repeat(level.min(6)), adoc_inline(line[level..].trim())))
+}
+
+fn adoc_admonition(line: &str) -> Option<String> {
+    for kind in ["NOTE", "TIP", "IMPORTANT", "WARNING", "CAUTION"] {
+        if let Some(text) = line.strip_prefix(kind).and_then(|r| r.strip_prefix(": ")) {
+            return Some(format!("> [!{}]\n> {}", kind, adoc_inline(text)));
+        }
+    }
+    None
+}
+
+/// `* a` / `** b` / `. one` / `.. two` → nested markdown list items
+fn adoc_list_item(line: &str) -> Option<String> {
+    let (marker, numbered) = match line.chars().next() {
+        Some('*') => ('*', false),
+        Some('-') => ('-', false),
+        Some('.') => ('.', true),
+        _ => return None,
+    };
+
+    let depth = line.chars().take_while(|&c| c == marker).count();
+    let item = line[depth..].strip_prefix(' ')?;
+    if marker == '-' && depth > 1 {
+        return None;
+    }
+
+    let indent = if numbered { "   " } else { "  " }.repeat(depth - 1);
+    let bullet = if numbered { "1." } else { "-" };
+    let item = match item.strip_prefix("[ ] ").map(|r| ("[ ] ", r))
+        .or_else(|| item.strip_prefix("[x] ").map(|r| ("[x] ", r)))
+        .or_else(|| item.strip_prefix("[*] ").map(|r| ("[x] ", r)))
+    {
+        Some((checkbox, rest)) => format!("{}{}", checkbox, adoc_inline(rest)),
+        None => adoc_inline(item),
+    };
+
+    Some(format!("{}{} {}", indent, bullet, item))
+}
+
+/// AsciiDoc tables have no separator row; add one after the first row
+fn adoc_table_row(line: &str, out: &mut [String]) -> String {
+    let cells: Vec<String> = line
+        .split('|')
+        .skip(1)
+        .map(|c| adoc_inline(c.trim()))
+        .collect();
+    let row = format!("| {} |", cells.join(" | "));
+
+    let previous_is_table = out.last().is_some_and(|l| l.starts_with('|'));
+    if previous_is_table {
+        row
+    } else {
+        let separator = vec!["---"; cells.len()].join(" | ");
+        format!("{}\n| {} |", row, separator)
+    }
+}
+
+/// Converts AsciiDoc links, xrefs and emphasis within a line
+fn adoc_inline(text: &str) -> String {
+    let rules = [
+        EmphasisRule { marker: '*', open: "**", close: "**", verbatim: false },
+        EmphasisRule { marker: '_', open: "*", close: "*", verbatim: false },
+        EmphasisRule { marker: '`', open: "`", close: "`", verbatim: true },
+        EmphasisRule { marker: '#', open: "==", close: "==", verbatim: false },
+    ];
+
+    let mut out = String::new();
+    let mut rest = text;
+
+    loop {
+        let next = [
+            rest.find("<<"),
+            rest.find("link:"),
+            rest.find("xref:"),
+            rest.find("image:"),
+            rest.find("http://"),
+            rest.find("https://"),
+            rest.find("mailto:"),
+        ]
+        .into_iter()
+        .flatten()
+        .min();
+
+        let Some(start) = next else { break };
+        out.push_str(&convert_emphasis(&rest[..start], &rules));
+        let candidate = &rest[start..];
+
+        if let Some(inner) = candidate.strip_prefix("<<") {
+            if let Some(end) = inner.find(">>") {
+                let (id, label) = match inner[..end].split_once(',') {
+                    Some((id, label)) => (id.trim(), label.trim()),
+                    None => (inner[..end].trim(), inner[..end].trim()),
+                };
+                out.push_str(&format!("[{}](#{})", label, id));
+                rest = &inner[end + 2..];
+                continue;
+            }
+        } else if let Some(link) = adoc_macro_at(candidate) {
+            out.push_str(&link.0);
+            rest = &candidate[link.1..];
+            continue;
+        }
+
+        // Not a link after all: emit the first character and keep scanning
+        let first_len = candidate.chars().next().map_or(1, char::len_utf8);
+        out.push_str(&candidate[..first_len]);
+        rest = &candidate[first_len..];
+    }
+
+    out.push_str(&convert_emphasis(rest, &rules));
+    out
+}
+
+/// Converts a link macro at the start of `text`; returns the markdown and bytes consumed
+fn adoc_macro_at(text: &str) -> Option<(String, usize)> {
+    let (body, prefix_len, image) = if let Some(b) = text.strip_prefix("link:") {
+        (b, 5, false)
+    } else if let Some(b) = text.strip_prefix("xref:") {
+        (b, 5, false)
+    } else if let Some(b) = text.strip_prefix("image:") {
+        (b, 6, true)
+    } else {
+        (text, 0, false)
+    };
+
+    let end = body
+        .find(|c: char| c.is_whitespace() || c == '[')
+        .unwrap_or(body.len());
+    let has_label = body[end..].starts_with('[');
+    let consumed = if has_label {
+        end + body[end..].find(']')? + 1
+    } else {
+        end
+    };
+
+    let markdown = adoc_macro_link(&body[..consumed], image)?;
+    Some((markdown, prefix_len + consumed))
+}
+
+/// `target[label]` → `[label](target)` (`![label](target)` for images)
+fn adoc_macro_link(body: &str, image: bool) -> Option<String> {
+    let (target, label) = match body.find('[') {
+        Some(open) => (&body[..open], body[open + 1..].trim_end_matches(']')),
+        None => (body, ""),
+    };
+    if target.is_empty() {
+        return None;
+    }
+
+    let label = label.split(',').next().unwrap_or("").trim_matches('"');
+    if image {
+        return Some(format!("![{}]({})", label, target));
+    }
+
+    let target = if target.contains("://") || target.starts_with("mailto:") {
+        target.to_string()
+    } else {
+        replace_extension(target, &["adoc", "asciidoc"])
+    };
+
+    if label.is_empty() {
+        if target.contains("://") {
+            Some(format!("<{}>", target))
+        } else {
+            Some(format!("[{}]({})", file_stem(&target), target))
+        }
+    } else {
+        Some(format!("[{}]({})", label, target))
+    }
+}
+
+// ============================================================================
+// HTML
+// ============================================================================
+
+/// Placeholder for one quote level of a blank line between blocks
+const SEPARATOR: char = '\u{1}';
+
+/// Converts simple HTML (as produced by note apps) to markdown
+///
+/// Handles headings, paragraphs/divs, line breaks, bold/italic/strike,
+/// inline code and `<pre>` blocks, links, images, lists (nested) and
+/// blockquotes. Callouts rendered by `crate::render` (a `data-callout`
+/// blockquote with a `callout-title` paragraph) become `> [!type] Title`
+/// again, and MathML with a TeX annotation (see `crate::math`) `$...$`. Unknown tags are dropped, keeping their text; `<script>` and
+/// `<style>` contents are removed.
+pub fn html_to_markdown(html: &str) -> String {
+    let mut out = String::new();
+    let mut lists: Vec<(bool, usize)> = Vec::new(); // (ordered, item counter)
+    let mut link_stack: Vec<String> = Vec::new();
+    let mut quote_depth = 0usize;
+    // Callout type until its title, where the title started, and whether
+    // the callout body follows the title line
+    let mut callout: Option<String> = None;
+    let mut title_start: Option<usize> = None;
+    let mut after_title = false;
+    let mut in_pre = false;
+    let mut skip_depth = 0usize;
+    let mut rest = html;
+
+    while !rest.is_empty() {
+        if let Some(comment) = rest.strip_prefix("<!--") {
+            rest = comment.find("-->").map_or("", |end| &comment[end + 3..]);
+            continue;
+        }
+
+        if rest.starts_with('<') {
+            let Some(end) = rest.find('>') else {
+                out.push_str(rest);
+                break;
+            };
+            let tag = &rest[1..end];
+            rest = &rest[end + 1..];
+
+            let closing = tag.starts_with('/');
+            let name = tag
+                .trim_start_matches('/')
+                .split(|c: char| c.is_whitespace() || c == '/')
+                .next()
+                .unwrap_or("")
+                .to_lowercase();
+
+            if matches!(name.as_str(), "script" | "style" | "head") {
+                if closing {
+                    skip_depth = skip_depth.saturating_sub(1);
+                } else {
+                    skip_depth += 1;
+                }
+                continue;
+            }
+            if skip_depth > 0 {
+                continue;
+            }
+
+            let quote_prefix = "> ".repeat(quote_depth);
+            match (name.as_str(), closing) {
+                ("h1" | "h2" | "h3" | "h4" | "h5" | "h6", false) => {
+                    let level = name[1..].parse::<usize>().unwrap_or(1);
+                    start_block(&mut out, &quote_prefix);
+                    out.push_str(&format!("{} ", "#".repeat(level)));
+                }
+                ("p", false) if callout.is_some() && has_class(tag, "callout-title") => {
+                    title_start = Some(out.len());
+                }
+                ("p", true) if title_start.is_some() => {
+                    let title = out.split_off(title_start.take().unwrap_or_default());
+                    let title = title.trim();
+                    let kind = callout.take().unwrap_or_default();
+                    out.truncate(out.trim_end().len());
+                    if !title.is_empty() && title != default_callout_title(&kind) {
+                        out.push(' ');
+                        out.push_str(title);
+                    }
+                    after_title = true;
+                }
+                ("p" | "div" | "h1" | "h2" | "h3" | "h4" | "h5" | "h6", _) => {
+                    callout = None;
+                    if std::mem::take(&mut after_title) {
+                        let trimmed = out.trim_end_matches([' ', '>', '\n']).len();
+                        out.truncate(trimmed);
+                        out.push('\n');
+                        out.push_str(&quote_prefix);
+                    } else {
+                        start_block(&mut out, &quote_prefix);
+                    }
+                }
+                ("br", _) => {
+                    out.push('\n');
+                    out.push_str(&quote_prefix);
+                }
+                ("b" | "strong", _) => out.push_str("**"),
+                ("i" | "em", _) => out.push('*'),
+                ("s" | "strike" | "del", _) => out.push_str("~~"),
+                ("code", _) if !in_pre => out.push('`'),
+                ("pre", false) => {
+                    start_block(&mut out, &quote_prefix);
+                    out.push_str("```\n");
+                    in_pre = true;
+                }
+                ("pre", true) => {
+                    if !out.ends_with('\n') {
+                        out.push('\n');
+                    }
+                    out.push_str("```\n");
+                    in_pre = false;
+                }
+                ("blockquote", false) => {
+                    quote_depth += 1;
+                    start_block(&mut out, &"> ".repeat(quote_depth));
+                    if let Some(kind) = attribute(tag, "data-callout") {
+                        out.push_str(&format!("[!{}]", kind));
+                        out.push_str(&attribute(tag, "data-callout-fold").unwrap_or_default());
+                        callout = Some(kind);
+                    }
+                }
+                ("blockquote", true) => {
+                    quote_depth = quote_depth.saturating_sub(1);
+                    start_block(&mut out, &"> ".repeat(quote_depth));
+                }
+                ("ul" | "ol", false) => {
+                    if lists.is_empty() {
+                        start_block(&mut out, &quote_prefix);
+                    }
+                    lists.push((name == "ol", 0));
+                }
+                ("ul" | "ol", true) => {
+                    lists.pop();
+                    if lists.is_empty() {
+                        start_block(&mut out, &quote_prefix);
+                    }
+                }
+                ("li", false) => {
+                    if !out.is_empty() && !out.ends_with('\n') {
+                        out.push('\n');
+                    }
+                    let depth = lists.len().saturating_sub(1);
+                    out.push_str(&quote_prefix);
+                    out.push_str(&"  ".repeat(depth));
+                    match lists.last_mut() {
+                        Some((true, counter)) => {
+                            *counter += 1;
+                            out.push_str(&format!("{}. ", counter));
+                        }
+                        _ => out.push_str("- "),
+                    }
+                }
+                ("a", false) => {
+                    link_stack.push(attribute(tag, "href").unwrap_or_default());
+                    out.push('[');
+                }
+                ("a", true) => {
+                    let href = link_stack.pop().unwrap_or_default();
+                    out.push_str(&format!("]({})", href));
+                }
+                ("img", _) => {
+                    let src = attribute(tag, "src").unwrap_or_default();
+                    let alt = attribute(tag, "alt").unwrap_or_default();
+                    out.push_str(&format!("![{}]({})", alt, src));
+                }
+                ("math", false) => {
+                    let end = rest.to_lowercase().find("</math").unwrap_or(rest.len());
+                    let tex = math_source(&rest[..end]);
+                    rest = &rest[end..];
+                    if attribute(tag, "display").as_deref() == Some("block") {
+                        start_block(&mut out, &quote_prefix);
+                        let separator = format!("\n{}", quote_prefix);
+                        out.push_str(&format!("$${}{}{}$$", separator, tex.replace('\n', &separator), separator));
+                        start_block(&mut out, &quote_prefix);
+                    } else {
+                        out.push_str(&format!("${}$", tex));
+                    }
+                }
+                ("hr", _) => {
+                    start_block(&mut out, &quote_prefix);
+                    out.push_str("---");
+                    start_block(&mut out, &quote_prefix);
+                }
+                _ => {}
+            }
+            continue;
+        }
+
+        let next_tag = rest.find("<!--").into_iter().chain(rest.find('<')).min().unwrap_or(rest.len());
+        let next_tag = if next_tag == 0 { rest.len() } else { next_tag };
+        let text = &rest[..next_tag];
+        rest = &rest[next_tag..];
+
+        if skip_depth > 0 {
+            continue;
+        }
+
+        let text = decode_entities(text);
+        if in_pre {
+            out.push_str(&text);
+        } else {
+            // Collapse whitespace like a browser would
+            let collapsed = text.split_whitespace().collect::<Vec<_>>().join(" ");
+            if collapsed.is_empty() {
+                if text.chars().any(char::is_whitespace) && !out.ends_with([' ', '\n']) && !out.is_empty() {
+                    out.push(' ');
+                }
+                continue;
+            }
+            if text.starts_with(char::is_whitespace) && !out.ends_with([' ', '\n']) && !out.is_empty() {
+                out.push(' ');
+            }
+            out.push_str(&collapsed);
+            if text.ends_with(char::is_whitespace) {
+                out.push(' ');
+            }
+        }
+    }
+
+    let mut lines: Vec<String> = out
+        .lines()
+        .map(|line| match line.len() {
+            n if n > 0 && line.chars().all(|c| c == SEPARATOR) => "> ".repeat(n).trim_end().to_string(),
+            _ => line.trim_end().to_string(),
+        })
+        .collect();
+    lines.dedup_by(|a, b| a.is_empty() && b.is_empty());
+    let mut result = lines.join("\n").trim().to_string();
+    result.push('\n');
+    result
+}
+
+/// Ends the current line and leaves one blank line before the next block
+///
+/// The blank line is written as `SEPARATOR`s, one per quote level it stays
+/// in: the levels of both the block above and the next one, so paragraphs
+/// of one quote stay together while separate quotes stay apart.
+fn start_block(out: &mut String, quote_prefix: &str) {
+    let trimmed = out.trim_end_matches([' ', '>']);
+    if trimmed.is_empty() {
+        out.clear();
+        out.push_str(quote_prefix);
+        return;
+    }
+    out.truncate(trimmed.len());
+
+    let mut depth = quote_prefix.matches('>').count();
+    // A blank line left by the previous block is reused
+    if let Some(body) = out.strip_suffix('\n') {
+        let start = body.rfind('\n').map_or(0, |i| i + 1);
+        if body[start..].chars().all(|c| c == SEPARATOR) {
+            depth = depth.min(body.len() - start);
+            out.truncate(start);
+        }
+    }
+    let content = out.trim_end_matches('\n');
+    let line = &content[content.rfind('\n').map_or(0, |i| i + 1)..];
+    let content_depth = line.chars().take_while(|&c| c == '>' || c == ' ').filter(|&c| c == '>').count();
+
+    if !out.ends_with('\n') {
+        out.push('\n');
+    }
+    out.extend(std::iter::repeat(SEPARATOR).take(depth.min(content_depth)));
+    out.push('\n');
+    out.push_str(quote_prefix);
+}
+
+/// TeX of a MathML element: its TeX annotation, else its text
+fn math_source(mathml: &str) -> String {
+    let lower = mathml.to_lowercase();
+    let annotation = lower.find(TEX_ENCODING).and_then(|at| {
+        let start = at + lower[at..].find('>')? + 1;
+        let end = start + lower[start..].find("</annotation")?;
+        Some(&mathml[start..end])
+    });
+    let source = match annotation {
+        Some(tex) => tex.to_string(),
+        None => {
+            let mut text = String::new();
+            let mut rest = mathml;
+            while let Some(open) = rest.find('<') {
+                text.push_str(&rest[..open]);
+                rest = rest[open..].find('>').map_or("", |close| &rest[open + close + 1..]);
+            }
+            text.push_str(rest);
+            text
+        }
+    };
+    decode_entities(source.trim())
+}
+
+/// Whether a tag's `class` attribute lists `class`
+fn has_class(tag: &str, class: &str) -> bool {
+    attribute(tag, "class").is_some_and(|classes| classes.split_whitespace().any(|c| c == class))
+}
+
+/// Reads `name="value"` (or single-quoted) from a tag's attribute list
+pub(crate) fn attribute(tag: &str, name: &str) -> Option<String> {
+    let lower = tag.to_lowercase();
+    let mut search = 0;
+    while let Some(found) = lower[search..].find(name) {
+        let start = search + found;
+        let preceded = start == 0 || lower.as_bytes()[start - 1].is_ascii_whitespace();
+        let after = tag[start + name.len()..].trim_start();
+        if let (true, Some(value)) = (preceded, after.strip_prefix('=')) {
+            let value = value.trim_start();
+            let quote = value.chars().next()?;
+            let value = if quote == '"' || quote == '\'' {
+                let inner = &value[1..];
+                &inner[..inner.find(quote)?]
+            } else {
+                value.split_whitespace().next()?
+            };
+            return Some(decode_entities(value));
+        }
+        search = start + name.len();
+    }
+    None
+}
+
+fn decode_entities(text: &str) -> String {
+    if !text.contains('&') {
+        return text.to_string();
+    }
+
+    let mut out = String::new();
+    let mut rest = text;
+    while let Some(start) = rest.find('&') {
+        out.push_str(&rest[..start]);
+        let candidate = &rest[start..];
+        let decoded = candidate.find(';').filter(|&end| end <= 10).and_then(|end| {
+            let entity = &candidate[1..end];
+            let c = match entity {
+                "amp" => Some('&'),
+                "lt" => Some('<'),
+                "gt" => Some('>'),
+                "quot" => Some('"'),
+                "apos" => Some('\''),
+                "nbsp" => Some(' '),
+                _ => entity
+                    .strip_prefix("#x")
+                    .or_else(|| entity.strip_prefix("#X"))
+                    .and_then(|hex| u32::from_str_radix(hex, 16).ok())
+                    .or_else(|| entity.strip_prefix('#').and_then(|dec| dec.parse().ok()))
+                    .and_then(char::from_u32),
+            };
+            c.map(|c| (c, end))
+        });
+
+        match decoded {
+            Some((c, end)) => {
+                out.push(c);
+                rest = &candidate[end + 1..];
+            }
+            None => {
+                out.push('&');
+                rest = &candidate[1..];
+            }
+        }
+    }
+    out.push_str(rest);
+    out
+}
+
+// ============================================================================
+// SHARED HELPERS
+// ============================================================================
+
+/// An inline emphasis marker and its markdown replacement
+struct EmphasisRule {
+    marker: char,
+    open: &'static str,
+    close: &'static str,
+    /// Contents are copied verbatim (code)
+    verbatim: bool,
+}
+
+/// Replaces `<m>text<m>` spans following the usual emphasis boundary rules:
+/// the opening marker follows whitespace/punctuation and precedes a
+/// non-space, the closing marker is the mirror image.
+fn convert_emphasis(text: &str, rules: &[EmphasisRule]) -> String {
+    let chars: Vec<char> = text.chars().collect();
+    let mut out = String::new();
+    let mut i = 0;
+
+    while i < chars.len() {
+        let c = chars[i];
+        let rule = rules.iter().find(|r| r.marker == c);
+        let opens = i == 0 || is_boundary(chars[i - 1]);
+
+        if let (Some(rule), true) = (rule, opens) {
+            if let Some(close) = find_closing(&chars, i, rule.marker) {
+                let inner: String = chars[i + 1..close].iter().collect();
+                let inner = if rule.verbatim { inner } else { convert_emphasis(&inner, rules) };
+                out.push_str(rule.open);
+                out.push_str(&inner);
+                out.push_str(rule.close);
+                i = close + 1;
+                continue;
+            }
+        }
+
+        out.push(c);
+        i += 1;
+    }
+
+    out
+}
+
+fn find_closing(chars: &[char], open: usize, marker: char) -> Option<usize> {
+    let first = *chars.get(open + 1)?;
+    if first.is_whitespace() || first == marker {
+        return None;
+    }
+
+    (open + 1..chars.len()).find(|&j| {
+        chars[j] == marker
+            && j > open + 1
+            && !chars[j - 1].is_whitespace()
+            && chars.get(j + 1).map_or(true, |&next| is_boundary(next))
+    })
+}
+
+fn is_boundary(c: char) -> bool {
+    c.is_whitespace() || "([{\"',.;:!?)]}-".contains(c)
+}
+
+fn with_frontmatter(meta: &Map<String, Value>, lines: &[String]) -> String {
+    let mut body = lines.join("\n");
+    // Collapse runs of blank lines left by dropped directives
+    while body.contains("\n\n\n") {
+        body = body.replace("\n\n\n", "\n\n");
+    }
+    let body = body.trim_matches('\n');
+
+    let mut out = frontmatter::render(meta);
+    if let Some(title) = meta.get("title").and_then(Value::as_str) {
+        let first_line = body.lines().next().unwrap_or("");
+        if first_line.trim() != format!("# {}", title) {
+            out.push_str(&format!("# {}\n\n", title));
+        }
+    }
+    out.push_str(body);
+    out.push('\n');
+    out
+}
+
+fn replace_extension(path: &str, from: &[&str]) -> String {
+    let (base, anchor) = match path.split_once('#') {
+        Some((base, anchor)) => (base, Some(anchor)),
+        None => (path, None),
+    };
+    let base = match base.rsplit_once('.') {
+        Some((stem, ext)) if from.iter().any(|f| f.eq_ignore_ascii_case(ext)) => format!("{}.md", stem),
+        _ => base.to_string(),
+    };
+    match anchor {
+        Some(anchor) => format!("{}#{}", base, anchor),
+        None => base,
+    }
+}
+
+fn file_stem(path: &str) -> String {
+    std::path::Path::new(path)
+        .file_stem()
+        .map(|s| s.to_string_lossy().to_string())
+        .unwrap_or_else(|| path.to_string())
+}
+
+fn is_image(path: &str) -> bool {
+    let lower = path.to_lowercase();
+    [".png", ".jpg", ".jpeg", ".gif", ".svg", ".webp"]
+        .iter()
+        .any(|ext| lower.ends_with(ext))
+}
+
+/// GitHub-style heading anchor
+fn slugify(text: &str) -> String {
+    text.trim()
+        .to_lowercase()
+        .chars()
+        .filter_map(|c| match c {
+            c if c.is_alphanumeric() || c == '-' || c == '_' => Some(c),
+            ' ' => Some('-'),
+            _ => None,
+        })
+        .collect()
+}
+
+// ============================================================================
+// TESTS
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_org_document() {
+        let org = "\
+#+TITLE: Garden
+#+FILETAGS: :home:plants:
+* TODO Water plants :weekly:
+:PROPERTIES:
+:ID: 1234
+:END:
+Use *rain* water, /not/ =tap= water.
+** Links
+- See [[https://example.com][the site]]
+- And [[file:soil.org][soil notes]]
+  1. nested
+#+BEGIN_SRC python
+print(\"*not bold*\")
+#+END_SRC
+| a | b |
+|---+---|
+| 1 | 2 |
+";
+        let md = org_to_markdown(org);
+        let expected = "\
+---
+tags: [home, plants]
+title: Garden
+---
+# Garden
+
+# TODO Water plants #weekly
+Use **rain** water, *not* `tap` water.
+## Links
+- See [the site](https://example.com)
+- And [soil notes](soil.md)
+  1. nested
+```python
+print(\"*not bold*\")
+```
+| a | b |
+|---|---|
+| 1 | 2 |
+";
+        assert_eq!(md, expected);
+    }
+
+    #[test]
+    fn test_org_emphasis_boundaries() {
+        assert_eq!(org_inline("a/b/c and 2*3*4"), "a/b/c and 2*3*4", "Markers inside words are literal");
+        assert_eq!(org_inline("+gone+ and ~code~"), "~~gone~~ and `code`");
+    }
+
+    #[test]
+    fn test_org_quote_and_comment() {
+        let md = org_to_markdown("#+BEGIN_QUOTE\nWise words\n#+END_QUOTE\n# private\n");
+        assert_eq!(md, "> Wise words\n<!-- private -->\n");
+    }
+
+    #[test]
+    fn test_asciidoc_document() {
+        let adoc = "\
+= User Guide
+:author: Ann
+:keywords: docs, guide
+
+== Install
+NOTE: Needs *admin* rights.
+
+. Download from https://example.com[the site]
+.. Unpack
+* See xref:setup.adoc[setup] and <<usage,Usage>>
+
+[source,rust]
+----
+let _x = *y*;
+----
+
+image::img/logo.png[Logo]
+";
+        let md = asciidoc_to_markdown(adoc);
+        let expected = "\
+---
+author: Ann
+tags: [docs, guide]
+title: User Guide
+---
+# User Guide
+
+## Install
+> [!NOTE]
+> Needs **admin** rights.
+
+1. Download from [the site](https://example.com)
+   1. Unpack
+- See [setup](setup.md) and [Usage](#usage)
+
+```rust
+let _x = *y*;
+```
+
+![Logo](img/logo.png)
+";
+        assert_eq!(md, expected);
+    }
+
+    #[test]
+    fn test_asciidoc_table_and_emphasis() {
+        let md = asciidoc_to_markdown("|===\n|Name |Qty\n|_Apple_ |3\n|===\n");
+        assert_eq!(md, "| Name | Qty |\n| --- | --- |\n| *Apple* | 3 |\n");
+    }
+
+    #[test]
+    fn test_html_to_markdown() {
+        let html = "<div><h1>Trip</h1></div><div>Pack <b>light</b> &amp; <i>early</i>.<br></div>\
+<ul><li>Tent</li><li>Stove<ul><li>Gas</li></ul></li></ul>\
+<ol><li>Book <a href=\"https://example.com/?a=1&amp;b=2\">train</a></li></ol>\
+<pre>let x = 1;\n</pre><blockquote>Travel far</blockquote><script>alert(1)</script>";
+        let expected = "\
+# Trip
+
+Pack **light** & *early*.
+
+- Tent
+- Stove
+  - Gas
+
+1. Book [train](https://example.com/?a=1&b=2)
+
+```
+let x = 1;
+```
+
+> Travel far
+";
+        assert_eq!(html_to_markdown(html), expected);
+    }
+
+    #[test]
+    fn test_html_to_markdown_callouts() {
+        let markdown = "> [!tip]- Use *this*\n> Body text\n>\n> More\n\n> [!warning]\n> Careful\n";
+        let html = crate::render::render_markdown(markdown, "", &crate::links::NoteLookup::default(), false);
+        assert_eq!(html_to_markdown(&html), markdown);
+
+        let quote = "<blockquote><p>One</p><p>Two</p></blockquote><p>After</p>";
+        assert_eq!(html_to_markdown(quote), "> One\n>\n> Two\n\nAfter\n");
+    }
+
+    #[test]
+    fn test_html_to_markdown_math() {
+        let markdown = "Energy $E = mc^2$ holds.\n\n> $$\n> \\sum_{k=1}^n k < n^2\n> $$\n";
+        let html = crate::render::render_markdown(markdown, "", &crate::links::NoteLookup::default(), false);
+        assert_eq!(html_to_markdown(&html), markdown);
+
+        let plain = "<p><math><mi>x</mi><mo>&lt;</mo><mn>1</mn></math></p>";
+        assert_eq!(html_to_markdown(plain), "$x<1$\n");
+    }
+
+    #[test]
+    fn test_decode_entities() {
+        assert_eq!(decode_entities("a &lt;b&gt; &#233;&#x41; & c"), "a <b> éA & c");
+    }
+}
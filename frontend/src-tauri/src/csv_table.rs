@@ -0,0 +1,567 @@
+//! CSV/TSV to Markdown Table Conversion
+//!
+//! Small, dependency-free CSV reader (RFC 4180 style quoting) plus a
+//! renderer producing aligned GitHub-flavored markdown tables.
+//!
+//! - Delimiter (`,` `\t` `;` `|`) and quote character (`"` `'`) are
+//!   detected from the first lines unless given explicitly
+//! - Quoted fields may contain delimiters, doubled quotes and newlines
+//! - Numeric columns are right-aligned
+//! - Rows can be written back out with `write_csv_row`
+//! - Very wide tables can be split into several tables of at most
+//!   `max_columns` columns; the first column is repeated in each chunk so
+//!   rows stay identifiable
+
+use serde::{Deserialize, Serialize};
+use crate::links;
+
+/// Candidate delimiters, in order of preference on ties
+const DELIMITERS: [char; 4] = [',', '\t', ';', '|'];
+
+/// Number of lines inspected for delimiter/quote detection
+const SAMPLE_LINES: usize = 20;
+
+/// Options for converting CSV text into a markdown table
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CsvTableOptions {
+    /// Field delimiter; detected if not set
+    #[serde(default)]
+    pub delimiter: Option<char>,
+    /// Quote character; detected if not set
+    #[serde(default)]
+    pub quote: Option<char>,
+    /// Whether the first row holds column names
+    #[serde(default = "default_true")]
+    pub has_header: bool,
+    /// Split tables wider than this into several tables
+    #[serde(default)]
+    pub max_columns: Option<usize>,
+}
+
+fn default_true() -> bool {
+    true
+}
+
+impl Default for CsvTableOptions {
+    fn default() -> Self {
+        Self {
+            delimiter: None,
+            quote: None,
+            has_header: true,
+            max_columns: None,
+        }
+    }
+}
+
+/// Converts CSV/TSV text into one or more markdown tables
+///
+/// # Returns
+/// * `Ok(String)` - Markdown (tables separated by a blank line)
+/// * `Err(String)` - If the input has no rows
+pub fn csv_to_markdown(input: &str, options: &CsvTableOptions) -> Result<String, String> {
+    let input = input.trim_start_matches('\u{feff}');
+    let delimiter = options.delimiter.unwrap_or_else(|| detect_delimiter(input));
+    let quote = options.quote.unwrap_or_else(|| detect_quote(input, delimiter));
+
+    let mut rows = parse_csv(input, delimiter, quote);
+    rows.retain(|row| row.iter().any(|cell| !cell.trim().is_empty()));
+
+    if rows.is_empty() {
+        return Err("CSV contains no rows".to_string());
+    }
+
+    let width = rows.iter().map(|r| r.len()).max().unwrap_or(0);
+    let header: Vec<String> = if options.has_header {
+        let mut header = rows.remove(0);
+        for (i, name) in header.iter_mut().enumerate() {
+            if name.trim().is_empty() {
+                *name = format!("Column {}", i + 1);
+            }
+        }
+        header.extend((header.len()..width).map(|i| format!("Column {}", i + 1)));
+        header
+    } else {
+        (1..=width).map(|i| format!("Column {}", i)).collect()
+    };
+
+    for row in rows.iter_mut() {
+        row.resize(width, String::new());
+    }
+
+    let chunks = column_chunks(width, options.max_columns);
+    let tables: Vec<String> = chunks
+        .iter()
+        .map(|columns| render_table(&header, &rows, columns))
+        .collect();
+
+    Ok(tables.join("\n\n"))
+}
+
+/// Picks the delimiter producing the most consistent field count
+pub fn detect_delimiter(input: &str) -> char {
+    let sample: Vec<&str> = input.lines().filter(|l| !l.trim().is_empty()).take(SAMPLE_LINES).collect();
+
+    let mut best = (',', 0usize, 0usize); // (delimiter, consistent lines, fields)
+    for &delimiter in &DELIMITERS {
+        let counts: Vec<usize> = sample.iter().map(|line| count_fields(line, delimiter)).collect();
+        let Some(&first) = counts.first() else { continue };
+        if first < 2 {
+            continue;
+        }
+        let consistent = counts.iter().filter(|&&c| c == first).count();
+        if consistent > best.1 || (consistent == best.1 && first > best.2) {
+            best = (delimiter, consistent, first);
+        }
+    }
+
+    best.0
+}
+
+/// Picks `'` only if fields clearly use single quotes, otherwise `"`
+fn detect_quote(input: &str, delimiter: char) -> char {
+    let (mut double, mut single) = (0, 0);
+    for line in input.lines().take(SAMPLE_LINES) {
+        for field in line.split(delimiter) {
+            match field.trim_start().chars().next() {
+                Some('"') => double += 1,
+                Some('\'') => single += 1,
+                _ => {}
+            }
+        }
+    }
+    if single > double { '\'' } else { '"' }
+}
+
+/// Counts fields on one line, ignoring delimiters inside double quotes
+fn count_fields(line: &str, delimiter: char) -> usize {
+    let mut in_quotes = false;
+    let mut count = 1;
+    for c in line.chars() {
+        if c == '"' {
+            in_quotes = !in_quotes;
+        } else if c == delimiter && !in_quotes {
+            count += 1;
+        }
+    }
+    count
+}
+
+/// Parses CSV text into rows of fields
+pub fn parse_csv(input: &str, delimiter: char, quote: char) -> Vec<Vec<String>> {
+    let mut rows = Vec::new();
+    let mut row = Vec::new();
+    let mut field = String::new();
+    let mut in_quotes = false;
+    let mut chars = input.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        if in_quotes {
+            if c == quote {
+                if chars.peek() == Some(&quote) {
+                    field.push(quote);
+                    chars.next();
+                } else {
+                    in_quotes = false;
+                }
+            } else {
+                field.push(c);
+            }
+        } else if c == quote && field.trim().is_empty() {
+            field.clear();
+            in_quotes = true;
+        } else if c == delimiter {
+            row.push(std::mem::take(&mut field));
+        } else if c == '\n' || c == '\r' {
+            if c == '\r' && chars.peek() == Some(&'\n') {
+                chars.next();
+            }
+            row.push(std::mem::take(&mut field));
+            rows.push(std::mem::take(&mut row));
+        } else {
+            field.push(c);
+        }
+    }
+
+    if !field.is_empty() || !row.is_empty() {
+        row.push(field);
+        rows.push(row);
+    }
+
+    rows
+}
+
+/// Formats one CSV line (with trailing newline), quoting fields as needed
+pub fn write_csv_row<S: AsRef<str>>(fields: &[S], delimiter: char) -> String {
+    let cells: Vec<String> = fields
+        .iter()
+        .map(|field| {
+            let field = field.as_ref();
+            if field.contains(delimiter) || field.contains('"') || field.contains('\n') || field.contains('\r') {
+                format!("\"{}\"", field.replace('"', "\"\""))
+            } else {
+                field.to_string()
+            }
+        })
+        .collect();
+    format!("{}\n", cells.join(&delimiter.to_string()))
+}
+
+/// Splits column indices into chunks of at most `max_columns`
+///
+/// Every chunk after the first starts with column 0 again.
+fn column_chunks(width: usize, max_columns: Option<usize>) -> Vec<Vec<usize>> {
+    match max_columns {
+        Some(max) if max >= 2 && width > max => {
+            let mut chunks = vec![(0..max).collect::<Vec<_>>()];
+            let mut next = max;
+            while next < width {
+                let end = (next + max - 1).min(width);
+                let mut chunk = vec![0];
+                chunk.extend(next..end);
+                chunks.push(chunk);
+                next = end;
+            }
+            chunks
+        }
+        _ => vec![(0..width).collect()],
+    }
+}
+
+/// Renders selected columns as an aligned markdown table
+fn render_table(header: &[String], rows: &[Vec<String>], columns: &[usize]) -> String {
+    let header: Vec<String> = columns.iter().map(|&c| escape_cell(&header[c])).collect();
+    let body: Vec<Vec<String>> = rows
+        .iter()
+        .map(|row| columns.iter().map(|&c| escape_cell(&row[c])).collect())
+        .collect();
+
+    let numeric: Vec<bool> = (0..columns.len())
+        .map(|i| {
+            let mut values = body.iter().map(|r| r[i].as_str()).filter(|v| !v.is_empty()).peekable();
+            values.peek().is_some() && values.all(is_numeric)
+        })
+        .collect();
+
+    let widths: Vec<usize> = (0..columns.len())
+        .map(|i| {
+            body.iter()
+                .map(|r| r[i].chars().count())
+                .chain(std::iter::once(header[i].chars().count()))
+                .max()
+                .unwrap_or(0)
+                .max(3)
+        })
+        .collect();
+
+    let format_row = |cells: &[String]| -> String {
+        let padded: Vec<String> = cells
+            .iter()
+            .enumerate()
+            .map(|(i, cell)| {
+                let pad = widths[i] - cell.chars().count();
+                if numeric[i] {
+                    format!("{}{}", " ".repeat(pad), cell)
+                } else {
+                    format!("{}{}", cell, " ".repeat(pad))
+                }
+            })
+            .collect();
+        format!("| {} |", padded.join(" | "))
+    };
+
+    let separator: Vec<String> = widths
+        .iter()
+        .zip(&numeric)
+        .map(|(&w, &num)| if num { format!("{}:", "-".repeat(w - 1)) } else { "-".repeat(w) })
+        .collect();
+
+    let mut lines = vec![format_row(&header), format!("| {} |", separator.join(" | "))];
+    lines.extend(body.iter().map(|r| format_row(r)));
+    lines.join("\n")
+}
+
+/// Makes a field safe inside a markdown table cell
+fn escape_cell(value: &str) -> String {
+    value
+        .trim()
+        .replace('|', "\\|")
+        .replace("\r\n", "<br>")
+        .replace('\n', "<br>")
+}
+
+fn is_numeric(value: &str) -> bool {
+    let cleaned: String = value.chars().filter(|c| !matches!(c, ',' | '_' | '%' | '$' | '€')).collect();
+    !cleaned.is_empty() && cleaned.parse::<f64>().is_ok()
+}
+
+// ============================================================================
+// CSV-BACKED TABLES
+// ============================================================================
+
+/// Info string of a fenced block that shows a CSV file as a table:
+///
+/// ````markdown
+/// ```csvtable path=data/budget.csv
+/// ```
+/// ````
+///
+/// `path` is relative to the note's folder, or to the workspace root when
+/// it starts with `/`. Optional `header=false`, `delimiter=;` (or `tab`)
+/// and `columns=N` work like `CsvTableOptions`. The block body is ignored.
+pub const CSV_BLOCK: &str = "csvtable";
+
+/// Replaces the `csvtable` blocks of note `from` with markdown tables
+///
+/// `read` loads a file by its workspace-relative path. Blocks whose file
+/// is missing or has no rows are left as written.
+pub fn expand_csv_blocks(content: &str, from: &str, read: &dyn Fn(&str) -> Option<String>) -> String {
+    if !content.contains(CSV_BLOCK) {
+        return content.to_string();
+    }
+
+    let lines: Vec<&str> = content.split('\n').collect();
+    let mut out: Vec<String> = Vec::new();
+    let mut fence: Option<&str> = None;
+    let mut i = 0;
+    while i < lines.len() {
+        let line = lines[i];
+        let trimmed = line.trim_start();
+        let Some(marker) = fence_marker(trimmed) else {
+            out.push(line.to_string());
+            i += 1;
+            continue;
+        };
+        if let Some(open) = fence {
+            if marker.starts_with(open) {
+                fence = None;
+            }
+            out.push(line.to_string());
+            i += 1;
+            continue;
+        }
+
+        let close = lines[i + 1..].iter().position(|l| l.trim_start().starts_with(marker)).map(|k| i + 1 + k);
+        let table = close.and_then(|_| csv_block_table(trimmed.trim_start_matches(marker), from, read));
+        match (close, table) {
+            (Some(close), Some(table)) => {
+                let indent = &line[..line.len() - trimmed.len()];
+                out.extend(table.split('\n').map(|row| format!("{}{}", indent, row)));
+                i = close + 1;
+            }
+            _ => {
+                fence = Some(marker);
+                out.push(line.to_string());
+                i += 1;
+            }
+        }
+    }
+
+    out.join("\n")
+}
+
+// Helper: The run of backticks or tildes opening or closing a fence
+fn fence_marker(trimmed: &str) -> Option<&str> {
+    let first = trimmed.chars().next().filter(|c| matches!(c, '`' | '~'))?;
+    let len = trimmed.len() - trimmed.trim_start_matches(first).len();
+    (len >= 3).then(|| &trimmed[..len])
+}
+
+// Helper: The table of a `csvtable` info string, if its file can be read
+fn csv_block_table(info: &str, from: &str, read: &dyn Fn(&str) -> Option<String>) -> Option<String> {
+    let attributes = info.trim().strip_prefix(CSV_BLOCK)?;
+    if !attributes.is_empty() && !attributes.starts_with(char::is_whitespace) {
+        return None;
+    }
+
+    let mut path = None;
+    let mut options = CsvTableOptions::default();
+    for (key, value) in parse_attributes(attributes) {
+        match key.as_str() {
+            "path" | "src" => path = Some(value),
+            "header" => options.has_header = value != "false" && value != "no",
+            "delimiter" => options.delimiter = if value == "tab" { Some('\t') } else { value.chars().next() },
+            "columns" => options.max_columns = value.parse().ok(),
+            _ => {}
+        }
+    }
+
+    let relative = links::join_relative(from, &path?);
+    let csv = read(&relative)?;
+    csv_to_markdown(&csv, &options).ok()
+}
+
+// Helper: `key=value` pairs; values may be quoted to hold spaces
+fn parse_attributes(text: &str) -> Vec<(String, String)> {
+    let mut attributes = Vec::new();
+    let mut rest = text.trim_start();
+    while let Some(eq) = rest.find('=') {
+        let key = rest[..eq].trim().to_lowercase();
+        let after = &rest[eq + 1..];
+        let (value, remaining) = match after.chars().next() {
+            Some(q @ ('"' | '\'')) => match after[1..].find(q) {
+                Some(end) => (&after[1..1 + end], &after[2 + end..]),
+                None => (&after[1..], ""),
+            },
+            _ => after.split_once(char::is_whitespace).unwrap_or((after, "")),
+        };
+        attributes.push((key, value.to_string()));
+        rest = remaining.trim_start();
+    }
+    attributes
+}
+
+/// Sets one cell of a CSV file, keeping its delimiter and line endings
+///
+/// # Arguments
+/// * `row` - Row of the file, 0 being the header (blank lines not counted);
+///   one past the last row appends a row
+/// * `column` - Column, 0-based, within the widest row
+///
+/// # Returns
+/// * `Err(String)` - If the row or column is out of range
+pub fn update_csv_cell(input: &str, row: usize, column: usize, value: &str) -> Result<String, String> {
+    let bom = if input.starts_with('\u{feff}') { "\u{feff}" } else { "" };
+    let input = input.trim_start_matches('\u{feff}');
+    let delimiter = detect_delimiter(input);
+    let quote = detect_quote(input, delimiter);
+    let mut rows = parse_csv(input, delimiter, quote);
+
+    let is_blank = |fields: &[String]| fields.iter().all(|cell| cell.trim().is_empty());
+    let records: Vec<usize> = (0..rows.len()).filter(|&i| !is_blank(&rows[i])).collect();
+    let width = rows.iter().map(|r| r.len()).max().unwrap_or(0).max(1);
+    if column >= width {
+        return Err(format!("Column {} not found ({} columns)", column + 1, width));
+    }
+    let index = match records.get(row) {
+        Some(&index) => index,
+        None if row == records.len() => {
+            rows.push(vec![String::new(); width]);
+            rows.len() - 1
+        }
+        None => return Err(format!("Row {} not found ({} rows)", row + 1, records.len())),
+    };
+
+    let fields = &mut rows[index];
+    if fields.len() <= column {
+        fields.resize(column + 1, String::new());
+    }
+    fields[column] = value.to_string();
+
+    let newline = if input.contains("\r\n") { "\r\n" } else { "\n" };
+    let mut output = bom.to_string();
+    for fields in &rows {
+        output.push_str(write_csv_row(fields, delimiter).trim_end_matches('\n'));
+        output.push_str(newline);
+    }
+    Ok(output)
+}
+
+// ============================================================================
+// TESTS
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_detect_delimiter() {
+        assert_eq!(detect_delimiter("a,b,c\n1,2,3"), ',');
+        assert_eq!(detect_delimiter("a\tb\tc\n1\t2\t3"), '\t');
+        assert_eq!(detect_delimiter("a;b;c\n1,5;2,5;3"), ';');
+        assert_eq!(detect_delimiter("\"x, y\",b\n1,2"), ',', "Quoted commas must not count");
+    }
+
+    #[test]
+    fn test_parse_quoted_fields() {
+        let rows = parse_csv("name,note\n\"Smith, J\",\"said \"\"hi\"\"\"\n", ',', '"');
+        assert_eq!(rows, vec![
+            vec!["name".to_string(), "note".to_string()],
+            vec!["Smith, J".to_string(), "said \"hi\"".to_string()],
+        ]);
+    }
+
+    #[test]
+    fn test_parse_multiline_field() {
+        let rows = parse_csv("a,b\r\n\"line1\nline2\",x\r\n", ',', '"');
+        assert_eq!(rows.len(), 2);
+        assert_eq!(rows[1][0], "line1\nline2");
+    }
+
+    #[test]
+    fn test_write_csv_row_roundtrip() {
+        let line = write_csv_row(&["plain", "a,b", "say \"hi\""], ',');
+        assert_eq!(line, "plain,\"a,b\",\"say \"\"hi\"\"\"\n");
+        assert_eq!(parse_csv(&line, ',', '"')[0], vec!["plain", "a,b", "say \"hi\""]);
+    }
+
+    #[test]
+    fn test_csv_to_markdown_aligned() {
+        let md = csv_to_markdown("Item,Qty\nApple,3\nBanana,12", &CsvTableOptions::default()).unwrap();
+        let expected = "\
+| Item   | Qty |
+| ------ | --: |
+| Apple  |   3 |
+| Banana |  12 |";
+        assert_eq!(md, expected);
+    }
+
+    #[test]
+    fn test_csv_to_markdown_escapes_cells() {
+        let md = csv_to_markdown("a,b\n\"x|y\",\"1\n2\"", &CsvTableOptions::default()).unwrap();
+        assert!(md.contains("x\\|y"), "Pipes must be escaped");
+        assert!(md.contains("1<br>2"), "Newlines become <br>");
+    }
+
+    #[test]
+    fn test_csv_without_header_and_ragged_rows() {
+        let options = CsvTableOptions { has_header: false, ..Default::default() };
+        let md = csv_to_markdown("1,2\n3,4,5", &options).unwrap();
+        assert!(md.starts_with("| Column 1 | Column 2 | Column 3 |"));
+    }
+
+    #[test]
+    fn test_wide_table_is_chunked() {
+        let options = CsvTableOptions { max_columns: Some(3), ..Default::default() };
+        let md = csv_to_markdown("id,a,b,c,d\n1,2,3,4,5", &options).unwrap();
+        let tables: Vec<&str> = md.split("\n\n").collect();
+
+        assert_eq!(tables.len(), 2);
+        assert!(tables[0].starts_with("|  id |   a |   b |"));
+        assert!(tables[1].starts_with("|  id |   c |   d |"), "Chunks repeat the first column");
+    }
+
+    #[test]
+    fn test_empty_csv_is_rejected() {
+        assert!(csv_to_markdown("\n\n", &CsvTableOptions::default()).is_err());
+    }
+
+    #[test]
+    fn test_expand_csv_blocks() {
+        let read = |path: &str| match path {
+            "notes/data/budget.csv" => Some("Item,Cost\nRent,900\n".to_string()),
+            "shared/codes.csv" => Some("A;B\n1;2\n".to_string()),
+            _ => None,
+        };
+        let note = "# Budget\n\n```csvtable path=data/budget.csv\n```\n\n- list\n  ~~~csvtable path=\"/shared/codes.csv\" header=false\n  ~~~\n\n```csvtable path=missing.csv\n```\n\n````\n```csvtable path=data/budget.csv\n```\n````";
+        let expanded = expand_csv_blocks(note, "notes/budget.md", &read);
+
+        assert!(expanded.contains("# Budget\n\n| Item | Cost |\n| ---- | ---: |\n| Rent |  900 |\n\n- list"));
+        assert!(expanded.contains("  | Column 1 | Column 2 |\n  | -------- | -------- |\n  | A        | B        |"));
+        assert!(expanded.contains("```csvtable path=missing.csv\n```"), "Missing files stay as written");
+        assert!(expanded.ends_with("````\n```csvtable path=data/budget.csv\n```\n````"), "Blocks inside code stay");
+    }
+
+    #[test]
+    fn test_update_csv_cell() {
+        let csv = "Item;Note\r\nRent;\"a;b\"\r\n\r\nFood;x\r\n";
+        let updated = update_csv_cell(csv, 2, 1, "say \"hi\"").unwrap();
+        assert_eq!(updated, "Item;Note\r\nRent;\"a;b\"\r\n\r\nFood;\"say \"\"hi\"\"\"\r\n");
+
+        let appended = update_csv_cell("a,b\n1,2\n", 2, 1, "4").unwrap();
+        assert_eq!(appended, "a,b\n1,2\n,4\n");
+
+        assert!(update_csv_cell("a,b\n1,2\n", 5, 0, "x").unwrap_err().contains("Row 6"));
+        assert!(update_csv_cell("a,b\n1,2\n", 0, 2, "x").unwrap_err().contains("Column 3"));
+    }
+}
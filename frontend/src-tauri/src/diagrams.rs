@@ -0,0 +1,454 @@
+//! Diagram Blocks
+//!
+//! Finds the diagram code blocks of a note (` ```mermaid `, ` ```plantuml `)
+//! and caches their rendered SVG, so previews and exports only render a
+//! diagram again when its source changed:
+//!
+//! - Every block is keyed by the hash of its language and source (see
+//!   `diagram_hash`), and gets an id derived from it that survives edits
+//!   elsewhere in the note
+//! - Rendered diagrams are kept in `.mdreader/diagrams/<hash>.svg`; the
+//!   renderer marks diagram blocks with `data-diagram="<hash>"` and
+//!   `inline_cached` swaps in the cached SVG as an image
+//! - Mermaid diagrams are rendered by the preview's Mermaid runtime, which
+//!   stores its SVG here; PlantUML diagrams are rendered by a PlantUML
+//!   server or a local `plantuml.jar`
+//!
+//! ## Trust
+//! Which server sees diagram sources and which jar runs is chosen per
+//! machine in `<config>/plantuml.json`, never by the workspace, so a synced
+//! folder cannot send notes elsewhere or run code.
+
+use std::collections::HashMap;
+use std::fs;
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::sync::mpsc;
+use std::thread;
+use std::time::{Duration, Instant};
+use serde::{Deserialize, Serialize};
+use crate::frontmatter;
+use crate::utils::base64_encode;
+use crate::workspace_settings::SETTINGS_DIR;
+
+/// Folder of cached SVGs inside the settings directory
+pub const CACHE_DIR: &str = "diagrams";
+
+/// File name of the PlantUML setting inside the config directory
+pub const PLANTUML_FILE: &str = "plantuml.json";
+
+/// Cached diagrams kept per workspace; the least recently used go first
+const MAX_CACHED: usize = 500;
+
+/// Largest SVG accepted into the cache
+pub const MAX_SVG_BYTES: usize = 5 * 1024 * 1024;
+
+/// Timeout of one PlantUML rendering
+const RENDER_TIMEOUT_SECS: u64 = 30;
+
+const POLL_INTERVAL: Duration = Duration::from_millis(20);
+
+const USER_AGENT: &str = concat!("MDReader/", env!("CARGO_PKG_VERSION"), " (diagrams)");
+
+/// Diagram language of a code block
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DiagramLanguage {
+    Mermaid,
+    PlantUml,
+}
+
+impl DiagramLanguage {
+    /// Language of a fence's info string (`mermaid`, `plantuml`, `puml`)
+    pub fn from_info(info: &str) -> Option<Self> {
+        match info.split_whitespace().next()?.to_lowercase().as_str() {
+            "mermaid" => Some(DiagramLanguage::Mermaid),
+            "plantuml" | "puml" => Some(DiagramLanguage::PlantUml),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            DiagramLanguage::Mermaid => "mermaid",
+            DiagramLanguage::PlantUml => "plantuml",
+        }
+    }
+}
+
+/// A diagram code block of a note
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct DiagramBlock {
+    /// `<language>-<hash prefix>`, numbered when a note repeats a diagram
+    pub id: String,
+    pub language: DiagramLanguage,
+    /// Cache key (see `diagram_hash`)
+    pub hash: String,
+    /// Line of the opening fence (1-based)
+    pub line: usize,
+    pub source: String,
+    /// Whether a rendered SVG is cached
+    pub cached: bool,
+}
+
+/// How PlantUML diagrams are rendered on this machine
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct PlantUmlConfig {
+    /// PlantUML server, e.g. `https://www.plantuml.com/plantuml`
+    #[serde(default)]
+    pub server: Option<String>,
+    /// Local `plantuml.jar`, used instead of the server when set
+    #[serde(default)]
+    pub jar: Option<String>,
+    /// Java runtime for the jar (default: `java` on the `PATH`)
+    #[serde(default)]
+    pub java: Option<String>,
+}
+
+impl PlantUmlConfig {
+    /// Loads the setting from `config_dir` (the default if missing)
+    pub fn load(config_dir: &Path) -> Result<Self, String> {
+        match fs::read_to_string(config_dir.join(PLANTUML_FILE)) {
+            Ok(json) => serde_json::from_str(&json)
+                .map_err(|e| format!("Failed to parse PlantUML settings: {}", e)),
+            Err(_) => Ok(Self::default()),
+        }
+    }
+
+    /// Saves the setting to `config_dir`
+    pub fn save(&self, config_dir: &Path) -> Result<(), String> {
+        let json = serde_json::to_string_pretty(self)
+            .map_err(|e| format!("Failed to serialize PlantUML settings: {}", e))?;
+        fs::write(config_dir.join(PLANTUML_FILE), json)
+            .map_err(|e| format!("Failed to save PlantUML settings: {}", e))
+    }
+
+    /// Checks the server is an `http(s)` URL and the jar a `.jar` file
+    pub fn validate(&self) -> Result<(), String> {
+        if let Some(server) = &self.server {
+            if !(server.starts_with("https://") || server.starts_with("http://")) {
+                return Err(format!("PlantUML server must be an http(s) URL: {}", server));
+            }
+        }
+        if let Some(jar) = &self.jar {
+            let path = Path::new(jar);
+            if path.extension().and_then(|e| e.to_str()).map(str::to_lowercase).as_deref() != Some("jar") {
+                return Err(format!("Not a jar file: {}", jar));
+            }
+            if !path.is_file() {
+                return Err(format!("PlantUML jar not found: {}", jar));
+            }
+        }
+        Ok(())
+    }
+
+    /// Renders PlantUML source to SVG with the jar, else the server
+    pub fn render(&self, source: &str) -> Result<String, String> {
+        let svg = match (&self.jar, &self.server) {
+            (Some(jar), _) => render_with_jar(self.java.as_deref().unwrap_or("java"), jar, source)?,
+            (None, Some(server)) => render_with_server(server, source)?,
+            (None, None) => return Err("PlantUML is not set up: choose a server or a plantuml.jar".to_string()),
+        };
+        if !svg.contains("<svg") {
+            return Err("PlantUML did not return an SVG".to_string());
+        }
+        Ok(svg)
+    }
+}
+
+/// Cache key of a diagram: blake3 of its language and source, with line
+/// ends, trailing spaces and tabs normalized so the preview, the renderer
+/// and `extract_diagrams` agree
+pub fn diagram_hash(language: DiagramLanguage, source: &str) -> String {
+    let source = source
+        .replace('\t', "    ")
+        .lines()
+        .map(str::trim_end)
+        .collect::<Vec<_>>()
+        .join("\n");
+    let key = format!("{}\n{}", language.as_str(), source.trim_matches('\n'));
+    blake3::hash(key.as_bytes()).to_hex().to_string()
+}
+
+/// Lists the diagram blocks of a note (frontmatter excluded)
+///
+/// Fences may be indented (inside list items); their lines are dedented
+/// by the fence's indentation. `cached` is left `false`.
+pub fn extract_diagrams(content: &str) -> Vec<DiagramBlock> {
+    let (_, body) = frontmatter::split(content);
+    let first_line = content[..content.len() - body.len()].lines().count() + 1;
+
+    let mut blocks = Vec::new();
+    let mut seen: HashMap<String, usize> = HashMap::new();
+    let mut lines = body.lines().enumerate();
+    while let Some((i, line)) = lines.next() {
+        let trimmed = line.trim_start();
+        let indent = line.len() - trimmed.len();
+        let Some(marker) = ["```", "~~~"].into_iter().find(|m| trimmed.starts_with(m)) else {
+            continue;
+        };
+        let fence_len = trimmed.len() - trimmed.trim_start_matches(marker.as_bytes()[0] as char).len();
+        let fence = &trimmed[..fence_len];
+        let language = DiagramLanguage::from_info(&trimmed[fence_len..]);
+
+        let mut source = String::new();
+        for (_, line) in lines.by_ref() {
+            let inner = line.trim_start();
+            if inner.starts_with(fence) && inner.trim_start_matches(fence.as_bytes()[0] as char).trim().is_empty() {
+                break;
+            }
+            let dedent = (line.len() - inner.len()).min(indent);
+            source.push_str(&line[dedent..]);
+            source.push('\n');
+        }
+
+        let Some(language) = language else {
+            continue;
+        };
+        let hash = diagram_hash(language, &source);
+        let count = seen.entry(hash.clone()).or_insert(0);
+        *count += 1;
+        let mut id = format!("{}-{}", language.as_str(), &hash[..12]);
+        if *count > 1 {
+            id = format!("{}-{}", id, count);
+        }
+        blocks.push(DiagramBlock { id, language, hash, line: first_line + i, source, cached: false });
+    }
+    blocks
+}
+
+/// Whether `hash` is a well-formed cache key (it names a cache file)
+pub fn is_valid_hash(hash: &str) -> bool {
+    hash.len() == 64 && hash.bytes().all(|b| b.is_ascii_digit() || (b'a'..=b'f').contains(&b))
+}
+
+/// Cached SVG of a diagram
+pub fn cached_svg(workspace_root: &Path, hash: &str) -> Option<String> {
+    if !is_valid_hash(hash) {
+        return None;
+    }
+    let path = cache_path(workspace_root, hash);
+    let svg = fs::read_to_string(&path).ok()?;
+    // Touch the file so pruning keeps recently used diagrams
+    let _ = fs::File::options().append(true).open(&path).and_then(|file| file.set_modified(std::time::SystemTime::now()));
+    Some(svg)
+}
+
+/// Stores the SVG of a diagram, pruning the least recently used ones
+/// beyond `MAX_CACHED`
+pub fn store_svg(workspace_root: &Path, hash: &str, svg: &str) -> Result<(), String> {
+    if !is_valid_hash(hash) {
+        return Err(format!("Invalid diagram hash: {}", hash));
+    }
+    if svg.len() > MAX_SVG_BYTES || !svg.contains("<svg") {
+        return Err("Not an SVG diagram, or too large".to_string());
+    }
+    let dir = workspace_root.join(SETTINGS_DIR).join(CACHE_DIR);
+    fs::create_dir_all(&dir)
+        .map_err(|e| format!("Failed to create diagram cache: {}", e))?;
+    fs::write(cache_path(workspace_root, hash), svg)
+        .map_err(|e| format!("Failed to cache diagram: {}", e))?;
+
+    let mut files: Vec<(std::time::SystemTime, PathBuf)> = fs::read_dir(&dir)
+        .map_err(|e| format!("Failed to read diagram cache: {}", e))?
+        .flatten()
+        .filter(|entry| entry.path().extension().is_some_and(|e| e == "svg"))
+        .filter_map(|entry| Some((entry.metadata().ok()?.modified().ok()?, entry.path())))
+        .collect();
+    if files.len() > MAX_CACHED {
+        files.sort();
+        for (_, path) in &files[..files.len() - MAX_CACHED] {
+            let _ = fs::remove_file(path);
+        }
+    }
+    Ok(())
+}
+
+/// Replaces the diagram blocks of rendered HTML (`<pre ... data-diagram=
+/// "<hash>">`) that have a cached SVG with `<figure class="diagram">`
+///
+/// The SVG is embedded as an `<img>` data URI, never as markup: the cache
+/// is filled by the webview and synced with the workspace, and SVG in an
+/// image cannot run scripts or load anything.
+pub fn inline_cached(html: &str, workspace_root: &Path) -> String {
+    const MARK: &str = " data-diagram=\"";
+    let mut out = String::with_capacity(html.len());
+    let mut rest = html;
+    while let Some(mark) = rest.find(MARK) {
+        let Some(open) = rest[..mark].rfind("<pre") else {
+            out.push_str(&rest[..mark + MARK.len()]);
+            rest = &rest[mark + MARK.len()..];
+            continue;
+        };
+        let hash_start = mark + MARK.len();
+        let hash = rest[hash_start..].split('"').next().unwrap_or("");
+        let close = rest[hash_start..].find("</pre>").map(|end| hash_start + end + "</pre>".len());
+        match (close, cached_svg(workspace_root, hash)) {
+            (Some(close), Some(svg)) => {
+                out.push_str(&rest[..open]);
+                let source = base64_encode(svg_element(&svg).as_bytes());
+                out.push_str(&format!(
+                    "<figure class=\"diagram\" data-diagram=\"{}\"><img alt=\"\" src=\"data:image/svg+xml;base64,{}\"></figure>",
+                    hash, source
+                ));
+                rest = &rest[close..];
+            }
+            _ => {
+                out.push_str(&rest[..hash_start]);
+                rest = &rest[hash_start..];
+            }
+        }
+    }
+    out.push_str(rest);
+    out
+}
+
+fn cache_path(workspace_root: &Path, hash: &str) -> PathBuf {
+    workspace_root.join(SETTINGS_DIR).join(CACHE_DIR).join(format!("{}.svg", hash))
+}
+
+// Helper: The `<svg>` element of an SVG file (without XML prolog or doctype)
+fn svg_element(svg: &str) -> &str {
+    let start = svg.find("<svg").unwrap_or(0);
+    let end = svg.rfind("</svg>").map_or(svg.len(), |end| end + "</svg>".len());
+    &svg[start..end.max(start)]
+}
+
+// Helper: Renders through a PlantUML server (`/svg/~h<hex>`: hex-encoded
+// source, which every server version understands)
+fn render_with_server(server: &str, source: &str) -> Result<String, String> {
+    let hex: String = source.bytes().map(|b| format!("{:02x}", b)).collect();
+    let url = format!("{}/svg/~h{}", server.trim_end_matches('/'), hex);
+    let agent = ureq::AgentBuilder::new()
+        .timeout(Duration::from_secs(RENDER_TIMEOUT_SECS))
+        .user_agent(USER_AGENT)
+        .build();
+    let response = match agent.get(&url).call() {
+        Ok(response) => response,
+        // Syntax errors come back as 400 with the error drawn as an SVG
+        Err(ureq::Error::Status(400, response)) => response,
+        Err(ureq::Error::Status(status, _)) => return Err(format!("Failed to render diagram: HTTP {}", status)),
+        Err(ureq::Error::Transport(e)) => return Err(format!("Failed to reach PlantUML server: {}", e)),
+    };
+    let mut svg = String::new();
+    response
+        .into_reader()
+        .take(MAX_SVG_BYTES as u64)
+        .read_to_string(&mut svg)
+        .map_err(|e| format!("Failed to read diagram: {}", e))?;
+    Ok(svg)
+}
+
+// Helper: Renders with `java -jar plantuml.jar -pipe`, killed after
+// `RENDER_TIMEOUT_SECS`
+fn render_with_jar(java: &str, jar: &str, source: &str) -> Result<String, String> {
+    let mut command = Command::new(java);
+    command
+        .args(["-Djava.awt.headless=true", "-jar", jar, "-tsvg", "-pipe", "-charset", "UTF-8"])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null());
+    #[cfg(windows)]
+    {
+        // CREATE_NO_WINDOW: no console window flashing up
+        use std::os::windows::process::CommandExt;
+        command.creation_flags(0x0800_0000);
+    }
+    let mut child = command
+        .spawn()
+        .map_err(|e| format!("Failed to run PlantUML: {}", e))?;
+
+    if let Some(mut input) = child.stdin.take() {
+        let source = source.to_string();
+        thread::spawn(move || {
+            let _ = input.write_all(source.as_bytes());
+        });
+    }
+    let (tx, output) = mpsc::channel();
+    if let Some(stdout) = child.stdout.take() {
+        thread::spawn(move || {
+            let mut svg = String::new();
+            let _ = stdout.take(MAX_SVG_BYTES as u64).read_to_string(&mut svg);
+            let _ = tx.send(svg);
+        });
+    }
+
+    let deadline = Instant::now() + Duration::from_secs(RENDER_TIMEOUT_SECS);
+    loop {
+        match child.try_wait() {
+            Ok(Some(_)) => break,
+            Ok(None) if Instant::now() >= deadline => {
+                let _ = child.kill();
+                let _ = child.wait();
+                return Err(format!("PlantUML did not finish within {}s", RENDER_TIMEOUT_SECS));
+            }
+            Ok(None) => thread::sleep(POLL_INTERVAL),
+            Err(e) => return Err(format!("Failed to wait for PlantUML: {}", e)),
+        }
+    }
+    // PlantUML exits non-zero on syntax errors but still draws the error
+    output
+        .recv_timeout(Duration::from_secs(1))
+        .map_err(|_| "PlantUML returned no output".to_string())
+}
+
+// ============================================================================
+// TESTS
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_extract_diagrams() {
+        let content = "---\ntitle: Flows\n---\n# Flows\n\n```mermaid\ngraph TD\n  A --> B\n```\n\n\
+                       - Step\n  ~~~puml\n  Alice -> Bob\n  ~~~\n\n```rust\nfn main() {}\n```\n\n\
+                       ````mermaid\ngraph TD  \n  A --> B\n````\n";
+        let blocks = extract_diagrams(content);
+        assert_eq!(blocks.len(), 3);
+        assert_eq!(blocks[0].language, DiagramLanguage::Mermaid);
+        assert_eq!(blocks[0].line, 6);
+        assert_eq!(blocks[0].source, "graph TD\n  A --> B\n");
+        assert_eq!(blocks[1].language, DiagramLanguage::PlantUml);
+        assert_eq!(blocks[1].source, "Alice -> Bob\n");
+        // Trailing spaces do not change the hash; repeats get numbered ids
+        assert_eq!(blocks[2].hash, blocks[0].hash);
+        assert_eq!(blocks[2].id, format!("{}-2", blocks[0].id));
+        assert!(blocks[0].id.starts_with("mermaid-") && blocks[0].id.len() == "mermaid-".len() + 12);
+    }
+
+    #[test]
+    fn test_cache_and_inline() {
+        let dir = TempDir::new().unwrap();
+        let hash = diagram_hash(DiagramLanguage::Mermaid, "graph TD\n  A --> B\n");
+        assert!(cached_svg(dir.path(), &hash).is_none());
+        assert!(store_svg(dir.path(), "../escape", "<svg></svg>").is_err());
+        assert!(store_svg(dir.path(), &hash, "not svg").is_err());
+        store_svg(dir.path(), &hash, "<?xml version=\"1.0\"?>\n<svg id=\"d\"></svg>\n").unwrap();
+
+        let html = format!(
+            "<p>x</p>\n<pre class=\"mermaid\" data-diagram=\"{}\">graph TD\n</pre>\n\
+             <pre class=\"mermaid\" data-diagram=\"{}\">other</pre>\n",
+            hash,
+            "0".repeat(64)
+        );
+        assert_eq!(
+            inline_cached(&html, dir.path()),
+            format!(
+                "<p>x</p>\n<figure class=\"diagram\" data-diagram=\"{}\"><img alt=\"\" src=\"data:image/svg+xml;base64,{}\"></figure>\n\
+                 <pre class=\"mermaid\" data-diagram=\"{}\">other</pre>\n",
+                hash,
+                base64_encode(b"<svg id=\"d\"></svg>"),
+                "0".repeat(64)
+            )
+        );
+
+        // Scripts in a cached SVG never reach the page as markup
+        store_svg(dir.path(), &hash, "<svg onload=\"alert(1)\"><script>alert(2)</script></svg>").unwrap();
+        let inlined = inline_cached(&html, dir.path());
+        assert!(!inlined.contains("<svg") && !inlined.contains("<script") && !inlined.contains("onload"));
+    }
+}
@@ -0,0 +1,156 @@
+//! Native Dialog Runner
+//!
+//! Native dialogs block until the user closes them. Run directly inside an
+//! async command they hold up a runtime worker (and on some platforms the
+//! event loop), so a dialog that never returns would hang the IPC layer.
+//!
+//! Dialogs are therefore awaited through a `DialogRunner`: the dialog
+//! future runs on its own task, and the command waits for its result on the
+//! blocking pool, giving up after `DIALOG_TIMEOUT_SECS` or when
+//! `cancel_dialog` is called. A timed-out or cancelled dialog may stay on
+//! screen until the user closes it; its result is then ignored.
+//!
+//! Only one dialog is open at a time.
+
+use std::future::Future;
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError, Sender};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+/// How long a command waits for the user to close a dialog
+pub const DIALOG_TIMEOUT_SECS: u64 = 300;
+
+type CancelFn = Box<dyn Fn() + Send>;
+
+/// The open dialog, if any (internally synchronized)
+#[derive(Clone, Default)]
+pub struct DialogRunner {
+    cancel: Arc<Mutex<Option<CancelFn>>>,
+}
+
+/// A dialog that was opened and whose result has not been received yet
+pub struct PendingDialog<T> {
+    sender: Sender<Option<T>>,
+    receiver: Receiver<Option<T>>,
+    cancel: Arc<Mutex<Option<CancelFn>>>,
+}
+
+impl<T> PendingDialog<T> {
+    /// Sender for the dialog's result
+    pub fn sender(&self) -> Sender<Option<T>> {
+        self.sender.clone()
+    }
+
+    /// Waits (blocking) for the result, a cancel or the timeout
+    pub fn wait(self, timeout: Duration) -> Result<Option<T>, String> {
+        match self.receiver.recv_timeout(timeout) {
+            Ok(result) => Ok(result),
+            Err(RecvTimeoutError::Timeout) => {
+                log::warn!("⏱️ Dialog not closed after {:?}, giving up", timeout);
+                Err("Dialog timed out".to_string())
+            }
+            Err(RecvTimeoutError::Disconnected) => Ok(None),
+        }
+    }
+}
+
+impl<T> Drop for PendingDialog<T> {
+    fn drop(&mut self) {
+        if let Ok(mut cancel) = self.cancel.lock() {
+            cancel.take();
+        }
+    }
+}
+
+impl DialogRunner {
+    /// Creates a runner with no dialog open
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Registers a new dialog, failing if another one is open
+    pub fn begin<T: Send + 'static>(&self) -> Result<PendingDialog<T>, String> {
+        let (sender, receiver) = mpsc::channel();
+        let mut cancel = self.cancel
+            .lock()
+            .map_err(|e| format!("Failed to lock dialogs: {}", e))?;
+        if cancel.is_some() {
+            return Err("Another dialog is already open".to_string());
+        }
+
+        let cancel_sender = sender.clone();
+        *cancel = Some(Box::new(move || {
+            let _ = cancel_sender.send(None);
+        }));
+        Ok(PendingDialog { sender, receiver, cancel: self.cancel.clone() })
+    }
+
+    /// Shows a dialog and waits for it off the async runtime
+    ///
+    /// Resolves to `None` if the dialog was cancelled by the user or by
+    /// `cancel`, and fails if it is still open after `timeout`.
+    pub async fn show<T, F>(&self, dialog: F, timeout: Duration) -> Result<Option<T>, String>
+    where
+        T: Send + 'static,
+        F: Future<Output = Option<T>> + Send + 'static,
+    {
+        let pending = self.begin()?;
+        let sender = pending.sender();
+        tauri::async_runtime::spawn(async move {
+            let _ = sender.send(dialog.await);
+        });
+
+        tauri::async_runtime::spawn_blocking(move || pending.wait(timeout))
+            .await
+            .map_err(|e| format!("Failed to wait for dialog: {}", e))?
+    }
+
+    /// Stops waiting for the open dialog; returns false if none is open
+    pub fn cancel(&self) -> bool {
+        match self.cancel.lock() {
+            Ok(cancel) => match cancel.as_ref() {
+                Some(cancel) => {
+                    cancel();
+                    true
+                }
+                None => false,
+            },
+            Err(_) => false,
+        }
+    }
+}
+
+// ============================================================================
+// TESTS
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_one_dialog_at_a_time() {
+        let runner = DialogRunner::new();
+
+        let first = runner.begin::<String>().unwrap();
+        assert!(runner.begin::<String>().is_err());
+
+        first.sender().send(Some("/notes".to_string())).unwrap();
+        assert_eq!(first.wait(Duration::from_secs(1)).unwrap(), Some("/notes".to_string()));
+        assert!(runner.begin::<String>().is_ok(), "finished dialog frees the slot");
+    }
+
+    #[test]
+    fn test_cancel_and_timeout() {
+        let runner = DialogRunner::new();
+        assert!(!runner.cancel());
+
+        let pending = runner.begin::<String>().unwrap();
+        assert!(runner.cancel());
+        assert_eq!(pending.wait(Duration::from_secs(1)).unwrap(), None);
+
+        let pending = runner.begin::<String>().unwrap();
+        let err = pending.wait(Duration::from_millis(10)).unwrap_err();
+        assert_eq!(err, "Dialog timed out");
+    }
+}
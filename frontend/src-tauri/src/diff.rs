@@ -0,0 +1,433 @@
+//! Line Diffs
+//!
+//! Computes line-based differences between two versions of a note (Myers'
+//! algorithm) and presents them either as a unified diff, the format of
+//! `diff -u` and `git diff`:
+//!
+//! ```text
+//! --- a/plan.md
+//! +++ b/plan.md
+//! @@ -2,3 +2,3 @@
+//!  context
+//! -old line
+//! +new line
+//! ```
+//!
+//! or as side-by-side rows, where a changed line can be refined into the
+//! words that changed.
+
+use serde::{Deserialize, Serialize};
+
+/// Unchanged lines shown around each change
+pub const DEFAULT_CONTEXT: usize = 3;
+
+/// A unified diff with its line counts
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct TextDiff {
+    /// Unified diff text (empty if both versions are equal)
+    pub unified: String,
+    /// Lines only in the new version
+    pub added: usize,
+    /// Lines only in the old version
+    pub removed: usize,
+}
+
+/// How a diff is presented
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DiffMode {
+    #[default]
+    Unified,
+    SideBySide,
+}
+
+/// A diff in one of the `DiffMode`s
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "mode", rename_all = "snake_case")]
+pub enum Diff {
+    Unified(TextDiff),
+    SideBySide {
+        rows: Vec<DiffRow>,
+        added: usize,
+        removed: usize,
+    },
+}
+
+/// What a side-by-side row shows
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RowKind {
+    Equal,
+    /// A removed line paired with the line that replaced it
+    Changed,
+    Removed,
+    Added,
+}
+
+/// One row of a side-by-side diff
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DiffRow {
+    pub kind: RowKind,
+    /// Line of the old version (`None` for added lines)
+    pub left: Option<DiffLine>,
+    /// Line of the new version (`None` for removed lines)
+    pub right: Option<DiffLine>,
+}
+
+/// A line of one side of a side-by-side diff
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DiffLine {
+    /// 1-based line number
+    pub number: usize,
+    /// Line text without its line break
+    pub text: String,
+    /// The line split into changed and unchanged parts (word-level
+    /// refinement of changed rows; empty otherwise)
+    #[serde(default)]
+    pub segments: Vec<Segment>,
+}
+
+/// Part of a line that did or did not change
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Segment {
+    pub text: String,
+    pub changed: bool,
+}
+
+/// One step of an edit script
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Edit {
+    Keep,
+    Delete,
+    Insert,
+}
+
+/// Diffs two texts line by line as a unified diff
+///
+/// # Arguments
+/// * `old` / `new` - The two versions
+/// * `labels` - File names shown in the `---` and `+++` headers
+/// * `context` - Unchanged lines shown around each change
+pub fn unified_diff(old: &str, new: &str, labels: (&str, &str), context: usize) -> TextDiff {
+    let a: Vec<&str> = old.split_inclusive('\n').collect();
+    let b: Vec<&str> = new.split_inclusive('\n').collect();
+    let edits = edit_script(&a, &b);
+
+    let added = edits.iter().filter(|e| **e == Edit::Insert).count();
+    let removed = edits.iter().filter(|e| **e == Edit::Delete).count();
+    if added == 0 && removed == 0 {
+        return TextDiff::default();
+    }
+
+    // Lines of each version consumed before every edit
+    let mut positions = Vec::with_capacity(edits.len() + 1);
+    let (mut x, mut y) = (0, 0);
+    for edit in &edits {
+        positions.push((x, y));
+        match edit {
+            Edit::Keep => (x, y) = (x + 1, y + 1),
+            Edit::Delete => x += 1,
+            Edit::Insert => y += 1,
+        }
+    }
+    positions.push((x, y));
+
+    let mut unified = format!("--- a/{}\n+++ b/{}\n", labels.0, labels.1);
+    for (start, end) in hunks(&edits, context) {
+        let old_count = edits[start..end].iter().filter(|e| **e != Edit::Insert).count();
+        let new_count = edits[start..end].iter().filter(|e| **e != Edit::Delete).count();
+        let (x, y) = positions[start];
+        unified.push_str(&format!(
+            "@@ -{} +{} @@\n",
+            hunk_range(x, old_count),
+            hunk_range(y, new_count)
+        ));
+
+        for (edit, &(x, y)) in edits[start..end].iter().zip(&positions[start..end]) {
+            let (prefix, line) = match edit {
+                Edit::Keep => (' ', a[x]),
+                Edit::Delete => ('-', a[x]),
+                Edit::Insert => ('+', b[y]),
+            };
+            unified.push(prefix);
+            unified.push_str(line);
+            if !line.ends_with('\n') {
+                unified.push_str("\n\\ No newline at end of file\n");
+            }
+        }
+    }
+
+    TextDiff { unified, added, removed }
+}
+
+/// Diffs two texts line by line as side-by-side rows
+///
+/// Removed lines directly followed by added lines are paired into
+/// `Changed` rows; with `words`, their segments mark the changed words.
+///
+/// # Returns
+/// * `(rows, added, removed)` - Rows and the added and removed line counts
+pub fn side_by_side(old: &str, new: &str, words: bool) -> (Vec<DiffRow>, usize, usize) {
+    let a: Vec<&str> = old.lines().collect();
+    let b: Vec<&str> = new.lines().collect();
+    let edits = edit_script(&a, &b);
+    let added = edits.iter().filter(|e| **e == Edit::Insert).count();
+    let removed = edits.iter().filter(|e| **e == Edit::Delete).count();
+
+    let line = |number: usize, text: &str| DiffLine { number: number + 1, text: text.to_string(), segments: Vec::new() };
+    let mut rows = Vec::new();
+    let (mut x, mut y) = (0, 0);
+    let mut i = 0;
+    while i < edits.len() {
+        if edits[i] == Edit::Keep {
+            rows.push(DiffRow { kind: RowKind::Equal, left: Some(line(x, a[x])), right: Some(line(y, b[y])) });
+            (x, y, i) = (x + 1, y + 1, i + 1);
+            continue;
+        }
+
+        // A block of deletions and insertions between unchanged lines
+        let deleted = edits[i..].iter().take_while(|e| **e == Edit::Delete).count();
+        let inserted = edits[i + deleted..].iter().take_while(|e| **e == Edit::Insert).count();
+        for k in 0..deleted.max(inserted) {
+            let mut left = (k < deleted).then(|| line(x + k, a[x + k]));
+            let mut right = (k < inserted).then(|| line(y + k, b[y + k]));
+            let kind = match (&mut left, &mut right) {
+                (Some(left), Some(right)) => {
+                    if words {
+                        (left.segments, right.segments) = word_segments(&left.text, &right.text);
+                    }
+                    RowKind::Changed
+                }
+                (Some(_), None) => RowKind::Removed,
+                _ => RowKind::Added,
+            };
+            rows.push(DiffRow { kind, left, right });
+        }
+        (x, y, i) = (x + deleted, y + inserted, i + deleted + inserted);
+    }
+
+    (rows, added, removed)
+}
+
+/// Segments of two versions of a line, split into words
+fn word_segments(old: &str, new: &str) -> (Vec<Segment>, Vec<Segment>) {
+    let a = words(old);
+    let b = words(new);
+    let (mut left, mut right) = (Vec::new(), Vec::new());
+    let (mut x, mut y) = (0, 0);
+    for edit in edit_script(&a, &b) {
+        match edit {
+            Edit::Keep => {
+                push_segment(&mut left, a[x], false);
+                push_segment(&mut right, b[y], false);
+                (x, y) = (x + 1, y + 1);
+            }
+            Edit::Delete => {
+                push_segment(&mut left, a[x], true);
+                x += 1;
+            }
+            Edit::Insert => {
+                push_segment(&mut right, b[y], true);
+                y += 1;
+            }
+        }
+    }
+    (left, right)
+}
+
+/// Splits a line into words, runs of whitespace and single other characters
+fn words(line: &str) -> Vec<&str> {
+    let mut tokens = Vec::new();
+    let mut rest = line;
+    while let Some(c) = rest.chars().next() {
+        let len = if c.is_alphanumeric() || c == '_' {
+            rest.find(|x: char| !(x.is_alphanumeric() || x == '_')).unwrap_or(rest.len())
+        } else if c.is_whitespace() {
+            rest.find(|x: char| !x.is_whitespace()).unwrap_or(rest.len())
+        } else {
+            c.len_utf8()
+        };
+        tokens.push(&rest[..len]);
+        rest = &rest[len..];
+    }
+    tokens
+}
+
+/// Appends text to the last segment if it has the same state
+fn push_segment(segments: &mut Vec<Segment>, text: &str, changed: bool) {
+    match segments.last_mut() {
+        Some(last) if last.changed == changed => last.text.push_str(text),
+        _ => segments.push(Segment { text: text.to_string(), changed }),
+    }
+}
+
+/// `start,count` of a hunk header (1-based; an empty range names the line before it)
+fn hunk_range(consumed: usize, count: usize) -> String {
+    let start = if count == 0 { consumed } else { consumed + 1 };
+    if count == 1 {
+        start.to_string()
+    } else {
+        format!("{},{}", start, count)
+    }
+}
+
+/// Edit ranges of the hunks: changes with `context` lines around them,
+/// merged where their context would overlap
+fn hunks(edits: &[Edit], context: usize) -> Vec<(usize, usize)> {
+    let mut hunks: Vec<(usize, usize)> = Vec::new();
+    for (i, _) in edits.iter().enumerate().filter(|(_, e)| **e != Edit::Keep) {
+        let start = i.saturating_sub(context);
+        let end = (i + 1 + context).min(edits.len());
+        match hunks.last_mut() {
+            Some(last) if start <= last.1 => last.1 = end,
+            _ => hunks.push((start, end)),
+        }
+    }
+    hunks
+}
+
+/// Shortest edit script from `a` to `b` (Myers' O(ND) algorithm)
+fn edit_script<T: PartialEq>(a: &[T], b: &[T]) -> Vec<Edit> {
+    // Common prefix and suffix need no search
+    let prefix = a.iter().zip(b).take_while(|(x, y)| x == y).count();
+    let suffix = a[prefix..]
+        .iter()
+        .rev()
+        .zip(b[prefix..].iter().rev())
+        .take_while(|(x, y)| x == y)
+        .count();
+    let (a_mid, b_mid) = (&a[prefix..a.len() - suffix], &b[prefix..b.len() - suffix]);
+
+    let mut edits = vec![Edit::Keep; prefix];
+    edits.extend(middle_edits(a_mid, b_mid));
+    edits.extend(vec![Edit::Keep; suffix]);
+    edits
+}
+
+fn middle_edits<T: PartialEq>(a: &[T], b: &[T]) -> Vec<Edit> {
+    let (n, m) = (a.len() as isize, b.len() as isize);
+    let max = n + m;
+    let offset = max as usize;
+    let index = |k: isize| (k + max) as usize;
+
+    let mut v = vec![0isize; 2 * offset + 2];
+    let mut trace = Vec::new();
+    'search: for d in 0..=max {
+        trace.push(v.clone());
+        for k in (-d..=d).step_by(2) {
+            let mut x = if k == -d || (k != d && v[index(k - 1)] < v[index(k + 1)]) {
+                v[index(k + 1)]
+            } else {
+                v[index(k - 1)] + 1
+            };
+            let mut y = x - k;
+            while x < n && y < m && a[x as usize] == b[y as usize] {
+                x += 1;
+                y += 1;
+            }
+            v[index(k)] = x;
+            if x >= n && y >= m {
+                break 'search;
+            }
+        }
+    }
+
+    // Walk the trace back from the end
+    let mut edits = Vec::new();
+    let (mut x, mut y) = (n, m);
+    for (d, v) in trace.iter().enumerate().rev() {
+        let d = d as isize;
+        let k = x - y;
+        let prev_k = if k == -d || (k != d && v[index(k - 1)] < v[index(k + 1)]) {
+            k + 1
+        } else {
+            k - 1
+        };
+        let prev_x = v[index(prev_k)];
+        let prev_y = prev_x - prev_k;
+        while x > prev_x && y > prev_y {
+            edits.push(Edit::Keep);
+            x -= 1;
+            y -= 1;
+        }
+        if d > 0 {
+            edits.push(if x == prev_x { Edit::Insert } else { Edit::Delete });
+        }
+        x = prev_x;
+        y = prev_y;
+    }
+    edits.reverse();
+    edits
+}
+
+// ============================================================================
+// TESTS
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_unified_diff() {
+        let old = "# Plan\n\none\ntwo\nthree\nfour\nfive\nsix\nseven\neight\nnine\n";
+        let new = "# Plan\n\none\n2\nthree\nfour\nfive\nsix\nseven\neight\nnine\nten\n";
+        let diff = unified_diff(old, new, ("plan.md", "plan.md"), DEFAULT_CONTEXT);
+
+        assert_eq!((diff.added, diff.removed), (2, 1));
+        assert_eq!(
+            diff.unified,
+            "--- a/plan.md\n+++ b/plan.md\n\
+             @@ -1,7 +1,7 @@\n # Plan\n \n one\n-two\n+2\n three\n four\n five\n\
+             @@ -9,3 +9,4 @@\n seven\n eight\n nine\n+ten\n"
+        );
+    }
+
+    #[test]
+    fn test_unified_diff_edges() {
+        assert_eq!(unified_diff("same\n", "same\n", ("a.md", "a.md"), 3), TextDiff::default());
+
+        let diff = unified_diff("", "new\n", ("a.md", "a.md"), 3);
+        assert_eq!(diff.unified, "--- a/a.md\n+++ b/a.md\n@@ -0,0 +1 @@\n+new\n");
+
+        let diff = unified_diff("a\nb", "a\nc", ("a.md", "a.md"), 0);
+        assert_eq!(
+            diff.unified,
+            "--- a/a.md\n+++ b/a.md\n@@ -2 +2 @@\n-b\n\\ No newline at end of file\n+c\n\\ No newline at end of file\n"
+        );
+    }
+
+    #[test]
+    fn test_edit_script_is_minimal() {
+        let a = ["a\n", "b\n", "c\n", "a\n", "b\n", "b\n", "a\n"];
+        let b = ["c\n", "b\n", "a\n", "b\n", "a\n", "c\n"];
+        let edits = edit_script(&a, &b);
+        let changes = edits.iter().filter(|e| **e != Edit::Keep).count();
+        assert_eq!(changes, 5);
+        assert_eq!(edits.iter().filter(|e| **e != Edit::Insert).count(), a.len());
+        assert_eq!(edits.iter().filter(|e| **e != Edit::Delete).count(), b.len());
+    }
+
+    #[test]
+    fn test_side_by_side() {
+        let (rows, added, removed) = side_by_side("# Plan\nShip the beta soon.\nold\n", "# Plan\nShip the release soon!\nnew\nmore\n", true);
+        assert_eq!((added, removed), (3, 2));
+
+        let kinds: Vec<RowKind> = rows.iter().map(|r| r.kind).collect();
+        assert_eq!(kinds, vec![RowKind::Equal, RowKind::Changed, RowKind::Changed, RowKind::Added]);
+        assert_eq!(rows[3].right.as_ref().map(|l| (l.number, l.text.as_str())), Some((4, "more")));
+
+        let segments = |line: &Option<DiffLine>| -> Vec<(String, bool)> {
+            line.as_ref().unwrap().segments.iter().map(|s| (s.text.clone(), s.changed)).collect()
+        };
+        assert_eq!(
+            segments(&rows[1].left),
+            vec![("Ship the ".to_string(), false), ("beta".to_string(), true), (" soon".to_string(), false), (".".to_string(), true)]
+        );
+        assert_eq!(
+            segments(&rows[1].right),
+            vec![("Ship the ".to_string(), false), ("release".to_string(), true), (" soon".to_string(), false), ("!".to_string(), true)]
+        );
+        assert!(rows[0].left.as_ref().unwrap().segments.is_empty());
+    }
+}
@@ -0,0 +1,331 @@
+//! Email Drafts
+//!
+//! Opens the default mail client with a draft of a note, for the common
+//! "send the meeting notes" case. The note goes as the message text, or as
+//! an HTML or PDF attachment (see `crate::text_pdf`). Recipients are left
+//! to the user, and nothing is sent without them.
+//!
+//! How the draft reaches the mail client depends on the platform:
+//!
+//! - Windows: Simple MAPI (`MAPISendMailW` with its compose dialog), which
+//!   Outlook, Thunderbird and most other clients provide
+//! - macOS: a `mailto:` URL for text; attachments go through Apple Mail,
+//!   since `mailto:` cannot carry files
+//! - Linux: `xdg-email`, which knows how to attach files for common clients
+//!
+//! Attachments are written to `<temp>/mdreader-mail/`, where the mail
+//! client can read them after the command returns; a day later they are
+//! removed by the next share.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+use serde::{Deserialize, Serialize};
+use crate::utils::sanitize_filename;
+
+/// Longest `mailto:` URL passed to the mail client; longer bodies are cut
+pub const MAX_MAILTO_CHARS: usize = 8000;
+
+/// How long attachments are kept for the mail client to read
+const ATTACHMENT_MAX_AGE: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// How a note is put into the draft
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum EmailFormat {
+    /// The markdown as the message text
+    Plain,
+    /// The rendered note as an `.html` attachment
+    Html,
+    /// The note as a `.pdf` attachment
+    Pdf,
+}
+
+/// A draft handed to the mail client
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct EmailDraft {
+    pub subject: String,
+    #[serde(skip)]
+    pub body: String,
+    /// File attached to the draft
+    pub attachment: Option<PathBuf>,
+}
+
+/// Builds a `mailto:` URL with the draft's subject and body (RFC 6068)
+///
+/// The body is cut, with `…`, to keep the URL within `MAX_MAILTO_CHARS`.
+pub fn mailto_url(draft: &EmailDraft) -> String {
+    let mut url = format!("mailto:?subject={}&body=", percent_encode(&draft.subject));
+    let ellipsis = percent_encode("\u{2026}");
+    let mut chars = draft.body.chars().peekable();
+    while let Some(c) = chars.next() {
+        let encoded = percent_encode(&c.to_string());
+        let reserve = if chars.peek().is_some() { ellipsis.len() } else { 0 };
+        if url.len() + encoded.len() + reserve > MAX_MAILTO_CHARS {
+            url.push_str(&ellipsis);
+            break;
+        }
+        url.push_str(&encoded);
+    }
+    url
+}
+
+/// Writes an attachment named after the note to a folder of its own
+///
+/// # Returns
+/// * `Ok(PathBuf)` - Path of the file to attach
+pub fn write_attachment(title: &str, extension: &str, content: &[u8]) -> Result<PathBuf, String> {
+    let root = attachment_dir();
+    clear_old_attachments(&root, ATTACHMENT_MAX_AGE);
+
+    // A folder per share keeps the file name readable in the mail
+    let dir = root.join(uuid::Uuid::new_v4().to_string());
+    fs::create_dir_all(&dir).map_err(|e| format!("Failed to create attachment folder: {}", e))?;
+    let path = dir.join(format!("{}.{}", sanitize_filename(title), extension));
+    fs::write(&path, content).map_err(|e| format!("Failed to write attachment: {}", e))?;
+    Ok(path)
+}
+
+/// Folder attachments are written to
+pub fn attachment_dir() -> PathBuf {
+    std::env::temp_dir().join("mdreader-mail")
+}
+
+/// Removes attachment folders older than `max_age`
+///
+/// # Returns
+/// Number of folders removed
+pub fn clear_old_attachments(root: &Path, max_age: Duration) -> usize {
+    let Ok(entries) = fs::read_dir(root) else {
+        return 0;
+    };
+    let now = SystemTime::now();
+    entries
+        .flatten()
+        .filter(|entry| {
+            entry
+                .metadata()
+                .and_then(|meta| meta.modified())
+                .is_ok_and(|modified| now.duration_since(modified).unwrap_or_default() >= max_age)
+        })
+        .filter(|entry| fs::remove_dir_all(entry.path()).is_ok())
+        .count()
+}
+
+/// Opens the draft in the default mail client
+///
+/// May block until the mail client has taken the draft (Windows: until its
+/// compose window is closed), so call it off the async runtime.
+pub fn compose(draft: &EmailDraft) -> Result<(), String> {
+    platform::compose(draft)
+}
+
+// Helper: Percent-encodes all but unreserved characters; line breaks
+// become CRLF as RFC 6068 asks
+fn percent_encode(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            'A'..='Z' | 'a'..='z' | '0'..='9' | '-' | '.' | '_' | '~' => out.push(c),
+            '\r' => {}
+            '\n' => out.push_str("%0D%0A"),
+            _ => {
+                let mut buf = [0u8; 4];
+                for byte in c.encode_utf8(&mut buf).bytes() {
+                    out.push_str(&format!("%{:02X}", byte));
+                }
+            }
+        }
+    }
+    out
+}
+
+#[cfg(windows)]
+mod platform {
+    use std::os::windows::ffi::OsStrExt;
+    use windows_sys::Win32::Foundation::FreeLibrary;
+    use windows_sys::Win32::System::LibraryLoader::{GetProcAddress, LoadLibraryW};
+    use windows_sys::Win32::System::Mapi::{MapiFileDescW, MapiMessageW, MAPI_DIALOG, MAPI_LOGON_UI};
+    use super::EmailDraft;
+
+    /// `MAPISendMailW`, looked up at runtime: mapi32.dll forwards it to the
+    /// default mail client
+    type SendMail = unsafe extern "system" fn(usize, usize, *const MapiMessageW, u32, u32) -> u32;
+
+    const SUCCESS_SUCCESS: u32 = 0;
+    const MAPI_E_USER_ABORT: u32 = 1;
+
+    pub fn compose(draft: &EmailDraft) -> Result<(), String> {
+        let mut subject = wide(draft.subject.as_ref());
+        let mut body = wide(draft.body.replace("\r\n", "\n").replace('\n', "\r\n").as_ref());
+        let mut path = draft.attachment.as_ref().map(|p| wide(p.as_os_str()));
+        let mut name = draft.attachment.as_ref().and_then(|p| p.file_name()).map(wide);
+        let mut files: Vec<MapiFileDescW> = match (&mut path, &mut name) {
+            (Some(path), Some(name)) => vec![MapiFileDescW {
+                ulReserved: 0,
+                flFlags: 0,
+                // Attachments go after the text
+                nPosition: u32::MAX,
+                lpszPathName: path.as_mut_ptr(),
+                lpszFileName: name.as_mut_ptr(),
+                lpFileType: std::ptr::null_mut(),
+            }],
+            _ => Vec::new(),
+        };
+
+        // SAFETY: All strings are NUL-terminated and outlive the call, and
+        // `send` has the signature documented for MAPISendMailW
+        let code = unsafe {
+            let library = LoadLibraryW(wide("mapi32.dll".as_ref()).as_ptr());
+            if library.is_null() {
+                return Err("No mail client is set up (mapi32.dll is missing)".to_string());
+            }
+            let code = GetProcAddress(library, b"MAPISendMailW\0".as_ptr()).map(|proc| {
+                let send: SendMail = std::mem::transmute(proc);
+                let message = MapiMessageW {
+                    lpszSubject: subject.as_mut_ptr(),
+                    lpszNoteText: body.as_mut_ptr(),
+                    nFileCount: files.len() as u32,
+                    lpFiles: files.as_mut_ptr(),
+                    ..std::mem::zeroed()
+                };
+                send(0, 0, &message, MAPI_DIALOG | MAPI_LOGON_UI, 0)
+            });
+            FreeLibrary(library);
+            code
+        };
+        match code {
+            None => Err("The mail client does not support drafts from other apps (no MAPISendMailW)".to_string()),
+            Some(SUCCESS_SUCCESS | MAPI_E_USER_ABORT) => Ok(()),
+            Some(code) => Err(format!("Failed to open mail client (MAPI error {})", code)),
+        }
+    }
+
+    // Helper: Encodes a string as NUL-terminated UTF-16
+    fn wide(text: &std::ffi::OsStr) -> Vec<u16> {
+        text.encode_wide().chain(std::iter::once(0)).collect()
+    }
+}
+
+#[cfg(target_os = "macos")]
+mod platform {
+    use std::process::Command;
+    use super::{mailto_url, EmailDraft};
+
+    /// Makes a visible Apple Mail draft: subject, text, attachment path
+    const MAIL_SCRIPT: &[&str] = &[
+        "on run argv",
+        "set attachmentFile to POSIX file (item 3 of argv)",
+        "tell application \"Mail\"",
+        "set draft to make new outgoing message with properties {subject:(item 1 of argv), content:(item 2 of argv), visible:true}",
+        "tell draft to make new attachment with properties {file name:attachmentFile} at after the last paragraph",
+        "activate",
+        "end tell",
+        "end run",
+    ];
+
+    pub fn compose(draft: &EmailDraft) -> Result<(), String> {
+        let mut command = match &draft.attachment {
+            None => {
+                let mut command = Command::new("open");
+                command.arg(mailto_url(draft));
+                command
+            }
+            Some(file) => {
+                let mut command = Command::new("osascript");
+                for line in MAIL_SCRIPT {
+                    command.args(["-e", line]);
+                }
+                command.arg(&draft.subject).arg(&draft.body).arg(file);
+                command
+            }
+        };
+        let output = command
+            .output()
+            .map_err(|e| format!("Failed to open mail client: {}", e))?;
+        if !output.status.success() {
+            return Err(format!(
+                "Failed to open mail client: {}",
+                String::from_utf8_lossy(&output.stderr).trim()
+            ));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(all(desktop, not(any(windows, target_os = "macos"))))]
+mod platform {
+    use std::process::{Command, Stdio};
+    use super::EmailDraft;
+
+    pub fn compose(draft: &EmailDraft) -> Result<(), String> {
+        let mut command = Command::new("xdg-email");
+        command
+            .args(["--utf8", "--subject", &draft.subject, "--body", &draft.body])
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null());
+        if let Some(file) = &draft.attachment {
+            command.arg("--attach").arg(file);
+        }
+        // Some clients keep xdg-email waiting until they quit
+        let mut child = command
+            .spawn()
+            .map_err(|e| format!("Failed to open mail client (is xdg-utils installed?): {}", e))?;
+        std::thread::spawn(move || match child.wait() {
+            Ok(status) if !status.success() => log::warn!("⚠️ xdg-email exited with {}", status),
+            Ok(_) => {}
+            Err(e) => log::warn!("⚠️ Failed to wait for xdg-email: {}", e),
+        });
+        Ok(())
+    }
+}
+
+#[cfg(mobile)]
+mod platform {
+    use super::EmailDraft;
+
+    pub fn compose(_draft: &EmailDraft) -> Result<(), String> {
+        Err("Sharing via email is not supported on this platform".to_string())
+    }
+}
+
+// ============================================================================
+// TESTS
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn draft(body: &str) -> EmailDraft {
+        EmailDraft { subject: "Sync: 3/4".to_string(), body: body.to_string(), attachment: None }
+    }
+
+    #[test]
+    fn test_mailto_url() {
+        assert_eq!(
+            mailto_url(&draft("- Ship it\n- Café & more")),
+            "mailto:?subject=Sync%3A%203%2F4&body=-%20Ship%20it%0D%0A-%20Caf%C3%A9%20%26%20more"
+        );
+
+        let long = mailto_url(&draft(&"a".repeat(MAX_MAILTO_CHARS)));
+        assert!(long.len() <= MAX_MAILTO_CHARS);
+        assert!(long.ends_with("a%E2%80%A6"));
+    }
+
+    #[test]
+    fn test_attachments() {
+        let path = write_attachment("Weekly: notes", "pdf", b"%PDF").unwrap();
+        assert_eq!(path.file_name().unwrap(), "Weekly_ notes.pdf");
+        assert_eq!(fs::read(&path).unwrap(), b"%PDF");
+        let _ = fs::remove_dir_all(path.parent().unwrap());
+
+        let root = TempDir::new().unwrap();
+        fs::create_dir(root.path().join("share")).unwrap();
+        assert_eq!(clear_old_attachments(root.path(), ATTACHMENT_MAX_AGE), 0);
+        assert_eq!(clear_old_attachments(root.path(), Duration::ZERO), 1);
+        assert!(!root.path().join("share").exists());
+    }
+}
@@ -0,0 +1,390 @@
+//! Backend Events
+//!
+//! Every event the backend emits to the frontend is defined here: its name
+//! and its payload type. Payloads are serialized as JSON objects with an
+//! added `version` field:
+//!
+//! ```json
+//! { "version": 1, "path": "/notes/plan.md", "event_type": "modified", "timestamp": "..." }
+//! ```
+//!
+//! The version of an event is bumped whenever a field is removed, renamed or
+//! changes meaning, so the frontend can keep handling older payloads (or
+//! ignore newer ones) during upgrades. Adding a field does not bump it.
+//!
+//! | Event | Payload |
+//! |-------|---------|
+//! | `file-changed` | `FileChangeEvent` |
+//! | `open-file-changed` | `OpenFileChangedEvent` |
+//! | `job-progress` | `JobProgress` (see `crate::jobs`) |
+//! | `sync-status` | `SyncStatusEvent` |
+//! | `settings-changed` | `SettingsChangedEvent` |
+//! | `backup-status` | `BackupStatusEvent` |
+//! | `metadata-reindexed` | `MetadataReindexedEvent` |
+//! | `shared-into-note` | `SharedIntoNoteEvent` |
+//! | `offline-journal` | `OfflineJournalEvent` |
+//! | `hook-finished` | `HookFinishedEvent` |
+//! | `link-check-finished` | `LinkCheckFinishedEvent` |
+//! | `save-failed` | `SaveFailedEvent` |
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tauri::{AppHandle, Emitter};
+use crate::diff::TextDiff;
+use crate::hooks::HookRun;
+use crate::jobs::JobProgress;
+use crate::link_check::LinkCheckReport;
+
+/// A file inside a watched directory was created, modified or deleted
+pub const FILE_CHANGED: &str = "file-changed";
+
+/// A watched open document was changed outside the editor
+pub const OPEN_FILE_CHANGED: &str = "open-file-changed";
+
+/// Progress of a long-running job
+pub const JOB_PROGRESS: &str = "job-progress";
+
+/// State of synchronization with a remote
+///
+/// Reserved: no sync backend emits it yet, but the frontend can subscribe.
+#[allow(dead_code)]
+pub const SYNC_STATUS: &str = "sync-status";
+
+/// Workspace settings were changed
+pub const SETTINGS_CHANGED: &str = "settings-changed";
+
+/// A scheduled backup started, finished or is overdue
+pub const BACKUP_STATUS: &str = "backup-status";
+
+/// The metadata cache was re-indexed after it drifted from the disk
+pub const METADATA_REINDEXED: &str = "metadata-reindexed";
+
+/// Items shared from another app were added to a note
+pub const SHARED_INTO_NOTE: &str = "shared-into-note";
+
+/// Saves were journaled while the workspace was unreachable, or replayed
+pub const OFFLINE_JOURNAL: &str = "offline-journal";
+
+/// A workspace hook finished (or failed, or was killed)
+pub const HOOK_FINISHED: &str = "hook-finished";
+
+/// A check of the workspace's external links finished
+pub const LINK_CHECK_FINISHED: &str = "link-check-finished";
+
+/// A queued save was refused (e.g. by a `before_save` hook) and dropped
+pub const SAVE_FAILED: &str = "save-failed";
+
+/// A payload of a backend event
+pub trait AppEvent: Serialize {
+    /// Event name
+    const NAME: &'static str;
+    /// Payload version, sent as the `version` field
+    const VERSION: u32;
+}
+
+/// Serializes an event payload with its `version` field
+pub fn versioned<E: AppEvent>(event: &E) -> Result<Value, String> {
+    let mut value = serde_json::to_value(event)
+        .map_err(|e| format!("Failed to serialize {} event: {}", E::NAME, e))?;
+    value
+        .as_object_mut()
+        .ok_or_else(|| format!("Payload of {} event is not an object", E::NAME))?
+        .insert("version".to_string(), Value::from(E::VERSION));
+    Ok(value)
+}
+
+/// Emits an event to all windows, logging (not failing) on error
+pub fn emit<E: AppEvent>(app_handle: &AppHandle, event: &E) {
+    let result = versioned(event)
+        .and_then(|payload| app_handle.emit(E::NAME, payload).map_err(|e| e.to_string()));
+    if let Err(e) = result {
+        log::error!("Failed to emit {} event: {}", E::NAME, e);
+    }
+}
+
+/// Emits an event to one window, logging (not failing) on error
+pub fn emit_to<E: AppEvent>(app_handle: &AppHandle, label: &str, event: &E) {
+    let result = versioned(event)
+        .and_then(|payload| app_handle.emit_to(label, E::NAME, payload).map_err(|e| e.to_string()));
+    if let Err(e) = result {
+        log::error!("Failed to emit {} event to {}: {}", E::NAME, label, e);
+    }
+}
+
+// ============================================================================
+// PAYLOADS
+// ============================================================================
+
+/// What happened to a file
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum FileChangeKind {
+    Created,
+    Modified,
+    Deleted,
+}
+
+/// Payload of `file-changed`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FileChangeEvent {
+    pub path: String,
+    pub event_type: FileChangeKind,
+    /// When the change was seen (RFC 3339, UTC)
+    pub timestamp: String,
+}
+
+impl AppEvent for FileChangeEvent {
+    const NAME: &'static str = FILE_CHANGED;
+    const VERSION: u32 = 1;
+}
+
+/// Payload of `open-file-changed`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OpenFileChangedEvent {
+    pub path: String,
+    /// New content, `None` if the file was deleted
+    #[serde(default)]
+    pub content: Option<String>,
+    /// Unified diff from the content the editor last saw or saved
+    #[serde(flatten)]
+    pub diff: TextDiff,
+    /// When the change was seen (RFC 3339, UTC)
+    pub timestamp: String,
+}
+
+impl AppEvent for OpenFileChangedEvent {
+    const NAME: &'static str = OPEN_FILE_CHANGED;
+    const VERSION: u32 = 1;
+}
+
+impl AppEvent for JobProgress {
+    const NAME: &'static str = JOB_PROGRESS;
+    const VERSION: u32 = 1;
+}
+
+/// Synchronization state
+#[allow(dead_code)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SyncState {
+    Idle,
+    Syncing,
+    Offline,
+    Error,
+}
+
+/// Payload of `sync-status`
+#[allow(dead_code)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SyncStatusEvent {
+    pub state: SyncState,
+    /// Error or progress message
+    #[serde(default)]
+    pub message: Option<String>,
+    /// Last successful sync (RFC 3339, UTC)
+    #[serde(default)]
+    pub last_synced: Option<String>,
+}
+
+impl AppEvent for SyncStatusEvent {
+    const NAME: &'static str = SYNC_STATUS;
+    const VERSION: u32 = 1;
+}
+
+/// Part of the workspace settings that changed
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SettingsSection {
+    FolderFlags,
+    SymlinkPolicy,
+    Attachments,
+    ExportFolders,
+    Statuses,
+    TemplateVariables,
+    MetadataSchema,
+    BackupTargets,
+    Gitignore,
+    Hooks,
+    LinkCheck,
+    Lists,
+}
+
+/// Payload of `settings-changed`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SettingsChangedEvent {
+    pub section: SettingsSection,
+    /// Workspace whose settings changed
+    pub workspace: String,
+}
+
+impl AppEvent for SettingsChangedEvent {
+    const NAME: &'static str = SETTINGS_CHANGED;
+    const VERSION: u32 = 1;
+}
+
+/// What happened to a backup target
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BackupState {
+    Running,
+    Succeeded,
+    Failed,
+    /// No backup succeeded for the target's `warn_after_days`
+    Overdue,
+}
+
+/// Payload of `backup-status`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BackupStatusEvent {
+    /// Name of the backup target
+    pub target: String,
+    pub state: BackupState,
+    /// Id of the backup (`succeeded`)
+    #[serde(default)]
+    pub backup_id: Option<String>,
+    /// Error message (`failed`)
+    #[serde(default)]
+    pub message: Option<String>,
+    /// Last successful backup (RFC 3339, UTC)
+    #[serde(default)]
+    pub last_success: Option<String>,
+    /// Days without a successful backup (`overdue`)
+    #[serde(default)]
+    pub days_without_success: Option<i64>,
+}
+
+impl AppEvent for BackupStatusEvent {
+    const NAME: &'static str = BACKUP_STATUS;
+    const VERSION: u32 = 1;
+}
+
+/// Payload of `metadata-reindexed`; results computed before it may be stale
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MetadataReindexedEvent {
+    pub workspace: String,
+    /// Cached files checked at startup
+    pub sampled: usize,
+    /// Sampled files that no longer matched the disk
+    pub drifted: usize,
+    /// Files added, changed or removed by the re-index
+    pub changed: usize,
+}
+
+impl AppEvent for MetadataReindexedEvent {
+    const NAME: &'static str = METADATA_REINDEXED;
+    const VERSION: u32 = 1;
+}
+
+/// Payload of `shared-into-note`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SharedIntoNoteEvent {
+    pub workspace: String,
+    /// Note the items were added to
+    pub path: String,
+    /// Items added
+    pub added: usize,
+    /// Attachments stored for shared images
+    #[serde(default)]
+    pub attachments: Vec<String>,
+}
+
+impl AppEvent for SharedIntoNoteEvent {
+    const NAME: &'static str = SHARED_INTO_NOTE;
+    const VERSION: u32 = 1;
+}
+
+/// What happened to the offline journal
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OfflineJournalState {
+    /// A save was journaled because the workspace is unreachable
+    Queued,
+    /// Journaled saves were replayed after the workspace came back
+    Replayed,
+}
+
+/// Payload of `offline-journal`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OfflineJournalEvent {
+    pub workspace: String,
+    pub state: OfflineJournalState,
+    /// Saves still journaled
+    pub pending: usize,
+    /// Notes written by the replay (`replayed`)
+    #[serde(default)]
+    pub written: Vec<String>,
+    /// Conflict copies written by the replay (`replayed`)
+    #[serde(default)]
+    pub conflicts: Vec<String>,
+}
+
+impl AppEvent for OfflineJournalEvent {
+    const NAME: &'static str = OFFLINE_JOURNAL;
+    const VERSION: u32 = 1;
+}
+
+/// Payload of `hook-finished`: the run's outcome and output
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HookFinishedEvent {
+    pub workspace: String,
+    /// Note the hook ran for, if any
+    #[serde(default)]
+    pub file: Option<String>,
+    #[serde(flatten)]
+    pub run: HookRun,
+}
+
+impl AppEvent for HookFinishedEvent {
+    const NAME: &'static str = HOOK_FINISHED;
+    const VERSION: u32 = 1;
+}
+
+/// Payload of `link-check-finished`: dead and redirected links
+#[derive(Debug, Clone, Serialize)]
+pub struct LinkCheckFinishedEvent {
+    pub workspace: String,
+    /// Run by the schedule rather than on demand
+    pub scheduled: bool,
+    #[serde(flatten)]
+    pub report: LinkCheckReport,
+}
+
+impl AppEvent for LinkCheckFinishedEvent {
+    const NAME: &'static str = LINK_CHECK_FINISHED;
+    const VERSION: u32 = 1;
+}
+
+/// Payload of `save-failed`: the note whose queued content was not written
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SaveFailedEvent {
+    pub path: String,
+    pub error: String,
+    pub timestamp: String,
+}
+
+impl AppEvent for SaveFailedEvent {
+    const NAME: &'static str = SAVE_FAILED;
+    const VERSION: u32 = 1;
+}
+
+// ============================================================================
+// TESTS
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_payload_carries_version() {
+        let event = SettingsChangedEvent {
+            section: SettingsSection::SymlinkPolicy,
+            workspace: "/notes".to_string(),
+        };
+
+        let payload = versioned(&event).unwrap();
+        assert!(payload["version"].is_number());
+        assert_eq!(payload["section"], "symlink_policy");
+        assert_eq!(payload["workspace"], "/notes");
+    }
+}
@@ -0,0 +1,270 @@
+//! Export Destination Policy
+//!
+//! Exports write outside the workspace, so their destination is not covered
+//! by the workspace path validation. Instead a destination is accepted only
+//! if it was:
+//!
+//! - picked by the user in the native save dialog (`choose_export_destination`),
+//!   which grants that one path for a single export, or
+//! - inside the workspace or one of the workspace's export folders.
+//!
+//! Export folders are kept per machine in the config directory
+//! (`ExportFolders`), never in the synced workspace settings, and each one
+//! must have been picked in the native folder dialog (`grant_folder`).
+//!
+//! Either way, nothing is ever written into a system directory (`/etc`,
+//! `/usr`, `C:\Windows`, `~/.ssh`, ...), even if the dialog allowed picking it.
+
+use std::collections::{BTreeMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use serde::{Deserialize, Serialize};
+
+/// File in the config directory holding the export folders of each workspace
+pub const EXPORT_FOLDERS_FILE: &str = "export-folders.json";
+
+/// System directories on Unix-like systems (macOS included)
+const UNIX_SYSTEM_DIRS: &[&str] = &[
+    "/bin", "/boot", "/dev", "/etc", "/lib", "/lib32", "/lib64", "/proc", "/run", "/sbin", "/sys",
+    "/usr", "/var/db", "/var/lib", "/var/log", "/System", "/Library", "/private/etc", "/private/var/db",
+];
+
+/// Folders in the home directory whose files are run or trusted by the system
+const HOME_SYSTEM_DIRS: &[&str] = &[".ssh", ".config/autostart", "Library/LaunchAgents"];
+
+/// Environment variables naming system directories on Windows
+const WINDOWS_SYSTEM_VARS: &[&str] = &["SystemRoot", "ProgramFiles", "ProgramFiles(x86)", "ProgramData"];
+
+/// System directories nothing may be exported into
+fn system_directories() -> Vec<PathBuf> {
+    let mut dirs: Vec<PathBuf> = UNIX_SYSTEM_DIRS.iter().map(PathBuf::from).collect();
+    dirs.extend(WINDOWS_SYSTEM_VARS.iter().filter_map(std::env::var_os).map(PathBuf::from));
+    if let Some(home) = dirs::home_dir() {
+        // Folders are compared canonicalized
+        let home = home.canonicalize().unwrap_or(home);
+        dirs.extend(HOME_SYSTEM_DIRS.iter().map(|dir| home.join(dir)));
+    }
+    dirs
+}
+
+/// Checks if a (canonical) folder is a filesystem root or inside a system directory
+pub fn is_system_directory(dir: &Path) -> bool {
+    dir.parent().is_none() || system_directories().iter().any(|system| dir.starts_with(system))
+}
+
+/// Resolves a destination file to its canonical folder joined with its name
+///
+/// The file itself may not exist yet, but its folder must.
+pub fn resolve_destination(dest: &Path) -> Result<PathBuf, String> {
+    let name = dest.file_name()
+        .ok_or_else(|| format!("Destination is not a file: {}", dest.display()))?;
+    let parent = match dest.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => parent,
+        _ => return Err(format!("Destination must be an absolute path: {}", dest.display())),
+    };
+    if !parent.exists() {
+        return Err(format!("Destination directory does not exist: {:?}", parent));
+    }
+    let parent = parent
+        .canonicalize()
+        .map_err(|e| format!("Failed to resolve destination: {}", e))?;
+    Ok(parent.join(name))
+}
+
+/// Export folders of each workspace on this machine
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ExportFolders {
+    /// Canonical absolute folders, keyed by workspace path
+    #[serde(default)]
+    folders: BTreeMap<String, Vec<String>>,
+}
+
+impl ExportFolders {
+    /// Loads the export folders from `config_dir` (none if missing)
+    pub fn load(config_dir: &Path) -> Result<Self, String> {
+        match fs::read_to_string(config_dir.join(EXPORT_FOLDERS_FILE)) {
+            Ok(json) => serde_json::from_str(&json)
+                .map_err(|e| format!("Failed to parse export folders: {}", e)),
+            Err(_) => Ok(Self::default()),
+        }
+    }
+
+    /// Saves the export folders to `config_dir`
+    pub fn save(&self, config_dir: &Path) -> Result<(), String> {
+        let json = serde_json::to_string_pretty(self)
+            .map_err(|e| format!("Failed to serialize export folders: {}", e))?;
+        fs::write(config_dir.join(EXPORT_FOLDERS_FILE), json)
+            .map_err(|e| format!("Failed to save export folders: {}", e))
+    }
+
+    /// Export folders of a workspace
+    pub fn get(&self, workspace: &str) -> &[String] {
+        self.folders.get(workspace).map_or(&[], Vec::as_slice)
+    }
+
+    /// Replaces the export folders of a workspace
+    pub fn set(&mut self, workspace: &str, folders: Vec<String>) {
+        if folders.is_empty() {
+            self.folders.remove(workspace);
+        } else {
+            self.folders.insert(workspace.to_string(), folders);
+        }
+    }
+}
+
+/// Destinations picked in the save dialog that have not been exported to
+/// yet, and folders picked in the folder dialog that have not been added
+/// to the export folders yet (internally synchronized)
+#[derive(Clone, Default)]
+pub struct ExportGrants {
+    granted: Arc<Mutex<HashSet<PathBuf>>>,
+    folders: Arc<Mutex<HashSet<PathBuf>>>,
+}
+
+impl ExportGrants {
+    /// Creates a registry with nothing granted
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Grants one export to a path the user picked, refusing system directories
+    pub fn grant(&self, dest: &Path) -> Result<PathBuf, String> {
+        let resolved = resolve_destination(dest)?;
+        refuse_system_directory(&resolved)?;
+        self.granted
+            .lock()
+            .map_err(|e| format!("Failed to lock export grants: {}", e))?
+            .insert(resolved.clone());
+        Ok(resolved)
+    }
+
+    /// Grants adding a folder the user picked to the export folders,
+    /// refusing system directories; returns the canonical folder
+    pub fn grant_folder(&self, dir: &Path) -> Result<PathBuf, String> {
+        let canonical = dir
+            .canonicalize()
+            .map_err(|e| format!("Failed to resolve folder: {}", e))?;
+        if is_system_directory(&canonical) {
+            return Err(format!("Cannot export into a system directory: {}", canonical.display()));
+        }
+        self.folders
+            .lock()
+            .map_err(|e| format!("Failed to lock export grants: {}", e))?
+            .insert(canonical.clone());
+        Ok(canonical)
+    }
+
+    /// Uses up the grant of a picked (canonical) folder; returns whether
+    /// it was granted
+    pub fn take_folder(&self, dir: &Path) -> Result<bool, String> {
+        Ok(self.folders
+            .lock()
+            .map_err(|e| format!("Failed to lock export grants: {}", e))?
+            .remove(dir))
+    }
+
+    /// Checks an export destination against the policy
+    ///
+    /// `allowed` are the workspace root and its export folders. A granted
+    /// path is used up by the check, so the next export to it needs a new
+    /// pick (or an allowed folder). Returns the resolved destination.
+    pub fn authorize(&self, dest: &Path, allowed: &[PathBuf]) -> Result<PathBuf, String> {
+        let resolved = resolve_destination(dest)?;
+        refuse_system_directory(&resolved)?;
+
+        let granted = self.granted
+            .lock()
+            .map_err(|e| format!("Failed to lock export grants: {}", e))?
+            .remove(&resolved);
+        let in_allowed_folder = allowed
+            .iter()
+            .filter_map(|folder| folder.canonicalize().ok())
+            .any(|folder| resolved.starts_with(folder));
+
+        if granted || in_allowed_folder {
+            Ok(resolved)
+        } else {
+            Err(format!(
+                "Export destination not allowed: {} (choose it in the save dialog or add its folder to the export folders)",
+                resolved.display()
+            ))
+        }
+    }
+}
+
+// Helper: Fails for destinations inside system directories
+fn refuse_system_directory(resolved: &Path) -> Result<(), String> {
+    match resolved.parent() {
+        Some(dir) if !is_system_directory(dir) => Ok(()),
+        _ => Err(format!("Cannot export into a system directory: {}", resolved.display())),
+    }
+}
+
+// ============================================================================
+// TESTS
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_granted_destination_is_used_once() {
+        let temp = TempDir::new().unwrap();
+        let dest = temp.path().join("report.md");
+        let grants = ExportGrants::new();
+
+        assert!(grants.authorize(&dest, &[]).unwrap_err().starts_with("Export destination not allowed"));
+
+        grants.grant(&dest).unwrap();
+        assert_eq!(grants.authorize(&dest, &[]).unwrap(), temp.path().canonicalize().unwrap().join("report.md"));
+        assert!(grants.authorize(&dest, &[]).is_err());
+    }
+
+    #[test]
+    fn test_allowed_folder_accepts_nested_destinations() {
+        let temp = TempDir::new().unwrap();
+        std::fs::create_dir(temp.path().join("exports")).unwrap();
+        let grants = ExportGrants::new();
+        let allowed = vec![temp.path().to_path_buf()];
+
+        assert!(grants.authorize(&temp.path().join("exports").join("a.zip"), &allowed).is_ok());
+        assert!(grants.authorize(&temp.path().join("missing").join("a.zip"), &allowed).is_err());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_system_directories_are_refused() {
+        let grants = ExportGrants::new();
+        let allowed = vec![PathBuf::from("/")];
+
+        let err = grants.grant(Path::new("/etc/export.md")).unwrap_err();
+        assert!(err.starts_with("Cannot export into a system directory"));
+        assert!(grants.authorize(Path::new("/export.md"), &allowed).is_err());
+        assert!(grants.authorize(Path::new("/usr/share/export.md"), &allowed).is_err());
+
+        let home = dirs::home_dir().unwrap();
+        let home = home.canonicalize().unwrap_or(home);
+        assert!(is_system_directory(&home.join(".ssh")));
+        assert!(is_system_directory(&home.join("Library/LaunchAgents/sub")));
+        assert!(!is_system_directory(&home.join("Documents")));
+    }
+
+    #[test]
+    fn test_export_folders_need_a_pick_and_stay_on_this_machine() {
+        let temp = TempDir::new().unwrap();
+        let grants = ExportGrants::new();
+        let folder = grants.grant_folder(temp.path()).unwrap();
+        assert!(grants.take_folder(&folder).unwrap());
+        assert!(!grants.take_folder(&folder).unwrap(), "A pick is used up");
+
+        let mut store = ExportFolders::default();
+        store.set("/notes", vec![folder.to_string_lossy().to_string()]);
+        store.save(temp.path()).unwrap();
+        let store = ExportFolders::load(temp.path()).unwrap();
+        assert_eq!(store.get("/notes"), [folder.to_string_lossy().to_string()]);
+        assert!(store.get("/other").is_empty());
+    }
+}
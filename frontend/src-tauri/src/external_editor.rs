@@ -0,0 +1,236 @@
+//! External Editor
+//!
+//! Builds the command that opens a note in another editor (vim, VS Code,
+//! ...) for a heavy edit; the command layer then watches the note and
+//! reports the editor's saves like any other external change (see
+//! `crate::open_file`).
+//!
+//! The editor is a command line such as `code --wait` or `"C:\Tools\nvim.exe"`,
+//! split like a shell would but never run through one. The note is appended,
+//! or replaces `{file}` where it appears. Terminal editors get a terminal
+//! window of their own, since the app has none. Without an editor, the
+//! system's default application for the file is used.
+//!
+//! The chosen editor is saved per machine in `<config>/external-editor.json`.
+
+use std::fs;
+use std::path::Path;
+use serde::{Deserialize, Serialize};
+
+/// File name of the editor setting inside the config directory
+pub const EDITOR_FILE: &str = "external-editor.json";
+
+/// Editors that need a terminal (file stem of the program)
+pub const TERMINAL_EDITORS: &[&str] = &["vim", "nvim", "vi", "nano", "emacs", "hx", "helix", "micro", "kak", "ne", "joe"];
+
+/// Placeholder replaced by the note's path
+const FILE_PLACEHOLDER: &str = "{file}";
+
+/// The external editor saved on this machine
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct EditorConfig {
+    /// Command line of the editor (`None`: the system default application)
+    #[serde(default)]
+    pub editor: Option<String>,
+}
+
+impl EditorConfig {
+    /// Loads the setting from `config_dir` (the default if missing)
+    pub fn load(config_dir: &Path) -> Result<Self, String> {
+        match fs::read_to_string(config_dir.join(EDITOR_FILE)) {
+            Ok(json) => serde_json::from_str(&json)
+                .map_err(|e| format!("Failed to parse external editor: {}", e)),
+            Err(_) => Ok(Self::default()),
+        }
+    }
+
+    /// Saves the setting to `config_dir`
+    pub fn save(&self, config_dir: &Path) -> Result<(), String> {
+        let json = serde_json::to_string_pretty(self)
+            .map_err(|e| format!("Failed to serialize external editor: {}", e))?;
+        fs::write(config_dir.join(EDITOR_FILE), json)
+            .map_err(|e| format!("Failed to save external editor: {}", e))
+    }
+}
+
+/// A process to start to edit a note
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct EditorLaunch {
+    pub program: String,
+    #[serde(skip)]
+    pub args: Vec<String>,
+    /// Whether the editor runs in a terminal window opened for it
+    pub terminal: bool,
+}
+
+/// Builds the process that opens `file` in `editor`
+///
+/// # Returns
+/// * `Err(String)` - If the editor command line is empty or badly quoted
+pub fn launch(editor: Option<&str>, file: &Path) -> Result<EditorLaunch, String> {
+    let file = file.to_string_lossy().to_string();
+    let Some(editor) = editor.map(str::trim).filter(|e| !e.is_empty()) else {
+        return Ok(system_default(file));
+    };
+
+    let mut words = split_command(editor)?;
+    let program = words.remove(0);
+    let mut args: Vec<String> = words;
+    if args.iter().any(|arg| arg.contains(FILE_PLACEHOLDER)) {
+        for arg in &mut args {
+            *arg = arg.replace(FILE_PLACEHOLDER, &file);
+        }
+    } else {
+        args.push(file);
+    }
+
+    if is_terminal_editor(&program) {
+        return Ok(in_terminal(program, args));
+    }
+    Ok(EditorLaunch { program, args, terminal: false })
+}
+
+/// Checks if a program is a terminal editor
+pub fn is_terminal_editor(program: &str) -> bool {
+    let stem = Path::new(program)
+        .file_stem()
+        .map(|s| s.to_string_lossy().to_lowercase())
+        .unwrap_or_default();
+    TERMINAL_EDITORS.contains(&stem.as_str())
+}
+
+/// Splits a command line into words, honoring quotes and backslash escapes
+///
+/// Backslashes only escape quotes and backslashes, so Windows paths can be
+/// written as they are.
+pub fn split_command(line: &str) -> Result<Vec<String>, String> {
+    let mut words = Vec::new();
+    let mut word = String::new();
+    let mut in_word = false;
+    let mut quote: Option<char> = None;
+    let mut chars = line.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match (quote, c) {
+            (Some(q), c) if c == q => quote = None,
+            (Some('"') | None, '\\') if matches!(chars.peek(), Some('"' | '\'' | '\\')) => {
+                word.extend(chars.next());
+                in_word = true;
+            }
+            (Some(_), c) => word.push(c),
+            (None, '"' | '\'') => {
+                quote = Some(c);
+                in_word = true;
+            }
+            (None, c) if c.is_whitespace() => {
+                if in_word {
+                    words.push(std::mem::take(&mut word));
+                    in_word = false;
+                }
+            }
+            (None, c) => {
+                word.push(c);
+                in_word = true;
+            }
+        }
+    }
+    if quote.is_some() {
+        return Err(format!("Unterminated quote in editor command: {}", line));
+    }
+    if in_word {
+        words.push(word);
+    }
+    if words.is_empty() {
+        return Err("Editor command is empty".to_string());
+    }
+    Ok(words)
+}
+
+// Helper: Opens the file with the system's default application
+fn system_default(file: String) -> EditorLaunch {
+    let (program, args) = if cfg!(target_os = "macos") {
+        // Default text editor rather than the app registered for `.md` (possibly this one)
+        ("open", vec!["-t".to_string(), file])
+    } else if cfg!(windows) {
+        ("explorer", vec![file])
+    } else {
+        ("xdg-open", vec![file])
+    };
+    EditorLaunch { program: program.to_string(), args, terminal: false }
+}
+
+// Helper: Runs a terminal editor in a new terminal window
+fn in_terminal(program: String, args: Vec<String>) -> EditorLaunch {
+    if cfg!(target_os = "macos") {
+        let command: Vec<String> = std::iter::once(&program).chain(&args).map(|w| shell_quote(w)).collect();
+        let script = format!(
+            "tell application \"Terminal\" to do script \"{}\"",
+            command.join(" ").replace('\\', "\\\\").replace('"', "\\\"")
+        );
+        return EditorLaunch {
+            program: "osascript".to_string(),
+            args: vec!["-e".to_string(), script, "-e".to_string(), "tell application \"Terminal\" to activate".to_string()],
+            terminal: true,
+        };
+    }
+    if cfg!(windows) {
+        // Started with a console of its own (see the command layer)
+        return EditorLaunch { program, args, terminal: true };
+    }
+    let terminal = std::env::var("TERMINAL").unwrap_or_else(|_| "x-terminal-emulator".to_string());
+    let mut terminal_args = vec!["-e".to_string(), program];
+    terminal_args.extend(args);
+    EditorLaunch { program: terminal, args: terminal_args, terminal: true }
+}
+
+// Helper: Quotes a word for a POSIX shell
+fn shell_quote(word: &str) -> String {
+    format!("'{}'", word.replace('\'', "'\\''"))
+}
+
+// ============================================================================
+// TESTS
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_split_command() {
+        assert_eq!(split_command("code --wait").unwrap(), ["code", "--wait"]);
+        assert_eq!(
+            split_command(r#""C:\Program Files\Editor\ed.exe" -n"#).unwrap(),
+            [r"C:\Program Files\Editor\ed.exe", "-n"]
+        );
+        assert_eq!(split_command(r#"ed 'a b' "say \"hi\"" ''"#).unwrap(), ["ed", "a b", "say \"hi\"", ""]);
+        assert!(split_command("ed 'open").is_err());
+        assert!(split_command("   ").is_err());
+    }
+
+    #[test]
+    fn test_launch_places_the_file() {
+        let file = Path::new("/notes/plan.md");
+        let code = launch(Some("code --wait"), file).unwrap();
+        assert_eq!(code.program, "code");
+        assert_eq!(code.args, ["--wait", "/notes/plan.md"]);
+        assert!(!code.terminal);
+
+        let placed = launch(Some("subl {file}:1"), file).unwrap();
+        assert_eq!(placed.args, ["/notes/plan.md:1"]);
+
+        assert!(launch(Some("/usr/bin/nvim"), file).unwrap().terminal);
+        assert!(!launch(None, file).unwrap().terminal);
+        assert!(!launch(Some("  "), file).unwrap().args.is_empty());
+    }
+
+    #[test]
+    fn test_config_roundtrip() {
+        let config = TempDir::new().unwrap();
+        assert_eq!(EditorConfig::load(config.path()).unwrap(), EditorConfig::default());
+        let saved = EditorConfig { editor: Some("code --wait".to_string()) };
+        saved.save(config.path()).unwrap();
+        assert_eq!(EditorConfig::load(config.path()).unwrap(), saved);
+    }
+}
@@ -0,0 +1,243 @@
+//! Favorites
+//!
+//! Starred notes and pinned folders of a workspace, in the order they were
+//! added. The store is kept per workspace in `.mdreader/favorites.json`:
+//!
+//! ```json
+//! { "favorites": [ { "path": "Projects/plan.md", "kind": "note", "added": "..." } ] }
+//! ```
+//!
+//! Renames, moves and deletes of files and folders are applied to the store
+//! (`follow_move` / `follow_delete`), so favorites keep pointing at the
+//! same notes.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use serde::{Deserialize, Serialize};
+use crate::workspace_settings::{relative_key, SETTINGS_DIR};
+
+/// File name of the store inside the settings directory
+pub const FAVORITES_FILE: &str = "favorites.json";
+
+/// What a favorite points at
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum FavoriteKind {
+    /// A starred note
+    Note,
+    /// A pinned folder
+    Folder,
+}
+
+/// A starred note or pinned folder
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Favorite {
+    /// Path relative to the workspace root
+    pub path: String,
+    pub kind: FavoriteKind,
+    /// When it was added (RFC 3339, UTC)
+    pub added: String,
+}
+
+/// Favorites of one workspace
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct FavoritesStore {
+    #[serde(default)]
+    pub favorites: Vec<Favorite>,
+}
+
+impl FavoritesStore {
+    /// Loads the store of a workspace (empty if missing or unreadable)
+    pub fn load(workspace_root: &Path) -> Self {
+        let path = store_path(workspace_root);
+        match fs::read_to_string(&path) {
+            Ok(json) => serde_json::from_str(&json).unwrap_or_else(|e| {
+                log::warn!("⚠️ Ignoring unreadable favorites {:?}: {}", path, e);
+                Self::default()
+            }),
+            Err(_) => Self::default(),
+        }
+    }
+
+    /// Writes the store to the workspace settings directory
+    pub fn save(&self, workspace_root: &Path) -> Result<(), String> {
+        let path = store_path(workspace_root);
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)
+                .map_err(|e| format!("Failed to create settings directory: {}", e))?;
+        }
+        let json = serde_json::to_string_pretty(self)
+            .map_err(|e| format!("Failed to serialize favorites: {}", e))?;
+        fs::write(&path, json)
+            .map_err(|e| format!("Failed to write favorites: {}", e))
+    }
+
+    /// Adds a favorite, or removes it if it is one; returns whether it is
+    /// a favorite now
+    pub fn toggle(&mut self, path: &str, kind: FavoriteKind, now: &str) -> bool {
+        let before = self.favorites.len();
+        self.favorites.retain(|f| f.path != path);
+        if self.favorites.len() != before {
+            return false;
+        }
+        self.favorites.push(Favorite { path: path.to_string(), kind, added: now.to_string() });
+        true
+    }
+
+    /// Follows a renamed file, or every favorite below a renamed folder
+    pub fn rename(&mut self, from: &str, to: &str) -> bool {
+        let prefix = format!("{}/", from);
+        let mut changed = false;
+        for favorite in &mut self.favorites {
+            if favorite.path == from || favorite.path.starts_with(&prefix) {
+                favorite.path = format!("{}{}", to, &favorite.path[from.len()..]);
+                changed = true;
+            }
+        }
+        changed
+    }
+
+    /// Drops the favorites of a deleted file or folder
+    pub fn forget(&mut self, path: &str) -> bool {
+        let prefix = format!("{}/", path);
+        let before = self.favorites.len();
+        self.favorites.retain(|f| f.path != path && !f.path.starts_with(&prefix));
+        self.favorites.len() != before
+    }
+}
+
+fn store_path(workspace_root: &Path) -> PathBuf {
+    workspace_root.join(SETTINGS_DIR).join(FAVORITES_FILE)
+}
+
+/// Access to the favorites of the current workspace, shared by all
+/// commands (internally synchronized, so toggles never overwrite each other)
+///
+/// Following renames and deletes logs failures instead of returning them:
+/// favorites must not break moving or deleting a file.
+#[derive(Clone, Default)]
+pub struct FavoritesTracker {
+    lock: Arc<Mutex<()>>,
+}
+
+impl FavoritesTracker {
+    /// Creates a tracker
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Stars a note or pins a folder (validated path), or removes it from
+    /// the favorites; returns whether it is a favorite now
+    pub fn toggle(&self, workspace: &str, path: &Path) -> Result<bool, String> {
+        let root = Path::new(workspace);
+        let key = relative_key(root, path)
+            .filter(|key| !key.is_empty())
+            .ok_or_else(|| format!("Not in the workspace: {:?}", path))?;
+        let kind = if path.is_dir() { FavoriteKind::Folder } else { FavoriteKind::Note };
+
+        let _guard = self.lock.lock().map_err(|e| format!("Failed to lock favorites: {}", e))?;
+        let mut store = FavoritesStore::load(root);
+        let favorite = store.toggle(&key, kind, &chrono::Utc::now().to_rfc3339());
+        store.save(root)?;
+        Ok(favorite)
+    }
+
+    /// Favorites of a workspace, in the order they were added; favorites
+    /// missing on disk (e.g. not synced yet) are left out but kept
+    pub fn list(&self, workspace: &str) -> Result<Vec<Favorite>, String> {
+        let root = Path::new(workspace);
+        let _guard = self.lock.lock().map_err(|e| format!("Failed to lock favorites: {}", e))?;
+        Ok(FavoritesStore::load(root)
+            .favorites
+            .into_iter()
+            .filter(|f| root.join(&f.path).exists())
+            .collect())
+    }
+
+    /// Moves favorites after a file or folder rename
+    pub fn follow_move(&self, workspace: &str, from: &Path, to: &Path) {
+        let root = Path::new(workspace);
+        if let (Some(from), Some(to)) = (relative_key(root, from), relative_key(root, to)) {
+            self.update(root, |store| store.rename(&from, &to));
+        }
+    }
+
+    /// Drops favorites after a file or folder delete
+    pub fn follow_delete(&self, workspace: &str, path: &Path) {
+        let root = Path::new(workspace);
+        if let Some(path) = relative_key(root, path) {
+            self.update(root, |store| store.forget(&path));
+        }
+    }
+
+    /// Applies a change and persists the store if it changed
+    fn update(&self, root: &Path, change: impl FnOnce(&mut FavoritesStore) -> bool) {
+        let Ok(_guard) = self.lock.lock() else {
+            log::warn!("⚠️ Favorites lock poisoned");
+            return;
+        };
+        let mut store = FavoritesStore::load(root);
+        if change(&mut store) {
+            if let Err(e) = store.save(root) {
+                log::warn!("⚠️ Favorites not saved: {}", e);
+            }
+        }
+    }
+}
+
+// ============================================================================
+// TESTS
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    const NOW: &str = "2024-05-01T10:00:00+00:00";
+
+    #[test]
+    fn test_toggle_rename_and_forget() {
+        let mut store = FavoritesStore::default();
+        assert!(store.toggle("Projects", FavoriteKind::Folder, NOW));
+        assert!(store.toggle("Projects/plan.md", FavoriteKind::Note, NOW));
+        assert!(store.toggle("Projects-old/a.md", FavoriteKind::Note, NOW));
+        assert!(!store.toggle("Projects-old/a.md", FavoriteKind::Note, NOW));
+
+        assert!(store.rename("Projects", "Archive/Projects"));
+        let paths: Vec<&str> = store.favorites.iter().map(|f| f.path.as_str()).collect();
+        assert_eq!(paths, ["Archive/Projects", "Archive/Projects/plan.md"]);
+
+        assert!(!store.rename("Projects", "Elsewhere"));
+        assert!(store.forget("Archive/Projects"));
+        assert!(store.favorites.is_empty());
+    }
+
+    #[test]
+    fn test_tracker_persists_and_follows_moves() {
+        let dir = TempDir::new().unwrap();
+        let root = dir.path().canonicalize().unwrap();
+        let workspace = root.to_str().unwrap();
+        fs::create_dir_all(root.join("Notes")).unwrap();
+        fs::write(root.join("Notes/a.md"), "# A").unwrap();
+
+        let tracker = FavoritesTracker::new();
+        assert!(tracker.toggle(workspace, &root.join("Notes/a.md")).unwrap());
+        assert!(tracker.toggle(workspace, &root.join("Notes")).unwrap());
+        assert!(tracker.toggle(workspace, &root).is_err(), "The workspace itself is no favorite");
+
+        fs::rename(root.join("Notes"), root.join("Archive")).unwrap();
+        tracker.follow_move(workspace, &root.join("Notes"), &root.join("Archive"));
+        let favorites = FavoritesTracker::new().list(workspace).unwrap();
+        let listed: Vec<(&str, FavoriteKind)> = favorites.iter().map(|f| (f.path.as_str(), f.kind)).collect();
+        assert_eq!(listed, [("Archive/a.md", FavoriteKind::Note), ("Archive", FavoriteKind::Folder)]);
+
+        // Missing on disk: hidden, but kept until deleted through the app
+        fs::remove_file(root.join("Archive/a.md")).unwrap();
+        assert_eq!(tracker.list(workspace).unwrap().len(), 1);
+        assert_eq!(FavoritesStore::load(&root).favorites.len(), 2);
+        tracker.follow_delete(workspace, &root.join("Archive/a.md"));
+        assert_eq!(FavoritesStore::load(&root).favorites.len(), 1);
+    }
+}
@@ -0,0 +1,181 @@
+//! File Access Recovery
+//!
+//! When a save fails with "permission denied" (EACCES/EPERM), the frontend
+//! offers a recovery flow instead of a dead end. `diagnose` explains why
+//! the file cannot be written:
+//!
+//! - the file itself is read-only (the Windows read-only attribute, or no
+//!   owner write bit on Unix), which `clear_readonly` can fix
+//! - its folder is read-only, so it cannot be created or replaced
+//! - on Windows, which processes hold the file open (through the Restart
+//!   Manager, as Explorer does for "file in use")
+
+use std::fs;
+use std::path::Path;
+use serde::{Deserialize, Serialize};
+use crate::file_meta::FilePermissions;
+
+/// A process holding a file open
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LockHolder {
+    pub pid: u32,
+    /// Application name as shown by the system
+    pub name: String,
+}
+
+/// Why a file may not be writable
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AccessDiagnosis {
+    pub path: String,
+    pub exists: bool,
+    /// The file is read-only; `clear_readonly` can make it writable
+    pub read_only: bool,
+    /// The folder is read-only, so files in it cannot be created or replaced
+    pub folder_read_only: bool,
+    /// Permission bits and owner (`None` if the file does not exist)
+    pub permissions: Option<FilePermissions>,
+    /// Processes that have the file open (Windows only, empty elsewhere)
+    pub lock_holders: Vec<LockHolder>,
+}
+
+/// Works out why `path` may not be writable
+pub fn diagnose(path: &Path) -> AccessDiagnosis {
+    let metadata = fs::metadata(path).ok();
+    let folder_read_only = path
+        .parent()
+        .and_then(|parent| fs::metadata(parent).ok())
+        .is_some_and(|meta| meta.permissions().readonly());
+
+    AccessDiagnosis {
+        path: path.to_string_lossy().to_string(),
+        exists: metadata.is_some(),
+        read_only: metadata.as_ref().is_some_and(|meta| meta.permissions().readonly()),
+        folder_read_only,
+        permissions: metadata.as_ref().map(FilePermissions::from_metadata),
+        lock_holders: if metadata.is_some() { lock_holders(path) } else { Vec::new() },
+    }
+}
+
+/// Checks if a file exists and is read-only
+pub fn is_read_only(path: &Path) -> bool {
+    fs::metadata(path).is_ok_and(|meta| meta.permissions().readonly())
+}
+
+/// Makes a read-only file writable again
+///
+/// Clears the read-only attribute on Windows and adds the owner write bit
+/// on Unix (`chmod u+w`), leaving the other permission bits as they are.
+///
+/// # Returns
+/// * `Ok(true)` - If the file was read-only
+/// * `Ok(false)` - If it was already writable
+pub fn clear_readonly(path: &Path) -> Result<bool, String> {
+    let metadata = fs::metadata(path)
+        .map_err(|e| format!("Failed to read file metadata: {}", e))?;
+    if !metadata.is_file() {
+        return Err(format!("Not a file: {}", path.display()));
+    }
+    if !metadata.permissions().readonly() {
+        return Ok(false);
+    }
+
+    let mut permissions = metadata.permissions();
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        permissions.set_mode(permissions.mode() | 0o200);
+    }
+    #[cfg(not(unix))]
+    permissions.set_readonly(false);
+
+    fs::set_permissions(path, permissions)
+        .map_err(|e| format!("Failed to clear read-only attribute: {}", e))?;
+    Ok(true)
+}
+
+/// Processes that have `path` open, as reported by the Restart Manager
+#[cfg(windows)]
+pub fn lock_holders(path: &Path) -> Vec<LockHolder> {
+    use std::os::windows::ffi::OsStrExt;
+    use windows_sys::Win32::System::RestartManager::{
+        RmEndSession, RmGetList, RmRegisterResources, RmStartSession, CCH_RM_SESSION_KEY, RM_PROCESS_INFO,
+    };
+    const ERROR_SUCCESS: u32 = 0;
+    const ERROR_MORE_DATA: u32 = 234;
+
+    let name: Vec<u16> = path.as_os_str().encode_wide().chain(std::iter::once(0)).collect();
+    let mut session = 0u32;
+    let mut key = [0u16; CCH_RM_SESSION_KEY as usize + 1];
+
+    // SAFETY: All pointers are valid for the duration of each call, and
+    // `infos` has room for `count` entries
+    unsafe {
+        if RmStartSession(&mut session, 0, key.as_mut_ptr()) != ERROR_SUCCESS {
+            return Vec::new();
+        }
+        let files = [name.as_ptr()];
+        let mut holders = Vec::new();
+        if RmRegisterResources(session, 1, files.as_ptr(), 0, std::ptr::null(), 0, std::ptr::null()) == ERROR_SUCCESS {
+            let (mut needed, mut count, mut reasons) = (0u32, 0u32, 0u32);
+            let status = RmGetList(session, &mut needed, &mut count, std::ptr::null_mut(), &mut reasons);
+            if status == ERROR_MORE_DATA && needed > 0 {
+                let mut infos: Vec<RM_PROCESS_INFO> = vec![std::mem::zeroed(); needed as usize];
+                count = needed;
+                if RmGetList(session, &mut needed, &mut count, infos.as_mut_ptr(), &mut reasons) == ERROR_SUCCESS {
+                    holders = infos[..count as usize]
+                        .iter()
+                        .map(|info| {
+                            let len = info.strAppName.iter().position(|&c| c == 0).unwrap_or(info.strAppName.len());
+                            LockHolder {
+                                pid: info.Process.dwProcessId,
+                                name: String::from_utf16_lossy(&info.strAppName[..len]),
+                            }
+                        })
+                        .collect();
+                }
+            }
+        }
+        RmEndSession(session);
+        holders
+    }
+}
+
+/// Processes that have `path` open (only known on Windows)
+#[cfg(not(windows))]
+pub fn lock_holders(_path: &Path) -> Vec<LockHolder> {
+    Vec::new()
+}
+
+// ============================================================================
+// TESTS
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_diagnose_and_clear_readonly() {
+        let workspace = TempDir::new().unwrap();
+        let note = workspace.path().join("locked.md");
+        fs::write(&note, "# Locked").unwrap();
+        let mut permissions = fs::metadata(&note).unwrap().permissions();
+        permissions.set_readonly(true);
+        fs::set_permissions(&note, permissions).unwrap();
+
+        let diagnosis = diagnose(&note);
+        assert!(diagnosis.exists && diagnosis.read_only);
+        assert!(!diagnosis.folder_read_only);
+        assert!(is_read_only(&note));
+
+        assert_eq!(clear_readonly(&note), Ok(true));
+        assert!(!is_read_only(&note));
+        fs::write(&note, "# Unlocked").unwrap();
+        assert_eq!(clear_readonly(&note), Ok(false));
+
+        let missing = diagnose(&workspace.path().join("missing.md"));
+        assert!(!missing.exists && !missing.read_only && missing.permissions.is_none());
+        assert!(clear_readonly(workspace.path()).is_err());
+    }
+}
@@ -0,0 +1,149 @@
+//! Filesystem Metadata Helpers
+//!
+//! Converts `std::fs::Metadata` into values the frontend can parse and sort:
+//! timestamps as RFC 3339 strings (UTC, millisecond precision) alongside
+//! epoch milliseconds, and permission/owner information where the platform
+//! provides it. Times a platform or filesystem does not record (e.g. creation
+//! time on some Linux filesystems) are `None`.
+
+use std::fs::Metadata;
+use std::time::SystemTime;
+use chrono::{DateTime, SecondsFormat, Utc};
+use serde::{Deserialize, Serialize};
+
+/// Created/accessed times and the modified time in epoch milliseconds
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct FileTimes {
+    #[serde(default)]
+    pub modified_ms: Option<i64>,
+    #[serde(default)]
+    pub created: Option<String>,
+    #[serde(default)]
+    pub created_ms: Option<i64>,
+    #[serde(default)]
+    pub accessed: Option<String>,
+    #[serde(default)]
+    pub accessed_ms: Option<i64>,
+}
+
+impl FileTimes {
+    /// Reads all available times of a file
+    pub fn from_metadata(metadata: &Metadata) -> Self {
+        let created = metadata.created().ok();
+        let accessed = metadata.accessed().ok();
+        Self {
+            modified_ms: metadata.modified().ok().map(epoch_ms),
+            created: created.map(iso_8601),
+            created_ms: created.map(epoch_ms),
+            accessed: accessed.map(iso_8601),
+            accessed_ms: accessed.map(epoch_ms),
+        }
+    }
+}
+
+/// Permission and owner information
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct FilePermissions {
+    pub readonly: bool,
+    /// Unix permission bits as `rwxr-xr-x`
+    #[serde(default)]
+    pub mode: Option<String>,
+    /// Unix owner user id
+    #[serde(default)]
+    pub uid: Option<u32>,
+    /// Unix owner group id
+    #[serde(default)]
+    pub gid: Option<u32>,
+}
+
+impl FilePermissions {
+    /// Reads the permissions of a file
+    #[cfg(unix)]
+    pub fn from_metadata(metadata: &Metadata) -> Self {
+        use std::os::unix::fs::MetadataExt;
+        Self {
+            readonly: metadata.permissions().readonly(),
+            mode: Some(mode_string(metadata.mode())),
+            uid: Some(metadata.uid()),
+            gid: Some(metadata.gid()),
+        }
+    }
+
+    /// Reads the permissions of a file
+    #[cfg(not(unix))]
+    pub fn from_metadata(metadata: &Metadata) -> Self {
+        Self {
+            readonly: metadata.permissions().readonly(),
+            ..Self::default()
+        }
+    }
+}
+
+/// Modified time as RFC 3339, falling back to the Unix epoch when unknown
+pub fn modified_iso(metadata: &Metadata) -> String {
+    iso_8601(metadata.modified().unwrap_or(SystemTime::UNIX_EPOCH))
+}
+
+/// Formats a time as RFC 3339 in UTC, e.g. `2024-05-17T09:30:00.000Z`
+pub fn iso_8601(time: SystemTime) -> String {
+    DateTime::<Utc>::from(time).to_rfc3339_opts(SecondsFormat::Millis, true)
+}
+
+/// Milliseconds since the Unix epoch (negative before 1970)
+pub fn epoch_ms(time: SystemTime) -> i64 {
+    match time.duration_since(SystemTime::UNIX_EPOCH) {
+        Ok(after) => after.as_millis() as i64,
+        Err(before) => -(before.duration().as_millis() as i64),
+    }
+}
+
+/// Renders the permission bits of a Unix mode
+#[cfg(any(unix, test))]
+fn mode_string(mode: u32) -> String {
+    const FLAGS: [(u32, char); 9] = [
+        (0o400, 'r'), (0o200, 'w'), (0o100, 'x'),
+        (0o040, 'r'), (0o020, 'w'), (0o010, 'x'),
+        (0o004, 'r'), (0o002, 'w'), (0o001, 'x'),
+    ];
+    FLAGS.iter().map(|&(bit, c)| if mode & bit != 0 { c } else { '-' }).collect()
+}
+
+// ============================================================================
+// TESTS
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn test_time_conversions() {
+        let time = SystemTime::UNIX_EPOCH + Duration::from_millis(1_715_938_200_250);
+        assert_eq!(epoch_ms(time), 1_715_938_200_250);
+        assert!(iso_8601(time).starts_with("2024-05-17T09:30:00"));
+        assert!(iso_8601(time).ends_with('Z'));
+
+        let before = SystemTime::UNIX_EPOCH - Duration::from_millis(1500);
+        assert_eq!(epoch_ms(before), -1500);
+    }
+
+    #[test]
+    fn test_mode_string() {
+        assert_eq!(mode_string(0o100644), "rw-r--r--");
+        assert_eq!(mode_string(0o40755), "rwxr-xr-x");
+    }
+
+    #[test]
+    fn test_from_metadata() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("note.md");
+        std::fs::write(&path, "# Note").unwrap();
+        let metadata = std::fs::metadata(&path).unwrap();
+
+        let times = FileTimes::from_metadata(&metadata);
+        assert!(times.modified_ms.unwrap() > 0);
+        assert!(!modified_iso(&metadata).starts_with("1970"));
+        assert!(!FilePermissions::from_metadata(&metadata).readonly);
+    }
+}
@@ -0,0 +1,322 @@
+//! Markdown Flavors
+//!
+//! Converts notes between CommonMark, GitHub Flavored Markdown (GFM) and
+//! MultiMarkdown, so a note exported to another tool renders there as it
+//! does in the editor. Only the constructs the flavors disagree on are
+//! rewritten:
+//!
+//! | Construct | CommonMark | GFM | MultiMarkdown |
+//! |-----------|------------|-----|---------------|
+//! | Task item | `- ☐ item` / `- ☑ item` | `- [ ] item` | `- ☐ item` |
+//! | Table | HTML `<table>` | pipe table | pipe table |
+//! | Footnote | `<sup>` link to a numbered list at the end | `[^1]` | `[^1]` |
+//! | Highlight | `<mark>x</mark>` | `<mark>x</mark>` | `{==x==}` |
+//! | Callout | quote with a bold title | `> [!NOTE]` alert | quote with a bold title |
+//!
+//! GFM input is read the way the editor writes it, with `==highlights==`
+//! and `> [!type] Title` callouts. `<mark>` and `☐`/`☑` items are read from
+//! any flavor. Frontmatter and fenced code are left as written.
+
+use std::collections::HashSet;
+use serde::{Deserialize, Serialize};
+use crate::frontmatter;
+use crate::links::NoteLookup;
+use crate::references::{footnote_marker, tidy_footnotes};
+use crate::render::{callout_family, callout_marker, default_callout_title, is_table_start, render_markdown};
+
+/// A markdown dialect
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum MarkdownFlavor {
+    CommonMark,
+    Gfm,
+    MultiMarkdown,
+}
+
+/// A converted note and what was rewritten
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct FlavorConversion {
+    pub content: String,
+    pub tasks: usize,
+    pub tables: usize,
+    pub footnotes: usize,
+    pub highlights: usize,
+    pub callouts: usize,
+}
+
+/// Converts note content from one flavor to another
+pub fn convert_flavor(content: &str, from: MarkdownFlavor, to: MarkdownFlavor) -> FlavorConversion {
+    let mut result = FlavorConversion::default();
+
+    // Footnotes become a numbered list, so number them by first reference
+    let html_footnotes = from != MarkdownFlavor::CommonMark && to == MarkdownFlavor::CommonMark;
+    let content = if html_footnotes { tidy_footnotes(content).0 } else { content.to_string() };
+
+    let (_, body) = frontmatter::split(&content);
+    let header = &content[..content.len() - body.len()];
+    let lines: Vec<String> = body.lines().map(String::from).collect();
+    let defined: HashSet<String> = if html_footnotes {
+        lines.iter().filter_map(|l| footnote_marker(l)).map(|(label, _)| label.to_string()).collect()
+    } else {
+        HashSet::new()
+    };
+
+    let mut out: Vec<String> = header.lines().map(String::from).collect();
+    let mut cited: HashSet<String> = HashSet::new();
+    let mut notes_started = false;
+    let mut in_fence = false;
+    let mut i = 0;
+    while i < lines.len() {
+        let line = &lines[i];
+        let trimmed = line.trim_start();
+        if trimmed.starts_with("```") || trimmed.starts_with("~~~") {
+            in_fence = !in_fence;
+        }
+        if in_fence || trimmed.starts_with("```") || trimmed.starts_with("~~~") {
+            out.push(line.clone());
+            i += 1;
+            continue;
+        }
+
+        if from != MarkdownFlavor::CommonMark && to == MarkdownFlavor::CommonMark && is_table_start(&lines, i) {
+            let end = (i + 2..lines.len())
+                .find(|&k| lines[k].trim().is_empty() || !lines[k].contains('|'))
+                .unwrap_or(lines.len());
+            let table = render_markdown(&lines[i..end].join("\n"), "", &NoteLookup::default(), false);
+            out.extend(table.lines().map(String::from));
+            result.tables += 1;
+            i = end;
+            continue;
+        }
+
+        let starts_quote = i == 0 || !lines[i - 1].trim_start().starts_with('>');
+        if let Some((prefix, kind, title)) = callout(line).filter(|_| from == MarkdownFlavor::Gfm && starts_quote) {
+            let mut converted = Vec::new();
+            if to == MarkdownFlavor::Gfm {
+                converted.push(format!("{}[!{}]", prefix, callout_family(kind).to_uppercase()));
+                if !title.is_empty() {
+                    converted.push(format!("{}**{}**", prefix, title));
+                }
+            } else {
+                let title = if title.is_empty() { default_callout_title(kind) } else { title.to_string() };
+                converted.push(format!("{}**{}**", prefix, title));
+                if lines.get(i + 1).is_some_and(|next| next.trim_start().starts_with('>')) {
+                    converted.push(prefix.trim_end().to_string());
+                }
+            }
+            if converted != [line.clone()] {
+                result.callouts += 1;
+            }
+            out.extend(converted);
+            i += 1;
+            continue;
+        }
+
+        let mut line = line.clone();
+        if let Some((label, text)) = footnote_marker(&line).filter(|(label, _)| defined.contains(*label)) {
+            if !notes_started {
+                out.extend(["---".to_string(), String::new()]);
+                notes_started = true;
+            }
+            line = format!("{}. <a id=\"fn-{}\"></a>{} [↩](#fnref-{})", label, label, text.trim(), label);
+            result.footnotes += 1;
+        }
+        if let Some(task) = convert_task(&line, from, to) {
+            line = task;
+            result.tasks += 1;
+        }
+        out.push(convert_inline(&line, from, to, &defined, &mut cited, &mut result));
+        i += 1;
+    }
+
+    let newline = if content.contains("\r\n") { "\r\n" } else { "\n" };
+    result.content = out.join(newline);
+    if content.ends_with('\n') && !out.is_empty() {
+        result.content.push_str(newline);
+    }
+    result
+}
+
+// Helper: Prefix, type and title of a `> [!type]` callout line (a fold
+// marker after the type is dropped)
+fn callout(line: &str) -> Option<(&str, &str, &str)> {
+    let quoted = line.trim_start().strip_prefix('>')?.trim_start();
+    let prefix = &line[..line.len() - quoted.len()];
+    let (kind, _, title) = callout_marker(quoted)?;
+    Some((prefix, kind, title))
+}
+
+// Helper: A task list item in the notation of `to`, if it changes
+fn convert_task(line: &str, from: MarkdownFlavor, to: MarkdownFlavor) -> Option<String> {
+    let marker = line.len() - line.trim_start().len();
+    let rest = &line[marker..];
+    let bullet = if rest.starts_with(['-', '*', '+']) {
+        1
+    } else {
+        let digits = rest.len() - rest.trim_start_matches(|c: char| c.is_ascii_digit()).len();
+        if digits == 0 || !rest[digits..].starts_with(['.', ')']) {
+            return None;
+        }
+        digits + 1
+    };
+    let text = rest[bullet..].strip_prefix(' ')?.trim_start();
+    let start = line.len() - text.len();
+
+    let (done, len) = match text {
+        t if from == MarkdownFlavor::Gfm && t.starts_with("[ ]") => (false, 3),
+        t if from == MarkdownFlavor::Gfm && (t.starts_with("[x]") || t.starts_with("[X]")) => (true, 3),
+        t if t.starts_with('☐') => (false, '☐'.len_utf8()),
+        t if t.starts_with('☑') => (true, '☑'.len_utf8()),
+        _ => return None,
+    };
+    if !(text[len..].is_empty() || text[len..].starts_with(' ')) {
+        return None;
+    }
+
+    let checkbox = match (to, done) {
+        (MarkdownFlavor::Gfm, false) => "[ ]",
+        (MarkdownFlavor::Gfm, true) => "[x]",
+        (_, false) => "☐",
+        (_, true) => "☑",
+    };
+    (text[..len] != *checkbox).then(|| format!("{}{}{}", &line[..start], checkbox, &text[len..]))
+}
+
+// Helper: Rewrites the highlights and, for CommonMark, the footnote
+// references of a line, leaving code spans alone
+fn convert_inline(
+    line: &str,
+    from: MarkdownFlavor,
+    to: MarkdownFlavor,
+    footnotes: &HashSet<String>,
+    cited: &mut HashSet<String>,
+    result: &mut FlavorConversion,
+) -> String {
+    let mut out = String::with_capacity(line.len());
+    let mut i = 0;
+    while i < line.len() {
+        let rest = &line[i..];
+
+        if rest.starts_with('`') {
+            let ticks = rest.len() - rest.trim_start_matches('`').len();
+            let end = rest[ticks..].find(&rest[..ticks]).map_or(ticks, |end| ticks + end + ticks);
+            out.push_str(&rest[..end]);
+            i += end;
+            continue;
+        }
+
+        if let Some((text, len)) = highlight_at(rest, from).filter(|_| !line[..i].ends_with('=')) {
+            let converted = match to {
+                MarkdownFlavor::MultiMarkdown => format!("{{=={}==}}", text),
+                _ => format!("<mark>{}</mark>", text),
+            };
+            if converted != rest[..len] {
+                result.highlights += 1;
+            }
+            out.push_str(&converted);
+            i += len;
+            continue;
+        }
+
+        if let Some(label) = rest
+            .strip_prefix("[^")
+            .and_then(|r| r.find(']').map(|end| &r[..end]))
+            .filter(|label| footnotes.contains(*label))
+        {
+            // Only the first reference is the back link's target
+            let id = if cited.insert(label.to_string()) { format!(" id=\"fnref-{}\"", label) } else { String::new() };
+            out.push_str(&format!("<sup><a href=\"#fn-{}\"{}>{}</a></sup>", label, id, label));
+            i += label.len() + 3;
+            continue;
+        }
+
+        let c = rest.chars().next().expect("rest is not empty");
+        let len = if c == '\\' { rest.chars().take(2).map(char::len_utf8).sum() } else { c.len_utf8() };
+        out.push_str(&rest[..len]);
+        i += len;
+    }
+    out
+}
+
+// Helper: Text and length of the highlight starting `rest`
+fn highlight_at(rest: &str, from: MarkdownFlavor) -> Option<(&str, usize)> {
+    if let Some(inner) = rest.strip_prefix("<mark>") {
+        let end = inner.find("</mark>")?;
+        return Some((&inner[..end], "<mark>".len() + end + "</mark>".len()));
+    }
+    if from == MarkdownFlavor::MultiMarkdown {
+        let inner = rest.strip_prefix("{==")?;
+        let end = inner.find("==}")?;
+        return Some((&inner[..end], end + 6));
+    }
+    if from == MarkdownFlavor::Gfm {
+        let inner = rest.strip_prefix("==")?;
+        if inner.starts_with(|c: char| c == '=' || c.is_whitespace()) {
+            return None;
+        }
+        let end = inner.find("==")?;
+        let text = &inner[..end];
+        if text.ends_with(char::is_whitespace) || inner[end + 2..].starts_with('=') {
+            return None;
+        }
+        return Some((text, end + 4));
+    }
+    None
+}
+
+// ============================================================================
+// TESTS
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const NOTE: &str = "---\ntitle: Study\n---\n\
+        > [!tip]- Remember\n> Cite sources[^src].\n\n\
+        - [ ] read ==chapter 2==\n- [x] take notes\n\n\
+        | Term | Meaning |\n|:-----|--------:|\n| `a|b` | ==x== |\n\n\
+        Keep `==code==` as is.\n\n\
+        [^src]: The paper.\n";
+
+    #[test]
+    fn test_gfm_to_multimarkdown_and_back() {
+        let mmd = convert_flavor(NOTE, MarkdownFlavor::Gfm, MarkdownFlavor::MultiMarkdown);
+        assert_eq!(
+            mmd.content,
+            "---\ntitle: Study\n---\n\
+             > **Remember**\n>\n> Cite sources[^src].\n\n\
+             - ☐ read {==chapter 2==}\n- ☑ take notes\n\n\
+             | Term | Meaning |\n|:-----|--------:|\n| `a|b` | {==x==} |\n\n\
+             Keep `==code==` as is.\n\n\
+             [^src]: The paper.\n"
+        );
+        assert_eq!((mmd.tasks, mmd.tables, mmd.footnotes, mmd.highlights, mmd.callouts), (2, 0, 0, 2, 1));
+
+        let gfm = convert_flavor(&mmd.content, MarkdownFlavor::MultiMarkdown, MarkdownFlavor::Gfm);
+        assert!(gfm.content.contains("- [ ] read <mark>chapter 2</mark>\n- [x] take notes\n"));
+        assert!(gfm.content.contains("| `a|b` | <mark>x</mark> |"));
+
+        let alert = convert_flavor("> [!bug] Crash\r\n> Details\r\n", MarkdownFlavor::Gfm, MarkdownFlavor::Gfm);
+        assert_eq!(alert.content, "> [!CAUTION]\r\n> **Crash**\r\n> Details\r\n");
+    }
+
+    #[test]
+    fn test_gfm_to_commonmark() {
+        let cm = convert_flavor(NOTE, MarkdownFlavor::Gfm, MarkdownFlavor::CommonMark);
+        assert!(cm.content.starts_with("---\ntitle: Study\n---\n> **Remember**\n>\n"));
+        assert!(cm.content.contains(
+            "> Cite sources<sup><a href=\"#fn-1\" id=\"fnref-1\">1</a></sup>.\n"
+        ));
+        assert!(cm.content.contains("- ☐ read <mark>chapter 2</mark>\n"));
+        assert!(cm.content.contains("<table>\n<thead>\n<tr>\n<th style=\"text-align:left\">Term</th>\n"));
+        assert!(cm.content.contains("<td style=\"text-align:right\"><mark>x</mark></td>\n"));
+        assert!(cm.content.contains("Keep `==code==` as is."));
+        assert!(cm.content.ends_with("---\n\n1. <a id=\"fn-1\"></a>The paper. [↩](#fnref-1)\n"));
+        assert_eq!((cm.tasks, cm.tables, cm.footnotes, cm.highlights, cm.callouts), (2, 1, 1, 1, 1));
+
+        // Nothing to do for plain CommonMark
+        let plain = "# Title\n\nSome ==text== and - [ ] this.\n";
+        assert_eq!(convert_flavor(plain, MarkdownFlavor::CommonMark, MarkdownFlavor::CommonMark).content, plain);
+    }
+}
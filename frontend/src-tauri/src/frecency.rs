@@ -0,0 +1,353 @@
+//! Frecency Tracking for Recent Files
+//!
+//! Ranks files by how often *and* how recently they were opened or saved,
+//! replacing the flat `recent_files` list of the workspace config. The store
+//! is kept per workspace in `.mdreader/frecency.json`:
+//!
+//! ```json
+//! { "files": { "Projects/plan.md": { "count": 12, "visits": [...] } } }
+//! ```
+//!
+//! Each file keeps its total visit count and its most recent visits. The
+//! score is `count * average visit weight`, where a visit weighs more the
+//! newer it is (and saves weigh more than opens). Repeated visits within
+//! `VISIT_COALESCE_SECS` count once, so autosave does not inflate scores.
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use serde::{Deserialize, Serialize};
+use crate::notes::relative_path;
+use crate::workspace_settings::SETTINGS_DIR;
+
+/// File name of the store inside the settings directory
+pub const FRECENCY_FILE: &str = "frecency.json";
+
+/// Number of recent visits sampled per file
+pub const MAX_SAMPLED_VISITS: usize = 10;
+
+/// Visits of the same file closer together than this count once
+pub const VISIT_COALESCE_SECS: i64 = 60;
+
+/// What the user did with a file
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum VisitKind {
+    Open,
+    Save,
+}
+
+/// A single sampled visit
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Visit {
+    /// Unix timestamp (seconds)
+    pub at: i64,
+    pub kind: VisitKind,
+}
+
+/// Visit history of one file
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct FrecencyEntry {
+    /// Total number of visits ever recorded
+    pub count: u64,
+    /// Most recent visits, oldest first
+    pub visits: Vec<Visit>,
+}
+
+impl FrecencyEntry {
+    /// Combined frequency/recency score at time `now`
+    pub fn score(&self, now: i64) -> f64 {
+        if self.visits.is_empty() {
+            return 0.0;
+        }
+        let total: f64 = self.visits.iter().map(|v| visit_weight(v, now)).sum();
+        self.count as f64 * total / self.visits.len() as f64
+    }
+
+    fn last_visited(&self) -> i64 {
+        self.visits.last().map_or(0, |v| v.at)
+    }
+}
+
+/// Weight of a visit by age (Firefox-style buckets)
+fn visit_weight(visit: &Visit, now: i64) -> f64 {
+    let age_days = (now - visit.at).max(0) / 86_400;
+    let recency = match age_days {
+        0..=3 => 100.0,
+        4..=14 => 70.0,
+        15..=31 => 50.0,
+        32..=90 => 30.0,
+        _ => 10.0,
+    };
+    match visit.kind {
+        VisitKind::Open => recency,
+        VisitKind::Save => recency * 1.5,
+    }
+}
+
+/// A ranked file returned to the frontend
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FrequentFile {
+    /// Path relative to the workspace root
+    pub path: String,
+    pub score: f64,
+    pub visit_count: u64,
+    /// Unix timestamp (seconds) of the latest visit
+    pub last_visited: i64,
+}
+
+/// Frecency data of one workspace
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct FrecencyStore {
+    #[serde(default)]
+    pub files: BTreeMap<String, FrecencyEntry>,
+}
+
+impl FrecencyStore {
+    /// Loads the store of a workspace (empty if missing or unreadable)
+    pub fn load(workspace_root: &Path) -> Self {
+        let path = store_path(workspace_root);
+        match fs::read_to_string(&path) {
+            Ok(json) => serde_json::from_str(&json).unwrap_or_else(|e| {
+                log::warn!("⚠️ Ignoring unreadable frecency store {:?}: {}", path, e);
+                Self::default()
+            }),
+            Err(_) => Self::default(),
+        }
+    }
+
+    /// Writes the store to the workspace settings directory
+    pub fn save(&self, workspace_root: &Path) -> Result<(), String> {
+        let path = store_path(workspace_root);
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)
+                .map_err(|e| format!("Failed to create settings directory: {}", e))?;
+        }
+        let json = serde_json::to_string_pretty(self)
+            .map_err(|e| format!("Failed to serialize frecency store: {}", e))?;
+        fs::write(&path, json)
+            .map_err(|e| format!("Failed to write frecency store: {}", e))
+    }
+
+    /// Records a visit; returns false if it was coalesced with the previous one
+    pub fn record(&mut self, path: &str, kind: VisitKind, now: i64) -> bool {
+        let entry = self.files.entry(path.to_string()).or_default();
+
+        if let Some(last) = entry.visits.last_mut() {
+            if now - last.at < VISIT_COALESCE_SECS {
+                // A save upgrades a just-recorded open
+                if kind == VisitKind::Save && last.kind == VisitKind::Open {
+                    last.kind = VisitKind::Save;
+                    return true;
+                }
+                return false;
+            }
+        }
+
+        entry.count += 1;
+        entry.visits.push(Visit { at: now, kind });
+        if entry.visits.len() > MAX_SAMPLED_VISITS {
+            entry.visits.remove(0);
+        }
+        true
+    }
+
+    /// Moves the history of a renamed file, or of every file below a renamed folder
+    pub fn rename(&mut self, from: &str, to: &str) -> bool {
+        let prefix = format!("{}/", from);
+        let moved: Vec<String> = self.files
+            .keys()
+            .filter(|k| *k == from || k.starts_with(&prefix))
+            .cloned()
+            .collect();
+
+        for key in &moved {
+            if let Some(entry) = self.files.remove(key) {
+                let new_key = format!("{}{}", to, &key[from.len()..]);
+                self.files.insert(new_key, entry);
+            }
+        }
+        !moved.is_empty()
+    }
+
+    /// Drops the history of a deleted file or folder
+    pub fn forget(&mut self, path: &str) -> bool {
+        let prefix = format!("{}/", path);
+        let before = self.files.len();
+        self.files.retain(|k, _| k != path && !k.starts_with(&prefix));
+        self.files.len() != before
+    }
+
+    /// Highest-scoring files, best first
+    pub fn top(&self, limit: usize, now: i64) -> Vec<FrequentFile> {
+        let mut ranked: Vec<FrequentFile> = self.files
+            .iter()
+            .map(|(path, entry)| FrequentFile {
+                path: path.clone(),
+                score: entry.score(now),
+                visit_count: entry.count,
+                last_visited: entry.last_visited(),
+            })
+            .collect();
+
+        ranked.sort_by(|a, b| {
+            b.score.total_cmp(&a.score).then_with(|| b.last_visited.cmp(&a.last_visited))
+        });
+        ranked.truncate(limit);
+        ranked
+    }
+}
+
+fn store_path(workspace_root: &Path) -> PathBuf {
+    workspace_root.join(SETTINGS_DIR).join(FRECENCY_FILE)
+}
+
+/// In-memory store of the current workspace, shared by all commands
+/// (internally synchronized)
+///
+/// The store is loaded on first use and reloaded when the workspace changes.
+/// Failures are logged, never returned: tracking must not break opening or
+/// saving a file.
+#[derive(Clone, Default)]
+pub struct FrecencyTracker {
+    current: Arc<Mutex<Option<(PathBuf, FrecencyStore)>>>,
+}
+
+impl FrecencyTracker {
+    /// Creates a tracker with no workspace loaded
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Records an open or save of a workspace file
+    pub fn record(&self, workspace: &str, file: &Path, kind: VisitKind) {
+        let now = chrono::Utc::now().timestamp();
+        self.update(workspace, |store, root| store.record(&relative_path(root, file), kind, now));
+    }
+
+    /// Moves history after a file or folder rename
+    pub fn follow_move(&self, workspace: &str, from: &Path, to: &Path) {
+        self.update(workspace, |store, root| {
+            store.rename(&relative_path(root, from), &relative_path(root, to))
+        });
+    }
+
+    /// Drops history after a file or folder delete
+    pub fn follow_delete(&self, workspace: &str, path: &Path) {
+        self.update(workspace, |store, root| store.forget(&relative_path(root, path)));
+    }
+
+    /// Highest-scoring files of a workspace
+    pub fn top(&self, workspace: &str, limit: usize) -> Result<Vec<FrequentFile>, String> {
+        let root = canonical_root(workspace);
+        let mut current = self.current
+            .lock()
+            .map_err(|e| format!("Failed to lock frecency store: {}", e))?;
+        let store = Self::store_for(&mut current, &root);
+        Ok(store.top(limit, chrono::Utc::now().timestamp()))
+    }
+
+    /// Applies a change and persists the store if it changed
+    fn update(&self, workspace: &str, change: impl FnOnce(&mut FrecencyStore, &Path) -> bool) {
+        let root = canonical_root(workspace);
+        let Ok(mut current) = self.current.lock() else {
+            log::warn!("⚠️ Frecency store lock poisoned");
+            return;
+        };
+
+        let store = Self::store_for(&mut current, &root);
+        if change(store, &root) {
+            if let Err(e) = store.save(&root) {
+                log::warn!("⚠️ Frecency not saved: {}", e);
+            }
+        }
+    }
+
+    fn store_for<'a>(current: &'a mut Option<(PathBuf, FrecencyStore)>, root: &Path) -> &'a mut FrecencyStore {
+        if current.as_ref().map_or(true, |(loaded, _)| loaded != root) {
+            *current = Some((root.to_path_buf(), FrecencyStore::load(root)));
+        }
+        &mut current.as_mut().unwrap().1
+    }
+}
+
+/// Validated paths are canonical, so keys are computed against the canonical root
+fn canonical_root(workspace: &str) -> PathBuf {
+    Path::new(workspace)
+        .canonicalize()
+        .unwrap_or_else(|_| PathBuf::from(workspace))
+}
+
+// ============================================================================
+// TESTS
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    const DAY: i64 = 86_400;
+    const NOW: i64 = 1_700_000_000;
+
+    #[test]
+    fn test_frequent_and_recent_files_rank_first() {
+        let mut store = FrecencyStore::default();
+
+        // Opened daily a while ago
+        for day in 0..12 {
+            store.record("old-daily.md", VisitKind::Open, NOW - (100 - day) * DAY);
+        }
+        // Opened twice this week
+        store.record("current.md", VisitKind::Open, NOW - 2 * DAY);
+        store.record("current.md", VisitKind::Save, NOW - DAY);
+        // Opened once today
+        store.record("once.md", VisitKind::Open, NOW);
+
+        let ranked: Vec<String> = store.top(10, NOW).into_iter().map(|f| f.path).collect();
+        assert_eq!(ranked, vec!["current.md", "old-daily.md", "once.md"]);
+        assert_eq!(store.top(1, NOW).len(), 1);
+    }
+
+    #[test]
+    fn test_rapid_visits_are_coalesced() {
+        let mut store = FrecencyStore::default();
+
+        assert!(store.record("a.md", VisitKind::Open, NOW));
+        assert!(store.record("a.md", VisitKind::Save, NOW + 5));
+        assert!(!store.record("a.md", VisitKind::Save, NOW + 10));
+        assert!(store.record("a.md", VisitKind::Save, NOW + VISIT_COALESCE_SECS + 1));
+
+        let entry = &store.files["a.md"];
+        assert_eq!(entry.count, 2);
+        assert_eq!(entry.visits[0].kind, VisitKind::Save);
+    }
+
+    #[test]
+    fn test_rename_and_forget_follow_folders() {
+        let mut store = FrecencyStore::default();
+        store.record("Projects/a.md", VisitKind::Open, NOW);
+        store.record("Projects/sub/b.md", VisitKind::Open, NOW);
+        store.record("Projects2/c.md", VisitKind::Open, NOW);
+
+        store.rename("Projects", "Archive");
+        assert!(store.files.contains_key("Archive/a.md"));
+        assert!(store.files.contains_key("Archive/sub/b.md"));
+        assert!(store.files.contains_key("Projects2/c.md"));
+
+        store.forget("Archive/sub");
+        assert_eq!(store.files.len(), 2);
+    }
+
+    #[test]
+    fn test_store_roundtrip() {
+        let workspace = TempDir::new().unwrap();
+        let mut store = FrecencyStore::default();
+        store.record("a.md", VisitKind::Save, NOW);
+
+        store.save(workspace.path()).unwrap();
+        assert!(workspace.path().join(".mdreader/frecency.json").exists());
+        assert_eq!(FrecencyStore::load(workspace.path()), store);
+    }
+}
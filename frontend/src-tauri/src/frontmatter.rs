@@ -0,0 +1,419 @@
+//! YAML Frontmatter Parsing
+//!
+//! Notes may start with a `---` delimited YAML block. This module reads the
+//! subset of YAML that appears in practice in note frontmatter, without
+//! pulling in a full YAML implementation:
+//!
+//! ```text
+//! ---
+//! title: "Reading list"
+//! status: in-progress
+//! rating: 4
+//! done: false
+//! tags: [books, 2024]
+//! authors:
+//!   - Ann
+//!   - Bob
+//! ---
+//! ```
+//!
+//! Scalars become JSON strings, numbers, booleans or null; inline `[a, b]`
+//! and block `- item` lists become arrays. Anything more exotic (nested
+//! maps, multi-line strings) is kept as a raw string. `render` writes
+//! fields back out in the same subset.
+
+use serde_json::{Map, Value};
+
+/// Splits a document into its frontmatter fields and body
+///
+/// # Returns
+/// * `(Some(fields), body)` - If the document starts with a closed `---` block
+/// * `(None, content)` - Otherwise
+pub fn split(content: &str) -> (Option<Map<String, Value>>, &str) {
+    let rest = match content
+        .strip_prefix("---\n")
+        .or_else(|| content.strip_prefix("---\r\n"))
+    {
+        Some(rest) => rest,
+        None => return (None, content),
+    };
+
+    let mut offset = 0;
+    for line in rest.split_inclusive('\n') {
+        let trimmed = line.trim_end();
+        if trimmed == "---" || trimmed == "..." {
+            let yaml = &rest[..offset];
+            let body = &rest[offset + line.len()..];
+            return (Some(parse_yaml(yaml)), body);
+        }
+        offset += line.len();
+    }
+
+    (None, content)
+}
+
+/// Collects tags from the `tags` (or `tag`) field
+///
+/// Accepts a list or a comma/space separated string; a leading `#` is dropped.
+pub fn tags(fields: &Map<String, Value>) -> Vec<String> {
+    let value = match fields.get("tags").or_else(|| fields.get("tag")) {
+        Some(value) => value,
+        None => return Vec::new(),
+    };
+
+    let raw: Vec<String> = match value {
+        Value::Array(items) => items.iter().map(value_to_string).collect(),
+        Value::String(s) => s
+            .split(|c: char| c == ',' || c.is_whitespace())
+            .map(str::to_string)
+            .collect(),
+        other => vec![value_to_string(other)],
+    };
+
+    raw.into_iter()
+        .map(|t| t.trim().trim_start_matches('#').to_string())
+        .filter(|t| !t.is_empty())
+        .collect()
+}
+
+/// Collects alternative names from the `aliases` (or `alias`) field
+///
+/// Accepts a list or a comma separated string.
+pub fn aliases(fields: &Map<String, Value>) -> Vec<String> {
+    let raw: Vec<String> = match fields.get("aliases").or_else(|| fields.get("alias")) {
+        Some(Value::Array(items)) => items.iter().map(value_to_string).collect(),
+        Some(Value::String(s)) => s.split(',').map(str::to_string).collect(),
+        Some(other) => vec![value_to_string(other)],
+        None => Vec::new(),
+    };
+    raw.into_iter()
+        .map(|alias| alias.trim().to_string())
+        .filter(|alias| !alias.is_empty())
+        .collect()
+}
+
+/// Reads a date field as ms since the epoch
+///
+/// Accepts RFC 3339 (`2024-05-17T09:30:00+02:00`), `YYYY-MM-DD HH:MM[:SS]`,
+/// `YYYY-MM-DDTHH:MM[:SS]` (both taken as UTC) and plain `YYYY-MM-DD`
+/// (midnight UTC).
+pub fn date_ms(fields: &Map<String, Value>, key: &str) -> Option<i64> {
+    parse_date_ms(&value_to_string(fields.get(key)?))
+}
+
+/// Parses a date in one of the formats accepted by `date_ms`
+pub fn parse_date_ms(text: &str) -> Option<i64> {
+    let text = text.trim();
+
+    if let Ok(date) = chrono::DateTime::parse_from_rfc3339(text) {
+        return Some(date.timestamp_millis());
+    }
+    let formats = ["%Y-%m-%d %H:%M:%S", "%Y-%m-%d %H:%M", "%Y-%m-%dT%H:%M:%S", "%Y-%m-%dT%H:%M"];
+    if let Some(time) = formats.iter().find_map(|f| chrono::NaiveDateTime::parse_from_str(text, f).ok()) {
+        return Some(time.and_utc().timestamp_millis());
+    }
+    chrono::NaiveDate::parse_from_str(text, "%Y-%m-%d")
+        .ok()
+        .and_then(|d| d.and_hms_opt(0, 0, 0))
+        .map(|t| t.and_utc().timestamp_millis())
+}
+
+/// Renders a field value as plain text (lists are joined with `, `)
+pub fn value_to_string(value: &Value) -> String {
+    match value {
+        Value::Null => String::new(),
+        Value::String(s) => s.clone(),
+        Value::Array(items) => items.iter().map(value_to_string).collect::<Vec<_>>().join(", "),
+        other => other.to_string(),
+    }
+}
+
+/// Replaces the frontmatter of a document with `fields`, keeping the body
+///
+/// A document without frontmatter gets a new block; an empty `fields`
+/// removes the block.
+pub fn replace_fields(content: &str, fields: &Map<String, Value>) -> String {
+    let (_, body) = split(content);
+    format!("{}{}", render(fields), body)
+}
+
+/// Renders fields as a `---` delimited frontmatter block (empty if no fields)
+pub fn render(fields: &Map<String, Value>) -> String {
+    if fields.is_empty() {
+        return String::new();
+    }
+
+    let mut out = String::from("---\n");
+    for (key, value) in fields {
+        match value {
+            Value::Array(items) => {
+                let items: Vec<String> = items.iter().map(render_scalar).collect();
+                out.push_str(&format!("{}: [{}]\n", key, items.join(", ")));
+            }
+            other => out.push_str(&format!("{}: {}\n", key, render_scalar(other))),
+        }
+    }
+    out.push_str("---\n");
+    out
+}
+
+/// Renders a scalar, quoting strings that would otherwise parse differently
+fn render_scalar(value: &Value) -> String {
+    match value {
+        Value::String(s) => {
+            let needs_quotes = s.is_empty()
+                || s.contains(": ")
+                || s.contains(" #")
+                || s.contains('\n')
+                || s.starts_with(|c: char| "[{'\"#&*!|>%@`-".contains(c) || c.is_whitespace())
+                || s.ends_with(char::is_whitespace)
+                || !matches!(parse_scalar(s), Value::String(_));
+            if needs_quotes {
+                serde_json::to_string(s).unwrap_or_default()
+            } else {
+                s.clone()
+            }
+        }
+        Value::Null => "null".to_string(),
+        other => other.to_string(),
+    }
+}
+
+fn parse_yaml(yaml: &str) -> Map<String, Value> {
+    let mut fields = Map::new();
+    let mut current_list: Option<String> = None;
+    let mut last_key: Option<String> = None;
+
+    for line in yaml.lines() {
+        if line.trim().is_empty() || line.trim_start().starts_with('#') {
+            continue;
+        }
+
+        let indented = line.starts_with(' ') || line.starts_with('\t');
+        let trimmed = line.trim();
+
+        // Block list item belonging to the previous key
+        if let (Some(key), Some(item)) = (&current_list, trimmed.strip_prefix('-')) {
+            if let Some(Value::Array(items)) = fields.get_mut(key) {
+                items.push(parse_scalar(item.trim()));
+            }
+            continue;
+        }
+
+        if indented {
+            // Nested content we do not model: keep it as text on the previous key
+            if let Some(value) = last_key.as_ref().and_then(|k| fields.get_mut(k)) {
+                match value {
+                    Value::String(s) => {
+                        s.push('\n');
+                        s.push_str(trimmed);
+                    }
+                    Value::Array(items) if items.is_empty() => {
+                        *value = Value::String(trimmed.to_string());
+                    }
+                    _ => {}
+                }
+            }
+            current_list = None;
+            continue;
+        }
+
+        current_list = None;
+        let Some((key, value)) = trimmed.split_once(':') else { continue };
+        let key = key.trim().trim_matches(|c| c == '"' || c == '\'').to_string();
+        let value = value.trim();
+        last_key = Some(key.clone());
+
+        if value.is_empty() {
+            // Either a block list or a nested map follows
+            fields.insert(key.clone(), Value::Array(Vec::new()));
+            current_list = Some(key);
+        } else {
+            fields.insert(key, parse_scalar(value));
+        }
+    }
+
+    // Keys without a value or items
+    for value in fields.values_mut() {
+        if matches!(value, Value::Array(items) if items.is_empty()) {
+            *value = Value::Null;
+        }
+    }
+
+    fields
+}
+
+fn parse_scalar(raw: &str) -> Value {
+    let raw = strip_comment(raw);
+
+    if let Some(inner) = raw.strip_prefix('[').and_then(|r| r.strip_suffix(']')) {
+        return Value::Array(
+            split_inline_list(inner)
+                .iter()
+                .map(|item| parse_scalar(item))
+                .collect(),
+        );
+    }
+
+    if raw.len() >= 2 {
+        let (first, last) = (raw.chars().next(), raw.chars().last());
+        if first == Some('"') && last == Some('"') {
+            let unescaped = serde_json::from_str::<String>(raw)
+                .unwrap_or_else(|_| raw[1..raw.len() - 1].to_string());
+            return Value::String(unescaped);
+        }
+        if first == Some('\'') && last == Some('\'') {
+            return Value::String(raw[1..raw.len() - 1].replace("''", "'"));
+        }
+    }
+
+    match raw {
+        "" | "~" | "null" | "Null" | "NULL" => return Value::Null,
+        "true" | "True" | "TRUE" | "yes" | "Yes" => return Value::Bool(true),
+        "false" | "False" | "FALSE" | "no" | "No" => return Value::Bool(false),
+        _ => {}
+    }
+
+    if let Ok(n) = raw.parse::<i64>() {
+        return Value::from(n);
+    }
+    if let Ok(f) = raw.parse::<f64>() {
+        if f.is_finite() {
+            return Value::from(f);
+        }
+    }
+
+    Value::String(raw.to_string())
+}
+
+/// Removes a trailing ` # comment` outside of quotes
+fn strip_comment(raw: &str) -> &str {
+    let mut quote = None;
+    let mut prev = ' ';
+    for (i, c) in raw.char_indices() {
+        match (quote, c) {
+            (None, '"') | (None, '\'') => quote = Some(c),
+            (Some(q), c) if c == q => quote = None,
+            (None, '#') if prev.is_whitespace() => return raw[..i].trim_end(),
+            _ => {}
+        }
+        prev = c;
+    }
+    raw.trim()
+}
+
+fn split_inline_list(inner: &str) -> Vec<String> {
+    let mut items = Vec::new();
+    let mut current = String::new();
+    let mut quote = None;
+
+    for c in inner.chars() {
+        match (quote, c) {
+            (None, '"') | (None, '\'') => {
+                quote = Some(c);
+                current.push(c);
+            }
+            (Some(q), c) if c == q => {
+                quote = None;
+                current.push(c);
+            }
+            (None, ',') => items.push(std::mem::take(&mut current)),
+            _ => current.push(c),
+        }
+    }
+    items.push(current);
+
+    items
+        .into_iter()
+        .map(|item| item.trim().to_string())
+        .filter(|item| !item.is_empty())
+        .collect()
+}
+
+// ============================================================================
+// TESTS
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    const NOTE: &str = "---\ntitle: \"Reading list\"\nstatus: in-progress # current\nrating: 4\nprogress: 0.5\ndone: false\ntags: [books, '2024']\nauthors:\n  - Ann\n  - Bob\nlinks:\n  site: a.com\n  repo: b.com\n---\n# Body\n";
+
+    #[test]
+    fn test_split_frontmatter() {
+        let (fields, body) = split(NOTE);
+        let fields = fields.expect("Frontmatter should be found");
+
+        assert_eq!(body, "# Body\n");
+        assert_eq!(fields["title"], json!("Reading list"));
+        assert_eq!(fields["status"], json!("in-progress"), "Trailing comments are dropped");
+        assert_eq!(fields["rating"], json!(4));
+        assert_eq!(fields["progress"], json!(0.5));
+        assert_eq!(fields["done"], json!(false));
+        assert_eq!(fields["tags"], json!(["books", "2024"]));
+        assert_eq!(fields["authors"], json!(["Ann", "Bob"]));
+        assert_eq!(fields["links"], json!("site: a.com\nrepo: b.com"), "Nested maps stay raw text");
+    }
+
+    #[test]
+    fn test_no_frontmatter() {
+        let (fields, body) = split("# Title\n---\n");
+        assert!(fields.is_none());
+        assert_eq!(body, "# Title\n---\n");
+
+        let (fields, _) = split("---\ntitle: unclosed\n");
+        assert!(fields.is_none(), "Unclosed block is not frontmatter");
+    }
+
+    fn parse(content: &str) -> Map<String, Value> {
+        split(content).0.unwrap_or_default()
+    }
+
+    #[test]
+    fn test_tags_from_string_and_list() {
+        assert_eq!(tags(&parse(NOTE)), vec!["books", "2024"]);
+        assert_eq!(tags(&parse("---\ntags: \"#a, b c\"\n---\n")), vec!["a", "b", "c"]);
+        assert!(tags(&parse("no frontmatter")).is_empty());
+    }
+
+    #[test]
+    fn test_date_ms_formats() {
+        let midnight = 1_700_006_400_000; // 2023-11-15T00:00:00Z
+        assert_eq!(date_ms(&parse("---\ncreated: 2023-11-15\n---\n"), "created"), Some(midnight));
+        assert_eq!(date_ms(&parse("---\ncreated: 2023-11-15 01:30\n---\n"), "created"), Some(midnight + 5_400_000));
+        assert_eq!(date_ms(&parse("---\ncreated: \"2023-11-15T02:00:00+01:00\"\n---\n"), "created"), Some(midnight + 3_600_000));
+        assert_eq!(date_ms(&parse("---\ncreated: someday\n---\n"), "created"), None);
+        assert_eq!(date_ms(&parse(NOTE), "created"), None);
+    }
+
+    #[test]
+    fn test_render_roundtrip() {
+        let fields = split(NOTE).0.unwrap();
+        let mut extra = fields.clone();
+        extra.insert("note".to_string(), json!("true"));
+        extra.insert("quote".to_string(), json!("a: b"));
+
+        let rendered = render(&extra);
+        assert!(rendered.starts_with("---\n") && rendered.ends_with("---\n"));
+        assert_eq!(split(&rendered).0.unwrap(), extra, "Rendered frontmatter must parse back");
+        assert_eq!(render(&Map::new()), "");
+    }
+
+    #[test]
+    fn test_replace_fields_keeps_body() {
+        let mut fields = Map::new();
+        fields.insert("status".to_string(), json!("done"));
+
+        assert_eq!(replace_fields("# Plan\n", &fields), "---\nstatus: done\n---\n# Plan\n");
+        assert_eq!(replace_fields("---\nstatus: draft\n---\n# Plan\n", &fields), "---\nstatus: done\n---\n# Plan\n");
+        assert_eq!(replace_fields("---\nstatus: draft\n---\n# Plan\n", &Map::new()), "# Plan\n");
+    }
+
+    #[test]
+    fn test_value_to_string() {
+        assert_eq!(value_to_string(&json!(["a", 1])), "a, 1");
+        assert_eq!(value_to_string(&Value::Null), "");
+    }
+}
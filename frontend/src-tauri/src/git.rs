@@ -0,0 +1,236 @@
+//! Git Status
+//!
+//! Version-control state of a workspace that lives in a git repository, for
+//! the sidebar: the status of each file (modified, untracked, committed but
+//! not pushed, ...) and a summary of the branch. Both come from one
+//! `git status --porcelain=v2 --branch` run, parsed here, plus a
+//! `git diff --name-only` against the upstream for unpushed files.
+//!
+//! Everything is empty (`None`) when the workspace is not in a repository
+//! or git cannot be run; callers treat that as "no VCS".
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use serde::{Deserialize, Serialize};
+
+/// Git state of a file, ordered by importance (a folder shows the most
+/// important state of its contents)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum GitFileStatus {
+    /// Committed, but the commit is not pushed to the upstream yet
+    Ahead,
+    Untracked,
+    /// New file added to the index
+    Added,
+    Deleted,
+    /// Changed in the index or the working tree (renames included)
+    Modified,
+    /// Unmerged during a merge or rebase
+    Conflicted,
+}
+
+/// Branch and working tree summary
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct GitSummary {
+    /// Current branch (`None` when HEAD is detached)
+    pub branch: Option<String>,
+    /// Abbreviated commit id of HEAD (`None` before the first commit)
+    pub head: Option<String>,
+    /// Upstream branch, e.g. `origin/main`
+    pub upstream: Option<String>,
+    /// Commits not pushed to the upstream
+    pub ahead: u32,
+    /// Upstream commits not pulled yet
+    pub behind: u32,
+    /// Tracked files with staged or unstaged changes
+    pub dirty: usize,
+    pub untracked: usize,
+    pub conflicted: usize,
+}
+
+/// Status of a repository
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct RepoStatus {
+    /// Top-level folder of the repository
+    pub root: PathBuf,
+    pub summary: GitSummary,
+    /// File states keyed by `/`-separated path relative to `root`
+    pub files: HashMap<String, GitFileStatus>,
+}
+
+impl RepoStatus {
+    /// State of a file, or for a folder the most important state inside it
+    pub fn status_of(&self, path: &Path) -> Option<GitFileStatus> {
+        let relative = path.strip_prefix(&self.root).ok()?;
+        let key = relative
+            .components()
+            .map(|c| c.as_os_str().to_string_lossy().to_string())
+            .collect::<Vec<_>>()
+            .join("/");
+        if let Some(status) = self.files.get(&key) {
+            return Some(*status);
+        }
+        let prefix = format!("{}/", key);
+        self.files
+            .iter()
+            .filter(|(file, _)| key.is_empty() || file.starts_with(&prefix))
+            .map(|(_, status)| *status)
+            .max()
+    }
+}
+
+/// Reads the git status of the repository containing `workspace_root`
+///
+/// Returns `None` if it is not in a repository or git cannot be run.
+pub fn repo_status(workspace_root: &Path) -> Option<RepoStatus> {
+    let root = git(workspace_root, &["rev-parse", "--show-toplevel"])?;
+    let root = PathBuf::from(root.trim()).canonicalize().ok()?;
+
+    let output = git(&root, &["status", "--porcelain=v2", "--branch", "-z", "--untracked-files=all"])?;
+    let mut status = parse_status(&output);
+    status.root = root;
+
+    // Files changed by commits that are not pushed yet
+    if status.summary.upstream.is_some() && status.summary.ahead > 0 {
+        let changed = git(&status.root, &["diff", "--name-only", "-z", "@{upstream}", "HEAD"]).unwrap_or_default();
+        for path in changed.split('\0').filter(|p| !p.is_empty()) {
+            status.files.entry(path.to_string()).or_insert(GitFileStatus::Ahead);
+        }
+    }
+    Some(status)
+}
+
+// Helper: Runs git in `dir`, returning its output if it succeeded
+fn git(dir: &Path, args: &[&str]) -> Option<String> {
+    let output = Command::new("git").arg("-C").arg(dir).args(args).output().ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    Some(String::from_utf8_lossy(&output.stdout).to_string())
+}
+
+/// Parses `git status --porcelain=v2 --branch -z` output (without `root`)
+pub fn parse_status(output: &str) -> RepoStatus {
+    let mut status = RepoStatus::default();
+    let summary = &mut status.summary;
+    let mut records = output.split('\0').filter(|r| !r.is_empty());
+
+    while let Some(record) = records.next() {
+        if let Some(header) = record.strip_prefix("# ") {
+            let (key, value) = header.split_once(' ').unwrap_or((header, ""));
+            match key {
+                "branch.oid" if value != "(initial)" => summary.head = Some(value.chars().take(7).collect()),
+                "branch.head" if value != "(detached)" => summary.branch = Some(value.to_string()),
+                "branch.upstream" => summary.upstream = Some(value.to_string()),
+                "branch.ab" => {
+                    for count in value.split(' ') {
+                        if let Some(n) = count.strip_prefix('+') {
+                            summary.ahead = n.parse().unwrap_or(0);
+                        } else if let Some(n) = count.strip_prefix('-') {
+                            summary.behind = n.parse().unwrap_or(0);
+                        }
+                    }
+                }
+                _ => {}
+            }
+            continue;
+        }
+
+        // Entries end with the path (which may contain spaces) after a
+        // fixed number of fields
+        let (kind, fields) = match record.chars().next() {
+            Some('1') => ('1', 9),
+            Some('2') => ('2', 10),
+            Some('u') => ('u', 11),
+            Some('?') => ('?', 2),
+            _ => continue,
+        };
+        let parts: Vec<&str> = record.splitn(fields, ' ').collect();
+        if parts.len() != fields {
+            continue;
+        }
+        let (xy, path) = (parts[1], parts[fields - 1]);
+        if kind == '2' {
+            // The original path of a rename follows as its own record
+            records.next();
+        }
+
+        let file_status = match kind {
+            '?' => GitFileStatus::Untracked,
+            'u' => GitFileStatus::Conflicted,
+            _ if xy.contains('D') => GitFileStatus::Deleted,
+            _ if xy.starts_with('A') => GitFileStatus::Added,
+            _ => GitFileStatus::Modified,
+        };
+        match file_status {
+            GitFileStatus::Untracked => summary.untracked += 1,
+            GitFileStatus::Conflicted => summary.conflicted += 1,
+            _ => summary.dirty += 1,
+        }
+        status.files.insert(path.to_string(), file_status);
+    }
+    status
+}
+
+// ============================================================================
+// TESTS
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_status() {
+        let output = [
+            "# branch.oid 4f2c9e1a7b3d5e6f708192a3b4c5d6e7f8091a2b",
+            "# branch.head main",
+            "# branch.upstream origin/main",
+            "# branch.ab +2 -1",
+            "1 .M N... 100644 100644 100644 aaaaaaa bbbbbbb notes/plan.md",
+            "1 A. N... 000000 100644 100644 0000000 ccccccc notes/new note.md",
+            "1 .D N... 100644 100644 000000 ddddddd ddddddd gone.md",
+            "2 R. N... 100644 100644 100644 eeeeeee eeeeeee R100 renamed.md",
+            "original.md",
+            "u UU N... 100644 100644 100644 100644 fffffff 1111111 2222222 merge.md",
+            "? drafts/idea.md",
+            "",
+        ]
+        .join("\0");
+        let status = parse_status(&output);
+
+        let summary = &status.summary;
+        assert_eq!(summary.branch.as_deref(), Some("main"));
+        assert_eq!(summary.head.as_deref(), Some("4f2c9e1"));
+        assert_eq!(summary.upstream.as_deref(), Some("origin/main"));
+        assert_eq!((summary.ahead, summary.behind), (2, 1));
+        assert_eq!((summary.dirty, summary.untracked, summary.conflicted), (4, 1, 1));
+
+        assert_eq!(status.files["notes/plan.md"], GitFileStatus::Modified);
+        assert_eq!(status.files["notes/new note.md"], GitFileStatus::Added);
+        assert_eq!(status.files["gone.md"], GitFileStatus::Deleted);
+        assert_eq!(status.files["renamed.md"], GitFileStatus::Modified);
+        assert!(!status.files.contains_key("original.md"));
+        assert_eq!(status.files["merge.md"], GitFileStatus::Conflicted);
+        assert_eq!(status.files["drafts/idea.md"], GitFileStatus::Untracked);
+
+        let detached = parse_status("# branch.oid (initial)\0# branch.head (detached)\0");
+        assert_eq!(detached.summary, GitSummary::default());
+    }
+
+    #[test]
+    fn test_status_of_folders() {
+        let mut status = RepoStatus { root: PathBuf::from("/repo"), ..Default::default() };
+        status.files.insert("notes/a.md".to_string(), GitFileStatus::Untracked);
+        status.files.insert("notes/deep/b.md".to_string(), GitFileStatus::Modified);
+        status.files.insert("notes-old/c.md".to_string(), GitFileStatus::Conflicted);
+
+        assert_eq!(status.status_of(Path::new("/repo/notes/a.md")), Some(GitFileStatus::Untracked));
+        assert_eq!(status.status_of(Path::new("/repo/notes")), Some(GitFileStatus::Modified));
+        assert_eq!(status.status_of(Path::new("/repo")), Some(GitFileStatus::Conflicted));
+        assert_eq!(status.status_of(Path::new("/repo/other.md")), None);
+        assert_eq!(status.status_of(Path::new("/elsewhere/a.md")), None);
+    }
+}
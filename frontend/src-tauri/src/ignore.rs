@@ -0,0 +1,262 @@
+//! Ignore Files
+//!
+//! Decides which files of a workspace are left out of listings, indexing
+//! (note scans, the metadata cache) and the file watcher:
+//! - `.mdreaderignore` files, always
+//! - `.gitignore` files and `.git/info/exclude`, unless the workspace turns
+//!   `respect_gitignore` off, so generated files kept in a vault repository
+//!   do not show up as notes
+//!
+//! Both use gitignore syntax: one pattern per line, `#` comments, `!` to
+//! re-include, a trailing `/` for folders only, and a `/` at the start or in
+//! the middle to anchor the pattern to the folder of the ignore file.
+//! Wildcards are those of `utils::matches_glob` (`*`, `**`, `?`); character
+//! classes are not supported. An ignore file in a subfolder applies to that
+//! subfolder, and later (deeper) rules override earlier ones.
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+use crate::utils::matches_glob;
+use crate::workspace_settings::WorkspaceSettings;
+
+/// Ignore file read regardless of git
+pub const IGNORE_FILE: &str = ".mdreaderignore";
+
+/// Git's ignore file
+pub const GITIGNORE_FILE: &str = ".gitignore";
+
+/// One pattern of an ignore file
+#[derive(Debug, Clone, PartialEq)]
+struct Rule {
+    /// Folder of the ignore file, relative to the root (`""` for the root)
+    base: String,
+    pattern: String,
+    negated: bool,
+    dir_only: bool,
+    /// Matched against the path below `base` instead of the file name
+    anchored: bool,
+}
+
+/// Ignore rules of a workspace; ignore files are read lazily per folder
+#[derive(Debug)]
+pub struct IgnoreRules {
+    root: PathBuf,
+    canonical_root: PathBuf,
+    gitignore: bool,
+    /// Root-level rules (`.git/info/exclude`)
+    global: Vec<Rule>,
+    /// Rules of the ignore files of each folder read so far
+    folders: HashMap<String, Vec<Rule>>,
+}
+
+impl IgnoreRules {
+    /// Rules for a workspace, honoring its `respect_gitignore` setting
+    pub fn load(workspace_root: &Path) -> Self {
+        let gitignore = WorkspaceSettings::load(workspace_root)
+            .map(|settings| settings.respect_gitignore)
+            .unwrap_or(true);
+        Self::new(workspace_root, gitignore)
+    }
+
+    /// Rules for a folder, with or without the git ignore files
+    pub fn new(root: &Path, gitignore: bool) -> Self {
+        let mut global = Vec::new();
+        if gitignore {
+            if let Ok(content) = fs::read_to_string(root.join(".git").join("info").join("exclude")) {
+                global = parse_rules(&content, "");
+            }
+        }
+        Self {
+            root: root.to_path_buf(),
+            canonical_root: root.canonicalize().unwrap_or_else(|_| root.to_path_buf()),
+            gitignore,
+            global,
+            folders: HashMap::new(),
+        }
+    }
+
+    /// Checks if a path is ignored, itself or through one of its folders
+    ///
+    /// Paths outside the root are never ignored.
+    pub fn is_ignored(&mut self, path: &Path, is_dir: bool) -> bool {
+        let Some(parts) = self.relative_parts(path) else {
+            return false;
+        };
+        (1..=parts.len()).any(|depth| {
+            let relative = parts[..depth].join("/");
+            self.matches(&relative, depth < parts.len() || is_dir)
+        })
+    }
+
+    /// Checks if a file name is an ignore file (changing it changes the rules)
+    pub fn is_ignore_file(path: &Path) -> bool {
+        path.file_name()
+            .is_some_and(|name| name == IGNORE_FILE || name == GITIGNORE_FILE)
+    }
+
+    // Helper: Whether the last matching rule for `relative` ignores it
+    fn matches(&mut self, relative: &str, is_dir: bool) -> bool {
+        let folder = relative.rsplit_once('/').map_or("", |(folder, _)| folder);
+        let mut folders = vec![""];
+        folders.extend(folder.match_indices('/').map(|(i, _)| &folder[..i]));
+        if !folder.is_empty() {
+            folders.push(folder);
+        }
+
+        let mut ignored = rules_verdict(&self.global, relative, is_dir);
+        for folder in folders {
+            if let Some(verdict) = rules_verdict(self.folder_rules(folder), relative, is_dir) {
+                ignored = Some(verdict);
+            }
+        }
+        ignored.unwrap_or(false)
+    }
+
+    // Helper: Rules of the ignore files in a folder, read on first use
+    fn folder_rules(&mut self, folder: &str) -> &[Rule] {
+        if !self.folders.contains_key(folder) {
+            let dir = self.root.join(folder);
+            let mut names = vec![IGNORE_FILE];
+            if self.gitignore {
+                names.insert(0, GITIGNORE_FILE);
+            }
+            let rules = names
+                .into_iter()
+                .filter_map(|name| fs::read_to_string(dir.join(name)).ok())
+                .flat_map(|content| parse_rules(&content, folder))
+                .collect();
+            self.folders.insert(folder.to_string(), rules);
+        }
+        &self.folders[folder]
+    }
+
+    // Helper: `/`-separated components of a path below the root
+    fn relative_parts(&self, path: &Path) -> Option<Vec<String>> {
+        let relative = path
+            .strip_prefix(&self.root)
+            .or_else(|_| path.strip_prefix(&self.canonical_root))
+            .ok()?;
+        let parts: Vec<String> = relative
+            .components()
+            .map(|c| match c {
+                Component::Normal(part) => Some(part.to_string_lossy().to_string()),
+                _ => None,
+            })
+            .collect::<Option<_>>()?;
+        (!parts.is_empty()).then_some(parts)
+    }
+}
+
+/// Parses an ignore file found in `base`
+fn parse_rules(content: &str, base: &str) -> Vec<Rule> {
+    content
+        .lines()
+        .filter_map(|line| {
+            let line = line.trim_end();
+            if line.is_empty() || line.starts_with('#') {
+                return None;
+            }
+            let (negated, line) = match line.strip_prefix('!') {
+                Some(rest) => (true, rest),
+                None => (false, line.strip_prefix('\\').unwrap_or(line)),
+            };
+            let dir_only = line.ends_with('/');
+            let line = line.trim_end_matches('/');
+            let anchored = line.trim_start_matches('/').contains('/') || line.starts_with('/');
+            let pattern = line.trim_start_matches('/').to_string();
+            (!pattern.is_empty()).then(|| Rule { base: base.to_string(), pattern, negated, dir_only, anchored })
+        })
+        .collect()
+}
+
+/// Verdict of the last rule matching `relative` (`true` = ignored)
+fn rules_verdict(rules: &[Rule], relative: &str, is_dir: bool) -> Option<bool> {
+    rules
+        .iter()
+        .rev()
+        .find(|rule| rule_matches(rule, relative, is_dir))
+        .map(|rule| !rule.negated)
+}
+
+fn rule_matches(rule: &Rule, relative: &str, is_dir: bool) -> bool {
+    if rule.dir_only && !is_dir {
+        return false;
+    }
+    let below = if rule.base.is_empty() {
+        relative
+    } else {
+        match relative.strip_prefix(&rule.base).and_then(|rest| rest.strip_prefix('/')) {
+            Some(below) => below,
+            None => return false,
+        }
+    };
+    if !rule.anchored {
+        let name = below.rsplit('/').next().unwrap_or(below);
+        return matches_glob(&rule.pattern, name);
+    }
+
+    // `matches_glob` tries a slash-free pattern on every component, but an
+    // anchored one (`/out`) only names an entry of the base folder
+    let pattern = rule.pattern.as_str();
+    if !pattern.contains('/') {
+        return !below.contains('/') && matches_glob(pattern, below);
+    }
+
+    // `**/` also matches no folder at all
+    matches_glob(pattern, below)
+        || pattern.strip_prefix("**/").is_some_and(|rest| matches_glob(rest, below))
+        || (pattern.contains("/**/") && matches_glob(&pattern.replacen("/**/", "/", 1), below))
+}
+
+// ============================================================================
+// TESTS
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_gitignore_rules() {
+        let workspace = TempDir::new().unwrap();
+        let root = workspace.path();
+        fs::create_dir_all(root.join("docs/build")).unwrap();
+        fs::write(root.join(".gitignore"), "# generated\n*.log\n/out/\nbuild/\n!keep.log\ndocs/**/draft.md\n").unwrap();
+        fs::write(root.join("docs/.gitignore"), "generated.md\n").unwrap();
+        fs::write(root.join(".mdreaderignore"), "private/\n").unwrap();
+
+        let mut rules = IgnoreRules::new(root, true);
+        assert!(rules.is_ignored(&root.join("debug.log"), false));
+        assert!(rules.is_ignored(&root.join("notes/debug.log"), false));
+        assert!(!rules.is_ignored(&root.join("keep.log"), false));
+        assert!(rules.is_ignored(&root.join("out"), true));
+        assert!(rules.is_ignored(&root.join("out/a.md"), false));
+        assert!(!rules.is_ignored(&root.join("notes/out/a.md"), false), "Anchored to the root");
+        assert!(rules.is_ignored(&root.join("docs/build/index.md"), false));
+        assert!(rules.is_ignored(&root.join("docs/draft.md"), false));
+        assert!(rules.is_ignored(&root.join("docs/a/b/draft.md"), false));
+        assert!(rules.is_ignored(&root.join("docs/generated.md"), false));
+        assert!(!rules.is_ignored(&root.join("generated.md"), false), "Nested rules stay in their folder");
+        assert!(rules.is_ignored(&root.join("private/plan.md"), false));
+        assert!(!rules.is_ignored(&root.join("notes/plan.md"), false));
+        assert!(!rules.is_ignored(Path::new("/elsewhere/debug.log"), false));
+
+        // Without git, only .mdreaderignore applies
+        let mut rules = IgnoreRules::new(root, false);
+        assert!(!rules.is_ignored(&root.join("debug.log"), false));
+        assert!(rules.is_ignored(&root.join("private/plan.md"), false));
+    }
+
+    #[test]
+    fn test_parse_rules() {
+        let rules = parse_rules("\\#hash.md\n!/keep/\n  \nsub/dir  \n", "notes");
+        assert_eq!(rules.len(), 3);
+        assert_eq!(rules[0].pattern, "#hash.md");
+        assert!(rules[1].negated && rules[1].dir_only && rules[1].anchored);
+        assert_eq!((rules[2].pattern.as_str(), rules[2].anchored), ("sub/dir", true));
+        assert_eq!(rules[2].base, "notes");
+        assert!(IgnoreRules::is_ignore_file(Path::new("/ws/docs/.gitignore")));
+    }
+}
@@ -0,0 +1,305 @@
+//! Long-Running Job Registry for MDReader
+//!
+//! Operations that can take a while (folder imports, exports, indexing)
+//! register themselves here. Each job gets an id, a cancellation flag that
+//! the worker checks between steps, and reports progress to the frontend
+//! through `job-progress` events.
+//!
+//! ## Lifecycle
+//! 1. Worker calls `JobRegistry::start(kind)` and receives a `JobHandle`
+//! 2. Worker emits `JobProgress` events and calls `handle.checkpoint()`
+//!    between steps, which parks it while the app is suspended (see
+//!    `crate::lifecycle`) and tells it whether to go on
+//! 3. Frontend may call `cancel_job(id)` at any time
+//! 4. Worker calls `JobRegistry::finish(id)` when done (or cancelled)
+
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use serde::Serialize;
+use tauri::AppHandle;
+use crate::events;
+use crate::lifecycle::Lifecycle;
+
+/// Entry in the job registry
+struct JobEntry {
+    kind: String,
+    started_at: chrono::DateTime<chrono::Utc>,
+    cancel: Arc<AtomicBool>,
+}
+
+/// Handle given to the worker running a job
+#[derive(Debug, Clone)]
+pub struct JobHandle {
+    pub id: String,
+    pub kind: String,
+    cancel: Arc<AtomicBool>,
+    lifecycle: Lifecycle,
+}
+
+impl JobHandle {
+    /// Checks if cancellation was requested
+    pub fn is_cancelled(&self) -> bool {
+        self.cancel.load(Ordering::Relaxed)
+    }
+
+    /// Waits while the app is suspended, then checks whether to go on
+    ///
+    /// Returns false once cancellation was requested.
+    pub fn checkpoint(&self) -> bool {
+        self.lifecycle.wait_while_suspended(|| self.is_cancelled());
+        !self.is_cancelled()
+    }
+
+    /// Builds a progress payload for this job
+    pub fn progress(&self, processed: u64, total: Option<u64>, current: Option<String>) -> JobProgress {
+        JobProgress {
+            job_id: self.id.clone(),
+            kind: self.kind.clone(),
+            processed,
+            total,
+            current,
+            done: false,
+        }
+    }
+}
+
+/// Progress payload emitted as a `job-progress` event
+#[derive(Debug, Clone, Serialize)]
+pub struct JobProgress {
+    pub job_id: String,
+    pub kind: String,
+    /// Units of work completed so far (files, bytes, ... depending on kind)
+    pub processed: u64,
+    /// Total units of work, if known
+    pub total: Option<u64>,
+    /// Item currently being processed
+    pub current: Option<String>,
+    /// True for the final event of a job
+    pub done: bool,
+}
+
+impl JobProgress {
+    /// Marks this payload as the final event of the job
+    pub fn finished(mut self) -> Self {
+        self.done = true;
+        self
+    }
+}
+
+/// Information about a running job
+#[derive(Debug, Clone, Serialize)]
+pub struct JobInfo {
+    pub id: String,
+    pub kind: String,
+    pub started_at: chrono::DateTime<chrono::Utc>,
+    pub cancel_requested: bool,
+}
+
+/// Registry of running jobs, keyed by job id
+#[derive(Default)]
+pub struct JobRegistry {
+    jobs: Mutex<HashMap<String, JobEntry>>,
+    next_id: AtomicU64,
+    /// Parks jobs at their checkpoints while the app is suspended
+    lifecycle: Lifecycle,
+}
+
+impl JobRegistry {
+    /// Creates an empty job registry with its own lifecycle
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates an empty job registry sharing the app's lifecycle
+    pub fn with_lifecycle(lifecycle: Lifecycle) -> Self {
+        Self {
+            lifecycle,
+            ..Self::default()
+        }
+    }
+
+    /// Registers a new job
+    ///
+    /// # Arguments
+    /// * `kind` - Short job type, e.g. `"import_folder"`
+    ///
+    /// # Returns
+    /// * `Ok(JobHandle)` - Handle for the worker
+    /// * `Err(String)` - If the mutex is poisoned
+    pub fn start(&self, kind: &str) -> Result<JobHandle, String> {
+        let mut jobs = self.jobs
+            .lock()
+            .map_err(|e| format!("Failed to lock jobs: {}", e))?;
+
+        let id = format!("{}-{}", kind, self.next_id.fetch_add(1, Ordering::Relaxed) + 1);
+        let cancel = Arc::new(AtomicBool::new(false));
+
+        jobs.insert(id.clone(), JobEntry {
+            kind: kind.to_string(),
+            started_at: chrono::Utc::now(),
+            cancel: Arc::clone(&cancel),
+        });
+
+        log::info!("⚙️ Started job: {}", id);
+        Ok(JobHandle {
+            id,
+            kind: kind.to_string(),
+            cancel,
+            lifecycle: self.lifecycle.clone(),
+        })
+    }
+
+    /// Requests cancellation of a job
+    ///
+    /// # Returns
+    /// * `Ok(true)` - If the job exists and was flagged
+    /// * `Ok(false)` - If no such job is running
+    pub fn cancel(&self, id: &str) -> Result<bool, String> {
+        let jobs = self.jobs
+            .lock()
+            .map_err(|e| format!("Failed to lock jobs: {}", e))?;
+
+        match jobs.get(id) {
+            Some(entry) => {
+                entry.cancel.store(true, Ordering::Relaxed);
+                log::info!("🛑 Cancellation requested for job: {}", id);
+                Ok(true)
+            }
+            None => Ok(false),
+        }
+    }
+
+    /// Removes a job from the registry once the worker is done
+    pub fn finish(&self, id: &str) -> Result<(), String> {
+        let mut jobs = self.jobs
+            .lock()
+            .map_err(|e| format!("Failed to lock jobs: {}", e))?;
+
+        if jobs.remove(id).is_some() {
+            log::info!("✅ Finished job: {}", id);
+        }
+        Ok(())
+    }
+
+    /// Requests cancellation of every running job (for app shutdown)
+    pub fn cancel_all(&self) -> Result<usize, String> {
+        let jobs = self.jobs
+            .lock()
+            .map_err(|e| format!("Failed to lock jobs: {}", e))?;
+
+        for entry in jobs.values() {
+            entry.cancel.store(true, Ordering::Relaxed);
+        }
+        Ok(jobs.len())
+    }
+
+    /// Lists running jobs
+    pub fn list(&self) -> Result<Vec<JobInfo>, String> {
+        let jobs = self.jobs
+            .lock()
+            .map_err(|e| format!("Failed to lock jobs: {}", e))?;
+
+        let mut infos: Vec<JobInfo> = jobs
+            .iter()
+            .map(|(id, entry)| JobInfo {
+                id: id.clone(),
+                kind: entry.kind.clone(),
+                started_at: entry.started_at,
+                cancel_requested: entry.cancel.load(Ordering::Relaxed),
+            })
+            .collect();
+        infos.sort_by_key(|info| info.started_at);
+
+        Ok(infos)
+    }
+
+    /// Gets the number of running jobs
+    pub fn job_count(&self) -> usize {
+        self.jobs
+            .lock()
+            .map(|j| j.len())
+            .unwrap_or(0)
+    }
+}
+
+/// Emits a progress event to the frontend, logging (not failing) on error
+pub fn emit_progress(app_handle: &AppHandle, progress: &JobProgress) {
+    events::emit(app_handle, progress);
+}
+
+// ============================================================================
+// TESTS
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_start_and_finish_job() {
+        let registry = JobRegistry::new();
+
+        let job = registry.start("import_folder").unwrap();
+        assert!(job.id.starts_with("import_folder-"));
+        assert_eq!(registry.job_count(), 1);
+
+        registry.finish(&job.id).unwrap();
+        assert_eq!(registry.job_count(), 0);
+    }
+
+    #[test]
+    fn test_job_ids_are_unique() {
+        let registry = JobRegistry::new();
+
+        let a = registry.start("export").unwrap();
+        let b = registry.start("export").unwrap();
+        assert_ne!(a.id, b.id);
+    }
+
+    #[test]
+    fn test_cancel_sets_flag() {
+        let registry = JobRegistry::new();
+        let job = registry.start("import_folder").unwrap();
+
+        assert!(!job.is_cancelled());
+        assert!(registry.cancel(&job.id).unwrap());
+        assert!(job.is_cancelled(), "Worker should observe the cancellation");
+
+        let info = &registry.list().unwrap()[0];
+        assert!(info.cancel_requested);
+    }
+
+    #[test]
+    fn test_checkpoint_parks_while_suspended() {
+        let lifecycle = Lifecycle::new();
+        let registry = JobRegistry::with_lifecycle(lifecycle.clone());
+        let job = registry.start("reindex").unwrap();
+        assert!(job.checkpoint());
+
+        lifecycle.suspend(Vec::new()).unwrap();
+        let worker = {
+            let job = job.clone();
+            std::thread::spawn(move || job.checkpoint())
+        };
+        registry.cancel(&job.id).unwrap();
+        assert!(!worker.join().unwrap(), "Cancelling should release a parked job");
+    }
+
+    #[test]
+    fn test_cancel_unknown_job() {
+        let registry = JobRegistry::new();
+        assert!(!registry.cancel("missing-1").unwrap());
+    }
+
+    #[test]
+    fn test_progress_payload() {
+        let registry = JobRegistry::new();
+        let job = registry.start("import_folder").unwrap();
+
+        let progress = job.progress(3, Some(10), Some("a.md".to_string()));
+        assert_eq!(progress.job_id, job.id);
+        assert!(!progress.done);
+        assert!(progress.finished().done);
+    }
+}
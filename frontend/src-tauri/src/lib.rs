@@ -7,12 +7,126 @@
 //! ```text
 //! lib.rs (entry point)
 //! ├── state.rs      - AppState management (watchers, workspace)
+//! ├── related.rs    - Related-notes ranking (tags, links, similarity, co-edits)
+//! ├── references.rs - Inline/reference link conversion and footnote renumbering
+//! ├── save_queue.rs - Debounced per-file save coalescing
+//! ├── search_index.rs - Persisted full-text search index (BM25, warm start)
+//! ├── safe_write.rs - Saves that retry while cloud sync clients lock the file
+//! ├── session.rs    - Per-workspace session (open tabs, layout)
+//! ├── share.rs      - Share sheet intake (mobile)
+//! ├── snippets.rs   - Per-workspace snippet library
+//! ├── storage.rs    - Storage backends (filesystem, Android SAF document trees)
+//! ├── sync_conflicts.rs - Conflict copies of sync tools (Dropbox, Syncthing)
+//! ├── write_locks.rs - Per-file write serialization
+//! ├── jobs.rs       - Long-running job registry (progress, cancel)
+//! ├── lifecycle.rs  - Suspend/resume state of the app (mobile)
+//! ├── link_check.rs - External link health (HEAD/GET, redirects, per-host pacing, cache)
+//! ├── link_preview.rs - Page titles for pasted links (public addresses only)
+//! ├── links.rs      - Workspace link index and graph metrics
+//! ├── lists.rs      - List renumbering, indentation and nesting repair
+//! ├── logseq.rs     - Logseq graph conversion
+//! ├── math.rs       - TeX math typesetting as MathML for exports
+//! ├── mermaid.rs    - Mermaid diagram generation from note structure
+//! ├── mentions.rs   - Unlinked mentions of a note's names
+//! ├── memory_budget.rs - Memory budget and LRU eviction of in-memory caches
+//! ├── merge.rs      - Section-aware merging of two notes
+//! ├── metadata_cache.rs - Cached note and asset metadata (app cache directory)
+//! ├── metadata_schema.rs - Per-workspace frontmatter schema validation
+//! ├── metrics.rs    - Habit and metric tracking (track: lines, time series)
+//! ├── mindmap_meta.rs - Mindmap sidecar metadata (.mindmap/<file>.json)
+//! ├── activity.rs   - Workspace activity feed (changes, saves, imports, commits)
+//! ├── app_lock.rs   - App lock (locked commands, relock after background time)
+//! ├── audio.rs      - Voice memo recording (WAV)
+//! ├── assets.rs     - Image asset bundling for exports
+//! ├── calendar.rs   - Dated events in notes (frontmatter, @event annotations) and .ics export
+//! ├── capabilities.rs - Feature, format and platform discovery
+//! ├── capture.rs    - Quick-capture append/prepend formatting
+//! ├── chart.rs      - Bar, line and pie charts of markdown tables (SVG)
+//! ├── command_guard.rs - Command rate limits and reentrancy guards
+//! ├── backup.rs     - Deduplicating content-addressed backups (chunked, compressed)
+//! ├── backup_targets.rs - Backup targets (external volumes, schedule, run status)
+//! ├── bear.rs       - Bear (TextBundle) and Apple Notes import
+//! ├── biometric.rs  - Device owner verification (Touch ID, Windows Hello, mobile biometrics)
+//! ├── board.rs      - Note status board columns
+//! ├── convert.rs    - Org-mode/AsciiDoc/HTML to markdown converters
+//! ├── csv_table.rs  - CSV/TSV parsing, markdown table rendering and csvtable blocks
+//! ├── diagrams.rs   - Diagram blocks (Mermaid, PlantUML) and their SVG cache
+//! ├── diff.rs       - Line diffs (unified and side-by-side with word changes)
+//! ├── dialogs.rs    - Native dialogs awaited off the async runtime (timeout, cancel)
+//! ├── email.rs      - Email drafts of notes (mailto, MAPI, xdg-email; attachments)
+//! ├── events.rs     - Event names and versioned payloads sent to the frontend
+//! ├── export_policy.rs - Allowed export destinations (save dialog, export folders)
+//! ├── file_access.rs - Permission error diagnosis and read-only recovery
+//! ├── external_editor.rs - External editor command lines (terminal editors, system default)
+//! ├── file_meta.rs  - File timestamps and permissions for listings
+//! ├── flavor.rs     - CommonMark / GFM / MultiMarkdown conversion
+//! ├── favorites.rs  - Starred notes and pinned folders (follow renames)
+//! ├── frecency.rs   - Frecency ranking of recently used files
+//! ├── frontmatter.rs - YAML frontmatter parsing
+//! ├── headings.rs   - Heading level shifts, single-H1 fix and TOC rebuild
+//! ├── git.rs        - Git status of files and branch summary
+//! ├── hooks.rs      - Workspace hooks around saves, opens and exports (sandboxed runs, approval)
+//! ├── ignore.rs     - .gitignore / .mdreaderignore rules for listings, indexing and watching
+//! ├── pdf.rs        - PDF text extraction and page rendering
+//! ├── people.rs     - Person pages and @handle mentions
+//! ├── plugins.rs    - Workspace WASM plugins (manifests, discovery, approval)
+//! ├── plugin_runtime.rs - Sandboxed plugin runs (wasmtime, capability-scoped host API)
+//! ├── print.rs      - Print layout (page size, page breaks, headers and footers)
+//! ├── render.rs     - Markdown to HTML for preview, share links, print and export
+//! ├── rtf.rs        - Rendered notes to RTF for rich text copy
+//! ├── ocr.rs        - Image text recognition and its store
+//! ├── outline.rs    - Heading outline (incremental parsing, stable ids, section edits)
+//! ├── overview.rs   - Workspace dashboard statistics
+//! ├── paging.rs     - Keyset paging of large directory listings
+//! ├── paste.rs      - Pasted HTML to markdown, tracking parameter and quote cleanup
+//! ├── notes.rs      - Note scanning, stats and dataset queries
+//! ├── offline_journal.rs - Saves journaled while the workspace is unreachable
+//! ├── open_file.rs  - Single-file watchers of open documents (external changes as diffs)
+//! ├── transcribe.rs - Offline speech-to-text (whisper.cpp)
+//! ├── tables.rs     - Pipe table parsing and structured edits (rows, columns, sort)
+//! ├── tag_suggest.rs - TF-IDF tag suggestions from note content
+//! ├── template.rs   - Template variables engine ({{date}}, {{title}}, ...)
+//! ├── text_pdf.rs   - Text-only PDF layout of notes (mail attachments)
+//! ├── text_stats.rs - Word, sentence and readability statistics of a text
+//! ├── thumbnails.rs - Cached image and mindmap thumbnails
+//! ├── timeline.rs   - Notes bucketed by creation/modification date
+//! ├── transclusion.rs - ![[embed]] expansion for preview and export
 //! ├── utils.rs      - Security utilities (path validation)
+//! ├── vault_report.rs - Markdown vault health report
+//! ├── vcard.rs      - vCard contacts to person pages
+//! ├── web_snapshot.rs - Readable local copies of linked pages
+//! ├── windows.rs    - Document window registry (one window per file)
+//! ├── workspace_settings.rs - Per-workspace settings (.mdreader/settings.json)
 //! └── commands/     - Tauri command handlers
 //!     ├── file_operations.rs  - File CRUD operations
 //!     ├── file_watcher.rs     - File system watching
+//!     ├── external_editor.rs  - Opening notes in an external editor and watching its saves
+//!     ├── opener.rs           - Reveal in file manager and open with the default app
 //!     ├── workspace.rs        - Workspace management
-//!     └── import_export.rs    - Import/export operations
+//!     ├── import_export.rs    - Import/export operations
+//!     ├── settings.rs         - Workspace settings (folder flags, symlinks, attachments, export folders, statuses)
+//!     ├── jobs.rs             - Job listing and cancellation
+//!     ├── lifecycle.rs        - Suspend/resume of background work
+//!     ├── offline.rs          - Offline save journal status, replay and watcher
+//!     ├── hooks.rs            - Workspace hook setup, approval and test runs
+//!     ├── plugins.rs          - Plugin listing, approval, commands, transforms and exporters
+//!     ├── memory.rs           - Cache memory usage, budget and trimmer
+//!     ├── app_lock.rs         - App lock status, setting and unlock
+//!     ├── mindmap.rs          - Mindmap sidecar metadata and live outlines
+//!     ├── diagrams.rs         - Mermaid generation, diagram blocks, SVG cache and table charts
+//!     ├── graph.rs            - Backlinks and link graph metrics
+//!     ├── session.rs          - Session save/restore
+//!     ├── share.rs            - Shared items routed into the inbox note
+//!     ├── windows.rs          - Document windows
+//!     ├── thumbnails.rs       - Thumbnail generation
+//!     ├── pdf.rs              - PDF text and page previews
+//!     ├── audio.rs            - Voice memo recording and transcription
+//!     ├── ocr.rs              - Image text recognition
+//!     ├── dialogs.rs          - Native open/save/folder pickers
+//!     ├── backup.rs           - Backup, verify, restore, prune and the volume watcher
+//!     ├── link_check.rs       - External link checks, the scheduled checker, page snapshots and link titles
+//!     ├── tables.rs           - Table edits (rows, columns, sort, realign)
+//!     └── frontmatter.rs      - Frontmatter updates and note status workflow
 //! ```
 //! 
 //! ## Security
@@ -21,9 +135,98 @@
 //! directory traversal attacks. See `utils::validate_path_within_workspace`.
 
 // Core modules
+mod activity;
+mod app_lock;
+mod assets;
+mod audio;
+mod backup;
+mod backup_targets;
+mod bear;
+mod biometric;
+mod board;
+mod calendar;
+mod capabilities;
+mod capture;
+mod chart;
+mod command_guard;
 mod commands;
+mod convert;
+mod csv_table;
+mod diagrams;
+mod dialogs;
+mod diff;
+mod email;
+mod events;
+mod export_policy;
+mod external_editor;
+mod favorites;
+mod file_access;
+mod file_meta;
+mod flavor;
+mod frecency;
+mod frontmatter;
+mod git;
+mod headings;
+mod hooks;
+mod ignore;
+mod jobs;
+mod lifecycle;
+mod link_check;
+mod link_preview;
+mod links;
+mod lists;
+mod logseq;
+mod math;
+mod mentions;
+mod memory_budget;
+mod merge;
+mod mermaid;
+mod metadata_cache;
+mod metadata_schema;
+mod metrics;
+mod mindmap_meta;
+mod notes;
+mod ocr;
+mod offline_journal;
+mod open_file;
+mod outline;
+mod overview;
+mod paging;
+mod paste;
+mod pdf;
+mod people;
+mod plugin_runtime;
+mod plugins;
+mod print;
+mod related;
+mod references;
+mod render;
+mod rtf;
+mod safe_write;
+mod save_queue;
+mod search_index;
+mod session;
+mod share;
+mod snippets;
 mod state;
+mod storage;
+mod sync_conflicts;
+mod tables;
+mod tag_suggest;
+mod template;
+mod text_pdf;
+mod text_stats;
+mod thumbnails;
+mod timeline;
+mod transclusion;
+mod transcribe;
 mod utils;
+mod vault_report;
+mod vcard;
+mod web_snapshot;
+mod windows;
+mod workspace_settings;
+mod write_locks;
 
 // Import Tauri traits
 use tauri::Manager;
@@ -32,6 +235,7 @@ use tauri::Manager;
 pub use state::AppState;
 pub use utils::{
     validate_path_within_workspace,
+    validate_path_with_symlink_policy,
     validate_file_path,
     validate_directory_path,
     sanitize_filename,
@@ -41,9 +245,17 @@ pub use utils::{
 
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
+    let state = AppState::new();
+    let command_guard = state.command_guard.clone();
+    let app_lock = state.app_lock.clone();
+    
     tauri::Builder::default()
         // Register application state
-        .manage(AppState::new())
+        .manage(state)
+        // Scoped storage (SAF) on Android
+        .plugin(storage::plugin())
+        // Share sheet intake on mobile
+        .plugin(share::plugin())
         .setup(|app| {
             // Initialize logging in debug mode
             if cfg!(debug_assertions) {
@@ -57,11 +269,43 @@ pub fn run() {
             log::info!("🚀 MDReader starting up...");
             log::info!("📦 Version: {}", env!("CARGO_PKG_VERSION"));
             
+            // Biometric prompt on mobile (desktop uses the OS APIs directly)
+            #[cfg(mobile)]
+            app.handle().plugin(tauri_plugin_biometric::init())?;
+            
+            // An enabled app lock starts locked, and so does one whose
+            // setting cannot be loaded
+            let state = app.state::<AppState>();
+            if let Err(e) = commands::workspace::get_config_dir().and_then(|dir| state.app_lock.load(&dir)) {
+                state.app_lock.engage();
+                log::warn!("⚠️ App lock setting not loaded, starting locked: {}", e);
+            }
+            
+            // Saves journaled while the workspace was unreachable
+            if let Err(e) = commands::workspace::get_config_dir().and_then(|dir| state.offline_journal.load(&dir)) {
+                log::warn!("⚠️ Offline journal not loaded: {}", e);
+            }
+            
+            // Replay journaled saves once their workspace is back
+            commands::offline::spawn_journal_watcher(app.handle().clone());
+            
+            // Run scheduled backups, e.g. when a backup drive is mounted
+            commands::backup::spawn_volume_watcher(app.handle().clone());
+            
+            // Load persisted indexes and catch up with changes made while closed
+            commands::workspace::spawn_index_warm_start(app.handle().clone());
+            
+            // Check external links on the workspace's schedule
+            commands::link_check::spawn_link_checker(app.handle().clone());
+            
+            // Keep in-memory caches within the memory budget
+            commands::memory::spawn_memory_trimmer(app.handle().clone());
+            
             log::info!("✅ Application initialized successfully");
             
             Ok(())
         })
-        .invoke_handler(tauri::generate_handler![
+        .invoke_handler(with_app_lock(app_lock, with_rate_limits(command_guard, tauri::generate_handler![
             // =====================================================
             // File Operations (with path validation)
             // =====================================================
@@ -71,14 +315,37 @@ pub fn run() {
             commands::file_operations::load_document_from_file,
             commands::file_operations::create_new_file,
             commands::file_operations::delete_file,
+            commands::file_operations::secure_delete_file,
             commands::file_operations::save_workspace_config,
             commands::file_operations::load_workspace_config,
+            commands::file_operations::get_frequent_files,
+            commands::file_operations::toggle_favorite,
+            commands::file_operations::list_favorites,
+            commands::file_operations::get_activity,
             commands::file_operations::rename_file,
             commands::file_operations::rename_directory,
             commands::file_operations::delete_directory,
             commands::file_operations::copy_file,
             commands::file_operations::move_file,
             commands::file_operations::file_exists,
+            commands::file_operations::diff_files,
+            commands::file_operations::diagnose_file_access,
+            commands::file_operations::clear_readonly_attribute,
+            commands::file_operations::find_sync_conflicts,
+            commands::file_operations::resolve_sync_conflict,
+            commands::file_operations::queue_save,
+            commands::file_operations::flush_pending_saves,
+            commands::file_operations::read_section,
+            commands::file_operations::write_section,
+            commands::file_operations::normalize_links,
+            commands::file_operations::tidy_footnotes,
+            commands::file_operations::shift_headings,
+            commands::file_operations::enforce_single_h1,
+            commands::file_operations::fix_list_numbering,
+            commands::file_operations::analyze_text,
+            commands::file_operations::append_to_note,
+            commands::file_operations::prepend_to_note,
+            commands::file_operations::save_attachment,
             
             // =====================================================
             // Workspace Management
@@ -91,41 +358,338 @@ pub fn run() {
             commands::workspace::create_default_folders,
             commands::workspace::create_welcome_document,
             commands::workspace::list_workspace_contents,
+            commands::workspace::list_directory_page,
             commands::workspace::verify_workspace_path,
+            commands::workspace::get_scratchpad,
+            commands::workspace::set_scratchpad,
+            commands::workspace::get_backend_capabilities,
+            commands::workspace::get_workspace_overview,
+            commands::workspace::get_git_summary,
+            commands::workspace::get_notes_timeline,
+            commands::workspace::get_upcoming_events,
+            commands::workspace::get_metric_series,
+            commands::workspace::get_quick_open_index,
+            commands::workspace::search_notes,
+            commands::workspace::generate_vault_report,
             
             // =====================================================
             // Import/Export Operations
             // =====================================================
             commands::import_export::import_markdown_file,
             commands::import_export::import_folder,
+            commands::import_export::import_csv_as_table,
+            commands::import_export::update_csv_cell,
+            commands::import_export::import_org,
+            commands::import_export::import_asciidoc,
+            commands::import_export::import_logseq_graph,
+            commands::import_export::import_bear_backup,
+            commands::import_export::import_apple_notes,
+            commands::import_export::import_vcards,
             commands::import_export::export_document,
+            commands::import_export::convert_flavor,
+            commands::import_export::embed_document_assets,
+            commands::import_export::resolve_transclusions,
+            commands::import_export::render_note_html,
+            commands::import_export::copy_as_html,
+            commands::import_export::copy_as_rtf,
+            commands::import_export::clean_pasted_content,
+            commands::import_export::share_via_email,
+            commands::import_export::print_document,
+            commands::import_export::export_workspace_to_zip,
+            commands::import_export::export_query_results,
+            commands::import_export::export_calendar,
             
             // =====================================================
             // File Watching (with state management)
             // =====================================================
             commands::file_watcher::watch_directory,
             commands::file_watcher::stop_watching,
+            commands::file_watcher::watch_file,
+            commands::file_watcher::unwatch_file,
             commands::file_watcher::get_file_metadata,
             commands::file_watcher::list_active_watchers,
             commands::file_watcher::stop_all_watchers,
-        ])
+            
+            // =====================================================
+            // External Editor
+            // =====================================================
+            commands::external_editor::get_external_editor,
+            commands::external_editor::set_external_editor,
+            commands::external_editor::open_in_external_editor,
+            
+            // =====================================================
+            // Reveal / Open With
+            // =====================================================
+            commands::opener::reveal_in_file_manager,
+            commands::opener::open_with_default_app,
+            
+            // =====================================================
+            // Workspace Settings
+            // =====================================================
+            commands::settings::get_workspace_settings,
+            commands::settings::set_folder_flags,
+            commands::settings::set_symlink_policy,
+            commands::settings::set_respect_gitignore,
+            commands::settings::set_attachment_settings,
+            commands::settings::get_export_folders,
+            commands::settings::set_export_folders,
+            commands::settings::set_status_values,
+            commands::settings::set_template_variables,
+            commands::settings::set_metadata_schema,
+            commands::settings::set_backup_targets,
+            commands::settings::set_link_check_schedule,
+            commands::settings::set_list_format,
+            
+            // =====================================================
+            // Long-Running Jobs
+            // =====================================================
+            commands::jobs::cancel_job,
+            commands::jobs::list_jobs,
+            
+            // =====================================================
+            // App Lifecycle (mobile suspend/resume)
+            // =====================================================
+            commands::lifecycle::suspend_background_tasks,
+            commands::lifecycle::resume_background_tasks,
+            
+            // =====================================================
+            // Offline Save Journal
+            // =====================================================
+            commands::offline::get_offline_journal,
+            commands::offline::replay_offline_journal,
+            
+            // =====================================================
+            // Workspace Hooks
+            // =====================================================
+            commands::hooks::get_workspace_hooks,
+            commands::hooks::set_workspace_hooks,
+            commands::hooks::approve_workspace_hooks,
+            commands::hooks::run_workspace_hook,
+            
+            // =====================================================
+            // Workspace Plugins
+            // =====================================================
+            commands::plugins::list_plugins,
+            commands::plugins::approve_plugin,
+            commands::plugins::revoke_plugin,
+            commands::plugins::run_plugin_command,
+            commands::plugins::transform_note_with_plugin,
+            commands::plugins::export_with_plugin,
+            
+            // =====================================================
+            // Memory Budget
+            // =====================================================
+            commands::memory::get_memory_usage,
+            commands::memory::set_memory_budget,
+            
+            // =====================================================
+            // App Lock
+            // =====================================================
+            commands::app_lock::get_app_lock_status,
+            commands::app_lock::set_app_lock,
+            commands::app_lock::unlock_app,
+            commands::app_lock::lock_app,
+            
+            // =====================================================
+            // Mindmap Metadata
+            // =====================================================
+            commands::mindmap::get_mindmap_meta,
+            commands::mindmap::set_mindmap_meta,
+            commands::mindmap::open_outline,
+            commands::mindmap::apply_outline_edit,
+            commands::mindmap::locate_node,
+            commands::mindmap::close_outline,
+            commands::mindmap::extract_subtree_to_note,
+            commands::mindmap::merge_notes,
+            
+            // =====================================================
+            // Diagrams
+            // =====================================================
+            commands::diagrams::generate_mermaid,
+            commands::diagrams::extract_diagrams,
+            commands::diagrams::get_cached_diagram,
+            commands::diagrams::cache_diagram,
+            commands::diagrams::render_diagram,
+            commands::diagrams::get_plantuml_config,
+            commands::diagrams::set_plantuml_config,
+            commands::diagrams::render_table_chart,
+            
+            // =====================================================
+            // Link Graph
+            // =====================================================
+            commands::graph::get_backlinks,
+            commands::graph::list_people,
+            commands::graph::get_person_mentions,
+            commands::link_check::check_external_links,
+            commands::link_check::snapshot_link,
+            commands::link_check::fetch_link_title,
+            commands::graph::get_graph_metrics,
+            commands::graph::get_related_notes,
+            commands::graph::find_unlinked_mentions,
+            commands::graph::link_mention,
+            
+            // =====================================================
+            // Session
+            // =====================================================
+            commands::session::save_session,
+            commands::session::load_session,
+            
+            // =====================================================
+            // Share Intake
+            // =====================================================
+            commands::share::import_pending_shares,
+            commands::share::receive_shared_items,
+            
+            // =====================================================
+            // Document Windows
+            // =====================================================
+            commands::windows::open_document_window,
+            commands::windows::list_document_windows,
+            
+            // =====================================================
+            // Thumbnails
+            // =====================================================
+            commands::thumbnails::get_thumbnail,
+            
+            // =====================================================
+            // PDF Attachments
+            // =====================================================
+            commands::pdf::extract_pdf_text,
+            commands::pdf::get_pdf_page_thumbnail,
+            
+            // =====================================================
+            // Audio Notes
+            // =====================================================
+            commands::audio::start_audio_recording,
+            commands::audio::stop_audio_recording,
+            commands::audio::transcribe_audio,
+            
+            // =====================================================
+            // Image Text Recognition
+            // =====================================================
+            commands::ocr::ocr_image,
+            
+            // =====================================================
+            // Native Dialogs
+            // =====================================================
+            commands::dialogs::choose_export_destination,
+            commands::dialogs::pick_save_path,
+            commands::dialogs::pick_files_to_import,
+            commands::dialogs::pick_folder,
+            commands::dialogs::cancel_dialog,
+            
+            // =====================================================
+            // Frontmatter and Note Status
+            // =====================================================
+            commands::frontmatter::update_frontmatter,
+            commands::frontmatter::set_note_status,
+            commands::frontmatter::list_notes_by_status,
+            commands::frontmatter::validate_note_metadata,
+            commands::frontmatter::find_schema_violations,
+            commands::frontmatter::suggest_tags,
+            commands::frontmatter::add_note_tags,
+            commands::frontmatter::create_alias_redirect,
+            
+            // =====================================================
+            // Tables
+            // =====================================================
+            commands::tables::table_ops,
+            
+            // =====================================================
+            // Templates and Snippets
+            // =====================================================
+            commands::templates::render_template,
+            commands::templates::list_snippets,
+            commands::templates::save_snippet,
+            commands::templates::delete_snippet,
+            commands::templates::expand_snippet,
+            
+            // =====================================================
+            // Backups
+            // =====================================================
+            commands::backup::create_backup,
+            commands::backup::list_backups,
+            commands::backup::verify_backup,
+            commands::backup::restore_backup,
+            commands::backup::delete_backup,
+            commands::backup::prune_backups,
+            commands::backup::get_backup_status,
+        ])))
         .on_window_event(|window, event| {
             // Handle window close for cleanup
             if let tauri::WindowEvent::CloseRequested { .. } = event {
+                let state: tauri::State<'_, AppState> = window.state();
+                
+                // Document windows only release their own file
+                if windows::is_document_window(window.label()) {
+                    commands::windows::release_document_window(&state, window.label());
+                    return;
+                }
+                
+                // Print windows hold no state of their own
+                if window.label().starts_with(print::PRINT_WINDOW_PREFIX) {
+                    return;
+                }
+                
                 log::info!("🔒 Window close requested, cleaning up...");
                 
-                // Get app state and clean up watchers
-                let state: tauri::State<'_, AppState> = window.state();
+                // Clean up watchers
                 match state.clear_all_watchers() {
                     Ok(count) => log::info!("🧹 Cleaned up {} file watchers", count),
                     Err(e) => log::error!("❌ Failed to clean up watchers: {}", e),
                 }
+                if let Err(e) = state.open_files.clear() {
+                    log::error!("❌ Failed to clean up open file watchers: {}", e);
+                }
+                
+                // Ask running jobs to stop at their next checkpoint
+                if let Ok(count) = state.jobs.cancel_all() {
+                    if count > 0 {
+                        log::info!("🛑 Cancelled {} running jobs", count);
+                    }
+                }
+                
+                // Write out any debounced saves that are still pending
+                match state.save_queue.flush_all() {
+                    Ok(count) => log::info!("💾 Flushed {} pending saves", count),
+                    Err(e) => log::error!("❌ Failed to flush pending saves: {}", e),
+                }
             }
         })
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
 }
 
+/// Wraps the command handler so every call passes its rate limit first
+/// (see `command_guard`); rejected calls fail with a `Rate limited:` error.
+fn with_rate_limits(
+    guard: command_guard::CommandGuard,
+    handler: impl Fn(tauri::ipc::Invoke) -> bool + Send + Sync + 'static,
+) -> impl Fn(tauri::ipc::Invoke) -> bool + Send + Sync + 'static {
+    move |invoke| {
+        if let Err(e) = guard.check(invoke.message.command(), std::time::Instant::now()) {
+            invoke.resolver.reject(e);
+            return true;
+        }
+        handler(invoke)
+    }
+}
+
+/// Wraps the command handler so calls are rejected with an `App locked:`
+/// error while the app lock is engaged (see `app_lock`).
+fn with_app_lock(
+    lock: app_lock::AppLock,
+    handler: impl Fn(tauri::ipc::Invoke) -> bool + Send + Sync + 'static,
+) -> impl Fn(tauri::ipc::Invoke) -> bool + Send + Sync + 'static {
+    move |invoke| {
+        if let Err(e) = lock.check(invoke.message.command()) {
+            invoke.resolver.reject(e);
+            return true;
+        }
+        handler(invoke)
+    }
+}
+
 // ============================================================================
 // INTEGRATION TESTS
 // ============================================================================
@@ -0,0 +1,153 @@
+//! App Lifecycle (Suspend/Resume) for MDReader
+//!
+//! Mobile systems suspend an app sent to the background and may kill it
+//! later without notice. While suspended, background work must stop:
+//! watchers are dropped, running jobs park at their next checkpoint (see
+//! `JobHandle::checkpoint`) and the polling threads skip their passes. On
+//! resume the watchers that were dropped are recreated and parked jobs go
+//! on where they stopped.
+//!
+//! This module only tracks the state; the suspend and resume steps live in
+//! `commands::lifecycle`.
+
+use std::sync::{Arc, Condvar, Mutex};
+use std::time::Duration;
+
+/// How often a parked job re-checks its cancellation flag
+const PARK_RECHECK_MS: u64 = 500;
+
+/// State kept between suspend and resume
+#[derive(Debug, Clone, Default)]
+struct Suspension {
+    since: Option<chrono::DateTime<chrono::Utc>>,
+    /// Directories that were watched when the app was suspended
+    watched_directories: Vec<String>,
+}
+
+/// What `Lifecycle::resume` hands back
+#[derive(Debug, Clone, PartialEq)]
+pub struct Resumed {
+    pub suspended_secs: i64,
+    pub watched_directories: Vec<String>,
+}
+
+/// Foreground/background state of the app (internally synchronized)
+#[derive(Debug, Clone, Default)]
+pub struct Lifecycle {
+    state: Arc<(Mutex<Suspension>, Condvar)>,
+}
+
+impl Lifecycle {
+    /// Creates a lifecycle in the foreground
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Marks the app as suspended, remembering the watched directories
+    ///
+    /// # Returns
+    /// * `Ok(false)` - If the app was already suspended (nothing changes)
+    pub fn suspend(&self, watched_directories: Vec<String>) -> Result<bool, String> {
+        let mut suspension = self.lock()?;
+        if suspension.since.is_some() {
+            return Ok(false);
+        }
+        *suspension = Suspension {
+            since: Some(chrono::Utc::now()),
+            watched_directories,
+        };
+        Ok(true)
+    }
+
+    /// Marks the app as back in the foreground and wakes parked jobs
+    ///
+    /// # Returns
+    /// * `Ok(None)` - If the app was not suspended
+    pub fn resume(&self) -> Result<Option<Resumed>, String> {
+        let mut suspension = self.lock()?;
+        let Some(since) = suspension.since else {
+            return Ok(None);
+        };
+        let resumed = Resumed {
+            suspended_secs: (chrono::Utc::now() - since).num_seconds(),
+            watched_directories: std::mem::take(&mut suspension.watched_directories),
+        };
+        *suspension = Suspension::default();
+        self.state.1.notify_all();
+        Ok(Some(resumed))
+    }
+
+    /// Checks if the app is suspended
+    pub fn is_suspended(&self) -> bool {
+        self.state.0
+            .lock()
+            .map(|s| s.since.is_some())
+            .unwrap_or(false)
+    }
+
+    /// Blocks while the app is suspended, or until `cancelled` returns true
+    pub fn wait_while_suspended(&self, cancelled: impl Fn() -> bool) {
+        let Ok(mut suspension) = self.state.0.lock() else {
+            return;
+        };
+        while suspension.since.is_some() && !cancelled() {
+            suspension = match self.state.1.wait_timeout(suspension, Duration::from_millis(PARK_RECHECK_MS)) {
+                Ok((guard, _)) => guard,
+                Err(_) => return,
+            };
+        }
+    }
+
+    fn lock(&self) -> Result<std::sync::MutexGuard<'_, Suspension>, String> {
+        self.state.0
+            .lock()
+            .map_err(|e| format!("Failed to lock lifecycle: {}", e))
+    }
+}
+
+// ============================================================================
+// TESTS
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::time::Instant;
+
+    #[test]
+    fn test_suspend_and_resume() {
+        let lifecycle = Lifecycle::new();
+        assert!(lifecycle.resume().unwrap().is_none());
+
+        assert!(lifecycle.suspend(vec!["/ws".to_string()]).unwrap());
+        assert!(lifecycle.is_suspended());
+        // A second suspend keeps the directories of the first
+        assert!(!lifecycle.suspend(Vec::new()).unwrap());
+
+        let resumed = lifecycle.resume().unwrap().unwrap();
+        assert_eq!(resumed.watched_directories, vec!["/ws".to_string()]);
+        assert!(!lifecycle.is_suspended());
+    }
+
+    #[test]
+    fn test_parked_worker_wakes_on_resume() {
+        let lifecycle = Lifecycle::new();
+        lifecycle.suspend(Vec::new()).unwrap();
+
+        let parked = lifecycle.clone();
+        let worker = std::thread::spawn(move || {
+            let start = Instant::now();
+            parked.wait_while_suspended(|| false);
+            start.elapsed()
+        });
+        std::thread::sleep(Duration::from_millis(50));
+        lifecycle.resume().unwrap();
+        assert!(worker.join().unwrap() >= Duration::from_millis(50));
+
+        // Cancellation also releases a parked worker
+        lifecycle.suspend(Vec::new()).unwrap();
+        let cancelled = AtomicBool::new(true);
+        lifecycle.wait_while_suspended(|| cancelled.load(Ordering::SeqCst));
+    }
+}
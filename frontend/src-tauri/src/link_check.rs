@@ -0,0 +1,589 @@
+//! External Link Health
+//!
+//! Checks the `http(s)` links of notes: markdown links, `<autolinks>` and
+//! bare URLs outside code. Each URL is requested with `HEAD`, or `GET` when
+//! a server refuses `HEAD`; redirects are followed by hand so the final
+//! target can be suggested as the link's replacement.
+//!
+//! Checks are polite to the sites they visit: URLs are grouped by host and
+//! each host gets one request at a time, spaced by `domain_interval_ms`,
+//! while up to `concurrency` hosts are checked in parallel. Results are
+//! cached in `.mdreader/link_check.json` for `max_age_hours`, so a re-run or
+//! a scheduled run (see `LinkCheckSchedule`) only visits new or expired
+//! URLs. `429 Too Many Requests` answers are reported but not cached.
+//!
+//! Only public addresses are requested (see `link_preview::public_addresses`):
+//! a note cannot make a scheduled check probe the local network.
+
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use std::fs;
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{mpsc, Mutex};
+use std::time::{Duration, Instant};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use crate::link_preview::public_addresses;
+use crate::workspace_settings::SETTINGS_DIR;
+
+/// File name of the result cache inside the settings directory
+pub const CACHE_FILE: &str = "link_check.json";
+
+/// Redirects followed before a link counts as broken
+pub const MAX_REDIRECTS: usize = 10;
+
+/// Hosts checked in parallel at most
+pub const MAX_CONCURRENCY: usize = 16;
+
+/// Cached results not confirmed for this many days are dropped
+const PRUNE_AFTER_DAYS: i64 = 30;
+
+/// Statuses after which `HEAD` is retried with `GET` (servers that do not
+/// implement or allow `HEAD`)
+const HEAD_REFUSED: &[u16] = &[400, 403, 404, 405, 501];
+
+const USER_AGENT: &str = concat!("MDReader/", env!("CARGO_PKG_VERSION"), " (link checker)");
+
+/// How links are checked
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LinkCheckOptions {
+    /// Hosts checked in parallel (1 to `MAX_CONCURRENCY`)
+    pub concurrency: usize,
+    /// Minimum time between two requests to the same host
+    pub domain_interval_ms: u64,
+    /// Timeout of a single request
+    pub timeout_secs: u64,
+    /// Cached results younger than this are reused
+    pub max_age_hours: u32,
+}
+
+impl Default for LinkCheckOptions {
+    fn default() -> Self {
+        Self { concurrency: 6, domain_interval_ms: 1000, timeout_secs: 10, max_age_hours: 24 }
+    }
+}
+
+/// Scheduled link checks of a workspace (part of the workspace settings)
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct LinkCheckSchedule {
+    /// Hours between scheduled checks of the whole workspace (0 = off)
+    #[serde(default)]
+    pub interval_hours: u32,
+    #[serde(default)]
+    pub options: LinkCheckOptions,
+}
+
+impl LinkCheckSchedule {
+    /// Checks if a scheduled run is due, given the end of the last run
+    pub fn is_due(&self, last_run: Option<DateTime<Utc>>, now: DateTime<Utc>) -> bool {
+        self.interval_hours > 0
+            && last_run.map_or(true, |last| now - last >= chrono::Duration::hours(self.interval_hours as i64))
+    }
+}
+
+/// Outcome of checking a URL
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LinkState {
+    Ok,
+    /// Answers from another URL
+    Redirected,
+    /// Error status, too many redirects, or unreachable
+    Broken,
+    /// The host asked to slow down; not checked this time
+    RateLimited,
+}
+
+/// Checked state of a URL
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct UrlStatus {
+    pub state: LinkState,
+    /// Final HTTP status (none if the host was unreachable)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub status: Option<u16>,
+    /// Where redirects lead
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub final_url: Option<String>,
+    /// Every redirect on the way was permanent (301/308)
+    #[serde(default)]
+    pub permanent: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    pub checked_at: DateTime<Utc>,
+}
+
+/// Where a link is written
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+pub struct LinkOccurrence {
+    /// Note path relative to the workspace root
+    pub path: String,
+    /// 1-based line
+    pub line: usize,
+}
+
+/// A dead or redirected link
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct LinkProblem {
+    pub url: String,
+    pub state: LinkState,
+    pub status: Option<u16>,
+    pub error: Option<String>,
+    /// Replacement for a redirected link: the final redirect target
+    pub suggestion: Option<String>,
+    /// Every redirect was permanent, so replacing the link is safe
+    pub permanent: bool,
+    pub occurrences: Vec<LinkOccurrence>,
+}
+
+/// Result of a link check
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct LinkCheckReport {
+    /// Distinct URLs found
+    pub urls: usize,
+    /// URLs requested in this run (the others came from the cache)
+    pub checked: usize,
+    pub broken: Vec<LinkProblem>,
+    pub redirected: Vec<LinkProblem>,
+    /// URLs whose host asked to slow down
+    pub rate_limited: Vec<String>,
+    /// The check was cancelled before every URL was checked
+    pub cancelled: bool,
+}
+
+/// Cached URL results of a workspace
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct LinkCache {
+    /// End of the last run over the whole workspace
+    #[serde(default)]
+    pub last_run: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub urls: BTreeMap<String, UrlStatus>,
+}
+
+impl LinkCache {
+    /// Loads the cache of a workspace (empty if none is saved)
+    pub fn load(workspace_root: &Path) -> Result<Self, String> {
+        let path = workspace_root.join(SETTINGS_DIR).join(CACHE_FILE);
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let json = fs::read_to_string(&path)
+            .map_err(|e| format!("Failed to read link check cache: {}", e))?;
+        serde_json::from_str(&json)
+            .map_err(|e| format!("Failed to parse link check cache: {}", e))
+    }
+
+    /// Saves the cache, dropping long-unconfirmed results and creating
+    /// `.mdreader/` if needed
+    pub fn save(&mut self, workspace_root: &Path, now: DateTime<Utc>) -> Result<(), String> {
+        self.urls.retain(|_, status| now - status.checked_at < chrono::Duration::days(PRUNE_AFTER_DAYS));
+
+        let dir = workspace_root.join(SETTINGS_DIR);
+        fs::create_dir_all(&dir)
+            .map_err(|e| format!("Failed to create settings directory: {}", e))?;
+        let json = serde_json::to_string_pretty(self)
+            .map_err(|e| format!("Failed to serialize link check cache: {}", e))?;
+        fs::write(dir.join(CACHE_FILE), json)
+            .map_err(|e| format!("Failed to save link check cache: {}", e))
+    }
+
+    // Helper: A cached result younger than `max_age_hours`
+    fn fresh(&self, url: &str, max_age_hours: u32, now: DateTime<Utc>) -> Option<&UrlStatus> {
+        self.urls
+            .get(url)
+            .filter(|status| now - status.checked_at < chrono::Duration::hours(max_age_hours as i64))
+    }
+}
+
+/// One HTTP response, as far as link checking cares
+#[derive(Debug, Clone, PartialEq)]
+pub struct Hop {
+    pub status: u16,
+    /// `Location` header of a redirect
+    pub location: Option<String>,
+}
+
+/// Sends single requests (no redirects followed)
+pub trait Probe: Sync {
+    /// Sends a `HEAD` or `GET` request; `Err` if the host is unreachable
+    fn request(&self, method: &str, url: &str) -> Result<Hop, String>;
+}
+
+/// `Probe` over HTTP; hosts on loopback, private or link-local addresses
+/// are reported unreachable rather than requested
+pub struct HttpProbe {
+    agent: ureq::Agent,
+}
+
+impl HttpProbe {
+    pub fn new(timeout: Duration) -> Self {
+        let agent = ureq::AgentBuilder::new()
+            .redirects(0)
+            .timeout(timeout)
+            .user_agent(USER_AGENT)
+            .resolver(public_addresses)
+            .build();
+        Self { agent }
+    }
+}
+
+impl Probe for HttpProbe {
+    fn request(&self, method: &str, url: &str) -> Result<Hop, String> {
+        let response = match self.agent.request(method, url).call() {
+            Ok(response) => response,
+            Err(ureq::Error::Status(_, response)) => response,
+            Err(ureq::Error::Transport(e)) => return Err(e.to_string()),
+        };
+        Ok(Hop { status: response.status(), location: response.header("Location").map(String::from) })
+    }
+}
+
+/// Finds the external links of a note body, with their 1-based lines
+pub fn external_links(body: &str, first_line: usize) -> Vec<(String, usize)> {
+    let mut links: Vec<(String, usize)> = Vec::new();
+    let mut in_fence = false;
+    for (i, line) in body.lines().enumerate() {
+        let trimmed = line.trim_start();
+        if trimmed.starts_with("```") || trimmed.starts_with("~~~") {
+            in_fence = !in_fence;
+            continue;
+        }
+        if in_fence {
+            continue;
+        }
+        // Odd backtick segments are inline code
+        for segment in line.split('`').step_by(2) {
+            for (at, _) in segment.match_indices("http") {
+                if segment[..at].chars().next_back().is_some_and(char::is_alphanumeric) {
+                    continue;
+                }
+                if let Some(url) = url_at(&segment[at..]) {
+                    let link = (url.to_string(), first_line + i);
+                    if !links.contains(&link) {
+                        links.push(link);
+                    }
+                }
+            }
+        }
+    }
+    links
+}
+
+/// Checks the URLs of `links` that have no fresh cached result, and
+/// reports the broken and redirected ones
+///
+/// `on_progress` receives the URLs checked so far, the URLs to check and
+/// the last URL; the check stops when it returns false.
+pub fn check_links(
+    links: &BTreeMap<String, Vec<LinkOccurrence>>,
+    cache: &mut LinkCache,
+    options: &LinkCheckOptions,
+    probe: &dyn Probe,
+    on_progress: &mut dyn FnMut(usize, usize, &str) -> bool,
+) -> LinkCheckReport {
+    let now = Utc::now();
+    let pending: Vec<&String> = links
+        .keys()
+        .filter(|url| cache.fresh(url, options.max_age_hours, now).is_none())
+        .collect();
+
+    // One queue per host, so a host never sees parallel requests
+    let mut by_host: BTreeMap<String, Vec<String>> = BTreeMap::new();
+    for url in &pending {
+        by_host.entry(host(url)).or_default().push(url.to_string());
+    }
+    let queue: Mutex<VecDeque<Vec<String>>> = Mutex::new(by_host.into_values().collect());
+    let workers = options.concurrency.clamp(1, MAX_CONCURRENCY).min(pending.len().max(1));
+    let interval = Duration::from_millis(options.domain_interval_ms);
+    let stop = AtomicBool::new(false);
+    let (sender, receiver) = mpsc::sync_channel::<(String, UrlStatus)>(workers);
+
+    let mut checked: HashMap<String, UrlStatus> = HashMap::new();
+    std::thread::scope(|scope| {
+        for _ in 0..workers {
+            let sender = sender.clone();
+            let (queue, stop) = (&queue, &stop);
+            scope.spawn(move || {
+                while let Some(urls) = queue.lock().ok().and_then(|mut queue| queue.pop_front()) {
+                    let mut last_request: Option<Instant> = None;
+                    for url in urls {
+                        if stop.load(Ordering::Relaxed) {
+                            return;
+                        }
+                        if let Some(wait) = last_request.and_then(|last| interval.checked_sub(last.elapsed())) {
+                            std::thread::sleep(wait);
+                        }
+                        last_request = Some(Instant::now());
+                        let status = check_url(probe, &url);
+                        if sender.send((url, status)).is_err() {
+                            return;
+                        }
+                    }
+                }
+            });
+        }
+        drop(sender);
+
+        for (url, status) in receiver {
+            checked.insert(url.clone(), status);
+            if !on_progress(checked.len(), pending.len(), &url) {
+                stop.store(true, Ordering::Relaxed);
+            }
+        }
+    });
+
+    let mut report = LinkCheckReport {
+        urls: links.len(),
+        checked: checked.len(),
+        cancelled: checked.len() < pending.len(),
+        ..LinkCheckReport::default()
+    };
+    for (url, occurrences) in links {
+        let status = match checked.remove(url) {
+            Some(status) => {
+                if status.state != LinkState::RateLimited {
+                    cache.urls.insert(url.clone(), status.clone());
+                }
+                status
+            }
+            None => match cache.urls.get(url) {
+                Some(status) => status.clone(),
+                None => continue,
+            },
+        };
+        let problem = |state| LinkProblem {
+            url: url.clone(),
+            state,
+            status: status.status,
+            error: status.error.clone(),
+            suggestion: status.final_url.clone().filter(|_| state == LinkState::Redirected),
+            permanent: status.permanent,
+            occurrences: occurrences.clone(),
+        };
+        match status.state {
+            LinkState::Ok => {}
+            LinkState::Broken => report.broken.push(problem(LinkState::Broken)),
+            LinkState::Redirected => report.redirected.push(problem(LinkState::Redirected)),
+            LinkState::RateLimited => report.rate_limited.push(url.clone()),
+        }
+    }
+    report
+}
+
+/// Checks one URL, following redirects
+pub fn check_url(probe: &dyn Probe, url: &str) -> UrlStatus {
+    let mut status = UrlStatus {
+        state: LinkState::Ok,
+        status: None,
+        final_url: None,
+        permanent: true,
+        error: None,
+        checked_at: Utc::now(),
+    };
+    let mut current = url.to_string();
+
+    for _ in 0..=MAX_REDIRECTS {
+        let mut result = probe.request("HEAD", &current);
+        if matches!(&result, Ok(hop) if HEAD_REFUSED.contains(&hop.status)) {
+            result = probe.request("GET", &current);
+        }
+        let hop = match result {
+            Ok(hop) => hop,
+            Err(e) => {
+                status.state = LinkState::Broken;
+                status.error = Some(e);
+                return status;
+            }
+        };
+        status.status = Some(hop.status);
+
+        match hop.location.filter(|_| (300..400).contains(&hop.status)) {
+            Some(location) => {
+                status.permanent &= matches!(hop.status, 301 | 308);
+                current = resolve_location(&current, &location);
+            }
+            None => {
+                // Adding or removing a trailing slash is not worth reporting
+                let moved = current.trim_end_matches('/') != url.trim_end_matches('/');
+                status.state = match hop.status {
+                    429 => LinkState::RateLimited,
+                    400.. => LinkState::Broken,
+                    _ if moved => LinkState::Redirected,
+                    _ => LinkState::Ok,
+                };
+                status.permanent &= moved;
+                status.final_url = moved.then_some(current);
+                return status;
+            }
+        }
+    }
+
+    status.state = LinkState::Broken;
+    status.permanent = false;
+    status.error = Some(format!("More than {} redirects", MAX_REDIRECTS));
+    status
+}
+
+// Helper: The URL starting `text`, without trailing punctuation and
+// unbalanced closing parentheses
+pub(crate) fn url_at(text: &str) -> Option<&str> {
+    if !text.starts_with("http://") && !text.starts_with("https://") {
+        return None;
+    }
+    let end = text
+        .find(|c: char| c.is_whitespace() || matches!(c, '<' | '>' | '"' | '[' | ']'))
+        .unwrap_or(text.len());
+    let mut url = &text[..end];
+    loop {
+        let trimmed = url.trim_end_matches(['.', ',', ';', ':', '!', '?', '\'', '*', '_']);
+        let trimmed = match trimmed.strip_suffix(')') {
+            Some(inner) if trimmed.matches('(').count() < trimmed.matches(')').count() => inner,
+            _ => trimmed,
+        };
+        if trimmed == url {
+            break;
+        }
+        url = trimmed;
+    }
+    let host = &url[url.find("://")? + 3..];
+    (!host.is_empty() && !host.starts_with('/')).then_some(url)
+}
+
+// Helper: Lowercase host (and port) of a URL
+pub(crate) fn host(url: &str) -> String {
+    let rest = url.split_once("://").map_or(url, |(_, rest)| rest);
+    let authority = rest.split(['/', '?', '#']).next().unwrap_or(rest);
+    let host = authority.rsplit_once('@').map_or(authority, |(_, host)| host);
+    host.to_lowercase()
+}
+
+// Helper: Resolves a `Location` header against the URL that sent it
+pub(crate) fn resolve_location(base: &str, location: &str) -> String {
+    let location = location.trim();
+    if location.contains("://") {
+        return location.to_string();
+    }
+    let (scheme, rest) = base.split_once("://").unwrap_or(("https", base));
+    if let Some(network_path) = location.strip_prefix("//") {
+        return format!("{}://{}", scheme, network_path);
+    }
+    let authority_end = rest.find(['/', '?', '#']).unwrap_or(rest.len());
+    let origin = format!("{}://{}", scheme, &rest[..authority_end]);
+    if location.starts_with('/') {
+        return format!("{}{}", origin, location);
+    }
+    let path = rest[authority_end..].split(['?', '#']).next().unwrap_or("");
+    let folder = path.rsplit_once('/').map_or("", |(folder, _)| folder);
+    format!("{}{}/{}", origin, folder, location)
+}
+
+// ============================================================================
+// TESTS
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Answers from a table; unknown URLs are unreachable
+    struct FakeProbe(HashMap<&'static str, (u16, Option<&'static str>)>);
+
+    impl Probe for FakeProbe {
+        fn request(&self, method: &str, url: &str) -> Result<Hop, String> {
+            let &(status, location) = self.0.get(url).ok_or("connection refused")?;
+            // This server refuses HEAD
+            if method == "HEAD" && url.contains("nohead") {
+                return Ok(Hop { status: 405, location: None });
+            }
+            Ok(Hop { status, location: location.map(String::from) })
+        }
+    }
+
+    #[test]
+    fn test_external_links() {
+        let body = "See [docs](https://a.io/docs \"Docs\") and <http://b.io/x>.\n\
+            Wiki: https://en.wikipedia.org/wiki/Rust_(language), done.\n\
+            (also https://c.io/page).\n`https://code.io` ```\n```\nhttps://fenced.io\n```\n\
+            mailto:x@https.io xhttps://no.io https://";
+        let links = external_links(body, 3);
+        let urls: Vec<(&str, usize)> = links.iter().map(|(url, line)| (url.as_str(), *line)).collect();
+        assert_eq!(
+            urls,
+            [
+                ("https://a.io/docs", 3),
+                ("http://b.io/x", 3),
+                ("https://en.wikipedia.org/wiki/Rust_(language)", 4),
+                ("https://c.io/page", 5),
+            ]
+        );
+
+        assert_eq!(resolve_location("https://a.io/x/y?q=1", "/new"), "https://a.io/new");
+        assert_eq!(resolve_location("https://a.io/x/y", "z"), "https://a.io/x/z");
+        assert_eq!(resolve_location("http://a.io/x", "//b.io/"), "http://b.io/");
+        assert_eq!(host("https://user@Example.COM:8080/path"), "example.com:8080");
+    }
+
+    #[test]
+    fn test_check_links() {
+        let probe = FakeProbe(HashMap::from([
+            ("https://ok.io/", (200, None)),
+            ("https://ok.io/slash", (301, Some("/slash/"))),
+            ("https://ok.io/slash/", (200, None)),
+            ("http://moved.io/a", (301, Some("https://moved.io/a"))),
+            ("https://moved.io/a", (302, Some("/b"))),
+            ("https://moved.io/b", (200, None)),
+            ("https://perm.io/old", (308, Some("https://perm.io/new"))),
+            ("https://perm.io/new", (200, None)),
+            ("https://gone.io/", (404, None)),
+            ("https://nohead.io/", (200, None)),
+            ("https://busy.io/", (429, None)),
+            ("https://loop.io/", (302, Some("https://loop.io/"))),
+        ]));
+        let at = |path: &str, line| vec![LinkOccurrence { path: path.to_string(), line }];
+        let mut links: BTreeMap<String, Vec<LinkOccurrence>> = BTreeMap::new();
+        for url in [
+            "https://ok.io/", "https://ok.io/slash", "http://moved.io/a", "https://perm.io/old", "https://gone.io/",
+            "https://nohead.io/", "https://busy.io/", "https://loop.io/", "https://down.io/",
+        ] {
+            links.insert(url.to_string(), at("a.md", 1));
+        }
+
+        let mut cache = LinkCache::default();
+        let options = LinkCheckOptions { concurrency: 4, domain_interval_ms: 0, ..LinkCheckOptions::default() };
+        let mut calls = 0;
+        let report = check_links(&links, &mut cache, &options, &probe, &mut |_, _, _| {
+            calls += 1;
+            true
+        });
+        assert_eq!((report.urls, report.checked, calls, report.cancelled), (9, 9, 9, false));
+
+        let broken: Vec<(&str, Option<u16>)> = report.broken.iter().map(|p| (p.url.as_str(), p.status)).collect();
+        assert_eq!(broken, [("https://down.io/", None), ("https://gone.io/", Some(404)), ("https://loop.io/", Some(302))]);
+        let redirected: Vec<(&str, Option<&str>, bool)> =
+            report.redirected.iter().map(|p| (p.url.as_str(), p.suggestion.as_deref(), p.permanent)).collect();
+        assert_eq!(
+            redirected,
+            [("http://moved.io/a", Some("https://moved.io/b"), false), ("https://perm.io/old", Some("https://perm.io/new"), true)]
+        );
+        assert_eq!(report.rate_limited, ["https://busy.io/"]);
+
+        // Cached results are reused; rate-limited URLs are retried
+        assert_eq!(cache.urls.len(), 8);
+        let again = check_links(&links, &mut cache, &options, &probe, &mut |_, _, _| true);
+        assert_eq!((again.checked, again.broken.len(), again.redirected.len()), (1, 3, 2));
+
+        // Cancelling stops the run
+        let mut fresh = LinkCache::default();
+        let serial = LinkCheckOptions { concurrency: 1, ..options };
+        let cancelled = check_links(&links, &mut fresh, &serial, &probe, &mut |_, _, _| false);
+        assert!(cancelled.cancelled);
+        assert!(cancelled.checked < links.len());
+
+        let schedule = LinkCheckSchedule { interval_hours: 24, options: LinkCheckOptions::default() };
+        let now = Utc::now();
+        assert!(schedule.is_due(None, now));
+        assert!(!schedule.is_due(Some(now - chrono::Duration::hours(2)), now));
+        assert!(!LinkCheckSchedule::default().is_due(None, now));
+    }
+}
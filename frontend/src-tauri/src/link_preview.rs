@@ -0,0 +1,259 @@
+//! Link Titles
+//!
+//! Title and description of a web page, so a pasted URL can become
+//! `[Title](url)`. Only the start of the page is read (up to `</head>`, at
+//! most `MAX_PAGE_BYTES`); `og:`/`twitter:` meta tags win over `<title>`.
+//!
+//! ## Security
+//! Requests may only reach public addresses: every host name, including
+//! redirect targets, is resolved by `public_addresses`, which refuses
+//! loopback, private, link-local and other non-public ranges. A link in a
+//! note can therefore not make the app probe the local network or cloud
+//! metadata endpoints.
+
+use std::io::{self, Read};
+use std::net::{IpAddr, Ipv4Addr, SocketAddr, ToSocketAddrs};
+use std::time::Duration;
+use serde::Serialize;
+use crate::convert::{attribute, html_to_markdown};
+use crate::link_check::{self, MAX_REDIRECTS};
+use crate::links::format_markdown_target;
+
+/// Reading stops after this many bytes, with or without `</head>`
+const MAX_PAGE_BYTES: usize = 512 * 1024;
+
+/// Longer descriptions are cut off
+const MAX_DESCRIPTION_CHARS: usize = 300;
+
+const USER_AGENT: &str = concat!("MDReader/", env!("CARGO_PKG_VERSION"), " (link preview)");
+
+/// Title and description of a page
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct LinkPreview {
+    /// URL after redirects
+    pub url: String,
+    /// Page title, or the host name if the page has none
+    pub title: String,
+    pub description: Option<String>,
+    pub site_name: Option<String>,
+    /// `[title](url)` for the URL as requested
+    pub markdown: String,
+}
+
+/// Fetches the title and description of a web page
+///
+/// # Returns
+/// * `Err(String)` - If the URL is not `http(s)`, resolves to a non-public
+///   address, times out or is not an HTML page
+pub fn fetch_link_title(url: &str, timeout: Duration) -> Result<LinkPreview, String> {
+    let url = url.trim();
+    if link_check::url_at(url) != Some(url) {
+        return Err(format!("Not a web link: {}", url));
+    }
+
+    let agent = ureq::AgentBuilder::new()
+        .redirects(MAX_REDIRECTS as u32)
+        .timeout(timeout)
+        .user_agent(USER_AGENT)
+        .resolver(public_addresses)
+        .build();
+    let response = agent.get(url).call().map_err(|e| match e {
+        ureq::Error::Status(status, _) => format!("Failed to fetch page: HTTP {}", status),
+        ureq::Error::Transport(e) => format!("Failed to fetch page: {}", e),
+    })?;
+
+    let content_type = response.content_type().to_lowercase();
+    if !content_type.contains("html") {
+        return Err(format!("Not a web page: {}", content_type));
+    }
+    let final_url = response.get_url().to_string();
+    let head = read_head(response.into_reader()).map_err(|e| format!("Failed to read page: {}", e))?;
+
+    let mut preview = page_metadata(&head, &final_url);
+    preview.markdown = markdown_link(&preview.title, url);
+    log::info!("🔗 Fetched title of {}: {:?}", url, preview.title);
+    Ok(preview)
+}
+
+/// Title, description and site name from the `<head>` of a page
+pub fn page_metadata(html: &str, url: &str) -> LinkPreview {
+    let lower = html.to_ascii_lowercase();
+    let mut meta: Vec<(String, String)> = Vec::new();
+    let mut search = 0;
+    while let Some(found) = lower[search..].find("<meta") {
+        let start = search + found + "<meta".len();
+        let end = lower[start..].find('>').map_or(html.len(), |end| start + end);
+        let tag = &html[start..end];
+        let key = attribute(tag, "property").or_else(|| attribute(tag, "name"));
+        if let (Some(key), Some(content)) = (key, attribute(tag, "content")) {
+            meta.push((key.to_lowercase(), collapse_whitespace(&content)));
+        }
+        search = end;
+    }
+    let meta_value = |keys: &[&str]| {
+        keys.iter()
+            .find_map(|key| meta.iter().find(|(k, v)| k == key && !v.is_empty()))
+            .map(|(_, value)| value.clone())
+    };
+
+    let title_element = lower.find("<title").and_then(|open| {
+        let start = open + lower[open..].find('>')? + 1;
+        let end = start + lower[start..].find("</title")?;
+        Some(collapse_whitespace(&html_to_markdown(&html[start..end])))
+    });
+    let title = meta_value(&["og:title", "twitter:title"])
+        .or(title_element.filter(|title| !title.is_empty()))
+        .unwrap_or_else(|| link_check::host(url));
+    let description = meta_value(&["og:description", "description", "twitter:description"]).map(|description| {
+        match description.char_indices().nth(MAX_DESCRIPTION_CHARS) {
+            Some((cut, _)) => format!("{}…", description[..cut].trim_end()),
+            None => description,
+        }
+    });
+
+    LinkPreview {
+        url: url.to_string(),
+        markdown: markdown_link(&title, url),
+        title,
+        description,
+        site_name: meta_value(&["og:site_name"]),
+    }
+}
+
+/// Whether an address is reachable on the public internet
+pub fn is_public_ip(ip: IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(ip) => is_public_ipv4(ip),
+        IpAddr::V6(ip) => {
+            if let Some(v4) = ip.to_ipv4_mapped() {
+                return is_public_ipv4(v4);
+            }
+            let segments = ip.segments();
+            // NAT64 (`64:ff9b::/96`) carries an IPv4 address
+            if segments[..6] == [0x64, 0xff9b, 0, 0, 0, 0] {
+                let [a, b] = segments[6].to_be_bytes();
+                let [c, d] = segments[7].to_be_bytes();
+                return is_public_ipv4(Ipv4Addr::new(a, b, c, d));
+            }
+            !(ip.is_loopback()
+                || ip.is_unspecified()
+                || ip.is_multicast()
+                || segments[0] & 0xfe00 == 0xfc00 // unique local
+                || segments[0] & 0xffc0 == 0xfe80 // link-local
+                || segments[0] & 0xffc0 == 0xfec0 // site-local (deprecated)
+                || segments[0] == 0x2001 && segments[1] == 0x0db8) // documentation
+        }
+    }
+}
+
+fn is_public_ipv4(ip: Ipv4Addr) -> bool {
+    let [a, b, c, _] = ip.octets();
+    !(ip.is_private()
+        || ip.is_loopback()
+        || ip.is_link_local()
+        || ip.is_unspecified()
+        || ip.is_broadcast()
+        || ip.is_documentation()
+        || ip.is_multicast()
+        || a == 0
+        || a == 100 && (64..128).contains(&b) // carrier-grade NAT
+        || a == 192 && b == 0 && c == 0 // IETF protocol assignments
+        || a == 198 && (18..20).contains(&b) // benchmarking
+        || a >= 240) // reserved
+}
+
+/// Resolver for HTTP agents that only returns public addresses
+///
+/// Used by every agent that requests URLs from notes or the webview
+/// (`web_snapshot`, `link_check`), so none of them reaches the local network.
+pub(crate) fn public_addresses(netloc: &str) -> io::Result<Vec<SocketAddr>> {
+    let addresses: Vec<SocketAddr> = netloc.to_socket_addrs()?.collect();
+    let public: Vec<SocketAddr> = addresses.iter().copied().filter(|address| is_public_ip(address.ip())).collect();
+    if public.is_empty() && !addresses.is_empty() {
+        log::warn!("🚫 Refused request to non-public address: {}", netloc);
+        return Err(io::Error::new(io::ErrorKind::PermissionDenied, format!("{} is not a public address", netloc)));
+    }
+    Ok(public)
+}
+
+// Helper: Reads a page up to the end of its `<head>` or `MAX_PAGE_BYTES`
+fn read_head(mut reader: impl Read) -> io::Result<String> {
+    let mut bytes = Vec::new();
+    let mut chunk = [0u8; 16 * 1024];
+    while bytes.len() < MAX_PAGE_BYTES {
+        let read = reader.read(&mut chunk)?;
+        if read == 0 {
+            break;
+        }
+        // The end tag may straddle two chunks
+        let from = bytes.len().saturating_sub("</head".len());
+        bytes.extend_from_slice(&chunk[..read]);
+        if bytes[from..].windows(6).any(|window| window.eq_ignore_ascii_case(b"</head")) {
+            break;
+        }
+    }
+    bytes.truncate(MAX_PAGE_BYTES);
+    Ok(String::from_utf8_lossy(&bytes).to_string())
+}
+
+fn markdown_link(title: &str, url: &str) -> String {
+    let mut text = String::with_capacity(title.len());
+    for c in title.chars() {
+        if matches!(c, '[' | ']' | '\\') {
+            text.push('\\');
+        }
+        text.push(c);
+    }
+    format!("[{}]({})", text, format_markdown_target(url, None, ""))
+}
+
+fn collapse_whitespace(text: &str) -> String {
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+// ============================================================================
+// TESTS
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_page_metadata() {
+        let html = "<html><head>\n<title>\n  Fallback [Title]\n</title>\n<meta name=\"description\" content=\"A page about   things &amp; stuff.\">\n<META property='og:site_name' content='Example'>\n</head><body><svg><title>Icon</title></svg></body></html>";
+        let preview = page_metadata(html, "https://example.com/a");
+        assert_eq!(preview.title, "Fallback [Title]");
+        assert_eq!(preview.markdown, "[Fallback \\[Title\\]](https://example.com/a)");
+        assert_eq!(preview.description.as_deref(), Some("A page about things & stuff."));
+        assert_eq!(preview.site_name.as_deref(), Some("Example"));
+
+        let og = format!("<meta property=\"og:title\" content=\"Shared title\"><meta property=\"og:description\" content=\"{}\"><title>Page</title>", "word ".repeat(100));
+        let preview = page_metadata(&og, "https://example.com/b");
+        assert_eq!(preview.title, "Shared title");
+        assert!(preview.description.unwrap().ends_with("word…"));
+
+        let preview = page_metadata("<p>No head</p>", "https://www.example.com/a (b)");
+        assert_eq!((preview.title.as_str(), preview.description), ("www.example.com", None));
+        assert_eq!(preview.markdown, "[www.example.com](<https://www.example.com/a (b)>)");
+    }
+
+    #[test]
+    fn test_is_public_ip() {
+        let public = ["93.184.216.34", "8.8.8.8", "2606:2800:220:1:248:1893:25c8:1946", "64:ff9b::808:808"];
+        let private = [
+            "127.0.0.1", "10.1.2.3", "172.16.0.1", "192.168.1.1", "169.254.169.254", "100.64.0.1", "0.0.0.0",
+            "224.0.0.1", "255.255.255.255", "::1", "::", "fd00::1", "fe80::1", "::ffff:127.0.0.1", "64:ff9b::a00:1",
+        ];
+        for ip in public {
+            assert!(is_public_ip(ip.parse().unwrap()), "{} is public", ip);
+        }
+        for ip in private {
+            assert!(!is_public_ip(ip.parse().unwrap()), "{} is not public", ip);
+        }
+
+        assert!(public_addresses("127.0.0.1:80").is_err());
+        assert!(fetch_link_title("file:///etc/passwd", Duration::from_secs(1)).is_err());
+        assert!(fetch_link_title("http://127.0.0.1:9/", Duration::from_secs(1)).unwrap_err().contains("not a public address"));
+    }
+}
@@ -0,0 +1,948 @@
+//! Workspace Link Index
+//!
+//! Builds the note-to-note link graph of a workspace from wiki links
+//! (`[[Note]]`, `[[folder/Note|alias]]`, `![[Note]]`) and relative markdown
+//! links (`[text](../Note.md#heading)`). Links inside code are ignored, as are
+//! external URLs and links to missing notes.
+//!
+//! Wiki links without a folder resolve by file name, preferring a note in the
+//! linking note's folder when several notes share the name. A wiki link that
+//! matches no file resolves through the frontmatter `aliases` of the notes,
+//! so a renamed note can keep answering to its old name.
+//!
+//! `@handle` mentions link to person pages (see `crate::people`).
+//!
+//! ## Rewriting
+//! `redirect_links` points links at a different note (when a section is
+//! split off or notes are merged) and `rebase_links` keeps relative links of
+//! text moved to another folder working.
+//!
+//! ## Metrics
+//! `GraphMetrics` summarizes the graph per note: link degrees, a cluster id
+//! from modularity-based community detection, and PageRank centrality.
+
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use serde::{Deserialize, Serialize};
+use crate::people::{can_start_mention, parse_mention};
+use crate::workspace_settings::relative_between;
+
+/// Damping factor of the PageRank random walk
+const PAGERANK_DAMPING: f64 = 0.85;
+
+/// Iteration cap for PageRank and community detection
+const MAX_ITERATIONS: usize = 100;
+
+/// Number of notes reported as hubs
+const HUB_COUNT: usize = 10;
+
+/// Resolved links between the notes of a workspace
+#[derive(Debug, Clone, Default)]
+pub struct LinkIndex {
+    /// Note paths relative to the workspace root, sorted
+    pub paths: Vec<String>,
+    pub titles: Vec<String>,
+    /// Outgoing links per note (indices into `paths`)
+    pub outgoing: Vec<BTreeSet<usize>>,
+    /// Incoming links per note (indices into `paths`)
+    pub incoming: Vec<BTreeSet<usize>>,
+}
+
+impl LinkIndex {
+    /// Builds the index from parsed notes (see `MetadataCache::link_index`)
+    pub fn from_parsed(mut notes: Vec<ParsedNote>) -> Self {
+        notes.sort_by(|a, b| a.path.cmp(&b.path));
+
+        let mut lookup = NoteLookup::new(notes.iter().map(|note| note.path.clone()).collect());
+        for note in &notes {
+            lookup.add_aliases(&note.path, &note.aliases);
+            lookup.add_person(&note.path, &note.handles);
+        }
+        let count = lookup.paths.len();
+        let mut titles = Vec::with_capacity(count);
+        let mut outgoing = vec![BTreeSet::new(); count];
+        let mut incoming = vec![BTreeSet::new(); count];
+
+        for (from, ParsedNote { path, title, links, .. }) in notes.into_iter().enumerate() {
+            titles.push(title);
+
+            let wiki = links.wiki.iter().map(|target| lookup.resolve_wiki(&path, target));
+            let relative = links.relative.iter().map(|target| lookup.resolve_relative(&path, target));
+            let people = links.people.iter().map(|handle| lookup.resolve_person(&path, handle));
+            for to in wiki.chain(relative).chain(people).flatten().filter(|&to| to != from) {
+                outgoing[from].insert(to);
+                incoming[to].insert(from);
+            }
+        }
+
+        Self {
+            paths: lookup.paths,
+            titles,
+            outgoing,
+            incoming,
+        }
+    }
+
+    /// Paths of the notes linking to `path`
+    pub fn backlinks(&self, path: &str) -> Vec<String> {
+        self.paths
+            .iter()
+            .position(|p| p == path)
+            .map(|i| self.incoming[i].iter().map(|&j| self.paths[j].clone()).collect())
+            .unwrap_or_default()
+    }
+
+    pub fn edge_count(&self) -> usize {
+        self.outgoing.iter().map(BTreeSet::len).sum()
+    }
+}
+
+/// What the link index needs to know about a note
+#[derive(Debug, Clone, Default)]
+pub struct ParsedNote {
+    /// Path relative to the workspace root
+    pub path: String,
+    pub title: String,
+    pub links: NoteLinks,
+    /// Frontmatter aliases
+    pub aliases: Vec<String>,
+    /// Handles of a person page (see `crate::people`)
+    pub handles: Vec<String>,
+}
+
+/// Unresolved link targets of a note, in order of appearance
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct NoteLinks {
+    /// `[[target]]` targets (without alias and heading)
+    #[serde(default)]
+    pub wiki: Vec<String>,
+    /// Relative `.md` targets of markdown links (without anchor)
+    #[serde(default)]
+    pub relative: Vec<String>,
+    /// Handles of `@handle` mentions (normalized, see `people::handle`)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub people: Vec<String>,
+}
+
+impl NoteLinks {
+    /// Extracts the links of a note body (frontmatter excluded)
+    pub fn from_body(body: &str) -> Self {
+        let mut links = Self::default();
+        for link in extract_links(body) {
+            match link {
+                Link::Wiki(target) => links.wiki.push(target),
+                Link::Relative(target) => links.relative.push(target),
+                Link::Person(handle) => links.people.push(handle),
+            }
+        }
+        links
+    }
+
+    /// Targets that resolve to no note, as written in the note at `from`
+    pub fn unresolved(&self, lookup: &NoteLookup, from: &str) -> Vec<String> {
+        let wiki = self.wiki.iter().filter(|target| lookup.resolve_wiki(from, target).is_none());
+        let relative = self.relative.iter().filter(|target| lookup.resolve_relative(from, target).is_none());
+        wiki.chain(relative).cloned().collect()
+    }
+}
+
+/// A link found in a note body
+#[derive(Debug, PartialEq)]
+enum Link {
+    /// `[[target]]` (without alias and heading)
+    Wiki(String),
+    /// `[text](target)` with a relative `.md` target (without anchor)
+    Relative(String),
+    /// `@handle` (normalized)
+    Person(String),
+}
+
+/// Extracts wiki links, relative markdown links and mentions outside code
+fn extract_links(body: &str) -> Vec<Link> {
+    let mut links = Vec::new();
+    let mut in_fence = false;
+
+    for line in body.lines() {
+        let trimmed = line.trim_start();
+        if trimmed.starts_with("```") || trimmed.starts_with("~~~") {
+            in_fence = !in_fence;
+            continue;
+        }
+        if in_fence {
+            continue;
+        }
+
+        // Split on backticks; odd segments are inline code
+        for (i, segment) in line.split('`').enumerate() {
+            if i % 2 == 0 {
+                extract_from_text(segment, &mut links);
+                extract_mentions(segment, &mut links);
+            }
+        }
+    }
+
+    links
+}
+
+fn extract_from_text(text: &str, links: &mut Vec<Link>) {
+    let mut rest = text;
+
+    while let Some(open) = rest.find('[') {
+        let after = &rest[open..];
+
+        if let Some(inner) = after.strip_prefix("[[") {
+            if let Some(end) = inner.find("]]") {
+                let target = inner[..end].split(['|', '#']).next().unwrap_or("").trim();
+                if !target.is_empty() {
+                    links.push(Link::Wiki(target.to_string()));
+                }
+                rest = &inner[end + 2..];
+                continue;
+            }
+        }
+
+        if let Some(close) = after.find("](") {
+            if let Some(end) = after[close + 2..].find(')') {
+                let target = after[close + 2..close + 2 + end].trim();
+                // Drop an optional title: [text](note.md "Title")
+                let target = target.split(" \"").next().unwrap_or(target);
+                let target = target.trim_start_matches('<').trim_end_matches('>');
+                let target = target.split('#').next().unwrap_or("");
+                if !target.contains("://") && !target.starts_with("mailto:") && is_markdown(target) {
+                    links.push(Link::Relative(percent_decode(target)));
+                }
+                rest = &after[close + 2 + end + 1..];
+                continue;
+            }
+        }
+
+        rest = &after[1..];
+    }
+}
+
+fn extract_mentions(text: &str, links: &mut Vec<Link>) {
+    for (at, _) in text.match_indices('@') {
+        if !can_start_mention(text[..at].chars().next_back()) {
+            continue;
+        }
+        if let Some((handle, _)) = parse_mention(&text[at..]) {
+            links.push(Link::Person(handle));
+        }
+    }
+}
+
+/// Finds notes by path or name, the way links refer to them
+#[derive(Debug, Clone, Default)]
+pub struct NoteLookup {
+    /// Note paths relative to the workspace root
+    pub paths: Vec<String>,
+    by_path: HashMap<String, usize>,
+    by_stem: HashMap<String, Vec<usize>>,
+    by_alias: HashMap<String, Vec<usize>>,
+    by_handle: HashMap<String, Vec<usize>>,
+}
+
+impl NoteLookup {
+    /// Indexes relative note paths (`/` separators)
+    pub fn new(paths: Vec<String>) -> Self {
+        let by_path = paths
+            .iter()
+            .enumerate()
+            .map(|(i, p)| (p.to_lowercase(), i))
+            .collect();
+        let mut by_stem: HashMap<String, Vec<usize>> = HashMap::new();
+        for (i, path) in paths.iter().enumerate() {
+            by_stem.entry(stem(path).to_lowercase()).or_default().push(i);
+        }
+
+        Self { paths, by_path, by_stem, by_alias: HashMap::new(), by_handle: HashMap::new() }
+    }
+
+    /// Lets wiki links reach the note at `path` by its aliases
+    ///
+    /// An alias with a folder (`Projects/Old plan`) also answers to its
+    /// last segment, like a file name does.
+    pub fn add_aliases(&mut self, path: &str, aliases: &[String]) {
+        let Some(&index) = self.by_path.get(&path.to_lowercase()) else {
+            return;
+        };
+        for alias in aliases {
+            let alias = alias.trim().trim_start_matches('/').to_lowercase();
+            let alias = alias.strip_suffix(".md").unwrap_or(&alias);
+            let name = alias.rsplit('/').next().unwrap_or(alias);
+            for key in [alias, name].into_iter().filter(|key| !key.is_empty()) {
+                let entry = self.by_alias.entry(key.to_string()).or_default();
+                if !entry.contains(&index) {
+                    entry.push(index);
+                }
+            }
+        }
+    }
+
+    /// Lets `@handle` mentions reach the person page at `path`
+    pub fn add_person(&mut self, path: &str, handles: &[String]) {
+        let Some(&index) = self.by_path.get(&path.to_lowercase()) else {
+            return;
+        };
+        for handle in handles {
+            let entry = self.by_handle.entry(handle.clone()).or_default();
+            if !entry.contains(&index) {
+                entry.push(index);
+            }
+        }
+    }
+
+    /// Resolves a wiki link target (without alias or heading) from note `from`
+    pub fn resolve_wiki(&self, from: &str, target: &str) -> Option<usize> {
+        let target = target.trim_start_matches('/');
+        let with_ext = if is_markdown(target) {
+            target.to_string()
+        } else {
+            format!("{}.md", target)
+        };
+
+        if target.contains('/') {
+            return self.by_path
+                .get(&with_ext.to_lowercase())
+                .copied()
+                .or_else(|| self.resolve_alias(from, target));
+        }
+
+        let Some(candidates) = self.by_stem.get(&stem(&with_ext).to_lowercase()) else {
+            return self.resolve_alias(from, target);
+        };
+        let folder = parent(from);
+        candidates
+            .iter()
+            .find(|&&i| parent(&self.paths[i]) == folder)
+            .or_else(|| candidates.first())
+            .copied()
+    }
+
+    fn resolve_alias(&self, from: &str, target: &str) -> Option<usize> {
+        let target = target.to_lowercase();
+        let candidates = self.by_alias.get(target.strip_suffix(".md").unwrap_or(&target))?;
+        self.nearest(from, candidates)
+    }
+
+    /// Resolves a normalized `@handle` mention from note `from`
+    pub fn resolve_person(&self, from: &str, handle: &str) -> Option<usize> {
+        self.nearest(from, self.by_handle.get(handle)?)
+    }
+
+    // Helper: Prefers a candidate in the folder of note `from`
+    fn nearest(&self, from: &str, candidates: &[usize]) -> Option<usize> {
+        let folder = parent(from);
+        candidates
+            .iter()
+            .find(|&&i| parent(&self.paths[i]) == folder)
+            .or_else(|| candidates.first())
+            .copied()
+    }
+
+    /// Resolves a relative markdown link target from note `from`
+    pub fn resolve_relative(&self, from: &str, target: &str) -> Option<usize> {
+        self.by_path.get(&join_relative(from, target).to_lowercase()).copied()
+    }
+}
+
+/// Joins a relative link onto the folder of the linking note
+pub(crate) fn join_relative(from: &str, target: &str) -> String {
+    let mut parts: Vec<&str> = if target.starts_with('/') {
+        Vec::new()
+    } else {
+        parent(from).split('/').filter(|p| !p.is_empty()).collect()
+    };
+
+    for part in target.split('/') {
+        match part {
+            "" | "." => {}
+            ".." => {
+                parts.pop();
+            }
+            part => parts.push(part),
+        }
+    }
+
+    parts.join("/")
+}
+
+fn parent(path: &str) -> &str {
+    path.rsplit_once('/').map_or("", |(dir, _)| dir)
+}
+
+fn stem(path: &str) -> &str {
+    let name = path.rsplit('/').next().unwrap_or(path);
+    name.rsplit_once('.').map_or(name, |(stem, _)| stem)
+}
+
+fn is_markdown(target: &str) -> bool {
+    target.to_lowercase().ends_with(".md")
+}
+
+/// Decodes `%XX` escapes of a link target
+pub fn percent_decode(text: &str) -> String {
+    let bytes = text.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+
+    while i < bytes.len() {
+        if bytes[i] == b'%' && i + 2 < bytes.len() {
+            let hex = std::str::from_utf8(&bytes[i + 1..i + 3]).unwrap_or("");
+            if let Ok(byte) = u8::from_str_radix(hex, 16) {
+                out.push(byte);
+                i += 3;
+                continue;
+            }
+        }
+        out.push(bytes[i]);
+        i += 1;
+    }
+
+    String::from_utf8_lossy(&out).to_string()
+}
+
+// ============================================================================
+// REWRITING
+// ============================================================================
+
+/// Kind of link handed to a `rewrite_links` callback
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LinkKind {
+    /// `[[...]]` (also `![[...]]`)
+    Wiki,
+    /// `[text](...)` (also images)
+    Markdown,
+}
+
+/// Rewrites links outside code
+///
+/// `rewrite` gets the raw text between the brackets (`Note#heading|alias`
+/// for wiki links, `target "title"` for markdown links) and returns its
+/// replacement, or `None` to leave the link unchanged.
+pub fn rewrite_links(body: &str, rewrite: &mut dyn FnMut(LinkKind, &str) -> Option<String>) -> String {
+    let mut out = String::with_capacity(body.len());
+    let mut in_fence = false;
+
+    for line in body.split_inclusive('\n') {
+        let trimmed = line.trim_start();
+        let is_fence = trimmed.starts_with("```") || trimmed.starts_with("~~~");
+        if is_fence {
+            in_fence = !in_fence;
+        }
+        if is_fence || in_fence {
+            out.push_str(line);
+            continue;
+        }
+
+        // Odd backtick segments are inline code
+        for (i, segment) in line.split('`').enumerate() {
+            if i > 0 {
+                out.push('`');
+            }
+            if i % 2 == 0 {
+                rewrite_text(segment, rewrite, &mut out);
+            } else {
+                out.push_str(segment);
+            }
+        }
+    }
+
+    out
+}
+
+fn rewrite_text(text: &str, rewrite: &mut dyn FnMut(LinkKind, &str) -> Option<String>, out: &mut String) {
+    let mut rest = text;
+
+    while let Some(open) = rest.find('[') {
+        out.push_str(&rest[..open]);
+        let after = &rest[open..];
+
+        if let Some(inner) = after.strip_prefix("[[") {
+            if let Some(end) = inner.find("]]") {
+                let raw = &inner[..end];
+                out.push_str("[[");
+                out.push_str(&rewrite(LinkKind::Wiki, raw).unwrap_or_else(|| raw.to_string()));
+                out.push_str("]]");
+                rest = &inner[end + 2..];
+                continue;
+            }
+        }
+
+        if let Some(close) = after.find("](") {
+            if let Some(end) = after[close + 2..].find(')') {
+                let raw = &after[close + 2..close + 2 + end];
+                out.push_str(&after[..close + 2]);
+                out.push_str(&rewrite(LinkKind::Markdown, raw).unwrap_or_else(|| raw.to_string()));
+                out.push(')');
+                rest = &after[close + 2 + end + 1..];
+                continue;
+            }
+        }
+
+        out.push('[');
+        rest = &after[1..];
+    }
+
+    out.push_str(rest);
+}
+
+/// Parts of a markdown link target: `<path#anchor> "title"`
+struct MarkdownTarget<'a> {
+    path: String,
+    anchor: Option<&'a str>,
+    /// Everything after the target (e.g. ` "Title"`)
+    suffix: &'a str,
+}
+
+fn parse_markdown_target(raw: &str) -> Option<MarkdownTarget<'_>> {
+    let raw = raw.trim();
+    let (target, suffix) = match raw.strip_prefix('<') {
+        Some(inner) => {
+            let end = inner.find('>')?;
+            (&inner[..end], &inner[end + 1..])
+        }
+        None => raw.split_once(' ').map_or((raw, ""), |(t, _)| (t, &raw[t.len()..])),
+    };
+    if target.contains("://") || target.starts_with("mailto:") {
+        return None;
+    }
+    let (path, anchor) = match target.split_once('#') {
+        Some((path, anchor)) => (path, Some(anchor)),
+        None => (target, None),
+    };
+    Some(MarkdownTarget { path: percent_decode(path), anchor, suffix })
+}
+
+/// Formats a markdown link target, bracketing it when it has spaces
+pub(crate) fn format_markdown_target(path: &str, anchor: Option<&str>, suffix: &str) -> String {
+    let mut target = path.to_string();
+    if let Some(anchor) = anchor {
+        target.push('#');
+        target.push_str(anchor);
+    }
+    if target.contains([' ', '(', ')']) {
+        format!("<{}>{}", target, suffix)
+    } else {
+        format!("{}{}", target, suffix)
+    }
+}
+
+/// Points links that resolve to note `old` at note `new` instead
+///
+/// Both are workspace-relative paths known to `lookup`; `from` is the note
+/// whose `body` is rewritten. `anchors` decides per link: it gets the link's
+/// heading/anchor and returns `None` to keep the link, or the anchor to use
+/// on `new`. Returns the new body and the number of links changed.
+pub fn redirect_links(
+    body: &str,
+    from: &str,
+    lookup: &NoteLookup,
+    old: &str,
+    new: &str,
+    anchors: &dyn Fn(Option<&str>) -> Option<Option<String>>,
+) -> (String, usize) {
+    let Some(old_index) = lookup.by_path.get(&old.to_lowercase()).copied() else {
+        return (body.to_string(), 0);
+    };
+    let from_index = lookup.by_path.get(&from.to_lowercase()).copied();
+    let points_at_old = |resolved: Option<usize>| resolved == Some(old_index);
+    let mut changed = 0;
+
+    let rewritten = rewrite_links(body, &mut |kind, raw| {
+        let replacement = match kind {
+            LinkKind::Wiki => {
+                let (target, alias) = match raw.split_once('|') {
+                    Some((target, alias)) => (target, Some(alias)),
+                    None => (raw, None),
+                };
+                let (name, heading) = match target.split_once('#') {
+                    Some((name, heading)) => (name.trim(), Some(heading)),
+                    None => (target.trim(), None),
+                };
+                let resolved = if name.is_empty() { from_index } else { lookup.resolve_wiki(from, name) };
+                if !points_at_old(resolved) {
+                    return None;
+                }
+                let heading = anchors(heading)?;
+                let mut link = wiki_target(lookup, from, new);
+                if let Some(heading) = heading {
+                    link.push('#');
+                    link.push_str(&heading);
+                }
+                if let Some(alias) = alias {
+                    link.push('|');
+                    link.push_str(alias);
+                }
+                link
+            }
+            LinkKind::Markdown => {
+                let target = parse_markdown_target(raw)?;
+                let resolved = if target.path.is_empty() {
+                    from_index
+                } else if is_markdown(&target.path) {
+                    lookup.resolve_relative(from, &target.path)
+                } else {
+                    None
+                };
+                if !points_at_old(resolved) {
+                    return None;
+                }
+                let anchor = anchors(target.anchor)?;
+                let path = relative_between(parent(from), new);
+                format_markdown_target(&path, anchor.as_deref(), target.suffix)
+            }
+        };
+        changed += 1;
+        Some(replacement)
+    });
+
+    (rewritten, changed)
+}
+
+/// Shortest wiki target that resolves to `path` from note `from`
+pub fn wiki_target(lookup: &NoteLookup, from: &str, path: &str) -> String {
+    let name = stem(path);
+    let target_index = lookup.by_path.get(&path.to_lowercase()).copied();
+    if target_index.is_some() && lookup.resolve_wiki(from, name) == target_index {
+        name.to_string()
+    } else {
+        path.strip_suffix(".md").unwrap_or(path).to_string()
+    }
+}
+
+/// Rewrites relative markdown links of a note body moved from note `from` to
+/// note `to` (workspace-relative paths), so they keep pointing at the same files
+///
+/// Wiki links, vault-absolute (`/...`) and anchor-only links are unaffected.
+pub fn rebase_links(body: &str, from: &str, to: &str) -> String {
+    if parent(from) == parent(to) {
+        return body.to_string();
+    }
+
+    rewrite_links(body, &mut |kind, raw| {
+        if kind != LinkKind::Markdown {
+            return None;
+        }
+        let target = parse_markdown_target(raw)?;
+        if target.path.is_empty() || target.path.starts_with('/') {
+            return None;
+        }
+        let path = relative_between(parent(to), &join_relative(from, &target.path));
+        Some(format_markdown_target(&path, target.anchor, target.suffix))
+    })
+}
+
+/// GitHub-style anchor of a heading (`Goals & Risks` -> `goals--risks`)
+pub fn heading_anchor(heading: &str) -> String {
+    heading
+        .trim()
+        .to_lowercase()
+        .chars()
+        .filter_map(|c| match c {
+            ' ' => Some('-'),
+            c if c.is_alphanumeric() || c == '-' || c == '_' => Some(c),
+            _ => None,
+        })
+        .collect()
+}
+
+// ============================================================================
+// METRICS
+// ============================================================================
+
+/// Graph metrics of a single note
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct NoteMetrics {
+    pub path: String,
+    pub title: String,
+    /// Number of notes linking here (backlinks)
+    pub in_degree: usize,
+    /// Number of notes linked from here
+    pub out_degree: usize,
+    /// Number of distinct neighbors, ignoring direction
+    pub degree: usize,
+    /// Community id; densely linked groups of notes share one
+    pub cluster: usize,
+    /// PageRank score (sums to 1 over all notes)
+    pub centrality: f64,
+}
+
+/// Metrics of the whole link graph
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GraphMetrics {
+    pub notes: Vec<NoteMetrics>,
+    pub edge_count: usize,
+    pub cluster_count: usize,
+    /// Most central notes, best first
+    pub hubs: Vec<String>,
+}
+
+impl GraphMetrics {
+    /// Computes metrics for every note of an index
+    pub fn compute(index: &LinkIndex) -> Self {
+        let neighbors: Vec<BTreeSet<usize>> = (0..index.paths.len())
+            .map(|i| index.outgoing[i].union(&index.incoming[i]).copied().collect())
+            .collect();
+        let clusters = detect_communities(&neighbors);
+        let centrality = pagerank(&index.outgoing);
+
+        let notes: Vec<NoteMetrics> = (0..index.paths.len())
+            .map(|i| NoteMetrics {
+                path: index.paths[i].clone(),
+                title: index.titles[i].clone(),
+                in_degree: index.incoming[i].len(),
+                out_degree: index.outgoing[i].len(),
+                degree: neighbors[i].len(),
+                cluster: clusters[i],
+                centrality: centrality[i],
+            })
+            .collect();
+
+        let mut ranked: Vec<&NoteMetrics> = notes.iter().filter(|n| n.degree > 0).collect();
+        ranked.sort_by(|a, b| b.centrality.total_cmp(&a.centrality).then_with(|| a.path.cmp(&b.path)));
+
+        Self {
+            edge_count: index.edge_count(),
+            cluster_count: clusters.iter().collect::<BTreeSet<_>>().len(),
+            hubs: ranked.iter().take(HUB_COUNT).map(|n| n.path.clone()).collect(),
+            notes,
+        }
+    }
+}
+
+/// Community detection on the undirected graph (Louvain local moving)
+///
+/// Each note repeatedly moves to the neighboring community with the largest
+/// modularity gain until no note moves. Ties keep the current community, then
+/// prefer the smallest id, so results are deterministic. Community ids are
+/// renumbered from 0 in order of first appearance.
+fn detect_communities(neighbors: &[BTreeSet<usize>]) -> Vec<usize> {
+    let degree: Vec<f64> = neighbors.iter().map(|n| n.len() as f64).collect();
+    let two_m: f64 = degree.iter().sum();
+    let mut community: Vec<usize> = (0..neighbors.len()).collect();
+    // Sum of degrees per community
+    let mut total = degree.clone();
+
+    for _ in 0..MAX_ITERATIONS {
+        if two_m == 0.0 {
+            break;
+        }
+        let mut moved = false;
+
+        for node in 0..neighbors.len() {
+            if neighbors[node].is_empty() {
+                continue;
+            }
+            let current = community[node];
+            total[current] -= degree[node];
+
+            let mut links: BTreeMap<usize, f64> = BTreeMap::new();
+            for &n in &neighbors[node] {
+                *links.entry(community[n]).or_default() += 1.0;
+            }
+
+            let gain = |c: usize, k_in: f64| k_in - total[c] * degree[node] / two_m;
+            let mut best = current;
+            let mut best_gain = gain(current, links.get(&current).copied().unwrap_or(0.0));
+            for (&c, &k_in) in &links {
+                let g = gain(c, k_in);
+                if g > best_gain + 1e-12 {
+                    best = c;
+                    best_gain = g;
+                }
+            }
+
+            total[best] += degree[node];
+            if best != current {
+                community[node] = best;
+                moved = true;
+            }
+        }
+
+        if !moved {
+            break;
+        }
+    }
+
+    let mut renumbered: HashMap<usize, usize> = HashMap::new();
+    community
+        .iter()
+        .map(|c| {
+            let next = renumbered.len();
+            *renumbered.entry(*c).or_insert(next)
+        })
+        .collect()
+}
+
+/// PageRank over the directed link graph
+///
+/// Notes without outgoing links spread their score evenly over all notes.
+fn pagerank(outgoing: &[BTreeSet<usize>]) -> Vec<f64> {
+    let n = outgoing.len();
+    if n == 0 {
+        return Vec::new();
+    }
+
+    let mut rank = vec![1.0 / n as f64; n];
+    for _ in 0..MAX_ITERATIONS {
+        let dangling: f64 = (0..n).filter(|&i| outgoing[i].is_empty()).map(|i| rank[i]).sum();
+        let base = (1.0 - PAGERANK_DAMPING) / n as f64 + PAGERANK_DAMPING * dangling / n as f64;
+        let mut next = vec![base; n];
+
+        for (from, targets) in outgoing.iter().enumerate() {
+            if targets.is_empty() {
+                continue;
+            }
+            let share = PAGERANK_DAMPING * rank[from] / targets.len() as f64;
+            for &to in targets {
+                next[to] += share;
+            }
+        }
+
+        let delta: f64 = next.iter().zip(&rank).map(|(a, b)| (a - b).abs()).sum();
+        rank = next;
+        if delta < 1e-9 {
+            break;
+        }
+    }
+
+    rank
+}
+
+// ============================================================================
+// TESTS
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::Path;
+    use crate::frontmatter;
+    use crate::notes::note_title;
+    use crate::people;
+
+    fn index(notes: &[(&str, &str)]) -> LinkIndex {
+        let parsed = notes
+            .iter()
+            .map(|(path, content)| {
+                let (fields, body) = frontmatter::split(content);
+                let fields = fields.unwrap_or_default();
+                let title = note_title(&fields, body, Path::new(path));
+                ParsedNote {
+                    path: path.to_string(),
+                    links: NoteLinks::from_body(body),
+                    aliases: frontmatter::aliases(&fields),
+                    handles: people::person_handles(path, &title, &fields),
+                    title,
+                }
+            })
+            .collect();
+        LinkIndex::from_parsed(parsed)
+    }
+
+    #[test]
+    fn test_extract_links() {
+        let body = "See [[Plan|the plan]] and [spec](docs/My%20Spec.md#api).\n\
+                    `[[not a link]]` [site](https://x.io/a.md) ![[Diagram]]\n\
+                    ```\n[[fenced]]\n```\n";
+
+        assert_eq!(
+            extract_links(body),
+            vec![
+                Link::Wiki("Plan".to_string()),
+                Link::Relative("docs/My Spec.md".to_string()),
+                Link::Wiki("Diagram".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_redirect_links() {
+        let lookup = NoteLookup::new(vec!["Projects/plan.md".to_string(), "Topics/goals.md".to_string(), "index.md".to_string()]);
+        let body = "[[plan#Goals|our goals]] [[plan#Risks]] [g](Projects/plan.md#goals \"Goals\") `[[plan#Goals]]`\n";
+        let to_goals = |anchor: Option<&str>| match anchor {
+            Some(a) if a.eq_ignore_ascii_case("goals") => Some(None),
+            _ => None,
+        };
+
+        let (updated, count) = redirect_links(body, "index.md", &lookup, "Projects/plan.md", "Topics/goals.md", &to_goals);
+        assert_eq!(count, 2);
+        assert_eq!(updated, "[[goals|our goals]] [[plan#Risks]] [g](Topics/goals.md \"Goals\") `[[plan#Goals]]`\n");
+
+        // Anchor-only links point at the note they are in
+        let (updated, _) = redirect_links("[[#Goals]]", "Projects/plan.md", &lookup, "Projects/plan.md", "Topics/goals.md", &to_goals);
+        assert_eq!(updated, "[[goals]]");
+    }
+
+    #[test]
+    fn test_rebase_links() {
+        let body = "![shot](assets/shot.png) [up](../index.md#top) [[plan]] [web](https://x.io/a.md) [s](#local)\n";
+        assert_eq!(
+            rebase_links(body, "Projects/plan.md", "Topics/Deep Dive/goals.md"),
+            "![shot](../../Projects/assets/shot.png) [up](../../index.md#top) [[plan]] [web](https://x.io/a.md) [s](#local)\n"
+        );
+        assert_eq!(heading_anchor("Goals & Risks"), "goals--risks");
+    }
+
+    #[test]
+    fn test_resolve_links_and_backlinks() {
+        let index = index(&[
+            ("Projects/plan.md", "[[notes]] [[Archive/old]] [up](../index.md) [[missing]] [[plan]]"),
+            ("Projects/notes.md", "# Notes"),
+            ("notes.md", "# Root notes"),
+            ("Archive/old.md", ""),
+            ("index.md", "[[notes]]"),
+        ]);
+
+        // Same-folder name wins; self-links and missing notes are dropped
+        assert_eq!(
+            index.outgoing[index.paths.iter().position(|p| p == "Projects/plan.md").unwrap()].len(),
+            3
+        );
+        assert_eq!(index.backlinks("Projects/notes.md"), vec!["Projects/plan.md"]);
+        assert_eq!(index.backlinks("notes.md"), vec!["index.md"]);
+        assert_eq!(index.backlinks("index.md"), vec!["Projects/plan.md"]);
+    }
+
+    #[test]
+    fn test_aliases_resolve_when_no_file_matches() {
+        let index = index(&[
+            ("Projects/roadmap.md", "---\naliases: [Old Plan, Projects/plan-2023]\n---\n# Roadmap"),
+            ("plan.md", "# Plan"),
+            ("index.md", "[[old plan]] [[Projects/plan-2023]] [[plan-2023]] [[plan]]"),
+        ]);
+        let at = |path: &str| index.paths.iter().position(|p| p == path).unwrap();
+
+        let targets: Vec<&str> = index.outgoing[at("index.md")].iter().map(|&i| index.paths[i].as_str()).collect();
+        assert_eq!(targets, vec!["Projects/roadmap.md", "plan.md"]);
+        assert_eq!(index.backlinks("Projects/roadmap.md"), vec!["index.md"]);
+    }
+
+    #[test]
+    fn test_graph_metrics() {
+        // Two triangles joined by a single link from c to d
+        let index = index(&[
+            ("a.md", "[[b]] [[c]]"),
+            ("b.md", "[[c]]"),
+            ("c.md", "[[a]] [[d]]"),
+            ("d.md", "[[e]] [[f]]"),
+            ("e.md", "[[f]]"),
+            ("f.md", "[[d]]"),
+            ("lonely.md", "no links"),
+        ]);
+        let metrics = GraphMetrics::compute(&index);
+        let note = |p: &str| metrics.notes.iter().find(|n| n.path == p).unwrap();
+
+        assert_eq!(metrics.edge_count, 9);
+        assert_eq!((note("c.md").in_degree, note("c.md").out_degree, note("c.md").degree), (2, 2, 3));
+        assert_eq!(note("a.md").cluster, note("b.md").cluster);
+        assert_eq!(note("e.md").cluster, note("f.md").cluster);
+        assert_ne!(note("a.md").cluster, note("e.md").cluster);
+        assert_eq!(metrics.cluster_count, 3);
+
+        let total: f64 = metrics.notes.iter().map(|n| n.centrality).sum();
+        assert!((total - 1.0).abs() < 1e-6);
+        assert!(note("d.md").centrality > note("lonely.md").centrality);
+        assert!(!metrics.hubs.contains(&"lonely.md".to_string()));
+    }
+}
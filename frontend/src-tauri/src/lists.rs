@@ -0,0 +1,327 @@
+//! List Formatting
+//!
+//! Tidies the lists of a note:
+//!
+//! - Ordered lists are renumbered from their first number; each nested
+//!   list counts on its own
+//! - Nested items are indented `indent` spaces per level, but at least up
+//!   to the parent's text (so children of `1.` stay nested) and never so
+//!   far that they would turn into code
+//! - Items indented further than one level below the item above move up
+//!   to that level; items between two levels join the deeper one
+//! - Text and code continuing an item move along with it
+//!
+//! Frontmatter, fenced and indented code and block quotes are left as
+//! written. Runs on demand and, when the workspace enables it, on every
+//! explicit save (see `ListFormat`).
+
+use serde::{Deserialize, Serialize};
+use crate::frontmatter;
+
+/// Spaces per nesting level unless configured otherwise
+pub const DEFAULT_INDENT: usize = 2;
+
+/// Widest indent per level; deeper items would read as code
+pub const MAX_INDENT: usize = 4;
+
+/// List formatting of a workspace (part of the workspace settings)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ListFormat {
+    /// Fix the lists of a note whenever it is saved explicitly
+    #[serde(default)]
+    pub on_save: bool,
+    /// Spaces per nesting level (1 to `MAX_INDENT`)
+    #[serde(default = "default_indent")]
+    pub indent: usize,
+}
+
+fn default_indent() -> usize {
+    DEFAULT_INDENT
+}
+
+impl Default for ListFormat {
+    fn default() -> Self {
+        Self {
+            on_save: false,
+            indent: DEFAULT_INDENT,
+        }
+    }
+}
+
+/// Result of `fix_lists`
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct ListReport {
+    /// List items seen
+    pub items: usize,
+    /// Items whose number changed
+    pub renumbered: usize,
+    /// Lines whose indentation changed
+    pub reindented: usize,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Marker {
+    Bullet(char),
+    /// Number and delimiter (`.` or `)`)
+    Ordered(u64, char),
+}
+
+impl Marker {
+    fn text(self) -> String {
+        match self {
+            Marker::Bullet(bullet) => bullet.to_string(),
+            Marker::Ordered(number, delimiter) => format!("{}{}", number, delimiter),
+        }
+    }
+}
+
+/// A list item line as written
+struct Item<'a> {
+    indent: usize,
+    marker: Marker,
+    /// Column the item's text starts at
+    offset: usize,
+    text: &'a str,
+}
+
+/// An open item, as written and as rewritten
+struct Level {
+    old_indent: usize,
+    old_offset: usize,
+    new_indent: usize,
+    new_offset: usize,
+    marker: Marker,
+}
+
+/// Renumbers and reindents the lists of a note with `indent` spaces per level
+pub fn fix_lists(content: &str, indent: usize) -> (String, ListReport) {
+    let indent = indent.clamp(1, MAX_INDENT);
+    let (_, body) = frontmatter::split(content);
+    let mut out = content[..content.len() - body.len()].to_string();
+    let mut report = ListReport::default();
+
+    let mut levels: Vec<Level> = Vec::new();
+    // Open fence: its marker and how far its lines move
+    let mut fence: Option<(String, isize)> = None;
+    let mut prev_blank = true;
+    let mut in_paragraph = false;
+
+    let mut lines = Vec::new();
+    for raw in body.split('\n') {
+        let (line, cr) = raw.strip_suffix('\r').map_or((raw, ""), |line| (line, "\r"));
+        let (column, text) = leading_columns(line);
+
+        if let Some((marker, shift)) = &fence {
+            let closes = text.starts_with(marker.as_str()) && text.trim_end().chars().all(|c| marker.starts_with(c));
+            let shift = *shift;
+            if closes {
+                fence = None;
+            }
+            lines.push(format!("{}{}", shifted(line, column, text, shift, &mut report), cr));
+            continue;
+        }
+        if text.is_empty() {
+            prev_blank = true;
+            lines.push(raw.to_string());
+            continue;
+        }
+
+        // A blank line ends the items this line is not indented into
+        if prev_blank {
+            while levels.last().is_some_and(|level| column < level.old_offset) {
+                levels.pop();
+            }
+        }
+        let deepest_offset = levels.last().map(|level| level.old_offset);
+        let ends_list = deepest_offset.is_some_and(|offset| column < offset)
+            && (text.starts_with('#') || is_thematic_break(text) || fence_marker(text).is_some());
+        if ends_list {
+            levels.clear();
+        }
+
+        let item = parse_item(line).filter(|item| match deepest_offset.filter(|_| !levels.is_empty()) {
+            // Deeper than the text of the item above is code, unless it
+            // follows text (code cannot interrupt a paragraph)
+            Some(offset) => item.indent < offset + 4 || !prev_blank,
+            // Only lists starting at 1 interrupt a paragraph
+            None => {
+                item.indent < 4
+                    && (!in_paragraph || prev_blank || matches!(item.marker, Marker::Bullet(_) | Marker::Ordered(1, _)))
+            }
+        });
+        prev_blank = false;
+
+        if let Some(item) = item {
+            lines.push(format!("{}{}", place_item(&item, &mut levels, indent, &mut report), cr));
+            in_paragraph = false;
+            continue;
+        }
+
+        match levels.last() {
+            Some(level) => {
+                // Continuation text; lazy lines join the item's text
+                let shift = level.new_offset as isize - level.old_offset as isize;
+                let line = if column >= level.old_offset {
+                    shifted(line, column, text, shift, &mut report)
+                } else {
+                    report.reindented += 1;
+                    format!("{}{}", " ".repeat(level.new_offset), text)
+                };
+                if let Some(marker) = fence_marker(text) {
+                    fence = Some((marker.to_string(), shift));
+                }
+                lines.push(format!("{}{}", line, cr));
+            }
+            None => {
+                if column < 4 {
+                    if let Some(marker) = fence_marker(text) {
+                        fence = Some((marker.to_string(), 0));
+                    }
+                }
+                in_paragraph = column >= 4 || !(text.starts_with('#') || is_thematic_break(text) || text.starts_with('>'));
+                lines.push(raw.to_string());
+            }
+        }
+    }
+
+    out.push_str(&lines.join("\n"));
+    (out, report)
+}
+
+// Helper: Nests, numbers and writes an item; updates the open items
+fn place_item(item: &Item, levels: &mut Vec<Level>, indent: usize, report: &mut ListReport) -> String {
+    report.items += 1;
+    while levels.last().is_some_and(|level| level.old_indent > item.indent) {
+        levels.pop();
+    }
+
+    let (new_indent, marker) = match levels.last() {
+        Some(sibling) if sibling.old_indent == item.indent => {
+            // A different bullet or delimiter starts a new list
+            let marker = match (sibling.marker, item.marker) {
+                (Marker::Ordered(previous, a), Marker::Ordered(_, b)) if a == b => Marker::Ordered(previous + 1, b),
+                _ => item.marker,
+            };
+            let sibling = levels.pop().expect("sibling level");
+            (sibling.new_indent, marker)
+        }
+        Some(parent) => ((parent.new_indent + indent).max(parent.new_offset), item.marker),
+        None => (0, item.marker),
+    };
+    if marker != item.marker {
+        report.renumbered += 1;
+    }
+    if new_indent != item.indent {
+        report.reindented += 1;
+    }
+    let marker_text = marker.text();
+    levels.push(Level {
+        old_indent: item.indent,
+        old_offset: item.offset,
+        new_indent,
+        new_offset: new_indent + marker_text.len() + 1,
+        marker,
+    });
+
+    let mut line = format!("{}{}", " ".repeat(new_indent), marker_text);
+    if !item.text.is_empty() {
+        line.push(' ');
+        line.push_str(item.text);
+    }
+    line
+}
+
+// Helper: Parses a list item line (`- text`, `* text`, `3. text`, `3) text`)
+fn parse_item(line: &str) -> Option<Item<'_>> {
+    let (indent, rest) = leading_columns(line);
+    if is_thematic_break(rest) {
+        return None;
+    }
+
+    let digits = rest.chars().take_while(char::is_ascii_digit).count();
+    let (marker, marker_len) = match rest.chars().next()? {
+        bullet @ ('-' | '*' | '+') => (Marker::Bullet(bullet), 1),
+        _ if (1..=9).contains(&digits) => {
+            let delimiter = rest[digits..].chars().next().filter(|c| matches!(c, '.' | ')'))?;
+            (Marker::Ordered(rest[..digits].parse().ok()?, delimiter), digits + 1)
+        }
+        _ => return None,
+    };
+
+    let after = &rest[marker_len..];
+    if !after.is_empty() && !after.starts_with([' ', '\t']) {
+        return None;
+    }
+    let text = after.trim();
+    let spaces = after.len() - after.trim_start().len();
+    let gap = if text.is_empty() || spaces > 4 { 1 } else { spaces };
+    Some(Item { indent, marker, offset: indent + marker_len + gap, text })
+}
+
+// Helper: Indentation in columns (tabs to the next multiple of 4) and the rest
+fn leading_columns(line: &str) -> (usize, &str) {
+    let mut column = 0;
+    for (i, c) in line.char_indices() {
+        match c {
+            ' ' => column += 1,
+            '\t' => column += 4 - column % 4,
+            _ => return (column, &line[i..]),
+        }
+    }
+    (column, "")
+}
+
+// Helper: The line moved by `shift` columns (unchanged if it does not move)
+fn shifted(line: &str, column: usize, text: &str, shift: isize, report: &mut ListReport) -> String {
+    if shift == 0 || text.is_empty() {
+        return line.to_string();
+    }
+    report.reindented += 1;
+    format!("{}{}", " ".repeat(column.saturating_add_signed(shift)), text)
+}
+
+fn fence_marker(text: &str) -> Option<&str> {
+    let first = text.chars().next().filter(|c| matches!(c, '`' | '~'))?;
+    let len = text.len() - text.trim_start_matches(first).len();
+    (len >= 3).then(|| &text[..len])
+}
+
+fn is_thematic_break(text: &str) -> bool {
+    let mut marks = text.chars().filter(|c| !c.is_whitespace());
+    let Some(first) = marks.next().filter(|c| matches!(c, '-' | '*' | '_')) else { return false };
+    let count = 1 + marks.clone().count();
+    count >= 3 && marks.all(|c| c == first)
+}
+
+// ============================================================================
+// TESTS
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fix_lists_renumbers_and_nests() {
+        let note = "---\ntags: [a]\n---\nSteps:\n\n1. First\n1. Second\n   continued\n      - deep\n   - sub\n5. Third\n\n   More text.\n\n   ```sh\n   run\n   ```\n7. Fourth\n    1) a\n    1) b\n\n- [ ] task\n        - too deep\n* other list\n\nThe year\n1984. was a year.\n\n---\n\n3. Starts at three\n3. Next\n";
+        let (fixed, report) = fix_lists(note, 2);
+        let expected = "---\ntags: [a]\n---\nSteps:\n\n1. First\n2. Second\n   continued\n   - deep\n   - sub\n3. Third\n\n   More text.\n\n   ```sh\n   run\n   ```\n4. Fourth\n   1) a\n   2) b\n\n- [ ] task\n  - too deep\n* other list\n\nThe year\n1984. was a year.\n\n---\n\n3. Starts at three\n4. Next\n";
+        assert_eq!(fixed, expected);
+        assert_eq!(report, ListReport { items: 13, renumbered: 5, reindented: 4 });
+
+        let (again, report) = fix_lists(&fixed, 2);
+        assert_eq!(again, fixed, "Fixing twice changes nothing");
+        assert_eq!(report.renumbered + report.reindented, 0);
+    }
+
+    #[test]
+    fn test_fix_lists_indent_width() {
+        let note = "- a\r\n  - b\r\n    text of b\r\n\r\n```\r\n1. code\r\n3. code\r\n```\r\n\r\n    1. indented code\r\n    3. indented code\r\n";
+        let (fixed, _) = fix_lists(note, 4);
+        assert_eq!(
+            fixed,
+            "- a\r\n    - b\r\n      text of b\r\n\r\n```\r\n1. code\r\n3. code\r\n```\r\n\r\n    1. indented code\r\n    3. indented code\r\n"
+        );
+        assert_eq!(fix_lists("1. a\n  1. b\n", 1).0, "1. a\n   1. b\n", "Children of `1.` stay nested");
+    }
+}
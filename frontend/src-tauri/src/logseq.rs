@@ -0,0 +1,554 @@
+//! Logseq Graph Import
+//!
+//! Converts a Logseq graph folder into plain markdown notes:
+//!
+//! ```text
+//! graph/                         dest/
+//! ├── journals/2024_01_31.md  →  ├── Journals/2024-01-31.md
+//! ├── pages/Work___Roadmap.md →  ├── Pages/Work/Roadmap.md
+//! ├── assets/diagram.png      →  ├── assets/diagram.png
+//! └── logseq/config.edn          (journal file name format)
+//! ```
+//!
+//! - Page properties (`key:: value` lines before the first block) become frontmatter
+//! - `[[Page]]` references become relative markdown links when the page exists
+//! - Block references `((uuid))` and embeds are resolved to the referenced
+//!   block's text, linked to its page; unresolved ones are reported
+//! - Block-only properties (`id::`, `collapsed::`) are dropped
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use crate::frontmatter;
+use crate::utils::sanitize_filename;
+
+/// Block properties that only matter to Logseq itself
+const INTERNAL_PROPERTIES: &[&str] = &["id", "collapsed", "heading"];
+
+/// A block reference that could not be resolved
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct UnresolvedBlockRef {
+    /// Converted note containing the reference (relative to the destination)
+    pub file: String,
+    pub uuid: String,
+}
+
+/// Result of a Logseq graph import
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct LogseqImportReport {
+    pub dest_path: String,
+    pub pages: usize,
+    pub journals: usize,
+    pub assets: usize,
+    /// Block references left as `((uuid))` in the output
+    pub unresolved_block_refs: Vec<UnresolvedBlockRef>,
+    /// Files that could not be converted (e.g. org-mode pages)
+    pub skipped: Vec<String>,
+}
+
+/// A page of the graph, before conversion
+struct Page {
+    /// Page name used in `[[...]]` references
+    name: String,
+    /// Output path relative to the destination, e.g. `Pages/Work/Roadmap.md`
+    output: String,
+    /// File body without page properties
+    body: String,
+    properties: Map<String, Value>,
+    is_journal: bool,
+}
+
+/// A block carrying an `id::` property
+struct BlockTarget {
+    output: String,
+    text: String,
+}
+
+/// Converts the graph at `source` into notes below `dest`
+pub fn import_graph(source: &Path, dest: &Path) -> Result<LogseqImportReport, String> {
+    if !source.join("pages").is_dir() && !source.join("journals").is_dir() {
+        return Err(format!("Not a Logseq graph (no pages/ or journals/): {}", source.display()));
+    }
+
+    let journal_format = read_journal_format(source);
+    let mut report = LogseqImportReport {
+        dest_path: dest.to_string_lossy().to_string(),
+        ..Default::default()
+    };
+
+    let mut pages = Vec::new();
+    for (folder, is_journal) in [("pages", false), ("journals", true)] {
+        let dir = source.join(folder);
+        let Ok(entries) = fs::read_dir(&dir) else { continue };
+
+        let mut files: Vec<_> = entries.flatten().map(|e| e.path()).collect();
+        files.sort();
+
+        for path in files {
+            let name = path.file_name().unwrap_or_default().to_string_lossy().to_string();
+            if !path.is_file() || name.starts_with('.') {
+                continue;
+            }
+            if !name.ends_with(".md") {
+                report.skipped.push(format!("{}/{}", folder, name));
+                continue;
+            }
+
+            let content = match fs::read_to_string(&path) {
+                Ok(content) => content,
+                Err(e) => {
+                    log::warn!("⚠️ Skipping {}: {}", path.display(), e);
+                    report.skipped.push(format!("{}/{}", folder, name));
+                    continue;
+                }
+            };
+
+            let stem = name.trim_end_matches(".md");
+            let (page_name, output) = if is_journal {
+                match journal_date(stem, &journal_format) {
+                    Some(date) => (journal_title(&date), format!("Journals/{}.md", date.format("%Y-%m-%d"))),
+                    None => (stem.to_string(), format!("Journals/{}.md", sanitize_filename(stem))),
+                }
+            } else {
+                let decoded = decode_page_name(stem);
+                let path: Vec<String> = decoded.split('/').map(sanitize_filename).collect();
+                (decoded, format!("Pages/{}.md", path.join("/")))
+            };
+
+            let (properties, body) = split_page_properties(&content);
+            pages.push(Page { name: page_name, output, body, properties, is_journal });
+        }
+    }
+
+    // Page names → output paths (Logseq names are case-insensitive)
+    let mut names: HashMap<String, String> = HashMap::new();
+    for page in &pages {
+        names.insert(page.name.to_lowercase(), page.output.clone());
+        if let Some(title) = page.properties.get("title").map(frontmatter::value_to_string) {
+            names.insert(title.to_lowercase(), page.output.clone());
+        }
+        for alias in page.properties.get("alias").map(aliases).unwrap_or_default() {
+            names.insert(alias.to_lowercase(), page.output.clone());
+        }
+    }
+
+    let blocks = index_blocks(&pages);
+
+    for page in &pages {
+        let mut unresolved = Vec::new();
+        let body = convert_body(&page.body, &page.output, &names, &blocks, &mut unresolved);
+        report.unresolved_block_refs.extend(unresolved.into_iter().map(|uuid| UnresolvedBlockRef {
+            file: page.output.clone(),
+            uuid,
+        }));
+
+        let mut properties = page.properties.clone();
+        if let Some(alias) = properties.remove("alias") {
+            properties.insert("aliases".to_string(), Value::from(aliases(&alias)));
+        }
+
+        let target = dest.join(&page.output);
+        if let Some(parent) = target.parent() {
+            fs::create_dir_all(parent)
+                .map_err(|e| format!("Failed to create directory: {}", e))?;
+        }
+        fs::write(&target, format!("{}{}", frontmatter::render(&properties), body))
+            .map_err(|e| format!("Failed to write {}: {}", page.output, e))?;
+
+        if page.is_journal {
+            report.journals += 1;
+        } else {
+            report.pages += 1;
+        }
+    }
+
+    report.assets = copy_assets(&source.join("assets"), &dest.join("assets"))?;
+    Ok(report)
+}
+
+/// Reads `:journal/file-name-format` from `logseq/config.edn`
+fn read_journal_format(source: &Path) -> String {
+    fs::read_to_string(source.join("logseq").join("config.edn"))
+        .ok()
+        .and_then(|edn| edn_string_value(&edn, ":journal/file-name-format"))
+        .unwrap_or_else(|| "yyyy_MM_dd".to_string())
+}
+
+/// Finds `key "value"` in EDN text, ignoring commented lines
+fn edn_string_value(edn: &str, key: &str) -> Option<String> {
+    edn.lines()
+        .filter(|line| !line.trim_start().starts_with(';'))
+        .find_map(|line| {
+            let rest = &line[line.find(key)? + key.len()..];
+            let start = rest.find('"')? + 1;
+            let end = rest[start..].find('"')? + start;
+            Some(rest[start..end].to_string())
+        })
+}
+
+/// Parses a journal file name in the given format (e.g. `yyyy_MM_dd`)
+fn journal_date(stem: &str, format: &str) -> Option<chrono::NaiveDate> {
+    let pattern = format
+        .replace("yyyy", "%Y")
+        .replace("MM", "%m")
+        .replace("dd", "%d");
+    chrono::NaiveDate::parse_from_str(stem, &pattern).ok()
+}
+
+/// Logseq's default journal page title, e.g. `Jan 31st, 2024`
+fn journal_title(date: &chrono::NaiveDate) -> String {
+    use chrono::Datelike;
+    let day = date.day();
+    let suffix = match (day % 10, day % 100) {
+        (1, n) if n != 11 => "st",
+        (2, n) if n != 12 => "nd",
+        (3, n) if n != 13 => "rd",
+        _ => "th",
+    };
+    format!("{} {}{}, {}", date.format("%b"), day, suffix, date.year())
+}
+
+/// Turns a page file name back into its page name (`___`/`%2F` are namespaces)
+fn decode_page_name(stem: &str) -> String {
+    let stem = stem.replace("___", "/");
+    let bytes = stem.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'%' && i + 2 < bytes.len() {
+            let hex = std::str::from_utf8(&bytes[i + 1..i + 3]).ok();
+            if let Some(b) = hex.and_then(|h| u8::from_str_radix(h, 16).ok()) {
+                out.push(b);
+                i += 3;
+                continue;
+            }
+        }
+        out.push(bytes[i]);
+        i += 1;
+    }
+    String::from_utf8_lossy(&out).to_string()
+}
+
+fn aliases(value: &Value) -> Vec<String> {
+    let text = frontmatter::value_to_string(value);
+    text.split(',')
+        .map(|a| a.trim().trim_start_matches("[[").trim_end_matches("]]").trim().to_string())
+        .filter(|a| !a.is_empty())
+        .collect()
+}
+
+/// Splits leading `key:: value` lines into page properties
+fn split_page_properties(content: &str) -> (Map<String, Value>, String) {
+    let mut properties = Map::new();
+    let mut lines = content.lines().peekable();
+
+    while let Some(line) = lines.peek() {
+        // Properties may sit in a first bullet (`- title:: X`) or at the top
+        let text = line.trim().trim_start_matches("- ");
+        match parse_property(text) {
+            Some((key, value)) => {
+                if !INTERNAL_PROPERTIES.contains(&key.as_str()) {
+                    properties.insert(key, property_value(&value));
+                }
+                lines.next();
+            }
+            None if line.trim().is_empty() && !properties.is_empty() => {
+                lines.next();
+            }
+            None => break,
+        }
+    }
+
+    (properties, lines.collect::<Vec<_>>().join("\n"))
+}
+
+fn parse_property(text: &str) -> Option<(String, String)> {
+    let (key, value) = text.split_once(":: ")
+        .or_else(|| text.strip_suffix("::").map(|k| (k, "")))?;
+    let valid = !key.is_empty()
+        && key.chars().all(|c| c.is_alphanumeric() || c == '-' || c == '_');
+    valid.then(|| (key.to_lowercase(), value.trim().to_string()))
+}
+
+/// Converts `tags:: a, [[b c]]` style values into lists, keeps others as text
+fn property_value(value: &str) -> Value {
+    if value.contains(',') || value.starts_with("[[") {
+        Value::Array(
+            value.split(',')
+                .map(|v| v.trim().trim_start_matches("[[").trim_end_matches("]]").trim_start_matches('#'))
+                .filter(|v| !v.is_empty())
+                .map(Value::from)
+                .collect(),
+        )
+    } else {
+        Value::from(value)
+    }
+}
+
+/// Collects every block with an `id::` property
+fn index_blocks(pages: &[Page]) -> HashMap<String, BlockTarget> {
+    let mut blocks = HashMap::new();
+
+    for page in pages {
+        let mut last_block = String::new();
+        for line in page.body.lines() {
+            let trimmed = line.trim_start();
+            if let Some(text) = trimmed.strip_prefix("- ") {
+                last_block = text.to_string();
+            } else if let Some(uuid) = trimmed.strip_prefix("id:: ") {
+                blocks.insert(uuid.trim().to_lowercase(), BlockTarget {
+                    output: page.output.clone(),
+                    text: strip_refs(&last_block),
+                });
+            }
+        }
+    }
+
+    blocks
+}
+
+/// Plain text of a block for use as link label
+fn strip_refs(text: &str) -> String {
+    text.replace("[[", "").replace("]]", "").trim().to_string()
+}
+
+/// Rewrites one page body; unresolved block uuids are pushed to `unresolved`
+fn convert_body(
+    body: &str,
+    output: &str,
+    names: &HashMap<String, String>,
+    blocks: &HashMap<String, BlockTarget>,
+    unresolved: &mut Vec<String>,
+) -> String {
+    let assets_prefix = format!("{}assets/", "../".repeat(output.matches('/').count()));
+    let mut lines = Vec::new();
+
+    for line in body.lines() {
+        let trimmed = line.trim_start();
+        if let Some((key, _)) = parse_property(trimmed) {
+            if INTERNAL_PROPERTIES.contains(&key.as_str()) {
+                continue;
+            }
+        }
+
+        // Logseq indents with tabs; markdown lists nest with two spaces
+        let indent = &line[..line.len() - trimmed.len()];
+        let indent = indent.replace('\t', "  ");
+
+        let mut text = trimmed.to_string();
+        text = replace_embeds(&text, output, blocks, unresolved);
+        text = replace_block_refs(&text, output, blocks, unresolved);
+        text = replace_page_refs(&text, output, names);
+        text = text.replace("../assets/", &assets_prefix);
+
+        lines.push(format!("{}{}", indent, text));
+    }
+
+    let mut converted = lines.join("\n").trim_matches('\n').to_string();
+    converted.push('\n');
+    converted
+}
+
+/// `{{embed ((uuid))}}` → quoted block text with a link
+fn replace_embeds(
+    text: &str,
+    output: &str,
+    blocks: &HashMap<String, BlockTarget>,
+    unresolved: &mut Vec<String>,
+) -> String {
+    replace_spans(text, "{{embed ((", "))}}", |uuid| {
+        match blocks.get(&uuid.to_lowercase()) {
+            Some(block) => Some(format!("> {} ([source]({}))", block.text, relative_link(output, &block.output))),
+            None => {
+                unresolved.push(uuid.to_string());
+                None
+            }
+        }
+    })
+}
+
+/// `((uuid))` → `[block text](page.md)`
+fn replace_block_refs(
+    text: &str,
+    output: &str,
+    blocks: &HashMap<String, BlockTarget>,
+    unresolved: &mut Vec<String>,
+) -> String {
+    replace_spans(text, "((", "))", |uuid| {
+        if !is_uuid(uuid) {
+            return None;
+        }
+        match blocks.get(&uuid.to_lowercase()) {
+            Some(block) => Some(format!("[{}]({})", block.text, relative_link(output, &block.output))),
+            None => {
+                unresolved.push(uuid.to_string());
+                None
+            }
+        }
+    })
+}
+
+fn is_uuid(text: &str) -> bool {
+    text.len() == 36 && text.chars().all(|c| c.is_ascii_hexdigit() || c == '-')
+}
+
+/// `[[Page]]` → `[Page](relative.md)`, `#[[multi word]]` → `#multi-word`
+fn replace_page_refs(text: &str, output: &str, names: &HashMap<String, String>) -> String {
+    let text = replace_spans(text, "#[[", "]]", |name| Some(format!("#{}", name.replace(' ', "-"))));
+    replace_spans(&text, "[[", "]]", |name| {
+        names.get(&name.to_lowercase())
+            .map(|target| format!("[{}]({})", name, relative_link(output, target)))
+    })
+}
+
+/// Replaces `open … close` spans using `f`; `None` keeps the span unchanged
+fn replace_spans(text: &str, open: &str, close: &str, mut f: impl FnMut(&str) -> Option<String>) -> String {
+    let mut out = String::new();
+    let mut rest = text;
+
+    while let Some(start) = rest.find(open) {
+        let after = &rest[start + open.len()..];
+        let Some(end) = after.find(close) else { break };
+
+        out.push_str(&rest[..start]);
+        let inner = &after[..end];
+        match f(inner) {
+            Some(replacement) => out.push_str(&replacement),
+            None => out.push_str(&rest[start..start + open.len() + end + close.len()]),
+        }
+        rest = &after[end + close.len()..];
+    }
+
+    out.push_str(rest);
+    out
+}
+
+/// Relative link from one output file to another, with spaces encoded
+fn relative_link(from: &str, to: &str) -> String {
+    let mut from_dir: Vec<&str> = from.split('/').collect();
+    from_dir.pop();
+    let to_parts: Vec<&str> = to.split('/').collect();
+
+    let common = from_dir.iter().zip(&to_parts).take_while(|(a, b)| a == b).count();
+    let mut parts: Vec<String> = vec!["..".to_string(); from_dir.len() - common];
+    parts.extend(to_parts[common..].iter().map(|p| p.replace(' ', "%20")));
+    parts.join("/")
+}
+
+fn copy_assets(source: &Path, dest: &Path) -> Result<usize, String> {
+    if !source.is_dir() {
+        return Ok(0);
+    }
+    fs::create_dir_all(dest).map_err(|e| format!("Failed to create assets folder: {}", e))?;
+
+    let mut count = 0;
+    for entry in fs::read_dir(source).map_err(|e| format!("Failed to read assets: {}", e))?.flatten() {
+        let path = entry.path();
+        if path.is_dir() {
+            count += copy_assets(&path, &dest.join(entry.file_name()))?;
+        } else if path.is_file() {
+            fs::copy(&path, dest.join(entry.file_name()))
+                .map_err(|e| format!("Failed to copy asset: {}", e))?;
+            count += 1;
+        }
+    }
+    Ok(count)
+}
+
+// ============================================================================
+// TESTS
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn setup_graph() -> TempDir {
+        let graph = TempDir::new().expect("Failed to create graph");
+        let root = graph.path();
+        for dir in ["pages", "journals", "assets", "logseq"] {
+            fs::create_dir_all(root.join(dir)).unwrap();
+        }
+
+        fs::write(root.join("logseq/config.edn"), ";; :journal/file-name-format \"x\"\n{:journal/file-name-format \"yyyy_MM_dd\"}\n").unwrap();
+        fs::write(
+            root.join("pages/Work___Roadmap.md"),
+            "alias:: Plan\ntags:: work, [[q1 goals]]\n\n- Ship v2\n\tid:: 6500aaaa-0000-4000-8000-000000000001\n\tcollapsed:: true\n\t- Draft ![chart](../assets/chart.png)\n",
+        ).unwrap();
+        fs::write(
+            root.join("journals/2024_01_31.md"),
+            "- Worked on [[Work/Roadmap]] and [[Plan]] #[[deep work]]\n- Progress: ((6500aaaa-0000-4000-8000-000000000001))\n- Missing ((6500aaaa-0000-4000-8000-00000000ffff))\n- See [[Unknown page]]\n",
+        ).unwrap();
+        fs::write(root.join("pages/Jan 31st, 2024.org"), "* org page").unwrap();
+        fs::write(root.join("assets/chart.png"), b"png").unwrap();
+        graph
+    }
+
+    #[test]
+    fn test_import_graph_structure() {
+        let graph = setup_graph();
+        let dest = TempDir::new().unwrap();
+
+        let report = import_graph(graph.path(), dest.path()).unwrap();
+        assert_eq!((report.pages, report.journals, report.assets), (1, 1, 1));
+        assert_eq!(report.skipped, vec!["pages/Jan 31st, 2024.org"]);
+        assert!(dest.path().join("Pages/Work/Roadmap.md").exists());
+        assert!(dest.path().join("Journals/2024-01-31.md").exists());
+        assert!(dest.path().join("assets/chart.png").exists());
+    }
+
+    #[test]
+    fn test_page_properties_and_asset_links() {
+        let graph = setup_graph();
+        let dest = TempDir::new().unwrap();
+        import_graph(graph.path(), dest.path()).unwrap();
+
+        let page = fs::read_to_string(dest.path().join("Pages/Work/Roadmap.md")).unwrap();
+        let (fields, body) = frontmatter::split(&page);
+        let fields = fields.expect("Properties become frontmatter");
+
+        assert_eq!(fields["tags"], serde_json::json!(["work", "q1 goals"]));
+        assert_eq!(fields["aliases"], serde_json::json!(["Plan"]));
+        assert_eq!(body, "- Ship v2\n  - Draft ![chart](../../assets/chart.png)\n", "Block ids are dropped");
+    }
+
+    #[test]
+    fn test_links_and_block_refs() {
+        let graph = setup_graph();
+        let dest = TempDir::new().unwrap();
+        let report = import_graph(graph.path(), dest.path()).unwrap();
+
+        let journal = fs::read_to_string(dest.path().join("Journals/2024-01-31.md")).unwrap();
+        assert!(journal.contains("[Work/Roadmap](../Pages/Work/Roadmap.md) and [Plan](../Pages/Work/Roadmap.md) #deep-work"));
+        assert!(journal.contains("Progress: [Ship v2](../Pages/Work/Roadmap.md)"));
+        assert!(journal.contains("See [[Unknown page]]"), "Unknown pages stay wiki links");
+
+        assert_eq!(report.unresolved_block_refs, vec![UnresolvedBlockRef {
+            file: "Journals/2024-01-31.md".to_string(),
+            uuid: "6500aaaa-0000-4000-8000-00000000ffff".to_string(),
+        }]);
+    }
+
+    #[test]
+    fn test_journal_names() {
+        let date = chrono::NaiveDate::from_ymd_opt(2024, 1, 31).unwrap();
+        assert_eq!(journal_title(&date), "Jan 31st, 2024");
+        assert_eq!(journal_date("2024_01_31", "yyyy_MM_dd"), Some(date));
+        assert_eq!(decode_page_name("a%2Fb___c"), "a/b/c");
+    }
+
+    #[test]
+    fn test_relative_link() {
+        assert_eq!(relative_link("Journals/a.md", "Pages/My Page.md"), "../Pages/My%20Page.md");
+        assert_eq!(relative_link("Pages/x/y.md", "Pages/z.md"), "../z.md");
+    }
+
+    #[test]
+    fn test_not_a_graph() {
+        let dir = TempDir::new().unwrap();
+        assert!(import_graph(dir.path(), dir.path()).is_err());
+    }
+}
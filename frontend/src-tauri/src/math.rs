@@ -0,0 +1,650 @@
+//! Math Typesetting
+//!
+//! Typesets the TeX of `$inline$` and `$$display$$` math as MathML, which
+//! the webviews, browsers and the print window lay out natively, so
+//! exported notes show equations instead of raw LaTeX without shipping a
+//! JavaScript renderer. The subset covers what notes typically use:
+//!
+//! - Letters, numbers, operators, Greek letters and common symbols
+//! - `^` and `_` scripts; limits of `\sum`, `\int`, `\lim`, ... in display math
+//! - `\frac`, `\binom`, `\sqrt[n]{}`, accents (`\hat`, `\vec`, `\overline`, ...)
+//! - `\text`, `\operatorname`, `\mathrm`, `\mathbf`, `\mathbb`, `\mathcal`, ...
+//! - `\left( ... \right)` and the `matrix`, `pmatrix`, `bmatrix`, `vmatrix`,
+//!   `cases` and `aligned` environments
+//!
+//! Unknown commands are shown in red (`<merror>`). The TeX source travels
+//! along as an `application/x-tex` annotation, which is how
+//! `convert::html_to_markdown` turns equations back into `$...$`.
+
+use crate::render::escape_html;
+
+/// MIME type of the TeX annotation
+pub const TEX_ENCODING: &str = "application/x-tex";
+
+/// Operators that take their scripts as limits in display math
+const LARGE_OPERATORS: &[&str] = &[
+    "sum", "prod", "coprod", "bigcup", "bigcap", "bigoplus", "bigotimes",
+    "lim", "max", "min", "sup", "inf", "limsup", "liminf",
+];
+
+/// Marks the nodes of `LARGE_OPERATORS` until their scripts are set
+const LIMITS: &str = " data-limits=\"\"";
+
+/// Function names set upright
+const FUNCTIONS: &[&str] = &[
+    "sin", "cos", "tan", "cot", "sec", "csc", "arcsin", "arccos", "arctan", "sinh", "cosh", "tanh",
+    "log", "ln", "lg", "exp", "det", "dim", "ker", "deg", "gcd", "arg", "hom", "Pr",
+    "lim", "max", "min", "sup", "inf", "limsup", "liminf",
+];
+
+/// Greek letters, `\name` to character
+const GREEK: &[(&str, char)] = &[
+    ("alpha", 'α'), ("beta", 'β'), ("gamma", 'γ'), ("delta", 'δ'), ("epsilon", 'ϵ'),
+    ("varepsilon", 'ε'), ("zeta", 'ζ'), ("eta", 'η'), ("theta", 'θ'), ("vartheta", 'ϑ'),
+    ("iota", 'ι'), ("kappa", 'κ'), ("lambda", 'λ'), ("mu", 'μ'), ("nu", 'ν'), ("xi", 'ξ'),
+    ("pi", 'π'), ("varpi", 'ϖ'), ("rho", 'ρ'), ("varrho", 'ϱ'), ("sigma", 'σ'), ("varsigma", 'ς'),
+    ("tau", 'τ'), ("upsilon", 'υ'), ("phi", 'ϕ'), ("varphi", 'φ'), ("chi", 'χ'), ("psi", 'ψ'),
+    ("omega", 'ω'), ("Gamma", 'Γ'), ("Delta", 'Δ'), ("Theta", 'Θ'), ("Lambda", 'Λ'), ("Xi", 'Ξ'),
+    ("Pi", 'Π'), ("Sigma", 'Σ'), ("Upsilon", 'Υ'), ("Phi", 'Φ'), ("Psi", 'Ψ'), ("Omega", 'Ω'),
+];
+
+/// Symbols set as operators, `\name` to character
+const OPERATORS: &[(&str, char)] = &[
+    ("times", '×'), ("cdot", '⋅'), ("div", '÷'), ("pm", '±'), ("mp", '∓'), ("ast", '∗'),
+    ("star", '⋆'), ("circ", '∘'), ("bullet", '∙'), ("oplus", '⊕'), ("otimes", '⊗'),
+    ("leq", '≤'), ("le", '≤'), ("geq", '≥'), ("ge", '≥'), ("neq", '≠'), ("ne", '≠'),
+    ("approx", '≈'), ("equiv", '≡'), ("sim", '∼'), ("simeq", '≃'), ("cong", '≅'),
+    ("propto", '∝'), ("ll", '≪'), ("gg", '≫'), ("perp", '⊥'), ("parallel", '∥'), ("mid", '∣'),
+    ("in", '∈'), ("notin", '∉'), ("ni", '∋'), ("subset", '⊂'), ("supset", '⊃'),
+    ("subseteq", '⊆'), ("supseteq", '⊇'), ("cup", '∪'), ("cap", '∩'), ("setminus", '∖'),
+    ("wedge", '∧'), ("land", '∧'), ("vee", '∨'), ("lor", '∨'), ("neg", '¬'), ("lnot", '¬'),
+    ("forall", '∀'), ("exists", '∃'), ("nexists", '∄'),
+    ("to", '→'), ("rightarrow", '→'), ("leftarrow", '←'), ("gets", '←'),
+    ("leftrightarrow", '↔'), ("Rightarrow", '⇒'), ("implies", '⇒'), ("Leftarrow", '⇐'),
+    ("Leftrightarrow", '⇔'), ("iff", '⇔'), ("mapsto", '↦'), ("uparrow", '↑'), ("downarrow", '↓'),
+    ("sum", '∑'), ("prod", '∏'), ("coprod", '∐'), ("int", '∫'), ("iint", '∬'), ("iiint", '∭'),
+    ("oint", '∮'), ("bigcup", '⋃'), ("bigcap", '⋂'), ("bigoplus", '⨁'), ("bigotimes", '⨂'),
+    ("langle", '⟨'), ("rangle", '⟩'), ("lfloor", '⌊'), ("rfloor", '⌋'), ("lceil", '⌈'),
+    ("rceil", '⌉'), ("vert", '|'), ("Vert", '‖'), ("lbrace", '{'), ("rbrace", '}'),
+    ("ldots", '…'), ("dots", '…'), ("cdots", '⋯'), ("vdots", '⋮'), ("ddots", '⋱'),
+    ("colon", ':'), ("prime", '′'),
+];
+
+/// Symbols set as identifiers, `\name` to character
+const IDENTIFIERS: &[(&str, char)] = &[
+    ("infty", '∞'), ("partial", '∂'), ("nabla", '∇'), ("emptyset", '∅'), ("varnothing", '∅'),
+    ("hbar", 'ℏ'), ("ell", 'ℓ'), ("Re", 'ℜ'), ("Im", 'ℑ'), ("aleph", 'ℵ'), ("angle", '∠'),
+    ("triangle", '△'), ("top", '⊤'), ("bot", '⊥'),
+];
+
+/// Accents, `\name` to the mark set over (or under) the argument
+const ACCENTS: &[(&str, char)] = &[
+    ("hat", '^'), ("widehat", '^'), ("bar", '¯'), ("overline", '‾'), ("vec", '→'),
+    ("overrightarrow", '→'), ("dot", '˙'), ("ddot", '¨'), ("tilde", '~'), ("widetilde", '~'),
+    ("check", 'ˇ'), ("breve", '˘'), ("acute", '´'), ("grave", '`'), ("underline", '_'),
+];
+
+/// Typesets TeX as a MathML `<math>` element
+///
+/// `display` sets the equation as its own block (`$$...$$`), with large
+/// operators taking their scripts above and below.
+pub fn tex_to_mathml(tex: &str, display: bool) -> String {
+    let parser = Parser { tex, pos: 0, display, variant: Variant::Default };
+    // `&` and `\\` outside an environment have nothing to separate
+    let nodes = parser.all();
+    format!(
+        "<math xmlns=\"http://www.w3.org/1998/Math/MathML\"{}><semantics>{}<annotation encoding=\"{}\">{}</annotation></semantics></math>",
+        if display { " display=\"block\"" } else { "" },
+        nodes.replace(LIMITS, ""),
+        TEX_ENCODING,
+        escape_html(tex.trim())
+    )
+}
+
+/// What ends a row
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Stop {
+    /// `}`
+    Group,
+    /// `&`
+    Cell,
+    /// `\\`
+    Row,
+    /// `\right`
+    Right,
+    /// `\end{...}`
+    EndEnvironment,
+}
+
+/// Letter style of `\mathbf`, `\mathbb`, ...
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Variant {
+    /// Italic letters
+    Default,
+    /// Upright letters (`\mathrm`)
+    Normal,
+    Bold,
+    DoubleStruck,
+    Script,
+    Fraktur,
+    SansSerif,
+    Monospace,
+}
+
+struct Parser<'a> {
+    tex: &'a str,
+    pos: usize,
+    display: bool,
+    variant: Variant,
+}
+
+impl<'a> Parser<'a> {
+    fn rest(&self) -> &'a str {
+        &self.tex[self.pos..]
+    }
+
+    fn skip_space(&mut self) {
+        let rest = self.rest();
+        self.pos += rest.len() - rest.trim_start().len();
+    }
+
+    /// The stop at the current position, if any
+    fn stop(&self) -> Option<Stop> {
+        let rest = self.rest();
+        if rest.starts_with('}') {
+            Some(Stop::Group)
+        } else if rest.starts_with('&') {
+            Some(Stop::Cell)
+        } else if rest.starts_with("\\\\") {
+            Some(Stop::Row)
+        } else if command_is(rest, "right") {
+            Some(Stop::Right)
+        } else if command_is(rest, "end") {
+            Some(Stop::EndEnvironment)
+        } else {
+            None
+        }
+    }
+
+    /// Skips `}`, `&`, `\\`, `\right` or `\end{...}` (without its delimiter)
+    fn skip_stop(&mut self) {
+        match self.stop() {
+            None => {}
+            Some(Stop::Group | Stop::Cell) => self.pos += 1,
+            Some(Stop::Row) => self.pos += 2,
+            Some(Stop::Right) => self.pos += "\\right".len(),
+            Some(Stop::EndEnvironment) => {
+                self.pos += "\\end".len();
+                self.group_text();
+            }
+        }
+    }
+
+    /// Parses nodes up to the next stop (not consumed) or the end
+    fn row(&mut self) -> Vec<String> {
+        let mut nodes: Vec<String> = Vec::new();
+        loop {
+            self.skip_space();
+            if self.rest().is_empty() || self.stop().is_some() {
+                return nodes;
+            }
+            if self.rest().starts_with(['^', '_']) {
+                let base = nodes.pop().unwrap_or_else(|| "<mrow></mrow>".to_string());
+                nodes.push(self.scripts(base));
+                continue;
+            }
+            if let Some(node) = self.atom() {
+                nodes.push(node);
+            }
+        }
+    }
+
+    /// Attaches the `^` and `_` scripts at the current position to `base`
+    fn scripts(&mut self, base: String) -> String {
+        let mut sub = None;
+        let mut sup = None;
+        loop {
+            self.skip_space();
+            let slot = match self.rest().chars().next() {
+                Some('_') if sub.is_none() => &mut sub,
+                Some('^') if sup.is_none() => &mut sup,
+                _ => break,
+            };
+            self.pos += 1;
+            *slot = Some(self.argument());
+        }
+
+        let limits = self.display && base.contains(LIMITS);
+        match (sub, sup, limits) {
+            (Some(sub), Some(sup), true) => format!("<munderover>{}{}{}</munderover>", base, sub, sup),
+            (Some(sub), None, true) => format!("<munder>{}{}</munder>", base, sub),
+            (None, Some(sup), true) => format!("<mover>{}{}</mover>", base, sup),
+            (Some(sub), Some(sup), false) => format!("<msubsup>{}{}{}</msubsup>", base, sub, sup),
+            (Some(sub), None, false) => format!("<msub>{}{}</msub>", base, sub),
+            (None, Some(sup), false) => format!("<msup>{}{}</msup>", base, sup),
+            (None, None, _) => base,
+        }
+    }
+
+    /// A single node: a group, a command or one character
+    fn atom(&mut self) -> Option<String> {
+        let c = self.rest().chars().next()?;
+        match c {
+            '{' => {
+                self.pos += 1;
+                let nodes = self.row();
+                if self.stop() == Some(Stop::Group) {
+                    self.pos += 1;
+                }
+                Some(mrow(nodes))
+            }
+            '\\' => self.command(),
+            '0'..='9' => {
+                let rest = self.rest();
+                let digits = |s: &str| s.find(|c: char| !c.is_ascii_digit()).unwrap_or(s.len());
+                let mut len = digits(rest);
+                if rest[len..].starts_with('.') && rest[len + 1..].starts_with(|c: char| c.is_ascii_digit()) {
+                    len += 1 + digits(&rest[len + 1..]);
+                }
+                let number = &rest[..len];
+                self.pos += len;
+                Some(format!("<mn>{}</mn>", escape_html(&styled(number, self.variant))))
+            }
+            c if c.is_alphabetic() => {
+                self.pos += c.len_utf8();
+                if self.variant == Variant::Normal {
+                    // Upright runs stay one word
+                    let rest = self.rest();
+                    let len = rest.find(|c: char| !c.is_alphabetic()).unwrap_or(rest.len());
+                    self.pos += len;
+                    let word = &self.tex[self.pos - len - c.len_utf8()..self.pos];
+                    return Some(format!("<mi mathvariant=\"normal\">{}</mi>", escape_html(word)));
+                }
+                Some(format!("<mi>{}</mi>", escape_html(&styled(&c.to_string(), self.variant))))
+            }
+            '~' => {
+                self.pos += 1;
+                Some("<mspace width=\"0.28em\"></mspace>".to_string())
+            }
+            _ => {
+                self.pos += c.len_utf8();
+                let symbol = match c {
+                    '-' => '−',
+                    '*' => '∗',
+                    '\'' => '′',
+                    other => other,
+                };
+                Some(operator(symbol, c == '(' || c == ')' || c == '[' || c == ']' || c == '|'))
+            }
+        }
+    }
+
+    /// The argument of a script or command: a group or a single atom
+    fn argument(&mut self) -> String {
+        self.skip_space();
+        self.atom().unwrap_or_else(|| "<mrow></mrow>".to_string())
+    }
+
+    /// The raw text of a `{...}` group (or the next character)
+    fn group_text(&mut self) -> String {
+        self.skip_space();
+        let rest = self.rest();
+        if let Some(inner) = rest.strip_prefix('{') {
+            let mut depth = 1;
+            for (i, c) in inner.char_indices() {
+                match c {
+                    '{' => depth += 1,
+                    '}' => depth -= 1,
+                    _ => {}
+                }
+                if depth == 0 {
+                    self.pos += i + 2;
+                    return inner[..i].to_string();
+                }
+            }
+            self.pos = self.tex.len();
+            return inner.to_string();
+        }
+        let c = rest.chars().next().map(String::from).unwrap_or_default();
+        self.pos += c.len();
+        c
+    }
+
+    /// A `\command` and its arguments
+    fn command(&mut self) -> Option<String> {
+        let rest = &self.rest()[1..];
+        let len = match rest.find(|c: char| !c.is_ascii_alphabetic()) {
+            Some(0) => rest.chars().next().map_or(0, char::len_utf8),
+            Some(len) => len,
+            None => rest.len(),
+        };
+        let name = rest[..len].to_string();
+        self.pos += 1 + len;
+
+        let node = match name.as_str() {
+            "" => return None,
+            "," | ":" | ";" | " " => "<mspace width=\"0.22em\"></mspace>".to_string(),
+            "!" => "<mspace width=\"-0.17em\"></mspace>".to_string(),
+            "quad" => "<mspace width=\"1em\"></mspace>".to_string(),
+            "qquad" => "<mspace width=\"2em\"></mspace>".to_string(),
+            "{" | "}" | "|" | "$" | "%" | "#" | "&" | "_" => operator(name.chars().next().unwrap_or(' '), false),
+            "frac" | "dfrac" | "tfrac" | "cfrac" => {
+                let numerator = self.argument();
+                format!("<mfrac>{}{}</mfrac>", numerator, self.argument())
+            }
+            "binom" => {
+                let top = self.argument();
+                format!(
+                    "<mrow><mo>(</mo><mfrac linethickness=\"0\">{}{}</mfrac><mo>)</mo></mrow>",
+                    top,
+                    self.argument()
+                )
+            }
+            "sqrt" => {
+                self.skip_space();
+                match self.rest().strip_prefix('[').and_then(|inner| inner.find(']').map(|end| inner[..end].to_string())) {
+                    Some(index) => {
+                        self.pos += index.len() + 2;
+                        let index = Parser { tex: &index, pos: 0, display: false, variant: self.variant }.all();
+                        format!("<mroot>{}{}</mroot>", self.argument(), index)
+                    }
+                    None => format!("<msqrt>{}</msqrt>", self.argument()),
+                }
+            }
+            "text" | "textrm" | "textit" | "textbf" | "mbox" => {
+                format!("<mtext>{}</mtext>", escape_html(&self.group_text()))
+            }
+            "operatorname" => format!("<mi>{}</mi>", escape_html(&self.group_text())),
+            "mathrm" | "mathit" | "mathbf" | "boldsymbol" | "mathbb" | "mathcal" | "mathscr"
+            | "mathfrak" | "mathsf" | "mathtt" => {
+                let variant = match name.as_str() {
+                    "mathrm" => Variant::Normal,
+                    "mathit" => Variant::Default,
+                    "mathbf" | "boldsymbol" => Variant::Bold,
+                    "mathbb" => Variant::DoubleStruck,
+                    "mathcal" | "mathscr" => Variant::Script,
+                    "mathfrak" => Variant::Fraktur,
+                    "mathsf" => Variant::SansSerif,
+                    _ => Variant::Monospace,
+                };
+                let outer = std::mem::replace(&mut self.variant, variant);
+                let node = self.argument();
+                self.variant = outer;
+                node
+            }
+            "left" => {
+                let open = self.delimiter();
+                let nodes = self.row();
+                let mut close = String::new();
+                if self.stop() == Some(Stop::Right) {
+                    self.skip_stop();
+                    close = self.delimiter();
+                }
+                format!("<mrow>{}{}{}</mrow>", open, nodes.concat(), close)
+            }
+            "big" | "Big" | "bigg" | "Bigg" | "bigl" | "bigr" | "Bigl" | "Bigr" | "biggl" | "biggr"
+            | "displaystyle" | "textstyle" | "limits" | "nolimits" => return None,
+            "begin" => self.environment(),
+            name if FUNCTIONS.contains(&name) => {
+                let limits = if LARGE_OPERATORS.contains(&name) { LIMITS } else { "" };
+                format!("<mi{}>{}</mi>", limits, name)
+            }
+            name => match lookup(GREEK, name) {
+                // Capitals are upright
+                Some(c) if c.is_uppercase() => format!("<mi mathvariant=\"normal\">{}</mi>", c),
+                Some(c) => format!("<mi>{}</mi>", c),
+                None => match lookup(IDENTIFIERS, name) {
+                    Some(c) => format!("<mi>{}</mi>", c),
+                    None => match (lookup(OPERATORS, name), lookup(ACCENTS, name)) {
+                        (Some(c), _) if LARGE_OPERATORS.contains(&name) => {
+                            format!("<mo largeop=\"true\"{}>{}</mo>", LIMITS, c)
+                        }
+                        (Some(c), _) => operator(c, false),
+                        (None, Some(mark)) if name == "underline" => {
+                            format!("<munder accentunder=\"true\">{}<mo>{}</mo></munder>", self.argument(), mark)
+                        }
+                        (None, Some(mark)) => {
+                            format!("<mover accent=\"true\">{}<mo>{}</mo></mover>", self.argument(), mark)
+                        }
+                        (None, None) => format!("<merror><mtext>\\{}</mtext></merror>", escape_html(name)),
+                    },
+                },
+            },
+        };
+        Some(node)
+    }
+
+    /// The delimiter after `\left` or `\right`; `.` is none
+    fn delimiter(&mut self) -> String {
+        self.skip_space();
+        let rest = self.rest();
+        let symbol = if let Some(command) = rest.strip_prefix('\\') {
+            let len = match command.find(|c: char| !c.is_ascii_alphabetic()) {
+                Some(0) => command.chars().next().map_or(0, char::len_utf8),
+                Some(len) => len,
+                None => command.len(),
+            };
+            self.pos += 1 + len;
+            match &command[..len] {
+                "{" | "lbrace" => Some('{'),
+                "}" | "rbrace" => Some('}'),
+                "|" | "Vert" => Some('‖'),
+                name => lookup(OPERATORS, name),
+            }
+        } else {
+            let c = rest.chars().next();
+            self.pos += c.map_or(0, char::len_utf8);
+            c.filter(|&c| c != '.')
+        };
+        symbol.map_or(String::new(), |c| format!("<mo stretchy=\"true\">{}</mo>", escape_html(&c.to_string())))
+    }
+
+    /// `\begin{name} ... \end{name}` as a table
+    fn environment(&mut self) -> String {
+        let name = self.group_text();
+        let name = name.trim_end_matches('*');
+        let mut rows: Vec<Vec<String>> = vec![Vec::new()];
+        loop {
+            let cell = mrow(self.row());
+            if let Some(row) = rows.last_mut() {
+                row.push(cell);
+            }
+            match self.stop() {
+                Some(Stop::Row) => {
+                    self.skip_stop();
+                    rows.push(Vec::new());
+                }
+                Some(Stop::EndEnvironment) => {
+                    self.skip_stop();
+                    break;
+                }
+                Some(Stop::Cell | Stop::Group | Stop::Right) => self.skip_stop(),
+                None => break,
+            }
+        }
+        // A trailing `\\` leaves an empty row
+        if rows.len() > 1 && rows.last().is_some_and(|row| row.iter().all(|cell| cell == "<mrow></mrow>")) {
+            rows.pop();
+        }
+
+        let align = match name {
+            "aligned" | "align" | "split" => " columnalign=\"right left\" columnspacing=\"0\"",
+            "cases" => " columnalign=\"left left\"",
+            _ => "",
+        };
+        let table = format!(
+            "<mtable{}>{}</mtable>",
+            align,
+            rows.iter()
+                .map(|row| format!("<mtr>{}</mtr>", row.iter().map(|cell| format!("<mtd>{}</mtd>", cell)).collect::<String>()))
+                .collect::<String>()
+        );
+        let (open, close) = match name {
+            "pmatrix" => ("(", ")"),
+            "bmatrix" => ("[", "]"),
+            "Bmatrix" | "cases" => ("{", ""),
+            "vmatrix" => ("|", "|"),
+            "Vmatrix" => ("‖", "‖"),
+            _ => return table,
+        };
+        let close = if name == "Bmatrix" { "}" } else { close };
+        let fence = |c: &str| if c.is_empty() { String::new() } else { format!("<mo>{}</mo>", c) };
+        format!("<mrow>{}{}{}</mrow>", fence(open), table, fence(close))
+    }
+
+    /// Parses the whole input as one node, skipping stray stops
+    fn all(mut self) -> String {
+        let mut nodes = Vec::new();
+        loop {
+            nodes.extend(self.row());
+            if self.stop().is_none() {
+                return mrow(nodes);
+            }
+            self.skip_stop();
+        }
+    }
+}
+
+// Helper: Whether `rest` starts with `\name` (not a longer command)
+fn command_is(rest: &str, name: &str) -> bool {
+    rest.strip_prefix('\\')
+        .and_then(|r| r.strip_prefix(name))
+        .is_some_and(|after| !after.starts_with(|c: char| c.is_ascii_alphabetic()))
+}
+
+// Helper: One node as is, several in an `<mrow>`
+fn mrow(nodes: Vec<String>) -> String {
+    if nodes.len() == 1 {
+        nodes.into_iter().next().unwrap_or_default()
+    } else {
+        format!("<mrow>{}</mrow>", nodes.concat())
+    }
+}
+
+fn operator(c: char, fence: bool) -> String {
+    let attributes = if fence { " stretchy=\"false\"" } else { "" };
+    format!("<mo{}>{}</mo>", attributes, escape_html(&c.to_string()))
+}
+
+fn lookup(table: &[(&str, char)], name: &str) -> Option<char> {
+    table.iter().find(|(n, _)| *n == name).map(|(_, c)| *c)
+}
+
+/// Maps letters and digits to the Mathematical Alphanumeric Symbols of a
+/// variant, which MathML Core relies on instead of `mathvariant`
+fn styled(text: &str, variant: Variant) -> String {
+    text.chars().map(|c| styled_char(c, variant)).collect()
+}
+
+fn styled_char(c: char, variant: Variant) -> char {
+    // Letters that predate the block live in Letterlike Symbols
+    let letterlike = match (variant, c) {
+        (Variant::DoubleStruck, 'C') => Some('ℂ'),
+        (Variant::DoubleStruck, 'H') => Some('ℍ'),
+        (Variant::DoubleStruck, 'N') => Some('ℕ'),
+        (Variant::DoubleStruck, 'P') => Some('ℙ'),
+        (Variant::DoubleStruck, 'Q') => Some('ℚ'),
+        (Variant::DoubleStruck, 'R') => Some('ℝ'),
+        (Variant::DoubleStruck, 'Z') => Some('ℤ'),
+        (Variant::Script, 'B') => Some('ℬ'),
+        (Variant::Script, 'E') => Some('ℰ'),
+        (Variant::Script, 'F') => Some('ℱ'),
+        (Variant::Script, 'H') => Some('ℋ'),
+        (Variant::Script, 'I') => Some('ℐ'),
+        (Variant::Script, 'L') => Some('ℒ'),
+        (Variant::Script, 'M') => Some('ℳ'),
+        (Variant::Script, 'R') => Some('ℛ'),
+        (Variant::Script, 'e') => Some('ℯ'),
+        (Variant::Script, 'g') => Some('ℊ'),
+        (Variant::Script, 'o') => Some('ℴ'),
+        (Variant::Fraktur, 'C') => Some('ℭ'),
+        (Variant::Fraktur, 'H') => Some('ℌ'),
+        (Variant::Fraktur, 'I') => Some('ℑ'),
+        (Variant::Fraktur, 'R') => Some('ℜ'),
+        (Variant::Fraktur, 'Z') => Some('ℨ'),
+        _ => None,
+    };
+    if let Some(letter) = letterlike {
+        return letter;
+    }
+
+    // First capital letter and first digit of the variant
+    let (letters, digits) = match variant {
+        Variant::Default | Variant::Normal => return c,
+        Variant::Bold => (0x1D400, Some(0x1D7CE)),
+        Variant::Script => (0x1D49C, None),
+        Variant::Fraktur => (0x1D504, None),
+        Variant::DoubleStruck => (0x1D538, Some(0x1D7D8)),
+        Variant::SansSerif => (0x1D5A0, Some(0x1D7E2)),
+        Variant::Monospace => (0x1D670, Some(0x1D7F6)),
+    };
+    let code = match c {
+        'A'..='Z' => letters + (c as u32 - 'A' as u32),
+        'a'..='z' => letters + 26 + (c as u32 - 'a' as u32),
+        '0'..='9' => match digits {
+            Some(digits) => digits + (c as u32 - '0' as u32),
+            None => return c,
+        },
+        _ => return c,
+    };
+    char::from_u32(code).unwrap_or(c)
+}
+
+// ============================================================================
+// TESTS
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Helper: The MathML between `<semantics>` and the annotation
+    fn body(tex: &str, display: bool) -> String {
+        let math = tex_to_mathml(tex, display);
+        let start = math.find("<semantics>").unwrap() + "<semantics>".len();
+        let end = math.find("<annotation").unwrap();
+        math[start..end].to_string()
+    }
+
+    #[test]
+    fn test_tex_to_mathml() {
+        assert_eq!(
+            body("x^2 + y_i^{n+1} = \\frac{a}{2}", false),
+            "<mrow><msup><mi>x</mi><mn>2</mn></msup><mo>+</mo><msubsup><mi>y</mi><mi>i</mi>\
+             <mrow><mi>n</mi><mo>+</mo><mn>1</mn></mrow></msubsup><mo>=</mo><mfrac><mi>a</mi><mn>2</mn></mfrac></mrow>"
+        );
+        assert_eq!(
+            body("\\sqrt[3]{\\alpha} \\leq \\sin\\theta", false),
+            "<mrow><mroot><mi>α</mi><mn>3</mn></mroot><mo>≤</mo><mi>sin</mi><mi>θ</mi></mrow>"
+        );
+        assert_eq!(
+            body("\\sum_{k=1}^n k", true),
+            "<mrow><munderover><mo largeop=\"true\">∑</mo><mrow><mi>k</mi><mo>=</mo><mn>1</mn></mrow><mi>n</mi></munderover><mi>k</mi></mrow>"
+        );
+        assert_eq!(body("\\sum_k", false), "<msub><mo largeop=\"true\">∑</mo><mi>k</mi></msub>");
+        assert_eq!(
+            body("\\mathbb{R} \\mathrm{d}x \\text{if } \\foo", false),
+            "<mrow><mi>ℝ</mi><mi mathvariant=\"normal\">d</mi><mi>x</mi><mtext>if </mtext><merror><mtext>\\foo</mtext></merror></mrow>"
+        );
+
+        let math = tex_to_mathml("a < b", true);
+        assert!(math.starts_with("<math xmlns=\"http://www.w3.org/1998/Math/MathML\" display=\"block\">"));
+        assert!(math.ends_with("<annotation encoding=\"application/x-tex\">a &lt; b</annotation></semantics></math>"));
+    }
+
+    #[test]
+    fn test_tex_environments() {
+        assert_eq!(
+            body("\\begin{pmatrix} 1 & 0 \\\\ 0 & 1 \\end{pmatrix}", false),
+            "<mrow><mo>(</mo><mtable><mtr><mtd><mn>1</mn></mtd><mtd><mn>0</mn></mtd></mtr>\
+             <mtr><mtd><mn>0</mn></mtd><mtd><mn>1</mn></mtd></mtr></mtable><mo>)</mo></mrow>"
+        );
+        assert_eq!(
+            body("\\left( x \\right.", false),
+            "<mrow><mo stretchy=\"true\">(</mo><mi>x</mi></mrow>"
+        );
+        // Unbalanced input still yields a complete element
+        let math = tex_to_mathml("\\frac{1}{ } }", false);
+        assert!(math.contains("<mfrac><mn>1</mn><mrow></mrow></mfrac>"));
+    }
+}
@@ -0,0 +1,256 @@
+//! Memory Budget
+//!
+//! Caps the memory held by the backend's in-memory caches, which matters on
+//! phones and low-RAM machines with large vaults. Each cache describes what
+//! it holds as `CacheEntry`s (an estimated size and when it was last used);
+//! while the total is over the budget, the least recently used entries are
+//! evicted:
+//!
+//! | Cache | Entry | After eviction |
+//! |-------|-------|----------------|
+//! | `outlines` | Parser of one document open in the mindmap view | Outline edits fail with `Document is not open` until it is opened again |
+//! | `metadata` | Metadata cache of the workspace | Reloaded from the app cache directory on next use |
+//! | `search` | Search index of the workspace | Reloaded from the app cache directory on next use |
+//!
+//! Thumbnails are cached on disk only, so they are not counted. Entries
+//! used in the last `MIN_IDLE_MS` are never evicted, so a cache in active
+//! use is not reloaded on every call even when it alone exceeds the budget.
+//!
+//! The budget is a user setting (not a workspace one), stored in
+//! `<config>/memory-budget.json`.
+
+use std::fs;
+use std::path::Path;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::OnceLock;
+use std::time::Instant;
+use serde::{Deserialize, Serialize};
+
+/// File name of the budget inside the config directory
+pub const BUDGET_FILE: &str = "memory-budget.json";
+
+/// Budget used until the user sets one (phones have far less memory)
+#[cfg(any(target_os = "android", target_os = "ios"))]
+pub const DEFAULT_LIMIT_MB: usize = 64;
+#[cfg(not(any(target_os = "android", target_os = "ios")))]
+pub const DEFAULT_LIMIT_MB: usize = 256;
+
+/// Bounds for a user-supplied budget
+pub const MIN_LIMIT_MB: usize = 16;
+pub const MAX_LIMIT_MB: usize = 8192;
+
+/// Entries used this recently are kept even over budget
+pub const MIN_IDLE_MS: u64 = 10_000;
+
+const MB: usize = 1024 * 1024;
+
+/// Caches the budget applies to
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CacheKind {
+    Outlines,
+    Metadata,
+    Search,
+}
+
+/// Something a cache holds in memory
+#[derive(Debug, Clone, PartialEq)]
+pub struct CacheEntry {
+    pub cache: CacheKind,
+    /// Document path (outlines) or workspace root
+    pub key: String,
+    /// Estimated size
+    pub bytes: usize,
+    /// Last use (see `stamp`)
+    pub last_used: u64,
+}
+
+/// Memory used by one cache
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct CacheUsage {
+    pub cache: CacheKind,
+    pub entries: usize,
+    pub bytes: usize,
+}
+
+/// Result of `get_memory_usage`
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct MemoryUsage {
+    pub limit_bytes: usize,
+    /// Estimated total of all caches
+    pub used_bytes: usize,
+    pub caches: Vec<CacheUsage>,
+}
+
+impl MemoryUsage {
+    /// Sums up cache entries per cache
+    pub fn from_entries(entries: &[CacheEntry], limit_bytes: usize) -> Self {
+        let mut caches: Vec<CacheUsage> = Vec::new();
+        for entry in entries {
+            match caches.iter_mut().find(|usage| usage.cache == entry.cache) {
+                Some(usage) => {
+                    usage.entries += 1;
+                    usage.bytes += entry.bytes;
+                }
+                None => caches.push(CacheUsage { cache: entry.cache, entries: 1, bytes: entry.bytes }),
+            }
+        }
+        caches.sort_by_key(|usage| usage.cache);
+
+        Self {
+            limit_bytes,
+            used_bytes: entries.iter().map(|entry| entry.bytes).sum(),
+            caches,
+        }
+    }
+}
+
+/// Milliseconds since the process started, used to order cache entries by use
+pub fn stamp() -> u64 {
+    static START: OnceLock<Instant> = OnceLock::new();
+    START.get_or_init(Instant::now).elapsed().as_millis() as u64
+}
+
+/// Picks the entries to evict to bring the total within `limit_bytes`,
+/// least recently used first; entries used after `now - MIN_IDLE_MS` are kept
+pub fn select_evictions(entries: &[CacheEntry], limit_bytes: usize, now: u64) -> Vec<&CacheEntry> {
+    let mut used: usize = entries.iter().map(|entry| entry.bytes).sum();
+    let mut by_age: Vec<&CacheEntry> = entries
+        .iter()
+        .filter(|entry| now.saturating_sub(entry.last_used) >= MIN_IDLE_MS)
+        .collect();
+    by_age.sort_by_key(|entry| entry.last_used);
+
+    let mut evicted = Vec::new();
+    for entry in by_age {
+        if used <= limit_bytes {
+            break;
+        }
+        used -= entry.bytes;
+        evicted.push(entry);
+    }
+    evicted
+}
+
+#[derive(Serialize, Deserialize)]
+struct StoredBudget {
+    limit_mb: usize,
+}
+
+/// Memory budget of the caches (internally synchronized)
+#[derive(Debug)]
+pub struct MemoryBudget {
+    limit_bytes: AtomicUsize,
+}
+
+impl Default for MemoryBudget {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl MemoryBudget {
+    /// Creates a budget of `DEFAULT_LIMIT_MB`
+    pub fn new() -> Self {
+        Self { limit_bytes: AtomicUsize::new(DEFAULT_LIMIT_MB * MB) }
+    }
+
+    /// Current budget in bytes
+    pub fn limit_bytes(&self) -> usize {
+        self.limit_bytes.load(Ordering::SeqCst)
+    }
+
+    /// Changes the budget
+    pub fn set_limit_mb(&self, limit_mb: usize) -> Result<(), String> {
+        if !(MIN_LIMIT_MB..=MAX_LIMIT_MB).contains(&limit_mb) {
+            return Err(format!(
+                "Invalid memory budget: {} MB (must be {}-{} MB)",
+                limit_mb, MIN_LIMIT_MB, MAX_LIMIT_MB
+            ));
+        }
+        self.limit_bytes.store(limit_mb * MB, Ordering::SeqCst);
+        Ok(())
+    }
+
+    /// Applies the budget saved in `config_dir`, if any
+    pub fn load(&self, config_dir: &Path) -> Result<(), String> {
+        let Ok(json) = fs::read_to_string(config_dir.join(BUDGET_FILE)) else {
+            return Ok(());
+        };
+        let stored: StoredBudget = serde_json::from_str(&json)
+            .map_err(|e| format!("Failed to parse memory budget: {}", e))?;
+        self.set_limit_mb(stored.limit_mb)
+    }
+
+    /// Saves the budget to `config_dir`
+    pub fn save(&self, config_dir: &Path) -> Result<(), String> {
+        let stored = StoredBudget { limit_mb: self.limit_bytes() / MB };
+        let json = serde_json::to_string_pretty(&stored)
+            .map_err(|e| format!("Failed to serialize memory budget: {}", e))?;
+        fs::write(config_dir.join(BUDGET_FILE), json)
+            .map_err(|e| format!("Failed to save memory budget: {}", e))
+    }
+}
+
+// ============================================================================
+// TESTS
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn entry(cache: CacheKind, key: &str, bytes: usize, last_used: u64) -> CacheEntry {
+        CacheEntry { cache, key: key.to_string(), bytes, last_used }
+    }
+
+    #[test]
+    fn test_evicts_least_recently_used_first() {
+        let now = 100_000;
+        let entries = vec![
+            entry(CacheKind::Search, "/vault", 50, 20_000),
+            entry(CacheKind::Outlines, "/vault/a.md", 30, 10_000),
+            entry(CacheKind::Outlines, "/vault/b.md", 30, 50_000),
+            entry(CacheKind::Metadata, "/vault", 40, now - 1_000),
+        ];
+
+        let keys = |evicted: Vec<&CacheEntry>| evicted.iter().map(|e| (e.cache, e.key.clone())).collect::<Vec<_>>();
+        assert!(select_evictions(&entries, 150, now).is_empty());
+        assert_eq!(keys(select_evictions(&entries, 120, now)), vec![(CacheKind::Outlines, "/vault/a.md".to_string())]);
+        assert_eq!(
+            keys(select_evictions(&entries, 70, now)),
+            vec![(CacheKind::Outlines, "/vault/a.md".to_string()), (CacheKind::Search, "/vault".to_string())]
+        );
+        // The metadata cache was just used, so it stays even over budget
+        assert_eq!(select_evictions(&entries, 0, now).len(), 3);
+
+        let usage = MemoryUsage::from_entries(&entries, 100);
+        assert_eq!(usage.used_bytes, 150);
+        assert_eq!(
+            usage.caches,
+            vec![
+                CacheUsage { cache: CacheKind::Outlines, entries: 2, bytes: 60 },
+                CacheUsage { cache: CacheKind::Metadata, entries: 1, bytes: 40 },
+                CacheUsage { cache: CacheKind::Search, entries: 1, bytes: 50 },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_budget_round_trip() {
+        let config = TempDir::new().unwrap();
+        let budget = MemoryBudget::new();
+        budget.load(config.path()).unwrap();
+        assert_eq!(budget.limit_bytes(), DEFAULT_LIMIT_MB * MB);
+
+        budget.set_limit_mb(32).unwrap();
+        budget.save(config.path()).unwrap();
+        let loaded = MemoryBudget::new();
+        loaded.load(config.path()).unwrap();
+        assert_eq!(loaded.limit_bytes(), 32 * MB);
+
+        assert!(budget.set_limit_mb(1).unwrap_err().starts_with("Invalid memory budget"));
+        assert_eq!(budget.limit_bytes(), 32 * MB);
+    }
+}
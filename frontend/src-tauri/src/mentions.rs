@@ -0,0 +1,220 @@
+//! Unlinked Mentions
+//!
+//! Finds plain-text occurrences of a note's names (title, file name and
+//! aliases) in other notes that are not links yet, so they can be turned
+//! into wiki links one by one. Matching ignores case and only counts whole
+//! words; text inside code, links, URLs and the frontmatter is skipped.
+
+use serde::{Deserialize, Serialize};
+use crate::frontmatter;
+
+/// Names shorter than this are too ambiguous to look for
+pub const MIN_NAME_LEN: usize = 3;
+
+/// Characters of context kept on each side of a mention
+const CONTEXT_CHARS: usize = 60;
+
+/// Byte range of a mention within a file's content
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TextRange {
+    pub start: usize,
+    pub end: usize,
+}
+
+/// A plain-text occurrence of a note name
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Mention {
+    /// Note containing the mention (relative to the workspace root)
+    pub path: String,
+    /// 1-based line number
+    pub line: usize,
+    pub range: TextRange,
+    /// The mention as written
+    pub text: String,
+    /// The line around the mention
+    pub context: String,
+}
+
+/// Finds unlinked mentions of `names` in the content of the note at `path`
+///
+/// Longer names win over shorter ones they contain.
+pub fn find_mentions(path: &str, content: &str, names: &[String]) -> Vec<Mention> {
+    let mut names: Vec<&str> = names
+        .iter()
+        .map(|n| n.trim())
+        .filter(|n| n.chars().count() >= MIN_NAME_LEN)
+        .collect();
+    names.sort_by_key(|n| std::cmp::Reverse(n.len()));
+    names.dedup_by(|a, b| a.eq_ignore_ascii_case(b));
+
+    let body = frontmatter::split(content).1;
+    let mut offset = content.len() - body.len();
+    let first_line = content[..offset].lines().count();
+
+    let mut mentions = Vec::new();
+    let mut in_fence = false;
+    for (i, line) in body.split_inclusive('\n').enumerate() {
+        let start = offset;
+        offset += line.len();
+        let line = line.trim_end_matches(['\n', '\r']);
+
+        let trimmed = line.trim_start();
+        if trimmed.starts_with("```") || trimmed.starts_with("~~~") {
+            in_fence = !in_fence;
+            continue;
+        }
+        if in_fence {
+            continue;
+        }
+
+        let masked = masked_spans(line);
+        let mut at = 0;
+        while at < line.len() {
+            let found = (!masked.iter().any(|&(s, e)| at >= s && at < e) && is_word_start(line, at))
+                .then(|| names.iter().find_map(|name| match_name(line, at, name)))
+                .flatten()
+                .filter(|&end| !masked.iter().any(|&(s, e)| end > s && at < e));
+            match found {
+                Some(end) => {
+                    mentions.push(Mention {
+                        path: path.to_string(),
+                        line: first_line + i + 1,
+                        range: TextRange { start: start + at, end: start + end },
+                        text: line[at..end].to_string(),
+                        context: context(line, at, end),
+                    });
+                    at = end;
+                }
+                None => at += line[at..].chars().next().map_or(1, char::len_utf8),
+            }
+        }
+    }
+    mentions
+}
+
+/// Turns the mention at `range` into a wiki link to `target`
+///
+/// The mention keeps its wording as the link alias when it differs from
+/// the target. Fails if the text at `range` is no longer one of `names`
+/// (the note changed since the mention was found).
+pub fn link_mention(content: &str, range: TextRange, names: &[String], target: &str) -> Result<String, String> {
+    let text = content
+        .get(range.start..range.end)
+        .ok_or("Mention is outside the note")?;
+    if !names.iter().any(|name| match_name(text, 0, name.trim()) == Some(text.len())) {
+        return Err(format!("Text at the mention changed: '{}'", text));
+    }
+
+    let link = if target.rsplit('/').next() == Some(text) {
+        format!("[[{}]]", target)
+    } else {
+        format!("[[{}|{}]]", target, text)
+    };
+    Ok(format!("{}{}{}", &content[..range.start], link, &content[range.end..]))
+}
+
+/// End of `name` if the line continues with it at `at` (ignoring case) as a whole word
+fn match_name(line: &str, at: usize, name: &str) -> Option<usize> {
+    let mut rest = line[at..].char_indices();
+    let mut end = at;
+    for expected in name.chars() {
+        let (i, c) = rest.next()?;
+        if !c.to_lowercase().eq(expected.to_lowercase()) {
+            return None;
+        }
+        end = at + i + c.len_utf8();
+    }
+    let next = line[end..].chars().next();
+    if next.is_some_and(is_word_char) {
+        return None;
+    }
+    Some(end)
+}
+
+fn is_word_start(line: &str, at: usize) -> bool {
+    !line[..at].chars().next_back().is_some_and(is_word_char)
+}
+
+fn is_word_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '_'
+}
+
+/// Byte spans of a line that are code, links or URLs
+fn masked_spans(line: &str) -> Vec<(usize, usize)> {
+    let mut spans = Vec::new();
+    let bytes = line.as_bytes();
+    let mut i = 0;
+    while i < bytes.len() {
+        let rest = &line[i..];
+        let end = if let Some(code) = rest.strip_prefix('`') {
+            code.find('`').map(|e| i + e + 2)
+        } else if rest.starts_with("[[") {
+            rest.find("]]").map(|e| i + e + 2)
+        } else if rest.starts_with('[') {
+            rest.find(']')
+                .filter(|&c| rest[c + 1..].starts_with('('))
+                .and_then(|c| rest[c..].find(')').map(|e| i + c + e + 1))
+        } else if rest.starts_with("http://") || rest.starts_with("https://") {
+            Some(i + rest.find(char::is_whitespace).unwrap_or(rest.len()))
+        } else {
+            None
+        };
+        match end {
+            Some(end) => {
+                spans.push((i, end));
+                i = end;
+            }
+            None => i += rest.chars().next().map_or(1, char::len_utf8),
+        }
+    }
+    spans
+}
+
+fn context(line: &str, start: usize, end: usize) -> String {
+    let before: String = line[..start].chars().rev().take(CONTEXT_CHARS).collect::<Vec<_>>().into_iter().rev().collect();
+    let after: String = line[end..].chars().take(CONTEXT_CHARS).collect();
+    format!("{}{}{}", before.trim_start(), &line[start..end], after.trim_end())
+}
+
+// ============================================================================
+// TESTS
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn names(names: &[&str]) -> Vec<String> {
+        names.iter().map(|n| n.to_string()).collect()
+    }
+
+    #[test]
+    fn test_find_mentions_skips_links_and_code() {
+        let content = "---\ntitle: Log\n---\nTalked about the roadmap today.\n\
+                       See [[Roadmap]] and [the roadmap](roadmap.md), `roadmap`.\n\
+                       ```\nroadmap\n```\nRoadmaps are not roadmap; Q3 Roadmap Review is.\n";
+        let mentions = find_mentions("log.md", content, &names(&["Roadmap", "Q3 Roadmap Review", "rm"]));
+
+        let found: Vec<(usize, &str)> = mentions.iter().map(|m| (m.line, m.text.as_str())).collect();
+        assert_eq!(found, vec![(4, "roadmap"), (9, "roadmap"), (9, "Q3 Roadmap Review")]);
+        assert_eq!(&content[mentions[0].range.start..mentions[0].range.end], "roadmap");
+        assert_eq!(mentions[0].context, "Talked about the roadmap today.");
+    }
+
+    #[test]
+    fn test_link_mention() {
+        let content = "Talked about the roadmap today.\n";
+        let range = TextRange { start: 17, end: 24 };
+        let names = names(&["Roadmap"]);
+
+        assert_eq!(
+            link_mention(content, range, &names, "Roadmap").unwrap(),
+            "Talked about the [[Roadmap|roadmap]] today.\n"
+        );
+        assert_eq!(
+            link_mention("See Roadmap.", TextRange { start: 4, end: 11 }, &names, "Roadmap").unwrap(),
+            "See [[Roadmap]]."
+        );
+        assert!(link_mention(content, TextRange { start: 0, end: 6 }, &names, "Roadmap").is_err());
+    }
+}
@@ -0,0 +1,281 @@
+//! Note Merging
+//!
+//! Combines two notes on the same topic into one, section by section. Both
+//! notes are split into a tree of heading sections (see `crate::outline`):
+//!
+//! - `append` adds the secondary note's top-level sections after the
+//!   primary's content
+//! - `interleave` merges sections with the same heading (title and level)
+//!   into each other, recursively; sections without a match are added at the
+//!   end of their parent
+//!
+//! Sections that already exist in the primary note with the same content are
+//! skipped. Content is compared ignoring blank lines and trailing whitespace.
+//! The primary note's frontmatter is kept; the secondary note's is dropped.
+
+use serde::{Deserialize, Serialize};
+use crate::frontmatter;
+use crate::outline::{OutlineDocument, OutlineNode};
+
+/// How the secondary note's sections are placed
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MergeStrategy {
+    /// After the primary note's content
+    #[default]
+    Append,
+    /// Into the primary section with the same heading
+    Interleave,
+}
+
+/// Result of merging two notes
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct MergeOutcome {
+    /// The merged primary note
+    pub content: String,
+    /// Secondary sections added as new sections
+    pub added_sections: usize,
+    /// Secondary sections merged into an existing section
+    pub merged_sections: usize,
+    /// Secondary sections skipped because the primary already has them
+    pub duplicate_sections: usize,
+}
+
+/// A heading and its lines, as a tree
+#[derive(Debug, Clone)]
+struct Section {
+    /// Heading line (`None` for the text before the first heading)
+    heading: Option<String>,
+    level: u8,
+    /// Normalized title, for matching
+    key: String,
+    /// Lines between the heading and the first subsection
+    body: Vec<String>,
+    children: Vec<Section>,
+}
+
+impl Section {
+    fn parse(content: &str) -> Self {
+        let lines: Vec<String> = content
+            .split('\n')
+            .map(|l| l.strip_suffix('\r').unwrap_or(l).to_string())
+            .collect();
+        Self::from_node(&OutlineDocument::new(content).outline(), &lines)
+    }
+
+    fn from_node(node: &OutlineNode, lines: &[String]) -> Self {
+        let is_root = node.level == 0;
+        let body_start = if is_root { 0 } else { node.line + 1 };
+        let body_end = node.children.first().map_or(node.end_line + 1, |c| c.line);
+        Self {
+            heading: (!is_root).then(|| lines[node.line].clone()),
+            level: node.level,
+            key: node.title.trim().to_lowercase(),
+            body: lines[body_start.min(body_end)..body_end].to_vec(),
+            children: node.children.iter().map(|c| Self::from_node(c, lines)).collect(),
+        }
+    }
+
+    fn render(&self, out: &mut Vec<String>) {
+        out.extend(self.heading.iter().cloned());
+        out.extend(self.body.iter().cloned());
+        for child in &self.children {
+            child.render(out);
+        }
+    }
+
+    fn lines(&self) -> Vec<String> {
+        let mut out = Vec::new();
+        self.render(&mut out);
+        out
+    }
+
+    /// Non-blank lines without trailing whitespace
+    fn fingerprint(&self) -> Vec<String> {
+        normalized(&self.lines())
+    }
+
+    fn has_body(&self) -> bool {
+        self.body.iter().any(|l| !l.trim().is_empty())
+    }
+
+    /// Adds a section after the last child, separated by a blank line
+    fn push_child(&mut self, mut child: Section) {
+        match self.children.last_mut() {
+            Some(last) => last.end_with_blank(),
+            None => ensure_blank(&mut self.body),
+        }
+        child.end_with_blank();
+        self.children.push(child);
+    }
+
+    /// Makes the rendered section end with a blank line
+    fn end_with_blank(&mut self) {
+        match self.children.last_mut() {
+            Some(last) => last.end_with_blank(),
+            None if self.heading.is_some() || !self.body.is_empty() => ensure_blank(&mut self.body),
+            None => {}
+        }
+    }
+
+    /// The section rendered last (itself if it has no subsections)
+    fn last_leaf(&mut self) -> &mut Section {
+        if self.children.is_empty() {
+            return self;
+        }
+        self.children.last_mut().unwrap().last_leaf()
+    }
+
+    /// Appends another body below this one, before the subsections
+    fn append_body(&mut self, other: &[String]) {
+        let lead = other.iter().take_while(|l| l.trim().is_empty()).count();
+        let tail = other.iter().rev().take_while(|l| l.trim().is_empty()).count();
+        let text = &other[lead..other.len() - tail];
+
+        let blank = self.body.iter().rev().take_while(|l| l.trim().is_empty()).count();
+        self.body.truncate(self.body.len() - blank);
+        if self.has_body() {
+            self.body.push(String::new());
+        }
+        self.body.extend(text.iter().cloned());
+        let keep = if self.children.is_empty() { blank } else { blank.max(1) };
+        self.body.extend(std::iter::repeat(String::new()).take(keep));
+    }
+}
+
+/// Merges `secondary` into `primary`
+pub fn merge(primary: &str, secondary: &str, strategy: MergeStrategy) -> MergeOutcome {
+    let (_, primary_body) = frontmatter::split(primary);
+    let frontmatter = &primary[..primary.len() - primary_body.len()];
+    let (_, secondary_body) = frontmatter::split(secondary);
+
+    let mut target = Section::parse(primary_body);
+    let source = Section::parse(secondary_body);
+    let mut outcome = MergeOutcome {
+        content: String::new(),
+        added_sections: 0,
+        merged_sections: 0,
+        duplicate_sections: 0,
+    };
+
+    match strategy {
+        MergeStrategy::Append => {
+            if source.has_body() && !contains_lines(&target, &normalized(&source.body)) {
+                append_preamble(&mut target, &source.body);
+            }
+            for child in source.children {
+                let fingerprint = child.fingerprint();
+                if target.children.iter().any(|c| c.fingerprint() == fingerprint) {
+                    outcome.duplicate_sections += 1;
+                } else {
+                    target.push_child(child);
+                    outcome.added_sections += 1;
+                }
+            }
+        }
+        MergeStrategy::Interleave => interleave(&mut target, source, &mut outcome),
+    }
+
+    let mut content = target.lines().join("\n").trim_end().to_string();
+    content.push('\n');
+    outcome.content = format!("{}{}", frontmatter, content.trim_start_matches('\n'));
+    outcome
+}
+
+// Helper: Merges the body and subsections of `source` into `target`
+fn interleave(target: &mut Section, source: Section, outcome: &mut MergeOutcome) {
+    let body = normalized(&source.body);
+    if !body.is_empty() && !contains_lines_in(&normalized(&target.body), &body) {
+        target.append_body(&source.body);
+    }
+
+    for child in source.children {
+        let matching = target
+            .children
+            .iter()
+            .position(|c| c.key == child.key && c.level == child.level);
+        match matching {
+            Some(index) if target.children[index].fingerprint() == child.fingerprint() => {
+                outcome.duplicate_sections += 1;
+            }
+            Some(index) => {
+                interleave(&mut target.children[index], child, outcome);
+                outcome.merged_sections += 1;
+            }
+            None => {
+                target.push_child(child);
+                outcome.added_sections += 1;
+            }
+        }
+    }
+}
+
+// Helper: Adds the secondary note's text before its first heading, after
+// all of the primary note's content
+fn append_preamble(target: &mut Section, body: &[String]) {
+    target.last_leaf().append_body(body);
+}
+
+fn contains_lines(section: &Section, needle: &[String]) -> bool {
+    contains_lines_in(&section.fingerprint(), needle)
+}
+
+fn contains_lines_in(haystack: &[String], needle: &[String]) -> bool {
+    needle.is_empty() || haystack.windows(needle.len()).any(|w| w == needle)
+}
+
+fn normalized(lines: &[String]) -> Vec<String> {
+    lines
+        .iter()
+        .map(|l| l.trim_end().to_string())
+        .filter(|l| !l.is_empty())
+        .collect()
+}
+
+fn ensure_blank(lines: &mut Vec<String>) {
+    if lines.last().map_or(true, |l| !l.trim().is_empty()) {
+        lines.push(String::new());
+    }
+}
+
+// ============================================================================
+// TESTS
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PRIMARY: &str = "---\ntags: [rust]\n---\n# Rust\nOwnership rules.\n\n## Tooling\ncargo\n\n## Links\n- book\n";
+    const SECONDARY: &str = "---\ntags: [lang]\n---\n# Rust\nBorrowing too.\n\n## Links\n- book\n\n## Tooling\nclippy\n\n## Async\ntokio\n";
+
+    #[test]
+    fn test_interleave_merges_matching_headings() {
+        let outcome = merge(PRIMARY, SECONDARY, MergeStrategy::Interleave);
+
+        assert_eq!(
+            outcome.content,
+            "---\ntags: [rust]\n---\n# Rust\nOwnership rules.\n\nBorrowing too.\n\n## Tooling\ncargo\n\nclippy\n\n## Links\n- book\n\n## Async\ntokio\n"
+        );
+        // Tooling and the top heading were merged, Links was identical
+        assert_eq!(outcome.merged_sections, 2);
+        assert_eq!(outcome.duplicate_sections, 1);
+        assert_eq!(outcome.added_sections, 1);
+    }
+
+    #[test]
+    fn test_append_skips_identical_sections() {
+        let primary = "Intro\n\n# Setup\nInstall it.\n";
+        let secondary = "# Setup\nInstall it.  \n\n# Usage\nRun it.";
+        let outcome = merge(primary, secondary, MergeStrategy::Append);
+
+        assert_eq!(outcome.content, "Intro\n\n# Setup\nInstall it.\n\n# Usage\nRun it.\n");
+        assert_eq!(outcome.added_sections, 1);
+        assert_eq!(outcome.duplicate_sections, 1);
+
+        // Merging again changes nothing
+        let again = merge(&outcome.content, secondary, MergeStrategy::Append);
+        assert_eq!(again.content, outcome.content);
+        assert_eq!(again.added_sections, 0);
+    }
+}
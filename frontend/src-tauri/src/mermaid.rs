@@ -0,0 +1,381 @@
+//! Mermaid Diagram Generation
+//!
+//! Builds Mermaid source from the structure of a plain markdown note:
+//!
+//! - **Flowchart** - every list item becomes a node, linked to its parent item
+//! - **Gantt** - task items (`- [ ] ...`) carrying a `YYYY-MM-DD` start date
+//!   and optional end date, grouped into sections by heading
+//! - **Sequence** - list items of the form `A -> B: message`
+//!   (`A --> B` for a dashed reply arrow)
+//!
+//! Fenced code blocks are ignored, so existing diagrams are not re-read.
+
+use std::path::Path;
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+use crate::frontmatter;
+use crate::notes::note_title;
+
+/// Diagram type to generate
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum MermaidKind {
+    Flowchart,
+    Gantt,
+    Sequence,
+}
+
+/// A list item with its indentation
+struct ListItem<'a> {
+    indent: usize,
+    task: Option<bool>,
+    text: &'a str,
+}
+
+/// A gantt task with its dates
+struct Task {
+    name: String,
+    done: bool,
+    start: NaiveDate,
+    end: Option<NaiveDate>,
+}
+
+/// Generates Mermaid source for a note
+///
+/// # Arguments
+/// * `content` - Full note content (frontmatter is used for the title)
+/// * `path` - Note path, used as title fallback
+/// * `kind` - Diagram type
+pub fn generate_mermaid(content: &str, path: &Path, kind: MermaidKind) -> Result<String, String> {
+    let (fields, body) = frontmatter::split(content);
+    let title = note_title(&fields.unwrap_or_default(), body, path);
+
+    match kind {
+        MermaidKind::Flowchart => flowchart(body),
+        MermaidKind::Gantt => gantt(body, &title),
+        MermaidKind::Sequence => sequence(body),
+    }
+}
+
+fn flowchart(body: &str) -> Result<String, String> {
+    let mut out = String::from("flowchart TD\n");
+    // Open ancestors as (indent, node number)
+    let mut stack: Vec<(usize, usize)> = Vec::new();
+    let mut count = 0;
+
+    for line in code_free_lines(body) {
+        let Some(item) = parse_list_item(line) else { continue };
+        count += 1;
+
+        while stack.last().is_some_and(|&(indent, _)| indent >= item.indent) {
+            stack.pop();
+        }
+        out.push_str(&format!("    n{}[\"{}\"]\n", count, escape_label(&plain_text(item.text))));
+        if let Some(&(_, parent)) = stack.last() {
+            out.push_str(&format!("    n{} --> n{}\n", parent, count));
+        }
+        stack.push((item.indent, count));
+    }
+
+    if count == 0 {
+        return Err("No list items found to build a flowchart".to_string());
+    }
+    Ok(out)
+}
+
+fn gantt(body: &str, title: &str) -> Result<String, String> {
+    let mut out = format!("gantt\n    title {}\n    dateFormat YYYY-MM-DD\n", escape_text(title));
+    let mut section: Option<String> = None;
+    let mut section_written = true;
+    let mut count = 0;
+
+    for line in code_free_lines(body) {
+        let after_hashes = line.trim_start().trim_start_matches('#');
+        if after_hashes.len() < line.trim_start().len() && after_hashes.starts_with(' ') {
+            section = Some(after_hashes.trim().to_string());
+            section_written = false;
+            continue;
+        }
+
+        let Some(item) = parse_list_item(line) else { continue };
+        let Some(done) = item.task else { continue };
+        let Some(task) = parse_task(item.text, done) else { continue };
+        count += 1;
+
+        if !section_written {
+            if let Some(section) = &section {
+                out.push_str(&format!("    section {}\n", escape_text(section)));
+            }
+            section_written = true;
+        }
+
+        let end = task.end.map_or("1d".to_string(), |end| end.format("%Y-%m-%d").to_string());
+        out.push_str(&format!(
+            "    {} :{}t{}, {}, {}\n",
+            task.name,
+            if task.done { "done, " } else { "" },
+            count,
+            task.start.format("%Y-%m-%d"),
+            end
+        ));
+    }
+
+    if count == 0 {
+        return Err("No tasks with dates found to build a gantt chart".to_string());
+    }
+    Ok(out)
+}
+
+fn sequence(body: &str) -> Result<String, String> {
+    let mut participants: Vec<String> = Vec::new();
+    let mut messages = Vec::new();
+
+    for line in code_free_lines(body) {
+        let Some(item) = parse_list_item(line) else { continue };
+        let Some((from, dashed, to, message)) = parse_message(item.text) else { continue };
+
+        for name in [&from, &to] {
+            if !participants.contains(name) {
+                participants.push(name.clone());
+            }
+        }
+        messages.push((from, dashed, to, message));
+    }
+
+    if messages.is_empty() {
+        return Err("No \"A -> B: message\" items found to build a sequence diagram".to_string());
+    }
+
+    let id = |name: &str| participant_id(&participants, name);
+    let mut out = String::from("sequenceDiagram\n");
+    for (i, name) in participants.iter().enumerate() {
+        if is_identifier(name) {
+            out.push_str(&format!("    participant {}\n", name));
+        } else {
+            out.push_str(&format!("    participant P{} as {}\n", i + 1, escape_text(name)));
+        }
+    }
+    for (from, dashed, to, message) in &messages {
+        let arrow = if *dashed { "-->>" } else { "->>" };
+        out.push_str(&format!("    {}{}{}: {}\n", id(from), arrow, id(to), escape_text(message)));
+    }
+    Ok(out)
+}
+
+/// Parses `A -> B: message` (or `A --> B: message`) into its parts
+fn parse_message(text: &str) -> Option<(String, bool, String, String)> {
+    let (arrow, message) = text.split_once(':')?;
+    let (from, to) = arrow.split_once("->")?;
+    let dashed = from.ends_with('-');
+    let from = plain_text(from.trim_end_matches('-')).trim().to_string();
+    let to = plain_text(to.trim_start_matches('>')).trim().to_string();
+
+    if from.is_empty() || to.is_empty() {
+        return None;
+    }
+    Some((from, dashed, to, plain_text(message).trim().to_string()))
+}
+
+fn participant_id(participants: &[String], name: &str) -> String {
+    if is_identifier(name) {
+        return name.to_string();
+    }
+    let index = participants.iter().position(|p| p == name).unwrap_or(0);
+    format!("P{}", index + 1)
+}
+
+fn is_identifier(name: &str) -> bool {
+    name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+        && name.starts_with(|c: char| c.is_ascii_alphabetic())
+}
+
+/// Extracts the dates of a task; the remaining text is its name
+fn parse_task(text: &str, done: bool) -> Option<Task> {
+    let mut dates = Vec::new();
+    let mut words = Vec::new();
+
+    for word in text.split_whitespace() {
+        let candidate = word
+            .trim_start_matches(|c: char| !c.is_ascii_digit())
+            .trim_end_matches(|c: char| !c.is_ascii_digit());
+        match NaiveDate::parse_from_str(candidate, "%Y-%m-%d") {
+            Ok(date) if candidate.len() == 10 => dates.push(date),
+            _ => words.push(word),
+        }
+    }
+
+    let start = *dates.first()?;
+    let end = dates.get(1).copied().filter(|end| *end > start);
+
+    // Drop trailing connectors and date markers left by the dates ("->", "📅")
+    let name = plain_text(&words.join(" "));
+    let name = name
+        .trim_end_matches(|c: char| !c.is_alphanumeric() && c != ')')
+        .replace([':', ';', '#'], " ");
+    let name = name.split_whitespace().collect::<Vec<_>>().join(" ");
+
+    Some(Task {
+        name: if name.is_empty() { "Task".to_string() } else { name },
+        done,
+        start,
+        end,
+    })
+}
+
+/// Parses a bullet or numbered list item
+fn parse_list_item(line: &str) -> Option<ListItem<'_>> {
+    let trimmed = line.trim_start();
+    let indent: usize = line[..line.len() - trimmed.len()]
+        .chars()
+        .map(|c| if c == '\t' { 4 } else { 1 })
+        .sum();
+
+    let rest = if let Some(rest) = trimmed.strip_prefix(['-', '*', '+']) {
+        rest
+    } else {
+        let digits = trimmed.len() - trimmed.trim_start_matches(|c: char| c.is_ascii_digit()).len();
+        if digits == 0 {
+            return None;
+        }
+        trimmed[digits..].strip_prefix(['.', ')'])?
+    };
+    let text = rest.strip_prefix(' ')?.trim();
+
+    let (task, text) = match text.get(..4) {
+        Some("[ ] ") => (Some(false), &text[4..]),
+        Some("[x] ") | Some("[X] ") => (Some(true), &text[4..]),
+        _ => (None, text),
+    };
+
+    if text.is_empty() {
+        return None;
+    }
+    Some(ListItem { indent, task, text })
+}
+
+/// Lines outside fenced code blocks
+fn code_free_lines(body: &str) -> impl Iterator<Item = &str> {
+    let mut in_fence = false;
+    body.lines().filter(move |line| {
+        let trimmed = line.trim_start();
+        if trimmed.starts_with("```") || trimmed.starts_with("~~~") {
+            in_fence = !in_fence;
+            return false;
+        }
+        !in_fence
+    })
+}
+
+/// Reduces links to their text and drops inline markup characters
+fn plain_text(text: &str) -> String {
+    let mut out = String::new();
+    let mut rest = text;
+
+    while let Some(c) = rest.chars().next() {
+        if let Some(inner) = rest.strip_prefix("[[") {
+            if let Some(end) = inner.find("]]") {
+                let link = &inner[..end];
+                out.push_str(link.rsplit('|').next().unwrap_or(link));
+                rest = &inner[end + 2..];
+                continue;
+            }
+        }
+        if c == '[' {
+            if let Some(close) = rest.find("](") {
+                if let Some(end) = rest[close..].find(')') {
+                    out.push_str(&rest[1..close]);
+                    rest = &rest[close + end + 1..];
+                    continue;
+                }
+            }
+        }
+        if !matches!(c, '*' | '`') {
+            out.push(c);
+        }
+        rest = &rest[c.len_utf8()..];
+    }
+
+    out
+}
+
+/// Escapes text for a quoted node label
+fn escape_label(text: &str) -> String {
+    text.replace('"', "#quot;")
+}
+
+/// Escapes text for unquoted positions (messages, titles)
+fn escape_text(text: &str) -> String {
+    text.replace('#', "#35;").replace(';', "#59;")
+}
+
+// ============================================================================
+// TESTS
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn generate(content: &str, kind: MermaidKind) -> Result<String, String> {
+        generate_mermaid(content, Path::new("plan.md"), kind)
+    }
+
+    #[test]
+    fn test_flowchart_from_nested_list() {
+        let note = "# Plan\n- Launch\n  - Build [[api|API]]\n    - Write \"spec\"\n  - Test\n- Retro\n";
+
+        assert_eq!(
+            generate(note, MermaidKind::Flowchart).unwrap(),
+            "flowchart TD\n\
+             \x20   n1[\"Launch\"]\n\
+             \x20   n2[\"Build API\"]\n\
+             \x20   n1 --> n2\n\
+             \x20   n3[\"Write #quot;spec#quot;\"]\n\
+             \x20   n2 --> n3\n\
+             \x20   n4[\"Test\"]\n\
+             \x20   n1 --> n4\n\
+             \x20   n5[\"Retro\"]\n"
+        );
+    }
+
+    #[test]
+    fn test_gantt_from_dated_tasks() {
+        let note = "---\ntitle: Release\n---\n## Build\n\
+                    - [x] Design 2024-03-01 -> 2024-03-05\n\
+                    - [ ] Docs: overview 📅 2024-03-08\n\
+                    - [ ] Undated task\n\
+                    - plain bullet 2024-03-09\n";
+
+        assert_eq!(
+            generate(note, MermaidKind::Gantt).unwrap(),
+            "gantt\n\
+             \x20   title Release\n\
+             \x20   dateFormat YYYY-MM-DD\n\
+             \x20   section Build\n\
+             \x20   Design :done, t1, 2024-03-01, 2024-03-05\n\
+             \x20   Docs overview :t2, 2024-03-08, 1d\n"
+        );
+    }
+
+    #[test]
+    fn test_sequence_from_message_bullets() {
+        let note = "- Client -> API Server: GET /notes\n- API Server --> Client: 200 OK\n- not a message\n";
+
+        assert_eq!(
+            generate(note, MermaidKind::Sequence).unwrap(),
+            "sequenceDiagram\n\
+             \x20   participant Client\n\
+             \x20   participant P2 as API Server\n\
+             \x20   Client->>P2: GET /notes\n\
+             \x20   P2-->>Client: 200 OK\n"
+        );
+    }
+
+    #[test]
+    fn test_ignores_code_and_reports_empty() {
+        let note = "```\n- A -> B: hidden\n```\n";
+        assert!(generate(note, MermaidKind::Sequence).is_err());
+        assert!(generate(note, MermaidKind::Flowchart).is_err());
+        assert!(generate(note, MermaidKind::Gantt).is_err());
+    }
+}
@@ -0,0 +1,602 @@
+//! Workspace Metadata Cache
+//!
+//! Keeps what views over the whole workspace need about each file, so they
+//! do not have to read every note again: title, tags, frontmatter, word
+//! count, link targets, calendar events and tracked metrics of notes, and
+//! the size of other files (assets). The cache is kept per workspace in the
+//! app's cache directory (`<cache>/mdreader/metadata/<hash of the workspace
+//! path>.json`), out of the workspace where sync tools would carry it along:
+//!
+//! ```json
+//! { "notes": { "plan.md": { "modified_ms": 1700000000000, "size": 812, "words": 120, ... } },
+//!   "assets": { "assets/diagram.png": { "modified_ms": 1700000000000, "size": 20480 } } }
+//! ```
+//!
+//! A refresh only stats the workspace files; notes are read again only when
+//! their modification time or size changed.
+//!
+//! When a workspace is opened, `check_sample` compares a sample of the cache
+//! with the disk (times, sizes and, for some notes, content hashes), so a
+//! vault edited while the app was closed is re-indexed up front instead of
+//! serving stale results. Content hashes also catch edits that kept the
+//! modification time (some sync tools restore it); the re-index then hashes
+//! every note.
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use crate::calendar::{self, NoteEvent};
+use crate::file_meta::epoch_ms;
+use crate::frontmatter;
+use crate::links::{LinkIndex, NoteLinks, NoteLookup, ParsedNote};
+use crate::memory_budget::{stamp, CacheEntry, CacheKind};
+use crate::metrics::{self, MetricSample};
+use crate::notes::{collect_workspace_files, inline_tags, is_markdown, note_title, relative_path};
+use crate::people;
+use crate::workspace_settings::SETTINGS_DIR;
+
+/// File name of the cache formerly kept in the settings directory
+pub const METADATA_FILE: &str = "metadata.json";
+
+/// Format version; caches of another version are rebuilt
+pub const METADATA_VERSION: u32 = 5;
+
+/// Cached files checked by `check_sample`
+pub const INTEGRITY_SAMPLE: usize = 64;
+
+/// Sampled notes whose content hash is checked as well
+pub const INTEGRITY_HASH_SAMPLE: usize = 16;
+
+/// Cached metadata of a note
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct CachedNote {
+    /// Modification time (ms since the epoch)
+    pub modified_ms: i64,
+    /// Creation time: the frontmatter `created` field, else the
+    /// filesystem's (where it records one)
+    #[serde(default)]
+    pub created_ms: Option<i64>,
+    /// File size in bytes
+    pub size: u64,
+    pub title: String,
+    /// Frontmatter and inline tags
+    #[serde(default)]
+    pub tags: Vec<String>,
+    /// Frontmatter `status` field
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub status: Option<String>,
+    /// Words in the body (frontmatter excluded)
+    pub words: usize,
+    /// Content hash (blake3, hex; empty in caches written before hashes)
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub hash: String,
+    #[serde(default)]
+    pub links: NoteLinks,
+    /// All frontmatter fields
+    #[serde(default, skip_serializing_if = "Map::is_empty")]
+    pub fields: Map<String, Value>,
+    /// Dated events (see `crate::calendar`)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub events: Vec<NoteEvent>,
+    /// Tracked values (see `crate::metrics`)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub metrics: Vec<MetricSample>,
+}
+
+/// Cached metadata of a non-note file
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct CachedAsset {
+    pub modified_ms: i64,
+    pub size: u64,
+}
+
+/// Metadata of every file of one workspace
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct MetadataCache {
+    #[serde(default)]
+    pub version: u32,
+    /// Notes keyed by relative path
+    #[serde(default)]
+    pub notes: BTreeMap<String, CachedNote>,
+    /// Other files keyed by relative path
+    #[serde(default)]
+    pub assets: BTreeMap<String, CachedAsset>,
+}
+
+impl MetadataCache {
+    /// Loads the cache of a workspace from `cache_dir` (empty if missing or
+    /// unreadable)
+    pub fn load(cache_dir: &Path, workspace_root: &Path) -> Self {
+        let path = cache_path(cache_dir, workspace_root);
+        let cache: Self = match fs::read_to_string(&path) {
+            Ok(json) => serde_json::from_str(&json).unwrap_or_else(|e| {
+                log::warn!("⚠️ Ignoring unreadable metadata cache {:?}: {}", path, e);
+                Self::default()
+            }),
+            Err(_) => Self::default(),
+        };
+        if cache.version == METADATA_VERSION {
+            cache
+        } else {
+            Self::default()
+        }
+    }
+
+    /// Writes the cache of a workspace to `cache_dir`
+    ///
+    /// A copy left in the workspace settings directory by earlier versions
+    /// is removed.
+    pub fn save(&self, cache_dir: &Path, workspace_root: &Path) -> Result<(), String> {
+        fs::create_dir_all(cache_dir)
+            .map_err(|e| format!("Failed to create cache directory: {}", e))?;
+        let json = serde_json::to_string(self)
+            .map_err(|e| format!("Failed to serialize metadata cache: {}", e))?;
+        fs::write(cache_path(cache_dir, workspace_root), json)
+            .map_err(|e| format!("Failed to write metadata cache: {}", e))?;
+
+        let _ = fs::remove_file(workspace_root.join(SETTINGS_DIR).join(METADATA_FILE));
+        Ok(())
+    }
+
+    /// Brings the cache up to date with the workspace files
+    ///
+    /// Returns the number of files that were added, changed or removed.
+    pub fn refresh(&mut self, workspace_root: &Path) -> Result<usize, String> {
+        self.refresh_with(workspace_root, false, &mut |_, _, _| true)
+    }
+
+    /// Refreshes the cache, reporting `(done, total, path)` per file
+    ///
+    /// With `verify_content`, notes whose time and size did not change are
+    /// hashed and read again if their content did. Stops with an error when
+    /// `progress` returns false, keeping the files not visited yet.
+    pub fn refresh_with(
+        &mut self,
+        workspace_root: &Path,
+        verify_content: bool,
+        progress: &mut dyn FnMut(usize, usize, &str) -> bool,
+    ) -> Result<usize, String> {
+        let mut notes = BTreeMap::new();
+        let mut assets = BTreeMap::new();
+        let mut changed = 0;
+
+        let files = collect_workspace_files(workspace_root, workspace_root)?;
+        for (done, path) in files.iter().enumerate() {
+            let key = relative_path(workspace_root, path);
+            if !progress(done, files.len(), &key) {
+                self.notes.append(&mut notes);
+                self.assets.append(&mut assets);
+                return Err("Metadata refresh cancelled".to_string());
+            }
+            let Ok(metadata) = fs::metadata(path) else {
+                continue;
+            };
+            let modified_ms = metadata.modified().map(epoch_ms).unwrap_or(0);
+            let size = metadata.len();
+
+            if is_markdown(path) {
+                let cached = self.notes
+                    .remove(&key)
+                    .filter(|note| note.modified_ms == modified_ms && note.size == size);
+                let cached = match cached {
+                    Some(note) if verify_content => fs::read_to_string(path)
+                        .is_ok_and(|content| content_hash(&content) == note.hash)
+                        .then_some(note),
+                    cached => cached,
+                };
+                let note = match cached {
+                    Some(note) => note,
+                    None => match read_note_metadata(path) {
+                        Ok(mut note) => {
+                            changed += 1;
+                            note.modified_ms = modified_ms;
+                            note.created_ms = note.created_ms.or_else(|| metadata.created().ok().map(epoch_ms));
+                            note.size = size;
+                            note
+                        }
+                        Err(e) => {
+                            log::warn!("⚠️ Skipping note in metadata cache {:?}: {}", path, e);
+                            continue;
+                        }
+                    },
+                };
+                notes.insert(key, note);
+            } else {
+                let asset = CachedAsset { modified_ms, size };
+                if self.assets.remove(&key).as_ref() != Some(&asset) {
+                    changed += 1;
+                }
+                assets.insert(key, asset);
+            }
+        }
+
+        // Whatever is left was deleted
+        changed += self.notes.len() + self.assets.len();
+        self.version = METADATA_VERSION;
+        self.notes = notes;
+        self.assets = assets;
+        Ok(changed)
+    }
+
+    /// Compares a sample of the cached files with the disk
+    ///
+    /// Up to `INTEGRITY_SAMPLE` files spread over the cache are checked;
+    /// `seed` picks which, so repeated checks cover other files.
+    pub fn check_sample(&self, workspace_root: &Path, seed: usize) -> IntegrityReport {
+        let keys: Vec<&String> = self.notes.keys().chain(self.assets.keys()).collect();
+        let step = (keys.len() / INTEGRITY_SAMPLE).max(1);
+        let mut report = IntegrityReport::default();
+        let mut hashed = 0;
+
+        for key in keys.into_iter().skip(seed % step).step_by(step).take(INTEGRITY_SAMPLE) {
+            report.sampled += 1;
+            let path = workspace_root.join(key);
+            let Ok(metadata) = fs::metadata(&path) else {
+                report.missing.push(key.clone());
+                continue;
+            };
+            let modified_ms = metadata.modified().map(epoch_ms).unwrap_or(0);
+            let (cached_ms, cached_size) = match (self.notes.get(key), self.assets.get(key)) {
+                (Some(note), _) => (note.modified_ms, note.size),
+                (_, Some(asset)) => (asset.modified_ms, asset.size),
+                _ => continue,
+            };
+            if (cached_ms, cached_size) != (modified_ms, metadata.len()) {
+                report.stale.push(key.clone());
+                continue;
+            }
+
+            let Some(note) = self.notes.get(key).filter(|note| !note.hash.is_empty()) else {
+                continue;
+            };
+            if hashed < INTEGRITY_HASH_SAMPLE {
+                hashed += 1;
+                if fs::read_to_string(&path).is_ok_and(|content| content_hash(&content) != note.hash) {
+                    report.content_changed.push(key.clone());
+                }
+            }
+        }
+        report
+    }
+}
+
+/// Result of `check_sample`
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct IntegrityReport {
+    /// Cached files checked
+    pub sampled: usize,
+    /// Files whose modification time or size changed since they were cached
+    pub stale: Vec<String>,
+    /// Notes whose content changed although time and size did not
+    pub content_changed: Vec<String>,
+    /// Files deleted since they were cached
+    pub missing: Vec<String>,
+}
+
+impl IntegrityReport {
+    /// Checks if the cache no longer matches the disk
+    pub fn has_drift(&self) -> bool {
+        !self.stale.is_empty() || !self.content_changed.is_empty() || !self.missing.is_empty()
+    }
+}
+
+impl MetadataCache {
+    /// Resolves the links between the cached notes
+    pub fn link_index(&self) -> LinkIndex {
+        LinkIndex::from_parsed(
+            self.notes
+                .iter()
+                .map(|(path, note)| ParsedNote {
+                    path: path.clone(),
+                    title: note.title.clone(),
+                    links: note.links.clone(),
+                    aliases: frontmatter::aliases(&note.fields),
+                    handles: people::person_handles(path, &note.title, &note.fields),
+                })
+                .collect(),
+        )
+    }
+
+    /// Estimated memory held by the cache
+    pub fn approx_bytes(&self) -> usize {
+        let strings = |items: &[String]| items.iter().map(|item| item.len() + 24).sum::<usize>();
+        let notes: usize = self.notes
+            .iter()
+            .map(|(path, note)| {
+                path.len()
+                    + std::mem::size_of::<CachedNote>()
+                    + note.title.len()
+                    + note.hash.len()
+                    + note.status.as_ref().map_or(0, |status| status.len())
+                    + strings(&note.tags)
+                    + strings(&note.links.wiki)
+                    + strings(&note.links.relative)
+                    + strings(&note.links.people)
+                    + serde_json::to_string(&note.fields).map_or(0, |json| json.len() * 2)
+                    + note.events.len() * std::mem::size_of::<NoteEvent>()
+                    + note.metrics.iter().map(|sample| std::mem::size_of::<MetricSample>() + sample.name.len()).sum::<usize>()
+            })
+            .sum();
+        let assets: usize = self.assets.keys().map(|path| path.len() + std::mem::size_of::<CachedAsset>()).sum();
+        notes + assets
+    }
+
+    /// Finds cached notes by path, name or alias
+    pub fn lookup(&self) -> NoteLookup {
+        let mut lookup = NoteLookup::new(self.notes.keys().cloned().collect());
+        for (path, note) in &self.notes {
+            lookup.add_aliases(path, &frontmatter::aliases(&note.fields));
+            lookup.add_person(path, &people::person_handles(path, &note.title, &note.fields));
+        }
+        lookup
+    }
+}
+
+/// Directory metadata caches are kept in
+pub fn cache_dir() -> Result<PathBuf, String> {
+    Ok(dirs::cache_dir()
+        .ok_or("Failed to get cache directory")?
+        .join("mdreader")
+        .join("metadata"))
+}
+
+fn cache_path(cache_dir: &Path, workspace_root: &Path) -> PathBuf {
+    let key = blake3::hash(workspace_root.to_string_lossy().as_bytes()).to_hex();
+    cache_dir.join(format!("{}.json", &key[..32]))
+}
+
+// Helper: Reads the content-derived fields of a note
+fn read_note_metadata(path: &Path) -> Result<CachedNote, String> {
+    let content = fs::read_to_string(path)
+        .map_err(|e| format!("Failed to read note: {}", e))?;
+    Ok(note_metadata(path, &content))
+}
+
+// Helper: Content-derived fields of a note
+fn note_metadata(path: &Path, content: &str) -> CachedNote {
+    let (fields, body) = frontmatter::split(content);
+    let fields = fields.unwrap_or_default();
+
+    let mut tags = frontmatter::tags(&fields);
+    for tag in inline_tags(body) {
+        if !tags.contains(&tag) {
+            tags.push(tag);
+        }
+    }
+
+    CachedNote {
+        hash: content_hash(content),
+        created_ms: frontmatter::date_ms(&fields, "created"),
+        title: note_title(&fields, body, path),
+        tags,
+        status: fields
+            .get("status")
+            .map(|status| frontmatter::value_to_string(status).trim().to_string())
+            .filter(|status| !status.is_empty()),
+        words: body.split_whitespace().count(),
+        links: NoteLinks::from_body(body),
+        events: calendar::note_events(content),
+        metrics: metrics::note_metrics(content),
+        fields,
+        ..CachedNote::default()
+    }
+}
+
+fn content_hash(content: &str) -> String {
+    blake3::hash(content.as_bytes()).to_hex().to_string()
+}
+
+/// In-memory metadata cache of the current workspace (internally synchronized)
+///
+/// The cache is loaded from disk on first use and reloaded when the
+/// workspace changes or after the memory budget evicted it; it is written
+/// back whenever a refresh changed it.
+#[derive(Clone, Default)]
+pub struct MetadataTracker {
+    current: Arc<Mutex<Option<(PathBuf, MetadataCache)>>>,
+    last_used: Arc<AtomicU64>,
+    /// Where caches are persisted (default: `cache_dir()`)
+    cache_dir: Option<PathBuf>,
+}
+
+impl MetadataTracker {
+    /// Creates a tracker with no workspace loaded
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates a tracker that persists caches in `cache_dir`
+    pub fn with_cache_dir(cache_dir: PathBuf) -> Self {
+        Self { cache_dir: Some(cache_dir), ..Self::default() }
+    }
+
+    /// Refreshes the cache of a workspace and passes it to `read`
+    pub fn with_fresh<T>(&self, workspace: &str, read: impl FnOnce(&MetadataCache) -> T) -> Result<T, String> {
+        self.with_loaded(workspace, |cache, root| {
+            let changed = cache.refresh(root)?;
+            self.save_changed(cache, root, changed);
+            Ok(read(cache))
+        })
+    }
+
+    /// Checks a sample of the stored cache of a workspace (see
+    /// `MetadataCache::check_sample`) without refreshing it
+    pub fn check_sample(&self, workspace: &str, seed: usize) -> Result<IntegrityReport, String> {
+        self.with_loaded(workspace, |cache, root| Ok(cache.check_sample(root, seed)))
+    }
+
+    /// Refreshes the cache of a workspace, reporting progress (see
+    /// `MetadataCache::refresh_with`)
+    ///
+    /// Returns the number of files that were added, changed or removed.
+    pub fn reindex(
+        &self,
+        workspace: &str,
+        verify_content: bool,
+        progress: &mut dyn FnMut(usize, usize, &str) -> bool,
+    ) -> Result<usize, String> {
+        self.with_loaded(workspace, |cache, root| {
+            let changed = cache.refresh_with(root, verify_content, progress)?;
+            self.save_changed(cache, root, changed);
+            Ok(changed)
+        })
+    }
+
+    /// The loaded cache as seen by the memory budget (see
+    /// `crate::memory_budget`); a cache in use right now is left out
+    pub fn cache_entries(&self) -> Vec<CacheEntry> {
+        let Ok(current) = self.current.try_lock() else {
+            return Vec::new();
+        };
+        current
+            .iter()
+            .map(|(root, cache)| CacheEntry {
+                cache: CacheKind::Metadata,
+                key: root.to_string_lossy().to_string(),
+                bytes: cache.approx_bytes(),
+                last_used: self.last_used.load(Ordering::SeqCst),
+            })
+            .collect()
+    }
+
+    /// Drops the loaded cache (it is reloaded from disk on next use)
+    ///
+    /// Returns false if no cache was loaded or it is in use.
+    pub fn unload(&self) -> bool {
+        self.current
+            .try_lock()
+            .map(|mut current| current.take().is_some())
+            .unwrap_or(false)
+    }
+
+    // Helper: Runs `f` on the cache of a workspace, loading it if needed
+    fn with_loaded<T>(
+        &self,
+        workspace: &str,
+        f: impl FnOnce(&mut MetadataCache, &Path) -> Result<T, String>,
+    ) -> Result<T, String> {
+        let root = Path::new(workspace)
+            .canonicalize()
+            .map_err(|e| format!("Failed to resolve workspace: {}", e))?;
+        let mut current = self.current
+            .lock()
+            .map_err(|e| format!("Failed to lock metadata cache: {}", e))?;
+
+        if current.as_ref().map_or(true, |(loaded, _)| *loaded != root) {
+            *current = Some((root.clone(), MetadataCache::load(&self.cache_dir()?, &root)));
+        }
+        self.last_used.store(stamp(), Ordering::SeqCst);
+        f(&mut current.as_mut().unwrap().1, &root)
+    }
+
+    // Helper: Writes the cache back after a refresh that changed it
+    fn save_changed(&self, cache: &MetadataCache, root: &Path, changed: usize) {
+        if changed > 0 {
+            log::info!("🗂️ Metadata cache updated: {} files changed", changed);
+            if let Err(e) = self.cache_dir().and_then(|dir| cache.save(&dir, root)) {
+                log::warn!("⚠️ Metadata cache not saved: {}", e);
+            }
+        }
+    }
+
+    // Helper: The directory caches are persisted in
+    fn cache_dir(&self) -> Result<PathBuf, String> {
+        match &self.cache_dir {
+            Some(dir) => Ok(dir.clone()),
+            None => cache_dir(),
+        }
+    }
+}
+
+// ============================================================================
+// TESTS
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_refresh_reads_only_changed_files() {
+        let temp = TempDir::new().unwrap();
+        let root = temp.path();
+        fs::write(root.join("plan.md"), "---\ntags: [work]\n---\n# Plan\nSee [[ideas]] #todo\n").unwrap();
+        fs::write(root.join("ideas.md"), "Some ideas").unwrap();
+        fs::create_dir(root.join("assets")).unwrap();
+        fs::write(root.join("assets").join("logo.png"), [0u8; 64]).unwrap();
+
+        let mut cache = MetadataCache::default();
+        assert_eq!(cache.refresh(root).unwrap(), 3);
+
+        let plan = &cache.notes["plan.md"];
+        assert_eq!(plan.title, "Plan");
+        assert_eq!(plan.tags, vec!["work", "todo"]);
+        assert_eq!(plan.links.wiki, vec!["ideas"]);
+        assert_eq!(cache.assets["assets/logo.png"].size, 64);
+
+        // Unchanged files are kept, deleted ones dropped
+        assert_eq!(cache.refresh(root).unwrap(), 0);
+        fs::remove_file(root.join("ideas.md")).unwrap();
+        assert_eq!(cache.refresh(root).unwrap(), 1);
+        assert!(!cache.notes.contains_key("ideas.md"));
+    }
+
+    #[test]
+    fn test_integrity_check_finds_drift() {
+        let temp = TempDir::new().unwrap();
+        let root = temp.path();
+        fs::write(root.join("a.md"), "# A\nfirst").unwrap();
+        fs::write(root.join("b.md"), "# B").unwrap();
+        fs::write(root.join("c.md"), "# C").unwrap();
+
+        let mut cache = MetadataCache::default();
+        cache.refresh(root).unwrap();
+        let report = cache.check_sample(root, 0);
+        assert_eq!(report.sampled, 3);
+        assert!(!report.has_drift());
+
+        // Edited while closed: one changed size, one kept time and size
+        fs::write(root.join("a.md"), "# A\nfirst and more").unwrap();
+        let c_modified = fs::metadata(root.join("c.md")).unwrap().modified().unwrap();
+        fs::write(root.join("c.md"), "# Z").unwrap();
+        fs::File::options().write(true).open(root.join("c.md")).unwrap().set_modified(c_modified).unwrap();
+        fs::remove_file(root.join("b.md")).unwrap();
+
+        let report = cache.check_sample(root, 0);
+        assert_eq!(report.stale, vec!["a.md"]);
+        assert_eq!(report.missing, vec!["b.md"]);
+        assert_eq!(report.content_changed, vec!["c.md"]);
+
+        // Only a content check sees the edit that kept time and size
+        let mut plain = cache.clone();
+        assert_eq!(plain.refresh(root).unwrap(), 2);
+        assert_eq!(plain.notes["c.md"].title, "C");
+        let mut visited = 0;
+        assert_eq!(cache.refresh_with(root, true, &mut |_, _, _| { visited += 1; true }).unwrap(), 3);
+        assert_eq!((visited, cache.notes["c.md"].title.as_str()), (2, "Z"));
+        assert!(!cache.check_sample(root, 0).has_drift());
+    }
+
+    #[test]
+    fn test_save_and_load_roundtrip() {
+        let temp = TempDir::new().unwrap();
+        fs::write(temp.path().join("a.md"), "# A\none two three").unwrap();
+
+        let cache_dir = TempDir::new().unwrap();
+        let tracker = MetadataTracker::with_cache_dir(cache_dir.path().to_path_buf());
+        let workspace = temp.path().to_string_lossy().to_string();
+        let cache = tracker.with_fresh(&workspace, MetadataCache::clone).unwrap();
+        assert_eq!(cache.notes.len(), 1);
+
+        let root = temp.path().canonicalize().unwrap();
+        assert_eq!(MetadataCache::load(cache_dir.path(), &root), cache);
+        // The cache lives in the cache directory, not the workspace
+        assert!(!root.join(SETTINGS_DIR).join(METADATA_FILE).exists());
+        assert!(cache.assets.is_empty());
+    }
+}
@@ -0,0 +1,229 @@
+//! Frontmatter Schema
+//!
+//! Lets a workspace describe the frontmatter its notes should carry, so
+//! structured vaults (contacts, book notes, projects) keep consistent
+//! metadata. The schema is part of the workspace settings:
+//!
+//! ```json
+//! { "metadata_schema": [
+//!     { "name": "rating", "type": "number", "folders": ["Books"] },
+//!     { "name": "stage", "type": "text", "required": true,
+//!       "allowed": ["lead", "customer"], "folders": ["CRM"] } ] }
+//! ```
+//!
+//! A field applies to every note unless `folders` limits it to notes inside
+//! those workspace-relative folders. Fields not in the schema are allowed.
+
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use crate::frontmatter;
+
+/// Type a frontmatter field must have
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FieldType {
+    /// Any scalar
+    #[default]
+    Text,
+    Number,
+    Boolean,
+    /// A date `frontmatter::parse_date_ms` understands (`YYYY-MM-DD`, RFC 3339, ...)
+    Date,
+    List,
+}
+
+impl FieldType {
+    fn matches(self, value: &Value) -> bool {
+        match self {
+            FieldType::Text => !matches!(value, Value::Array(_) | Value::Object(_)),
+            FieldType::Number => value.is_number(),
+            FieldType::Boolean => value.is_boolean(),
+            FieldType::Date => value.as_str().is_some_and(|text| frontmatter::parse_date_ms(text).is_some()),
+            FieldType::List => value.is_array(),
+        }
+    }
+
+    fn describe(self) -> &'static str {
+        match self {
+            FieldType::Text => "text",
+            FieldType::Number => "a number",
+            FieldType::Boolean => "true or false",
+            FieldType::Date => "a date",
+            FieldType::List => "a list",
+        }
+    }
+}
+
+/// Schema of one frontmatter field
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct FieldSchema {
+    pub name: String,
+    #[serde(rename = "type", default)]
+    pub field_type: FieldType,
+    /// Notes must set the field
+    #[serde(default)]
+    pub required: bool,
+    /// Values the field may take (each item, for lists); empty allows any
+    #[serde(default)]
+    pub allowed: Vec<String>,
+    /// Workspace-relative folders the field applies to; empty means all notes
+    #[serde(default)]
+    pub folders: Vec<String>,
+}
+
+impl FieldSchema {
+    /// Whether the field applies to the note at `relative_path`
+    fn applies_to(&self, relative_path: &str) -> bool {
+        self.folders.is_empty()
+            || self.folders.iter().any(|folder| {
+                let folder = folder.trim_matches('/');
+                folder.is_empty()
+                    || relative_path
+                        .strip_prefix(folder)
+                        .is_some_and(|rest| rest.starts_with('/'))
+            })
+    }
+}
+
+/// What is wrong with a field
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ViolationKind {
+    Missing,
+    WrongType,
+    NotAllowed,
+}
+
+/// A field of a note that does not match the schema
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SchemaViolation {
+    /// Note path relative to the workspace root
+    pub path: String,
+    pub field: String,
+    pub kind: ViolationKind,
+    pub message: String,
+}
+
+/// Checks a schema for empty or duplicate field names
+pub fn validate_schema(schema: &[FieldSchema]) -> Result<(), String> {
+    for (i, field) in schema.iter().enumerate() {
+        let name = field.name.trim();
+        if name.is_empty() || name.contains(['\n', ':']) {
+            return Err(format!("Invalid field name: {:?}", field.name));
+        }
+        if schema[..i].iter().any(|other| other.name.trim() == name) {
+            return Err(format!("Duplicate field in schema: {}", name));
+        }
+    }
+    Ok(())
+}
+
+/// Checks the frontmatter fields of the note at `relative_path`
+///
+/// A field set to null counts as missing.
+pub fn validate_fields(schema: &[FieldSchema], relative_path: &str, fields: &Map<String, Value>) -> Vec<SchemaViolation> {
+    let mut violations = Vec::new();
+    let mut violation = |field: &FieldSchema, kind, message: String| {
+        violations.push(SchemaViolation {
+            path: relative_path.to_string(),
+            field: field.name.clone(),
+            kind,
+            message,
+        });
+    };
+
+    for field in schema.iter().filter(|f| f.applies_to(relative_path)) {
+        let value = match fields.get(field.name.trim()) {
+            Some(Value::Null) | None => {
+                if field.required {
+                    violation(field, ViolationKind::Missing, format!("Missing required field '{}'", field.name));
+                }
+                continue;
+            }
+            Some(value) => value,
+        };
+
+        if !field.field_type.matches(value) {
+            violation(
+                field,
+                ViolationKind::WrongType,
+                format!("Field '{}' should be {}", field.name, field.field_type.describe()),
+            );
+            continue;
+        }
+
+        if field.allowed.is_empty() {
+            continue;
+        }
+        let items = match value {
+            Value::Array(items) => items.iter().collect(),
+            value => vec![value],
+        };
+        for item in items {
+            let item = frontmatter::value_to_string(item);
+            if !field.allowed.iter().any(|allowed| allowed.eq_ignore_ascii_case(item.trim())) {
+                violation(
+                    field,
+                    ViolationKind::NotAllowed,
+                    format!(
+                        "Field '{}' has value '{}', expected one of: {}",
+                        field.name,
+                        item,
+                        field.allowed.join(", ")
+                    ),
+                );
+            }
+        }
+    }
+    violations
+}
+
+// ============================================================================
+// TESTS
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn schema() -> Vec<FieldSchema> {
+        vec![
+            FieldSchema { name: "stage".into(), required: true, allowed: vec!["lead".into(), "customer".into()], folders: vec!["CRM".into()], ..FieldSchema::default() },
+            FieldSchema { name: "rating".into(), field_type: FieldType::Number, ..FieldSchema::default() },
+            FieldSchema { name: "read".into(), field_type: FieldType::Date, ..FieldSchema::default() },
+            FieldSchema { name: "genres".into(), field_type: FieldType::List, allowed: vec!["sf".into(), "poetry".into()], ..FieldSchema::default() },
+        ]
+    }
+
+    fn kinds(path: &str, yaml: &str) -> Vec<(String, ViolationKind)> {
+        let content = format!("---\n{}\n---\n", yaml);
+        let fields = frontmatter::split(&content).0.unwrap();
+        validate_fields(&schema(), path, &fields).into_iter().map(|v| (v.field, v.kind)).collect()
+    }
+
+    #[test]
+    fn test_validate_fields() {
+        assert!(kinds("Books/dune.md", "rating: 5\nread: 2024-03-01\ngenres: [SF]").is_empty());
+        assert_eq!(
+            kinds("Books/dune.md", "rating: five\nread: soon\ngenres: [sf, cooking]"),
+            vec![
+                ("rating".to_string(), ViolationKind::WrongType),
+                ("read".to_string(), ViolationKind::WrongType),
+                ("genres".to_string(), ViolationKind::NotAllowed),
+            ]
+        );
+
+        // Folder-scoped fields only apply inside their folder
+        assert_eq!(kinds("CRM/ann.md", "name: Ann"), vec![("stage".to_string(), ViolationKind::Missing)]);
+        assert_eq!(kinds("CRM/bob.md", "stage: partner"), vec![("stage".to_string(), ViolationKind::NotAllowed)]);
+        assert!(kinds("CRMarchive/old.md", "name: Old").is_empty());
+    }
+
+    #[test]
+    fn test_validate_schema() {
+        assert!(validate_schema(&schema()).is_ok());
+        let mut duplicate = schema();
+        duplicate.push(FieldSchema { name: " rating ".into(), ..FieldSchema::default() });
+        assert_eq!(validate_schema(&duplicate).unwrap_err(), "Duplicate field in schema: rating");
+    }
+}
@@ -0,0 +1,263 @@
+//! Metric Tracking
+//!
+//! Values tracked in notes, so daily notes double as a habit and
+//! quantified-self log:
+//!
+//! ```text
+//! ---
+//! metrics:
+//!   weight: 82.5 kg
+//!   meditated: yes
+//! ---
+//!
+//! - track: sleep 7.5h
+//! - track: ran
+//! ```
+//!
+//! A sample is a name followed by a number and an optional unit; without a
+//! number it records a habit as done (`1`), and `yes`/`no` (or `true`/
+//! `false`, `done`) count as `1`/`0`. Names are matched case-insensitively.
+//! Tracking lines in code are ignored.
+//!
+//! A sample is dated by its note: a file name starting with `YYYY-MM-DD`
+//! (daily notes), else the frontmatter `date` field, else the creation or
+//! modification date. Samples are extracted when notes are indexed (see
+//! `crate::metadata_cache`).
+
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use crate::frontmatter;
+use crate::metadata_cache::{CachedNote, MetadataCache};
+use crate::timeline::TimelineRange;
+
+/// List item prefix of a tracking line (after the bullet)
+const TRACK_PREFIX: &str = "track:";
+
+/// A value tracked in a note
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MetricSample {
+    /// Normalized name (lowercase, single spaces)
+    pub name: String,
+    pub value: f64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub unit: Option<String>,
+    /// Line of the tracking line (1-based; 0 for frontmatter metrics)
+    #[serde(default)]
+    pub line: usize,
+}
+
+/// A sample placed in time
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct MetricPoint {
+    /// Date of the note (`YYYY-MM-DD`)
+    pub date: String,
+    pub value: f64,
+    /// Note the sample was written in
+    pub path: String,
+    pub line: usize,
+}
+
+/// Samples of one metric, oldest first
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct MetricSeries {
+    pub name: String,
+    /// Unit of the most recent sample that has one
+    pub unit: Option<String>,
+    pub points: Vec<MetricPoint>,
+}
+
+/// Extracts the samples of a note: the frontmatter `metrics` field, then
+/// `- track:` lines in the body
+pub fn note_metrics(content: &str) -> Vec<MetricSample> {
+    let (fields, body) = frontmatter::split(content);
+    let mut samples: Vec<MetricSample> = match fields.as_ref().and_then(|f| f.get("metrics")) {
+        Some(Value::String(text)) => text
+            .lines()
+            .filter_map(|line| line.split_once(':'))
+            .filter_map(|(name, value)| sample(name, value.trim()))
+            .collect(),
+        Some(Value::Array(items)) => items
+            .iter()
+            .map(frontmatter::value_to_string)
+            .filter_map(|item| parse_sample(&item))
+            .collect(),
+        _ => Vec::new(),
+    };
+
+    let first_line = content[..content.len() - body.len()].lines().count() + 1;
+    let mut in_code = false;
+    for (i, line) in body.lines().enumerate() {
+        let trimmed = line.trim_start();
+        if trimmed.starts_with("```") || trimmed.starts_with("~~~") {
+            in_code = !in_code;
+            continue;
+        }
+        if in_code {
+            continue;
+        }
+        let Some(rest) = trimmed
+            .strip_prefix(['-', '*', '+'])
+            .and_then(|rest| rest.trim_start().strip_prefix(TRACK_PREFIX))
+        else {
+            continue;
+        };
+        if let Some(mut sample) = parse_sample(rest) {
+            sample.line = first_line + i;
+            samples.push(sample);
+        }
+    }
+    samples
+}
+
+/// Samples of the metric `name` dated within `range`, oldest first
+pub fn metric_series(cache: &MetadataCache, name: &str, range: &TimelineRange) -> Result<MetricSeries, String> {
+    let (from, to) = range.bounds()?;
+    let name = normalize_name(name);
+    if name.is_empty() {
+        return Err("Metric name is empty".to_string());
+    }
+
+    let mut samples: Vec<(NaiveDate, &MetricSample, &str)> = cache
+        .notes
+        .iter()
+        .filter_map(|(path, note)| Some((note_date(path, note)?, path, note)))
+        .filter(|(date, _, _)| !(from.is_some_and(|from| *date < from) || to.is_some_and(|to| *date > to)))
+        .flat_map(|(date, path, note)| {
+            let name = &name;
+            note.metrics.iter().filter(move |s| &s.name == name).map(move |s| (date, s, path.as_str()))
+        })
+        .collect();
+    samples.sort_by(|a, b| a.0.cmp(&b.0).then_with(|| a.2.cmp(b.2)).then_with(|| a.1.line.cmp(&b.1.line)));
+
+    let unit = samples.iter().rev().find_map(|(_, sample, _)| sample.unit.clone());
+    let points = samples
+        .into_iter()
+        .map(|(date, sample, path)| MetricPoint {
+            date: date.format("%Y-%m-%d").to_string(),
+            value: sample.value,
+            path: path.to_string(),
+            line: sample.line,
+        })
+        .collect();
+    Ok(MetricSeries { name, unit, points })
+}
+
+/// Date of a note's samples: a `YYYY-MM-DD` file name prefix, the `date`
+/// field, or the creation or modification date
+pub fn note_date(path: &str, note: &CachedNote) -> Option<NaiveDate> {
+    let name = path.rsplit('/').next().unwrap_or(path);
+    if let Some(date) = name.get(..10).and_then(|prefix| NaiveDate::parse_from_str(prefix, "%Y-%m-%d").ok()) {
+        return Some(date);
+    }
+    let ms = frontmatter::date_ms(&note.fields, "date").or(note.created_ms).unwrap_or(note.modified_ms);
+    DateTime::<Utc>::from_timestamp_millis(ms).map(|t| t.date_naive())
+}
+
+// Helper: Parses `name value[unit]` (or `name: value`); a name alone is a done habit
+fn parse_sample(text: &str) -> Option<MetricSample> {
+    if let Some((name, value)) = text.split_once(':') {
+        return sample(name, value.trim());
+    }
+    let words: Vec<&str> = text.split_whitespace().collect();
+    // The value is the first word starting with a number, after the name
+    match (1..words.len()).find(|&i| leading_number(words[i]).is_some()) {
+        Some(i) => sample(&words[..i].join(" "), &words[i..].join(" ")),
+        None => sample(text, ""),
+    }
+}
+
+// Helper: A sample from a name and a value text (`82.5 kg`, `7h`, `yes`, or empty)
+fn sample(name: &str, value: &str) -> Option<MetricSample> {
+    let name = normalize_name(name);
+    if name.is_empty() || leading_number(&name).is_some() {
+        return None;
+    }
+    let (value, unit) = match value.to_lowercase().as_str() {
+        "" | "yes" | "true" | "done" | "x" => (1.0, None),
+        "no" | "false" => (0.0, None),
+        _ => {
+            let (number, len) = leading_number(value)?;
+            let unit = value[len..].trim();
+            (number, (!unit.is_empty()).then(|| unit.to_string()))
+        }
+    };
+    Some(MetricSample { name, value, unit, line: 0 })
+}
+
+// Helper: The number at the start of `text` and its length in bytes
+fn leading_number(text: &str) -> Option<(f64, usize)> {
+    let digits = text.strip_prefix(['-', '+']).unwrap_or(text);
+    let sign = text.len() - digits.len();
+    let len = digits.find(|c: char| !(c.is_ascii_digit() || c == '.')).unwrap_or(digits.len());
+    if !digits.starts_with(|c: char| c.is_ascii_digit()) {
+        return None;
+    }
+    let number: f64 = text[..sign + len].parse().ok()?;
+    number.is_finite().then_some((number, sign + len))
+}
+
+fn normalize_name(name: &str) -> String {
+    name.split_whitespace().collect::<Vec<_>>().join(" ").to_lowercase()
+}
+
+// ============================================================================
+// TESTS
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_note_metrics() {
+        let content = "---\nmetrics:\n  Weight: 82.5 kg\n  meditated: yes\n---\n\
+            # 2024-06-01\n- track: sleep 7.5h\n* track: Blood  Pressure 120 mmHg\n- track: ran\n\
+            - track: mood: 4\n```\n- track: weight 1\n```\ntrack: not a list item 3\n";
+        let samples = note_metrics(content);
+        let summary: Vec<(&str, f64, Option<&str>, usize)> =
+            samples.iter().map(|s| (s.name.as_str(), s.value, s.unit.as_deref(), s.line)).collect();
+        assert_eq!(
+            summary,
+            [
+                ("weight", 82.5, Some("kg"), 0),
+                ("meditated", 1.0, None, 0),
+                ("sleep", 7.5, Some("h"), 7),
+                ("blood pressure", 120.0, Some("mmHg"), 8),
+                ("ran", 1.0, None, 9),
+                ("mood", 4.0, None, 10),
+            ]
+        );
+        assert!(note_metrics("- track: 5").is_empty());
+        assert_eq!(note_metrics("- track: temp -2.5C")[0].value, -2.5);
+    }
+
+    #[test]
+    fn test_metric_series() {
+        let mut cache = MetadataCache::default();
+        let note = |content: &str, created_ms: Option<i64>| CachedNote {
+            metrics: note_metrics(content),
+            fields: frontmatter::split(content).0.unwrap_or_default(),
+            created_ms,
+            ..CachedNote::default()
+        };
+        cache.notes.insert("daily/2024-06-02.md".into(), note("- track: weight 82", None));
+        cache.notes.insert("daily/2024-06-01 Sat.md".into(), note("- track: weight 83.1 kg\n- track: Weight 83", None));
+        cache.notes.insert("log.md".into(), note("---\ndate: 2024-05-20\n---\n- track: weight 85", None));
+        // 2024-07-01 00:00 UTC
+        cache.notes.insert("later.md".into(), note("- track: weight 80", Some(1_719_792_000_000)));
+        cache.notes.insert("other.md".into(), note("- track: sleep 8", None));
+
+        let range = TimelineRange { from: Some("2024-05-25".into()), to: Some("2024-06-30".into()) };
+        let series = metric_series(&cache, "WEIGHT", &range).unwrap();
+        assert_eq!(series.name, "weight");
+        assert_eq!(series.unit.as_deref(), Some("kg"));
+        let points: Vec<(&str, f64)> = series.points.iter().map(|p| (p.date.as_str(), p.value)).collect();
+        assert_eq!(points, [("2024-06-01", 83.1), ("2024-06-01", 83.0), ("2024-06-02", 82.0)]);
+
+        let all = metric_series(&cache, "weight", &TimelineRange::default()).unwrap();
+        assert_eq!(all.points.first().map(|p| p.path.as_str()), Some("log.md"));
+        assert_eq!(all.points.last().map(|p| p.date.as_str()), Some("2024-07-01"));
+        assert!(metric_series(&cache, " ", &TimelineRange::default()).is_err());
+    }
+}
@@ -0,0 +1,248 @@
+//! Mindmap Sidecar Metadata
+//!
+//! Mindmap-only attributes (node colors, collapsed state, icons, manual
+//! positions) are kept out of the markdown in a sidecar file per note:
+//!
+//! ```text
+//! workspace/
+//! ├── Projects/plan.md
+//! └── .mindmap/
+//!     └── Projects/plan.md.json
+//! ```
+//!
+//! The sidecar tree mirrors the workspace, so renaming, moving or deleting a
+//! note (or a whole folder) applies the same operation to its sidecars via
+//! `move_sidecar` / `remove_sidecar`.
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use serde::{Deserialize, Serialize};
+use crate::workspace_settings::relative_key;
+
+/// Name of the hidden sidecar directory at the workspace root
+pub const MINDMAP_DIR: &str = ".mindmap";
+
+/// Position of a node in a manual layout
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct NodePosition {
+    pub x: f64,
+    pub y: f64,
+}
+
+/// Display attributes of a single mindmap node
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct NodeMeta {
+    #[serde(default)]
+    pub color: Option<String>,
+    #[serde(default)]
+    pub collapsed: bool,
+    #[serde(default)]
+    pub icon: Option<String>,
+    #[serde(default)]
+    pub position: Option<NodePosition>,
+}
+
+/// Mindmap metadata of one note
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct MindmapMeta {
+    /// Layout algorithm chosen by the user (e.g. `"radial"`, `"manual"`)
+    #[serde(default)]
+    pub layout: Option<String>,
+    /// Node attributes keyed by node id
+    #[serde(default)]
+    pub nodes: BTreeMap<String, NodeMeta>,
+}
+
+impl MindmapMeta {
+    /// Returns true if there is nothing worth storing
+    pub fn is_empty(&self) -> bool {
+        self.layout.is_none() && self.nodes.values().all(|n| *n == NodeMeta::default())
+    }
+}
+
+/// Gets the sidecar file of a note (`None` if the note is outside the workspace)
+pub fn sidecar_path(workspace_root: &Path, note: &Path) -> Option<PathBuf> {
+    let key = relative_key(workspace_root, note).filter(|k| !k.is_empty())?;
+    Some(sidecar_root(workspace_root).join(format!("{}.json", key)))
+}
+
+/// Gets the sidecar folder mirroring a workspace folder
+fn sidecar_dir(workspace_root: &Path, dir: &Path) -> Option<PathBuf> {
+    let key = relative_key(workspace_root, dir).filter(|k| !k.is_empty())?;
+    Some(sidecar_root(workspace_root).join(key))
+}
+
+fn sidecar_root(workspace_root: &Path) -> PathBuf {
+    workspace_root
+        .canonicalize()
+        .unwrap_or_else(|_| workspace_root.to_path_buf())
+        .join(MINDMAP_DIR)
+}
+
+/// Loads a note's metadata (empty if it has no sidecar)
+pub fn load(workspace_root: &Path, note: &Path) -> Result<MindmapMeta, String> {
+    let path = sidecar_path(workspace_root, note).ok_or("Note is outside the workspace")?;
+
+    if !path.exists() {
+        return Ok(MindmapMeta::default());
+    }
+
+    let content = fs::read_to_string(&path)
+        .map_err(|e| format!("Failed to read mindmap metadata: {}", e))?;
+    serde_json::from_str(&content)
+        .map_err(|e| format!("Failed to parse mindmap metadata: {}", e))
+}
+
+/// Saves a note's metadata; empty metadata removes the sidecar
+pub fn save(workspace_root: &Path, note: &Path, meta: &MindmapMeta) -> Result<(), String> {
+    let path = sidecar_path(workspace_root, note).ok_or("Note is outside the workspace")?;
+
+    if meta.is_empty() {
+        return remove_file_if_exists(&path);
+    }
+
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)
+            .map_err(|e| format!("Failed to create mindmap directory: {}", e))?;
+    }
+
+    let json = serde_json::to_string_pretty(meta)
+        .map_err(|e| format!("Failed to serialize mindmap metadata: {}", e))?;
+
+    // Write to a temp file first so a crash never leaves a truncated sidecar
+    let tmp = path.with_extension("json.tmp");
+    fs::write(&tmp, json).map_err(|e| format!("Failed to write mindmap metadata: {}", e))?;
+    fs::rename(&tmp, &path).map_err(|e| format!("Failed to write mindmap metadata: {}", e))
+}
+
+/// Moves the sidecar(s) of a renamed/moved note or folder
+///
+/// Call after the note itself was moved. Missing sidecars are not an error.
+pub fn move_sidecar(workspace_root: &Path, from: &Path, to: &Path) -> Result<(), String> {
+    let pairs = [
+        (sidecar_path(workspace_root, from), sidecar_path(workspace_root, to)),
+        (sidecar_dir(workspace_root, from), sidecar_dir(workspace_root, to)),
+    ];
+
+    for (old, new) in pairs {
+        let (Some(old), Some(new)) = (old, new) else { continue };
+        if !old.exists() {
+            continue;
+        }
+        if let Some(parent) = new.parent() {
+            fs::create_dir_all(parent)
+                .map_err(|e| format!("Failed to create mindmap directory: {}", e))?;
+        }
+        fs::rename(&old, &new)
+            .map_err(|e| format!("Failed to move mindmap metadata: {}", e))?;
+    }
+
+    Ok(())
+}
+
+/// Removes the sidecar(s) of a deleted note or folder
+pub fn remove_sidecar(workspace_root: &Path, path: &Path) -> Result<(), String> {
+    if let Some(file) = sidecar_path(workspace_root, path) {
+        remove_file_if_exists(&file)?;
+    }
+    if let Some(dir) = sidecar_dir(workspace_root, path) {
+        if dir.is_dir() {
+            fs::remove_dir_all(&dir)
+                .map_err(|e| format!("Failed to remove mindmap metadata: {}", e))?;
+        }
+    }
+    Ok(())
+}
+
+/// Moves sidecars after a rename, logging instead of failing the rename
+pub fn follow_move(workspace: &str, from: &Path, to: &Path) {
+    if let Err(e) = move_sidecar(Path::new(workspace), from, to) {
+        log::warn!("⚠️ Mindmap metadata not moved for {:?}: {}", from, e);
+    }
+}
+
+/// Removes sidecars after a delete, logging instead of failing the delete
+pub fn follow_delete(workspace: &str, path: &Path) {
+    if let Err(e) = remove_sidecar(Path::new(workspace), path) {
+        log::warn!("⚠️ Mindmap metadata not removed for {:?}: {}", path, e);
+    }
+}
+
+fn remove_file_if_exists(path: &Path) -> Result<(), String> {
+    match fs::remove_file(path) {
+        Ok(()) => Ok(()),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(()),
+        Err(e) => Err(format!("Failed to remove mindmap metadata: {}", e)),
+    }
+}
+
+// ============================================================================
+// TESTS
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn sample_meta() -> MindmapMeta {
+        let mut meta = MindmapMeta::default();
+        meta.nodes.insert("h-1".to_string(), NodeMeta {
+            color: Some("#ff0000".to_string()),
+            collapsed: true,
+            icon: None,
+            position: Some(NodePosition { x: 10.0, y: -4.5 }),
+        });
+        meta
+    }
+
+    fn setup() -> (TempDir, PathBuf) {
+        let workspace = TempDir::new().unwrap();
+        let root = workspace.path().canonicalize().unwrap();
+        fs::create_dir_all(root.join("Projects")).unwrap();
+        fs::write(root.join("Projects/plan.md"), "# Plan").unwrap();
+        (workspace, root)
+    }
+
+    #[test]
+    fn test_save_and_load() {
+        let (_workspace, root) = setup();
+        let note = root.join("Projects/plan.md");
+
+        assert_eq!(load(&root, &note).unwrap(), MindmapMeta::default());
+
+        save(&root, &note, &sample_meta()).unwrap();
+        assert!(root.join(".mindmap/Projects/plan.md.json").exists());
+        assert_eq!(load(&root, &note).unwrap(), sample_meta());
+    }
+
+    #[test]
+    fn test_empty_meta_removes_sidecar() {
+        let (_workspace, root) = setup();
+        let note = root.join("Projects/plan.md");
+
+        save(&root, &note, &sample_meta()).unwrap();
+        save(&root, &note, &MindmapMeta::default()).unwrap();
+        assert!(!sidecar_path(&root, &note).unwrap().exists());
+    }
+
+    #[test]
+    fn test_sidecar_follows_file_and_folder_moves() {
+        let (_workspace, root) = setup();
+        save(&root, &root.join("Projects/plan.md"), &sample_meta()).unwrap();
+
+        // File rename
+        fs::rename(root.join("Projects/plan.md"), root.join("Projects/roadmap.md")).unwrap();
+        move_sidecar(&root, &root.join("Projects/plan.md"), &root.join("Projects/roadmap.md")).unwrap();
+        assert_eq!(load(&root, &root.join("Projects/roadmap.md")).unwrap(), sample_meta());
+
+        // Folder rename
+        fs::rename(root.join("Projects"), root.join("Archive")).unwrap();
+        move_sidecar(&root, &root.join("Projects"), &root.join("Archive")).unwrap();
+        assert_eq!(load(&root, &root.join("Archive/roadmap.md")).unwrap(), sample_meta());
+
+        remove_sidecar(&root, &root.join("Archive")).unwrap();
+        assert!(!root.join(".mindmap/Archive").exists());
+    }
+}
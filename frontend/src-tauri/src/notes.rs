@@ -0,0 +1,735 @@
+//! Workspace Note Scanning and Queries
+//!
+//! Helpers for reading every note of a workspace as structured data:
+//! frontmatter fields, tags, title and simple text statistics. Commands that
+//! need a view over many notes (dataset export, dashboards) build on
+//! `collect_markdown_files` and `read_note`.
+//!
+//! ## Queries
+//! A `NoteQuery` selects notes by folder, tags and frontmatter field values
+//! and picks the columns to return. Results can be rendered as CSV or JSON.
+
+use std::collections::{BTreeMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use crate::csv_table::write_csv_row;
+use crate::frontmatter;
+use crate::ignore::IgnoreRules;
+use crate::utils::walk_metadata;
+use crate::workspace_settings::WorkspaceSettings;
+
+/// Recursively collects `.md` files below `dir`, skipping hidden and
+/// ignored entries
+///
+/// Symbolic links are followed according to the workspace symlink policy.
+pub fn collect_markdown_files(workspace_root: &Path, dir: &Path) -> Result<Vec<PathBuf>, String> {
+    let mut files = collect_workspace_files(workspace_root, dir)?;
+    files.retain(|path| is_markdown(path));
+    Ok(files)
+}
+
+/// Recursively collects all files below `dir`, skipping hidden entries and
+/// those matched by ignore files (see `crate::ignore`)
+///
+/// Symbolic links are followed according to the workspace symlink policy
+/// (see `utils::walk_metadata`).
+pub fn collect_workspace_files(workspace_root: &Path, dir: &Path) -> Result<Vec<PathBuf>, String> {
+    let policy = WorkspaceSettings::load_symlink_policy(workspace_root);
+    let mut ignore = IgnoreRules::load(workspace_root);
+    let canonical_root = workspace_root
+        .canonicalize()
+        .map_err(|e| format!("Failed to resolve workspace: {}", e))?;
+
+    let mut visited: HashSet<PathBuf> = dir.canonicalize().into_iter().collect();
+    let mut files = Vec::new();
+    let mut stack = vec![dir.to_path_buf()];
+
+    while let Some(current) = stack.pop() {
+        let entries = fs::read_dir(&current)
+            .map_err(|e| format!("Failed to read directory: {}", e))?;
+
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if entry.file_name().to_string_lossy().starts_with('.') {
+                continue;
+            }
+
+            match walk_metadata(&path, &canonical_root, policy, &mut visited) {
+                Some(meta) if ignore.is_ignored(&path, meta.is_dir()) => {}
+                Some(meta) if meta.is_dir() => stack.push(path),
+                Some(_) => files.push(path),
+                None => {}
+            }
+        }
+    }
+
+    files.sort();
+    Ok(files)
+}
+
+/// Checks if a path has the `.md` extension
+pub fn is_markdown(path: &Path) -> bool {
+    path.extension().is_some_and(|e| e.eq_ignore_ascii_case("md"))
+}
+
+/// Simple text statistics of a note body
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct NoteStats {
+    pub word_count: usize,
+    pub char_count: usize,
+    pub line_count: usize,
+    pub link_count: usize,
+}
+
+impl NoteStats {
+    /// Computes statistics for a note body (frontmatter excluded)
+    pub fn from_body(body: &str) -> Self {
+        Self {
+            word_count: body.split_whitespace().count(),
+            char_count: body.chars().count(),
+            line_count: body.lines().count(),
+            link_count: body.matches("](").count() + body.matches("[[").count(),
+        }
+    }
+}
+
+/// A note read as structured data
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NoteRecord {
+    /// Path relative to the workspace root, with `/` separators
+    pub path: String,
+    pub title: String,
+    pub tags: Vec<String>,
+    /// Frontmatter fields
+    pub fields: Map<String, Value>,
+    pub stats: NoteStats,
+    pub modified: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+/// Reads a note file into a `NoteRecord`
+pub fn read_note(workspace_root: &Path, path: &Path) -> Result<NoteRecord, String> {
+    let content = fs::read_to_string(path)
+        .map_err(|e| format!("Failed to read note {}: {}", path.display(), e))?;
+    let modified = fs::metadata(path)
+        .and_then(|m| m.modified())
+        .ok()
+        .map(chrono::DateTime::<chrono::Utc>::from);
+
+    let (fields, body) = frontmatter::split(&content);
+    let fields = fields.unwrap_or_default();
+
+    let mut tags = frontmatter::tags(&fields);
+    for tag in inline_tags(body) {
+        if !tags.contains(&tag) {
+            tags.push(tag);
+        }
+    }
+
+    Ok(NoteRecord {
+        path: relative_path(workspace_root, path),
+        title: note_title(&fields, body, path),
+        tags,
+        stats: NoteStats::from_body(body),
+        fields,
+        modified,
+    })
+}
+
+/// Title from the `title` field, the first `# ` heading, or the file name
+pub fn note_title(fields: &Map<String, Value>, body: &str, path: &Path) -> String {
+    if let Some(title) = fields.get("title").map(frontmatter::value_to_string) {
+        if !title.trim().is_empty() {
+            return title.trim().to_string();
+        }
+    }
+
+    body.lines()
+        .find_map(|line| line.strip_prefix("# "))
+        .map(|h| h.trim().to_string())
+        .unwrap_or_else(|| {
+            path.file_stem()
+                .map(|s| s.to_string_lossy().to_string())
+                .unwrap_or_default()
+        })
+}
+
+/// Extracts `#tag` occurrences from a note body, outside code
+pub fn inline_tags(body: &str) -> Vec<String> {
+    let mut tags: Vec<String> = Vec::new();
+    let mut in_fence = false;
+
+    for line in body.lines() {
+        if line.trim_start().starts_with("```") {
+            in_fence = !in_fence;
+            continue;
+        }
+        if in_fence {
+            continue;
+        }
+
+        let mut prev = ' ';
+        let mut in_code = false;
+        let chars: Vec<char> = line.chars().collect();
+        let mut i = 0;
+        while i < chars.len() {
+            let c = chars[i];
+            if c == '`' {
+                in_code = !in_code;
+            } else if c == '#' && !in_code && prev.is_whitespace() {
+                let tag: String = chars[i + 1..]
+                    .iter()
+                    .take_while(|c| c.is_alphanumeric() || matches!(c, '-' | '_' | '/'))
+                    .collect();
+                // Pure numbers (`#1`) are issue references, not tags
+                if !tag.is_empty() && !tag.chars().all(|c| c.is_ascii_digit()) && !tags.contains(&tag) {
+                    tags.push(tag.clone());
+                }
+                i += tag.chars().count();
+            }
+            prev = chars[i];
+            i += 1;
+        }
+    }
+
+    tags
+}
+
+/// Path relative to the workspace root, with `/` separators
+pub fn relative_path(workspace_root: &Path, path: &Path) -> String {
+    path.strip_prefix(workspace_root)
+        .unwrap_or(path)
+        .components()
+        .map(|c| c.as_os_str().to_string_lossy().to_string())
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+// ============================================================================
+// PREVIEWS
+// ============================================================================
+
+/// Bytes read from the start of a note to build its preview
+pub const PREVIEW_READ_BYTES: u64 = 8 * 1024;
+
+/// Characters of body text in a preview
+pub const PREVIEW_CHARS: usize = 200;
+
+/// Card data for a note, built from the head of the file only
+///
+/// Tags and task counts cover the part of the note that was read, which is
+/// the whole note for anything up to `PREVIEW_READ_BYTES`.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct NotePreview {
+    pub title: String,
+    /// Plain text excerpt of the body with markdown syntax removed
+    pub preview: String,
+    pub tags: Vec<String>,
+    pub tasks_total: usize,
+    pub tasks_done: usize,
+}
+
+/// Reads the preview of a note without loading the whole file
+pub fn read_preview(path: &Path) -> Result<NotePreview, String> {
+    use std::io::Read;
+
+    let mut head = Vec::new();
+    fs::File::open(path)
+        .and_then(|file| file.take(PREVIEW_READ_BYTES).read_to_end(&mut head))
+        .map_err(|e| format!("Failed to read note {}: {}", path.display(), e))?;
+
+    // The cut may fall inside a multi-byte character
+    let text = match String::from_utf8(head) {
+        Ok(text) => text,
+        Err(e) => {
+            let valid = e.utf8_error().valid_up_to();
+            let mut bytes = e.into_bytes();
+            bytes.truncate(valid);
+            String::from_utf8(bytes).unwrap_or_default()
+        }
+    };
+
+    Ok(note_preview(&text, path))
+}
+
+/// Builds the preview of (the head of) a note
+pub fn note_preview(content: &str, path: &Path) -> NotePreview {
+    let (fields, body) = frontmatter::split(content);
+    let fields = fields.unwrap_or_default();
+
+    let mut tags = frontmatter::tags(&fields);
+    for tag in inline_tags(body) {
+        if !tags.contains(&tag) {
+            tags.push(tag);
+        }
+    }
+
+    let (tasks_total, tasks_done) = count_tasks(body);
+
+    NotePreview {
+        title: note_title(&fields, body, path),
+        preview: preview_text(body, PREVIEW_CHARS),
+        tags,
+        tasks_total,
+        tasks_done,
+    }
+}
+
+/// Counts `- [ ]` / `- [x]` task list items outside code
+fn count_tasks(body: &str) -> (usize, usize) {
+    let mut total = 0;
+    let mut done = 0;
+    let mut in_fence = false;
+
+    for line in body.lines() {
+        let trimmed = line.trim_start();
+        if trimmed.starts_with("```") || trimmed.starts_with("~~~") {
+            in_fence = !in_fence;
+            continue;
+        }
+        if in_fence {
+            continue;
+        }
+        let Some(item) = ["- ", "* ", "+ "].iter().find_map(|m| trimmed.strip_prefix(m)) else {
+            continue;
+        };
+        if item.starts_with("[ ]") {
+            total += 1;
+        } else if item.starts_with("[x]") || item.starts_with("[X]") {
+            total += 1;
+            done += 1;
+        }
+    }
+
+    (total, done)
+}
+
+/// Plain text of a body, skipping headings and code, cut at a word boundary
+fn preview_text(body: &str, max_chars: usize) -> String {
+    let mut words: Vec<String> = Vec::new();
+    let mut in_fence = false;
+
+    for line in body.lines() {
+        let trimmed = line.trim();
+        if trimmed.starts_with("```") || trimmed.starts_with("~~~") {
+            in_fence = !in_fence;
+            continue;
+        }
+        if in_fence || trimmed.starts_with('#') || trimmed.chars().all(|c| matches!(c, '-' | '*' | '_' | '|' | ':' | ' ')) {
+            continue;
+        }
+
+        let text = strip_line_markers(trimmed);
+        words.extend(strip_inline_markdown(text).split_whitespace().map(String::from));
+    }
+
+    let mut preview = String::new();
+    for word in words {
+        let needed = if preview.is_empty() { 0 } else { 1 } + word.chars().count();
+        if preview.chars().count() + needed > max_chars {
+            preview.push('…');
+            break;
+        }
+        if !preview.is_empty() {
+            preview.push(' ');
+        }
+        preview.push_str(&word);
+    }
+    preview
+}
+
+/// Removes block markers: quotes, list bullets, numbering and task boxes
+fn strip_line_markers(line: &str) -> &str {
+    let mut text = line.trim_start_matches('>').trim_start();
+    for marker in ["- ", "* ", "+ "] {
+        if let Some(rest) = text.strip_prefix(marker) {
+            text = rest;
+            break;
+        }
+    }
+    if let Some((number, rest)) = text.split_once(". ") {
+        if !number.is_empty() && number.chars().all(|c| c.is_ascii_digit()) {
+            text = rest;
+        }
+    }
+    for task in ["[ ] ", "[x] ", "[X] "] {
+        if let Some(rest) = text.strip_prefix(task) {
+            return rest;
+        }
+    }
+    text
+}
+
+/// Keeps the visible text of links, wiki links and emphasis
+fn strip_inline_markdown(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut rest = text;
+
+    while let Some(c) = rest.chars().next() {
+        if let Some(inner) = rest.strip_prefix("[[") {
+            if let Some(end) = inner.find("]]") {
+                let target = &inner[..end];
+                out.push_str(target.rsplit('|').next().unwrap_or(target));
+                rest = &inner[end + 2..];
+                continue;
+            }
+        }
+        if c == '[' || rest.starts_with("![") {
+            let start = if c == '!' { 2 } else { 1 };
+            if let Some(close) = rest[start..].find("](") {
+                let label = &rest[start..start + close];
+                if let Some(end) = rest[start + close + 2..].find(')') {
+                    out.push_str(label);
+                    rest = &rest[start + close + 2 + end + 1..];
+                    continue;
+                }
+            }
+        }
+        if !matches!(c, '*' | '_' | '`' | '~' | '=') {
+            out.push(c);
+        }
+        rest = &rest[c.len_utf8()..];
+    }
+
+    out
+}
+
+// ============================================================================
+// QUERIES
+// ============================================================================
+
+/// Selection and columns for a note dataset
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct NoteQuery {
+    /// Only notes below this folder (relative to the workspace)
+    #[serde(default)]
+    pub folder: Option<String>,
+    /// Notes must carry all of these tags (case-insensitive)
+    #[serde(default)]
+    pub tags: Vec<String>,
+    /// Frontmatter field values that must match (case-insensitive; for
+    /// lists, one item must match). `"*"` only requires the field to be set.
+    #[serde(default)]
+    pub filters: BTreeMap<String, String>,
+    /// Frontmatter fields to output; empty means every field found
+    #[serde(default)]
+    pub fields: Vec<String>,
+    /// Add word/char/line/link counts and modification time
+    #[serde(default)]
+    pub include_stats: bool,
+    /// Field to sort by (`path`, `title`, `modified` or a frontmatter field)
+    #[serde(default)]
+    pub sort_by: Option<String>,
+    #[serde(default)]
+    pub descending: bool,
+}
+
+/// Output format of a note dataset
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DatasetFormat {
+    Csv,
+    Json,
+}
+
+impl NoteQuery {
+    /// Checks if a note satisfies the tag and field filters
+    pub fn matches(&self, note: &NoteRecord) -> bool {
+        let has_tags = self.tags.iter().all(|wanted| {
+            let wanted = wanted.trim_start_matches('#');
+            note.tags.iter().any(|t| t.eq_ignore_ascii_case(wanted))
+        });
+
+        has_tags && self.filters.iter().all(|(field, expected)| {
+            match note.fields.get(field) {
+                None | Some(Value::Null) => false,
+                Some(_) if expected == "*" => true,
+                Some(Value::Array(items)) => items
+                    .iter()
+                    .any(|item| frontmatter::value_to_string(item).eq_ignore_ascii_case(expected)),
+                Some(value) => frontmatter::value_to_string(value).eq_ignore_ascii_case(expected),
+            }
+        })
+    }
+
+    /// Runs the query over the notes of a workspace
+    pub fn run(&self, workspace_root: &Path) -> Result<Vec<NoteRecord>, String> {
+        let dir = match &self.folder {
+            Some(folder) if !folder.trim().is_empty() => workspace_root.join(folder),
+            _ => workspace_root.to_path_buf(),
+        };
+
+        let mut notes = Vec::new();
+        for path in collect_markdown_files(workspace_root, &dir)? {
+            match read_note(workspace_root, &path) {
+                Ok(note) if self.matches(&note) => notes.push(note),
+                Ok(_) => {}
+                Err(e) => log::warn!("⚠️ Skipping note in query: {}", e),
+            }
+        }
+
+        if let Some(key) = &self.sort_by {
+            notes.sort_by_cached_key(|note| sort_key(note, key));
+            if self.descending {
+                notes.reverse();
+            }
+        }
+
+        Ok(notes)
+    }
+
+    /// Column names for a result set
+    pub fn columns(&self, notes: &[NoteRecord]) -> Vec<String> {
+        let mut columns = vec!["path".to_string(), "title".to_string(), "tags".to_string()];
+
+        if self.fields.is_empty() {
+            let mut seen: Vec<String> = Vec::new();
+            for note in notes {
+                for key in note.fields.keys() {
+                    if !seen.contains(key) && !matches!(key.as_str(), "title" | "tags") {
+                        seen.push(key.clone());
+                    }
+                }
+            }
+            columns.extend(seen);
+        } else {
+            columns.extend(self.fields.iter().filter(|f| !columns.contains(f)).cloned().collect::<Vec<_>>());
+        }
+
+        if self.include_stats {
+            columns.extend(
+                ["word_count", "char_count", "line_count", "link_count", "modified"]
+                    .iter()
+                    .map(|s| s.to_string()),
+            );
+        }
+
+        columns
+    }
+
+    /// Renders results in the requested format
+    pub fn render(&self, notes: &[NoteRecord], format: DatasetFormat) -> Result<String, String> {
+        let columns = self.columns(notes);
+
+        match format {
+            DatasetFormat::Csv => {
+                let mut out = write_csv_row(&columns, ',');
+                for note in notes {
+                    let row: Vec<String> = columns
+                        .iter()
+                        .map(|c| frontmatter::value_to_string(&column_value(note, c)))
+                        .collect();
+                    out.push_str(&write_csv_row(&row, ','));
+                }
+                Ok(out)
+            }
+            DatasetFormat::Json => {
+                let rows: Vec<Value> = notes
+                    .iter()
+                    .map(|note| {
+                        let mut row = Map::new();
+                        for column in &columns {
+                            row.insert(column.clone(), column_value(note, column));
+                        }
+                        Value::Object(row)
+                    })
+                    .collect();
+                serde_json::to_string_pretty(&rows)
+                    .map_err(|e| format!("Failed to serialize results: {}", e))
+            }
+        }
+    }
+}
+
+/// Value of one output column for a note
+fn column_value(note: &NoteRecord, column: &str) -> Value {
+    match column {
+        "path" => Value::from(note.path.clone()),
+        "title" => Value::from(note.title.clone()),
+        "tags" => Value::from(note.tags.clone()),
+        "word_count" => Value::from(note.stats.word_count),
+        "char_count" => Value::from(note.stats.char_count),
+        "line_count" => Value::from(note.stats.line_count),
+        "link_count" => Value::from(note.stats.link_count),
+        "modified" => note.modified.map(|m| Value::from(m.to_rfc3339())).unwrap_or(Value::Null),
+        field => note.fields.get(field).cloned().unwrap_or(Value::Null),
+    }
+}
+
+/// Sort key; numbers sort numerically before text, missing values last
+fn sort_key(note: &NoteRecord, column: &str) -> (u8, i64, String) {
+    match column_value(note, column) {
+        Value::Null => (2, 0, String::new()),
+        Value::Number(n) => (0, n.as_f64().map(|f| (f * 1000.0) as i64).unwrap_or(0), String::new()),
+        other => (1, 0, frontmatter::value_to_string(&other).to_lowercase()),
+    }
+}
+
+// ============================================================================
+// TESTS
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn setup_notes() -> TempDir {
+        let dir = TempDir::new().expect("Failed to create workspace");
+        let root = dir.path();
+        fs::create_dir_all(root.join("Books")).unwrap();
+        fs::create_dir_all(root.join(".hidden")).unwrap();
+
+        fs::write(
+            root.join("Books/dune.md"),
+            "---\ntitle: Dune\nstatus: done\nrating: 5\ntags: [books, scifi]\n---\nGreat read. #favorite\n",
+        ).unwrap();
+        fs::write(
+            root.join("Books/emma.md"),
+            "---\nstatus: reading\nrating: 3\ntags: books\n---\n# Emma\n\nSlow start.\n",
+        ).unwrap();
+        fs::write(root.join("journal.md"), "# Today\n\nNo frontmatter, see #1 and `#code`.\n").unwrap();
+        fs::write(root.join(".hidden/secret.md"), "---\ntags: books\n---\n").unwrap();
+        dir
+    }
+
+    #[test]
+    fn test_collect_skips_hidden() {
+        let dir = setup_notes();
+        let files = collect_markdown_files(dir.path(), dir.path()).unwrap();
+        assert_eq!(files.len(), 3);
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn test_collect_follows_links_by_policy() {
+        use crate::workspace_settings::{SymlinkPolicy, WorkspaceSettings};
+
+        let workspace = setup_notes();
+        let outside = TempDir::new().unwrap();
+        fs::write(outside.path().join("secret.md"), "# Secret").unwrap();
+        std::os::unix::fs::symlink(outside.path(), workspace.path().join("external")).unwrap();
+        std::os::unix::fs::symlink(workspace.path(), workspace.path().join("Books").join("loop")).unwrap();
+
+        let names = |files: Vec<PathBuf>| -> Vec<String> {
+            files.iter().map(|f| f.file_name().unwrap().to_string_lossy().to_string()).collect()
+        };
+        let files = collect_markdown_files(workspace.path(), workspace.path()).unwrap();
+        assert!(!names(files).contains(&"secret.md".to_string()), "Links out of the workspace are not followed");
+
+        let settings = WorkspaceSettings { symlink_policy: SymlinkPolicy::Follow, ..Default::default() };
+        settings.save(workspace.path()).unwrap();
+        let files = collect_markdown_files(workspace.path(), workspace.path()).unwrap();
+        let count = files.len();
+        assert!(names(files).contains(&"secret.md".to_string()), "Follow reaches linked folders");
+        assert_eq!(count, 4, "The link cycle adds nothing");
+    }
+
+    #[test]
+    fn test_read_note_title_and_tags() {
+        let dir = setup_notes();
+        let note = read_note(dir.path(), &dir.path().join("Books/dune.md")).unwrap();
+
+        assert_eq!(note.path, "Books/dune.md");
+        assert_eq!(note.title, "Dune");
+        assert_eq!(note.tags, vec!["books", "scifi", "favorite"]);
+
+        let emma = read_note(dir.path(), &dir.path().join("Books/emma.md")).unwrap();
+        assert_eq!(emma.title, "Emma", "Falls back to the first heading");
+    }
+
+    #[test]
+    fn test_inline_tags_ignore_code_and_numbers() {
+        let body = "#one and #two/sub\n`#inline`\n```\n#fenced\n```\nissue #42";
+        assert_eq!(inline_tags(body), vec!["one", "two/sub"]);
+    }
+
+    #[test]
+    fn test_note_preview() {
+        let content = "---\ntags: [work]\n---\n# Weekly Plan\n\nShip the **beta** and review [[Roadmap|the roadmap]] with [Ana](mailto:ana@x.io). #planning\n\n```\ncode is skipped\n```\n- [x] Draft notes\n- [ ] Send invite\n1. Ordered\n";
+        let preview = note_preview(content, Path::new("plan.md"));
+
+        assert_eq!(preview.title, "Weekly Plan");
+        assert_eq!(
+            preview.preview,
+            "Ship the beta and review the roadmap with Ana. #planning Draft notes Send invite Ordered"
+        );
+        assert_eq!(preview.tags, vec!["work", "planning"]);
+        assert_eq!((preview.tasks_total, preview.tasks_done), (2, 1));
+    }
+
+    #[test]
+    fn test_preview_is_truncated() {
+        let body = "word ".repeat(100);
+        let text = preview_text(&body, 22);
+        assert_eq!(text, "word word word word…");
+    }
+
+    #[test]
+    fn test_read_preview_reads_head_only() {
+        let workspace = TempDir::new().unwrap();
+        let path = workspace.path().join("long.md");
+        let mut content = "# Long\n- [ ] first\n".to_string();
+        content.push_str(&"é".repeat(PREVIEW_READ_BYTES as usize));
+        content.push_str("\n- [ ] beyond the head\n");
+        fs::write(&path, content).unwrap();
+
+        let preview = read_preview(&path).unwrap();
+        assert_eq!(preview.title, "Long");
+        assert_eq!(preview.tasks_total, 1);
+    }
+
+    #[test]
+    fn test_query_filters_and_sorting() {
+        let dir = setup_notes();
+        let mut query = NoteQuery {
+            tags: vec!["books".to_string()],
+            sort_by: Some("rating".to_string()),
+            ..Default::default()
+        };
+
+        let notes = query.run(dir.path()).unwrap();
+        let titles: Vec<&str> = notes.iter().map(|n| n.title.as_str()).collect();
+        assert_eq!(titles, vec!["Emma", "Dune"]);
+
+        query.filters.insert("status".to_string(), "DONE".to_string());
+        assert_eq!(query.run(dir.path()).unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_render_csv() {
+        let dir = setup_notes();
+        let query = NoteQuery {
+            folder: Some("Books".to_string()),
+            fields: vec!["status".to_string(), "rating".to_string()],
+            sort_by: Some("path".to_string()),
+            ..Default::default()
+        };
+
+        let notes = query.run(dir.path()).unwrap();
+        let csv = query.render(&notes, DatasetFormat::Csv).unwrap();
+        let lines: Vec<&str> = csv.lines().collect();
+
+        assert_eq!(lines[0], "path,title,tags,status,rating");
+        assert_eq!(lines[1], "Books/dune.md,Dune,\"books, scifi, favorite\",done,5");
+    }
+
+    #[test]
+    fn test_render_json_with_stats() {
+        let dir = setup_notes();
+        let query = NoteQuery {
+            filters: BTreeMap::from([("status".to_string(), "*".to_string())]),
+            include_stats: true,
+            ..Default::default()
+        };
+
+        let notes = query.run(dir.path()).unwrap();
+        let json: Value = serde_json::from_str(&query.render(&notes, DatasetFormat::Json).unwrap()).unwrap();
+        let rows = json.as_array().unwrap();
+
+        assert_eq!(rows.len(), 2);
+        assert!(rows[0]["word_count"].as_u64().unwrap() > 0);
+        assert!(rows[0].get("status").is_some());
+    }
+}
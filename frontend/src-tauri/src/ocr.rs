@@ -0,0 +1,202 @@
+//! Image Text Recognition (OCR)
+//!
+//! Makes screenshots of text searchable. Images are recognized with
+//! Tesseract and the text is stored per workspace in `.mdreader/ocr.json`,
+//! keyed by the image's workspace-relative path:
+//!
+//! ```json
+//! { "assets": { "notes/assets/shot.png": { "text": "...", "lang": "eng", ... } } }
+//! ```
+//!
+//! An entry remembers the image's modification time, so a replaced image is
+//! recognized again. The text can also be placed in the note that shows the
+//! image, as a collapsed `<details>` block under the image link.
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use serde::{Deserialize, Serialize};
+use crate::workspace_settings::SETTINGS_DIR;
+
+/// File name of the store inside the settings directory
+pub const OCR_FILE: &str = "ocr.json";
+
+/// Tesseract language used when none is given
+pub const DEFAULT_LANG: &str = "eng";
+
+/// Images `ocr_image` accepts
+pub const OCR_EXTENSIONS: &[&str] = &["png", "jpg", "jpeg", "gif", "webp", "bmp", "tif", "tiff"];
+
+/// Summary line of the collapsed block inserted into notes
+const BLOCK_SUMMARY: &str = "Text in image";
+
+/// Recognized text of one image
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct OcrEntry {
+    pub text: String,
+    /// Tesseract language(s) used, e.g. `eng` or `eng+deu`
+    pub lang: String,
+    /// Modification time (epoch ms) of the image that was recognized
+    #[serde(default)]
+    pub modified_ms: Option<i64>,
+    /// When the text was recognized (RFC 3339)
+    pub recognized_at: String,
+}
+
+/// OCR results of one workspace
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct OcrStore {
+    #[serde(default)]
+    pub assets: BTreeMap<String, OcrEntry>,
+}
+
+impl OcrStore {
+    /// Loads the store of a workspace (empty if missing or unreadable)
+    pub fn load(workspace_root: &Path) -> Self {
+        let path = store_path(workspace_root);
+        match fs::read_to_string(&path) {
+            Ok(json) => serde_json::from_str(&json).unwrap_or_else(|e| {
+                log::warn!("⚠️ Ignoring unreadable OCR store {:?}: {}", path, e);
+                Self::default()
+            }),
+            Err(_) => Self::default(),
+        }
+    }
+
+    /// Writes the store to the workspace settings directory
+    pub fn save(&self, workspace_root: &Path) -> Result<(), String> {
+        let path = store_path(workspace_root);
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)
+                .map_err(|e| format!("Failed to create settings directory: {}", e))?;
+        }
+        let json = serde_json::to_string_pretty(self)
+            .map_err(|e| format!("Failed to serialize OCR store: {}", e))?;
+        fs::write(&path, json)
+            .map_err(|e| format!("Failed to write OCR store: {}", e))
+    }
+
+    /// Stored text of an image, if it was recognized from the same version
+    /// of the image and with the same language
+    pub fn fresh(&self, asset: &str, modified_ms: Option<i64>, lang: &str) -> Option<&OcrEntry> {
+        self.assets
+            .get(asset)
+            .filter(|entry| entry.modified_ms == modified_ms && entry.lang == lang)
+    }
+}
+
+/// Path of the OCR store of a workspace
+pub fn store_path(workspace_root: &Path) -> PathBuf {
+    workspace_root.join(SETTINGS_DIR).join(OCR_FILE)
+}
+
+/// Checks if Tesseract starts with the given language data installed
+pub fn available(lang: &str) -> bool {
+    tesseract::Tesseract::new(None, Some(lang)).is_ok()
+}
+
+/// Recognizes the text in an image
+pub fn recognize(path: &Path, lang: &str) -> Result<String, String> {
+    let text = tesseract::Tesseract::new(None, Some(lang))
+        .map_err(|e| format!("Failed to start text recognition: {}", e))?
+        .set_image(&path.to_string_lossy())
+        .map_err(|e| format!("Failed to read image: {}", e))?
+        .get_text()
+        .map_err(|e| format!("Failed to recognize text: {}", e))?;
+    Ok(text.trim().to_string())
+}
+
+/// Places recognized text as a collapsed block under the image link
+///
+/// The block goes after the first line mentioning `image_name` (or at the
+/// end of the note). An existing block under that line is replaced.
+pub fn insert_text_block(content: &str, image_name: &str, text: &str) -> String {
+    let block = format!(
+        "<details>\n<summary>{}</summary>\n\n{}\n\n</details>",
+        BLOCK_SUMMARY,
+        text.trim()
+    );
+    let lines: Vec<&str> = content.lines().collect();
+
+    let Some(link_line) = lines.iter().position(|line| line.contains(image_name)) else {
+        let mut updated = content.trim_end().to_string();
+        if !updated.is_empty() {
+            updated.push_str("\n\n");
+        }
+        updated.push_str(&block);
+        updated.push('\n');
+        return updated;
+    };
+
+    // Skip an earlier block for the same image
+    let mut rest = link_line + 1;
+    let summary = format!("<summary>{}</summary>", BLOCK_SUMMARY);
+    if lines.get(rest).map(|l| l.trim()) == Some("<details>")
+        && lines.get(rest + 1).map(|l| l.trim()) == Some(summary.as_str())
+    {
+        if let Some(end) = lines[rest..].iter().position(|l| l.trim() == "</details>") {
+            rest += end + 1;
+        }
+    }
+
+    let mut updated: Vec<&str> = lines[..=link_line].to_vec();
+    updated.push(&block);
+    updated.extend(&lines[rest..]);
+    let mut updated = updated.join("\n");
+    if content.ends_with('\n') {
+        updated.push('\n');
+    }
+    updated
+}
+
+// ============================================================================
+// TESTS
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_block_goes_under_image_link() {
+        let note = "# Meeting\n![shot](assets/shot.png)\nNext steps\n";
+        let updated = insert_text_block(note, "shot.png", "Q3 targets\n");
+
+        assert_eq!(
+            updated,
+            "# Meeting\n![shot](assets/shot.png)\n<details>\n<summary>Text in image</summary>\n\nQ3 targets\n\n</details>\nNext steps\n"
+        );
+        // Recognizing again replaces the block instead of stacking another
+        assert_eq!(
+            insert_text_block(&updated, "shot.png", "Q4 targets"),
+            updated.replace("Q3", "Q4")
+        );
+    }
+
+    #[test]
+    fn test_block_appended_when_image_not_linked() {
+        assert_eq!(
+            insert_text_block("# Notes\n", "shot.png", "hello"),
+            "# Notes\n\n<details>\n<summary>Text in image</summary>\n\nhello\n\n</details>\n"
+        );
+    }
+
+    #[test]
+    fn test_store_freshness() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let mut store = OcrStore::default();
+        store.assets.insert("assets/shot.png".to_string(), OcrEntry {
+            text: "hello".to_string(),
+            lang: "eng".to_string(),
+            modified_ms: Some(1000),
+            recognized_at: "2024-05-17T09:30:00.000Z".to_string(),
+        });
+        store.save(dir.path()).unwrap();
+
+        let loaded = OcrStore::load(dir.path());
+        assert_eq!(loaded, store);
+        assert!(loaded.fresh("assets/shot.png", Some(1000), "eng").is_some());
+        assert!(loaded.fresh("assets/shot.png", Some(2000), "eng").is_none());
+        assert!(loaded.fresh("assets/shot.png", Some(1000), "deu").is_none());
+    }
+}
@@ -0,0 +1,336 @@
+//! Offline Write Journal
+//!
+//! A workspace on an external drive, a network share or an iCloud Drive
+//! folder that is offline can disappear while notes are open. Instead of
+//! failing, saves made while the workspace folder is unreachable are
+//! recorded in a journal in the app config directory
+//! (`<config>/offline-journal.json`), one entry per file with its latest
+//! content, and replayed once the folder is back.
+//!
+//! Each entry remembers the hash of the file as the editor last knew it
+//! (the base). On replay the file is only overwritten if it still has that
+//! content; if it was changed elsewhere meanwhile, the journaled content is
+//! written next to it as a conflict copy instead, named like the copies of
+//! sync tools (`note (offline conflicted copy 2024-05-01 093455).md`) so it
+//! shows up in the sync conflict list (see `crate::sync_conflicts`).
+
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// File name of the journal inside the config directory
+pub const JOURNAL_FILE: &str = "offline-journal.json";
+
+/// A save waiting for its workspace to come back
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct JournalEntry {
+    pub workspace: String,
+    /// Absolute path of the note
+    pub path: String,
+    /// Latest content saved while offline
+    pub content: String,
+    /// Hash of the note as the editor last knew it (`None` if unknown or
+    /// the note did not exist)
+    pub base_hash: Option<String>,
+    /// First save of the note while offline
+    pub queued_at: DateTime<Utc>,
+    /// Latest save of the note while offline
+    pub updated_at: DateTime<Utc>,
+}
+
+/// What replaying an entry does with the note on disk
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReplayAction {
+    /// The note is unchanged since the base: write the content
+    Write,
+    /// The note already has the content: drop the entry
+    AlreadyApplied,
+    /// The note changed elsewhere: write the content as a conflict copy
+    Conflict,
+}
+
+/// Journal of saves made while their workspace was unreachable
+/// (internally synchronized)
+#[derive(Clone, Default)]
+pub struct OfflineJournal {
+    state: Arc<Mutex<JournalState>>,
+}
+
+#[derive(Default)]
+struct JournalState {
+    /// Where the journal is saved (`None` until loaded)
+    dir: Option<PathBuf>,
+    entries: Vec<JournalEntry>,
+}
+
+impl OfflineJournal {
+    /// Creates an empty journal that is not saved anywhere
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Loads the journal from `config_dir`; later changes are saved there
+    pub fn load(&self, config_dir: &Path) -> Result<usize, String> {
+        let entries: Vec<JournalEntry> = match fs::read_to_string(config_dir.join(JOURNAL_FILE)) {
+            Ok(json) => serde_json::from_str(&json)
+                .map_err(|e| format!("Failed to parse offline journal: {}", e))?,
+            Err(_) => Vec::new(),
+        };
+        let mut state = self.lock()?;
+        state.dir = Some(config_dir.to_path_buf());
+        state.entries = entries;
+        Ok(state.entries.len())
+    }
+
+    /// Records a save of `path`, replacing the content of an earlier entry
+    /// for it (whose base is kept)
+    ///
+    /// # Returns
+    /// * `Ok(true)` - If an entry for the note was already pending
+    pub fn record(
+        &self,
+        workspace: &str,
+        path: &Path,
+        content: &str,
+        base_hash: Option<String>,
+        now: DateTime<Utc>,
+    ) -> Result<bool, String> {
+        let path = path.to_string_lossy();
+        let mut state = self.lock()?;
+        let replaced = match state.entries.iter_mut().find(|e| e.workspace == workspace && e.path == path) {
+            Some(entry) => {
+                entry.content = content.to_string();
+                entry.updated_at = now;
+                true
+            }
+            None => {
+                state.entries.push(JournalEntry {
+                    workspace: workspace.to_string(),
+                    path: path.to_string(),
+                    content: content.to_string(),
+                    base_hash,
+                    queued_at: now,
+                    updated_at: now,
+                });
+                false
+            }
+        };
+        state.save()?;
+        Ok(replaced)
+    }
+
+    /// Pending entries of a workspace, oldest first
+    pub fn pending(&self, workspace: &str) -> Vec<JournalEntry> {
+        self.state
+            .lock()
+            .map(|s| s.entries.iter().filter(|e| e.workspace == workspace).cloned().collect())
+            .unwrap_or_default()
+    }
+
+    /// Number of pending entries of all workspaces
+    pub fn len(&self) -> usize {
+        self.state.lock().map(|s| s.entries.len()).unwrap_or(0)
+    }
+
+    /// Checks if no saves are pending
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Removes a replayed entry, unless it was saved again since `replayed`
+    /// was taken
+    pub fn complete(&self, replayed: &JournalEntry) -> Result<bool, String> {
+        let mut state = self.lock()?;
+        let before = state.entries.len();
+        state.entries.retain(|e| {
+            !(e.workspace == replayed.workspace && e.path == replayed.path && e.updated_at == replayed.updated_at)
+        });
+        let removed = state.entries.len() < before;
+        if removed {
+            state.save()?;
+        }
+        Ok(removed)
+    }
+
+    /// Rebases a replayed entry on the content now on disk, so a later
+    /// replay of newer content does not conflict with the earlier one
+    pub fn rebase(&self, workspace: &str, path: &str, base_hash: String) -> Result<(), String> {
+        let mut state = self.lock()?;
+        if let Some(entry) = state.entries.iter_mut().find(|e| e.workspace == workspace && e.path == path) {
+            entry.base_hash = Some(base_hash);
+            state.save()?;
+        }
+        Ok(())
+    }
+
+    fn lock(&self) -> Result<std::sync::MutexGuard<'_, JournalState>, String> {
+        self.state
+            .lock()
+            .map_err(|e| format!("Failed to lock offline journal: {}", e))
+    }
+}
+
+impl JournalState {
+    // Helper: Writes through a temporary file so a crash never leaves a
+    // truncated journal
+    fn save(&self) -> Result<(), String> {
+        let Some(dir) = &self.dir else {
+            return Ok(());
+        };
+        let path = dir.join(JOURNAL_FILE);
+        if self.entries.is_empty() {
+            return match fs::remove_file(&path) {
+                Err(e) if e.kind() != std::io::ErrorKind::NotFound => {
+                    Err(format!("Failed to save offline journal: {}", e))
+                }
+                _ => Ok(()),
+            };
+        }
+        let json = serde_json::to_string(&self.entries)
+            .map_err(|e| format!("Failed to serialize offline journal: {}", e))?;
+        let temp_path = path.with_extension("json.tmp");
+        fs::write(&temp_path, json)
+            .map_err(|e| format!("Failed to save offline journal: {}", e))?;
+        fs::rename(&temp_path, &path)
+            .map_err(|e| format!("Failed to save offline journal: {}", e))
+    }
+}
+
+/// Checks if the workspace folder can be reached
+///
+/// A drive that was ejected or a share that dropped makes the folder
+/// disappear or fail to list.
+pub fn is_reachable(workspace_root: &Path) -> bool {
+    fs::read_dir(workspace_root).is_ok()
+}
+
+/// Checks a note path without touching the disk, for saves to an
+/// unreachable workspace (which `validate_file_path` cannot resolve)
+///
+/// The path must be an absolute `.md` path below `workspace_root` without
+/// `..` components. Replay validates it fully once the workspace is back.
+pub fn offline_note_path(requested: &str, workspace_root: &str) -> Result<PathBuf, String> {
+    let path = Path::new(requested);
+    let inside = path.is_absolute()
+        && path.starts_with(workspace_root)
+        && !path.components().any(|c| matches!(c, Component::ParentDir | Component::CurDir));
+    if !inside {
+        return Err(format!("Access denied: path '{}' is outside workspace '{}'", requested, workspace_root));
+    }
+    if path.extension().map(|e| e.to_string_lossy().to_lowercase()) != Some("md".to_string()) {
+        return Err(format!("Invalid path '{}': only notes can be saved offline", requested));
+    }
+    Ok(path.to_path_buf())
+}
+
+/// Content hash (blake3, hex)
+pub fn content_hash(content: &str) -> String {
+    blake3::hash(content.as_bytes()).to_hex().to_string()
+}
+
+/// Decides how to replay `entry` onto a note that now has `current`
+/// (`None` if the note does not exist)
+pub fn replay_action(entry: &JournalEntry, current: Option<&str>) -> ReplayAction {
+    let current_hash = current.map(content_hash);
+    if current_hash.as_deref() == Some(content_hash(&entry.content).as_str()) {
+        ReplayAction::AlreadyApplied
+    } else if current_hash == entry.base_hash {
+        ReplayAction::Write
+    } else {
+        ReplayAction::Conflict
+    }
+}
+
+/// Path of the conflict copy of `path`, not taken by another file
+pub fn conflict_copy_path(path: &Path, now: DateTime<Utc>) -> PathBuf {
+    let stem = path.file_stem().map(|s| s.to_string_lossy().to_string()).unwrap_or_default();
+    let extension = path.extension().map(|e| format!(".{}", e.to_string_lossy())).unwrap_or_default();
+    let stamp = now.format("%Y-%m-%d %H%M%S");
+
+    let mut candidate = path.with_file_name(format!("{} (offline conflicted copy {}){}", stem, stamp, extension));
+    let mut n = 2;
+    while candidate.exists() {
+        candidate = path.with_file_name(format!("{} (offline conflicted copy {} ({})){}", stem, stamp, n, extension));
+        n += 1;
+    }
+    candidate
+}
+
+// ============================================================================
+// TESTS
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sync_conflicts::parse_conflict_name;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_journal_coalesces_and_persists() {
+        let config = TempDir::new().unwrap();
+        let journal = OfflineJournal::new();
+        journal.load(config.path()).unwrap();
+        let note = Path::new("/Volumes/Notes/plan.md");
+        let start = Utc::now();
+
+        let base = Some(content_hash("v1"));
+        assert!(!journal.record("/Volumes/Notes", note, "v2", base.clone(), start).unwrap());
+        let later = start + chrono::Duration::seconds(5);
+        assert!(journal.record("/Volumes/Notes", note, "v3", None, later).unwrap());
+
+        let restarted = OfflineJournal::new();
+        assert_eq!(restarted.load(config.path()).unwrap(), 1);
+        let entry = &restarted.pending("/Volumes/Notes")[0];
+        assert_eq!((entry.content.as_str(), &entry.base_hash), ("v3", &base));
+        assert_eq!((entry.queued_at, entry.updated_at), (start, later));
+
+        // An entry saved again since it was taken stays pending
+        let mut stale = entry.clone();
+        stale.updated_at = start;
+        assert!(!restarted.complete(&stale).unwrap());
+        assert!(restarted.complete(entry).unwrap());
+        assert!(restarted.is_empty());
+        assert!(!config.path().join(JOURNAL_FILE).exists());
+    }
+
+    #[test]
+    fn test_offline_note_path() {
+        assert!(offline_note_path("/Volumes/Notes/a/plan.md", "/Volumes/Notes").is_ok());
+        assert!(offline_note_path("/Volumes/Notes/../etc/plan.md", "/Volumes/Notes").is_err());
+        assert!(offline_note_path("/Volumes/Other/plan.md", "/Volumes/Notes").is_err());
+        assert!(offline_note_path("/Volumes/Notes/image.png", "/Volumes/Notes").is_err());
+    }
+
+    #[test]
+    fn test_replay_detects_conflicts() {
+        let entry = JournalEntry {
+            workspace: "/notes".to_string(),
+            path: "/notes/plan.md".to_string(),
+            content: "offline edit".to_string(),
+            base_hash: Some(content_hash("before")),
+            queued_at: Utc::now(),
+            updated_at: Utc::now(),
+        };
+        assert_eq!(replay_action(&entry, Some("before")), ReplayAction::Write);
+        assert_eq!(replay_action(&entry, Some("offline edit")), ReplayAction::AlreadyApplied);
+        assert_eq!(replay_action(&entry, Some("edited elsewhere")), ReplayAction::Conflict);
+        assert_eq!(replay_action(&entry, None), ReplayAction::Conflict);
+
+        let new_note = JournalEntry { base_hash: None, ..entry };
+        assert_eq!(replay_action(&new_note, None), ReplayAction::Write);
+
+        let dir = TempDir::new().unwrap();
+        let now = DateTime::parse_from_rfc3339("2024-05-01T09:34:55Z").unwrap().with_timezone(&Utc);
+        let copy = conflict_copy_path(&dir.path().join("plan.md"), now);
+        assert_eq!(copy.file_name().unwrap(), "plan (offline conflicted copy 2024-05-01 093455).md");
+        let name = parse_conflict_name("plan (offline conflicted copy 2024-05-01 093455).md").unwrap();
+        assert_eq!((name.original.as_str(), name.device.as_deref()), ("plan.md", Some("offline")));
+
+        fs::write(&copy, "taken").unwrap();
+        let second = conflict_copy_path(&dir.path().join("plan.md"), now);
+        assert_eq!(second.file_name().unwrap(), "plan (offline conflicted copy 2024-05-01 093455 (2)).md");
+    }
+}
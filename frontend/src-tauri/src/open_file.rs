@@ -0,0 +1,275 @@
+//! Open File Watching
+//!
+//! Watches the documents open in an editor for external changes, one file
+//! at a time instead of a whole directory tree. Each watched file keeps a
+//! baseline (its content as the editor knows it); when the file changes on
+//! disk, the change is reported with a unified diff against the baseline
+//! (see `crate::diff`), which then moves to the new content.
+//!
+//! Saves made by the editor itself update the baseline before they land
+//! (`OpenFileWatchers::expect`), so they are not reported back. Events
+//! that leave the content unchanged (touches, repeated notifications of
+//! one save) are dropped.
+//!
+//! The parent folder is watched rather than the file, so replacements by
+//! rename (atomic saves of other editors) keep being noticed.
+//!
+//! While the app is suspended the watches are dropped but the baselines
+//! kept; on resume every file is watched again and compared with its
+//! baseline, so changes made in the meantime are reported like any other.
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use notify::{Config, Event, EventKind, RecommendedWatcher, RecursiveMode, Result as NotifyResult, Watcher};
+use crate::diff::{unified_diff, TextDiff, DEFAULT_CONTEXT};
+
+/// An external change of a watched file
+#[derive(Debug, Clone, PartialEq)]
+pub struct ExternalChange {
+    pub path: PathBuf,
+    /// New content, `None` if the file was deleted
+    pub content: Option<String>,
+    /// Changes from the baseline
+    pub diff: TextDiff,
+}
+
+/// Callback run for every external change of a watched file
+type OnChange = Arc<dyn Fn(ExternalChange) + Send + Sync>;
+
+/// A watched file
+struct WatchedFile {
+    /// Kept to keep the watch alive; dropping it stops watching (`None`
+    /// while suspended)
+    watcher: Option<RecommendedWatcher>,
+    /// Content as last seen or saved by the editor (`None` if missing)
+    baseline: Option<String>,
+    on_change: OnChange,
+}
+
+/// Registry of watched open files (internally synchronized)
+#[derive(Clone, Default)]
+pub struct OpenFileWatchers {
+    files: Arc<Mutex<HashMap<PathBuf, WatchedFile>>>,
+}
+
+impl OpenFileWatchers {
+    /// Creates an empty registry
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Starts watching a file, replacing an existing watch of it
+    ///
+    /// `on_change` runs on the watcher thread for every external change.
+    ///
+    /// # Returns
+    /// * `Ok(true)` - If the file was already watched (its baseline is reset)
+    pub fn watch(
+        &self,
+        path: &Path,
+        on_change: impl Fn(ExternalChange) + Send + Sync + 'static,
+    ) -> Result<bool, String> {
+        let on_change: OnChange = Arc::new(on_change);
+        let file = WatchedFile {
+            watcher: Some(self.start(path, Arc::clone(&on_change))?),
+            baseline: fs::read_to_string(path).ok(),
+            on_change,
+        };
+        let replaced = self.lock()?.insert(path.to_path_buf(), file);
+        // Dropped outside the lock, which the old watcher's callback takes
+        Ok(replaced.is_some())
+    }
+
+    /// Stops watching a file; returns whether it was watched
+    pub fn unwatch(&self, path: &Path) -> Result<bool, String> {
+        let removed = self.lock()?.remove(path);
+        Ok(removed.is_some())
+    }
+
+    /// Records content the editor is about to write, so the write is not
+    /// reported as an external change
+    pub fn expect(&self, path: &Path, content: &str) {
+        if let Ok(mut files) = self.files.lock() {
+            if let Some(file) = files.get_mut(path) {
+                file.baseline = Some(content.to_string());
+            }
+        }
+    }
+
+    /// Content of a watched file as last seen or saved by the editor
+    ///
+    /// `None` if the file is not watched or was missing.
+    pub fn baseline(&self, path: &Path) -> Option<String> {
+        self.files.lock().ok()?.get(path)?.baseline.clone()
+    }
+
+    /// Stops watching all files; returns how many were watched
+    pub fn clear(&self) -> Result<usize, String> {
+        let files: Vec<WatchedFile> = self.lock()?.drain().map(|(_, file)| file).collect();
+        Ok(files.len())
+    }
+
+    /// Drops the watches of all files, keeping their baselines
+    ///
+    /// Returns the number of files that were being watched.
+    pub fn suspend(&self) -> Result<usize, String> {
+        let watchers: Vec<RecommendedWatcher> = self.lock()?
+            .values_mut()
+            .filter_map(|file| file.watcher.take())
+            .collect();
+        Ok(watchers.len())
+    }
+
+    /// Watches the suspended files again and reports the ones that changed
+    /// meanwhile through their callbacks
+    ///
+    /// Files that can no longer be watched (their folder is gone) are
+    /// dropped. Returns the number of files watched again.
+    pub fn resume(&self) -> Result<usize, String> {
+        let suspended: Vec<(PathBuf, OnChange)> = self.lock()?
+            .iter()
+            .filter(|(_, file)| file.watcher.is_none())
+            .map(|(path, file)| (path.clone(), Arc::clone(&file.on_change)))
+            .collect();
+
+        let mut resumed = 0;
+        for (path, on_change) in suspended {
+            match self.start(&path, Arc::clone(&on_change)) {
+                Ok(watcher) => {
+                    if let Some(file) = self.lock()?.get_mut(&path) {
+                        file.watcher = Some(watcher);
+                        resumed += 1;
+                    }
+                }
+                Err(e) => {
+                    log::warn!("⚠️ Not watching {:?} again: {}", path, e);
+                    self.lock()?.remove(&path);
+                }
+            }
+            if let Some(change) = check(&self.files, &path) {
+                on_change(change);
+            }
+        }
+        Ok(resumed)
+    }
+
+    // Helper: Watches the parent folder of a file for changes of the file
+    fn start(&self, path: &Path, on_change: OnChange) -> Result<RecommendedWatcher, String> {
+        let parent = path
+            .parent()
+            .ok_or_else(|| format!("File has no parent folder: {:?}", path))?;
+        let name = path.file_name().map(|n| n.to_os_string());
+
+        let files = Arc::clone(&self.files);
+        let watched = path.to_path_buf();
+        let mut watcher = RecommendedWatcher::new(
+            move |res: NotifyResult<Event>| {
+                let Ok(event) = res else { return };
+                let relevant = matches!(event.kind, EventKind::Create(_) | EventKind::Modify(_) | EventKind::Remove(_))
+                    && event.paths.iter().any(|p| p.file_name().map(|n| n.to_os_string()) == name);
+                if let Some(change) = relevant.then(|| check(&files, &watched)).flatten() {
+                    on_change(change);
+                }
+            },
+            Config::default(),
+        )
+        .map_err(|e| format!("Failed to create watcher: {}", e))?;
+        watcher
+            .watch(parent, RecursiveMode::NonRecursive)
+            .map_err(|e| format!("Failed to watch file: {}", e))?;
+        Ok(watcher)
+    }
+
+    fn lock(&self) -> Result<std::sync::MutexGuard<'_, HashMap<PathBuf, WatchedFile>>, String> {
+        self.files
+            .lock()
+            .map_err(|e| format!("Failed to lock open file watchers: {}", e))
+    }
+}
+
+// Helper: Compares a watched file with its baseline and moves the baseline
+fn check(files: &Mutex<HashMap<PathBuf, WatchedFile>>, path: &Path) -> Option<ExternalChange> {
+    let current = fs::read_to_string(path).ok();
+    let mut files = files.lock().ok()?;
+    let file = files.get_mut(path)?;
+    let change = compare(path, file.baseline.as_deref(), current.as_deref())?;
+    file.baseline = current;
+    Some(change)
+}
+
+/// The change from `baseline` to `current`, if there is one
+fn compare(path: &Path, baseline: Option<&str>, current: Option<&str>) -> Option<ExternalChange> {
+    if baseline == current {
+        return None;
+    }
+    let label = path.file_name().map_or_else(String::new, |n| n.to_string_lossy().to_string());
+    Some(ExternalChange {
+        path: path.to_path_buf(),
+        content: current.map(str::to_string),
+        diff: unified_diff(baseline.unwrap_or(""), current.unwrap_or(""), (&label, &label), DEFAULT_CONTEXT),
+    })
+}
+
+// ============================================================================
+// TESTS
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_compare() {
+        let path = Path::new("/ws/plan.md");
+        assert_eq!(compare(path, Some("a\n"), Some("a\n")), None);
+        assert_eq!(compare(path, None, None), None);
+
+        let change = compare(path, Some("a\n"), Some("b\n")).unwrap();
+        assert_eq!(change.content.as_deref(), Some("b\n"));
+        assert_eq!((change.diff.added, change.diff.removed), (1, 1));
+        assert!(change.diff.unified.starts_with("--- a/plan.md\n"));
+
+        let deleted = compare(path, Some("a\n"), None).unwrap();
+        assert_eq!(deleted.content, None);
+        assert_eq!(deleted.diff.removed, 1);
+    }
+
+    #[test]
+    fn test_watch_lifecycle() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("plan.md");
+        fs::write(&path, "a\n").unwrap();
+
+        let watchers = OpenFileWatchers::new();
+        assert!(!watchers.watch(&path, |_| {}).unwrap());
+        assert!(watchers.watch(&path, |_| {}).unwrap());
+
+        assert!(watchers.unwatch(&path).unwrap());
+        assert!(!watchers.unwatch(&path).unwrap());
+
+        watchers.watch(&path, |_| {}).unwrap();
+        assert_eq!(watchers.clear().unwrap(), 1);
+    }
+
+    #[test]
+    fn test_changes_while_suspended_are_reported_on_resume() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("plan.md");
+        fs::write(&path, "a\n").unwrap();
+
+        let changes = Arc::new(Mutex::new(Vec::new()));
+        let watchers = OpenFileWatchers::new();
+        let seen = Arc::clone(&changes);
+        watchers.watch(&path, move |change| seen.lock().unwrap().push(change)).unwrap();
+
+        assert_eq!(watchers.suspend().unwrap(), 1);
+        assert_eq!(watchers.suspend().unwrap(), 0);
+        fs::write(&path, "b\n").unwrap();
+
+        assert_eq!(watchers.resume().unwrap(), 1);
+        let changes = changes.lock().unwrap();
+        assert!(changes.iter().any(|c| c.content.as_deref() == Some("b\n")));
+    }
+}
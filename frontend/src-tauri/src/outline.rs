@@ -0,0 +1,1011 @@
+//! Incremental Outline Parser
+//!
+//! The mindmap view is driven by the heading outline of the open document.
+//! Re-parsing a large document on every keystroke is wasteful, so each open
+//! document keeps an `OutlineDocument` that accepts text deltas and only
+//! rebuilds the part of the outline the edit can affect:
+//!
+//! - Body edits return the section containing the edit
+//! - Heading edits that keep every level return the smallest section
+//!   containing the edited headings
+//! - Adding, removing or re-leveling headings returns the nearest parent
+//!   section whose children may be re-parented
+//!
+//! Edits that add or remove a code fence or frontmatter delimiter can change
+//! how every following line is read, so they fall back to a full parse.
+//! Node ids survive edits (and full parses, where headings are matched by
+//! level and title), so mindmap metadata keyed by id stays attached.
+//!
+//! ## Stable ids
+//! Node ids are only meaningful while a document is open. Every heading and
+//! list item also gets a `stable_id` derived from its content: a hash of the
+//! parent's stable id, the normalized text and the index among same-text
+//! siblings. It is the same after a restart and survives edits elsewhere in
+//! the document, so `locate` can map a mindmap node back to its current lines.
+//!
+//! ## Sections
+//! `read_section` / `write_section` use the same parser to read or replace
+//! the block below one heading without touching the rest of the file.
+
+use std::collections::{HashMap, VecDeque};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use serde::{Deserialize, Serialize};
+use crate::memory_budget::{stamp, CacheEntry, CacheKind};
+
+/// Id of the synthetic node representing the whole document
+pub const ROOT_ID: &str = "root";
+
+/// Position in a document (0-based line, column in characters)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TextPosition {
+    pub line: usize,
+    pub column: usize,
+}
+
+/// Range of text replaced by an edit (end is exclusive)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TextRange {
+    pub start: TextPosition,
+    pub end: TextPosition,
+}
+
+/// A heading and the section it spans
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct OutlineNode {
+    pub id: String,
+    /// Content-derived id that persists across sessions
+    pub stable_id: String,
+    /// Heading level (0 for the document root)
+    pub level: u8,
+    pub title: String,
+    /// Line of the heading
+    pub line: usize,
+    /// Last line of the section (inclusive)
+    pub end_line: usize,
+    pub children: Vec<OutlineNode>,
+    /// List items directly below the heading (before its first subheading)
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub items: Vec<OutlineItem>,
+}
+
+/// A list item and the lines it spans
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct OutlineItem {
+    pub stable_id: String,
+    /// Item text without the list marker
+    pub text: String,
+    pub line: usize,
+    /// Last line of the item, nested items included (inclusive)
+    pub end_line: usize,
+    pub children: Vec<OutlineItem>,
+}
+
+/// Current position of a node found by id
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct NodeLocation {
+    pub stable_id: String,
+    /// Heading title or list item text
+    pub text: String,
+    pub line: usize,
+    /// Last line of the node (inclusive)
+    pub end_line: usize,
+}
+
+/// Result of applying an edit
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct OutlineUpdate {
+    /// Replaces the node with the same id (`"root"` replaces the whole outline)
+    pub subtree: OutlineNode,
+    /// Last line touched by the edit, numbered as before the edit
+    pub edit_end_line: usize,
+    /// Change in line count caused by the edit
+    ///
+    /// Nodes outside `subtree` whose `line` is after `edit_end_line`, or whose
+    /// `end_line` is at or after it, must be shifted by this amount.
+    pub line_delta: isize,
+}
+
+#[derive(Debug, Clone)]
+struct Heading {
+    id: u64,
+    line: usize,
+    level: u8,
+    title: String,
+}
+
+/// Parsed state of one open document
+#[derive(Debug, Clone, Default)]
+pub struct OutlineDocument {
+    lines: Vec<String>,
+    /// Headings ordered by line
+    headings: Vec<Heading>,
+    /// Inclusive line spans of frontmatter and fenced code, where `#` is not a heading
+    blocks: Vec<(usize, usize)>,
+    next_id: u64,
+}
+
+impl OutlineDocument {
+    /// Parses a full document
+    pub fn new(content: &str) -> Self {
+        let mut doc = Self {
+            lines: split_lines(content),
+            ..Self::default()
+        };
+        doc.reparse();
+        doc
+    }
+
+    /// Estimated memory held by the parsed state
+    pub fn approx_bytes(&self) -> usize {
+        let lines: usize = self.lines.iter().map(|line| line.capacity() + std::mem::size_of::<String>()).sum();
+        let headings: usize = self.headings
+            .iter()
+            .map(|heading| heading.title.capacity() + std::mem::size_of::<Heading>())
+            .sum();
+        lines + headings + self.blocks.len() * std::mem::size_of::<(usize, usize)>()
+    }
+
+    /// Gets the full outline
+    pub fn outline(&self) -> OutlineNode {
+        self.build_subtree(None)
+    }
+
+    /// Finds a heading or list item by `stable_id` (or heading `id`)
+    pub fn locate(&self, node_id: &str) -> Option<NodeLocation> {
+        fn find_item(items: &[OutlineItem], node_id: &str) -> Option<NodeLocation> {
+            items.iter().find_map(|item| {
+                if item.stable_id == node_id {
+                    return Some(NodeLocation {
+                        stable_id: item.stable_id.clone(),
+                        text: item.text.clone(),
+                        line: item.line,
+                        end_line: item.end_line,
+                    });
+                }
+                find_item(&item.children, node_id)
+            })
+        }
+        fn find(node: &OutlineNode, node_id: &str) -> Option<NodeLocation> {
+            if node.stable_id == node_id || node.id == node_id {
+                return Some(NodeLocation {
+                    stable_id: node.stable_id.clone(),
+                    text: node.title.clone(),
+                    line: node.line,
+                    end_line: node.end_line,
+                });
+            }
+            find_item(&node.items, node_id).or_else(|| node.children.iter().find_map(|c| find(c, node_id)))
+        }
+
+        find(&self.outline(), node_id)
+    }
+
+    /// Replaces `range` with `text` and returns the affected part of the outline
+    pub fn apply_edit(&mut self, range: TextRange, text: &str) -> Result<OutlineUpdate, String> {
+        let (s, e) = (range.start.line, range.end.line);
+        if (s, range.start.column) > (e, range.end.column) || e >= self.lines.len() {
+            return Err("Edit range out of bounds".to_string());
+        }
+        let start_byte = byte_index(&self.lines[s], range.start.column)?;
+        let end_byte = byte_index(&self.lines[e], range.end.column)?;
+
+        let mut replaced = String::new();
+        replaced.push_str(&self.lines[s][..start_byte]);
+        replaced.push_str(text);
+        replaced.push_str(&self.lines[e][end_byte..]);
+        let new_lines = split_lines(&replaced);
+
+        let line_delta = new_lines.len() as isize - (e - s + 1) as isize;
+        let needs_full_parse = self.lines[s..=e].iter().chain(&new_lines).any(|l| is_block_delimiter(l));
+        let inserted_count = new_lines.len();
+        self.lines.splice(s..=e, new_lines);
+
+        if needs_full_parse {
+            self.reparse();
+            return Ok(OutlineUpdate {
+                subtree: self.outline(),
+                edit_end_line: e,
+                line_delta,
+            });
+        }
+
+        let shift = |line: usize| (line as isize + line_delta) as usize;
+        let in_block = self.in_block(s);
+        for block in &mut self.blocks {
+            if block.0 > e {
+                *block = (shift(block.0), shift(block.1));
+            } else if block.1 >= s {
+                // No delimiter was touched, so the edit lies inside this block
+                block.1 = shift(block.1);
+            }
+        }
+
+        // Re-scan only the edited lines, reusing ids of the headings they replace
+        let first = self.headings.partition_point(|h| h.line < s);
+        let last = self.headings.partition_point(|h| h.line <= e);
+        let removed: Vec<Heading> = self.headings.drain(first..last).collect();
+        let mut inserted: Vec<Heading> = Vec::new();
+        if !in_block {
+            for line in s..s + inserted_count {
+                if let Some((level, title)) = parse_heading(&self.lines[line]) {
+                    inserted.push(Heading { id: 0, line, level, title });
+                }
+            }
+        }
+        self.assign_ids(&removed, &mut inserted);
+        for heading in &mut self.headings[first..] {
+            heading.line = shift(heading.line);
+        }
+        let inserted_len = inserted.len();
+        self.headings.splice(first..first, inserted.clone());
+
+        let same_shape = removed.len() == inserted.len()
+            && removed.iter().zip(&inserted).all(|(a, b)| a.level == b.level);
+
+        let root = if same_shape {
+            // Smallest section containing line `s` and every edited heading
+            let mut candidate = self.headings[..first + inserted_len]
+                .iter()
+                .rposition(|h| h.line <= s);
+            let last_edited = inserted.last().map_or(s, |h| h.line);
+            while let Some(index) = candidate {
+                if self.section_end(index) >= last_edited {
+                    break;
+                }
+                candidate = self.parent_of(index);
+            }
+            candidate
+        } else {
+            // Headings after the edit may move under a different parent
+            let min_level = removed.iter().chain(&inserted).map(|h| h.level).min().unwrap_or(1);
+            self.headings[..first].iter().rposition(|h| h.level < min_level)
+        };
+
+        Ok(OutlineUpdate {
+            subtree: self.build_subtree(root),
+            edit_end_line: e,
+            line_delta,
+        })
+    }
+
+    /// Gives re-scanned headings the ids of the headings they replace
+    ///
+    /// A heading keeps the id of a replaced heading with the same title, or
+    /// else takes the next unclaimed one in order (so retitling keeps the id).
+    fn assign_ids(&mut self, removed: &[Heading], inserted: &mut [Heading]) {
+        let mut claimed = vec![false; removed.len()];
+        for heading in inserted.iter_mut() {
+            if let Some(i) = (0..removed.len()).find(|&i| !claimed[i] && removed[i].title == heading.title) {
+                claimed[i] = true;
+                heading.id = removed[i].id;
+            }
+        }
+        for heading in inserted.iter_mut().filter(|h| h.id == 0) {
+            heading.id = match (0..removed.len()).find(|&i| !claimed[i]) {
+                Some(i) => {
+                    claimed[i] = true;
+                    removed[i].id
+                }
+                None => {
+                    self.next_id += 1;
+                    self.next_id
+                }
+            };
+        }
+    }
+
+    /// Full parse, keeping ids of headings with an unchanged level and title
+    fn reparse(&mut self) {
+        let mut known: HashMap<(u8, String), VecDeque<u64>> = HashMap::new();
+        for heading in self.headings.drain(..) {
+            known.entry((heading.level, heading.title)).or_default().push_back(heading.id);
+        }
+
+        self.blocks = find_blocks(&self.lines);
+        let mut headings = Vec::new();
+        let mut blocks = self.blocks.iter().peekable();
+        for (line, text) in self.lines.iter().enumerate() {
+            while blocks.peek().is_some_and(|b| b.1 < line) {
+                blocks.next();
+            }
+            if blocks.peek().is_some_and(|b| b.0 <= line) {
+                continue;
+            }
+            if let Some((level, title)) = parse_heading(text) {
+                let id = known
+                    .get_mut(&(level, title.clone()))
+                    .and_then(|ids| ids.pop_front())
+                    .unwrap_or_else(|| {
+                        self.next_id += 1;
+                        self.next_id
+                    });
+                headings.push(Heading { id, line, level, title });
+            }
+        }
+        self.headings = headings;
+    }
+
+    fn in_block(&self, line: usize) -> bool {
+        self.blocks.iter().any(|&(start, end)| start <= line && line <= end)
+    }
+
+    /// Last line of the section starting at heading `index`
+    fn section_end(&self, index: usize) -> usize {
+        let level = self.headings[index].level;
+        self.headings[index + 1..]
+            .iter()
+            .find(|h| h.level <= level)
+            .map_or(self.last_line(), |h| h.line - 1)
+    }
+
+    fn parent_of(&self, index: usize) -> Option<usize> {
+        let level = self.headings[index].level;
+        self.headings[..index].iter().rposition(|h| h.level < level)
+    }
+
+    fn last_line(&self) -> usize {
+        self.lines.len().saturating_sub(1)
+    }
+
+    /// Builds the tree below heading `root` (`None` = whole document)
+    fn build_subtree(&self, root: Option<usize>) -> OutlineNode {
+        let (mut node, range) = match root {
+            Some(index) => {
+                let heading = &self.headings[index];
+                let end = self.headings[index + 1..]
+                    .iter()
+                    .position(|h| h.level <= heading.level)
+                    .map_or(self.headings.len(), |p| index + 1 + p);
+                let mut node = self.node(index);
+                node.stable_id = self.heading_stable_id(index);
+                (node, index + 1..end)
+            }
+            None => {
+                let body_end = self.headings.first().map_or(Some(self.last_line()), |h| h.line.checked_sub(1));
+                let node = OutlineNode {
+                    id: ROOT_ID.to_string(),
+                    stable_id: ROOT_ID.to_string(),
+                    level: 0,
+                    title: String::new(),
+                    line: 0,
+                    end_line: self.last_line(),
+                    children: Vec::new(),
+                    items: body_end.map_or_else(Vec::new, |end| self.list_items(0, end)),
+                };
+                (node, 0..self.headings.len())
+            }
+        };
+
+        // Stack of open sections; each is attached to its parent once closed
+        let mut stack: Vec<OutlineNode> = Vec::new();
+        for index in range {
+            let heading = &self.headings[index];
+            while stack.last().is_some_and(|n| n.level >= heading.level) {
+                let done = stack.pop().unwrap();
+                stack.last_mut().unwrap_or(&mut node).children.push(done);
+            }
+            stack.push(self.node(index));
+        }
+        while let Some(done) = stack.pop() {
+            stack.last_mut().unwrap_or(&mut node).children.push(done);
+        }
+        assign_stable_ids(&mut node);
+        node
+    }
+
+    /// Node of heading `index` with its list items (stable ids not yet assigned)
+    fn node(&self, index: usize) -> OutlineNode {
+        let heading = &self.headings[index];
+        let body_end = self.headings.get(index + 1).map_or(self.last_line(), |h| h.line - 1);
+        OutlineNode {
+            id: format!("h-{}", heading.id),
+            stable_id: String::new(),
+            level: heading.level,
+            title: heading.title.clone(),
+            line: heading.line,
+            end_line: self.section_end(index),
+            children: Vec::new(),
+            items: self.list_items(heading.line + 1, body_end),
+        }
+    }
+
+    /// Stable id of heading `index`, computed from its ancestors
+    fn heading_stable_id(&self, index: usize) -> String {
+        let parent = self.parent_of(index);
+        let parent_id = parent.map_or_else(|| ROOT_ID.to_string(), |p| self.heading_stable_id(p));
+        let text = normalize_text(&self.headings[index].title);
+        let occurrence = (parent.map_or(0, |p| p + 1)..index)
+            .filter(|&i| self.parent_of(i) == parent && normalize_text(&self.headings[i].title) == text)
+            .count();
+        stable_hash(&parent_id, "h", &text, occurrence)
+    }
+
+    /// Nested list items found in lines `start..=end` (stable ids not yet assigned)
+    fn list_items(&self, start: usize, end: usize) -> Vec<OutlineItem> {
+        let flat: Vec<(usize, usize, String)> = (start..=end.min(self.last_line()))
+            .filter(|&line| !self.in_block(line))
+            .filter_map(|line| parse_list_item(&self.lines[line]).map(|(indent, text)| (line, indent, text)))
+            .collect();
+
+        // Stack of open items; each is attached to its parent once closed
+        let mut roots: Vec<OutlineItem> = Vec::new();
+        let mut stack: Vec<(usize, OutlineItem)> = Vec::new();
+        for (line, indent, text) in flat {
+            // An item ends before the next line that is not indented past its marker
+            let mut item_end = (line + 1..=end)
+                .find(|&l| {
+                    let text = &self.lines[l];
+                    !text.trim().is_empty() && indent_width(text) <= indent
+                })
+                .map_or(end, |l| l - 1);
+            while item_end > line && self.lines[item_end].trim().is_empty() {
+                item_end -= 1;
+            }
+
+            while stack.last().is_some_and(|(i, _)| *i >= indent) {
+                let (_, done) = stack.pop().unwrap();
+                match stack.last_mut() {
+                    Some((_, parent)) => parent.children.push(done),
+                    None => roots.push(done),
+                }
+            }
+            let item = OutlineItem { stable_id: String::new(), text, line, end_line: item_end, children: Vec::new() };
+            stack.push((indent, item));
+        }
+        while let Some((_, done)) = stack.pop() {
+            match stack.last_mut() {
+                Some((_, parent)) => parent.children.push(done),
+                None => roots.push(done),
+            }
+        }
+        roots
+    }
+}
+
+/// Fills in the stable ids below a node whose own stable id is set
+fn assign_stable_ids(node: &mut OutlineNode) {
+    let mut seen: HashMap<String, usize> = HashMap::new();
+    for child in &mut node.children {
+        let text = normalize_text(&child.title);
+        let occurrence = seen.entry(text.clone()).or_insert(0);
+        child.stable_id = stable_hash(&node.stable_id, "h", &text, *occurrence);
+        *occurrence += 1;
+        assign_stable_ids(child);
+    }
+    assign_item_ids(&node.stable_id, &mut node.items);
+}
+
+fn assign_item_ids(parent_id: &str, items: &mut [OutlineItem]) {
+    let mut seen: HashMap<String, usize> = HashMap::new();
+    for item in items {
+        let text = normalize_text(strip_task_marker(&item.text));
+        let occurrence = seen.entry(text.clone()).or_insert(0);
+        item.stable_id = stable_hash(parent_id, "i", &text, *occurrence);
+        *occurrence += 1;
+        assign_item_ids(&item.stable_id.clone(), &mut item.children);
+    }
+}
+
+/// FNV-1a over the id parts; unlike `DefaultHasher` it never changes between builds
+fn stable_hash(parent_id: &str, kind: &str, text: &str, occurrence: usize) -> String {
+    let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+    for part in [parent_id, kind, text, &occurrence.to_string()] {
+        for byte in part.bytes().chain(std::iter::once(0x1f)) {
+            hash ^= byte as u64;
+            hash = hash.wrapping_mul(0x0100_0000_01b3);
+        }
+    }
+    format!("n-{:016x}", hash)
+}
+
+/// Case- and whitespace-insensitive form of node text
+fn normalize_text(text: &str) -> String {
+    text.split_whitespace().collect::<Vec<_>>().join(" ").to_lowercase()
+}
+
+/// Drops a task checkbox, so checking a task keeps its id
+fn strip_task_marker(text: &str) -> &str {
+    ["[ ] ", "[x] ", "[X] "]
+        .iter()
+        .find_map(|marker| text.strip_prefix(marker))
+        .unwrap_or(text)
+}
+
+/// Parsed documents keyed by absolute file path, with their last use (see
+/// `crate::memory_budget::stamp`)
+type OpenDocuments = HashMap<PathBuf, (OutlineDocument, u64)>;
+
+/// Open documents (internally synchronized)
+#[derive(Clone, Default)]
+pub struct OutlineRegistry {
+    documents: Arc<Mutex<OpenDocuments>>,
+}
+
+impl OutlineRegistry {
+    /// Creates an empty registry
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Parses a document (replacing any previous state) and returns its outline
+    pub fn open(&self, path: PathBuf, content: &str) -> Result<OutlineNode, String> {
+        let document = OutlineDocument::new(content);
+        let outline = document.outline();
+        self.lock()?.insert(path, (document, stamp()));
+        Ok(outline)
+    }
+
+    /// Applies an edit to an open document
+    pub fn apply_edit(&self, path: &Path, range: TextRange, text: &str) -> Result<OutlineUpdate, String> {
+        let mut documents = self.lock()?;
+        let (document, last_used) = documents
+            .get_mut(path)
+            .ok_or_else(|| format!("Document is not open: {}", path.display()))?;
+        *last_used = stamp();
+        document.apply_edit(range, text)
+    }
+
+    /// Returns true if the document is open
+    pub fn is_open(&self, path: &Path) -> bool {
+        self.lock().map(|docs| docs.contains_key(path)).unwrap_or(false)
+    }
+
+    /// Locates a node in an open document
+    pub fn locate(&self, path: &Path, node_id: &str) -> Result<Option<NodeLocation>, String> {
+        let mut documents = self.lock()?;
+        let (document, last_used) = documents
+            .get_mut(path)
+            .ok_or_else(|| format!("Document is not open: {}", path.display()))?;
+        *last_used = stamp();
+        Ok(document.locate(node_id))
+    }
+
+    /// Drops the state of a document; returns true if it was open
+    pub fn close(&self, path: &Path) -> Result<bool, String> {
+        Ok(self.lock()?.remove(path).is_some())
+    }
+
+    /// Open documents as seen by the memory budget (see `crate::memory_budget`)
+    pub fn cache_entries(&self) -> Result<Vec<CacheEntry>, String> {
+        Ok(self.lock()?
+            .iter()
+            .map(|(path, (document, last_used))| CacheEntry {
+                cache: CacheKind::Outlines,
+                key: path.to_string_lossy().to_string(),
+                bytes: document.approx_bytes(),
+                last_used: *last_used,
+            })
+            .collect())
+    }
+
+    fn lock(&self) -> Result<std::sync::MutexGuard<'_, OpenDocuments>, String> {
+        self.documents
+            .lock()
+            .map_err(|e| format!("Failed to lock outlines: {}", e))
+    }
+}
+
+fn split_lines(content: &str) -> Vec<String> {
+    content
+        .split('\n')
+        .map(|l| l.strip_suffix('\r').unwrap_or(l).to_string())
+        .collect()
+}
+
+fn byte_index(line: &str, column: usize) -> Result<usize, String> {
+    match line.char_indices().nth(column) {
+        Some((index, _)) => Ok(index),
+        None if column == line.chars().count() => Ok(line.len()),
+        None => Err("Edit range out of bounds".to_string()),
+    }
+}
+
+/// Parses an ATX heading (`## Title ##`)
+pub(crate) fn parse_heading(line: &str) -> Option<(u8, String)> {
+    let indent = line.len() - line.trim_start_matches(' ').len();
+    if indent > 3 {
+        return None;
+    }
+    let rest = &line[indent..];
+    let level = rest.len() - rest.trim_start_matches('#').len();
+    if !(1..=6).contains(&level) {
+        return None;
+    }
+    let rest = &rest[level..];
+    if !rest.is_empty() && !rest.starts_with([' ', '\t']) {
+        return None;
+    }
+
+    let mut title = rest.trim();
+    let without_closing = title.trim_end_matches('#');
+    if without_closing.is_empty() || without_closing.ends_with([' ', '\t']) {
+        title = without_closing.trim_end();
+    }
+    Some((level as u8, title.to_string()))
+}
+
+/// Parses a bullet or ordered list item, returning its indent and text
+fn parse_list_item(line: &str) -> Option<(usize, String)> {
+    let rest = line.trim_start();
+    let marker_len = if rest.starts_with(['-', '*', '+']) {
+        1
+    } else {
+        let digits = rest.len() - rest.trim_start_matches(|c: char| c.is_ascii_digit()).len();
+        if !(1..=9).contains(&digits) || !rest[digits..].starts_with(['.', ')']) {
+            return None;
+        }
+        digits + 1
+    };
+    let text = &rest[marker_len..];
+    if !text.is_empty() && !text.starts_with([' ', '\t']) {
+        return None;
+    }
+    Some((indent_width(line), text.trim().to_string()))
+}
+
+/// Leading whitespace width, counting a tab as four spaces
+fn indent_width(line: &str) -> usize {
+    line.chars()
+        .take_while(|c| c.is_whitespace())
+        .map(|c| if c == '\t' { 4 } else { 1 })
+        .sum()
+}
+
+/// Lines that can open or close a code fence or frontmatter block
+fn is_block_delimiter(line: &str) -> bool {
+    let trimmed = line.trim();
+    trimmed.starts_with("```") || trimmed.starts_with("~~~") || trimmed == "---" || trimmed == "..."
+}
+
+/// Finds frontmatter and fenced code blocks (inclusive line spans)
+pub(crate) fn find_blocks(lines: &[String]) -> Vec<(usize, usize)> {
+    let mut blocks = Vec::new();
+    let mut line = 0;
+
+    if lines.first().is_some_and(|l| l.trim_end() == "---") {
+        if let Some(close) = lines[1..].iter().position(|l| matches!(l.trim_end(), "---" | "...")) {
+            blocks.push((0, close + 1));
+            line = close + 2;
+        }
+    }
+
+    while line < lines.len() {
+        let trimmed = lines[line].trim_start();
+        let marker = trimmed.chars().next().filter(|c| *c == '`' || *c == '~');
+        let fence_len = marker.map_or(0, |m| trimmed.len() - trimmed.trim_start_matches(m).len());
+
+        if let (Some(marker), true) = (marker, fence_len >= 3) {
+            let close = lines[line + 1..].iter().position(|l| {
+                let t = l.trim();
+                t.len() >= fence_len && t.chars().all(|c| c == marker)
+            });
+            let end = close.map_or(lines.len() - 1, |c| line + 1 + c);
+            blocks.push((line, end));
+            line = end + 1;
+        } else {
+            line += 1;
+        }
+    }
+
+    blocks
+}
+
+// ============================================================================
+// SECTIONS
+// ============================================================================
+
+/// Lines of a heading's block (heading line excluded, subsections included)
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct SectionSpan {
+    heading_line: usize,
+    /// Last line of the block (inclusive)
+    end_line: usize,
+}
+
+impl OutlineDocument {
+    /// Finds the first heading matching `heading`
+    ///
+    /// Titles are compared case-insensitively. A `#` prefix (`"## Notes"`)
+    /// also requires the level to match.
+    fn find_section(&self, heading: &str) -> Option<SectionSpan> {
+        let (level, title) = match parse_heading(heading.trim()) {
+            Some((level, title)) if heading.trim_start().starts_with('#') => (Some(level), title),
+            _ => (None, heading.trim().to_string()),
+        };
+
+        let index = self.headings.iter().position(|h| {
+            h.title.to_lowercase() == title.to_lowercase() && level.map_or(true, |l| l == h.level)
+        })?;
+
+        Some(SectionSpan {
+            heading_line: self.headings[index].line,
+            end_line: self.section_end(index),
+        })
+    }
+}
+
+/// Reads the block below a heading, up to the next heading of the same or higher level
+pub fn read_section(content: &str, heading: &str) -> Result<String, String> {
+    let doc = OutlineDocument::new(content);
+    let span = doc.find_section(heading)
+        .ok_or_else(|| format!("Section not found: {}", heading))?;
+
+    let body = &doc.lines[span.heading_line + 1..=span.end_line.max(span.heading_line)];
+    Ok(body.join("\n").trim_matches('\n').to_string())
+}
+
+/// Reads a heading's line together with its block (as embedded by `![[note#heading]]`)
+pub fn read_section_with_heading(content: &str, heading: &str) -> Result<String, String> {
+    let doc = OutlineDocument::new(content);
+    let span = doc.find_section(heading)
+        .ok_or_else(|| format!("Section not found: {}", heading))?;
+
+    Ok(doc.lines[span.heading_line..=span.end_line].join("\n").trim_end().to_string())
+}
+
+/// Replaces the block below a heading, keeping the heading line and the
+/// blank lines that separate the block from the next section
+pub fn write_section(content: &str, heading: &str, body: &str) -> Result<String, String> {
+    let doc = OutlineDocument::new(content);
+    let span = doc.find_section(heading)
+        .ok_or_else(|| format!("Section not found: {}", heading))?;
+
+    let old_body = &doc.lines[span.heading_line + 1..=span.end_line.max(span.heading_line)];
+    let trailing_blank = old_body.iter().rev().take_while(|l| l.trim().is_empty()).count();
+    let at_end = span.end_line == doc.last_line();
+
+    let mut lines: Vec<String> = doc.lines[..=span.heading_line].to_vec();
+    let body = body.trim_matches('\n');
+    if !body.is_empty() {
+        lines.extend(split_lines(body));
+    }
+    // Keep the separation before the next heading, or the final newline
+    let keep = if at_end { trailing_blank.min(1) } else { trailing_blank.max(1) };
+    lines.extend(std::iter::repeat(String::new()).take(keep));
+    lines.extend(doc.lines[span.end_line + 1..].iter().cloned());
+
+    Ok(lines.join("\n"))
+}
+
+/// A heading section cut out of a document
+#[derive(Debug, Clone, PartialEq)]
+pub struct CutSection {
+    /// Titles of the headings in the section, the cut heading first
+    pub headings: Vec<String>,
+    /// The section, with headings raised so the cut heading is level 1
+    pub markdown: String,
+    /// The document with the section replaced
+    pub remaining: String,
+}
+
+/// Cuts the section of a heading (found by `stable_id` or `id`) out of a
+/// document, putting `replacement` in its place
+pub fn cut_section(content: &str, node_id: &str, replacement: &str) -> Result<CutSection, String> {
+    let doc = OutlineDocument::new(content);
+    let location = doc.locate(node_id)
+        .ok_or_else(|| format!("Node not found: {}", node_id))?;
+    let index = doc.headings.iter().position(|h| h.line == location.line)
+        .ok_or_else(|| format!("Node is not a heading: {}", node_id))?;
+
+    let (start, end) = (location.line, doc.section_end(index));
+    let level = doc.headings[index].level;
+    let section_headings: Vec<&Heading> = doc.headings[index..]
+        .iter()
+        .take_while(|h| h.line <= end)
+        .collect();
+
+    let mut extracted: Vec<String> = Vec::new();
+    let mut headings = section_headings.iter().peekable();
+    for (line, text) in doc.lines.iter().enumerate().take(end + 1).skip(start) {
+        if headings.next_if(|h| h.line == line).is_some() {
+            // Drop `level - 1` of the heading's `#` marks
+            let hash = text.find('#').unwrap_or(0);
+            extracted.push(format!("{}{}", &text[..hash], &text[hash + level as usize - 1..]));
+        } else {
+            extracted.push(text.clone());
+        }
+    }
+    let trailing_blank = extracted.iter().rev().take_while(|l| l.trim().is_empty()).count();
+    extracted.truncate(extracted.len() - trailing_blank);
+
+    // Keep the separation before the next heading, or the final newline
+    let at_end = end == doc.last_line();
+    let keep = if at_end { trailing_blank.min(1) } else { trailing_blank.max(1) };
+    let mut remaining: Vec<String> = doc.lines[..start].to_vec();
+    remaining.extend(split_lines(replacement));
+    remaining.extend(std::iter::repeat(String::new()).take(keep));
+    remaining.extend(doc.lines[end + 1..].iter().cloned());
+
+    Ok(CutSection {
+        headings: section_headings.iter().map(|h| h.title.clone()).collect(),
+        markdown: format!("{}\n", extracted.join("\n")),
+        remaining: remaining.join("\n"),
+    })
+}
+
+// ============================================================================
+// TESTS
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DOC: &str = "# Plan\nintro\n## Goals\n- ship\n## Risks\n```\n# not a heading\n```\n# Notes\n";
+
+    fn at(line: usize, column: usize) -> TextPosition {
+        TextPosition { line, column }
+    }
+
+    fn edit(sl: usize, sc: usize, el: usize, ec: usize) -> TextRange {
+        TextRange { start: at(sl, sc), end: at(el, ec) }
+    }
+
+    fn titles(node: &OutlineNode) -> Vec<String> {
+        node.children.iter().map(|c| c.title.clone()).collect()
+    }
+
+    fn strip_ids(mut node: OutlineNode) -> OutlineNode {
+        node.id.clear();
+        node.children = node.children.into_iter().map(strip_ids).collect();
+        node
+    }
+
+    #[test]
+    fn test_parse_outline() {
+        let outline = OutlineDocument::new(DOC).outline();
+
+        assert_eq!(titles(&outline), vec!["Plan", "Notes"]);
+        let plan = &outline.children[0];
+        assert_eq!(titles(plan), vec!["Goals", "Risks"]);
+        assert_eq!((plan.line, plan.end_line), (0, 7));
+        assert!(plan.children[1].children.is_empty());
+    }
+
+    #[test]
+    fn test_body_edit_returns_enclosing_section() {
+        let mut doc = OutlineDocument::new(DOC);
+        let goals_id = doc.outline().children[0].children[0].id.clone();
+
+        let update = doc.apply_edit(edit(3, 6, 3, 6), "\n- test").unwrap();
+
+        assert_eq!(update.subtree.id, goals_id);
+        assert_eq!((update.subtree.line, update.subtree.end_line), (2, 4));
+        assert_eq!((update.edit_end_line, update.line_delta), (3, 1));
+        assert_eq!(doc.outline().children[1].line, 9);
+    }
+
+    #[test]
+    fn test_title_edit_keeps_node_id() {
+        let mut doc = OutlineDocument::new(DOC);
+        let risks_id = doc.outline().children[0].children[1].id.clone();
+
+        let update = doc.apply_edit(edit(4, 3, 4, 8), "Open risks").unwrap();
+
+        assert_eq!(update.subtree.id, risks_id);
+        assert_eq!(update.subtree.title, "Open risks");
+    }
+
+    #[test]
+    fn test_new_heading_reparents_following_sections() {
+        let mut doc = OutlineDocument::new(DOC);
+        let plan_id = doc.outline().children[0].id.clone();
+        let goals_id = doc.outline().children[0].children[0].id.clone();
+
+        // Demoting "Goals" puts it under the new "Scope" section
+        let update = doc.apply_edit(edit(2, 0, 2, 2), "## Scope\n###").unwrap();
+
+        assert_eq!(update.subtree.id, plan_id);
+        assert_eq!(titles(&update.subtree), vec!["Scope", "Risks"]);
+        assert_eq!(titles(&update.subtree.children[0]), vec!["Goals"]);
+        assert_eq!(update.subtree.children[0].children[0].id, goals_id);
+        assert_eq!(strip_ids(doc.outline()), strip_ids(OutlineDocument::new(&doc.lines.join("\n")).outline()));
+    }
+
+    #[test]
+    fn test_fence_edit_falls_back_to_full_parse() {
+        let mut doc = OutlineDocument::new(DOC);
+
+        // Removing the closing fence turns "# Notes" into code
+        let update = doc.apply_edit(edit(7, 0, 7, 3), "").unwrap();
+
+        assert_eq!(update.subtree.id, ROOT_ID);
+        assert_eq!(titles(&update.subtree), vec!["Plan"]);
+    }
+
+    #[test]
+    fn test_stable_ids_survive_edits_and_reparse() {
+        let mut doc = OutlineDocument::new(DOC);
+        let goals = doc.outline().children[0].children[0].clone();
+        let notes_id = doc.outline().children[1].stable_id.clone();
+
+        // Same content parsed in another session gets the same ids
+        assert_eq!(OutlineDocument::new(DOC).outline().children[0].children[0].stable_id, goals.stable_id);
+
+        doc.apply_edit(edit(1, 0, 1, 0), "more intro\n\n").unwrap();
+        let location = doc.locate(&goals.stable_id).unwrap();
+        assert_eq!((location.text.as_str(), location.line, location.end_line), ("Goals", 4, 5));
+        assert_eq!(doc.locate(&notes_id).unwrap().line, 10);
+        assert!(doc.locate("n-missing").is_none());
+    }
+
+    #[test]
+    fn test_list_items_and_duplicate_titles() {
+        let note = "# Tasks\n- [ ] Ship\n  - Write docs\n\n  more about docs\n- Ship\n\nAfter list\n## Ship\n## Ship\n";
+        let outline = OutlineDocument::new(note).outline();
+        let tasks = &outline.children[0];
+
+        assert_eq!(tasks.items.len(), 2);
+        let first = &tasks.items[0];
+        assert_eq!((first.text.as_str(), first.line, first.end_line), ("[ ] Ship", 1, 4));
+        assert_eq!((first.children[0].line, first.children[0].end_line), (2, 2));
+        assert_eq!(tasks.items[1].end_line, 5);
+
+        // Same text under one parent is told apart by occurrence
+        assert_ne!(tasks.items[0].stable_id, tasks.items[1].stable_id);
+        assert_ne!(tasks.children[0].stable_id, tasks.children[1].stable_id);
+
+        // Checking a task keeps its id
+        let checked = OutlineDocument::new(&note.replace("[ ]", "[x]")).outline();
+        assert_eq!(checked.children[0].items[0].stable_id, first.stable_id);
+    }
+
+    #[test]
+    fn test_cut_section() {
+        let note = "# Plan\nintro\n## Goals\n- ship\n### Stretch\n- docs\n\n## Risks\n- none\n";
+        let goals = OutlineDocument::new(note).outline().children[0].children[0].stable_id.clone();
+
+        let cut = cut_section(note, &goals, "![[Goals]]").unwrap();
+        assert_eq!(cut.headings, vec!["Goals", "Stretch"]);
+        assert_eq!(cut.markdown, "# Goals\n- ship\n## Stretch\n- docs\n");
+        assert_eq!(cut.remaining, "# Plan\nintro\n![[Goals]]\n\n## Risks\n- none\n");
+
+        let item = OutlineDocument::new(note).outline().children[0].children[0].items[0].stable_id.clone();
+        assert!(cut_section(note, &item, "").is_err());
+    }
+
+    #[test]
+    fn test_edit_out_of_bounds() {
+        let mut doc = OutlineDocument::new("# A");
+        assert!(doc.apply_edit(edit(0, 0, 3, 0), "x").is_err());
+        assert!(doc.apply_edit(edit(0, 9, 0, 9), "x").is_err());
+    }
+
+    #[test]
+    fn test_parse_heading() {
+        assert_eq!(parse_heading("## Title ##"), Some((2, "Title".to_string())));
+        assert_eq!(parse_heading("#"), Some((1, String::new())));
+        assert_eq!(parse_heading("#hashtag"), None);
+        assert_eq!(parse_heading("    # code"), None);
+        assert_eq!(parse_heading("####### seven"), None);
+    }
+
+    #[test]
+    fn test_read_section() {
+        let note = "# Day\n## Meeting Notes\n- standup\n### Follow-ups\n- mail\n\n## Todo\n- a\n";
+
+        assert_eq!(read_section(note, "meeting notes").unwrap(), "- standup\n### Follow-ups\n- mail");
+        assert_eq!(read_section(note, "## Todo").unwrap(), "- a");
+        assert!(read_section(note, "### Todo").is_err());
+        assert!(read_section(note, "Missing").is_err());
+    }
+
+    #[test]
+    fn test_write_section() {
+        let note = "# Day\n## Meeting Notes\n- standup\n\n## Todo\n- a\n";
+
+        assert_eq!(
+            write_section(note, "Meeting Notes", "- standup\n- retro\n").unwrap(),
+            "# Day\n## Meeting Notes\n- standup\n- retro\n\n## Todo\n- a\n"
+        );
+        assert_eq!(
+            write_section(note, "Todo", "- b").unwrap(),
+            "# Day\n## Meeting Notes\n- standup\n\n## Todo\n- b\n"
+        );
+        assert_eq!(
+            write_section("## A\n## B", "A", "text").unwrap(),
+            "## A\ntext\n\n## B"
+        );
+    }
+}
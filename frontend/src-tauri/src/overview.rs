@@ -0,0 +1,231 @@
+//! Workspace Overview Statistics
+//!
+//! Summarizes a workspace for the dashboard view: totals, notes created and
+//! modified per week, the largest and most-linked notes, tag distribution
+//! and how much space assets take. Everything is computed from the metadata
+//! cache (see `crate::metadata_cache`), so no note is read here.
+
+use std::collections::{BTreeMap, HashMap};
+use chrono::{DateTime, Datelike, Duration, Utc};
+use serde::Serialize;
+use crate::metadata_cache::{CachedNote, MetadataCache};
+
+/// Number of weeks reported in the activity histogram (current week included)
+pub const OVERVIEW_WEEKS: usize = 12;
+
+/// Number of notes in the largest and most-linked lists
+pub const OVERVIEW_TOP: usize = 10;
+
+/// Notes created and modified in one week
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct WeekActivity {
+    /// Monday the week starts on (`YYYY-MM-DD`, UTC)
+    pub week_start: String,
+    pub created: usize,
+    pub modified: usize,
+}
+
+/// A note in a top list
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct RankedNote {
+    pub path: String,
+    pub title: String,
+    /// Words (largest notes) or notes linking here (most-linked notes)
+    pub value: usize,
+}
+
+/// Number of notes with a tag
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct TagCount {
+    pub tag: String,
+    pub count: usize,
+}
+
+/// Space taken by files of one extension
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct AssetKindUsage {
+    pub files: usize,
+    pub bytes: u64,
+}
+
+/// Space taken by non-note files
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct AssetUsage {
+    pub files: usize,
+    pub bytes: u64,
+    /// Usage per lowercase extension (`""` for files without one)
+    pub by_extension: BTreeMap<String, AssetKindUsage>,
+}
+
+/// Result of `get_workspace_overview`
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct WorkspaceOverview {
+    pub total_notes: usize,
+    pub total_words: usize,
+    /// Oldest week first
+    pub weeks: Vec<WeekActivity>,
+    /// By word count, largest first
+    pub largest_notes: Vec<RankedNote>,
+    /// By backlinks, most linked first
+    pub most_linked_notes: Vec<RankedNote>,
+    /// Most used first
+    pub tags: Vec<TagCount>,
+    pub assets: AssetUsage,
+}
+
+impl WorkspaceOverview {
+    /// Computes the overview of a cache at `now_ms` (ms since the epoch)
+    pub fn from_cache(cache: &MetadataCache, now_ms: i64) -> Self {
+        let notes: Vec<(&String, &CachedNote)> = cache.notes.iter().collect();
+
+        let mut largest: Vec<RankedNote> = notes
+            .iter()
+            .map(|(path, note)| RankedNote { path: path.to_string(), title: note.title.clone(), value: note.words })
+            .collect();
+        rank(&mut largest);
+
+        let index = cache.link_index();
+        let mut most_linked: Vec<RankedNote> = (0..index.paths.len())
+            .filter(|&i| !index.incoming[i].is_empty())
+            .map(|i| RankedNote {
+                path: index.paths[i].clone(),
+                title: index.titles[i].clone(),
+                value: index.incoming[i].len(),
+            })
+            .collect();
+        rank(&mut most_linked);
+
+        let mut tag_counts: HashMap<&str, usize> = HashMap::new();
+        for (_, note) in &notes {
+            for tag in &note.tags {
+                *tag_counts.entry(tag.as_str()).or_default() += 1;
+            }
+        }
+        let mut tags: Vec<TagCount> = tag_counts
+            .into_iter()
+            .map(|(tag, count)| TagCount { tag: tag.to_string(), count })
+            .collect();
+        tags.sort_by(|a, b| b.count.cmp(&a.count).then_with(|| a.tag.cmp(&b.tag)));
+
+        let mut assets = AssetUsage::default();
+        for (path, asset) in &cache.assets {
+            let extension = path
+                .rsplit_once('/')
+                .map_or(path.as_str(), |(_, name)| name)
+                .rsplit_once('.')
+                .map(|(_, ext)| ext.to_lowercase())
+                .unwrap_or_default();
+            let usage = assets.by_extension.entry(extension).or_default();
+            usage.files += 1;
+            usage.bytes += asset.size;
+            assets.files += 1;
+            assets.bytes += asset.size;
+        }
+
+        Self {
+            total_notes: notes.len(),
+            total_words: notes.iter().map(|(_, note)| note.words).sum(),
+            weeks: weekly_activity(cache, now_ms),
+            largest_notes: largest,
+            most_linked_notes: most_linked,
+            tags,
+            assets,
+        }
+    }
+}
+
+// Helper: Sorts a top list by value (then path) and keeps the top entries
+fn rank(notes: &mut Vec<RankedNote>) {
+    notes.sort_by(|a, b| b.value.cmp(&a.value).then_with(|| a.path.cmp(&b.path)));
+    notes.truncate(OVERVIEW_TOP);
+}
+
+// Helper: Counts notes created and modified in each of the last weeks
+fn weekly_activity(cache: &MetadataCache, now_ms: i64) -> Vec<WeekActivity> {
+    let Some(now) = DateTime::<Utc>::from_timestamp_millis(now_ms) else {
+        return Vec::new();
+    };
+    let this_monday = now.date_naive() - Duration::days(now.weekday().num_days_from_monday() as i64);
+    let first_monday = this_monday - Duration::weeks(OVERVIEW_WEEKS as i64 - 1);
+    let first_ms = first_monday.and_hms_opt(0, 0, 0).map_or(i64::MAX, |t| t.and_utc().timestamp_millis());
+
+    let mut weeks: Vec<WeekActivity> = (0..OVERVIEW_WEEKS)
+        .map(|i| WeekActivity {
+            week_start: (first_monday + Duration::weeks(i as i64)).format("%Y-%m-%d").to_string(),
+            created: 0,
+            modified: 0,
+        })
+        .collect();
+
+    let week_of = |ms: i64| -> Option<usize> {
+        if ms < first_ms || ms > now_ms {
+            return None;
+        }
+        Some(((ms - first_ms) / Duration::weeks(1).num_milliseconds()) as usize).filter(|&w| w < OVERVIEW_WEEKS)
+    };
+    for note in cache.notes.values() {
+        if let Some(week) = note.created_ms.and_then(week_of) {
+            weeks[week].created += 1;
+        }
+        if let Some(week) = week_of(note.modified_ms) {
+            weeks[week].modified += 1;
+        }
+    }
+    weeks
+}
+
+// ============================================================================
+// TESTS
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::links::NoteLinks;
+    use crate::metadata_cache::CachedAsset;
+
+    const DAY_MS: i64 = 86_400_000;
+    /// Wednesday, 2023-11-15 00:00 UTC
+    const NOW: i64 = 1_700_006_400_000;
+
+    fn note(words: usize, tags: &[&str], wiki: &[&str], modified_ms: i64) -> CachedNote {
+        CachedNote {
+            modified_ms,
+            created_ms: Some(modified_ms),
+            title: String::new(),
+            tags: tags.iter().map(|t| t.to_string()).collect(),
+            words,
+            links: NoteLinks { wiki: wiki.iter().map(|t| t.to_string()).collect(), relative: Vec::new() },
+            ..CachedNote::default()
+        }
+    }
+
+    #[test]
+    fn test_overview_from_cache() {
+        let mut cache = MetadataCache::default();
+        cache.notes.insert("hub.md".into(), note(10, &["rust"], &[], NOW));
+        cache.notes.insert("a.md".into(), note(300, &["rust", "web"], &["hub"], NOW - 8 * DAY_MS));
+        cache.notes.insert("b.md".into(), note(50, &[], &["hub", "a"], NOW - 200 * DAY_MS));
+        cache.assets.insert("assets/x.PNG".into(), CachedAsset { modified_ms: NOW, size: 100 });
+        cache.assets.insert("assets/y.png".into(), CachedAsset { modified_ms: NOW, size: 50 });
+
+        let overview = WorkspaceOverview::from_cache(&cache, NOW);
+
+        assert_eq!(overview.total_notes, 3);
+        assert_eq!(overview.total_words, 360);
+        assert_eq!(overview.largest_notes[0].path, "a.md");
+        assert_eq!(overview.most_linked_notes[0].path, "hub.md");
+        assert_eq!(overview.most_linked_notes[0].value, 2);
+        assert_eq!(overview.tags[0], TagCount { tag: "rust".into(), count: 2 });
+        assert_eq!(overview.assets.bytes, 150);
+        assert_eq!(overview.assets.by_extension["png"].files, 2);
+
+        assert_eq!(overview.weeks.len(), OVERVIEW_WEEKS);
+        let last = overview.weeks.last().unwrap();
+        assert_eq!(last.week_start, "2023-11-13");
+        assert_eq!((last.created, last.modified), (1, 1));
+        assert_eq!(overview.weeks[OVERVIEW_WEEKS - 2].modified, 1);
+        // Notes older than the histogram are not counted
+        assert_eq!(overview.weeks.iter().map(|w| w.modified).sum::<usize>(), 2);
+    }
+}
@@ -0,0 +1,154 @@
+//! Keyset Paging of Directory Listings
+//!
+//! A folder with tens of thousands of notes cannot be sent to the webview in
+//! one response on a phone. Listings are paged by sort key instead of by
+//! offset: the cursor is the key of the last entry of the previous page, and
+//! each page is selected in one streaming pass over the directory that only
+//! keeps the `page_size` smallest keys after the cursor in a bounded heap.
+//! Memory stays proportional to the page, not the folder, and entries added
+//! or removed between two pages never shift the pages that follow.
+//!
+//! Entries sort like `list_workspace_contents`: folders first, then by name.
+
+use std::cmp::Ordering;
+use std::collections::BinaryHeap;
+
+/// Page size used when the caller does not specify one
+pub const DEFAULT_PAGE_SIZE: usize = 200;
+
+/// Upper bound for a caller-supplied page size
+pub const MAX_PAGE_SIZE: usize = 1000;
+
+/// Sort key of a listed entry
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PageKey {
+    pub is_directory: bool,
+    pub name: String,
+}
+
+impl Ord for PageKey {
+    fn cmp(&self, other: &Self) -> Ordering {
+        // Folders first
+        other.is_directory
+            .cmp(&self.is_directory)
+            .then_with(|| self.name.cmp(&other.name))
+    }
+}
+
+impl PartialOrd for PageKey {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl PageKey {
+    /// Opaque cursor pointing after this key
+    pub fn to_cursor(&self) -> String {
+        format!("{}:{}", if self.is_directory { 'd' } else { 'f' }, self.name)
+    }
+
+    /// Parses a cursor made by `to_cursor`
+    pub fn from_cursor(cursor: &str) -> Result<Self, String> {
+        let (kind, name) = cursor
+            .split_once(':')
+            .ok_or_else(|| format!("Invalid cursor: {}", cursor))?;
+        let is_directory = match kind {
+            "d" => true,
+            "f" => false,
+            _ => return Err(format!("Invalid cursor: {}", cursor)),
+        };
+        Ok(Self { is_directory, name: name.to_string() })
+    }
+}
+
+/// Clamps a caller-supplied page size to `1..=MAX_PAGE_SIZE`
+pub fn page_size(requested: Option<usize>) -> usize {
+    requested.unwrap_or(DEFAULT_PAGE_SIZE).clamp(1, MAX_PAGE_SIZE)
+}
+
+/// Keys of one page, selected from a stream of keys
+#[derive(Debug, Clone, PartialEq)]
+pub struct PageSelection {
+    /// Keys of the page, in order
+    pub keys: Vec<PageKey>,
+    /// True if more keys follow the page
+    pub has_more: bool,
+    /// Number of keys in the whole stream
+    pub total: usize,
+}
+
+/// Selects the `size` smallest keys after `after` from `keys`
+///
+/// Keeps at most `size + 1` keys in memory, however long the stream is.
+pub fn select_page(keys: impl Iterator<Item = PageKey>, after: Option<&PageKey>, size: usize) -> PageSelection {
+    // Max-heap: the largest kept key is dropped first
+    let mut heap = BinaryHeap::with_capacity(size + 2);
+    let mut total = 0;
+    for key in keys {
+        total += 1;
+        if after.is_some_and(|after| key <= *after) {
+            continue;
+        }
+        if heap.len() <= size {
+            heap.push(key);
+        } else if heap.peek().is_some_and(|largest| key < *largest) {
+            heap.pop();
+            heap.push(key);
+        }
+    }
+
+    let mut keys = heap.into_sorted_vec();
+    let has_more = keys.len() > size;
+    keys.truncate(size);
+    PageSelection { keys, has_more, total }
+}
+
+// ============================================================================
+// TESTS
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(is_directory: bool, name: &str) -> PageKey {
+        PageKey { is_directory, name: name.to_string() }
+    }
+
+    #[test]
+    fn test_pages_cover_stream_in_order() {
+        let keys: Vec<PageKey> = (0..25)
+            .map(|i| key(i % 5 == 0, &format!("note-{:02}.md", (i * 7) % 25)))
+            .collect();
+        let mut expected = keys.clone();
+        expected.sort();
+
+        let mut seen = Vec::new();
+        let mut cursor: Option<PageKey> = None;
+        loop {
+            let page = select_page(keys.clone().into_iter(), cursor.as_ref(), 4);
+            assert_eq!(page.total, 25);
+            assert!(page.keys.len() <= 4);
+            seen.extend(page.keys.iter().cloned());
+            if !page.has_more {
+                break;
+            }
+            let last = page.keys.last().unwrap().to_cursor();
+            cursor = Some(PageKey::from_cursor(&last).unwrap());
+        }
+        assert_eq!(seen, expected);
+        assert!(seen[0].is_directory, "Folders should come first");
+    }
+
+    #[test]
+    fn test_cursor_roundtrip() {
+        let folder = key(true, "a:b");
+        assert_eq!(PageKey::from_cursor(&folder.to_cursor()).unwrap(), folder);
+        assert!(PageKey::from_cursor("x:name").is_err());
+        assert!(PageKey::from_cursor("name").is_err());
+
+        assert_eq!(page_size(None), DEFAULT_PAGE_SIZE);
+        assert_eq!(page_size(Some(0)), 1);
+        assert_eq!(page_size(Some(1_000_000)), MAX_PAGE_SIZE);
+    }
+}
@@ -0,0 +1,250 @@
+//! Paste Cleanup
+//!
+//! Turns clipboard content into markdown for the editor's paste handler:
+//!
+//! - HTML (from browsers, word processors) is converted to markdown with
+//!   `convert::html_to_markdown`
+//! - Tracking parameters (`utm_*`, `fbclid`, `gclid` ...) are removed from
+//!   web URLs
+//! - Curly quotes become straight quotes
+//! - Links to files inside the workspace (`file:///...` or absolute paths)
+//!   become relative to the note pasted into, or vault-absolute (`/...`)
+//!   without one
+//!
+//! Each step can be turned off with `PasteOptions`.
+
+use std::path::Path;
+use serde::{Deserialize, Serialize};
+use crate::convert::html_to_markdown;
+use crate::links::{format_markdown_target, percent_decode, rewrite_links, LinkKind};
+use crate::workspace_settings::relative_between;
+
+/// Query parameters that only track where a visitor came from
+const TRACKING_PARAMS: &[&str] = &[
+    "fbclid", "gclid", "gclsrc", "dclid", "gbraid", "wbraid", "msclkid", "yclid", "twclid", "igshid",
+    "mc_cid", "mc_eid", "_hsenc", "_hsmi", "mkt_tok", "oly_anon_id", "oly_enc_id", "vero_id", "ref_src",
+];
+
+/// Steps of `clean_pasted_content`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PasteOptions {
+    /// Whether the input is HTML; detected if not set
+    pub html: Option<bool>,
+    pub strip_tracking: bool,
+    pub straighten_quotes: bool,
+    pub relative_links: bool,
+}
+
+impl Default for PasteOptions {
+    fn default() -> Self {
+        Self { html: None, strip_tracking: true, straighten_quotes: true, relative_links: true }
+    }
+}
+
+/// Result of `clean_pasted_content`
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct CleanedPaste {
+    /// Markdown to insert
+    pub content: String,
+    /// The input was converted from HTML
+    pub converted_html: bool,
+    /// Tracking parameters removed
+    pub tracking_removed: usize,
+    /// Curly quotes straightened
+    pub quotes_straightened: usize,
+    /// File links made relative
+    pub links_relativized: usize,
+}
+
+/// Cleans up pasted content
+///
+/// # Arguments
+/// * `workspace` - Root of the workspace, for file links
+/// * `note` - Workspace-relative path of the note pasted into
+pub fn clean_pasted_content(input: &str, options: &PasteOptions, workspace: &Path, note: Option<&str>) -> CleanedPaste {
+    let mut cleaned = CleanedPaste {
+        converted_html: options.html.unwrap_or_else(|| looks_like_html(input)),
+        ..Default::default()
+    };
+    let mut content = if cleaned.converted_html {
+        // Block breaks around a fragment would end up in the middle of a line
+        html_to_markdown(input).trim_matches('\n').to_string()
+    } else {
+        input.to_string()
+    };
+
+    if options.strip_tracking {
+        content = strip_tracking(&content, &mut cleaned.tracking_removed);
+    }
+    if options.straighten_quotes {
+        content = content
+            .chars()
+            .map(|c| match c {
+                '\u{201C}' | '\u{201D}' | '\u{201E}' | '\u{201F}' => {
+                    cleaned.quotes_straightened += 1;
+                    '"'
+                }
+                '\u{2018}' | '\u{2019}' | '\u{201A}' | '\u{201B}' => {
+                    cleaned.quotes_straightened += 1;
+                    '\''
+                }
+                c => c,
+            })
+            .collect();
+    }
+    if options.relative_links {
+        content = rewrite_links(&content, &mut |kind, raw| {
+            let link = (kind == LinkKind::Markdown).then(|| relative_file_link(raw, workspace, note)).flatten();
+            cleaned.links_relativized += usize::from(link.is_some());
+            link
+        });
+    }
+
+    cleaned.content = content;
+    cleaned
+}
+
+// Helper: Whether pasted text is an HTML fragment rather than text
+fn looks_like_html(input: &str) -> bool {
+    let trimmed = input.trim_start();
+    let lower = trimmed.to_lowercase();
+    trimmed.starts_with('<') && ["</", "<br", "<img", "<meta"].iter().any(|tag| lower.contains(tag))
+}
+
+// Helper: Removes tracking parameters from the web URLs in a text
+fn strip_tracking(text: &str, removed: &mut usize) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = ["https://", "http://"].iter().filter_map(|scheme| rest.find(scheme)).min() {
+        out.push_str(&rest[..start]);
+        let end = url_end(&rest[start..]);
+        out.push_str(&clean_url(&rest[start..start + end], removed));
+        rest = &rest[start + end..];
+    }
+    out.push_str(rest);
+    out
+}
+
+// Helper: Length of the URL at the start of a text (stops at whitespace,
+// quotes, brackets and a `)` it did not open)
+fn url_end(text: &str) -> usize {
+    let mut depth = 0usize;
+    for (i, c) in text.char_indices() {
+        match c {
+            '(' => depth += 1,
+            ')' if depth == 0 => return i,
+            ')' => depth -= 1,
+            c if c.is_whitespace() || matches!(c, '<' | '>' | '"' | ']' | '`') => return i,
+            _ => {}
+        }
+    }
+    text.len()
+}
+
+fn clean_url(url: &str, removed: &mut usize) -> String {
+    let (base, fragment) = url.split_once('#').map_or((url, None), |(base, fragment)| (base, Some(fragment)));
+    let Some((path, query)) = base.split_once('?') else {
+        return url.to_string();
+    };
+
+    let params: Vec<&str> = query.split('&').filter(|p| !p.is_empty()).collect();
+    let kept: Vec<&str> = params.iter().copied().filter(|param| !is_tracking(param)).collect();
+    if kept.len() == params.len() {
+        return url.to_string();
+    }
+    *removed += params.len() - kept.len();
+
+    let mut cleaned = path.to_string();
+    if !kept.is_empty() {
+        cleaned.push('?');
+        cleaned.push_str(&kept.join("&"));
+    }
+    if let Some(fragment) = fragment {
+        cleaned.push('#');
+        cleaned.push_str(fragment);
+    }
+    cleaned
+}
+
+fn is_tracking(param: &str) -> bool {
+    let key = param.split('=').next().unwrap_or(param).to_lowercase();
+    key.starts_with("utm_") || TRACKING_PARAMS.contains(&key.as_str())
+}
+
+// Helper: The target of a markdown link to a file in the workspace, made
+// relative to `note`; `None` for any other link
+fn relative_file_link(raw: &str, workspace: &Path, note: Option<&str>) -> Option<String> {
+    let raw = raw.trim();
+    let (target, suffix) = match raw.strip_prefix('<') {
+        Some(inner) => inner.split_once('>')?,
+        None => raw.split_once(' ').map_or((raw, ""), |(target, _)| (target, &raw[target.len()..])),
+    };
+    let (path, anchor) = target.split_once('#').map_or((target, None), |(path, anchor)| (path, Some(anchor)));
+
+    let path = match path.strip_prefix("file://") {
+        Some(url) => {
+            let url = url.strip_prefix("localhost").unwrap_or(url);
+            // `/C:/...` on Windows
+            let url = if url.as_bytes().get(2) == Some(&b':') { &url[1..] } else { url };
+            percent_decode(url)
+        }
+        None => percent_decode(path),
+    };
+    let relative: Vec<String> = Path::new(&path)
+        .strip_prefix(workspace)
+        .ok()?
+        .components()
+        .map(|c| c.as_os_str().to_string_lossy().to_string())
+        .collect();
+    if relative.is_empty() || !Path::new(&path).is_absolute() {
+        return None;
+    }
+
+    let relative = relative.join("/");
+    let path = match note {
+        Some(note) => relative_between(note.rsplit_once('/').map_or("", |(folder, _)| folder), &relative),
+        None => format!("/{}", relative),
+    };
+    Some(format_markdown_target(&path, anchor, suffix))
+}
+
+// ============================================================================
+// TESTS
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_clean_pasted_html() {
+        let html = "<meta charset=\"utf-8\"><p>Read \u{201C}the <b>guide</b>\u{201D} at <a href=\"https://example.com/guide?id=7&amp;utm_source=news&amp;fbclid=abc#setup\">example</a>.</p>";
+        let cleaned = clean_pasted_content(html, &PasteOptions::default(), Path::new("/notes"), None);
+        assert_eq!(cleaned.content, "Read \"the **guide**\" at [example](https://example.com/guide?id=7#setup).");
+        assert_eq!((cleaned.converted_html, cleaned.tracking_removed, cleaned.quotes_straightened), (true, 2, 2));
+
+        // Plain text stays text; a bare URL loses its `?` with its last parameter
+        let text = "It\u{2019}s <b> here: https://example.com/a_(b)?utm_medium=x) ok";
+        let cleaned = clean_pasted_content(text, &PasteOptions::default(), Path::new("/notes"), None);
+        assert_eq!(cleaned.content, "It's <b> here: https://example.com/a_(b)) ok");
+        assert!(!cleaned.converted_html);
+
+        let options = PasteOptions { strip_tracking: false, straighten_quotes: false, ..Default::default() };
+        assert_eq!(clean_pasted_content(text, &options, Path::new("/notes"), None).content, text);
+    }
+
+    #[test]
+    fn test_relative_file_links() {
+        let text = "See [plan](file:///home/me/notes/projects/plan.md#goals) and ![photo](</home/me/notes/assets/my photo.png> \"Trip\"), not [other](file:///tmp/x.md) or [vault](/projects/plan.md)\n\n```\n[code](file:///home/me/notes/a.md)\n```\n";
+        let workspace = Path::new("/home/me/notes");
+
+        let cleaned = clean_pasted_content(text, &PasteOptions::default(), workspace, Some("journal/2024/today.md"));
+        assert_eq!(cleaned.links_relativized, 2);
+        assert!(cleaned.content.starts_with("See [plan](../../projects/plan.md#goals) and ![photo](<../../assets/my photo.png> \"Trip\"), not [other](file:///tmp/x.md) or [vault](/projects/plan.md)\n"));
+        assert!(cleaned.content.contains("[code](file:///home/me/notes/a.md)"), "Code is left alone");
+
+        let cleaned = clean_pasted_content(text, &PasteOptions::default(), workspace, None);
+        assert!(cleaned.content.starts_with("See [plan](/projects/plan.md#goals) and ![photo](</assets/my photo.png> \"Trip\")"));
+    }
+}
@@ -0,0 +1,206 @@
+//! PDF Attachments
+//!
+//! Text extraction and page previews for PDFs attached to notes, so they can
+//! be indexed for full-text search and shown as cards without a PDF reader in
+//! the webview:
+//!
+//! - Text is extracted per page with `pdf-extract` and cached as JSON under
+//!   `<cache>/mdreader/pdf_text/`
+//! - Pages are rendered with PDFium into the thumbnail cache. The PDFium
+//!   library is looked up next to the executable, then on the system.
+//!
+//! Both caches are keyed like thumbnails (source path, modification time), so
+//! a replaced PDF is processed again.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use serde::{Deserialize, Serialize};
+use pdfium_render::prelude::*;
+use crate::thumbnails::{self, Thumbnail, MAX_SIZE, MIN_SIZE};
+
+/// Files the PDF commands accept
+pub const PDF_EXTENSIONS: &[&str] = &["pdf"];
+
+/// Length of the excerpt shown on preview cards
+const EXCERPT_CHARS: usize = 200;
+
+/// Text of a PDF, page by page
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PdfText {
+    /// Text of each page, with whitespace runs collapsed
+    pub pages: Vec<String>,
+    /// All pages joined by blank lines, for indexing
+    pub text: String,
+    /// Start of the text for preview cards
+    pub excerpt: String,
+}
+
+impl PdfText {
+    /// Builds the text from extracted pages
+    pub fn from_pages(pages: Vec<String>) -> Self {
+        let pages: Vec<String> = pages.iter().map(|page| collapse_whitespace(page)).collect();
+        let text = pages
+            .iter()
+            .filter(|page| !page.is_empty())
+            .cloned()
+            .collect::<Vec<_>>()
+            .join("\n\n");
+        let excerpt = excerpt(&text, EXCERPT_CHARS);
+        Self { pages, text, excerpt }
+    }
+}
+
+/// Directory extracted text is cached in
+pub fn text_cache_dir() -> Result<PathBuf, String> {
+    Ok(dirs::cache_dir()
+        .ok_or("Failed to get cache directory")?
+        .join("mdreader")
+        .join("pdf_text"))
+}
+
+/// Returns the text of a PDF, extracting it on a cache miss
+pub fn extract_text(path: &Path, cache_dir: &Path) -> Result<PdfText, String> {
+    let metadata = fs::metadata(path)
+        .map_err(|e| format!("Failed to read file: {}", e))?;
+    let cache_path = cache_dir.join(format!(
+        "{}.json",
+        thumbnails::cache_key(path, metadata.modified().ok(), 0)
+    ));
+
+    if let Some(cached) = fs::read_to_string(&cache_path)
+        .ok()
+        .and_then(|json| serde_json::from_str::<PdfText>(&json).ok())
+    {
+        return Ok(cached);
+    }
+
+    let pages = pdf_extract::extract_text_by_pages(path)
+        .map_err(|e| format!("Failed to extract PDF text: {}", e))?;
+    let text = PdfText::from_pages(pages);
+
+    // A failed cache write only costs a re-extraction next time
+    if fs::create_dir_all(cache_dir).is_ok() {
+        if let Ok(json) = serde_json::to_string(&text) {
+            if let Err(e) = fs::write(&cache_path, json) {
+                log::warn!("⚠️ Failed to cache PDF text for {:?}: {}", path, e);
+            }
+        }
+    }
+
+    log::info!("📄 Extracted text of {:?} ({} pages)", path, text.pages.len());
+    Ok(text)
+}
+
+/// Renders one page (1-based) of a PDF as a PNG thumbnail
+pub fn page_thumbnail(
+    path: &Path,
+    page: u16,
+    size: u32,
+    cache_dir: &Path,
+    inline: bool,
+) -> Result<Thumbnail, String> {
+    let size = size.clamp(MIN_SIZE, MAX_SIZE);
+    if page == 0 {
+        return Err("Page numbers start at 1".to_string());
+    }
+
+    let metadata = fs::metadata(path)
+        .map_err(|e| format!("Failed to read file: {}", e))?;
+    let cache_path = cache_dir.join(format!(
+        "{}-p{}-{}.png",
+        thumbnails::cache_key(path, metadata.modified().ok(), size),
+        page,
+        size
+    ));
+
+    if cache_path.exists() {
+        return thumbnails::finish(cache_path, "image/png", inline);
+    }
+
+    fs::create_dir_all(cache_dir)
+        .map_err(|e| format!("Failed to create thumbnail cache: {}", e))?;
+
+    let pdfium = load_pdfium()?;
+    let document = pdfium
+        .load_pdf_from_file(path, None)
+        .map_err(|e| format!("Failed to open PDF: {}", e))?;
+    let pages = document.pages();
+    if page > pages.len() {
+        return Err(format!("Page {} out of range (document has {} pages)", page, pages.len()));
+    }
+
+    let config = PdfRenderConfig::new()
+        .set_target_width(size as i32)
+        .set_maximum_height(size as i32);
+    pages
+        .get(page - 1)
+        .and_then(|p| p.render_with_config(&config))
+        .map_err(|e| format!("Failed to render PDF page: {}", e))?
+        .as_image()
+        .save_with_format(&cache_path, image::ImageFormat::Png)
+        .map_err(|e| format!("Failed to write thumbnail: {}", e))?;
+
+    log::info!("🖼️ Rendered page {} of {:?} ({}px)", page, path, size);
+    thumbnails::finish(cache_path, "image/png", inline)
+}
+
+/// Checks if the PDFium library can be loaded (page previews work)
+pub fn renderer_available() -> bool {
+    load_pdfium().is_ok()
+}
+
+/// Binds PDFium, preferring a library shipped next to the executable
+fn load_pdfium() -> Result<Pdfium, String> {
+    let bundled = std::env::current_exe()
+        .ok()
+        .and_then(|exe| exe.parent().map(Pdfium::pdfium_platform_library_name_at_path));
+
+    bundled
+        .map_or_else(Pdfium::bind_to_system_library, |library| {
+            Pdfium::bind_to_library(library).or_else(|_| Pdfium::bind_to_system_library())
+        })
+        .map(Pdfium::new)
+        .map_err(|e| format!("Failed to load PDF renderer: {}", e))
+}
+
+fn collapse_whitespace(text: &str) -> String {
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+fn excerpt(text: &str, max_chars: usize) -> String {
+    let flat = collapse_whitespace(text);
+    if flat.chars().count() <= max_chars {
+        return flat;
+    }
+    let cut: String = flat.chars().take(max_chars).collect();
+    let cut = cut.rsplit_once(' ').map_or(cut.as_str(), |(head, _)| head);
+    format!("{}…", cut.trim_end())
+}
+
+// ============================================================================
+// TESTS
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pdf_text_from_pages() {
+        let text = PdfText::from_pages(vec![
+            "Quarterly  report\n\n  Revenue grew".to_string(),
+            "   \n".to_string(),
+            "Appendix".to_string(),
+        ]);
+
+        assert_eq!(text.pages, vec!["Quarterly report Revenue grew", "", "Appendix"]);
+        assert_eq!(text.text, "Quarterly report Revenue grew\n\nAppendix");
+        assert_eq!(text.excerpt, "Quarterly report Revenue grew Appendix");
+    }
+
+    #[test]
+    fn test_excerpt_cuts_at_word() {
+        assert_eq!(excerpt("alpha beta gamma", 12), "alpha beta…");
+        assert_eq!(excerpt("short", 12), "short");
+    }
+}
@@ -0,0 +1,253 @@
+//! People Pages
+//!
+//! A person page is a note with `type: person` in its frontmatter, as the
+//! vCard import writes them (see `crate::vcard`). Other notes mention a
+//! person with `@handle`: the person's name in lowercase with words joined
+//! by `-` (`@ann-smith` for "Ann Smith"), or the page's `handle` field, file
+//! name or one of its aliases, normalized the same way.
+//!
+//! Mentions are extracted with the other links of a note (see
+//! `crate::links`), so they count in backlinks and the graph, and render as
+//! links to the person page (see `crate::render`). `@` after a letter
+//! (e-mail addresses) or in a URL path does not start a mention.
+
+use std::collections::{HashMap, HashSet};
+use serde::Serialize;
+use serde_json::{Map, Value};
+use crate::frontmatter;
+use crate::metadata_cache::MetadataCache;
+
+/// Frontmatter `type` of person pages
+pub const PERSON_TYPE: &str = "person";
+
+/// A person page with the number of notes mentioning it
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Person {
+    /// Path relative to the workspace root
+    pub path: String,
+    pub name: String,
+    /// Handle to mention the person with (without `@`)
+    pub handle: String,
+    pub email: Option<String>,
+    pub organization: Option<String>,
+    /// Notes mentioning the person
+    pub mentioned_in: usize,
+}
+
+/// A note mentioning a person
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct PersonMention {
+    pub path: String,
+    pub title: String,
+    /// Modification time of the note (ms since the epoch)
+    pub modified_ms: i64,
+    /// Mentions of the person in the note
+    pub count: usize,
+}
+
+/// Checks if frontmatter fields make a note a person page
+pub fn is_person(fields: &Map<String, Value>) -> bool {
+    fields
+        .get("type")
+        .and_then(Value::as_str)
+        .is_some_and(|kind| kind.trim().eq_ignore_ascii_case(PERSON_TYPE))
+}
+
+/// Normalizes a name to a handle: lowercase letters and digits, words
+/// joined by `-` (`Ann Smith` → `ann-smith`)
+pub fn handle(name: &str) -> String {
+    let mut out = String::with_capacity(name.len());
+    for c in name.chars() {
+        if c.is_alphanumeric() {
+            out.extend(c.to_lowercase());
+        } else if (c.is_whitespace() || matches!(c, '-' | '_' | '.')) && !out.is_empty() && !out.ends_with('-') {
+            out.push('-');
+        }
+    }
+    out.trim_end_matches('-').to_string()
+}
+
+/// Handles a note answers to: empty unless it is a person page
+pub fn person_handles(path: &str, title: &str, fields: &Map<String, Value>) -> Vec<String> {
+    if !is_person(fields) {
+        return Vec::new();
+    }
+    let name = path.rsplit('/').next().unwrap_or(path);
+    let stem = name.strip_suffix(".md").unwrap_or(name);
+    let field = fields.get("handle").map(frontmatter::value_to_string).unwrap_or_default();
+
+    let mut handles: Vec<String> = Vec::new();
+    let names = [field.as_str(), title, stem].into_iter().map(String::from).chain(frontmatter::aliases(fields));
+    for handle in names.map(|name| handle(name.trim_start_matches('@'))) {
+        if !handle.is_empty() && !handles.contains(&handle) {
+            handles.push(handle);
+        }
+    }
+    handles
+}
+
+/// Checks if `@` may start a mention after the character `prev`
+pub fn can_start_mention(prev: Option<char>) -> bool {
+    !prev.is_some_and(|c| c.is_alphanumeric() || matches!(c, '/' | '@' | '.' | '_' | '-'))
+}
+
+/// Parses an `@handle` mention at the start of `rest`
+///
+/// Trailing `.`, `-` and `_` end the sentence rather than the handle, and
+/// `@name(` is an annotation (such as `@event(...)`), not a mention.
+///
+/// # Returns
+/// * `Some((handle, len))` - The normalized handle and the mention's length in bytes
+pub fn parse_mention(rest: &str) -> Option<(String, usize)> {
+    let name = rest.strip_prefix('@')?;
+    if !name.starts_with(char::is_alphabetic) {
+        return None;
+    }
+    let end = name
+        .find(|c: char| !(c.is_alphanumeric() || matches!(c, '-' | '_' | '.')))
+        .unwrap_or(name.len());
+    let len = name[..end].trim_end_matches(['-', '_', '.']).len();
+    if name[len..].starts_with('(') {
+        return None;
+    }
+    Some((handle(&name[..len]), len + 1))
+}
+
+/// Lists the person pages of a workspace by name, with how many notes
+/// mention each
+pub fn list_people(cache: &MetadataCache) -> Vec<Person> {
+    let mut mentioned_in: HashMap<String, usize> = HashMap::new();
+    let pairs: HashSet<(String, String)> = mentioned_people(cache).into_iter().collect();
+    for (_, target) in pairs {
+        *mentioned_in.entry(target).or_default() += 1;
+    }
+
+    let mut people: Vec<Person> = cache
+        .notes
+        .iter()
+        .filter(|(_, note)| is_person(&note.fields))
+        .map(|(path, note)| {
+            let field = |key: &str| {
+                note.fields
+                    .get(key)
+                    .and_then(|value| match value {
+                        Value::Array(items) => items.first(),
+                        value => Some(value),
+                    })
+                    .map(frontmatter::value_to_string)
+                    .filter(|value| !value.is_empty())
+            };
+            Person {
+                path: path.clone(),
+                name: note.title.clone(),
+                handle: person_handles(path, &note.title, &note.fields).into_iter().next().unwrap_or_default(),
+                email: field("email"),
+                organization: field("organization"),
+                mentioned_in: mentioned_in.get(path).copied().unwrap_or(0),
+            }
+        })
+        .collect();
+    people.sort_by(|a, b| a.name.to_lowercase().cmp(&b.name.to_lowercase()).then_with(|| a.path.cmp(&b.path)));
+    people
+}
+
+/// Notes mentioning the person page at `path`, most recently modified first
+pub fn mentions_of(cache: &MetadataCache, path: &str) -> Vec<PersonMention> {
+    let mut counts: HashMap<String, usize> = HashMap::new();
+    for (from, target) in mentioned_people(cache) {
+        if target == path {
+            *counts.entry(from).or_default() += 1;
+        }
+    }
+
+    let mut mentions: Vec<PersonMention> = counts
+        .into_iter()
+        .filter_map(|(from, count)| {
+            let note = cache.notes.get(&from)?;
+            Some(PersonMention { title: note.title.clone(), modified_ms: note.modified_ms, count, path: from })
+        })
+        .collect();
+    mentions.sort_by(|a, b| b.modified_ms.cmp(&a.modified_ms).then_with(|| a.path.cmp(&b.path)));
+    mentions
+}
+
+// Helper: Resolves every mention of the workspace; returns (note, person page)
+// pairs, one per mention
+fn mentioned_people(cache: &MetadataCache) -> Vec<(String, String)> {
+    let lookup = cache.lookup();
+    cache
+        .notes
+        .iter()
+        .flat_map(|(from, note)| {
+            let lookup = &lookup;
+            note.links.people.iter().filter_map(move |handle| {
+                let target = lookup.resolve_person(from, handle)?;
+                Some((from.clone(), lookup.paths[target].clone()))
+            })
+        })
+        .filter(|(from, target)| from != target)
+        .collect()
+}
+
+// ============================================================================
+// TESTS
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::links::NoteLinks;
+    use crate::metadata_cache::CachedNote;
+
+    fn note(title: &str, content: &str, modified_ms: i64) -> CachedNote {
+        let (fields, body) = frontmatter::split(content);
+        CachedNote {
+            title: title.to_string(),
+            modified_ms,
+            links: NoteLinks::from_body(body),
+            fields: fields.unwrap_or_default(),
+            ..CachedNote::default()
+        }
+    }
+
+    #[test]
+    fn test_handles_and_mentions() {
+        assert_eq!(handle("Ann Smith"), "ann-smith");
+        assert_eq!(handle("  J.R.R. Tolkien "), "j-r-r-tolkien");
+        assert_eq!(handle("Zoë_Ng"), "zoë-ng");
+
+        assert_eq!(parse_mention("@ann-smith."), Some(("ann-smith".to_string(), 10)));
+        assert_eq!(parse_mention("@Ann_Smith, hi"), Some(("ann-smith".to_string(), 10)));
+        assert_eq!(parse_mention("@event(2024-06-01)"), None);
+        assert_eq!(parse_mention("@2pm"), None);
+        assert!(!can_start_mention(Some('n')));
+        assert!(can_start_mention(Some('(')));
+
+        let fields = frontmatter::split("---\ntype: person\nhandle: \"@annie\"\naliases: [A. Smith]\n---\n").0.unwrap();
+        assert_eq!(person_handles("people/Ann Smith.md", "Ann Smith", &fields), ["annie", "ann-smith", "a-smith"]);
+        assert!(person_handles("Plan.md", "Plan", &Map::new()).is_empty());
+    }
+
+    #[test]
+    fn test_people_and_mentions() {
+        let mut cache = MetadataCache::default();
+        let ann = "---\ntype: person\nemail: [ann@example.com, ann@home.org]\n---\n# Ann Smith\n";
+        cache.notes.insert("people/Ann Smith.md".into(), note("Ann Smith", ann, 1));
+        cache.notes.insert("people/Bob.md".into(), note("Bob", "---\ntype: person\n---\n", 1));
+        cache.notes.insert("Bob.md".into(), note("Bob (not a person)", "", 1));
+        cache.notes.insert("sync.md".into(), note("Sync", "@ann-smith and @Ann_Smith met @bob; mail ann@example.com", 20));
+        cache.notes.insert("retro.md".into(), note("Retro", "Thanks @ann-smith! `@bob` @nobody", 30));
+
+        let people = list_people(&cache);
+        let summary: Vec<(&str, &str, usize)> = people.iter().map(|p| (p.name.as_str(), p.handle.as_str(), p.mentioned_in)).collect();
+        assert_eq!(summary, [("Ann Smith", "ann-smith", 2), ("Bob", "bob", 1)]);
+        assert_eq!(people[0].email.as_deref(), Some("ann@example.com"));
+
+        let mentions = mentions_of(&cache, "people/Ann Smith.md");
+        let found: Vec<(&str, usize)> = mentions.iter().map(|m| (m.path.as_str(), m.count)).collect();
+        assert_eq!(found, [("retro.md", 1), ("sync.md", 2)]);
+
+        // Mentions are links: the person page has backlinks
+        assert_eq!(cache.link_index().backlinks("people/Bob.md"), ["sync.md"]);
+    }
+}
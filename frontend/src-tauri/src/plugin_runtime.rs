@@ -0,0 +1,521 @@
+//! Plugin Runtime
+//!
+//! Runs the WebAssembly plugins of a workspace (see `crate::plugins`) with
+//! wasmtime. A plugin gets no WASI, so no file system, network, clock or
+//! environment: only the host functions below, each checked against the
+//! capabilities in its manifest. Every run gets a fresh instance limited to
+//! `MAX_FUEL` instructions and `MAX_MEMORY_BYTES` of memory. Compiled
+//! modules are kept per module hash.
+//!
+//! Plugins only run on desktop; iOS does not allow the JIT compiler.
+//!
+//! # ABI
+//!
+//! Strings and bytes cross the boundary as a pointer and length into the
+//! plugin's memory. Functions returning them pack both into an `i64`
+//! (`ptr << 32 | len`, `0` when empty, negative on error). The module
+//! exports:
+//!
+//! | Export | Signature | Purpose |
+//! |--------|-----------|---------|
+//! | `memory` | memory | Linear memory |
+//! | `alloc` | `(len: i32) -> i32` | Allocates `len` bytes for the host |
+//! | `run` | `(cmd_ptr, cmd_len, input_ptr, input_len: i32) -> i64` | Runs a command, returns its output |
+//!
+//! and may import from `mdreader`:
+//!
+//! | Import | Capability | Purpose |
+//! |--------|------------|---------|
+//! | `log(ptr, len)` | - | Adds a line to the run's log |
+//! | `fail(ptr, len)` | - | Fails the run with a message |
+//! | `note_path() -> i64` | - | The note the command runs on (empty if none) |
+//! | `read_note(ptr, len) -> i64` | `read_notes` | Content of a note, by workspace-relative path |
+//! | `list_notes() -> i64` | `read_notes` | JSON array of the workspace's notes |
+//! | `write_note(ptr, len, content_ptr, content_len) -> i32` | `write_notes` | Creates or overwrites a note, `0` on success |
+//! | `last_error() -> i64` | - | Why the last host call failed |
+
+use std::fs;
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use serde::Serialize;
+use crate::notes::{collect_markdown_files, relative_path};
+use crate::plugins::{self, Capability, Plugin};
+use crate::safe_write;
+use crate::utils::validate_file_path;
+use crate::workspace_settings::ensure_writable;
+
+/// Instructions a run may execute
+pub const MAX_FUEL: u64 = 2_000_000_000;
+
+/// Memory a run may use
+pub const MAX_MEMORY_BYTES: usize = 256 * 1024 * 1024;
+
+/// Largest input, output, note or message passed across the boundary
+pub const MAX_TRANSFER_BYTES: usize = 32 * 1024 * 1024;
+
+/// Log lines kept per run
+#[cfg_attr(not(desktop), allow(dead_code))]
+const MAX_LOG_LINES: usize = 200;
+
+/// What a plugin command runs on
+#[derive(Debug, Clone)]
+pub struct PluginRequest {
+    /// The configured workspace root
+    pub workspace: String,
+    /// Name of the command
+    pub command: String,
+    /// Input passed to `run`
+    pub input: Vec<u8>,
+    /// Workspace-relative path of the note the command runs on
+    pub note: Option<String>,
+}
+
+/// Result of a plugin run
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct PluginRun {
+    /// What `run` returned
+    #[serde(skip)]
+    pub output: Vec<u8>,
+    /// Lines the plugin logged
+    pub logs: Vec<String>,
+    /// Notes the plugin wrote, workspace-relative
+    pub written: Vec<String>,
+    pub fuel_used: u64,
+    pub duration_ms: u64,
+}
+
+/// Runs plugins; shared by the commands through `AppState`
+#[derive(Clone, Default)]
+pub struct PluginHost {
+    runtime: Arc<Mutex<platform::Runtime>>,
+}
+
+impl PluginHost {
+    /// Creates a host that compiles modules on first use
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Runs a command of an approved plugin
+    ///
+    /// Blocks until the plugin returns, fails or runs out of fuel.
+    pub fn run(&self, plugin: &Plugin, request: &PluginRequest) -> Result<PluginRun, String> {
+        if plugin.manifest.command(&request.command).is_none() {
+            return Err(format!("Plugin {} has no command {}", plugin.manifest.id, request.command));
+        }
+        if request.input.len() > MAX_TRANSFER_BYTES {
+            return Err(format!("Input for plugin {} is too large", plugin.manifest.id));
+        }
+        let module = {
+            let mut runtime = self.runtime.lock()
+                .map_err(|e| format!("Failed to lock plugin runtime: {}", e))?;
+            runtime.module(plugin)?
+        };
+        platform::run(module, plugin, request)
+    }
+
+    /// Drops the compiled modules that are not in `keep` (by module hash)
+    pub fn retain(&self, keep: &[String]) {
+        if let Ok(mut runtime) = self.runtime.lock() {
+            runtime.retain(keep);
+        }
+    }
+}
+
+/// Packs a pointer and length into an `i64`
+pub fn pack(ptr: u32, len: u32) -> i64 {
+    ((ptr as u64) << 32 | len as u64) as i64
+}
+
+/// Unpacks a pointer and length, `None` for an error (negative)
+pub fn unpack(value: i64) -> Option<(u32, u32)> {
+    if value < 0 {
+        return None;
+    }
+    Some(((value >> 32) as u32, value as u32))
+}
+
+/// State a run shares with the host functions
+#[cfg_attr(not(desktop), allow(dead_code))]
+struct HostState {
+    workspace: String,
+    note: Option<String>,
+    capabilities: Vec<Capability>,
+    logs: Vec<String>,
+    written: Vec<String>,
+    failure: Option<String>,
+    last_error: Option<String>,
+    #[cfg(desktop)]
+    limits: wasmtime::StoreLimits,
+}
+
+#[cfg_attr(not(desktop), allow(dead_code))]
+impl HostState {
+    fn log(&mut self, line: String) {
+        if self.logs.len() < MAX_LOG_LINES {
+            self.logs.push(line);
+        }
+    }
+
+    // Helper: Checks a capability before a host call
+    fn require(&self, capability: Capability) -> Result<(), String> {
+        if self.capabilities.contains(&capability) {
+            return Ok(());
+        }
+        Err(format!("Capability not granted: {:?}", capability))
+    }
+
+    fn read_note(&self, path: &str) -> Result<Vec<u8>, String> {
+        self.require(Capability::ReadNotes)?;
+        let validated = self.validate(path)?;
+        let size = fs::metadata(&validated)
+            .map_err(|e| format!("Failed to read {}: {}", path, e))?
+            .len();
+        if size > MAX_TRANSFER_BYTES as u64 {
+            return Err(format!("{} is too large", path));
+        }
+        fs::read(&validated).map_err(|e| format!("Failed to read {}: {}", path, e))
+    }
+
+    fn list_notes(&self) -> Result<Vec<u8>, String> {
+        self.require(Capability::ReadNotes)?;
+        let root = Path::new(&self.workspace)
+            .canonicalize()
+            .map_err(|e| format!("Failed to resolve workspace: {}", e))?;
+        let notes: Vec<String> = collect_markdown_files(&root, &root)?
+            .iter()
+            .map(|path| relative_path(&root, path))
+            .collect();
+        serde_json::to_vec(&notes).map_err(|e| format!("Failed to list notes: {}", e))
+    }
+
+    fn write_note(&mut self, path: &str, content: &[u8]) -> Result<(), String> {
+        self.require(Capability::WriteNotes)?;
+        let content = std::str::from_utf8(content)
+            .map_err(|_| format!("Content for {} is not UTF-8", path))?;
+        let validated = self.validate(path)?;
+        ensure_writable(&self.workspace, &validated)?;
+        safe_write::write_file(&validated, content)?;
+        self.written.push(path.to_string());
+        Ok(())
+    }
+
+    // Helper: Resolves and validates a note path given by the plugin
+    fn validate(&self, path: &str) -> Result<std::path::PathBuf, String> {
+        let resolved = plugins::note_path(Path::new(&self.workspace), path)?;
+        validate_file_path(&resolved.to_string_lossy(), &self.workspace, &["md"])
+            .map_err(|e| format!("Security error: {}", e))
+    }
+}
+
+#[cfg(desktop)]
+mod platform {
+    use super::*;
+    use std::collections::HashMap;
+    use std::time::Instant;
+    use wasmtime::{
+        Caller, Config, Engine, Extern, Linker, Memory, Module, Store, StoreLimitsBuilder, Trap, TypedFunc,
+    };
+
+    /// The engine and the compiled modules, by module hash
+    #[derive(Default)]
+    pub struct Runtime {
+        engine: Option<Engine>,
+        modules: HashMap<String, Module>,
+    }
+
+    impl Runtime {
+        pub fn module(&mut self, plugin: &Plugin) -> Result<Module, String> {
+            if let Some(module) = self.modules.get(&plugin.module_hash) {
+                return Ok(module.clone());
+            }
+            let engine = match &self.engine {
+                Some(engine) => engine.clone(),
+                None => {
+                    let mut config = Config::new();
+                    config.consume_fuel(true);
+                    let engine = Engine::new(&config)
+                        .map_err(|e| format!("Failed to start plugin runtime: {}", e))?;
+                    self.engine = Some(engine.clone());
+                    engine
+                }
+            };
+            let module = Module::new(&engine, plugin.read_module()?)
+                .map_err(|e| format!("Failed to compile plugin {}: {}", plugin.manifest.id, e))?;
+            log::info!("🧩 Compiled plugin {}", plugin.manifest.id);
+            self.modules.insert(plugin.module_hash.clone(), module.clone());
+            Ok(module)
+        }
+
+        pub fn retain(&mut self, keep: &[String]) {
+            self.modules.retain(|hash, _| keep.contains(hash));
+        }
+    }
+
+    pub fn run(module: Module, plugin: &Plugin, request: &PluginRequest) -> Result<PluginRun, String> {
+        let id = &plugin.manifest.id;
+        let started = Instant::now();
+        let state = HostState {
+            workspace: request.workspace.clone(),
+            note: request.note.clone(),
+            capabilities: plugin.manifest.capabilities.clone(),
+            logs: Vec::new(),
+            written: Vec::new(),
+            failure: None,
+            last_error: None,
+            limits: StoreLimitsBuilder::new().memory_size(MAX_MEMORY_BYTES).instances(1).build(),
+        };
+        let mut store = Store::new(module.engine(), state);
+        store.limiter(|state| &mut state.limits);
+        store.set_fuel(MAX_FUEL).map_err(|e| format!("Failed to start plugin {}: {}", id, e))?;
+
+        let instance = linker(module.engine())
+            .and_then(|linker| linker.instantiate(&mut store, &module))
+            .map_err(|e| format!("Failed to load plugin {}: {}", id, e))?;
+        let memory = instance
+            .get_memory(&mut store, "memory")
+            .ok_or_else(|| format!("Plugin {} exports no memory", id))?;
+        let alloc = instance
+            .get_typed_func::<i32, i32>(&mut store, "alloc")
+            .map_err(|e| format!("Plugin {} exports no alloc: {}", id, e))?;
+        let entry = instance
+            .get_typed_func::<(i32, i32, i32, i32), i64>(&mut store, "run")
+            .map_err(|e| format!("Plugin {} exports no run: {}", id, e))?;
+
+        let result = call(&mut store, &alloc, &memory, &entry, request);
+        let fuel_used = MAX_FUEL - store.get_fuel().unwrap_or(0);
+
+        let packed = match result {
+            Ok(packed) => packed,
+            Err(e) if e.downcast_ref::<Trap>() == Some(&Trap::OutOfFuel) => {
+                return Err(format!("Plugin {} stopped after {} instructions", id, MAX_FUEL));
+            }
+            Err(e) => return Err(format!("Plugin {} crashed: {}", id, e)),
+        };
+        if let Some(failure) = store.data_mut().failure.take() {
+            return Err(format!("Plugin {} failed: {}", id, failure));
+        }
+        let Some((ptr, len)) = unpack(packed) else {
+            let reason = store.data_mut().last_error.take().unwrap_or_else(|| "returned an error".to_string());
+            return Err(format!("Plugin {} failed: {}", id, reason));
+        };
+        if len as usize > MAX_TRANSFER_BYTES {
+            return Err(format!("Output of plugin {} is too large", id));
+        }
+        let mut output = vec![0; len as usize];
+        memory
+            .read(&store, ptr as usize, &mut output)
+            .map_err(|e| format!("Plugin {} returned invalid output: {}", id, e))?;
+
+        let state = store.into_data();
+        Ok(PluginRun {
+            output,
+            logs: state.logs,
+            written: state.written,
+            fuel_used,
+            duration_ms: started.elapsed().as_millis() as u64,
+        })
+    }
+
+    // Helper: Passes the command and input to `run`
+    fn call(
+        store: &mut Store<HostState>,
+        alloc: &TypedFunc<i32, i32>,
+        memory: &Memory,
+        entry: &TypedFunc<(i32, i32, i32, i32), i64>,
+        request: &PluginRequest,
+    ) -> wasmtime::Result<i64> {
+        let (command_ptr, command_len) = pass(store, alloc, memory, request.command.as_bytes())?;
+        let (input_ptr, input_len) = pass(store, alloc, memory, &request.input)?;
+        entry.call(&mut *store, (command_ptr, command_len, input_ptr, input_len))
+    }
+
+    // Helper: Copies bytes into the plugin's memory through its `alloc`
+    fn pass(
+        store: &mut Store<HostState>,
+        alloc: &TypedFunc<i32, i32>,
+        memory: &Memory,
+        bytes: &[u8],
+    ) -> wasmtime::Result<(i32, i32)> {
+        if bytes.is_empty() {
+            return Ok((0, 0));
+        }
+        let ptr = alloc.call(&mut *store, bytes.len() as i32)?;
+        memory.write(&mut *store, ptr as u32 as usize, bytes)?;
+        Ok((ptr, bytes.len() as i32))
+    }
+
+    // Helper: Defines the host functions
+    fn linker(engine: &Engine) -> wasmtime::Result<Linker<HostState>> {
+        let mut linker = Linker::new(engine);
+        linker.func_wrap("mdreader", "log", |mut caller: Caller<'_, HostState>, ptr: i32, len: i32| -> wasmtime::Result<()> {
+            let line = guest_string(&mut caller, ptr, len)?;
+            caller.data_mut().log(line);
+            Ok(())
+        })?;
+        linker.func_wrap("mdreader", "fail", |mut caller: Caller<'_, HostState>, ptr: i32, len: i32| -> wasmtime::Result<()> {
+            let message = guest_string(&mut caller, ptr, len)?;
+            caller.data_mut().failure = Some(message);
+            Ok(())
+        })?;
+        linker.func_wrap("mdreader", "note_path", |mut caller: Caller<'_, HostState>| -> wasmtime::Result<i64> {
+            let note = caller.data().note.clone().unwrap_or_default();
+            give(&mut caller, note.as_bytes())
+        })?;
+        linker.func_wrap("mdreader", "read_note", |mut caller: Caller<'_, HostState>, ptr: i32, len: i32| -> wasmtime::Result<i64> {
+            let path = guest_string(&mut caller, ptr, len)?;
+            let content = caller.data().read_note(&path);
+            answer(&mut caller, content)
+        })?;
+        linker.func_wrap("mdreader", "list_notes", |mut caller: Caller<'_, HostState>| -> wasmtime::Result<i64> {
+            let notes = caller.data().list_notes();
+            answer(&mut caller, notes)
+        })?;
+        linker.func_wrap(
+            "mdreader",
+            "write_note",
+            |mut caller: Caller<'_, HostState>, ptr: i32, len: i32, content_ptr: i32, content_len: i32| -> wasmtime::Result<i32> {
+                let path = guest_string(&mut caller, ptr, len)?;
+                let content = guest_bytes(&mut caller, content_ptr, content_len)?;
+                match caller.data_mut().write_note(&path, &content) {
+                    Ok(()) => Ok(0),
+                    Err(e) => Ok(refuse(&mut caller, e) as i32),
+                }
+            },
+        )?;
+        linker.func_wrap("mdreader", "last_error", |mut caller: Caller<'_, HostState>| -> wasmtime::Result<i64> {
+            let error = caller.data_mut().last_error.take().unwrap_or_default();
+            give(&mut caller, error.as_bytes())
+        })?;
+        Ok(linker)
+    }
+
+    // Helper: Returns a host call's bytes to the plugin, or the error
+    fn answer(caller: &mut Caller<'_, HostState>, result: Result<Vec<u8>, String>) -> wasmtime::Result<i64> {
+        match result {
+            Ok(bytes) => give(caller, &bytes),
+            Err(e) => Ok(refuse(caller, e)),
+        }
+    }
+
+    // Helper: Records why a host call failed for `last_error`
+    fn refuse(caller: &mut Caller<'_, HostState>, error: String) -> i64 {
+        log::warn!("⚠️ Plugin host call refused: {}", error);
+        caller.data_mut().last_error = Some(error);
+        -1
+    }
+
+    // Helper: Copies bytes into the plugin's memory from a host function
+    fn give(caller: &mut Caller<'_, HostState>, bytes: &[u8]) -> wasmtime::Result<i64> {
+        if bytes.is_empty() {
+            return Ok(0);
+        }
+        let alloc = caller
+            .get_export("alloc")
+            .and_then(Extern::into_func)
+            .ok_or_else(|| wasmtime::Error::msg("plugin exports no alloc"))?
+            .typed::<i32, i32>(&*caller)?;
+        let ptr = alloc.call(&mut *caller, bytes.len() as i32)?;
+        memory(caller)?.write(&mut *caller, ptr as u32 as usize, bytes)?;
+        Ok(pack(ptr as u32, bytes.len() as u32))
+    }
+
+    // Helper: Reads bytes the plugin passed
+    fn guest_bytes(caller: &mut Caller<'_, HostState>, ptr: i32, len: i32) -> wasmtime::Result<Vec<u8>> {
+        if len < 0 || len as usize > MAX_TRANSFER_BYTES {
+            return Err(wasmtime::Error::msg("invalid length"));
+        }
+        let mut buffer = vec![0; len as usize];
+        memory(caller)?.read(&*caller, ptr as u32 as usize, &mut buffer)?;
+        Ok(buffer)
+    }
+
+    // Helper: Reads a string the plugin passed
+    fn guest_string(caller: &mut Caller<'_, HostState>, ptr: i32, len: i32) -> wasmtime::Result<String> {
+        Ok(String::from_utf8_lossy(&guest_bytes(caller, ptr, len)?).to_string())
+    }
+
+    fn memory(caller: &mut Caller<'_, HostState>) -> wasmtime::Result<Memory> {
+        caller
+            .get_export("memory")
+            .and_then(Extern::into_memory)
+            .ok_or_else(|| wasmtime::Error::msg("plugin exports no memory"))
+    }
+}
+
+#[cfg(not(desktop))]
+mod platform {
+    use super::*;
+
+    pub struct Module;
+
+    #[derive(Default)]
+    pub struct Runtime;
+
+    impl Runtime {
+        pub fn module(&mut self, _plugin: &Plugin) -> Result<Module, String> {
+            Err("Plugins are not supported on this platform".to_string())
+        }
+
+        pub fn retain(&mut self, _keep: &[String]) {}
+    }
+
+    pub fn run(_module: Module, _plugin: &Plugin, _request: &PluginRequest) -> Result<PluginRun, String> {
+        Err("Plugins are not supported on this platform".to_string())
+    }
+}
+
+// ============================================================================
+// TESTS
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn host(workspace: &Path, capabilities: Vec<Capability>) -> HostState {
+        HostState {
+            workspace: workspace.to_string_lossy().to_string(),
+            note: None,
+            capabilities,
+            logs: Vec::new(),
+            written: Vec::new(),
+            failure: None,
+            last_error: None,
+            #[cfg(desktop)]
+            limits: wasmtime::StoreLimits::default(),
+        }
+    }
+
+    #[test]
+    fn test_pack_roundtrip() {
+        assert_eq!(unpack(pack(0x10, 5)), Some((0x10, 5)));
+        assert_eq!(unpack(pack(u32::MAX >> 1, u32::MAX)), Some((u32::MAX >> 1, u32::MAX)));
+        assert_eq!(pack(0, 0), 0);
+        assert_eq!(unpack(-1), None);
+    }
+
+    #[test]
+    fn test_host_calls_respect_capabilities() {
+        let workspace = TempDir::new().unwrap();
+        let root = workspace.path();
+        fs::create_dir(root.join("a")).unwrap();
+        fs::write(root.join("a/plan.md"), "# Plan").unwrap();
+
+        let mut reader = host(root, vec![Capability::ReadNotes]);
+        assert_eq!(reader.read_note("a/plan.md").unwrap(), b"# Plan");
+        let listed: Vec<String> = serde_json::from_slice(&reader.list_notes().unwrap()).unwrap();
+        assert_eq!(listed, ["a/plan.md"]);
+        assert!(reader.write_note("a/plan.md", b"changed").is_err());
+        assert!(reader.read_note("../outside.md").is_err());
+
+        let mut writer = host(root, vec![Capability::WriteNotes]);
+        assert!(writer.read_note("a/plan.md").is_err());
+        writer.write_note("a/summary.md", b"# Summary").unwrap();
+        assert!(writer.write_note("a/script.sh", b"rm -rf /").is_err());
+        assert!(writer.write_note("missing/note.md", b"x").is_err());
+        assert_eq!(writer.written, ["a/summary.md"]);
+        assert_eq!(fs::read_to_string(root.join("a/summary.md")).unwrap(), "# Summary");
+    }
+}
@@ -0,0 +1,367 @@
+//! Workspace Plugins
+//!
+//! A workspace can bring WebAssembly plugins in its `plugins/` folder, one
+//! folder per plugin with a `plugin.json` manifest and the module:
+//!
+//! ```text
+//! plugins/
+//! └── docx-export/
+//!     ├── plugin.json
+//!     └── plugin.wasm
+//! ```
+//!
+//! The manifest names the commands the plugin adds and the capabilities it
+//! needs:
+//!
+//! ```json
+//! {
+//!   "id": "docx-export",
+//!   "name": "DOCX export",
+//!   "capabilities": ["read_notes"],
+//!   "commands": [{ "name": "export", "title": "Export as DOCX", "kind": "exporter", "extension": "docx" }]
+//! }
+//! ```
+//!
+//! Commands are of three kinds: a `command` gets input from the frontend
+//! and returns its output to it, a `transform` gets a note and returns its
+//! new content, and an `exporter` gets a note and returns the file written
+//! to the export destination.
+//!
+//! Plugins run in a sandbox without access to the file system, network or
+//! environment (see `crate::plugin_runtime`); they can only use the host
+//! functions their capabilities allow, on notes inside the workspace. Like
+//! hooks (see `crate::hooks`), plugins come with the folder, so each one
+//! only runs once the user approved its exact manifest and module on this
+//! machine (`<config>/plugin-trust.json`).
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+use serde::{Deserialize, Serialize};
+
+/// Folder of the plugins inside the workspace
+pub const PLUGINS_DIR: &str = "plugins";
+
+/// File name of a plugin's manifest
+pub const MANIFEST_FILE: &str = "plugin.json";
+
+/// File name of the approved plugins inside the config directory
+pub const TRUST_FILE: &str = "plugin-trust.json";
+
+/// Largest module that is loaded
+pub const MAX_MODULE_BYTES: u64 = 32 * 1024 * 1024;
+
+/// What a plugin may do through the host API
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Capability {
+    /// Read and list the workspace's notes
+    ReadNotes,
+    /// Create and overwrite the workspace's notes
+    WriteNotes,
+}
+
+/// How a plugin command is used
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CommandKind {
+    /// Input from the frontend, output back to it
+    Command,
+    /// A note's content in, its new content out
+    Transform,
+    /// A note's content in, the exported file out
+    Exporter,
+}
+
+/// A command a plugin adds
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PluginCommand {
+    /// Name passed to the module's `run`
+    pub name: String,
+    /// Label shown in the UI
+    #[serde(default)]
+    pub title: String,
+    pub kind: CommandKind,
+    /// Extension of the exported file (`exporter`)
+    #[serde(default)]
+    pub extension: Option<String>,
+}
+
+fn default_module() -> String {
+    "plugin.wasm".to_string()
+}
+
+/// Contents of `plugin.json`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PluginManifest {
+    /// Unique id (letters, digits, `-` and `_`)
+    pub id: String,
+    pub name: String,
+    #[serde(default)]
+    pub version: String,
+    #[serde(default)]
+    pub description: String,
+    /// File name of the module inside the plugin folder
+    #[serde(default = "default_module")]
+    pub module: String,
+    #[serde(default)]
+    pub capabilities: Vec<Capability>,
+    #[serde(default)]
+    pub commands: Vec<PluginCommand>,
+}
+
+impl PluginManifest {
+    /// Checks the manifest after it is parsed
+    pub fn validate(&self) -> Result<(), String> {
+        let valid_id = !self.id.is_empty()
+            && self.id.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+        if !valid_id {
+            return Err(format!("Invalid plugin id: {}", self.id));
+        }
+        let module = Path::new(&self.module);
+        if module.components().count() != 1 || !matches!(module.components().next(), Some(Component::Normal(_))) {
+            return Err(format!("Plugin {}: module must be a file in the plugin folder", self.id));
+        }
+        for command in &self.commands {
+            if command.name.trim().is_empty() {
+                return Err(format!("Plugin {}: command without a name", self.id));
+            }
+            if command.kind == CommandKind::Exporter && command.extension.is_none() {
+                return Err(format!("Plugin {}: exporter {} has no extension", self.id, command.name));
+            }
+        }
+        Ok(())
+    }
+
+    /// Finds a command by name
+    pub fn command(&self, name: &str) -> Option<&PluginCommand> {
+        self.commands.iter().find(|c| c.name == name)
+    }
+
+    /// Checks if the manifest grants a capability
+    pub fn allows(&self, capability: Capability) -> bool {
+        self.capabilities.contains(&capability)
+    }
+}
+
+/// A plugin found in the workspace
+#[derive(Debug, Clone, PartialEq)]
+pub struct Plugin {
+    pub manifest: PluginManifest,
+    /// The plugin's folder
+    pub dir: PathBuf,
+    /// Hash of the module
+    pub module_hash: String,
+    /// Fingerprint of the manifest and module, as approved by the user
+    pub fingerprint: String,
+}
+
+impl Plugin {
+    /// Reads the module, refusing it if it changed since it was discovered
+    pub fn read_module(&self) -> Result<Vec<u8>, String> {
+        let module = fs::read(self.dir.join(&self.manifest.module))
+            .map_err(|e| format!("Failed to read module {}: {}", self.manifest.module, e))?;
+        if blake3::hash(&module).to_hex().as_str() != self.module_hash {
+            return Err(format!("Plugin {} changed on disk, reload the plugins", self.manifest.id));
+        }
+        Ok(module)
+    }
+}
+
+/// A plugin folder that could not be loaded
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct PluginError {
+    /// Name of the plugin folder
+    pub folder: String,
+    pub error: String,
+}
+
+/// Finds the plugins of a workspace
+///
+/// Folders with a missing or invalid manifest or module are reported as
+/// errors; plugins with an id already taken are skipped.
+pub fn discover(workspace_root: &Path) -> (Vec<Plugin>, Vec<PluginError>) {
+    let mut plugins: Vec<Plugin> = Vec::new();
+    let mut errors = Vec::new();
+    let Ok(entries) = fs::read_dir(workspace_root.join(PLUGINS_DIR)) else {
+        return (plugins, errors);
+    };
+    let mut dirs: Vec<PathBuf> = entries
+        .filter_map(Result::ok)
+        .map(|entry| entry.path())
+        .filter(|path| path.is_dir())
+        .collect();
+    dirs.sort();
+
+    for dir in dirs {
+        let folder = dir.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+        match load(&dir) {
+            Ok(plugin) if plugins.iter().any(|p| p.manifest.id == plugin.manifest.id) => errors.push(PluginError {
+                folder,
+                error: format!("Duplicate plugin id: {}", plugin.manifest.id),
+            }),
+            Ok(plugin) => plugins.push(plugin),
+            Err(error) => errors.push(PluginError { folder, error }),
+        }
+    }
+    (plugins, errors)
+}
+
+// Helper: Reads and checks one plugin folder
+fn load(dir: &Path) -> Result<Plugin, String> {
+    let manifest_json = fs::read(dir.join(MANIFEST_FILE))
+        .map_err(|e| format!("Failed to read {}: {}", MANIFEST_FILE, e))?;
+    let manifest: PluginManifest = serde_json::from_slice(&manifest_json)
+        .map_err(|e| format!("Failed to parse {}: {}", MANIFEST_FILE, e))?;
+    manifest.validate()?;
+
+    let module_path = dir.join(&manifest.module);
+    let size = fs::metadata(&module_path)
+        .map_err(|e| format!("Failed to read module {}: {}", manifest.module, e))?
+        .len();
+    if size > MAX_MODULE_BYTES {
+        return Err(format!("Module {} is larger than {} MB", manifest.module, MAX_MODULE_BYTES / 1024 / 1024));
+    }
+    let module = fs::read(&module_path)
+        .map_err(|e| format!("Failed to read module {}: {}", manifest.module, e))?;
+
+    let module_hash = blake3::hash(&module).to_hex().to_string();
+    let mut fingerprinted = manifest_json;
+    fingerprinted.extend_from_slice(module_hash.as_bytes());
+    Ok(Plugin {
+        manifest,
+        dir: dir.to_path_buf(),
+        fingerprint: blake3::hash(&fingerprinted).to_hex().to_string(),
+        module_hash,
+    })
+}
+
+/// Resolves a workspace-relative note path given by a plugin
+///
+/// The path must be relative, stay inside the workspace and name a `.md`
+/// file; the caller validates the result against the workspace (symlinks).
+pub fn note_path(workspace_root: &Path, relative: &str) -> Result<PathBuf, String> {
+    let path = Path::new(relative);
+    if relative.is_empty() || !path.components().all(|c| matches!(c, Component::Normal(_))) {
+        return Err(format!("Invalid note path: {}", relative));
+    }
+    if path.extension().map(|e| e.to_string_lossy().to_lowercase()) != Some("md".to_string()) {
+        return Err(format!("Not a note: {}", relative));
+    }
+    Ok(workspace_root.join(path))
+}
+
+/// Plugins approved on this machine, per workspace
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct PluginTrust {
+    /// Fingerprints of the approved plugins, keyed by workspace and id
+    #[serde(default)]
+    approved: BTreeMap<String, BTreeMap<String, String>>,
+}
+
+impl PluginTrust {
+    /// Loads the approvals from `config_dir` (none if missing)
+    pub fn load(config_dir: &Path) -> Result<Self, String> {
+        match fs::read_to_string(config_dir.join(TRUST_FILE)) {
+            Ok(json) => serde_json::from_str(&json)
+                .map_err(|e| format!("Failed to parse plugin approvals: {}", e)),
+            Err(_) => Ok(Self::default()),
+        }
+    }
+
+    /// Saves the approvals to `config_dir`
+    pub fn save(&self, config_dir: &Path) -> Result<(), String> {
+        let json = serde_json::to_string_pretty(self)
+            .map_err(|e| format!("Failed to serialize plugin approvals: {}", e))?;
+        fs::write(config_dir.join(TRUST_FILE), json)
+            .map_err(|e| format!("Failed to save plugin approvals: {}", e))
+    }
+
+    /// Checks if the user approved this exact plugin for the workspace
+    pub fn is_trusted(&self, workspace: &str, plugin: &Plugin) -> bool {
+        self.approved
+            .get(workspace)
+            .and_then(|plugins| plugins.get(&plugin.manifest.id))
+            == Some(&plugin.fingerprint)
+    }
+
+    /// Approves a plugin for the workspace, replacing an earlier approval
+    pub fn approve(&mut self, workspace: &str, plugin: &Plugin) {
+        self.approved
+            .entry(workspace.to_string())
+            .or_default()
+            .insert(plugin.manifest.id.clone(), plugin.fingerprint.clone());
+    }
+
+    /// Withdraws the approval of a plugin; returns whether it was approved
+    pub fn revoke(&mut self, workspace: &str, id: &str) -> bool {
+        self.approved
+            .get_mut(workspace)
+            .is_some_and(|plugins| plugins.remove(id).is_some())
+    }
+}
+
+// ============================================================================
+// TESTS
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn add_plugin(root: &Path, folder: &str, manifest: &str, module: &[u8]) {
+        let dir = root.join(PLUGINS_DIR).join(folder);
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join(MANIFEST_FILE), manifest).unwrap();
+        fs::write(dir.join("plugin.wasm"), module).unwrap();
+    }
+
+    #[test]
+    fn test_discover_and_trust() {
+        let workspace = TempDir::new().unwrap();
+        let root = workspace.path();
+        add_plugin(root, "docx", r#"{
+            "id": "docx-export", "name": "DOCX export", "capabilities": ["read_notes"],
+            "commands": [{ "name": "export", "kind": "exporter", "extension": "docx" }]
+        }"#, b"\0asm v1");
+        add_plugin(root, "broken", r#"{ "id": "../escape", "name": "Broken" }"#, b"");
+        add_plugin(root, "no-ext", r#"{
+            "id": "no-ext", "name": "No extension", "commands": [{ "name": "x", "kind": "exporter" }]
+        }"#, b"");
+
+        let (plugins, errors) = discover(root);
+        assert_eq!(plugins.len(), 1);
+        assert_eq!(errors.iter().map(|e| e.folder.as_str()).collect::<Vec<_>>(), ["broken", "no-ext"]);
+        let plugin = &plugins[0];
+        assert!(plugin.manifest.allows(Capability::ReadNotes));
+        assert!(!plugin.manifest.allows(Capability::WriteNotes));
+        assert_eq!(plugin.manifest.command("export").unwrap().kind, CommandKind::Exporter);
+
+        let config = TempDir::new().unwrap();
+        let mut trust = PluginTrust::load(config.path()).unwrap();
+        assert!(!trust.is_trusted("/notes", plugin));
+        trust.approve("/notes", plugin);
+        trust.save(config.path()).unwrap();
+        assert!(PluginTrust::load(config.path()).unwrap().is_trusted("/notes", plugin));
+
+        // A new module (e.g. synced from another machine) needs a new approval
+        add_plugin(root, "docx", &fs::read_to_string(plugin.dir.join(MANIFEST_FILE)).unwrap(), b"\0asm v2");
+        assert!(plugin.read_module().is_err());
+        let (updated, _) = discover(root);
+        assert!(!trust.is_trusted("/notes", &updated[0]));
+        assert_eq!(updated[0].read_module().unwrap(), b"\0asm v2");
+        assert!(trust.revoke("/notes", "docx-export"));
+    }
+
+    #[test]
+    fn test_note_path_stays_in_workspace() {
+        let root = Path::new("/notes");
+        assert_eq!(note_path(root, "a/plan.md").unwrap(), root.join("a/plan.md"));
+        assert!(note_path(root, "../plan.md").is_err());
+        assert!(note_path(root, "/etc/plan.md").is_err());
+        assert!(note_path(root, "a/./plan.md").is_ok());
+        assert!(note_path(root, "image.png").is_err());
+        assert!(note_path(root, "").is_err());
+    }
+}
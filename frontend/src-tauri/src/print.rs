@@ -0,0 +1,174 @@
+//! Print Layout
+//!
+//! Builds the printable document for a note: the HTML of the shared
+//! rendering path (see `crate::render`) plus print CSS for paper size and
+//! margins, page breaks before top-level headings, and running headers and
+//! footers with the note title and page numbers (`@page` margin boxes).
+
+use serde::{Deserialize, Serialize};
+use crate::render::{html_document, RenderOptions};
+
+/// Label prefix of print windows
+pub const PRINT_WINDOW_PREFIX: &str = "print-";
+
+/// Paper size of a printed note
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PageSize {
+    #[default]
+    A4,
+    A5,
+    Letter,
+    Legal,
+}
+
+impl PageSize {
+    fn css(self) -> &'static str {
+        match self {
+            PageSize::A4 => "A4",
+            PageSize::A5 => "A5",
+            PageSize::Letter => "letter",
+            PageSize::Legal => "legal",
+        }
+    }
+}
+
+/// Options for printing a note
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PrintOptions {
+    #[serde(default)]
+    pub page_size: PageSize,
+    #[serde(default)]
+    pub landscape: bool,
+    /// Page margin in millimetres
+    #[serde(default = "default_margin")]
+    pub margin_mm: u32,
+    /// Start every `# heading` on a new page
+    #[serde(default = "default_true")]
+    pub break_before_h1: bool,
+    /// Note title at the top of every page
+    #[serde(default = "default_true")]
+    pub header: bool,
+    /// `Page N of M` at the bottom of every page
+    #[serde(default = "default_true")]
+    pub page_numbers: bool,
+    /// Rendering options (images are always embedded for printing)
+    #[serde(default)]
+    pub render: RenderOptions,
+}
+
+fn default_margin() -> u32 {
+    18
+}
+
+fn default_true() -> bool {
+    true
+}
+
+impl Default for PrintOptions {
+    fn default() -> Self {
+        Self {
+            page_size: PageSize::default(),
+            landscape: false,
+            margin_mm: default_margin(),
+            break_before_h1: true,
+            header: true,
+            page_numbers: true,
+            render: RenderOptions::default(),
+        }
+    }
+}
+
+/// Wraps a rendered note into a document laid out for printing
+pub fn print_document(title: &str, body: &str, options: &PrintOptions) -> String {
+    let style = format!("<style>\n{}</style>\n</head>", print_stylesheet(title, options));
+    html_document(title, body).replacen("</head>", &style, 1)
+}
+
+/// Print CSS for a note titled `title`
+pub fn print_stylesheet(title: &str, options: &PrintOptions) -> String {
+    let orientation = if options.landscape { "landscape" } else { "portrait" };
+    let mut page = format!(
+        "  size: {} {};\n  margin: {}mm;\n",
+        options.page_size.css(),
+        orientation,
+        options.margin_mm
+    );
+    let margin_box = "font-size: 9pt; color: #59636e;";
+    if options.header {
+        page.push_str(&format!("  @top-center {{ content: {}; {} }}\n", css_string(title), margin_box));
+    }
+    if options.page_numbers {
+        page.push_str(&format!(
+            "  @bottom-right {{ content: \"Page \" counter(page) \" of \" counter(pages); {} }}\n",
+            margin_box
+        ));
+    }
+
+    let mut css = format!("@page {{\n{}}}\n", page);
+    css.push_str(
+        "@media print {\n\
+         \x20 .markdown-body { max-width: none; padding: 0; }\n\
+         \x20 h1, h2, h3, h4, h5, h6 { break-after: avoid; }\n\
+         \x20 pre, blockquote, table, img, .mermaid { break-inside: avoid; }\n",
+    );
+    if options.break_before_h1 {
+        css.push_str("  h1 { break-before: page; }\n  h1:first-child { break-before: avoid; }\n");
+    }
+    css.push_str("}\n");
+    css
+}
+
+/// Quotes text as a CSS string that cannot end the `<style>` element
+fn css_string(text: &str) -> String {
+    let mut out = String::from("\"");
+    for c in text.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '<' => out.push_str("\\3C "),
+            '\n' | '\r' => out.push(' '),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
+
+// ============================================================================
+// TESTS
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_print_stylesheet() {
+        let css = print_stylesheet("Trip \"plan\" </style>", &PrintOptions::default());
+        assert!(css.contains("size: A4 portrait;\n  margin: 18mm;"));
+        assert!(css.contains("@top-center { content: \"Trip \\\"plan\\\" \\3C /style>\";"));
+        assert!(css.contains("counter(page) \" of \" counter(pages)"));
+        assert!(css.contains("h1 { break-before: page; }"));
+
+        let options = PrintOptions {
+            page_size: PageSize::Letter,
+            landscape: true,
+            break_before_h1: false,
+            header: false,
+            page_numbers: false,
+            ..PrintOptions::default()
+        };
+        let css = print_stylesheet("Trip", &options);
+        assert!(css.starts_with("@page {\n  size: letter landscape;\n  margin: 18mm;\n}\n"));
+        assert!(!css.contains("break-before"));
+    }
+
+    #[test]
+    fn test_print_document() {
+        let doc = print_document("Trip", "<h1 id=\"trip\">Trip</h1>\n", &PrintOptions::default());
+        assert_eq!(doc.matches("<style>").count(), 2);
+        assert!(doc.contains("@page {"));
+        assert!(doc.contains("<article class=\"markdown-body\">\n<h1 id=\"trip\">Trip</h1>\n</article>"));
+    }
+}
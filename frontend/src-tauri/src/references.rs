@@ -0,0 +1,736 @@
+//! Reference Links and Footnotes
+//!
+//! Keeps the link and footnote notation of long documents tidy:
+//!
+//! - `normalize_links` converts inline links (`[text](url "title")`) to
+//!   reference links (`[text][1]` plus a `[1]: url "title"` definition at the
+//!   end of the note) or back. Links to the same target share a definition.
+//!   Images stay inline when converting to references.
+//! - `tidy_footnotes` renumbers footnotes `1, 2, 3, ...` in order of first
+//!   reference, moves their definitions to the end of the note in that
+//!   order and drops definitions nothing refers to.
+//!
+//! Notes are parsed into blocks (text lines, link definitions, footnote
+//! definitions with their indented continuation lines, and verbatim
+//! frontmatter and fenced code) and text into inline nodes (links, images
+//! and footnote references, skipping code spans and `[[wiki links]]`).
+//! Nodes that are not rewritten are printed exactly as written. Links and
+//! link definitions are read within one line.
+
+use std::collections::{HashMap, HashSet};
+use serde::{Deserialize, Serialize};
+use crate::frontmatter;
+
+/// Notation `normalize_links` converts links to
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LinkNotation {
+    /// `[text](url)`
+    Inline,
+    /// `[text][label]` with a `[label]: url` definition
+    Reference,
+}
+
+/// Result of `normalize_links`
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct LinkNormalizeReport {
+    /// Links rewritten
+    pub converted: usize,
+    /// Definitions added (to references) or removed (to inline)
+    pub definitions: usize,
+    /// Labels of reference links without a definition, left as written
+    pub unresolved: Vec<String>,
+}
+
+/// Result of `tidy_footnotes`
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct FootnoteReport {
+    /// Footnotes kept
+    pub footnotes: usize,
+    /// Footnotes whose label changed
+    pub renumbered: usize,
+    /// Labels of definitions dropped as unreferenced or duplicate
+    pub removed: Vec<String>,
+    /// Labels referenced without a definition, left as written
+    pub missing: Vec<String>,
+}
+
+/// Converts the links of a note to `notation`
+pub fn normalize_links(content: &str, notation: LinkNotation) -> (String, LinkNormalizeReport) {
+    let mut doc = Document::parse(content);
+    let mut report = LinkNormalizeReport::default();
+
+    match notation {
+        LinkNotation::Reference => {
+            let mut labels: HashSet<String> = doc.definitions().map(|d| label_key(&d.label)).collect();
+            let mut by_target: HashMap<(String, Option<String>), String> = HashMap::new();
+            for definition in doc.definitions() {
+                by_target
+                    .entry((definition.url.clone(), definition.title.clone()))
+                    .or_insert_with(|| definition.label.clone());
+            }
+
+            let mut added = Vec::new();
+            doc.rewrite_text(&mut |inline, text| {
+                let Inline::Link(link) = inline else { return None };
+                let Target::Inline { url, title } = link.target else { return None };
+                if link.image || url.is_empty() {
+                    return None;
+                }
+                let key = (url.to_string(), title.map(String::from));
+                let label = by_target
+                    .entry(key)
+                    .or_insert_with(|| {
+                        let label = (1..)
+                            .map(|n: usize| n.to_string())
+                            .find(|n| !labels.contains(n))
+                            .expect("unbounded counter always finds a free label");
+                        labels.insert(label.clone());
+                        added.push(Block::Definition(Definition {
+                            label: label.clone(),
+                            url: url.to_string(),
+                            title: title.map(String::from),
+                        }));
+                        label
+                    })
+                    .clone();
+                report.converted += 1;
+                Some(format!("[{}][{}]", text, label))
+            });
+            report.definitions = added.len();
+            doc.append(added);
+        }
+        LinkNotation::Inline => {
+            let mut definitions: HashMap<String, Definition> = HashMap::new();
+            for definition in doc.definitions() {
+                definitions.entry(label_key(&definition.label)).or_insert_with(|| definition.clone());
+            }
+
+            let mut used: HashSet<String> = HashSet::new();
+            doc.rewrite_text(&mut |inline, text| {
+                let Inline::Link(link) = inline else { return None };
+                let label = match link.target {
+                    Target::Inline { .. } => return None,
+                    Target::Reference("") | Target::Shortcut => link.text,
+                    Target::Reference(label) => label,
+                };
+                let Some(definition) = definitions.get(&label_key(label)) else {
+                    if !matches!(link.target, Target::Shortcut) && !report.unresolved.iter().any(|l| l == label) {
+                        report.unresolved.push(label.to_string());
+                    }
+                    return None;
+                };
+                used.insert(label_key(label));
+                report.converted += 1;
+                let title = definition.title.as_ref().map_or(String::new(), |t| format!(" {}", t));
+                Some(format!("{}[{}]({}{})", if link.image { "!" } else { "" }, text, definition.url, title))
+            });
+
+            // Every use of a used definition was inlined, so it can go
+            let inlined = |block: &Block| matches!(block, Block::Definition(d) if used.contains(&label_key(&d.label)));
+            report.definitions = doc.blocks.iter().filter(|block| inlined(block)).count();
+            doc.remove(inlined);
+        }
+    }
+
+    (doc.print(), report)
+}
+
+/// Renumbers footnotes in order of first reference and collects their
+/// definitions at the end of the note
+pub fn tidy_footnotes(content: &str) -> (String, FootnoteReport) {
+    let mut doc = Document::parse(content);
+    let mut report = FootnoteReport::default();
+
+    let mut defined: HashSet<String> = HashSet::new();
+    for block in &doc.blocks {
+        if let Block::Footnote(footnote) = block {
+            if !defined.insert(label_key(&footnote.label)) {
+                report.removed.push(footnote.label.clone());
+            }
+        }
+    }
+
+    // First pass: order of first reference (definitions included)
+    let mut order: Vec<String> = Vec::new();
+    doc.rewrite_text(&mut |inline, _| {
+        if let Inline::FootnoteRef(label) = inline {
+            let key = label_key(label);
+            if !defined.contains(&key) {
+                if !report.missing.iter().any(|l| l == label) {
+                    report.missing.push(label.to_string());
+                }
+            } else if !order.contains(&key) {
+                order.push(key);
+            }
+        }
+        None
+    });
+    let numbers: HashMap<String, String> =
+        order.iter().enumerate().map(|(i, key)| (key.clone(), (i + 1).to_string())).collect();
+
+    doc.rewrite_text(&mut |inline, _| match inline {
+        Inline::FootnoteRef(label) => numbers.get(&label_key(label)).map(|n| format!("[^{}]", n)),
+        _ => None,
+    });
+
+    let mut footnotes: HashMap<String, Footnote> = HashMap::new();
+    for block in &doc.blocks {
+        if let Block::Footnote(footnote) = block {
+            let key = label_key(&footnote.label);
+            if !numbers.contains_key(&key) && !footnotes.contains_key(&key) {
+                report.removed.push(footnote.label.clone());
+            }
+            footnotes.entry(key).or_insert_with(|| footnote.clone());
+        }
+    }
+    doc.remove(|block| matches!(block, Block::Footnote(_)));
+
+    let mut tidy = Vec::new();
+    for key in &order {
+        let Some(mut footnote) = footnotes.remove(key) else { continue };
+        let number = numbers[key].clone();
+        if footnote.label != number {
+            report.renumbered += 1;
+        }
+        footnote.label = number;
+        tidy.push(Block::Footnote(footnote));
+    }
+    report.footnotes = tidy.len();
+    doc.append(tidy);
+
+    (doc.print(), report)
+}
+
+// ============================================================================
+// DOCUMENT
+// ============================================================================
+
+/// A note split into blocks
+#[derive(Debug, Clone)]
+struct Document {
+    blocks: Vec<Block>,
+    newline: &'static str,
+    trailing_newline: bool,
+}
+
+#[derive(Debug, Clone)]
+enum Block {
+    /// Frontmatter and fenced code lines, left as written
+    Verbatim(String),
+    /// Any other line
+    Text(String),
+    Definition(Definition),
+    Footnote(Footnote),
+}
+
+/// A link reference definition `[label]: url "title"`
+#[derive(Debug, Clone)]
+struct Definition {
+    label: String,
+    /// Destination as written (`<...>` included)
+    url: String,
+    /// Title with its quotes
+    title: Option<String>,
+}
+
+/// A footnote definition `[^label]: text` and its continuation lines
+#[derive(Debug, Clone)]
+struct Footnote {
+    label: String,
+    /// Text after the marker, then the continuation lines as written
+    lines: Vec<String>,
+}
+
+/// An inline node
+enum Inline<'a> {
+    Link(Link<'a>),
+    FootnoteRef(&'a str),
+}
+
+/// A link or image
+struct Link<'a> {
+    image: bool,
+    text: &'a str,
+    target: Target<'a>,
+    /// The whole link as written
+    raw: &'a str,
+}
+
+#[derive(Clone, Copy)]
+enum Target<'a> {
+    /// `(url "title")`
+    Inline { url: &'a str, title: Option<&'a str> },
+    /// `[label]`; empty for a collapsed `[]`
+    Reference(&'a str),
+    /// No target: `[text]` is its own label
+    Shortcut,
+}
+
+/// Called with each inline node and its rewritten text; returns the
+/// node's replacement, or `None` to keep it
+type Visitor<'v> = dyn FnMut(&Inline<'_>, &str) -> Option<String> + 'v;
+
+impl Document {
+    fn parse(content: &str) -> Self {
+        let (_, body) = frontmatter::split(content);
+        let header = &content[..content.len() - body.len()];
+        let mut blocks: Vec<Block> = header.lines().map(|l| Block::Verbatim(l.to_string())).collect();
+
+        let lines: Vec<&str> = body.lines().collect();
+        let mut in_fence = false;
+        let mut i = 0;
+        while i < lines.len() {
+            let line = lines[i];
+            i += 1;
+            let trimmed = line.trim_start();
+            if trimmed.starts_with("```") || trimmed.starts_with("~~~") {
+                in_fence = !in_fence;
+                blocks.push(Block::Verbatim(line.to_string()));
+                continue;
+            }
+            if in_fence {
+                blocks.push(Block::Verbatim(line.to_string()));
+                continue;
+            }
+
+            if let Some((label, text)) = footnote_marker(line) {
+                let mut footnote = Footnote { label: label.to_string(), lines: vec![text.to_string()] };
+                while i < lines.len() {
+                    let next = lines[i];
+                    let continues = if next.trim().is_empty() {
+                        // Blank lines belong to the footnote if it continues after them
+                        lines[i..].iter().find(|l| !l.trim().is_empty()).is_some_and(|l| is_indented(l))
+                    } else {
+                        is_indented(next) || (!lines[i - 1].trim().is_empty() && is_lazy_continuation(next))
+                    };
+                    if !continues {
+                        break;
+                    }
+                    footnote.lines.push(next.to_string());
+                    i += 1;
+                }
+                blocks.push(Block::Footnote(footnote));
+            } else if let Some(definition) = parse_definition(line) {
+                blocks.push(Block::Definition(definition));
+            } else {
+                blocks.push(Block::Text(line.to_string()));
+            }
+        }
+
+        Self {
+            blocks,
+            newline: if content.contains("\r\n") { "\r\n" } else { "\n" },
+            trailing_newline: content.ends_with('\n'),
+        }
+    }
+
+    fn definitions(&self) -> impl Iterator<Item = &Definition> {
+        self.blocks.iter().filter_map(|block| match block {
+            Block::Definition(definition) => Some(definition),
+            _ => None,
+        })
+    }
+
+    /// Rewrites the inline nodes of text lines and footnotes, in order
+    fn rewrite_text(&mut self, visit: &mut Visitor<'_>) {
+        for block in &mut self.blocks {
+            match block {
+                Block::Text(line) => *line = rewrite(line, visit),
+                Block::Footnote(footnote) => {
+                    for line in &mut footnote.lines {
+                        *line = rewrite(line, visit);
+                    }
+                }
+                Block::Verbatim(_) | Block::Definition(_) => {}
+            }
+        }
+    }
+
+    /// Removes blocks, along with a blank line that would double up
+    fn remove(&mut self, remove: impl Fn(&Block) -> bool) {
+        let mut kept: Vec<Block> = Vec::with_capacity(self.blocks.len());
+        let mut after_removed = false;
+        for block in std::mem::take(&mut self.blocks) {
+            if remove(&block) {
+                after_removed = true;
+                continue;
+            }
+            let blank = is_blank(&block);
+            if after_removed && blank && kept.last().map_or(true, is_blank) {
+                continue;
+            }
+            after_removed = false;
+            kept.push(block);
+        }
+        if after_removed {
+            while kept.last().is_some_and(is_blank) {
+                kept.pop();
+            }
+        }
+        self.blocks = kept;
+    }
+
+    /// Appends blocks at the end, after a blank line unless they continue a
+    /// run of the same kind
+    fn append(&mut self, blocks: Vec<Block>) {
+        let Some(first) = blocks.first() else { return };
+        while self.blocks.last().is_some_and(is_blank) {
+            self.blocks.pop();
+        }
+        let continues = match (self.blocks.last(), first) {
+            (Some(Block::Definition(_)), Block::Definition(_)) => true,
+            (Some(Block::Footnote(_)), Block::Footnote(_)) => true,
+            (last, _) => last.is_none(),
+        };
+        if !continues {
+            self.blocks.push(Block::Text(String::new()));
+        }
+        self.blocks.extend(blocks);
+        self.trailing_newline = true;
+    }
+
+    fn print(&self) -> String {
+        let mut lines: Vec<String> = Vec::with_capacity(self.blocks.len());
+        for block in &self.blocks {
+            match block {
+                Block::Verbatim(line) | Block::Text(line) => lines.push(line.clone()),
+                Block::Definition(d) => lines.push(match &d.title {
+                    Some(title) => format!("[{}]: {} {}", d.label, d.url, title),
+                    None => format!("[{}]: {}", d.label, d.url),
+                }),
+                Block::Footnote(footnote) => {
+                    lines.push(format!("[^{}]: {}", footnote.label, footnote.lines[0].trim_start()));
+                    lines.extend(footnote.lines[1..].iter().cloned());
+                }
+            }
+        }
+        let mut out = lines.join(self.newline);
+        if self.trailing_newline && !lines.is_empty() {
+            out.push_str(self.newline);
+        }
+        out
+    }
+}
+
+fn is_blank(block: &Block) -> bool {
+    matches!(block, Block::Text(line) if line.trim().is_empty())
+}
+
+fn is_indented(line: &str) -> bool {
+    line.starts_with("    ") || line.starts_with('\t')
+}
+
+// Helper: A line that continues the paragraph above it rather than
+// starting a block of its own
+fn is_lazy_continuation(line: &str) -> bool {
+    let trimmed = line.trim_start();
+    footnote_marker(line).is_none()
+        && parse_definition(line).is_none()
+        && !trimmed.starts_with(['#', '>', '|', '-', '*', '+'])
+        && !trimmed.starts_with("```")
+        && !trimmed.starts_with("~~~")
+}
+
+// Helper: Label and text of a `[^label]: text` line
+pub(crate) fn footnote_marker(line: &str) -> Option<(&str, &str)> {
+    let rest = line.strip_prefix("[^")?;
+    let close = rest.find(']')?;
+    let label = &rest[..close];
+    if label.is_empty() || label.contains(char::is_whitespace) {
+        return None;
+    }
+    Some((label, rest[close + 1..].strip_prefix(':')?))
+}
+
+// Helper: Parses `[label]: url "title"` (up to three spaces of indent)
+fn parse_definition(line: &str) -> Option<Definition> {
+    let indent = line.len() - line.trim_start_matches(' ').len();
+    if indent > 3 {
+        return None;
+    }
+    let rest = line[indent..].strip_prefix('[')?;
+    let close = rest.find(']')?;
+    let label = &rest[..close];
+    if label.trim().is_empty() || label.starts_with('^') || label.contains('[') {
+        return None;
+    }
+    let rest = rest[close + 1..].strip_prefix(':')?.trim_start();
+    let (url, rest) = destination(rest)?;
+    if url.is_empty() {
+        return None;
+    }
+    let rest = rest.trim();
+    let title = if rest.is_empty() {
+        None
+    } else {
+        let (title, after) = title(rest)?;
+        if !after.trim().is_empty() {
+            return None;
+        }
+        Some(title.to_string())
+    };
+    Some(Definition { label: label.to_string(), url: url.to_string(), title })
+}
+
+/// Matching key of a label: case and whitespace runs do not matter
+fn label_key(label: &str) -> String {
+    label.split_whitespace().collect::<Vec<_>>().join(" ").to_lowercase()
+}
+
+// ============================================================================
+// INLINE NODES
+// ============================================================================
+
+// Helper: Rewrites the inline nodes of a line, innermost first
+fn rewrite(line: &str, visit: &mut Visitor<'_>) -> String {
+    let bytes = line.as_bytes();
+    let mut out = String::with_capacity(line.len());
+    let mut copied = 0;
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'\\' => i += 2,
+            b'`' => i = skip_code_span(line, i),
+            b'[' if line[i..].starts_with("[[") => {
+                i = line[i + 2..].find("]]").map_or(i + 2, |end| i + 2 + end + 2);
+            }
+            b'[' | b'!' => {
+                let Some((inline, end)) = parse_inline(line, i) else {
+                    i += 1;
+                    continue;
+                };
+                out.push_str(&line[copied..i]);
+                let replacement = match &inline {
+                    Inline::Link(link) => {
+                        let text = rewrite(link.text, visit);
+                        let replacement = visit(&inline, &text);
+                        replacement.unwrap_or_else(|| {
+                            let prefix = if link.image { 2 } else { 1 };
+                            format!("{}{}{}", &link.raw[..prefix], text, &link.raw[prefix + link.text.len()..])
+                        })
+                    }
+                    Inline::FootnoteRef(_) => visit(&inline, "").unwrap_or_else(|| line[i..end].to_string()),
+                };
+                out.push_str(&replacement);
+                copied = end;
+                i = end;
+            }
+            _ => i += 1,
+        }
+    }
+    out.push_str(&line[copied.min(line.len())..]);
+    out
+}
+
+// Helper: The link, image or footnote reference starting at `start`, and
+// where it ends
+fn parse_inline(line: &str, start: usize) -> Option<(Inline<'_>, usize)> {
+    let image = line[start..].starts_with("![");
+    let open = if image { start + 1 } else { start };
+    if !line[open..].starts_with('[') {
+        return None;
+    }
+
+    if !image {
+        if let Some(rest) = line[open..].strip_prefix("[^") {
+            let close = rest.find(']')?;
+            let label = &rest[..close];
+            if label.is_empty() || label.contains(char::is_whitespace) {
+                return None;
+            }
+            return Some((Inline::FootnoteRef(label), open + 2 + close + 1));
+        }
+    }
+
+    let close = closing_bracket(line, open)?;
+    let text = &line[open + 1..close];
+    let after = &line[close + 1..];
+
+    let (target, end) = if let Some(inner) = after.strip_prefix('(') {
+        let (url, rest) = destination(inner.trim_start())?;
+        let rest_trimmed = rest.trim_start();
+        let (title, rest) = if rest_trimmed.len() < rest.len() && rest_trimmed.starts_with(['"', '\'', '(']) {
+            let (title, rest) = title(rest_trimmed)?;
+            (Some(title), rest)
+        } else {
+            (None, rest)
+        };
+        let rest = rest.trim_start().strip_prefix(')')?;
+        (Target::Inline { url, title }, line.len() - rest.len())
+    } else if let Some(inner) = after.strip_prefix('[') {
+        let label_end = inner.find(']')?;
+        let label = &inner[..label_end];
+        if label.contains('[') {
+            return None;
+        }
+        (Target::Reference(label), close + 1 + 1 + label_end + 1)
+    } else {
+        if text.trim().is_empty() {
+            return None;
+        }
+        (Target::Shortcut, close + 1)
+    };
+
+    Some((Inline::Link(Link { image, text, target, raw: &line[start..end] }), end))
+}
+
+// Helper: Index of the `]` closing the `[` at `open`, skipping escapes and code spans
+fn closing_bracket(line: &str, open: usize) -> Option<usize> {
+    let bytes = line.as_bytes();
+    let mut depth = 0usize;
+    let mut i = open;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'\\' => i += 1,
+            b'`' => {
+                i = skip_code_span(line, i);
+                continue;
+            }
+            b'[' => depth += 1,
+            b']' => {
+                depth -= 1;
+                if depth == 0 {
+                    return Some(i);
+                }
+            }
+            _ => {}
+        }
+        i += 1;
+    }
+    None
+}
+
+// Helper: Index after the code span starting at `start` (or after its
+// backticks if it is never closed)
+fn skip_code_span(line: &str, start: usize) -> usize {
+    let ticks = line[start..].len() - line[start..].trim_start_matches('`').len();
+    let fence = &line[start..start + ticks];
+    let mut search = start + ticks;
+    while let Some(found) = line[search..].find(fence) {
+        let end = search + found;
+        let run = line[end..].len() - line[end..].trim_start_matches('`').len();
+        if run == ticks {
+            return end + ticks;
+        }
+        search = end + run;
+    }
+    start + ticks
+}
+
+// Helper: A link destination (`<...>` or up to whitespace or an unbalanced
+// `)`) and the text after it
+fn destination(text: &str) -> Option<(&str, &str)> {
+    if text.starts_with('<') {
+        let end = text.find('>')?;
+        return Some((&text[..=end], &text[end + 1..]));
+    }
+    let bytes = text.as_bytes();
+    let mut depth = 0usize;
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'\\' => i += 1,
+            b'(' => depth += 1,
+            b')' if depth == 0 => break,
+            b')' => depth -= 1,
+            c if c.is_ascii_whitespace() => break,
+            _ => {}
+        }
+        i += 1;
+    }
+    let i = i.min(text.len());
+    Some((&text[..i], &text[i..]))
+}
+
+// Helper: A quoted or parenthesized title (delimiters included) and the
+// text after it
+fn title(text: &str) -> Option<(&str, &str)> {
+    let close = match text.chars().next()? {
+        '"' => '"',
+        '\'' => '\'',
+        '(' => ')',
+        _ => return None,
+    };
+    let bytes = text.as_bytes();
+    let mut i = 1;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'\\' => i += 1,
+            c if c == close as u8 => return Some((&text[..=i], &text[i + 1..])),
+            _ => {}
+        }
+        i += 1;
+    }
+    None
+}
+
+// ============================================================================
+// TESTS
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_normalize_links() {
+        let content = "---\ntitle: Paper\n---\n# Intro\n\
+            See [Rust](https://rust-lang.org \"Rust\") and [docs](https://docs.rs/a_(b)).\n\
+            Again [the language](https://rust-lang.org \"Rust\"), ![logo](logo.png), [[Wiki]] and `[x](y)`.\n\
+            - [ ] task with [old][ref]\n\
+            ```\n[code](https://example.com)\n```\n\n\
+            [ref]: https://example.com/old 'Old'\n";
+
+        let (references, report) = normalize_links(content, LinkNotation::Reference);
+        assert_eq!(
+            references,
+            "---\ntitle: Paper\n---\n# Intro\n\
+             See [Rust][1] and [docs][2].\n\
+             Again [the language][1], ![logo](logo.png), [[Wiki]] and `[x](y)`.\n\
+             - [ ] task with [old][ref]\n\
+             ```\n[code](https://example.com)\n```\n\n\
+             [ref]: https://example.com/old 'Old'\n\
+             [1]: https://rust-lang.org \"Rust\"\n\
+             [2]: https://docs.rs/a_(b)\n"
+        );
+        assert_eq!((report.converted, report.definitions), (3, 2));
+
+        let (inline, report) = normalize_links(&references, LinkNotation::Inline);
+        assert_eq!(
+            inline,
+            content.replace("[old][ref]", "[old](https://example.com/old 'Old')").replace("\n\n[ref]: https://example.com/old 'Old'", "")
+        );
+        assert_eq!((report.converted, report.definitions), (4, 3));
+
+        let (unchanged, report) = normalize_links("[a][missing] and [Shortcut] and [b][]\n\n[shortcut]: /s\n", LinkNotation::Inline);
+        assert_eq!(unchanged, "[a][missing] and [Shortcut](/s) and [b][]\n");
+        assert_eq!(report.unresolved, ["missing", "b"]);
+    }
+
+    #[test]
+    fn test_tidy_footnotes() {
+        let content = "Claim[^b] and another[^a], again[^b], lost[^x].\r\n\r\n\
+            [^a]: First source.\r\n\
+            [^unused]: Never cited.\r\n\r\n\
+            Closing words.\r\n\r\n\
+            [^b]: Second source with a note[^a].\r\n\r\n    Continued paragraph.\r\n\
+            [^a]: Duplicate.\r\n";
+
+        let (tidy, report) = tidy_footnotes(content);
+        assert_eq!(
+            tidy,
+            "Claim[^1] and another[^2], again[^1], lost[^x].\r\n\r\n\
+             Closing words.\r\n\r\n\
+             [^1]: Second source with a note[^2].\r\n\r\n    Continued paragraph.\r\n\
+             [^2]: First source.\r\n"
+        );
+        assert_eq!(report.footnotes, 2);
+        assert_eq!(report.renumbered, 2);
+        assert_eq!(report.removed, ["a", "unused"]);
+        assert_eq!(report.missing, ["x"]);
+
+        // Tidy notes stay as they are
+        assert_eq!(tidy_footnotes(&tidy).0, tidy);
+    }
+}
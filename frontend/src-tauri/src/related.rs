@@ -0,0 +1,234 @@
+//! Related Notes
+//!
+//! Ranks the notes related to one note for the related-notes panel by
+//! combining several signals, each adding to the score and explaining
+//! itself with a reason:
+//!
+//! | Signal | Weight | Reason |
+//! |--------|--------|--------|
+//! | Direct link either way | 3 | "links to this note", "linked from this note" |
+//! | Each shared link target | 1.5 | "links to the same note" |
+//! | Each shared backlink | 1 | "linked from the same note" |
+//! | Each shared tag | 1 | "shares 3 tags" |
+//! | Text similarity (TF-IDF cosine) | 4 × similarity | "similar content (42%)" |
+//! | Each co-edit session (max 3) | 1 | "edited together 2 times" |
+//!
+//! Two notes are edited together when both were saved or modified within
+//! `CO_EDIT_WINDOW_SECS` of each other (from the activity feed).
+
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use serde::Serialize;
+use crate::activity::{ActivityEntry, ActivityKind};
+use crate::metadata_cache::MetadataCache;
+
+/// Number of related notes returned when the caller does not say
+pub const DEFAULT_RELATED_LIMIT: usize = 10;
+
+/// Saves this close together count as one editing session
+pub const CO_EDIT_WINDOW_SECS: i64 = 15 * 60;
+
+/// Text similarity below this is not a signal
+pub const MIN_SIMILARITY: f64 = 0.1;
+
+const DIRECT_LINK_WEIGHT: f64 = 3.0;
+const SHARED_TARGET_WEIGHT: f64 = 1.5;
+const SHARED_SOURCE_WEIGHT: f64 = 1.0;
+const SHARED_TAG_WEIGHT: f64 = 1.0;
+const SIMILARITY_WEIGHT: f64 = 4.0;
+const CO_EDIT_WEIGHT: f64 = 1.0;
+const MAX_CO_EDITS: usize = 3;
+
+/// A note related to the current one
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct RelatedNote {
+    pub path: String,
+    pub title: String,
+    pub score: f64,
+    /// Why the note is related, strongest signal first
+    pub reasons: Vec<String>,
+}
+
+/// Signals besides the cache: text similarity and co-edit counts per note path
+#[derive(Debug, Clone, Default)]
+pub struct RelatedSignals {
+    pub similarity: HashMap<String, f64>,
+    pub co_edits: HashMap<String, usize>,
+}
+
+/// Ranks the notes of a cache by relatedness to `target`
+pub fn related_notes(
+    cache: &MetadataCache,
+    target: &str,
+    signals: &RelatedSignals,
+    limit: usize,
+) -> Vec<RelatedNote> {
+    let index = cache.link_index();
+    let Some(me) = index.paths.iter().position(|p| p == target) else {
+        return Vec::new();
+    };
+    let my_tags: BTreeSet<String> = cache.notes[target].tags.iter().map(|t| t.to_lowercase()).collect();
+
+    let mut related = Vec::new();
+    for (other, path) in index.paths.iter().enumerate() {
+        if other == me {
+            continue;
+        }
+        let mut reasons: Vec<(f64, String)> = Vec::new();
+
+        if index.outgoing[me].contains(&other) {
+            reasons.push((DIRECT_LINK_WEIGHT, "linked from this note".to_string()));
+        }
+        if index.incoming[me].contains(&other) {
+            reasons.push((DIRECT_LINK_WEIGHT, "links to this note".to_string()));
+        }
+        let targets = index.outgoing[me].intersection(&index.outgoing[other]).count();
+        if targets > 0 {
+            reasons.push((targets as f64 * SHARED_TARGET_WEIGHT, plural(targets, "links to the same note", "links to {} of the same notes")));
+        }
+        let sources = index.incoming[me].intersection(&index.incoming[other]).count();
+        if sources > 0 {
+            reasons.push((sources as f64 * SHARED_SOURCE_WEIGHT, plural(sources, "linked from the same note", "linked from {} of the same notes")));
+        }
+
+        let tags = cache.notes[path].tags.iter().filter(|t| my_tags.contains(&t.to_lowercase())).count();
+        if tags > 0 {
+            reasons.push((tags as f64 * SHARED_TAG_WEIGHT, plural(tags, "shares 1 tag", "shares {} tags")));
+        }
+
+        let similarity = signals.similarity.get(path).copied().unwrap_or(0.0);
+        if similarity >= MIN_SIMILARITY {
+            reasons.push((similarity * SIMILARITY_WEIGHT, format!("similar content ({:.0}%)", similarity * 100.0)));
+        }
+
+        let co_edits = signals.co_edits.get(path).copied().unwrap_or(0);
+        if co_edits > 0 {
+            let weight = co_edits.min(MAX_CO_EDITS) as f64 * CO_EDIT_WEIGHT;
+            reasons.push((weight, plural(co_edits, "edited together once", "edited together {} times")));
+        }
+
+        if reasons.is_empty() {
+            continue;
+        }
+        reasons.sort_by(|a, b| b.0.total_cmp(&a.0));
+        related.push(RelatedNote {
+            path: path.clone(),
+            title: index.titles[other].clone(),
+            score: (reasons.iter().map(|(w, _)| w).sum::<f64>() * 100.0).round() / 100.0,
+            reasons: reasons.into_iter().map(|(_, reason)| reason).collect(),
+        });
+    }
+
+    related.sort_by(|a, b| b.score.total_cmp(&a.score).then_with(|| a.path.cmp(&b.path)));
+    related.truncate(limit);
+    related
+}
+
+/// Counts the editing sessions `target` shared with other notes
+///
+/// `entries` must be oldest first, as stored by the activity feed.
+pub fn co_edit_counts(entries: &[ActivityEntry], target: &str) -> HashMap<String, usize> {
+    let edits: Vec<(i64, &str)> = entries
+        .iter()
+        .filter(|e| matches!(e.kind, ActivityKind::Saved | ActivityKind::Modified))
+        .filter_map(|e| e.path.as_deref().map(|path| (e.at, path)))
+        .collect();
+
+    // Editing sessions of the target: runs of edits closer than the window
+    let mut target_sessions: Vec<(i64, i64)> = Vec::new();
+    for &(at, _) in edits.iter().filter(|(_, p)| *p == target) {
+        match target_sessions.last_mut() {
+            Some((_, end)) if at - *end <= CO_EDIT_WINDOW_SECS => *end = at,
+            _ => target_sessions.push((at, at)),
+        }
+    }
+
+    let mut sessions: BTreeMap<&str, BTreeSet<usize>> = BTreeMap::new();
+    for &(at, path) in edits.iter().filter(|(_, p)| *p != target) {
+        let session = target_sessions
+            .iter()
+            .position(|&(start, end)| at >= start - CO_EDIT_WINDOW_SECS && at <= end + CO_EDIT_WINDOW_SECS);
+        if let Some(session) = session {
+            sessions.entry(path).or_default().insert(session);
+        }
+    }
+    sessions
+        .into_iter()
+        .map(|(path, sessions)| (path.to_string(), sessions.len()))
+        .collect()
+}
+
+fn plural(count: usize, one: &str, many: &str) -> String {
+    if count == 1 {
+        one.to_string()
+    } else {
+        many.replace("{}", &count.to_string())
+    }
+}
+
+// ============================================================================
+// TESTS
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::links::NoteLinks;
+    use crate::metadata_cache::CachedNote;
+
+    fn note(tags: &[&str], body: &str) -> CachedNote {
+        CachedNote {
+            title: "Note".into(),
+            tags: tags.iter().map(|t| t.to_string()).collect(),
+            links: NoteLinks::from_body(body),
+            ..CachedNote::default()
+        }
+    }
+
+    #[test]
+    fn test_signals_combine_with_reasons() {
+        let mut cache = MetadataCache::default();
+        cache.notes.insert("plan.md".into(), note(&["work", "q3"], "See [[roadmap]] and [[budget]]"));
+        cache.notes.insert("roadmap.md".into(), note(&[], ""));
+        cache.notes.insert("budget.md".into(), note(&[], ""));
+        cache.notes.insert("review.md".into(), note(&["Work", "q3"], "[[roadmap]] [[budget]]"));
+        cache.notes.insert("recipes.md".into(), note(&["food"], ""));
+
+        let signals = RelatedSignals {
+            similarity: [("recipes.md".to_string(), 0.05), ("review.md".to_string(), 0.5)].into(),
+            co_edits: [("budget.md".to_string(), 2)].into(),
+        };
+        let related = related_notes(&cache, "plan.md", &signals, 10);
+
+        let paths: Vec<&str> = related.iter().map(|r| r.path.as_str()).collect();
+        assert_eq!(paths, vec!["review.md", "budget.md", "roadmap.md"]);
+        assert_eq!(
+            related[0].reasons,
+            vec!["links to 2 of the same notes", "shares 2 tags", "similar content (50%)"]
+        );
+        assert_eq!(related[1].reasons, vec!["linked from this note", "edited together 2 times"]);
+        assert_eq!(related[0].score, 7.0);
+    }
+
+    #[test]
+    fn test_co_edit_sessions() {
+        let edit = |at, path: &str| ActivityEntry {
+            at,
+            kind: ActivityKind::Saved,
+            path: Some(path.to_string()),
+            detail: None,
+        };
+        let entries = vec![
+            edit(1_000, "plan.md"),
+            edit(1_100, "budget.md"),
+            edit(1_200, "budget.md"),
+            edit(50_000, "budget.md"),
+            edit(90_000, "plan.md"),
+            edit(90_300, "budget.md"),
+            edit(90_400, "ideas.md"),
+        ];
+        let counts = co_edit_counts(&entries, "plan.md");
+        assert_eq!(counts.get("budget.md"), Some(&2));
+        assert_eq!(counts.get("ideas.md"), Some(&1));
+        assert!(co_edit_counts(&entries, "missing.md").is_empty());
+    }
+}
@@ -0,0 +1,1371 @@
+//! Markdown Rendering
+//!
+//! Renders note markdown to the HTML the editor preview shows, so preview
+//! windows, share links, print and HTML exports share one rendering path:
+//!
+//! - Headings get GitHub-style `id`s (see `links::heading_anchor`)
+//! - `[[wiki links]]` resolve through the note lookup, aliases included;
+//!   unresolved ones get `class="wikilink unresolved"` and no `href`
+//! - `@handle` mentions of person pages become `class="mention"` links;
+//!   unresolved ones stay text (see `crate::people`)
+//! - Fenced code is wrapped in Prism-style `token` spans
+//! - ` ```mermaid ` blocks become `<pre class="mermaid">`, which the
+//!   Mermaid runtime of the preview turns into diagrams, and ` ```plantuml `
+//!   blocks `<pre class="plantuml">`; both carry their cache key as
+//!   `data-diagram` (see `crate::diagrams`)
+//! - Task lists, tables, `==highlights==`, `~~strikethrough~~` and `#tags`
+//! - `$inline$` and `$$display$$` math is typeset as MathML (see `crate::math`)
+//! - Obsidian callouts and GitHub alerts (`> [!note] Title`) become
+//!   `<blockquote class="callout">` with a title paragraph, colored by type;
+//!   `convert::html_to_markdown` turns them back into callouts
+//!
+//! Raw HTML in notes is escaped rather than passed through, so rendered
+//! notes are safe to show in share links. Embeds are expanded beforehand
+//! (see `crate::transclusion`); embeds left as written render as images
+//! (`![[photo.png]]`) or as an unresolved embed marker.
+
+use std::collections::HashMap;
+use serde::{Deserialize, Serialize};
+use crate::diagrams::{diagram_hash, DiagramLanguage};
+use crate::frontmatter;
+use crate::links::{heading_anchor, NoteLookup};
+use crate::math::tex_to_mathml;
+use crate::people;
+use crate::transclusion::DEFAULT_DEPTH;
+use crate::workspace_settings::relative_between;
+
+/// Extensions of embeds rendered as images
+const IMAGE_EXTENSIONS: &[&str] = &["png", "jpg", "jpeg", "gif", "webp", "svg", "bmp", "avif"];
+
+/// Styles of a standalone document
+const DOCUMENT_STYLE: &str = "\
+body { margin: 0; font-family: -apple-system, BlinkMacSystemFont, 'Segoe UI', sans-serif; line-height: 1.6; color: #1f2328; }
+.markdown-body { max-width: 48rem; margin: 0 auto; padding: 2rem; }
+pre { background: #f6f8fa; padding: 1rem; overflow-x: auto; border-radius: 6px; }
+code { font-family: ui-monospace, SFMono-Regular, Menlo, monospace; font-size: 0.9em; }
+blockquote { margin: 0; padding: 0 1rem; color: #59636e; border-left: 0.25em solid #d1d9e0; }
+table { border-collapse: collapse; }
+th, td { border: 1px solid #d1d9e0; padding: 0.4rem 0.8rem; }
+img { max-width: 100%; }
+mark { background: #fff8c5; }
+figure.diagram { margin: 1rem 0; text-align: center; }
+figure.diagram img { max-width: 100%; height: auto; }
+math[display=block] { margin: 1rem 0; overflow-x: auto; }
+.callout { margin: 1rem 0; padding: 0.5rem 1rem; color: inherit; background: #f6f8fa; border-left-color: #0969da; border-radius: 6px; }
+.callout-title { margin: 0 0 0.25rem; font-weight: 600; color: #0969da; }
+.callout-tip { border-left-color: #1a7f37; } .callout-tip .callout-title { color: #1a7f37; }
+.callout-important { border-left-color: #8250df; } .callout-important .callout-title { color: #8250df; }
+.callout-warning { border-left-color: #9a6700; } .callout-warning .callout-title { color: #9a6700; }
+.callout-caution { border-left-color: #cf222e; } .callout-caution .callout-title { color: #cf222e; }
+.task-list-item { list-style: none; }
+.wikilink.unresolved { color: #9a6700; }
+.tag { color: #0969da; }
+.token.comment { color: #59636e; }
+.token.string { color: #0a3069; }
+.token.number { color: #0550ae; }
+.token.keyword { color: #cf222e; }
+@media print { .markdown-body { max-width: none; padding: 0; } }
+";
+
+/// Options for rendering a note to HTML
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RenderOptions {
+    /// Embed nesting depth (0 leaves `![[embeds]]` as written)
+    #[serde(default = "default_depth")]
+    pub embed_depth: usize,
+    /// Wrap code in Prism-style token spans
+    #[serde(default = "default_true")]
+    pub highlight_code: bool,
+    /// Inline workspace images as `data:` URIs (print, share links)
+    #[serde(default)]
+    pub embed_images: bool,
+    /// Produce a complete HTML document with title and styles
+    #[serde(default)]
+    pub standalone: bool,
+    /// Replace diagram blocks with their cached SVG (see `crate::diagrams`)
+    #[serde(default = "default_true")]
+    pub cached_diagrams: bool,
+}
+
+fn default_depth() -> usize {
+    DEFAULT_DEPTH
+}
+
+fn default_true() -> bool {
+    true
+}
+
+impl Default for RenderOptions {
+    fn default() -> Self {
+        Self {
+            embed_depth: DEFAULT_DEPTH,
+            highlight_code: true,
+            embed_images: false,
+            standalone: false,
+            cached_diagrams: true,
+        }
+    }
+}
+
+/// Renders note content (frontmatter is dropped) to an HTML fragment
+///
+/// # Arguments
+/// * `content` - Note content, embeds already expanded
+/// * `from` - The note's path relative to the workspace, for link targets
+/// * `lookup` - Notes wiki links can resolve to
+/// * `highlight` - Whether to highlight fenced code
+pub fn render_markdown(content: &str, from: &str, lookup: &NoteLookup, highlight: bool) -> String {
+    let body = frontmatter::split(content).1;
+    let lines: Vec<String> = body.lines().map(expand_tabs).collect();
+
+    let mut renderer = Renderer {
+        from,
+        lookup,
+        highlight,
+        ids: HashMap::new(),
+    };
+    let mut out = String::new();
+    renderer.blocks(&lines, false, &mut out);
+    out
+}
+
+/// Wraps a rendered fragment into a standalone HTML document
+pub fn html_document(title: &str, body: &str) -> String {
+    format!(
+        "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n\
+         <meta name=\"viewport\" content=\"width=device-width, initial-scale=1\">\n\
+         <title>{}</title>\n<style>\n{}</style>\n</head>\n<body>\n\
+         <article class=\"markdown-body\">\n{}</article>\n</body>\n</html>\n",
+        escape_html(title),
+        DOCUMENT_STYLE,
+        body
+    )
+}
+
+struct Renderer<'a> {
+    from: &'a str,
+    lookup: &'a NoteLookup,
+    highlight: bool,
+    /// Heading ids handed out so far, for de-duplication
+    ids: HashMap<String, usize>,
+}
+
+/// A list item marker
+#[derive(Debug, Clone, Copy)]
+struct ListMarker {
+    /// Columns before the marker
+    indent: usize,
+    /// Start number of an ordered item
+    number: Option<u64>,
+    /// Byte offset of the item text
+    content: usize,
+}
+
+// ============================================================================
+// BLOCKS
+// ============================================================================
+
+impl Renderer<'_> {
+    /// Renders block-level markup; `tight` drops the `<p>` around paragraphs
+    fn blocks(&mut self, lines: &[String], tight: bool, out: &mut String) {
+        let mut i = 0;
+        while i < lines.len() {
+            let line = lines[i].as_str();
+            if line.trim().is_empty() {
+                i += 1;
+                continue;
+            }
+
+            if let Some(marker) = fence(line) {
+                let info = line.trim_start()[marker.len()..].trim();
+                let end = lines[i + 1..]
+                    .iter()
+                    .position(|l| closes_fence(l, marker))
+                    .map_or(lines.len(), |k| i + 1 + k);
+                self.code_block(info, &lines[i + 1..end], out);
+                i = end + 1;
+            } else if let Some((level, text)) = atx_heading(line) {
+                self.heading(level, text, out);
+                i += 1;
+            } else if is_rule(line) {
+                out.push_str("<hr>\n");
+                i += 1;
+            } else if quote_text(line).is_some() {
+                let end = self.quote(&lines[i..], out);
+                i += end;
+            } else if let Some((tex, end)) = math_block(lines, i) {
+                out.push_str(&tex_to_mathml(&tex, true));
+                out.push('\n');
+                i = end;
+            } else if let Some(marker) = list_marker(line) {
+                let end = list_end(lines, i, marker);
+                self.list(&lines[i..end], out);
+                i = end;
+            } else if is_table_start(lines, i) {
+                let end = lines[i + 2..]
+                    .iter()
+                    .position(|l| l.trim().is_empty() || !l.contains('|'))
+                    .map_or(lines.len(), |k| i + 2 + k);
+                self.table(&lines[i..end], out);
+                i = end;
+            } else {
+                i = self.paragraph(lines, i, tight, out);
+            }
+        }
+    }
+
+    /// Renders the paragraph starting at line `start`; returns the next line
+    fn paragraph(&mut self, lines: &[String], start: usize, tight: bool, out: &mut String) -> usize {
+        let mut end = start + 1;
+        let mut setext = None;
+        while end < lines.len() {
+            let line = lines[end].trim();
+            if !line.is_empty() && line.chars().all(|c| c == '=') {
+                setext = Some(1);
+                break;
+            }
+            if !line.is_empty() && line.chars().all(|c| c == '-') {
+                setext = Some(2);
+                break;
+            }
+            if line.is_empty() || starts_block(&lines[end]) {
+                break;
+            }
+            end += 1;
+        }
+
+        // Hard breaks become `\` + newline, which `inline` understands
+        let text = lines[start..end]
+            .iter()
+            .enumerate()
+            .map(|(k, line)| {
+                let hard = start + k + 1 < end && line.ends_with("  ");
+                let line = line.trim();
+                if hard { format!("{}\\", line) } else { line.to_string() }
+            })
+            .collect::<Vec<_>>()
+            .join("\n");
+
+        match setext {
+            Some(level) => {
+                self.heading(level, &text, out);
+                end + 1
+            }
+            None if tight => {
+                out.push_str(&self.inline(&text));
+                out.push('\n');
+                end
+            }
+            None => {
+                out.push_str(&format!("<p>{}</p>\n", self.inline(&text)));
+                end
+            }
+        }
+    }
+
+    fn heading(&mut self, level: usize, text: &str, out: &mut String) {
+        let anchor = heading_anchor(text);
+        let seen = self.ids.entry(anchor.clone()).or_insert(0);
+        let id = if *seen == 0 { anchor } else { format!("{}-{}", anchor, seen) };
+        *seen += 1;
+        out.push_str(&format!(
+            "<h{} id=\"{}\">{}</h{}>\n",
+            level,
+            escape_html(&id),
+            self.inline(text),
+            level
+        ));
+    }
+
+    fn code_block(&self, info: &str, lines: &[String], out: &mut String) {
+        let code = lines.iter().map(|l| format!("{}\n", l)).collect::<String>();
+        let lang = info.split_whitespace().next().unwrap_or("").to_lowercase();
+
+        if let Some(language) = DiagramLanguage::from_info(&lang) {
+            out.push_str(&format!(
+                "<pre class=\"{}\" data-diagram=\"{}\">{}</pre>\n",
+                language.as_str(),
+                diagram_hash(language, &code),
+                escape_html(&code)
+            ));
+        } else if lang.is_empty() {
+            out.push_str(&format!("<pre><code>{}</code></pre>\n", escape_html(&code)));
+        } else {
+            let body = self.highlight
+                .then(|| highlight(&code, &lang))
+                .flatten()
+                .unwrap_or_else(|| escape_html(&code));
+            let class = escape_html(&lang);
+            out.push_str(&format!(
+                "<pre class=\"language-{}\"><code class=\"language-{}\">{}</code></pre>\n",
+                class, class, body
+            ));
+        }
+    }
+
+    /// Renders the blockquote at the start of `lines`; returns its line count
+    fn quote(&mut self, lines: &[String], out: &mut String) -> usize {
+        let mut inner = Vec::new();
+        let mut end = 0;
+        while end < lines.len() {
+            let line = &lines[end];
+            match quote_text(line) {
+                Some(text) => inner.push(text.to_string()),
+                // Lazy continuation of a quoted paragraph
+                None if !line.trim().is_empty()
+                    && inner.last().is_some_and(|l: &String| !l.trim().is_empty())
+                    && !starts_block(line) => inner.push(line.clone()),
+                None => break,
+            }
+            end += 1;
+        }
+
+        match inner.first().and_then(|line| callout_marker(line)) {
+            Some((kind, fold, title)) => {
+                let title = if title.is_empty() { escape_html(&default_callout_title(kind)) } else { self.inline(title) };
+                let fold = fold.map_or(String::new(), |f| format!(" data-callout-fold=\"{}\"", f));
+                out.push_str(&format!(
+                    "<blockquote class=\"callout callout-{}\" data-callout=\"{}\"{}>\n<p class=\"callout-title\">{}</p>\n",
+                    callout_family(kind),
+                    escape_html(&kind.to_lowercase()),
+                    fold,
+                    title
+                ));
+                self.blocks(&inner[1..], false, out);
+            }
+            None => {
+                out.push_str("<blockquote>\n");
+                self.blocks(&inner, false, out);
+            }
+        }
+        out.push_str("</blockquote>\n");
+        end
+    }
+
+    fn list(&mut self, lines: &[String], out: &mut String) {
+        let Some(first) = list_marker(&lines[0]) else { return };
+
+        // Item lines, dedented to the item's text column
+        let mut items: Vec<(ListMarker, Vec<String>)> = Vec::new();
+        for line in lines {
+            let marker = list_marker(line);
+            match items.last_mut() {
+                // Markers indented to the item text belong to a nested list
+                Some((current, body)) if !marker.is_some_and(|m| m.indent < current.content) => {
+                    body.push(dedent(line, current.content));
+                }
+                _ => {
+                    if let Some(marker) = marker {
+                        items.push((marker, vec![line[marker.content..].to_string()]));
+                    }
+                }
+            }
+        }
+
+        // A blank line followed by more content makes the list loose
+        let last_text = lines.iter().rposition(|l| !l.trim().is_empty()).unwrap_or(0);
+        let tight = !lines[..last_text].iter().any(|l| l.trim().is_empty());
+        let tasks = items.iter().any(|(_, body)| task_state(&body[0]).is_some());
+
+        let class = if tasks { " class=\"contains-task-list\"" } else { "" };
+        match first.number {
+            Some(1) => out.push_str(&format!("<ol{}>\n", class)),
+            Some(start) => out.push_str(&format!("<ol start=\"{}\"{}>\n", start, class)),
+            None => out.push_str(&format!("<ul{}>\n", class)),
+        }
+
+        for (_, mut body) in items {
+            let task = task_state(&body[0]);
+            if task.is_some() {
+                body[0] = body[0][3..].trim_start().to_string();
+            }
+            let mut inner = String::new();
+            self.blocks(&body, tight, &mut inner);
+            match task {
+                Some(done) => out.push_str(&format!(
+                    "<li class=\"task-list-item\"><input type=\"checkbox\" class=\"task-list-item-checkbox\" disabled{}> {}</li>\n",
+                    if done { " checked" } else { "" },
+                    inner.trim_end()
+                )),
+                None => out.push_str(&format!("<li>{}</li>\n", inner.trim_end())),
+            }
+        }
+
+        out.push_str(if first.number.is_some() { "</ol>\n" } else { "</ul>\n" });
+    }
+
+    fn table(&mut self, lines: &[String], out: &mut String) {
+        let header = split_row(&lines[0]);
+        let aligns: Vec<&str> = split_row(&lines[1])
+            .iter()
+            .map(|cell| match (cell.starts_with(':'), cell.ends_with(':')) {
+                (true, true) => " style=\"text-align:center\"",
+                (false, true) => " style=\"text-align:right\"",
+                (true, false) => " style=\"text-align:left\"",
+                (false, false) => "",
+            })
+            .collect();
+
+        out.push_str("<table>\n<thead>\n<tr>\n");
+        for (cell, align) in header.iter().zip(&aligns) {
+            out.push_str(&format!("<th{}>{}</th>\n", align, self.inline(cell)));
+        }
+        out.push_str("</tr>\n</thead>\n");
+
+        if lines.len() > 2 {
+            out.push_str("<tbody>\n");
+            for line in &lines[2..] {
+                let cells = split_row(line);
+                out.push_str("<tr>\n");
+                for (k, align) in aligns.iter().enumerate() {
+                    let cell = cells.get(k).map_or("", String::as_str);
+                    out.push_str(&format!("<td{}>{}</td>\n", align, self.inline(cell)));
+                }
+                out.push_str("</tr>\n");
+            }
+            out.push_str("</tbody>\n");
+        }
+        out.push_str("</table>\n");
+    }
+}
+
+/// The opening fence (`` ``` `` or `~~~`, possibly longer) of a line
+fn fence(line: &str) -> Option<&str> {
+    let trimmed = line.trim_start();
+    let marker = trimmed.chars().next().filter(|&c| c == '`' || c == '~')?;
+    let len = trimmed.len() - trimmed.trim_start_matches(marker).len();
+    (len >= 3 && !(marker == '`' && trimmed[len..].contains('`'))).then(|| &trimmed[..len])
+}
+
+fn closes_fence(line: &str, marker: &str) -> bool {
+    let trimmed = line.trim();
+    let c = marker.chars().next().unwrap_or('`');
+    trimmed.len() >= marker.len() && trimmed.chars().all(|x| x == c)
+}
+
+/// Level and text of an ATX heading (`## Title`)
+fn atx_heading(line: &str) -> Option<(usize, &str)> {
+    let trimmed = line.trim_start();
+    let level = trimmed.len() - trimmed.trim_start_matches('#').len();
+    let rest = &trimmed[level..];
+    if !(1..=6).contains(&level) || !(rest.is_empty() || rest.starts_with(' ')) {
+        return None;
+    }
+    let text = rest.trim();
+    // Optional closing hashes: `## Title ##`
+    let closed = text.trim_end_matches('#');
+    let text = if closed.is_empty() || closed.ends_with(' ') { closed.trim_end() } else { text };
+    Some((level, text))
+}
+
+/// `---`, `***` or `___`, possibly spaced
+fn is_rule(line: &str) -> bool {
+    let chars: Vec<char> = line.chars().filter(|c| !c.is_whitespace()).collect();
+    chars.len() >= 3 && matches!(chars[0], '-' | '*' | '_') && chars.iter().all(|&c| c == chars[0])
+}
+
+/// Text of a blockquote line without its `>`
+fn quote_text(line: &str) -> Option<&str> {
+    let rest = line.trim_start().strip_prefix('>')?;
+    Some(rest.strip_prefix(' ').unwrap_or(rest))
+}
+
+/// Type, fold marker (`-`/`+`) and title of a callout's first quote line
+/// (`[!note]- Title`, without the `>`)
+pub(crate) fn callout_marker(text: &str) -> Option<(&str, Option<char>, &str)> {
+    let rest = text.trim_start().strip_prefix("[!")?;
+    let close = rest.find(']')?;
+    let kind = &rest[..close];
+    if kind.is_empty() || !kind.chars().all(|c| c.is_alphanumeric() || c == '-' || c == '_') {
+        return None;
+    }
+    let rest = &rest[close + 1..];
+    let fold = rest.chars().next().filter(|c| matches!(c, '-' | '+'));
+    let title = fold.map_or(rest, |f| &rest[f.len_utf8()..]).trim();
+    Some((kind, fold, title))
+}
+
+/// The GitHub alert type (`note`, `tip`, `important`, `warning`,
+/// `caution`) a callout type is styled as
+pub(crate) fn callout_family(kind: &str) -> &'static str {
+    match kind.to_lowercase().as_str() {
+        "tip" | "hint" | "success" | "check" | "done" => "tip",
+        "important" | "example" => "important",
+        "warning" | "attention" | "question" | "help" | "faq" => "warning",
+        "caution" | "danger" | "error" | "failure" | "fail" | "missing" | "bug" => "caution",
+        _ => "note",
+    }
+}
+
+/// Title shown for a callout without one: its type, capitalized
+pub(crate) fn default_callout_title(kind: &str) -> String {
+    let lower = kind.to_lowercase();
+    let mut chars = lower.chars();
+    chars.next().map_or(String::new(), |first| first.to_uppercase().chain(chars).collect())
+}
+
+fn list_marker(line: &str) -> Option<ListMarker> {
+    let trimmed = line.trim_start();
+    let indent = line.len() - trimmed.len();
+
+    let (number, marker_len) = if trimmed.starts_with(['-', '*', '+']) {
+        (None, 1)
+    } else {
+        let digits = trimmed.len() - trimmed.trim_start_matches(|c: char| c.is_ascii_digit()).len();
+        if digits == 0 || digits > 9 || !trimmed[digits..].starts_with(['.', ')']) {
+            return None;
+        }
+        (trimmed[..digits].parse().ok(), digits + 1)
+    };
+
+    let after = &trimmed[marker_len..];
+    if !after.is_empty() && !after.starts_with(' ') {
+        return None;
+    }
+    let spaces = after.len() - after.trim_start_matches(' ').len();
+    // Text indented further than four spaces starts with its indentation
+    let spaces = if (1..=4).contains(&spaces) { spaces } else { 1.min(after.len()) };
+    Some(ListMarker { indent, number, content: indent + marker_len + spaces })
+}
+
+/// End (exclusive) of the list that starts at line `start`
+fn list_end(lines: &[String], start: usize, first: ListMarker) -> usize {
+    let mut end = start + 1;
+    while end < lines.len() {
+        let line = &lines[end];
+        if line.trim().is_empty() {
+            // The list goes on if the next text is indented or another item
+            let next = lines[end..].iter().position(|l| !l.trim().is_empty()).map(|k| end + k);
+            match next {
+                Some(k) if indent(&lines[k]) > first.indent
+                    || list_marker(&lines[k]).is_some_and(|m| m.indent == first.indent && is_same_kind(m, first)) =>
+                {
+                    end = k;
+                }
+                _ => break,
+            }
+        } else if indent(line) > first.indent {
+            end += 1;
+        } else if let Some(marker) = list_marker(line).filter(|_| !is_rule(line)) {
+            if !is_same_kind(marker, first) {
+                break;
+            }
+            end += 1;
+        } else if !starts_block(line) {
+            // Lazy continuation of the previous item's text
+            end += 1;
+        } else {
+            break;
+        }
+    }
+    end
+}
+
+fn is_same_kind(a: ListMarker, b: ListMarker) -> bool {
+    a.number.is_some() == b.number.is_some()
+}
+
+/// `Some(done)` if an item starts with a task box (`[ ]` or `[x]`)
+fn task_state(text: &str) -> Option<bool> {
+    let rest = text.get(3..)?;
+    if !(rest.is_empty() || rest.starts_with(' ')) {
+        return None;
+    }
+    match &text[..3] {
+        "[ ]" => Some(false),
+        "[x]" | "[X]" => Some(true),
+        _ => None,
+    }
+}
+
+/// Whether a pipe table (header row, then delimiter row) starts at `at`
+pub(crate) fn is_table_start(lines: &[String], at: usize) -> bool {
+    let Some(delimiter) = lines.get(at + 1) else { return false };
+    let cells = split_row(delimiter);
+    lines[at].contains('|')
+        && delimiter.contains('-')
+        && cells.len() == split_row(&lines[at]).len()
+        && cells.iter().all(|cell| {
+            let dashes = cell.trim_start_matches(':').trim_end_matches(':');
+            !dashes.is_empty() && dashes.chars().all(|c| c == '-')
+        })
+}
+
+/// Cells of a table row; `\|` and pipes inside code stay in the cell
+pub(crate) fn split_row(line: &str) -> Vec<String> {
+    let trimmed = line.trim();
+    let trimmed = trimmed.strip_prefix('|').unwrap_or(trimmed);
+    let trimmed = if trimmed.ends_with('|') && !trimmed.ends_with("\\|") {
+        &trimmed[..trimmed.len() - 1]
+    } else {
+        trimmed
+    };
+
+    let mut cells = Vec::new();
+    let mut cell = String::new();
+    let mut in_code = false;
+    let mut chars = trimmed.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' if chars.peek() == Some(&'|') => {
+                cell.push('|');
+                chars.next();
+            }
+            '`' => {
+                in_code = !in_code;
+                cell.push(c);
+            }
+            '|' if !in_code => cells.push(std::mem::take(&mut cell).trim().to_string()),
+            c => cell.push(c),
+        }
+    }
+    cells.push(cell.trim().to_string());
+    cells
+}
+
+/// TeX of the `$$` math block starting at line `start`, and the line after
+/// it; a block without closing `$$` is a paragraph
+fn math_block(lines: &[String], start: usize) -> Option<(String, usize)> {
+    let first = lines[start].trim().strip_prefix("$$")?;
+    if let Some(tex) = first.strip_suffix("$$").filter(|tex| !tex.contains("$$")) {
+        return Some((tex.to_string(), start + 1));
+    }
+    let mut tex = vec![first];
+    for (k, line) in lines.iter().enumerate().skip(start + 1) {
+        match line.trim().strip_suffix("$$") {
+            Some(last) => {
+                tex.push(last);
+                return Some((tex.join("\n"), k + 1));
+            }
+            None => tex.push(line),
+        }
+    }
+    None
+}
+
+/// Whether a line interrupts a paragraph
+fn starts_block(line: &str) -> bool {
+    fence(line).is_some()
+        || line.trim_start().starts_with("$$")
+        || atx_heading(line).is_some()
+        || is_rule(line)
+        || quote_text(line).is_some()
+        || list_marker(line).is_some_and(|m| m.content < line.len())
+}
+
+fn indent(line: &str) -> usize {
+    line.len() - line.trim_start_matches(' ').len()
+}
+
+fn dedent(line: &str, columns: usize) -> String {
+    line[indent(line).min(columns)..].to_string()
+}
+
+/// Replaces leading tabs with four spaces each
+fn expand_tabs(line: &str) -> String {
+    let rest = line.trim_start_matches(['\t', ' ']);
+    let leading = &line[..line.len() - rest.len()];
+    if !leading.contains('\t') {
+        return line.to_string();
+    }
+    let spaces: usize = leading.chars().map(|c| if c == '\t' { 4 } else { 1 }).sum();
+    format!("{}{}", " ".repeat(spaces), rest)
+}
+
+// ============================================================================
+// INLINE
+// ============================================================================
+
+impl Renderer<'_> {
+    fn inline(&self, text: &str) -> String {
+        let mut out = String::new();
+        let mut at = 0;
+        while let Some(c) = text[at..].chars().next() {
+            match self.inline_at(text, at) {
+                Some((html, len)) => {
+                    out.push_str(&html);
+                    at += len;
+                }
+                None => {
+                    out.push_str(&escape_html(&text[at..at + c.len_utf8()]));
+                    at += c.len_utf8();
+                }
+            }
+        }
+        out
+    }
+
+    /// HTML and source length of the markup starting at byte `at` of `text`
+    fn inline_at(&self, text: &str, at: usize) -> Option<(String, usize)> {
+        let rest = &text[at..];
+        match rest.chars().next()? {
+            '\\' => escaped_char(rest),
+            '`' => Some(code_span(rest)),
+            '$' => math_span(rest),
+            '!' if rest.starts_with("![[") => embed(rest),
+            '!' if rest.starts_with("![") => self.image(rest),
+            '[' if rest.starts_with("[[") => self.wiki_link(rest),
+            '[' => self.link(rest),
+            '<' => autolink(rest),
+            'h' if !prev_char(text, at).is_some_and(char::is_alphanumeric) => bare_url(rest),
+            '*' | '_' => Some(self.emphasis(text, at)),
+            '~' | '=' => Some(self.mark(text, at)),
+            '#' if !prev_char(text, at).is_some_and(|c| !c.is_whitespace()) => tag(rest),
+            '@' if people::can_start_mention(prev_char(text, at)) => self.mention(rest),
+            _ => None,
+        }
+    }
+
+    fn wiki_link(&self, rest: &str) -> Option<(String, usize)> {
+        let end = rest[2..].find("]]")?;
+        let inner = &rest[2..2 + end];
+        let (target, alias) = match inner.split_once('|') {
+            Some((target, alias)) => (target.trim(), Some(alias.trim())),
+            None => (inner.trim(), None),
+        };
+        let (note, fragment) = match target.split_once('#') {
+            Some((note, fragment)) => (note.trim(), Some(fragment.trim())),
+            None => (target, None),
+        };
+        let text = escape_html(alias.unwrap_or(target));
+        let anchor = fragment.map(|f| format!("#{}", fragment_anchor(f))).unwrap_or_default();
+
+        let html = if note.is_empty() {
+            format!("<a class=\"wikilink\" href=\"{}\">{}</a>", escape_html(&anchor), text)
+        } else {
+            match self.lookup.resolve_wiki(self.from, note) {
+                Some(index) => {
+                    let path = &self.lookup.paths[index];
+                    let href = relative_between(parent(self.from), path);
+                    format!(
+                        "<a class=\"wikilink\" href=\"{}{}\" data-note=\"{}\">{}</a>",
+                        escape_html(&encode_path(&href)),
+                        escape_html(&anchor),
+                        escape_html(path),
+                        text
+                    )
+                }
+                None => format!(
+                    "<a class=\"wikilink unresolved\" data-note=\"{}\">{}</a>",
+                    escape_html(note),
+                    text
+                ),
+            }
+        };
+        Some((html, end + 4))
+    }
+
+    fn mention(&self, rest: &str) -> Option<(String, usize)> {
+        let (handle, len) = people::parse_mention(rest)?;
+        let path = &self.lookup.paths[self.lookup.resolve_person(self.from, &handle)?];
+        let href = relative_between(parent(self.from), path);
+        let html = format!(
+            "<a class=\"mention\" href=\"{}\" data-note=\"{}\">{}</a>",
+            escape_html(&encode_path(&href)),
+            escape_html(path),
+            escape_html(&rest[..len])
+        );
+        Some((html, len))
+    }
+
+    fn link(&self, rest: &str) -> Option<(String, usize)> {
+        let close = matching(rest, '[', ']')?;
+        let after = &rest[close + 1..];
+        if !after.starts_with('(') {
+            return None;
+        }
+        let paren = matching(after, '(', ')')?;
+        let (href, title) = link_destination(&after[1..paren]);
+        let text = self.inline(&rest[1..close]);
+        let len = close + 1 + paren + 1;
+
+        if !is_safe_url(&href) {
+            return Some((text, len));
+        }
+        Some((format!("<a href=\"{}\"{}>{}</a>", escape_html(&href), title_attr(title), text), len))
+    }
+
+    fn image(&self, rest: &str) -> Option<(String, usize)> {
+        let close = matching(&rest[1..], '[', ']')? + 1;
+        let after = &rest[close + 1..];
+        if !after.starts_with('(') {
+            return None;
+        }
+        let paren = matching(after, '(', ')')?;
+        let (src, title) = link_destination(&after[1..paren]);
+        let alt = &rest[2..close];
+        let len = close + 1 + paren + 1;
+
+        if !is_safe_url(&src) && !src.starts_with("data:image/") {
+            return Some((escape_html(alt), len));
+        }
+        Some((
+            format!("<img src=\"{}\" alt=\"{}\"{}>", escape_html(&src), escape_html(alt), title_attr(title)),
+            len,
+        ))
+    }
+
+    /// `*em*`, `**strong**` and `***both***` (also with `_`)
+    fn emphasis(&self, text: &str, at: usize) -> (String, usize) {
+        let marker = text.as_bytes()[at];
+        let run = run_length(text, at, marker);
+        let literal = (text[at..at + run].to_string(), run);
+
+        let opens = run <= 3
+            && text[at + run..].chars().next().is_some_and(|c| !c.is_whitespace())
+            && !(marker == b'_' && prev_char(text, at).is_some_and(char::is_alphanumeric));
+        let Some(close) = opens.then(|| find_closing(text, at + run, marker, run)).flatten() else {
+            return literal;
+        };
+
+        let inner = self.inline(&text[at + run..close]);
+        let html = match run {
+            1 => format!("<em>{}</em>", inner),
+            2 => format!("<strong>{}</strong>", inner),
+            _ => format!("<em><strong>{}</strong></em>", inner),
+        };
+        (html, close + run - at)
+    }
+
+    /// `~~strikethrough~~` and `==highlight==`
+    fn mark(&self, text: &str, at: usize) -> (String, usize) {
+        let marker = text.as_bytes()[at];
+        let run = run_length(text, at, marker);
+        let literal = (text[at..at + run].to_string(), run);
+
+        let opens = run == 2 && text[at + run..].chars().next().is_some_and(|c| !c.is_whitespace());
+        let Some(close) = opens.then(|| find_closing(text, at + run, marker, run)).flatten() else {
+            return (escape_html(&literal.0), literal.1);
+        };
+
+        let inner = self.inline(&text[at + run..close]);
+        let tag = if marker == b'~' { "del" } else { "mark" };
+        (format!("<{}>{}</{}>", tag, inner, tag), close + run - at)
+    }
+}
+
+/// `![[file]]` left by transclusion: an image, or an unresolved embed
+fn embed(rest: &str) -> Option<(String, usize)> {
+    let end = rest[3..].find("]]")?;
+    let inner = &rest[3..3 + end];
+    let (target, option) = match inner.split_once('|') {
+        Some((target, option)) => (target.trim(), Some(option.trim())),
+        None => (inner.trim(), None),
+    };
+
+    let extension = target.rsplit_once('.').map(|(_, ext)| ext.to_lowercase());
+    let html = if extension.is_some_and(|ext| IMAGE_EXTENSIONS.contains(&ext.as_str())) {
+        // `![[photo.png|300]]` sets the width
+        let (alt, width) = match option {
+            Some(width) if !width.is_empty() && width.chars().all(|c| c.is_ascii_digit()) => (target, Some(width)),
+            Some(alt) => (alt, None),
+            None => (target, None),
+        };
+        format!(
+            "<img src=\"{}\" alt=\"{}\"{}>",
+            escape_html(&encode_path(target)),
+            escape_html(alt),
+            width.map(|w| format!(" width=\"{}\"", w)).unwrap_or_default()
+        )
+    } else {
+        format!("<span class=\"embed unresolved\">{}</span>", escape_html(inner))
+    };
+    Some((html, end + 5))
+}
+
+fn escaped_char(rest: &str) -> Option<(String, usize)> {
+    let c = rest[1..].chars().next()?;
+    if c == '\n' {
+        return Some(("<br>\n".to_string(), 2));
+    }
+    c.is_ascii_punctuation().then(|| (escape_html(&rest[1..2]), 2))
+}
+
+/// Inline code; an unclosed backtick run stays literal
+fn code_span(rest: &str) -> (String, usize) {
+    let run = run_length(rest, 0, b'`');
+    let mut at = run;
+    while let Some(offset) = rest[at..].find('`') {
+        let start = at + offset;
+        let len = run_length(rest, start, b'`');
+        if len == run {
+            let code = rest[run..start].replace('\n', " ");
+            let code = match code.strip_prefix(' ').and_then(|c| c.strip_suffix(' ')) {
+                Some(inner) if !inner.trim().is_empty() => inner.to_string(),
+                _ => code,
+            };
+            return (format!("<code>{}</code>", escape_html(&code)), start + len);
+        }
+        at = start + len;
+    }
+    (rest[..run].to_string(), run)
+}
+
+/// `$inline$` and `$$display$$` math; the closing `$` follows a non-space
+/// and is not followed by a digit, and a `$` after a space ends the search,
+/// so prices like `$5 and $10` stay text
+fn math_span(rest: &str) -> Option<(String, usize)> {
+    let run = if rest.starts_with("$$") { 2 } else { 1 };
+    let delimiter = &rest[..run];
+    if rest[run..].starts_with(char::is_whitespace) || rest[run..].is_empty() {
+        return None;
+    }
+    let mut at = run;
+    while let Some(offset) = rest[at..].find(delimiter) {
+        let close = at + offset;
+        let after = &rest[close + run..];
+        if rest[..close].ends_with(char::is_whitespace) {
+            return None;
+        }
+        let escaped = rest[..close].ends_with('\\');
+        if close > run && !escaped && !(run == 1 && (after.starts_with('$') || after.starts_with(|c: char| c.is_ascii_digit()))) {
+            return Some((tex_to_mathml(&rest[run..close], run == 2), close + run));
+        }
+        at = close + 1;
+    }
+    None
+}
+
+/// `<https://...>` and `<name@example.com>`
+fn autolink(rest: &str) -> Option<(String, usize)> {
+    let end = rest.find('>')?;
+    let inner = &rest[1..end];
+    if inner.is_empty() || inner.contains(char::is_whitespace) {
+        return None;
+    }
+    let href = if inner.starts_with("http://") || inner.starts_with("https://") {
+        inner.to_string()
+    } else if inner.contains('@') && !inner.contains(':') {
+        format!("mailto:{}", inner)
+    } else {
+        return None;
+    };
+    Some((format!("<a href=\"{}\">{}</a>", escape_html(&href), escape_html(inner)), end + 1))
+}
+
+fn bare_url(rest: &str) -> Option<(String, usize)> {
+    if !rest.starts_with("http://") && !rest.starts_with("https://") {
+        return None;
+    }
+    let end = rest.find(|c: char| c.is_whitespace() || c == '<').unwrap_or(rest.len());
+    let url = rest[..end].trim_end_matches(['.', ',', ';', ':', '!', '?', ')', '\'', '"']);
+    if url.ends_with("//") {
+        return None;
+    }
+    let url_html = escape_html(url);
+    Some((format!("<a href=\"{}\">{}</a>", url_html, url_html), url.len()))
+}
+
+/// `#tag` (letters, digits, `-`, `_` and `/`; not only digits)
+fn tag(rest: &str) -> Option<(String, usize)> {
+    let name = &rest[1..];
+    let len = name
+        .find(|c: char| !(c.is_alphanumeric() || matches!(c, '-' | '_' | '/')))
+        .unwrap_or(name.len());
+    let name = &name[..len];
+    if name.is_empty() || name.chars().all(|c| c.is_ascii_digit()) {
+        return None;
+    }
+    Some((format!("<span class=\"tag\">#{}</span>", escape_html(name)), len + 1))
+}
+
+/// Start of the run of exactly `run` markers that closes one opened before `from`
+///
+/// The closing run must follow text (not whitespace); code spans are skipped.
+fn find_closing(text: &str, from: usize, marker: u8, run: usize) -> Option<usize> {
+    let bytes = text.as_bytes();
+    let mut at = from;
+    while at < bytes.len() {
+        if bytes[at] == b'`' {
+            at += code_span(&text[at..]).1;
+            continue;
+        }
+        if bytes[at] != marker {
+            at += 1;
+            continue;
+        }
+        let len = run_length(text, at, marker);
+        let after_text = !prev_char(text, at).is_some_and(char::is_whitespace);
+        let word_follows = text[at + len..].chars().next().is_some_and(char::is_alphanumeric);
+        if len == run && at > from && after_text && !(marker == b'_' && word_follows) {
+            return Some(at);
+        }
+        at += len;
+    }
+    None
+}
+
+/// Index of the bracket closing the one `text` starts with
+fn matching(text: &str, open: char, close: char) -> Option<usize> {
+    let mut depth = 0;
+    let mut escaped = false;
+    for (i, c) in text.char_indices() {
+        match c {
+            _ if escaped => escaped = false,
+            '\\' => escaped = true,
+            c if c == open => depth += 1,
+            c if c == close => {
+                depth -= 1;
+                if depth == 0 {
+                    return Some(i);
+                }
+            }
+            _ => {}
+        }
+    }
+    None
+}
+
+/// Target and optional title of `[text](target "title")`
+fn link_destination(inner: &str) -> (String, Option<String>) {
+    let inner = inner.trim();
+    let (target, rest) = match inner.strip_prefix('<').and_then(|r| r.split_once('>')) {
+        Some((target, rest)) => (target, rest),
+        None => inner.split_once(char::is_whitespace).unwrap_or((inner, "")),
+    };
+    let rest = rest.trim();
+    let title = ['"', '\'']
+        .iter()
+        .find_map(|&q| rest.strip_prefix(q).and_then(|r| r.strip_suffix(q)))
+        .map(str::to_string);
+    (target.to_string(), title)
+}
+
+fn title_attr(title: Option<String>) -> String {
+    title.map(|t| format!(" title=\"{}\"", escape_html(&t))).unwrap_or_default()
+}
+
+/// Rejects `javascript:` and other schemes a link should not run
+fn is_safe_url(url: &str) -> bool {
+    let url = url.trim().to_lowercase();
+    match url.find(':') {
+        Some(colon) if !url[..colon].contains(['/', '?', '#']) => {
+            ["http", "https", "mailto", "tel"].contains(&&url[..colon])
+        }
+        _ => true,
+    }
+}
+
+/// Anchor of a link fragment: a heading, or a `^block-id`
+fn fragment_anchor(fragment: &str) -> String {
+    match fragment.strip_prefix('^') {
+        Some(block) => format!("^{}", block),
+        None => heading_anchor(fragment),
+    }
+}
+
+/// Escapes the characters of a path that would end or break a URL
+fn encode_path(path: &str) -> String {
+    path.chars()
+        .map(|c| match c {
+            ' ' => "%20".to_string(),
+            '#' => "%23".to_string(),
+            '?' => "%3F".to_string(),
+            '%' => "%25".to_string(),
+            c => c.to_string(),
+        })
+        .collect()
+}
+
+fn parent(path: &str) -> &str {
+    path.rsplit_once('/').map_or("", |(dir, _)| dir)
+}
+
+fn run_length(text: &str, at: usize, marker: u8) -> usize {
+    text.as_bytes()[at..].iter().take_while(|&&b| b == marker).count()
+}
+
+fn prev_char(text: &str, at: usize) -> Option<char> {
+    text[..at].chars().next_back()
+}
+
+pub(crate) fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+// ============================================================================
+// CODE HIGHLIGHTING
+// ============================================================================
+
+/// Token rules of a highlighted language
+struct Syntax {
+    keywords: &'static [&'static str],
+    line_comments: &'static [&'static str],
+    block_comment: Option<(&'static str, &'static str)>,
+    quotes: &'static [char],
+    case_insensitive: bool,
+}
+
+const RUST_KEYWORDS: &[&str] = &[
+    "as", "async", "await", "break", "const", "continue", "crate", "dyn", "else", "enum", "extern",
+    "false", "fn", "for", "if", "impl", "in", "let", "loop", "match", "mod", "move", "mut", "pub",
+    "ref", "return", "self", "Self", "static", "struct", "super", "trait", "true", "type", "unsafe",
+    "use", "where", "while",
+];
+
+const JS_KEYWORDS: &[&str] = &[
+    "async", "await", "break", "case", "catch", "class", "const", "continue", "default", "delete",
+    "do", "else", "enum", "export", "extends", "false", "finally", "for", "from", "function", "if",
+    "implements", "import", "in", "instanceof", "interface", "let", "new", "null", "of", "private",
+    "protected", "public", "readonly", "return", "static", "super", "switch", "this", "throw", "true",
+    "try", "type", "typeof", "undefined", "var", "void", "while", "yield",
+];
+
+const PYTHON_KEYWORDS: &[&str] = &[
+    "False", "None", "True", "and", "as", "assert", "async", "await", "break", "class", "continue",
+    "def", "del", "elif", "else", "except", "finally", "for", "from", "global", "if", "import", "in",
+    "is", "lambda", "nonlocal", "not", "or", "pass", "raise", "return", "self", "try", "while",
+    "with", "yield",
+];
+
+const GO_KEYWORDS: &[&str] = &[
+    "break", "case", "chan", "const", "continue", "default", "defer", "else", "fallthrough", "false",
+    "for", "func", "go", "goto", "if", "import", "interface", "map", "nil", "package", "range",
+    "return", "select", "struct", "switch", "true", "type", "var",
+];
+
+const C_KEYWORDS: &[&str] = &[
+    "abstract", "auto", "bool", "break", "case", "catch", "char", "class", "const", "continue",
+    "default", "delete", "do", "double", "else", "enum", "extends", "extern", "false", "final",
+    "float", "for", "fun", "goto", "if", "implements", "import", "inline", "int", "interface",
+    "long", "namespace", "new", "null", "nullptr", "override", "package", "private", "protected",
+    "public", "return", "short", "signed", "sizeof", "static", "struct", "super", "switch",
+    "template", "this", "throw", "true", "try", "typedef", "union", "unsigned", "using", "val",
+    "var", "virtual", "void", "volatile", "while",
+];
+
+const SHELL_KEYWORDS: &[&str] = &[
+    "case", "do", "done", "echo", "elif", "else", "esac", "export", "fi", "for", "function", "if",
+    "in", "local", "return", "then", "until", "while",
+];
+
+const SQL_KEYWORDS: &[&str] = &[
+    "and", "as", "asc", "by", "create", "delete", "desc", "distinct", "drop", "from", "group",
+    "having", "in", "index", "insert", "into", "is", "join", "left", "limit", "not", "null", "on",
+    "or", "order", "right", "select", "set", "table", "update", "values", "where",
+];
+
+const DATA_KEYWORDS: &[&str] = &["true", "false", "null"];
+
+fn syntax(lang: &str) -> Option<Syntax> {
+    let c_like = |keywords| Syntax {
+        keywords,
+        line_comments: &["//"],
+        block_comment: Some(("/*", "*/")),
+        quotes: &['"', '\''],
+        case_insensitive: false,
+    };
+    let hash_comments = |keywords| Syntax {
+        keywords,
+        line_comments: &["#"],
+        block_comment: None,
+        quotes: &['"', '\''],
+        case_insensitive: false,
+    };
+
+    Some(match lang {
+        // `'` also starts lifetimes in Rust, so only `"` delimits strings
+        "rust" | "rs" => Syntax { quotes: &['"'], ..c_like(RUST_KEYWORDS) },
+        "js" | "javascript" | "jsx" | "ts" | "typescript" | "tsx" => {
+            Syntax { quotes: &['"', '\'', '`'], ..c_like(JS_KEYWORDS) }
+        }
+        "go" => Syntax { quotes: &['"', '`'], ..c_like(GO_KEYWORDS) },
+        "c" | "h" | "cpp" | "c++" | "java" | "cs" | "csharp" | "kotlin" | "kt" | "swift" => c_like(C_KEYWORDS),
+        "python" | "py" => hash_comments(PYTHON_KEYWORDS),
+        "bash" | "sh" | "shell" | "zsh" => hash_comments(SHELL_KEYWORDS),
+        "yaml" | "yml" | "toml" => hash_comments(DATA_KEYWORDS),
+        "json" => Syntax { line_comments: &[], quotes: &['"'], ..c_like(DATA_KEYWORDS) },
+        "sql" => Syntax {
+            keywords: SQL_KEYWORDS,
+            line_comments: &["--"],
+            block_comment: Some(("/*", "*/")),
+            quotes: &['\''],
+            case_insensitive: true,
+        },
+        _ => return None,
+    })
+}
+
+/// Highlights code as Prism does, or `None` for an unknown language
+fn highlight(code: &str, lang: &str) -> Option<String> {
+    let syntax = syntax(lang)?;
+    let mut out = String::new();
+    let mut at = 0;
+
+    while let Some(c) = code[at..].chars().next() {
+        let rest = &code[at..];
+        let token = if syntax.line_comments.iter().any(|m| rest.starts_with(m)) {
+            Some(("comment", rest.find('\n').unwrap_or(rest.len())))
+        } else if let Some((open, close)) = syntax.block_comment.filter(|(open, _)| rest.starts_with(*open)) {
+            Some(("comment", rest[open.len()..].find(close).map_or(rest.len(), |e| open.len() + e + close.len())))
+        } else if syntax.quotes.contains(&c) {
+            Some(("string", string_length(rest, c)))
+        } else if c.is_ascii_digit() && !prev_char(code, at).is_some_and(is_identifier_char) {
+            Some(("number", rest.find(|x: char| !(x.is_ascii_alphanumeric() || x == '.' || x == '_')).unwrap_or(rest.len())))
+        } else if is_identifier_char(c) && !prev_char(code, at).is_some_and(is_identifier_char) {
+            let len = rest.find(|x: char| !is_identifier_char(x)).unwrap_or(rest.len());
+            let word = &rest[..len];
+            let keyword = if syntax.case_insensitive {
+                syntax.keywords.contains(&word.to_lowercase().as_str())
+            } else {
+                syntax.keywords.contains(&word)
+            };
+            out.push_str(&if keyword { span("keyword", word) } else { escape_html(word) });
+            at += len;
+            continue;
+        } else {
+            None
+        };
+
+        match token {
+            Some((kind, len)) => {
+                out.push_str(&span(kind, &rest[..len]));
+                at += len;
+            }
+            None => {
+                out.push_str(&escape_html(&rest[..c.len_utf8()]));
+                at += c.len_utf8();
+            }
+        }
+    }
+    Some(out)
+}
+
+/// Length of a string literal up to its closing quote (or the line end)
+fn string_length(rest: &str, quote: char) -> usize {
+    let mut escaped = false;
+    for (i, c) in rest.char_indices().skip(1) {
+        match c {
+            _ if escaped => escaped = false,
+            '\\' => escaped = true,
+            '\n' if quote != '`' => return i,
+            c if c == quote => return i + 1,
+            _ => {}
+        }
+    }
+    rest.len()
+}
+
+fn is_identifier_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '_'
+}
+
+fn span(kind: &str, text: &str) -> String {
+    format!("<span class=\"token {}\">{}</span>", kind, escape_html(text))
+}
+
+// ============================================================================
+// TESTS
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn render(content: &str) -> String {
+        let lookup = NoteLookup::new(vec!["notes/Plan.md".to_string(), "Daily/Log.md".to_string()]);
+        render_markdown(content, "Daily/Log.md", &lookup, true)
+    }
+
+    #[test]
+    fn test_render_blocks() {
+        let content = "---\ntitle: Log\n---\n# Intro\n\nSome *text* and **bold**  \nnext line.\n\n\
+                       > quoted\n\n---\n\n## Intro\n\n| A | B |\n|:--|--:|\n| 1 | `a|b` |\n";
+        assert_eq!(
+            render(content),
+            "<h1 id=\"intro\">Intro</h1>\n\
+             <p>Some <em>text</em> and <strong>bold</strong><br>\nnext line.</p>\n\
+             <blockquote>\n<p>quoted</p>\n</blockquote>\n<hr>\n\
+             <h2 id=\"intro-1\">Intro</h2>\n\
+             <table>\n<thead>\n<tr>\n<th style=\"text-align:left\">A</th>\n<th style=\"text-align:right\">B</th>\n</tr>\n</thead>\n\
+             <tbody>\n<tr>\n<td style=\"text-align:left\">1</td>\n<td style=\"text-align:right\"><code>a|b</code></td>\n</tr>\n</tbody>\n</table>\n"
+        );
+    }
+
+    #[test]
+    fn test_render_lists() {
+        let content = "- [x] done\n- [ ] open\n  - nested\n\n3. three\n4. four\n";
+        assert_eq!(
+            render(content),
+            "<ul class=\"contains-task-list\">\n\
+             <li class=\"task-list-item\"><input type=\"checkbox\" class=\"task-list-item-checkbox\" disabled checked> done</li>\n\
+             <li class=\"task-list-item\"><input type=\"checkbox\" class=\"task-list-item-checkbox\" disabled> open\n<ul>\n<li>nested</li>\n</ul></li>\n\
+             </ul>\n<ol start=\"3\">\n<li>three</li>\n<li>four</li>\n</ol>\n"
+        );
+    }
+
+    #[test]
+    fn test_render_links() {
+        let html = render("See [[Plan#Next Steps|the plan]], [[Missing]], [[#Top]] and [site](https://example.com \"Home\").");
+        assert!(html.contains("<a class=\"wikilink\" href=\"../notes/Plan.md#next-steps\" data-note=\"notes/Plan.md\">the plan</a>"));
+        assert!(html.contains("<a class=\"wikilink unresolved\" data-note=\"Missing\">Missing</a>"));
+        assert!(html.contains("<a class=\"wikilink\" href=\"#top\">#Top</a>"));
+        assert!(html.contains("<a href=\"https://example.com\" title=\"Home\">site</a>"));
+
+        let html = render("[x](javascript:alert(1)) ![[photo one.png|200]] ![[Note]] <b>hi</b> #idea #42 https://a.io/x.");
+        assert_eq!(
+            html,
+            "<p>x <img src=\"photo%20one.png\" alt=\"photo one.png\" width=\"200\"> \
+             <span class=\"embed unresolved\">Note</span> &lt;b&gt;hi&lt;/b&gt; \
+             <span class=\"tag\">#idea</span> #42 <a href=\"https://a.io/x\">https://a.io/x</a>.</p>\n"
+        );
+    }
+
+    #[test]
+    fn test_render_callouts() {
+        let content = "> [!tip]- Use *this*\n> Body text\n\n> [!WARNING]\n> Careful\n\n> [!] plain\n";
+        assert_eq!(
+            render(content),
+            "<blockquote class=\"callout callout-tip\" data-callout=\"tip\" data-callout-fold=\"-\">\n\
+             <p class=\"callout-title\">Use <em>this</em></p>\n<p>Body text</p>\n</blockquote>\n\
+             <blockquote class=\"callout callout-warning\" data-callout=\"warning\">\n\
+             <p class=\"callout-title\">Warning</p>\n<p>Careful</p>\n</blockquote>\n\
+             <blockquote>\n<p>[!] plain</p>\n</blockquote>\n"
+        );
+    }
+
+    #[test]
+    fn test_render_math() {
+        let html = render("Costs $5 and $10, but $x^2$ is \\$y$.\n\n$$\n\\frac{a}{b}\n$$\n");
+        assert!(html.starts_with("<p>Costs $5 and $10, but <math xmlns="));
+        assert!(html.contains("<msup><mi>x</mi><mn>2</mn></msup>"));
+        assert!(html.contains("is $y$.</p>"));
+        assert!(html.contains("<math xmlns=\"http://www.w3.org/1998/Math/MathML\" display=\"block\"><semantics><mfrac>"));
+    }
+
+    #[test]
+    fn test_render_inline_marks() {
+        assert_eq!(
+            render("snake_case_name, ~~old~~ ==new== *a **b** c* 2 * 3 \\*literal\\*"),
+            "<p>snake_case_name, <del>old</del> <mark>new</mark> <em>a <strong>b</strong> c</em> 2 * 3 *literal*</p>\n"
+        );
+    }
+
+    #[test]
+    fn test_render_code_blocks() {
+        let content = "```rust\nfn main() { let s = \"<hi>\"; } // done\n```\n\n```mermaid\ngraph TD; A-->B\n```\n";
+        let diagram = format!(
+            "<pre class=\"mermaid\" data-diagram=\"{}\">graph TD; A--&gt;B\n</pre>\n",
+            diagram_hash(DiagramLanguage::Mermaid, "graph TD; A-->B")
+        );
+        assert_eq!(
+            render(content),
+            "<pre class=\"language-rust\"><code class=\"language-rust\">\
+             <span class=\"token keyword\">fn</span> main() { <span class=\"token keyword\">let</span> s = \
+             <span class=\"token string\">&quot;&lt;hi&gt;&quot;</span>; } <span class=\"token comment\">// done</span>\n\
+             </code></pre>\n".to_string()
+                + &diagram
+        );
+    }
+
+    #[test]
+    fn test_html_document() {
+        let doc = html_document("Plan & <Goals>", "<p>Hi</p>\n");
+        assert!(doc.starts_with("<!DOCTYPE html>"));
+        assert!(doc.contains("<title>Plan &amp; &lt;Goals&gt;</title>"));
+        assert!(doc.contains("<article class=\"markdown-body\">\n<p>Hi</p>\n</article>"));
+    }
+}
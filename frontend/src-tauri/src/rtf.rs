@@ -0,0 +1,396 @@
+//! Rich Text Format
+//!
+//! Converts rendered notes (see `crate::render`) to RTF, for pasting into
+//! apps that take rich text but not HTML from the clipboard (Word,
+//! TextEdit, many mail clients).
+//!
+//! Only the HTML the renderer produces is understood: headings,
+//! paragraphs, emphasis, links, inline and fenced code, quotes, nested
+//! lists, task checkboxes, tables and rules. Images become their alt text,
+//! math (see `crate::math`) its TeX source in the code font.
+//! Any other tag is dropped and its text kept.
+
+/// Document header: fonts (body, code) and colors (link, -, highlight, quote)
+const HEADER: &str = "{\\rtf1\\ansi\\ansicpg1252\\deff0\\uc1\
+{\\fonttbl{\\f0\\fswiss\\fcharset0 Helvetica;}{\\f1\\fmodern\\fcharset0 Courier New;}}\
+{\\colortbl;\\red9\\green105\\blue218;\\red246\\green248\\blue250;\\red255\\green248\\blue197;\\red89\\green99\\blue110;}\n";
+
+/// Indent per list or quote level, in twips
+const INDENT: usize = 360;
+
+/// Width of a table, in twips (6.5in)
+const TABLE_WIDTH: usize = 9360;
+
+/// Font sizes of `h1`..`h6`, in half points
+const HEADING_SIZES: [usize; 6] = [36, 32, 28, 24, 22, 22];
+
+/// Converts a rendered HTML fragment to an RTF document
+pub fn html_to_rtf(html: &str) -> String {
+    let mut writer = Writer::default();
+    let mut rest = html;
+    while !rest.is_empty() {
+        if let Some(tag) = rest.strip_prefix('<') {
+            let end = tag.find('>').unwrap_or(tag.len());
+            writer.tag(&tag[..end]);
+            rest = tag.get(end + 1..).unwrap_or("");
+        } else {
+            let end = rest.find('<').unwrap_or(rest.len());
+            writer.text(&decode_entities(&rest[..end]));
+            rest = &rest[end..];
+        }
+    }
+    writer.end_para();
+    format!("{}{}}}", HEADER, writer.out)
+}
+
+/// An open list
+struct List {
+    ordered: bool,
+    next: u64,
+}
+
+#[derive(Default)]
+struct Writer {
+    out: String,
+    para_open: bool,
+    /// No text written to the open paragraph yet
+    para_empty: bool,
+    lists: Vec<List>,
+    quote_depth: usize,
+    pre: bool,
+    /// Cells of the table row being written
+    row: Option<Vec<String>>,
+    /// Closing RTF of open inline tags, by tag name
+    closers: Vec<(String, &'static str)>,
+    /// Inside `<math>`, where only the TeX annotation is written
+    math: bool,
+}
+
+impl Writer {
+    fn tag(&mut self, source: &str) {
+        let closing = source.starts_with('/');
+        let source = source.trim_start_matches('/').trim_end_matches('/');
+        let name = source.split_whitespace().next().unwrap_or("").to_ascii_lowercase();
+        if closing {
+            self.close(&name);
+        } else {
+            self.open(&name, source);
+        }
+    }
+
+    fn open(&mut self, name: &str, source: &str) {
+        match name {
+            // Loose list items wrap their text in paragraphs
+            "p" if !(self.para_open && self.para_empty) => self.start_para(""),
+            "h1" | "h2" | "h3" | "h4" | "h5" | "h6" => {
+                let level = name[1..].parse::<usize>().unwrap_or(1);
+                self.start_para("");
+                self.write(&format!("{{\\b\\fs{} ", HEADING_SIZES[level - 1]));
+                self.closers.push((name.to_string(), "}"));
+            }
+            "pre" => {
+                self.start_para("\\f1\\fs20");
+                self.pre = true;
+            }
+            "blockquote" => {
+                self.end_para();
+                self.quote_depth += 1;
+            }
+            "ul" | "ol" => {
+                self.end_para();
+                let start = attribute(source, "start").and_then(|s| s.parse().ok()).unwrap_or(1);
+                self.lists.push(List { ordered: name == "ol", next: start });
+            }
+            "li" => {
+                let indent = self.indent();
+                self.start_para(&format!("\\fi-{}\\tx{}", INDENT, indent));
+                let marker = match self.lists.last_mut() {
+                    Some(list) if list.ordered => {
+                        list.next += 1;
+                        format!("{}.", list.next - 1)
+                    }
+                    _ => "\\bullet".to_string(),
+                };
+                self.write(&format!("{}\\tab ", marker));
+            }
+            "hr" => {
+                self.end_para();
+                self.write("\\pard\\plain\\brdrb\\brdrs\\brdrw10\\brsp20 \\par\n");
+            }
+            "br" => self.write("\\line "),
+            "table" => self.end_para(),
+            "tr" => self.row = Some(Vec::new()),
+            "td" | "th" => {
+                if let Some(row) = &mut self.row {
+                    row.push(String::new());
+                }
+                if name == "th" {
+                    self.inline(name, "{\\b ", "}");
+                }
+            }
+            "strong" | "b" => self.inline(name, "{\\b ", "}"),
+            "em" | "i" => self.inline(name, "{\\i ", "}"),
+            "del" | "s" => self.inline(name, "{\\strike ", "}"),
+            "mark" => self.inline(name, "{\\highlight3 ", "}"),
+            "code" if !self.pre => self.inline(name, "{\\f1 ", "}"),
+            "a" => match attribute(source, "href") {
+                Some(href) => {
+                    let open = format!(
+                        "{{\\field{{\\*\\fldinst{{HYPERLINK \"{}\"}}}}{{\\fldrslt{{\\ul\\cf1 ",
+                        escape(&href.replace('"', "%22"))
+                    );
+                    self.inline(name, &open, "}}}");
+                }
+                None => self.inline(name, "{", "}"),
+            },
+            "input" => {
+                let checked = source.split_whitespace().any(|word| word == "checked");
+                self.text(if checked { "\u{2611}" } else { "\u{2610}" });
+            }
+            "img" => {
+                if let Some(alt) = attribute(source, "alt").filter(|alt| !alt.is_empty()) {
+                    self.text(&format!("[{}]", alt));
+                }
+            }
+            "math" => {
+                if attribute(source, "display").as_deref() == Some("block") {
+                    self.start_para("");
+                }
+                self.math = true;
+            }
+            "annotation" if self.math => self.inline(name, "{\\f1 ", "}"),
+            _ => {}
+        }
+    }
+
+    fn close(&mut self, name: &str) {
+        if self.closers.last().is_some_and(|(open, _)| open == name) {
+            if let Some((_, closer)) = self.closers.pop() {
+                self.write(closer);
+            }
+        }
+        match name {
+            "p" | "h1" | "h2" | "h3" | "h4" | "h5" | "h6" | "li" => self.end_para(),
+            "pre" => {
+                // The last line of a code block ends with a newline too
+                if self.out.ends_with("\\line ") {
+                    self.out.truncate(self.out.len() - "\\line ".len());
+                }
+                self.pre = false;
+                self.end_para();
+            }
+            "blockquote" => {
+                self.end_para();
+                self.quote_depth = self.quote_depth.saturating_sub(1);
+            }
+            "ul" | "ol" => {
+                self.end_para();
+                self.lists.pop();
+            }
+            "tr" => self.end_row(),
+            "math" => self.math = false,
+            _ => {}
+        }
+    }
+
+    fn text(&mut self, text: &str) {
+        if self.math && !self.closers.last().is_some_and(|(open, _)| open == "annotation") {
+            return;
+        }
+        if self.pre {
+            for line in text.split_inclusive('\n') {
+                self.write(&escape(line.trim_end_matches('\n')));
+                if line.ends_with('\n') {
+                    self.write("\\line ");
+                }
+            }
+            return;
+        }
+
+        // Runs of whitespace become one space, as in HTML
+        let mut collapsed = text.split_whitespace().collect::<Vec<_>>().join(" ");
+        if text.starts_with(char::is_whitespace) {
+            collapsed.insert(0, ' ');
+        }
+        if text.ends_with(char::is_whitespace) && collapsed.len() > 1 {
+            collapsed.push(' ');
+        }
+        if self.row.as_ref().is_some_and(Vec::is_empty) {
+            return;
+        }
+        if self.row.is_none() {
+            if text.trim().is_empty() && (!self.para_open || self.para_empty) {
+                return;
+            }
+            if !self.para_open {
+                self.start_para("");
+            }
+            if self.para_empty {
+                collapsed = collapsed.trim_start().to_string();
+            }
+        }
+        if !collapsed.is_empty() {
+            self.write(&escape(&collapsed));
+            self.para_empty = false;
+        }
+    }
+
+    // Helper: Opens an inline tag, starting a paragraph if needed
+    fn inline(&mut self, name: &str, open: &str, close: &'static str) {
+        if self.row.is_none() && !self.para_open {
+            self.start_para("");
+        }
+        self.write(open);
+        self.closers.push((name.to_string(), close));
+    }
+
+    fn indent(&self) -> usize {
+        INDENT * (self.lists.len() + self.quote_depth)
+    }
+
+    fn start_para(&mut self, format: &str) {
+        self.end_para();
+        let quote = if self.quote_depth > 0 { "\\cf4" } else { "" };
+        let start = format!("\\pard\\plain\\f0\\fs22\\sa120\\li{}{}{} ", self.indent(), quote, format);
+        self.write(&start);
+        self.para_open = true;
+        self.para_empty = true;
+    }
+
+    fn end_para(&mut self) {
+        if self.para_open {
+            self.write("\\par\n");
+            self.para_open = false;
+        }
+    }
+
+    fn end_row(&mut self) {
+        let Some(cells) = self.row.take() else { return };
+        let width = TABLE_WIDTH / cells.len().max(1);
+        self.out.push_str("\\trowd\\trgaph108");
+        for k in 1..=cells.len() {
+            self.out.push_str(&format!("\\cellx{}", width * k));
+        }
+        for cell in cells {
+            self.out.push_str(&format!("\\pard\\intbl\\plain\\f0\\fs22 {}\\cell", cell.trim()));
+        }
+        self.out.push_str("\\row\n\\pard\n");
+    }
+
+    // Helper: Appends RTF to the current table cell or the document
+    fn write(&mut self, rtf: &str) {
+        match self.row.as_mut().and_then(|row| row.last_mut()) {
+            Some(cell) => cell.push_str(rtf),
+            None => self.out.push_str(rtf),
+        }
+    }
+}
+
+/// Escapes text for RTF (non-ASCII as `\uN?`)
+fn escape(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '\\' => out.push_str("\\\\"),
+            '{' => out.push_str("\\{"),
+            '}' => out.push_str("\\}"),
+            '\t' => out.push_str("\\tab "),
+            c if c.is_ascii() && !c.is_ascii_control() => out.push(c),
+            c if c.is_ascii_control() => {}
+            c => {
+                let mut units = [0u16; 2];
+                for unit in c.encode_utf16(&mut units) {
+                    out.push_str(&format!("\\u{}?", *unit as i16));
+                }
+            }
+        }
+    }
+    out
+}
+
+// Helper: Value of an attribute in a tag's source (as the renderer writes them)
+fn attribute(source: &str, name: &str) -> Option<String> {
+    let start = source.find(&format!(" {}=\"", name))? + name.len() + 3;
+    let end = source[start..].find('"')?;
+    Some(decode_entities(&source[start..start + end]))
+}
+
+// Helper: Decodes the entities the renderer writes, and numeric ones
+fn decode_entities(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(amp) = rest.find('&') {
+        out.push_str(&rest[..amp]);
+        rest = &rest[amp..];
+        let entity = rest.find(';').filter(|&end| end <= 10).map(|end| &rest[1..end]);
+        let decoded = entity.and_then(|entity| match entity {
+            "amp" => Some('&'),
+            "lt" => Some('<'),
+            "gt" => Some('>'),
+            "quot" => Some('"'),
+            "apos" => Some('\''),
+            "nbsp" => Some('\u{a0}'),
+            _ => entity
+                .strip_prefix("#x")
+                .or_else(|| entity.strip_prefix("#X"))
+                .and_then(|hex| u32::from_str_radix(hex, 16).ok())
+                .or_else(|| entity.strip_prefix('#').and_then(|dec| dec.parse().ok()))
+                .and_then(char::from_u32),
+        });
+        match (entity, decoded) {
+            (Some(entity), Some(c)) => {
+                out.push(c);
+                rest = &rest[entity.len() + 2..];
+            }
+            _ => {
+                out.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+    out.push_str(rest);
+    out
+}
+
+// ============================================================================
+// TESTS
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_blocks_and_inline_formatting() {
+        let rtf = html_to_rtf(
+            "<h2 id=\"plan\">Plan &amp; <em>notes</em> <mark>now</mark></h2>\n\
+             <p>See <a href=\"https://example.com/?a=1&amp;b=2\">the <strong>site</strong></a> {now}, café 😀</p>\n\
+             <ul>\n<li>one</li>\n<li>two\n<ol start=\"3\">\n<li>three</li>\n</ol></li>\n</ul>\n\
+             <pre><code>fn main() {}\n</code></pre>\n",
+        );
+        assert!(rtf.starts_with("{\\rtf1") && rtf.ends_with('}'));
+        assert!(rtf.contains("{\\b\\fs32 Plan & {\\i notes} {\\highlight3 now}}\\par"));
+        assert!(rtf.contains("HYPERLINK \"https://example.com/?a=1&b=2\"}}{\\fldrslt{\\ul\\cf1 the {\\b site}}}}"));
+        assert!(rtf.contains(" \\{now\\}, caf\\u233? \\u-10179?\\u-8704?\\par"));
+        assert!(rtf.contains("\\li360\\fi-360\\tx360 \\bullet\\tab one\\par"));
+        assert!(rtf.contains("\\li720\\fi-360\\tx720 3.\\tab three\\par"));
+        assert!(rtf.contains("\\f1\\fs20 fn main() \\{\\}\\par"));
+        assert_eq!(rtf.matches('{').count() - rtf.matches("\\{").count(), rtf.matches('}').count() - rtf.matches("\\}").count());
+    }
+
+    #[test]
+    fn test_tables_and_tasks() {
+        let rtf = html_to_rtf(
+            "<table>\n<thead>\n<tr>\n<th>Name</th>\n<th style=\"text-align:right\">Qty</th>\n</tr>\n</thead>\n\
+             <tbody>\n<tr>\n<td>Tea</td>\n<td>2</td>\n</tr>\n</tbody>\n</table>\n\
+             <ul class=\"contains-task-list\">\n<li class=\"task-list-item\"><input type=\"checkbox\" class=\"task-list-item-checkbox\" disabled checked> done</li>\n</ul>\n",
+        );
+        assert!(!rtf.contains("\n \\trowd"));
+        assert!(rtf.contains("\\trowd\\trgaph108\\cellx4680\\cellx9360\\pard\\intbl\\plain\\f0\\fs22 {\\b Name}\\cell"));
+        assert!(rtf.contains("\\fs22 Tea\\cell\\pard\\intbl\\plain\\f0\\fs22 2\\cell\\row"));
+        assert!(rtf.contains("\\bullet\\tab \\u9745? done\\par"));
+
+        let html = crate::render::render_markdown("Area $\\pi r^2$\n", "", &crate::links::NoteLookup::default(), false);
+        assert!(html_to_rtf(&html).contains("Area {\\f1 \\\\pi r^2}\\par"));
+    }
+}
@@ -0,0 +1,225 @@
+//! Cloud-Sync-Safe Writes
+//!
+//! OneDrive, iCloud Drive, Dropbox and similar clients lock a file for a
+//! moment while they upload or download it, so a save landing at that
+//! moment fails with a sharing violation or "resource busy" error. Saves go
+//! through `write_file`, which recognizes these errors, retries with
+//! exponential backoff and, if the file stays locked, reports a
+//! `Sync tool interfered` error instead of a generic failure so the
+//! frontend can tell the user to retry once syncing finishes. Permission
+//! errors are reported as `Permission denied` so the frontend can start the
+//! recovery flow of `crate::file_access`.
+
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Component, Path};
+use std::thread;
+use std::time::Duration;
+use crate::file_access;
+
+/// Waits between attempts after a sync lock (about 1.5s in total)
+const RETRY_DELAYS_MS: &[u64] = &[50, 100, 200, 400, 800];
+
+/// Error returned when a file cannot be written
+#[derive(Debug, Clone, PartialEq)]
+pub enum WriteError {
+    /// A sync client kept the file locked through every retry
+    SyncInterference {
+        path: String,
+        /// Sync client owning the folder, if recognized from the path
+        provider: Option<&'static str>,
+        attempts: usize,
+    },
+    /// The file or its folder is not writable (EACCES/EPERM)
+    PermissionDenied {
+        path: String,
+        /// The file itself is read-only (see `file_access::clear_readonly`)
+        read_only: bool,
+    },
+    /// Any other I/O failure
+    Io(String),
+}
+
+impl fmt::Display for WriteError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            WriteError::SyncInterference { path, provider, attempts } => write!(
+                f,
+                "Sync tool interfered: {} kept {} locked after {} attempts, try again once syncing finishes",
+                provider.unwrap_or("A sync tool"),
+                path,
+                attempts
+            ),
+            WriteError::PermissionDenied { path, read_only: true } => {
+                write!(f, "Permission denied: {} is read-only", path)
+            }
+            WriteError::PermissionDenied { path, read_only: false } => {
+                write!(f, "Permission denied: cannot write {}", path)
+            }
+            WriteError::Io(msg) => write!(f, "Failed to save file: {}", msg),
+        }
+    }
+}
+
+impl std::error::Error for WriteError {}
+
+impl From<WriteError> for String {
+    fn from(e: WriteError) -> String {
+        e.to_string()
+    }
+}
+
+/// Writes a file, retrying while a cloud sync client holds it locked
+pub fn write_file(path: &Path, contents: impl AsRef<[u8]>) -> Result<(), WriteError> {
+    let contents = contents.as_ref();
+    with_retry(path, RETRY_DELAYS_MS, || fs::write(path, contents))
+}
+
+// Helper: Runs `write` until it succeeds, fails for another reason or the
+// delays run out
+fn with_retry(
+    path: &Path,
+    delays: &[u64],
+    mut write: impl FnMut() -> io::Result<()>,
+) -> Result<(), WriteError> {
+    let provider = cloud_provider(path);
+    let mut attempts = 0;
+
+    loop {
+        attempts += 1;
+        let error = match write() {
+            Ok(()) => return Ok(()),
+            Err(e) => e,
+        };
+        // A read-only file stays read-only, however long we wait
+        let read_only = error.kind() == io::ErrorKind::PermissionDenied && file_access::is_read_only(path);
+        if read_only || !is_sync_lock_error(&error, provider.is_some()) {
+            return Err(match error.kind() {
+                io::ErrorKind::PermissionDenied => WriteError::PermissionDenied {
+                    path: path.to_string_lossy().to_string(),
+                    read_only,
+                },
+                _ => WriteError::Io(error.to_string()),
+            });
+        }
+        let Some(delay) = delays.get(attempts - 1) else {
+            log::warn!("⚠️ {:?} still locked after {} attempts: {}", path, attempts, error);
+            return Err(WriteError::SyncInterference {
+                path: path.to_string_lossy().to_string(),
+                provider,
+                attempts,
+            });
+        };
+        log::warn!("⏳ {:?} is locked ({}), retrying in {}ms", path, error, delay);
+        thread::sleep(Duration::from_millis(*delay));
+    }
+}
+
+/// Checks if an error is a transient lock, as held by sync clients
+///
+/// Access denied is only transient inside a synced folder, where OneDrive
+/// briefly denies access while uploading.
+pub fn is_sync_lock_error(error: &io::Error, in_synced_folder: bool) -> bool {
+    if matches!(error.kind(), io::ErrorKind::WouldBlock | io::ErrorKind::TimedOut) {
+        return true;
+    }
+    if in_synced_folder && error.kind() == io::ErrorKind::PermissionDenied {
+        return true;
+    }
+    error.raw_os_error().is_some_and(|code| LOCK_ERRORS.contains(&code))
+}
+
+/// ERROR_SHARING_VIOLATION, ERROR_LOCK_VIOLATION,
+/// ERROR_CLOUD_FILE_PROVIDER_NOT_RUNNING, ERROR_CLOUD_FILE_IN_USE and
+/// ERROR_CLOUD_FILE_PROPERTY_LOCK_CONFLICT
+#[cfg(windows)]
+const LOCK_ERRORS: &[i32] = &[32, 33, 362, 391, 397];
+
+/// EBUSY and EDEADLK (iCloud Drive while it downloads an evicted file)
+#[cfg(target_os = "macos")]
+const LOCK_ERRORS: &[i32] = &[16, 11];
+
+/// EBUSY
+#[cfg(not(any(windows, target_os = "macos")))]
+const LOCK_ERRORS: &[i32] = &[16];
+
+/// Sync client owning the folder of `path`, recognized by folder names
+pub fn cloud_provider(path: &Path) -> Option<&'static str> {
+    path.components().find_map(|component| {
+        let Component::Normal(name) = component else {
+            return None;
+        };
+        let name = name.to_string_lossy();
+        if name.starts_with("OneDrive") {
+            Some("OneDrive")
+        } else if name == "iCloud Drive" || name == "Mobile Documents" || name.starts_with("iCloudDrive") {
+            Some("iCloud Drive")
+        } else if name == "Dropbox" || name.starts_with("Dropbox (") {
+            Some("Dropbox")
+        } else if name == "Google Drive" || name.starts_with("GoogleDrive") {
+            Some("Google Drive")
+        } else {
+            None
+        }
+    })
+}
+
+// ============================================================================
+// TESTS
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn busy() -> io::Error {
+        io::Error::new(io::ErrorKind::WouldBlock, "busy")
+    }
+
+    #[test]
+    fn test_retries_sync_locks() {
+        let path = Path::new("/Users/me/OneDrive - Work/notes/plan.md");
+
+        let mut calls = 0;
+        let result = with_retry(path, &[0, 0, 0], || {
+            calls += 1;
+            if calls < 3 { Err(busy()) } else { Ok(()) }
+        });
+        assert_eq!((result, calls), (Ok(()), 3));
+
+        let result = with_retry(path, &[0, 0], || Err(busy()));
+        assert_eq!(
+            result,
+            Err(WriteError::SyncInterference { path: path.to_string_lossy().to_string(), provider: Some("OneDrive"), attempts: 3 })
+        );
+        assert!(String::from(result.unwrap_err()).starts_with("Sync tool interfered: OneDrive kept"));
+
+        // Other failures are not retried
+        let mut calls = 0;
+        let result = with_retry(path, &[0, 0], || {
+            calls += 1;
+            Err(io::Error::new(io::ErrorKind::NotFound, "gone"))
+        });
+        assert_eq!((result, calls), (Err(WriteError::Io("gone".to_string())), 1));
+
+        let denied = || Err(io::Error::new(io::ErrorKind::PermissionDenied, "denied"));
+        let outside = Path::new("/home/me/notes/plan.md");
+        assert_eq!(
+            with_retry(outside, &[0], denied),
+            Err(WriteError::PermissionDenied { path: outside.to_string_lossy().to_string(), read_only: false })
+        );
+    }
+
+    #[test]
+    fn test_lock_detection() {
+        let denied = io::Error::new(io::ErrorKind::PermissionDenied, "denied");
+        assert!(is_sync_lock_error(&denied, true));
+        assert!(!is_sync_lock_error(&denied, false));
+        assert!(is_sync_lock_error(&io::Error::from_raw_os_error(LOCK_ERRORS[0]), false));
+
+        assert_eq!(cloud_provider(Path::new("/Users/me/Library/Mobile Documents/com~apple~CloudDocs/a.md")), Some("iCloud Drive"));
+        assert_eq!(cloud_provider(Path::new("/home/me/Dropbox (Team)/a.md")), Some("Dropbox"));
+        assert_eq!(cloud_provider(Path::new("/home/me/notes/a.md")), None);
+    }
+}
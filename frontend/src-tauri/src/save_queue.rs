@@ -0,0 +1,579 @@
+//! Debounced Save Queue for MDReader
+//!
+//! Editor autosave can fire many writes per minute for the same document.
+//! This module coalesces those writes per file: each queued save replaces
+//! any pending content for that path and pushes the write deadline back,
+//! so only the latest content hits the disk once typing settles.
+//!
+//! A single background flusher thread is spawned per path while a save is
+//! pending. Writes go through the shared per-path `WriteLocks`, so a flush
+//! never interleaves with a direct save or an older flush of the same file.
+//! Async commands flush with the `_async` variants, which wait for the lock
+//! and write (running any `before_save` hooks) off the async runtime.
+//!
+//! A write that fails puts its content back into the queue (unless newer
+//! content arrived meanwhile) and the flusher retries it with a growing
+//! delay, so a locked or briefly unreachable file never loses an edit.
+//!
+//! A save may carry a `Prepare` step (the `before_save` hooks and list
+//! fixes of the editor's saves), which runs on the content right before it
+//! is written, so queued saves get the same treatment as direct ones. A
+//! step that refuses the content is final: the save is dropped, not
+//! retried, and only the prepared content is kept for write retries.
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+use crate::safe_write;
+use crate::write_locks::{WriteGuard, WriteLocks, DEFAULT_LOCK_WAIT_MS};
+
+/// Default debounce window applied when the caller does not specify one
+pub const DEFAULT_DEBOUNCE_MS: u64 = 750;
+
+/// Upper bound for a caller-supplied debounce window
+pub const MAX_DEBOUNCE_MS: u64 = 10_000;
+
+/// Maximum time a save may stay pending while new content keeps arriving
+///
+/// Without this cap, continuous typing would postpone the write forever.
+pub const MAX_PENDING_MS: u64 = 5_000;
+
+/// First delay before a failed write is retried (doubled on each failure)
+const RETRY_MIN_MS: u64 = 1_000;
+
+/// Longest delay between retries of a failed write
+const RETRY_MAX_MS: u64 = 60_000;
+
+/// Step run on the content of a save right before it is written; an
+/// error drops the save (the step reports it, e.g. as `save-failed`)
+pub type Prepare = Arc<dyn Fn(&Path, String) -> Result<String, String> + Send + Sync>;
+
+/// A save waiting for its debounce window to elapse
+struct PendingSave {
+    content: String,
+    prepare: Option<Prepare>,
+    deadline: Instant,
+    first_queued: Instant,
+    coalesced: u64,
+}
+
+/// Registry of pending debounced saves, keyed by absolute file path
+#[derive(Clone, Default)]
+pub struct SaveQueue {
+    pending: Arc<Mutex<HashMap<PathBuf, PendingSave>>>,
+    /// Per-path write locks shared with the direct save commands
+    write_locks: WriteLocks,
+}
+
+/// Summary of the save queue for diagnostics
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct SaveQueueStats {
+    pub pending_files: usize,
+    pub coalesced_writes: u64,
+}
+
+impl SaveQueue {
+    /// Creates an empty save queue with its own lock registry
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates an empty save queue sharing an existing lock registry
+    pub fn with_write_locks(write_locks: WriteLocks) -> Self {
+        Self {
+            pending: Arc::default(),
+            write_locks,
+        }
+    }
+
+    /// Queues content to be written to `path` after `debounce` of inactivity
+    ///
+    /// # Arguments
+    /// * `path` - Validated absolute file path
+    /// * `content` - Full document content (replaces any pending content)
+    /// * `debounce` - Quiet period before the write happens
+    ///
+    /// # Returns
+    /// * `Ok(true)` - If an already pending save was coalesced
+    /// * `Ok(false)` - If this is a new pending save
+    /// * `Err(String)` - If the mutex is poisoned
+    pub fn queue(&self, path: PathBuf, content: String, debounce: Duration) -> Result<bool, String> {
+        self.queue_with(path, content, debounce, None)
+    }
+
+    /// Queues content like `queue`, with a step to run on it before it is
+    /// written (replaces the step of any pending content)
+    pub fn queue_with(
+        &self,
+        path: PathBuf,
+        content: String,
+        debounce: Duration,
+        prepare: Option<Prepare>,
+    ) -> Result<bool, String> {
+        let mut pending = self.pending
+            .lock()
+            .map_err(|e| format!("Failed to lock save queue: {}", e))?;
+
+        let now = Instant::now();
+
+        if let Some(entry) = pending.get_mut(&path) {
+            let latest = entry.first_queued + Duration::from_millis(MAX_PENDING_MS);
+            entry.content = content;
+            entry.prepare = prepare;
+            entry.deadline = (now + debounce).min(latest);
+            entry.coalesced += 1;
+            return Ok(true);
+        }
+
+        pending.insert(path.clone(), PendingSave {
+            content,
+            prepare,
+            deadline: now + debounce,
+            first_queued: now,
+            coalesced: 0,
+        });
+        drop(pending);
+
+        self.spawn_flusher(path);
+        Ok(false)
+    }
+
+    /// Drops a pending save without writing it
+    ///
+    /// Used when a direct save supersedes the queued content.
+    pub fn cancel(&self, path: &Path) -> Result<bool, String> {
+        let mut pending = self.pending
+            .lock()
+            .map_err(|e| format!("Failed to lock save queue: {}", e))?;
+
+        Ok(pending.remove(path).is_some())
+    }
+
+    /// Immediately writes the pending save for a single path, if any
+    ///
+    /// If the write fails, the content stays pending (unless newer content
+    /// was queued meanwhile). If the save's `Prepare` step refuses it, the
+    /// save is dropped.
+    ///
+    /// # Returns
+    /// * `Ok(true)` - If pending content was written
+    /// * `Ok(false)` - If nothing was pending for this path, or the save was refused
+    pub fn flush_path(&self, path: &Path) -> Result<bool, String> {
+        let guard = self.write_locks.acquire(path)?;
+        self.flush_locked(path, &guard)
+    }
+
+    /// Writes the pending save for a path like `flush_path`, for a caller
+    /// already holding the path's write lock (e.g. before renaming it)
+    pub fn flush_locked(&self, path: &Path, _guard: &WriteGuard) -> Result<bool, String> {
+        let entry = self.pending
+            .lock()
+            .map_err(|e| format!("Failed to lock save queue: {}", e))?
+            .remove(path);
+
+        let Some(mut entry) = entry else {
+            return Ok(false);
+        };
+        if let Some(prepare) = entry.prepare.take() {
+            match prepare(path, std::mem::take(&mut entry.content)) {
+                Ok(content) => entry.content = content,
+                Err(e) => {
+                    log::warn!("🚫 Debounced save refused for {:?}: {}", path, e);
+                    return Ok(false);
+                }
+            }
+        }
+        if let Err(e) = write_pending(path, &entry) {
+            // Retries write the prepared content without preparing it again
+            self.pending
+                .lock()
+                .map_err(|e| format!("Failed to lock save queue: {}", e))?
+                .entry(path.to_path_buf())
+                .or_insert(entry);
+            return Err(e);
+        }
+        Ok(true)
+    }
+
+    /// Writes the pending save for a path like `flush_path`, without
+    /// blocking the async runtime
+    ///
+    /// Waits at most `DEFAULT_LOCK_WAIT_MS` for the path's write lock.
+    pub async fn flush_path_async(&self, path: &Path) -> Result<bool, String> {
+        let guard = self.write_locks
+            .acquire_async(path, Duration::from_millis(DEFAULT_LOCK_WAIT_MS)).await?;
+        Ok(self.flush_locked_async(path, guard).await?.0)
+    }
+
+    /// Writes the pending save for a path like `flush_locked`, without
+    /// blocking the async runtime; hands the guard back to the caller
+    pub async fn flush_locked_async(&self, path: &Path, guard: WriteGuard) -> Result<(bool, WriteGuard), String> {
+        // Nothing else can queue a write for the path while the guard is held
+        if !self.is_pending(path) {
+            return Ok((false, guard));
+        }
+
+        let (queue, path) = (self.clone(), path.to_path_buf());
+        tauri::async_runtime::spawn_blocking(move || {
+            let flushed = queue.flush_locked(&path, &guard)?;
+            Ok((flushed, guard))
+        })
+        .await
+        .map_err(|e| format!("Failed to flush pending save: {}", e))?
+    }
+
+    /// Drops the pending saves of every file below `dir` without writing
+    /// them (e.g. before the folder is deleted)
+    ///
+    /// # Returns
+    /// * `Ok(usize)` - Number of saves dropped
+    pub fn cancel_under(&self, dir: &Path) -> Result<usize, String> {
+        let mut pending = self.pending
+            .lock()
+            .map_err(|e| format!("Failed to lock save queue: {}", e))?;
+
+        let before = pending.len();
+        pending.retain(|path, _| !path.starts_with(dir));
+        Ok(before - pending.len())
+    }
+
+    /// Immediately writes every pending save (e.g. on window close)
+    ///
+    /// # Returns
+    /// * `Ok(usize)` - Number of files written
+    /// * `Err(String)` - First write error encountered (remaining files are still attempted)
+    pub fn flush_all(&self) -> Result<usize, String> {
+        self.flush_matching(|_| true)
+    }
+
+    /// Writes every pending save like `flush_all`, without blocking the
+    /// async runtime
+    pub async fn flush_all_async(&self) -> Result<usize, String> {
+        self.flush_matching_async(|_| true).await
+    }
+
+    /// Immediately writes the pending saves of every file below `dir`
+    /// (e.g. before the folder is renamed), without blocking the async
+    /// runtime
+    ///
+    /// # Returns
+    /// * `Ok(usize)` - Number of files written
+    /// * `Err(String)` - First write error encountered (remaining files are still attempted)
+    pub async fn flush_under_async(&self, dir: &Path) -> Result<usize, String> {
+        self.flush_matching_async(|path| path.starts_with(dir)).await
+    }
+
+    fn flush_matching(&self, matches: impl Fn(&Path) -> bool) -> Result<usize, String> {
+        let mut written = 0;
+        let mut first_error = None;
+
+        for path in &self.pending_paths(matches)? {
+            match self.flush_path(path) {
+                Ok(true) => written += 1,
+                Ok(false) => {}
+                Err(e) => {
+                    first_error.get_or_insert(e);
+                }
+            }
+        }
+
+        match first_error {
+            Some(e) => Err(e),
+            None => Ok(written),
+        }
+    }
+
+    async fn flush_matching_async(&self, matches: impl Fn(&Path) -> bool) -> Result<usize, String> {
+        let mut written = 0;
+        let mut first_error = None;
+
+        for path in &self.pending_paths(matches)? {
+            match self.flush_path_async(path).await {
+                Ok(true) => written += 1,
+                Ok(false) => {}
+                Err(e) => {
+                    first_error.get_or_insert(e);
+                }
+            }
+        }
+
+        match first_error {
+            Some(e) => Err(e),
+            None => Ok(written),
+        }
+    }
+
+    fn pending_paths(&self, matches: impl Fn(&Path) -> bool) -> Result<Vec<PathBuf>, String> {
+        Ok(self.pending
+            .lock()
+            .map_err(|e| format!("Failed to lock save queue: {}", e))?
+            .keys()
+            .filter(|path| matches(path))
+            .cloned()
+            .collect())
+    }
+
+    /// Checks if a save is pending for a path
+    pub fn is_pending(&self, path: &Path) -> bool {
+        self.pending
+            .lock()
+            .map(|p| p.contains_key(path))
+            .unwrap_or(false)
+    }
+
+    /// Gets the number of files with pending saves
+    pub fn pending_count(&self) -> usize {
+        self.pending
+            .lock()
+            .map(|p| p.len())
+            .unwrap_or(0)
+    }
+
+    /// Gets queue statistics
+    pub fn stats(&self) -> Result<SaveQueueStats, String> {
+        let pending = self.pending
+            .lock()
+            .map_err(|e| format!("Failed to lock save queue: {}", e))?;
+
+        Ok(SaveQueueStats {
+            pending_files: pending.len(),
+            coalesced_writes: pending.values().map(|p| p.coalesced).sum(),
+        })
+    }
+
+    /// Spawns the background thread that waits out the debounce window
+    /// and retries failed writes
+    fn spawn_flusher(&self, path: PathBuf) {
+        let queue = self.clone();
+        let mut retry = Duration::from_millis(RETRY_MIN_MS);
+
+        thread::spawn(move || loop {
+            let wait = match queue.pending.lock() {
+                Ok(pending) => match pending.get(&path) {
+                    Some(entry) => entry.deadline.saturating_duration_since(Instant::now()),
+                    // Flushed or cancelled by someone else
+                    None => return,
+                },
+                Err(_) => return,
+            };
+
+            if !wait.is_zero() {
+                thread::sleep(wait);
+                continue;
+            }
+
+            if let Err(e) = queue.flush_path(&path) {
+                log::error!("❌ Debounced save failed for {:?}, retrying in {:?}: {}", path, retry, e);
+                thread::sleep(retry);
+                retry = (retry * 2).min(Duration::from_millis(RETRY_MAX_MS));
+                continue;
+            }
+            return;
+        });
+    }
+}
+
+/// Writes the (prepared) content of a pending save to disk
+fn write_pending(path: &Path, entry: &PendingSave) -> Result<(), String> {
+    safe_write::write_file(path, &entry.content)?;
+
+    log::info!("💾 Saved document (debounced, {} coalesced): {:?}", entry.coalesced, path);
+    Ok(())
+}
+
+// ============================================================================
+// TESTS
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    /// Helper to create test workspace
+    fn setup_test_workspace() -> TempDir {
+        TempDir::new().expect("Failed to create temp dir")
+    }
+
+    #[test]
+    fn test_queue_writes_after_debounce() {
+        let temp = setup_test_workspace();
+        let path = temp.path().join("doc.md");
+        let queue = SaveQueue::new();
+
+        let coalesced = queue.queue(path.clone(), "hello".to_string(), Duration::from_millis(50)).unwrap();
+        assert!(!coalesced, "First save should not be coalesced");
+        assert!(queue.is_pending(&path));
+        assert!(!path.exists(), "File should not be written before debounce elapses");
+
+        thread::sleep(Duration::from_millis(300));
+
+        assert!(!queue.is_pending(&path), "Save should be flushed");
+        assert_eq!(fs::read_to_string(&path).unwrap(), "hello");
+    }
+
+    #[test]
+    fn test_rapid_saves_are_coalesced() {
+        let temp = setup_test_workspace();
+        let path = temp.path().join("doc.md");
+        let queue = SaveQueue::new();
+
+        for i in 0..5 {
+            queue.queue(path.clone(), format!("version {}", i), Duration::from_millis(100)).unwrap();
+        }
+
+        let stats = queue.stats().unwrap();
+        assert_eq!(stats.pending_files, 1);
+        assert_eq!(stats.coalesced_writes, 4);
+
+        thread::sleep(Duration::from_millis(400));
+        assert_eq!(fs::read_to_string(&path).unwrap(), "version 4", "Latest content should win");
+    }
+
+    #[test]
+    fn test_flush_all_writes_immediately() {
+        let temp = setup_test_workspace();
+        let a = temp.path().join("a.md");
+        let b = temp.path().join("b.md");
+        let queue = SaveQueue::new();
+
+        queue.queue(a.clone(), "A".to_string(), Duration::from_secs(5)).unwrap();
+        queue.queue(b.clone(), "B".to_string(), Duration::from_secs(5)).unwrap();
+
+        assert_eq!(queue.flush_all().unwrap(), 2);
+        assert_eq!(queue.pending_count(), 0);
+        assert_eq!(fs::read_to_string(&a).unwrap(), "A");
+        assert_eq!(fs::read_to_string(&b).unwrap(), "B");
+    }
+
+    #[test]
+    fn test_cancel_drops_pending_save() {
+        let temp = setup_test_workspace();
+        let path = temp.path().join("doc.md");
+        let queue = SaveQueue::new();
+
+        queue.queue(path.clone(), "draft".to_string(), Duration::from_millis(50)).unwrap();
+        assert!(queue.cancel(&path).unwrap());
+        assert!(!queue.cancel(&path).unwrap(), "Second cancel should find nothing");
+
+        thread::sleep(Duration::from_millis(200));
+        assert!(!path.exists(), "Cancelled save should never be written");
+    }
+
+    #[test]
+    fn test_prepare_runs_before_write() {
+        let temp = setup_test_workspace();
+        let path = temp.path().join("doc.md");
+        let queue = SaveQueue::new();
+
+        let prepare: Prepare = Arc::new(|_, content| Ok(content.to_uppercase()));
+        queue.queue_with(path.clone(), "draft".to_string(), Duration::from_secs(5), Some(prepare.clone())).unwrap();
+        queue.queue_with(path.clone(), "final".to_string(), Duration::from_secs(5), Some(prepare)).unwrap();
+        assert!(queue.flush_path(&path).unwrap());
+        assert_eq!(fs::read_to_string(&path).unwrap(), "FINAL");
+
+        let refuse: Prepare = Arc::new(|_, _| Err("Hook failed: lint".to_string()));
+        queue.queue_with(path.clone(), "broken".to_string(), Duration::from_secs(5), Some(refuse)).unwrap();
+        assert!(!queue.flush_path(&path).unwrap(), "A refused save is not written");
+        assert!(!queue.is_pending(&path), "A refused save is dropped, not retried");
+        assert_eq!(fs::read_to_string(&path).unwrap(), "FINAL");
+    }
+
+    #[test]
+    fn test_failed_write_stays_pending() {
+        let temp = setup_test_workspace();
+        let path = temp.path().join("missing").join("doc.md");
+        let queue = SaveQueue::new();
+
+        let runs = Arc::new(std::sync::atomic::AtomicUsize::new(0));
+        let counted = runs.clone();
+        let prepare: Prepare = Arc::new(move |_, content| {
+            counted.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+            Ok(content)
+        });
+        queue.queue_with(path.clone(), "kept".to_string(), Duration::from_secs(5), Some(prepare)).unwrap();
+        assert!(queue.flush_path(&path).is_err());
+        assert!(queue.is_pending(&path), "Content must survive a failed write");
+
+        fs::create_dir(temp.path().join("missing")).unwrap();
+        assert!(queue.flush_path(&path).unwrap());
+        assert_eq!(fs::read_to_string(&path).unwrap(), "kept");
+        assert_eq!(runs.load(std::sync::atomic::Ordering::SeqCst), 1, "A retry must not prepare again");
+    }
+
+    #[test]
+    fn test_flush_and_cancel_under_folder() {
+        let temp = setup_test_workspace();
+        fs::create_dir(temp.path().join("notes")).unwrap();
+        let inside = temp.path().join("notes").join("a.md");
+        let sibling = temp.path().join("notes-old.md");
+        let queue = SaveQueue::new();
+
+        queue.queue(inside.clone(), "A".to_string(), Duration::from_secs(5)).unwrap();
+        queue.queue(sibling.clone(), "B".to_string(), Duration::from_secs(5)).unwrap();
+        let flushed = tauri::async_runtime::block_on(queue.flush_under_async(&temp.path().join("notes")));
+        assert_eq!(flushed.unwrap(), 1);
+        assert_eq!(fs::read_to_string(&inside).unwrap(), "A");
+        assert!(queue.is_pending(&sibling), "Only files inside the folder are flushed");
+
+        queue.queue(inside.clone(), "A2".to_string(), Duration::from_secs(5)).unwrap();
+        assert_eq!(queue.cancel_under(&temp.path().join("notes")).unwrap(), 1);
+        assert_eq!(queue.pending_count(), 1);
+    }
+
+    #[test]
+    fn test_async_flush_waits_off_the_runtime() {
+        let temp = setup_test_workspace();
+        let a = temp.path().join("a.md");
+        let b = temp.path().join("b.md");
+        let locks = WriteLocks::new();
+        let queue = SaveQueue::with_write_locks(locks.clone());
+
+        queue.queue(a.clone(), "A".to_string(), Duration::from_secs(5)).unwrap();
+        queue.queue(b.clone(), "B".to_string(), Duration::from_secs(5)).unwrap();
+
+        // A direct save holding `a` finishes shortly
+        let guard = locks.try_acquire(&a, Duration::ZERO).unwrap();
+        let release = thread::spawn(move || {
+            thread::sleep(Duration::from_millis(100));
+            drop(guard);
+        });
+
+        assert!(tauri::async_runtime::block_on(queue.flush_path_async(&a)).unwrap());
+        assert_eq!(fs::read_to_string(&a).unwrap(), "A");
+        assert_eq!(tauri::async_runtime::block_on(queue.flush_all_async()).unwrap(), 1);
+        assert_eq!(fs::read_to_string(&b).unwrap(), "B");
+        assert!(!tauri::async_runtime::block_on(queue.flush_path_async(&b)).unwrap());
+        release.join().unwrap();
+    }
+
+    #[test]
+    fn test_flush_path_without_pending() {
+        let temp = setup_test_workspace();
+        let queue = SaveQueue::new();
+
+        assert!(!queue.flush_path(&temp.path().join("none.md")).unwrap());
+    }
+
+    #[test]
+    fn test_flush_waits_for_write_lock() {
+        let temp = setup_test_workspace();
+        let path = temp.path().join("doc.md");
+        let locks = WriteLocks::new();
+        let queue = SaveQueue::with_write_locks(locks.clone());
+
+        // Simulate a direct save holding the file
+        let guard = locks.try_acquire(&path, Duration::ZERO).unwrap();
+        queue.queue(path.clone(), "queued".to_string(), Duration::from_millis(10)).unwrap();
+
+        thread::sleep(Duration::from_millis(150));
+        assert!(!path.exists(), "Flush must not write while the file is locked");
+
+        drop(guard);
+        thread::sleep(Duration::from_millis(150));
+        assert_eq!(fs::read_to_string(&path).unwrap(), "queued");
+    }
+}
@@ -0,0 +1,602 @@
+//! Full-Text Search Index
+//!
+//! Term frequencies of every note, kept per workspace in the app's cache
+//! directory (`<cache>/mdreader/search_index/<hash of the workspace path>.json`)
+//! so a large vault is searchable right after startup without reading every
+//! note again. Being derived data, the index stays out of the workspace,
+//! where sync tools would carry it to every device:
+//!
+//! ```json
+//! { "version": 1, "notes": { "plan.md": { "modified_ms": 1700000000000, "size": 812,
+//!   "hash": "…", "title": "Plan", "length": 120, "terms": { "budget": 3, ... } } } }
+//! ```
+//!
+//! The persisted index is loaded first and answers queries at once; it is
+//! then reconciled with the disk in the background. Reconciling stats every
+//! note and only reads those whose modification time or size changed, and
+//! only re-tokenizes those whose content hash changed too.
+//!
+//! Queries match every term (the last one as a prefix, for search as you
+//! type) and rank notes with BM25; title words weigh more than body words.
+
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use serde::{Deserialize, Serialize};
+use crate::file_meta::epoch_ms;
+use crate::frontmatter;
+use crate::memory_budget::{stamp, CacheEntry, CacheKind};
+use crate::notes::{collect_markdown_files, note_title, relative_path};
+use crate::workspace_settings::SETTINGS_DIR;
+
+/// File name of the index formerly kept in the settings directory
+pub const SEARCH_INDEX_FILE: &str = "search_index.json";
+
+/// Format version; indexes of another version are rebuilt
+pub const SEARCH_INDEX_VERSION: u32 = 1;
+
+/// Results returned when the caller does not give a limit
+pub const DEFAULT_SEARCH_LIMIT: usize = 50;
+
+/// How much a title word counts compared to a body word
+const TITLE_WEIGHT: u32 = 3;
+
+/// BM25 term frequency saturation
+const BM25_K1: f64 = 1.2;
+
+/// BM25 document length normalization
+const BM25_B: f64 = 0.75;
+
+/// Characters of context shown around the first match
+const SNIPPET_CHARS: usize = 160;
+
+/// Indexed terms of a note
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct IndexedNote {
+    /// Modification time (ms since the epoch)
+    pub modified_ms: i64,
+    pub size: u64,
+    /// Content hash (blake3, hex)
+    pub hash: String,
+    pub title: String,
+    /// Weighted number of terms
+    pub length: u32,
+    /// Weighted count of each term
+    pub terms: BTreeMap<String, u32>,
+}
+
+impl IndexedNote {
+    /// Tokenizes a note
+    pub fn from_content(path: &Path, content: &str) -> Self {
+        let (fields, body) = frontmatter::split(content);
+        let title = note_title(&fields.unwrap_or_default(), body, path);
+
+        let mut terms = BTreeMap::new();
+        for term in tokenize(body) {
+            *terms.entry(term).or_insert(0) += 1;
+        }
+        for term in tokenize(&title) {
+            *terms.entry(term).or_insert(0) += TITLE_WEIGHT;
+        }
+        Self {
+            hash: content_hash(content),
+            title,
+            length: terms.values().sum(),
+            terms,
+            ..Self::default()
+        }
+    }
+}
+
+/// A note matching a query
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SearchHit {
+    /// Path relative to the workspace root
+    pub path: String,
+    pub title: String,
+    pub score: f64,
+    /// Text around the first match (filled in by the caller)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub snippet: Option<String>,
+}
+
+/// Changes found on disk by `scan_changes`
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct IndexDelta {
+    /// New or edited notes
+    pub indexed: Vec<(String, IndexedNote)>,
+    /// Notes with a new time or size but the same content
+    pub touched: Vec<(String, i64, u64)>,
+    /// Notes deleted since
+    pub removed: Vec<String>,
+}
+
+impl IndexDelta {
+    pub fn is_empty(&self) -> bool {
+        self.indexed.is_empty() && self.touched.is_empty() && self.removed.is_empty()
+    }
+
+    /// Number of notes added, changed or removed
+    pub fn changed(&self) -> usize {
+        self.indexed.len() + self.removed.len()
+    }
+}
+
+/// Time, size and hash of every indexed note, taken before a scan
+pub type IndexSnapshot = BTreeMap<String, (i64, u64, String)>;
+
+/// Full-text index of one workspace
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SearchIndex {
+    #[serde(default)]
+    pub version: u32,
+    /// Notes keyed by relative path
+    #[serde(default)]
+    pub notes: BTreeMap<String, IndexedNote>,
+    /// Notes containing each term (derived from `notes`, not stored)
+    #[serde(skip)]
+    postings: HashMap<String, BTreeSet<String>>,
+}
+
+impl SearchIndex {
+    /// Loads the index of a workspace from `cache_dir` (empty if missing,
+    /// unreadable or of another version)
+    pub fn load(cache_dir: &Path, workspace_root: &Path) -> Self {
+        let path = index_path(cache_dir, workspace_root);
+        let mut index: Self = match fs::read_to_string(&path) {
+            Ok(json) => serde_json::from_str(&json).unwrap_or_else(|e| {
+                log::warn!("⚠️ Ignoring unreadable search index {:?}: {}", path, e);
+                Self::default()
+            }),
+            Err(_) => Self::default(),
+        };
+        if index.version != SEARCH_INDEX_VERSION {
+            index = Self { version: SEARCH_INDEX_VERSION, ..Self::default() };
+        }
+        index.rebuild_postings();
+        index
+    }
+
+    /// Writes the index of a workspace to `cache_dir`
+    ///
+    /// A copy left in the workspace settings directory by earlier versions
+    /// is removed.
+    pub fn save(&self, cache_dir: &Path, workspace_root: &Path) -> Result<(), String> {
+        fs::create_dir_all(cache_dir)
+            .map_err(|e| format!("Failed to create cache directory: {}", e))?;
+        let json = serde_json::to_string(self)
+            .map_err(|e| format!("Failed to serialize search index: {}", e))?;
+        fs::write(index_path(cache_dir, workspace_root), json)
+            .map_err(|e| format!("Failed to write search index: {}", e))?;
+
+        let _ = fs::remove_file(workspace_root.join(SETTINGS_DIR).join(SEARCH_INDEX_FILE));
+        Ok(())
+    }
+
+    /// Estimated memory held by the index and its postings
+    pub fn approx_bytes(&self) -> usize {
+        let notes: usize = self.notes
+            .iter()
+            .map(|(path, note)| {
+                path.len()
+                    + std::mem::size_of::<IndexedNote>()
+                    + note.hash.len()
+                    + note.title.len()
+                    + note.terms.keys().map(|term| term.len() + 32).sum::<usize>()
+            })
+            .sum();
+        let postings: usize = self.postings
+            .iter()
+            .map(|(term, paths)| term.len() + 48 + paths.iter().map(|path| path.len() + 32).sum::<usize>())
+            .sum();
+        notes + postings
+    }
+
+    /// Time, size and hash of every note, for `scan_changes`
+    pub fn snapshot(&self) -> IndexSnapshot {
+        self.notes
+            .iter()
+            .map(|(path, note)| (path.clone(), (note.modified_ms, note.size, note.hash.clone())))
+            .collect()
+    }
+
+    /// Applies changes found by `scan_changes`
+    pub fn apply(&mut self, delta: IndexDelta) {
+        for path in delta.removed {
+            self.remove(&path);
+        }
+        for (path, modified_ms, size) in delta.touched {
+            if let Some(note) = self.notes.get_mut(&path) {
+                note.modified_ms = modified_ms;
+                note.size = size;
+            }
+        }
+        for (path, note) in delta.indexed {
+            self.remove(&path);
+            for term in note.terms.keys() {
+                self.postings.entry(term.clone()).or_default().insert(path.clone());
+            }
+            self.notes.insert(path, note);
+        }
+        self.version = SEARCH_INDEX_VERSION;
+    }
+
+    /// Notes matching every term of `query`, best first
+    pub fn search(&self, query: &str, limit: usize) -> Vec<SearchHit> {
+        let terms = tokenize(query);
+        let Some((last, rest)) = terms.split_last() else {
+            return Vec::new();
+        };
+
+        // Each query term with the index terms it matches
+        let mut groups: Vec<Vec<&str>> = rest
+            .iter()
+            .map(|term| self.postings.get_key_value(term).map(|(t, _)| vec![t.as_str()]).unwrap_or_default())
+            .collect();
+        groups.push(self.postings.keys().filter(|t| t.starts_with(last.as_str())).map(String::as_str).collect());
+        if groups.iter().any(Vec::is_empty) {
+            return Vec::new();
+        }
+
+        // Notes containing some expansion of every query term
+        let mut candidates: Option<BTreeSet<&String>> = None;
+        for group in &groups {
+            let notes: BTreeSet<&String> = group.iter().flat_map(|t| &self.postings[*t]).collect();
+            candidates = Some(match candidates {
+                Some(previous) => previous.intersection(&notes).copied().collect(),
+                None => notes,
+            });
+        }
+
+        let count = self.notes.len() as f64;
+        let average = self.notes.values().map(|n| n.length as f64).sum::<f64>() / count.max(1.0);
+        let mut hits: Vec<SearchHit> = candidates
+            .unwrap_or_default()
+            .into_iter()
+            .filter_map(|path| {
+                let note = self.notes.get(path)?;
+                let norm = BM25_K1 * (1.0 - BM25_B + BM25_B * note.length as f64 / average.max(1.0));
+                let score = groups
+                    .iter()
+                    .flatten()
+                    .filter_map(|term| {
+                        let tf = *note.terms.get(*term)? as f64;
+                        let df = self.postings[*term].len() as f64;
+                        let idf = ((count - df + 0.5) / (df + 0.5) + 1.0).ln();
+                        Some(idf * tf * (BM25_K1 + 1.0) / (tf + norm))
+                    })
+                    .sum();
+                Some(SearchHit { path: path.clone(), title: note.title.clone(), score, snippet: None })
+            })
+            .collect();
+        hits.sort_by(|a, b| b.score.total_cmp(&a.score).then_with(|| a.path.cmp(&b.path)));
+        hits.truncate(limit);
+        hits
+    }
+
+    fn remove(&mut self, path: &str) {
+        let Some(note) = self.notes.remove(path) else {
+            return;
+        };
+        for term in note.terms.keys() {
+            if let Some(paths) = self.postings.get_mut(term) {
+                paths.remove(path);
+                if paths.is_empty() {
+                    self.postings.remove(term);
+                }
+            }
+        }
+    }
+
+    fn rebuild_postings(&mut self) {
+        self.postings.clear();
+        for (path, note) in &self.notes {
+            for term in note.terms.keys() {
+                self.postings.entry(term.clone()).or_default().insert(path.clone());
+            }
+        }
+    }
+}
+
+/// Compares the notes on disk with a snapshot of the index
+///
+/// Reads only notes whose time or size changed, reporting
+/// `(done, total, path)` per note; stops with an error when `progress`
+/// returns false.
+pub fn scan_changes(
+    workspace_root: &Path,
+    snapshot: &IndexSnapshot,
+    progress: &mut dyn FnMut(usize, usize, &str) -> bool,
+) -> Result<IndexDelta, String> {
+    let mut delta = IndexDelta::default();
+    let mut seen = BTreeSet::new();
+
+    let files = collect_markdown_files(workspace_root, workspace_root)?;
+    for (done, path) in files.iter().enumerate() {
+        let key = relative_path(workspace_root, path);
+        if !progress(done, files.len(), &key) {
+            return Err("Search index update cancelled".to_string());
+        }
+        let Ok(metadata) = fs::metadata(path) else {
+            continue;
+        };
+        let modified_ms = metadata.modified().map(epoch_ms).unwrap_or(0);
+        let size = metadata.len();
+        let indexed = snapshot.get(&key);
+        seen.insert(key.clone());
+
+        if indexed.is_some_and(|(ms, len, _)| (*ms, *len) == (modified_ms, size)) {
+            continue;
+        }
+        let content = match fs::read_to_string(path) {
+            Ok(content) => content,
+            Err(e) => {
+                log::warn!("⚠️ Skipping note in search index {:?}: {}", path, e);
+                continue;
+            }
+        };
+        if indexed.is_some_and(|(_, _, hash)| *hash == content_hash(&content)) {
+            delta.touched.push((key, modified_ms, size));
+        } else {
+            let mut note = IndexedNote::from_content(path, &content);
+            note.modified_ms = modified_ms;
+            note.size = size;
+            delta.indexed.push((key, note));
+        }
+    }
+
+    delta.removed = snapshot.keys().filter(|key| !seen.contains(*key)).cloned().collect();
+    Ok(delta)
+}
+
+/// Splits text into lowercase words of at least two letters or digits
+pub fn tokenize(text: &str) -> Vec<String> {
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|word| word.chars().count() >= 2)
+        .map(str::to_lowercase)
+        .collect()
+}
+
+/// The line of `content` around the first word matching `query`
+pub fn snippet(content: &str, query: &str) -> Option<String> {
+    let terms = tokenize(query);
+    let (_, body) = frontmatter::split(content);
+    let line = body.lines().map(str::trim).find(|line| {
+        tokenize(line).iter().any(|word| terms.iter().any(|term| word.starts_with(term.as_str())))
+    })?;
+    let mut snippet: String = line.chars().take(SNIPPET_CHARS).collect();
+    if line.chars().count() > SNIPPET_CHARS {
+        snippet.push('…');
+    }
+    Some(snippet)
+}
+
+/// Directory search indexes are cached in
+pub fn cache_dir() -> Result<PathBuf, String> {
+    Ok(dirs::cache_dir()
+        .ok_or("Failed to get cache directory")?
+        .join("mdreader")
+        .join("search_index"))
+}
+
+fn index_path(cache_dir: &Path, workspace_root: &Path) -> PathBuf {
+    let key = blake3::hash(workspace_root.to_string_lossy().as_bytes()).to_hex();
+    cache_dir.join(format!("{}.json", &key[..32]))
+}
+
+fn content_hash(content: &str) -> String {
+    blake3::hash(content.as_bytes()).to_hex().to_string()
+}
+
+/// Search index of the current workspace (internally synchronized)
+///
+/// The persisted index is loaded on first use; reconciling scans the disk
+/// without holding the index, so queries keep being answered meanwhile.
+#[derive(Clone, Default)]
+pub struct SearchTracker {
+    current: Arc<Mutex<Option<(PathBuf, SearchIndex)>>>,
+    reconciling: Arc<AtomicBool>,
+    last_used: Arc<AtomicU64>,
+    /// Where indexes are persisted (default: `cache_dir()`)
+    cache_dir: Option<PathBuf>,
+}
+
+impl SearchTracker {
+    /// Creates a tracker with no workspace loaded
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates a tracker that persists indexes in `cache_dir`
+    pub fn with_cache_dir(cache_dir: PathBuf) -> Self {
+        Self { cache_dir: Some(cache_dir), ..Self::default() }
+    }
+
+    /// Loads the persisted index of a workspace, unless already loaded
+    ///
+    /// Returns the number of indexed notes.
+    pub fn load(&self, workspace: &str) -> Result<usize, String> {
+        self.with_index(workspace, |index| index.notes.len())
+    }
+
+    /// Checks if the index is being reconciled with the disk
+    pub fn is_reconciling(&self) -> bool {
+        self.reconciling.load(Ordering::SeqCst)
+    }
+
+    /// Brings the index of a workspace up to date with the disk and saves it
+    ///
+    /// # Returns
+    /// * `Ok(Some(changed))` - Notes added, changed or removed
+    /// * `Ok(None)` - If another reconcile is already running
+    pub fn reconcile(
+        &self,
+        workspace: &str,
+        progress: &mut dyn FnMut(usize, usize, &str) -> bool,
+    ) -> Result<Option<usize>, String> {
+        if self.reconciling.swap(true, Ordering::SeqCst) {
+            return Ok(None);
+        }
+        let result = self.reconcile_now(workspace, progress);
+        self.reconciling.store(false, Ordering::SeqCst);
+        result.map(Some)
+    }
+
+    /// Searches the index of a workspace as it is (see `SearchIndex::search`)
+    pub fn search(&self, workspace: &str, query: &str, limit: usize) -> Result<Vec<SearchHit>, String> {
+        self.with_index(workspace, |index| index.search(query, limit))
+    }
+
+    /// The loaded index as seen by the memory budget (see
+    /// `crate::memory_budget`); an index in use right now is left out
+    pub fn cache_entries(&self) -> Vec<CacheEntry> {
+        let Ok(current) = self.current.try_lock() else {
+            return Vec::new();
+        };
+        current
+            .iter()
+            .map(|(root, index)| CacheEntry {
+                cache: CacheKind::Search,
+                key: root.to_string_lossy().to_string(),
+                bytes: index.approx_bytes(),
+                last_used: self.last_used.load(Ordering::SeqCst),
+            })
+            .collect()
+    }
+
+    /// Drops the loaded index (it is reloaded from disk on next use)
+    ///
+    /// Returns false if no index was loaded, it is in use or being reconciled.
+    pub fn unload(&self) -> bool {
+        if self.is_reconciling() {
+            return false;
+        }
+        self.current
+            .try_lock()
+            .map(|mut current| current.take().is_some())
+            .unwrap_or(false)
+    }
+
+    // Helper: Snapshot under the lock, scan without it, apply under it
+    fn reconcile_now(
+        &self,
+        workspace: &str,
+        progress: &mut dyn FnMut(usize, usize, &str) -> bool,
+    ) -> Result<usize, String> {
+        let snapshot = self.with_index(workspace, |index| index.snapshot())?;
+        let root = resolve(workspace)?;
+        let delta = scan_changes(&root, &snapshot, progress)?;
+        if delta.is_empty() {
+            return Ok(0);
+        }
+
+        let changed = delta.changed();
+        let cache_dir = self.cache_dir()?;
+        self.with_index(workspace, |index| {
+            index.apply(delta);
+            index.save(&cache_dir, &root)
+        })??;
+        log::info!("🔎 Search index updated: {} notes changed", changed);
+        Ok(changed)
+    }
+
+    // Helper: Runs `f` on the index of a workspace, loading it if needed
+    fn with_index<T>(&self, workspace: &str, f: impl FnOnce(&mut SearchIndex) -> T) -> Result<T, String> {
+        let root = resolve(workspace)?;
+        let mut current = self.current
+            .lock()
+            .map_err(|e| format!("Failed to lock search index: {}", e))?;
+
+        if current.as_ref().map_or(true, |(loaded, _)| *loaded != root) {
+            *current = Some((root.clone(), SearchIndex::load(&self.cache_dir()?, &root)));
+        }
+        self.last_used.store(stamp(), Ordering::SeqCst);
+        Ok(f(&mut current.as_mut().unwrap().1))
+    }
+
+    // Helper: The directory indexes are persisted in
+    fn cache_dir(&self) -> Result<PathBuf, String> {
+        match &self.cache_dir {
+            Some(dir) => Ok(dir.clone()),
+            None => cache_dir(),
+        }
+    }
+}
+
+fn resolve(workspace: &str) -> Result<PathBuf, String> {
+    Path::new(workspace)
+        .canonicalize()
+        .map_err(|e| format!("Failed to resolve workspace: {}", e))
+}
+
+// ============================================================================
+// TESTS
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_search_ranks_and_matches_prefixes() {
+        let temp = TempDir::new().unwrap();
+        let root = temp.path();
+        fs::write(root.join("budget.md"), "# Budget 2024\nTravel budget and rent").unwrap();
+        fs::write(root.join("trip.md"), "# Trip\nBook the travel, check the budget").unwrap();
+        fs::write(root.join("misc.md"), "# Misc\nNothing to see").unwrap();
+
+        let cache = TempDir::new().unwrap();
+        let mut index = SearchIndex::load(cache.path(), root);
+        let delta = scan_changes(root, &index.snapshot(), &mut |_, _, _| true).unwrap();
+        assert_eq!(delta.changed(), 3);
+        index.apply(delta);
+
+        let hits = index.search("budget", 10);
+        assert_eq!(hits.iter().map(|h| h.path.as_str()).collect::<Vec<_>>(), vec!["budget.md", "trip.md"]);
+        assert_eq!(hits[0].title, "Budget 2024");
+        assert_eq!(index.search("travel bud", 10).len(), 2);
+        assert_eq!(index.search("rent trav", 10).len(), 1);
+        assert!(index.search("budget nothing", 10).is_empty());
+        assert!(index.search("  ", 10).is_empty());
+
+        assert_eq!(
+            snippet("---\ntitle: x\n---\n# Trip\nBook the travel", "trav").as_deref(),
+            Some("Book the travel")
+        );
+    }
+
+    #[test]
+    fn test_persisted_index_reconciles_deltas() {
+        let temp = TempDir::new().unwrap();
+        let root = temp.path();
+        fs::write(root.join("a.md"), "alpha beta").unwrap();
+        fs::write(root.join("b.md"), "gamma").unwrap();
+
+        let cache = TempDir::new().unwrap();
+        let tracker = SearchTracker::with_cache_dir(cache.path().to_path_buf());
+        let workspace = root.to_string_lossy().to_string();
+        assert_eq!(tracker.load(&workspace).unwrap(), 0);
+        assert_eq!(tracker.reconcile(&workspace, &mut |_, _, _| true).unwrap(), Some(2));
+
+        // A new session answers from disk before reconciling
+        fs::write(root.join("a.md"), "alpha delta epsilon").unwrap();
+        fs::remove_file(root.join("b.md")).unwrap();
+        let warm = SearchTracker::with_cache_dir(cache.path().to_path_buf());
+        assert_eq!(warm.load(&workspace).unwrap(), 2);
+        assert_eq!(warm.search(&workspace, "gamma", 10).unwrap().len(), 1);
+
+        let mut seen = 0;
+        assert_eq!(warm.reconcile(&workspace, &mut |_, _, _| { seen += 1; true }).unwrap(), Some(2));
+        assert_eq!(seen, 1);
+        assert!(warm.search(&workspace, "gamma", 10).unwrap().is_empty());
+        assert_eq!(warm.search(&workspace, "delta", 10).unwrap()[0].path, "a.md");
+        assert_eq!(warm.reconcile(&workspace, &mut |_, _, _| true).unwrap(), Some(0));
+
+        // The index lives in the cache directory, not the workspace
+        assert!(!root.join(SETTINGS_DIR).join(SEARCH_INDEX_FILE).exists());
+        assert_eq!(fs::read_dir(cache.path()).unwrap().count(), 1);
+    }
+}
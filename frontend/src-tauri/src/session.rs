@@ -0,0 +1,167 @@
+//! Session Persistence
+//!
+//! Remembers where the user left off in a workspace: open tabs with their
+//! cursor and scroll positions, the active tab, and the panel layout. The
+//! session is stored per workspace in `.mdreader/session.json` with
+//! workspace-relative paths, so it survives moving the workspace folder.
+//!
+//! The panel layout is opaque to the backend and stored as given.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use crate::workspace_settings::SETTINGS_DIR;
+
+/// File name of the session inside the settings directory
+pub const SESSION_FILE: &str = "session.json";
+
+/// Cursor location inside a document (0-based)
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct CursorPosition {
+    pub line: usize,
+    pub column: usize,
+}
+
+/// One open document
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SessionTab {
+    /// Document path (absolute when talking to the frontend, relative on disk)
+    pub path: String,
+    #[serde(default)]
+    pub cursor: Option<CursorPosition>,
+    /// Vertical scroll offset in pixels
+    #[serde(default)]
+    pub scroll_top: Option<f64>,
+    #[serde(default)]
+    pub pinned: bool,
+}
+
+/// Everything needed to restore the window after a relaunch
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Session {
+    #[serde(default)]
+    pub tabs: Vec<SessionTab>,
+    /// Path of the active tab
+    #[serde(default)]
+    pub active_tab: Option<String>,
+    /// Panel sizes and visibility, as defined by the frontend
+    #[serde(default)]
+    pub layout: Map<String, Value>,
+    #[serde(default)]
+    pub saved_at: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+impl Session {
+    /// Loads the session of a workspace with absolute tab paths
+    ///
+    /// Tabs whose file no longer exists are dropped; if the active tab was
+    /// one of them, the first remaining tab becomes active.
+    pub fn load(workspace_root: &Path) -> Result<Self, String> {
+        let path = session_path(workspace_root);
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+
+        let json = fs::read_to_string(&path)
+            .map_err(|e| format!("Failed to read session: {}", e))?;
+        let mut session: Session = serde_json::from_str(&json)
+            .map_err(|e| format!("Failed to parse session: {}", e))?;
+
+        let to_absolute = |relative: &str| workspace_root.join(relative).to_string_lossy().to_string();
+        session.tabs.retain(|tab| workspace_root.join(&tab.path).is_file());
+        for tab in &mut session.tabs {
+            tab.path = to_absolute(&tab.path);
+        }
+
+        session.active_tab = session
+            .active_tab
+            .map(|active| to_absolute(&active))
+            .filter(|active| session.tabs.iter().any(|t| &t.path == active))
+            .or_else(|| session.tabs.first().map(|t| t.path.clone()));
+
+        Ok(session)
+    }
+
+    /// Saves a session whose tab paths were already made workspace-relative
+    pub fn save(&self, workspace_root: &Path) -> Result<(), String> {
+        let path = session_path(workspace_root);
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)
+                .map_err(|e| format!("Failed to create settings directory: {}", e))?;
+        }
+
+        let json = serde_json::to_string_pretty(self)
+            .map_err(|e| format!("Failed to serialize session: {}", e))?;
+        fs::write(&path, json)
+            .map_err(|e| format!("Failed to save session: {}", e))
+    }
+}
+
+fn session_path(workspace_root: &Path) -> PathBuf {
+    workspace_root.join(SETTINGS_DIR).join(SESSION_FILE)
+}
+
+// ============================================================================
+// TESTS
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn tab(path: &str) -> SessionTab {
+        SessionTab {
+            path: path.to_string(),
+            cursor: Some(CursorPosition { line: 4, column: 2 }),
+            scroll_top: Some(120.5),
+            pinned: false,
+        }
+    }
+
+    #[test]
+    fn test_roundtrip_restores_absolute_paths() {
+        let workspace = TempDir::new().unwrap();
+        let root = workspace.path();
+        fs::create_dir_all(root.join("Projects")).unwrap();
+        fs::write(root.join("Projects/plan.md"), "# Plan").unwrap();
+
+        let mut layout = Map::new();
+        layout.insert("sidebar".to_string(), Value::from("collapsed"));
+        let session = Session {
+            tabs: vec![tab("Projects/plan.md")],
+            active_tab: Some("Projects/plan.md".to_string()),
+            layout: layout.clone(),
+            saved_at: None,
+        };
+        session.save(root).unwrap();
+
+        let loaded = Session::load(root).unwrap();
+        let expected = root.join("Projects/plan.md").to_string_lossy().to_string();
+        assert_eq!(loaded.tabs[0].path, expected);
+        assert_eq!(loaded.tabs[0].cursor, Some(CursorPosition { line: 4, column: 2 }));
+        assert_eq!(loaded.active_tab, Some(expected));
+        assert_eq!(loaded.layout, layout);
+    }
+
+    #[test]
+    fn test_missing_files_are_dropped() {
+        let workspace = TempDir::new().unwrap();
+        let root = workspace.path();
+        fs::write(root.join("kept.md"), "").unwrap();
+
+        Session {
+            tabs: vec![tab("gone.md"), tab("kept.md")],
+            active_tab: Some("gone.md".to_string()),
+            ..Default::default()
+        }
+        .save(root)
+        .unwrap();
+
+        let loaded = Session::load(root).unwrap();
+        assert_eq!(loaded.tabs.len(), 1);
+        assert_eq!(loaded.active_tab, Some(root.join("kept.md").to_string_lossy().to_string()));
+        assert_eq!(Session::load(&root.join("none")).unwrap(), Session::default());
+    }
+}
@@ -0,0 +1,225 @@
+//! Share Sheet Intake
+//!
+//! Text, links and images shared into the app from other apps (the Android
+//! share sheet, the iOS share extension) are queued on the native side and
+//! pulled by the backend at launch and whenever the app comes back to the
+//! foreground. Each item becomes a timestamped bullet of the workspace's
+//! inbox note (see `crate::capture`); shared images are stored as
+//! attachments of that note first and the bullet links to them.
+//!
+//! The native sides live in `gen/android/.../ShareIntakePlugin.kt` and
+//! `gen/apple/Sources/.../ShareIntakePlugin.swift`. Images are handed over
+//! as files the native side copied into the app's cache, which are removed
+//! once stored.
+
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use serde::{Deserialize, Serialize};
+use tauri::plugin::{Builder, TauriPlugin};
+use tauri::Runtime;
+use crate::capture::CaptureOptions;
+
+/// Workspace-relative note that shared items are appended to
+pub const INBOX_NOTE: &str = "Inbox.md";
+
+/// Package and class of the Android plugin
+#[cfg(target_os = "android")]
+const ANDROID_PLUGIN: (&str, &str) = ("com.mdreader.desktop", "ShareIntakePlugin");
+
+/// Something shared into the app
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum SharedItem {
+    /// Plain text (a selection, a note from another app)
+    Text {
+        text: String,
+        /// Subject line some apps send along (e-mail subject, page title)
+        #[serde(default)]
+        subject: Option<String>,
+    },
+    /// A link, usually from a browser
+    Url {
+        url: String,
+        #[serde(default)]
+        title: Option<String>,
+    },
+    /// An image, copied by the native side to `path`
+    Image {
+        path: String,
+        /// File name to store it under (defaults to the name of `path`)
+        #[serde(default)]
+        name: Option<String>,
+    },
+}
+
+impl SharedItem {
+    /// Text of the inbox entry; images need their link (`None` otherwise)
+    pub fn entry_text(&self, image_link: Option<&str>) -> Option<String> {
+        match self {
+            SharedItem::Text { text, subject } => {
+                let text = text.trim();
+                match subject.as_deref().map(str::trim).filter(|s| !s.is_empty() && !text.starts_with(*s)) {
+                    Some(subject) if !text.is_empty() => Some(format!("**{}**\n{}", subject, text)),
+                    Some(subject) => Some(format!("**{}**", subject)),
+                    None if !text.is_empty() => Some(text.to_string()),
+                    None => None,
+                }
+            }
+            SharedItem::Url { url, title } => {
+                let url = url.trim();
+                if url.is_empty() {
+                    return None;
+                }
+                Some(match title.as_deref().map(str::trim).filter(|t| !t.is_empty() && *t != url) {
+                    Some(title) => format!("[{}]({})", title.replace(']', "\\]"), url),
+                    None => format!("<{}>", url),
+                })
+            }
+            SharedItem::Image { .. } => image_link.map(str::to_string),
+        }
+    }
+
+    /// File name a shared image is stored under
+    pub fn image_name(&self) -> Option<String> {
+        let SharedItem::Image { path, name } = self else {
+            return None;
+        };
+        name.clone()
+            .filter(|n| !n.trim().is_empty())
+            .or_else(|| Path::new(path).file_name().map(|n| n.to_string_lossy().to_string()))
+            .or_else(|| Some("shared-image.png".to_string()))
+    }
+}
+
+/// How shared items are written into the inbox note
+pub fn capture_options() -> CaptureOptions {
+    CaptureOptions {
+        timestamp: true,
+        bullet: true,
+        ..CaptureOptions::default()
+    }
+}
+
+/// Native queue of shared items
+pub trait ShareSource: Send + Sync {
+    /// Removes and returns the items shared since the last call
+    fn take(&self) -> Result<Vec<SharedItem>, String>;
+}
+
+/// Shared items waiting to be pulled from the native side (internally
+/// synchronized); empty on desktop, where nothing installs a source
+#[derive(Clone, Default)]
+pub struct ShareInbox {
+    source: Arc<Mutex<Option<Arc<dyn ShareSource>>>>,
+}
+
+impl ShareInbox {
+    /// Creates an inbox without a native source
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Installs the native source (mobile)
+    #[cfg_attr(not(mobile), allow(dead_code))]
+    pub fn set_source(&self, source: Arc<dyn ShareSource>) {
+        if let Ok(mut current) = self.source.lock() {
+            *current = Some(source);
+        }
+    }
+
+    /// Removes and returns the pending shared items
+    pub fn take(&self) -> Result<Vec<SharedItem>, String> {
+        let source = self.source
+            .lock()
+            .map_err(|e| format!("Failed to lock share inbox: {}", e))?
+            .clone();
+        match source {
+            Some(source) => source.take(),
+            None => Ok(Vec::new()),
+        }
+    }
+}
+
+/// Plugin installing the native share queue into `AppState` (does nothing
+/// on desktop)
+pub fn plugin<R: Runtime>() -> TauriPlugin<R> {
+    Builder::new("share-intake")
+        .setup(|_app, _api| {
+            #[cfg(target_os = "android")]
+            {
+                use tauri::Manager;
+                let handle = _api.register_android_plugin(ANDROID_PLUGIN.0, ANDROID_PLUGIN.1)?;
+                _app.state::<crate::state::AppState>().shares.set_source(Arc::new(NativeShares { handle }));
+            }
+            #[cfg(target_os = "ios")]
+            {
+                use tauri::Manager;
+                let handle = _api.register_ios_plugin(init_plugin_share_intake)?;
+                _app.state::<crate::state::AppState>().shares.set_source(Arc::new(NativeShares { handle }));
+            }
+            Ok(())
+        })
+        .build()
+}
+
+#[cfg(target_os = "ios")]
+tauri::ios_plugin_binding!(init_plugin_share_intake);
+
+/// Queue of the mobile plugin (`takeShares` returns `{ items }`)
+#[cfg(mobile)]
+struct NativeShares<R: Runtime> {
+    handle: tauri::plugin::PluginHandle<R>,
+}
+
+#[cfg(mobile)]
+impl<R: Runtime> ShareSource for NativeShares<R> {
+    fn take(&self) -> Result<Vec<SharedItem>, String> {
+        #[derive(Deserialize)]
+        struct Pending {
+            items: Vec<SharedItem>,
+        }
+        self.handle
+            .run_mobile_plugin::<Pending>("takeShares", ())
+            .map(|pending| pending.items)
+            .map_err(|e| format!("Failed to read shared items: {}", e))
+    }
+}
+
+// ============================================================================
+// TESTS
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_entry_text() {
+        let text = SharedItem::Text { text: " buy milk \n".to_string(), subject: None };
+        assert_eq!(text.entry_text(None).as_deref(), Some("buy milk"));
+
+        let with_subject = SharedItem::Text { text: "body".to_string(), subject: Some("Re: plan".to_string()) };
+        assert_eq!(with_subject.entry_text(None).as_deref(), Some("**Re: plan**\nbody"));
+
+        let url = SharedItem::Url { url: "https://example.com".to_string(), title: Some("Example [1]".to_string()) };
+        assert_eq!(url.entry_text(None).as_deref(), Some("[Example [1\\]](https://example.com)"));
+        let bare = SharedItem::Url { url: "https://example.com".to_string(), title: None };
+        assert_eq!(bare.entry_text(None).as_deref(), Some("<https://example.com>"));
+
+        let image = SharedItem::Image { path: "/cache/shared/IMG_1.jpg".to_string(), name: None };
+        assert_eq!(image.entry_text(None), None);
+        assert_eq!(image.entry_text(Some("![](assets/IMG_1.jpg)")).as_deref(), Some("![](assets/IMG_1.jpg)"));
+        assert_eq!(image.image_name().as_deref(), Some("IMG_1.jpg"));
+    }
+
+    #[test]
+    fn test_items_from_native_json() {
+        let items: Vec<SharedItem> = serde_json::from_str(
+            r#"[{"kind":"url","url":"https://a.b"},{"kind":"image","path":"/c/x.png","name":"x.png"}]"#,
+        )
+        .unwrap();
+        assert_eq!(items[0], SharedItem::Url { url: "https://a.b".to_string(), title: None });
+        assert_eq!(items[1].image_name().as_deref(), Some("x.png"));
+        assert!(ShareInbox::new().take().unwrap().is_empty());
+    }
+}
@@ -0,0 +1,187 @@
+//! Snippet Library
+//!
+//! Reusable pieces of text (meeting-note scaffolds, callouts, sign-offs)
+//! kept per workspace in `.mdreader/snippets.json`, so they travel with the
+//! folder like the workspace settings:
+//!
+//! ```json
+//! { "snippets": { "2f1c…": { "name": "Meeting", "body": "## {{date}} {{topic}}\n- ", ... } } }
+//! ```
+//!
+//! Snippet bodies are templates (see `crate::template`) and are expanded
+//! when inserted.
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use serde::{Deserialize, Serialize};
+use crate::workspace_settings::SETTINGS_DIR;
+
+/// File name of the library inside the settings directory
+pub const SNIPPETS_FILE: &str = "snippets.json";
+
+/// A stored snippet
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Snippet {
+    pub id: String,
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+    /// Template text inserted by the snippet
+    pub body: String,
+    /// Last change (ms since the epoch)
+    #[serde(default)]
+    pub updated_ms: i64,
+}
+
+/// A snippet sent by the frontend; without `id` a new snippet is created
+#[derive(Debug, Clone, Deserialize)]
+pub struct SnippetInput {
+    #[serde(default)]
+    pub id: Option<String>,
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+    pub body: String,
+}
+
+/// Snippets of one workspace
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct SnippetLibrary {
+    /// Snippets keyed by id
+    #[serde(default)]
+    pub snippets: BTreeMap<String, Snippet>,
+}
+
+impl SnippetLibrary {
+    /// Loads the library of a workspace (empty if there is none yet)
+    pub fn load(workspace_root: &Path) -> Result<Self, String> {
+        let path = library_path(workspace_root);
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let json = fs::read_to_string(&path)
+            .map_err(|e| format!("Failed to read snippets: {}", e))?;
+        serde_json::from_str(&json)
+            .map_err(|e| format!("Failed to parse snippets: {}", e))
+    }
+
+    /// Writes the library to the workspace settings directory
+    pub fn save(&self, workspace_root: &Path) -> Result<(), String> {
+        let path = library_path(workspace_root);
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)
+                .map_err(|e| format!("Failed to create settings directory: {}", e))?;
+        }
+        let json = serde_json::to_string_pretty(self)
+            .map_err(|e| format!("Failed to serialize snippets: {}", e))?;
+        fs::write(&path, json)
+            .map_err(|e| format!("Failed to write snippets: {}", e))
+    }
+
+    /// Snippets sorted by name
+    pub fn list(&self) -> Vec<Snippet> {
+        let mut snippets: Vec<Snippet> = self.snippets.values().cloned().collect();
+        snippets.sort_by(|a, b| a.name.to_lowercase().cmp(&b.name.to_lowercase()).then_with(|| a.id.cmp(&b.id)));
+        snippets
+    }
+
+    /// Adds or replaces a snippet
+    ///
+    /// Names must be non-empty and unique (case-insensitively); an input
+    /// with an unknown `id` is refused rather than created.
+    pub fn upsert(&mut self, input: SnippetInput, now_ms: i64) -> Result<Snippet, String> {
+        let name = input.name.trim();
+        if name.is_empty() {
+            return Err("Snippet name cannot be empty".to_string());
+        }
+
+        let id = match input.id {
+            Some(id) if self.snippets.contains_key(&id) => id,
+            Some(id) => return Err(format!("Snippet not found: {}", id)),
+            None => uuid::Uuid::new_v4().to_string(),
+        };
+        if self.snippets.values().any(|s| s.id != id && s.name.eq_ignore_ascii_case(name)) {
+            return Err(format!("A snippet named '{}' already exists", name));
+        }
+
+        let snippet = Snippet {
+            id: id.clone(),
+            name: name.to_string(),
+            description: input.description.trim().to_string(),
+            body: input.body,
+            updated_ms: now_ms,
+        };
+        self.snippets.insert(id, snippet.clone());
+        Ok(snippet)
+    }
+
+    /// Removes a snippet
+    pub fn remove(&mut self, id: &str) -> Result<Snippet, String> {
+        self.snippets
+            .remove(id)
+            .ok_or_else(|| format!("Snippet not found: {}", id))
+    }
+
+    /// Looks up a snippet
+    pub fn get(&self, id: &str) -> Result<&Snippet, String> {
+        self.snippets
+            .get(id)
+            .ok_or_else(|| format!("Snippet not found: {}", id))
+    }
+}
+
+fn library_path(workspace_root: &Path) -> PathBuf {
+    workspace_root.join(SETTINGS_DIR).join(SNIPPETS_FILE)
+}
+
+// ============================================================================
+// TESTS
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn input(id: Option<&str>, name: &str, body: &str) -> SnippetInput {
+        SnippetInput {
+            id: id.map(String::from),
+            name: name.to_string(),
+            description: String::new(),
+            body: body.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_upsert_and_remove() {
+        let mut library = SnippetLibrary::default();
+        let meeting = library.upsert(input(None, " Meeting ", "## {{date}}"), 1).unwrap();
+        library.upsert(input(None, "callout", "> [!note]"), 2).unwrap();
+        assert_eq!(meeting.name, "Meeting");
+
+        // Names stay unique; updates keep the id
+        assert!(library.upsert(input(None, "meeting", ""), 3).is_err());
+        let updated = library.upsert(input(Some(&meeting.id), "Meeting", "## {{date}} {{topic}}"), 4).unwrap();
+        assert_eq!((updated.id.as_str(), updated.updated_ms), (meeting.id.as_str(), 4));
+        assert!(library.upsert(input(Some("missing"), "Other", ""), 5).is_err());
+
+        let names: Vec<String> = library.list().into_iter().map(|s| s.name).collect();
+        assert_eq!(names, vec!["callout", "Meeting"]);
+
+        library.remove(&meeting.id).unwrap();
+        assert_eq!(library.get(&meeting.id).unwrap_err(), format!("Snippet not found: {}", meeting.id));
+    }
+
+    #[test]
+    fn test_save_and_load_roundtrip() {
+        let temp = TempDir::new().unwrap();
+        assert_eq!(SnippetLibrary::load(temp.path()).unwrap(), SnippetLibrary::default());
+
+        let mut library = SnippetLibrary::default();
+        library.upsert(input(None, "Sign-off", "Cheers,\n{{author}}"), 7).unwrap();
+        library.save(temp.path()).unwrap();
+
+        assert_eq!(SnippetLibrary::load(temp.path()).unwrap(), library);
+    }
+}
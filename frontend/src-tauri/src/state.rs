@@ -3,12 +3,54 @@
 //! This module provides centralized state management for the Tauri application,
 //! including:
 //! - File watcher registry (preventing memory leaks)
+//! - Watchers of open documents
 //! - Workspace path tracking
+//! - Debounced save queue
+//! - Journal of saves made while the workspace was unreachable
+//! - Per-file write locks
+//! - Long-running job registry
+//! - Foreground/background lifecycle of the app
+//! - Incremental outlines of open documents
+//! - Frecency of opened/saved files
+//! - Activity feed of workspace changes
+//! - Metadata cache of workspace files
+//! - Memory budget of the in-memory caches
+//! - Storage backend of scoped (Android SAF) workspaces
+//! - Items shared into the app from the mobile share sheet
+//! - Open document windows
+//! - Audio recorder for voice memos
+//! - Command rate limits and in-flight guards
+//! - App lock (biometric/passcode unlock)
+//! - Export destinations granted by the save dialog
+//! - The open native dialog
 //! - Thread-safe state access
 
 use std::collections::HashMap;
+use std::path::Path;
 use std::sync::Mutex;
 use notify::RecommendedWatcher;
+use crate::activity::ActivityTracker;
+use crate::app_lock::AppLock;
+use crate::audio::AudioRecorder;
+use crate::command_guard::CommandGuard;
+use crate::dialogs::DialogRunner;
+use crate::export_policy::ExportGrants;
+use crate::favorites::FavoritesTracker;
+use crate::frecency::FrecencyTracker;
+use crate::jobs::JobRegistry;
+use crate::lifecycle::Lifecycle;
+use crate::memory_budget::{select_evictions, stamp, CacheEntry, CacheKind, MemoryBudget, MemoryUsage};
+use crate::metadata_cache::MetadataTracker;
+use crate::search_index::SearchTracker;
+use crate::offline_journal::OfflineJournal;
+use crate::open_file::OpenFileWatchers;
+use crate::outline::OutlineRegistry;
+use crate::plugin_runtime::PluginHost;
+use crate::save_queue::SaveQueue;
+use crate::share::ShareInbox;
+use crate::storage::StorageRouter;
+use crate::windows::WindowRegistry;
+use crate::write_locks::WriteLocks;
 
 /// Application state managed by Tauri
 /// 
@@ -38,11 +80,77 @@ pub struct AppState {
     /// original implementation. Watchers are properly dropped when removed.
     pub watchers: Mutex<HashMap<String, WatcherEntry>>,
     
+    /// Single-file watchers of documents open in an editor
+    pub open_files: OpenFileWatchers,
+    
     /// Current workspace root path
     /// 
     /// All file operations are validated against this path to prevent
     /// directory traversal attacks.
     workspace_path: Mutex<Option<String>>,
+    
+    /// Pending debounced saves (internally synchronized)
+    pub save_queue: SaveQueue,
+    
+    /// Per-file write locks serializing saves to the same path
+    pub write_locks: WriteLocks,
+    
+    /// Running long operations (imports, exports) with cancel flags
+    pub jobs: JobRegistry,
+    
+    /// Whether the app is suspended (mobile), shared with the jobs
+    pub lifecycle: Lifecycle,
+    
+    /// Incremental outline parsers of documents open in the mindmap view
+    pub outlines: OutlineRegistry,
+    
+    /// Open/save history of the current workspace for recent-file ranking
+    pub frecency: FrecencyTracker,
+    
+    /// Starred notes and pinned folders of the current workspace
+    pub favorites: FavoritesTracker,
+    
+    /// Recent changes, saves and imports of the current workspace
+    pub activity: ActivityTracker,
+    
+    /// Cached note and asset metadata of the current workspace
+    pub metadata: MetadataTracker,
+    
+    /// Full-text search index of the current workspace
+    pub search: SearchTracker,
+    
+    /// Memory budget of the outline, metadata and search caches
+    pub memory: MemoryBudget,
+    
+    /// Saves waiting for an unreachable workspace to come back
+    pub offline_journal: OfflineJournal,
+    
+    /// Runtime of the workspace plugins, with their compiled modules
+    pub plugins: PluginHost,
+    
+    /// Storage backends; content URI workspaces use the scoped one
+    pub storage: StorageRouter,
+    
+    /// Native queue of items shared into the app (mobile)
+    pub shares: ShareInbox,
+    
+    /// Notes open in their own native window
+    pub windows: WindowRegistry,
+    
+    /// Voice memo recording in progress, if any
+    pub recorder: AudioRecorder,
+    
+    /// Rate limits checked by the invoke handler, and in-flight keys
+    pub command_guard: CommandGuard,
+    
+    /// App lock checked by the invoke handler
+    pub app_lock: AppLock,
+    
+    /// Export destinations picked in the save dialog and not yet used
+    pub export_grants: ExportGrants,
+    
+    /// Native dialog being waited for, if any
+    pub dialogs: DialogRunner,
 }
 
 /// Entry in the watcher registry
@@ -67,9 +175,34 @@ impl Default for AppState {
 impl AppState {
     /// Creates a new AppState instance
     pub fn new() -> Self {
+        let write_locks = WriteLocks::new();
+        let lifecycle = Lifecycle::new();
+        
         Self {
             watchers: Mutex::new(HashMap::new()),
+            open_files: OpenFileWatchers::new(),
             workspace_path: Mutex::new(None),
+            save_queue: SaveQueue::with_write_locks(write_locks.clone()),
+            write_locks,
+            jobs: JobRegistry::with_lifecycle(lifecycle.clone()),
+            lifecycle,
+            outlines: OutlineRegistry::new(),
+            frecency: FrecencyTracker::new(),
+            favorites: FavoritesTracker::new(),
+            activity: ActivityTracker::new(),
+            metadata: MetadataTracker::new(),
+            search: SearchTracker::new(),
+            memory: MemoryBudget::new(),
+            offline_journal: OfflineJournal::new(),
+            plugins: PluginHost::new(),
+            storage: StorageRouter::new(),
+            shares: ShareInbox::new(),
+            windows: WindowRegistry::new(),
+            recorder: AudioRecorder::new(),
+            command_guard: CommandGuard::new(),
+            app_lock: AppLock::new(),
+            export_grants: ExportGrants::new(),
+            dialogs: DialogRunner::new(),
         }
     }
     
@@ -127,6 +260,46 @@ impl AppState {
         Ok(())
     }
     
+    // =========================================================================
+    // Memory Budget
+    // =========================================================================
+    
+    /// Gets the estimated memory held by each cache
+    pub fn memory_usage(&self) -> Result<MemoryUsage, String> {
+        Ok(MemoryUsage::from_entries(&self.cache_entries()?, self.memory.limit_bytes()))
+    }
+    
+    /// Evicts the least recently used cache entries while over the budget
+    /// 
+    /// # Returns
+    /// * `Ok(usize)` - Number of entries evicted
+    /// * `Err(String)` - If a mutex is poisoned
+    pub fn enforce_memory_budget(&self) -> Result<usize, String> {
+        let entries = self.cache_entries()?;
+        let mut evicted = 0;
+        
+        for entry in select_evictions(&entries, self.memory.limit_bytes(), stamp()) {
+            let dropped = match entry.cache {
+                CacheKind::Outlines => self.outlines.close(Path::new(&entry.key))?,
+                CacheKind::Metadata => self.metadata.unload(),
+                CacheKind::Search => self.search.unload(),
+            };
+            if dropped {
+                log::info!("🧹 Evicted {:?} cache entry {} (~{} KB)", entry.cache, entry.key, entry.bytes / 1024);
+                evicted += 1;
+            }
+        }
+        Ok(evicted)
+    }
+    
+    // Helper: Entries of every cache under the budget
+    fn cache_entries(&self) -> Result<Vec<CacheEntry>, String> {
+        let mut entries = self.outlines.cache_entries()?;
+        entries.extend(self.metadata.cache_entries());
+        entries.extend(self.search.cache_entries());
+        Ok(entries)
+    }
+    
     // =========================================================================
     // Watcher Registry Management
     // =========================================================================
@@ -0,0 +1,752 @@
+//! Storage Backends
+//!
+//! On mobile, folders outside the app's own sandbox can only be reached
+//! through the system's document picker, and the access it grants must be
+//! kept across launches. The core file commands go through the `Storage`
+//! trait so the frontend makes the same calls on every backend:
+//!
+//! - `LocalStorage` - plain filesystem paths (desktop, and app storage on mobile)
+//! - `SafStorage` - a document tree granted through the Storage Access
+//!   Framework (Android)
+//! - `BookmarkStorage` - folders granted in the Files app, such as iCloud
+//!   Drive or On My iPhone (iOS)
+//!
+//! ## Android
+//! A scoped workspace is the tree URI the picker returned, and paths inside
+//! it are the tree URI followed by `/`-separated display names:
+//!
+//! ```text
+//! content://com.android.externalstorage.documents/tree/primary%3ANotes/Projects/plan.md
+//! ```
+//!
+//! The tree grant is persisted (`takePersistableUriPermission`), so the
+//! workspace stays accessible after a restart until the user revokes it in
+//! the system settings; commands then fail with `Permission denied`.
+//! The Kotlin side lives in `gen/android/.../ScopedStoragePlugin.kt`.
+//!
+//! ## iOS
+//! Picked folders keep their filesystem paths, but access to them only lasts
+//! while the app runs. Each folder is saved as a security-scoped bookmark in
+//! `<config>/folder-bookmarks.json` and resolved again at launch (which also
+//! finds a folder that was moved or renamed). Reads and writes go through
+//! `NSFileCoordinator`, so a save never clobbers a version iCloud is syncing
+//! in, and reading a file iCloud evicted downloads it first. The Swift side
+//! lives in `gen/apple/Sources/.../ScopedStoragePlugin.swift`.
+
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use serde::{Deserialize, Serialize};
+use tauri::plugin::{Builder, TauriPlugin};
+use tauri::Runtime;
+use crate::file_meta::epoch_ms;
+use crate::safe_write;
+use crate::utils::validate_path_within_workspace;
+
+/// Scheme of Android content URIs
+pub const CONTENT_SCHEME: &str = "content://";
+
+/// Package and class of the Android plugin
+#[cfg(target_os = "android")]
+const ANDROID_PLUGIN: (&str, &str) = ("com.mdreader.desktop", "ScopedStoragePlugin");
+
+/// File name of the iOS folder bookmarks inside the config directory
+#[cfg_attr(not(target_os = "ios"), allow(dead_code))]
+pub const BOOKMARKS_FILE: &str = "folder-bookmarks.json";
+
+/// A file or folder as reported by a storage backend
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StorageEntry {
+    pub name: String,
+    pub path: String,
+    pub is_directory: bool,
+    pub size: u64,
+    /// Modification time (ms since the epoch, 0 if unknown)
+    pub modified_ms: i64,
+}
+
+/// File access behind the core file commands
+///
+/// Errors are plain messages; commands add what they were doing.
+pub trait Storage: Send + Sync {
+    /// Checks if the workspace lives in this backend
+    fn holds(&self, workspace: &str) -> bool;
+
+    /// Validates a path sent by the frontend against the workspace root
+    fn resolve(&self, path: &str, workspace: &str) -> Result<PathBuf, String>;
+
+    /// Lists the entries of a folder
+    fn list(&self, dir: &Path) -> Result<Vec<StorageEntry>, String>;
+
+    /// Describes a file or folder (`None` if it does not exist)
+    fn stat(&self, path: &Path) -> Result<Option<StorageEntry>, String>;
+
+    fn read_to_string(&self, path: &Path) -> Result<String, String>;
+
+    /// Replaces (or creates) a file; errors are complete messages
+    /// (see `crate::safe_write`)
+    fn write(&self, path: &Path, contents: &str) -> Result<(), String>;
+
+    /// Creates a file that must not exist yet; returns false if it does
+    fn create_new(&self, path: &Path, contents: &str) -> Result<bool, String>;
+
+    /// Deletes a file, or a folder (with its contents if `recursive`)
+    fn remove(&self, path: &Path, recursive: bool) -> Result<(), String>;
+
+    /// Renames or moves a file or folder
+    fn rename(&self, from: &Path, to: &Path) -> Result<(), String>;
+
+    fn copy(&self, from: &Path, to: &Path) -> Result<(), String>;
+
+    /// Lets the user pick a new workspace root, if the backend has a picker
+    fn pick_root(&self) -> Result<Option<String>, String> {
+        Err("Folder picker not available for this storage".to_string())
+    }
+}
+
+/// Storage on the local filesystem
+#[derive(Debug, Clone, Copy, Default)]
+pub struct LocalStorage;
+
+impl Storage for LocalStorage {
+    fn holds(&self, workspace: &str) -> bool {
+        !is_document_uri(workspace)
+    }
+
+    fn resolve(&self, path: &str, workspace: &str) -> Result<PathBuf, String> {
+        validate_path_within_workspace(path, workspace).map_err(|e| e.to_string())
+    }
+
+    fn list(&self, dir: &Path) -> Result<Vec<StorageEntry>, String> {
+        let mut entries = Vec::new();
+        for entry in fs::read_dir(dir).map_err(|e| e.to_string())? {
+            let entry = entry.map_err(|e| e.to_string())?;
+            if let Some(entry) = self.stat(&entry.path())? {
+                entries.push(entry);
+            }
+        }
+        Ok(entries)
+    }
+
+    fn stat(&self, path: &Path) -> Result<Option<StorageEntry>, String> {
+        let metadata = match fs::metadata(path) {
+            Ok(metadata) => metadata,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(e.to_string()),
+        };
+        Ok(Some(StorageEntry {
+            name: path.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default(),
+            path: path.to_string_lossy().to_string(),
+            is_directory: metadata.is_dir(),
+            size: metadata.len(),
+            modified_ms: metadata.modified().map(epoch_ms).unwrap_or(0),
+        }))
+    }
+
+    fn read_to_string(&self, path: &Path) -> Result<String, String> {
+        fs::read_to_string(path).map_err(|e| e.to_string())
+    }
+
+    fn write(&self, path: &Path, contents: &str) -> Result<(), String> {
+        Ok(safe_write::write_file(path, contents)?)
+    }
+
+    fn create_new(&self, path: &Path, contents: &str) -> Result<bool, String> {
+        // create_new() fails if the file exists - no TOCTOU window
+        let mut file = match fs::OpenOptions::new().write(true).create_new(true).open(path) {
+            Ok(file) => file,
+            Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists => return Ok(false),
+            Err(e) => return Err(e.to_string()),
+        };
+        file.write_all(contents.as_bytes()).map_err(|e| e.to_string())?;
+        Ok(true)
+    }
+
+    fn remove(&self, path: &Path, recursive: bool) -> Result<(), String> {
+        let result = if !path.is_dir() {
+            fs::remove_file(path)
+        } else if recursive {
+            fs::remove_dir_all(path)
+        } else {
+            fs::remove_dir(path)
+        };
+        result.map_err(|e| e.to_string())
+    }
+
+    fn rename(&self, from: &Path, to: &Path) -> Result<(), String> {
+        fs::rename(from, to).map_err(|e| e.to_string())
+    }
+
+    fn copy(&self, from: &Path, to: &Path) -> Result<(), String> {
+        fs::copy(from, to).map(|_| ()).map_err(|e| e.to_string())
+    }
+}
+
+/// Checks if a workspace or path is a content URI (scoped storage)
+pub fn is_document_uri(path: &str) -> bool {
+    path.starts_with(CONTENT_SCHEME)
+}
+
+/// Splits a scoped path into its tree URI and the `/`-separated names below it
+///
+/// `content://<authority>/tree/<id>` is the tree; the id is percent-encoded,
+/// so it contains no `/`.
+#[cfg_attr(not(target_os = "android"), allow(dead_code))]
+pub fn split_document_path(path: &str) -> Option<(&str, &str)> {
+    let rest = path.strip_prefix(CONTENT_SCHEME)?;
+    let mut parts = rest.splitn(4, '/');
+    let authority = parts.next().filter(|a| !a.is_empty())?;
+    if parts.next()? != "tree" {
+        return None;
+    }
+    let id = parts.next().filter(|id| !id.is_empty())?;
+    let tree_len = CONTENT_SCHEME.len() + authority.len() + "/tree/".len() + id.len();
+    Some((&path[..tree_len], parts.next().unwrap_or("")))
+}
+
+/// Validates a scoped path against the workspace tree
+///
+/// # Returns
+/// * `Ok(String)` - The names below the tree, `/`-separated (empty for the root)
+/// * `Err(String)` - If the path is in another tree or has `.`, `..` or empty names
+#[cfg_attr(not(target_os = "android"), allow(dead_code))]
+pub fn scoped_relative(path: &str, workspace: &str) -> Result<String, String> {
+    let (tree, relative) = split_document_path(path)
+        .ok_or_else(|| format!("Invalid document path: {}", path))?;
+    let (workspace_tree, _) = split_document_path(workspace)
+        .ok_or_else(|| format!("Invalid workspace: {}", workspace))?;
+    if tree != workspace_tree {
+        return Err(format!("Path outside workspace: {}", path));
+    }
+
+    let names: Vec<&str> = relative.trim_end_matches('/').split('/').filter(|_| !relative.is_empty()).collect();
+    if names.iter().any(|name| name.is_empty() || *name == "." || *name == ".." || name.contains(['\\', '\0'])) {
+        return Err(format!("Invalid path: {}", path));
+    }
+    Ok(names.join("/"))
+}
+
+/// A folder granted in the iOS Files app, as a security-scoped bookmark
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FolderBookmark {
+    /// Path of the folder when it was last resolved
+    pub path: String,
+    /// Bookmark data (base64)
+    pub bookmark: String,
+}
+
+/// Folder bookmarks stored in `<config>/folder-bookmarks.json`
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct BookmarkStore {
+    #[serde(default)]
+    pub folders: Vec<FolderBookmark>,
+}
+
+#[cfg_attr(not(target_os = "ios"), allow(dead_code))]
+impl BookmarkStore {
+    /// Loads the bookmarks (empty if none are saved)
+    pub fn load(config_dir: &Path) -> Result<Self, String> {
+        let path = config_dir.join(BOOKMARKS_FILE);
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let json = fs::read_to_string(&path)
+            .map_err(|e| format!("Failed to read folder bookmarks: {}", e))?;
+        serde_json::from_str(&json)
+            .map_err(|e| format!("Failed to parse folder bookmarks: {}", e))
+    }
+
+    pub fn save(&self, config_dir: &Path) -> Result<(), String> {
+        let json = serde_json::to_string_pretty(self)
+            .map_err(|e| format!("Failed to serialize folder bookmarks: {}", e))?;
+        fs::write(config_dir.join(BOOKMARKS_FILE), json)
+            .map_err(|e| format!("Failed to save folder bookmarks: {}", e))
+    }
+
+    /// Adds a bookmark, replacing the one of the same folder
+    pub fn upsert(&mut self, folder: FolderBookmark) {
+        self.folders.retain(|existing| existing.path != folder.path);
+        self.folders.push(folder);
+    }
+
+    /// Bookmarked folder containing `path`, if any
+    pub fn root_of(&self, path: &str) -> Option<&FolderBookmark> {
+        self.folders.iter().find(|folder| Path::new(path).starts_with(&folder.path))
+    }
+}
+
+/// Picks the storage backend of each workspace (internally synchronized)
+#[derive(Default)]
+pub struct StorageRouter {
+    scoped: Mutex<Option<Arc<dyn Storage>>>,
+}
+
+impl StorageRouter {
+    /// Creates a router with only local storage
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Installs the platform's backend for folders granted by the user
+    #[cfg_attr(not(any(target_os = "android", target_os = "ios")), allow(dead_code))]
+    pub fn set_scoped(&self, storage: Arc<dyn Storage>) {
+        if let Ok(mut scoped) = self.scoped.lock() {
+            *scoped = Some(storage);
+        }
+    }
+
+    /// Returns the backend holding a workspace
+    pub fn for_workspace(&self, workspace: &str) -> Result<Arc<dyn Storage>, String> {
+        if let Some(scoped) = self.scoped()?.filter(|scoped| scoped.holds(workspace)) {
+            return Ok(scoped);
+        }
+        if is_document_uri(workspace) {
+            return Err("Scoped storage is not available on this platform".to_string());
+        }
+        Ok(Arc::new(LocalStorage))
+    }
+
+    /// Lets the user grant a folder; `None` if they cancelled
+    pub fn pick_scoped_root(&self) -> Result<Option<String>, String> {
+        match self.scoped()? {
+            Some(storage) => storage.pick_root(),
+            None => Err("Scoped storage is not available on this platform".to_string()),
+        }
+    }
+
+    fn scoped(&self) -> Result<Option<Arc<dyn Storage>>, String> {
+        self.scoped
+            .lock()
+            .map(|scoped| scoped.clone())
+            .map_err(|e| format!("Failed to lock storage: {}", e))
+    }
+}
+
+/// Plugin installing the mobile scoped storage backend into `AppState`
+/// (does nothing on desktop)
+pub fn plugin<R: Runtime>() -> TauriPlugin<R> {
+    Builder::new("scoped-storage")
+        .setup(|_app, _api| {
+            #[cfg(target_os = "android")]
+            {
+                use tauri::Manager;
+                let handle = _api.register_android_plugin(ANDROID_PLUGIN.0, ANDROID_PLUGIN.1)?;
+                _app.state::<crate::state::AppState>().storage.set_scoped(Arc::new(SafStorage::new(handle)));
+                log::info!("📱 Scoped storage (SAF) enabled");
+            }
+            #[cfg(target_os = "ios")]
+            {
+                use tauri::Manager;
+                let handle = _api.register_ios_plugin(init_plugin_scoped_storage)?;
+                let config_dir = crate::commands::workspace::get_config_dir()?;
+                let storage = BookmarkStorage::new(handle, config_dir);
+                storage.restore();
+                _app.state::<crate::state::AppState>().storage.set_scoped(Arc::new(storage));
+                log::info!("📱 Scoped storage (security-scoped bookmarks) enabled");
+            }
+            Ok(())
+        })
+        .build()
+}
+
+/// Android: documents of a tree granted through SAF
+#[cfg(target_os = "android")]
+mod saf {
+    use super::*;
+
+    /// Storage in a document tree granted through SAF
+    pub struct SafStorage<R: Runtime> {
+        handle: tauri::plugin::PluginHandle<R>,
+    }
+
+    #[derive(Serialize)]
+    struct DocumentArgs<'a> {
+        tree: &'a str,
+        path: &'a str,
+    }
+
+    #[derive(Serialize)]
+    #[serde(rename_all = "camelCase")]
+    struct WriteArgs<'a> {
+        tree: &'a str,
+        path: &'a str,
+        contents: &'a str,
+        create_new: bool,
+    }
+
+    #[derive(Serialize)]
+    struct MoveArgs<'a> {
+        tree: &'a str,
+        from: &'a str,
+        to: &'a str,
+    }
+
+    #[derive(Serialize)]
+    struct RemoveArgs<'a> {
+        tree: &'a str,
+        path: &'a str,
+        recursive: bool,
+    }
+
+    #[derive(Deserialize)]
+    struct Entries {
+        entries: Vec<StorageEntry>,
+    }
+
+    #[derive(Deserialize)]
+    struct Stat {
+        entry: Option<StorageEntry>,
+    }
+
+    #[derive(Deserialize)]
+    struct Contents {
+        contents: String,
+    }
+
+    #[derive(Deserialize)]
+    struct Created {
+        created: bool,
+    }
+
+    #[derive(Deserialize)]
+    struct Picked {
+        uri: Option<String>,
+    }
+
+    #[derive(Deserialize)]
+    struct Done {}
+
+    impl<R: Runtime> SafStorage<R> {
+        pub fn new(handle: tauri::plugin::PluginHandle<R>) -> Self {
+            Self { handle }
+        }
+
+        fn call<T: serde::de::DeserializeOwned>(&self, command: &str, args: impl Serialize) -> Result<T, String> {
+            self.handle
+                .run_mobile_plugin(command, args)
+                .map_err(|e| e.to_string())
+        }
+    }
+
+    // Helper: Tree URI and names of a scoped path
+    fn split(path: &Path) -> Result<(&str, &str), String> {
+        path.to_str()
+            .and_then(split_document_path)
+            .ok_or_else(|| format!("Invalid document path: {}", path.display()))
+    }
+
+    impl<R: Runtime> Storage for SafStorage<R> {
+        fn holds(&self, workspace: &str) -> bool {
+            is_document_uri(workspace)
+        }
+
+        fn resolve(&self, path: &str, workspace: &str) -> Result<PathBuf, String> {
+            let relative = scoped_relative(path, workspace)?;
+            let (tree, _) = split_document_path(workspace).unwrap_or((workspace, ""));
+            Ok(PathBuf::from(if relative.is_empty() { tree.to_string() } else { format!("{}/{}", tree, relative) }))
+        }
+
+        fn list(&self, dir: &Path) -> Result<Vec<StorageEntry>, String> {
+            let (tree, path) = split(dir)?;
+            Ok(self.call::<Entries>("list", DocumentArgs { tree, path })?.entries)
+        }
+
+        fn stat(&self, path: &Path) -> Result<Option<StorageEntry>, String> {
+            let (tree, path) = split(path)?;
+            Ok(self.call::<Stat>("stat", DocumentArgs { tree, path })?.entry)
+        }
+
+        fn read_to_string(&self, path: &Path) -> Result<String, String> {
+            let (tree, path) = split(path)?;
+            Ok(self.call::<Contents>("read", DocumentArgs { tree, path })?.contents)
+        }
+
+        fn write(&self, path: &Path, contents: &str) -> Result<(), String> {
+            let (tree, path) = split(path)?;
+            self.call::<Created>("write", WriteArgs { tree, path, contents, create_new: false })
+                .map(|_| ())
+                .map_err(|e| format!("Failed to save file: {}", e))
+        }
+
+        fn create_new(&self, path: &Path, contents: &str) -> Result<bool, String> {
+            let (tree, path) = split(path)?;
+            Ok(self.call::<Created>("write", WriteArgs { tree, path, contents, create_new: true })?.created)
+        }
+
+        fn remove(&self, path: &Path, recursive: bool) -> Result<(), String> {
+            let (tree, path) = split(path)?;
+            self.call::<Done>("remove", RemoveArgs { tree, path, recursive }).map(|_| ())
+        }
+
+        fn rename(&self, from: &Path, to: &Path) -> Result<(), String> {
+            let ((tree, from), (_, to)) = (split(from)?, split(to)?);
+            self.call::<Done>("rename", MoveArgs { tree, from, to }).map(|_| ())
+        }
+
+        fn copy(&self, from: &Path, to: &Path) -> Result<(), String> {
+            let ((tree, from), (_, to)) = (split(from)?, split(to)?);
+            self.call::<Done>("copy", MoveArgs { tree, from, to }).map(|_| ())
+        }
+
+        fn pick_root(&self) -> Result<Option<String>, String> {
+            Ok(self.call::<Picked>("pickTree", ())?.uri)
+        }
+    }
+}
+
+#[cfg(target_os = "android")]
+use saf::SafStorage;
+
+#[cfg(target_os = "ios")]
+tauri::ios_plugin_binding!(init_plugin_scoped_storage);
+
+/// iOS: folders granted in the Files app, kept as security-scoped bookmarks
+#[cfg(target_os = "ios")]
+mod bookmarks {
+    use super::*;
+
+    /// Storage in folders granted through the iOS document picker
+    pub struct BookmarkStorage<R: Runtime> {
+        handle: tauri::plugin::PluginHandle<R>,
+        config_dir: PathBuf,
+        /// Folders accessible in this launch
+        granted: Mutex<BookmarkStore>,
+    }
+
+    #[derive(Serialize)]
+    struct PathArgs<'a> {
+        path: &'a str,
+    }
+
+    #[derive(Serialize)]
+    #[serde(rename_all = "camelCase")]
+    struct WriteArgs<'a> {
+        path: &'a str,
+        contents: &'a str,
+        create_new: bool,
+    }
+
+    #[derive(Serialize)]
+    struct RemoveArgs<'a> {
+        path: &'a str,
+        recursive: bool,
+    }
+
+    #[derive(Serialize)]
+    struct MoveArgs<'a> {
+        from: &'a str,
+        to: &'a str,
+    }
+
+    #[derive(Serialize)]
+    struct BookmarkArgs<'a> {
+        bookmark: &'a str,
+    }
+
+    #[derive(Deserialize)]
+    struct Contents {
+        contents: String,
+    }
+
+    #[derive(Deserialize)]
+    struct Created {
+        created: bool,
+    }
+
+    #[derive(Deserialize)]
+    struct Picked {
+        path: Option<String>,
+        bookmark: Option<String>,
+    }
+
+    #[derive(Deserialize)]
+    struct Done {}
+
+    impl<R: Runtime> BookmarkStorage<R> {
+        pub fn new(handle: tauri::plugin::PluginHandle<R>, config_dir: PathBuf) -> Self {
+            Self { handle, config_dir, granted: Mutex::new(BookmarkStore::default()) }
+        }
+
+        /// Resolves the saved bookmarks, regaining access to their folders
+        ///
+        /// Stale bookmarks (the folder moved) are refreshed; folders whose
+        /// access was revoked or that were deleted are forgotten.
+        pub fn restore(&self) {
+            let store = match BookmarkStore::load(&self.config_dir) {
+                Ok(store) => store,
+                Err(e) => {
+                    log::warn!("⚠️ Folder bookmarks not restored: {}", e);
+                    return;
+                }
+            };
+            let mut restored = BookmarkStore::default();
+            for folder in &store.folders {
+                match self.call::<FolderBookmark>("resolveBookmark", BookmarkArgs { bookmark: &folder.bookmark }) {
+                    Ok(resolved) => {
+                        if resolved.path != folder.path {
+                            log::info!("📁 Bookmarked folder moved: {} → {}", folder.path, resolved.path);
+                        }
+                        restored.upsert(resolved);
+                    }
+                    Err(e) => log::warn!("⚠️ Lost access to {}: {}", folder.path, e),
+                }
+            }
+            if restored != store {
+                if let Err(e) = restored.save(&self.config_dir) {
+                    log::warn!("⚠️ {}", e);
+                }
+            }
+            if let Ok(mut granted) = self.granted.lock() {
+                *granted = restored;
+            }
+        }
+
+        fn call<T: serde::de::DeserializeOwned>(&self, command: &str, args: impl Serialize) -> Result<T, String> {
+            self.handle
+                .run_mobile_plugin(command, args)
+                .map_err(|e| e.to_string())
+        }
+    }
+
+    // Helper: Path as the plugin takes it
+    fn text(path: &Path) -> &str {
+        path.to_str().unwrap_or_default()
+    }
+
+    impl<R: Runtime> Storage for BookmarkStorage<R> {
+        fn holds(&self, workspace: &str) -> bool {
+            self.granted
+                .lock()
+                .map(|granted| granted.root_of(workspace).is_some())
+                .unwrap_or(false)
+        }
+
+        fn resolve(&self, path: &str, workspace: &str) -> Result<PathBuf, String> {
+            LocalStorage.resolve(path, workspace)
+        }
+
+        fn list(&self, dir: &Path) -> Result<Vec<StorageEntry>, String> {
+            LocalStorage.list(dir)
+        }
+
+        fn stat(&self, path: &Path) -> Result<Option<StorageEntry>, String> {
+            LocalStorage.stat(path)
+        }
+
+        fn read_to_string(&self, path: &Path) -> Result<String, String> {
+            Ok(self.call::<Contents>("read", PathArgs { path: text(path) })?.contents)
+        }
+
+        fn write(&self, path: &Path, contents: &str) -> Result<(), String> {
+            self.call::<Created>("write", WriteArgs { path: text(path), contents, create_new: false })
+                .map(|_| ())
+                .map_err(|e| format!("Failed to save file: {}", e))
+        }
+
+        fn create_new(&self, path: &Path, contents: &str) -> Result<bool, String> {
+            Ok(self.call::<Created>("write", WriteArgs { path: text(path), contents, create_new: true })?.created)
+        }
+
+        fn remove(&self, path: &Path, recursive: bool) -> Result<(), String> {
+            self.call::<Done>("remove", RemoveArgs { path: text(path), recursive }).map(|_| ())
+        }
+
+        fn rename(&self, from: &Path, to: &Path) -> Result<(), String> {
+            self.call::<Done>("move", MoveArgs { from: text(from), to: text(to) }).map(|_| ())
+        }
+
+        fn copy(&self, from: &Path, to: &Path) -> Result<(), String> {
+            self.call::<Done>("copy", MoveArgs { from: text(from), to: text(to) }).map(|_| ())
+        }
+
+        fn pick_root(&self) -> Result<Option<String>, String> {
+            let picked = self.call::<Picked>("pickFolder", ())?;
+            let (Some(path), Some(bookmark)) = (picked.path, picked.bookmark) else {
+                return Ok(None);
+            };
+
+            let folder = FolderBookmark { path: path.clone(), bookmark };
+            let mut store = BookmarkStore::load(&self.config_dir)?;
+            store.upsert(folder.clone());
+            store.save(&self.config_dir)?;
+            if let Ok(mut granted) = self.granted.lock() {
+                granted.upsert(folder);
+            }
+            log::info!("📁 Bookmarked folder: {}", path);
+            Ok(Some(path))
+        }
+    }
+}
+
+#[cfg(target_os = "ios")]
+use bookmarks::BookmarkStorage;
+
+// ============================================================================
+// TESTS
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    const TREE: &str = "content://com.android.externalstorage.documents/tree/primary%3ANotes";
+
+    #[test]
+    fn test_scoped_paths() {
+        assert_eq!(split_document_path(&format!("{}/Projects/plan.md", TREE)), Some((TREE, "Projects/plan.md")));
+        assert_eq!(split_document_path(TREE), Some((TREE, "")));
+        assert_eq!(split_document_path("/home/me/notes"), None);
+        assert_eq!(split_document_path("content://authority/document/1"), None);
+
+        assert_eq!(scoped_relative(&format!("{}/Projects/plan.md", TREE), TREE).unwrap(), "Projects/plan.md");
+        assert_eq!(scoped_relative(&format!("{}/", TREE), TREE).unwrap(), "");
+        assert!(scoped_relative(&format!("{}/Projects/../../x.md", TREE), TREE).is_err());
+        assert!(scoped_relative(&format!("{}//x.md", TREE), TREE).is_err());
+        let other = "content://com.android.externalstorage.documents/tree/primary%3AOther/x.md";
+        assert!(scoped_relative(other, TREE).unwrap_err().starts_with("Path outside workspace"));
+    }
+
+    #[test]
+    fn test_local_storage() {
+        let workspace = TempDir::new().unwrap();
+        let storage = StorageRouter::new().for_workspace(&workspace.path().to_string_lossy()).unwrap();
+        let note = workspace.path().join("plan.md");
+
+        assert_eq!(storage.create_new(&note, "# Plan"), Ok(true));
+        assert_eq!(storage.create_new(&note, "# Other"), Ok(false));
+        assert_eq!(storage.read_to_string(&note).unwrap(), "# Plan");
+        storage.write(&note, "# Plan\n\nv2").unwrap();
+
+        let entry = storage.stat(&note).unwrap().unwrap();
+        assert_eq!((entry.name.as_str(), entry.is_directory, entry.size), ("plan.md", false, 10));
+        assert_eq!(storage.list(workspace.path()).unwrap().len(), 1);
+
+        let moved = workspace.path().join("done.md");
+        storage.rename(&note, &moved).unwrap();
+        storage.remove(&moved, false).unwrap();
+        assert_eq!(storage.stat(&moved).unwrap(), None);
+
+        assert!(StorageRouter::new().for_workspace(TREE).is_err());
+    }
+
+    #[test]
+    fn test_bookmark_store() {
+        let config = TempDir::new().unwrap();
+        let mut store = BookmarkStore::load(config.path()).unwrap();
+        assert!(store.folders.is_empty());
+
+        let notes = |bookmark: &str| FolderBookmark { path: "/private/var/mobile/Notes".into(), bookmark: bookmark.into() };
+        store.upsert(notes("b1"));
+        store.upsert(FolderBookmark { path: "/private/var/mobile/Work".into(), bookmark: "w1".into() });
+        store.upsert(notes("b2"));
+        store.save(config.path()).unwrap();
+
+        let loaded = BookmarkStore::load(config.path()).unwrap();
+        assert_eq!(loaded.folders.len(), 2);
+        assert_eq!(loaded.root_of("/private/var/mobile/Notes/plan.md"), Some(&notes("b2")));
+        assert_eq!(loaded.root_of("/private/var/mobile/NotesArchive/plan.md"), None);
+    }
+}
@@ -0,0 +1,231 @@
+//! Sync Conflict Copies
+//!
+//! File sync tools keep both versions when a note changes on two devices
+//! at once, saving the second one next to the original under a new name:
+//!
+//! - Dropbox: `note (conflicted copy 2024-05-01).md` or
+//!   `note (Anna's conflicted copy 2024-05-01).md`; Nextcloud and ownCloud
+//!   use the same pattern with a time (`... 2024-05-01 093455).md`)
+//! - Syncthing: `note.sync-conflict-20240501-093455-ABCDEF1.md`
+//!
+//! This module recognizes these names, pairs each copy with its original
+//! and works out the content the original gets when a conflict is resolved.
+
+use std::path::{Path, PathBuf};
+use serde::{Deserialize, Serialize};
+use crate::merge::{self, MergeStrategy};
+
+/// Sync tool that created a conflict copy
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SyncTool {
+    /// Dropbox, Nextcloud or ownCloud (`(conflicted copy ...)`)
+    Dropbox,
+    Syncthing,
+}
+
+/// What a conflict copy's file name says about it
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConflictName {
+    /// File name of the original
+    pub original: String,
+    pub tool: SyncTool,
+    /// Date of the conflict (`YYYY-MM-DD`), if the name has one
+    pub date: Option<String>,
+    /// Device or user the copy came from, if the name has one
+    pub device: Option<String>,
+}
+
+/// A conflict copy found in the workspace
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SyncConflict {
+    /// The conflict copy
+    pub path: String,
+    /// The note it is a copy of
+    pub original: String,
+    /// False if the original was deleted or renamed since
+    pub original_exists: bool,
+    pub tool: SyncTool,
+    pub date: Option<String>,
+    pub device: Option<String>,
+}
+
+/// How a conflict is resolved; the copy is removed afterwards
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "action", rename_all = "snake_case")]
+pub enum ConflictResolution {
+    /// Keep the original as it is
+    KeepOriginal,
+    /// Replace the original with the copy
+    KeepCopy,
+    /// Merge the copy's sections into the original (see `crate::merge`)
+    Merge {
+        #[serde(default)]
+        strategy: MergeStrategy,
+    },
+    /// Replace the original with content merged by hand
+    Replace { content: String },
+}
+
+/// Recognizes the file name of a conflict copy
+pub fn parse_conflict_name(file_name: &str) -> Option<ConflictName> {
+    parse_syncthing(file_name).or_else(|| parse_dropbox(file_name))
+}
+
+// Helper: `stem.sync-conflict-YYYYMMDD-HHMMSS-DEVICE[.ext]`
+fn parse_syncthing(file_name: &str) -> Option<ConflictName> {
+    let (stem, rest) = file_name.split_once(".sync-conflict-")?;
+    let (tag, extension) = match rest.split_once('.') {
+        Some((tag, extension)) => (tag, Some(extension)),
+        None => (rest, None),
+    };
+    let mut parts = tag.splitn(3, '-');
+    let date = parts.next().filter(|d| d.len() == 8 && d.bytes().all(|b| b.is_ascii_digit()))?;
+    parts.next()?;
+    let device = parts.next().filter(|d| !d.is_empty()).map(str::to_string);
+
+    let original = match extension {
+        Some(extension) => format!("{}.{}", stem, extension),
+        None => stem.to_string(),
+    };
+    (!stem.is_empty()).then(|| ConflictName {
+        original,
+        tool: SyncTool::Syncthing,
+        date: Some(format!("{}-{}-{}", &date[..4], &date[4..6], &date[6..])),
+        device,
+    })
+}
+
+// Helper: `stem ([device's ]conflicted copy DATE[ TIME][ (n)])[.ext]`
+fn parse_dropbox(file_name: &str) -> Option<ConflictName> {
+    let (name, extension) = match file_name.rsplit_once('.') {
+        Some((name, extension)) if name.ends_with(')') => (name, Some(extension)),
+        _ => (file_name, None),
+    };
+    let marker = name.find("conflicted copy")?;
+    let open = name[..marker].rfind(" (")?;
+    let inner = name[open + 2..].strip_suffix(')')?;
+    let stem = &name[..open];
+
+    let (device, details) = inner.split_once("conflicted copy")?;
+    let device = device.trim().trim_end_matches("'s").trim_end_matches("’s");
+    let date = details.trim().get(..10).filter(|d| is_date(d)).map(str::to_string);
+
+    let original = match extension {
+        Some(extension) => format!("{}.{}", stem, extension),
+        None => stem.to_string(),
+    };
+    (!stem.is_empty()).then(|| ConflictName {
+        original,
+        tool: SyncTool::Dropbox,
+        date,
+        device: (!device.is_empty()).then(|| device.to_string()),
+    })
+}
+
+// Helper: `YYYY-MM-DD`
+fn is_date(text: &str) -> bool {
+    text.len() == 10
+        && text.bytes().enumerate().all(|(i, b)| if i == 4 || i == 7 { b == b'-' } else { b.is_ascii_digit() })
+}
+
+/// Finds the conflict copies among `files` and pairs them with their originals
+pub fn find_conflicts(files: &[PathBuf]) -> Vec<SyncConflict> {
+    files
+        .iter()
+        .filter_map(|path| {
+            let name = parse_conflict_name(&path.file_name()?.to_string_lossy())?;
+            let original = path.with_file_name(&name.original);
+            Some(SyncConflict {
+                path: path.to_string_lossy().to_string(),
+                original_exists: original.is_file(),
+                original: original.to_string_lossy().to_string(),
+                tool: name.tool,
+                date: name.date,
+                device: name.device,
+            })
+        })
+        .collect()
+}
+
+/// Path of the original of a conflict copy
+pub fn original_path(copy: &Path) -> Option<PathBuf> {
+    let name = parse_conflict_name(&copy.file_name()?.to_string_lossy())?;
+    Some(copy.with_file_name(name.original))
+}
+
+/// Content the original has after resolving the conflict
+pub fn resolved_content(original: &str, copy: &str, resolution: &ConflictResolution) -> String {
+    match resolution {
+        ConflictResolution::KeepOriginal => original.to_string(),
+        ConflictResolution::KeepCopy => copy.to_string(),
+        ConflictResolution::Merge { strategy } => merge::merge(original, copy, *strategy).content,
+        ConflictResolution::Replace { content } => content.clone(),
+    }
+}
+
+// ============================================================================
+// TESTS
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_parse_conflict_names() {
+        let dropbox = parse_conflict_name("Plan (Anna's conflicted copy 2024-05-01).md").unwrap();
+        assert_eq!(dropbox.original, "Plan.md");
+        assert_eq!(dropbox.tool, SyncTool::Dropbox);
+        assert_eq!(dropbox.date.as_deref(), Some("2024-05-01"));
+        assert_eq!(dropbox.device.as_deref(), Some("Anna"));
+
+        let nextcloud = parse_conflict_name("notes (v2) (conflicted copy 2024-05-01 093455).md").unwrap();
+        assert_eq!(nextcloud.original, "notes (v2).md");
+        assert_eq!(nextcloud.device, None);
+
+        let numbered = parse_conflict_name("a (conflicted copy 2024-05-01 (1)).md").unwrap();
+        assert_eq!((numbered.original.as_str(), numbered.date.as_deref()), ("a.md", Some("2024-05-01")));
+
+        let syncthing = parse_conflict_name("Plan.sync-conflict-20240501-093455-ABCDEF1.md").unwrap();
+        assert_eq!(syncthing.original, "Plan.md");
+        assert_eq!(syncthing.tool, SyncTool::Syncthing);
+        assert_eq!(syncthing.date.as_deref(), Some("2024-05-01"));
+        assert_eq!(syncthing.device.as_deref(), Some("ABCDEF1"));
+
+        assert_eq!(parse_conflict_name("Plan.md"), None);
+        assert_eq!(parse_conflict_name("Plan (copy).md"), None);
+        assert_eq!(parse_conflict_name("(conflicted copy 2024-05-01).md"), None);
+        assert_eq!(parse_conflict_name("x.sync-conflict-notadate-1-A.md"), None);
+    }
+
+    #[test]
+    fn test_find_and_resolve() {
+        let workspace = TempDir::new().unwrap();
+        let root = workspace.path();
+        fs::write(root.join("Plan.md"), "# Plan\n\nA\n").unwrap();
+        let files = vec![
+            root.join("Plan.md"),
+            root.join("Plan (conflicted copy 2024-05-01).md"),
+            root.join("Gone.sync-conflict-20240501-093455-ABCDEF1.md"),
+        ];
+
+        let conflicts = find_conflicts(&files);
+        assert_eq!(conflicts.len(), 2);
+        assert_eq!(conflicts[0].original, root.join("Plan.md").to_string_lossy());
+        assert!(conflicts[0].original_exists);
+        assert!(!conflicts[1].original_exists);
+        assert_eq!(original_path(&files[2]), Some(root.join("Gone.md")));
+
+        let (original, copy) = ("# Plan\n\nA\n", "# Plan\n\nA\n\n## Ideas\n\nB\n");
+        assert_eq!(resolved_content(original, copy, &ConflictResolution::KeepOriginal), original);
+        assert_eq!(resolved_content(original, copy, &ConflictResolution::KeepCopy), copy);
+        let merged = resolved_content(original, copy, &ConflictResolution::Merge { strategy: MergeStrategy::Interleave });
+        assert!(merged.contains("## Ideas") && merged.matches("A\n").count() == 1);
+
+        let resolution: ConflictResolution = serde_json::from_str(r#"{"action":"merge"}"#).unwrap();
+        assert_eq!(resolution, ConflictResolution::Merge { strategy: MergeStrategy::Append });
+    }
+}
@@ -0,0 +1,374 @@
+//! Markdown Tables
+//!
+//! Finds the pipe tables of a note and edits them as rows and cells, so
+//! the table editor never slices markdown text itself:
+//!
+//! - Add or remove a row or column
+//! - Sort the rows by a column (numbers by value, text case-insensitively)
+//! - Realign: pad the cells so the columns line up
+//!
+//! An edited table is written back aligned, with its column alignments
+//! (`:--`, `:-:`, `--:`) and indentation kept. Pipes in cells are escaped.
+
+use std::cmp::Ordering;
+use serde::{Deserialize, Serialize};
+use crate::frontmatter;
+use crate::render::{is_table_start, split_row};
+
+/// Narrowest column, so the delimiter row stays a valid `---`
+const MIN_WIDTH: usize = 3;
+
+/// Alignment of a column, from the delimiter row
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Alignment {
+    None,
+    Left,
+    Center,
+    Right,
+}
+
+/// A pipe table of a note
+#[derive(Debug, Clone, PartialEq)]
+pub struct MarkdownTable {
+    /// Line of the header row (1-based)
+    pub line: usize,
+    /// Whitespace before the header row (tables in lists)
+    pub indent: String,
+    pub header: Vec<String>,
+    pub alignments: Vec<Alignment>,
+    pub rows: Vec<Vec<String>>,
+}
+
+/// A structured edit of a table; rows and columns are 0-based, rows not
+/// counting the header
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "op", rename_all = "snake_case")]
+pub enum TableOp {
+    /// Insert an empty row before `at`, or append one
+    AddRow {
+        #[serde(default)]
+        at: Option<usize>,
+    },
+    RemoveRow { row: usize },
+    /// Insert a column before `at`, or append one
+    AddColumn {
+        #[serde(default)]
+        at: Option<usize>,
+        #[serde(default)]
+        name: String,
+    },
+    RemoveColumn { column: usize },
+    /// Sort the rows; empty cells go last either way
+    SortByColumn {
+        column: usize,
+        #[serde(default)]
+        descending: bool,
+    },
+    /// Only line up the columns
+    Realign,
+}
+
+/// Pipe tables of a note, in order (frontmatter and fenced code excluded)
+pub fn markdown_tables(content: &str) -> Vec<MarkdownTable> {
+    let (_, body) = frontmatter::split(content);
+    let first_line = content[..content.len() - body.len()].lines().count() + 1;
+    let lines: Vec<String> = body.lines().map(str::to_string).collect();
+
+    let mut tables = Vec::new();
+    let mut fence: Option<&str> = None;
+    let mut i = 0;
+    while i < lines.len() {
+        let trimmed = lines[i].trim_start();
+        if let Some(marker) = ["```", "~~~"].into_iter().find(|m| trimmed.starts_with(m)) {
+            fence = match fence {
+                Some(open) if open == marker => None,
+                None => Some(marker),
+                other => other,
+            };
+            i += 1;
+            continue;
+        }
+        if fence.is_some() || !is_table_start(&lines, i) {
+            i += 1;
+            continue;
+        }
+
+        let end = lines[i + 2..]
+            .iter()
+            .position(|l| l.trim().is_empty() || !l.contains('|'))
+            .map_or(lines.len(), |k| i + 2 + k);
+        tables.push(MarkdownTable {
+            line: first_line + i,
+            indent: lines[i][..lines[i].len() - trimmed.len()].to_string(),
+            header: split_row(&lines[i]),
+            alignments: split_row(&lines[i + 1]).iter().map(|cell| alignment(cell)).collect(),
+            rows: lines[i + 2..end].iter().map(|line| split_row(line)).collect(),
+        });
+        i = end;
+    }
+    tables
+}
+
+/// Applies `op` to table `table_index` (0-based) of a note
+///
+/// # Returns
+/// * `Ok(String)` - The note with the table rewritten
+/// * `Err(String)` - If there is no such table, row or column
+pub fn table_ops(content: &str, table_index: usize, op: &TableOp) -> Result<String, String> {
+    let tables = markdown_tables(content);
+    let mut table = tables
+        .get(table_index)
+        .cloned()
+        .ok_or_else(|| format!("Table {} not found ({} tables in the note)", table_index + 1, tables.len()))?;
+    let start = table.line - 1;
+    let end = start + 2 + table.rows.len();
+    table.apply(op)?;
+
+    let crlf = content.contains("\r\n");
+    let mut lines: Vec<&str> = content.split('\n').collect();
+    let rendered = table.to_markdown();
+    let replacement: Vec<String> = rendered
+        .lines()
+        .map(|line| if crlf { format!("{}\r", line) } else { line.to_string() })
+        .collect();
+    lines.splice(start..end, replacement.iter().map(String::as_str));
+    Ok(lines.join("\n"))
+}
+
+impl MarkdownTable {
+    /// Number of columns (the header's, or the widest row's)
+    pub fn width(&self) -> usize {
+        self.rows.iter().map(Vec::len).chain(std::iter::once(self.header.len())).max().unwrap_or(0)
+    }
+
+    /// Applies a structured edit
+    pub fn apply(&mut self, op: &TableOp) -> Result<(), String> {
+        self.normalize();
+        let width = self.width();
+        let row_count = self.rows.len();
+        let check_row = |row: usize| {
+            (row < row_count).then_some(()).ok_or_else(|| format!("Row {} not found ({} rows)", row + 1, row_count))
+        };
+        let check_column = |column: usize| {
+            (column < width).then_some(()).ok_or_else(|| format!("Column {} not found ({} columns)", column + 1, width))
+        };
+
+        match op {
+            TableOp::AddRow { at } => {
+                let at = at.unwrap_or(row_count);
+                if at > row_count {
+                    check_row(at)?;
+                }
+                self.rows.insert(at, vec![String::new(); width]);
+            }
+            TableOp::RemoveRow { row } => {
+                check_row(*row)?;
+                self.rows.remove(*row);
+            }
+            TableOp::AddColumn { at, name } => {
+                let at = at.unwrap_or(width);
+                if at > width {
+                    check_column(at)?;
+                }
+                self.header.insert(at, name.trim().to_string());
+                self.alignments.insert(at, Alignment::None);
+                for row in &mut self.rows {
+                    row.insert(at, String::new());
+                }
+            }
+            TableOp::RemoveColumn { column } => {
+                check_column(*column)?;
+                if width == 1 {
+                    return Err("Cannot remove the only column of a table".to_string());
+                }
+                self.header.remove(*column);
+                self.alignments.remove(*column);
+                for row in &mut self.rows {
+                    row.remove(*column);
+                }
+            }
+            TableOp::SortByColumn { column, descending } => {
+                check_column(*column)?;
+                // Stable, so equal cells keep their order
+                self.rows.sort_by(|a, b| {
+                    let (a, b) = (&a[*column], &b[*column]);
+                    match (a.is_empty(), b.is_empty()) {
+                        (true, true) => Ordering::Equal,
+                        (true, false) => Ordering::Greater,
+                        (false, true) => Ordering::Less,
+                        (false, false) if *descending => compare_cells(b, a),
+                        (false, false) => compare_cells(a, b),
+                    }
+                });
+            }
+            TableOp::Realign => {}
+        }
+        Ok(())
+    }
+
+    /// The table as aligned markdown (without trailing newline)
+    pub fn to_markdown(&self) -> String {
+        let mut table = self.clone();
+        table.normalize();
+        let escape = |rows: &[String]| -> Vec<String> { rows.iter().map(|cell| escape_cell(cell)).collect() };
+        let header = escape(&table.header);
+        let rows: Vec<Vec<String>> = table.rows.iter().map(|row| escape(row)).collect();
+
+        let widths: Vec<usize> = (0..header.len())
+            .map(|i| {
+                rows.iter()
+                    .map(|row| row[i].chars().count())
+                    .chain(std::iter::once(header[i].chars().count()))
+                    .max()
+                    .unwrap_or(0)
+                    .max(MIN_WIDTH)
+            })
+            .collect();
+
+        let format_row = |cells: &[String]| -> String {
+            let padded: Vec<String> = cells
+                .iter()
+                .enumerate()
+                .map(|(i, cell)| {
+                    let pad = widths[i] - cell.chars().count();
+                    match table.alignments[i] {
+                        Alignment::Right => format!("{}{}", " ".repeat(pad), cell),
+                        Alignment::Center => format!("{}{}{}", " ".repeat(pad / 2), cell, " ".repeat(pad - pad / 2)),
+                        Alignment::None | Alignment::Left => format!("{}{}", cell, " ".repeat(pad)),
+                    }
+                })
+                .collect();
+            format!("{}| {} |", table.indent, padded.join(" | "))
+        };
+
+        let delimiter: Vec<String> = widths
+            .iter()
+            .zip(&table.alignments)
+            .map(|(&w, alignment)| match alignment {
+                Alignment::None => "-".repeat(w),
+                Alignment::Left => format!(":{}", "-".repeat(w - 1)),
+                Alignment::Center => format!(":{}:", "-".repeat(w - 2)),
+                Alignment::Right => format!("{}:", "-".repeat(w - 1)),
+            })
+            .collect();
+
+        let mut lines = vec![format_row(&header), format!("{}| {} |", table.indent, delimiter.join(" | "))];
+        lines.extend(rows.iter().map(|row| format_row(row)));
+        lines.join("\n")
+    }
+
+    // Helper: Pads the header, alignments and rows to the same width
+    fn normalize(&mut self) {
+        let width = self.width();
+        self.header.resize(width, String::new());
+        self.alignments.resize(width, Alignment::None);
+        for row in &mut self.rows {
+            row.resize(width, String::new());
+        }
+    }
+}
+
+/// Value of a cell: digits with an optional sign, decimal point, thousands
+/// separators, currency or percent sign and emphasis
+pub fn parse_number(cell: &str) -> Option<f64> {
+    let cleaned: String = cell
+        .trim()
+        .trim_matches(['*', '_', '`'])
+        .chars()
+        .filter(|c| !matches!(c, ',' | ' ' | '\u{a0}' | '$' | '€' | '£' | '¥' | '%'))
+        .collect();
+    if cleaned.is_empty() || !cleaned.chars().any(|c| c.is_ascii_digit()) {
+        return None;
+    }
+    cleaned.parse::<f64>().ok().filter(|v| v.is_finite())
+}
+
+fn alignment(delimiter: &str) -> Alignment {
+    match (delimiter.starts_with(':'), delimiter.ends_with(':')) {
+        (true, true) => Alignment::Center,
+        (true, false) => Alignment::Left,
+        (false, true) => Alignment::Right,
+        (false, false) => Alignment::None,
+    }
+}
+
+// Helper: Numbers before text, numbers by value, text case-insensitively
+fn compare_cells(a: &str, b: &str) -> Ordering {
+    match (parse_number(a), parse_number(b)) {
+        (Some(x), Some(y)) => x.total_cmp(&y),
+        (Some(_), None) => Ordering::Less,
+        (None, Some(_)) => Ordering::Greater,
+        (None, None) => a.to_lowercase().cmp(&b.to_lowercase()).then_with(|| a.cmp(b)),
+    }
+}
+
+fn escape_cell(cell: &str) -> String {
+    cell.trim().replace('|', "\\|")
+}
+
+// ============================================================================
+// TESTS
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const NOTE: &str = "---\ntitle: Sales\n---\n# Sales\n\n```\n| not | a table |\n|---|---|\n```\n\n\
+                        | Quarter | Revenue | Notes |\n|:--|--:|:-:|\n\
+                        | Q3 | $1,200 | a \\| b |\n| **Q1** | 900 | |\n| q2 | | x |\n\nAfter\n";
+
+    #[test]
+    fn test_markdown_tables_and_numbers() {
+        let tables = markdown_tables(NOTE);
+        assert_eq!(tables.len(), 1);
+        assert_eq!(tables[0].line, 11);
+        assert_eq!(tables[0].header, ["Quarter", "Revenue", "Notes"]);
+        assert_eq!(tables[0].alignments, [Alignment::Left, Alignment::Right, Alignment::Center]);
+        assert_eq!(tables[0].rows[0], ["Q3", "$1,200", "a | b"]);
+        assert_eq!(tables[0].rows.len(), 3);
+
+        assert_eq!(parse_number("$1,200"), Some(1200.0));
+        assert_eq!(parse_number("**-3.5**"), Some(-3.5));
+        assert_eq!(parse_number("12.5%"), Some(12.5));
+        assert_eq!(parse_number("n/a"), None);
+        assert_eq!(parse_number("-"), None);
+    }
+
+    #[test]
+    fn test_table_ops() {
+        let sorted = table_ops(NOTE, 0, &TableOp::SortByColumn { column: 1, descending: false }).unwrap();
+        let expected = "\
+| Quarter | Revenue | Notes  |
+| :------ | ------: | :----: |
+| **Q1**  |     900 |        |
+| Q3      |  $1,200 | a \\| b |
+| q2      |         |   x    |";
+        assert!(sorted.contains(&format!("\n\n{}\n\nAfter\n", expected)), "{}", sorted);
+        assert!(sorted.starts_with("---\ntitle: Sales\n---\n# Sales\n\n```\n| not | a table |"));
+
+        let by_name = table_ops(NOTE, 0, &TableOp::SortByColumn { column: 0, descending: true }).unwrap();
+        let names: Vec<String> = markdown_tables(&by_name)[0].rows.iter().map(|r| r[0].clone()).collect();
+        assert_eq!(names, ["Q3", "q2", "**Q1**"]);
+
+        let edited = [
+            TableOp::AddColumn { at: Some(1), name: "Owner".to_string() },
+            TableOp::RemoveRow { row: 0 },
+            TableOp::AddRow { at: None },
+            TableOp::RemoveColumn { column: 3 },
+        ]
+        .iter()
+        .try_fold(NOTE.replace('\n', "\r\n"), |note, op| table_ops(&note, 0, op))
+        .unwrap();
+        let table = &markdown_tables(&edited)[0];
+        assert_eq!(table.header, ["Quarter", "Owner", "Revenue"]);
+        assert_eq!(table.rows, [["**Q1**", "", "900"], ["q2", "", ""], ["", "", ""]]);
+        assert!(edited.contains("| Quarter | Owner | Revenue |\r\n| :------ | ----- | ------: |\r\n"));
+
+        assert!(table_ops(NOTE, 0, &TableOp::RemoveRow { row: 3 }).unwrap_err().contains("Row 4"));
+        assert!(table_ops(NOTE, 1, &TableOp::Realign).is_err());
+        let op: TableOp = serde_json::from_str(r#"{"op":"sort_by_column","column":2}"#).unwrap();
+        assert_eq!(op, TableOp::SortByColumn { column: 2, descending: false });
+    }
+}
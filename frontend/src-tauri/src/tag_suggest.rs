@@ -0,0 +1,211 @@
+//! Tag Suggestions
+//!
+//! Proposes tags for a note from its content. Words are weighted by TF-IDF
+//! against the rest of the vault, so words the note uses a lot but other
+//! notes rarely use rank highest:
+//!
+//! ```text
+//! score(term) = tf(term, note) * (ln((notes + 1) / (notes_with_term + 1)) + 1)
+//! ```
+//!
+//! Tags already used elsewhere in the vault are preferred over new words
+//! (their score is doubled), so suggestions converge on the existing
+//! vocabulary. A nested tag such as `projects/alpha` matches the word
+//! `alpha`. Confidence is the score relative to the best candidate.
+//!
+//! The same weights give a text similarity between notes
+//! (`cosine_similarity`), used by the related-notes panel.
+
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use serde::Serialize;
+
+/// Number of suggestions returned when the caller does not say
+pub const DEFAULT_SUGGESTION_LIMIT: usize = 10;
+
+/// Words shorter than this are never suggested
+const MIN_TERM_LEN: usize = 3;
+
+/// Score multiplier for tags the vault already uses
+const EXISTING_TAG_BOOST: f64 = 2.0;
+
+/// Common English words that make poor tags
+const STOP_WORDS: &[&str] = &[
+    "about", "after", "again", "all", "also", "and", "any", "are", "because", "been", "before",
+    "being", "between", "both", "but", "can", "could", "did", "does", "doing", "done", "down",
+    "each", "even", "few", "for", "from", "further", "get", "had", "has", "have", "having", "her",
+    "here", "hers", "him", "his", "how", "into", "its", "just", "like", "make", "more", "most",
+    "much", "must", "need", "not", "now", "off", "once", "one", "only", "other", "our", "out",
+    "over", "own", "same", "she", "should", "some", "such", "than", "that", "the", "their",
+    "them", "then", "there", "these", "they", "this", "those", "through", "too", "two", "under",
+    "until", "use", "used", "very", "was", "way", "were", "what", "when", "where", "which",
+    "while", "who", "why", "will", "with", "would", "yes", "yet", "you", "your",
+];
+
+/// A proposed tag
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct TagSuggestion {
+    pub tag: String,
+    /// Score relative to the best suggestion (0-1)
+    pub confidence: f64,
+    /// The vault already uses this tag
+    pub existing: bool,
+}
+
+/// Document frequencies of the vault's words
+#[derive(Debug, Clone, Default)]
+pub struct Corpus {
+    /// Number of notes
+    pub notes: usize,
+    /// Number of notes containing each word
+    pub document_frequency: HashMap<String, usize>,
+}
+
+impl Corpus {
+    /// Adds a note body to the corpus
+    pub fn add(&mut self, body: &str) {
+        self.notes += 1;
+        let terms: BTreeSet<String> = tokenize(body).collect();
+        for term in terms {
+            *self.document_frequency.entry(term).or_insert(0) += 1;
+        }
+    }
+
+    fn idf(&self, term: &str) -> f64 {
+        let df = self.document_frequency.get(term).copied().unwrap_or(0);
+        ((self.notes as f64 + 1.0) / (df as f64 + 1.0)).ln() + 1.0
+    }
+
+    /// TF-IDF weights of the words of a body
+    pub fn vector(&self, body: &str) -> HashMap<String, f64> {
+        let mut vector: HashMap<String, f64> = HashMap::new();
+        let mut total = 0;
+        for term in tokenize(body) {
+            *vector.entry(term).or_insert(0.0) += 1.0;
+            total += 1;
+        }
+        for (term, weight) in vector.iter_mut() {
+            *weight = *weight / total as f64 * self.idf(term);
+        }
+        vector
+    }
+}
+
+/// Cosine similarity of two TF-IDF vectors (0 when either is empty)
+pub fn cosine_similarity(a: &HashMap<String, f64>, b: &HashMap<String, f64>) -> f64 {
+    let dot: f64 = a.iter().filter_map(|(term, x)| b.get(term).map(|y| x * y)).sum();
+    let norm = |v: &HashMap<String, f64>| v.values().map(|x| x * x).sum::<f64>().sqrt();
+    let norms = norm(a) * norm(b);
+    if norms == 0.0 {
+        0.0
+    } else {
+        dot / norms
+    }
+}
+
+/// Lower-cased words of a body, outside code and without stop words
+pub fn tokenize(body: &str) -> impl Iterator<Item = String> + '_ {
+    let mut in_fence = false;
+    body.lines()
+        .filter(move |line| {
+            if line.trim_start().starts_with("```") {
+                in_fence = !in_fence;
+                return false;
+            }
+            !in_fence
+        })
+        .flat_map(|line| line.split(|c: char| !(c.is_alphanumeric() || c == '-')))
+        .map(|word| word.trim_matches('-').to_lowercase())
+        .filter(|word| {
+            word.chars().count() >= MIN_TERM_LEN
+                && !word.chars().all(|c| c.is_ascii_digit())
+                && !STOP_WORDS.contains(&word.as_str())
+        })
+}
+
+/// Ranks tag candidates for a note body
+///
+/// `vault_tags` are the tags used anywhere in the vault; tags in
+/// `current_tags` (the note's own) are never suggested.
+pub fn suggest_tags(
+    body: &str,
+    corpus: &Corpus,
+    vault_tags: &BTreeSet<String>,
+    current_tags: &[String],
+    limit: usize,
+) -> Vec<TagSuggestion> {
+    let mut counts: BTreeMap<String, usize> = BTreeMap::new();
+    let mut total = 0;
+    for term in tokenize(body) {
+        *counts.entry(term).or_insert(0) += 1;
+        total += 1;
+    }
+    if total == 0 {
+        return Vec::new();
+    }
+    let score = |term: &str| counts.get(term).map(|&n| n as f64 / total as f64 * corpus.idf(term));
+    let is_current = |tag: &str| current_tags.iter().any(|t| t.eq_ignore_ascii_case(tag));
+
+    let mut candidates: Vec<(String, f64, bool)> = Vec::new();
+    for tag in vault_tags.iter().filter(|tag| !is_current(tag)) {
+        let word = tag.rsplit('/').next().unwrap_or(tag).to_lowercase();
+        if let Some(score) = score(&word) {
+            candidates.push((tag.clone(), score * EXISTING_TAG_BOOST, true));
+        }
+    }
+    for term in counts.keys() {
+        let taken = candidates.iter().any(|(tag, _, _)| tag.rsplit('/').next().unwrap_or(tag).eq_ignore_ascii_case(term));
+        if !taken && !is_current(term) {
+            candidates.push((term.clone(), score(term).unwrap_or(0.0), false));
+        }
+    }
+
+    candidates.sort_by(|a, b| b.1.total_cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+    candidates.truncate(limit);
+    let best = candidates.first().map_or(1.0, |c| c.1);
+
+    candidates
+        .into_iter()
+        .map(|(tag, score, existing)| TagSuggestion {
+            tag,
+            confidence: (score / best * 100.0).round() / 100.0,
+            existing,
+        })
+        .collect()
+}
+
+// ============================================================================
+// TESTS
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tokenize_skips_code_and_stop_words() {
+        let body = "The Rust compiler's borrow-checker, in 2024.\n```\nlet code = 1;\n```\nRust again";
+        let words: Vec<String> = tokenize(body).collect();
+        assert_eq!(words, vec!["rust", "compiler", "borrow-checker", "rust"]);
+    }
+
+    #[test]
+    fn test_suggestions_prefer_rare_words_and_vault_tags() {
+        let mut corpus = Corpus::default();
+        corpus.add("Meeting notes about the garden");
+        corpus.add("Meeting notes about the budget");
+        corpus.add("Sourdough recipe: flour, water, sourdough starter");
+
+        let vault_tags: BTreeSet<String> = ["cooking/sourdough".to_string(), "meeting".to_string()].into();
+        let body = "Sourdough recipe: flour, water, sourdough starter";
+        let suggestions = suggest_tags(body, &corpus, &vault_tags, &["meeting".to_string()], 3);
+
+        assert_eq!(suggestions[0].tag, "cooking/sourdough");
+        assert!(suggestions[0].existing);
+        assert_eq!(suggestions[0].confidence, 1.0);
+        assert!(suggestions.iter().all(|s| s.tag != "sourdough" && s.tag != "meeting"));
+        assert_eq!(suggestions.len(), 3);
+        assert!(!suggestions[1].existing && suggestions[1].confidence < 1.0);
+
+        assert!(suggest_tags("", &corpus, &vault_tags, &[], 3).is_empty());
+    }
+}
@@ -0,0 +1,162 @@
+//! Template Variables
+//!
+//! A small template engine shared by every feature that fills in text:
+//! new-file templates, capture templates and snippets. Placeholders are
+//! written `{{name}}` or `{{name:argument}}`:
+//!
+//! | Placeholder | Value |
+//! |-------------|-------|
+//! | `{{date}}`, `{{date:FMT}}` | Current date, `%Y-%m-%d` or the chrono format `FMT` |
+//! | `{{time}}`, `{{time:FMT}}` | Current time, `%H:%M` or the chrono format `FMT` |
+//! | `{{title}}` | Title of the note being created |
+//! | `{{uuid}}` | A new random UUID (a different one per occurrence) |
+//! | `{{clipboard}}` | Clipboard text passed in by the frontend |
+//! | `{{name}}` | A caller or workspace variable (`template_variables` setting) |
+//!
+//! Variables cannot shadow the built-ins; caller variables take precedence
+//! over workspace variables. Unknown placeholders, and placeholders with an
+//! invalid date format, are left as written.
+
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use chrono::NaiveDateTime;
+use serde::Deserialize;
+
+/// Placeholders provided by the engine itself
+pub const BUILTIN_PLACEHOLDERS: &[&str] = &["date", "time", "title", "uuid", "clipboard"];
+
+/// Whether `name` can be used as a variable name
+pub fn is_variable_name(name: &str) -> bool {
+    !name.is_empty() && name.chars().all(|c| c.is_alphanumeric() || c == '_' || c == '-')
+}
+
+/// Values available to placeholders
+#[derive(Debug, Clone)]
+pub struct TemplateContext {
+    /// Local time used by `{{date}}` and `{{time}}`
+    pub now: NaiveDateTime,
+    pub title: String,
+    pub clipboard: Option<String>,
+    /// Caller and workspace variables, merged
+    pub variables: BTreeMap<String, String>,
+}
+
+/// Template values sent by the frontend
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct TemplateInput {
+    #[serde(default)]
+    pub title: Option<String>,
+    #[serde(default)]
+    pub clipboard: Option<String>,
+    #[serde(default)]
+    pub variables: BTreeMap<String, String>,
+}
+
+impl TemplateContext {
+    /// Context from frontend input, layered over the workspace variables
+    pub fn from_input(now: NaiveDateTime, input: TemplateInput, workspace_variables: &BTreeMap<String, String>) -> Self {
+        let mut variables = workspace_variables.clone();
+        variables.extend(input.variables);
+        Self {
+            now,
+            title: input.title.unwrap_or_default(),
+            clipboard: input.clipboard,
+            variables,
+        }
+    }
+
+    /// Value of a placeholder, or `None` if it is unknown
+    fn resolve(&self, name: &str, argument: Option<&str>) -> Option<String> {
+        match (name, argument) {
+            ("date", format) => format_time(self.now, format.unwrap_or("%Y-%m-%d")),
+            ("time", format) => format_time(self.now, format.unwrap_or("%H:%M")),
+            ("title", None) => Some(self.title.clone()),
+            ("uuid", None) => Some(uuid::Uuid::new_v4().to_string()),
+            ("clipboard", None) => Some(self.clipboard.clone().unwrap_or_default()),
+            (name, None) => self.variables.get(name).cloned(),
+            _ => None,
+        }
+    }
+}
+
+/// Fills the placeholders of `text`
+pub fn render_template(text: &str, context: &TemplateContext) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut rest = text;
+
+    while let Some(open) = rest.find("{{") {
+        out.push_str(&rest[..open]);
+        let after = &rest[open + 2..];
+        let Some(close) = after.find("}}") else {
+            rest = &rest[open..];
+            break;
+        };
+
+        let placeholder = &after[..close];
+        let (name, argument) = match placeholder.split_once(':') {
+            Some((name, argument)) => (name.trim(), Some(argument)),
+            None => (placeholder.trim(), None),
+        };
+        match context.resolve(name, argument) {
+            Some(value) => out.push_str(&value),
+            None => out.push_str(&rest[open..open + 2 + close + 2]),
+        }
+        rest = &after[close + 2..];
+    }
+
+    out.push_str(rest);
+    out
+}
+
+// Helper: Formats a time, or `None` for an invalid chrono format
+fn format_time(now: NaiveDateTime, format: &str) -> Option<String> {
+    let mut out = String::new();
+    write!(out, "{}", now.format(format)).ok()?;
+    Some(out)
+}
+
+// ============================================================================
+// TESTS
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn now() -> NaiveDateTime {
+        chrono::NaiveDate::from_ymd_opt(2024, 5, 17).unwrap().and_hms_opt(9, 30, 0).unwrap()
+    }
+
+    #[test]
+    fn test_builtin_placeholders() {
+        let input = TemplateInput { title: Some("Ideas".to_string()), ..TemplateInput::default() };
+        let context = TemplateContext::from_input(now(), input, &BTreeMap::new());
+        assert_eq!(
+            render_template("# {{title}}\nCreated {{date}} {{time}}\n", &context),
+            "# Ideas\nCreated 2024-05-17 09:30\n"
+        );
+        assert_eq!(render_template("{{date:%d.%m.%Y}} {{time:%H}}", &context), "17.05.2024 09");
+        assert_eq!(render_template("{{clipboard}}|{{unknown}}|{{title", &context), "|{{unknown}}|{{title");
+
+        let ids = render_template("{{uuid}} {{uuid}}", &context);
+        let (a, b) = ids.split_once(' ').unwrap();
+        assert_eq!(a.len(), 36);
+        assert_ne!(a, b);
+    }
+
+    #[test]
+    fn test_variable_precedence() {
+        let workspace: BTreeMap<String, String> = [("author", "Ana"), ("title", "Workspace")]
+            .into_iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect();
+        let input = TemplateInput {
+            title: Some("Note".to_string()),
+            clipboard: Some("copied".to_string()),
+            variables: [("author".to_string(), "Bo".to_string())].into_iter().collect(),
+        };
+        let context = TemplateContext::from_input(now(), input, &workspace);
+
+        assert_eq!(render_template("{{author}} {{title}} {{ clipboard }}", &context), "Bo Note copied");
+    }
+}
@@ -0,0 +1,507 @@
+//! Text PDF Documents
+//!
+//! Lays out a note as a plain PDF (A4, the PDF base fonts Helvetica and
+//! Courier) where a file is needed without user interaction, such as a mail
+//! attachment. The print window (see `crate::print`) remains the way to get
+//! a PDF that looks like the preview; this one is text only:
+//!
+//! - Headings in bold, paragraphs, quotes and list items wrapped to the page
+//! - Callouts (`> [!note] Title`) as quotes headed by their title in bold
+//! - Fenced code in Courier, long lines cut at the margin
+//! - Inline markup removed: links keep their text, images their alt text
+//!
+//! The base fonts cover Windows-1252; other characters are written as `?`.
+
+use std::fmt::Write;
+use crate::render::{callout_marker, default_callout_title};
+
+/// Page size (A4), in points
+const PAGE_WIDTH: f32 = 595.0;
+const PAGE_HEIGHT: f32 = 842.0;
+
+/// Page margin, in points
+const MARGIN: f32 = 56.0;
+
+/// Font sizes of body text and code, in points
+const BODY_SIZE: f32 = 11.0;
+const CODE_SIZE: f32 = 9.5;
+
+/// Font sizes of `#`..`######`, in points
+const HEADING_SIZES: [f32; 6] = [20.0, 16.0, 14.0, 12.0, 11.0, 11.0];
+
+/// Indent per list or quote level, in points
+const INDENT: f32 = 16.0;
+
+/// Line height, relative to the font size
+const LEADING: f32 = 1.35;
+
+/// Character widths of Helvetica for ` `..`~`, in 1/1000 of the font size
+const HELVETICA: [u16; 95] = [
+    278, 278, 355, 556, 556, 889, 667, 191, 333, 333, 389, 584, 278, 333, 278, 278,
+    556, 556, 556, 556, 556, 556, 556, 556, 556, 556, 278, 278, 584, 584, 584, 556,
+    1015, 667, 667, 722, 722, 667, 611, 778, 722, 278, 500, 667, 556, 833, 722, 778,
+    667, 778, 722, 667, 611, 722, 667, 944, 667, 667, 611, 278, 278, 278, 469, 556,
+    333, 556, 556, 500, 556, 556, 278, 556, 556, 222, 222, 500, 222, 833, 556, 556,
+    556, 556, 333, 500, 278, 556, 500, 722, 500, 500, 500, 334, 260, 334, 584,
+];
+
+/// Character widths of Helvetica-Bold for ` `..`~`
+const HELVETICA_BOLD: [u16; 95] = [
+    278, 333, 474, 556, 556, 889, 722, 238, 333, 333, 389, 584, 278, 333, 278, 278,
+    556, 556, 556, 556, 556, 556, 556, 556, 556, 556, 333, 333, 584, 584, 584, 611,
+    975, 722, 722, 722, 722, 667, 611, 778, 722, 278, 556, 722, 611, 833, 722, 778,
+    667, 778, 722, 667, 611, 722, 667, 944, 667, 667, 611, 333, 278, 333, 584, 556,
+    333, 556, 611, 556, 611, 556, 333, 611, 611, 278, 278, 556, 278, 889, 611, 611,
+    611, 611, 389, 556, 333, 611, 556, 778, 556, 556, 500, 389, 280, 389, 584,
+];
+
+/// Lays out a note's markdown (without frontmatter) as a PDF document
+pub fn markdown_to_pdf(title: &str, markdown: &str) -> Vec<u8> {
+    let pages = paginate(&layout(markdown));
+    assemble(title, &pages)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Font {
+    Regular,
+    Bold,
+    Mono,
+}
+
+impl Font {
+    /// Name of the font in the page resources
+    fn resource(self) -> &'static str {
+        match self {
+            Font::Regular => "F1",
+            Font::Bold => "F2",
+            Font::Mono => "F3",
+        }
+    }
+
+    /// Width of `text` at `size`, in points
+    fn measure(self, text: &str, size: f32) -> f32 {
+        let table = match self {
+            Font::Regular => &HELVETICA,
+            Font::Bold => &HELVETICA_BOLD,
+            Font::Mono => return text.chars().count() as f32 * 0.6 * size,
+        };
+        let units: u32 = text
+            .chars()
+            .map(|c| match c {
+                ' '..='~' => table[c as usize - 32] as u32,
+                _ => 556,
+            })
+            .sum();
+        units as f32 * size / 1000.0
+    }
+}
+
+/// A laid out line: text, or a horizontal rule if `text` is `None`
+#[derive(Debug)]
+struct Line {
+    font: Font,
+    size: f32,
+    indent: f32,
+    /// Extra space above the line, dropped at the top of a page
+    space_before: f32,
+    text: Option<String>,
+}
+
+/// A paragraph or list item collecting its lines before it is wrapped
+struct Pending {
+    text: String,
+    indent: f32,
+    /// Hanging indent of the lines after the first (list items)
+    hanging: f32,
+    quoted: bool,
+}
+
+// Helper: Splits markdown into wrapped lines
+fn layout(markdown: &str) -> Vec<Line> {
+    let mut lines = Vec::new();
+    let mut pending: Option<Pending> = None;
+    let mut in_code = false;
+    let width = PAGE_WIDTH - 2.0 * MARGIN;
+
+    for raw in markdown.lines() {
+        let trimmed = raw.trim();
+        if trimmed.starts_with("```") || trimmed.starts_with("~~~") {
+            flush(&mut lines, &mut pending, width);
+            in_code = !in_code;
+            continue;
+        }
+        if in_code {
+            let code = raw.replace('\t', "    ");
+            for (i, text) in cut(&code, Font::Mono, CODE_SIZE, width).into_iter().enumerate() {
+                let space_before = if i == 0 && lines.last().is_some_and(|l: &Line| l.font != Font::Mono) { 4.0 } else { 0.0 };
+                lines.push(Line { font: Font::Mono, size: CODE_SIZE, indent: 0.0, space_before, text: Some(text) });
+            }
+            continue;
+        }
+        if trimmed.is_empty() {
+            flush(&mut lines, &mut pending, width);
+            continue;
+        }
+
+        let hashes = trimmed.chars().take_while(|&c| c == '#').count();
+        if (1..=6).contains(&hashes) && trimmed[hashes..].starts_with(' ') {
+            flush(&mut lines, &mut pending, width);
+            let size = HEADING_SIZES[hashes - 1];
+            let text = plain_inline(trimmed[hashes..].trim().trim_end_matches('#').trim_end());
+            for (i, text) in wrap(&text, Font::Bold, size, width).into_iter().enumerate() {
+                let space_before = if i == 0 { size * 0.6 } else { 0.0 };
+                lines.push(Line { font: Font::Bold, size, indent: 0.0, space_before, text: Some(text) });
+            }
+            continue;
+        }
+        let compact: String = trimmed.chars().filter(|c| !c.is_whitespace()).collect();
+        if compact.len() >= 3 && ['-', '*', '_'].iter().any(|&m| compact.chars().all(|c| c == m)) {
+            flush(&mut lines, &mut pending, width);
+            lines.push(Line { font: Font::Regular, size: BODY_SIZE, indent: 0.0, space_before: 4.0, text: None });
+            continue;
+        }
+        if trimmed.starts_with('|') {
+            flush(&mut lines, &mut pending, width);
+            // Separator rows only align the columns
+            if trimmed.chars().all(|c| matches!(c, '|' | '-' | ':' | ' ')) {
+                continue;
+            }
+            let cells: Vec<String> = trimmed.trim_matches('|').split('|').map(|cell| plain_inline(cell.trim())).collect();
+            for text in wrap(&cells.join("   "), Font::Regular, BODY_SIZE, width) {
+                lines.push(Line { font: Font::Regular, size: BODY_SIZE, indent: 0.0, space_before: 0.0, text: Some(text) });
+            }
+            continue;
+        }
+
+        // Quotes and list items; a continuation line joins the pending paragraph
+        let mut text = raw.trim_end();
+        let mut indent = 0.0;
+        let mut quoted = false;
+        while let Some(rest) = text.trim_start().strip_prefix('>') {
+            text = rest;
+            indent += INDENT;
+            quoted = true;
+        }
+        let level = (text.len() - text.trim_start().len()) / 2;
+        let text = text.trim_start();
+        if let Some((kind, _, title)) = callout_marker(text).filter(|_| quoted) {
+            flush(&mut lines, &mut pending, width);
+            let title = if title.is_empty() { default_callout_title(kind) } else { plain_inline(title) };
+            for (i, text) in wrap(&title, Font::Bold, BODY_SIZE, width - indent).into_iter().enumerate() {
+                let space_before = if i == 0 { BODY_SIZE * 0.4 } else { 0.0 };
+                lines.push(Line { font: Font::Bold, size: BODY_SIZE, indent, space_before, text: Some(text) });
+            }
+            continue;
+        }
+        if let Some((marker, item)) = list_marker(text) {
+            flush(&mut lines, &mut pending, width);
+            let indent = indent + level as f32 * INDENT;
+            let marker = format!("{} ", marker);
+            let hanging = Font::Regular.measure(&marker, BODY_SIZE);
+            pending = Some(Pending { text: format!("{}{}", marker, plain_inline(item)), indent, hanging, quoted });
+            continue;
+        }
+        match &mut pending {
+            Some(paragraph) if paragraph.quoted == quoted => {
+                paragraph.text.push(' ');
+                paragraph.text.push_str(&plain_inline(text));
+            }
+            _ => {
+                flush(&mut lines, &mut pending, width);
+                pending = Some(Pending { text: plain_inline(text), indent, hanging: 0.0, quoted });
+            }
+        }
+    }
+    flush(&mut lines, &mut pending, width);
+    lines
+}
+
+// Helper: Wraps the pending paragraph into lines
+fn flush(lines: &mut Vec<Line>, pending: &mut Option<Pending>, width: f32) {
+    let Some(paragraph) = pending.take() else {
+        return;
+    };
+    let first = paragraph.indent;
+    let rest = paragraph.indent + paragraph.hanging;
+    // Lines after the first are narrower by the hanging indent
+    let wrapped = wrap(&paragraph.text, Font::Regular, BODY_SIZE, width - rest);
+    for (i, text) in wrapped.into_iter().enumerate() {
+        let (indent, space_before) = if i == 0 { (first, BODY_SIZE * 0.4) } else { (rest, 0.0) };
+        lines.push(Line { font: Font::Regular, size: BODY_SIZE, indent, space_before, text: Some(text) });
+    }
+}
+
+// Helper: Splits a list item into its marker (`•`, `1.`, `[x]`) and text
+fn list_marker(text: &str) -> Option<(String, &str)> {
+    let item = if let Some(item) = ["- ", "* ", "+ "].iter().find_map(|m| text.strip_prefix(m)) {
+        item
+    } else {
+        let digits = text.chars().take_while(|c| c.is_ascii_digit()).count();
+        let item = text[digits..].strip_prefix(". ").or_else(|| text[digits..].strip_prefix(") "));
+        return match item {
+            Some(item) if digits > 0 => Some((format!("{}.", &text[..digits]), item)),
+            _ => None,
+        };
+    };
+    for (task, marker) in [("[ ] ", "[ ]"), ("[x] ", "[x]"), ("[X] ", "[x]")] {
+        if let Some(item) = item.strip_prefix(task) {
+            return Some((marker.to_string(), item));
+        }
+    }
+    Some(("\u{2022}".to_string(), item))
+}
+
+// Helper: Removes inline markup, keeping link text, wiki link aliases and
+// image alt text
+fn plain_inline(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(c) = rest.chars().next() {
+        if let Some(inner) = rest.strip_prefix("[[") {
+            if let Some(end) = inner.find("]]") {
+                let link = &inner[..end];
+                out.push_str(link.split_once('|').map_or(link, |(_, alias)| alias));
+                rest = &inner[end + 2..];
+                continue;
+            }
+        }
+        let label_start = if rest.starts_with("![") { 2 } else if c == '[' { 1 } else { 0 };
+        if label_start > 0 {
+            let after = &rest[label_start..];
+            if let Some((label, target)) = after.split_once("](").filter(|(label, _)| !label.contains(']')) {
+                if let Some(end) = target.find(')') {
+                    out.push_str(&plain_inline(label));
+                    rest = &target[end + 1..];
+                    continue;
+                }
+            }
+        }
+        if c == '\\' && rest[1..].starts_with(|n: char| n.is_ascii_punctuation()) {
+            out.push_str(&rest[1..2]);
+            rest = &rest[2..];
+            continue;
+        }
+        if let Some(after) = ["**", "__", "~~", "=="].iter().find_map(|m| rest.strip_prefix(m)) {
+            rest = after;
+            continue;
+        }
+        if !matches!(c, '*' | '`') {
+            out.push(c);
+        }
+        rest = &rest[c.len_utf8()..];
+    }
+    out
+}
+
+// Helper: Wraps text at spaces to fit `width`; words wider than the line
+// are cut
+fn wrap(text: &str, font: Font, size: f32, width: f32) -> Vec<String> {
+    let mut lines = Vec::new();
+    let mut line = String::new();
+    for word in text.split_whitespace() {
+        for piece in cut(word, font, size, width) {
+            let candidate = if line.is_empty() { piece.clone() } else { format!("{} {}", line, piece) };
+            if line.is_empty() || font.measure(&candidate, size) <= width {
+                line = candidate;
+            } else {
+                lines.push(std::mem::replace(&mut line, piece));
+            }
+        }
+    }
+    if !line.is_empty() {
+        lines.push(line);
+    }
+    lines
+}
+
+// Helper: Cuts text into pieces no wider than `width`
+fn cut(text: &str, font: Font, size: f32, width: f32) -> Vec<String> {
+    let mut pieces = vec![String::new()];
+    for c in text.chars() {
+        let piece = pieces.last_mut().expect("pieces is never empty");
+        piece.push(c);
+        if piece.chars().count() > 1 && font.measure(piece, size) > width {
+            piece.pop();
+            pieces.push(c.to_string());
+        }
+    }
+    pieces
+}
+
+// Helper: Places lines on pages; returns each page's content stream
+fn paginate(lines: &[Line]) -> Vec<String> {
+    let top = PAGE_HEIGHT - MARGIN;
+    let mut pages = Vec::new();
+    let mut content = String::new();
+    let mut y = top;
+    let mut page_start = true;
+
+    for line in lines {
+        let height = line.size * LEADING;
+        let mut space_before = if page_start { 0.0 } else { line.space_before };
+        if y - space_before - height < MARGIN && !page_start {
+            pages.push(std::mem::take(&mut content));
+            y = top;
+            space_before = 0.0;
+        }
+        y -= space_before + height;
+        page_start = false;
+        let baseline = y + (height - line.size) / 2.0 + line.size * 0.2;
+        let x = MARGIN + line.indent;
+        match &line.text {
+            Some(text) => {
+                let _ = writeln!(
+                    content,
+                    "BT /{} {:.1} Tf {:.2} {:.2} Td {} Tj ET",
+                    line.font.resource(), line.size, x, baseline, pdf_string(text)
+                );
+            }
+            None => {
+                let rule_y = y + height / 2.0;
+                let _ = writeln!(content, "0.75 G 0.5 w {:.2} {:.2} m {:.2} {:.2} l S 0 G", x, rule_y, PAGE_WIDTH - MARGIN, rule_y);
+            }
+        }
+    }
+    if !content.is_empty() || pages.is_empty() {
+        pages.push(content);
+    }
+    pages
+}
+
+// Helper: Writes the document: catalog, page tree, fonts, info, then a
+// page and its content for each page
+fn assemble(title: &str, pages: &[String]) -> Vec<u8> {
+    const FIRST_PAGE: usize = 7;
+    let kids: Vec<String> = (0..pages.len()).map(|i| format!("{} 0 R", FIRST_PAGE + 2 * i)).collect();
+
+    let mut objects = vec![
+        "<< /Type /Catalog /Pages 2 0 R >>".to_string(),
+        format!("<< /Type /Pages /Kids [{}] /Count {} >>", kids.join(" "), pages.len()),
+    ];
+    for font in ["Helvetica", "Helvetica-Bold", "Courier"] {
+        objects.push(format!("<< /Type /Font /Subtype /Type1 /BaseFont /{} /Encoding /WinAnsiEncoding >>", font));
+    }
+    objects.push(format!("<< /Title {} /Producer (MDReader) >>", pdf_string(title)));
+    for (i, content) in pages.iter().enumerate() {
+        objects.push(format!(
+            "<< /Type /Page /Parent 2 0 R /MediaBox [0 0 {} {}] \
+             /Resources << /Font << /F1 3 0 R /F2 4 0 R /F3 5 0 R >> >> /Contents {} 0 R >>",
+            PAGE_WIDTH, PAGE_HEIGHT, FIRST_PAGE + 2 * i + 1
+        ));
+        objects.push(format!("<< /Length {} >>\nstream\n{}\nendstream", content.len(), content));
+    }
+
+    // Content is ASCII (see `pdf_string`), so offsets are byte offsets
+    let mut pdf = String::from("%PDF-1.4\n");
+    let mut offsets = Vec::with_capacity(objects.len());
+    for (i, object) in objects.iter().enumerate() {
+        offsets.push(pdf.len());
+        let _ = write!(pdf, "{} 0 obj\n{}\nendobj\n", i + 1, object);
+    }
+    let xref = pdf.len();
+    let _ = write!(pdf, "xref\n0 {}\n0000000000 65535 f \n", objects.len() + 1);
+    for offset in offsets {
+        let _ = writeln!(pdf, "{:010} 00000 n ", offset);
+    }
+    let _ = write!(
+        pdf,
+        "trailer\n<< /Size {} /Root 1 0 R /Info 6 0 R >>\nstartxref\n{}\n%%EOF\n",
+        objects.len() + 1,
+        xref
+    );
+    pdf.into_bytes()
+}
+
+// Helper: Encodes text as a PDF string literal in WinAnsiEncoding, with
+// octal escapes for bytes outside ASCII
+fn pdf_string(text: &str) -> String {
+    let mut out = String::with_capacity(text.len() + 2);
+    out.push('(');
+    for c in text.chars() {
+        let byte = match c {
+            ' '..='~' => c as u8,
+            '\u{a0}'..='\u{ff}' => c as u32 as u8,
+            '\u{20ac}' => 0x80,
+            '\u{2026}' => 0x85,
+            '\u{2018}' => 0x91,
+            '\u{2019}' => 0x92,
+            '\u{201c}' => 0x93,
+            '\u{201d}' => 0x94,
+            '\u{2022}' => 0x95,
+            '\u{2013}' => 0x96,
+            '\u{2014}' => 0x97,
+            '\u{2122}' => 0x99,
+            _ => b'?',
+        };
+        match byte {
+            b'(' | b')' | b'\\' => {
+                out.push('\\');
+                out.push(byte as char);
+            }
+            0x20..=0x7e => out.push(byte as char),
+            _ => {
+                let _ = write!(out, "\\{:03o}", byte);
+            }
+        }
+    }
+    out.push(')');
+    out
+}
+
+// ============================================================================
+// TESTS
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_layout_of_blocks() {
+        let lines = layout(
+            "# Weekly sync\n\nNotes by **Ann** with [the plan](plan.md) and [[Roadmap|our roadmap]].\n\n\
+             - [x] Ship it\n  1. Nested step\n\n> Quoted\n> text\n\n---\n\n```\nlet x = 1;\n```\n",
+        );
+        let texts: Vec<Option<&str>> = lines.iter().map(|l| l.text.as_deref()).collect();
+        assert_eq!(
+            texts,
+            [
+                Some("Weekly sync"),
+                Some("Notes by Ann with the plan and our roadmap."),
+                Some("[x] Ship it"),
+                Some("1. Nested step"),
+                Some("Quoted text"),
+                None,
+                Some("let x = 1;"),
+            ]
+        );
+        assert_eq!(lines[0].font, Font::Bold);
+        assert_eq!(lines[3].indent, INDENT);
+        assert_eq!(lines[4].indent, INDENT);
+        assert_eq!(lines[6].font, Font::Mono);
+
+        let callout = layout("> [!warning]- Mind the **gap**\n> Body\n\n> [!tip]\n> Hint\n");
+        let texts: Vec<Option<&str>> = callout.iter().map(|l| l.text.as_deref()).collect();
+        assert_eq!(texts, [Some("Mind the gap"), Some("Body"), Some("Tip"), Some("Hint")]);
+        assert_eq!(callout[0].font, Font::Bold);
+        assert_eq!(callout[0].indent, INDENT);
+    }
+
+    #[test]
+    fn test_wrapping_and_pages() {
+        let width = PAGE_WIDTH - 2.0 * MARGIN;
+        let wrapped = wrap(&"word ".repeat(200), Font::Regular, BODY_SIZE, width);
+        assert!(wrapped.len() > 5);
+        assert!(wrapped.iter().all(|line| Font::Regular.measure(line, BODY_SIZE) <= width));
+        assert_eq!(cut(&"x".repeat(200), Font::Mono, CODE_SIZE, width).len(), 3);
+
+        let pdf = String::from_utf8(markdown_to_pdf("Notes (draft)", &"Line of text\n\n".repeat(100))).unwrap();
+        assert!(pdf.starts_with("%PDF-1.4\n"));
+        assert!(pdf.ends_with("%%EOF\n"));
+        assert!(pdf.contains("/Title (Notes \\(draft\\))"));
+        assert!(pdf.contains("/Count 3"));
+        let offset: usize = pdf.lines().find(|l| l.ends_with(" 00000 n ")).unwrap()[..10].parse().unwrap();
+        assert!(pdf[offset..].starts_with("1 0 obj"));
+        assert_eq!(pdf_string("caf\u{e9} \u{2014} \u{4e2d}"), "(caf\\351 \\227 ?)");
+    }
+}
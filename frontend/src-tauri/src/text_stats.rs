@@ -0,0 +1,325 @@
+//! Text Statistics
+//!
+//! Counts and readability of a piece of markdown, such as the selection in
+//! the editor. Markup is stripped first: link targets, emphasis marks,
+//! heading and list markers and HTML tags don't count, and neither does
+//! code in fences. Frontmatter is skipped.
+//!
+//! - Words are runs of letters or digits; CJK characters count one each
+//! - Sentences end at `.`, `!`, `?` (and their CJK forms); a heading, list
+//!   item or paragraph without one still counts as a sentence
+//! - Readability is the Flesch reading ease and Flesch-Kincaid grade, with
+//!   syllables estimated for English
+//! - Reading and speaking times assume 238 and 130 words per minute
+
+use serde::Serialize;
+use crate::frontmatter;
+
+/// Average silent reading speed of adults, in words per minute
+const READING_WPM: f64 = 238.0;
+
+/// Presentation speaking speed, in words per minute
+const SPEAKING_WPM: f64 = 130.0;
+
+/// Words whose `.` does not end a sentence
+const ABBREVIATIONS: &[&str] = &["mr", "mrs", "ms", "dr", "prof", "st", "vs", "etc", "no", "fig", "approx"];
+
+/// Statistics of a text
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct TextAnalysis {
+    pub words: usize,
+    /// Characters of the text as shown (markup stripped)
+    pub characters: usize,
+    pub characters_no_spaces: usize,
+    pub sentences: usize,
+    /// Paragraphs, headings and list items with text
+    pub paragraphs: usize,
+    pub syllables: usize,
+    /// Flesch reading ease (higher is easier, 60-70 is plain English);
+    /// `None` without words
+    pub reading_ease: Option<f64>,
+    /// Flesch-Kincaid grade level; `None` without words
+    pub grade_level: Option<f64>,
+    pub reading_seconds: u64,
+    pub speaking_seconds: u64,
+}
+
+/// Analyzes a piece of markdown
+pub fn analyze_text(content: &str) -> TextAnalysis {
+    let (_, body) = frontmatter::split(content);
+    let mut analysis = TextAnalysis::default();
+
+    // Text of the current paragraph, heading or list item
+    let mut block = String::new();
+    let mut fence: Option<String> = None;
+    for line in body.lines() {
+        let trimmed = line.trim();
+        if let Some(open) = &fence {
+            if trimmed.starts_with(open.as_str()) && trimmed.chars().all(|c| open.starts_with(c)) {
+                fence = None;
+            }
+            continue;
+        }
+        if let Some(marker) = fence_marker(trimmed) {
+            end_block(&mut block, &mut analysis);
+            fence = Some(marker.to_string());
+            continue;
+        }
+
+        let (text, block_start) = plain_line(trimmed);
+        if block_start || text.trim().is_empty() {
+            end_block(&mut block, &mut analysis);
+        }
+        if text.trim().is_empty() {
+            continue;
+        }
+
+        let (words, syllables) = count_words(&text);
+        analysis.words += words;
+        analysis.syllables += syllables;
+        analysis.characters += text.chars().count();
+        analysis.characters_no_spaces += text.chars().filter(|c| !c.is_whitespace()).count();
+        if !block.is_empty() {
+            block.push(' ');
+        }
+        block.push_str(text.trim());
+    }
+    end_block(&mut block, &mut analysis);
+
+    if analysis.words > 0 {
+        let words = analysis.words as f64;
+        let sentences = analysis.sentences.max(1) as f64;
+        let syllables = analysis.syllables as f64;
+        analysis.reading_ease = Some(round1(206.835 - 1.015 * words / sentences - 84.6 * syllables / words));
+        analysis.grade_level = Some(round1((0.39 * words / sentences + 11.8 * syllables / words - 15.59).max(0.0)));
+    }
+    analysis.reading_seconds = (analysis.words as f64 / READING_WPM * 60.0).ceil() as u64;
+    analysis.speaking_seconds = (analysis.words as f64 / SPEAKING_WPM * 60.0).ceil() as u64;
+    analysis
+}
+
+// Helper: Counts the sentences of a finished block; its last one needs no
+// terminator
+fn end_block(block: &mut String, analysis: &mut TextAnalysis) {
+    if count_words(block).0 > 0 {
+        analysis.paragraphs += 1;
+        analysis.sentences += sentence_ends(block) + usize::from(!ends_sentence(block));
+    }
+    block.clear();
+}
+
+// Helper: A line without markup, and whether it starts a block of its own
+// (heading, list item, quote, table row)
+fn plain_line(line: &str) -> (String, bool) {
+    let mut rest = line;
+    let mut block_start = false;
+
+    while let Some(quoted) = rest.strip_prefix('>') {
+        rest = quoted.trim_start();
+        block_start = true;
+    }
+    let hashes = rest.len() - rest.trim_start_matches('#').len();
+    if (1..=6).contains(&hashes) && rest[hashes..].starts_with(' ') {
+        rest = rest[hashes..].trim_end_matches('#').trim();
+        block_start = true;
+    }
+    let digits = rest.chars().take_while(char::is_ascii_digit).count();
+    if let Some(item) = rest
+        .strip_prefix(['-', '*', '+'])
+        .or_else(|| rest[digits..].strip_prefix(['.', ')']).filter(|_| digits > 0))
+        .filter(|item| item.starts_with(' '))
+    {
+        rest = item.trim_start();
+        rest = rest.strip_prefix("[ ] ").or_else(|| rest.strip_prefix("[x] ")).unwrap_or(rest);
+        block_start = true;
+    }
+    if rest.starts_with('|') {
+        block_start = true;
+    }
+    if rest.chars().all(|c| matches!(c, '-' | '=' | '*' | '_' | '|' | ':' | ' ')) {
+        return (String::new(), true);
+    }
+
+    let mut text = String::with_capacity(rest.len());
+    let mut chars = rest.char_indices().peekable();
+    while let Some((i, c)) = chars.next() {
+        match c {
+            // Link and image targets: keep the text, drop `(url)`
+            ']' if rest[i + 1..].starts_with('(') => {
+                let close = rest[i + 1..].find(')').map_or(rest.len(), |k| i + 1 + k);
+                while chars.peek().is_some_and(|&(k, _)| k <= close) {
+                    chars.next();
+                }
+            }
+            // Wiki links: keep the alias or the target
+            '[' if rest[i..].starts_with("[[") => {
+                let inner_end = rest[i + 2..].find("]]").map_or(rest.len(), |k| i + 2 + k);
+                let inner = &rest[(i + 2).min(inner_end)..inner_end];
+                text.push_str(inner.rsplit('|').next().unwrap_or(inner).split('#').next().unwrap_or(""));
+                while chars.peek().is_some_and(|&(k, _)| k < inner_end + 2) {
+                    chars.next();
+                }
+            }
+            '<' if rest[i..].find('>').is_some_and(|k| rest[i + 1..i + k].chars().all(|c| c.is_alphanumeric() || "/-=\"' :.".contains(c))) => {
+                let close = i + rest[i..].find('>').unwrap_or(0);
+                while chars.peek().is_some_and(|&(k, _)| k <= close) {
+                    chars.next();
+                }
+            }
+            '[' | ']' | '*' | '_' | '`' | '~' | '!' if is_markup(rest, i, c) => {}
+            '|' => text.push(' '),
+            c => text.push(c),
+        }
+    }
+    (text, block_start)
+}
+
+// Helper: Whether a mark is markup rather than text (`_` inside a word and
+// `!` ending a sentence are text)
+fn is_markup(line: &str, at: usize, c: char) -> bool {
+    match c {
+        '_' => {
+            let before = line[..at].chars().next_back().is_some_and(char::is_alphanumeric);
+            let after = line[at + 1..].chars().next().is_some_and(char::is_alphanumeric);
+            !(before && after)
+        }
+        '!' => line[at + 1..].starts_with('['),
+        _ => true,
+    }
+}
+
+// Helper: Words and their estimated syllables
+fn count_words(text: &str) -> (usize, usize) {
+    let (mut words, mut syllables) = (0, 0);
+    for token in text.split(|c: char| c.is_whitespace() || matches!(c, '—' | '–' | '/')) {
+        let mut latin = String::new();
+        for c in token.chars() {
+            if is_cjk(c) {
+                words += 1;
+                syllables += 1;
+            } else if c.is_alphanumeric() || (c == '\'' && !latin.is_empty()) {
+                latin.push(c);
+            }
+        }
+        if latin.chars().any(char::is_alphanumeric) {
+            words += 1;
+            syllables += count_syllables(&latin);
+        }
+    }
+    (words, syllables)
+}
+
+/// Estimated syllables of an English word (vowel groups, silent `e`)
+fn count_syllables(word: &str) -> usize {
+    let word = word.to_lowercase();
+    if word.chars().any(|c| c.is_ascii_digit()) {
+        return 1;
+    }
+    let is_vowel = |c: char| "aeiouyàáâäèéêëìíîïòóôöùúûü".contains(c);
+    let chars: Vec<char> = word.chars().filter(|c| c.is_alphabetic()).collect();
+    let mut count = 0;
+    let mut previous_vowel = false;
+    for &c in &chars {
+        let vowel = is_vowel(c);
+        if vowel && !previous_vowel {
+            count += 1;
+        }
+        previous_vowel = vowel;
+    }
+    // Silent final `e`, but not in `-le` (`table`)
+    if chars.len() > 2 && chars.ends_with(&['e']) && !is_vowel(chars[chars.len() - 2]) && count > 1 {
+        let le = chars[chars.len() - 2] == 'l' && !is_vowel(chars[chars.len() - 3]);
+        if !le {
+            count -= 1;
+        }
+    }
+    count.max(1)
+}
+
+// Helper: Sentence terminators followed by a space or the end of the line
+fn sentence_ends(text: &str) -> usize {
+    let chars: Vec<char> = text.chars().collect();
+    let mut count = 0;
+    for (i, &c) in chars.iter().enumerate() {
+        if matches!(c, '。' | '！' | '？') {
+            count += 1;
+            continue;
+        }
+        if !matches!(c, '.' | '!' | '?') {
+            continue;
+        }
+        let next = chars[i + 1..].iter().find(|c| !matches!(c, '.' | '!' | '?' | '"' | '\'' | ')' | '”' | '’'));
+        let ends = matches!(next, None | Some(' ')) && chars[i + 1..].first().map_or(true, |n| !matches!(n, '.' | '!' | '?'));
+        let after_word = i > 0 && chars[i - 1].is_alphanumeric();
+        if ends && after_word && !(c == '.' && is_abbreviation(&chars[..i])) {
+            count += 1;
+        }
+    }
+    count
+}
+
+// Helper: Whether the word before a `.` is an abbreviation (`e.g.`, `Dr.`)
+fn is_abbreviation(before: &[char]) -> bool {
+    let start = before.iter().rposition(|c| c.is_whitespace()).map_or(0, |i| i + 1);
+    let word: String = before[start..].iter().collect::<String>().to_lowercase();
+    word.contains('.') || ABBREVIATIONS.contains(&word.as_str())
+}
+
+fn fence_marker(text: &str) -> Option<&str> {
+    let first = text.chars().next().filter(|c| matches!(c, '`' | '~'))?;
+    let len = text.len() - text.trim_start_matches(first).len();
+    (len >= 3).then(|| &text[..len])
+}
+
+fn ends_sentence(text: &str) -> bool {
+    text.trim_end()
+        .trim_end_matches(['"', '\'', ')', '”', '’'])
+        .ends_with(['.', '!', '?', '。', '！', '？'])
+}
+
+fn is_cjk(c: char) -> bool {
+    matches!(c as u32,
+        0x3040..=0x30FF     // Hiragana, Katakana
+        | 0x3400..=0x4DBF   // CJK Extension A
+        | 0x4E00..=0x9FFF   // CJK Unified Ideographs
+        | 0xAC00..=0xD7AF   // Hangul
+        | 0xF900..=0xFAFF)  // CJK Compatibility Ideographs
+}
+
+fn round1(value: f64) -> f64 {
+    (value * 10.0).round() / 10.0
+}
+
+// ============================================================================
+// TESTS
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_analyze_text() {
+        let text = "---\ntitle: x\n---\n# The **cat**\n\nThe cat sat on the [mat](https://example.com/a.b). It was happy!\nSee [[Dogs|dogs]] too\n\n- one item\n- e.g. this\n\n```\nlet code = 1;\n```\n";
+        let analysis = analyze_text(text);
+        assert_eq!(analysis.words, 18);
+        assert_eq!(analysis.sentences, 6, "Heading, three in the paragraph, two items");
+        assert_eq!(analysis.paragraphs, 4);
+        assert_eq!(analysis.characters, "The cat".len() + "The cat sat on the mat. It was happy!".len() + "See dogs too".len() + "one item".len() + "e.g. this".len());
+        assert_eq!((analysis.reading_seconds, analysis.speaking_seconds), (5, 9));
+        assert!(analysis.reading_ease.unwrap() > 90.0, "Short words and sentences read easily");
+
+        let japanese = analyze_text("吾輩は猫である。名前はまだ無い。");
+        assert_eq!((japanese.words, japanese.sentences), (14, 2));
+        assert_eq!(analyze_text("```\ncode\n```\n"), TextAnalysis::default());
+    }
+
+    #[test]
+    fn test_count_syllables() {
+        let counts: Vec<usize> = ["cat", "table", "make", "readability", "the", "simple", "queue", "2024"]
+            .iter()
+            .map(|w| count_syllables(w))
+            .collect();
+        assert_eq!(counts, [1, 2, 1, 5, 1, 2, 1, 1]);
+    }
+}
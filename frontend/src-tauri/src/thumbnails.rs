@@ -0,0 +1,296 @@
+//! Thumbnail Generation
+//!
+//! Small previews for gallery and card views, cached under the user cache
+//! directory (`<cache>/mdreader/thumbnails/`):
+//!
+//! - Raster images are scaled to fit `size` × `size` and stored as PNG
+//! - Notes are rendered as an SVG mindmap of their heading outline
+//! - SVG images are already scalable and returned as they are
+//!
+//! Cache entries are keyed by source path, modification time and size, so an
+//! edited file gets a fresh thumbnail and stale entries are simply unused.
+
+use std::collections::hash_map::DefaultHasher;
+use std::fs;
+use std::hash::{Hash, Hasher};
+use std::path::{Path, PathBuf};
+use serde::Serialize;
+use crate::outline::{OutlineDocument, OutlineNode};
+use crate::utils::base64_encode;
+
+/// Edge length used when the caller does not specify one
+pub const DEFAULT_SIZE: u32 = 256;
+
+/// Bounds for a caller-supplied size
+pub const MIN_SIZE: u32 = 16;
+pub const MAX_SIZE: u32 = 1024;
+
+/// Raster formats that can be thumbnailed
+pub const IMAGE_EXTENSIONS: &[&str] = &["png", "jpg", "jpeg", "gif", "webp", "bmp"];
+
+/// Files `get_thumbnail` accepts
+pub const THUMBNAIL_EXTENSIONS: &[&str] = &["png", "jpg", "jpeg", "gif", "webp", "bmp", "svg", "md"];
+
+/// Deepest heading level drawn in a mindmap thumbnail (below the root)
+const MINDMAP_DEPTH: usize = 2;
+
+/// A generated (or cached) thumbnail
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Thumbnail {
+    /// File holding the thumbnail
+    pub cache_path: String,
+    pub mime: String,
+    /// `data:` URI of the thumbnail, when requested inline
+    pub data_uri: Option<String>,
+}
+
+/// Directory thumbnails are cached in
+pub fn cache_dir() -> Result<PathBuf, String> {
+    Ok(dirs::cache_dir()
+        .ok_or("Failed to get cache directory")?
+        .join("mdreader")
+        .join("thumbnails"))
+}
+
+/// Returns the thumbnail of `path`, generating it on a cache miss
+pub fn thumbnail(path: &Path, size: u32, cache_dir: &Path, inline: bool) -> Result<Thumbnail, String> {
+    let size = size.clamp(MIN_SIZE, MAX_SIZE);
+    let extension = path
+        .extension()
+        .map(|e| e.to_string_lossy().to_lowercase())
+        .unwrap_or_default();
+
+    if extension == "svg" {
+        return finish(path.to_path_buf(), "image/svg+xml", inline);
+    }
+
+    let is_note = extension == "md";
+    if !is_note && !IMAGE_EXTENSIONS.contains(&extension.as_str()) {
+        return Err(format!("Unsupported thumbnail type: .{}", extension));
+    }
+
+    let metadata = fs::metadata(path)
+        .map_err(|e| format!("Failed to read file: {}", e))?;
+    let modified = metadata.modified().ok();
+    let file_name = format!(
+        "{}-{}.{}",
+        cache_key(path, modified, size),
+        size,
+        if is_note { "svg" } else { "png" }
+    );
+    let cache_path = cache_dir.join(file_name);
+    let mime = if is_note { "image/svg+xml" } else { "image/png" };
+
+    if cache_path.exists() {
+        return finish(cache_path, mime, inline);
+    }
+
+    fs::create_dir_all(cache_dir)
+        .map_err(|e| format!("Failed to create thumbnail cache: {}", e))?;
+
+    if is_note {
+        let content = fs::read_to_string(path)
+            .map_err(|e| format!("Failed to read note: {}", e))?;
+        let title = path
+            .file_stem()
+            .map(|s| s.to_string_lossy().to_string())
+            .unwrap_or_default();
+        fs::write(&cache_path, render_mindmap_svg(&content, &title, size))
+            .map_err(|e| format!("Failed to write thumbnail: {}", e))?;
+    } else {
+        let image = image::open(path)
+            .map_err(|e| format!("Failed to decode image: {}", e))?;
+        image
+            .thumbnail(size, size)
+            .save_with_format(&cache_path, image::ImageFormat::Png)
+            .map_err(|e| format!("Failed to write thumbnail: {}", e))?;
+    }
+
+    log::info!("🖼️ Generated thumbnail for {:?} ({}px)", path, size);
+    finish(cache_path, mime, inline)
+}
+
+/// Wraps a cache file as a thumbnail, reading it back when `inline`
+pub(crate) fn finish(cache_path: PathBuf, mime: &str, inline: bool) -> Result<Thumbnail, String> {
+    let data_uri = if inline {
+        let bytes = fs::read(&cache_path)
+            .map_err(|e| format!("Failed to read thumbnail: {}", e))?;
+        Some(format!("data:{};base64,{}", mime, base64_encode(&bytes)))
+    } else {
+        None
+    };
+
+    Ok(Thumbnail {
+        cache_path: cache_path.to_string_lossy().to_string(),
+        mime: mime.to_string(),
+        data_uri,
+    })
+}
+
+/// Cache file stem for a source file at a given modification time and size
+pub(crate) fn cache_key(path: &Path, modified: Option<std::time::SystemTime>, size: u32) -> String {
+    let mut hasher = DefaultHasher::new();
+    path.hash(&mut hasher);
+    modified.hash(&mut hasher);
+    size.hash(&mut hasher);
+    format!("{:016x}", hasher.finish())
+}
+
+// ============================================================================
+// MINDMAP RENDERING
+// ============================================================================
+
+/// One box of the mindmap layout
+struct LaidOutNode {
+    title: String,
+    depth: usize,
+    y: f64,
+    parent: Option<usize>,
+}
+
+/// Renders the heading outline of a note as a left-to-right mindmap
+///
+/// The root is the note's only top-level heading, or `fallback_title` when
+/// there are several (or none). Two levels below the root are drawn.
+pub fn render_mindmap_svg(content: &str, fallback_title: &str, size: u32) -> String {
+    let outline = OutlineDocument::new(content).outline();
+    let (title, branches) = match outline.children.as_slice() {
+        [single] if single.level == 1 => (single.title.clone(), single.children.clone()),
+        _ => (fallback_title.to_string(), outline.children.clone()),
+    };
+
+    let mut nodes = vec![LaidOutNode { title, depth: 0, y: 0.0, parent: None }];
+    let mut next_row = 0.0;
+    for branch in &branches {
+        layout(branch, 1, 0, &mut nodes, &mut next_row);
+    }
+    let rows = next_row.max(1.0);
+    nodes[0].y = if branches.is_empty() {
+        0.0
+    } else {
+        let children: Vec<f64> = nodes.iter().filter(|n| n.parent == Some(0)).map(|n| n.y).collect();
+        children.iter().sum::<f64>() / children.len() as f64
+    };
+
+    const ROW_HEIGHT: f64 = 28.0;
+    const COLUMN_WIDTH: f64 = 150.0;
+    const NODE_WIDTH: f64 = 130.0;
+    const NODE_HEIGHT: f64 = 22.0;
+    const COLORS: [&str; 3] = ["#6366f1", "#0ea5e9", "#94a3b8"];
+
+    let width = COLUMN_WIDTH * (MINDMAP_DEPTH + 1) as f64;
+    let height = ROW_HEIGHT * rows;
+    let center = |node: &LaidOutNode| {
+        (10.0 + COLUMN_WIDTH * node.depth as f64, ROW_HEIGHT * node.y + ROW_HEIGHT / 2.0)
+    };
+
+    let mut svg = format!(
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{size}\" height=\"{size}\" viewBox=\"0 0 {w} {h}\" preserveAspectRatio=\"xMidYMid meet\">",
+        size = size,
+        w = width,
+        h = height
+    );
+    svg.push_str("<rect width=\"100%\" height=\"100%\" fill=\"#ffffff\"/>");
+
+    for node in &nodes {
+        if let Some(parent) = node.parent {
+            let (px, py) = center(&nodes[parent]);
+            let (x, y) = center(node);
+            svg.push_str(&format!(
+                "<path d=\"M{} {} C{} {} {} {} {} {}\" stroke=\"#cbd5e1\" fill=\"none\" stroke-width=\"1.5\"/>",
+                px + NODE_WIDTH, py, px + NODE_WIDTH + 10.0, py, x - 10.0, y, x, y
+            ));
+        }
+    }
+
+    for node in &nodes {
+        let (x, y) = center(node);
+        let color = COLORS[node.depth.min(COLORS.len() - 1)];
+        svg.push_str(&format!(
+            "<rect x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\" rx=\"6\" fill=\"{}\"/>",
+            x, y - NODE_HEIGHT / 2.0, NODE_WIDTH, NODE_HEIGHT, color
+        ));
+        svg.push_str(&format!(
+            "<text x=\"{}\" y=\"{}\" font-family=\"sans-serif\" font-size=\"11\" fill=\"#ffffff\">{}</text>",
+            x + 8.0, y + 4.0, xml_escape(&truncate(&node.title, 20))
+        ));
+    }
+
+    svg.push_str("</svg>");
+    svg
+}
+
+/// Lays out a subtree; leaves take one row each, parents sit between children
+fn layout(node: &OutlineNode, depth: usize, parent: usize, nodes: &mut Vec<LaidOutNode>, next_row: &mut f64) {
+    let index = nodes.len();
+    nodes.push(LaidOutNode { title: node.title.clone(), depth, y: 0.0, parent: Some(parent) });
+
+    if depth >= MINDMAP_DEPTH || node.children.is_empty() {
+        nodes[index].y = *next_row;
+        *next_row += 1.0;
+        return;
+    }
+
+    let first = nodes.len();
+    for child in &node.children {
+        layout(child, depth + 1, index, nodes, next_row);
+    }
+    let children: Vec<f64> = nodes[first..].iter().filter(|n| n.parent == Some(index)).map(|n| n.y).collect();
+    nodes[index].y = children.iter().sum::<f64>() / children.len() as f64;
+}
+
+fn truncate(text: &str, max_chars: usize) -> String {
+    if text.chars().count() <= max_chars {
+        return text.to_string();
+    }
+    let cut: String = text.chars().take(max_chars - 1).collect();
+    format!("{}…", cut.trim_end())
+}
+
+fn xml_escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+// ============================================================================
+// TESTS
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_mindmap_svg_draws_two_levels() {
+        let svg = render_mindmap_svg("# Trip\n## Packing\n### Clothes\n#### Socks\n## Route <A & B>\n", "trip", 200);
+
+        assert!(svg.starts_with("<svg"));
+        assert!(svg.contains(">Trip<"));
+        assert!(svg.contains(">Clothes<"));
+        assert!(!svg.contains("Socks"));
+        assert!(svg.contains("Route &lt;A &amp; B&gt;"));
+        assert_eq!(svg.matches("<path").count(), 3);
+    }
+
+    #[test]
+    fn test_note_thumbnail_is_cached() {
+        let dir = TempDir::new().unwrap();
+        let note = dir.path().join("plan.md");
+        fs::write(&note, "# Plan\n## Goals\n").unwrap();
+        let cache = dir.path().join("cache");
+
+        let first = thumbnail(&note, 128, &cache, true).unwrap();
+        assert_eq!(first.mime, "image/svg+xml");
+        assert!(first.data_uri.unwrap().starts_with("data:image/svg+xml;base64,"));
+
+        let second = thumbnail(&note, 128, &cache, false).unwrap();
+        assert_eq!(second.cache_path, first.cache_path);
+        assert_eq!(second.data_uri, None);
+        assert_eq!(fs::read_dir(&cache).unwrap().count(), 1);
+
+        assert!(thumbnail(&dir.path().join("doc.pdf"), 128, &cache, false).is_err());
+    }
+}
@@ -0,0 +1,171 @@
+//! Notes Timeline
+//!
+//! Buckets notes by the day, week, month or year they were created and
+//! modified, for calendar and timeline navigation. Creation dates come from
+//! the frontmatter `created` field when a note has one, else from the
+//! filesystem; modification dates from the filesystem. Everything is read
+//! from the metadata cache (see `crate::metadata_cache`).
+//!
+//! Buckets are computed in UTC; weeks start on Monday.
+
+use std::collections::BTreeMap;
+use chrono::{DateTime, Datelike, Duration, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use crate::metadata_cache::MetadataCache;
+
+/// Size of a timeline bucket
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Granularity {
+    #[default]
+    Day,
+    Week,
+    Month,
+    Year,
+}
+
+impl Granularity {
+    /// First day of the bucket containing `date`
+    fn bucket_start(self, date: NaiveDate) -> NaiveDate {
+        match self {
+            Granularity::Day => date,
+            Granularity::Week => date - Duration::days(date.weekday().num_days_from_monday() as i64),
+            Granularity::Month => date.with_day(1).unwrap_or(date),
+            Granularity::Year => NaiveDate::from_ymd_opt(date.year(), 1, 1).unwrap_or(date),
+        }
+    }
+}
+
+/// Dates to include (`YYYY-MM-DD`, both inclusive, either open)
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct TimelineRange {
+    #[serde(default)]
+    pub from: Option<String>,
+    #[serde(default)]
+    pub to: Option<String>,
+}
+
+impl TimelineRange {
+    /// Parses the bounds, failing on a malformed date
+    pub(crate) fn bounds(&self) -> Result<(Option<NaiveDate>, Option<NaiveDate>), String> {
+        let parse = |date: &Option<String>| {
+            date.as_deref()
+                .map(|d| NaiveDate::parse_from_str(d.trim(), "%Y-%m-%d").map_err(|_| format!("Invalid date: {}", d)))
+                .transpose()
+        };
+        Ok((parse(&self.from)?, parse(&self.to)?))
+    }
+}
+
+/// A note placed on the timeline
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct TimelineNote {
+    pub path: String,
+    pub title: String,
+    /// Time of the event (ms since the epoch)
+    pub at_ms: i64,
+}
+
+/// Notes created and modified within one bucket
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct TimelineBucket {
+    /// First day of the bucket (`YYYY-MM-DD`)
+    pub start: String,
+    /// Newest first
+    pub created: Vec<TimelineNote>,
+    /// Newest first
+    pub modified: Vec<TimelineNote>,
+}
+
+/// Buckets the notes of a cache, oldest bucket first; empty buckets are left out
+pub fn build_timeline(
+    cache: &MetadataCache,
+    granularity: Granularity,
+    range: &TimelineRange,
+) -> Result<Vec<TimelineBucket>, String> {
+    let (from, to) = range.bounds()?;
+    let mut buckets: BTreeMap<NaiveDate, TimelineBucket> = BTreeMap::new();
+
+    let mut place = |path: &str, title: &str, at_ms: i64, created: bool| {
+        let Some(date) = DateTime::<Utc>::from_timestamp_millis(at_ms).map(|t| t.date_naive()) else {
+            return;
+        };
+        if from.is_some_and(|from| date < from) || to.is_some_and(|to| date > to) {
+            return;
+        }
+        let start = granularity.bucket_start(date);
+        let bucket = buckets.entry(start).or_insert_with(|| TimelineBucket {
+            start: start.format("%Y-%m-%d").to_string(),
+            created: Vec::new(),
+            modified: Vec::new(),
+        });
+        let note = TimelineNote { path: path.to_string(), title: title.to_string(), at_ms };
+        if created {
+            bucket.created.push(note);
+        } else {
+            bucket.modified.push(note);
+        }
+    };
+
+    for (path, note) in &cache.notes {
+        if let Some(created_ms) = note.created_ms {
+            place(path, &note.title, created_ms, true);
+        }
+        place(path, &note.title, note.modified_ms, false);
+    }
+
+    let mut buckets: Vec<TimelineBucket> = buckets.into_values().collect();
+    for bucket in &mut buckets {
+        bucket.created.sort_by(|a, b| b.at_ms.cmp(&a.at_ms).then_with(|| a.path.cmp(&b.path)));
+        bucket.modified.sort_by(|a, b| b.at_ms.cmp(&a.at_ms).then_with(|| a.path.cmp(&b.path)));
+    }
+    Ok(buckets)
+}
+
+// ============================================================================
+// TESTS
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::metadata_cache::CachedNote;
+
+    const DAY_MS: i64 = 86_400_000;
+    /// Wednesday, 2023-11-15 00:00 UTC
+    const WED: i64 = 1_700_006_400_000;
+
+    fn cache() -> MetadataCache {
+        let mut cache = MetadataCache::default();
+        let note = |created_ms, modified_ms| CachedNote { created_ms, modified_ms, ..CachedNote::default() };
+        cache.notes.insert("a.md".into(), note(Some(WED - 2 * DAY_MS), WED));
+        cache.notes.insert("b.md".into(), note(None, WED + 5 * DAY_MS));
+        cache.notes.insert("old.md".into(), note(Some(WED - 400 * DAY_MS), WED - 400 * DAY_MS));
+        cache
+    }
+
+    #[test]
+    fn test_weekly_buckets() {
+        let buckets = build_timeline(&cache(), Granularity::Week, &TimelineRange::default()).unwrap();
+        let starts: Vec<&str> = buckets.iter().map(|b| b.start.as_str()).collect();
+        assert_eq!(starts, vec!["2022-10-10", "2023-11-13", "2023-11-20"]);
+
+        // Created on Monday, modified on Wednesday: same week
+        assert_eq!(buckets[1].created[0].path, "a.md");
+        assert_eq!(buckets[1].modified[0].path, "a.md");
+        assert_eq!(buckets[2].modified[0].path, "b.md");
+        assert!(buckets[2].created.is_empty());
+    }
+
+    #[test]
+    fn test_range_and_month_granularity() {
+        let range = TimelineRange { from: Some("2023-11-01".into()), to: Some("2023-11-15".into()) };
+        let buckets = build_timeline(&cache(), Granularity::Month, &range).unwrap();
+        assert_eq!(buckets.len(), 1);
+        assert_eq!(buckets[0].start, "2023-11-01");
+        assert_eq!((buckets[0].created.len(), buckets[0].modified.len()), (1, 1));
+
+        let bad = TimelineRange { from: Some("last week".into()), to: None };
+        assert_eq!(build_timeline(&cache(), Granularity::Day, &bad).unwrap_err(), "Invalid date: last week");
+    }
+}
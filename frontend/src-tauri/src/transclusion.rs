@@ -0,0 +1,246 @@
+//! Transclusion Resolution
+//!
+//! Expands note embeds for preview rendering and exports:
+//!
+//! - `![[Note]]` - the whole note (frontmatter dropped)
+//! - `![[Note#Heading]]` - the heading and its section
+//! - `![[Note#^block-id]]` - the line marked with `^block-id`
+//! - `![[#Heading]]` - a section of the embedding note itself
+//!
+//! Embeds are expanded recursively up to a depth limit. An embed that would
+//! include itself (directly or through other notes), points to a missing
+//! note or section, or lies beyond the depth limit is left as written.
+//! Embeds of non-markdown files (`![[diagram.png]]`) and embeds inside code
+//! are never touched.
+
+use std::fs;
+use std::path::Path;
+use crate::frontmatter;
+use crate::links::NoteLookup;
+use crate::notes::{collect_markdown_files, relative_path};
+use crate::outline;
+
+/// Nesting depth used when the caller does not specify one
+pub const DEFAULT_DEPTH: usize = 5;
+
+/// Upper bound for a caller-supplied depth
+pub const MAX_DEPTH: usize = 10;
+
+/// Expands the embeds of a workspace note
+///
+/// # Arguments
+/// * `workspace_root` - Workspace root used to resolve embed targets
+/// * `path` - Note to expand
+/// * `depth` - Maximum nesting depth (capped at `MAX_DEPTH`)
+pub fn resolve_transclusions(workspace_root: &Path, path: &Path, depth: usize) -> Result<String, String> {
+    let content = fs::read_to_string(path)
+        .map_err(|e| format!("Failed to read note: {}", e))?;
+
+    let paths = collect_markdown_files(workspace_root, workspace_root)?
+        .iter()
+        .map(|p| relative_path(workspace_root, p))
+        .collect();
+    let lookup = NoteLookup::new(paths);
+    let read = |relative: &str| fs::read_to_string(workspace_root.join(relative)).ok();
+
+    let from = relative_path(workspace_root, path);
+    Ok(expand(&content, &from, depth.min(MAX_DEPTH), &lookup, &read))
+}
+
+/// Expands embeds in `content`, which belongs to note `from`
+///
+/// `read` loads a note by its relative path.
+pub fn expand(
+    content: &str,
+    from: &str,
+    depth: usize,
+    lookup: &NoteLookup,
+    read: &dyn Fn(&str) -> Option<String>,
+) -> String {
+    let mut expander = Expander {
+        lookup,
+        read,
+        stack: vec![embed_key(from, None)],
+    };
+    expander.expand(content, from, depth)
+}
+
+struct Expander<'a> {
+    lookup: &'a NoteLookup,
+    read: &'a dyn Fn(&str) -> Option<String>,
+    /// Embeds currently being expanded (`path#fragment`), for cycle detection
+    stack: Vec<String>,
+}
+
+impl Expander<'_> {
+    fn expand(&mut self, content: &str, from: &str, depth: usize) -> String {
+        if depth == 0 || !content.contains("![[") {
+            return content.to_string();
+        }
+
+        let mut out = Vec::new();
+        let mut in_fence = false;
+
+        for line in content.split('\n') {
+            let trimmed = line.trim_start();
+            if trimmed.starts_with("```") || trimmed.starts_with("~~~") {
+                in_fence = !in_fence;
+            }
+            if in_fence || !line.contains("![[") {
+                out.push(line.to_string());
+                continue;
+            }
+            out.push(self.expand_line(line, from, depth));
+        }
+
+        out.join("\n")
+    }
+
+    fn expand_line(&mut self, line: &str, from: &str, depth: usize) -> String {
+        let mut out = String::new();
+        let mut rest = line;
+
+        while let Some(start) = rest.find("![[") {
+            let Some(len) = rest[start + 3..].find("]]") else { break };
+            let embed = &rest[start..start + 3 + len + 2];
+            out.push_str(&rest[..start]);
+
+            // Backticks before the embed leave it inside inline code when unbalanced
+            let in_code = out.matches('`').count() % 2 == 1;
+            let inner = &embed[3..embed.len() - 2];
+            match (!in_code).then(|| self.embed(inner, from, depth)).flatten() {
+                Some(expanded) => out.push_str(&expanded),
+                None => out.push_str(embed),
+            }
+            rest = &rest[start + embed.len()..];
+        }
+
+        out.push_str(rest);
+        out
+    }
+
+    /// Content of one embed, or `None` if it is left as written
+    fn embed(&mut self, inner: &str, from: &str, depth: usize) -> Option<String> {
+        let target = inner.split('|').next().unwrap_or("").trim();
+        let (note, fragment) = match target.split_once('#') {
+            Some((note, fragment)) => (note.trim(), Some(fragment.trim())),
+            None => (target, None),
+        };
+
+        if has_other_extension(note) {
+            return None;
+        }
+
+        let path = if note.is_empty() {
+            from.to_string()
+        } else {
+            let index = self.lookup.resolve_wiki(from, note)?;
+            self.lookup.paths[index].clone()
+        };
+
+        let key = embed_key(&path, fragment);
+        // Embedding a note into itself only makes sense for one of its sections
+        if self.stack.contains(&key) || (fragment.is_none() && path.eq_ignore_ascii_case(from)) {
+            log::warn!("⚠️ Transclusion cycle at ![[{}]] in {}", inner, from);
+            return None;
+        }
+
+        let content = (self.read)(&path)?;
+        let (_, body) = frontmatter::split(&content);
+        let included = match fragment {
+            Some(block) if block.starts_with('^') => find_block(body, block)?,
+            Some(heading) => outline::read_section_with_heading(body, heading).ok()?,
+            None => body.trim_matches('\n').to_string(),
+        };
+
+        self.stack.push(key);
+        let expanded = self.expand(&included, &path, depth - 1);
+        self.stack.pop();
+        Some(expanded)
+    }
+}
+
+fn embed_key(path: &str, fragment: Option<&str>) -> String {
+    format!("{}#{}", path.to_lowercase(), fragment.unwrap_or("").to_lowercase())
+}
+
+/// True for embeds of attachments (`image.png`, `doc.pdf`)
+fn has_other_extension(note: &str) -> bool {
+    let name = note.rsplit('/').next().unwrap_or(note);
+    match name.rsplit_once('.') {
+        Some((_, ext)) => !ext.eq_ignore_ascii_case("md") && !ext.is_empty() && !ext.contains(' '),
+        None => false,
+    }
+}
+
+/// Finds the line carrying a `^block-id` marker, without the marker
+fn find_block(body: &str, block: &str) -> Option<String> {
+    body.lines().find_map(|line| {
+        let text = line.trim_end().strip_suffix(block)?;
+        if text.is_empty() || text.ends_with(char::is_whitespace) {
+            Some(text.trim_end().to_string())
+        } else {
+            None
+        }
+    })
+}
+
+// ============================================================================
+// TESTS
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    fn run(notes: &[(&str, &str)], from: &str, depth: usize) -> String {
+        let files: HashMap<String, String> = notes
+            .iter()
+            .map(|(p, c)| (p.to_string(), c.to_string()))
+            .collect();
+        let lookup = NoteLookup::new(files.keys().cloned().collect());
+        let read = |p: &str| files.get(p).cloned();
+        expand(&files[from], from, depth, &lookup, &read)
+    }
+
+    #[test]
+    fn test_expand_note_section_and_block() {
+        let notes = [
+            ("main.md", "Intro\n![[recipe]]\n![[recipe#Steps]]\nSee ![[recipe#^tip]].\n![[photo.png]]"),
+            ("recipe.md", "---\ntags: [food]\n---\n# Recipe\n## Steps\n1. Mix\n## Tips\nUse salt ^tip\n"),
+        ];
+
+        assert_eq!(
+            run(&notes, "main.md", DEFAULT_DEPTH),
+            "Intro\n# Recipe\n## Steps\n1. Mix\n## Tips\nUse salt ^tip\n## Steps\n1. Mix\nSee Use salt.\n![[photo.png]]"
+        );
+    }
+
+    #[test]
+    fn test_nested_embeds_respect_depth() {
+        let notes = [("a.md", "A ![[b]]"), ("b.md", "B ![[c]]"), ("c.md", "C")];
+
+        assert_eq!(run(&notes, "a.md", 5), "A B C");
+        assert_eq!(run(&notes, "a.md", 1), "A B ![[c]]");
+        assert_eq!(run(&notes, "a.md", 0), "A ![[b]]");
+    }
+
+    #[test]
+    fn test_cycles_are_left_as_written() {
+        let notes = [
+            ("a.md", "# A\n![[b]]\n## Part\npart\n![[#Part]]"),
+            ("b.md", "B ![[a]]"),
+        ];
+
+        // The section embeds itself, so the inner embed stays as written
+        assert_eq!(run(&notes, "a.md", 5), "# A\nB ![[a]]\n## Part\npart\n## Part\npart\n![[#Part]]");
+    }
+
+    #[test]
+    fn test_code_and_missing_targets_untouched() {
+        let notes = [("a.md", "`![[b]]`\n```\n![[b]]\n```\n![[missing]] ![[b#Nope]]"), ("b.md", "B")];
+
+        assert_eq!(run(&notes, "a.md", 5), "`![[b]]`\n```\n![[b]]\n```\n![[missing]] ![[b#Nope]]");
+    }
+}
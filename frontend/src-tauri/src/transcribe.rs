@@ -0,0 +1,337 @@
+//! Offline Speech-to-Text
+//!
+//! Transcribes audio attachments locally with whisper.cpp (`whisper-rs`):
+//!
+//! - The model (`ggml-base.bin`, ~150 MB) is downloaded on first use into the
+//!   given model directory and reused afterwards. Downloads go to a `.part`
+//!   file first, so an interrupted download is never mistaken for a model,
+//!   and come from a pinned revision whose SHA-256 is checked before the
+//!   file is moved into place.
+//! - Audio is read from 16-bit PCM WAV (what voice memos are saved as), mixed
+//!   down to mono and resampled to the 16 kHz whisper expects.
+//! - The result is a list of timestamped segments that renders as a markdown
+//!   list for insertion under the audio link.
+
+use std::fs;
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use whisper_rs::{FullParams, SamplingStrategy, WhisperContext, WhisperContextParameters};
+
+/// File name of the whisper model
+pub const MODEL_FILE: &str = "ggml-base.bin";
+
+/// Where the model is downloaded from (a fixed revision, not `main`)
+const MODEL_URL: &str =
+    "https://huggingface.co/ggerganov/whisper.cpp/resolve/5359861c739e955e79d9a303bcbc70fb988958b1/ggml-base.bin";
+
+/// SHA-256 of the model at that revision
+const MODEL_SHA256: &str = "60ed5bc3dd14eea856493d334349b405782ddcaf0028d4b5df4088345fba2efe";
+
+/// Connecting to the model host gives up after this long
+const CONNECT_TIMEOUT_SECS: u64 = 30;
+
+/// The download gives up if no data arrives for this long
+const READ_TIMEOUT_SECS: u64 = 60;
+
+/// Sample rate whisper works at
+const WHISPER_SAMPLE_RATE: u32 = 16_000;
+
+/// Download chunk size (progress is reported per chunk)
+const DOWNLOAD_CHUNK: usize = 256 * 1024;
+
+/// A transcribed stretch of audio
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct TranscriptSegment {
+    pub start_ms: u64,
+    pub end_ms: u64,
+    pub text: String,
+}
+
+/// Result of a transcription
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Transcript {
+    /// Language requested (`auto` when detected)
+    pub language: String,
+    pub segments: Vec<TranscriptSegment>,
+    /// Segments as a markdown list, e.g. `- [00:42] Hello`
+    pub markdown: String,
+}
+
+impl Transcript {
+    /// Builds a transcript, dropping empty segments
+    pub fn new(language: &str, segments: Vec<TranscriptSegment>) -> Self {
+        let segments: Vec<TranscriptSegment> = segments
+            .into_iter()
+            .map(|s| TranscriptSegment { text: s.text.trim().to_string(), ..s })
+            .filter(|s| !s.text.is_empty())
+            .collect();
+        let markdown = segments
+            .iter()
+            .map(|s| format!("- [{}] {}", format_timestamp(s.start_ms), s.text))
+            .collect::<Vec<_>>()
+            .join("\n");
+        Self { language: language.to_string(), segments, markdown }
+    }
+}
+
+/// Returns the model path, downloading the model first if needed
+///
+/// `on_progress` receives downloaded and total bytes; the download stops
+/// when `cancelled` returns true.
+pub fn ensure_model(
+    model_dir: &Path,
+    on_progress: &mut dyn FnMut(u64, Option<u64>),
+    cancelled: &dyn Fn() -> bool,
+) -> Result<PathBuf, String> {
+    let model_path = model_dir.join(MODEL_FILE);
+    if model_path.is_file() {
+        return Ok(model_path);
+    }
+
+    fs::create_dir_all(model_dir)
+        .map_err(|e| format!("Failed to create model directory: {}", e))?;
+
+    log::info!("⬇️ Downloading speech model to {:?}", model_path);
+    let agent = ureq::AgentBuilder::new()
+        .timeout_connect(Duration::from_secs(CONNECT_TIMEOUT_SECS))
+        .timeout_read(Duration::from_secs(READ_TIMEOUT_SECS))
+        .build();
+    let response = agent
+        .get(MODEL_URL)
+        .call()
+        .map_err(|e| format!("Failed to download speech model: {}", e))?;
+    let total = response
+        .header("Content-Length")
+        .and_then(|length| length.parse::<u64>().ok());
+
+    let part_path = model_dir.join(format!("{}.part", MODEL_FILE));
+    let mut part = fs::File::create(&part_path)
+        .map_err(|e| format!("Failed to create model file: {}", e))?;
+    let mut reader = response.into_reader();
+    let mut buffer = vec![0u8; DOWNLOAD_CHUNK];
+    let mut downloaded = 0u64;
+    let mut hasher = Sha256::new();
+
+    loop {
+        if cancelled() {
+            drop(part);
+            let _ = fs::remove_file(&part_path);
+            return Err("Model download cancelled".to_string());
+        }
+        let read = reader
+            .read(&mut buffer)
+            .map_err(|e| format!("Failed to download speech model: {}", e))?;
+        if read == 0 {
+            break;
+        }
+        part.write_all(&buffer[..read])
+            .map_err(|e| format!("Failed to write model file: {}", e))?;
+        hasher.update(&buffer[..read]);
+        downloaded += read as u64;
+        on_progress(downloaded, total);
+    }
+
+    if total.is_some_and(|total| total != downloaded) {
+        let _ = fs::remove_file(&part_path);
+        return Err("Speech model download was incomplete".to_string());
+    }
+    let digest = format!("{:x}", hasher.finalize());
+    if !digest.eq_ignore_ascii_case(MODEL_SHA256) {
+        let _ = fs::remove_file(&part_path);
+        log::warn!("⚠️ Speech model checksum mismatch: {}", digest);
+        return Err("Speech model download is corrupt or was tampered with".to_string());
+    }
+
+    fs::rename(&part_path, &model_path)
+        .map_err(|e| format!("Failed to store speech model: {}", e))?;
+    Ok(model_path)
+}
+
+/// Transcribes 16 kHz mono samples
+///
+/// `language` is an ISO 639-1 code or `auto`; `on_progress` receives the
+/// percentage done.
+pub fn transcribe(
+    model_path: &Path,
+    samples: &[f32],
+    language: &str,
+    on_progress: impl FnMut(i32) + 'static,
+) -> Result<Transcript, String> {
+    let context = WhisperContext::new_with_params(
+        &model_path.to_string_lossy(),
+        WhisperContextParameters::default(),
+    )
+    .map_err(|e| format!("Failed to load speech model: {}", e))?;
+    let mut state = context
+        .create_state()
+        .map_err(|e| format!("Failed to load speech model: {}", e))?;
+
+    let mut params = FullParams::new(SamplingStrategy::Greedy { best_of: 1 });
+    params.set_language(Some(language));
+    params.set_print_progress(false);
+    params.set_print_realtime(false);
+    params.set_progress_callback_safe(on_progress);
+
+    state
+        .full(params, samples)
+        .map_err(|e| format!("Failed to transcribe audio: {}", e))?;
+
+    let count = state
+        .full_n_segments()
+        .map_err(|e| format!("Failed to read transcript: {}", e))?;
+    let mut segments = Vec::new();
+    for i in 0..count {
+        let read = |e| format!("Failed to read transcript: {}", e);
+        // whisper timestamps are in centiseconds
+        segments.push(TranscriptSegment {
+            start_ms: state.full_get_segment_t0(i).map_err(read)?.max(0) as u64 * 10,
+            end_ms: state.full_get_segment_t1(i).map_err(read)?.max(0) as u64 * 10,
+            text: state.full_get_segment_text(i).map_err(read)?,
+        });
+    }
+
+    Ok(Transcript::new(language, segments))
+}
+
+/// Decodes a 16-bit PCM WAV file to whisper input (16 kHz mono)
+pub fn wav_to_whisper_input(bytes: &[u8]) -> Result<Vec<f32>, String> {
+    let wav = read_wav(bytes)?;
+    Ok(resample(&wav.mono, wav.sample_rate, WHISPER_SAMPLE_RATE))
+}
+
+/// Mono samples of a WAV file
+struct Wav {
+    mono: Vec<f32>,
+    sample_rate: u32,
+}
+
+fn read_wav(bytes: &[u8]) -> Result<Wav, String> {
+    let invalid = || "Unsupported audio: expected a 16-bit PCM WAV file".to_string();
+    if bytes.len() < 12 || &bytes[0..4] != b"RIFF" || &bytes[8..12] != b"WAVE" {
+        return Err(invalid());
+    }
+
+    let u16_at = |at: usize| u16::from_le_bytes([bytes[at], bytes[at + 1]]);
+    let u32_at = |at: usize| u32::from_le_bytes([bytes[at], bytes[at + 1], bytes[at + 2], bytes[at + 3]]);
+
+    let mut format = None;
+    let mut offset = 12;
+    while offset + 8 <= bytes.len() {
+        let id = &bytes[offset..offset + 4];
+        let len = u32_at(offset + 4) as usize;
+        let body = offset + 8;
+        let end = body.saturating_add(len).min(bytes.len());
+
+        if id == b"fmt " && len >= 16 && end - body >= 16 {
+            // (format tag, channels, sample rate, bits per sample)
+            format = Some((u16_at(body), u16_at(body + 2), u32_at(body + 4), u16_at(body + 14)));
+        } else if id == b"data" {
+            let (tag, channels, sample_rate, bits) = format.ok_or_else(invalid)?;
+            if tag != 1 || bits != 16 || channels == 0 || sample_rate == 0 {
+                return Err(invalid());
+            }
+            let samples: Vec<f32> = bytes[body..end]
+                .chunks_exact(2)
+                .map(|b| i16::from_le_bytes([b[0], b[1]]) as f32 / 32768.0)
+                .collect();
+            let mono = samples
+                .chunks(channels as usize)
+                .map(|frame| frame.iter().sum::<f32>() / frame.len() as f32)
+                .collect();
+            return Ok(Wav { mono, sample_rate });
+        }
+
+        // Chunks are padded to an even length
+        offset = body + len + (len & 1);
+    }
+
+    Err(invalid())
+}
+
+/// Linear resampling between sample rates
+fn resample(samples: &[f32], from: u32, to: u32) -> Vec<f32> {
+    if from == to || samples.is_empty() {
+        return samples.to_vec();
+    }
+
+    let ratio = from as f64 / to as f64;
+    let length = (samples.len() as f64 / ratio).floor() as usize;
+    (0..length)
+        .map(|i| {
+            let position = i as f64 * ratio;
+            let index = position as usize;
+            let next = samples[(index + 1).min(samples.len() - 1)];
+            let fraction = (position - index as f64) as f32;
+            samples[index] + (next - samples[index]) * fraction
+        })
+        .collect()
+}
+
+/// `mm:ss`, or `h:mm:ss` from one hour on
+fn format_timestamp(ms: u64) -> String {
+    let seconds = ms / 1000;
+    let (hours, minutes, seconds) = (seconds / 3600, seconds / 60 % 60, seconds % 60);
+    if hours > 0 {
+        format!("{}:{:02}:{:02}", hours, minutes, seconds)
+    } else {
+        format!("{:02}:{:02}", minutes, seconds)
+    }
+}
+
+// ============================================================================
+// TESTS
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{Local, TimeZone};
+    use crate::audio::Recording;
+
+    #[test]
+    fn test_transcript_markdown() {
+        let segment = |start_ms, text: &str| TranscriptSegment { start_ms, end_ms: start_ms + 1000, text: text.to_string() };
+        let transcript = Transcript::new("en", vec![
+            segment(0, " Welcome everyone."),
+            segment(42_500, "  "),
+            segment(3_725_000, "Action items next."),
+        ]);
+
+        assert_eq!(transcript.segments.len(), 2);
+        assert_eq!(transcript.markdown, "- [00:00] Welcome everyone.\n- [1:02:05] Action items next.");
+    }
+
+    #[test]
+    fn test_wav_input_is_mono_16khz() {
+        let recording = Recording {
+            samples: [16384, 0, -16384, 0].repeat(16_000),
+            sample_rate: 32_000,
+            channels: 2,
+            started_at: Local.with_ymd_and_hms(2024, 5, 17, 9, 30, 0).unwrap(),
+        };
+
+        let input = wav_to_whisper_input(&recording.to_wav()).unwrap();
+        assert_eq!(input.len(), 16_000);
+        assert!(input.iter().all(|s| s.abs() <= 0.25));
+
+        assert!(wav_to_whisper_input(b"OggS not a wav").is_err());
+    }
+
+    #[test]
+    fn test_resample_halves_length() {
+        assert_eq!(resample(&[0.0, 1.0, 0.0, 1.0], 32_000, 16_000), vec![0.0, 0.0]);
+        assert_eq!(resample(&[0.5], 16_000, 16_000), vec![0.5]);
+    }
+
+    #[test]
+    fn test_existing_model_is_reused() {
+        let dir = tempfile::TempDir::new().unwrap();
+        fs::write(dir.path().join(MODEL_FILE), b"model").unwrap();
+
+        let path = ensure_model(dir.path(), &mut |_, _| panic!("no download expected"), &|| false).unwrap();
+        assert_eq!(path, dir.path().join(MODEL_FILE));
+    }
+}
@@ -5,7 +5,9 @@
 //! - Input sanitization helpers
 //! - Common error types
 
+use std::collections::HashSet;
 use std::path::{Path, PathBuf};
+use crate::workspace_settings::{SymlinkPolicy, WorkspaceSettings};
 
 /// Custom error types for utility functions
 #[derive(Debug, Clone, PartialEq)]
@@ -18,6 +20,8 @@ pub enum ValidationError {
     InvalidWorkspaceRoot { path: String, reason: String },
     /// Path contains invalid characters or patterns
     InvalidPathPattern { path: String, reason: String },
+    /// Path goes through a symbolic link that the workspace policy forbids
+    SymlinkNotAllowed { path: String, link: String },
 }
 
 impl std::fmt::Display for ValidationError {
@@ -35,6 +39,9 @@ impl std::fmt::Display for ValidationError {
             ValidationError::InvalidPathPattern { path, reason } => {
                 write!(f, "Invalid path pattern '{}': {}", path, reason)
             }
+            ValidationError::SymlinkNotAllowed { path, link } => {
+                write!(f, "Symlink not allowed: path '{}' goes through link '{}'", path, link)
+            }
         }
     }
 }
@@ -51,6 +58,9 @@ pub type ValidationResult<T> = Result<T, ValidationError>;
 /// 2. Canonicalizing both paths to resolve symlinks
 /// 3. Verifying the requested path starts with the workspace root
 /// 
+/// Symlinks are handled according to the workspace's `symlink_policy`
+/// (see `workspace_settings::SymlinkPolicy`), read from `.mdreader/settings.json`.
+/// 
 /// # Arguments
 /// * `requested_path` - The path requested by the frontend
 /// * `workspace_root` - The root directory of the workspace
@@ -81,6 +91,26 @@ pub type ValidationResult<T> = Result<T, ValidationError>;
 pub fn validate_path_within_workspace(
     requested_path: &str,
     workspace_root: &str,
+) -> ValidationResult<PathBuf> {
+    let policy = WorkspaceSettings::load_symlink_policy(Path::new(workspace_root));
+    validate_path_with_symlink_policy(requested_path, workspace_root, policy)
+}
+
+/// Same as `validate_path_within_workspace`, with an explicit symlink policy.
+/// 
+/// # Arguments
+/// * `requested_path` - The path requested by the frontend
+/// * `workspace_root` - The root directory of the workspace
+/// * `policy` - How to treat symlinks between the workspace root and the path
+/// 
+/// # Returns
+/// * `Ok(PathBuf)` - The canonicalized safe path
+/// * `Err(ValidationError)` - If the path is invalid, outside workspace or
+///   traverses a forbidden symlink
+pub fn validate_path_with_symlink_policy(
+    requested_path: &str,
+    workspace_root: &str,
+    policy: SymlinkPolicy,
 ) -> ValidationResult<PathBuf> {
     // Quick check for obvious traversal patterns
     if contains_traversal_pattern(requested_path) {
@@ -159,6 +189,21 @@ pub fn validate_path_within_workspace(
         parent_canonical.join(filename)
     };
     
+    // Apply the symlink policy to links below the workspace root
+    if let Some(link) = find_symlink_in_path(requested, workspace, &workspace_canonical) {
+        match policy {
+            SymlinkPolicy::Deny => {
+                return Err(ValidationError::SymlinkNotAllowed {
+                    path: requested_path.to_string(),
+                    link: link.to_string_lossy().to_string(),
+                });
+            }
+            // The link itself lives inside the workspace; its target may be anywhere
+            SymlinkPolicy::Follow => return Ok(requested_canonical),
+            SymlinkPolicy::AllowWithinWorkspace => {}
+        }
+    }
+    
     // Check if the requested path is within the workspace
     if !requested_canonical.starts_with(&workspace_canonical) {
         return Err(ValidationError::PathOutsideWorkspace {
@@ -236,6 +281,129 @@ pub fn validate_directory_path(
     Ok(path)
 }
 
+/// Validates a directory that is to be created along with its missing parents.
+/// 
+/// Only the nearest existing ancestor has to exist; it is validated like any
+/// other directory and the missing folders are appended to it.
+/// 
+/// # Arguments
+/// * `requested_path` - The directory path requested
+/// * `workspace_root` - The root directory of the workspace
+/// 
+/// # Returns
+/// * `Ok(PathBuf)` - The validated path, below the canonical ancestor
+/// * `Err(ValidationError)` - If validation of the ancestor fails
+pub fn validate_new_directory_path(
+    requested_path: &str,
+    workspace_root: &str,
+) -> ValidationResult<PathBuf> {
+    if contains_traversal_pattern(requested_path) {
+        return Err(ValidationError::InvalidPathPattern {
+            path: requested_path.to_string(),
+            reason: "Path contains directory traversal patterns".to_string(),
+        });
+    }
+    
+    // A dangling link counts as existing, so it is validated, not replaced
+    let mut ancestor = Path::new(requested_path);
+    let mut missing = Vec::new();
+    while std::fs::symlink_metadata(ancestor).is_err() {
+        match (ancestor.parent(), ancestor.file_name()) {
+            (Some(parent), Some(name)) => {
+                missing.push(name);
+                ancestor = parent;
+            }
+            _ => {
+                return Err(ValidationError::PathResolutionFailed {
+                    path: requested_path.to_string(),
+                    reason: "No parent directory exists".to_string(),
+                });
+            }
+        }
+    }
+    
+    let mut path = validate_directory_path(&ancestor.to_string_lossy(), workspace_root, true)?;
+    path.extend(missing.into_iter().rev());
+    Ok(path)
+}
+
+/// Finds the first symlink between the workspace root and the requested path.
+/// 
+/// Only components below the workspace root are inspected, so a workspace
+/// that itself lives behind a link (e.g. `/var` → `/private/var` on macOS)
+/// is not affected by the policy.
+fn find_symlink_in_path(requested: &Path, workspace: &Path, workspace_canonical: &Path) -> Option<PathBuf> {
+    let (base, relative) = match requested.strip_prefix(workspace) {
+        Ok(relative) => (workspace, relative),
+        Err(_) => (workspace_canonical, requested.strip_prefix(workspace_canonical).ok()?),
+    };
+    
+    let mut current = base.to_path_buf();
+    for component in relative.components() {
+        current.push(component);
+        match std::fs::symlink_metadata(&current) {
+            Ok(metadata) if metadata.file_type().is_symlink() => return Some(current),
+            Ok(_) => {}
+            // Not created yet - nothing further down can be a link
+            Err(_) => break,
+        }
+    }
+    
+    None
+}
+
+/// Metadata of an entry met while walking the workspace, or `None` if the
+/// walk must skip it.
+/// 
+/// Symbolic links are followed only when `is_symlink_visible` allows it, so
+/// a walk never leaves the workspace unless the policy says so. A directory
+/// already in `visited` (canonical paths of the directories entered so far,
+/// starting with the walk's root) is skipped, so link cycles end the walk.
+pub fn walk_metadata(
+    path: &Path,
+    workspace_canonical: &Path,
+    policy: SymlinkPolicy,
+    visited: &mut HashSet<PathBuf>,
+) -> Option<std::fs::Metadata> {
+    let metadata = std::fs::symlink_metadata(path).ok()?;
+    let metadata = if metadata.file_type().is_symlink() {
+        if !is_symlink_visible(path, workspace_canonical, policy) {
+            return None;
+        }
+        std::fs::metadata(path).ok()?
+    } else {
+        metadata
+    };
+    
+    if metadata.is_dir() && !visited.insert(path.canonicalize().ok()?) {
+        return None;
+    }
+    Some(metadata)
+}
+
+/// Decides whether a symlink found while listing a directory should be shown.
+/// 
+/// # Arguments
+/// * `link` - Path of the symlink itself
+/// * `workspace_canonical` - Canonicalized workspace root
+/// * `policy` - The workspace's symlink policy
+/// 
+/// # Returns
+/// `false` for denied or broken links, and for links pointing outside the
+/// workspace under `AllowWithinWorkspace`.
+pub fn is_symlink_visible(link: &Path, workspace_canonical: &Path, policy: SymlinkPolicy) -> bool {
+    let target = match link.canonicalize() {
+        Ok(target) => target,
+        Err(_) => return false,
+    };
+    
+    match policy {
+        SymlinkPolicy::Deny => false,
+        SymlinkPolicy::Follow => true,
+        SymlinkPolicy::AllowWithinWorkspace => target.starts_with(workspace_canonical),
+    }
+}
+
 /// Checks if a path string contains directory traversal patterns.
 /// 
 /// This is a quick pre-check before canonicalization.
@@ -267,6 +435,73 @@ fn contains_traversal_pattern(path: &str) -> bool {
     false
 }
 
+/// Matches a `/`-separated relative path against a simple glob pattern.
+/// 
+/// Supported syntax:
+/// - `*` matches any run of characters except `/`
+/// - `**` matches any run of characters, including `/`
+/// - `?` matches a single character except `/`
+/// 
+/// A pattern without `/` is matched against every path component, so
+/// `node_modules` or `*.tmp` match at any depth. A pattern containing `/`
+/// must match the whole relative path.
+/// 
+/// # Example
+/// ```rust,ignore
+/// assert!(matches_glob("*.tmp", "drafts/old.tmp"));
+/// assert!(matches_glob("drafts/**", "drafts/2024/a.md"));
+/// assert!(!matches_glob("drafts/*", "drafts/2024/a.md"));
+/// ```
+pub fn matches_glob(pattern: &str, relative_path: &str) -> bool {
+    let pattern = pattern.trim().trim_end_matches('/');
+    
+    if pattern.is_empty() {
+        return false;
+    }
+    
+    if pattern.contains('/') {
+        glob_match(pattern.as_bytes(), relative_path.as_bytes())
+    } else {
+        relative_path
+            .split('/')
+            .any(|component| glob_match(pattern.as_bytes(), component.as_bytes()))
+    }
+}
+
+/// Iterative wildcard matcher with backtracking for `*` and `**`
+fn glob_match(pattern: &[u8], text: &[u8]) -> bool {
+    let (mut p, mut t) = (0, 0);
+    // Position to resume from after the last star: (pattern index, text index, crosses '/')
+    let mut backtrack: Option<(usize, usize, bool)> = None;
+    
+    while t < text.len() {
+        if p < pattern.len() && pattern[p] == b'*' {
+            let double = pattern.get(p + 1) == Some(&b'*');
+            p += if double { 2 } else { 1 };
+            backtrack = Some((p, t, double));
+            continue;
+        }
+        
+        if p < pattern.len() && (pattern[p] == text[t] || (pattern[p] == b'?' && text[t] != b'/')) {
+            p += 1;
+            t += 1;
+            continue;
+        }
+        
+        match backtrack {
+            Some((star_p, star_t, crosses)) if crosses || text[star_t] != b'/' => {
+                backtrack = Some((star_p, star_t + 1, crosses));
+                p = star_p;
+                t = star_t + 1;
+            }
+            _ => return false,
+        }
+    }
+    
+    // Trailing stars match the empty remainder
+    pattern[p..].iter().all(|&c| c == b'*')
+}
+
 /// Sanitizes a filename by removing or replacing invalid characters.
 /// 
 /// # Arguments
@@ -305,6 +540,52 @@ pub fn sanitize_filename(filename: &str) -> String {
     sanitized
 }
 
+/// Encodes bytes as standard base64 with padding (for `data:` URIs)
+pub fn base64_encode(bytes: &[u8]) -> String {
+    const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+    let mut out = String::with_capacity(bytes.len().div_ceil(3) * 4);
+
+    for chunk in bytes.chunks(3) {
+        let b = [chunk[0], *chunk.get(1).unwrap_or(&0), *chunk.get(2).unwrap_or(&0)];
+        let n = (b[0] as u32) << 16 | (b[1] as u32) << 8 | b[2] as u32;
+        for i in 0..4 {
+            if i <= chunk.len() {
+                out.push(ALPHABET[(n >> (18 - 6 * i) & 0x3f) as usize] as char);
+            } else {
+                out.push('=');
+            }
+        }
+    }
+    out
+}
+
+/// Extensions the system would run rather than open
+const EXECUTABLE_EXTENSIONS: &[&str] = &[
+    "exe", "com", "bat", "cmd", "msi", "scr", "pif", "cpl", "lnk", "url", "reg", "hta",
+    "ps1", "vbs", "vbe", "js", "jse", "wsf", "wsh", "jar",
+    "app", "command", "tool", "workflow", "pkg", "sh", "desktop", "appimage", "run",
+];
+
+/// Checks if handing a path to the system's default application would run it
+/// 
+/// True for executable extensions (including `.app` bundles, which are
+/// folders) and, on Unix, for files without an extension that have an
+/// execute bit.
+pub fn is_executable(path: &Path) -> bool {
+    if let Some(ext) = path.extension() {
+        let ext = ext.to_string_lossy().to_lowercase();
+        return EXECUTABLE_EXTENSIONS.contains(&ext.as_str());
+    }
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        if let Ok(metadata) = std::fs::metadata(path) {
+            return metadata.is_file() && metadata.permissions().mode() & 0o111 != 0;
+        }
+    }
+    false
+}
+
 // ============================================================================
 // TESTS
 // ============================================================================
@@ -553,6 +834,25 @@ mod tests {
         assert!(result.is_err());
     }
 
+    #[test]
+    fn test_new_directory_below_missing_parents() {
+        let workspace = setup_test_workspace();
+        let workspace_path = workspace.path().to_str().unwrap();
+        let dir_path = workspace.path().join("notes/a/b/c");
+        
+        let result = validate_new_directory_path(dir_path.to_str().unwrap(), workspace_path).unwrap();
+        assert_eq!(result, workspace.path().canonicalize().unwrap().join("notes/a/b/c"));
+        
+        // Missing parents outside the workspace are still refused
+        let outside = TempDir::new().unwrap();
+        let outside_path = outside.path().join("a/b");
+        assert!(validate_new_directory_path(outside_path.to_str().unwrap(), workspace_path).is_err());
+        
+        // So is a file standing in for a parent
+        let under_file = workspace.path().join("test.md/a");
+        assert!(validate_new_directory_path(under_file.to_str().unwrap(), workspace_path).is_err());
+    }
+
     // -------------------------------------------------------------------------
     // contains_traversal_pattern tests
     // -------------------------------------------------------------------------
@@ -622,4 +922,155 @@ mod tests {
         assert!(display.contains("outside workspace"));
         assert!(display.contains("/etc/passwd"));
     }
+
+    // -------------------------------------------------------------------------
+    // Symlink Policy Tests
+    // -------------------------------------------------------------------------
+
+    #[cfg(unix)]
+    fn setup_workspace_with_links() -> (TempDir, TempDir) {
+        let workspace = setup_test_workspace();
+        let outside = TempDir::new().expect("Failed to create outside dir");
+        File::create(outside.path().join("external.md")).expect("Failed to create external.md");
+        
+        std::os::unix::fs::symlink(outside.path(), workspace.path().join("external"))
+            .expect("Failed to create outside link");
+        std::os::unix::fs::symlink(workspace.path().join("notes"), workspace.path().join("notes-link"))
+            .expect("Failed to create inside link");
+        
+        (workspace, outside)
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn test_symlink_policy_allow_within_workspace() {
+        let (workspace, _outside) = setup_workspace_with_links();
+        let root = workspace.path().to_str().unwrap();
+        let policy = SymlinkPolicy::AllowWithinWorkspace;
+        
+        let inside = workspace.path().join("notes-link/todo.md");
+        assert!(validate_path_with_symlink_policy(inside.to_str().unwrap(), root, policy).is_ok());
+        
+        let outside = workspace.path().join("external/external.md");
+        assert!(validate_path_with_symlink_policy(outside.to_str().unwrap(), root, policy).is_err());
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn test_symlink_policy_deny() {
+        let (workspace, _outside) = setup_workspace_with_links();
+        let root = workspace.path().to_str().unwrap();
+        
+        let inside = workspace.path().join("notes-link/todo.md");
+        let result = validate_path_with_symlink_policy(inside.to_str().unwrap(), root, SymlinkPolicy::Deny);
+        assert!(matches!(result, Err(ValidationError::SymlinkNotAllowed { .. })), "Deny should refuse any link");
+        
+        let regular = workspace.path().join("notes/todo.md");
+        assert!(validate_path_with_symlink_policy(regular.to_str().unwrap(), root, SymlinkPolicy::Deny).is_ok());
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn test_symlink_policy_follow() {
+        let (workspace, _outside) = setup_workspace_with_links();
+        let root = workspace.path().to_str().unwrap();
+        
+        let outside = workspace.path().join("external/external.md");
+        let result = validate_path_with_symlink_policy(outside.to_str().unwrap(), root, SymlinkPolicy::Follow);
+        assert!(result.is_ok(), "Follow should allow links pointing outside");
+        
+        // Traversal is still rejected
+        let attack = format!("{}/../../etc/passwd", root);
+        assert!(validate_path_with_symlink_policy(&attack, root, SymlinkPolicy::Follow).is_err());
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn test_is_symlink_visible() {
+        let (workspace, _outside) = setup_workspace_with_links();
+        let root = workspace.path().canonicalize().unwrap();
+        let inside = workspace.path().join("notes-link");
+        let outside = workspace.path().join("external");
+        
+        assert!(is_symlink_visible(&inside, &root, SymlinkPolicy::AllowWithinWorkspace));
+        assert!(!is_symlink_visible(&outside, &root, SymlinkPolicy::AllowWithinWorkspace));
+        assert!(is_symlink_visible(&outside, &root, SymlinkPolicy::Follow));
+        assert!(!is_symlink_visible(&inside, &root, SymlinkPolicy::Deny));
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn test_walk_metadata_skips_hidden_links_and_cycles() {
+        let (workspace, _outside) = setup_workspace_with_links();
+        let root = workspace.path().canonicalize().unwrap();
+        std::os::unix::fs::symlink(workspace.path(), workspace.path().join("notes").join("loop"))
+            .expect("Failed to create cycle");
+        let policy = SymlinkPolicy::AllowWithinWorkspace;
+        let mut visited = HashSet::from([root.clone()]);
+        
+        assert!(walk_metadata(&workspace.path().join("external"), &root, policy, &mut visited).is_none());
+        assert!(walk_metadata(&workspace.path().join("notes"), &root, policy, &mut visited).unwrap().is_dir());
+        assert!(walk_metadata(&workspace.path().join("notes").join("loop"), &root, policy, &mut visited).is_none());
+        // The link reaches `notes` a second time
+        assert!(walk_metadata(&workspace.path().join("notes-link"), &root, policy, &mut visited).is_none());
+        
+        let mut visited = HashSet::from([root.clone()]);
+        assert!(walk_metadata(&workspace.path().join("external"), &root, SymlinkPolicy::Follow, &mut visited).is_some());
+    }
+
+    // -------------------------------------------------------------------------
+    // Glob Matching Tests
+    // -------------------------------------------------------------------------
+
+    #[test]
+    fn test_matches_glob_component_patterns() {
+        assert!(matches_glob("node_modules", "project/node_modules/pkg/index.md"));
+        assert!(matches_glob("*.tmp", "drafts/old.tmp"));
+        assert!(matches_glob(".git", ".git/config"));
+        assert!(!matches_glob("*.tmp", "drafts/old.md"));
+        assert!(!matches_glob("node", "node_modules/readme.md"));
+    }
+
+    #[test]
+    fn test_matches_glob_path_patterns() {
+        assert!(matches_glob("drafts/*", "drafts/a.md"));
+        assert!(!matches_glob("drafts/*", "drafts/2024/a.md"), "Single star must not cross directories");
+        assert!(matches_glob("drafts/**", "drafts/2024/a.md"));
+        assert!(matches_glob("**/secret?.md", "a/b/secret1.md"));
+        assert!(!matches_glob("drafts/*", "other/drafts/a.md"));
+    }
+
+    #[test]
+    fn test_base64_encode() {
+        assert_eq!(base64_encode(b""), "");
+        assert_eq!(base64_encode(b"f"), "Zg==");
+        assert_eq!(base64_encode(b"fo"), "Zm8=");
+        assert_eq!(base64_encode(b"foobar"), "Zm9vYmFy");
+    }
+
+    #[test]
+    fn test_matches_glob_empty_pattern() {
+        assert!(!matches_glob("", "anything.md"));
+        assert!(!matches_glob("   ", "anything.md"));
+    }
+
+    #[test]
+    fn test_is_executable() {
+        let workspace = setup_test_workspace();
+        let root = workspace.path();
+        assert!(!is_executable(&root.join("notes/todo.md")));
+        assert!(is_executable(&root.join("setup.EXE")));
+        assert!(is_executable(&root.join("Tool.app")));
+        assert!(!is_executable(&root.join("notes")));
+
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let script = root.join("notes/run");
+            fs::write(&script, "#!/bin/sh").unwrap();
+            assert!(!is_executable(&script));
+            fs::set_permissions(&script, fs::Permissions::from_mode(0o755)).unwrap();
+            assert!(is_executable(&script));
+        }
+    }
 }
@@ -0,0 +1,152 @@
+//! Vault Health Report
+//!
+//! Renders a markdown note describing the state of a workspace: totals,
+//! broken links, orphan notes, the largest notes and the recently modified
+//! ones. The report is written into the workspace, so committing it over
+//! time gives a diffable history of the vault's health.
+//!
+//! Note paths are written as inline code rather than links, so a report does
+//! not link to the notes it lists (which would stop them being orphans in
+//! the next report). The report itself is left out of its own statistics.
+//! Output is sorted, so two reports of an unchanged vault differ only in
+//! their `generated` date.
+
+use chrono::{DateTime, Utc};
+use crate::metadata_cache::MetadataCache;
+use crate::overview::WorkspaceOverview;
+
+/// Entries listed per section before the rest is summarized
+pub const REPORT_LIST_LIMIT: usize = 100;
+
+/// Renders the report of a cache at `now_ms` (ms since the epoch)
+///
+/// `report_path` is the relative path the report is written to.
+pub fn render_report(cache: &MetadataCache, report_path: &str, now_ms: i64) -> String {
+    let mut cache = cache.clone();
+    cache.notes.remove(report_path);
+    let overview = WorkspaceOverview::from_cache(&cache, now_ms);
+
+    let lookup = cache.lookup();
+    let broken: Vec<(String, String)> = cache.notes
+        .iter()
+        .flat_map(|(path, note)| {
+            note.links
+                .unresolved(&lookup, path)
+                .into_iter()
+                .map(move |target| (path.clone(), target))
+        })
+        .collect();
+
+    let index = cache.link_index();
+    let orphans: Vec<&String> = (0..index.paths.len())
+        .filter(|&i| index.incoming[i].is_empty() && index.outgoing[i].is_empty())
+        .map(|i| &index.paths[i])
+        .collect();
+
+    let mut recent: Vec<(&String, i64)> = cache.notes.iter().map(|(path, note)| (path, note.modified_ms)).collect();
+    recent.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(b.0)));
+    recent.truncate(10);
+
+    let mut out = format!("---\ngenerated: {}\n---\n# Vault Report\n\n", format_date(now_ms, "%Y-%m-%d"));
+
+    out.push_str("## Statistics\n\n| Metric | Value |\n| --- | --- |\n");
+    let rows = [
+        ("Notes", overview.total_notes.to_string()),
+        ("Words", overview.total_words.to_string()),
+        ("Links", index.edge_count().to_string()),
+        ("Broken links", broken.len().to_string()),
+        ("Orphan notes", orphans.len().to_string()),
+        ("Tags", overview.tags.len().to_string()),
+        ("Assets", format!("{} files, {}", overview.assets.files, format_bytes(overview.assets.bytes))),
+    ];
+    for (metric, value) in rows {
+        out.push_str(&format!("| {} | {} |\n", metric, value));
+    }
+
+    out.push_str("\n## Broken Links\n\n");
+    push_list(&mut out, broken.iter().map(|(from, target)| format!("`{}` → `{}`", from, target)));
+
+    out.push_str("\n## Orphan Notes\n\n");
+    push_list(&mut out, orphans.iter().map(|path| format!("`{}`", path)));
+
+    out.push_str("\n## Largest Notes\n\n| Note | Words |\n| --- | --- |\n");
+    for note in &overview.largest_notes {
+        out.push_str(&format!("| `{}` | {} |\n", note.path, note.value));
+    }
+
+    out.push_str("\n## Recently Modified\n\n");
+    push_list(&mut out, recent.iter().map(|(path, ms)| format!("`{}` ({})", path, format_date(*ms, "%Y-%m-%d %H:%M"))));
+
+    out
+}
+
+// Helper: Appends a bullet list, capped at `REPORT_LIST_LIMIT` entries
+fn push_list(out: &mut String, items: impl Iterator<Item = String>) {
+    let items: Vec<String> = items.collect();
+    if items.is_empty() {
+        out.push_str("None.\n");
+        return;
+    }
+    for item in items.iter().take(REPORT_LIST_LIMIT) {
+        out.push_str(&format!("- {}\n", item));
+    }
+    if items.len() > REPORT_LIST_LIMIT {
+        out.push_str(&format!("- …and {} more\n", items.len() - REPORT_LIST_LIMIT));
+    }
+}
+
+fn format_date(ms: i64, format: &str) -> String {
+    DateTime::<Utc>::from_timestamp_millis(ms)
+        .map(|t| t.format(format).to_string())
+        .unwrap_or_default()
+}
+
+fn format_bytes(bytes: u64) -> String {
+    match bytes {
+        0..=1023 => format!("{} B", bytes),
+        1024..=1_048_575 => format!("{:.1} KB", bytes as f64 / 1024.0),
+        _ => format!("{:.1} MB", bytes as f64 / 1_048_576.0),
+    }
+}
+
+// ============================================================================
+// TESTS
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::links::NoteLinks;
+    use crate::metadata_cache::CachedNote;
+
+    /// 2023-11-15 00:00 UTC
+    const NOW: i64 = 1_700_006_400_000;
+
+    fn note(words: usize, wiki: &[&str]) -> CachedNote {
+        CachedNote {
+            modified_ms: NOW,
+            words,
+            links: NoteLinks { wiki: wiki.iter().map(|t| t.to_string()).collect(), relative: Vec::new() },
+            ..CachedNote::default()
+        }
+    }
+
+    #[test]
+    fn test_report_lists_broken_links_and_orphans() {
+        let mut cache = MetadataCache::default();
+        cache.notes.insert("a.md".into(), note(100, &["b", "missing"]));
+        cache.notes.insert("b.md".into(), note(20, &[]));
+        cache.notes.insert("lonely.md".into(), note(5, &[]));
+        cache.notes.insert("Reports/vault.md".into(), note(500, &["lonely"]));
+
+        let report = render_report(&cache, "Reports/vault.md", NOW);
+
+        assert!(report.starts_with("---\ngenerated: 2023-11-15\n---\n# Vault Report\n"));
+        assert!(report.contains("| Notes | 3 |"));
+        assert!(report.contains("| Broken links | 1 |"));
+        assert!(report.contains("- `a.md` → `missing`"));
+        assert!(report.contains("## Orphan Notes\n\n- `lonely.md`\n"));
+        assert!(report.contains("| `a.md` | 100 |"));
+        assert!(!report.contains("Reports/vault.md"));
+    }
+}
@@ -0,0 +1,403 @@
+//! vCard Import
+//!
+//! Turns contacts exported as vCards (`.vcf`, versions 2.1, 3.0 and 4.0)
+//! into person pages (see `crate::people`): one note per contact, with the
+//! contact details as frontmatter fields and the vCard `NOTE` as body.
+//!
+//! Parsing covers what address books actually export: folded lines, item
+//! groups (`item1.EMAIL`), quoted-printable values of version 2.1, escaped
+//! `,` `;` and newlines, and `tel:` / `mailto:` URIs. Photos, keys and
+//! other binary properties are skipped.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use serde_json::{Map, Value};
+use crate::bear::{unique_stem, NotesImportReport};
+use crate::frontmatter;
+use crate::people::{self, PERSON_TYPE};
+use crate::utils::sanitize_filename;
+
+/// File extensions of vCard files
+const VCARD_EXTENSIONS: &[&str] = &["vcf", "vcard"];
+
+/// A contact parsed from a vCard
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Contact {
+    /// Formatted name (`FN`, or built from `N`)
+    pub name: String,
+    pub nicknames: Vec<String>,
+    pub emails: Vec<String>,
+    pub phones: Vec<String>,
+    pub organization: Option<String>,
+    /// Job title (`TITLE`, or `ROLE`)
+    pub role: Option<String>,
+    /// `YYYY-MM-DD`, or `--MM-DD` without a year
+    pub birthday: Option<String>,
+    /// First postal address, components joined by `, `
+    pub address: Option<String>,
+    pub urls: Vec<String>,
+    pub note: Option<String>,
+}
+
+/// Imports a `.vcf` file, or every vCard file of a folder, as person pages
+pub fn import_vcards(source: &Path, dest: &Path) -> Result<NotesImportReport, String> {
+    let files = vcard_files(source)?;
+    if files.is_empty() {
+        return Err(format!("No vCard files found in {}", source.display()));
+    }
+
+    let mut report = NotesImportReport {
+        dest_path: dest.to_string_lossy().to_string(),
+        ..Default::default()
+    };
+    fs::create_dir_all(dest).map_err(|e| format!("Failed to create directory: {}", e))?;
+
+    for file in files {
+        let text = match fs::read(&file) {
+            Ok(bytes) => String::from_utf8_lossy(&bytes).to_string(),
+            Err(e) => {
+                log::warn!("⚠️ Skipping vCard file {}: {}", file.display(), e);
+                report.skipped.push(format!("{}: {}", file.display(), e));
+                continue;
+            }
+        };
+
+        for contact in parse_vcards(&text) {
+            let stem = unique_stem(dest, &sanitize_filename(&contact.name));
+            let name = format!("{}.md", stem);
+            match fs::write(dest.join(&name), contact_note(&contact)) {
+                Ok(()) => report.notes.push(name),
+                Err(e) => report.skipped.push(format!("{}: {}", contact.name, e)),
+            }
+        }
+    }
+
+    if report.notes.is_empty() && report.skipped.is_empty() {
+        return Err(format!("No contacts found in {}", source.display()));
+    }
+    Ok(report)
+}
+
+/// Parses every `BEGIN:VCARD` … `END:VCARD` block of `text`
+///
+/// Contacts without a name, e-mail address or organization are skipped;
+/// a contact without a name is named after the first of the others.
+pub fn parse_vcards(text: &str) -> Vec<Contact> {
+    let mut contacts = Vec::new();
+    let mut current: Option<Contact> = None;
+
+    for line in unfold(text) {
+        let Some((name, params, value)) = split_property(&line) else {
+            continue;
+        };
+        match name.as_str() {
+            "BEGIN" if value.eq_ignore_ascii_case("VCARD") => current = Some(Contact::default()),
+            "END" if value.eq_ignore_ascii_case("VCARD") => {
+                if let Some(mut contact) = current.take() {
+                    if contact.name.is_empty() {
+                        contact.name = contact.emails.first().or(contact.organization.as_ref()).cloned().unwrap_or_default();
+                    }
+                    if !contact.name.is_empty() {
+                        contacts.push(contact);
+                    }
+                }
+            }
+            _ => {
+                if let Some(contact) = current.as_mut() {
+                    let value = if is_quoted_printable(&params) { decode_quoted_printable(&value) } else { value };
+                    apply_property(contact, &name, &value);
+                }
+            }
+        }
+    }
+    contacts
+}
+
+/// Renders a contact as a person page: frontmatter fields and `# Name`
+pub fn contact_note(contact: &Contact) -> String {
+    let mut fields = Map::new();
+    fields.insert("type".to_string(), Value::from(PERSON_TYPE));
+    fields.insert("name".to_string(), Value::from(contact.name.as_str()));
+    fields.insert("handle".to_string(), Value::from(people::handle(&contact.name)));
+    if !contact.nicknames.is_empty() {
+        fields.insert("aliases".to_string(), Value::from(contact.nicknames.clone()));
+    }
+    for (key, values) in [("email", &contact.emails), ("phone", &contact.phones), ("url", &contact.urls)] {
+        match values.as_slice() {
+            [] => {}
+            [value] => {
+                fields.insert(key.to_string(), Value::from(value.as_str()));
+            }
+            values => {
+                fields.insert(key.to_string(), Value::from(values.to_vec()));
+            }
+        }
+    }
+    for (key, value) in [
+        ("organization", &contact.organization),
+        ("role", &contact.role),
+        ("birthday", &contact.birthday),
+        ("address", &contact.address),
+    ] {
+        if let Some(value) = value {
+            fields.insert(key.to_string(), Value::from(value.as_str()));
+        }
+    }
+
+    let mut note = format!("{}# {}\n", frontmatter::render(&fields), contact.name);
+    if let Some(text) = &contact.note {
+        note.push_str(&format!("\n{}\n", text.trim_end()));
+    }
+    note
+}
+
+// Helper: The source file itself, or the vCard files of a folder by name
+fn vcard_files(source: &Path) -> Result<Vec<PathBuf>, String> {
+    if source.is_file() {
+        return Ok(vec![source.to_path_buf()]);
+    }
+    let entries = fs::read_dir(source).map_err(|e| format!("Failed to read directory: {}", e))?;
+    let mut files: Vec<PathBuf> = entries
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| {
+            path.is_file()
+                && path
+                    .extension()
+                    .and_then(|ext| ext.to_str())
+                    .is_some_and(|ext| VCARD_EXTENSIONS.iter().any(|known| ext.eq_ignore_ascii_case(known)))
+        })
+        .collect();
+    files.sort();
+    Ok(files)
+}
+
+// Helper: Joins folded lines (continuations start with a space or tab) and
+// quoted-printable soft line breaks (a line ending in `=`)
+fn unfold(text: &str) -> Vec<String> {
+    let mut lines: Vec<String> = Vec::new();
+    for raw in text.lines() {
+        let last = lines.last_mut();
+        match last {
+            Some(last) if raw.starts_with([' ', '\t']) => last.push_str(&raw[1..]),
+            Some(last) if last.ends_with('=') && is_quoted_printable(last.split(':').next().unwrap_or("")) => {
+                last.pop();
+                last.push_str(raw);
+            }
+            _ => lines.push(raw.to_string()),
+        }
+    }
+    lines
+}
+
+// Helper: Splits `group.NAME;PARAMS:value` into (NAME, PARAMS, value)
+fn split_property(line: &str) -> Option<(String, String, String)> {
+    // The value starts at the first `:` outside a quoted parameter value
+    let mut quoted = false;
+    let colon = line.char_indices().find_map(|(i, c)| match c {
+        '"' => {
+            quoted = !quoted;
+            None
+        }
+        ':' if !quoted => Some(i),
+        _ => None,
+    })?;
+    let (head, value) = (&line[..colon], &line[colon + 1..]);
+    let (name, params) = head.split_once(';').unwrap_or((head, ""));
+    let name = name.rsplit('.').next().unwrap_or(name).trim().to_uppercase();
+    Some((name, params.to_string(), value.to_string()))
+}
+
+fn is_quoted_printable(params: &str) -> bool {
+    params.to_uppercase().contains("QUOTED-PRINTABLE")
+}
+
+fn decode_quoted_printable(value: &str) -> String {
+    let bytes = value.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let hex = bytes.get(i + 1..i + 3).and_then(|hex| std::str::from_utf8(hex).ok());
+        match (bytes[i], hex.and_then(|hex| u8::from_str_radix(hex, 16).ok())) {
+            (b'=', Some(byte)) => {
+                out.push(byte);
+                i += 3;
+            }
+            (byte, _) => {
+                out.push(byte);
+                i += 1;
+            }
+        }
+    }
+    String::from_utf8_lossy(&out).to_string()
+}
+
+fn apply_property(contact: &mut Contact, name: &str, value: &str) {
+    let text = unescape(value).trim().to_string();
+    match name {
+        "FN" if !text.is_empty() => contact.name = text,
+        "N" if contact.name.is_empty() => {
+            // Family;Given;Additional;Prefix;Suffix
+            let parts = split_components(value, ';');
+            let words: Vec<&str> = [1, 2, 0]
+                .iter()
+                .filter_map(|&i| parts.get(i).map(String::as_str))
+                .filter(|s| !s.is_empty())
+                .collect();
+            contact.name = words.join(" ");
+        }
+        "NICKNAME" => contact.nicknames.extend(split_components(value, ',').into_iter().filter(|s| !s.is_empty())),
+        "EMAIL" => push_unique(&mut contact.emails, strip_scheme(&text, "mailto:")),
+        "TEL" => push_unique(&mut contact.phones, strip_scheme(&text, "tel:")),
+        "URL" => push_unique(&mut contact.urls, text),
+        "ORG" => contact.organization = joined(split_components(value, ';')),
+        "TITLE" if !text.is_empty() => contact.role = Some(text),
+        "ROLE" if contact.role.is_none() && !text.is_empty() => contact.role = Some(text),
+        "BDAY" => contact.birthday = normalize_birthday(&text),
+        "ADR" if contact.address.is_none() => contact.address = joined(split_components(value, ';')),
+        "NOTE" if !text.is_empty() => contact.note = Some(text),
+        _ => {}
+    }
+}
+
+fn push_unique(values: &mut Vec<String>, value: String) {
+    if !value.is_empty() && !values.contains(&value) {
+        values.push(value);
+    }
+}
+
+fn strip_scheme(text: &str, scheme: &str) -> String {
+    match text.get(..scheme.len()) {
+        Some(prefix) if prefix.eq_ignore_ascii_case(scheme) => text[scheme.len()..].to_string(),
+        _ => text.to_string(),
+    }
+}
+
+fn joined(parts: Vec<String>) -> Option<String> {
+    let parts: Vec<String> = parts.into_iter().filter(|s| !s.is_empty()).collect();
+    (!parts.is_empty()).then(|| parts.join(", "))
+}
+
+// Helper: `19850412` and `1985-04-12T00:00:00Z` → `1985-04-12`; `--0412` → `--04-12`
+fn normalize_birthday(text: &str) -> Option<String> {
+    let date = text.split('T').next().unwrap_or(text).trim();
+    let (prefix, digits) = match date.strip_prefix("--") {
+        Some(rest) => ("--", rest),
+        None => ("", date),
+    };
+    let normalized = match (prefix, digits.len()) {
+        _ if !digits.bytes().all(|b| b.is_ascii_digit()) => date.to_string(),
+        ("", 8) => format!("{}-{}-{}", &digits[..4], &digits[4..6], &digits[6..]),
+        ("--", 4) => format!("--{}-{}", &digits[..2], &digits[2..]),
+        _ => date.to_string(),
+    };
+    (!normalized.is_empty()).then_some(normalized)
+}
+
+/// Splits a structured value at unescaped `separator`s, unescaping each part
+fn split_components(value: &str, separator: char) -> Vec<String> {
+    let mut parts = Vec::new();
+    let mut part = String::new();
+    let mut chars = value.chars();
+    while let Some(c) = chars.next() {
+        if c == '\\' {
+            part.push(c);
+            if let Some(next) = chars.next() {
+                part.push(next);
+            }
+        } else if c == separator {
+            parts.push(unescape(&part).trim().to_string());
+            part.clear();
+        } else {
+            part.push(c);
+        }
+    }
+    parts.push(unescape(&part).trim().to_string());
+    parts
+}
+
+/// `\n` → newline, `\,` → `,`, `\;` → `;`, `\\` → `\`
+fn unescape(value: &str) -> String {
+    let mut out = String::with_capacity(value.len());
+    let mut chars = value.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            out.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('n' | 'N') => out.push('\n'),
+            Some(other) => out.push(other),
+            None => out.push('\\'),
+        }
+    }
+    out
+}
+
+// ============================================================================
+// TESTS
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    const CARDS: &str = "BEGIN:VCARD\r\nVERSION:3.0\r\nFN:Ann Smith\r\nN:Smith;Ann;;;\r\n\
+        NICKNAME:Annie,A. Smith\r\nitem1.EMAIL;type=INTERNET;type=pref:ann@example.com\r\n\
+        item2.EMAIL:mailto:ann@home.org\r\nTEL;TYPE=CELL:tel:+1 555 0100\r\nORG:Acme\\, Inc.;Research\r\n\
+        TITLE:Lead\r\nBDAY:19850412\r\nADR;TYPE=WORK:;;1 Main St;Spring\r\n field;IL;62701;USA\r\n\
+        NOTE:Met at the conference.\\nLikes tea.\r\nPHOTO;ENCODING=b;TYPE=JPEG:AAAA\r\nEND:VCARD\r\n\
+        BEGIN:VCARD\r\nVERSION:2.1\r\nN;CHARSET=UTF-8;ENCODING=QUOTED-PRINTABLE:M=C3=BCller;J=C3=B6rg\r\n\
+        NOTE;ENCODING=QUOTED-PRINTABLE:First line=0D=0A=\r\nsecond line\r\nEND:VCARD\r\n\
+        BEGIN:VCARD\r\nVERSION:4.0\r\nEMAIL:nobody@example.com\r\nEND:VCARD\r\n\
+        BEGIN:VCARD\r\nVERSION:4.0\r\nTEL:123\r\nEND:VCARD\r\n";
+
+    #[test]
+    fn test_parse_vcards() {
+        let contacts = parse_vcards(CARDS);
+        assert_eq!(contacts.len(), 3);
+
+        let ann = &contacts[0];
+        assert_eq!(ann.name, "Ann Smith");
+        assert_eq!(ann.nicknames, ["Annie", "A. Smith"]);
+        assert_eq!(ann.emails, ["ann@example.com", "ann@home.org"]);
+        assert_eq!(ann.phones, ["+1 555 0100"]);
+        assert_eq!(ann.organization.as_deref(), Some("Acme, Inc., Research"));
+        assert_eq!(ann.role.as_deref(), Some("Lead"));
+        assert_eq!(ann.birthday.as_deref(), Some("1985-04-12"));
+        assert_eq!(ann.address.as_deref(), Some("1 Main St, Springfield, IL, 62701, USA"));
+        assert_eq!(ann.note.as_deref(), Some("Met at the conference.\nLikes tea."));
+
+        assert_eq!(contacts[1].name, "Jörg Müller");
+        assert_eq!(contacts[1].note.as_deref(), Some("First line\r\nsecond line"));
+        assert_eq!(contacts[2].name, "nobody@example.com");
+    }
+
+    #[test]
+    fn test_import_vcards() {
+        let source = TempDir::new().unwrap();
+        let dest = TempDir::new().unwrap();
+        fs::write(source.path().join("contacts.vcf"), CARDS).unwrap();
+        fs::write(source.path().join("more.VCF"), "BEGIN:VCARD\nFN:Ann Smith\nEND:VCARD\n").unwrap();
+        fs::write(source.path().join("notes.txt"), "BEGIN:VCARD\nFN:Ignored\nEND:VCARD\n").unwrap();
+
+        let report = import_vcards(source.path(), dest.path()).unwrap();
+        assert_eq!(report.notes, ["Ann Smith.md", "Jörg Müller.md", "nobody@example.com.md", "Ann Smith 2.md"]);
+
+        let content = fs::read_to_string(dest.path().join("Ann Smith.md")).unwrap();
+        assert!(content.ends_with("# Ann Smith\n\nMet at the conference.\nLikes tea.\n"));
+        let (fields, body) = frontmatter::split(&content);
+        let fields = fields.unwrap();
+        assert_eq!(fields["email"], Value::from(vec!["ann@example.com", "ann@home.org"]));
+        assert_eq!(fields["phone"], Value::from("+1 555 0100"));
+        assert_eq!(fields["organization"], Value::from("Acme, Inc., Research"));
+        assert_eq!(
+            people::person_handles("Ann Smith.md", "Ann Smith", &fields),
+            ["ann-smith", "annie", "a-smith"]
+        );
+        assert!(body.starts_with("# Ann Smith"));
+
+        assert!(import_vcards(&source.path().join("missing"), dest.path()).is_err());
+    }
+}
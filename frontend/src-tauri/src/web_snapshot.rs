@@ -0,0 +1,289 @@
+//! Web Page Snapshots
+//!
+//! Local copies of pages linked from notes, so research notes survive link
+//! rot. A snapshot keeps the readable part of a page: the `<article>` or
+//! `<main>` element (else the body without navigation, sidebars and
+//! footers) is converted to markdown (see `convert::html_to_markdown`) and
+//! rendered into a standalone HTML file (see `crate::render`). Scripts,
+//! styles and raw HTML are dropped on the way, so snapshots are safe to
+//! open in the preview. Relative links and images point back to the site.
+//!
+//! Snapshots live in the `web` folder of the note's attachment folder
+//! (`assets/web/` by default) and the note gets an "archived copy" link
+//! next to the URL.
+
+use std::io::Read;
+use std::time::Duration;
+use chrono::NaiveDate;
+use crate::capture::{self, CapturePosition};
+use crate::convert::html_to_markdown;
+use crate::frontmatter;
+use crate::link_check::{self, external_links, MAX_REDIRECTS};
+use crate::link_preview::public_addresses;
+use crate::links::NoteLookup;
+use crate::render::{html_document, render_markdown};
+
+/// Folder of snapshots inside the attachment folder
+pub const SNAPSHOT_FOLDER: &str = "web";
+
+/// Label of the link to a snapshot
+pub const ARCHIVED_LABEL: &str = "archived copy";
+
+/// Pages larger than this are cut off
+const MAX_PAGE_BYTES: u64 = 5 * 1024 * 1024;
+
+/// Longest title part of a snapshot file name
+const MAX_NAME_CHARS: usize = 60;
+
+/// Elements left out of a page without `<article>` or `<main>`
+const BOILERPLATE: &[&str] = &["nav", "header", "footer", "aside", "form", "noscript", "svg"];
+
+const USER_AGENT: &str = concat!("MDReader/", env!("CARGO_PKG_VERSION"), " (snapshot)");
+
+/// A downloaded page
+#[derive(Debug, Clone, PartialEq)]
+pub struct WebPage {
+    /// URL after redirects
+    pub url: String,
+    pub html: String,
+}
+
+/// Downloads an HTML page, following redirects; only public addresses
+/// are reached (see `link_preview::public_addresses`)
+pub fn fetch_page(url: &str, timeout: Duration) -> Result<WebPage, String> {
+    let agent = ureq::AgentBuilder::new()
+        .redirects(MAX_REDIRECTS as u32)
+        .timeout(timeout)
+        .user_agent(USER_AGENT)
+        .resolver(public_addresses)
+        .build();
+    let response = agent.get(url).call().map_err(|e| match e {
+        ureq::Error::Status(status, _) => format!("Failed to fetch page: HTTP {}", status),
+        ureq::Error::Transport(e) => format!("Failed to fetch page: {}", e),
+    })?;
+
+    let content_type = response.content_type().to_lowercase();
+    if !content_type.contains("html") && !content_type.starts_with("text/") {
+        return Err(format!("Not a web page: {}", content_type));
+    }
+
+    let final_url = response.get_url().to_string();
+    let mut bytes = Vec::new();
+    response
+        .into_reader()
+        .take(MAX_PAGE_BYTES)
+        .read_to_end(&mut bytes)
+        .map_err(|e| format!("Failed to read page: {}", e))?;
+    Ok(WebPage { url: final_url, html: String::from_utf8_lossy(&bytes).to_string() })
+}
+
+/// Title and readable markdown of a page; relative links are resolved
+/// against `base_url`
+pub fn readable_page(html: &str, base_url: &str) -> (String, String) {
+    let title = element(html, "title")
+        .or_else(|| element(html, "h1"))
+        .map(|title| html_to_markdown(title).trim().to_string())
+        .filter(|title| !title.is_empty())
+        .unwrap_or_else(|| link_check::host(base_url));
+
+    let markdown = match element(html, "article").or_else(|| element(html, "main")) {
+        Some(content) => html_to_markdown(content),
+        None => {
+            let mut body = element(html, "body").unwrap_or(html).to_string();
+            for name in BOILERPLATE {
+                body = strip_element(&body, name);
+            }
+            html_to_markdown(&body)
+        }
+    };
+    (title, absolute_links(&markdown, base_url))
+}
+
+/// Standalone HTML of a snapshot, headed by its source and date
+pub fn snapshot_document(title: &str, source: &str, archived: NaiveDate, markdown: &str) -> String {
+    let content = format!(
+        "# {}\n\n> Archived copy of <{}> taken on {}\n\n---\n\n{}",
+        title.replace('\n', " "),
+        source,
+        archived.format("%Y-%m-%d"),
+        markdown
+    );
+    html_document(title, &render_markdown(&content, "", &NoteLookup::default(), false))
+}
+
+/// File name of a snapshot: host, title and date
+pub fn snapshot_name(url: &str, title: &str, archived: NaiveDate) -> String {
+    let host = link_check::host(url);
+    let host = host.strip_prefix("www.").unwrap_or(&host);
+    let mut name = slug(host);
+    let title = slug(title);
+    if !title.is_empty() {
+        name.push('-');
+        name.extend(title.chars().take(MAX_NAME_CHARS));
+    }
+    format!("{}-{}.html", name.trim_end_matches('-'), archived.format("%Y-%m-%d"))
+}
+
+/// Adds `link` after the first line of `content` that links `url`; if the
+/// note does not mention the URL, the URL and the link are appended
+pub fn add_archived_link(content: &str, url: &str, link: &str) -> Result<String, String> {
+    let (_, body) = frontmatter::split(content);
+    let first_line = content[..content.len() - body.len()].lines().count() + 1;
+    let Some(line) = external_links(body, first_line)
+        .into_iter()
+        .find(|(found, _)| found == url)
+        .map(|(_, line)| line)
+    else {
+        return capture::insert_entry(content, &format!("<{}> ({})", url, link), None, CapturePosition::Append);
+    };
+
+    let mut out = String::with_capacity(content.len() + link.len() + 3);
+    for (i, text) in content.split_inclusive('\n').enumerate() {
+        if i + 1 != line {
+            out.push_str(text);
+            continue;
+        }
+        let ending = &text[text.trim_end_matches(['\r', '\n']).len()..];
+        out.push_str(text.trim_end_matches(['\r', '\n']).trim_end());
+        out.push_str(&format!(" ({})", link));
+        out.push_str(ending);
+    }
+    Ok(out)
+}
+
+// Helper: Contents of the first `<name>` element, up to its last closing tag
+fn element<'a>(html: &'a str, name: &str) -> Option<&'a str> {
+    let lower = html.to_ascii_lowercase();
+    let open = open_tag(&lower, name, 0)?;
+    let start = open + lower[open..].find('>')? + 1;
+    let end = lower.rfind(&format!("</{}", name)).filter(|&end| end >= start)?;
+    Some(&html[start..end])
+}
+
+// Helper: Removes every `<name>` element with its contents
+fn strip_element(html: &str, name: &str) -> String {
+    let lower = html.to_ascii_lowercase();
+    let close = format!("</{}", name);
+    let mut out = String::with_capacity(html.len());
+    let mut pos = 0;
+    while let Some(open) = open_tag(&lower, name, pos) {
+        out.push_str(&html[pos..open]);
+        pos = match lower[open..].find(&close) {
+            Some(end) => {
+                let end = open + end;
+                lower[end..].find('>').map_or(html.len(), |gt| end + gt + 1)
+            }
+            None => html.len(),
+        };
+    }
+    out.push_str(&html[pos..]);
+    out
+}
+
+// Helper: Byte offset of the next `<name` tag (not `<names`) from `from`
+fn open_tag(lower: &str, name: &str, from: usize) -> Option<usize> {
+    let tag = format!("<{}", name);
+    let mut search = from;
+    while let Some(found) = lower[search..].find(&tag) {
+        let start = search + found;
+        let next = lower[start + tag.len()..].chars().next();
+        if matches!(next, Some(c) if c == '>' || c == '/' || c.is_whitespace()) {
+            return Some(start);
+        }
+        search = start + tag.len();
+    }
+    None
+}
+
+// Helper: Resolves the relative targets of markdown links and images
+fn absolute_links(markdown: &str, base_url: &str) -> String {
+    let mut out = String::with_capacity(markdown.len());
+    let mut rest = markdown;
+    while let Some(start) = rest.find("](") {
+        out.push_str(&rest[..start + 2]);
+        rest = &rest[start + 2..];
+        let end = rest.find(')').unwrap_or(rest.len());
+        let target = &rest[..end];
+        let keep = target.is_empty()
+            || target.contains("://")
+            || target.starts_with(['#', '<'])
+            || ["mailto:", "tel:", "data:", "javascript:"].iter().any(|s| target.starts_with(s));
+        if keep {
+            out.push_str(target);
+        } else {
+            out.push_str(&link_check::resolve_location(base_url, target));
+        }
+        rest = &rest[end..];
+    }
+    out.push_str(rest);
+    out
+}
+
+fn slug(text: &str) -> String {
+    let mut slug = String::new();
+    for c in text.to_lowercase().chars() {
+        if c.is_alphanumeric() {
+            slug.push(c);
+        } else if !slug.is_empty() && !slug.ends_with('-') {
+            slug.push('-');
+        }
+    }
+    slug.trim_end_matches('-').to_string()
+}
+
+// ============================================================================
+// TESTS
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_readable_page() {
+        let html = "<html><head><title>Rust &amp; You</title><script>track()</script></head>\
+            <body><nav><a href=\"/\">Home</a></nav><h1>Rust</h1><p>See <a href=\"docs/intro\">intro</a> \
+            and <img src=\"/logo.png\" alt=\"logo\"> or <a href=\"#top\">top</a>.</p>\
+            <footer>(c) 2024</footer></body></html>";
+        let (title, markdown) = readable_page(html, "https://example.com/blog/post?x=1");
+        assert_eq!(title, "Rust & You");
+        assert_eq!(
+            markdown,
+            "# Rust\n\nSee [intro](https://example.com/blog/docs/intro) and \
+             ![logo](https://example.com/logo.png) or [top](#top).\n"
+        );
+
+        let article = "<body><nav>Menu</nav><article class=\"post\"><p>Body</p></article></body>";
+        assert_eq!(readable_page(article, "https://example.com/").1, "Body\n");
+
+        let date = NaiveDate::from_ymd_opt(2024, 6, 1).unwrap();
+        assert_eq!(
+            snapshot_name("https://www.example.com/a", "Rust & You: A Guide!", date),
+            "example-com-rust-you-a-guide-2024-06-01.html"
+        );
+        let document = snapshot_document("Rust", "https://example.com/", date, "Body <b>x</b>\n");
+        assert!(document.contains("Archived copy of"));
+        assert!(!document.contains("<b>"));
+    }
+
+    #[test]
+    fn test_add_archived_link() {
+        let content = "---\ntitle: Research\n---\nSee https://example.com/a/b and more\r\n\
+            Also https://example.com/a.\n";
+        let updated = add_archived_link(content, "https://example.com/a", "[archived copy](assets/web/a.html)").unwrap();
+        assert_eq!(
+            updated,
+            "---\ntitle: Research\n---\nSee https://example.com/a/b and more\r\n\
+             Also https://example.com/a. ([archived copy](assets/web/a.html))\n"
+        );
+
+        let appended = add_archived_link("# Notes\n", "https://example.com/", "[archived copy](x.html)").unwrap();
+        assert_eq!(appended, "# Notes\n\n<https://example.com/> ([archived copy](x.html))\n");
+    }
+
+    #[test]
+    fn test_fetch_page_refuses_private_hosts() {
+        let error = fetch_page("http://127.0.0.1:9/", Duration::from_secs(1)).unwrap_err();
+        assert!(error.contains("not a public address"), "{}", error);
+    }
+}
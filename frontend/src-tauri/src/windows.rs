@@ -0,0 +1,182 @@
+//! Document Windows
+//!
+//! Tracks notes opened in their own native window (for dual-monitor
+//! workflows). Each document window has a unique label (`doc-N`) and owns
+//! exactly one file:
+//!
+//! - Opening a file that already has a window focuses that window instead of
+//!   creating a second editor for the same file.
+//! - File change events for an owned file are sent to the owning window (and
+//!   the main window) rather than broadcast to every window.
+//! - Closing a document window flushes its pending save and releases the file;
+//!   closing the main window cleans up the whole application.
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use serde::Serialize;
+
+/// Label of the main application window
+pub const MAIN_WINDOW: &str = "main";
+
+/// Label prefix of document windows
+pub const DOCUMENT_WINDOW_PREFIX: &str = "doc-";
+
+/// A note open in its own window
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct DocumentWindow {
+    pub label: String,
+    pub path: PathBuf,
+    pub opened_at: chrono::DateTime<chrono::Utc>,
+}
+
+/// Registry of open document windows (internally synchronized)
+#[derive(Clone, Default)]
+pub struct WindowRegistry {
+    windows: Arc<Mutex<HashMap<String, DocumentWindow>>>,
+    next_id: Arc<AtomicU64>,
+}
+
+impl WindowRegistry {
+    /// Creates an empty registry
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns a fresh, unused window label
+    pub fn next_label(&self) -> String {
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed) + 1;
+        format!("{}{}", DOCUMENT_WINDOW_PREFIX, id)
+    }
+
+    /// Registers a window for a file
+    ///
+    /// Fails if the file is already owned by another window.
+    pub fn register(&self, label: &str, path: &Path) -> Result<(), String> {
+        let mut windows = self.lock()?;
+        if let Some(owner) = windows.values().find(|w| w.path == path) {
+            return Err(format!("File is already open in window '{}'", owner.label));
+        }
+        windows.insert(label.to_string(), DocumentWindow {
+            label: label.to_string(),
+            path: path.to_path_buf(),
+            opened_at: chrono::Utc::now(),
+        });
+        Ok(())
+    }
+
+    /// Label of the window owning a file, if any
+    pub fn owner_of(&self, path: &Path) -> Option<String> {
+        let windows = self.windows.lock().ok()?;
+        windows.values().find(|w| w.path == path).map(|w| w.label.clone())
+    }
+
+    /// Unregisters a window; returns its entry if it was registered
+    pub fn remove(&self, label: &str) -> Option<DocumentWindow> {
+        self.windows.lock().ok()?.remove(label)
+    }
+
+    /// Updates owned paths after a file or folder was moved
+    pub fn follow_move(&self, from: &Path, to: &Path) {
+        let Ok(mut windows) = self.windows.lock() else { return };
+        for window in windows.values_mut() {
+            if let Ok(rest) = window.path.strip_prefix(from) {
+                window.path = to.join(rest);
+            }
+        }
+    }
+
+    /// All open document windows, oldest first
+    pub fn list(&self) -> Result<Vec<DocumentWindow>, String> {
+        let mut list: Vec<DocumentWindow> = self.lock()?.values().cloned().collect();
+        list.sort_by(|a, b| a.opened_at.cmp(&b.opened_at).then_with(|| a.label.cmp(&b.label)));
+        Ok(list)
+    }
+
+    /// Windows that should receive a change event for a file
+    pub fn event_targets(&self, path: &Path) -> Vec<String> {
+        let mut targets = vec![MAIN_WINDOW.to_string()];
+        targets.extend(self.owner_of(path));
+        targets
+    }
+
+    fn lock(&self) -> Result<std::sync::MutexGuard<'_, HashMap<String, DocumentWindow>>, String> {
+        self.windows
+            .lock()
+            .map_err(|e| format!("Failed to lock window registry: {}", e))
+    }
+}
+
+/// True for labels created by `WindowRegistry::next_label`
+pub fn is_document_window(label: &str) -> bool {
+    label.starts_with(DOCUMENT_WINDOW_PREFIX)
+}
+
+/// Frontend route of a document window
+pub fn document_url(path: &Path, label: &str) -> String {
+    format!(
+        "workspace?file={}&window={}",
+        percent_encode(&path.to_string_lossy()),
+        percent_encode(label)
+    )
+}
+
+/// Encodes everything except RFC 3986 unreserved characters
+fn percent_encode(value: &str) -> String {
+    let mut out = String::with_capacity(value.len());
+    for byte in value.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => out.push(byte as char),
+            _ => out.push_str(&format!("%{:02X}", byte)),
+        }
+    }
+    out
+}
+
+// ============================================================================
+// TESTS
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_one_window_per_file() {
+        let registry = WindowRegistry::new();
+        let first = registry.next_label();
+        let second = registry.next_label();
+        assert_eq!((first.as_str(), second.as_str()), ("doc-1", "doc-2"));
+
+        registry.register(&first, Path::new("/ws/a.md")).unwrap();
+        assert!(registry.register(&second, Path::new("/ws/a.md")).is_err());
+        assert_eq!(registry.owner_of(Path::new("/ws/a.md")), Some(first.clone()));
+
+        assert_eq!(registry.event_targets(Path::new("/ws/a.md")), vec!["main", "doc-1"]);
+        assert_eq!(registry.event_targets(Path::new("/ws/b.md")), vec!["main"]);
+
+        assert!(registry.remove(&first).is_some());
+        registry.register(&second, Path::new("/ws/a.md")).unwrap();
+        assert_eq!(registry.list().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_follow_move_updates_owned_paths() {
+        let registry = WindowRegistry::new();
+        registry.register("doc-1", Path::new("/ws/Projects/plan.md")).unwrap();
+
+        registry.follow_move(Path::new("/ws/Projects"), Path::new("/ws/Archive"));
+        assert_eq!(registry.owner_of(Path::new("/ws/Archive/plan.md")), Some("doc-1".to_string()));
+    }
+
+    #[test]
+    fn test_document_url() {
+        assert!(is_document_window("doc-3"));
+        assert!(!is_document_window(MAIN_WINDOW));
+        assert_eq!(
+            document_url(Path::new("/ws/My Notes/ä.md"), "doc-1"),
+            "workspace?file=%2Fws%2FMy%20Notes%2F%C3%A4.md&window=doc-1"
+        );
+    }
+}
@@ -0,0 +1,628 @@
+//! Per-Workspace Settings for MDReader
+//!
+//! Settings that belong to a workspace (rather than to the user) live inside
+//! the workspace itself at `.mdreader/settings.json`, so they travel with the
+//! folder when it is synced or copied to another machine.
+//!
+//! Currently stored:
+//! - Folder protection flags (read-only / archived)
+//! - Symlink policy
+//! - Attachment folder and link style for pasted/dropped files
+//! - Folders outside the workspace that exports may write to
+//! - Status values of the note status workflow
+//! - Template variables available to every template (`{{name}}`)
+//! - Frontmatter schema notes are checked against
+//! - Hooks run around saves, opens and exports
+
+use std::collections::BTreeMap;
+use std::fmt;
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+use serde::{Deserialize, Serialize};
+use crate::backup_targets::BackupTarget;
+use crate::hooks::Hook;
+use crate::link_check::LinkCheckSchedule;
+use crate::lists::ListFormat;
+use crate::metadata_schema::FieldSchema;
+
+/// Name of the hidden per-workspace metadata directory
+pub const SETTINGS_DIR: &str = ".mdreader";
+
+/// File name of the settings file inside `SETTINGS_DIR`
+pub const SETTINGS_FILE: &str = "settings.json";
+
+/// Status values used when a workspace configures none
+pub const DEFAULT_STATUSES: &[&str] = &["draft", "in-progress", "done"];
+
+/// Protection flags for a single folder
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct FolderFlags {
+    /// Contents may be read but never modified
+    #[serde(default)]
+    pub read_only: bool,
+    /// Folder is archived: treated as read-only and hidden from active views
+    #[serde(default)]
+    pub archived: bool,
+}
+
+impl FolderFlags {
+    /// Returns true if no flag is set
+    pub fn is_empty(&self) -> bool {
+        !self.read_only && !self.archived
+    }
+}
+
+/// How symbolic links inside the workspace are treated
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SymlinkPolicy {
+    /// Follow links wherever they point, including outside the workspace
+    Follow,
+    /// Refuse any path that goes through a link, and hide links in listings
+    Deny,
+    /// Follow links only if their target is inside the workspace
+    #[default]
+    AllowWithinWorkspace,
+}
+
+/// Where pasted, dropped and downloaded attachments are stored
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AttachmentLocation {
+    /// `assets/` next to the note
+    #[default]
+    AssetsFolder,
+    /// `attachments/<note name>/` next to the note
+    PerNote,
+    /// One workspace-wide folder (`global_folder`)
+    Global,
+}
+
+/// How links to attachments are written into notes
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LinkStyle {
+    /// `![name](assets/image.png)`, relative to the note
+    #[default]
+    Relative,
+    /// `![name](/assets/image.png)`, relative to the workspace root
+    VaultAbsolute,
+    /// `![[assets/image.png]]`
+    WikiEmbed,
+}
+
+/// Attachment placement and link style
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AttachmentSettings {
+    #[serde(default)]
+    pub location: AttachmentLocation,
+    /// Workspace-relative folder used by `AttachmentLocation::Global`
+    #[serde(default = "default_global_folder")]
+    pub global_folder: String,
+    #[serde(default)]
+    pub link_style: LinkStyle,
+}
+
+fn default_global_folder() -> String {
+    "attachments".to_string()
+}
+
+impl Default for AttachmentSettings {
+    fn default() -> Self {
+        Self {
+            location: AttachmentLocation::default(),
+            global_folder: default_global_folder(),
+            link_style: LinkStyle::default(),
+        }
+    }
+}
+
+impl AttachmentSettings {
+    /// Folder that attachments of `note` go to
+    pub fn attachment_dir(&self, workspace_root: &Path, note: &Path) -> PathBuf {
+        let note_dir = note.parent().unwrap_or(workspace_root);
+        match self.location {
+            AttachmentLocation::AssetsFolder => note_dir.join("assets"),
+            AttachmentLocation::PerNote => {
+                let stem = note.file_stem().map(|s| s.to_string_lossy().to_string()).unwrap_or_default();
+                note_dir.join("attachments").join(stem)
+            }
+            AttachmentLocation::Global => workspace_root.join(&self.global_folder),
+        }
+    }
+
+    /// Markdown that embeds `attachment` into `note`
+    ///
+    /// Both paths must be inside `workspace_root`.
+    pub fn link(&self, workspace_root: &Path, note: &Path, attachment: &Path) -> String {
+        if self.link_style == LinkStyle::WikiEmbed {
+            return format!("![[{}]]", relative_key(workspace_root, attachment).unwrap_or_default());
+        }
+        let name = attachment.file_stem().map(|s| s.to_string_lossy().to_string()).unwrap_or_default();
+        format!("!{}", self.text_link(workspace_root, note, attachment, &name))
+    }
+
+    /// Markdown that links to `attachment` from `note` with the label `text`
+    /// instead of embedding it
+    pub fn text_link(&self, workspace_root: &Path, note: &Path, attachment: &Path, text: &str) -> String {
+        let vault_path = relative_key(workspace_root, attachment).unwrap_or_default();
+
+        let target = match self.link_style {
+            LinkStyle::WikiEmbed => return format!("[[{}|{}]]", vault_path, text),
+            LinkStyle::VaultAbsolute => format!("/{}", vault_path),
+            LinkStyle::Relative => {
+                let note_dir = note.parent().unwrap_or(workspace_root);
+                let note_dir_key = relative_key(workspace_root, note_dir).unwrap_or_default();
+                relative_between(&note_dir_key, &vault_path)
+            }
+        };
+
+        if target.contains([' ', '(', ')']) {
+            format!("[{}](<{}>)", text, target)
+        } else {
+            format!("[{}]({})", text, target)
+        }
+    }
+
+    /// Fails if the global folder is not a plain workspace-relative path
+    pub fn validate(&self) -> Result<(), String> {
+        let folder = Path::new(&self.global_folder);
+        let plain = !self.global_folder.trim().is_empty()
+            && folder.components().all(|c| matches!(c, Component::Normal(_)))
+            && !self.global_folder.starts_with('.');
+        if plain {
+            Ok(())
+        } else {
+            Err(format!("Invalid attachment folder: '{}'", self.global_folder))
+        }
+    }
+}
+
+/// Path from folder `from` to `to`, both `/`-separated and workspace-relative
+pub(crate) fn relative_between(from: &str, to: &str) -> String {
+    let from: Vec<&str> = from.split('/').filter(|p| !p.is_empty()).collect();
+    let to: Vec<&str> = to.split('/').filter(|p| !p.is_empty()).collect();
+    let common = from.iter().zip(&to).take_while(|(a, b)| a == b).count();
+
+    let mut parts: Vec<&str> = vec![".."; from.len() - common];
+    parts.extend(&to[common..]);
+    parts.join("/")
+}
+
+/// Settings stored in `<workspace>/.mdreader/settings.json`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WorkspaceSettings {
+    /// Folder flags keyed by workspace-relative path using `/` separators
+    #[serde(default)]
+    pub folders: BTreeMap<String, FolderFlags>,
+    /// Symlink handling for path validation and listings
+    #[serde(default)]
+    pub symlink_policy: SymlinkPolicy,
+    /// Where attachments go and how they are linked
+    #[serde(default)]
+    pub attachments: AttachmentSettings,
+    /// Values of the frontmatter `status` field, in board order
+    /// (empty means `DEFAULT_STATUSES`)
+    #[serde(default)]
+    pub statuses: Vec<String>,
+    /// Variables for templates and snippets (see `crate::template`)
+    #[serde(default)]
+    pub template_variables: BTreeMap<String, String>,
+    /// Frontmatter fields notes should carry (see `crate::metadata_schema`)
+    #[serde(default)]
+    pub metadata_schema: Vec<FieldSchema>,
+    /// Where and how often the workspace is backed up (see `crate::backup_targets`)
+    #[serde(default)]
+    pub backup_targets: Vec<BackupTarget>,
+    /// Leave files matched by `.gitignore` out of listings, indexing and the
+    /// watcher (`.mdreaderignore` always applies; see `crate::ignore`)
+    #[serde(default = "default_true")]
+    pub respect_gitignore: bool,
+    /// External tools run around saves, opens and exports, once approved
+    /// on this machine (see `crate::hooks`)
+    #[serde(default)]
+    pub hooks: Vec<Hook>,
+    /// Scheduled checks of external links (see `crate::link_check`)
+    #[serde(default)]
+    pub link_check: LinkCheckSchedule,
+    /// List renumbering and indentation, optionally on save (see `crate::lists`)
+    #[serde(default)]
+    pub lists: ListFormat,
+}
+
+fn default_true() -> bool {
+    true
+}
+
+impl Default for WorkspaceSettings {
+    fn default() -> Self {
+        Self {
+            folders: BTreeMap::new(),
+            symlink_policy: SymlinkPolicy::default(),
+            attachments: AttachmentSettings::default(),
+            statuses: Vec::new(),
+            template_variables: BTreeMap::new(),
+            metadata_schema: Vec::new(),
+            backup_targets: Vec::new(),
+            respect_gitignore: true,
+            hooks: Vec::new(),
+            link_check: LinkCheckSchedule::default(),
+            lists: ListFormat::default(),
+        }
+    }
+}
+
+/// Error returned when a mutating operation targets a protected folder
+#[derive(Debug, Clone, PartialEq)]
+pub enum FolderProtectionError {
+    /// Folder (or an ancestor) is marked read-only
+    ReadOnly { folder: String, path: String },
+    /// Folder (or an ancestor) is archived
+    Archived { folder: String, path: String },
+}
+
+impl fmt::Display for FolderProtectionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FolderProtectionError::ReadOnly { folder, path } => {
+                write!(f, "Read-only folder: '{}' is protected, cannot modify '{}'", folder, path)
+            }
+            FolderProtectionError::Archived { folder, path } => {
+                write!(f, "Archived folder: '{}' is archived, cannot modify '{}'", folder, path)
+            }
+        }
+    }
+}
+
+impl std::error::Error for FolderProtectionError {}
+
+impl WorkspaceSettings {
+    /// Loads settings for a workspace, returning defaults if none are saved
+    pub fn load(workspace_root: &Path) -> Result<Self, String> {
+        let path = settings_path(workspace_root);
+
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+
+        let json = fs::read_to_string(&path)
+            .map_err(|e| format!("Failed to read workspace settings: {}", e))?;
+
+        serde_json::from_str(&json)
+            .map_err(|e| format!("Failed to parse workspace settings: {}", e))
+    }
+
+    /// Saves settings for a workspace, creating `.mdreader/` if needed
+    pub fn save(&self, workspace_root: &Path) -> Result<(), String> {
+        let dir = workspace_root.join(SETTINGS_DIR);
+        fs::create_dir_all(&dir)
+            .map_err(|e| format!("Failed to create settings directory: {}", e))?;
+
+        let json = serde_json::to_string_pretty(self)
+            .map_err(|e| format!("Failed to serialize workspace settings: {}", e))?;
+
+        fs::write(dir.join(SETTINGS_FILE), json)
+            .map_err(|e| format!("Failed to save workspace settings: {}", e))?;
+
+        Ok(())
+    }
+
+    /// Loads only the symlink policy, falling back to the default on error
+    ///
+    /// Path validation must keep working even if the settings file is corrupt.
+    pub fn load_symlink_policy(workspace_root: &Path) -> SymlinkPolicy {
+        match Self::load(workspace_root) {
+            Ok(settings) => settings.symlink_policy,
+            Err(e) => {
+                log::warn!("⚠️ Using default symlink policy: {}", e);
+                SymlinkPolicy::default()
+            }
+        }
+    }
+
+    /// Configured status values, or the defaults
+    pub fn status_values(&self) -> Vec<String> {
+        if self.statuses.is_empty() {
+            DEFAULT_STATUSES.iter().map(|s| s.to_string()).collect()
+        } else {
+            self.statuses.clone()
+        }
+    }
+
+    /// Sets (or clears, when both flags are false) the flags for a folder
+    pub fn set_folder_flags(&mut self, relative_folder: String, flags: FolderFlags) {
+        if flags.is_empty() {
+            self.folders.remove(&relative_folder);
+        } else {
+            self.folders.insert(relative_folder, flags);
+        }
+    }
+
+    /// Finds the nearest protected folder containing `relative_path` (inclusive)
+    pub fn protecting_folder(&self, relative_path: &str) -> Option<(&str, &FolderFlags)> {
+        self.folders
+            .iter()
+            .filter(|(folder, _)| is_same_or_ancestor(folder, relative_path))
+            .max_by_key(|(folder, _)| folder.len())
+            .map(|(folder, flags)| (folder.as_str(), flags))
+    }
+
+    /// Fails if `relative_path` lies inside a protected folder
+    pub fn check_writable(&self, relative_path: &str) -> Result<(), FolderProtectionError> {
+        match self.protecting_folder(relative_path) {
+            Some((folder, flags)) => Err(protection_error(folder, relative_path, flags)),
+            None => Ok(()),
+        }
+    }
+
+    /// Fails if `relative_path` lies inside, or itself contains, a protected folder
+    ///
+    /// Used for directory-level operations (delete, rename, move) where the
+    /// protected folder would be affected even if it is a descendant.
+    pub fn check_tree_writable(&self, relative_path: &str) -> Result<(), FolderProtectionError> {
+        self.check_writable(relative_path)?;
+
+        match self.folders.iter().find(|(folder, _)| is_same_or_ancestor(relative_path, folder)) {
+            Some((folder, flags)) => Err(protection_error(folder, relative_path, flags)),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Gets the location of the settings file for a workspace
+pub fn settings_path(workspace_root: &Path) -> PathBuf {
+    workspace_root.join(SETTINGS_DIR).join(SETTINGS_FILE)
+}
+
+/// Converts an absolute path into the `/`-separated key used in settings
+///
+/// Returns `None` if the path is not inside the workspace. The workspace
+/// root itself maps to an empty string.
+pub fn relative_key(workspace_root: &Path, path: &Path) -> Option<String> {
+    let root = workspace_root.canonicalize().unwrap_or_else(|_| workspace_root.to_path_buf());
+    let relative = path.strip_prefix(&root).ok()?;
+
+    let parts: Vec<String> = relative
+        .components()
+        .filter_map(|c| match c {
+            Component::Normal(part) => Some(part.to_string_lossy().to_string()),
+            _ => None,
+        })
+        .collect();
+
+    Some(parts.join("/"))
+}
+
+/// Ensures a validated path is not inside a read-only or archived folder
+///
+/// # Arguments
+/// * `workspace_root` - The configured workspace root
+/// * `path` - A path already validated to be within the workspace
+///
+/// # Returns
+/// * `Ok(())` - If the path may be modified
+/// * `Err(String)` - A `Read-only folder:` / `Archived folder:` error
+pub fn ensure_writable(workspace_root: &str, path: &Path) -> Result<(), String> {
+    let root = Path::new(workspace_root);
+    let settings = WorkspaceSettings::load(root)?;
+
+    match relative_key(root, path) {
+        Some(key) => settings.check_writable(&key).map_err(|e| e.to_string()),
+        None => Ok(()),
+    }
+}
+
+/// Same as `ensure_writable`, but also refuses if `path` contains a protected folder
+pub fn ensure_tree_writable(workspace_root: &str, path: &Path) -> Result<(), String> {
+    let root = Path::new(workspace_root);
+    let settings = WorkspaceSettings::load(root)?;
+
+    match relative_key(root, path) {
+        Some(key) => settings.check_tree_writable(&key).map_err(|e| e.to_string()),
+        None => Ok(()),
+    }
+}
+
+/// Checks whether `ancestor` equals `path` or is one of its parent folders
+fn is_same_or_ancestor(ancestor: &str, path: &str) -> bool {
+    if ancestor.is_empty() {
+        return true;
+    }
+
+    path == ancestor
+        || (path.starts_with(ancestor) && path.as_bytes().get(ancestor.len()) == Some(&b'/'))
+}
+
+fn protection_error(folder: &str, path: &str, flags: &FolderFlags) -> FolderProtectionError {
+    let folder = folder.to_string();
+    let path = path.to_string();
+
+    if flags.archived {
+        FolderProtectionError::Archived { folder, path }
+    } else {
+        FolderProtectionError::ReadOnly { folder, path }
+    }
+}
+
+// ============================================================================
+// TESTS
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    /// Helper to create test workspace
+    fn setup_test_workspace() -> TempDir {
+        TempDir::new().expect("Failed to create temp dir")
+    }
+
+    fn read_only() -> FolderFlags {
+        FolderFlags { read_only: true, archived: false }
+    }
+
+    // -------------------------------------------------------------------------
+    // Persistence Tests
+    // -------------------------------------------------------------------------
+
+    #[test]
+    fn test_load_missing_settings_returns_default() {
+        let temp = setup_test_workspace();
+        let settings = WorkspaceSettings::load(temp.path()).unwrap();
+        assert!(settings.folders.is_empty());
+    }
+
+    #[test]
+    fn test_save_and_load_roundtrip() {
+        let temp = setup_test_workspace();
+        let mut settings = WorkspaceSettings::default();
+        settings.set_folder_flags("Reference".to_string(), read_only());
+        settings.save(temp.path()).unwrap();
+
+        assert!(settings_path(temp.path()).exists(), "Settings file should be created");
+
+        let loaded = WorkspaceSettings::load(temp.path()).unwrap();
+        assert_eq!(loaded, settings);
+    }
+
+    #[test]
+    fn test_clearing_flags_removes_entry() {
+        let mut settings = WorkspaceSettings::default();
+        settings.set_folder_flags("Reference".to_string(), read_only());
+        settings.set_folder_flags("Reference".to_string(), FolderFlags::default());
+        assert!(settings.folders.is_empty());
+    }
+
+    // -------------------------------------------------------------------------
+    // Protection Tests
+    // -------------------------------------------------------------------------
+
+    #[test]
+    fn test_files_inside_protected_folder_are_refused() {
+        let mut settings = WorkspaceSettings::default();
+        settings.set_folder_flags("Reference".to_string(), read_only());
+
+        assert!(settings.check_writable("Reference").is_err());
+        assert!(settings.check_writable("Reference/paper.md").is_err());
+        assert!(settings.check_writable("Reference/deep/nested.md").is_err());
+    }
+
+    #[test]
+    fn test_similar_prefix_is_not_protected() {
+        let mut settings = WorkspaceSettings::default();
+        settings.set_folder_flags("Reference".to_string(), read_only());
+
+        assert!(settings.check_writable("References/note.md").is_ok());
+        assert!(settings.check_writable("notes.md").is_ok());
+    }
+
+    #[test]
+    fn test_archived_error_message() {
+        let mut settings = WorkspaceSettings::default();
+        settings.set_folder_flags("Old".to_string(), FolderFlags { read_only: false, archived: true });
+
+        let err = settings.check_writable("Old/a.md").unwrap_err();
+        assert!(matches!(err, FolderProtectionError::Archived { .. }));
+        assert!(err.to_string().starts_with("Archived folder:"));
+    }
+
+    #[test]
+    fn test_tree_check_catches_protected_descendant() {
+        let mut settings = WorkspaceSettings::default();
+        settings.set_folder_flags("Projects/Reference".to_string(), read_only());
+
+        assert!(settings.check_writable("Projects").is_ok(), "Parent itself is writable");
+        assert!(settings.check_tree_writable("Projects").is_err(), "Deleting the parent would remove a protected folder");
+        assert!(settings.check_tree_writable("Other").is_ok());
+    }
+
+    #[test]
+    fn test_ensure_writable_reads_settings_from_disk() {
+        let temp = setup_test_workspace();
+        let root = temp.path().canonicalize().unwrap();
+        fs::create_dir_all(root.join("Reference")).unwrap();
+
+        let mut settings = WorkspaceSettings::default();
+        settings.set_folder_flags("Reference".to_string(), read_only());
+        settings.save(&root).unwrap();
+
+        let workspace = root.to_string_lossy().to_string();
+        let result = ensure_writable(&workspace, &root.join("Reference").join("a.md"));
+        assert!(result.unwrap_err().starts_with("Read-only folder:"));
+        assert!(ensure_writable(&workspace, &root.join("a.md")).is_ok());
+    }
+
+    #[test]
+    fn test_symlink_policy_defaults_and_roundtrip() {
+        let temp = setup_test_workspace();
+        assert_eq!(WorkspaceSettings::load_symlink_policy(temp.path()), SymlinkPolicy::AllowWithinWorkspace);
+
+        let settings = WorkspaceSettings { symlink_policy: SymlinkPolicy::Deny, ..Default::default() };
+        settings.save(temp.path()).unwrap();
+        assert_eq!(WorkspaceSettings::load_symlink_policy(temp.path()), SymlinkPolicy::Deny);
+
+        let json = fs::read_to_string(settings_path(temp.path())).unwrap();
+        assert!(json.contains("\"deny\""), "Policy should be stored in snake_case");
+    }
+
+    #[test]
+    fn test_corrupt_settings_fall_back_to_default_policy() {
+        let temp = setup_test_workspace();
+        fs::create_dir_all(temp.path().join(SETTINGS_DIR)).unwrap();
+        fs::write(settings_path(temp.path()), "{ not json").unwrap();
+
+        assert_eq!(WorkspaceSettings::load_symlink_policy(temp.path()), SymlinkPolicy::default());
+    }
+
+    #[test]
+    fn test_relative_key() {
+        let temp = setup_test_workspace();
+        let root = temp.path().canonicalize().unwrap();
+
+        assert_eq!(relative_key(&root, &root.join("a").join("b.md")).unwrap(), "a/b.md");
+        assert_eq!(relative_key(&root, &root).unwrap(), "");
+        assert!(relative_key(&root, Path::new("/elsewhere/file.md")).is_none());
+    }
+
+    #[test]
+    fn test_attachment_dir_per_location() {
+        let temp = setup_test_workspace();
+        let root = temp.path().canonicalize().unwrap();
+        let note = root.join("Projects").join("plan.md");
+        let mut settings = AttachmentSettings::default();
+
+        assert_eq!(settings.attachment_dir(&root, &note), root.join("Projects/assets"));
+        settings.location = AttachmentLocation::PerNote;
+        assert_eq!(settings.attachment_dir(&root, &note), root.join("Projects/attachments/plan"));
+        settings.location = AttachmentLocation::Global;
+        assert_eq!(settings.attachment_dir(&root, &note), root.join("attachments"));
+    }
+
+    #[test]
+    fn test_attachment_link_styles() {
+        let temp = setup_test_workspace();
+        let root = temp.path().canonicalize().unwrap();
+        let note = root.join("Projects").join("plan.md");
+        let image = root.join("attachments").join("my shot.png");
+        let mut settings = AttachmentSettings::default();
+
+        assert_eq!(settings.link(&root, &note, &image), "![my shot](<../attachments/my shot.png>)");
+        settings.link_style = LinkStyle::VaultAbsolute;
+        assert_eq!(settings.link(&root, &note, &root.join("assets/a.png")), "![a](/assets/a.png)");
+        settings.link_style = LinkStyle::WikiEmbed;
+        assert_eq!(settings.link(&root, &note, &image), "![[attachments/my shot.png]]");
+    }
+
+    #[test]
+    fn test_attachment_settings_validation_and_defaults() {
+        let parsed: WorkspaceSettings = serde_json::from_str(r#"{"attachments": {"location": "global"}}"#).unwrap();
+        assert_eq!(parsed.attachments.global_folder, "attachments");
+        assert!(parsed.attachments.validate().is_ok());
+
+        for bad in ["", "../outside", "/abs", ".hidden"] {
+            let settings = AttachmentSettings { global_folder: bad.to_string(), ..Default::default() };
+            assert!(settings.validate().is_err(), "{} should be rejected", bad);
+        }
+    }
+}
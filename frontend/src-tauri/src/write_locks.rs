@@ -0,0 +1,268 @@
+//! Per-File Write Locks for MDReader
+//!
+//! Two windows, or autosave racing a manual save, can interleave writes to
+//! the same file. This registry hands out one exclusive guard per path so
+//! writes are serialized. Interactive commands wait only briefly and then
+//! surface a `Busy` error carrying a retry hint; background writers (the
+//! debounced save queue) simply wait their turn.
+//!
+//! Async commands use `acquire_async`, which waits for a busy file on the
+//! blocking pool rather than on an async runtime worker.
+
+use std::collections::HashMap;
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Condvar, Mutex};
+use std::time::{Duration, Instant};
+
+/// How long interactive commands wait for a busy file before giving up
+pub const DEFAULT_LOCK_WAIT_MS: u64 = 500;
+
+/// Lower bound for the retry hint returned with a `Busy` error
+const MIN_RETRY_AFTER_MS: u64 = 100;
+
+/// Upper bound for the retry hint returned with a `Busy` error
+const MAX_RETRY_AFTER_MS: u64 = 2_000;
+
+/// Error returned when a file lock cannot be acquired
+#[derive(Debug, Clone, PartialEq)]
+pub enum WriteLockError {
+    /// Another write to the same file is still in progress
+    Busy {
+        path: String,
+        retry_after_ms: u64,
+    },
+    /// The lock registry mutex was poisoned
+    Poisoned(String),
+}
+
+impl fmt::Display for WriteLockError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            WriteLockError::Busy { path, retry_after_ms } => {
+                write!(f, "Busy: {} is being written, retry after {}ms", path, retry_after_ms)
+            }
+            WriteLockError::Poisoned(msg) => write!(f, "Failed to lock write registry: {}", msg),
+        }
+    }
+}
+
+impl std::error::Error for WriteLockError {}
+
+impl From<WriteLockError> for String {
+    fn from(e: WriteLockError) -> String {
+        e.to_string()
+    }
+}
+
+#[derive(Default)]
+struct Registry {
+    /// Paths currently being written, with the time the write started
+    held: Mutex<HashMap<PathBuf, Instant>>,
+    released: Condvar,
+}
+
+/// Registry of per-path write locks (cheap to clone, shared internally)
+#[derive(Clone, Default)]
+pub struct WriteLocks {
+    inner: Arc<Registry>,
+}
+
+/// Exclusive write access to one path; released on drop
+pub struct WriteGuard {
+    registry: Arc<Registry>,
+    path: PathBuf,
+}
+
+impl Drop for WriteGuard {
+    fn drop(&mut self) {
+        if let Ok(mut held) = self.registry.held.lock() {
+            held.remove(&self.path);
+        }
+        self.registry.released.notify_all();
+    }
+}
+
+impl WriteLocks {
+    /// Creates an empty lock registry
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Acquires the lock for `path`, waiting at most `max_wait`
+    ///
+    /// # Returns
+    /// * `Ok(WriteGuard)` - Exclusive access until the guard is dropped
+    /// * `Err(WriteLockError::Busy)` - If another write is still running
+    pub fn try_acquire(&self, path: &Path, max_wait: Duration) -> Result<WriteGuard, WriteLockError> {
+        let deadline = Instant::now() + max_wait;
+        let mut held = self.inner.held
+            .lock()
+            .map_err(|e| WriteLockError::Poisoned(e.to_string()))?;
+
+        while let Some(started) = held.get(path).copied() {
+            let now = Instant::now();
+            if now >= deadline {
+                return Err(WriteLockError::Busy {
+                    path: path.to_string_lossy().to_string(),
+                    retry_after_ms: retry_hint(now.duration_since(started)),
+                });
+            }
+
+            held = self.inner.released
+                .wait_timeout(held, deadline - now)
+                .map_err(|e| WriteLockError::Poisoned(e.to_string()))?
+                .0;
+        }
+
+        Ok(self.insert(&mut held, path))
+    }
+
+    /// Acquires the lock for `path` like `try_acquire`, without blocking
+    /// the async runtime while another write finishes
+    pub async fn acquire_async(&self, path: &Path, max_wait: Duration) -> Result<WriteGuard, WriteLockError> {
+        match self.try_acquire(path, Duration::ZERO) {
+            Err(WriteLockError::Busy { .. }) if !max_wait.is_zero() => {}
+            result => return result,
+        }
+
+        let (locks, path) = (self.clone(), path.to_path_buf());
+        tauri::async_runtime::spawn_blocking(move || locks.try_acquire(&path, max_wait))
+            .await
+            .map_err(|e| WriteLockError::Poisoned(e.to_string()))?
+    }
+
+    /// Acquires the lock for `path`, waiting as long as necessary
+    ///
+    /// Intended for background writers that must not drop content.
+    pub fn acquire(&self, path: &Path) -> Result<WriteGuard, WriteLockError> {
+        let mut held = self.inner.held
+            .lock()
+            .map_err(|e| WriteLockError::Poisoned(e.to_string()))?;
+
+        while held.contains_key(path) {
+            held = self.inner.released
+                .wait(held)
+                .map_err(|e| WriteLockError::Poisoned(e.to_string()))?;
+        }
+
+        Ok(self.insert(&mut held, path))
+    }
+
+    /// Checks if a write to `path` is currently in progress
+    pub fn is_locked(&self, path: &Path) -> bool {
+        self.inner.held
+            .lock()
+            .map(|h| h.contains_key(path))
+            .unwrap_or(false)
+    }
+
+    fn insert(&self, held: &mut HashMap<PathBuf, Instant>, path: &Path) -> WriteGuard {
+        held.insert(path.to_path_buf(), Instant::now());
+        WriteGuard {
+            registry: Arc::clone(&self.inner),
+            path: path.to_path_buf(),
+        }
+    }
+}
+
+/// Suggests a retry delay based on how long the current write has been running
+///
+/// Long-running writes (large files, slow network drives) get a longer hint.
+fn retry_hint(elapsed: Duration) -> u64 {
+    (elapsed.as_millis() as u64).clamp(MIN_RETRY_AFTER_MS, MAX_RETRY_AFTER_MS)
+}
+
+// ============================================================================
+// TESTS
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::thread;
+
+    #[test]
+    fn test_acquire_and_release() {
+        let locks = WriteLocks::new();
+        let path = Path::new("/workspace/doc.md");
+
+        let guard = locks.try_acquire(path, Duration::ZERO).unwrap();
+        assert!(locks.is_locked(path));
+
+        drop(guard);
+        assert!(!locks.is_locked(path), "Lock should be released on drop");
+    }
+
+    #[test]
+    fn test_busy_error_with_retry_after() {
+        let locks = WriteLocks::new();
+        let path = Path::new("/workspace/doc.md");
+
+        let _guard = locks.try_acquire(path, Duration::ZERO).unwrap();
+        let err = locks.try_acquire(path, Duration::from_millis(20)).err().unwrap();
+
+        match &err {
+            WriteLockError::Busy { retry_after_ms, .. } => {
+                assert!(*retry_after_ms >= MIN_RETRY_AFTER_MS);
+                assert!(*retry_after_ms <= MAX_RETRY_AFTER_MS);
+            }
+            other => panic!("Expected Busy, got {:?}", other),
+        }
+        assert!(err.to_string().starts_with("Busy:"), "Error message should be recognizable");
+    }
+
+    #[test]
+    fn test_different_paths_do_not_block() {
+        let locks = WriteLocks::new();
+
+        let _a = locks.try_acquire(Path::new("/workspace/a.md"), Duration::ZERO).unwrap();
+        let b = locks.try_acquire(Path::new("/workspace/b.md"), Duration::ZERO);
+        assert!(b.is_ok(), "Locks on different files should be independent");
+    }
+
+    #[test]
+    fn test_waiter_gets_lock_after_release() {
+        let locks = WriteLocks::new();
+        let path = PathBuf::from("/workspace/doc.md");
+
+        let guard = locks.try_acquire(&path, Duration::ZERO).unwrap();
+
+        let locks_clone = locks.clone();
+        let path_clone = path.clone();
+        let handle = thread::spawn(move || {
+            locks_clone.try_acquire(&path_clone, Duration::from_secs(2)).is_ok()
+        });
+
+        thread::sleep(Duration::from_millis(50));
+        drop(guard);
+
+        assert!(handle.join().unwrap(), "Waiter should acquire the lock once released");
+    }
+
+    #[test]
+    fn test_acquire_async_waits_off_the_runtime() {
+        let locks = WriteLocks::new();
+        let path = PathBuf::from("/workspace/doc.md");
+        let guard = locks.try_acquire(&path, Duration::ZERO).unwrap();
+
+        let releaser = thread::spawn(move || {
+            thread::sleep(Duration::from_millis(50));
+            drop(guard);
+        });
+        let acquired = tauri::async_runtime::block_on(locks.acquire_async(&path, Duration::from_secs(2)));
+        assert!(acquired.is_ok(), "Waiter should acquire the lock once released");
+        releaser.join().unwrap();
+
+        let _held = acquired.unwrap();
+        let busy = tauri::async_runtime::block_on(locks.acquire_async(&path, Duration::ZERO));
+        assert!(matches!(busy, Err(WriteLockError::Busy { .. })));
+    }
+
+    #[test]
+    fn test_retry_hint_is_clamped() {
+        assert_eq!(retry_hint(Duration::from_millis(1)), MIN_RETRY_AFTER_MS);
+        assert_eq!(retry_hint(Duration::from_millis(750)), 750);
+        assert_eq!(retry_hint(Duration::from_secs(60)), MAX_RETRY_AFTER_MS);
+    }
+}